@@ -0,0 +1,12 @@
+-- Saved device searches (smart lists) usable as bulk/job targets
+CREATE TABLE saved_searches (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT DEFAULT '',
+    filter TEXT NOT NULL DEFAULT '{}',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Job templates can target the devices matched by a saved search
+ALTER TABLE job_templates ADD COLUMN target_saved_search_id INTEGER DEFAULT 0;
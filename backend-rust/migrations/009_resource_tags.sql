@@ -0,0 +1,37 @@
+-- Generic key/value tags for devices, templates, credentials and jobs
+CREATE TABLE resource_tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    resource_type TEXT NOT NULL,
+    resource_id TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL DEFAULT '',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(resource_type, resource_id, key)
+);
+
+CREATE INDEX idx_resource_tags_resource ON resource_tags(resource_type, resource_id);
+CREATE INDEX idx_resource_tags_kv ON resource_tags(key, value);
+
+-- Drop tags along with the resource they belong to
+CREATE TRIGGER trg_devices_delete_tags AFTER DELETE ON devices
+BEGIN
+    DELETE FROM resource_tags WHERE resource_type = 'device' AND resource_id = CAST(OLD.id AS TEXT);
+END;
+
+CREATE TRIGGER trg_templates_delete_tags AFTER DELETE ON templates
+BEGIN
+    DELETE FROM resource_tags WHERE resource_type = 'template' AND resource_id = CAST(OLD.id AS TEXT);
+END;
+
+CREATE TRIGGER trg_credentials_delete_tags AFTER DELETE ON credentials
+BEGIN
+    DELETE FROM resource_tags WHERE resource_type = 'credential' AND resource_id = CAST(OLD.id AS TEXT);
+END;
+
+CREATE TRIGGER trg_jobs_delete_tags AFTER DELETE ON jobs
+BEGIN
+    DELETE FROM resource_tags WHERE resource_type = 'job' AND resource_id = OLD.id;
+END;
+
+-- Job templates can target every device carrying a tag ("key" or "key=value")
+ALTER TABLE job_templates ADD COLUMN target_tag TEXT DEFAULT '';
@@ -0,0 +1,92 @@
+-- Full-text search indexes (FTS5, external content) kept in sync by triggers
+
+-- ── Devices ───────────────────────────────────────────────────
+CREATE VIRTUAL TABLE devices_fts USING fts5(
+    hostname, ip, mac, model, serial_number,
+    content='devices', content_rowid='id'
+);
+
+CREATE TRIGGER trg_devices_fts_insert AFTER INSERT ON devices BEGIN
+    INSERT INTO devices_fts(rowid, hostname, ip, mac, model, serial_number)
+    VALUES (new.id, new.hostname, new.ip, new.mac, new.model, new.serial_number);
+END;
+
+CREATE TRIGGER trg_devices_fts_delete AFTER DELETE ON devices BEGIN
+    INSERT INTO devices_fts(devices_fts, rowid, hostname, ip, mac, model, serial_number)
+    VALUES ('delete', old.id, old.hostname, old.ip, old.mac, old.model, old.serial_number);
+END;
+
+CREATE TRIGGER trg_devices_fts_update AFTER UPDATE ON devices BEGIN
+    INSERT INTO devices_fts(devices_fts, rowid, hostname, ip, mac, model, serial_number)
+    VALUES ('delete', old.id, old.hostname, old.ip, old.mac, old.model, old.serial_number);
+    INSERT INTO devices_fts(rowid, hostname, ip, mac, model, serial_number)
+    VALUES (new.id, new.hostname, new.ip, new.mac, new.model, new.serial_number);
+END;
+
+-- ── Templates ─────────────────────────────────────────────────
+CREATE VIRTUAL TABLE templates_fts USING fts5(
+    name, description, content,
+    content='templates', content_rowid='id'
+);
+
+CREATE TRIGGER trg_templates_fts_insert AFTER INSERT ON templates BEGIN
+    INSERT INTO templates_fts(rowid, name, description, content)
+    VALUES (new.id, new.name, new.description, new.content);
+END;
+
+CREATE TRIGGER trg_templates_fts_delete AFTER DELETE ON templates BEGIN
+    INSERT INTO templates_fts(templates_fts, rowid, name, description, content)
+    VALUES ('delete', old.id, old.name, old.description, old.content);
+END;
+
+CREATE TRIGGER trg_templates_fts_update AFTER UPDATE ON templates BEGIN
+    INSERT INTO templates_fts(templates_fts, rowid, name, description, content)
+    VALUES ('delete', old.id, old.name, old.description, old.content);
+    INSERT INTO templates_fts(rowid, name, description, content)
+    VALUES (new.id, new.name, new.description, new.content);
+END;
+
+-- ── Job output (jobs use TEXT ids, so index on the implicit rowid) ──
+CREATE VIRTUAL TABLE jobs_fts USING fts5(
+    command, output, error,
+    content='jobs', content_rowid='rowid'
+);
+
+CREATE TRIGGER trg_jobs_fts_insert AFTER INSERT ON jobs BEGIN
+    INSERT INTO jobs_fts(rowid, command, output, error)
+    VALUES (new.rowid, new.command, new.output, new.error);
+END;
+
+CREATE TRIGGER trg_jobs_fts_delete AFTER DELETE ON jobs BEGIN
+    INSERT INTO jobs_fts(jobs_fts, rowid, command, output, error)
+    VALUES ('delete', old.rowid, old.command, old.output, old.error);
+END;
+
+CREATE TRIGGER trg_jobs_fts_update AFTER UPDATE ON jobs BEGIN
+    INSERT INTO jobs_fts(jobs_fts, rowid, command, output, error)
+    VALUES ('delete', old.rowid, old.command, old.output, old.error);
+    INSERT INTO jobs_fts(rowid, command, output, error)
+    VALUES (new.rowid, new.command, new.output, new.error);
+END;
+
+-- ── Discovery logs ────────────────────────────────────────────
+CREATE VIRTUAL TABLE discovery_logs_fts USING fts5(
+    hostname, mac, ip, vendor, message,
+    content='discovery_logs', content_rowid='id'
+);
+
+CREATE TRIGGER trg_discovery_logs_fts_insert AFTER INSERT ON discovery_logs BEGIN
+    INSERT INTO discovery_logs_fts(rowid, hostname, mac, ip, vendor, message)
+    VALUES (new.id, new.hostname, new.mac, new.ip, new.vendor, new.message);
+END;
+
+CREATE TRIGGER trg_discovery_logs_fts_delete AFTER DELETE ON discovery_logs BEGIN
+    INSERT INTO discovery_logs_fts(discovery_logs_fts, rowid, hostname, mac, ip, vendor, message)
+    VALUES ('delete', old.id, old.hostname, old.mac, old.ip, old.vendor, old.message);
+END;
+
+-- Index rows that existed before this migration
+INSERT INTO devices_fts(devices_fts) VALUES ('rebuild');
+INSERT INTO templates_fts(templates_fts) VALUES ('rebuild');
+INSERT INTO jobs_fts(jobs_fts) VALUES ('rebuild');
+INSERT INTO discovery_logs_fts(discovery_logs_fts) VALUES ('rebuild');
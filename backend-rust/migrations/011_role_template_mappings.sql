@@ -0,0 +1,38 @@
+-- Role templates used to be found by name ("Arista EOS Default" + role "spine" -> "Arista EOS Spine").
+-- Render paths now use explicit device_role_templates mappings; carry existing pairs over.
+
+-- Create any device role implied by a convention-named template pair
+INSERT INTO device_roles (name, description, group_names, created_at, updated_at)
+SELECT DISTINCT
+    REPLACE(LOWER(SUBSTR(rt.name, LENGTH(bt.name) - 6)), ' ', '-'),
+    'Migrated from template naming convention',
+    '[]',
+    CURRENT_TIMESTAMP,
+    CURRENT_TIMESTAMP
+FROM templates bt
+JOIN templates rt
+    ON rt.id != bt.id
+   AND SUBSTR(rt.name, 1, LENGTH(bt.name) - 7) = SUBSTR(bt.name, 1, LENGTH(bt.name) - 8) || ' '
+   AND rt.name NOT LIKE '% Default'
+WHERE bt.name LIKE '% Default'
+  AND LENGTH(rt.name) > LENGTH(bt.name) - 7
+  AND NOT EXISTS (
+      SELECT 1 FROM device_roles r
+      WHERE r.name = REPLACE(LOWER(SUBSTR(rt.name, LENGTH(bt.name) - 6)), ' ', '-')
+  );
+
+-- Map each convention-named role template onto its role, after any existing mappings
+INSERT OR IGNORE INTO device_role_templates (role_id, template_id, sort_order)
+SELECT
+    r.id,
+    rt.id,
+    (SELECT COALESCE(MAX(x.sort_order) + 1, 0) FROM device_role_templates x WHERE x.role_id = r.id)
+FROM templates bt
+JOIN templates rt
+    ON rt.id != bt.id
+   AND SUBSTR(rt.name, 1, LENGTH(bt.name) - 7) = SUBSTR(bt.name, 1, LENGTH(bt.name) - 8) || ' '
+   AND rt.name NOT LIKE '% Default'
+JOIN device_roles r
+    ON r.name = REPLACE(LOWER(SUBSTR(rt.name, LENGTH(bt.name) - 6)), ' ', '-')
+WHERE bt.name LIKE '% Default'
+  AND LENGTH(rt.name) > LENGTH(bt.name) - 7;
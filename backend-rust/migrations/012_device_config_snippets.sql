@@ -0,0 +1,8 @@
+-- Per-device config snippets prepended/appended to the rendered template output
+CREATE TABLE device_config_snippets (
+    device_id INTEGER PRIMARY KEY,
+    pre_config TEXT NOT NULL DEFAULT '',
+    post_config TEXT NOT NULL DEFAULT '',
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (device_id) REFERENCES devices(id) ON DELETE CASCADE
+);
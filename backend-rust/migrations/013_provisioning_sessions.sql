@@ -0,0 +1,15 @@
+-- Provisioning sessions track a device re-bootstrapping after a reprovision request
+CREATE TABLE provisioning_sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'active',
+    config_file TEXT NOT NULL DEFAULT '',
+    lease_released INTEGER NOT NULL DEFAULT 0,
+    started_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    lease_at DATETIME,
+    config_pulled_at DATETIME,
+    completed_at DATETIME,
+    FOREIGN KEY (device_id) REFERENCES devices(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_provisioning_sessions_device ON provisioning_sessions(device_id, status);
@@ -0,0 +1,4 @@
+-- Prefixes are unique per VRF; prefixes without a VRF (NULL or 0) share the global scope
+CREATE UNIQUE INDEX idx_ipam_prefixes_vrf_unique ON ipam_prefixes(network_int, broadcast_int, COALESCE(vrf_id, 0));
+
+CREATE INDEX idx_ipam_ip_addresses_vrf_address ON ipam_ip_addresses(address_int, COALESCE(vrf_id, 0));
@@ -0,0 +1,14 @@
+-- ARP/ND collection: last-seen tracking on managed addresses, plus addresses seen in the network but not in IPAM
+ALTER TABLE ipam_ip_addresses ADD COLUMN last_seen DATETIME;
+ALTER TABLE ipam_ip_addresses ADD COLUMN last_seen_mac TEXT NOT NULL DEFAULT '';
+
+CREATE TABLE ipam_discovered_addresses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    address TEXT NOT NULL UNIQUE,
+    mac TEXT NOT NULL DEFAULT '',
+    device_id INTEGER,
+    interface_name TEXT NOT NULL DEFAULT '',
+    first_seen DATETIME DEFAULT CURRENT_TIMESTAMP,
+    last_seen DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (device_id) REFERENCES devices(id) ON DELETE SET NULL
+);
@@ -0,0 +1,17 @@
+-- Credential usage audit and rotation tracking
+ALTER TABLE credentials ADD COLUMN last_used_at DATETIME;
+ALTER TABLE credentials ADD COLUMN rotated_at DATETIME;
+-- Password replaced by the last rotation; rotation push jobs log in with it
+ALTER TABLE credentials ADD COLUMN previous_password TEXT NOT NULL DEFAULT '';
+
+CREATE TABLE credential_usage (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    credential_id INTEGER NOT NULL,
+    device_id INTEGER,
+    job_id TEXT NOT NULL DEFAULT '',
+    used_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (credential_id) REFERENCES credentials(id) ON DELETE CASCADE,
+    FOREIGN KEY (device_id) REFERENCES devices(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_credential_usage_credential ON credential_usage(credential_id, used_at);
@@ -0,0 +1,26 @@
+-- Bind devices and groups to stored credentials instead of inline ssh_user/ssh_pass
+ALTER TABLE devices ADD COLUMN credential_id INTEGER REFERENCES credentials(id) ON DELETE SET NULL;
+ALTER TABLE groups ADD COLUMN credential_id INTEGER REFERENCES credentials(id) ON DELETE SET NULL;
+
+-- Convert each distinct inline user/password pair into a credential
+INSERT INTO credentials (name, description, cred_type, username, password, created_at, updated_at)
+SELECT COALESCE(NULLIF(ssh_user, ''), 'default user') || ' (migrated from ' || MIN(hostname) || ')',
+       'Converted from inline device SSH credentials',
+       'ssh',
+       COALESCE(ssh_user, ''),
+       COALESCE(ssh_pass, ''),
+       CURRENT_TIMESTAMP,
+       CURRENT_TIMESTAMP
+FROM devices
+WHERE COALESCE(ssh_user, '') != '' OR COALESCE(ssh_pass, '') != ''
+GROUP BY COALESCE(ssh_user, ''), COALESCE(ssh_pass, '');
+
+UPDATE devices SET credential_id = (
+    SELECT MIN(c.id) FROM credentials c
+    WHERE c.description = 'Converted from inline device SSH credentials'
+      AND c.username = COALESCE(devices.ssh_user, '')
+      AND c.password = COALESCE(devices.ssh_pass, '')
+)
+WHERE COALESCE(ssh_user, '') != '' OR COALESCE(ssh_pass, '') != '';
+
+UPDATE devices SET ssh_user = '', ssh_pass = '' WHERE credential_id IS NOT NULL;
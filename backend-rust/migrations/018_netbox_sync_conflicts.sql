@@ -0,0 +1,27 @@
+-- NetBox pull conflict review queue
+ALTER TABLE netbox_config ADD COLUMN conflict_policy TEXT NOT NULL DEFAULT 'queue';
+
+-- Field values NetBox and the local device last agreed on, used to tell which side changed
+CREATE TABLE netbox_sync_state (
+    device_id INTEGER PRIMARY KEY,
+    netbox_id INTEGER NOT NULL,
+    baseline TEXT NOT NULL DEFAULT '{}',
+    synced_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (device_id) REFERENCES devices(id) ON DELETE CASCADE
+);
+
+CREATE TABLE netbox_sync_conflicts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER NOT NULL,
+    netbox_id INTEGER NOT NULL,
+    fields TEXT NOT NULL DEFAULT '[]',
+    status TEXT NOT NULL DEFAULT 'pending',
+    resolution TEXT NOT NULL DEFAULT '',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    resolved_at DATETIME,
+    FOREIGN KEY (device_id) REFERENCES devices(id) ON DELETE CASCADE
+);
+
+-- At most one open conflict per device; re-pulls refresh it
+CREATE UNIQUE INDEX idx_netbox_sync_conflicts_pending ON netbox_sync_conflicts(device_id) WHERE status = 'pending';
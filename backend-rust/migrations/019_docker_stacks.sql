@@ -0,0 +1,10 @@
+-- Named groups of companion containers (dnsmasq variants, FRR lab nodes, GPU node simulators)
+-- managed together from the Docker API. Services are stored as a JSON array.
+CREATE TABLE docker_stacks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT DEFAULT '',
+    services TEXT NOT NULL DEFAULT '[]',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,15 @@
+-- Virtual lab nodes: containers standing in for a topology's devices so deploy/diff
+-- jobs can be rehearsed against the lab before touching hardware
+CREATE TABLE lab_nodes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    topology_id INTEGER NOT NULL REFERENCES topologies(id) ON DELETE CASCADE,
+    device_id INTEGER NOT NULL UNIQUE REFERENCES devices(id) ON DELETE CASCADE,
+    container_name TEXT NOT NULL,
+    container_id TEXT DEFAULT '',
+    image TEXT NOT NULL,
+    mgmt_ip TEXT DEFAULT '',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_lab_nodes_topology ON lab_nodes(topology_id);
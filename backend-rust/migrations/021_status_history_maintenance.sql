@@ -0,0 +1,27 @@
+-- Device status transitions, recorded whenever a device's status changes, for availability reporting
+CREATE TABLE device_status_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    previous_status TEXT NOT NULL DEFAULT '',
+    status TEXT NOT NULL,
+    changed_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_device_status_history_device ON device_status_history(device_id, changed_at);
+CREATE INDEX idx_device_status_history_changed ON device_status_history(changed_at);
+
+-- Scheduled maintenance. A window covers one device, the direct members of one group,
+-- or every device when neither is set. Downtime inside a window doesn't count against SLA.
+CREATE TABLE maintenance_windows (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    description TEXT DEFAULT '',
+    device_id INTEGER REFERENCES devices(id) ON DELETE CASCADE,
+    group_id INTEGER REFERENCES groups(id) ON DELETE CASCADE,
+    starts_at DATETIME NOT NULL,
+    ends_at DATETIME NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_maintenance_windows_range ON maintenance_windows(starts_at, ends_at);
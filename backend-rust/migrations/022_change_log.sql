@@ -0,0 +1,20 @@
+-- Change log: deploys, template edits, variable changes and settings changes, with the user
+-- who made them. Deploy entries reference their job via resource_id.
+CREATE TABLE change_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    category TEXT NOT NULL,
+    action TEXT NOT NULL,
+    resource_type TEXT NOT NULL DEFAULT '',
+    resource_id TEXT NOT NULL DEFAULT '',
+    resource_name TEXT NOT NULL DEFAULT '',
+    device_id INTEGER REFERENCES devices(id) ON DELETE SET NULL,
+    group_id INTEGER REFERENCES groups(id) ON DELETE SET NULL,
+    username TEXT NOT NULL DEFAULT '',
+    summary TEXT NOT NULL DEFAULT '',
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_change_log_created ON change_log(created_at);
+CREATE INDEX idx_change_log_device ON change_log(device_id, created_at);
+CREATE INDEX idx_change_log_group ON change_log(group_id, created_at);
+CREATE INDEX idx_change_log_username ON change_log(username, created_at);
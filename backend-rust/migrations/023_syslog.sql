@@ -0,0 +1,46 @@
+-- Syslog events received from managed devices, matched to a device by source IP
+CREATE TABLE syslog_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER REFERENCES devices(id) ON DELETE CASCADE,
+    source_ip TEXT NOT NULL,
+    facility INTEGER NOT NULL,
+    severity INTEGER NOT NULL,
+    hostname TEXT NOT NULL DEFAULT '',
+    app_name TEXT NOT NULL DEFAULT '',
+    message TEXT NOT NULL,
+    received_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_syslog_events_device ON syslog_events(device_id, received_at);
+CREATE INDEX idx_syslog_events_received ON syslog_events(received_at);
+-- Incoming messages are matched to devices by source IP
+CREATE INDEX idx_devices_ip ON devices(ip);
+
+-- Patterns that raise an alert when a received message matches. max_severity limits a rule
+-- to messages at least that severe (0 = emergency .. 7 = debug).
+CREATE TABLE syslog_alert_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    pattern TEXT NOT NULL,
+    max_severity INTEGER NOT NULL DEFAULT 7,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL
+);
+
+CREATE TABLE syslog_alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    rule_id INTEGER NOT NULL REFERENCES syslog_alert_rules(id) ON DELETE CASCADE,
+    event_id INTEGER NOT NULL REFERENCES syslog_events(id) ON DELETE CASCADE,
+    device_id INTEGER REFERENCES devices(id) ON DELETE CASCADE,
+    acknowledged INTEGER NOT NULL DEFAULT 0,
+    acknowledged_by TEXT NOT NULL DEFAULT '',
+    acknowledged_at DATETIME,
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_syslog_alerts_created ON syslog_alerts(acknowledged, created_at);
+
+INSERT INTO syslog_alert_rules (name, pattern, max_severity, enabled, created_at, updated_at) VALUES
+    ('BGP neighbor down', '(?i)(bgp[^ ]*adjchange.*\bdown\b|bgp.*(neighbor|peer).*\b(down|idle)\b)', 7, 1, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP),
+    ('Interface errors', '(?i)((crc|input|output|frame) errors?|err-?disabled|lineproto-5-updown.*\bdown\b)', 7, 1, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP);
@@ -0,0 +1,13 @@
+-- Interface inventory collected from devices by interface_collect jobs
+CREATE TABLE device_interfaces (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT '',
+    speed_mbps INTEGER,
+    description TEXT NOT NULL DEFAULT '',
+    mac TEXT NOT NULL DEFAULT '',
+    ip TEXT NOT NULL DEFAULT '',
+    collected_at DATETIME NOT NULL,
+    UNIQUE(device_id, name)
+);
@@ -0,0 +1,27 @@
+-- Hardware inventory (chassis, modules, PSUs, fans, transceivers) collected from devices
+CREATE TABLE device_hardware (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    component_type TEXT NOT NULL,
+    name TEXT NOT NULL,
+    part_number TEXT NOT NULL DEFAULT '',
+    serial_number TEXT NOT NULL DEFAULT '',
+    description TEXT NOT NULL DEFAULT '',
+    collected_at DATETIME NOT NULL,
+    UNIQUE(device_id, name)
+);
+
+CREATE INDEX idx_device_hardware_serial ON device_hardware(serial_number);
+
+-- Chassis serials that differ from the device's recorded serial
+CREATE TABLE hardware_serial_mismatches (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    recorded_serial TEXT NOT NULL,
+    observed_serial TEXT NOT NULL,
+    detected_at DATETIME NOT NULL,
+    resolved_at DATETIME,
+    resolved_by TEXT NOT NULL DEFAULT ''
+);
+
+CREATE INDEX idx_hardware_serial_mismatches_device ON hardware_serial_mismatches(device_id, resolved_at);
@@ -0,0 +1,28 @@
+-- Topology-wide deploys: devices are deployed stage by stage, each stage gated on the
+-- previous one's deploy (and optional verification) jobs succeeding
+CREATE TABLE topology_deploys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    topology_id INTEGER NOT NULL REFERENCES topologies(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'running',
+    -- JSON array of stages, each an array of topology roles
+    stages TEXT NOT NULL,
+    verify_command TEXT NOT NULL DEFAULT '',
+    lab INTEGER NOT NULL DEFAULT 0,
+    current_stage INTEGER NOT NULL DEFAULT 0,
+    error TEXT NOT NULL DEFAULT '',
+    triggered_by TEXT NOT NULL DEFAULT '',
+    created_at DATETIME NOT NULL,
+    completed_at DATETIME
+);
+
+CREATE INDEX idx_topology_deploys_topology ON topology_deploys(topology_id, created_at);
+
+-- Devices planned into each stage, with the jobs run for them
+CREATE TABLE topology_deploy_devices (
+    deploy_id INTEGER NOT NULL REFERENCES topology_deploys(id) ON DELETE CASCADE,
+    device_id INTEGER NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    stage INTEGER NOT NULL,
+    deploy_job_id TEXT,
+    verify_job_id TEXT,
+    PRIMARY KEY (deploy_id, device_id)
+);
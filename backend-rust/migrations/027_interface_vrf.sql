@@ -0,0 +1,3 @@
+-- VRF membership of collected interfaces: NULL when the device's VRFs weren't collected,
+-- empty for the default VRF
+ALTER TABLE device_interfaces ADD COLUMN vrf TEXT DEFAULT NULL;
@@ -0,0 +1,13 @@
+-- Provenance of templates installed from the community template catalog. content is the
+-- catalog version that was installed, to tell local edits apart from upstream updates.
+CREATE TABLE template_catalog_installs (
+    template_id INTEGER PRIMARY KEY REFERENCES templates(id) ON DELETE CASCADE,
+    source_url TEXT NOT NULL,
+    source_path TEXT NOT NULL,
+    commit_sha TEXT NOT NULL,
+    content TEXT NOT NULL,
+    installed_by TEXT NOT NULL DEFAULT '',
+    installed_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_template_catalog_installs_source ON template_catalog_installs(source_url, source_path);
@@ -0,0 +1,21 @@
+-- Seed data is applied as versioned sets: each set runs once per version instead of on every start
+CREATE TABLE seed_versions (
+    name TEXT PRIMARY KEY,
+    version INTEGER NOT NULL,
+    applied_at DATETIME NOT NULL
+);
+
+-- Seeded items and the seed content last applied to each. A local copy that no longer matches
+-- applied_content (or is flagged user_modified) isn't overwritten on upgrade; the new seed
+-- content waits in pending_content until the user applies or dismisses it.
+CREATE TABLE seed_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    seed_key TEXT NOT NULL,
+    resource_id INTEGER,
+    applied_content TEXT NOT NULL,
+    user_modified INTEGER NOT NULL DEFAULT 0,
+    pending_content TEXT DEFAULT NULL,
+    updated_at DATETIME NOT NULL,
+    UNIQUE(kind, seed_key)
+);
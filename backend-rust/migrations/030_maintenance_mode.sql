@@ -0,0 +1,8 @@
+-- Global maintenance mode, kept across restarts so a freeze isn't lifted by a restart
+CREATE TABLE maintenance_mode (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    enabled INTEGER NOT NULL DEFAULT 0,
+    reason TEXT NOT NULL DEFAULT '',
+    changed_by TEXT NOT NULL DEFAULT '',
+    changed_at DATETIME DEFAULT NULL
+);
@@ -0,0 +1,14 @@
+-- History of database backups and VACUUM/ANALYZE runs
+CREATE TABLE db_maintenance_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    filename TEXT DEFAULT NULL,
+    size_bytes INTEGER NOT NULL DEFAULT 0,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    uploaded_to TEXT DEFAULT NULL,
+    error TEXT DEFAULT NULL,
+    triggered_by TEXT NOT NULL DEFAULT '',
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_db_maintenance_runs_kind ON db_maintenance_runs(kind, created_at);
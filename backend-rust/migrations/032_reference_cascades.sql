@@ -0,0 +1,32 @@
+-- Devices, vendors and job templates refer to vendors, templates and groups by value rather
+-- than through foreign keys. These triggers clear those references when the target is
+-- deleted, so a delete can never leave them dangling. Whether a referenced row may be
+-- deleted at all is decided by the delete policy in settings.
+
+CREATE TRIGGER vendors_clear_references AFTER DELETE ON vendors
+BEGIN
+    UPDATE devices SET vendor = '' WHERE vendor = CAST(OLD.id AS TEXT) OR vendor = OLD.name;
+END;
+
+CREATE TRIGGER templates_clear_references AFTER DELETE ON templates
+BEGIN
+    UPDATE devices SET config_template = '' WHERE config_template = CAST(OLD.id AS TEXT);
+    UPDATE vendors SET default_template = '' WHERE default_template = CAST(OLD.id AS TEXT);
+END;
+
+CREATE TRIGGER groups_clear_references AFTER DELETE ON groups
+BEGIN
+    UPDATE job_templates SET target_group_id = 0 WHERE target_group_id = OLD.id;
+END;
+
+-- Clean up references already left dangling by earlier deletes
+UPDATE devices SET config_template = ''
+WHERE config_template != '' AND CAST(config_template AS INTEGER) NOT IN (SELECT id FROM templates)
+  AND config_template GLOB '[0-9]*';
+UPDATE devices SET vendor = ''
+WHERE vendor != '' AND vendor GLOB '[0-9]*' AND CAST(vendor AS INTEGER) NOT IN (SELECT id FROM vendors);
+UPDATE vendors SET default_template = ''
+WHERE default_template != '' AND default_template GLOB '[0-9]*'
+  AND CAST(default_template AS INTEGER) NOT IN (SELECT id FROM templates);
+UPDATE job_templates SET target_group_id = 0
+WHERE target_group_id != 0 AND target_group_id NOT IN (SELECT id FROM groups);
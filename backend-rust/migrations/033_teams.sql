@@ -0,0 +1,56 @@
+-- Teams: users belong to teams, teams own devices/groups/templates, and per-team rules grant
+-- device actions to team members
+CREATE TABLE teams (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE team_members (
+    team_id INTEGER NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (team_id, user_id)
+);
+
+CREATE INDEX idx_team_members_user ON team_members(user_id);
+
+-- A resource has at most one owning team
+CREATE TABLE team_resources (
+    resource_type TEXT NOT NULL,
+    resource_id INTEGER NOT NULL,
+    team_id INTEGER NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (resource_type, resource_id)
+);
+
+CREATE INDEX idx_team_resources_team ON team_resources(team_id);
+
+CREATE TABLE team_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    team_id INTEGER NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    action TEXT NOT NULL,
+    scope TEXT NOT NULL,
+    group_id INTEGER DEFAULT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    command_pattern TEXT NOT NULL DEFAULT '*',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_team_rules_team ON team_rules(team_id);
+
+CREATE TRIGGER devices_drop_team_ownership AFTER DELETE ON devices
+BEGIN
+    DELETE FROM team_resources WHERE resource_type = 'device' AND resource_id = OLD.id;
+END;
+
+CREATE TRIGGER groups_drop_team_ownership AFTER DELETE ON groups
+BEGIN
+    DELETE FROM team_resources WHERE resource_type = 'group' AND resource_id = OLD.id;
+END;
+
+CREATE TRIGGER templates_drop_team_ownership AFTER DELETE ON templates
+BEGIN
+    DELETE FROM team_resources WHERE resource_type = 'template' AND resource_id = OLD.id;
+END;
@@ -0,0 +1,37 @@
+-- Permissions matrix: roles group users, and (resource, action) grants are assigned to roles
+-- or teams
+CREATE TABLE roles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE role_members (
+    role_id INTEGER NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (role_id, user_id)
+);
+
+CREATE INDEX idx_role_members_user ON role_members(user_id);
+
+-- subject_type is 'role' or 'team'
+CREATE TABLE permission_grants (
+    subject_type TEXT NOT NULL,
+    subject_id INTEGER NOT NULL,
+    resource TEXT NOT NULL,
+    action TEXT NOT NULL,
+    PRIMARY KEY (subject_type, subject_id, resource, action)
+);
+
+CREATE TRIGGER roles_drop_permission_grants AFTER DELETE ON roles
+BEGIN
+    DELETE FROM permission_grants WHERE subject_type = 'role' AND subject_id = OLD.id;
+END;
+
+CREATE TRIGGER teams_drop_permission_grants AFTER DELETE ON teams
+BEGIN
+    DELETE FROM permission_grants WHERE subject_type = 'team' AND subject_id = OLD.id;
+END;
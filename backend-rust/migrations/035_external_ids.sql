@@ -0,0 +1,35 @@
+-- Client-supplied identifiers (e.g. Terraform resource IDs) mapped to local rows, so
+-- declarative clients can upsert by their own key
+CREATE TABLE external_ids (
+    resource_type TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    resource_id INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (resource_type, external_id),
+    UNIQUE (resource_type, resource_id)
+);
+
+CREATE TRIGGER devices_drop_external_id AFTER DELETE ON devices
+BEGIN
+    DELETE FROM external_ids WHERE resource_type = 'device' AND resource_id = OLD.id;
+END;
+
+CREATE TRIGGER groups_drop_external_id AFTER DELETE ON groups
+BEGIN
+    DELETE FROM external_ids WHERE resource_type = 'group' AND resource_id = OLD.id;
+END;
+
+CREATE TRIGGER templates_drop_external_id AFTER DELETE ON templates
+BEGIN
+    DELETE FROM external_ids WHERE resource_type = 'template' AND resource_id = OLD.id;
+END;
+
+CREATE TRIGGER ipam_prefixes_drop_external_id AFTER DELETE ON ipam_prefixes
+BEGIN
+    DELETE FROM external_ids WHERE resource_type = 'ipam_prefix' AND resource_id = OLD.id;
+END;
+
+CREATE TRIGGER ipam_ip_addresses_drop_external_id AFTER DELETE ON ipam_ip_addresses
+BEGIN
+    DELETE FROM external_ids WHERE resource_type = 'ipam_ip_address' AND resource_id = OLD.id;
+END;
@@ -0,0 +1,31 @@
+-- Automation rules: when a hub event of `event_type` matching `filter` occurs, run `action`
+CREATE TABLE automation_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+    event_type TEXT NOT NULL,
+    -- JSON object of payload field -> expected value
+    filter TEXT NOT NULL DEFAULT '{}',
+    action_type TEXT NOT NULL,
+    -- JSON settings for the action type
+    action TEXT NOT NULL DEFAULT '{}',
+    enabled INTEGER NOT NULL DEFAULT 1,
+    last_triggered_at DATETIME,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_automation_rules_event ON automation_rules(event_type);
+
+CREATE TABLE automation_rule_executions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    rule_id INTEGER NOT NULL REFERENCES automation_rules(id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    event_payload TEXT NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL,
+    output TEXT NOT NULL DEFAULT '',
+    error TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_automation_rule_executions_rule ON automation_rule_executions(rule_id, id);
@@ -0,0 +1,30 @@
+-- Notification channels: where reports and alerts are delivered
+CREATE TABLE notification_channels (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    channel_type TEXT NOT NULL,
+    -- JSON settings for the channel type
+    config TEXT NOT NULL DEFAULT '{}',
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Scheduled fleet reports, sent to notification channels on a cron schedule
+CREATE TABLE report_schedules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+    schedule TEXT NOT NULL,
+    -- JSON array of notification channel IDs
+    channel_ids TEXT NOT NULL DEFAULT '[]',
+    stale_backup_days INTEGER NOT NULL DEFAULT 7,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    last_run_at DATETIME,
+    last_status TEXT,
+    last_error TEXT,
+    -- JSON array of device IDs with interface drift at the last run, to report new drift
+    last_drift_device_ids TEXT NOT NULL DEFAULT '[]',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,44 @@
+-- Runbooks: ordered manual and automated steps, optionally attached to a device role
+CREATE TABLE runbooks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+    device_role_id INTEGER REFERENCES device_roles(id) ON DELETE SET NULL,
+    -- JSON array of steps
+    steps TEXT NOT NULL DEFAULT '[]',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- One execution of a runbook against a device
+CREATE TABLE runbook_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    runbook_id INTEGER NOT NULL REFERENCES runbooks(id) ON DELETE CASCADE,
+    device_id INTEGER NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    status TEXT NOT NULL,
+    started_by TEXT NOT NULL DEFAULT '',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    completed_at DATETIME
+);
+
+CREATE INDEX idx_runbook_runs_runbook ON runbook_runs(runbook_id);
+CREATE INDEX idx_runbook_runs_device ON runbook_runs(device_id);
+
+-- The runbook's steps as they were when the run started, with their sign-off
+CREATE TABLE runbook_run_steps (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id INTEGER NOT NULL REFERENCES runbook_runs(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    instructions TEXT NOT NULL DEFAULT '',
+    step_type TEXT NOT NULL,
+    vendor_action_id INTEGER,
+    job_template_id INTEGER,
+    status TEXT NOT NULL,
+    -- JSON array of job IDs queued by the step's last execution
+    job_ids TEXT NOT NULL DEFAULT '[]',
+    signed_off_by TEXT NOT NULL DEFAULT '',
+    signed_off_at DATETIME,
+    notes TEXT NOT NULL DEFAULT '',
+    UNIQUE(run_id, position)
+);
@@ -0,0 +1,20 @@
+-- Configs staged for a device, pushed by the scheduler at their activation time
+CREATE TABLE staged_configs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    description TEXT NOT NULL DEFAULT '',
+    config TEXT NOT NULL,
+    activate_at DATETIME NOT NULL,
+    -- Wait for a maintenance window covering the device once activate_at has passed
+    require_maintenance_window INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL,
+    job_id TEXT,
+    error TEXT NOT NULL DEFAULT '',
+    created_by TEXT NOT NULL DEFAULT '',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    activated_at DATETIME
+);
+
+CREATE INDEX idx_staged_configs_device ON staged_configs(device_id);
+CREATE INDEX idx_staged_configs_status ON staged_configs(status, activate_at);
@@ -0,0 +1,4 @@
+-- Confirmed-commit deploys: commit with a rollback timer, then confirm once the device is
+-- still reachable
+ALTER TABLE vendors ADD COLUMN commit_confirm_command TEXT NOT NULL DEFAULT '';
+ALTER TABLE vendors ADD COLUMN confirm_command TEXT NOT NULL DEFAULT '';
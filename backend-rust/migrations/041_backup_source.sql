@@ -0,0 +1,3 @@
+-- Backups uploaded by operators alongside those fetched over SSH
+ALTER TABLE backups ADD COLUMN source TEXT NOT NULL DEFAULT 'ssh';
+ALTER TABLE backups ADD COLUMN description TEXT NOT NULL DEFAULT '';
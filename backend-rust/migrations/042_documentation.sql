@@ -0,0 +1,34 @@
+-- Markdown notes and file attachments documenting a device or topology. Attachment files
+-- live on disk under {BACKUP_DIR}/attachments; only their metadata is stored here.
+CREATE TABLE notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER REFERENCES devices(id) ON DELETE CASCADE,
+    topology_id INTEGER REFERENCES topologies(id) ON DELETE CASCADE,
+    title TEXT NOT NULL DEFAULT '',
+    body TEXT NOT NULL DEFAULT '',
+    created_by TEXT NOT NULL DEFAULT '',
+    updated_by TEXT NOT NULL DEFAULT '',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((device_id IS NULL) <> (topology_id IS NULL))
+);
+
+CREATE INDEX idx_notes_device ON notes(device_id);
+CREATE INDEX idx_notes_topology ON notes(topology_id);
+
+CREATE TABLE attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER REFERENCES devices(id) ON DELETE CASCADE,
+    topology_id INTEGER REFERENCES topologies(id) ON DELETE CASCADE,
+    filename TEXT NOT NULL,
+    stored_name TEXT NOT NULL UNIQUE,
+    content_type TEXT NOT NULL DEFAULT 'application/octet-stream',
+    size INTEGER NOT NULL DEFAULT 0,
+    description TEXT NOT NULL DEFAULT '',
+    uploaded_by TEXT NOT NULL DEFAULT '',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((device_id IS NULL) <> (topology_id IS NULL))
+);
+
+CREATE INDEX idx_attachments_device ON attachments(device_id);
+CREATE INDEX idx_attachments_topology ON attachments(topology_id);
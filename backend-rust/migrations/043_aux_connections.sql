@@ -0,0 +1,18 @@
+-- Power feeds (PDU outlet to power supply) and console lines (console server port to console
+-- port), tracked per device like port assignments
+CREATE TABLE device_aux_connections (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('power', 'console')),
+    port_name TEXT NOT NULL,
+    remote_device_id INTEGER REFERENCES devices(id) ON DELETE SET NULL,
+    remote_port_name TEXT NOT NULL DEFAULT '',
+    description TEXT NOT NULL DEFAULT '',
+    cable_length_meters REAL DEFAULT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(device_id, kind, port_name)
+);
+
+CREATE INDEX idx_aux_connections_device ON device_aux_connections(device_id);
+CREATE INDEX idx_aux_connections_remote ON device_aux_connections(remote_device_id);
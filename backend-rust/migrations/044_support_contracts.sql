@@ -0,0 +1,22 @@
+-- Warranty / support contracts, covering a single device or every device of a model.
+-- A device's own contract takes precedence over its model's.
+CREATE TABLE support_contracts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_id INTEGER UNIQUE REFERENCES devices(id) ON DELETE CASCADE,
+    device_model_id INTEGER UNIQUE REFERENCES device_models(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL DEFAULT '',
+    -- Contract / case reference quoted when opening a support ticket
+    support_id TEXT NOT NULL DEFAULT '',
+    -- Service level, e.g. "NBD", "4h onsite"
+    vendor_sla TEXT NOT NULL DEFAULT '',
+    starts_on DATE,
+    expires_on DATE NOT NULL,
+    notes TEXT NOT NULL DEFAULT '',
+    -- expires_on the expiry notification was sent for; a renewal (new expires_on) re-arms it
+    alerted_expires_on DATE,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((device_id IS NULL) <> (device_model_id IS NULL))
+);
+
+CREATE INDEX idx_support_contracts_expires_on ON support_contracts(expires_on);
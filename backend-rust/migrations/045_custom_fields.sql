@@ -0,0 +1,31 @@
+-- Operator-defined device attributes (asset tag, cost center, owner, ...)
+CREATE TABLE custom_fields (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- Identifier used in filters, CSV columns and templates ({{custom_fields.asset_tag}})
+    name TEXT NOT NULL UNIQUE,
+    label TEXT NOT NULL DEFAULT '',
+    description TEXT NOT NULL DEFAULT '',
+    -- text, integer, boolean, date or select
+    field_type TEXT NOT NULL DEFAULT 'text',
+    required INTEGER NOT NULL DEFAULT 0,
+    -- JSON array of allowed values for select fields
+    choices TEXT NOT NULL DEFAULT '[]',
+    -- Regex text values must match (empty = any)
+    pattern TEXT NOT NULL DEFAULT '',
+    min_value INTEGER,
+    max_value INTEGER,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Values are stored in canonical text form: integers as digits, booleans as true/false,
+-- dates as YYYY-MM-DD
+CREATE TABLE device_custom_field_values (
+    device_id INTEGER NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    field_id INTEGER NOT NULL REFERENCES custom_fields(id) ON DELETE CASCADE,
+    value TEXT NOT NULL,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (device_id, field_id)
+);
+
+CREATE INDEX idx_device_custom_field_values_field ON device_custom_field_values(field_id, value);
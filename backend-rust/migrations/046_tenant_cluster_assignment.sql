@@ -0,0 +1,27 @@
+-- Assign devices to a tenant and a GPU cluster. Each carries variables that apply to its devices,
+-- resolved after the "all" group and before device groups.
+ALTER TABLE devices ADD COLUMN tenant_id INTEGER REFERENCES tenants(id) ON DELETE SET NULL;
+ALTER TABLE devices ADD COLUMN gpu_cluster_id INTEGER REFERENCES gpu_clusters(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_devices_tenant ON devices(tenant_id);
+CREATE INDEX idx_devices_gpu_cluster ON devices(gpu_cluster_id);
+
+CREATE TABLE tenant_variables (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL DEFAULT '',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(tenant_id, key)
+);
+
+CREATE TABLE gpu_cluster_variables (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    gpu_cluster_id INTEGER NOT NULL REFERENCES gpu_clusters(id) ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL DEFAULT '',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(gpu_cluster_id, key)
+);
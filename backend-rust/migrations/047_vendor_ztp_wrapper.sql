@@ -0,0 +1,3 @@
+-- Tera template wrapping the config served from /ztp/config/{mac}, for NOSes whose HTTP ZTP
+-- fetches a bootstrap script rather than a config. Empty serves the config as-is.
+ALTER TABLE vendors ADD COLUMN ztp_wrapper TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,16 @@
+-- Responses of job-creating requests by Idempotency-Key, replayed when a client retries.
+-- Keys are per user; rows older than the replay window are deleted as new keys arrive.
+CREATE TABLE idempotency_keys (
+    username TEXT NOT NULL,
+    idempotency_key TEXT NOT NULL,
+    -- SHA-256 of the method, path and body, so a key reused for another request is rejected
+    request_hash TEXT NOT NULL,
+    -- NULL while the first request is still running
+    status_code INTEGER,
+    content_type TEXT NOT NULL DEFAULT '',
+    response_body BLOB,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (username, idempotency_key)
+);
+
+CREATE INDEX idx_idempotency_keys_created ON idempotency_keys(created_at);
@@ -0,0 +1,2 @@
+-- Per-job run time limit in seconds; NULL uses the job_timeout_secs setting
+ALTER TABLE jobs ADD COLUMN timeout_secs INTEGER;
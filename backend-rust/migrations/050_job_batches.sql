@@ -0,0 +1,14 @@
+-- Bulk job requests; each queued child job points at its batch through jobs.batch_id
+CREATE TABLE job_batches (
+    id TEXT PRIMARY KEY,
+    job_type TEXT NOT NULL,
+    command TEXT NOT NULL DEFAULT '',
+    -- Targeted group, or 0 for an explicit device list
+    group_id INTEGER NOT NULL DEFAULT 0,
+    created_by TEXT NOT NULL DEFAULT '',
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+ALTER TABLE jobs ADD COLUMN batch_id TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX idx_jobs_batch_id ON jobs(batch_id);
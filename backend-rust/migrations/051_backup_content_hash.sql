@@ -0,0 +1,7 @@
+-- SHA-256 of a backup's config. Backups with a hash share one gzip-compressed file per distinct
+-- config under objects/ in BACKUP_DIR; older backups keep an empty hash and their plain file.
+ALTER TABLE backups ADD COLUMN content_hash TEXT NOT NULL DEFAULT '';
+-- Whether the config differed from the device's previous backup when it was taken
+ALTER TABLE backups ADD COLUMN changed INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX idx_backups_content_hash ON backups(content_hash);
@@ -0,0 +1,33 @@
+-- Threshold rules over collected data, evaluated periodically
+CREATE TABLE alert_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+    metric TEXT NOT NULL,
+    threshold REAL NOT NULL,
+    -- How long the threshold must stay exceeded before the alert fires
+    for_minutes INTEGER NOT NULL DEFAULT 0,
+    channel_ids TEXT NOT NULL DEFAULT '[]',
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- One row per rule and subject (a device or a prefix) each time its threshold is exceeded;
+-- pending until for_minutes have passed, then firing until the value drops back
+CREATE TABLE alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    rule_id INTEGER NOT NULL REFERENCES alert_rules(id) ON DELETE CASCADE,
+    subject_type TEXT NOT NULL,
+    subject_id INTEGER NOT NULL,
+    subject_name TEXT NOT NULL DEFAULT '',
+    value REAL NOT NULL,
+    status TEXT NOT NULL,
+    started_at DATETIME NOT NULL,
+    fired_at DATETIME,
+    resolved_at DATETIME,
+    updated_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_alerts_rule_subject ON alerts(rule_id, subject_type, subject_id, status);
+CREATE INDEX idx_alerts_status ON alerts(status, started_at);
@@ -0,0 +1,12 @@
+-- Alert lifecycle: acknowledgement, assignment and manual resolution. An alert's dedupe key
+-- identifies what it's about (rule and subject); at most one unresolved alert has each key.
+ALTER TABLE alerts ADD COLUMN dedupe_key TEXT NOT NULL DEFAULT '';
+ALTER TABLE alerts ADD COLUMN acknowledged_by TEXT NOT NULL DEFAULT '';
+ALTER TABLE alerts ADD COLUMN acknowledged_at DATETIME;
+ALTER TABLE alerts ADD COLUMN assigned_to TEXT NOT NULL DEFAULT '';
+ALTER TABLE alerts ADD COLUMN resolved_by TEXT NOT NULL DEFAULT '';
+
+UPDATE alerts SET dedupe_key = rule_id || ':' || subject_type || ':' || subject_id;
+
+CREATE UNIQUE INDEX idx_alerts_open_dedupe ON alerts(dedupe_key) WHERE status != 'resolved';
+CREATE INDEX idx_alerts_assigned_to ON alerts(assigned_to);
@@ -0,0 +1,18 @@
+-- Monthly partitions of the high-volume history tables. Rows from past months are moved out of
+-- the live table into {table}_p{YYYYMM}; reads go through the {table}_all views, which are
+-- rebuilt over the live table and its partitions whenever the set of partitions changes.
+CREATE TABLE table_partitions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_name TEXT NOT NULL,
+    partition_name TEXT NOT NULL UNIQUE,
+    month TEXT NOT NULL,
+    row_count INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    UNIQUE(table_name, month)
+);
+
+CREATE VIEW jobs_all AS SELECT * FROM jobs;
+CREATE VIEW syslog_events_all AS SELECT * FROM syslog_events;
+CREATE VIEW discovery_logs_all AS SELECT * FROM discovery_logs;
+CREATE VIEW device_status_history_all AS SELECT * FROM device_status_history;
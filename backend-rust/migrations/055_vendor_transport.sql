@@ -0,0 +1,3 @@
+-- How deploys reach a vendor's devices: 'ssh' (interactive CLI shell) or 'netconf'
+-- (edit-config and commit over the NETCONF SSH subsystem)
+ALTER TABLE vendors ADD COLUMN transport TEXT NOT NULL DEFAULT 'ssh';
@@ -0,0 +1,19 @@
+-- API requests counted per consumer (user and login token), endpoint class and hour. Counts are
+-- gathered in memory and added to the hour's row about once a minute.
+CREATE TABLE api_usage (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    hour DATETIME NOT NULL,
+    username TEXT NOT NULL,
+    token_id TEXT NOT NULL,
+    token_issued_at DATETIME NOT NULL,
+    resource TEXT NOT NULL,
+    access TEXT NOT NULL,
+    requests INTEGER NOT NULL DEFAULT 0,
+    errors INTEGER NOT NULL DEFAULT 0,
+    request_bytes INTEGER NOT NULL DEFAULT 0,
+    response_bytes INTEGER NOT NULL DEFAULT 0,
+    UNIQUE(hour, username, token_id, resource, access)
+);
+
+CREATE INDEX idx_api_usage_hour ON api_usage(hour);
+CREATE INDEX idx_api_usage_username ON api_usage(username, hour);
@@ -0,0 +1,3 @@
+-- Serial numbers identify devices alongside MACs: lookups compare them trimmed and
+-- case-insensitively
+CREATE INDEX IF NOT EXISTS idx_devices_serial_identity ON devices(UPPER(TRIM(serial_number)));
@@ -0,0 +1,37 @@
+-- gNMI telemetry: subscriptions to device paths, and the samples they collect
+
+-- A subscription covers one device, or every device of a vendor. Without a credential the
+-- device's SSH credentials are used.
+CREATE TABLE telemetry_subscriptions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    device_id INTEGER REFERENCES devices(id) ON DELETE CASCADE,
+    vendor_id INTEGER REFERENCES vendors(id) ON DELETE CASCADE,
+    paths TEXT NOT NULL DEFAULT '[]',
+    mode TEXT NOT NULL DEFAULT 'sample',
+    sample_interval_secs INTEGER NOT NULL DEFAULT 30,
+    encoding TEXT NOT NULL DEFAULT 'json_ietf',
+    port INTEGER NOT NULL DEFAULT 57400,
+    tls INTEGER NOT NULL DEFAULT 1,
+    tls_skip_verify INTEGER NOT NULL DEFAULT 0,
+    tls_ca_cert TEXT NOT NULL DEFAULT '',
+    tls_server_name TEXT NOT NULL DEFAULT '',
+    credential_id INTEGER REFERENCES credentials(id) ON DELETE SET NULL,
+    store_samples INTEGER NOT NULL DEFAULT 1,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL
+);
+
+-- One leaf value per row; value is JSON and timestamp is the device's
+CREATE TABLE telemetry_samples (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    subscription_id INTEGER NOT NULL REFERENCES telemetry_subscriptions(id) ON DELETE CASCADE,
+    device_id INTEGER NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    value TEXT NOT NULL,
+    timestamp DATETIME NOT NULL
+);
+
+CREATE INDEX idx_telemetry_samples_device_path ON telemetry_samples(device_id, path, timestamp);
+CREATE INDEX idx_telemetry_samples_timestamp ON telemetry_samples(timestamp);
@@ -0,0 +1,16 @@
+-- Commands the SSH connect test runs to read a device's uptime, hostname, version and
+-- interfaces. Rows without a vendor are the set for devices whose vendor has no row for the
+-- field. Commands are tried in order until one prints output; the optional output parser
+-- then picks the value out of it.
+CREATE TABLE probe_commands (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    vendor_id INTEGER REFERENCES vendors(id) ON DELETE CASCADE,
+    field TEXT NOT NULL,
+    commands TEXT NOT NULL DEFAULT '[]',
+    output_parser_id INTEGER REFERENCES output_parsers(id) ON DELETE SET NULL,
+    max_lines INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL
+);
+
+CREATE UNIQUE INDEX idx_probe_commands_vendor_field ON probe_commands(COALESCE(vendor_id, 0), field);
@@ -0,0 +1,6 @@
+-- Credential rotation pushes used to queue command jobs with the new password in the command,
+-- which the device may also have echoed into the output. The update trigger drops the old text
+-- from the search index.
+UPDATE jobs
+SET command = '[redacted]', output = CASE WHEN output IS NULL THEN NULL ELSE '[redacted]' END
+WHERE triggered_by = 'credential_rotation' AND job_type = 'command';
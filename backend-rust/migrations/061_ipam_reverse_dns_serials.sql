@@ -0,0 +1,9 @@
+-- Last SOA serial handed out for each VRF's reverse DNS export, with a fingerprint of the
+-- PTR records and nameserver it covered. The export bumps the serial only when the
+-- fingerprint changes, so secondaries pick up every change, deletes included.
+CREATE TABLE ipam_reverse_dns_serials (
+    vrf_id INTEGER PRIMARY KEY,
+    serial INTEGER NOT NULL,
+    fingerprint TEXT NOT NULL,
+    updated_at DATETIME NOT NULL
+);
@@ -1,28 +1,47 @@
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::mpsc;
+use tokio::task::JoinSet;
 use tokio::time::{sleep, Duration};
 
 use crate::db::Store;
-use crate::models::Lease;
+use crate::models::{backup_source, Backup, Device, Lease, NewBackup, Settings};
+use crate::services::config_history::ConfigHistory;
+use crate::ws::{BackupProgressPayload, EventType, Hub};
+
+/// Upper bound on the backup_concurrency setting
+const MAX_BACKUP_CONCURRENCY: usize = 64;
+
+/// Subdirectory of BACKUP_DIR holding one gzip-compressed file per distinct backed-up config
+const OBJECTS_SUBDIR: &str = "objects";
 
 /// Backup service handles automated config backups via SSH
 pub struct BackupService {
     store: Store,
     backup_dir: String,
     pending_tx: mpsc::Sender<i64>,
+    ws_hub: Option<Arc<Hub>>,
+    history: Option<Arc<ConfigHistory>>,
 }
 
 impl BackupService {
-    pub fn new(store: Store, backup_dir: String) -> Arc<Self> {
+    pub fn new(
+        store: Store,
+        backup_dir: String,
+        ws_hub: Option<Arc<Hub>>,
+        history: Option<Arc<ConfigHistory>>,
+    ) -> Arc<Self> {
         let (pending_tx, pending_rx) = mpsc::channel(100);
 
         let service = Arc::new(Self {
             store,
             backup_dir,
             pending_tx,
+            ws_hub,
+            history,
         });
 
         // Start the worker
@@ -61,6 +80,10 @@ impl BackupService {
 
         // Get settings for backup delay
         let backup_delay = match self.store.get_settings().await {
+            Ok(settings) if settings.features.disable_auto_backups => {
+                tracing::debug!("Auto-backups disabled, not scheduling a backup for {}", device.hostname);
+                return;
+            }
             Ok(settings) => settings.backup_delay,
             Err(e) => {
                 tracing::warn!("Failed to load settings for backup delay, using default: {}", e);
@@ -75,25 +98,96 @@ impl BackupService {
             backup_delay
         );
 
-        // Schedule backup after delay using device ID
-        let device_id = device.id;
+        self.schedule_backup(device.id, backup_delay.max(0) as u64);
+    }
+
+    /// Queue a backup for a device by ID after a delay
+    pub fn schedule_backup(&self, device_id: i64, delay_secs: u64) {
         let tx = self.pending_tx.clone();
         tokio::spawn(async move {
-            sleep(Duration::from_secs(backup_delay as u64)).await;
+            sleep(Duration::from_secs(delay_secs)).await;
             if let Err(e) = tx.send(device_id).await {
                 tracing::warn!("Failed to queue scheduled backup: {}", e);
             }
         });
     }
 
-    async fn worker(&self, mut pending_rx: mpsc::Receiver<i64>) {
-        while let Some(device_id) = pending_rx.recv().await {
-            if let Err(e) = self.perform_backup(device_id).await {
-                tracing::error!("Backup failed for {}: {}", device_id, e);
+    /// Run queued backups on up to `backup_concurrency` workers. A device is backed up by one
+    /// worker at a time: queueing it again while it runs backs it up once more afterwards, and
+    /// queueing it again while it waits is a no-op.
+    async fn worker(self: Arc<Self>, mut pending_rx: mpsc::Receiver<i64>) {
+        let mut running: JoinSet<Result<()>> = JoinSet::new();
+        // Device of each running task, so a panicked backup still releases its device
+        let mut tasks: HashMap<tokio::task::Id, i64> = HashMap::new();
+        let mut in_flight: HashSet<i64> = HashSet::new();
+        let mut rerun: HashSet<i64> = HashSet::new();
+        let mut waiting: VecDeque<i64> = VecDeque::new();
+
+        loop {
+            if !waiting.is_empty() {
+                let limit = self.concurrency().await;
+                while running.len() < limit {
+                    let Some(device_id) = waiting.pop_front() else { break };
+                    in_flight.insert(device_id);
+                    let svc = self.clone();
+                    let handle = running.spawn(async move { svc.perform_backup(device_id).await });
+                    tasks.insert(handle.id(), device_id);
+                    self.broadcast_progress(EventType::BackupStarted, device_id, None, waiting.len(), running.len()).await;
+                }
+            }
+
+            tokio::select! {
+                received = pending_rx.recv() => match received {
+                    Some(device_id) if in_flight.contains(&device_id) => {
+                        rerun.insert(device_id);
+                    }
+                    Some(device_id) => {
+                        if !waiting.contains(&device_id) {
+                            waiting.push_back(device_id);
+                        }
+                    }
+                    None => break,
+                },
+                Some(finished) = running.join_next_with_id() => {
+                    let (task_id, result, panicked) = match finished {
+                        Ok((id, result)) => (id, result, false),
+                        Err(e) => (e.id(), Err(anyhow::anyhow!("backup worker panicked: {}", e)), true),
+                    };
+                    let Some(device_id) = tasks.remove(&task_id) else { continue };
+                    in_flight.remove(&device_id);
+                    // A panicked backup isn't rerun, so a panic can't repeat in a loop
+                    if rerun.remove(&device_id) && !panicked {
+                        waiting.push_back(device_id);
+                    }
+                    let (event_type, error) = match result {
+                        Ok(()) => (EventType::BackupCompleted, None),
+                        Err(e) => {
+                            tracing::error!("Backup failed for {}: {}", device_id, e);
+                            (EventType::BackupFailed, Some(e.to_string()))
+                        }
+                    };
+                    self.broadcast_progress(event_type, device_id, error, waiting.len(), running.len()).await;
+                }
             }
         }
     }
 
+    async fn concurrency(&self) -> usize {
+        match self.store.get_settings().await {
+            Ok(settings) => (settings.backup_concurrency.max(1) as usize).min(MAX_BACKUP_CONCURRENCY),
+            Err(e) => {
+                tracing::warn!("Failed to load settings for backup concurrency, using default: {}", e);
+                Settings::default().backup_concurrency as usize
+            }
+        }
+    }
+
+    async fn broadcast_progress(&self, event_type: EventType, device_id: i64, error: Option<String>, queued: usize, running: usize) {
+        if let Some(ref hub) = self.ws_hub {
+            hub.broadcast_backup_progress(event_type, BackupProgressPayload { device_id, error, queued, running }).await;
+        }
+    }
+
     async fn perform_backup(&self, device_id: i64) -> Result<()> {
         let device = self
             .store
@@ -104,30 +198,15 @@ impl BackupService {
         let settings = self.store.get_settings().await?;
 
         // Determine credentials
-        let user = device
-            .ssh_user
-            .clone()
-            .filter(|s| !s.is_empty())
-            .unwrap_or(settings.default_ssh_user.clone());
-        let pass = device
-            .ssh_pass
-            .clone()
-            .filter(|s| !s.is_empty())
-            .unwrap_or(settings.default_ssh_pass.clone());
-
-        // Determine backup command
-        let command = if let Some(vendor) = match device.vendor.as_deref() {
-            Some(v) if !v.is_empty() => self.store.resolve_vendor(v).await.ok().flatten(),
-            _ => None,
-        } {
-            if !vendor.backup_command.is_empty() {
-                vendor.backup_command
-            } else {
-                settings.backup_command.clone()
+        let crate::utils::ResolvedSshCredentials { user, pass, credential_id } =
+            crate::utils::resolve_ssh_credentials(&self.store, &device).await;
+        if let Some(cred_id) = credential_id {
+            if let Err(e) = self.store.record_credential_usage(cred_id, Some(device.id), "").await {
+                tracing::warn!("Failed to record usage of credential {}: {}", cred_id, e);
             }
-        } else {
-            settings.backup_command.clone()
-        };
+        }
+
+        let command = resolve_backup_command(&self.store, &device, &settings).await;
 
         tracing::info!(
             "Starting backup for {} ({}) as {}",
@@ -163,36 +242,130 @@ impl BackupService {
         }
 
         // Save backup
-        self.save_backup(&device.hostname, device_id, &config_output).await?;
+        store_backup(&self.store, &self.backup_dir, &device, &config_output, backup_source::SSH, "", Utc::now()).await?;
+        if let Some(history) = &self.history {
+            if let Err(e) = history.record_backup(&device.hostname, &config_output, None).await {
+                tracing::warn!("Failed to commit backup of {} to history: {}", device.hostname, e);
+            }
+        }
 
         // Update device status
         self.store.update_device_status(device_id, crate::models::device_status::ONLINE).await?;
         self.store.update_device_backup_time(device_id).await?;
         self.store.clear_device_error(device_id).await?;
 
+        // Refresh the hardware inventory while the device is known to be reachable
+        if let Err(e) = crate::jobs::collect_hardware_inventory(&self.store, &device, &user, &pass).await {
+            tracing::warn!("Hardware inventory collection failed for {}: {}", device.hostname, e);
+        }
+
         tracing::info!("Backup completed for {}", device.hostname);
         Ok(())
     }
 
-    async fn save_backup(&self, hostname: &str, device_id: i64, config: &str) -> Result<()> {
-        // Ensure backup directory exists
-        tokio::fs::create_dir_all(&self.backup_dir).await?;
+}
+
+/// Hex SHA-256 of a config, the key of its file under objects/
+fn content_hash(config: &str) -> String {
+    ring::digest::digest(&ring::digest::SHA256, config.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn object_path(backup_dir: &str, hash: &str) -> std::path::PathBuf {
+    Path::new(backup_dir).join(OBJECTS_SUBDIR).join(format!("{}.cfg.gz", hash))
+}
 
-        // Generate filename
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-        let safe_name = hostname.replace('/', "_");
-        let filename = format!("{}_{}.cfg", safe_name, timestamp);
-        let file_path = Path::new(&self.backup_dir).join(&filename);
+fn gzip(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Write;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
 
-        // Write file
-        tokio::fs::write(&file_path, config).await?;
+fn gunzip(data: &[u8]) -> std::io::Result<String> {
+    use std::io::Read;
+    let mut out = String::new();
+    flate2::read::GzDecoder::new(data).read_to_string(&mut out)?;
+    Ok(out)
+}
 
-        // Record in database
-        let size = config.len() as i64;
-        self.store.create_backup(device_id, &filename, size).await?;
+/// Read the config a backup recorded: its compressed object when it has a content hash, else the
+/// plain file written before deduplication
+pub async fn read_backup(backup_dir: &str, backup: &Backup) -> std::io::Result<String> {
+    if backup.content_hash.is_empty() {
+        return tokio::fs::read_to_string(Path::new(backup_dir).join(&backup.filename)).await;
+    }
+    let data = tokio::fs::read(object_path(backup_dir, &backup.content_hash)).await?;
+    gunzip(&data)
+}
 
-        Ok(())
+/// Save a config to the backup directory and record it as a backup of the device taken at
+/// `created_at`. Configs are stored gzip-compressed once per distinct content, so repeated
+/// backups of an unchanged device only add a database row.
+pub async fn store_backup(
+    store: &Store,
+    backup_dir: &str,
+    device: &Device,
+    config: &str,
+    source: &str,
+    description: &str,
+    created_at: DateTime<Utc>,
+) -> Result<Backup> {
+    tokio::fs::create_dir_all(Path::new(backup_dir).join(OBJECTS_SUBDIR)).await?;
+
+    let hash = content_hash(config);
+    let path = object_path(backup_dir, &hash);
+    if !tokio::fs::try_exists(&path).await? {
+        // Write then rename so a concurrent backup of the same config never reads a partial object
+        let tmp = path.with_extension(format!("gz.{}.tmp", device.id));
+        tokio::fs::write(&tmp, gzip(config.as_bytes())?).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+    }
+
+    // The filename names the backup for display and downloads; its content lives in the object
+    let timestamp = created_at.format("%Y%m%d_%H%M%S");
+    let filename = format!("{}_{}.cfg", device.hostname.replace('/', "_"), timestamp);
+
+    store
+        .create_backup(NewBackup {
+            device_id: device.id,
+            filename,
+            size: config.len() as i64,
+            source: source.to_string(),
+            description: description.to_string(),
+            content_hash: hash,
+            created_at,
+        })
+        .await
+}
+
+/// The command that prints a device's running config: its vendor's backup command,
+/// else the global one from settings
+pub(crate) async fn resolve_backup_command(store: &Store, device: &Device, settings: &Settings) -> String {
+    match device.vendor.as_deref() {
+        Some(v) if !v.is_empty() => match store.resolve_vendor(v).await.ok().flatten() {
+            Some(vendor) if !vendor.backup_command.is_empty() => vendor.backup_command,
+            _ => settings.backup_command.clone(),
+        },
+        _ => settings.backup_command.clone(),
+    }
+}
+
+/// Fetch a device's running config over SSH with its backup command, without saving a backup
+pub async fn fetch_running_config(store: &Store, device: &Device) -> Result<String> {
+    let settings = store.get_settings().await?;
+    let crate::utils::ResolvedSshCredentials { user, pass, credential_id } =
+        crate::utils::resolve_ssh_credentials(store, device).await;
+    if let Some(cred_id) = credential_id {
+        if let Err(e) = store.record_credential_usage(cred_id, Some(device.id), "").await {
+            tracing::warn!("Failed to record usage of credential {}: {}", cred_id, e);
+        }
     }
+    let command = resolve_backup_command(store, device, &settings).await;
+    ssh_command(&device.ip, &user, &pass, &command).await
 }
 
 async fn ssh_command(host: &str, user: &str, pass: &str, command: &str) -> Result<String> {
@@ -0,0 +1,230 @@
+//! Admin subcommands that work directly against the database, for recovery and scripting when the
+//! HTTP API is unavailable. Running the binary without a subcommand (or with `serve`) starts the server.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::db::{DeviceStore, RenderStore, Store};
+use crate::jobs::RenderCache;
+use crate::models::*;
+use crate::services::inventory_render;
+
+const USAGE: &str = "\
+Usage: forge-config [COMMAND]
+
+Commands:
+  serve                                   Run the server (default)
+  user list                               List users
+  user reset-password <USERNAME> [--password <PASSWORD>]
+                                          Set a new password and re-enable the account;
+                                          a random password is generated and printed when omitted
+  device list [--json]                    List devices
+  db migrate                              Apply pending database migrations
+  export [--output <FILE>]                Write devices, groups, templates, vendors and topologies as JSON
+  render --device <ID|HOSTNAME|MAC> [--template <ID|NAME>]
+                                          Print a device's rendered config, optionally with another template
+  render-batch --inventory <FILE> [--output <DIR>]
+                                          Render an inventory file without the database; writes
+                                          <hostname>.cfg files to DIR or prints JSON, and fails if any device fails
+  help                                    Show this help
+
+Configuration is read from the same environment variables and CONFIG_FILE as the server.";
+
+/// Inventory written by `export`
+#[derive(Serialize)]
+struct Export {
+    exported_at: chrono::DateTime<chrono::Utc>,
+    devices: Vec<Device>,
+    groups: Vec<Group>,
+    templates: Vec<Template>,
+    vendors: Vec<Vendor>,
+    topologies: Vec<Topology>,
+}
+
+/// Whether the arguments name a subcommand rather than the server
+pub fn is_command(args: &[String]) -> bool {
+    args.first().is_some_and(|a| a != "serve")
+}
+
+/// Value following `name` in the arguments
+fn option(args: &[String], name: &str) -> Result<Option<String>> {
+    match args.iter().position(|a| a == name) {
+        Some(i) => match args.get(i + 1) {
+            Some(value) if !value.starts_with("--") => Ok(Some(value.clone())),
+            _ => bail!("{} requires a value", name),
+        },
+        None => Ok(None),
+    }
+}
+
+pub async fn run(args: Vec<String>) -> Result<()> {
+    let words: Vec<&str> = args.iter().map(String::as_str).collect();
+    if matches!(words.as_slice(), ["help"] | ["--help"] | ["-h"]) {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    // Needs no database
+    if words.first() == Some(&"render-batch") {
+        let inventory = option(&args, "--inventory")?.context("render-batch requires --inventory")?;
+        return render_batch(Path::new(&inventory), option(&args, "--output")?).await;
+    }
+
+    let cfg = Config::load()?;
+    let store = Store::with_pool_size(&cfg.db_path, cfg.db_max_connections).await?;
+
+    match words.as_slice() {
+        ["user", "list"] => list_users(&store).await,
+        ["user", "reset-password", username, ..] if !username.starts_with("--") => {
+            reset_password(&store, username, option(&args, "--password")?).await
+        }
+        ["device", "list", ..] => list_devices(&store, words.contains(&"--json")).await,
+        ["db", "migrate"] => {
+            // Opening the store applied any pending migrations
+            match sqlx::migrate!("./migrations").iter().last() {
+                Some(m) => println!("{} is at migration {} ({})", cfg.db_path, m.version, m.description),
+                None => println!("{} has no migrations", cfg.db_path),
+            }
+            Ok(())
+        }
+        ["export", ..] => export(&store, option(&args, "--output")?).await,
+        ["render", ..] => {
+            let device = option(&args, "--device")?.context("render requires --device")?;
+            render(&store, &device, option(&args, "--template")?).await
+        }
+        _ => bail!("unknown command '{}'\n\n{}", words.join(" "), USAGE),
+    }
+}
+
+async fn list_users(store: &Store) -> Result<()> {
+    println!("{:<6} {:<24} {:<8}", "ID", "USERNAME", "ENABLED");
+    for user in store.list_users().await? {
+        println!("{:<6} {:<24} {:<8}", user.id, user.username, user.enabled);
+    }
+    Ok(())
+}
+
+async fn reset_password(store: &Store, username: &str, password: Option<String>) -> Result<()> {
+    let user = store
+        .get_user_by_username(username)
+        .await?
+        .with_context(|| format!("user '{}' not found", username))?;
+    let generated = password.is_none();
+    let password = password.unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+    if password.is_empty() {
+        bail!("password must not be empty");
+    }
+    store
+        .update_user(user.id, &UpdateUserRequest { username: user.username.clone(), password: Some(password.clone()), enabled: true })
+        .await?;
+    if generated {
+        println!("Password for '{}' reset to: {}", user.username, password);
+    } else {
+        println!("Password for '{}' reset", user.username);
+    }
+    Ok(())
+}
+
+async fn list_devices(store: &Store, json: bool) -> Result<()> {
+    let devices = store.list_devices().await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&devices)?);
+        return Ok(());
+    }
+    println!("{:<6} {:<32} {:<16} {:<18} {:<12} {:<10}", "ID", "HOSTNAME", "IP", "MAC", "VENDOR", "STATUS");
+    for d in devices {
+        println!(
+            "{:<6} {:<32} {:<16} {:<18} {:<12} {:<10}",
+            d.id,
+            d.hostname,
+            d.ip,
+            d.mac.as_deref().unwrap_or("-"),
+            d.vendor.as_deref().unwrap_or("-"),
+            d.status
+        );
+    }
+    Ok(())
+}
+
+async fn export(store: &Store, output: Option<String>) -> Result<()> {
+    let export = Export {
+        exported_at: chrono::Utc::now(),
+        devices: store.list_devices().await?,
+        groups: store.list_groups().await?,
+        templates: store.list_templates().await?,
+        vendors: store.list_vendors().await?,
+        topologies: store.list_topologies().await?,
+    };
+    let json = serde_json::to_string_pretty(&export)?;
+    match output {
+        Some(path) => {
+            tokio::fs::write(&path, json).await.with_context(|| format!("failed to write {}", path))?;
+            eprintln!("Exported {} devices to {}", export.devices.len(), path);
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+async fn find_device<S: DeviceStore + ?Sized>(store: &S, key: &str) -> Result<Device> {
+    if let Ok(id) = key.parse::<i64>() {
+        if let Some(device) = store.get_device(id).await? {
+            return Ok(device);
+        }
+    }
+    if let Some(device) = store.get_device_by_mac(&crate::utils::normalize_mac(key)).await? {
+        return Ok(device);
+    }
+    store
+        .list_devices()
+        .await?
+        .into_iter()
+        .find(|d| d.hostname == key)
+        .with_context(|| format!("device '{}' not found", key))
+}
+
+async fn render<S: DeviceStore + RenderStore + ?Sized>(store: &S, device: &str, template: Option<String>) -> Result<()> {
+    let device = find_device(store, device).await?;
+    let cache = RenderCache::new();
+    let config = match template {
+        Some(key) => {
+            let template = match key.parse::<i64>() {
+                Ok(id) => store.get_template(id).await?,
+                Err(_) => store.get_template_by_name(&key).await?,
+            }
+            .with_context(|| format!("template '{}' not found", key))?;
+            crate::jobs::render_device_with_template(store, &cache, &device, &template).await?
+        }
+        None => crate::jobs::render_device(store, &cache, &device).await?.1,
+    };
+    print!("{}", config);
+    if !config.ends_with('\n') {
+        println!();
+    }
+    Ok(())
+}
+
+async fn render_batch(inventory: &Path, output: Option<String>) -> Result<()> {
+    let inventory = inventory_render::load(inventory).await?;
+    let batch = inventory_render::render(&inventory)?;
+    match output {
+        Some(dir) => {
+            tokio::fs::create_dir_all(&dir).await.with_context(|| format!("failed to create {}", dir))?;
+            for (hostname, config) in &batch.configs {
+                let path = Path::new(&dir).join(format!("{}.cfg", hostname.replace(['/', '\\'], "_")));
+                tokio::fs::write(&path, config).await.with_context(|| format!("failed to write {}", path.display()))?;
+            }
+            eprintln!("Rendered {} configs to {}", batch.configs.len(), dir);
+        }
+        None => println!("{}", serde_json::to_string_pretty(&batch)?),
+    }
+    for (hostname, error) in &batch.errors {
+        eprintln!("{}: {}", hostname, error);
+    }
+    if !batch.errors.is_empty() {
+        bail!("{} of {} devices failed to render", batch.errors.len(), inventory.devices.len());
+    }
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use anyhow::Context;
+use std::collections::HashMap;
 use std::env;
 
 /// Config holds all application configuration
@@ -11,16 +13,75 @@ pub struct Config {
     pub backup_dir: String,
     pub lease_path: String,
     pub dnsmasq_pid: String,
+    /// `host:port` or `unix:/path/to.sock`; ignored under systemd socket activation
     pub listen_addr: String,
     pub dhcp_interface: String,
     pub frontend_dir: String,
     pub jwt_secret: String,
+    /// UDP and TCP address of the syslog receiver; empty disables it
+    pub syslog_listen_addr: String,
+    /// UDP address of the built-in TFTP server; empty leaves TFTP to dnsmasq
+    pub tftp_listen_addr: String,
+    /// HTTPS listen address; empty disables TLS
+    pub tls_listen_addr: String,
+    /// PEM certificate chain and private key served when ACME is not used
+    pub tls_cert_path: String,
+    pub tls_key_path: String,
+    /// Domains to request a certificate for over ACME; empty uses the certificate files
+    pub acme_domains: Vec<String>,
+    pub acme_email: String,
+    pub acme_directory_url: String,
+    /// `http-01` (answered on LISTEN_ADDR, which must be reachable on port 80) or `dns-01`
+    pub acme_challenge: String,
+    /// Command run as `<hook> present|cleanup <record name> <value>` to publish DNS-01 TXT records
+    pub acme_dns_hook: String,
+    /// Where the ACME account key and issued certificate are kept
+    pub acme_dir: String,
+    /// Outgoing mail server for email notification channels; empty disables email
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_username: String,
+    pub smtp_password: String,
+    pub smtp_from: String,
+    /// `starttls`, `tls` (implicit TLS, usually port 465) or `none`
+    pub smtp_tls: String,
+    /// InfluxDB line protocol write URL metrics are pushed to; empty disables the export
+    pub metrics_export_url: String,
+    /// Sent as `Authorization: Token <token>`
+    pub metrics_export_token: String,
+    pub metrics_export_interval_secs: u64,
+    /// SSH operations (commands, deploys, probes) allowed to run at once
+    pub ssh_max_concurrency: usize,
+    /// SSH operations allowed to run at once on one device; the rest queue for it
+    pub ssh_max_per_device: usize,
+    /// How long an authenticated SSH session is kept for reuse; 0 closes sessions after each use
+    pub ssh_pool_idle_secs: u64,
+    /// Consecutive SSH connection failures to a device before its breaker opens; 0 disables it
+    pub ssh_breaker_threshold: u32,
+    /// How long an open breaker makes SSH to its device fail fast
+    pub ssh_breaker_cooldown_secs: u64,
+    /// Jobs run at once; jobs on the same device still run one at a time
+    pub jobs_concurrency: usize,
+    /// Jobs waiting to start before new ones are refused with 429
+    pub jobs_queue_capacity: usize,
+    /// Git repository templates and backups are committed to; empty disables the history
+    pub git_history_dir: String,
+    /// Remote the history is pushed to after each commit; empty keeps it local
+    pub git_history_remote: String,
+    /// Optional KEY=VALUE file read at startup and on reload; environment variables take precedence
+    pub config_file: String,
+    /// tracing filter directives (RUST_LOG)
+    pub log_filter: String,
+    pub status_check_interval_secs: u64,
+    pub discovery_cleanup_interval_secs: u64,
 }
 
 impl Config {
-    /// Load configuration from environment variables with defaults
-    pub fn load() -> Self {
-        Self {
+    /// Load configuration from environment variables and CONFIG_FILE, with defaults
+    pub fn load() -> anyhow::Result<Self> {
+        let source = Source::new(env::var("CONFIG_FILE").unwrap_or_default())?;
+        let get_env = |key: &str, default: &str| source.get(key, default);
+        Ok(Self {
             db_path: get_env("DB_PATH", "/data/forge-config.db"),
             db_max_connections: get_env("DB_MAX_CONNECTIONS", "5")
                 .parse()
@@ -35,10 +96,143 @@ impl Config {
             dhcp_interface: get_env("DHCP_INTERFACE", "eth0"),
             frontend_dir: get_env("FRONTEND_DIR", "/app/frontend"),
             jwt_secret: get_env("JWT_SECRET", ""),
-        }
+            syslog_listen_addr: get_env("SYSLOG_LISTEN_ADDR", "0.0.0.0:514"),
+            tftp_listen_addr: get_env("TFTP_LISTEN_ADDR", "0.0.0.0:69"),
+            tls_listen_addr: get_env("TLS_LISTEN_ADDR", ""),
+            tls_cert_path: get_env("TLS_CERT_PATH", ""),
+            tls_key_path: get_env("TLS_KEY_PATH", ""),
+            acme_domains: get_env("ACME_DOMAINS", "")
+                .split(',')
+                .map(|d| d.trim().to_lowercase())
+                .filter(|d| !d.is_empty())
+                .collect(),
+            acme_email: get_env("ACME_EMAIL", ""),
+            acme_directory_url: get_env("ACME_DIRECTORY_URL", "https://acme-v02.api.letsencrypt.org/directory"),
+            acme_challenge: get_env("ACME_CHALLENGE", "http-01"),
+            acme_dns_hook: get_env("ACME_DNS_HOOK", ""),
+            acme_dir: get_env("ACME_DIR", "/data/acme"),
+            smtp_host: get_env("SMTP_HOST", ""),
+            smtp_port: get_env("SMTP_PORT", "587").parse().unwrap_or(587),
+            smtp_username: get_env("SMTP_USERNAME", ""),
+            smtp_password: get_env("SMTP_PASSWORD", ""),
+            smtp_from: get_env("SMTP_FROM", "forge-config@localhost"),
+            smtp_tls: get_env("SMTP_TLS", "starttls").to_lowercase(),
+            metrics_export_url: get_env("METRICS_EXPORT_URL", ""),
+            metrics_export_token: get_env("METRICS_EXPORT_TOKEN", ""),
+            metrics_export_interval_secs: get_env("METRICS_EXPORT_INTERVAL_SECS", "60")
+                .parse()
+                .unwrap_or(60)
+                .max(1),
+            ssh_max_concurrency: get_env("SSH_MAX_CONCURRENCY", "64")
+                .parse()
+                .unwrap_or(64)
+                .max(1),
+            ssh_max_per_device: get_env("SSH_MAX_PER_DEVICE", "2")
+                .parse()
+                .unwrap_or(2)
+                .max(1),
+            ssh_pool_idle_secs: get_env("SSH_POOL_IDLE_SECS", "60").parse().unwrap_or(60),
+            ssh_breaker_threshold: get_env("SSH_BREAKER_THRESHOLD", "5").parse().unwrap_or(5),
+            ssh_breaker_cooldown_secs: get_env("SSH_BREAKER_COOLDOWN_SECS", "300").parse().unwrap_or(300),
+            jobs_concurrency: get_env("JOBS_CONCURRENCY", "8")
+                .parse()
+                .unwrap_or(8)
+                .max(1),
+            jobs_queue_capacity: get_env("JOBS_QUEUE_CAPACITY", "100")
+                .parse()
+                .unwrap_or(100)
+                .max(1),
+            git_history_dir: get_env("GIT_HISTORY_DIR", ""),
+            git_history_remote: get_env("GIT_HISTORY_REMOTE", ""),
+            log_filter: get_env("RUST_LOG", "forge_config=info,tower_http=debug"),
+            status_check_interval_secs: get_env("STATUS_CHECK_INTERVAL_SECS", "60")
+                .parse()
+                .unwrap_or(60)
+                .max(1),
+            discovery_cleanup_interval_secs: get_env("DISCOVERY_CLEANUP_INTERVAL_SECS", "60")
+                .parse()
+                .unwrap_or(60)
+                .max(1),
+            config_file: source.path,
+        })
     }
+
+    /// Every value by its variable name, and whether it can change without a restart
+    pub fn entries(&self) -> Vec<(&'static str, String, bool)> {
+        vec![
+            ("RUST_LOG", self.log_filter.clone(), true),
+            ("STATUS_CHECK_INTERVAL_SECS", self.status_check_interval_secs.to_string(), true),
+            ("DISCOVERY_CLEANUP_INTERVAL_SECS", self.discovery_cleanup_interval_secs.to_string(), true),
+            ("DB_PATH", self.db_path.clone(), false),
+            ("DB_MAX_CONNECTIONS", self.db_max_connections.to_string(), false),
+            ("DNSMASQ_CONFIG", self.dnsmasq_config.clone(), false),
+            ("TFTP_DIR", self.tftp_dir.clone(), false),
+            ("TEMPLATES_DIR", self.templates_dir.clone(), false),
+            ("BACKUP_DIR", self.backup_dir.clone(), false),
+            ("LEASE_PATH", self.lease_path.clone(), false),
+            ("DNSMASQ_PID", self.dnsmasq_pid.clone(), false),
+            ("LISTEN_ADDR", self.listen_addr.clone(), false),
+            ("DHCP_INTERFACE", self.dhcp_interface.clone(), false),
+            ("FRONTEND_DIR", self.frontend_dir.clone(), false),
+            ("JWT_SECRET", self.jwt_secret.clone(), false),
+            ("SYSLOG_LISTEN_ADDR", self.syslog_listen_addr.clone(), false),
+            ("TFTP_LISTEN_ADDR", self.tftp_listen_addr.clone(), false),
+            ("TLS_LISTEN_ADDR", self.tls_listen_addr.clone(), false),
+            ("TLS_CERT_PATH", self.tls_cert_path.clone(), false),
+            ("TLS_KEY_PATH", self.tls_key_path.clone(), false),
+            ("ACME_DOMAINS", self.acme_domains.join(","), false),
+            ("ACME_EMAIL", self.acme_email.clone(), false),
+            ("ACME_DIRECTORY_URL", self.acme_directory_url.clone(), false),
+            ("ACME_CHALLENGE", self.acme_challenge.clone(), false),
+            ("ACME_DNS_HOOK", self.acme_dns_hook.clone(), false),
+            ("ACME_DIR", self.acme_dir.clone(), false),
+            ("SMTP_HOST", self.smtp_host.clone(), false),
+            ("SMTP_PORT", self.smtp_port.to_string(), false),
+            ("SMTP_USERNAME", self.smtp_username.clone(), false),
+            ("SMTP_PASSWORD", self.smtp_password.clone(), false),
+            ("SMTP_FROM", self.smtp_from.clone(), false),
+            ("SMTP_TLS", self.smtp_tls.clone(), false),
+            ("METRICS_EXPORT_URL", self.metrics_export_url.clone(), false),
+            ("METRICS_EXPORT_TOKEN", self.metrics_export_token.clone(), false),
+            ("METRICS_EXPORT_INTERVAL_SECS", self.metrics_export_interval_secs.to_string(), false),
+            ("SSH_MAX_CONCURRENCY", self.ssh_max_concurrency.to_string(), false),
+            ("SSH_MAX_PER_DEVICE", self.ssh_max_per_device.to_string(), false),
+            ("SSH_POOL_IDLE_SECS", self.ssh_pool_idle_secs.to_string(), false),
+            ("SSH_BREAKER_THRESHOLD", self.ssh_breaker_threshold.to_string(), false),
+            ("SSH_BREAKER_COOLDOWN_SECS", self.ssh_breaker_cooldown_secs.to_string(), false),
+            ("JOBS_CONCURRENCY", self.jobs_concurrency.to_string(), false),
+            ("JOBS_QUEUE_CAPACITY", self.jobs_queue_capacity.to_string(), false),
+            ("GIT_HISTORY_DIR", self.git_history_dir.clone(), false),
+            ("GIT_HISTORY_REMOTE", self.git_history_remote.clone(), false),
+        ]
+    }
+}
+
+/// Values that are never echoed back
+pub const SECRET_KEYS: &[&str] = &["JWT_SECRET", "SMTP_PASSWORD", "METRICS_EXPORT_TOKEN", "GIT_HISTORY_REMOTE"];
+
+/// Environment variables, falling back to the config file
+struct Source {
+    path: String,
+    file: HashMap<String, String>,
 }
 
-fn get_env(key: &str, default: &str) -> String {
-    env::var(key).unwrap_or_else(|_| default.to_string())
+impl Source {
+    fn new(path: String) -> anyhow::Result<Self> {
+        let mut file = HashMap::new();
+        if !path.is_empty() {
+            for item in dotenvy::from_path_iter(&path).with_context(|| format!("failed to read CONFIG_FILE {}", path))? {
+                let (key, value) = item.with_context(|| format!("invalid line in {}", path))?;
+                file.insert(key, value);
+            }
+        }
+        Ok(Self { path, file })
+    }
+
+    fn get(&self, key: &str, default: &str) -> String {
+        env::var(key)
+            .ok()
+            .or_else(|| self.file.get(key).cloned())
+            .unwrap_or_else(|| default.to_string())
+    }
 }
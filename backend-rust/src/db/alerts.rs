@@ -0,0 +1,326 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_alert_rule_row(row: &SqliteRow) -> AlertRule {
+    let channel_ids: String = row.get("channel_ids");
+    AlertRule {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        metric: row.get("metric"),
+        threshold: row.get("threshold"),
+        for_minutes: row.get("for_minutes"),
+        channel_ids: serde_json::from_str(&channel_ids).unwrap_or_default(),
+        enabled: row.get("enabled"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn map_alert_row(row: &SqliteRow) -> Alert {
+    Alert {
+        id: row.get("id"),
+        dedupe_key: row.get("dedupe_key"),
+        rule_id: row.get("rule_id"),
+        rule_name: row.get("rule_name"),
+        metric: row.get("metric"),
+        threshold: row.get("threshold"),
+        subject_type: row.get("subject_type"),
+        subject_id: row.get("subject_id"),
+        subject_name: row.get("subject_name"),
+        value: row.get("value"),
+        status: row.get("status"),
+        started_at: row.get("started_at"),
+        fired_at: row.get("fired_at"),
+        acknowledged_by: row.get("acknowledged_by"),
+        acknowledged_at: row.get("acknowledged_at"),
+        assigned_to: row.get("assigned_to"),
+        resolved_at: row.get("resolved_at"),
+        resolved_by: row.get("resolved_by"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+const SELECT_ALERT: &str = r#"
+    SELECT a.*, r.name AS rule_name, r.metric, r.threshold
+    FROM alerts a
+    JOIN alert_rules r ON r.id = a.rule_id
+"#;
+
+/// Metric alert rule and alert database operations
+pub struct AlertRepo;
+
+impl AlertRepo {
+    pub async fn list_rules(pool: &Pool<Sqlite>) -> Result<Vec<AlertRule>> {
+        let rows = sqlx::query("SELECT * FROM alert_rules ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_alert_rule_row).collect())
+    }
+
+    pub async fn list_enabled_rules(pool: &Pool<Sqlite>) -> Result<Vec<AlertRule>> {
+        let rows = sqlx::query("SELECT * FROM alert_rules WHERE enabled = 1 ORDER BY id")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_alert_rule_row).collect())
+    }
+
+    pub async fn get_rule(pool: &Pool<Sqlite>, id: i64) -> Result<Option<AlertRule>> {
+        let row = sqlx::query("SELECT * FROM alert_rules WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_alert_rule_row))
+    }
+
+    pub async fn create_rule(pool: &Pool<Sqlite>, req: &CreateAlertRuleRequest) -> Result<AlertRule> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO alert_rules (name, description, metric, threshold, for_minutes, channel_ids, enabled, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&req.metric)
+        .bind(req.threshold)
+        .bind(req.for_minutes)
+        .bind(serde_json::to_string(&req.channel_ids)?)
+        .bind(req.enabled)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get_rule(pool, result.last_insert_rowid())
+            .await?
+            .context("Alert rule not found after creation")
+    }
+
+    /// Update a rule. Changing its metric resolves the rule's open alerts, since they measured
+    /// something else.
+    pub async fn update_rule(pool: &Pool<Sqlite>, id: i64, req: &CreateAlertRuleRequest) -> Result<AlertRule> {
+        let now = Utc::now();
+        let mut tx = pool.begin().await?;
+        let result = sqlx::query(
+            r#"
+            UPDATE alert_rules SET name = ?, description = ?, metric = ?, threshold = ?, for_minutes = ?,
+                channel_ids = ?, enabled = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&req.metric)
+        .bind(req.threshold)
+        .bind(req.for_minutes)
+        .bind(serde_json::to_string(&req.channel_ids)?)
+        .bind(req.enabled)
+        .bind(now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Alert rule", &id.to_string()).into());
+        }
+        sqlx::query(
+            r#"
+            UPDATE alerts SET status = ?, resolved_at = ?, updated_at = ?
+            WHERE rule_id = ? AND status != ? AND subject_type != ?
+            "#,
+        )
+        .bind(alert_status::RESOLVED)
+        .bind(now)
+        .bind(now)
+        .bind(id)
+        .bind(alert_status::RESOLVED)
+        .bind(alert_metric::subject_type(&req.metric))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Self::get_rule(pool, id)
+            .await?
+            .context("Alert rule not found after update")
+    }
+
+    pub async fn delete_rule(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM alert_rules WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Alert rule", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    pub async fn get_alert(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Alert>> {
+        let row = sqlx::query(&format!("{} WHERE a.id = ?", SELECT_ALERT))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_alert_row))
+    }
+
+    /// Alerts newest first; without a status filter, only unresolved ones
+    pub async fn list_alerts(pool: &Pool<Sqlite>, query: &AlertQuery) -> Result<Vec<Alert>> {
+        let rows = sqlx::query(&format!(
+            r#"{} WHERE (? IS NULL OR a.rule_id = ?)
+                 AND (? IS NULL OR a.assigned_to = ?)
+                 AND (CASE WHEN ? IS NULL THEN a.status != ? ELSE a.status = ? END)
+               ORDER BY a.started_at DESC, a.id DESC LIMIT ?"#,
+            SELECT_ALERT
+        ))
+        .bind(query.rule_id)
+        .bind(query.rule_id)
+        .bind(query.assigned_to.as_deref())
+        .bind(query.assigned_to.as_deref())
+        .bind(query.status.as_deref())
+        .bind(alert_status::RESOLVED)
+        .bind(query.status.as_deref())
+        .bind(query.limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_alert_row).collect())
+    }
+
+    /// A rule's unresolved alerts
+    pub async fn list_open_alerts(pool: &Pool<Sqlite>, rule_id: i64) -> Result<Vec<Alert>> {
+        let rows = sqlx::query(&format!("{} WHERE a.rule_id = ? AND a.status != ? ORDER BY a.id", SELECT_ALERT))
+            .bind(rule_id)
+            .bind(alert_status::RESOLVED)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_alert_row).collect())
+    }
+
+    /// Open an alert for a subject that just exceeded the rule's threshold
+    pub async fn open_alert(
+        pool: &Pool<Sqlite>,
+        rule: &AlertRule,
+        subject_id: i64,
+        subject_name: &str,
+        value: f64,
+        status: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Alert> {
+        let fired_at = (status == alert_status::FIRING).then_some(now);
+        let subject_type = alert_metric::subject_type(&rule.metric);
+        let result = sqlx::query(
+            r#"
+            INSERT INTO alerts (dedupe_key, rule_id, subject_type, subject_id, subject_name, value, status, started_at, fired_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(alert_dedupe_key(rule.id, subject_type, subject_id))
+        .bind(rule.id)
+        .bind(subject_type)
+        .bind(subject_id)
+        .bind(subject_name)
+        .bind(value)
+        .bind(status)
+        .bind(now)
+        .bind(fired_at)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get_alert(pool, result.last_insert_rowid())
+            .await?
+            .context("Alert not found after creation")
+    }
+
+    /// Record the latest value of an open alert, and its status when it changes
+    pub async fn update_alert(pool: &Pool<Sqlite>, id: i64, value: f64, status: &str, now: DateTime<Utc>) -> Result<()> {
+        let resolved_at = (status == alert_status::RESOLVED).then_some(now);
+        sqlx::query(
+            r#"
+            UPDATE alerts SET value = ?, updated_at = ?, resolved_at = COALESCE(?, resolved_at),
+                fired_at = CASE WHEN ? = ? AND fired_at IS NULL THEN ? ELSE fired_at END,
+                status = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(value)
+        .bind(now)
+        .bind(resolved_at)
+        .bind(status)
+        .bind(alert_status::FIRING)
+        .bind(now)
+        .bind(status)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Acknowledge a pending or firing alert. Returns None when the alert isn't in one of those states.
+    pub async fn acknowledge_alert(pool: &Pool<Sqlite>, id: i64, username: &str) -> Result<Option<Alert>> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            UPDATE alerts SET status = ?, acknowledged_by = ?, acknowledged_at = ?, updated_at = ?
+            WHERE id = ? AND status IN (?, ?)
+            "#,
+        )
+        .bind(alert_status::ACKNOWLEDGED)
+        .bind(username)
+        .bind(now)
+        .bind(now)
+        .bind(id)
+        .bind(alert_status::PENDING)
+        .bind(alert_status::FIRING)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        Self::get_alert(pool, id).await
+    }
+
+    /// Assign an alert to a user, or unassign it with an empty username
+    pub async fn assign_alert(pool: &Pool<Sqlite>, id: i64, username: &str) -> Result<Alert> {
+        let result = sqlx::query("UPDATE alerts SET assigned_to = ?, updated_at = ? WHERE id = ?")
+            .bind(username)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Alert", &id.to_string()).into());
+        }
+        Self::get_alert(pool, id)
+            .await?
+            .context("Alert not found after update")
+    }
+
+    /// Resolve an unresolved alert by hand. Returns None when it was already resolved.
+    pub async fn resolve_alert(pool: &Pool<Sqlite>, id: i64, username: &str) -> Result<Option<Alert>> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            "UPDATE alerts SET status = ?, resolved_by = ?, resolved_at = ?, updated_at = ? WHERE id = ? AND status != ?",
+        )
+        .bind(alert_status::RESOLVED)
+        .bind(username)
+        .bind(now)
+        .bind(now)
+        .bind(id)
+        .bind(alert_status::RESOLVED)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        Self::get_alert(pool, id).await
+    }
+}
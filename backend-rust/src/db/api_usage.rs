@@ -0,0 +1,142 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{sqlite::SqliteRow, Pool, Row, Sqlite};
+
+use crate::models::*;
+
+const SUMS: &str = "SUM(requests) AS requests, SUM(errors) AS errors, \
+                    SUM(request_bytes) AS request_bytes, SUM(response_bytes) AS response_bytes";
+
+fn map_totals(row: &SqliteRow) -> ApiUsageTotals {
+    ApiUsageTotals {
+        requests: row.get("requests"),
+        errors: row.get("errors"),
+        request_bytes: row.get("request_bytes"),
+        response_bytes: row.get("response_bytes"),
+    }
+}
+
+/// Run a report query filtered on `hour >= since` and, when given, the username
+async fn fetch(pool: &Pool<Sqlite>, sql: &str, since: DateTime<Utc>, username: Option<&str>) -> Result<Vec<SqliteRow>> {
+    let mut query = sqlx::query(sql).bind(since);
+    if let Some(username) = username {
+        query = query.bind(username);
+    }
+    Ok(query.fetch_all(pool).await?)
+}
+
+/// Hourly API usage counts
+pub struct ApiUsageRepo;
+
+impl ApiUsageRepo {
+    /// Add counts to their hours' rows in one transaction
+    pub async fn add(pool: &Pool<Sqlite>, entries: &[ApiUsageEntry]) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        for entry in entries {
+            sqlx::query(
+                r#"
+                INSERT INTO api_usage (hour, username, token_id, token_issued_at, resource, access,
+                                       requests, errors, request_bytes, response_bytes)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(hour, username, token_id, resource, access) DO UPDATE SET
+                    requests = requests + excluded.requests,
+                    errors = errors + excluded.errors,
+                    request_bytes = request_bytes + excluded.request_bytes,
+                    response_bytes = response_bytes + excluded.response_bytes
+                "#,
+            )
+            .bind(entry.hour)
+            .bind(&entry.username)
+            .bind(&entry.token_id)
+            .bind(entry.token_issued_at)
+            .bind(&entry.resource)
+            .bind(&entry.access)
+            .bind(entry.totals.requests)
+            .bind(entry.totals.errors)
+            .bind(entry.totals.request_bytes)
+            .bind(entry.totals.response_bytes)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Delete the hours before `before`, returning the rows removed
+    pub async fn prune(pool: &Pool<Sqlite>, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM api_usage WHERE hour < ?")
+            .bind(before)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Usage per user from the hour starting `since`, with each user's tokens and endpoint
+    /// classes, busiest first
+    pub async fn report(pool: &Pool<Sqlite>, since: DateTime<Utc>, username: Option<&str>) -> Result<Vec<ApiConsumerUsage>> {
+        let filter = if username.is_some() { "hour >= ? AND username = ?" } else { "hour >= ?" };
+
+        let users = fetch(
+            pool,
+            &format!("SELECT username, {} FROM api_usage WHERE {} GROUP BY username ORDER BY requests DESC, username", SUMS, filter),
+            since,
+            username,
+        )
+        .await?;
+        let mut consumers: Vec<ApiConsumerUsage> = users
+            .iter()
+            .map(|row| ApiConsumerUsage {
+                username: row.get("username"),
+                totals: map_totals(row),
+                tokens: Vec::new(),
+                endpoints: Vec::new(),
+            })
+            .collect();
+
+        let tokens = fetch(
+            pool,
+            &format!(
+                "SELECT username, token_id, MIN(token_issued_at) AS issued_at, MAX(hour) AS last_used_hour, {} \
+                 FROM api_usage WHERE {} GROUP BY username, token_id ORDER BY requests DESC, token_id",
+                SUMS, filter
+            ),
+            since,
+            username,
+        )
+        .await?;
+        for row in &tokens {
+            let username: String = row.get("username");
+            if let Some(consumer) = consumers.iter_mut().find(|c| c.username == username) {
+                consumer.tokens.push(ApiTokenUsage {
+                    token_id: row.get("token_id"),
+                    issued_at: row.get("issued_at"),
+                    last_used_hour: row.get("last_used_hour"),
+                    totals: map_totals(row),
+                });
+            }
+        }
+
+        let endpoints = fetch(
+            pool,
+            &format!(
+                "SELECT username, resource, access, {} FROM api_usage WHERE {} \
+                 GROUP BY username, resource, access ORDER BY requests DESC, resource, access",
+                SUMS, filter
+            ),
+            since,
+            username,
+        )
+        .await?;
+        for row in &endpoints {
+            let username: String = row.get("username");
+            if let Some(consumer) = consumers.iter_mut().find(|c| c.username == username) {
+                consumer.endpoints.push(ApiEndpointUsage {
+                    resource: row.get("resource"),
+                    access: row.get("access"),
+                    totals: map_totals(row),
+                });
+            }
+        }
+        Ok(consumers)
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+/// Executions kept per rule; older ones are dropped as new ones are recorded
+const EXECUTIONS_KEPT: i64 = 200;
+
+fn map_automation_rule_row(row: &SqliteRow) -> AutomationRule {
+    let filter: String = row.get("filter");
+    let action: String = row.get("action");
+    AutomationRule {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        event_type: row.get("event_type"),
+        filter: serde_json::from_str(&filter).unwrap_or_default(),
+        action_type: row.get("action_type"),
+        action: serde_json::from_str(&action).unwrap_or_default(),
+        enabled: row.get("enabled"),
+        last_triggered_at: row.get("last_triggered_at"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn map_automation_execution_row(row: &SqliteRow) -> AutomationExecution {
+    let payload: String = row.get("event_payload");
+    AutomationExecution {
+        id: row.get("id"),
+        rule_id: row.get("rule_id"),
+        event_type: row.get("event_type"),
+        event_payload: serde_json::from_str(&payload).unwrap_or_default(),
+        status: row.get("status"),
+        output: row.get("output"),
+        error: row.get("error"),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// Automation rule database operations
+pub struct AutomationRepo;
+
+impl AutomationRepo {
+    pub async fn list_rules(pool: &Pool<Sqlite>) -> Result<Vec<AutomationRule>> {
+        let rows = sqlx::query("SELECT * FROM automation_rules ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_automation_rule_row).collect())
+    }
+
+    /// Enabled rules that trigger on `event_type`
+    pub async fn list_rules_for_event(pool: &Pool<Sqlite>, event_type: &str) -> Result<Vec<AutomationRule>> {
+        let rows = sqlx::query(
+            "SELECT * FROM automation_rules WHERE enabled = 1 AND (event_type = ? OR event_type = ?) ORDER BY id",
+        )
+        .bind(event_type)
+        .bind(automation_event::ANY)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_automation_rule_row).collect())
+    }
+
+    pub async fn get_rule(pool: &Pool<Sqlite>, id: i64) -> Result<Option<AutomationRule>> {
+        let row = sqlx::query("SELECT * FROM automation_rules WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_automation_rule_row))
+    }
+
+    pub async fn create_rule(pool: &Pool<Sqlite>, req: &CreateAutomationRuleRequest) -> Result<AutomationRule> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO automation_rules (name, description, event_type, filter, action_type, action, enabled, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&req.event_type)
+        .bind(serde_json::to_string(&req.filter)?)
+        .bind(&req.action_type)
+        .bind(req.action.to_string())
+        .bind(req.enabled)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get_rule(pool, result.last_insert_rowid())
+            .await?
+            .context("Automation rule not found after creation")
+    }
+
+    pub async fn update_rule(pool: &Pool<Sqlite>, id: i64, req: &CreateAutomationRuleRequest) -> Result<AutomationRule> {
+        let result = sqlx::query(
+            r#"
+            UPDATE automation_rules SET name = ?, description = ?, event_type = ?, filter = ?, action_type = ?,
+                action = ?, enabled = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&req.event_type)
+        .bind(serde_json::to_string(&req.filter)?)
+        .bind(&req.action_type)
+        .bind(req.action.to_string())
+        .bind(req.enabled)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Automation rule", &id.to_string()).into());
+        }
+        Self::get_rule(pool, id)
+            .await?
+            .context("Automation rule not found after update")
+    }
+
+    pub async fn delete_rule(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM automation_rules WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Automation rule", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    pub async fn record_execution(
+        pool: &Pool<Sqlite>,
+        rule_id: i64,
+        event_type: &str,
+        payload: &serde_json::Value,
+        result: &Result<String, String>,
+    ) -> Result<()> {
+        let now = Utc::now();
+        let (status, output, error) = match result {
+            Ok(output) => (automation_execution_status::SUCCESS, output.as_str(), None),
+            Err(error) => (automation_execution_status::FAILED, "", Some(error.as_str())),
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO automation_rule_executions (rule_id, event_type, event_payload, status, output, error, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(rule_id)
+        .bind(event_type)
+        .bind(payload.to_string())
+        .bind(status)
+        .bind(output)
+        .bind(error)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        sqlx::query("UPDATE automation_rules SET last_triggered_at = ? WHERE id = ?")
+            .bind(now)
+            .bind(rule_id)
+            .execute(pool)
+            .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM automation_rule_executions WHERE rule_id = ? AND id NOT IN (
+                SELECT id FROM automation_rule_executions WHERE rule_id = ? ORDER BY id DESC LIMIT ?
+            )
+            "#,
+        )
+        .bind(rule_id)
+        .bind(rule_id)
+        .bind(EXECUTIONS_KEPT)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_executions(pool: &Pool<Sqlite>, rule_id: i64, limit: i64) -> Result<Vec<AutomationExecution>> {
+        let rows = sqlx::query(
+            "SELECT * FROM automation_rule_executions WHERE rule_id = ? ORDER BY id DESC LIMIT ?",
+        )
+        .bind(rule_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_automation_execution_row).collect())
+    }
+}
@@ -0,0 +1,121 @@
+//! In-process read-through cache for lookups made on nearly every job and render: the vendor
+//! list, settings, templates and the group hierarchy. The Store's write methods invalidate the
+//! entries they affect, so a read never returns data older than the last write made through it.
+
+use anyhow::Result;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::models::*;
+
+/// One cached value with its hit/miss counters
+pub(crate) struct CacheSlot<T> {
+    name: &'static str,
+    value: RwLock<Option<Arc<T>>>,
+    /// Bumped on every invalidation, so a load that raced with a write doesn't store what it
+    /// read before the write
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<T> CacheSlot<T> {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            value: RwLock::new(None),
+            generation: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The cached value, or the result of `load` (cached for next time)
+    pub(crate) async fn get_or_load<F, Fut>(&self, load: F) -> Result<Arc<T>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let cached = self.read().clone();
+        if let Some(value) = cached {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let generation = self.generation.load(Ordering::Acquire);
+        let value = Arc::new(load().await?);
+        let mut slot = self.write();
+        if self.generation.load(Ordering::Acquire) == generation {
+            *slot = Some(value.clone());
+        }
+        Ok(value)
+    }
+
+    pub(crate) fn invalidate(&self) {
+        let mut slot = self.write();
+        self.generation.fetch_add(1, Ordering::Release);
+        *slot = None;
+    }
+
+    fn stats(&self) -> LookupCacheStats {
+        LookupCacheStats {
+            name: self.name.to_string(),
+            cached: self.read().is_some(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    // A panic while holding the lock can't leave the slot inconsistent, so recover from poisoning
+    fn read(&self) -> RwLockReadGuard<'_, Option<Arc<T>>> {
+        self.value.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Option<Arc<T>>> {
+        self.value.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The cached lookups. Vendor and template rows carry device counts, so device writes
+/// invalidate them too.
+pub(crate) struct LookupCache {
+    pub(crate) vendors: CacheSlot<Vec<Vendor>>,
+    pub(crate) templates: CacheSlot<Vec<Template>>,
+    pub(crate) settings: CacheSlot<Settings>,
+    /// Every group without counts, as used to walk ancestors during variable resolution
+    pub(crate) groups: CacheSlot<Vec<Group>>,
+}
+
+impl LookupCache {
+    pub(crate) fn new() -> Self {
+        Self {
+            vendors: CacheSlot::new("vendors"),
+            templates: CacheSlot::new("templates"),
+            settings: CacheSlot::new("settings"),
+            groups: CacheSlot::new("groups"),
+        }
+    }
+
+    /// Device counts on vendor and template rows change with any device write
+    pub(crate) fn invalidate_device_counts(&self) {
+        self.vendors.invalidate();
+        self.templates.invalidate();
+    }
+
+    pub(crate) fn clear(&self) {
+        self.invalidate_device_counts();
+        self.settings.invalidate();
+        self.groups.invalidate();
+    }
+
+    pub(crate) fn stats(&self) -> Vec<LookupCacheStats> {
+        vec![
+            self.vendors.stats(),
+            self.templates.stats(),
+            self.settings.stats(),
+            self.groups.stats(),
+        ]
+    }
+}
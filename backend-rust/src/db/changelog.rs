@@ -0,0 +1,117 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_change_log_row(row: &SqliteRow) -> ChangeLogEntry {
+    ChangeLogEntry {
+        id: row.get("id"),
+        category: row.get("category"),
+        action: row.get("action"),
+        resource_type: row.get("resource_type"),
+        resource_id: row.get("resource_id"),
+        resource_name: row.get("resource_name"),
+        device_id: row.get("device_id"),
+        hostname: row.get("hostname"),
+        group_id: row.get("group_id"),
+        group_name: row.get("group_name"),
+        username: row.get("username"),
+        summary: row.get("summary"),
+        job_status: row.get("job_status"),
+        created_at: row.get("created_at"),
+    }
+}
+
+const SELECT_CHANGE_LOG: &str = r#"
+    SELECT c.id, c.category, c.action, c.resource_type, c.resource_id, c.resource_name,
+           c.device_id, d.hostname, c.group_id, g.name AS group_name, c.username, c.summary,
+           j.status AS job_status, c.created_at
+    FROM change_log c
+    LEFT JOIN devices d ON d.id = c.device_id
+    LEFT JOIN groups g ON g.id = c.group_id
+    LEFT JOIN jobs_all j ON c.resource_type = 'job' AND j.id = c.resource_id
+"#;
+
+pub struct ChangeLogRepo;
+
+impl ChangeLogRepo {
+    pub async fn record(pool: &Pool<Sqlite>, entry: &NewChangeLogEntry, username: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO change_log (category, action, resource_type, resource_id, resource_name,
+                                    device_id, group_id, username, summary, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(entry.category)
+        .bind(entry.action)
+        .bind(entry.resource_type)
+        .bind(&entry.resource_id)
+        .bind(&entry.resource_name)
+        .bind(entry.device_id)
+        .bind(entry.group_id)
+        .bind(username)
+        .bind(&entry.summary)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Entries matching the query, newest first
+    pub async fn list(pool: &Pool<Sqlite>, query: &ChangeLogQuery) -> Result<Vec<ChangeLogEntry>> {
+        enum Arg<'a> {
+            Text(&'a str),
+            Int(i64),
+            Time(DateTime<Utc>),
+        }
+
+        let mut clauses: Vec<&str> = Vec::new();
+        let mut args: Vec<Arg> = Vec::new();
+
+        if let Some(device_id) = query.device_id {
+            clauses.push("c.device_id = ?");
+            args.push(Arg::Int(device_id));
+        }
+        if let Some(group_id) = query.group_id {
+            clauses.push("(c.group_id = ? OR c.device_id IN (SELECT m.device_id FROM device_group_members m WHERE m.group_id = ?))");
+            args.push(Arg::Int(group_id));
+            args.push(Arg::Int(group_id));
+        }
+        if let Some(ref user) = query.user {
+            clauses.push("c.username = ?");
+            args.push(Arg::Text(user));
+        }
+        if let Some(ref category) = query.category {
+            clauses.push("c.category = ?");
+            args.push(Arg::Text(category));
+        }
+        if let Some(since) = query.since {
+            clauses.push("c.created_at >= ?");
+            args.push(Arg::Time(since));
+        }
+        if let Some(until) = query.until {
+            clauses.push("c.created_at < ?");
+            args.push(Arg::Time(until));
+        }
+
+        let where_sql = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+        let sql = format!("{} {} ORDER BY c.created_at DESC, c.id DESC LIMIT ?", SELECT_CHANGE_LOG, where_sql);
+
+        let mut q = sqlx::query(&sql);
+        for arg in args {
+            q = match arg {
+                Arg::Text(s) => q.bind(s),
+                Arg::Int(i) => q.bind(i),
+                Arg::Time(t) => q.bind(t),
+            };
+        }
+        let rows = q.bind(query.limit).fetch_all(pool).await?;
+        Ok(rows.iter().map(map_change_log_row).collect())
+    }
+}
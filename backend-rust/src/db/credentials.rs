@@ -1,5 +1,5 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
 
 use crate::models::*;
@@ -15,11 +15,54 @@ fn map_credential_row(row: &SqliteRow) -> Credential {
         cred_type: row.get("cred_type"),
         username: row.get("username"),
         password: row.get("password"),
+        previous_password: row.try_get("previous_password").unwrap_or_default(),
+        last_used_at: row.try_get("last_used_at").ok().flatten(),
+        rotated_at: row.try_get("rotated_at").ok().flatten(),
         created_at: row.get("created_at"),
         updated_at: row.get("updated_at"),
     }
 }
 
+fn map_usage_row(row: &SqliteRow) -> CredentialUsage {
+    CredentialUsage {
+        id: row.get("id"),
+        credential_id: row.get("credential_id"),
+        device_id: row.get("device_id"),
+        device_hostname: row.try_get("device_hostname").ok().flatten(),
+        job_id: row.get("job_id"),
+        used_at: row.get("used_at"),
+    }
+}
+
+/// Staleness warnings for a credential: not rotated (or created) within max_age_days,
+/// never used, or not used within max_age_days
+fn credential_warnings(
+    created_at: DateTime<Utc>,
+    rotated_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    max_age_days: i64,
+) -> Vec<String> {
+    let now = Utc::now();
+    let mut warnings = Vec::new();
+    if max_age_days <= 0 {
+        return warnings;
+    }
+
+    let age = (now - rotated_at.unwrap_or(created_at)).num_days();
+    if age > max_age_days {
+        let since = if rotated_at.is_some() { "rotated" } else { "created" };
+        warnings.push(format!("password not rotated in {} days (last {} {})", age, since, rotated_at.unwrap_or(created_at).format("%Y-%m-%d")));
+    }
+    match last_used_at {
+        None => warnings.push("never used by a job".to_string()),
+        Some(used) if (now - used).num_days() > max_age_days => {
+            warnings.push(format!("not used in {} days", (now - used).num_days()));
+        }
+        Some(_) => {}
+    }
+    warnings
+}
+
 // ========== Credential Repo ==========
 
 pub struct CredentialRepo;
@@ -81,4 +124,86 @@ impl CredentialRepo {
         }
         Ok(())
     }
+
+    /// Record that a job authenticated with the credential
+    pub async fn record_usage(pool: &Pool<Sqlite>, id: i64, device_id: Option<i64>, job_id: &str) -> Result<()> {
+        let now = Utc::now();
+        sqlx::query("INSERT INTO credential_usage (credential_id, device_id, job_id, used_at) VALUES (?, ?, ?, ?)")
+            .bind(id).bind(device_id).bind(job_id).bind(now)
+            .execute(pool).await?;
+        sqlx::query("UPDATE credentials SET last_used_at = ? WHERE id = ?")
+            .bind(now).bind(id)
+            .execute(pool).await?;
+        Ok(())
+    }
+
+    pub async fn list_usage(pool: &Pool<Sqlite>, id: i64, limit: i64) -> Result<Vec<CredentialUsage>> {
+        let rows = sqlx::query(
+            r#"SELECT u.*, d.hostname as device_hostname
+               FROM credential_usage u
+               LEFT JOIN devices d ON u.device_id = d.id
+               WHERE u.credential_id = ?
+               ORDER BY u.used_at DESC, u.id DESC
+               LIMIT ?"#
+        )
+        .bind(id).bind(limit)
+        .fetch_all(pool).await?;
+        Ok(rows.iter().map(map_usage_row).collect())
+    }
+
+    /// Devices bound to the credential or that have used it
+    pub async fn used_device_ids(pool: &Pool<Sqlite>, id: i64) -> Result<Vec<i64>> {
+        let rows = sqlx::query(
+            r#"SELECT device_id FROM credential_usage WHERE credential_id = ? AND device_id IS NOT NULL
+               UNION
+               SELECT id AS device_id FROM devices WHERE credential_id = ?
+               ORDER BY device_id"#
+        )
+        .bind(id).bind(id).fetch_all(pool).await?;
+        Ok(rows.iter().map(|r| r.get("device_id")).collect())
+    }
+
+    /// Usage counts and staleness warnings for one credential, or all when id is None
+    pub async fn audit(pool: &Pool<Sqlite>, id: Option<i64>, max_age_days: i64) -> Result<Vec<CredentialAudit>> {
+        let rows = sqlx::query(
+            r#"SELECT c.id, c.name, c.created_at, c.last_used_at, c.rotated_at,
+                      COUNT(DISTINCT u.device_id) as device_count,
+                      COUNT(DISTINCT NULLIF(u.job_id, '')) as job_count
+               FROM credentials c
+               LEFT JOIN credential_usage u ON u.credential_id = c.id
+               WHERE (? IS NULL OR c.id = ?)
+               GROUP BY c.id
+               ORDER BY c.name"#
+        )
+        .bind(id).bind(id)
+        .fetch_all(pool).await?;
+
+        Ok(rows.iter().map(|row| {
+            let last_used_at: Option<DateTime<Utc>> = row.get("last_used_at");
+            let rotated_at: Option<DateTime<Utc>> = row.get("rotated_at");
+            CredentialAudit {
+                credential_id: row.get("id"),
+                name: row.get("name"),
+                warnings: credential_warnings(row.get("created_at"), rotated_at, last_used_at, max_age_days),
+                last_used_at,
+                rotated_at,
+                device_count: row.get("device_count"),
+                job_count: row.get("job_count"),
+            }
+        }).collect())
+    }
+
+    /// Replace the password, keeping the old one for push jobs that still need to log in with it
+    pub async fn rotate(pool: &Pool<Sqlite>, id: i64, password: &str) -> Result<Credential> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            "UPDATE credentials SET previous_password = password, password = ?, rotated_at = ?, updated_at = ? WHERE id = ?"
+        )
+        .bind(password).bind(now).bind(now).bind(id)
+        .execute(pool).await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Credential", &id.to_string()).into());
+        }
+        Self::get(pool, id).await?.context("Credential not found after rotation")
+    }
 }
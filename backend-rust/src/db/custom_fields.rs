@@ -0,0 +1,158 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+use std::collections::BTreeMap;
+
+use crate::models::*;
+
+fn map_custom_field_row(row: &SqliteRow) -> CustomField {
+    let choices: String = row.get("choices");
+    CustomField {
+        id: row.get("id"),
+        name: row.get("name"),
+        label: row.get("label"),
+        description: row.get("description"),
+        field_type: row.get("field_type"),
+        required: row.get("required"),
+        choices: serde_json::from_str(&choices).unwrap_or_default(),
+        pattern: row.get("pattern"),
+        min_value: row.get("min_value"),
+        max_value: row.get("max_value"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Custom field definition and device value operations
+pub struct CustomFieldRepo;
+
+impl CustomFieldRepo {
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<CustomField>> {
+        let rows = sqlx::query("SELECT * FROM custom_fields ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_custom_field_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<CustomField>> {
+        let row = sqlx::query("SELECT * FROM custom_fields WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_custom_field_row))
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, req: &CreateCustomFieldRequest) -> Result<CustomField> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO custom_fields (name, label, description, field_type, required, choices, pattern,
+                min_value, max_value, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.label)
+        .bind(&req.description)
+        .bind(&req.field_type)
+        .bind(req.required)
+        .bind(serde_json::to_string(&req.choices)?)
+        .bind(&req.pattern)
+        .bind(req.min_value)
+        .bind(req.max_value)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get(pool, result.last_insert_rowid())
+            .await?
+            .context("Custom field not found after creation")
+    }
+
+    pub async fn update(pool: &Pool<Sqlite>, id: i64, req: &CreateCustomFieldRequest) -> Result<CustomField> {
+        let result = sqlx::query(
+            r#"
+            UPDATE custom_fields SET name = ?, label = ?, description = ?, field_type = ?, required = ?,
+                choices = ?, pattern = ?, min_value = ?, max_value = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.label)
+        .bind(&req.description)
+        .bind(&req.field_type)
+        .bind(req.required)
+        .bind(serde_json::to_string(&req.choices)?)
+        .bind(&req.pattern)
+        .bind(req.min_value)
+        .bind(req.max_value)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Custom field", &id.to_string()).into());
+        }
+        Self::get(pool, id)
+            .await?
+            .context("Custom field not found after update")
+    }
+
+    /// Delete a field definition along with every device's value for it
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM custom_fields WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Custom field", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Stored values of one field across devices, by device ID
+    pub async fn list_values(pool: &Pool<Sqlite>, field_id: i64) -> Result<BTreeMap<i64, String>> {
+        let rows = sqlx::query("SELECT device_id, value FROM device_custom_field_values WHERE field_id = ?")
+            .bind(field_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(|r| (r.get("device_id"), r.get("value"))).collect())
+    }
+
+    /// Set (`Some`) or clear (`None`) a device's values in one transaction
+    pub async fn set_device_values(pool: &Pool<Sqlite>, device_id: i64, values: &[(i64, Option<String>)]) -> Result<()> {
+        let now = Utc::now();
+        let mut tx = pool.begin().await?;
+        for (field_id, value) in values {
+            match value {
+                Some(value) => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO device_custom_field_values (device_id, field_id, value, updated_at)
+                        VALUES (?, ?, ?, ?)
+                        ON CONFLICT(device_id, field_id) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+                        "#,
+                    )
+                    .bind(device_id)
+                    .bind(field_id)
+                    .bind(value)
+                    .bind(now)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                None => {
+                    sqlx::query("DELETE FROM device_custom_field_values WHERE device_id = ? AND field_id = ?")
+                        .bind(device_id)
+                        .bind(field_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
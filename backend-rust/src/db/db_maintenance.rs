@@ -0,0 +1,82 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_run_row(row: &SqliteRow) -> DbMaintenanceRun {
+    DbMaintenanceRun {
+        id: row.get("id"),
+        kind: row.get("kind"),
+        filename: row.get("filename"),
+        size_bytes: row.get("size_bytes"),
+        duration_ms: row.get("duration_ms"),
+        uploaded_to: row.get("uploaded_to"),
+        error: row.get("error"),
+        triggered_by: row.get("triggered_by"),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// Database backup and VACUUM history operations
+pub struct DbMaintenanceRepo;
+
+impl DbMaintenanceRepo {
+    pub async fn list(pool: &Pool<Sqlite>, kind: &str, limit: i64) -> Result<Vec<DbMaintenanceRun>> {
+        let rows = sqlx::query("SELECT * FROM db_maintenance_runs WHERE kind = ? ORDER BY created_at DESC, id DESC LIMIT ?")
+            .bind(kind)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_run_row).collect())
+    }
+
+    /// Most recent successful run of a kind
+    pub async fn last_success(pool: &Pool<Sqlite>, kind: &str) -> Result<Option<DbMaintenanceRun>> {
+        let row = sqlx::query(
+            "SELECT * FROM db_maintenance_runs WHERE kind = ? AND error IS NULL ORDER BY created_at DESC, id DESC LIMIT 1",
+        )
+        .bind(kind)
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.as_ref().map(map_run_row))
+    }
+
+    pub async fn record(pool: &Pool<Sqlite>, run: &NewDbMaintenanceRun) -> Result<DbMaintenanceRun> {
+        let result = sqlx::query(
+            r#"INSERT INTO db_maintenance_runs
+                   (kind, filename, size_bytes, duration_ms, uploaded_to, error, triggered_by, created_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(run.kind)
+        .bind(&run.filename)
+        .bind(run.size_bytes)
+        .bind(run.duration_ms)
+        .bind(&run.uploaded_to)
+        .bind(&run.error)
+        .bind(&run.triggered_by)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        let row = sqlx::query("SELECT * FROM db_maintenance_runs WHERE id = ?")
+            .bind(result.last_insert_rowid())
+            .fetch_one(pool)
+            .await?;
+        Ok(map_run_row(&row))
+    }
+
+    /// Copy the live database to `path` as a consistent snapshot, without blocking writers
+    pub async fn backup_to(pool: &Pool<Sqlite>, path: &str) -> Result<()> {
+        sqlx::query("VACUUM INTO ?").bind(path).execute(pool).await?;
+        Ok(())
+    }
+
+    /// Rebuild the database file and refresh query planner statistics, returning the new size
+    pub async fn vacuum_analyze(pool: &Pool<Sqlite>) -> Result<i64> {
+        sqlx::query("VACUUM").execute(pool).await?;
+        sqlx::query("ANALYZE").execute(pool).await?;
+        let (page_count,): (i64,) = sqlx::query_as("PRAGMA page_count").fetch_one(pool).await?;
+        let (page_size,): (i64,) = sqlx::query_as("PRAGMA page_size").fetch_one(pool).await?;
+        Ok(page_count * page_size)
+    }
+}
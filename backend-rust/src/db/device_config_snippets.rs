@@ -0,0 +1,60 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_snippets_row(row: &SqliteRow) -> DeviceConfigSnippets {
+    DeviceConfigSnippets {
+        device_id: row.get("device_id"),
+        pre_config: row.get("pre_config"),
+        post_config: row.get("post_config"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Per-device config snippet database operations
+pub struct DeviceConfigSnippetRepo;
+
+impl DeviceConfigSnippetRepo {
+    pub async fn get(pool: &Pool<Sqlite>, device_id: i64) -> Result<Option<DeviceConfigSnippets>> {
+        let row = sqlx::query("SELECT * FROM device_config_snippets WHERE device_id = ?")
+            .bind(device_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_snippets_row))
+    }
+
+    pub async fn set(pool: &Pool<Sqlite>, device_id: i64, req: &SetDeviceConfigSnippetsRequest) -> Result<DeviceConfigSnippets> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"INSERT INTO device_config_snippets (device_id, pre_config, post_config, updated_at)
+               VALUES (?, ?, ?, ?)
+               ON CONFLICT(device_id) DO UPDATE SET
+                   pre_config = excluded.pre_config,
+                   post_config = excluded.post_config,
+                   updated_at = excluded.updated_at"#,
+        )
+        .bind(device_id)
+        .bind(&req.pre_config)
+        .bind(&req.post_config)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Ok(DeviceConfigSnippets {
+            device_id,
+            pre_config: req.pre_config.clone(),
+            post_config: req.post_config.clone(),
+            updated_at: Some(now),
+        })
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, device_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM device_config_snippets WHERE device_id = ?")
+            .bind(device_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
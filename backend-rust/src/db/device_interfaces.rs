@@ -0,0 +1,114 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+use crate::utils;
+
+fn map_device_interface_row(row: &SqliteRow) -> DeviceInterface {
+    DeviceInterface {
+        id: row.get("id"),
+        device_id: row.get("device_id"),
+        name: row.get("name"),
+        status: row.get("status"),
+        speed_mbps: row.get("speed_mbps"),
+        description: row.get("description"),
+        mac: row.get("mac"),
+        ip: row.get("ip"),
+        vrf: row.get("vrf"),
+        collected_at: row.get("collected_at"),
+    }
+}
+
+pub struct DeviceInterfaceRepo;
+
+impl DeviceInterfaceRepo {
+    pub async fn list_for_device(pool: &Pool<Sqlite>, device_id: i64) -> Result<Vec<DeviceInterface>> {
+        let rows = sqlx::query("SELECT * FROM device_interfaces WHERE device_id = ? ORDER BY name")
+            .bind(device_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_device_interface_row).collect())
+    }
+
+    pub async fn list_all(pool: &Pool<Sqlite>) -> Result<Vec<DeviceInterface>> {
+        let rows = sqlx::query("SELECT * FROM device_interfaces ORDER BY device_id, name")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_device_interface_row).collect())
+    }
+
+    /// Replace a device's interface inventory with freshly collected entries, then bind IPAM
+    /// addresses matching an interface IP to that interface. Addresses already bound to another
+    /// device are left alone.
+    pub async fn replace_for_device(
+        pool: &Pool<Sqlite>,
+        device_id: i64,
+        entries: &[InterfaceInventoryEntry],
+    ) -> Result<InterfaceInventorySummary> {
+        let now = Utc::now();
+        let mut summary = InterfaceInventorySummary { interfaces: entries.len(), ..Default::default() };
+        let mut tx = pool.begin().await?;
+
+        let existing: Vec<String> = sqlx::query_scalar("SELECT name FROM device_interfaces WHERE device_id = ?")
+            .bind(device_id)
+            .fetch_all(&mut *tx)
+            .await?;
+        for name in existing.iter().filter(|name| !entries.iter().any(|e| &e.name == *name)) {
+            sqlx::query("DELETE FROM device_interfaces WHERE device_id = ? AND name = ?")
+                .bind(device_id)
+                .bind(name)
+                .execute(&mut *tx)
+                .await?;
+            summary.removed += 1;
+        }
+
+        for entry in entries {
+            sqlx::query(
+                r#"INSERT INTO device_interfaces (device_id, name, status, speed_mbps, description, mac, ip, vrf, collected_at)
+                   VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                   ON CONFLICT(device_id, name) DO UPDATE SET
+                       status = excluded.status,
+                       speed_mbps = excluded.speed_mbps,
+                       description = excluded.description,
+                       mac = excluded.mac,
+                       ip = excluded.ip,
+                       vrf = excluded.vrf,
+                       collected_at = excluded.collected_at"#,
+            )
+            .bind(device_id)
+            .bind(&entry.name)
+            .bind(&entry.status)
+            .bind(entry.speed_mbps)
+            .bind(&entry.description)
+            .bind(&entry.mac)
+            .bind(&entry.ip)
+            .bind(&entry.vrf)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+            let address = entry.ip.split('/').next().unwrap_or_default();
+            let Ok(addr_int) = utils::parse_ipv4_to_u32(address) else {
+                continue;
+            };
+            let bound = sqlx::query(
+                r#"UPDATE ipam_ip_addresses SET device_id = ?, interface_name = ?, updated_at = ?
+                   WHERE address_int = ?
+                     AND (device_id IS NULL OR (device_id = ? AND COALESCE(interface_name, '') != ?))"#,
+            )
+            .bind(device_id)
+            .bind(&entry.name)
+            .bind(now)
+            .bind(addr_int as i64)
+            .bind(device_id)
+            .bind(&entry.name)
+            .execute(&mut *tx)
+            .await?;
+            summary.ipam_bound += bound.rows_affected() as usize;
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
+}
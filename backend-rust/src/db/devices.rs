@@ -11,9 +11,16 @@ const SELECT_DEVICE: &str = r#"
            COALESCE(v.name, d.vendor) as vendor,
            d.vendor as vendor_id,
            d.model, d.serial_number, d.config_template,
-           d.ssh_user, d.ssh_pass, d.topology_id, d.topology_role,
-           d.hall_id, d.row_id, d.rack_id, d.rack_position,
+           d.credential_id, d.ssh_user, d.ssh_pass, d.topology_id, d.topology_role,
+           d.hall_id, d.row_id, d.rack_id, d.rack_position, d.tenant_id, d.gpu_cluster_id,
            d.status, d.device_type, d.last_seen, d.last_backup, d.last_error,
+           (SELECT json_group_object(f.name, CASE f.field_type
+                       WHEN 'integer' THEN CAST(cv.value AS INTEGER)
+                       WHEN 'boolean' THEN json(cv.value)
+                       ELSE cv.value END)
+            FROM device_custom_field_values cv
+            JOIN custom_fields f ON f.id = cv.field_id
+            WHERE cv.device_id = d.id) as custom_fields,
            d.created_at, d.updated_at
     FROM devices d
     LEFT JOIN vendors v ON CAST(v.id AS TEXT) = d.vendor
@@ -41,6 +48,169 @@ impl DeviceRepo {
         Ok(rows.iter().map(map_device_row).collect())
     }
 
+    /// Devices assigned to a rack, placed or not
+    pub async fn list_by_rack(pool: &Pool<Sqlite>, rack_id: i64) -> Result<Vec<Device>> {
+        let rows = sqlx::query(&format!("{} WHERE d.rack_id = ? ORDER BY d.rack_position DESC, d.hostname", SELECT_DEVICE))
+            .bind(rack_id)
+            .fetch_all(pool)
+            .await?;
+
+        Ok(rows.iter().map(map_device_row).collect())
+    }
+
+    /// List devices matching a declarative filter. Every set field is ANDed together.
+    pub async fn list_filtered(pool: &Pool<Sqlite>, filter: &DeviceFilter) -> Result<Vec<Device>> {
+        enum Arg<'a> {
+            Text(&'a str),
+            Int(i64),
+        }
+
+        let mut clauses: Vec<&str> = Vec::new();
+        let mut args: Vec<Arg> = Vec::new();
+
+        if let Some(ref vendor) = filter.vendor {
+            clauses.push("(d.vendor = ? OR LOWER(v.name) = LOWER(?))");
+            args.push(Arg::Text(vendor));
+            args.push(Arg::Text(vendor));
+        }
+        if let Some(group_id) = filter.group_id {
+            clauses.push("EXISTS (SELECT 1 FROM device_group_members m WHERE m.device_id = d.id AND m.group_id = ?)");
+            args.push(Arg::Int(group_id));
+        }
+        if let Some(ref status) = filter.status {
+            clauses.push("d.status = ?");
+            args.push(Arg::Text(status));
+        }
+        if let Some(topology_id) = filter.topology_id {
+            clauses.push("d.topology_id = ?");
+            args.push(Arg::Int(topology_id));
+        }
+        if let Some(tenant_id) = filter.tenant_id {
+            clauses.push("d.tenant_id = ?");
+            args.push(Arg::Int(tenant_id));
+        }
+        if let Some(gpu_cluster_id) = filter.gpu_cluster_id {
+            clauses.push("d.gpu_cluster_id = ?");
+            args.push(Arg::Int(gpu_cluster_id));
+        }
+        if let Some(ref role) = filter.topology_role {
+            clauses.push("d.topology_role = ?");
+            args.push(Arg::Text(role));
+        }
+        if let Some(ref device_type) = filter.device_type {
+            clauses.push("d.device_type = ?");
+            args.push(Arg::Text(device_type));
+        }
+        let hostname_pattern = filter.hostname.as_ref().map(|h| h.replace('*', "%"));
+        if let Some(ref pattern) = hostname_pattern {
+            clauses.push("d.hostname LIKE ?");
+            args.push(Arg::Text(pattern));
+        }
+        match filter.has_backup {
+            Some(true) => clauses.push("EXISTS (SELECT 1 FROM backups b WHERE b.device_id = d.id)"),
+            Some(false) => clauses.push("NOT EXISTS (SELECT 1 FROM backups b WHERE b.device_id = d.id)"),
+            None => {}
+        }
+        let contains_patterns: Vec<String> = filter
+            .variables
+            .iter()
+            .map(|p| format!("%{}%", p.value))
+            .collect();
+        for (pred, contains) in filter.variables.iter().zip(&contains_patterns) {
+            match pred.op.as_str() {
+                variable_op::EXISTS => {
+                    clauses.push("EXISTS (SELECT 1 FROM device_variables dv WHERE dv.device_id = d.id AND dv.key = ?)");
+                    args.push(Arg::Text(&pred.key));
+                }
+                variable_op::MISSING => {
+                    clauses.push("NOT EXISTS (SELECT 1 FROM device_variables dv WHERE dv.device_id = d.id AND dv.key = ?)");
+                    args.push(Arg::Text(&pred.key));
+                }
+                variable_op::NE => {
+                    clauses.push("NOT EXISTS (SELECT 1 FROM device_variables dv WHERE dv.device_id = d.id AND dv.key = ? AND dv.value = ?)");
+                    args.push(Arg::Text(&pred.key));
+                    args.push(Arg::Text(&pred.value));
+                }
+                variable_op::CONTAINS => {
+                    clauses.push("EXISTS (SELECT 1 FROM device_variables dv WHERE dv.device_id = d.id AND dv.key = ? AND dv.value LIKE ?)");
+                    args.push(Arg::Text(&pred.key));
+                    args.push(Arg::Text(contains));
+                }
+                _ => {
+                    clauses.push("EXISTS (SELECT 1 FROM device_variables dv WHERE dv.device_id = d.id AND dv.key = ? AND dv.value = ?)");
+                    args.push(Arg::Text(&pred.key));
+                    args.push(Arg::Text(&pred.value));
+                }
+            }
+        }
+
+        let field_contains_patterns: Vec<String> = filter
+            .custom_fields
+            .iter()
+            .map(|p| format!("%{}%", p.value))
+            .collect();
+        for (pred, contains) in filter.custom_fields.iter().zip(&field_contains_patterns) {
+            match pred.op.as_str() {
+                variable_op::EXISTS => {
+                    clauses.push("EXISTS (SELECT 1 FROM device_custom_field_values cv JOIN custom_fields f ON f.id = cv.field_id WHERE cv.device_id = d.id AND f.name = ?)");
+                    args.push(Arg::Text(&pred.key));
+                }
+                variable_op::MISSING => {
+                    clauses.push("NOT EXISTS (SELECT 1 FROM device_custom_field_values cv JOIN custom_fields f ON f.id = cv.field_id WHERE cv.device_id = d.id AND f.name = ?)");
+                    args.push(Arg::Text(&pred.key));
+                }
+                variable_op::NE => {
+                    clauses.push("NOT EXISTS (SELECT 1 FROM device_custom_field_values cv JOIN custom_fields f ON f.id = cv.field_id WHERE cv.device_id = d.id AND f.name = ? AND cv.value = ?)");
+                    args.push(Arg::Text(&pred.key));
+                    args.push(Arg::Text(&pred.value));
+                }
+                variable_op::CONTAINS => {
+                    clauses.push("EXISTS (SELECT 1 FROM device_custom_field_values cv JOIN custom_fields f ON f.id = cv.field_id WHERE cv.device_id = d.id AND f.name = ? AND cv.value LIKE ?)");
+                    args.push(Arg::Text(&pred.key));
+                    args.push(Arg::Text(contains));
+                }
+                _ => {
+                    clauses.push("EXISTS (SELECT 1 FROM device_custom_field_values cv JOIN custom_fields f ON f.id = cv.field_id WHERE cv.device_id = d.id AND f.name = ? AND cv.value = ?)");
+                    args.push(Arg::Text(&pred.key));
+                    args.push(Arg::Text(&pred.value));
+                }
+            }
+        }
+
+        let tag_selectors: Vec<TagSelector> = filter.tags.iter().filter_map(|t| TagSelector::parse(t)).collect();
+        for sel in &tag_selectors {
+            match sel.value {
+                Some(ref value) => {
+                    clauses.push("EXISTS (SELECT 1 FROM resource_tags t WHERE t.resource_type = 'device' AND t.resource_id = CAST(d.id AS TEXT) AND t.key = ? AND t.value = ?)");
+                    args.push(Arg::Text(&sel.key));
+                    args.push(Arg::Text(value));
+                }
+                None => {
+                    clauses.push("EXISTS (SELECT 1 FROM resource_tags t WHERE t.resource_type = 'device' AND t.resource_id = CAST(d.id AS TEXT) AND t.key = ?)");
+                    args.push(Arg::Text(&sel.key));
+                }
+            }
+        }
+
+        let where_sql = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+        let sql = format!("{} {} ORDER BY d.hostname", SELECT_DEVICE, where_sql);
+
+        let mut query = sqlx::query(&sql);
+        for arg in args {
+            query = match arg {
+                Arg::Text(s) => query.bind(s),
+                Arg::Int(i) => query.bind(i),
+            };
+        }
+        let rows = query.fetch_all(pool).await?;
+
+        Ok(rows.iter().map(map_device_row).collect())
+    }
+
     pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Device>> {
         let row = sqlx::query(&format!("{} WHERE d.id = ?", SELECT_DEVICE))
             .bind(id)
@@ -50,6 +220,15 @@ impl DeviceRepo {
         Ok(row.as_ref().map(map_device_row))
     }
 
+    /// ID of the device with this management IP, if any
+    pub async fn find_id_by_ip(pool: &Pool<Sqlite>, ip: &str) -> Result<Option<i64>> {
+        let id = sqlx::query_scalar("SELECT id FROM devices WHERE ip = ? ORDER BY id LIMIT 1")
+            .bind(ip)
+            .fetch_optional(pool)
+            .await?;
+        Ok(id)
+    }
+
     pub async fn get_by_mac(pool: &Pool<Sqlite>, mac: &str) -> Result<Option<Device>> {
         let row = sqlx::query(&format!("{} WHERE d.mac = ?", SELECT_DEVICE))
             .bind(mac)
@@ -59,15 +238,29 @@ impl DeviceRepo {
         Ok(row.as_ref().map(map_device_row))
     }
 
+    /// The device whose serial number matches, trimmed and ignoring case
+    pub async fn get_by_serial(pool: &Pool<Sqlite>, serial: &str) -> Result<Option<Device>> {
+        let serial = crate::utils::normalize_serial(serial);
+        if serial.is_empty() {
+            return Ok(None);
+        }
+        let row = sqlx::query(&format!("{} WHERE UPPER(TRIM(d.serial_number)) = ? ORDER BY d.id LIMIT 1", SELECT_DEVICE))
+            .bind(serial)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(row.as_ref().map(map_device_row))
+    }
+
     pub async fn create(pool: &Pool<Sqlite>, req: &CreateDeviceRequest) -> Result<Device> {
         let now = Utc::now();
         let result = sqlx::query(
             r#"
             INSERT INTO devices (mac, ip, hostname, vendor, model, serial_number, config_template,
-                                ssh_user, ssh_pass, topology_id, topology_role,
+                                credential_id, ssh_user, ssh_pass, topology_id, topology_role,
                                 hall_id, row_id, rack_id, rack_position,
                                 device_type, status, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'offline', ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'offline', ?, ?)
             "#,
         )
         .bind(&req.mac)
@@ -77,6 +270,7 @@ impl DeviceRepo {
         .bind(&req.model.clone().unwrap_or_default())
         .bind(&req.serial_number.clone().unwrap_or_default())
         .bind(&req.config_template)
+        .bind(req.credential_id)
         .bind(&req.ssh_user.clone().unwrap_or_default())
         .bind(&req.ssh_pass.clone().unwrap_or_default())
         .bind(req.topology_id)
@@ -103,7 +297,7 @@ impl DeviceRepo {
         let result = sqlx::query(
             r#"
             UPDATE devices SET ip = ?, hostname = ?, vendor = ?, model = ?, serial_number = ?,
-                              config_template = ?, ssh_user = ?, ssh_pass = ?,
+                              config_template = ?, credential_id = ?, ssh_user = ?, ssh_pass = ?,
                               topology_id = ?, topology_role = ?,
                               hall_id = ?, row_id = ?, rack_id = ?, rack_position = ?,
                               device_type = ?, updated_at = ?
@@ -116,6 +310,7 @@ impl DeviceRepo {
         .bind(&req.model.clone().unwrap_or_default())
         .bind(&req.serial_number.clone().unwrap_or_default())
         .bind(&req.config_template)
+        .bind(req.credential_id)
         .bind(&req.ssh_user.clone().unwrap_or_default())
         .bind(&req.ssh_pass.clone().unwrap_or_default())
         .bind(req.topology_id)
@@ -139,6 +334,79 @@ impl DeviceRepo {
             .context("Device not found after update")
     }
 
+    /// Fold the source devices into `target_id` in one transaction. Variables and group
+    /// memberships are copied (the target's own values win on conflict); jobs, backups, IPAM
+    /// addresses, notes and attachments are re-pointed. Blank identity fields on the target are filled from the
+    /// sources, which are then deleted along with any other per-device data.
+    pub async fn merge(pool: &Pool<Sqlite>, target_id: i64, source_ids: &[i64]) -> Result<MergeDevicesStats> {
+        let mut tx = pool.begin().await?;
+        let mut stats = MergeDevicesStats::default();
+        for &source_id in source_ids {
+            stats.variables_moved += sqlx::query(
+                r#"INSERT OR IGNORE INTO device_variables (device_id, key, value, created_at, updated_at)
+                   SELECT ?, key, value, created_at, updated_at FROM device_variables WHERE device_id = ?"#,
+            )
+            .bind(target_id)
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            stats.group_memberships_moved += sqlx::query(
+                r#"INSERT OR IGNORE INTO device_group_members (device_id, group_id, created_at)
+                   SELECT ?, group_id, created_at FROM device_group_members WHERE device_id = ?"#,
+            )
+            .bind(target_id)
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            for (table, counter) in [
+                ("jobs", &mut stats.jobs_moved),
+                ("backups", &mut stats.backups_moved),
+                ("ipam_ip_addresses", &mut stats.ip_addresses_moved),
+                ("notes", &mut stats.notes_moved),
+                ("attachments", &mut stats.attachments_moved),
+            ] {
+                // Archived jobs move too, in their monthly partitions
+                for table in super::partitions::with_partitions(&mut tx, table).await? {
+                    *counter += sqlx::query(&format!("UPDATE {} SET device_id = ? WHERE device_id = ?", table))
+                        .bind(target_id)
+                        .bind(source_id)
+                        .execute(&mut *tx)
+                        .await?
+                        .rows_affected();
+                }
+            }
+            sqlx::query(
+                r#"UPDATE devices SET
+                       mac = COALESCE(NULLIF(devices.mac, ''), s.mac),
+                       ip = COALESCE(NULLIF(devices.ip, ''), s.ip),
+                       vendor = COALESCE(NULLIF(devices.vendor, ''), s.vendor),
+                       model = COALESCE(NULLIF(devices.model, ''), s.model),
+                       serial_number = COALESCE(NULLIF(devices.serial_number, ''), s.serial_number),
+                       config_template = COALESCE(NULLIF(devices.config_template, ''), s.config_template),
+                       last_backup = MAX(COALESCE(devices.last_backup, s.last_backup), COALESCE(s.last_backup, devices.last_backup)),
+                       updated_at = ?
+                   FROM (SELECT * FROM devices WHERE id = ?) AS s
+                   WHERE devices.id = ?"#,
+            )
+            .bind(Utc::now())
+            .bind(source_id)
+            .bind(target_id)
+            .execute(&mut *tx)
+            .await?;
+            let deleted = sqlx::query("DELETE FROM devices WHERE id = ?")
+                .bind(source_id)
+                .execute(&mut *tx)
+                .await?;
+            if deleted.rows_affected() == 0 {
+                return Err(super::NotFoundError::new("Device", &source_id.to_string()).into());
+            }
+        }
+        tx.commit().await?;
+        Ok(stats)
+    }
+
     pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
         // Clean up IPAM IP addresses assigned to this device
         sqlx::query("DELETE FROM ipam_ip_addresses WHERE device_id = ?")
@@ -157,8 +425,22 @@ impl DeviceRepo {
         Ok(())
     }
 
+    /// Set the device's status, recording the transition in the status history when it changes
     pub async fn update_status(pool: &Pool<Sqlite>, id: i64, status: &str) -> Result<()> {
         let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO device_status_history (device_id, previous_status, status, changed_at)
+            SELECT id, COALESCE(status, ''), ?, ? FROM devices WHERE id = ? AND COALESCE(status, '') != ?
+            "#,
+        )
+        .bind(status)
+        .bind(now)
+        .bind(id)
+        .bind(status)
+        .execute(pool)
+        .await?;
+
         sqlx::query("UPDATE devices SET status = ?, last_seen = ?, updated_at = ? WHERE id = ?")
             .bind(status)
             .bind(now)
@@ -180,6 +462,51 @@ impl DeviceRepo {
         Ok(())
     }
 
+    /// Set the device's management IP without touching its other fields
+    pub async fn update_ip(pool: &Pool<Sqlite>, id: i64, ip: &str) -> Result<()> {
+        let result = sqlx::query("UPDATE devices SET ip = ?, updated_at = ? WHERE id = ?")
+            .bind(ip)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Device", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Move the device to a new MAC without touching its other fields
+    pub async fn update_mac(pool: &Pool<Sqlite>, id: i64, mac: &str) -> Result<()> {
+        let result = sqlx::query("UPDATE devices SET mac = ?, updated_at = ? WHERE id = ?")
+            .bind(mac)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Device", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Set the device's recorded serial number without touching its other fields
+    pub async fn update_serial(pool: &Pool<Sqlite>, id: i64, serial: &str) -> Result<()> {
+        let result = sqlx::query("UPDATE devices SET serial_number = ?, updated_at = ? WHERE id = ?")
+            .bind(serial)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Device", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
     pub async fn delete_by_topology(pool: &Pool<Sqlite>, topology_id: i64) -> Result<u64> {
         // Collect device IDs first for cleanup
         let device_ids: Vec<i64> = sqlx::query_scalar(
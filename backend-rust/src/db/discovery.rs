@@ -45,7 +45,7 @@ impl DiscoveryRepo {
         let rows = sqlx::query(
             r#"
             SELECT id, event_type, mac, ip, hostname, vendor, message, created_at
-            FROM discovery_logs
+            FROM discovery_logs_all
             ORDER BY created_at DESC
             LIMIT ?
             "#,
@@ -120,7 +120,9 @@ impl DiscoveryRepo {
             r#"
             SELECT dd.mac, dd.ip, dd.hostname, dd.vendor, dd.model, dd.serial_number,
                    dd.vendor_class, dd.user_class, dd.dhcp_client_id, dd.requested_options,
-                   dd.relay_address, dd.circuit_id, dd.remote_id, dd.subscriber_id, dd.expires_at
+                   dd.relay_address, dd.circuit_id, dd.remote_id, dd.subscriber_id, dd.expires_at,
+                   (SELECT MIN(s.id) FROM devices s
+                    WHERE dd.serial_number != '' AND UPPER(TRIM(s.serial_number)) = UPPER(TRIM(dd.serial_number))) AS matched_device_id
             FROM discovered_devices dd
             LEFT JOIN devices d ON LOWER(d.mac) = LOWER(dd.mac)
             WHERE d.mac IS NULL AND dd.last_seen >= ?
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+use super::row_helpers::none_if_empty;
+
+fn map_docker_stack_row(row: &SqliteRow) -> DockerStack {
+    let services_json: String = row.get("services");
+    DockerStack {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: none_if_empty(row.get("description")),
+        services: serde_json::from_str(&services_json).unwrap_or_default(),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+pub struct DockerStackRepo;
+
+impl DockerStackRepo {
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<DockerStack>> {
+        let rows = sqlx::query("SELECT * FROM docker_stacks ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_docker_stack_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<DockerStack>> {
+        let row = sqlx::query("SELECT * FROM docker_stacks WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_docker_stack_row))
+    }
+
+    pub async fn get_by_name(pool: &Pool<Sqlite>, name: &str) -> Result<Option<DockerStack>> {
+        let row = sqlx::query("SELECT * FROM docker_stacks WHERE name = ?")
+            .bind(name)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_docker_stack_row))
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, req: &CreateDockerStackRequest) -> Result<DockerStack> {
+        let now = Utc::now();
+        let services_json = serde_json::to_string(&req.services)?;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO docker_stacks (name, description, services, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&services_json)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        let new_id = result.last_insert_rowid();
+        Self::get(pool, new_id)
+            .await?
+            .context("Docker stack not found after creation")
+    }
+
+    pub async fn update(pool: &Pool<Sqlite>, id: i64, req: &CreateDockerStackRequest) -> Result<DockerStack> {
+        let now = Utc::now();
+        let services_json = serde_json::to_string(&req.services)?;
+        let result = sqlx::query(
+            r#"
+            UPDATE docker_stacks SET name = ?, description = ?, services = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&services_json)
+        .bind(now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Docker stack", &id.to_string()).into());
+        }
+
+        Self::get(pool, id)
+            .await?
+            .context("Docker stack not found after update")
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM docker_stacks WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Docker stack", &id.to_string()).into());
+        }
+        Ok(())
+    }
+}
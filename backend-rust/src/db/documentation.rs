@@ -0,0 +1,178 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_note_row(row: &SqliteRow) -> Note {
+    Note {
+        id: row.get("id"),
+        device_id: row.get("device_id"),
+        topology_id: row.get("topology_id"),
+        title: row.get("title"),
+        body: row.get("body"),
+        created_by: row.get("created_by"),
+        updated_by: row.get("updated_by"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn map_attachment_row(row: &SqliteRow) -> Attachment {
+    Attachment {
+        id: row.get("id"),
+        device_id: row.get("device_id"),
+        topology_id: row.get("topology_id"),
+        filename: row.get("filename"),
+        stored_name: row.get("stored_name"),
+        content_type: row.get("content_type"),
+        size: row.get("size"),
+        description: row.get("description"),
+        uploaded_by: row.get("uploaded_by"),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// Note database operations
+pub struct NoteRepo;
+
+impl NoteRepo {
+    /// Notes on a device or topology, most recently updated first
+    pub async fn list(pool: &Pool<Sqlite>, owner: DocOwner) -> Result<Vec<Note>> {
+        let rows = sqlx::query(
+            "SELECT * FROM notes WHERE device_id IS ? AND topology_id IS ? ORDER BY updated_at DESC, id DESC",
+        )
+        .bind(owner.device_id())
+        .bind(owner.topology_id())
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_note_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Note>> {
+        let row = sqlx::query("SELECT * FROM notes WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_note_row))
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, owner: DocOwner, req: &CreateNoteRequest, created_by: &str) -> Result<Note> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO notes (device_id, topology_id, title, body, created_by, updated_by, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(owner.device_id())
+        .bind(owner.topology_id())
+        .bind(&req.title)
+        .bind(&req.body)
+        .bind(created_by)
+        .bind(created_by)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get(pool, result.last_insert_rowid())
+            .await?
+            .context("Note not found after creation")
+    }
+
+    pub async fn update(pool: &Pool<Sqlite>, note: &Note, updated_by: &str) -> Result<Note> {
+        sqlx::query("UPDATE notes SET title = ?, body = ?, updated_by = ?, updated_at = ? WHERE id = ?")
+            .bind(&note.title)
+            .bind(&note.body)
+            .bind(updated_by)
+            .bind(Utc::now())
+            .bind(note.id)
+            .execute(pool)
+            .await?;
+
+        Self::get(pool, note.id)
+            .await?
+            .ok_or_else(|| super::NotFoundError::new("Note", &note.id.to_string()).into())
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM notes WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Note", &id.to_string()).into());
+        }
+        Ok(())
+    }
+}
+
+/// Attachment metadata database operations
+pub struct AttachmentRepo;
+
+impl AttachmentRepo {
+    /// Attachments on a device or topology, newest first
+    pub async fn list(pool: &Pool<Sqlite>, owner: DocOwner) -> Result<Vec<Attachment>> {
+        let rows = sqlx::query(
+            "SELECT * FROM attachments WHERE device_id IS ? AND topology_id IS ? ORDER BY created_at DESC, id DESC",
+        )
+        .bind(owner.device_id())
+        .bind(owner.topology_id())
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_attachment_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Attachment>> {
+        let row = sqlx::query("SELECT * FROM attachments WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_attachment_row))
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, attachment: &Attachment) -> Result<Attachment> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO attachments (device_id, topology_id, filename, stored_name, content_type, size,
+                                     description, uploaded_by, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(attachment.device_id)
+        .bind(attachment.topology_id)
+        .bind(&attachment.filename)
+        .bind(&attachment.stored_name)
+        .bind(&attachment.content_type)
+        .bind(attachment.size)
+        .bind(&attachment.description)
+        .bind(&attachment.uploaded_by)
+        .bind(attachment.created_at)
+        .execute(pool)
+        .await?;
+
+        Self::get(pool, result.last_insert_rowid())
+            .await?
+            .context("Attachment not found after creation")
+    }
+
+    /// Stored file names of every attachment, to find files left behind by deleted owners
+    pub async fn list_stored_names(pool: &Pool<Sqlite>) -> Result<Vec<String>> {
+        let rows = sqlx::query("SELECT stored_name FROM attachments")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(|r| r.get("stored_name")).collect())
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM attachments WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Attachment", &id.to_string()).into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_external_id_row(row: &SqliteRow) -> ExternalId {
+    ExternalId {
+        resource_type: row.get("resource_type"),
+        external_id: row.get("external_id"),
+        resource_id: row.get("resource_id"),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// External ID mapping database operations
+pub struct ExternalIdRepo;
+
+impl ExternalIdRepo {
+    pub async fn list(pool: &Pool<Sqlite>, resource_type: Option<&str>) -> Result<Vec<ExternalId>> {
+        let rows = sqlx::query(
+            "SELECT * FROM external_ids WHERE (? IS NULL OR resource_type = ?) ORDER BY resource_type, external_id",
+        )
+        .bind(resource_type)
+        .bind(resource_type)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_external_id_row).collect())
+    }
+
+    pub async fn resolve(pool: &Pool<Sqlite>, resource_type: &str, external_id: &str) -> Result<Option<i64>> {
+        Ok(sqlx::query_scalar("SELECT resource_id FROM external_ids WHERE resource_type = ? AND external_id = ?")
+            .bind(resource_type)
+            .bind(external_id)
+            .fetch_optional(pool)
+            .await?)
+    }
+
+    pub async fn find_for_resource(pool: &Pool<Sqlite>, resource_type: &str, resource_id: i64) -> Result<Option<String>> {
+        Ok(sqlx::query_scalar("SELECT external_id FROM external_ids WHERE resource_type = ? AND resource_id = ?")
+            .bind(resource_type)
+            .bind(resource_id)
+            .fetch_optional(pool)
+            .await?)
+    }
+
+    /// Bind `external_id` to a resource; either side already being bound is a conflict
+    pub async fn bind(pool: &Pool<Sqlite>, resource_type: &str, external_id: &str, resource_id: i64) -> Result<ExternalId> {
+        if let Some(existing) = Self::resolve(pool, resource_type, external_id).await? {
+            return Err(super::ConflictError::new(format!(
+                "external ID '{}' is already bound to {} {}",
+                external_id, resource_type, existing
+            ))
+            .into());
+        }
+        if let Some(existing) = Self::find_for_resource(pool, resource_type, resource_id).await? {
+            return Err(super::ConflictError::new(format!(
+                "{} {} is already bound to external ID '{}'",
+                resource_type, resource_id, existing
+            ))
+            .into());
+        }
+        let row = sqlx::query(
+            "INSERT INTO external_ids (resource_type, external_id, resource_id) VALUES (?, ?, ?) RETURNING *",
+        )
+        .bind(resource_type)
+        .bind(external_id)
+        .bind(resource_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(map_external_id_row(&row))
+    }
+
+    pub async fn unbind(pool: &Pool<Sqlite>, resource_type: &str, external_id: &str) -> Result<()> {
+        let result = sqlx::query("DELETE FROM external_ids WHERE resource_type = ? AND external_id = ?")
+            .bind(resource_type)
+            .bind(external_id)
+            .execute(pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("External ID", external_id).into());
+        }
+        Ok(())
+    }
+}
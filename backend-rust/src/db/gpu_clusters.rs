@@ -17,23 +17,40 @@ fn map_gpu_cluster_row(row: &SqliteRow) -> GpuCluster {
         status: row.get("status"),
         topology_id: row.try_get::<Option<i64>, _>("topology_id").ok().flatten(),
         vrf_id: row.try_get::<Option<i64>, _>("vrf_id").ok().flatten(),
+        device_count: row.get("device_count"),
         created_at: row.get("created_at"),
         updated_at: row.get("updated_at"),
     }
 }
 
+fn map_gpu_cluster_variable_row(row: &SqliteRow) -> GpuClusterVariable {
+    GpuClusterVariable {
+        id: row.get("id"),
+        gpu_cluster_id: row.get("gpu_cluster_id"),
+        key: row.get("key"),
+        value: row.get("value"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+const SELECT_GPU_CLUSTER: &str = r#"
+    SELECT g.*, (SELECT COUNT(*) FROM devices d WHERE d.gpu_cluster_id = g.id) as device_count
+    FROM gpu_clusters g
+"#;
+
 pub struct GpuClusterRepo;
 
 impl GpuClusterRepo {
     pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<GpuCluster>> {
-        let rows = sqlx::query("SELECT * FROM gpu_clusters ORDER BY name")
+        let rows = sqlx::query(&format!("{} ORDER BY g.name", SELECT_GPU_CLUSTER))
             .fetch_all(pool)
             .await?;
         Ok(rows.iter().map(map_gpu_cluster_row).collect())
     }
 
     pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<GpuCluster>> {
-        let row = sqlx::query("SELECT * FROM gpu_clusters WHERE id = ?")
+        let row = sqlx::query(&format!("{} WHERE g.id = ?", SELECT_GPU_CLUSTER))
             .bind(id)
             .fetch_optional(pool)
             .await?;
@@ -111,4 +128,84 @@ impl GpuClusterRepo {
         }
         Ok(())
     }
+
+    // ========== Variables ==========
+
+    pub async fn list_variables(pool: &Pool<Sqlite>, gpu_cluster_id: i64) -> Result<Vec<GpuClusterVariable>> {
+        let rows = sqlx::query(
+            "SELECT id, gpu_cluster_id, key, value, created_at, updated_at FROM gpu_cluster_variables WHERE gpu_cluster_id = ? ORDER BY key",
+        )
+        .bind(gpu_cluster_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_gpu_cluster_variable_row).collect())
+    }
+
+    pub async fn set_variable(pool: &Pool<Sqlite>, gpu_cluster_id: i64, key: &str, value: &str) -> Result<()> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"INSERT INTO gpu_cluster_variables (gpu_cluster_id, key, value, created_at, updated_at)
+               VALUES (?, ?, ?, ?, ?)
+               ON CONFLICT(gpu_cluster_id, key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at"#,
+        )
+        .bind(gpu_cluster_id)
+        .bind(key)
+        .bind(value)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_variable(pool: &Pool<Sqlite>, gpu_cluster_id: i64, key: &str) -> Result<()> {
+        sqlx::query("DELETE FROM gpu_cluster_variables WHERE gpu_cluster_id = ? AND key = ?")
+            .bind(gpu_cluster_id)
+            .bind(key)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    // ========== Device Assignment ==========
+
+    /// Assign (`Some`) or unassign (`None`) a device
+    pub async fn assign_device(pool: &Pool<Sqlite>, device_id: i64, gpu_cluster_id: Option<i64>) -> Result<()> {
+        let result = sqlx::query("UPDATE devices SET gpu_cluster_id = ?, updated_at = ? WHERE id = ?")
+            .bind(gpu_cluster_id)
+            .bind(Utc::now())
+            .bind(device_id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Device", &device_id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Replace the cluster's devices: the listed devices are assigned, every other device it
+    /// had is unassigned
+    pub async fn set_devices(pool: &Pool<Sqlite>, gpu_cluster_id: i64, device_ids: &[i64]) -> Result<()> {
+        let now = Utc::now();
+        let mut tx = pool.begin().await?;
+        sqlx::query("UPDATE devices SET gpu_cluster_id = NULL, updated_at = ? WHERE gpu_cluster_id = ?")
+            .bind(now)
+            .bind(gpu_cluster_id)
+            .execute(&mut *tx)
+            .await?;
+        for device_id in device_ids {
+            let result = sqlx::query("UPDATE devices SET gpu_cluster_id = ?, updated_at = ? WHERE id = ?")
+                .bind(gpu_cluster_id)
+                .bind(now)
+                .bind(device_id)
+                .execute(&mut *tx)
+                .await?;
+            if result.rows_affected() == 0 {
+                return Err(super::NotFoundError::new("Device", &device_id.to_string()).into());
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
 }
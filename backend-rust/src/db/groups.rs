@@ -11,6 +11,7 @@ fn map_group_row(row: &SqliteRow) -> Group {
         description: super::row_helpers::none_if_empty(row.get("description")),
         parent_id: row.get("parent_id"),
         precedence: row.get("precedence"),
+        credential_id: row.try_get("credential_id").ok().flatten(),
         device_count: row.try_get("device_count").ok(),
         child_count: row.try_get("child_count").ok(),
         created_at: row.get("created_at"),
@@ -30,7 +31,7 @@ fn map_group_variable_row(row: &SqliteRow) -> GroupVariable {
 }
 
 const SELECT_GROUP: &str = r#"
-    SELECT g.id, g.name, g.description, g.parent_id, g.precedence,
+    SELECT g.id, g.name, g.description, g.parent_id, g.precedence, g.credential_id,
            g.created_at, g.updated_at,
            CASE WHEN g.id = 1 THEN (SELECT COUNT(*) FROM devices)
            ELSE COALESCE((
@@ -83,13 +84,14 @@ impl GroupRepo {
     pub async fn create(pool: &Pool<Sqlite>, req: &CreateGroupRequest) -> Result<Group> {
         let now = Utc::now();
         let result = sqlx::query(
-            r#"INSERT INTO groups (name, description, parent_id, precedence, created_at, updated_at)
-               VALUES (?, ?, ?, ?, ?, ?)"#,
+            r#"INSERT INTO groups (name, description, parent_id, precedence, credential_id, created_at, updated_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?)"#,
         )
         .bind(&req.name)
         .bind(req.description.as_deref().unwrap_or(""))
         .bind(&req.parent_id)
         .bind(req.precedence)
+        .bind(req.credential_id)
         .bind(now)
         .bind(now)
         .execute(pool)
@@ -104,13 +106,14 @@ impl GroupRepo {
     pub async fn update(pool: &Pool<Sqlite>, id: i64, req: &CreateGroupRequest) -> Result<Group> {
         let now = Utc::now();
         let result = sqlx::query(
-            r#"UPDATE groups SET name = ?, description = ?, parent_id = ?, precedence = ?, updated_at = ?
+            r#"UPDATE groups SET name = ?, description = ?, parent_id = ?, precedence = ?, credential_id = ?, updated_at = ?
                WHERE id = ?"#,
         )
         .bind(&req.name)
         .bind(req.description.as_deref().unwrap_or(""))
         .bind(&req.parent_id)
         .bind(req.precedence)
+        .bind(req.credential_id)
         .bind(now)
         .bind(id)
         .execute(pool)
@@ -190,6 +193,14 @@ impl GroupRepo {
         Ok(rows.iter().map(|r| r.get::<i64, _>("device_id")).collect())
     }
 
+    /// Every direct (device_id, group_id) membership
+    pub async fn list_memberships(pool: &Pool<Sqlite>) -> Result<Vec<(i64, i64)>> {
+        let rows = sqlx::query("SELECT device_id, group_id FROM device_group_members")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(|r| (r.get("device_id"), r.get("group_id"))).collect())
+    }
+
     pub async fn list_device_groups(pool: &Pool<Sqlite>, device_id: i64) -> Result<Vec<Group>> {
         let rows = sqlx::query(&format!(
             "{} WHERE g.id IN (SELECT group_id FROM device_group_members WHERE device_id = ?) ORDER BY g.precedence ASC",
@@ -318,7 +329,7 @@ impl GroupRepo {
     /// Load all groups (for resolution algorithm)
     pub async fn list_all_raw(pool: &Pool<Sqlite>) -> Result<Vec<Group>> {
         let rows = sqlx::query(
-            "SELECT id, name, description, parent_id, precedence, created_at, updated_at FROM groups ORDER BY precedence ASC",
+            "SELECT id, name, description, parent_id, precedence, credential_id, created_at, updated_at FROM groups ORDER BY precedence ASC",
         )
         .fetch_all(pool)
         .await?;
@@ -328,6 +339,7 @@ impl GroupRepo {
             description: super::row_helpers::none_if_empty(r.get("description")),
             parent_id: r.get("parent_id"),
             precedence: r.get("precedence"),
+            credential_id: r.get("credential_id"),
             device_count: None,
             child_count: None,
             created_at: r.get("created_at"),
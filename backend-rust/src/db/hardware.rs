@@ -0,0 +1,188 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_device_hardware_row(row: &SqliteRow) -> DeviceHardware {
+    DeviceHardware {
+        id: row.get("id"),
+        device_id: row.get("device_id"),
+        component_type: row.get("component_type"),
+        name: row.get("name"),
+        part_number: row.get("part_number"),
+        serial_number: row.get("serial_number"),
+        description: row.get("description"),
+        collected_at: row.get("collected_at"),
+    }
+}
+
+fn map_serial_mismatch_row(row: &SqliteRow) -> SerialMismatch {
+    SerialMismatch {
+        id: row.get("id"),
+        device_id: row.get("device_id"),
+        hostname: row.get("hostname"),
+        recorded_serial: row.get("recorded_serial"),
+        observed_serial: row.get("observed_serial"),
+        detected_at: row.get("detected_at"),
+        resolved_at: row.get("resolved_at"),
+        resolved_by: row.get("resolved_by"),
+    }
+}
+
+const SELECT_MISMATCH: &str = r#"
+    SELECT m.*, d.hostname
+    FROM hardware_serial_mismatches m
+    JOIN devices d ON d.id = m.device_id
+"#;
+
+pub struct HardwareRepo;
+
+impl HardwareRepo {
+    pub async fn list_for_device(pool: &Pool<Sqlite>, device_id: i64) -> Result<Vec<DeviceHardware>> {
+        let rows = sqlx::query("SELECT * FROM device_hardware WHERE device_id = ? ORDER BY component_type, name")
+            .bind(device_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_device_hardware_row).collect())
+    }
+
+    pub async fn list_all(pool: &Pool<Sqlite>) -> Result<Vec<DeviceHardware>> {
+        let rows = sqlx::query("SELECT * FROM device_hardware ORDER BY device_id, component_type, name")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_device_hardware_row).collect())
+    }
+
+    /// Replace a device's hardware inventory with freshly collected entries.
+    /// Returns the number of stored and removed components.
+    pub async fn replace_for_device(
+        pool: &Pool<Sqlite>,
+        device_id: i64,
+        entries: &[HardwareInventoryEntry],
+    ) -> Result<(usize, usize)> {
+        let now = Utc::now();
+        let mut tx = pool.begin().await?;
+
+        let existing: Vec<String> = sqlx::query_scalar("SELECT name FROM device_hardware WHERE device_id = ?")
+            .bind(device_id)
+            .fetch_all(&mut *tx)
+            .await?;
+        let mut removed = 0;
+        for name in existing.iter().filter(|name| !entries.iter().any(|e| &e.name == *name)) {
+            sqlx::query("DELETE FROM device_hardware WHERE device_id = ? AND name = ?")
+                .bind(device_id)
+                .bind(name)
+                .execute(&mut *tx)
+                .await?;
+            removed += 1;
+        }
+
+        for entry in entries {
+            sqlx::query(
+                r#"INSERT INTO device_hardware (device_id, component_type, name, part_number, serial_number, description, collected_at)
+                   VALUES (?, ?, ?, ?, ?, ?, ?)
+                   ON CONFLICT(device_id, name) DO UPDATE SET
+                       component_type = excluded.component_type,
+                       part_number = excluded.part_number,
+                       serial_number = excluded.serial_number,
+                       description = excluded.description,
+                       collected_at = excluded.collected_at"#,
+            )
+            .bind(device_id)
+            .bind(&entry.component_type)
+            .bind(&entry.name)
+            .bind(&entry.part_number)
+            .bind(&entry.serial_number)
+            .bind(&entry.description)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok((entries.len(), removed))
+    }
+
+    /// Record a serial mismatch, reusing the unresolved one for the same observed serial
+    pub async fn record_mismatch(
+        pool: &Pool<Sqlite>,
+        device_id: i64,
+        recorded_serial: &str,
+        observed_serial: &str,
+    ) -> Result<SerialMismatch> {
+        let existing: Option<i64> = sqlx::query_scalar(
+            r#"SELECT id FROM hardware_serial_mismatches
+               WHERE device_id = ? AND observed_serial = ? AND resolved_at IS NULL"#,
+        )
+        .bind(device_id)
+        .bind(observed_serial)
+        .fetch_optional(pool)
+        .await?;
+
+        let id = match existing {
+            Some(id) => id,
+            None => sqlx::query(
+                r#"INSERT INTO hardware_serial_mismatches (device_id, recorded_serial, observed_serial, detected_at)
+                   VALUES (?, ?, ?, ?)"#,
+            )
+            .bind(device_id)
+            .bind(recorded_serial)
+            .bind(observed_serial)
+            .bind(Utc::now())
+            .execute(pool)
+            .await?
+            .last_insert_rowid(),
+        };
+
+        Self::get_mismatch(pool, id)
+            .await?
+            .ok_or_else(|| super::NotFoundError::new("Serial mismatch", &id.to_string()).into())
+    }
+
+    pub async fn get_mismatch(pool: &Pool<Sqlite>, id: i64) -> Result<Option<SerialMismatch>> {
+        let row = sqlx::query(&format!("{} WHERE m.id = ?", SELECT_MISMATCH))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_serial_mismatch_row))
+    }
+
+    pub async fn list_mismatches(pool: &Pool<Sqlite>, include_resolved: bool) -> Result<Vec<SerialMismatch>> {
+        let filter = if include_resolved { "" } else { "WHERE m.resolved_at IS NULL" };
+        let rows = sqlx::query(&format!("{} {} ORDER BY m.detected_at DESC, m.id DESC", SELECT_MISMATCH, filter))
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_serial_mismatch_row).collect())
+    }
+
+    /// Accept the observed serial as the device's serial, resolving every open mismatch of the device
+    pub async fn accept_mismatch(pool: &Pool<Sqlite>, id: i64, username: &str) -> Result<SerialMismatch> {
+        let mismatch = Self::get_mismatch(pool, id)
+            .await?
+            .ok_or_else(|| super::NotFoundError::new("Serial mismatch", &id.to_string()))?;
+        let now = Utc::now();
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("UPDATE devices SET serial_number = ?, updated_at = ? WHERE id = ?")
+            .bind(&mismatch.observed_serial)
+            .bind(now)
+            .bind(mismatch.device_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"UPDATE hardware_serial_mismatches SET resolved_at = ?, resolved_by = ?
+               WHERE device_id = ? AND resolved_at IS NULL"#,
+        )
+        .bind(now)
+        .bind(username)
+        .bind(mismatch.device_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Self::get_mismatch(pool, id)
+            .await?
+            .ok_or_else(|| super::NotFoundError::new("Serial mismatch", &id.to_string()).into())
+    }
+}
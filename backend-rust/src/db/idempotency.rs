@@ -0,0 +1,100 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Row, Sqlite};
+
+use crate::models::IdempotencyReservation;
+
+/// Idempotency-Key database operations
+pub struct IdempotencyRepo;
+
+impl IdempotencyRepo {
+    /// Claim `key` for a request. Keys created before `expired_before` are forgotten, and
+    /// requests still unfinished since before `abandoned_before` are taken to have died with
+    /// the server.
+    pub async fn reserve(
+        pool: &Pool<Sqlite>,
+        username: &str,
+        key: &str,
+        request_hash: &str,
+        expired_before: DateTime<Utc>,
+        abandoned_before: DateTime<Utc>,
+    ) -> Result<IdempotencyReservation> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE created_at < ? OR (status_code IS NULL AND created_at < ?)")
+            .bind(expired_before)
+            .bind(abandoned_before)
+            .execute(pool)
+            .await?;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO idempotency_keys (username, idempotency_key, request_hash, created_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(username, idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(username)
+        .bind(key)
+        .bind(request_hash)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        if inserted.rows_affected() == 1 {
+            return Ok(IdempotencyReservation::Reserved);
+        }
+
+        let row = sqlx::query(
+            "SELECT request_hash, status_code, content_type, response_body FROM idempotency_keys WHERE username = ? AND idempotency_key = ?",
+        )
+        .bind(username)
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+        // A row released between the insert and this read belongs to a request that just failed
+        let Some(row) = row else {
+            return Ok(IdempotencyReservation::InProgress);
+        };
+        if row.get::<String, _>("request_hash") != request_hash {
+            return Ok(IdempotencyReservation::Mismatch);
+        }
+        Ok(match row.get::<Option<i64>, _>("status_code") {
+            Some(status_code) => IdempotencyReservation::Completed {
+                status_code: status_code as u16,
+                content_type: row.get("content_type"),
+                body: row.get::<Option<Vec<u8>>, _>("response_body").unwrap_or_default(),
+            },
+            None => IdempotencyReservation::InProgress,
+        })
+    }
+
+    /// Store the response of the request holding `key`
+    pub async fn complete(
+        pool: &Pool<Sqlite>,
+        username: &str,
+        key: &str,
+        status_code: u16,
+        content_type: &str,
+        body: &[u8],
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE idempotency_keys SET status_code = ?, content_type = ?, response_body = ? WHERE username = ? AND idempotency_key = ?",
+        )
+        .bind(status_code as i64)
+        .bind(content_type)
+        .bind(body)
+        .bind(username)
+        .bind(key)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Free `key` after its request failed, so a retry runs again
+    pub async fn release(pool: &Pool<Sqlite>, username: &str, key: &str) -> Result<()> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE username = ? AND idempotency_key = ? AND status_code IS NULL")
+            .bind(username)
+            .bind(key)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::Result;
+use std::collections::BTreeSet;
+use sqlx::{Pool, Sqlite};
+
+use crate::models::*;
+
+/// A reference from `table.column` to `references.id` that may have been left dangling by
+/// deletes made before foreign keys were enforced, or by direct database edits.
+struct IntegrityCheck {
+    name: &'static str,
+    description: &'static str,
+    table: &'static str,
+    column: &'static str,
+    references: &'static str,
+    action: &'static str,
+}
+
+const CHECKS: &[IntegrityCheck] = &[
+    IntegrityCheck {
+        name: "device_variables_device",
+        description: "variables of deleted devices",
+        table: "device_variables",
+        column: "device_id",
+        references: "devices",
+        action: integrity_action::DELETE,
+    },
+    IntegrityCheck {
+        name: "group_variables_group",
+        description: "variables of deleted groups",
+        table: "group_variables",
+        column: "group_id",
+        references: "groups",
+        action: integrity_action::DELETE,
+    },
+    IntegrityCheck {
+        name: "group_members_device",
+        description: "group memberships of deleted devices",
+        table: "device_group_members",
+        column: "device_id",
+        references: "devices",
+        action: integrity_action::DELETE,
+    },
+    IntegrityCheck {
+        name: "group_members_group",
+        description: "group memberships with missing groups",
+        table: "device_group_members",
+        column: "group_id",
+        references: "groups",
+        action: integrity_action::DELETE,
+    },
+    IntegrityCheck {
+        name: "groups_parent",
+        description: "groups whose parent group is gone",
+        table: "groups",
+        column: "parent_id",
+        references: "groups",
+        action: integrity_action::NULLIFY,
+    },
+    IntegrityCheck {
+        name: "jobs_device",
+        description: "jobs referencing deleted devices",
+        table: "jobs",
+        column: "device_id",
+        references: "devices",
+        action: integrity_action::DELETE,
+    },
+    IntegrityCheck {
+        name: "backups_device",
+        description: "config backups of deleted devices",
+        table: "backups",
+        column: "device_id",
+        references: "devices",
+        action: integrity_action::DELETE,
+    },
+    IntegrityCheck {
+        name: "port_assignments_device",
+        description: "port assignments of deleted devices",
+        table: "device_port_assignments",
+        column: "device_id",
+        references: "devices",
+        action: integrity_action::DELETE,
+    },
+    IntegrityCheck {
+        name: "port_assignments_remote_device",
+        description: "port assignments whose remote device is gone",
+        table: "device_port_assignments",
+        column: "remote_device_id",
+        references: "devices",
+        action: integrity_action::NULLIFY,
+    },
+    IntegrityCheck {
+        name: "ip_addresses_device",
+        description: "IPAM addresses assigned to deleted devices",
+        table: "ipam_ip_addresses",
+        column: "device_id",
+        references: "devices",
+        action: integrity_action::DELETE,
+    },
+    IntegrityCheck {
+        name: "interfaces_device",
+        description: "collected interfaces of deleted devices",
+        table: "device_interfaces",
+        column: "device_id",
+        references: "devices",
+        action: integrity_action::DELETE,
+    },
+    IntegrityCheck {
+        name: "hardware_device",
+        description: "hardware inventory of deleted devices",
+        table: "device_hardware",
+        column: "device_id",
+        references: "devices",
+        action: integrity_action::DELETE,
+    },
+    IntegrityCheck {
+        name: "devices_credential",
+        description: "devices bound to deleted credentials",
+        table: "devices",
+        column: "credential_id",
+        references: "credentials",
+        action: integrity_action::NULLIFY,
+    },
+    IntegrityCheck {
+        name: "templates_vendor",
+        description: "templates of deleted vendors",
+        table: "templates",
+        column: "vendor_id",
+        references: "vendors",
+        action: integrity_action::NULLIFY,
+    },
+];
+
+const MISSING_ID_SAMPLE: i64 = 10;
+
+impl IntegrityCheck {
+    /// Rows whose reference is set but points nowhere. Device-less jobs use device_id 0.
+    fn orphan_condition(&self) -> String {
+        format!(
+            "{col} IS NOT NULL AND {col} != 0 AND {col} NOT IN (SELECT id FROM {parent})",
+            col = self.column,
+            parent = self.references,
+        )
+    }
+}
+
+/// Orphaned-row detection and cleanup
+pub struct IntegrityRepo;
+
+impl IntegrityRepo {
+    pub fn check_count() -> usize {
+        CHECKS.len()
+    }
+
+    pub fn is_known_check(name: &str) -> bool {
+        CHECKS.iter().any(|c| c.name == name)
+    }
+
+    /// Partitioned tables are checked across their partitions too, which hold archived rows
+    pub async fn find_issues(pool: &Pool<Sqlite>) -> Result<Vec<IntegrityIssue>> {
+        let mut conn = pool.acquire().await?;
+        let mut issues = Vec::new();
+        for check in CHECKS {
+            let condition = check.orphan_condition();
+            let mut count = 0;
+            let mut missing_ids = BTreeSet::new();
+            for table in super::partitions::with_partitions(&mut conn, check.table).await? {
+                let (table_count,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {} WHERE {}", table, condition))
+                    .fetch_one(&mut *conn)
+                    .await?;
+                if table_count == 0 {
+                    continue;
+                }
+                count += table_count;
+                let ids: Vec<(i64,)> = sqlx::query_as(&format!(
+                    "SELECT DISTINCT {} FROM {} WHERE {} ORDER BY 1 LIMIT ?",
+                    check.column, table, condition
+                ))
+                .bind(MISSING_ID_SAMPLE)
+                .fetch_all(&mut *conn)
+                .await?;
+                missing_ids.extend(ids.into_iter().map(|(id,)| id));
+            }
+            if count == 0 {
+                continue;
+            }
+            issues.push(IntegrityIssue {
+                check: check.name.to_string(),
+                description: check.description.to_string(),
+                table: check.table.to_string(),
+                column: check.column.to_string(),
+                references: check.references.to_string(),
+                action: check.action.to_string(),
+                count,
+                missing_ids: missing_ids.into_iter().take(MISSING_ID_SAMPLE as usize).collect(),
+            });
+        }
+        Ok(issues)
+    }
+
+    /// Repair the named checks (all when empty) in one transaction
+    pub async fn cleanup(pool: &Pool<Sqlite>, names: &[String]) -> Result<Vec<IntegrityCleanupResult>> {
+        let mut tx = pool.begin().await?;
+        let mut results = Vec::new();
+        for check in CHECKS.iter().filter(|c| names.is_empty() || names.iter().any(|n| n == c.name)) {
+            let condition = check.orphan_condition();
+            let mut rows_affected = 0;
+            for table in super::partitions::with_partitions(&mut tx, check.table).await? {
+                let sql = if check.action == integrity_action::NULLIFY {
+                    format!("UPDATE {} SET {} = NULL WHERE {}", table, check.column, condition)
+                } else {
+                    format!("DELETE FROM {} WHERE {}", table, condition)
+                };
+                rows_affected += sqlx::query(&sql).execute(&mut *tx).await?.rows_affected();
+            }
+            if rows_affected > 0 {
+                results.push(IntegrityCleanupResult {
+                    check: check.name.to_string(),
+                    action: check.action.to_string(),
+                    rows_affected,
+                });
+            }
+        }
+        tx.commit().await?;
+        Ok(results)
+    }
+}
@@ -3,6 +3,11 @@ use sqlx::{Row, sqlite::SqliteRow};
 use crate::models::*;
 use crate::db::row_helpers::none_if_empty;
 
+/// VRF scope key: prefixes and addresses without a VRF (NULL or 0) share the global scope 0
+pub(super) fn vrf_scope(vrf_id: Option<i64>) -> i64 {
+    vrf_id.unwrap_or(0)
+}
+
 pub(super) fn csv_to_vec(csv: Option<String>) -> Vec<String> {
     match csv {
         Some(s) if !s.is_empty() => s.split(',').map(|s| s.to_string()).collect(),
@@ -152,6 +157,8 @@ pub(super) fn map_ip_address_row(row: &SqliteRow) -> IpamIpAddress {
         interface_name: none_if_empty(row.get("interface_name")),
         vrf_id: row.try_get::<Option<i64>, _>("vrf_id").ok().flatten(),
         vrf_name: row.try_get("vrf_name").ok().and_then(|v: Option<String>| v),
+        last_seen: row.try_get("last_seen").ok().flatten(),
+        last_seen_mac: none_if_empty(row.try_get("last_seen_mac").ok().flatten()),
         created_at: row.get("created_at"),
         updated_at: row.get("updated_at"),
     }
@@ -180,3 +187,16 @@ pub(super) fn map_tag_row(row: &SqliteRow) -> IpamTag {
         created_at: row.get("created_at"),
     }
 }
+
+pub(super) fn map_discovered_address_row(row: &SqliteRow) -> IpamDiscoveredAddress {
+    IpamDiscoveredAddress {
+        id: row.get("id"),
+        address: row.get("address"),
+        mac: row.get("mac"),
+        device_id: row.get("device_id"),
+        device_hostname: row.try_get("device_hostname").ok().flatten(),
+        interface_name: none_if_empty(row.get("interface_name")),
+        first_seen: row.get("first_seen"),
+        last_seen: row.get("last_seen"),
+    }
+}
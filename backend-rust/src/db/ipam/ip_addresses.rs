@@ -4,7 +4,7 @@ use sqlx::{Pool, Row, Sqlite};
 
 use crate::models::*;
 use crate::utils;
-use super::helpers::map_ip_address_row;
+use super::helpers::{map_ip_address_row, vrf_scope};
 use super::prefixes::IpamPrefixRepo;
 
 const SELECT_IP_ADDRESS: &str = r#"
@@ -29,6 +29,13 @@ impl IpamIpAddressRepo {
         Ok(rows.iter().map(map_ip_address_row).collect())
     }
 
+    /// List IP addresses in one VRF; 0 selects addresses without a VRF
+    pub async fn list_by_vrf(pool: &Pool<Sqlite>, vrf_id: i64) -> Result<Vec<IpamIpAddress>> {
+        let rows = sqlx::query(&format!("{} WHERE COALESCE(ip.vrf_id, 0) = ? ORDER BY ip.address_int", SELECT_IP_ADDRESS))
+            .bind(vrf_id).fetch_all(pool).await?;
+        Ok(rows.iter().map(map_ip_address_row).collect())
+    }
+
     pub async fn list_by_prefix(pool: &Pool<Sqlite>, prefix_id: i64) -> Result<Vec<IpamIpAddress>> {
         let rows = sqlx::query(&format!("{} WHERE ip.prefix_id = ? ORDER BY ip.address_int", SELECT_IP_ADDRESS))
             .bind(prefix_id).fetch_all(pool).await?;
@@ -58,6 +65,10 @@ impl IpamIpAddressRepo {
             ));
         }
 
+        // Addresses inherit their prefix's VRF and are unique within it
+        let vrf_id = req.vrf_id.or(prefix.vrf_id);
+        Self::check_duplicate(pool, addr_str, addr_int, vrf_id, None).await?;
+
         let now = Utc::now();
         let result = sqlx::query(
             r#"INSERT INTO ipam_ip_addresses (address, address_int, prefix_id, description,
@@ -72,7 +83,7 @@ impl IpamIpAddressRepo {
         .bind(req.dns_name.as_deref().unwrap_or(""))
         .bind(req.device_id)
         .bind(req.interface_name.as_deref().unwrap_or(""))
-        .bind(req.vrf_id)
+        .bind(vrf_id)
         .bind(now)
         .bind(now)
         .execute(pool).await?;
@@ -93,6 +104,12 @@ impl IpamIpAddressRepo {
         let addr_str = req.address.trim_end_matches("/32");
         let addr_int = utils::parse_ipv4_to_u32(addr_str)
             .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let prefix = IpamPrefixRepo::get(pool, req.prefix_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Prefix not found: {}", req.prefix_id))?;
+        let vrf_id = req.vrf_id.or(prefix.vrf_id);
+        Self::check_duplicate(pool, addr_str, addr_int, vrf_id, Some(id)).await?;
+
         let now = Utc::now();
         let result = sqlx::query(
             r#"UPDATE ipam_ip_addresses SET address = ?, address_int = ?, prefix_id = ?,
@@ -107,7 +124,7 @@ impl IpamIpAddressRepo {
         .bind(req.dns_name.as_deref().unwrap_or(""))
         .bind(req.device_id)
         .bind(req.interface_name.as_deref().unwrap_or(""))
-        .bind(req.vrf_id)
+        .bind(vrf_id)
         .bind(now)
         .bind(id)
         .execute(pool).await?;
@@ -127,6 +144,23 @@ impl IpamIpAddressRepo {
         Self::get(pool, id).await?.context("IP address not found after update")
     }
 
+    async fn check_duplicate(pool: &Pool<Sqlite>, addr_str: &str, addr_int: u32, vrf_id: Option<i64>, exclude_id: Option<i64>) -> Result<()> {
+        let existing: Option<i64> = sqlx::query_scalar(
+            "SELECT id FROM ipam_ip_addresses WHERE address_int = ? AND COALESCE(vrf_id, 0) = ? AND id != ?"
+        )
+        .bind(addr_int as i64)
+        .bind(vrf_scope(vrf_id))
+        .bind(exclude_id.unwrap_or(0))
+        .fetch_optional(pool).await?;
+        if let Some(existing_id) = existing {
+            return Err(crate::db::ConflictError::new(format!(
+                "Duplicate IP address: {} already exists (id={})",
+                addr_str, existing_id
+            )).into());
+        }
+        Ok(())
+    }
+
     pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
         let result = sqlx::query("DELETE FROM ipam_ip_addresses WHERE id = ?").bind(id).execute(pool).await?;
         if result.rows_affected() == 0 {
@@ -147,9 +181,17 @@ impl IpamIpAddressRepo {
         let pbcast = prefix.broadcast_int as u32;
         let plen = prefix.prefix_length as u8;
 
+        // Addresses used in the prefix's VRF, whichever prefix they were recorded under
         let rows = sqlx::query(
-            "SELECT address_int FROM ipam_ip_addresses WHERE prefix_id = ? ORDER BY address_int"
-        ).bind(prefix_id).fetch_all(pool).await?;
+            r#"SELECT address_int FROM ipam_ip_addresses
+               WHERE prefix_id = ? OR (COALESCE(vrf_id, 0) = ? AND address_int BETWEEN ? AND ?)
+               ORDER BY address_int"#
+        )
+        .bind(prefix_id)
+        .bind(vrf_scope(prefix.vrf_id))
+        .bind(prefix.network_int)
+        .bind(prefix.broadcast_int)
+        .fetch_all(pool).await?;
 
         let allocated: Vec<u32> = rows.iter()
             .map(|r| r.get::<i64, _>("address_int") as u32)
@@ -176,3 +218,29 @@ impl IpamIpAddressRepo {
         Self::create(pool, &create_req).await
     }
 }
+
+// ========== Reverse DNS Serial Repo ==========
+
+pub struct IpamReverseDnsSerialRepo;
+
+impl IpamReverseDnsSerialRepo {
+    /// Last serial and fingerprint exported for a VRF; 0 selects addresses without a VRF
+    pub async fn get(pool: &Pool<Sqlite>, vrf_id: i64) -> Result<Option<(u32, String)>> {
+        let row = sqlx::query("SELECT serial, fingerprint FROM ipam_reverse_dns_serials WHERE vrf_id = ?")
+            .bind(vrf_id).fetch_optional(pool).await?;
+        Ok(row.map(|r| (r.get::<i64, _>("serial") as u32, r.get("fingerprint"))))
+    }
+
+    /// Record a newly issued serial; an older serial never overwrites a newer one
+    pub async fn save(pool: &Pool<Sqlite>, vrf_id: i64, serial: u32, fingerprint: &str) -> Result<()> {
+        sqlx::query(
+            r#"INSERT INTO ipam_reverse_dns_serials (vrf_id, serial, fingerprint, updated_at) VALUES (?, ?, ?, ?)
+               ON CONFLICT(vrf_id) DO UPDATE SET serial = excluded.serial, fingerprint = excluded.fingerprint,
+                   updated_at = excluded.updated_at
+               WHERE excluded.serial > ipam_reverse_dns_serials.serial"#
+        )
+            .bind(vrf_id).bind(serial as i64).bind(fingerprint).bind(Utc::now())
+            .execute(pool).await?;
+        Ok(())
+    }
+}
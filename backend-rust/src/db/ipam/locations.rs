@@ -308,6 +308,20 @@ impl IpamRackRepo {
         Ok(rows.iter().map(map_rack_row).collect())
     }
 
+    /// Racks in a datacenter's halls, by hall, row and rack name
+    pub async fn list_by_datacenter(pool: &Pool<Sqlite>, datacenter_id: i64) -> Result<Vec<IpamRack>> {
+        let rows = sqlx::query(
+            r#"SELECT rk.*, r.name as row_name,
+                      COALESCE((SELECT COUNT(*) FROM devices WHERE rack_id = rk.id), 0) as device_count
+               FROM ipam_racks rk
+               JOIN ipam_rows r ON rk.row_id = r.id
+               JOIN ipam_halls h ON r.hall_id = h.id
+               WHERE h.datacenter_id = ?
+               ORDER BY h.name, r.name, rk.name"#
+        ).bind(datacenter_id).fetch_all(pool).await?;
+        Ok(rows.iter().map(map_rack_row).collect())
+    }
+
     pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<IpamRack>> {
         let row = sqlx::query(
             r#"SELECT rk.*, r.name as row_name,
@@ -1,10 +1,12 @@
 mod helpers;
 mod ip_addresses;
 mod locations;
+mod neighbors;
 mod prefixes;
 mod vrfs;
 
 pub use ip_addresses::*;
 pub use locations::*;
+pub use neighbors::*;
 pub use prefixes::*;
 pub use vrfs::*;
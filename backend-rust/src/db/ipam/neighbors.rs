@@ -0,0 +1,85 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::models::*;
+use crate::utils;
+use super::helpers::map_discovered_address_row;
+
+const SELECT_DISCOVERED: &str = r#"
+    SELECT da.*, d.hostname as device_hostname
+    FROM ipam_discovered_addresses da
+    LEFT JOIN devices d ON da.device_id = d.id
+"#;
+
+pub struct IpamNeighborRepo;
+
+impl IpamNeighborRepo {
+    pub async fn list_discovered(pool: &Pool<Sqlite>) -> Result<Vec<IpamDiscoveredAddress>> {
+        let rows = sqlx::query(&format!("{} ORDER BY da.last_seen DESC, da.address", SELECT_DISCOVERED))
+            .fetch_all(pool).await?;
+        Ok(rows.iter().map(map_discovered_address_row).collect())
+    }
+
+    pub async fn delete_discovered(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM ipam_discovered_addresses WHERE id = ?")
+            .bind(id).execute(pool).await?;
+        if result.rows_affected() == 0 {
+            return Err(crate::db::NotFoundError::new("Discovered address", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Match neighbor entries collected from a device against IPAM. Managed IPv4 addresses get their
+    /// last_seen/last_seen_mac refreshed (in every VRF holding the address, since the entry doesn't
+    /// say which one it came from); anything else is recorded as discovered in the network.
+    pub async fn reconcile(pool: &Pool<Sqlite>, device_id: i64, entries: &[NeighborEntry]) -> Result<NeighborReconcileSummary> {
+        let now = Utc::now();
+        let mut summary = NeighborReconcileSummary { entries: entries.len(), ..Default::default() };
+        let mut tx = pool.begin().await?;
+
+        for entry in entries {
+            let managed = match utils::parse_ipv4_to_u32(&entry.address) {
+                Ok(addr_int) => {
+                    sqlx::query("UPDATE ipam_ip_addresses SET last_seen = ?, last_seen_mac = ? WHERE address_int = ?")
+                        .bind(now)
+                        .bind(&entry.mac)
+                        .bind(addr_int as i64)
+                        .execute(&mut *tx).await?
+                        .rows_affected() > 0
+                }
+                // IPAM tracks IPv4 only, so ND entries are always discoveries
+                Err(_) => false,
+            };
+
+            if managed {
+                summary.managed += 1;
+                sqlx::query("DELETE FROM ipam_discovered_addresses WHERE address = ?")
+                    .bind(&entry.address)
+                    .execute(&mut *tx).await?;
+                continue;
+            }
+
+            summary.discovered += 1;
+            sqlx::query(
+                r#"INSERT INTO ipam_discovered_addresses (address, mac, device_id, interface_name, first_seen, last_seen)
+                   VALUES (?, ?, ?, ?, ?, ?)
+                   ON CONFLICT(address) DO UPDATE SET
+                       mac = excluded.mac,
+                       device_id = excluded.device_id,
+                       interface_name = excluded.interface_name,
+                       last_seen = excluded.last_seen"#
+            )
+            .bind(&entry.address)
+            .bind(&entry.mac)
+            .bind(device_id)
+            .bind(entry.interface.as_deref().unwrap_or(""))
+            .bind(now)
+            .bind(now)
+            .execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
+}
@@ -4,7 +4,7 @@ use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
 
 use crate::models::*;
 use crate::utils;
-use super::helpers::map_prefix_row;
+use super::helpers::{map_prefix_row, vrf_scope};
 
 const SELECT_PREFIX: &str = r#"
     SELECT p.*,
@@ -30,12 +30,80 @@ impl IpamPrefixRepo {
         Ok(rows.iter().map(map_prefix_row).collect())
     }
 
+    /// List prefixes in one VRF; 0 selects prefixes without a VRF
+    pub async fn list_by_vrf(pool: &Pool<Sqlite>, vrf_id: i64) -> Result<Vec<IpamPrefix>> {
+        let rows = sqlx::query(&format!("{} WHERE COALESCE(p.vrf_id, 0) = ? ORDER BY p.network_int, p.prefix_length", SELECT_PREFIX))
+            .bind(vrf_id)
+            .fetch_all(pool).await?;
+        Ok(rows.iter().map(map_prefix_row).collect())
+    }
+
     pub async fn list_supernets(pool: &Pool<Sqlite>) -> Result<Vec<IpamPrefix>> {
         let rows = sqlx::query(&format!("{} WHERE p.is_supernet = 1 ORDER BY p.network_int", SELECT_PREFIX))
             .fetch_all(pool).await?;
         Ok(rows.iter().map(map_prefix_row).collect())
     }
 
+    /// Load the prefix hierarchy below `root` (or below all top-level prefixes) in one
+    /// recursive query, descending at most `max_depth` levels
+    pub async fn tree(pool: &Pool<Sqlite>, root: Option<i64>, max_depth: i32) -> Result<Vec<IpamPrefixTreeNode>> {
+        let rows = sqlx::query(&format!(
+            r#"WITH RECURSIVE tree(id, depth) AS (
+                   SELECT id, 0 FROM ipam_prefixes
+                   WHERE (?1 IS NULL AND parent_id IS NULL) OR id = ?1
+                   UNION ALL
+                   SELECT c.id, t.depth + 1 FROM ipam_prefixes c
+                   JOIN tree t ON c.parent_id = t.id
+                   WHERE t.depth < ?2
+               )
+               SELECT x.*, t.depth,
+                      COALESCE((SELECT SUM(c.broadcast_int - c.network_int + 1) FROM ipam_prefixes c
+                                WHERE c.parent_id = x.id), 0) as child_address_count
+               FROM ({}) x
+               JOIN tree t ON t.id = x.id
+               ORDER BY x.network_int, x.prefix_length"#,
+            SELECT_PREFIX
+        ))
+        .bind(root)
+        .bind(max_depth)
+        .fetch_all(pool)
+        .await?;
+
+        let mut roots = Vec::new();
+        let mut children: std::collections::HashMap<i64, Vec<IpamPrefixTreeNode>> = std::collections::HashMap::new();
+        for row in &rows {
+            let mut prefix = map_prefix_row(row);
+            let depth: i32 = row.get("depth");
+            let size = (prefix.broadcast_int - prefix.network_int + 1) as f64;
+            let used = row.get::<i64, _>("child_address_count") + prefix.ip_address_count.unwrap_or(0) as i64;
+            prefix.utilization = Some(((used as f64 / size) * 100.0).min(100.0));
+
+            let node = IpamPrefixTreeNode {
+                children_truncated: depth >= max_depth && prefix.child_prefix_count.unwrap_or(0) > 0,
+                prefix,
+                depth,
+                children: Vec::new(),
+            };
+            match node.prefix.parent_id {
+                Some(parent_id) if depth > 0 => children.entry(parent_id).or_default().push(node),
+                _ => roots.push(node),
+            }
+        }
+
+        fn attach(node: &mut IpamPrefixTreeNode, children: &mut std::collections::HashMap<i64, Vec<IpamPrefixTreeNode>>) {
+            if let Some(mut kids) = children.remove(&node.prefix.id) {
+                for kid in &mut kids {
+                    attach(kid, children);
+                }
+                node.children = kids;
+            }
+        }
+        for node in &mut roots {
+            attach(node, &mut children);
+        }
+        Ok(roots)
+    }
+
     pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<IpamPrefix>> {
         let row = sqlx::query(&format!("{} WHERE p.id = ?", SELECT_PREFIX))
             .bind(id).fetch_optional(pool).await?;
@@ -45,18 +113,11 @@ impl IpamPrefixRepo {
     pub async fn find_by_cidr(pool: &Pool<Sqlite>, cidr: &str, vrf_id: Option<i64>) -> Result<Option<IpamPrefix>> {
         let (network, broadcast, _) = utils::parse_cidr(cidr)
             .map_err(|e| anyhow::anyhow!("{}", e))?;
-        let row = if let Some(vid) = vrf_id {
-            sqlx::query(&format!("{} WHERE p.network_int = ? AND p.broadcast_int = ? AND p.vrf_id = ?", SELECT_PREFIX))
-                .bind(network as i64)
-                .bind(broadcast as i64)
-                .bind(vid)
-                .fetch_optional(pool).await?
-        } else {
-            sqlx::query(&format!("{} WHERE p.network_int = ? AND p.broadcast_int = ? AND (p.vrf_id IS NULL OR p.vrf_id = 0)", SELECT_PREFIX))
-                .bind(network as i64)
-                .bind(broadcast as i64)
-                .fetch_optional(pool).await?
-        };
+        let row = sqlx::query(&format!("{} WHERE p.network_int = ? AND p.broadcast_int = ? AND COALESCE(p.vrf_id, 0) = ?", SELECT_PREFIX))
+            .bind(network as i64)
+            .bind(broadcast as i64)
+            .bind(vrf_scope(vrf_id))
+            .fetch_optional(pool).await?;
         Ok(row.as_ref().map(map_prefix_row))
     }
 
@@ -65,46 +126,11 @@ impl IpamPrefixRepo {
         let (network, broadcast, prefix_len) = utils::parse_cidr(&req.prefix)
             .map_err(|e| anyhow::anyhow!("{}", e))?;
 
-        // Check for duplicate CIDR within same VRF (NULL vrf_id = global)
-        let existing: Option<SqliteRow> = if let Some(vid) = req.vrf_id {
-            sqlx::query(
-                "SELECT id, prefix FROM ipam_prefixes WHERE network_int = ? AND broadcast_int = ? AND vrf_id = ?"
-            )
-            .bind(network as i64)
-            .bind(broadcast as i64)
-            .bind(vid)
-            .fetch_optional(pool).await?
-        } else {
-            sqlx::query(
-                "SELECT id, prefix FROM ipam_prefixes WHERE network_int = ? AND broadcast_int = ? AND (vrf_id IS NULL OR vrf_id = 0)"
-            )
-            .bind(network as i64)
-            .bind(broadcast as i64)
-            .fetch_optional(pool).await?
-        };
-
-        if let Some(row) = existing {
-            let existing_id: i64 = row.get("id");
-            let existing_prefix: String = row.get("prefix");
-            return Err(anyhow::anyhow!(
-                "Duplicate prefix: {} already exists (id={})",
-                existing_prefix, existing_id
-            ));
-        }
+        // Validate parent containment; a prefix without a VRF inherits its parent's
+        let vrf_id = Self::check_parent(pool, req, network, broadcast).await?;
 
-        // Validate parent containment
-        if let Some(parent_id) = req.parent_id {
-            let parent = Self::get(pool, parent_id).await?
-                .ok_or_else(|| anyhow::anyhow!("Parent prefix not found: {}", parent_id))?;
-            let parent_net = parent.network_int as u32;
-            let parent_bcast = parent.broadcast_int as u32;
-            if network < parent_net || broadcast > parent_bcast {
-                return Err(anyhow::anyhow!(
-                    "Prefix {} does not fit within parent {}",
-                    req.prefix, parent.prefix
-                ));
-            }
-        }
+        // Check for duplicate CIDR within same VRF (NULL vrf_id = global)
+        Self::check_duplicate(pool, network, broadcast, vrf_id, None).await?;
 
         let now = Utc::now();
         let canonical_prefix = utils::format_cidr(network, prefix_len);
@@ -123,7 +149,7 @@ impl IpamPrefixRepo {
         .bind(req.parent_id)
         .bind(&req.datacenter_id)
         .bind(req.vlan_id)
-        .bind(&req.vrf_id)
+        .bind(vrf_id)
         .bind(now)
         .bind(now)
         .execute(pool).await?;
@@ -144,34 +170,10 @@ impl IpamPrefixRepo {
         let (network, broadcast, prefix_len) = utils::parse_cidr(&req.prefix)
             .map_err(|e| anyhow::anyhow!("{}", e))?;
 
-        // Check for duplicate CIDR within same VRF (exclude self)
-        let existing: Option<SqliteRow> = if let Some(vid) = req.vrf_id {
-            sqlx::query(
-                "SELECT id, prefix FROM ipam_prefixes WHERE network_int = ? AND broadcast_int = ? AND vrf_id = ? AND id != ?"
-            )
-            .bind(network as i64)
-            .bind(broadcast as i64)
-            .bind(vid)
-            .bind(id)
-            .fetch_optional(pool).await?
-        } else {
-            sqlx::query(
-                "SELECT id, prefix FROM ipam_prefixes WHERE network_int = ? AND broadcast_int = ? AND (vrf_id IS NULL OR vrf_id = 0) AND id != ?"
-            )
-            .bind(network as i64)
-            .bind(broadcast as i64)
-            .bind(id)
-            .fetch_optional(pool).await?
-        };
+        let vrf_id = Self::check_parent(pool, req, network, broadcast).await?;
 
-        if let Some(row) = existing {
-            let existing_id: i64 = row.get("id");
-            let existing_prefix: String = row.get("prefix");
-            return Err(anyhow::anyhow!(
-                "Duplicate prefix: {} already exists (id={})",
-                existing_prefix, existing_id
-            ));
-        }
+        // Check for duplicate CIDR within same VRF (exclude self)
+        Self::check_duplicate(pool, network, broadcast, vrf_id, Some(id)).await?;
 
         let now = Utc::now();
         let canonical_prefix = utils::format_cidr(network, prefix_len);
@@ -190,7 +192,7 @@ impl IpamPrefixRepo {
         .bind(req.parent_id)
         .bind(&req.datacenter_id)
         .bind(req.vlan_id)
-        .bind(&req.vrf_id)
+        .bind(vrf_id)
         .bind(now)
         .bind(id)
         .execute(pool).await?;
@@ -210,6 +212,55 @@ impl IpamPrefixRepo {
         Self::get(pool, id).await?.context("Prefix not found after update")
     }
 
+    /// Check that a prefix fits within its parent and shares its VRF, returning the prefix's effective VRF
+    async fn check_parent(pool: &Pool<Sqlite>, req: &CreateIpamPrefixRequest, network: u32, broadcast: u32) -> Result<Option<i64>> {
+        let parent_id = match req.parent_id {
+            Some(parent_id) => parent_id,
+            None => return Ok(req.vrf_id),
+        };
+        let parent = Self::get(pool, parent_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Parent prefix not found: {}", parent_id))?;
+        let parent_net = parent.network_int as u32;
+        let parent_bcast = parent.broadcast_int as u32;
+        if network < parent_net || broadcast > parent_bcast {
+            return Err(anyhow::anyhow!(
+                "Prefix {} does not fit within parent {}",
+                req.prefix, parent.prefix
+            ));
+        }
+
+        // A global parent may hold prefixes of any VRF; a VRF parent only its own
+        let vrf_id = req.vrf_id.or(parent.vrf_id);
+        if vrf_scope(parent.vrf_id) != 0 && vrf_scope(vrf_id) != vrf_scope(parent.vrf_id) {
+            return Err(anyhow::anyhow!(
+                "Prefix {} must be in the same VRF as its parent {}",
+                req.prefix, parent.prefix
+            ));
+        }
+        Ok(vrf_id)
+    }
+
+    async fn check_duplicate(pool: &Pool<Sqlite>, network: u32, broadcast: u32, vrf_id: Option<i64>, exclude_id: Option<i64>) -> Result<()> {
+        let existing: Option<SqliteRow> = sqlx::query(
+            "SELECT id, prefix FROM ipam_prefixes WHERE network_int = ? AND broadcast_int = ? AND COALESCE(vrf_id, 0) = ? AND id != ?"
+        )
+        .bind(network as i64)
+        .bind(broadcast as i64)
+        .bind(vrf_scope(vrf_id))
+        .bind(exclude_id.unwrap_or(0))
+        .fetch_optional(pool).await?;
+
+        if let Some(row) = existing {
+            let existing_id: i64 = row.get("id");
+            let existing_prefix: String = row.get("prefix");
+            return Err(crate::db::ConflictError::new(format!(
+                "Duplicate prefix: {} already exists (id={})",
+                existing_prefix, existing_id
+            )).into());
+        }
+        Ok(())
+    }
+
     pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
         let result = sqlx::query("DELETE FROM ipam_prefixes WHERE id = ?").bind(id).execute(pool).await?;
         if result.rows_affected() == 0 {
@@ -218,6 +269,41 @@ impl IpamPrefixRepo {
         Ok(())
     }
 
+    /// Free CIDR blocks remaining inside a prefix, given its child prefixes and IP addresses
+    pub async fn available_blocks(pool: &Pool<Sqlite>, id: i64) -> Result<IpamPrefixAvailability> {
+        let prefix = Self::get(pool, id).await?
+            .ok_or_else(|| crate::db::NotFoundError::new("Prefix", &id.to_string()))?;
+
+        let rows = sqlx::query(
+            r#"SELECT network_int, broadcast_int FROM ipam_prefixes WHERE parent_id = ?
+               UNION ALL
+               SELECT address_int, address_int FROM ipam_ip_addresses WHERE prefix_id = ?"#
+        ).bind(id).bind(id).fetch_all(pool).await?;
+        let allocated: Vec<(u32, u32)> = rows.iter()
+            .map(|r| (r.get::<i64, _>(0) as u32, r.get::<i64, _>(1) as u32))
+            .collect();
+
+        let blocks: Vec<IpamAvailableBlock> = utils::free_cidr_blocks(
+            prefix.network_int as u32,
+            prefix.broadcast_int as u32,
+            &allocated,
+        )
+        .into_iter()
+        .map(|(net, len)| IpamAvailableBlock {
+            prefix: utils::format_cidr(net, len),
+            prefix_length: len as i32,
+            size: 1u64 << (32 - len as u32),
+        })
+        .collect();
+
+        Ok(IpamPrefixAvailability {
+            prefix_id: prefix.id,
+            prefix: prefix.prefix,
+            free_addresses: blocks.iter().map(|b| b.size).sum(),
+            blocks,
+        })
+    }
+
     pub async fn next_available_prefix(
         pool: &Pool<Sqlite>,
         parent_id: i64,
@@ -229,10 +315,20 @@ impl IpamPrefixRepo {
         let parent_net = parent.network_int as u32;
         let parent_bcast = parent.broadcast_int as u32;
 
-        // Load allocated children sorted by network_int
+        // Load allocations in the parent's VRF that fall inside it: its children, plus any
+        // prefix of the same VRF not linked by parent_id. Other VRFs may reuse the space.
         let rows = sqlx::query(
-            "SELECT network_int, broadcast_int FROM ipam_prefixes WHERE parent_id = ? ORDER BY network_int"
-        ).bind(parent_id).fetch_all(pool).await?;
+            r#"SELECT network_int, broadcast_int FROM ipam_prefixes
+               WHERE id != ? AND COALESCE(vrf_id, 0) = ?
+                 AND (parent_id = ? OR (network_int >= ? AND broadcast_int <= ?))
+               ORDER BY network_int"#
+        )
+        .bind(parent_id)
+        .bind(vrf_scope(parent.vrf_id))
+        .bind(parent_id)
+        .bind(parent.network_int)
+        .bind(parent.broadcast_int)
+        .fetch_all(pool).await?;
 
         let allocated: Vec<(u32, u32)> = rows.iter()
             .map(|r| (r.get::<i64, _>("network_int") as u32, r.get::<i64, _>("broadcast_int") as u32))
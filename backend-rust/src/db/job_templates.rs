@@ -22,6 +22,8 @@ fn map_row(row: &SqliteRow) -> JobTemplate {
         target_mode: row.get("target_mode"),
         target_device_ids,
         target_group_id: row.get("target_group_id"),
+        target_saved_search_id: row.try_get("target_saved_search_id").unwrap_or(0),
+        target_tag: row.try_get::<Option<String>, _>("target_tag").ok().flatten().unwrap_or_default(),
         schedule: row.get("schedule"),
         enabled: row.get::<i32, _>("enabled") != 0,
         last_run_at: row.get("last_run_at"),
@@ -55,8 +57,9 @@ impl JobTemplateRepo {
 
         let result = sqlx::query(
             r#"INSERT INTO job_templates (name, description, job_type, command, action_id,
-                target_mode, target_device_ids, target_group_id, schedule, enabled, created_at, updated_at, credential_id)
-               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+                target_mode, target_device_ids, target_group_id, schedule, enabled, created_at, updated_at, credential_id,
+                target_saved_search_id, target_tag)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
         )
         .bind(&req.name)
         .bind(&req.description)
@@ -71,6 +74,8 @@ impl JobTemplateRepo {
         .bind(now)
         .bind(now)
         .bind(req.credential_id)
+        .bind(req.target_saved_search_id)
+        .bind(&req.target_tag)
         .execute(pool)
         .await?;
 
@@ -87,7 +92,8 @@ impl JobTemplateRepo {
         let result = sqlx::query(
             r#"UPDATE job_templates SET name = ?, description = ?, job_type = ?, command = ?,
                 action_id = ?, target_mode = ?, target_device_ids = ?, target_group_id = ?,
-                schedule = ?, enabled = ?, updated_at = ?, credential_id = ?
+                schedule = ?, enabled = ?, updated_at = ?, credential_id = ?, target_saved_search_id = ?,
+                target_tag = ?
                WHERE id = ?"#,
         )
         .bind(&req.name)
@@ -102,6 +108,8 @@ impl JobTemplateRepo {
         .bind(req.enabled as i32)
         .bind(now)
         .bind(req.credential_id)
+        .bind(req.target_saved_search_id)
+        .bind(&req.target_tag)
         .bind(id)
         .execute(pool)
         .await?;
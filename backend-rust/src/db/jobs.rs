@@ -1,5 +1,5 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
 
 use crate::models::*;
@@ -18,24 +18,31 @@ fn map_job_row(row: &SqliteRow) -> Job {
         completed_at: row.get("completed_at"),
         credential_id: row.get("credential_id"),
         triggered_by: row.try_get("triggered_by").unwrap_or_else(|_| "manual".to_string()),
+        timeout_secs: row.get("timeout_secs"),
+        batch_id: row.get("batch_id"),
     }
 }
 
 const SELECT_JOB: &str = r#"
     SELECT id, job_type, device_id, command, status, output, error,
-           created_at, started_at, completed_at, credential_id, triggered_by
-    FROM jobs
+           created_at, started_at, completed_at, credential_id, triggered_by, timeout_secs, batch_id
+    FROM jobs_all
 "#;
 
 pub struct JobRepo;
 
 impl JobRepo {
     pub async fn create(pool: &Pool<Sqlite>, id: &str, req: &CreateJobRequest) -> Result<Job> {
+        Self::create_in_batch(pool, id, req, "").await
+    }
+
+    /// Create a job belonging to a bulk request batch
+    pub async fn create_in_batch(pool: &Pool<Sqlite>, id: &str, req: &CreateJobRequest, batch_id: &str) -> Result<Job> {
         let now = Utc::now();
         sqlx::query(
             r#"
-            INSERT INTO jobs (id, job_type, device_id, command, status, created_at, credential_id, triggered_by)
-            VALUES (?, ?, ?, ?, 'queued', ?, ?, ?)
+            INSERT INTO jobs (id, job_type, device_id, command, status, created_at, credential_id, triggered_by, timeout_secs, batch_id)
+            VALUES (?, ?, ?, ?, 'queued', ?, ?, ?, ?, ?)
             "#,
         )
         .bind(id)
@@ -45,6 +52,8 @@ impl JobRepo {
         .bind(now)
         .bind(&req.credential_id)
         .bind(&req.triggered_by)
+        .bind(req.timeout_secs)
+        .bind(batch_id)
         .execute(pool)
         .await?;
 
@@ -61,13 +70,15 @@ impl JobRepo {
         Ok(row.as_ref().map(map_job_row))
     }
 
-    pub async fn update_started(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
-        sqlx::query("UPDATE jobs SET status = 'running', started_at = ? WHERE id = ?")
+    /// Mark a queued (or, after a restart, running) job as running; false if it was cancelled
+    /// or has finished
+    pub async fn update_started(pool: &Pool<Sqlite>, id: &str) -> Result<bool> {
+        let result = sqlx::query("UPDATE jobs SET status = 'running', started_at = ? WHERE id = ? AND status IN ('queued', 'running')")
             .bind(Utc::now())
             .bind(id)
             .execute(pool)
             .await?;
-        Ok(())
+        Ok(result.rows_affected() > 0)
     }
 
     pub async fn update_completed(pool: &Pool<Sqlite>, id: &str, output: &str) -> Result<()> {
@@ -90,6 +101,19 @@ impl JobRepo {
         Ok(())
     }
 
+    /// Mark a job cancelled if it hasn't finished; false if it had
+    pub async fn update_cancelled(pool: &Pool<Sqlite>, id: &str, error: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE jobs SET status = 'cancelled', error = ?, completed_at = ? WHERE id = ? AND status IN ('queued', 'running')",
+        )
+        .bind(error)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn list_by_device(pool: &Pool<Sqlite>, device_id: i64, limit: i32) -> Result<Vec<Job>> {
         let rows = sqlx::query(&format!("{} WHERE device_id = ? ORDER BY created_at DESC LIMIT ?", SELECT_JOB))
             .bind(device_id)
@@ -107,6 +131,33 @@ impl JobRepo {
         Ok(rows.iter().map(map_job_row).collect())
     }
 
+    pub async fn list_by_batch(pool: &Pool<Sqlite>, batch_id: &str) -> Result<Vec<Job>> {
+        let rows = sqlx::query(&format!("{} WHERE batch_id = ? ORDER BY created_at, device_id", SELECT_JOB))
+            .bind(batch_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_job_row).collect())
+    }
+
+    /// Failed jobs created at or after `since`, newest first
+    pub async fn list_failed_since(pool: &Pool<Sqlite>, since: DateTime<Utc>) -> Result<Vec<Job>> {
+        let rows = sqlx::query(&format!("{} WHERE status = ? AND created_at >= ? ORDER BY created_at DESC", SELECT_JOB))
+            .bind(job_status::FAILED)
+            .bind(since)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_job_row).collect())
+    }
+
+    /// Jobs that completed or failed at or after `since`
+    pub async fn list_finished_since(pool: &Pool<Sqlite>, since: DateTime<Utc>) -> Result<Vec<Job>> {
+        let rows = sqlx::query(&format!("{} WHERE completed_at >= ? ORDER BY completed_at", SELECT_JOB))
+            .bind(since)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_job_row).collect())
+    }
+
     /// Find jobs that are stuck (queued or running) — used for crash recovery
     pub async fn list_stuck(pool: &Pool<Sqlite>) -> Result<Vec<Job>> {
         let rows = sqlx::query(&format!("{} WHERE status IN ('queued', 'running') ORDER BY created_at", SELECT_JOB))
@@ -115,3 +166,56 @@ impl JobRepo {
         Ok(rows.iter().map(map_job_row).collect())
     }
 }
+
+fn map_batch_row(row: &SqliteRow) -> JobBatch {
+    JobBatch {
+        id: row.get("id"),
+        job_type: row.get("job_type"),
+        command: row.get("command"),
+        group_id: row.get("group_id"),
+        created_by: row.get("created_by"),
+        created_at: row.get("created_at"),
+        status: job_status::QUEUED.to_string(),
+        total: 0,
+        queued: 0,
+        running: 0,
+        completed: 0,
+        failed: 0,
+        cancelled: 0,
+        jobs: None,
+    }
+}
+
+pub struct JobBatchRepo;
+
+impl JobBatchRepo {
+    pub async fn create(pool: &Pool<Sqlite>, id: &str, req: &BulkJobRequest, created_by: &str) -> Result<()> {
+        sqlx::query("INSERT INTO job_batches (id, job_type, command, group_id, created_by, created_at) VALUES (?, ?, ?, ?, ?, ?)")
+            .bind(id)
+            .bind(&req.job_type)
+            .bind(&req.command)
+            .bind(req.group_id)
+            .bind(created_by)
+            .bind(Utc::now())
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Get a batch with its job counts and aggregate status, but not its jobs
+    pub async fn get(pool: &Pool<Sqlite>, id: &str) -> Result<Option<JobBatch>> {
+        let row = sqlx::query("SELECT id, job_type, command, group_id, created_by, created_at FROM job_batches WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        let Some(mut batch) = row.as_ref().map(map_batch_row) else {
+            return Ok(None);
+        };
+        let counts: Vec<(String, i64)> = sqlx::query_as("SELECT status, COUNT(*) FROM jobs_all WHERE batch_id = ? GROUP BY status")
+            .bind(id)
+            .fetch_all(pool)
+            .await?;
+        batch.set_counts(&counts);
+        Ok(Some(batch))
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_lab_node_row(row: &SqliteRow) -> LabNode {
+    LabNode {
+        id: row.get("id"),
+        topology_id: row.get("topology_id"),
+        device_id: row.get("device_id"),
+        hostname: row.get("hostname"),
+        container_name: row.get("container_name"),
+        container_id: row.get::<Option<String>, _>("container_id").unwrap_or_default(),
+        image: row.get("image"),
+        mgmt_ip: row.get::<Option<String>, _>("mgmt_ip").unwrap_or_default(),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+const SELECT_LAB_NODES: &str = r#"
+    SELECT l.*, d.hostname FROM lab_nodes l
+    JOIN devices d ON d.id = l.device_id
+"#;
+
+pub struct LabNodeRepo;
+
+impl LabNodeRepo {
+    pub async fn list_for_topology(pool: &Pool<Sqlite>, topology_id: i64) -> Result<Vec<LabNode>> {
+        let rows = sqlx::query(&format!("{} WHERE l.topology_id = ? ORDER BY d.hostname", SELECT_LAB_NODES))
+            .bind(topology_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_lab_node_row).collect())
+    }
+
+    pub async fn get_for_device(pool: &Pool<Sqlite>, device_id: i64) -> Result<Option<LabNode>> {
+        let row = sqlx::query(&format!("{} WHERE l.device_id = ?", SELECT_LAB_NODES))
+            .bind(device_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_lab_node_row))
+    }
+
+    /// Record (or re-point) the container standing in for a device
+    pub async fn upsert(
+        pool: &Pool<Sqlite>,
+        topology_id: i64,
+        device_id: i64,
+        container_name: &str,
+        container_id: &str,
+        image: &str,
+        mgmt_ip: &str,
+    ) -> Result<()> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO lab_nodes (topology_id, device_id, container_name, container_id, image, mgmt_ip, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(device_id) DO UPDATE SET
+                topology_id = excluded.topology_id,
+                container_name = excluded.container_name,
+                container_id = excluded.container_id,
+                image = excluded.image,
+                mgmt_ip = excluded.mgmt_ip,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(topology_id)
+        .bind(device_id)
+        .bind(container_name)
+        .bind(container_id)
+        .bind(image)
+        .bind(mgmt_ip)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_for_topology(pool: &Pool<Sqlite>, topology_id: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM lab_nodes WHERE topology_id = ?")
+            .bind(topology_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
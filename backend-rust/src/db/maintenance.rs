@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+use super::row_helpers::none_if_empty;
+
+fn map_maintenance_window_row(row: &SqliteRow) -> MaintenanceWindow {
+    MaintenanceWindow {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: none_if_empty(row.get("description")),
+        device_id: row.get("device_id"),
+        group_id: row.get("group_id"),
+        starts_at: row.get("starts_at"),
+        ends_at: row.get("ends_at"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+pub struct MaintenanceWindowRepo;
+
+impl MaintenanceWindowRepo {
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<MaintenanceWindow>> {
+        let rows = sqlx::query("SELECT * FROM maintenance_windows ORDER BY starts_at DESC")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_maintenance_window_row).collect())
+    }
+
+    /// Windows that overlap `[start, end)`
+    pub async fn list_overlapping(pool: &Pool<Sqlite>, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<MaintenanceWindow>> {
+        let rows = sqlx::query("SELECT * FROM maintenance_windows WHERE starts_at < ? AND ends_at > ? ORDER BY starts_at")
+            .bind(end)
+            .bind(start)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_maintenance_window_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<MaintenanceWindow>> {
+        let row = sqlx::query("SELECT * FROM maintenance_windows WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_maintenance_window_row))
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, req: &CreateMaintenanceWindowRequest) -> Result<MaintenanceWindow> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO maintenance_windows (name, description, device_id, group_id, starts_at, ends_at, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(req.device_id)
+        .bind(req.group_id)
+        .bind(req.starts_at)
+        .bind(req.ends_at)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        let new_id = result.last_insert_rowid();
+        Self::get(pool, new_id)
+            .await?
+            .context("Maintenance window not found after creation")
+    }
+
+    pub async fn update(pool: &Pool<Sqlite>, id: i64, req: &CreateMaintenanceWindowRequest) -> Result<MaintenanceWindow> {
+        let result = sqlx::query(
+            r#"
+            UPDATE maintenance_windows SET name = ?, description = ?, device_id = ?, group_id = ?,
+                                           starts_at = ?, ends_at = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(req.device_id)
+        .bind(req.group_id)
+        .bind(req.starts_at)
+        .bind(req.ends_at)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Maintenance window", &id.to_string()).into());
+        }
+
+        Self::get(pool, id)
+            .await?
+            .context("Maintenance window not found after update")
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM maintenance_windows WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Maintenance window", &id.to_string()).into());
+        }
+        Ok(())
+    }
+}
+
+pub struct MaintenanceModeRepo;
+
+impl MaintenanceModeRepo {
+    pub async fn get(pool: &Pool<Sqlite>) -> Result<MaintenanceModeState> {
+        let row = sqlx::query("SELECT * FROM maintenance_mode WHERE id = 1")
+            .fetch_optional(pool)
+            .await?;
+        Ok(row
+            .map(|row| MaintenanceModeState {
+                enabled: row.get("enabled"),
+                reason: row.get("reason"),
+                changed_by: row.get("changed_by"),
+                changed_at: row.get("changed_at"),
+            })
+            .unwrap_or_default())
+    }
+
+    pub async fn set(pool: &Pool<Sqlite>, state: &MaintenanceModeState) -> Result<()> {
+        sqlx::query(
+            r#"INSERT INTO maintenance_mode (id, enabled, reason, changed_by, changed_at) VALUES (1, ?, ?, ?, ?)
+               ON CONFLICT(id) DO UPDATE SET
+                   enabled = excluded.enabled,
+                   reason = excluded.reason,
+                   changed_by = excluded.changed_by,
+                   changed_at = excluded.changed_at"#,
+        )
+        .bind(state.enabled)
+        .bind(&state.reason)
+        .bind(&state.changed_by)
+        .bind(state.changed_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
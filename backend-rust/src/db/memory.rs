@@ -0,0 +1,225 @@
+//! In-memory implementation of the persistence traits, for unit tests of the render path that
+//! would otherwise need a SQLite file. Covers the entities behind a config render; role templates are looked up by
+//! topology role directly rather than through device roles, and devices have no port assignments.
+
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::models::*;
+
+use super::traits::*;
+
+#[derive(Default)]
+struct MemoryState {
+    next_id: i64,
+    devices: BTreeMap<i64, Device>,
+    vendors: BTreeMap<i64, Vendor>,
+    templates: BTreeMap<i64, Template>,
+    settings: Settings,
+    variables: HashMap<i64, HashMap<String, String>>,
+    role_templates: HashMap<String, Vec<i64>>,
+}
+
+impl MemoryState {
+    fn next_id(&mut self) -> i64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    state: Mutex<MemoryState>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn add_device(&self, req: &CreateDeviceRequest) -> Device {
+        let mut state = self.lock();
+        let device = device_from_request(state.next_id(), req);
+        state.devices.insert(device.id, device.clone());
+        device
+    }
+
+    pub fn add_vendor(&self, req: &CreateVendorRequest) -> Vendor {
+        let mut state = self.lock();
+        let vendor = vendor_from_request(state.next_id(), req);
+        state.vendors.insert(vendor.id, vendor.clone());
+        vendor
+    }
+
+    pub fn add_template(&self, req: &CreateTemplateRequest) -> Template {
+        let mut state = self.lock();
+        let template = template_from_request(state.next_id(), req);
+        state.templates.insert(template.id, template.clone());
+        template
+    }
+
+    /// Set a device's resolved variables
+    pub fn set_device_variables(&self, device_id: i64, vars: HashMap<String, String>) {
+        self.lock().variables.insert(device_id, vars);
+    }
+
+    /// Use these templates, in order, as the role layers of devices with `topology_role`
+    pub fn set_role_templates(&self, topology_role: &str, template_ids: Vec<i64>) {
+        self.lock().role_templates.insert(topology_role.to_string(), template_ids);
+    }
+}
+
+fn device_from_request(id: i64, req: &CreateDeviceRequest) -> Device {
+    let now = Utc::now();
+    Device {
+        id,
+        mac: Some(req.mac.clone()).filter(|m| !m.is_empty()),
+        ip: req.ip.clone(),
+        hostname: req.hostname.clone(),
+        vendor: req.vendor.clone(),
+        vendor_id: req.vendor.clone(),
+        model: req.model.clone(),
+        serial_number: req.serial_number.clone(),
+        config_template: req.config_template.clone(),
+        credential_id: req.credential_id,
+        ssh_user: req.ssh_user.clone(),
+        ssh_pass: req.ssh_pass.clone(),
+        topology_id: req.topology_id,
+        topology_role: req.topology_role.clone(),
+        hall_id: req.hall_id,
+        row_id: req.row_id,
+        rack_id: req.rack_id,
+        rack_position: req.rack_position,
+        tenant_id: None,
+        gpu_cluster_id: None,
+        status: device_status::OFFLINE.to_string(),
+        device_type: req.device_type.clone().unwrap_or_else(|| "internal".to_string()),
+        last_seen: None,
+        last_backup: None,
+        last_error: None,
+        custom_fields: BTreeMap::new(),
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+fn vendor_from_request(id: i64, req: &CreateVendorRequest) -> Vendor {
+    let now = Utc::now();
+    Vendor {
+        id,
+        name: req.name.clone(),
+        backup_command: req.backup_command.clone(),
+        deploy_command: req.deploy_command.clone(),
+        diff_command: req.diff_command.clone(),
+        commit_confirm_command: req.commit_confirm_command.clone(),
+        confirm_command: req.confirm_command.clone(),
+        transport: req.transport.clone(),
+        ssh_port: req.ssh_port,
+        ssh_user: Some(req.ssh_user.clone()).filter(|u| !u.is_empty()),
+        ssh_pass: Some(req.ssh_pass.clone()).filter(|p| !p.is_empty()),
+        mac_prefixes: req.mac_prefixes.clone(),
+        vendor_class: req.vendor_class.clone(),
+        default_template: req.default_template.clone(),
+        group_names: req.group_names.clone(),
+        ztp_wrapper: req.ztp_wrapper.clone(),
+        device_count: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+fn template_from_request(id: i64, req: &CreateTemplateRequest) -> Template {
+    let now = Utc::now();
+    Template {
+        id,
+        name: req.name.clone(),
+        description: req.description.clone(),
+        vendor_id: req.vendor_id,
+        content: req.content.clone(),
+        device_count: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[async_trait::async_trait]
+impl DeviceStore for MemoryStore {
+    async fn list_devices(&self) -> Result<Vec<Device>> {
+        let mut devices: Vec<Device> = self.lock().devices.values().cloned().collect();
+        devices.sort_by(|a, b| a.hostname.cmp(&b.hostname));
+        Ok(devices)
+    }
+
+    async fn get_device(&self, id: i64) -> Result<Option<Device>> {
+        Ok(self.lock().devices.get(&id).cloned())
+    }
+
+    async fn get_device_by_mac(&self, mac: &str) -> Result<Option<Device>> {
+        Ok(self.lock().devices.values().find(|d| d.mac.as_deref() == Some(mac)).cloned())
+    }
+}
+
+#[async_trait::async_trait]
+impl VendorStore for MemoryStore {
+    async fn get_vendor(&self, id: i64) -> Result<Option<Vendor>> {
+        Ok(self.lock().vendors.get(&id).cloned())
+    }
+
+    async fn get_vendor_by_name(&self, name: &str) -> Result<Option<Vendor>> {
+        Ok(self.lock().vendors.values().find(|v| v.name.eq_ignore_ascii_case(name)).cloned())
+    }
+}
+
+#[async_trait::async_trait]
+impl TemplateStore for MemoryStore {
+    async fn get_template(&self, id: i64) -> Result<Option<Template>> {
+        Ok(self.lock().templates.get(&id).cloned())
+    }
+
+    async fn get_template_by_name(&self, name: &str) -> Result<Option<Template>> {
+        Ok(self.lock().templates.values().find(|t| t.name == name).cloned())
+    }
+}
+
+#[async_trait::async_trait]
+impl SettingsStore for MemoryStore {
+    async fn get_settings(&self) -> Result<Settings> {
+        Ok(self.lock().settings.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl RenderInputStore for MemoryStore {
+    async fn resolve_role_templates(
+        &self,
+        base: &Template,
+        _vendor: Option<&str>,
+        topology_role: Option<&str>,
+    ) -> Result<Vec<Template>> {
+        let state = self.lock();
+        let ids = topology_role.and_then(|r| state.role_templates.get(r)).cloned().unwrap_or_default();
+        Ok(ids
+            .iter()
+            .filter(|id| **id != base.id)
+            .filter_map(|id| state.templates.get(id).cloned())
+            .collect())
+    }
+
+    async fn resolve_device_variables_flat(&self, device_id: i64) -> Result<HashMap<String, String>> {
+        Ok(self.lock().variables.get(&device_id).cloned().unwrap_or_default())
+    }
+
+    async fn list_port_assignments(&self, _device_id: i64) -> Result<Vec<PortAssignment>> {
+        Ok(Vec::new())
+    }
+
+    async fn apply_device_config_snippets(&self, _device_id: i64, rendered: String) -> Result<String> {
+        Ok(rendered)
+    }
+}
@@ -1,21 +1,62 @@
+mod alerts;
+mod api_usage;
+mod automation;
+mod cache;
+mod changelog;
 mod credentials;
+mod custom_fields;
+mod db_maintenance;
+mod device_config_snippets;
+mod device_interfaces;
 mod device_models;
 mod device_roles;
 mod device_variables;
 mod devices;
 mod dhcp_options;
+mod docker_stacks;
+mod documentation;
+mod external_ids;
+mod hardware;
+mod idempotency;
+mod integrity;
 mod port_assignments;
+mod provisioning;
+mod references;
+mod report_schedules;
+mod runbooks;
 mod discovery;
 mod groups;
 mod ipam;
 mod job_templates;
+mod maintenance;
+mod lab_nodes;
 mod jobs;
+#[cfg(test)]
+pub mod memory;
+mod netbox_sync;
+mod notifications;
 mod output_parsers;
+mod partitions;
+mod permissions;
+mod probe_commands;
 pub(crate) mod row_helpers;
+mod saved_searches;
+mod search;
 pub mod seeds;
+mod seed_items;
 mod settings;
+mod staged_configs;
+mod support_contracts;
+mod status_history;
+mod syslog;
+mod tags;
+mod teams;
+mod telemetry;
 mod templates;
+mod template_catalog;
 mod topologies;
+mod topology_deploys;
+mod traits;
 mod users;
 mod variable_resolution;
 mod vendor_actions;
@@ -25,11 +66,15 @@ mod tenants;
 mod store_ipam;
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use sqlx::{sqlite::SqlitePoolOptions, Pool, Sqlite};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 
 use crate::models::*;
 
+pub use traits::{DeviceStore, RenderStore, TemplateStore, VendorStore};
+
 /// Typed error for "resource not found" — enables reliable downcast
 /// in the API error handler instead of fragile string matching.
 #[derive(Debug)]
@@ -55,10 +100,29 @@ impl std::fmt::Display for NotFoundError {
 
 impl std::error::Error for NotFoundError {}
 
+/// Typed error for a create or update that collides with an existing resource; surfaces as 409
+#[derive(Debug)]
+pub struct ConflictError(pub String);
+
+impl ConflictError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl std::fmt::Display for ConflictError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ConflictError {}
+
 /// Store handles all database operations, delegating to per-entity repo modules.
 #[derive(Clone)]
 pub struct Store {
     pool: Pool<Sqlite>,
+    cache: Arc<cache::LookupCache>,
 }
 
 impl Store {
@@ -77,7 +141,7 @@ impl Store {
             .await
             .context("Failed to connect to database")?;
 
-        let store = Self { pool };
+        let store = Self { pool, cache: Arc::new(cache::LookupCache::new()) };
         store.migrate().await?;
         Ok(store)
     }
@@ -103,19 +167,33 @@ impl Store {
                 .await?;
         }
 
-        // Seed defaults
-        self.seed_default_vendors().await?;
-        self.seed_default_templates().await?;
-        self.resolve_vendor_default_templates().await?;
-        self.seed_default_dhcp_options().await?;
+        // Seed defaults: each seed set runs once per version (see seeds::SEED_SETS)
+        for (seed_set, version) in seeds::SEED_SETS {
+            if seed_items::SeedRepo::applied_version(&self.pool, seed_set).await? >= *version {
+                continue;
+            }
+            match *seed_set {
+                "vendors" => self.seed_default_vendors().await?,
+                "templates" => {
+                    self.seed_default_templates().await?;
+                    self.resolve_vendor_default_templates().await?;
+                }
+                "dhcp_options" => self.seed_default_dhcp_options().await?,
+                "vendor_actions" => self.seed_default_vendor_actions().await?,
+                "output_parsers" => self.seed_default_output_parsers().await?,
+                "device_models" => self.seed_default_device_models().await?,
+                "ipam_supernets" => self.seed_default_ipam_supernets().await?,
+                "credential" => self.seed_default_credential().await?,
+                "device_roles" => self.seed_default_device_roles().await?,
+                "locations" => self.seed_default_locations().await?,
+                "probe_commands" => self.seed_default_probe_commands().await?,
+                other => anyhow::bail!("unknown seed set '{}'", other),
+            }
+            seed_items::SeedRepo::record_version(&self.pool, seed_set, *version).await?;
+            tracing::info!("Applied seed set {} v{}", seed_set, version);
+        }
+        // Always ensure there is a way to log in
         self.seed_default_user().await?;
-        self.seed_default_vendor_actions().await?;
-        self.seed_default_output_parsers().await?;
-        self.seed_default_device_models().await?;
-        self.seed_default_ipam_supernets().await?;
-        self.seed_default_credential().await?;
-        self.seed_default_device_roles().await?;
-        self.seed_default_locations().await?;
 
         // Ensure "all" group invariants
         self.ensure_all_group().await?;
@@ -124,7 +202,13 @@ impl Store {
         // Fix any devices that have vendor name strings instead of numeric IDs
         self.normalize_device_vendor_ids().await?;
         self.normalize_topology_roles().await?;
+        self.normalize_device_macs().await?;
 
+        // Partition views follow the columns migrations add to the live tables
+        partitions::PartitionRepo::rebuild_views(&self.pool).await?;
+
+        // Seeding writes around the Store's methods
+        self.cache.clear();
         Ok(())
     }
 
@@ -175,37 +259,58 @@ impl Store {
                     .await?;
             }
         }
+
+        // Fill in confirmed-commit commands on vendors that don't have any yet
+        for (name, commit_confirm_command, confirm_command) in seeds::seed_vendor_commit_confirm_params() {
+            sqlx::query(
+                "UPDATE vendors SET commit_confirm_command = ?, confirm_command = ? WHERE name = ? AND commit_confirm_command = '' AND confirm_command = ''",
+            )
+            .bind(&commit_confirm_command)
+            .bind(&confirm_command)
+            .bind(&name)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        // Fill in ZTP bootstrap wrappers on vendors that don't have one yet
+        for (name, ztp_wrapper) in seeds::seed_vendor_ztp_wrapper_params() {
+            sqlx::query("UPDATE vendors SET ztp_wrapper = ? WHERE name = ? AND ztp_wrapper = ''")
+                .bind(&ztp_wrapper)
+                .bind(&name)
+                .execute(&self.pool)
+                .await?;
+        }
         Ok(())
     }
 
+    /// Seed default templates, tracking the seed content applied to each. A template still
+    /// matching the last applied seed is upgraded in place; one customized locally (or flagged
+    /// user_modified) keeps its content and the new seed content is held as a pending update.
+    /// Seeded templates the user deleted stay deleted.
     async fn seed_default_templates(&self) -> Result<()> {
         // Build a mapping from old text vendor IDs to new integer IDs
         let vendor_map = self.build_vendor_id_map().await?;
 
         for (id, name, description, vendor_id, content) in seeds::seed_template_params() {
-            // Role templates (spine/leaf) use UPDATE-or-INSERT so they stay
-            // in sync with updated variable-based templates across upgrades.
-            // Base templates use INSERT with NOT EXISTS so user edits are preserved.
             let vendor_id_val: Option<i64> = if vendor_id.is_empty() {
                 None
             } else {
                 vendor_map.get(&vendor_id).copied()
             };
-
-            if seeds::is_role_template(&id) {
-                // Update existing role template, or insert if missing
-                let updated = sqlx::query(
-                    "UPDATE templates SET description = ?, vendor_id = ?, content = ?, updated_at = CURRENT_TIMESTAMP WHERE name = ?"
-                )
-                .bind(&description)
-                .bind(&vendor_id_val)
-                .bind(&content)
+            let item = seed_items::SeedRepo::find_item(&self.pool, seed_item_kind::TEMPLATE, &id).await?;
+            if item.as_ref().is_some_and(|i| i.applied_content == content) {
+                continue;
+            }
+            let existing: Option<(i64, String)> = sqlx::query_as("SELECT id, content FROM templates WHERE name = ?")
                 .bind(&name)
-                .execute(&self.pool)
+                .fetch_optional(&self.pool)
                 .await?;
 
-                if updated.rows_affected() == 0 {
-                    sqlx::query(
+            let (template_id, current) = match (existing, &item) {
+                (Some(existing), _) => existing,
+                (None, Some(_)) => continue,
+                (None, None) => {
+                    let result = sqlx::query(
                         r#"
                         INSERT INTO templates (name, description, vendor_id, content, created_at, updated_at)
                         VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
@@ -213,26 +318,47 @@ impl Store {
                     )
                     .bind(&name)
                     .bind(&description)
-                    .bind(&vendor_id_val)
+                    .bind(vendor_id_val)
                     .bind(&content)
                     .execute(&self.pool)
                     .await?;
+                    seed_items::SeedRepo::upsert_item(
+                        &self.pool, seed_item_kind::TEMPLATE, &id, Some(result.last_insert_rowid()), &content, false, None,
+                    )
+                    .await?;
+                    continue;
                 }
-            } else {
+            };
+
+            // Before seeds were tracked, role templates were overwritten on every start, so an
+            // untracked role template holds an earlier seed; other untracked ones may be edited.
+            let unmodified = current == content
+                || match &item {
+                    Some(item) => !item.user_modified && current == item.applied_content,
+                    None => seeds::is_role_template(&id),
+                };
+            if unmodified {
                 sqlx::query(
-                    r#"
-                    INSERT INTO templates (name, description, vendor_id, content, created_at, updated_at)
-                    SELECT ?, ?, ?, ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
-                    WHERE NOT EXISTS (SELECT 1 FROM templates WHERE name = ?)
-                    "#,
+                    "UPDATE templates SET description = ?, vendor_id = ?, content = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
                 )
-                .bind(&name)
                 .bind(&description)
-                .bind(&vendor_id_val)
+                .bind(vendor_id_val)
                 .bind(&content)
-                .bind(&name)
+                .bind(template_id)
                 .execute(&self.pool)
                 .await?;
+                let user_modified = item.as_ref().is_some_and(|i| i.user_modified);
+                seed_items::SeedRepo::upsert_item(
+                    &self.pool, seed_item_kind::TEMPLATE, &id, Some(template_id), &content, user_modified, None,
+                )
+                .await?;
+            } else {
+                let applied = item.map(|i| i.applied_content).unwrap_or_else(|| current.clone());
+                seed_items::SeedRepo::upsert_item(
+                    &self.pool, seed_item_kind::TEMPLATE, &id, Some(template_id), &applied, true, Some(&content),
+                )
+                .await?;
+                tracing::info!("Template '{}' was customized; seed update held for review", name);
             }
         }
         Ok(())
@@ -393,6 +519,40 @@ impl Store {
         Ok(())
     }
 
+    async fn seed_default_probe_commands(&self) -> Result<()> {
+        let vendor_map = self.build_vendor_id_map().await?;
+
+        for probe in seeds::seed_probe_command_data() {
+            let vendor_id = if probe.vendor_id.is_empty() {
+                None
+            } else {
+                match vendor_map.get(probe.vendor_id) {
+                    Some(&vid) => Some(vid),
+                    None => continue, // Skip sets for vendors that were removed
+                }
+            };
+            let commands = serde_json::to_string(probe.commands)?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO probe_commands (vendor_id, field, commands, output_parser_id, max_lines, created_at, updated_at)
+                SELECT ?, ?, ?, (SELECT id FROM output_parsers WHERE name = ?), ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
+                WHERE NOT EXISTS (SELECT 1 FROM probe_commands WHERE vendor_id IS ? AND field = ?)
+                "#,
+            )
+            .bind(vendor_id)
+            .bind(probe.field)
+            .bind(&commands)
+            .bind(probe.parser)
+            .bind(probe.max_lines)
+            .bind(vendor_id)
+            .bind(probe.field)
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
     async fn seed_default_device_models(&self) -> Result<()> {
         let vendor_map = self.build_vendor_id_map().await?;
 
@@ -430,7 +590,7 @@ impl Store {
                 r#"
                 INSERT OR IGNORE INTO ipam_prefixes (prefix, network_int, broadcast_int, prefix_length, description, status, is_supernet, created_at, updated_at)
                 SELECT ?, ?, ?, ?, ?, 'active', ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
-                WHERE NOT EXISTS (SELECT 1 FROM ipam_prefixes WHERE network_int = ? AND broadcast_int = ?)
+                WHERE NOT EXISTS (SELECT 1 FROM ipam_prefixes WHERE network_int = ? AND broadcast_int = ? AND COALESCE(vrf_id, 0) = 0)
                 "#,
             )
             .bind(prefix)
@@ -597,32 +757,62 @@ impl Store {
         devices::DeviceRepo::list(&self.pool).await
     }
 
+    pub async fn list_rack_devices(&self, rack_id: i64) -> Result<Vec<Device>> {
+        devices::DeviceRepo::list_by_rack(&self.pool, rack_id).await
+    }
+
     pub async fn list_devices_paged(&self, limit: i32, offset: i32) -> Result<Vec<Device>> {
         devices::DeviceRepo::list_paged(&self.pool, limit, offset).await
     }
 
+    pub async fn list_devices_filtered(&self, filter: &DeviceFilter) -> Result<Vec<Device>> {
+        devices::DeviceRepo::list_filtered(&self.pool, filter).await
+    }
+
     pub async fn get_device(&self, id: i64) -> Result<Option<Device>> {
         devices::DeviceRepo::get(&self.pool, id).await
     }
 
+    pub async fn find_device_id_by_ip(&self, ip: &str) -> Result<Option<i64>> {
+        devices::DeviceRepo::find_id_by_ip(&self.pool, ip).await
+    }
+
     pub async fn get_device_by_mac(&self, mac: &str) -> Result<Option<Device>> {
         devices::DeviceRepo::get_by_mac(&self.pool, mac).await
     }
 
+    pub async fn get_device_by_serial(&self, serial: &str) -> Result<Option<Device>> {
+        devices::DeviceRepo::get_by_serial(&self.pool, serial).await
+    }
+
     pub async fn create_device(&self, req: &CreateDeviceRequest) -> Result<Device> {
-        devices::DeviceRepo::create(&self.pool, req).await
+        let result = devices::DeviceRepo::create(&self.pool, req).await;
+        self.cache.invalidate_device_counts();
+        result
     }
 
     pub async fn update_device(&self, id: i64, req: &UpdateDeviceRequest) -> Result<Device> {
-        devices::DeviceRepo::update(&self.pool, id, req).await
+        let result = devices::DeviceRepo::update(&self.pool, id, req).await;
+        self.cache.invalidate_device_counts();
+        result
     }
 
     pub async fn delete_device(&self, id: i64) -> Result<()> {
-        devices::DeviceRepo::delete(&self.pool, id).await
+        let result = devices::DeviceRepo::delete(&self.pool, id).await;
+        self.cache.invalidate_device_counts();
+        result
+    }
+
+    pub async fn merge_devices(&self, target_id: i64, source_ids: &[i64]) -> Result<MergeDevicesStats> {
+        let result = devices::DeviceRepo::merge(&self.pool, target_id, source_ids).await;
+        self.cache.invalidate_device_counts();
+        result
     }
 
     pub async fn delete_devices_by_topology(&self, topology_id: i64) -> Result<u64> {
-        devices::DeviceRepo::delete_by_topology(&self.pool, topology_id).await
+        let result = devices::DeviceRepo::delete_by_topology(&self.pool, topology_id).await;
+        self.cache.invalidate_device_counts();
+        result
     }
 
     pub async fn update_device_status(&self, id: i64, status: &str) -> Result<()> {
@@ -633,6 +823,21 @@ impl Store {
         devices::DeviceRepo::update_backup_time(&self.pool, id).await
     }
 
+    pub async fn update_device_ip(&self, id: i64, ip: &str) -> Result<()> {
+        devices::DeviceRepo::update_ip(&self.pool, id, ip).await
+    }
+
+    pub async fn update_device_mac(&self, id: i64, mac: &str) -> Result<()> {
+        let result = devices::DeviceRepo::update_mac(&self.pool, id, mac).await;
+        // Vendor and template device counts count MACs
+        self.cache.invalidate_device_counts();
+        result
+    }
+
+    pub async fn update_device_serial(&self, id: i64, serial: &str) -> Result<()> {
+        devices::DeviceRepo::update_serial(&self.pool, id, serial).await
+    }
+
     pub async fn update_device_error(&self, id: i64, error_msg: &str) -> Result<()> {
         devices::DeviceRepo::update_error(&self.pool, id, error_msg).await
     }
@@ -641,340 +846,1486 @@ impl Store {
         devices::DeviceRepo::update_error(&self.pool, id, "").await
     }
 
-    // ========== Settings Operations ==========
+    // ========== Device Config Snippet Operations ==========
 
-    pub async fn get_settings(&self) -> Result<Settings> {
-        settings::SettingsRepo::get(&self.pool).await
+    pub async fn get_device_config_snippets(&self, device_id: i64) -> Result<Option<DeviceConfigSnippets>> {
+        device_config_snippets::DeviceConfigSnippetRepo::get(&self.pool, device_id).await
     }
 
-    pub async fn update_settings(&self, s: &Settings) -> Result<()> {
-        settings::SettingsRepo::update(&self.pool, s).await
+    pub async fn set_device_config_snippets(&self, device_id: i64, req: &SetDeviceConfigSnippetsRequest) -> Result<DeviceConfigSnippets> {
+        device_config_snippets::DeviceConfigSnippetRepo::set(&self.pool, device_id, req).await
     }
 
-    // ========== Device Variable Operations ==========
+    pub async fn delete_device_config_snippets(&self, device_id: i64) -> Result<()> {
+        device_config_snippets::DeviceConfigSnippetRepo::delete(&self.pool, device_id).await
+    }
 
-    pub async fn list_device_variables(&self, device_id: i64) -> Result<Vec<DeviceVariable>> {
-        device_variables::DeviceVariableRepo::list_by_device(&self.pool, device_id).await
+    /// Wrap a device's rendered config with its pre/post snippets, if it has any
+    pub async fn apply_device_config_snippets(&self, device_id: i64, rendered: String) -> Result<String> {
+        Ok(match self.get_device_config_snippets(device_id).await? {
+            Some(snippets) => snippets.apply(&rendered),
+            None => rendered,
+        })
     }
 
-    pub async fn list_variables_by_key(&self, key: &str) -> Result<Vec<DeviceVariable>> {
-        device_variables::DeviceVariableRepo::list_by_key(&self.pool, key).await
+    // ========== Provisioning Session Operations ==========
+
+    pub async fn list_provisioning_sessions(&self, device_id: i64) -> Result<Vec<ProvisioningSession>> {
+        provisioning::ProvisioningRepo::list_for_device(&self.pool, device_id).await
     }
 
-    pub async fn get_device_variable(&self, device_id: i64, key: &str) -> Result<Option<DeviceVariable>> {
-        device_variables::DeviceVariableRepo::get(&self.pool, device_id, key).await
+    pub async fn open_provisioning_session(&self, device_id: i64, config_file: &str, lease_released: bool) -> Result<ProvisioningSession> {
+        provisioning::ProvisioningRepo::open(&self.pool, device_id, config_file, lease_released).await
     }
 
-    pub async fn set_device_variable(&self, device_id: i64, key: &str, value: &str) -> Result<()> {
-        device_variables::DeviceVariableRepo::set(&self.pool, device_id, key, value).await
+    pub async fn record_provisioning_lease(&self, mac: &str) -> Result<()> {
+        provisioning::ProvisioningRepo::record_lease(&self.pool, mac).await
     }
 
-    pub async fn delete_device_variable(&self, device_id: i64, key: &str) -> Result<()> {
-        device_variables::DeviceVariableRepo::delete(&self.pool, device_id, key).await
+    pub async fn record_provisioning_config_pulled(&self, device_id: i64) -> Result<()> {
+        provisioning::ProvisioningRepo::record_config_pulled(&self.pool, device_id).await
     }
 
-    pub async fn delete_all_device_variables(&self, device_id: i64) -> Result<()> {
-        device_variables::DeviceVariableRepo::delete_all_for_device(&self.pool, device_id).await
+    pub async fn complete_provisioning_session(&self, device_id: i64) -> Result<bool> {
+        provisioning::ProvisioningRepo::complete_if_leased(&self.pool, device_id).await
     }
 
-    pub async fn list_variable_keys(&self) -> Result<Vec<(String, i64)>> {
-        device_variables::DeviceVariableRepo::list_keys(&self.pool).await
+    // ========== Device Interface Operations ==========
+
+    pub async fn list_device_interfaces(&self, device_id: i64) -> Result<Vec<DeviceInterface>> {
+        device_interfaces::DeviceInterfaceRepo::list_for_device(&self.pool, device_id).await
     }
 
-    pub async fn bulk_set_device_variables(&self, entries: &[(i64, String, String)]) -> Result<()> {
-        device_variables::DeviceVariableRepo::bulk_set(&self.pool, entries).await
+    pub async fn list_all_device_interfaces(&self) -> Result<Vec<DeviceInterface>> {
+        device_interfaces::DeviceInterfaceRepo::list_all(&self.pool).await
     }
 
-    pub async fn delete_variable_key(&self, key: &str) -> Result<()> {
-        device_variables::DeviceVariableRepo::delete_key(&self.pool, key).await
+    pub async fn replace_device_interfaces(
+        &self,
+        device_id: i64,
+        entries: &[InterfaceInventoryEntry],
+    ) -> Result<InterfaceInventorySummary> {
+        device_interfaces::DeviceInterfaceRepo::replace_for_device(&self.pool, device_id, entries).await
     }
 
-    // ========== Backup Operations ==========
+    // ========== Hardware Inventory Operations ==========
 
-    pub async fn create_backup(&self, device_id: i64, filename: &str, size: i64) -> Result<Backup> {
-        settings::BackupRepo::create(&self.pool, device_id, filename, size).await
+    pub async fn list_device_hardware(&self, device_id: i64) -> Result<Vec<DeviceHardware>> {
+        hardware::HardwareRepo::list_for_device(&self.pool, device_id).await
     }
 
-    pub async fn list_backups(&self, device_id: i64) -> Result<Vec<Backup>> {
-        settings::BackupRepo::list(&self.pool, device_id).await
+    pub async fn list_all_hardware(&self) -> Result<Vec<DeviceHardware>> {
+        hardware::HardwareRepo::list_all(&self.pool).await
     }
 
-    pub async fn get_backup(&self, id: i64) -> Result<Option<Backup>> {
-        settings::BackupRepo::get(&self.pool, id).await
+    pub async fn replace_device_hardware(&self, device_id: i64, entries: &[HardwareInventoryEntry]) -> Result<(usize, usize)> {
+        hardware::HardwareRepo::replace_for_device(&self.pool, device_id, entries).await
     }
 
-    // ========== Vendor Operations ==========
-
-    pub async fn list_vendors(&self) -> Result<Vec<Vendor>> {
-        vendors::VendorRepo::list(&self.pool).await
+    pub async fn record_serial_mismatch(&self, device_id: i64, recorded: &str, observed: &str) -> Result<SerialMismatch> {
+        hardware::HardwareRepo::record_mismatch(&self.pool, device_id, recorded, observed).await
     }
 
-    pub async fn get_vendor(&self, id: i64) -> Result<Option<Vendor>> {
-        vendors::VendorRepo::get(&self.pool, id).await
+    pub async fn list_serial_mismatches(&self, include_resolved: bool) -> Result<Vec<SerialMismatch>> {
+        hardware::HardwareRepo::list_mismatches(&self.pool, include_resolved).await
     }
 
-    pub async fn get_vendor_by_name(&self, name: &str) -> Result<Option<Vendor>> {
-        vendors::VendorRepo::get_by_name(&self.pool, name).await
+    pub async fn get_serial_mismatch(&self, id: i64) -> Result<Option<SerialMismatch>> {
+        hardware::HardwareRepo::get_mismatch(&self.pool, id).await
     }
 
-    /// Resolve a vendor from either an integer ID string or a name (case-insensitive)
-    pub async fn resolve_vendor(&self, vendor_str: &str) -> Result<Option<Vendor>> {
-        if let Ok(id) = vendor_str.parse::<i64>() {
-            self.get_vendor(id).await
-        } else {
-            self.get_vendor_by_name(vendor_str).await
-        }
+    pub async fn accept_serial_mismatch(&self, id: i64, username: &str) -> Result<SerialMismatch> {
+        hardware::HardwareRepo::accept_mismatch(&self.pool, id, username).await
     }
 
-    pub async fn create_vendor(&self, req: &CreateVendorRequest) -> Result<Vendor> {
-        vendors::VendorRepo::create(&self.pool, req).await
-    }
+    // ========== Custom Field Operations ==========
 
-    pub async fn update_vendor(&self, id: i64, req: &CreateVendorRequest) -> Result<Vendor> {
-        vendors::VendorRepo::update(&self.pool, id, req).await
+    pub async fn list_custom_fields(&self) -> Result<Vec<CustomField>> {
+        custom_fields::CustomFieldRepo::list(&self.pool).await
     }
 
-    pub async fn delete_vendor(&self, id: i64) -> Result<()> {
-        vendors::VendorRepo::delete(&self.pool, id).await
+    pub async fn get_custom_field(&self, id: i64) -> Result<Option<CustomField>> {
+        custom_fields::CustomFieldRepo::get(&self.pool, id).await
     }
 
-    // ========== Device Model Operations ==========
-
-    pub async fn list_device_models(&self) -> Result<Vec<DeviceModel>> {
-        device_models::DeviceModelRepo::list(&self.pool).await
+    pub async fn create_custom_field(&self, req: &CreateCustomFieldRequest) -> Result<CustomField> {
+        custom_fields::CustomFieldRepo::create(&self.pool, req).await
     }
 
-    pub async fn get_device_model(&self, id: i64) -> Result<Option<DeviceModel>> {
-        device_models::DeviceModelRepo::get(&self.pool, id).await
+    pub async fn update_custom_field(&self, id: i64, req: &CreateCustomFieldRequest) -> Result<CustomField> {
+        custom_fields::CustomFieldRepo::update(&self.pool, id, req).await
     }
 
-    pub async fn create_device_model(&self, req: &CreateDeviceModelRequest) -> Result<DeviceModel> {
-        device_models::DeviceModelRepo::create(&self.pool, req).await
+    pub async fn delete_custom_field(&self, id: i64) -> Result<()> {
+        custom_fields::CustomFieldRepo::delete(&self.pool, id).await
     }
 
-    pub async fn update_device_model(&self, id: i64, req: &CreateDeviceModelRequest) -> Result<DeviceModel> {
-        device_models::DeviceModelRepo::update(&self.pool, id, req).await
+    pub async fn list_custom_field_values(&self, field_id: i64) -> Result<BTreeMap<i64, String>> {
+        custom_fields::CustomFieldRepo::list_values(&self.pool, field_id).await
     }
 
-    pub async fn delete_device_model(&self, id: i64) -> Result<()> {
-        device_models::DeviceModelRepo::delete(&self.pool, id).await
+    pub async fn set_device_custom_field_values(&self, device_id: i64, values: &[(i64, Option<String>)]) -> Result<()> {
+        custom_fields::CustomFieldRepo::set_device_values(&self.pool, device_id, values).await
     }
 
-    // ========== Port Assignment Operations ==========
+    // ========== Support Contract Operations ==========
 
-    pub async fn list_port_assignments(&self, device_id: i64) -> Result<Vec<PortAssignment>> {
-        port_assignments::PortAssignmentRepo::list_for_device(&self.pool, device_id).await
+    pub async fn list_support_contracts(&self) -> Result<Vec<SupportContract>> {
+        support_contracts::SupportContractRepo::list(&self.pool).await
     }
 
-    pub async fn list_port_assignments_for_patch_panel(&self, pp_device_id: i64) -> Result<Vec<PortAssignment>> {
-        port_assignments::PortAssignmentRepo::list_for_patch_panel(&self.pool, pp_device_id).await
+    pub async fn get_support_contract(&self, id: i64) -> Result<Option<SupportContract>> {
+        support_contracts::SupportContractRepo::get(&self.pool, id).await
     }
 
-    pub async fn set_port_assignment(&self, device_id: i64, req: &SetPortAssignmentRequest) -> Result<PortAssignment> {
-        port_assignments::PortAssignmentRepo::set(&self.pool, device_id, req).await
+    pub async fn create_support_contract(&self, req: &CreateSupportContractRequest) -> Result<SupportContract> {
+        support_contracts::SupportContractRepo::create(&self.pool, req).await
     }
 
-    pub async fn bulk_set_port_assignments(&self, device_id: i64, assignments: &[SetPortAssignmentRequest]) -> Result<Vec<PortAssignment>> {
-        port_assignments::PortAssignmentRepo::bulk_set(&self.pool, device_id, assignments).await
+    pub async fn update_support_contract(&self, id: i64, req: &CreateSupportContractRequest) -> Result<SupportContract> {
+        support_contracts::SupportContractRepo::update(&self.pool, id, req).await
     }
 
-    pub async fn delete_port_assignment(&self, device_id: i64, port_name: &str) -> Result<()> {
-        port_assignments::PortAssignmentRepo::delete(&self.pool, device_id, port_name).await
+    pub async fn delete_support_contract(&self, id: i64) -> Result<()> {
+        support_contracts::SupportContractRepo::delete(&self.pool, id).await
     }
 
-    // ========== DHCP Option Operations ==========
+    pub async fn mark_support_contract_alerted(&self, id: i64, expires_on: chrono::NaiveDate) -> Result<()> {
+        support_contracts::SupportContractRepo::mark_alerted(&self.pool, id, expires_on).await
+    }
 
-    pub async fn list_dhcp_options(&self) -> Result<Vec<DhcpOption>> {
-        dhcp_options::DhcpOptionRepo::list(&self.pool).await
+    // ========== Status History Operations ==========
+
+    pub async fn list_status_transitions_since(&self, since: DateTime<Utc>) -> Result<Vec<StatusTransition>> {
+        status_history::StatusHistoryRepo::list_since(&self.pool, since).await
     }
 
-    pub async fn get_dhcp_option(&self, id: i64) -> Result<Option<DhcpOption>> {
-        dhcp_options::DhcpOptionRepo::get(&self.pool, id).await
+    pub async fn device_statuses_at(&self, at: DateTime<Utc>) -> Result<HashMap<i64, String>> {
+        status_history::StatusHistoryRepo::statuses_at(&self.pool, at).await
     }
 
-    pub async fn create_dhcp_option(&self, req: &CreateDhcpOptionRequest) -> Result<DhcpOption> {
-        dhcp_options::DhcpOptionRepo::create(&self.pool, req).await
+    // ========== Maintenance Window Operations ==========
+
+    pub async fn list_maintenance_windows(&self) -> Result<Vec<MaintenanceWindow>> {
+        maintenance::MaintenanceWindowRepo::list(&self.pool).await
     }
 
-    pub async fn update_dhcp_option(&self, id: i64, req: &CreateDhcpOptionRequest) -> Result<DhcpOption> {
-        dhcp_options::DhcpOptionRepo::update(&self.pool, id, req).await
+    pub async fn list_maintenance_windows_overlapping(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<MaintenanceWindow>> {
+        maintenance::MaintenanceWindowRepo::list_overlapping(&self.pool, start, end).await
     }
 
-    pub async fn delete_dhcp_option(&self, id: i64) -> Result<()> {
-        dhcp_options::DhcpOptionRepo::delete(&self.pool, id).await
+    pub async fn get_maintenance_window(&self, id: i64) -> Result<Option<MaintenanceWindow>> {
+        maintenance::MaintenanceWindowRepo::get(&self.pool, id).await
     }
 
-    // ========== Template Operations ==========
+    pub async fn create_maintenance_window(&self, req: &CreateMaintenanceWindowRequest) -> Result<MaintenanceWindow> {
+        maintenance::MaintenanceWindowRepo::create(&self.pool, req).await
+    }
 
-    pub async fn list_templates(&self) -> Result<Vec<Template>> {
-        templates::TemplateRepo::list(&self.pool).await
+    pub async fn update_maintenance_window(&self, id: i64, req: &CreateMaintenanceWindowRequest) -> Result<MaintenanceWindow> {
+        maintenance::MaintenanceWindowRepo::update(&self.pool, id, req).await
     }
 
-    pub async fn get_template(&self, id: i64) -> Result<Option<Template>> {
-        templates::TemplateRepo::get(&self.pool, id).await
+    pub async fn delete_maintenance_window(&self, id: i64) -> Result<()> {
+        maintenance::MaintenanceWindowRepo::delete(&self.pool, id).await
     }
 
-    pub async fn get_template_by_name(&self, name: &str) -> Result<Option<Template>> {
-        templates::TemplateRepo::get_by_name(&self.pool, name).await
+    pub async fn get_maintenance_mode(&self) -> Result<MaintenanceModeState> {
+        maintenance::MaintenanceModeRepo::get(&self.pool).await
     }
 
-    pub async fn create_template(&self, req: &CreateTemplateRequest) -> Result<Template> {
-        templates::TemplateRepo::create(&self.pool, req).await
+    pub async fn set_maintenance_mode(&self, state: &MaintenanceModeState) -> Result<()> {
+        maintenance::MaintenanceModeRepo::set(&self.pool, state).await
     }
 
-    pub async fn update_template(&self, id: i64, req: &CreateTemplateRequest) -> Result<Template> {
-        templates::TemplateRepo::update(&self.pool, id, req).await
+    // ========== Change Log Operations ==========
+
+    pub async fn record_change(&self, entry: &NewChangeLogEntry, username: &str) -> Result<()> {
+        changelog::ChangeLogRepo::record(&self.pool, entry, username).await
     }
 
-    pub async fn delete_template(&self, id: i64) -> Result<()> {
-        templates::TemplateRepo::delete(&self.pool, id).await
+    pub async fn list_changes(&self, query: &ChangeLogQuery) -> Result<Vec<ChangeLogEntry>> {
+        changelog::ChangeLogRepo::list(&self.pool, query).await
     }
 
-    // ========== Discovery Operations ==========
+    // ========== Syslog Operations ==========
 
-    pub async fn create_discovery_log(&self, req: &CreateDiscoveryLogRequest) -> Result<DiscoveryLog> {
-        discovery::DiscoveryRepo::create_log(&self.pool, req).await
+    pub async fn create_syslog_event(&self, source_ip: &str, device_id: Option<i64>, msg: &SyslogMessage) -> Result<i64> {
+        syslog::SyslogRepo::create_event(&self.pool, source_ip, device_id, msg).await
     }
 
-    pub async fn list_discovery_logs(&self, limit: i32) -> Result<Vec<DiscoveryLog>> {
-        discovery::DiscoveryRepo::list_logs(&self.pool, limit).await
+    pub async fn list_syslog_events(&self, query: &SyslogEventQuery) -> Result<Vec<SyslogEvent>> {
+        syslog::SyslogRepo::list_events(&self.pool, query).await
     }
 
-    pub async fn clear_discovery_logs(&self) -> Result<()> {
-        discovery::DiscoveryRepo::clear_logs(&self.pool).await
+    pub async fn prune_syslog_events(&self, keep: i64) -> Result<u64> {
+        syslog::SyslogRepo::prune_events(&self.pool, keep).await
     }
 
-    pub async fn upsert_discovered_device(&self, lease: &Lease) -> Result<()> {
-        discovery::DiscoveryRepo::upsert_discovered_device(&self.pool, lease).await
+    pub async fn list_syslog_alert_rules(&self) -> Result<Vec<SyslogAlertRule>> {
+        syslog::SyslogRepo::list_rules(&self.pool).await
     }
 
-    pub async fn list_discovered_devices(&self) -> Result<Vec<Lease>> {
-        discovery::DiscoveryRepo::list_discovered_devices(&self.pool).await
+    pub async fn get_syslog_alert_rule(&self, id: i64) -> Result<Option<SyslogAlertRule>> {
+        syslog::SyslogRepo::get_rule(&self.pool, id).await
     }
 
-    pub async fn delete_discovered_device(&self, mac: &str) -> Result<()> {
-        discovery::DiscoveryRepo::delete_discovered_device(&self.pool, mac).await
+    pub async fn create_syslog_alert_rule(&self, req: &CreateSyslogAlertRuleRequest) -> Result<SyslogAlertRule> {
+        syslog::SyslogRepo::create_rule(&self.pool, req).await
     }
 
-    pub async fn clear_discovered_devices(&self) -> Result<()> {
-        discovery::DiscoveryRepo::clear_discovered_devices(&self.pool).await
+    pub async fn update_syslog_alert_rule(&self, id: i64, req: &CreateSyslogAlertRuleRequest) -> Result<SyslogAlertRule> {
+        syslog::SyslogRepo::update_rule(&self.pool, id, req).await
     }
 
-    pub async fn cleanup_stale_discovered_devices(&self) -> Result<u64> {
-        discovery::DiscoveryRepo::cleanup_stale_discovered_devices(&self.pool).await
+    pub async fn delete_syslog_alert_rule(&self, id: i64) -> Result<()> {
+        syslog::SyslogRepo::delete_rule(&self.pool, id).await
     }
 
-    // ========== NetBox Config Operations ==========
+    pub async fn create_syslog_alert(&self, rule_id: i64, event_id: i64, device_id: Option<i64>) -> Result<SyslogAlert> {
+        syslog::SyslogRepo::create_alert(&self.pool, rule_id, event_id, device_id).await
+    }
 
-    pub async fn get_netbox_config(&self) -> Result<NetBoxConfig> {
-        settings::NetBoxConfigRepo::get(&self.pool).await
+    pub async fn list_syslog_alerts(&self, query: &SyslogAlertQuery) -> Result<Vec<SyslogAlert>> {
+        syslog::SyslogRepo::list_alerts(&self.pool, query).await
     }
 
-    pub async fn save_netbox_config(&self, config: &NetBoxConfig) -> Result<()> {
-        settings::NetBoxConfigRepo::save(&self.pool, config).await
+    pub async fn acknowledge_syslog_alert(&self, id: i64, username: &str) -> Result<SyslogAlert> {
+        syslog::SyslogRepo::acknowledge_alert(&self.pool, id, username).await
     }
 
-    // ========== Vendor Action Operations ==========
+    // ========== Telemetry Operations ==========
 
-    pub async fn get_vendor_action(&self, id: i64) -> Result<Option<VendorAction>> {
-        vendor_actions::VendorActionRepo::get(&self.pool, id).await
+    pub async fn list_telemetry_subscriptions(&self) -> Result<Vec<TelemetrySubscription>> {
+        telemetry::TelemetryRepo::list_subscriptions(&self.pool).await
     }
 
-    pub async fn list_vendor_actions(&self) -> Result<Vec<VendorAction>> {
-        vendor_actions::VendorActionRepo::list_all(&self.pool).await
+    pub async fn get_telemetry_subscription(&self, id: i64) -> Result<Option<TelemetrySubscription>> {
+        telemetry::TelemetryRepo::get_subscription(&self.pool, id).await
     }
 
-    pub async fn list_vendor_actions_by_vendor(&self, vendor_id: i64) -> Result<Vec<VendorAction>> {
-        vendor_actions::VendorActionRepo::list_by_vendor(&self.pool, vendor_id).await
+    pub async fn create_telemetry_subscription(&self, req: &CreateTelemetrySubscriptionRequest) -> Result<TelemetrySubscription> {
+        telemetry::TelemetryRepo::create_subscription(&self.pool, req).await
     }
 
-    pub async fn create_vendor_action(&self, req: &CreateVendorActionRequest) -> Result<VendorAction> {
-        vendor_actions::VendorActionRepo::create(&self.pool, req).await
+    pub async fn update_telemetry_subscription(&self, id: i64, req: &CreateTelemetrySubscriptionRequest) -> Result<TelemetrySubscription> {
+        telemetry::TelemetryRepo::update_subscription(&self.pool, id, req).await
     }
 
-    pub async fn update_vendor_action(&self, id: i64, req: &CreateVendorActionRequest) -> Result<VendorAction> {
-        vendor_actions::VendorActionRepo::update(&self.pool, id, req).await
+    pub async fn delete_telemetry_subscription(&self, id: i64) -> Result<()> {
+        telemetry::TelemetryRepo::delete_subscription(&self.pool, id).await
     }
 
-    pub async fn delete_vendor_action(&self, id: i64) -> Result<()> {
-        vendor_actions::VendorActionRepo::delete(&self.pool, id).await
+    pub async fn insert_telemetry_samples(
+        &self,
+        subscription_id: i64,
+        device_id: i64,
+        timestamp: DateTime<Utc>,
+        updates: &[TelemetryUpdate],
+    ) -> Result<()> {
+        telemetry::TelemetryRepo::insert_samples(&self.pool, subscription_id, device_id, timestamp, updates).await
     }
 
-    // ========== Job Operations ==========
+    pub async fn list_telemetry_samples(&self, query: &TelemetrySampleQuery) -> Result<Vec<TelemetrySample>> {
+        telemetry::TelemetryRepo::list_samples(&self.pool, query).await
+    }
 
-    pub async fn create_job(&self, id: &str, req: &CreateJobRequest) -> Result<Job> {
-        jobs::JobRepo::create(&self.pool, id, req).await
+    pub async fn telemetry_samples_since(&self, since: DateTime<Utc>) -> Result<Vec<TelemetrySample>> {
+        telemetry::TelemetryRepo::samples_since(&self.pool, since).await
     }
 
-    pub async fn get_job(&self, id: &str) -> Result<Option<Job>> {
-        jobs::JobRepo::get(&self.pool, id).await
+    pub async fn prune_telemetry_samples(&self, before: DateTime<Utc>) -> Result<u64> {
+        telemetry::TelemetryRepo::prune_samples(&self.pool, before).await
     }
 
-    pub async fn update_job_started(&self, id: &str) -> Result<()> {
-        jobs::JobRepo::update_started(&self.pool, id).await
+    // ========== Metric Alert Operations ==========
+
+    pub async fn list_alert_rules(&self) -> Result<Vec<AlertRule>> {
+        alerts::AlertRepo::list_rules(&self.pool).await
     }
 
-    pub async fn update_job_completed(&self, id: &str, output: &str) -> Result<()> {
-        jobs::JobRepo::update_completed(&self.pool, id, output).await
+    pub async fn list_enabled_alert_rules(&self) -> Result<Vec<AlertRule>> {
+        alerts::AlertRepo::list_enabled_rules(&self.pool).await
     }
 
-    pub async fn update_job_failed(&self, id: &str, error: &str) -> Result<()> {
-        jobs::JobRepo::update_failed(&self.pool, id, error).await
+    pub async fn get_alert_rule(&self, id: i64) -> Result<Option<AlertRule>> {
+        alerts::AlertRepo::get_rule(&self.pool, id).await
     }
 
-    pub async fn list_jobs_by_device(&self, device_id: i64, limit: i32) -> Result<Vec<Job>> {
-        jobs::JobRepo::list_by_device(&self.pool, device_id, limit).await
+    pub async fn create_alert_rule(&self, req: &CreateAlertRuleRequest) -> Result<AlertRule> {
+        alerts::AlertRepo::create_rule(&self.pool, req).await
     }
 
-    pub async fn list_jobs_recent(&self, limit: i32) -> Result<Vec<Job>> {
-        jobs::JobRepo::list_recent(&self.pool, limit).await
+    pub async fn update_alert_rule(&self, id: i64, req: &CreateAlertRuleRequest) -> Result<AlertRule> {
+        alerts::AlertRepo::update_rule(&self.pool, id, req).await
     }
 
-    pub async fn list_jobs_stuck(&self) -> Result<Vec<Job>> {
-        jobs::JobRepo::list_stuck(&self.pool).await
+    pub async fn delete_alert_rule(&self, id: i64) -> Result<()> {
+        alerts::AlertRepo::delete_rule(&self.pool, id).await
     }
 
-    // ========== Job Template Operations ==========
+    pub async fn get_alert(&self, id: i64) -> Result<Option<Alert>> {
+        alerts::AlertRepo::get_alert(&self.pool, id).await
+    }
 
-    pub async fn list_job_templates(&self) -> Result<Vec<JobTemplate>> {
-        job_templates::JobTemplateRepo::list(&self.pool).await
+    pub async fn list_alerts(&self, query: &AlertQuery) -> Result<Vec<Alert>> {
+        alerts::AlertRepo::list_alerts(&self.pool, query).await
     }
 
-    pub async fn get_job_template(&self, id: i64) -> Result<Option<JobTemplate>> {
-        job_templates::JobTemplateRepo::get(&self.pool, id).await
+    pub async fn list_open_alerts(&self, rule_id: i64) -> Result<Vec<Alert>> {
+        alerts::AlertRepo::list_open_alerts(&self.pool, rule_id).await
     }
 
-    pub async fn create_job_template(&self, req: &CreateJobTemplateRequest) -> Result<JobTemplate> {
-        job_templates::JobTemplateRepo::create(&self.pool, req).await
+    pub async fn open_alert(
+        &self,
+        rule: &AlertRule,
+        subject_id: i64,
+        subject_name: &str,
+        value: f64,
+        status: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Alert> {
+        alerts::AlertRepo::open_alert(&self.pool, rule, subject_id, subject_name, value, status, now).await
     }
 
-    pub async fn update_job_template(&self, id: i64, req: &CreateJobTemplateRequest) -> Result<JobTemplate> {
-        job_templates::JobTemplateRepo::update(&self.pool, id, req).await
+    pub async fn update_alert(&self, id: i64, value: f64, status: &str, now: DateTime<Utc>) -> Result<()> {
+        alerts::AlertRepo::update_alert(&self.pool, id, value, status, now).await
     }
 
-    pub async fn delete_job_template(&self, id: i64) -> Result<()> {
-        job_templates::JobTemplateRepo::delete(&self.pool, id).await
+    pub async fn acknowledge_alert(&self, id: i64, username: &str) -> Result<Option<Alert>> {
+        alerts::AlertRepo::acknowledge_alert(&self.pool, id, username).await
     }
 
-    pub async fn list_scheduled_job_templates(&self) -> Result<Vec<JobTemplate>> {
-        job_templates::JobTemplateRepo::list_scheduled(&self.pool).await
+    pub async fn assign_alert(&self, id: i64, username: &str) -> Result<Alert> {
+        alerts::AlertRepo::assign_alert(&self.pool, id, username).await
     }
 
-    pub async fn update_job_template_last_run(&self, id: i64) -> Result<()> {
-        job_templates::JobTemplateRepo::update_last_run(&self.pool, id).await
+    pub async fn resolve_alert(&self, id: i64, username: &str) -> Result<Option<Alert>> {
+        alerts::AlertRepo::resolve_alert(&self.pool, id, username).await
     }
 
-    // ========== Topology Operations ==========
+    // ========== Settings Operations ==========
 
-    pub async fn list_topologies(&self) -> Result<Vec<Topology>> {
-        topologies::TopologyRepo::list(&self.pool).await
+    pub async fn get_settings(&self) -> Result<Settings> {
+        let settings = self.cache.settings.get_or_load(|| settings::SettingsRepo::get(&self.pool)).await?;
+        Ok(settings.as_ref().clone())
     }
 
-    pub async fn get_topology(&self, id: i64) -> Result<Option<Topology>> {
-        topologies::TopologyRepo::get(&self.pool, id).await
+    pub async fn update_settings(&self, s: &Settings) -> Result<()> {
+        let result = settings::SettingsRepo::update(&self.pool, s).await;
+        self.cache.settings.invalidate();
+        result
     }
 
-    pub async fn create_topology(&self, req: &CreateTopologyRequest) -> Result<Topology> {
+    // ========== Device Variable Operations ==========
+
+    pub async fn list_device_variables(&self, device_id: i64) -> Result<Vec<DeviceVariable>> {
+        device_variables::DeviceVariableRepo::list_by_device(&self.pool, device_id).await
+    }
+
+    pub async fn list_variables_by_key(&self, key: &str) -> Result<Vec<DeviceVariable>> {
+        device_variables::DeviceVariableRepo::list_by_key(&self.pool, key).await
+    }
+
+    pub async fn get_device_variable(&self, device_id: i64, key: &str) -> Result<Option<DeviceVariable>> {
+        device_variables::DeviceVariableRepo::get(&self.pool, device_id, key).await
+    }
+
+    pub async fn set_device_variable(&self, device_id: i64, key: &str, value: &str) -> Result<()> {
+        device_variables::DeviceVariableRepo::set(&self.pool, device_id, key, value).await
+    }
+
+    pub async fn delete_device_variable(&self, device_id: i64, key: &str) -> Result<()> {
+        device_variables::DeviceVariableRepo::delete(&self.pool, device_id, key).await
+    }
+
+    pub async fn delete_all_device_variables(&self, device_id: i64) -> Result<()> {
+        device_variables::DeviceVariableRepo::delete_all_for_device(&self.pool, device_id).await
+    }
+
+    pub async fn list_variable_keys(&self) -> Result<Vec<(String, i64)>> {
+        device_variables::DeviceVariableRepo::list_keys(&self.pool).await
+    }
+
+    pub async fn bulk_set_device_variables(&self, entries: &[(i64, String, String)]) -> Result<()> {
+        device_variables::DeviceVariableRepo::bulk_set(&self.pool, entries).await
+    }
+
+    pub async fn delete_variable_key(&self, key: &str) -> Result<()> {
+        device_variables::DeviceVariableRepo::delete_key(&self.pool, key).await
+    }
+
+    // ========== Backup Operations ==========
+
+    pub async fn create_backup(&self, backup: NewBackup) -> Result<Backup> {
+        settings::BackupRepo::create(&self.pool, backup).await
+    }
+
+    pub async fn list_backups(&self, device_id: i64) -> Result<Vec<Backup>> {
+        settings::BackupRepo::list(&self.pool, device_id).await
+    }
+
+    pub async fn get_backup(&self, id: i64) -> Result<Option<Backup>> {
+        settings::BackupRepo::get(&self.pool, id).await
+    }
+
+    pub async fn list_backups_for_search(&self, latest_only: bool, device_id: Option<i64>) -> Result<Vec<Backup>> {
+        settings::BackupRepo::list_for_search(&self.pool, latest_only, device_id).await
+    }
+
+    // ========== Vendor Operations ==========
+
+    async fn cached_vendors(&self) -> Result<Arc<Vec<Vendor>>> {
+        self.cache.vendors.get_or_load(|| vendors::VendorRepo::list(&self.pool)).await
+    }
+
+    pub async fn list_vendors(&self) -> Result<Vec<Vendor>> {
+        Ok(self.cached_vendors().await?.as_ref().clone())
+    }
+
+    pub async fn get_vendor(&self, id: i64) -> Result<Option<Vendor>> {
+        Ok(self.cached_vendors().await?.iter().find(|v| v.id == id).cloned())
+    }
+
+    /// Case-insensitive lookup by vendor name
+    pub async fn get_vendor_by_name(&self, name: &str) -> Result<Option<Vendor>> {
+        Ok(self.cached_vendors().await?.iter().find(|v| v.name.eq_ignore_ascii_case(name)).cloned())
+    }
+
+    /// Resolve a vendor from either an integer ID string or a name (case-insensitive)
+    pub async fn resolve_vendor(&self, vendor_str: &str) -> Result<Option<Vendor>> {
+        if let Ok(id) = vendor_str.parse::<i64>() {
+            self.get_vendor(id).await
+        } else {
+            self.get_vendor_by_name(vendor_str).await
+        }
+    }
+
+    pub async fn create_vendor(&self, req: &CreateVendorRequest) -> Result<Vendor> {
+        let result = vendors::VendorRepo::create(&self.pool, req).await;
+        self.cache.vendors.invalidate();
+        result
+    }
+
+    pub async fn update_vendor(&self, id: i64, req: &CreateVendorRequest) -> Result<Vendor> {
+        let result = vendors::VendorRepo::update(&self.pool, id, req).await;
+        self.cache.vendors.invalidate();
+        result
+    }
+
+    pub async fn delete_vendor(&self, id: i64) -> Result<()> {
+        let result = vendors::VendorRepo::delete(&self.pool, id).await;
+        // Templates of the vendor lose their vendor_id
+        self.cache.vendors.invalidate();
+        self.cache.templates.invalidate();
+        result
+    }
+
+    // ========== Device Model Operations ==========
+
+    pub async fn list_device_models(&self) -> Result<Vec<DeviceModel>> {
+        device_models::DeviceModelRepo::list(&self.pool).await
+    }
+
+    pub async fn get_device_model(&self, id: i64) -> Result<Option<DeviceModel>> {
+        device_models::DeviceModelRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_device_model(&self, req: &CreateDeviceModelRequest) -> Result<DeviceModel> {
+        device_models::DeviceModelRepo::create(&self.pool, req).await
+    }
+
+    pub async fn update_device_model(&self, id: i64, req: &CreateDeviceModelRequest) -> Result<DeviceModel> {
+        device_models::DeviceModelRepo::update(&self.pool, id, req).await
+    }
+
+    pub async fn delete_device_model(&self, id: i64) -> Result<()> {
+        device_models::DeviceModelRepo::delete(&self.pool, id).await
+    }
+
+    // ========== Port Assignment Operations ==========
+
+    pub async fn list_port_assignments(&self, device_id: i64) -> Result<Vec<PortAssignment>> {
+        port_assignments::PortAssignmentRepo::list_for_device(&self.pool, device_id).await
+    }
+
+    pub async fn list_port_assignments_for_patch_panel(&self, pp_device_id: i64) -> Result<Vec<PortAssignment>> {
+        port_assignments::PortAssignmentRepo::list_for_patch_panel(&self.pool, pp_device_id).await
+    }
+
+    pub async fn set_port_assignment(&self, device_id: i64, req: &SetPortAssignmentRequest) -> Result<PortAssignment> {
+        port_assignments::PortAssignmentRepo::set(&self.pool, device_id, req).await
+    }
+
+    pub async fn bulk_set_port_assignments(&self, device_id: i64, assignments: &[SetPortAssignmentRequest]) -> Result<Vec<PortAssignment>> {
+        port_assignments::PortAssignmentRepo::bulk_set(&self.pool, device_id, assignments).await
+    }
+
+    pub async fn delete_port_assignment(&self, device_id: i64, port_name: &str) -> Result<()> {
+        port_assignments::PortAssignmentRepo::delete(&self.pool, device_id, port_name).await
+    }
+
+    pub async fn list_aux_connections(&self, device_id: i64, kind: &str) -> Result<Vec<AuxConnection>> {
+        port_assignments::AuxConnectionRepo::list_for_device(&self.pool, device_id, kind).await
+    }
+
+    pub async fn find_aux_connection_by_remote(&self, kind: &str, remote_device_id: i64, remote_port_name: &str) -> Result<Option<AuxConnection>> {
+        port_assignments::AuxConnectionRepo::find_by_remote(&self.pool, kind, remote_device_id, remote_port_name).await
+    }
+
+    pub async fn set_aux_connection(&self, device_id: i64, kind: &str, port_name: &str, req: &SetAuxConnectionRequest) -> Result<AuxConnection> {
+        port_assignments::AuxConnectionRepo::set(&self.pool, device_id, kind, port_name, req).await
+    }
+
+    pub async fn delete_aux_connection(&self, device_id: i64, kind: &str, port_name: &str) -> Result<()> {
+        port_assignments::AuxConnectionRepo::delete(&self.pool, device_id, kind, port_name).await
+    }
+
+    // ========== DHCP Option Operations ==========
+
+    pub async fn list_dhcp_options(&self) -> Result<Vec<DhcpOption>> {
+        dhcp_options::DhcpOptionRepo::list(&self.pool).await
+    }
+
+    pub async fn get_dhcp_option(&self, id: i64) -> Result<Option<DhcpOption>> {
+        dhcp_options::DhcpOptionRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_dhcp_option(&self, req: &CreateDhcpOptionRequest) -> Result<DhcpOption> {
+        dhcp_options::DhcpOptionRepo::create(&self.pool, req).await
+    }
+
+    pub async fn update_dhcp_option(&self, id: i64, req: &CreateDhcpOptionRequest) -> Result<DhcpOption> {
+        dhcp_options::DhcpOptionRepo::update(&self.pool, id, req).await
+    }
+
+    pub async fn delete_dhcp_option(&self, id: i64) -> Result<()> {
+        dhcp_options::DhcpOptionRepo::delete(&self.pool, id).await
+    }
+
+    // ========== Template Operations ==========
+
+    async fn cached_templates(&self) -> Result<Arc<Vec<Template>>> {
+        self.cache.templates.get_or_load(|| templates::TemplateRepo::list(&self.pool)).await
+    }
+
+    pub async fn list_templates(&self) -> Result<Vec<Template>> {
+        Ok(self.cached_templates().await?.as_ref().clone())
+    }
+
+    pub async fn get_template(&self, id: i64) -> Result<Option<Template>> {
+        Ok(self.cached_templates().await?.iter().find(|t| t.id == id).cloned())
+    }
+
+    pub async fn get_template_by_name(&self, name: &str) -> Result<Option<Template>> {
+        Ok(self.cached_templates().await?.iter().find(|t| t.name == name).cloned())
+    }
+
+    pub async fn create_template(&self, req: &CreateTemplateRequest) -> Result<Template> {
+        let result = templates::TemplateRepo::create(&self.pool, req).await;
+        self.cache.templates.invalidate();
+        result
+    }
+
+    pub async fn update_template(&self, id: i64, req: &CreateTemplateRequest) -> Result<Template> {
+        let result = templates::TemplateRepo::update(&self.pool, id, req).await;
+        self.cache.templates.invalidate();
+        result
+    }
+
+    pub async fn delete_template(&self, id: i64) -> Result<()> {
+        let result = templates::TemplateRepo::delete(&self.pool, id).await;
+        // Vendors defaulting to the template lose their default_template
+        self.cache.templates.invalidate();
+        self.cache.vendors.invalidate();
+        result
+    }
+
+    // ========== Database Maintenance Operations ==========
+
+    pub async fn list_db_maintenance_runs(&self, kind: &str, limit: i64) -> Result<Vec<DbMaintenanceRun>> {
+        db_maintenance::DbMaintenanceRepo::list(&self.pool, kind, limit).await
+    }
+
+    pub async fn last_successful_db_maintenance_run(&self, kind: &str) -> Result<Option<DbMaintenanceRun>> {
+        db_maintenance::DbMaintenanceRepo::last_success(&self.pool, kind).await
+    }
+
+    pub async fn record_db_maintenance_run(&self, run: &NewDbMaintenanceRun) -> Result<DbMaintenanceRun> {
+        db_maintenance::DbMaintenanceRepo::record(&self.pool, run).await
+    }
+
+    pub async fn backup_database_to(&self, path: &str) -> Result<()> {
+        db_maintenance::DbMaintenanceRepo::backup_to(&self.pool, path).await
+    }
+
+    pub async fn vacuum_analyze(&self) -> Result<i64> {
+        db_maintenance::DbMaintenanceRepo::vacuum_analyze(&self.pool).await
+    }
+
+    pub async fn list_table_partitions(&self) -> Result<Vec<TablePartition>> {
+        partitions::PartitionRepo::list(&self.pool).await
+    }
+
+    pub async fn rollover_partitions(&self, current_month: &str) -> Result<(i64, Vec<String>)> {
+        partitions::PartitionRepo::rollover(&self.pool, current_month).await
+    }
+
+    pub async fn drop_partitions_before(&self, cutoff_month: &str) -> Result<Vec<String>> {
+        partitions::PartitionRepo::drop_before(&self.pool, cutoff_month).await
+    }
+
+    // ========== API Usage Operations ==========
+
+    pub async fn add_api_usage(&self, entries: &[ApiUsageEntry]) -> Result<()> {
+        api_usage::ApiUsageRepo::add(&self.pool, entries).await
+    }
+
+    pub async fn prune_api_usage(&self, before: DateTime<Utc>) -> Result<u64> {
+        api_usage::ApiUsageRepo::prune(&self.pool, before).await
+    }
+
+    pub async fn api_usage_report(&self, since: DateTime<Utc>, username: Option<&str>) -> Result<Vec<ApiConsumerUsage>> {
+        api_usage::ApiUsageRepo::report(&self.pool, since, username).await
+    }
+
+    // ========== Reference Operations ==========
+
+    pub async fn vendor_references(&self, id: i64, name: &str) -> Result<Vec<ReferenceCount>> {
+        references::ReferencesRepo::vendor(&self.pool, id, name).await
+    }
+
+    pub async fn template_references(&self, id: i64) -> Result<Vec<ReferenceCount>> {
+        references::ReferencesRepo::template(&self.pool, id).await
+    }
+
+    pub async fn group_references(&self, id: i64) -> Result<Vec<ReferenceCount>> {
+        references::ReferencesRepo::group(&self.pool, id).await
+    }
+
+    // ========== Team Operations ==========
+
+    pub async fn list_teams(&self) -> Result<Vec<Team>> {
+        teams::TeamRepo::list(&self.pool).await
+    }
+
+    pub async fn get_team(&self, id: i64) -> Result<Option<Team>> {
+        teams::TeamRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_team(&self, req: &CreateTeamRequest) -> Result<Team> {
+        teams::TeamRepo::create(&self.pool, req).await
+    }
+
+    pub async fn update_team(&self, id: i64, req: &CreateTeamRequest) -> Result<Team> {
+        teams::TeamRepo::update(&self.pool, id, req).await
+    }
+
+    pub async fn delete_team(&self, id: i64) -> Result<()> {
+        teams::TeamRepo::delete(&self.pool, id).await
+    }
+
+    pub async fn set_team_members(&self, team_id: i64, user_ids: &[i64]) -> Result<()> {
+        teams::TeamRepo::set_members(&self.pool, team_id, user_ids).await
+    }
+
+    pub async fn team_ids_for_user(&self, username: &str) -> Result<Vec<i64>> {
+        teams::TeamRepo::team_ids_for_user(&self.pool, username).await
+    }
+
+    pub async fn list_team_resources(&self, team_id: i64) -> Result<Vec<TeamResource>> {
+        teams::TeamRepo::list_resources(&self.pool, team_id).await
+    }
+
+    pub async fn set_resource_owner(&self, resource_type: &str, resource_id: i64, team_id: i64) -> Result<()> {
+        teams::TeamRepo::set_owner(&self.pool, resource_type, resource_id, team_id).await
+    }
+
+    pub async fn remove_resource_owner(&self, team_id: i64, resource_type: &str, resource_id: i64) -> Result<()> {
+        teams::TeamRepo::remove_owner(&self.pool, team_id, resource_type, resource_id).await
+    }
+
+    pub async fn resource_owner(&self, resource_type: &str, resource_id: i64) -> Result<Option<i64>> {
+        teams::TeamRepo::owner_of(&self.pool, resource_type, resource_id).await
+    }
+
+    pub async fn list_team_rules(&self, team_ids: &[i64]) -> Result<Vec<TeamRule>> {
+        teams::TeamRepo::list_rules(&self.pool, team_ids).await
+    }
+
+    pub async fn create_team_rule(&self, team_id: i64, req: &CreateTeamRuleRequest) -> Result<TeamRule> {
+        teams::TeamRepo::create_rule(&self.pool, team_id, req).await
+    }
+
+    pub async fn delete_team_rule(&self, team_id: i64, rule_id: i64) -> Result<()> {
+        teams::TeamRepo::delete_rule(&self.pool, team_id, rule_id).await
+    }
+
+    // ========== Permission Operations ==========
+
+    pub async fn list_roles(&self) -> Result<Vec<Role>> {
+        permissions::PermissionRepo::list_roles(&self.pool).await
+    }
+
+    pub async fn get_role(&self, id: i64) -> Result<Option<Role>> {
+        permissions::PermissionRepo::get_role(&self.pool, id).await
+    }
+
+    pub async fn create_role(&self, req: &CreateRoleRequest) -> Result<Role> {
+        permissions::PermissionRepo::create_role(&self.pool, req).await
+    }
+
+    pub async fn update_role(&self, id: i64, req: &CreateRoleRequest) -> Result<Role> {
+        permissions::PermissionRepo::update_role(&self.pool, id, req).await
+    }
+
+    pub async fn delete_role(&self, id: i64) -> Result<()> {
+        permissions::PermissionRepo::delete_role(&self.pool, id).await
+    }
+
+    pub async fn set_role_members(&self, role_id: i64, user_ids: &[i64]) -> Result<()> {
+        permissions::PermissionRepo::set_role_members(&self.pool, role_id, user_ids).await
+    }
+
+    pub async fn role_ids_for_user(&self, user_id: i64) -> Result<Vec<i64>> {
+        permissions::PermissionRepo::role_ids_for_user(&self.pool, user_id).await
+    }
+
+    pub async fn list_permissions(&self, subject_type: &str, subject_id: i64) -> Result<Vec<Permission>> {
+        permissions::PermissionRepo::list_grants(&self.pool, subject_type, subject_id).await
+    }
+
+    pub async fn set_permissions(&self, subject_type: &str, subject_id: i64, permissions: &[Permission]) -> Result<()> {
+        permissions::PermissionRepo::set_grants(&self.pool, subject_type, subject_id, permissions).await
+    }
+
+    /// Grants of every role and team, including ones with no grants
+    pub async fn permission_subjects(&self) -> Result<Vec<SubjectPermissions>> {
+        permissions::PermissionRepo::list_subjects(&self.pool).await
+    }
+
+    // ========== External ID Operations ==========
+
+    pub async fn list_external_ids(&self, resource_type: Option<&str>) -> Result<Vec<ExternalId>> {
+        external_ids::ExternalIdRepo::list(&self.pool, resource_type).await
+    }
+
+    pub async fn resolve_external_id(&self, resource_type: &str, external_id: &str) -> Result<Option<i64>> {
+        external_ids::ExternalIdRepo::resolve(&self.pool, resource_type, external_id).await
+    }
+
+    pub async fn bind_external_id(&self, resource_type: &str, external_id: &str, resource_id: i64) -> Result<ExternalId> {
+        external_ids::ExternalIdRepo::bind(&self.pool, resource_type, external_id, resource_id).await
+    }
+
+    pub async fn unbind_external_id(&self, resource_type: &str, external_id: &str) -> Result<()> {
+        external_ids::ExternalIdRepo::unbind(&self.pool, resource_type, external_id).await
+    }
+
+    // ========== Automation Operations ==========
+
+    pub async fn list_automation_rules(&self) -> Result<Vec<AutomationRule>> {
+        automation::AutomationRepo::list_rules(&self.pool).await
+    }
+
+    pub async fn list_automation_rules_for_event(&self, event_type: &str) -> Result<Vec<AutomationRule>> {
+        automation::AutomationRepo::list_rules_for_event(&self.pool, event_type).await
+    }
+
+    pub async fn get_automation_rule(&self, id: i64) -> Result<Option<AutomationRule>> {
+        automation::AutomationRepo::get_rule(&self.pool, id).await
+    }
+
+    pub async fn create_automation_rule(&self, req: &CreateAutomationRuleRequest) -> Result<AutomationRule> {
+        automation::AutomationRepo::create_rule(&self.pool, req).await
+    }
+
+    pub async fn update_automation_rule(&self, id: i64, req: &CreateAutomationRuleRequest) -> Result<AutomationRule> {
+        automation::AutomationRepo::update_rule(&self.pool, id, req).await
+    }
+
+    pub async fn delete_automation_rule(&self, id: i64) -> Result<()> {
+        automation::AutomationRepo::delete_rule(&self.pool, id).await
+    }
+
+    pub async fn record_automation_execution(
+        &self,
+        rule_id: i64,
+        event_type: &str,
+        payload: &serde_json::Value,
+        result: &std::result::Result<String, String>,
+    ) -> Result<()> {
+        automation::AutomationRepo::record_execution(&self.pool, rule_id, event_type, payload, result).await
+    }
+
+    pub async fn list_automation_executions(&self, rule_id: i64, limit: i64) -> Result<Vec<AutomationExecution>> {
+        automation::AutomationRepo::list_executions(&self.pool, rule_id, limit).await
+    }
+
+    // ========== Notification Channel Operations ==========
+
+    pub async fn list_notification_channels(&self) -> Result<Vec<NotificationChannel>> {
+        notifications::NotificationChannelRepo::list(&self.pool).await
+    }
+
+    pub async fn get_notification_channel(&self, id: i64) -> Result<Option<NotificationChannel>> {
+        notifications::NotificationChannelRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_notification_channel(&self, req: &CreateNotificationChannelRequest) -> Result<NotificationChannel> {
+        notifications::NotificationChannelRepo::create(&self.pool, req).await
+    }
+
+    pub async fn update_notification_channel(
+        &self,
+        id: i64,
+        req: &CreateNotificationChannelRequest,
+    ) -> Result<NotificationChannel> {
+        notifications::NotificationChannelRepo::update(&self.pool, id, req).await
+    }
+
+    pub async fn delete_notification_channel(&self, id: i64) -> Result<()> {
+        notifications::NotificationChannelRepo::delete(&self.pool, id).await
+    }
+
+    // ========== Report Schedule Operations ==========
+
+    pub async fn list_report_schedules(&self) -> Result<Vec<ReportSchedule>> {
+        report_schedules::ReportScheduleRepo::list(&self.pool).await
+    }
+
+    pub async fn list_enabled_report_schedules(&self) -> Result<Vec<ReportSchedule>> {
+        report_schedules::ReportScheduleRepo::list_enabled(&self.pool).await
+    }
+
+    pub async fn get_report_schedule(&self, id: i64) -> Result<Option<ReportSchedule>> {
+        report_schedules::ReportScheduleRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_report_schedule(&self, req: &CreateReportScheduleRequest) -> Result<ReportSchedule> {
+        report_schedules::ReportScheduleRepo::create(&self.pool, req).await
+    }
+
+    pub async fn update_report_schedule(&self, id: i64, req: &CreateReportScheduleRequest) -> Result<ReportSchedule> {
+        report_schedules::ReportScheduleRepo::update(&self.pool, id, req).await
+    }
+
+    pub async fn delete_report_schedule(&self, id: i64) -> Result<()> {
+        report_schedules::ReportScheduleRepo::delete(&self.pool, id).await
+    }
+
+    pub async fn record_report_schedule_run(&self, id: i64, error: Option<&str>, drift_device_ids: &[i64]) -> Result<()> {
+        report_schedules::ReportScheduleRepo::record_run(&self.pool, id, error, drift_device_ids).await
+    }
+
+    // ========== Runbook Operations ==========
+
+    pub async fn list_runbooks(&self, device_role_id: Option<i64>) -> Result<Vec<Runbook>> {
+        runbooks::RunbookRepo::list(&self.pool, device_role_id).await
+    }
+
+    pub async fn get_runbook(&self, id: i64) -> Result<Option<Runbook>> {
+        runbooks::RunbookRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_runbook(&self, req: &CreateRunbookRequest) -> Result<Runbook> {
+        runbooks::RunbookRepo::create(&self.pool, req).await
+    }
+
+    pub async fn update_runbook(&self, id: i64, req: &CreateRunbookRequest) -> Result<Runbook> {
+        runbooks::RunbookRepo::update(&self.pool, id, req).await
+    }
+
+    pub async fn delete_runbook(&self, id: i64) -> Result<()> {
+        runbooks::RunbookRepo::delete(&self.pool, id).await
+    }
+
+    pub async fn list_runbook_runs(&self, query: &RunbookRunQuery) -> Result<Vec<RunbookRun>> {
+        runbooks::RunbookRepo::list_runs(&self.pool, query).await
+    }
+
+    pub async fn get_runbook_run(&self, id: i64) -> Result<Option<RunbookRun>> {
+        runbooks::RunbookRepo::get_run(&self.pool, id).await
+    }
+
+    pub async fn create_runbook_run(&self, runbook: &Runbook, device_id: i64, started_by: &str) -> Result<RunbookRun> {
+        runbooks::RunbookRepo::create_run(&self.pool, runbook, device_id, started_by).await
+    }
+
+    pub async fn set_runbook_step_executed(&self, run_id: i64, position: i64, job_ids: &[String]) -> Result<()> {
+        runbooks::RunbookRepo::set_step_executed(&self.pool, run_id, position, job_ids).await
+    }
+
+    pub async fn sign_off_runbook_step(
+        &self,
+        run_id: i64,
+        position: i64,
+        status: &str,
+        username: &str,
+        notes: &str,
+    ) -> Result<()> {
+        runbooks::RunbookRepo::sign_off_step(&self.pool, run_id, position, status, username, notes).await
+    }
+
+    pub async fn finish_runbook_run(&self, run_id: i64, status: &str) -> Result<()> {
+        runbooks::RunbookRepo::finish_run(&self.pool, run_id, status).await
+    }
+
+    // ========== Staged Config Operations ==========
+
+    pub async fn list_staged_configs(&self, query: &StagedConfigQuery) -> Result<Vec<StagedConfig>> {
+        staged_configs::StagedConfigRepo::list(&self.pool, query).await
+    }
+
+    pub async fn list_due_staged_configs(&self, now: DateTime<Utc>) -> Result<Vec<StagedConfig>> {
+        staged_configs::StagedConfigRepo::list_due(&self.pool, now).await
+    }
+
+    pub async fn get_staged_config(&self, id: i64) -> Result<Option<StagedConfig>> {
+        staged_configs::StagedConfigRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_staged_config(
+        &self,
+        device_id: i64,
+        config: &str,
+        req: &CreateStagedConfigRequest,
+        created_by: &str,
+    ) -> Result<StagedConfig> {
+        staged_configs::StagedConfigRepo::create(&self.pool, device_id, config, req, created_by).await
+    }
+
+    pub async fn update_scheduled_staged_config(&self, staged: &StagedConfig) -> Result<bool> {
+        staged_configs::StagedConfigRepo::update_scheduled(&self.pool, staged).await
+    }
+
+    pub async fn transition_scheduled_staged_config(&self, id: i64, status: &str, job_id: Option<&str>) -> Result<bool> {
+        staged_configs::StagedConfigRepo::transition_scheduled(&self.pool, id, status, job_id).await
+    }
+
+    pub async fn finish_staged_config(&self, id: i64, status: &str, error: &str) -> Result<()> {
+        staged_configs::StagedConfigRepo::finish(&self.pool, id, status, error).await
+    }
+
+    // ========== Documentation Operations ==========
+
+    pub async fn list_notes(&self, owner: DocOwner) -> Result<Vec<Note>> {
+        documentation::NoteRepo::list(&self.pool, owner).await
+    }
+
+    pub async fn get_note(&self, id: i64) -> Result<Option<Note>> {
+        documentation::NoteRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_note(&self, owner: DocOwner, req: &CreateNoteRequest, created_by: &str) -> Result<Note> {
+        documentation::NoteRepo::create(&self.pool, owner, req, created_by).await
+    }
+
+    pub async fn update_note(&self, note: &Note, updated_by: &str) -> Result<Note> {
+        documentation::NoteRepo::update(&self.pool, note, updated_by).await
+    }
+
+    pub async fn delete_note(&self, id: i64) -> Result<()> {
+        documentation::NoteRepo::delete(&self.pool, id).await
+    }
+
+    pub async fn list_attachments(&self, owner: DocOwner) -> Result<Vec<Attachment>> {
+        documentation::AttachmentRepo::list(&self.pool, owner).await
+    }
+
+    pub async fn get_attachment(&self, id: i64) -> Result<Option<Attachment>> {
+        documentation::AttachmentRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_attachment(&self, attachment: &Attachment) -> Result<Attachment> {
+        documentation::AttachmentRepo::create(&self.pool, attachment).await
+    }
+
+    pub async fn list_attachment_stored_names(&self) -> Result<Vec<String>> {
+        documentation::AttachmentRepo::list_stored_names(&self.pool).await
+    }
+
+    pub async fn delete_attachment(&self, id: i64) -> Result<()> {
+        documentation::AttachmentRepo::delete(&self.pool, id).await
+    }
+
+    // ========== Integrity Operations ==========
+
+    /// Scan for rows that reference deleted devices, groups and other parents
+    pub async fn check_integrity(&self) -> Result<IntegrityReport> {
+        let issues = integrity::IntegrityRepo::find_issues(&self.pool).await?;
+        Ok(IntegrityReport {
+            checked_at: chrono::Utc::now(),
+            checks_run: integrity::IntegrityRepo::check_count(),
+            total_orphans: issues.iter().map(|i| i.count).sum(),
+            issues,
+        })
+    }
+
+    pub fn is_known_integrity_check(&self, name: &str) -> bool {
+        integrity::IntegrityRepo::is_known_check(name)
+    }
+
+    pub async fn cleanup_integrity_issues(&self, checks: &[String]) -> Result<Vec<IntegrityCleanupResult>> {
+        integrity::IntegrityRepo::cleanup(&self.pool, checks).await
+    }
+
+    // ========== Seed Operations ==========
+
+    /// Each seed set's current version alongside the version applied to this database
+    pub async fn list_seed_versions(&self) -> Result<Vec<SeedSetVersion>> {
+        let applied = seed_items::SeedRepo::list_versions(&self.pool).await?;
+        Ok(seeds::SEED_SETS
+            .iter()
+            .map(|(name, version)| {
+                let row = applied.iter().find(|(n, _, _)| n == name);
+                SeedSetVersion {
+                    name: name.to_string(),
+                    version: *version,
+                    applied_version: row.map(|(_, v, _)| *v).unwrap_or(0),
+                    applied_at: row.map(|(_, _, at)| *at),
+                }
+            })
+            .collect())
+    }
+
+    pub async fn get_seed_item(&self, id: i64) -> Result<Option<SeedItem>> {
+        seed_items::SeedRepo::get_item(&self.pool, id).await
+    }
+
+    pub async fn list_pending_seed_items(&self) -> Result<Vec<SeedItem>> {
+        seed_items::SeedRepo::list_pending(&self.pool).await
+    }
+
+    pub async fn save_seed_item(&self, item: &SeedItem) -> Result<()> {
+        seed_items::SeedRepo::upsert_item(
+            &self.pool,
+            &item.kind,
+            &item.seed_key,
+            item.resource_id,
+            &item.applied_content,
+            item.user_modified,
+            item.pending_content.as_deref(),
+        )
+        .await
+    }
+
+    pub async fn set_seed_item_user_modified(&self, id: i64, user_modified: bool) -> Result<SeedItem> {
+        seed_items::SeedRepo::set_user_modified(&self.pool, id, user_modified).await
+    }
+
+    // ========== Template Catalog Operations ==========
+
+    pub async fn list_template_provenance(&self) -> Result<Vec<TemplateProvenance>> {
+        template_catalog::TemplateCatalogRepo::list(&self.pool).await
+    }
+
+    pub async fn get_template_provenance(&self, template_id: i64) -> Result<Option<TemplateProvenance>> {
+        template_catalog::TemplateCatalogRepo::get(&self.pool, template_id).await
+    }
+
+    pub async fn record_template_provenance(
+        &self,
+        template_id: i64,
+        source_url: &str,
+        source_path: &str,
+        commit_sha: &str,
+        content: &str,
+        installed_by: &str,
+    ) -> Result<()> {
+        template_catalog::TemplateCatalogRepo::record(
+            &self.pool, template_id, source_url, source_path, commit_sha, content, installed_by,
+        )
+        .await
+    }
+
+    // ========== Discovery Operations ==========
+
+    pub async fn create_discovery_log(&self, req: &CreateDiscoveryLogRequest) -> Result<DiscoveryLog> {
+        discovery::DiscoveryRepo::create_log(&self.pool, req).await
+    }
+
+    pub async fn list_discovery_logs(&self, limit: i32) -> Result<Vec<DiscoveryLog>> {
+        discovery::DiscoveryRepo::list_logs(&self.pool, limit).await
+    }
+
+    pub async fn clear_discovery_logs(&self) -> Result<()> {
+        discovery::DiscoveryRepo::clear_logs(&self.pool).await?;
+        partitions::PartitionRepo::drop_all(&self.pool, "discovery_logs").await
+    }
+
+    pub async fn upsert_discovered_device(&self, lease: &Lease) -> Result<()> {
+        discovery::DiscoveryRepo::upsert_discovered_device(&self.pool, lease).await
+    }
+
+    pub async fn list_discovered_devices(&self) -> Result<Vec<Lease>> {
+        discovery::DiscoveryRepo::list_discovered_devices(&self.pool).await
+    }
+
+    pub async fn delete_discovered_device(&self, mac: &str) -> Result<()> {
+        discovery::DiscoveryRepo::delete_discovered_device(&self.pool, mac).await
+    }
+
+    pub async fn clear_discovered_devices(&self) -> Result<()> {
+        discovery::DiscoveryRepo::clear_discovered_devices(&self.pool).await
+    }
+
+    pub async fn cleanup_stale_discovered_devices(&self) -> Result<u64> {
+        discovery::DiscoveryRepo::cleanup_stale_discovered_devices(&self.pool).await
+    }
+
+    // ========== NetBox Config Operations ==========
+
+    pub async fn get_netbox_config(&self) -> Result<NetBoxConfig> {
+        settings::NetBoxConfigRepo::get(&self.pool).await
+    }
+
+    pub async fn get_netbox_sync_baseline(&self, device_id: i64) -> Result<Option<BTreeMap<String, String>>> {
+        netbox_sync::NetBoxSyncRepo::get_baseline(&self.pool, device_id).await
+    }
+
+    pub async fn set_netbox_sync_baseline(&self, device_id: i64, netbox_id: i64, baseline: &BTreeMap<String, String>) -> Result<()> {
+        netbox_sync::NetBoxSyncRepo::set_baseline(&self.pool, device_id, netbox_id, baseline).await
+    }
+
+    pub async fn list_sync_conflicts(&self, status: &str) -> Result<Vec<SyncConflict>> {
+        netbox_sync::NetBoxSyncRepo::list_conflicts(&self.pool, status).await
+    }
+
+    pub async fn get_sync_conflict(&self, id: i64) -> Result<Option<SyncConflict>> {
+        netbox_sync::NetBoxSyncRepo::get_conflict(&self.pool, id).await
+    }
+
+    pub async fn upsert_sync_conflict(&self, device_id: i64, netbox_id: i64, fields: &[SyncConflictField]) -> Result<()> {
+        netbox_sync::NetBoxSyncRepo::upsert_pending_conflict(&self.pool, device_id, netbox_id, fields).await
+    }
+
+    pub async fn clear_sync_conflict(&self, device_id: i64) -> Result<()> {
+        netbox_sync::NetBoxSyncRepo::clear_pending_conflict(&self.pool, device_id).await
+    }
+
+    pub async fn resolve_sync_conflict(&self, id: i64, resolution: &str) -> Result<SyncConflict> {
+        netbox_sync::NetBoxSyncRepo::mark_resolved(&self.pool, id, resolution).await
+    }
+
+    pub async fn save_netbox_config(&self, config: &NetBoxConfig) -> Result<()> {
+        settings::NetBoxConfigRepo::save(&self.pool, config).await
+    }
+
+    // ========== Vendor Action Operations ==========
+
+    pub async fn get_vendor_action(&self, id: i64) -> Result<Option<VendorAction>> {
+        vendor_actions::VendorActionRepo::get(&self.pool, id).await
+    }
+
+    pub async fn list_vendor_actions(&self) -> Result<Vec<VendorAction>> {
+        vendor_actions::VendorActionRepo::list_all(&self.pool).await
+    }
+
+    pub async fn list_vendor_actions_by_vendor(&self, vendor_id: i64) -> Result<Vec<VendorAction>> {
+        vendor_actions::VendorActionRepo::list_by_vendor(&self.pool, vendor_id).await
+    }
+
+    pub async fn create_vendor_action(&self, req: &CreateVendorActionRequest) -> Result<VendorAction> {
+        vendor_actions::VendorActionRepo::create(&self.pool, req).await
+    }
+
+    pub async fn update_vendor_action(&self, id: i64, req: &CreateVendorActionRequest) -> Result<VendorAction> {
+        vendor_actions::VendorActionRepo::update(&self.pool, id, req).await
+    }
+
+    pub async fn delete_vendor_action(&self, id: i64) -> Result<()> {
+        vendor_actions::VendorActionRepo::delete(&self.pool, id).await
+    }
+
+    // ========== Probe Command Operations ==========
+
+    pub async fn list_probe_commands(&self) -> Result<Vec<ProbeCommand>> {
+        probe_commands::ProbeCommandRepo::list(&self.pool).await
+    }
+
+    pub async fn get_probe_command(&self, id: i64) -> Result<Option<ProbeCommand>> {
+        probe_commands::ProbeCommandRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_probe_command(&self, req: &CreateProbeCommandRequest) -> Result<ProbeCommand> {
+        probe_commands::ProbeCommandRepo::create(&self.pool, req).await
+    }
+
+    pub async fn update_probe_command(&self, id: i64, req: &CreateProbeCommandRequest) -> Result<ProbeCommand> {
+        probe_commands::ProbeCommandRepo::update(&self.pool, id, req).await
+    }
+
+    pub async fn delete_probe_command(&self, id: i64) -> Result<()> {
+        probe_commands::ProbeCommandRepo::delete(&self.pool, id).await
+    }
+
+    /// The probe steps for a vendor (id or name): per field, the vendor's own command set if it
+    /// has one, else the default set. Unknown or missing vendors get the default sets.
+    pub async fn probe_steps(&self, vendor: Option<&str>) -> Result<Vec<ProbeStep>> {
+        let vendor_id = match vendor.filter(|v| !v.is_empty()) {
+            Some(v) => self.resolve_vendor(v).await?.map(|v| v.id),
+            None => None,
+        };
+        let rows = probe_commands::ProbeCommandRepo::list_for_vendor(&self.pool, vendor_id).await?;
+
+        let mut steps = Vec::new();
+        for field in probe_field::ALL {
+            // Vendor rows sort first, so the first match wins over the default
+            let Some(row) = rows.iter().find(|r| r.field == *field) else {
+                continue;
+            };
+            let parser = match row.output_parser_id {
+                Some(id) => self.get_output_parser(id).await?.filter(|p| p.enabled),
+                None => None,
+            };
+            steps.push(ProbeStep {
+                field: row.field.clone(),
+                commands: row.commands.clone(),
+                parser,
+                max_lines: row.max_lines,
+            });
+        }
+        Ok(steps)
+    }
+
+    // ========== Job Operations ==========
+
+    pub async fn create_job(&self, id: &str, req: &CreateJobRequest) -> Result<Job> {
+        jobs::JobRepo::create(&self.pool, id, req).await
+    }
+
+    pub async fn create_batch_job(&self, id: &str, req: &CreateJobRequest, batch_id: &str) -> Result<Job> {
+        jobs::JobRepo::create_in_batch(&self.pool, id, req, batch_id).await
+    }
+
+    pub async fn get_job(&self, id: &str) -> Result<Option<Job>> {
+        jobs::JobRepo::get(&self.pool, id).await
+    }
+
+    pub async fn update_job_started(&self, id: &str) -> Result<bool> {
+        jobs::JobRepo::update_started(&self.pool, id).await
+    }
+
+    pub async fn update_job_completed(&self, id: &str, output: &str) -> Result<()> {
+        jobs::JobRepo::update_completed(&self.pool, id, output).await
+    }
+
+    pub async fn update_job_failed(&self, id: &str, error: &str) -> Result<()> {
+        jobs::JobRepo::update_failed(&self.pool, id, error).await
+    }
+
+    pub async fn update_job_cancelled(&self, id: &str, error: &str) -> Result<bool> {
+        jobs::JobRepo::update_cancelled(&self.pool, id, error).await
+    }
+
+    pub async fn list_jobs_by_device(&self, device_id: i64, limit: i32) -> Result<Vec<Job>> {
+        jobs::JobRepo::list_by_device(&self.pool, device_id, limit).await
+    }
+
+    pub async fn list_jobs_recent(&self, limit: i32) -> Result<Vec<Job>> {
+        jobs::JobRepo::list_recent(&self.pool, limit).await
+    }
+
+    pub async fn list_jobs_by_batch(&self, batch_id: &str) -> Result<Vec<Job>> {
+        jobs::JobRepo::list_by_batch(&self.pool, batch_id).await
+    }
+
+    pub async fn create_job_batch(&self, id: &str, req: &BulkJobRequest, created_by: &str) -> Result<()> {
+        jobs::JobBatchRepo::create(&self.pool, id, req, created_by).await
+    }
+
+    pub async fn get_job_batch(&self, id: &str) -> Result<Option<JobBatch>> {
+        jobs::JobBatchRepo::get(&self.pool, id).await
+    }
+
+    pub async fn list_jobs_failed_since(&self, since: DateTime<Utc>) -> Result<Vec<Job>> {
+        jobs::JobRepo::list_failed_since(&self.pool, since).await
+    }
+
+    pub async fn list_jobs_finished_since(&self, since: DateTime<Utc>) -> Result<Vec<Job>> {
+        jobs::JobRepo::list_finished_since(&self.pool, since).await
+    }
+
+    pub async fn list_jobs_stuck(&self) -> Result<Vec<Job>> {
+        jobs::JobRepo::list_stuck(&self.pool).await
+    }
+
+    // ========== Idempotency Key Operations ==========
+
+    pub async fn reserve_idempotency_key(
+        &self,
+        username: &str,
+        key: &str,
+        request_hash: &str,
+        expired_before: DateTime<Utc>,
+        abandoned_before: DateTime<Utc>,
+    ) -> Result<IdempotencyReservation> {
+        idempotency::IdempotencyRepo::reserve(&self.pool, username, key, request_hash, expired_before, abandoned_before)
+            .await
+    }
+
+    pub async fn complete_idempotency_key(
+        &self,
+        username: &str,
+        key: &str,
+        status_code: u16,
+        content_type: &str,
+        body: &[u8],
+    ) -> Result<()> {
+        idempotency::IdempotencyRepo::complete(&self.pool, username, key, status_code, content_type, body).await
+    }
+
+    pub async fn release_idempotency_key(&self, username: &str, key: &str) -> Result<()> {
+        idempotency::IdempotencyRepo::release(&self.pool, username, key).await
+    }
+
+    // ========== Job Template Operations ==========
+
+    pub async fn list_job_templates(&self) -> Result<Vec<JobTemplate>> {
+        job_templates::JobTemplateRepo::list(&self.pool).await
+    }
+
+    pub async fn get_job_template(&self, id: i64) -> Result<Option<JobTemplate>> {
+        job_templates::JobTemplateRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_job_template(&self, req: &CreateJobTemplateRequest) -> Result<JobTemplate> {
+        job_templates::JobTemplateRepo::create(&self.pool, req).await
+    }
+
+    pub async fn update_job_template(&self, id: i64, req: &CreateJobTemplateRequest) -> Result<JobTemplate> {
+        job_templates::JobTemplateRepo::update(&self.pool, id, req).await
+    }
+
+    pub async fn delete_job_template(&self, id: i64) -> Result<()> {
+        job_templates::JobTemplateRepo::delete(&self.pool, id).await
+    }
+
+    pub async fn list_scheduled_job_templates(&self) -> Result<Vec<JobTemplate>> {
+        job_templates::JobTemplateRepo::list_scheduled(&self.pool).await
+    }
+
+    pub async fn update_job_template_last_run(&self, id: i64) -> Result<()> {
+        job_templates::JobTemplateRepo::update_last_run(&self.pool, id).await
+    }
+
+    /// Resolve the device IDs a job template targets (explicit list, group, saved search, or tag)
+    pub async fn resolve_job_template_targets(&self, tmpl: &JobTemplate) -> Result<Vec<i64>> {
+        match tmpl.target_mode.as_str() {
+            "group" if tmpl.target_group_id != 0 => self.list_group_members(tmpl.target_group_id).await,
+            "saved_search" if tmpl.target_saved_search_id != 0 => {
+                let search = self
+                    .get_saved_search(tmpl.target_saved_search_id)
+                    .await?
+                    .ok_or_else(|| NotFoundError::new("Saved search", &tmpl.target_saved_search_id.to_string()))?;
+                let devices = self.list_devices_filtered(&search.filter).await?;
+                Ok(devices.into_iter().map(|d| d.id).collect())
+            }
+            "tag" if !tmpl.target_tag.is_empty() => {
+                let filter = DeviceFilter { tags: vec![tmpl.target_tag.clone()], ..Default::default() };
+                let devices = self.list_devices_filtered(&filter).await?;
+                Ok(devices.into_iter().map(|d| d.id).collect())
+            }
+            _ => Ok(tmpl.target_device_ids.clone()),
+        }
+    }
+
+    // ========== Saved Search Operations ==========
+
+    pub async fn list_saved_searches(&self) -> Result<Vec<SavedSearch>> {
+        saved_searches::SavedSearchRepo::list(&self.pool).await
+    }
+
+    pub async fn get_saved_search(&self, id: i64) -> Result<Option<SavedSearch>> {
+        saved_searches::SavedSearchRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_saved_search(&self, req: &CreateSavedSearchRequest) -> Result<SavedSearch> {
+        saved_searches::SavedSearchRepo::create(&self.pool, req).await
+    }
+
+    pub async fn update_saved_search(&self, id: i64, req: &CreateSavedSearchRequest) -> Result<SavedSearch> {
+        saved_searches::SavedSearchRepo::update(&self.pool, id, req).await
+    }
+
+    pub async fn delete_saved_search(&self, id: i64) -> Result<()> {
+        saved_searches::SavedSearchRepo::delete(&self.pool, id).await
+    }
+
+    // ========== Docker Stack Operations ==========
+
+    pub async fn list_docker_stacks(&self) -> Result<Vec<DockerStack>> {
+        docker_stacks::DockerStackRepo::list(&self.pool).await
+    }
+
+    pub async fn get_docker_stack(&self, id: i64) -> Result<Option<DockerStack>> {
+        docker_stacks::DockerStackRepo::get(&self.pool, id).await
+    }
+
+    pub async fn get_docker_stack_by_name(&self, name: &str) -> Result<Option<DockerStack>> {
+        docker_stacks::DockerStackRepo::get_by_name(&self.pool, name).await
+    }
+
+    pub async fn create_docker_stack(&self, req: &CreateDockerStackRequest) -> Result<DockerStack> {
+        docker_stacks::DockerStackRepo::create(&self.pool, req).await
+    }
+
+    pub async fn update_docker_stack(&self, id: i64, req: &CreateDockerStackRequest) -> Result<DockerStack> {
+        docker_stacks::DockerStackRepo::update(&self.pool, id, req).await
+    }
+
+    pub async fn delete_docker_stack(&self, id: i64) -> Result<()> {
+        docker_stacks::DockerStackRepo::delete(&self.pool, id).await
+    }
+
+    // ========== Search Operations ==========
+
+    pub async fn search(&self, fts_query: &str, limit: i32) -> Result<SearchResults> {
+        search::SearchRepo::search(&self.pool, fts_query, limit).await
+    }
+
+    // ========== Tag Operations ==========
+
+    pub async fn list_resource_tags(&self, resource_type: &str, resource_id: &str) -> Result<Vec<ResourceTag>> {
+        tags::TagRepo::list_for_resource(&self.pool, resource_type, resource_id).await
+    }
+
+    pub async fn list_all_resource_tags(&self, resource_type: Option<&str>) -> Result<Vec<ResourceTag>> {
+        tags::TagRepo::list_all(&self.pool, resource_type).await
+    }
+
+    pub async fn list_resource_tag_keys(&self) -> Result<Vec<String>> {
+        tags::TagRepo::list_distinct_keys(&self.pool).await
+    }
+
+    pub async fn set_resource_tag(&self, resource_type: &str, resource_id: &str, key: &str, value: &str) -> Result<()> {
+        tags::TagRepo::set(&self.pool, resource_type, resource_id, key, value).await
+    }
+
+    pub async fn delete_resource_tag(&self, resource_type: &str, resource_id: &str, key: &str) -> Result<()> {
+        tags::TagRepo::delete(&self.pool, resource_type, resource_id, key).await
+    }
+
+    pub async fn find_tagged_resource_ids(&self, resource_type: &str, selector: &TagSelector) -> Result<Vec<String>> {
+        tags::TagRepo::find_resource_ids(&self.pool, resource_type, selector).await
+    }
+
+    // ========== Topology Operations ==========
+
+    pub async fn list_topologies(&self) -> Result<Vec<Topology>> {
+        topologies::TopologyRepo::list(&self.pool).await
+    }
+
+    pub async fn get_topology(&self, id: i64) -> Result<Option<Topology>> {
+        topologies::TopologyRepo::get(&self.pool, id).await
+    }
+
+    pub async fn create_topology(&self, req: &CreateTopologyRequest) -> Result<Topology> {
         topologies::TopologyRepo::create(&self.pool, req).await
     }
 
@@ -994,6 +2345,78 @@ impl Store {
         Ok(())
     }
 
+    // ========== Topology Deploy Operations ==========
+
+    pub async fn get_topology_deploy(&self, id: i64) -> Result<Option<TopologyDeploy>> {
+        topology_deploys::TopologyDeployRepo::get(&self.pool, id).await
+    }
+
+    pub async fn list_topology_deploys(&self, topology_id: i64) -> Result<Vec<TopologyDeploy>> {
+        topology_deploys::TopologyDeployRepo::list_for_topology(&self.pool, topology_id).await
+    }
+
+    pub async fn create_topology_deploy(
+        &self,
+        topology_id: i64,
+        stages: &[Vec<String>],
+        verify_command: &str,
+        lab: bool,
+        triggered_by: &str,
+        plan: &[(i64, i64)],
+    ) -> Result<TopologyDeploy> {
+        topology_deploys::TopologyDeployRepo::create(&self.pool, topology_id, stages, verify_command, lab, triggered_by, plan).await
+    }
+
+    pub async fn list_topology_deploy_devices(&self, deploy_id: i64) -> Result<Vec<TopologyDeployDevice>> {
+        topology_deploys::TopologyDeployRepo::list_devices(&self.pool, deploy_id).await
+    }
+
+    pub async fn set_topology_deploy_stage(&self, id: i64, stage: i64) -> Result<()> {
+        topology_deploys::TopologyDeployRepo::set_stage(&self.pool, id, stage).await
+    }
+
+    pub async fn set_topology_deploy_job(&self, id: i64, device_id: i64, job_id: &str) -> Result<()> {
+        topology_deploys::TopologyDeployRepo::set_deploy_job(&self.pool, id, device_id, job_id).await
+    }
+
+    pub async fn set_topology_verify_job(&self, id: i64, device_id: i64, job_id: &str) -> Result<()> {
+        topology_deploys::TopologyDeployRepo::set_verify_job(&self.pool, id, device_id, job_id).await
+    }
+
+    pub async fn finish_topology_deploy(&self, id: i64, status: &str, error: &str) -> Result<()> {
+        topology_deploys::TopologyDeployRepo::finish(&self.pool, id, status, error).await
+    }
+
+    pub async fn fail_running_topology_deploys(&self, error: &str) -> Result<u64> {
+        topology_deploys::TopologyDeployRepo::fail_running(&self.pool, error).await
+    }
+
+    // ========== Lab Node Operations ==========
+
+    pub async fn list_lab_nodes(&self, topology_id: i64) -> Result<Vec<LabNode>> {
+        lab_nodes::LabNodeRepo::list_for_topology(&self.pool, topology_id).await
+    }
+
+    pub async fn get_lab_node_for_device(&self, device_id: i64) -> Result<Option<LabNode>> {
+        lab_nodes::LabNodeRepo::get_for_device(&self.pool, device_id).await
+    }
+
+    pub async fn upsert_lab_node(
+        &self,
+        topology_id: i64,
+        device_id: i64,
+        container_name: &str,
+        container_id: &str,
+        image: &str,
+        mgmt_ip: &str,
+    ) -> Result<()> {
+        lab_nodes::LabNodeRepo::upsert(&self.pool, topology_id, device_id, container_name, container_id, image, mgmt_ip).await
+    }
+
+    pub async fn delete_lab_nodes(&self, topology_id: i64) -> Result<u64> {
+        lab_nodes::LabNodeRepo::delete_for_topology(&self.pool, topology_id).await
+    }
+
     // ========== Group Operations ==========
 
     pub async fn list_groups(&self) -> Result<Vec<Group>> {
@@ -1009,15 +2432,21 @@ impl Store {
     }
 
     pub async fn create_group(&self, req: &CreateGroupRequest) -> Result<Group> {
-        groups::GroupRepo::create(&self.pool, req).await
+        let result = groups::GroupRepo::create(&self.pool, req).await;
+        self.cache.groups.invalidate();
+        result
     }
 
     pub async fn update_group(&self, id: i64, req: &CreateGroupRequest) -> Result<Group> {
-        groups::GroupRepo::update(&self.pool, id, req).await
+        let result = groups::GroupRepo::update(&self.pool, id, req).await;
+        self.cache.groups.invalidate();
+        result
     }
 
     pub async fn delete_group(&self, id: i64) -> Result<()> {
-        groups::GroupRepo::delete(&self.pool, id).await
+        let result = groups::GroupRepo::delete(&self.pool, id).await;
+        self.cache.groups.invalidate();
+        result
     }
 
     // ========== Group Variable Operations ==========
@@ -1044,6 +2473,10 @@ impl Store {
         groups::GroupRepo::list_device_groups(&self.pool, device_id).await
     }
 
+    pub async fn list_group_memberships(&self) -> Result<Vec<(i64, i64)>> {
+        groups::GroupRepo::list_memberships(&self.pool).await
+    }
+
     pub async fn add_device_to_group(&self, device_id: i64, group_id: i64) -> Result<()> {
         groups::GroupRepo::add_device_to_group(&self.pool, device_id, group_id).await
     }
@@ -1072,12 +2505,40 @@ impl Store {
 
     // ========== Variable Resolution ==========
 
+    /// Every group without counts, for walking the hierarchy
+    async fn group_hierarchy(&self) -> Result<Arc<Vec<Group>>> {
+        self.cache.groups.get_or_load(|| groups::GroupRepo::list_all_raw(&self.pool)).await
+    }
+
     pub async fn resolve_device_variables(&self, device_id: i64) -> Result<ResolvedVariablesResponse> {
-        variable_resolution::VariableResolver::resolve(&self.pool, device_id).await
+        let all_groups = self.group_hierarchy().await?;
+        variable_resolution::VariableResolver::resolve(&self.pool, &all_groups, device_id).await
     }
 
     pub async fn resolve_device_variables_flat(&self, device_id: i64) -> Result<HashMap<String, String>> {
-        variable_resolution::VariableResolver::resolve_flat(&self.pool, device_id).await
+        let all_groups = self.group_hierarchy().await?;
+        variable_resolution::VariableResolver::resolve_flat(&self.pool, &all_groups, device_id).await
+    }
+
+    /// Variable keys set on any device, group, tenant or GPU cluster
+    pub async fn list_all_variable_keys(&self) -> Result<HashSet<String>> {
+        variable_resolution::VariableResolver::all_keys(&self.pool).await
+    }
+
+    pub async fn resolve_group_credential(&self, device_id: i64) -> Result<Option<i64>> {
+        let all_groups = self.group_hierarchy().await?;
+        variable_resolution::VariableResolver::resolve_group_credential(&self.pool, &all_groups, device_id).await
+    }
+
+    // ========== Lookup Cache ==========
+
+    pub fn lookup_cache_stats(&self) -> Vec<LookupCacheStats> {
+        self.cache.stats()
+    }
+
+    /// Drop every cached lookup, e.g. after the database was changed outside the Store
+    pub fn clear_lookup_cache(&self) {
+        self.cache.clear();
     }
 
     // ========== Credential Operations ==========
@@ -1102,6 +2563,26 @@ impl Store {
         credentials::CredentialRepo::delete(&self.pool, id).await
     }
 
+    pub async fn record_credential_usage(&self, id: i64, device_id: Option<i64>, job_id: &str) -> Result<()> {
+        credentials::CredentialRepo::record_usage(&self.pool, id, device_id, job_id).await
+    }
+
+    pub async fn list_credential_usage(&self, id: i64, limit: i64) -> Result<Vec<CredentialUsage>> {
+        credentials::CredentialRepo::list_usage(&self.pool, id, limit).await
+    }
+
+    pub async fn credential_used_device_ids(&self, id: i64) -> Result<Vec<i64>> {
+        credentials::CredentialRepo::used_device_ids(&self.pool, id).await
+    }
+
+    pub async fn audit_credentials(&self, id: Option<i64>, max_age_days: i64) -> Result<Vec<CredentialAudit>> {
+        credentials::CredentialRepo::audit(&self.pool, id, max_age_days).await
+    }
+
+    pub async fn rotate_credential(&self, id: i64, password: &str) -> Result<Credential> {
+        credentials::CredentialRepo::rotate(&self.pool, id, password).await
+    }
+
     // ========== Device Role Operations ==========
 
     pub async fn list_device_roles(&self) -> Result<Vec<DeviceRole>> {
@@ -1128,6 +2609,67 @@ impl Store {
         device_roles::DeviceRoleRepo::delete(&self.pool, id).await
     }
 
+    /// The device role of a device, looked up as "{vendor}-{role}" first, then "{role}" (topology
+    /// roles are stored with spaces, role names with hyphens)
+    pub async fn resolve_device_role(
+        &self,
+        vendor: Option<&Vendor>,
+        topology_role: Option<&str>,
+    ) -> Result<Option<DeviceRole>> {
+        let role_key = match topology_role.map(str::trim) {
+            Some(r) if !r.is_empty() => r.to_lowercase().replace(' ', "-"),
+            _ => return Ok(None),
+        };
+        let mut candidates = Vec::new();
+        if let Some(v) = vendor {
+            candidates.push(format!("{}-{}", v.name.to_lowercase(), role_key));
+        }
+        candidates.push(role_key);
+
+        for name in &candidates {
+            if let Some(r) = self.find_device_role_by_name(name).await? {
+                return Ok(Some(r));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Resolve the role templates for a device from its device role's explicit template mappings.
+    ///
+    /// The device role is found with `resolve_device_role`. Only templates for the device's
+    /// vendor — or the base template's vendor when the device has none — and vendor-agnostic
+    /// templates apply. Results keep the role's sort order.
+    pub async fn resolve_role_templates(
+        &self,
+        base: &Template,
+        vendor: Option<&str>,
+        topology_role: Option<&str>,
+    ) -> Result<Vec<Template>> {
+        if topology_role.is_none_or(|r| r.trim().is_empty()) {
+            return Ok(Vec::new());
+        }
+        let vendor = match vendor {
+            Some(v) if !v.is_empty() => self.resolve_vendor(v).await?,
+            _ => None,
+        };
+        let role = self.resolve_device_role(vendor.as_ref(), topology_role).await?;
+        let template_ids = match role.and_then(|r| r.template_ids) {
+            Some(ids) => ids,
+            None => return Ok(Vec::new()),
+        };
+
+        let vendor_id = vendor.map(|v| v.id).or(base.vendor_id);
+        let mut templates = Vec::new();
+        for tid in template_ids {
+            if let Some(t) = self.get_template(tid).await? {
+                if t.id != base.id && (t.vendor_id.is_none() || t.vendor_id == vendor_id) {
+                    templates.push(t);
+                }
+            }
+        }
+        Ok(templates)
+    }
+
     // ========== Output Parser Operations ==========
 
     pub async fn list_output_parsers(&self) -> Result<Vec<OutputParser>> {
@@ -1172,6 +2714,26 @@ impl Store {
         gpu_clusters::GpuClusterRepo::delete(&self.pool, id).await
     }
 
+    pub async fn list_gpu_cluster_variables(&self, gpu_cluster_id: i64) -> Result<Vec<GpuClusterVariable>> {
+        gpu_clusters::GpuClusterRepo::list_variables(&self.pool, gpu_cluster_id).await
+    }
+
+    pub async fn set_gpu_cluster_variable(&self, gpu_cluster_id: i64, key: &str, value: &str) -> Result<()> {
+        gpu_clusters::GpuClusterRepo::set_variable(&self.pool, gpu_cluster_id, key, value).await
+    }
+
+    pub async fn delete_gpu_cluster_variable(&self, gpu_cluster_id: i64, key: &str) -> Result<()> {
+        gpu_clusters::GpuClusterRepo::delete_variable(&self.pool, gpu_cluster_id, key).await
+    }
+
+    pub async fn assign_device_gpu_cluster(&self, device_id: i64, gpu_cluster_id: Option<i64>) -> Result<()> {
+        gpu_clusters::GpuClusterRepo::assign_device(&self.pool, device_id, gpu_cluster_id).await
+    }
+
+    pub async fn set_gpu_cluster_devices(&self, gpu_cluster_id: i64, device_ids: &[i64]) -> Result<()> {
+        gpu_clusters::GpuClusterRepo::set_devices(&self.pool, gpu_cluster_id, device_ids).await
+    }
+
     // ========== Tenant Operations ==========
 
     pub async fn list_tenants(&self) -> Result<Vec<Tenant>> {
@@ -1194,6 +2756,26 @@ impl Store {
         tenants::TenantRepo::delete(&self.pool, id).await
     }
 
+    pub async fn list_tenant_variables(&self, tenant_id: i64) -> Result<Vec<TenantVariable>> {
+        tenants::TenantRepo::list_variables(&self.pool, tenant_id).await
+    }
+
+    pub async fn set_tenant_variable(&self, tenant_id: i64, key: &str, value: &str) -> Result<()> {
+        tenants::TenantRepo::set_variable(&self.pool, tenant_id, key, value).await
+    }
+
+    pub async fn delete_tenant_variable(&self, tenant_id: i64, key: &str) -> Result<()> {
+        tenants::TenantRepo::delete_variable(&self.pool, tenant_id, key).await
+    }
+
+    pub async fn assign_device_tenant(&self, device_id: i64, tenant_id: Option<i64>) -> Result<()> {
+        tenants::TenantRepo::assign_device(&self.pool, device_id, tenant_id).await
+    }
+
+    pub async fn set_tenant_devices(&self, tenant_id: i64, device_ids: &[i64]) -> Result<()> {
+        tenants::TenantRepo::set_devices(&self.pool, tenant_id, device_ids).await
+    }
+
     // ========== Ensure "all" group ==========
 
     async fn ensure_all_group(&self) -> Result<()> {
@@ -1205,6 +2787,7 @@ impl Store {
                 description: Some("Default group — all devices inherit from this".to_string()),
                 parent_id: None,
                 precedence: 0,
+                credential_id: None,
             };
             groups::GroupRepo::create(&self.pool, &req).await?;
         }
@@ -1232,6 +2815,7 @@ impl Store {
                     description: Some(description.to_string()),
                     parent_id: None,
                     precedence,
+                    credential_id: None,
                 };
                 groups::GroupRepo::create(&self.pool, &req).await?;
             }
@@ -1291,6 +2875,30 @@ impl Store {
         }
         Ok(())
     }
+
+    /// Rewrite device MACs stored in other notations in their canonical form. MACs that don't
+    /// parse are left alone and reported.
+    async fn normalize_device_macs(&self) -> Result<()> {
+        let rows: Vec<(i64, String)> = sqlx::query_as("SELECT id, mac FROM devices WHERE mac != '' AND mac IS NOT NULL")
+            .fetch_all(&self.pool)
+            .await?;
+
+        for (device_id, mac) in &rows {
+            match crate::utils::parse_mac(mac) {
+                Some(canonical) if canonical != *mac => {
+                    sqlx::query("UPDATE devices SET mac = ? WHERE id = ?")
+                        .bind(&canonical)
+                        .bind(device_id)
+                        .execute(&self.pool)
+                        .await?;
+                    tracing::info!("Normalized device {} MAC '{}' -> '{}'", device_id, mac, canonical);
+                }
+                Some(_) => {}
+                None => tracing::warn!("Device {} has an invalid MAC '{}'", device_id, mac),
+            }
+        }
+        Ok(())
+    }
 }
 
 // Re-export seed helpers for the API
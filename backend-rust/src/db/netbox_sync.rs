@@ -0,0 +1,122 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+use std::collections::BTreeMap;
+
+use crate::models::*;
+use super::row_helpers::none_if_empty;
+
+fn map_conflict_row(row: &SqliteRow) -> SyncConflict {
+    let fields_json: String = row.get("fields");
+    SyncConflict {
+        id: row.get("id"),
+        device_id: row.get("device_id"),
+        device_hostname: row.try_get("device_hostname").ok().flatten(),
+        netbox_id: row.get("netbox_id"),
+        fields: serde_json::from_str(&fields_json).unwrap_or_default(),
+        status: row.get("status"),
+        resolution: none_if_empty(row.get("resolution")),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        resolved_at: row.get("resolved_at"),
+    }
+}
+
+const SELECT_CONFLICT: &str = r#"
+    SELECT c.*, d.hostname as device_hostname
+    FROM netbox_sync_conflicts c
+    LEFT JOIN devices d ON c.device_id = d.id
+"#;
+
+/// NetBox sync baselines and the pull conflict queue
+pub struct NetBoxSyncRepo;
+
+impl NetBoxSyncRepo {
+    /// Field values NetBox and the device last agreed on, if the device has been synced
+    pub async fn get_baseline(pool: &Pool<Sqlite>, device_id: i64) -> Result<Option<BTreeMap<String, String>>> {
+        let baseline: Option<String> = sqlx::query_scalar("SELECT baseline FROM netbox_sync_state WHERE device_id = ?")
+            .bind(device_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(baseline.map(|b| serde_json::from_str(&b).unwrap_or_default()))
+    }
+
+    pub async fn set_baseline(pool: &Pool<Sqlite>, device_id: i64, netbox_id: i64, baseline: &BTreeMap<String, String>) -> Result<()> {
+        sqlx::query(
+            r#"INSERT INTO netbox_sync_state (device_id, netbox_id, baseline, synced_at) VALUES (?, ?, ?, ?)
+               ON CONFLICT(device_id) DO UPDATE SET
+                   netbox_id = excluded.netbox_id, baseline = excluded.baseline, synced_at = excluded.synced_at"#
+        )
+        .bind(device_id)
+        .bind(netbox_id)
+        .bind(serde_json::to_string(baseline)?)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_conflicts(pool: &Pool<Sqlite>, status: &str) -> Result<Vec<SyncConflict>> {
+        let rows = sqlx::query(&format!("{} WHERE c.status = ? ORDER BY c.updated_at DESC, c.id DESC", SELECT_CONFLICT))
+            .bind(status)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_conflict_row).collect())
+    }
+
+    pub async fn get_conflict(pool: &Pool<Sqlite>, id: i64) -> Result<Option<SyncConflict>> {
+        let row = sqlx::query(&format!("{} WHERE c.id = ?", SELECT_CONFLICT))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_conflict_row))
+    }
+
+    /// Open a conflict for the device, or refresh the fields of its pending one
+    pub async fn upsert_pending_conflict(pool: &Pool<Sqlite>, device_id: i64, netbox_id: i64, fields: &[SyncConflictField]) -> Result<()> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"INSERT INTO netbox_sync_conflicts (device_id, netbox_id, fields, status, created_at, updated_at)
+               VALUES (?, ?, ?, ?, ?, ?)
+               ON CONFLICT(device_id) WHERE status = 'pending' DO UPDATE SET
+                   netbox_id = excluded.netbox_id, fields = excluded.fields, updated_at = excluded.updated_at"#
+        )
+        .bind(device_id)
+        .bind(netbox_id)
+        .bind(serde_json::to_string(fields)?)
+        .bind(sync_conflict_status::PENDING)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Drop the device's pending conflict once the two sides agree again
+    pub async fn clear_pending_conflict(pool: &Pool<Sqlite>, device_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM netbox_sync_conflicts WHERE device_id = ? AND status = ?")
+            .bind(device_id)
+            .bind(sync_conflict_status::PENDING)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn mark_resolved(pool: &Pool<Sqlite>, id: i64, resolution: &str) -> Result<SyncConflict> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            "UPDATE netbox_sync_conflicts SET status = ?, resolution = ?, resolved_at = ?, updated_at = ? WHERE id = ?"
+        )
+        .bind(sync_conflict_status::RESOLVED)
+        .bind(resolution)
+        .bind(now)
+        .bind(now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Sync conflict", &id.to_string()).into());
+        }
+        Self::get_conflict(pool, id).await?.context("Sync conflict not found after resolution")
+    }
+}
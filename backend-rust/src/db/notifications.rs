@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_notification_channel_row(row: &SqliteRow) -> NotificationChannel {
+    let config: String = row.get("config");
+    NotificationChannel {
+        id: row.get("id"),
+        name: row.get("name"),
+        channel_type: row.get("channel_type"),
+        config: serde_json::from_str(&config).unwrap_or_default(),
+        enabled: row.get("enabled"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Notification channel database operations
+pub struct NotificationChannelRepo;
+
+impl NotificationChannelRepo {
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<NotificationChannel>> {
+        let rows = sqlx::query("SELECT * FROM notification_channels ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_notification_channel_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<NotificationChannel>> {
+        let row = sqlx::query("SELECT * FROM notification_channels WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_notification_channel_row))
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, req: &CreateNotificationChannelRequest) -> Result<NotificationChannel> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO notification_channels (name, channel_type, config, enabled, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.channel_type)
+        .bind(req.config.to_string())
+        .bind(req.enabled)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get(pool, result.last_insert_rowid())
+            .await?
+            .context("Notification channel not found after creation")
+    }
+
+    pub async fn update(pool: &Pool<Sqlite>, id: i64, req: &CreateNotificationChannelRequest) -> Result<NotificationChannel> {
+        let result = sqlx::query(
+            "UPDATE notification_channels SET name = ?, channel_type = ?, config = ?, enabled = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(&req.name)
+        .bind(&req.channel_type)
+        .bind(req.config.to_string())
+        .bind(req.enabled)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Notification channel", &id.to_string()).into());
+        }
+        Self::get(pool, id)
+            .await?
+            .context("Notification channel not found after update")
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM notification_channels WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Notification channel", &id.to_string()).into());
+        }
+        Ok(())
+    }
+}
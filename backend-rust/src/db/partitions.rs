@@ -0,0 +1,407 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, SqliteConnection, sqlite::SqliteRow};
+
+use crate::models::*;
+
+/// A history table split by month. Rows whose `time_column` falls in a past month move to that
+/// month's partition once `archivable` holds for them; readers use the `{name}_all` view.
+struct PartitionedTable {
+    name: &'static str,
+    time_column: &'static str,
+    /// Condition a row must also meet to leave the live table
+    archivable: &'static str,
+    /// Column lists indexed on every partition
+    indexes: &'static [&'static str],
+    /// Columns of the live table's search index; each partition gets its own index over them
+    fts: &'static [&'static str],
+}
+
+const PARTITIONED_TABLES: &[PartitionedTable] = &[
+    // Queued and running jobs are still being updated
+    PartitionedTable {
+        name: "jobs",
+        time_column: "created_at",
+        archivable: "status IN ('completed', 'failed', 'cancelled')",
+        indexes: &["id", "device_id, created_at", "batch_id", "completed_at"],
+        fts: &["command", "output", "error"],
+    },
+    // Events with syslog alerts stay, since the alerts reference them
+    PartitionedTable {
+        name: "syslog_events",
+        time_column: "received_at",
+        archivable: "NOT EXISTS (SELECT 1 FROM syslog_alerts a WHERE a.event_id = syslog_events.id)",
+        indexes: &["id", "device_id, received_at", "received_at"],
+        fts: &[],
+    },
+    PartitionedTable {
+        name: "discovery_logs",
+        time_column: "created_at",
+        archivable: "1",
+        indexes: &["id", "mac", "created_at"],
+        fts: &["hostname", "mac", "ip", "vendor", "message"],
+    },
+    PartitionedTable {
+        name: "device_status_history",
+        time_column: "changed_at",
+        archivable: "1",
+        indexes: &["id", "device_id, changed_at", "changed_at"],
+        fts: &[],
+    },
+];
+
+fn map_partition_row(row: &SqliteRow) -> TablePartition {
+    TablePartition {
+        id: row.get("id"),
+        table_name: row.get("table_name"),
+        partition_name: row.get("partition_name"),
+        month: row.get("month"),
+        row_count: row.get("row_count"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn partitioned_table(name: &str) -> Result<&'static PartitionedTable> {
+    PARTITIONED_TABLES
+        .iter()
+        .find(|t| t.name == name)
+        .with_context(|| format!("{} is not a partitioned table", name))
+}
+
+async fn columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>> {
+    Ok(sqlx::query_scalar("SELECT name FROM pragma_table_info(?) ORDER BY cid")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?)
+}
+
+pub(super) async fn partition_names(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>> {
+    Ok(sqlx::query_scalar("SELECT partition_name FROM table_partitions WHERE table_name = ? ORDER BY month")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?)
+}
+
+/// A table and its partitions, for writes that must reach archived rows too. Tables that aren't
+/// partitioned come back alone.
+pub(super) async fn with_partitions(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>> {
+    let mut tables = vec![table.to_string()];
+    tables.extend(partition_names(conn, table).await?);
+    Ok(tables)
+}
+
+async fn table_exists(conn: &mut SqliteConnection, name: &str) -> Result<bool> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE name = ?")
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(count > 0)
+}
+
+/// (column, parent table, parent column) for each of a table's foreign keys
+async fn foreign_keys(conn: &mut SqliteConnection, table: &str) -> Result<Vec<(String, String, String)>> {
+    Ok(sqlx::query_as(r#"SELECT "from", "table", COALESCE("to", 'id') FROM pragma_foreign_key_list(?)"#)
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?)
+}
+
+/// Create a partition from the live table's own CREATE TABLE statement, so it keeps the column
+/// types, constraints and foreign keys (deleting a device cascades into archived rows too)
+async fn create_partition_table(conn: &mut SqliteConnection, table: &str, partition: &str) -> Result<()> {
+    let sql: String = sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?")
+        .bind(table)
+        .fetch_one(&mut *conn)
+        .await?;
+    let body = sql.find('(').map(|i| &sql[i..]).with_context(|| format!("unexpected schema for {}", table))?;
+    sqlx::query(&format!("CREATE TABLE IF NOT EXISTS {} {}", partition, body))
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Index a partition, and give it a search index kept current by triggers like the live table's
+async fn create_partition_indexes(conn: &mut SqliteConnection, table: &PartitionedTable, partition: &str) -> Result<()> {
+    for (i, index) in table.indexes.iter().enumerate() {
+        sqlx::query(&format!("CREATE INDEX IF NOT EXISTS idx_{}_{} ON {}({})", partition, i, partition, index))
+            .execute(&mut *conn)
+            .await?;
+    }
+    if table.fts.is_empty() {
+        return Ok(());
+    }
+    let fts = format!("{}_fts", partition);
+    let columns = table.fts.join(", ");
+    let values = |row: &str| table.fts.iter().map(|c| format!("{}.{}", row, c)).collect::<Vec<_>>().join(", ");
+    let statements = [
+        format!("CREATE VIRTUAL TABLE IF NOT EXISTS {fts} USING fts5({columns}, content='{partition}', content_rowid='rowid')"),
+        format!(
+            "CREATE TRIGGER IF NOT EXISTS trg_{fts}_insert AFTER INSERT ON {partition} BEGIN \
+             INSERT INTO {fts}(rowid, {columns}) VALUES (new.rowid, {new}); END",
+            new = values("new"),
+        ),
+        format!(
+            "CREATE TRIGGER IF NOT EXISTS trg_{fts}_delete AFTER DELETE ON {partition} BEGIN \
+             INSERT INTO {fts}({fts}, rowid, {columns}) VALUES ('delete', old.rowid, {old}); END",
+            old = values("old"),
+        ),
+        format!(
+            "CREATE TRIGGER IF NOT EXISTS trg_{fts}_update AFTER UPDATE ON {partition} BEGIN \
+             INSERT INTO {fts}({fts}, rowid, {columns}) VALUES ('delete', old.rowid, {old}); \
+             INSERT INTO {fts}(rowid, {columns}) VALUES (new.rowid, {new}); END",
+            old = values("old"),
+            new = values("new"),
+        ),
+    ];
+    for statement in statements {
+        sqlx::query(&statement).execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+/// Bring a partition made by an earlier version up to date. Those were created without the
+/// live table's foreign keys and search index; the rebuilt partition leaves out the rows its
+/// foreign keys would already have removed, such as status history of deleted devices.
+/// The table's view must be dropped first, as renaming a table checks the views using it.
+async fn upgrade_partition(conn: &mut SqliteConnection, table: &PartitionedTable, partition: &str) -> Result<()> {
+    let live_keys = foreign_keys(conn, table.name).await?;
+    if !live_keys.is_empty() && foreign_keys(conn, partition).await?.is_empty() {
+        let live = columns(conn, table.name).await?;
+        sync_columns(conn, &live, partition).await?;
+        let rebuilt = format!("{}_rebuild", partition);
+        create_partition_table(conn, table.name, &rebuilt).await?;
+
+        let column_list = live.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(", ");
+        let referenced = live_keys
+            .iter()
+            .map(|(column, parent, parent_column)| {
+                format!("(\"{c}\" IS NULL OR \"{c}\" IN (SELECT \"{}\" FROM {}))", parent_column, parent, c = column)
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        sqlx::query(&format!(
+            "INSERT INTO {rebuilt} ({cols}) SELECT {cols} FROM {partition} WHERE {referenced}",
+            cols = column_list,
+        ))
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query(&format!("DROP TABLE {}", partition)).execute(&mut *conn).await?;
+        sqlx::query(&format!("ALTER TABLE {} RENAME TO {}", rebuilt, partition)).execute(&mut *conn).await?;
+        sqlx::query(&format!(
+            "UPDATE table_partitions SET row_count = (SELECT COUNT(*) FROM {}) WHERE partition_name = ?",
+            partition
+        ))
+        .bind(partition)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let fts = format!("{}_fts", partition);
+    let indexed = table.fts.is_empty() || table_exists(conn, &fts).await?;
+    create_partition_indexes(conn, table, partition).await?;
+    if !indexed {
+        sqlx::query(&format!("INSERT INTO {fts}({fts}) VALUES ('rebuild')"))
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Add the columns a partition is missing, after a migration added them to the live table
+async fn sync_columns(conn: &mut SqliteConnection, live: &[String], partition: &str) -> Result<()> {
+    let existing = columns(conn, partition).await?;
+    for column in live.iter().filter(|c| !existing.contains(c)) {
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN \"{}\"", partition, column))
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Recreate a table's `{name}_all` view over the live table and each of its partitions
+async fn rebuild_view(conn: &mut SqliteConnection, table: &PartitionedTable) -> Result<()> {
+    let live = columns(conn, table.name).await?;
+    let column_list = live.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(", ");
+    let mut selects = vec![format!("SELECT {} FROM {}", column_list, table.name)];
+    for partition in partition_names(conn, table.name).await? {
+        sync_columns(conn, &live, &partition).await?;
+        selects.push(format!("SELECT {} FROM {}", column_list, partition));
+    }
+    sqlx::query(&format!("DROP VIEW IF EXISTS {}_all", table.name))
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(&format!("CREATE VIEW {}_all AS {}", table.name, selects.join(" UNION ALL ")))
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Monthly partitions of the history tables
+pub struct PartitionRepo;
+
+impl PartitionRepo {
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<TablePartition>> {
+        let rows = sqlx::query("SELECT * FROM table_partitions ORDER BY table_name, month DESC")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_partition_row).collect())
+    }
+
+    /// Upgrade partitions made by earlier versions and recreate every `{table}_all` view, so
+    /// they follow columns added to the live tables
+    pub async fn rebuild_views(pool: &Pool<Sqlite>) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        for table in PARTITIONED_TABLES {
+            sqlx::query(&format!("DROP VIEW IF EXISTS {}_all", table.name))
+                .execute(&mut *tx)
+                .await?;
+            for partition in partition_names(&mut tx, table.name).await? {
+                upgrade_partition(&mut tx, table, &partition).await?;
+            }
+            rebuild_view(&mut tx, table).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Move the archivable rows of months before `current_month` (YYYY-MM) out of the live
+    /// tables, one transaction per table and month. Returns the rows moved and the partitions
+    /// created.
+    pub async fn rollover(pool: &Pool<Sqlite>, current_month: &str) -> Result<(i64, Vec<String>)> {
+        let (current_start, _) = crate::utils::month_bounds(current_month)
+            .with_context(|| format!("invalid month '{}'", current_month))?;
+        let mut moved = 0;
+        let mut created = Vec::new();
+
+        for table in PARTITIONED_TABLES {
+            let months: Vec<String> = sqlx::query_scalar(&format!(
+                "SELECT DISTINCT substr({col}, 1, 7) FROM {table} WHERE {col} < ? AND {cond} ORDER BY 1",
+                col = table.time_column,
+                table = table.name,
+                cond = table.archivable,
+            ))
+            .bind(current_start.format("%Y-%m-%d").to_string())
+            .fetch_all(pool)
+            .await?;
+
+            for month in months {
+                let Some((start, end)) = crate::utils::month_bounds(&month) else {
+                    tracing::warn!("Partitioning {}: skipping rows with unparseable {} '{}'", table.name, table.time_column, month);
+                    continue;
+                };
+                let partition = format!("{}_p{}", table.name, month.replace('-', ""));
+                let now = Utc::now();
+                let mut tx = pool.begin().await?;
+
+                let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM table_partitions WHERE partition_name = ?")
+                    .bind(&partition)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                let live = columns(&mut tx, table.name).await?;
+                if exists.is_none() {
+                    create_partition_table(&mut tx, table.name, &partition).await?;
+                    create_partition_indexes(&mut tx, table, &partition).await?;
+                    sqlx::query(
+                        "INSERT INTO table_partitions (table_name, partition_name, month, row_count, created_at, updated_at) VALUES (?, ?, ?, 0, ?, ?)",
+                    )
+                    .bind(table.name)
+                    .bind(&partition)
+                    .bind(&month)
+                    .bind(now)
+                    .bind(now)
+                    .execute(&mut *tx)
+                    .await?;
+                } else {
+                    sync_columns(&mut tx, &live, &partition).await?;
+                }
+
+                let column_list = live.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(", ");
+                let rows = format!(
+                    "{col} >= ? AND {col} < ? AND {cond}",
+                    col = table.time_column,
+                    cond = table.archivable
+                );
+                let (start, end) = (start.format("%Y-%m-%d").to_string(), end.format("%Y-%m-%d").to_string());
+                let count = sqlx::query(&format!(
+                    "INSERT INTO {} ({cols}) SELECT {cols} FROM {} WHERE {}",
+                    partition,
+                    table.name,
+                    rows,
+                    cols = column_list
+                ))
+                .bind(&start)
+                .bind(&end)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected() as i64;
+                sqlx::query(&format!("DELETE FROM {} WHERE {}", table.name, rows))
+                    .bind(&start)
+                    .bind(&end)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("UPDATE table_partitions SET row_count = row_count + ?, updated_at = ? WHERE partition_name = ?")
+                    .bind(count)
+                    .bind(now)
+                    .bind(&partition)
+                    .execute(&mut *tx)
+                    .await?;
+                if exists.is_none() {
+                    rebuild_view(&mut tx, table).await?;
+                    created.push(partition);
+                }
+                tx.commit().await?;
+                moved += count;
+            }
+        }
+        Ok((moved, created))
+    }
+
+    /// Drop the partitions of months before `cutoff_month` (YYYY-MM), returning their names
+    pub async fn drop_before(pool: &Pool<Sqlite>, cutoff_month: &str) -> Result<Vec<String>> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT table_name, partition_name FROM table_partitions WHERE month < ? ORDER BY month")
+                .bind(cutoff_month)
+                .fetch_all(pool)
+                .await?;
+        let mut dropped = Vec::new();
+        for (table_name, partition) in rows {
+            Self::drop_partition(pool, &table_name, &partition).await?;
+            dropped.push(partition);
+        }
+        Ok(dropped)
+    }
+
+    /// Drop every partition of a table, e.g. when its log is cleared
+    pub async fn drop_all(pool: &Pool<Sqlite>, table_name: &str) -> Result<()> {
+        let partitions = {
+            let mut conn = pool.acquire().await?;
+            partition_names(&mut conn, table_name).await?
+        };
+        for partition in partitions {
+            Self::drop_partition(pool, table_name, &partition).await?;
+        }
+        Ok(())
+    }
+
+    /// Take a partition out of its table's view, then drop it. Dropping the whole table is
+    /// what keeps expiring a month from locking the live table for a large DELETE.
+    async fn drop_partition(pool: &Pool<Sqlite>, table_name: &str, partition: &str) -> Result<()> {
+        let table = partitioned_table(table_name)?;
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM table_partitions WHERE partition_name = ?")
+            .bind(partition)
+            .execute(&mut *tx)
+            .await?;
+        rebuild_view(&mut tx, table).await?;
+        sqlx::query(&format!("DROP TABLE IF EXISTS {}", partition))
+            .execute(&mut *tx)
+            .await?;
+        // The search index outlives its content table otherwise
+        sqlx::query(&format!("DROP TABLE IF EXISTS {}_fts", partition))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
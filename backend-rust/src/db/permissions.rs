@@ -0,0 +1,170 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_role_row(row: &SqliteRow, members: Vec<RoleMember>, permissions: Vec<Permission>) -> Role {
+    Role {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        members,
+        permissions,
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Role, role membership and permission grant database operations
+pub struct PermissionRepo;
+
+impl PermissionRepo {
+    async fn role_members(pool: &Pool<Sqlite>, role_id: i64) -> Result<Vec<RoleMember>> {
+        let rows = sqlx::query(
+            r#"SELECT u.id, u.username FROM role_members m JOIN users u ON u.id = m.user_id
+               WHERE m.role_id = ? ORDER BY u.username"#,
+        )
+        .bind(role_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|r| RoleMember { user_id: r.get("id"), username: r.get("username") })
+            .collect())
+    }
+
+    async fn load_role(pool: &Pool<Sqlite>, row: &SqliteRow) -> Result<Role> {
+        let id: i64 = row.get("id");
+        let members = Self::role_members(pool, id).await?;
+        let permissions = Self::list_grants(pool, permission_subject::ROLE, id).await?;
+        Ok(map_role_row(row, members, permissions))
+    }
+
+    pub async fn list_roles(pool: &Pool<Sqlite>) -> Result<Vec<Role>> {
+        let rows = sqlx::query("SELECT * FROM roles ORDER BY name").fetch_all(pool).await?;
+        let mut roles = Vec::with_capacity(rows.len());
+        for row in &rows {
+            roles.push(Self::load_role(pool, row).await?);
+        }
+        Ok(roles)
+    }
+
+    pub async fn get_role(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Role>> {
+        let row = sqlx::query("SELECT * FROM roles WHERE id = ?").bind(id).fetch_optional(pool).await?;
+        match row {
+            Some(row) => Ok(Some(Self::load_role(pool, &row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn create_role(pool: &Pool<Sqlite>, req: &CreateRoleRequest) -> Result<Role> {
+        let now = Utc::now();
+        let result = sqlx::query("INSERT INTO roles (name, description, created_at, updated_at) VALUES (?, ?, ?, ?)")
+            .bind(&req.name)
+            .bind(&req.description)
+            .bind(now)
+            .bind(now)
+            .execute(pool)
+            .await?;
+        let id = result.last_insert_rowid();
+        Self::get_role(pool, id).await?.ok_or_else(|| super::NotFoundError::new("Role", &id.to_string()).into())
+    }
+
+    pub async fn update_role(pool: &Pool<Sqlite>, id: i64, req: &CreateRoleRequest) -> Result<Role> {
+        let result = sqlx::query("UPDATE roles SET name = ?, description = ?, updated_at = ? WHERE id = ?")
+            .bind(&req.name)
+            .bind(&req.description)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Role", &id.to_string()).into());
+        }
+        Self::get_role(pool, id).await?.ok_or_else(|| super::NotFoundError::new("Role", &id.to_string()).into())
+    }
+
+    pub async fn delete_role(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM roles WHERE id = ?").bind(id).execute(pool).await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Role", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    pub async fn set_role_members(pool: &Pool<Sqlite>, role_id: i64, user_ids: &[i64]) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM role_members WHERE role_id = ?").bind(role_id).execute(&mut *tx).await?;
+        for user_id in user_ids {
+            sqlx::query("INSERT OR IGNORE INTO role_members (role_id, user_id) VALUES (?, ?)")
+                .bind(role_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn role_ids_for_user(pool: &Pool<Sqlite>, user_id: i64) -> Result<Vec<i64>> {
+        let rows: Vec<(i64,)> = sqlx::query_as("SELECT role_id FROM role_members WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
+    // ========== Grants ==========
+
+    pub async fn list_grants(pool: &Pool<Sqlite>, subject_type: &str, subject_id: i64) -> Result<Vec<Permission>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT resource, action FROM permission_grants WHERE subject_type = ? AND subject_id = ? ORDER BY resource, action",
+        )
+        .bind(subject_type)
+        .bind(subject_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(|(resource, action)| Permission { resource, action }).collect())
+    }
+
+    pub async fn set_grants(pool: &Pool<Sqlite>, subject_type: &str, subject_id: i64, permissions: &[Permission]) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM permission_grants WHERE subject_type = ? AND subject_id = ?")
+            .bind(subject_type)
+            .bind(subject_id)
+            .execute(&mut *tx)
+            .await?;
+        for p in permissions {
+            sqlx::query(
+                "INSERT OR IGNORE INTO permission_grants (subject_type, subject_id, resource, action) VALUES (?, ?, ?, ?)",
+            )
+            .bind(subject_type)
+            .bind(subject_id)
+            .bind(&p.resource)
+            .bind(&p.action)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn list_subjects(pool: &Pool<Sqlite>) -> Result<Vec<SubjectPermissions>> {
+        let rows = sqlx::query(
+            r#"SELECT 'role' AS subject_type, id, name FROM roles
+               UNION ALL SELECT 'team', id, name FROM teams
+               ORDER BY subject_type, name"#,
+        )
+        .fetch_all(pool)
+        .await?;
+        let mut subjects = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let subject_type: String = row.get("subject_type");
+            let subject_id: i64 = row.get("id");
+            let permissions = Self::list_grants(pool, &subject_type, subject_id).await?;
+            subjects.push(SubjectPermissions { subject_type, subject_id, name: row.get("name"), permissions });
+        }
+        Ok(subjects)
+    }
+}
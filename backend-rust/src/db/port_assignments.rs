@@ -177,3 +177,118 @@ impl PortAssignmentRepo {
         Ok(())
     }
 }
+
+fn map_aux_row(row: &SqliteRow) -> AuxConnection {
+    AuxConnection {
+        id: row.get("id"),
+        device_id: row.get("device_id"),
+        kind: row.get("kind"),
+        port_name: row.get("port_name"),
+        remote_device_id: row.get("remote_device_id"),
+        remote_port_name: row.get("remote_port_name"),
+        description: none_if_empty(row.get("description")),
+        cable_length_meters: row.get("cable_length_meters"),
+        device_hostname: row.get("device_hostname"),
+        remote_device_hostname: none_if_empty(row.get("remote_device_hostname")),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+const SELECT_AUX_CONNECTION: &str = r#"
+    SELECT ac.*, d.hostname AS device_hostname, rd.hostname AS remote_device_hostname
+    FROM device_aux_connections ac
+    JOIN devices d ON d.id = ac.device_id
+    LEFT JOIN devices rd ON rd.id = ac.remote_device_id
+"#;
+
+pub struct AuxConnectionRepo;
+
+impl AuxConnectionRepo {
+    /// List a device's power or console connections, and those landing on it as a PDU or
+    /// console server
+    pub async fn list_for_device(pool: &Pool<Sqlite>, device_id: i64, kind: &str) -> Result<Vec<AuxConnection>> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE ac.kind = ? AND (ac.device_id = ? OR ac.remote_device_id = ?) ORDER BY ac.device_id != ?, d.hostname, ac.port_name",
+            SELECT_AUX_CONNECTION
+        ))
+        .bind(kind)
+        .bind(device_id)
+        .bind(device_id)
+        .bind(device_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.iter().map(map_aux_row).collect())
+    }
+
+    /// Find the connection plugged into a PDU outlet or console server port
+    pub async fn find_by_remote(pool: &Pool<Sqlite>, kind: &str, remote_device_id: i64, remote_port_name: &str) -> Result<Option<AuxConnection>> {
+        let row = sqlx::query(&format!(
+            "{} WHERE ac.kind = ? AND ac.remote_device_id = ? AND ac.remote_port_name = ? COLLATE NOCASE",
+            SELECT_AUX_CONNECTION
+        ))
+        .bind(kind)
+        .bind(remote_device_id)
+        .bind(remote_port_name)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.as_ref().map(map_aux_row))
+    }
+
+    /// Upsert a single power or console connection
+    pub async fn set(pool: &Pool<Sqlite>, device_id: i64, kind: &str, port_name: &str, req: &SetAuxConnectionRequest) -> Result<AuxConnection> {
+        let now = chrono::Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO device_aux_connections (device_id, kind, port_name, remote_device_id, remote_port_name,
+                description, cable_length_meters, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(device_id, kind, port_name) DO UPDATE SET
+                remote_device_id = excluded.remote_device_id,
+                remote_port_name = excluded.remote_port_name,
+                description = excluded.description,
+                cable_length_meters = excluded.cable_length_meters,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(device_id)
+        .bind(kind)
+        .bind(port_name)
+        .bind(req.remote_device_id)
+        .bind(&req.remote_port_name)
+        .bind(req.description.as_deref().unwrap_or_default())
+        .bind(req.cable_length_meters)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        let row = sqlx::query(&format!(
+            "{} WHERE ac.device_id = ? AND ac.kind = ? AND ac.port_name = ?",
+            SELECT_AUX_CONNECTION
+        ))
+        .bind(device_id)
+        .bind(kind)
+        .bind(port_name)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(map_aux_row(&row))
+    }
+
+    /// Delete a single power or console connection
+    pub async fn delete(pool: &Pool<Sqlite>, device_id: i64, kind: &str, port_name: &str) -> Result<()> {
+        let result = sqlx::query("DELETE FROM device_aux_connections WHERE device_id = ? AND kind = ? AND port_name = ?")
+            .bind(device_id)
+            .bind(kind)
+            .bind(port_name)
+            .execute(pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Connection", port_name).into());
+        }
+        Ok(())
+    }
+}
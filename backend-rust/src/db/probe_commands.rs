@@ -0,0 +1,112 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_probe_command_row(row: &SqliteRow) -> ProbeCommand {
+    let commands: String = row.get("commands");
+    ProbeCommand {
+        id: row.get("id"),
+        vendor_id: row.get("vendor_id"),
+        field: row.get("field"),
+        commands: serde_json::from_str(&commands).unwrap_or_default(),
+        output_parser_id: row.get("output_parser_id"),
+        max_lines: row.get("max_lines"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Per-vendor device probe command database operations
+pub struct ProbeCommandRepo;
+
+impl ProbeCommandRepo {
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<ProbeCommand>> {
+        let rows = sqlx::query("SELECT * FROM probe_commands ORDER BY vendor_id IS NOT NULL, vendor_id, field")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_probe_command_row).collect())
+    }
+
+    /// The vendor's own rows followed by the default rows (vendor_id NULL)
+    pub async fn list_for_vendor(pool: &Pool<Sqlite>, vendor_id: Option<i64>) -> Result<Vec<ProbeCommand>> {
+        let rows = sqlx::query(
+            "SELECT * FROM probe_commands WHERE vendor_id = ? OR vendor_id IS NULL ORDER BY vendor_id IS NULL, field",
+        )
+        .bind(vendor_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_probe_command_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<ProbeCommand>> {
+        let row = sqlx::query("SELECT * FROM probe_commands WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_probe_command_row))
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, req: &CreateProbeCommandRequest) -> Result<ProbeCommand> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO probe_commands (vendor_id, field, commands, output_parser_id, max_lines, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(req.vendor_id)
+        .bind(&req.field)
+        .bind(serde_json::to_string(&req.commands)?)
+        .bind(req.output_parser_id)
+        .bind(req.max_lines)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get(pool, result.last_insert_rowid())
+            .await?
+            .context("Probe command not found after creation")
+    }
+
+    pub async fn update(pool: &Pool<Sqlite>, id: i64, req: &CreateProbeCommandRequest) -> Result<ProbeCommand> {
+        let result = sqlx::query(
+            r#"
+            UPDATE probe_commands SET vendor_id = ?, field = ?, commands = ?, output_parser_id = ?,
+                                      max_lines = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(req.vendor_id)
+        .bind(&req.field)
+        .bind(serde_json::to_string(&req.commands)?)
+        .bind(req.output_parser_id)
+        .bind(req.max_lines)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("probe command", &id.to_string()).into());
+        }
+
+        Self::get(pool, id)
+            .await?
+            .context("Probe command not found after update")
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM probe_commands WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("probe command", &id.to_string()).into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_session_row(row: &SqliteRow) -> ProvisioningSession {
+    ProvisioningSession {
+        id: row.get("id"),
+        device_id: row.get("device_id"),
+        status: row.get("status"),
+        config_file: row.get("config_file"),
+        lease_released: row.get("lease_released"),
+        started_at: row.get("started_at"),
+        lease_at: row.get("lease_at"),
+        config_pulled_at: row.get("config_pulled_at"),
+        completed_at: row.get("completed_at"),
+    }
+}
+
+/// Provisioning session database operations
+pub struct ProvisioningRepo;
+
+impl ProvisioningRepo {
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<ProvisioningSession>> {
+        let row = sqlx::query("SELECT * FROM provisioning_sessions WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_session_row))
+    }
+
+    pub async fn list_for_device(pool: &Pool<Sqlite>, device_id: i64) -> Result<Vec<ProvisioningSession>> {
+        let rows = sqlx::query("SELECT * FROM provisioning_sessions WHERE device_id = ? ORDER BY id DESC")
+            .bind(device_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_session_row).collect())
+    }
+
+    /// Open a session for a device, superseding any session still active
+    pub async fn open(pool: &Pool<Sqlite>, device_id: i64, config_file: &str, lease_released: bool) -> Result<ProvisioningSession> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("UPDATE provisioning_sessions SET status = ? WHERE device_id = ? AND status = ?")
+            .bind(provisioning_status::SUPERSEDED)
+            .bind(device_id)
+            .bind(provisioning_status::ACTIVE)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query(
+            r#"INSERT INTO provisioning_sessions (device_id, status, config_file, lease_released, started_at)
+               VALUES (?, ?, ?, ?, ?)"#,
+        )
+        .bind(device_id)
+        .bind(provisioning_status::ACTIVE)
+        .bind(config_file)
+        .bind(lease_released)
+        .bind(Utc::now())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Self::get(pool, result.last_insert_rowid())
+            .await?
+            .context("Provisioning session not found after creation")
+    }
+
+    /// Record a DHCP lease for the active session of the device with this MAC
+    pub async fn record_lease(pool: &Pool<Sqlite>, mac: &str) -> Result<()> {
+        sqlx::query(
+            r#"UPDATE provisioning_sessions SET lease_at = ?
+               WHERE status = ? AND device_id IN (SELECT id FROM devices WHERE mac = ?)"#,
+        )
+        .bind(Utc::now())
+        .bind(provisioning_status::ACTIVE)
+        .bind(mac)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_config_pulled(pool: &Pool<Sqlite>, device_id: i64) -> Result<()> {
+        sqlx::query("UPDATE provisioning_sessions SET config_pulled_at = ? WHERE device_id = ? AND status = ?")
+            .bind(Utc::now())
+            .bind(device_id)
+            .bind(provisioning_status::ACTIVE)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Complete the device's active session once it has taken a new lease; returns whether one completed
+    pub async fn complete_if_leased(pool: &Pool<Sqlite>, device_id: i64) -> Result<bool> {
+        let result = sqlx::query(
+            r#"UPDATE provisioning_sessions SET status = ?, completed_at = ?
+               WHERE device_id = ? AND status = ? AND lease_at IS NOT NULL"#,
+        )
+        .bind(provisioning_status::COMPLETED)
+        .bind(Utc::now())
+        .bind(device_id)
+        .bind(provisioning_status::ACTIVE)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
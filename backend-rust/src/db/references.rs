@@ -0,0 +1,144 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+
+use crate::models::*;
+
+const EXAMPLE_LIMIT: i64 = 5;
+
+/// One kind of referring row: `SELECT {label} FROM {source}`, with `?` bound to each bind value
+struct ReferenceQuery {
+    kind: &'static str,
+    source: &'static str,
+    label: &'static str,
+    deleted_with_entity: bool,
+}
+
+async fn count_references(pool: &Pool<Sqlite>, queries: &[ReferenceQuery], binds: &[String]) -> Result<Vec<ReferenceCount>> {
+    let mut references = Vec::new();
+    for query in queries {
+        let sql = format!("SELECT COUNT(*) FROM {}", query.source);
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&sql);
+        for bind in binds {
+            count_query = count_query.bind(bind);
+        }
+        let (count,) = count_query.fetch_one(pool).await?;
+        if count == 0 {
+            continue;
+        }
+        let sql = format!("SELECT {} FROM {} ORDER BY 1 LIMIT {}", query.label, query.source, EXAMPLE_LIMIT);
+        let mut examples_query = sqlx::query_as::<_, (String,)>(&sql);
+        for bind in binds {
+            examples_query = examples_query.bind(bind);
+        }
+        let examples = examples_query.fetch_all(pool).await?;
+        references.push(ReferenceCount {
+            kind: query.kind.to_string(),
+            count,
+            examples: examples.into_iter().map(|(label,)| label).collect(),
+            deleted_with_entity: query.deleted_with_entity,
+        });
+    }
+    Ok(references)
+}
+
+/// Lookups of what refers to vendors, templates and groups
+pub struct ReferencesRepo;
+
+impl ReferencesRepo {
+    /// Rows referring to a vendor, by ID or (for devices not yet normalized) by name
+    pub async fn vendor(pool: &Pool<Sqlite>, id: i64, name: &str) -> Result<Vec<ReferenceCount>> {
+        const QUERIES: &[ReferenceQuery] = &[
+            ReferenceQuery {
+                kind: "devices",
+                source: "devices WHERE vendor = ?1 OR vendor = ?2",
+                label: "hostname",
+                deleted_with_entity: false,
+            },
+            ReferenceQuery {
+                kind: "templates",
+                source: "templates WHERE vendor_id = ?1",
+                label: "name",
+                deleted_with_entity: false,
+            },
+            ReferenceQuery {
+                kind: "dhcp_options",
+                source: "dhcp_options WHERE vendor_id = ?1",
+                label: "name",
+                deleted_with_entity: false,
+            },
+            ReferenceQuery {
+                kind: "device_models",
+                source: "device_models WHERE vendor_id = ?1",
+                label: "model",
+                deleted_with_entity: true,
+            },
+            ReferenceQuery {
+                kind: "vendor_actions",
+                source: "vendor_actions WHERE vendor_id = ?1",
+                label: "label",
+                deleted_with_entity: true,
+            },
+        ];
+        count_references(pool, QUERIES, &[id.to_string(), name.to_string()]).await
+    }
+
+    pub async fn template(pool: &Pool<Sqlite>, id: i64) -> Result<Vec<ReferenceCount>> {
+        const QUERIES: &[ReferenceQuery] = &[
+            ReferenceQuery {
+                kind: "devices",
+                source: "devices WHERE config_template = ?1",
+                label: "hostname",
+                deleted_with_entity: false,
+            },
+            ReferenceQuery {
+                kind: "vendor_defaults",
+                source: "vendors WHERE default_template = ?1",
+                label: "name",
+                deleted_with_entity: false,
+            },
+            ReferenceQuery {
+                kind: "device_roles",
+                source: "device_role_templates rt JOIN device_roles r ON r.id = rt.role_id WHERE rt.template_id = ?1",
+                label: "r.name",
+                deleted_with_entity: false,
+            },
+        ];
+        count_references(pool, QUERIES, &[id.to_string()]).await
+    }
+
+    pub async fn group(pool: &Pool<Sqlite>, id: i64) -> Result<Vec<ReferenceCount>> {
+        const QUERIES: &[ReferenceQuery] = &[
+            ReferenceQuery {
+                kind: "members",
+                source: "device_group_members m JOIN devices d ON d.id = m.device_id WHERE m.group_id = ?1",
+                label: "d.hostname",
+                deleted_with_entity: false,
+            },
+            ReferenceQuery {
+                kind: "child_groups",
+                source: "groups WHERE parent_id = ?1",
+                label: "name",
+                deleted_with_entity: false,
+            },
+            ReferenceQuery {
+                kind: "job_templates",
+                source: "job_templates WHERE target_group_id = ?1",
+                label: "name",
+                deleted_with_entity: false,
+            },
+            ReferenceQuery {
+                kind: "variables",
+                source: "group_variables WHERE group_id = ?1",
+                label: "key",
+                deleted_with_entity: true,
+            },
+            ReferenceQuery {
+                kind: "maintenance_windows",
+                source: "maintenance_windows WHERE group_id = ?1",
+                label: "name",
+                deleted_with_entity: true,
+            },
+        ];
+        count_references(pool, QUERIES, &[id.to_string()]).await
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_report_schedule_row(row: &SqliteRow) -> ReportSchedule {
+    let channel_ids: String = row.get("channel_ids");
+    let drift: String = row.get("last_drift_device_ids");
+    ReportSchedule {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        schedule: row.get("schedule"),
+        channel_ids: serde_json::from_str(&channel_ids).unwrap_or_default(),
+        stale_backup_days: row.get("stale_backup_days"),
+        enabled: row.get("enabled"),
+        last_run_at: row.get("last_run_at"),
+        last_status: row.get("last_status"),
+        last_error: row.get("last_error"),
+        last_drift_device_ids: serde_json::from_str(&drift).unwrap_or_default(),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Report schedule database operations
+pub struct ReportScheduleRepo;
+
+impl ReportScheduleRepo {
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<ReportSchedule>> {
+        let rows = sqlx::query("SELECT * FROM report_schedules ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_report_schedule_row).collect())
+    }
+
+    pub async fn list_enabled(pool: &Pool<Sqlite>) -> Result<Vec<ReportSchedule>> {
+        let rows = sqlx::query("SELECT * FROM report_schedules WHERE enabled = 1 ORDER BY id")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_report_schedule_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<ReportSchedule>> {
+        let row = sqlx::query("SELECT * FROM report_schedules WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_report_schedule_row))
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, req: &CreateReportScheduleRequest) -> Result<ReportSchedule> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO report_schedules (name, description, schedule, channel_ids, stale_backup_days, enabled, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&req.schedule)
+        .bind(serde_json::to_string(&req.channel_ids)?)
+        .bind(req.stale_backup_days)
+        .bind(req.enabled)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get(pool, result.last_insert_rowid())
+            .await?
+            .context("Report schedule not found after creation")
+    }
+
+    pub async fn update(pool: &Pool<Sqlite>, id: i64, req: &CreateReportScheduleRequest) -> Result<ReportSchedule> {
+        let result = sqlx::query(
+            r#"
+            UPDATE report_schedules SET name = ?, description = ?, schedule = ?, channel_ids = ?,
+                stale_backup_days = ?, enabled = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&req.schedule)
+        .bind(serde_json::to_string(&req.channel_ids)?)
+        .bind(req.stale_backup_days)
+        .bind(req.enabled)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Report schedule", &id.to_string()).into());
+        }
+        Self::get(pool, id)
+            .await?
+            .context("Report schedule not found after update")
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM report_schedules WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Report schedule", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Record a run; the drifted devices are kept so the next run can report new drift
+    pub async fn record_run(
+        pool: &Pool<Sqlite>,
+        id: i64,
+        error: Option<&str>,
+        drift_device_ids: &[i64],
+    ) -> Result<()> {
+        let status = if error.is_some() { report_run_status::FAILED } else { report_run_status::SUCCESS };
+        sqlx::query(
+            "UPDATE report_schedules SET last_run_at = ?, last_status = ?, last_error = ?, last_drift_device_ids = ? WHERE id = ?",
+        )
+        .bind(Utc::now())
+        .bind(status)
+        .bind(error)
+        .bind(serde_json::to_string(drift_device_ids)?)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -19,6 +19,7 @@ pub fn map_device_row(row: &SqliteRow) -> Device {
         model: none_if_empty(row.get("model")),
         serial_number: none_if_empty(row.get("serial_number")),
         config_template: row.get("config_template"),
+        credential_id: row.try_get::<Option<i64>, _>("credential_id").ok().flatten(),
         ssh_user: none_if_empty(row.get("ssh_user")),
         ssh_pass: none_if_empty(row.get("ssh_pass")),
         topology_id: row.try_get::<Option<i64>, _>("topology_id").ok().flatten(),
@@ -30,11 +31,19 @@ pub fn map_device_row(row: &SqliteRow) -> Device {
             let v: i32 = row.get("rack_position");
             if v == 0 { None } else { Some(v) }
         },
+        tenant_id: row.try_get::<Option<i64>, _>("tenant_id").ok().flatten(),
+        gpu_cluster_id: row.try_get::<Option<i64>, _>("gpu_cluster_id").ok().flatten(),
         status: row.get("status"),
         device_type: row.get("device_type"),
         last_seen: row.get("last_seen"),
         last_backup: row.get("last_backup"),
         last_error: none_if_empty(row.get("last_error")),
+        custom_fields: row
+            .try_get::<Option<String>, _>("custom_fields")
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
         created_at: row.get("created_at"),
         updated_at: row.get("updated_at"),
     }
@@ -52,6 +61,9 @@ pub fn map_vendor_row(row: &SqliteRow) -> Vendor {
         backup_command: row.get("backup_command"),
         deploy_command: row.get::<String, _>("deploy_command"),
         diff_command: row.get::<String, _>("diff_command"),
+        commit_confirm_command: row.get("commit_confirm_command"),
+        confirm_command: row.get("confirm_command"),
+        transport: row.get("transport"),
         ssh_port: row.get("ssh_port"),
         ssh_user: none_if_empty(row.get("ssh_user")),
         ssh_pass: none_if_empty(row.get("ssh_pass")),
@@ -59,6 +71,7 @@ pub fn map_vendor_row(row: &SqliteRow) -> Vendor {
         vendor_class: row.get("vendor_class"),
         default_template: row.get("default_template"),
         group_names,
+        ztp_wrapper: row.get("ztp_wrapper"),
         device_count: Some(row.get("device_count")),
         created_at: row.get("created_at"),
         updated_at: row.get("updated_at"),
@@ -117,6 +130,10 @@ pub fn map_backup_row(row: &SqliteRow) -> Backup {
         device_id: row.get("device_id"),
         filename: row.get("filename"),
         size: row.get("size"),
+        source: row.get("source"),
+        description: row.get("description"),
+        content_hash: row.get("content_hash"),
+        changed: row.get::<i32, _>("changed") == 1,
         created_at: row.get("created_at"),
     }
 }
@@ -159,5 +176,6 @@ pub fn map_discovered_device_row(row: &SqliteRow) -> Lease {
         circuit_id: none_if_empty(row.get("circuit_id")),
         remote_id: none_if_empty(row.get("remote_id")),
         subscriber_id: none_if_empty(row.get("subscriber_id")),
+        matched_device_id: row.get("matched_device_id"),
     }
 }
@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+const SELECT_RUN: &str = r#"
+    SELECT r.*, COALESCE(b.name, '') AS runbook_name, COALESCE(d.hostname, '') AS hostname
+    FROM runbook_runs r
+    LEFT JOIN runbooks b ON b.id = r.runbook_id
+    LEFT JOIN devices d ON d.id = r.device_id
+"#;
+
+fn map_runbook_row(row: &SqliteRow) -> Runbook {
+    let steps: String = row.get("steps");
+    Runbook {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        device_role_id: row.get("device_role_id"),
+        steps: serde_json::from_str(&steps).unwrap_or_default(),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn map_run_row(row: &SqliteRow) -> RunbookRun {
+    RunbookRun {
+        id: row.get("id"),
+        runbook_id: row.get("runbook_id"),
+        runbook_name: row.get("runbook_name"),
+        device_id: row.get("device_id"),
+        hostname: row.get("hostname"),
+        status: row.get("status"),
+        started_by: row.get("started_by"),
+        created_at: row.get("created_at"),
+        completed_at: row.get("completed_at"),
+        steps: Vec::new(),
+    }
+}
+
+fn map_run_step_row(row: &SqliteRow) -> RunbookRunStep {
+    let job_ids: String = row.get("job_ids");
+    RunbookRunStep {
+        position: row.get("position"),
+        title: row.get("title"),
+        instructions: row.get("instructions"),
+        step_type: row.get("step_type"),
+        vendor_action_id: row.get("vendor_action_id"),
+        job_template_id: row.get("job_template_id"),
+        status: row.get("status"),
+        job_ids: serde_json::from_str(&job_ids).unwrap_or_default(),
+        signed_off_by: row.get("signed_off_by"),
+        signed_off_at: row.get("signed_off_at"),
+        notes: row.get("notes"),
+    }
+}
+
+/// Runbook and runbook run database operations
+pub struct RunbookRepo;
+
+impl RunbookRepo {
+    pub async fn list(pool: &Pool<Sqlite>, device_role_id: Option<i64>) -> Result<Vec<Runbook>> {
+        let rows = sqlx::query("SELECT * FROM runbooks WHERE (? IS NULL OR device_role_id = ?) ORDER BY name")
+            .bind(device_role_id)
+            .bind(device_role_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_runbook_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Runbook>> {
+        let row = sqlx::query("SELECT * FROM runbooks WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_runbook_row))
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, req: &CreateRunbookRequest) -> Result<Runbook> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            "INSERT INTO runbooks (name, description, device_role_id, steps, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(req.device_role_id)
+        .bind(serde_json::to_string(&req.steps)?)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get(pool, result.last_insert_rowid())
+            .await?
+            .context("Runbook not found after creation")
+    }
+
+    pub async fn update(pool: &Pool<Sqlite>, id: i64, req: &CreateRunbookRequest) -> Result<Runbook> {
+        let result = sqlx::query(
+            "UPDATE runbooks SET name = ?, description = ?, device_role_id = ?, steps = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(req.device_role_id)
+        .bind(serde_json::to_string(&req.steps)?)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Runbook", &id.to_string()).into());
+        }
+        Self::get(pool, id).await?.context("Runbook not found after update")
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM runbooks WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Runbook", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    async fn load_steps(pool: &Pool<Sqlite>, run: &mut RunbookRun) -> Result<()> {
+        let rows = sqlx::query("SELECT * FROM runbook_run_steps WHERE run_id = ? ORDER BY position")
+            .bind(run.id)
+            .fetch_all(pool)
+            .await?;
+        run.steps = rows.iter().map(map_run_step_row).collect();
+        Ok(())
+    }
+
+    pub async fn list_runs(pool: &Pool<Sqlite>, query: &RunbookRunQuery) -> Result<Vec<RunbookRun>> {
+        let rows = sqlx::query(&format!(
+            r#"{} WHERE (? IS NULL OR r.runbook_id = ?)
+                 AND (? IS NULL OR r.device_id = ?)
+                 AND (? IS NULL OR r.status = ?)
+               ORDER BY r.id DESC LIMIT ?"#,
+            SELECT_RUN
+        ))
+        .bind(query.runbook_id)
+        .bind(query.runbook_id)
+        .bind(query.device_id)
+        .bind(query.device_id)
+        .bind(&query.status)
+        .bind(&query.status)
+        .bind(query.limit)
+        .fetch_all(pool)
+        .await?;
+        let mut runs: Vec<RunbookRun> = rows.iter().map(map_run_row).collect();
+        for run in &mut runs {
+            Self::load_steps(pool, run).await?;
+        }
+        Ok(runs)
+    }
+
+    pub async fn get_run(pool: &Pool<Sqlite>, id: i64) -> Result<Option<RunbookRun>> {
+        let row = sqlx::query(&format!("{} WHERE r.id = ?", SELECT_RUN))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        let Some(mut run) = row.as_ref().map(map_run_row) else {
+            return Ok(None);
+        };
+        Self::load_steps(pool, &mut run).await?;
+        Ok(Some(run))
+    }
+
+    /// Start a run, copying the runbook's current steps
+    pub async fn create_run(pool: &Pool<Sqlite>, runbook: &Runbook, device_id: i64, started_by: &str) -> Result<RunbookRun> {
+        let mut tx = pool.begin().await?;
+        let result = sqlx::query(
+            "INSERT INTO runbook_runs (runbook_id, device_id, status, started_by, created_at) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(runbook.id)
+        .bind(device_id)
+        .bind(runbook_run_status::IN_PROGRESS)
+        .bind(started_by)
+        .bind(Utc::now())
+        .execute(&mut *tx)
+        .await?;
+        let run_id = result.last_insert_rowid();
+
+        for (i, step) in runbook.steps.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO runbook_run_steps (run_id, position, title, instructions, step_type, vendor_action_id, job_template_id, status)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(run_id)
+            .bind(i as i64 + 1)
+            .bind(&step.title)
+            .bind(&step.instructions)
+            .bind(&step.step_type)
+            .bind(step.vendor_action_id)
+            .bind(step.job_template_id)
+            .bind(runbook_step_status::PENDING)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Self::get_run(pool, run_id)
+            .await?
+            .context("Runbook run not found after creation")
+    }
+
+    /// Record the jobs an automated step queued
+    pub async fn set_step_executed(pool: &Pool<Sqlite>, run_id: i64, position: i64, job_ids: &[String]) -> Result<()> {
+        sqlx::query("UPDATE runbook_run_steps SET status = ?, job_ids = ? WHERE run_id = ? AND position = ?")
+            .bind(runbook_step_status::EXECUTED)
+            .bind(serde_json::to_string(job_ids)?)
+            .bind(run_id)
+            .bind(position)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn sign_off_step(
+        pool: &Pool<Sqlite>,
+        run_id: i64,
+        position: i64,
+        status: &str,
+        username: &str,
+        notes: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE runbook_run_steps SET status = ?, signed_off_by = ?, signed_off_at = ?, notes = ? WHERE run_id = ? AND position = ?",
+        )
+        .bind(status)
+        .bind(username)
+        .bind(Utc::now())
+        .bind(notes)
+        .bind(run_id)
+        .bind(position)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Finish a run with a final status
+    pub async fn finish_run(pool: &Pool<Sqlite>, run_id: i64, status: &str) -> Result<()> {
+        sqlx::query("UPDATE runbook_runs SET status = ?, completed_at = ? WHERE id = ?")
+            .bind(status)
+            .bind(Utc::now())
+            .bind(run_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+use super::row_helpers::none_if_empty;
+
+fn map_saved_search_row(row: &SqliteRow) -> SavedSearch {
+    let filter_json: String = row.get("filter");
+    SavedSearch {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: none_if_empty(row.get("description")),
+        filter: serde_json::from_str(&filter_json).unwrap_or_default(),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+pub struct SavedSearchRepo;
+
+impl SavedSearchRepo {
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<SavedSearch>> {
+        let rows = sqlx::query("SELECT * FROM saved_searches ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_saved_search_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<SavedSearch>> {
+        let row = sqlx::query("SELECT * FROM saved_searches WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_saved_search_row))
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, req: &CreateSavedSearchRequest) -> Result<SavedSearch> {
+        let now = Utc::now();
+        let filter_json = serde_json::to_string(&req.filter)?;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO saved_searches (name, description, filter, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&filter_json)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        let new_id = result.last_insert_rowid();
+        Self::get(pool, new_id)
+            .await?
+            .context("Saved search not found after creation")
+    }
+
+    pub async fn update(pool: &Pool<Sqlite>, id: i64, req: &CreateSavedSearchRequest) -> Result<SavedSearch> {
+        let now = Utc::now();
+        let filter_json = serde_json::to_string(&req.filter)?;
+        let result = sqlx::query(
+            r#"
+            UPDATE saved_searches SET name = ?, description = ?, filter = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&filter_json)
+        .bind(now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Saved search", &id.to_string()).into());
+        }
+
+        Self::get(pool, id)
+            .await?
+            .context("Saved search not found after update")
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM saved_searches WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Saved search", &id.to_string()).into());
+        }
+        Ok(())
+    }
+}
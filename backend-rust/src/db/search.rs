@@ -0,0 +1,88 @@
+use anyhow::Result;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_hit_row(row: &SqliteRow) -> SearchHit {
+    SearchHit {
+        id: row.get("id"),
+        title: row.get::<Option<String>, _>("title").unwrap_or_default(),
+        snippet: row.get::<Option<String>, _>("snippet").unwrap_or_default(),
+    }
+}
+
+/// Search a partitioned table's live index and each partition's own index as one ranked list.
+/// `select` builds the query for one index from its FTS table and content table names.
+async fn search_partitioned(
+    pool: &Pool<Sqlite>,
+    table: &str,
+    fts_query: &str,
+    limit: i32,
+    select: impl Fn(&str, &str) -> String,
+) -> Result<Vec<SqliteRow>> {
+    let tables = {
+        let mut conn = pool.acquire().await?;
+        super::partitions::with_partitions(&mut conn, table).await?
+    };
+    let selects: Vec<String> = tables.iter().map(|t| select(&format!("{}_fts", t), t)).collect();
+    let sql = format!(
+        "SELECT id, title, snippet FROM ({}) ORDER BY rank LIMIT ?",
+        selects.join(" UNION ALL ")
+    );
+    let mut query = sqlx::query(&sql);
+    for _ in &tables {
+        query = query.bind(fts_query);
+    }
+    Ok(query.bind(limit).fetch_all(pool).await?)
+}
+
+/// Global full-text search over the FTS5 indexes
+pub struct SearchRepo;
+
+impl SearchRepo {
+    /// Run an FTS5 MATCH expression against every index, best matches first
+    pub async fn search(pool: &Pool<Sqlite>, fts_query: &str, limit: i32) -> Result<SearchResults> {
+        let devices = sqlx::query(
+            r#"SELECT CAST(d.id AS TEXT) as id, d.hostname as title,
+                      snippet(devices_fts, -1, '[', ']', '...', 12) as snippet
+               FROM devices_fts JOIN devices d ON d.id = devices_fts.rowid
+               WHERE devices_fts MATCH ? ORDER BY rank LIMIT ?"#,
+        )
+        .bind(fts_query).bind(limit).fetch_all(pool).await?;
+
+        let templates = sqlx::query(
+            r#"SELECT CAST(t.id AS TEXT) as id, t.name as title,
+                      snippet(templates_fts, -1, '[', ']', '...', 12) as snippet
+               FROM templates_fts JOIN templates t ON t.id = templates_fts.rowid
+               WHERE templates_fts MATCH ? ORDER BY rank LIMIT ?"#,
+        )
+        .bind(fts_query).bind(limit).fetch_all(pool).await?;
+
+        let jobs = search_partitioned(pool, "jobs", fts_query, limit, |fts, table| {
+            format!(
+                r#"SELECT j.id as id, j.job_type || ' ' || j.status as title,
+                          snippet({fts}, -1, '[', ']', '...', 12) as snippet, {fts}.rank as rank
+                   FROM {fts} JOIN {table} j ON j.rowid = {fts}.rowid
+                   WHERE {fts} MATCH ?"#
+            )
+        })
+        .await?;
+
+        let discovery_logs = search_partitioned(pool, "discovery_logs", fts_query, limit, |fts, table| {
+            format!(
+                r#"SELECT CAST(l.id AS TEXT) as id, l.event_type || ' ' || l.mac as title,
+                          snippet({fts}, -1, '[', ']', '...', 12) as snippet, {fts}.rank as rank
+                   FROM {fts} JOIN {table} l ON l.rowid = {fts}.rowid
+                   WHERE {fts} MATCH ?"#
+            )
+        })
+        .await?;
+
+        Ok(SearchResults {
+            devices: devices.iter().map(map_hit_row).collect(),
+            templates: templates.iter().map(map_hit_row).collect(),
+            jobs: jobs.iter().map(map_hit_row).collect(),
+            discovery_logs: discovery_logs.iter().map(map_hit_row).collect(),
+        })
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_seed_item_row(row: &SqliteRow) -> SeedItem {
+    SeedItem {
+        id: row.get("id"),
+        kind: row.get("kind"),
+        seed_key: row.get("seed_key"),
+        resource_id: row.get("resource_id"),
+        applied_content: row.get("applied_content"),
+        user_modified: row.get("user_modified"),
+        pending_content: row.get("pending_content"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Seed version and seed item tracking database operations
+pub struct SeedRepo;
+
+impl SeedRepo {
+    /// Version of a seed set applied to this database, 0 if never applied
+    pub async fn applied_version(pool: &Pool<Sqlite>, name: &str) -> Result<i64> {
+        let version: Option<i64> = sqlx::query_scalar("SELECT version FROM seed_versions WHERE name = ?")
+            .bind(name)
+            .fetch_optional(pool)
+            .await?;
+        Ok(version.unwrap_or(0))
+    }
+
+    pub async fn list_versions(pool: &Pool<Sqlite>) -> Result<Vec<(String, i64, DateTime<Utc>)>> {
+        Ok(sqlx::query_as("SELECT name, version, applied_at FROM seed_versions ORDER BY name")
+            .fetch_all(pool)
+            .await?)
+    }
+
+    pub async fn record_version(pool: &Pool<Sqlite>, name: &str, version: i64) -> Result<()> {
+        sqlx::query(
+            r#"INSERT INTO seed_versions (name, version, applied_at) VALUES (?, ?, ?)
+               ON CONFLICT(name) DO UPDATE SET version = excluded.version, applied_at = excluded.applied_at"#,
+        )
+        .bind(name)
+        .bind(version)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_item(pool: &Pool<Sqlite>, id: i64) -> Result<Option<SeedItem>> {
+        let row = sqlx::query("SELECT * FROM seed_items WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_seed_item_row))
+    }
+
+    pub async fn find_item(pool: &Pool<Sqlite>, kind: &str, seed_key: &str) -> Result<Option<SeedItem>> {
+        let row = sqlx::query("SELECT * FROM seed_items WHERE kind = ? AND seed_key = ?")
+            .bind(kind)
+            .bind(seed_key)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_seed_item_row))
+    }
+
+    pub async fn list_pending(pool: &Pool<Sqlite>) -> Result<Vec<SeedItem>> {
+        let rows = sqlx::query("SELECT * FROM seed_items WHERE pending_content IS NOT NULL ORDER BY kind, seed_key")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_seed_item_row).collect())
+    }
+
+    /// Record the state of a seeded item
+    pub async fn upsert_item(
+        pool: &Pool<Sqlite>,
+        kind: &str,
+        seed_key: &str,
+        resource_id: Option<i64>,
+        applied_content: &str,
+        user_modified: bool,
+        pending_content: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"INSERT INTO seed_items (kind, seed_key, resource_id, applied_content, user_modified, pending_content, updated_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?)
+               ON CONFLICT(kind, seed_key) DO UPDATE SET
+                   resource_id = excluded.resource_id,
+                   applied_content = excluded.applied_content,
+                   user_modified = excluded.user_modified,
+                   pending_content = excluded.pending_content,
+                   updated_at = excluded.updated_at"#,
+        )
+        .bind(kind)
+        .bind(seed_key)
+        .bind(resource_id)
+        .bind(applied_content)
+        .bind(user_modified)
+        .bind(pending_content)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_user_modified(pool: &Pool<Sqlite>, id: i64, user_modified: bool) -> Result<SeedItem> {
+        sqlx::query("UPDATE seed_items SET user_modified = ?, updated_at = ? WHERE id = ?")
+            .bind(user_modified)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Self::get_item(pool, id)
+            .await?
+            .ok_or_else(|| super::NotFoundError::new("Seed item", &id.to_string()).into())
+    }
+}
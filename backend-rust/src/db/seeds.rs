@@ -8,13 +8,26 @@ pub(super) struct DefaultVendor {
     backup_command: String,
     deploy_command: String,
     diff_command: String,
+    commit_confirm_command: String,
+    confirm_command: String,
     ssh_port: i32,
     mac_prefixes: Vec<String>,
     vendor_class: String,
     default_template: String,
     group_names: Vec<String>,
+    ztp_wrapper: String,
 }
 
+/// Arista ZTP runs the file it fetches as a script: write the config as the startup-config,
+/// which EOS loads on the reboot that ends ZTP
+const ARISTA_ZTP_WRAPPER: &str = r#"#!/bin/bash
+# ForgeConfig ZTP bootstrap for {{ Hostname }}
+cat > /mnt/flash/startup-config <<'FORGE_CONFIG_EOF'
+{{ config }}
+FORGE_CONFIG_EOF
+exit 0
+"#;
+
 pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
     vec![
         DefaultVendor {
@@ -23,11 +36,14 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             backup_command: "config export".to_string(),
             deploy_command: String::new(),
             diff_command: String::new(),
+            commit_confirm_command: String::new(),
+            confirm_command: String::new(),
             ssh_port: 22,
             mac_prefixes: vec!["00:13:C6".to_string()],
             vendor_class: "OpenGear".to_string(),
             default_template: "opengear-lighthouse".to_string(),
             group_names: vec![],
+            ztp_wrapper: String::new(),
         },
         DefaultVendor {
             id: "cisco".to_string(),
@@ -35,6 +51,8 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             backup_command: "show running-config".to_string(),
             deploy_command: "configure terminal\n{CONFIG}\nend\nwrite memory".to_string(),
             diff_command: String::new(),
+            commit_confirm_command: String::new(),
+            confirm_command: String::new(),
             ssh_port: 22,
             mac_prefixes: vec![
                 "00:00:0C".to_string(), "00:1A:2F".to_string(), "00:1B:0D".to_string(),
@@ -45,6 +63,7 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             vendor_class: "Cisco Systems, Inc.".to_string(),
             default_template: "cisco-ios".to_string(),
             group_names: vec![],
+            ztp_wrapper: String::new(),
         },
         DefaultVendor {
             id: "arista".to_string(),
@@ -52,6 +71,8 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             backup_command: "show running-config".to_string(),
             deploy_command: "configure session ztp-deploy\n{CONFIG}\ncommit".to_string(),
             diff_command: "configure session ztp-diff\n{CONFIG}\nshow session-config diffs\nabort".to_string(),
+            commit_confirm_command: "configure session {SESSION}\n{CONFIG}\ncommit timer {TIMER_HMS}".to_string(),
+            confirm_command: "configure session {SESSION} commit".to_string(),
             ssh_port: 22,
             mac_prefixes: vec![
                 "00:1C:73".to_string(), "28:99:3A".to_string(), "44:4C:A8".to_string(),
@@ -60,6 +81,7 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             vendor_class: "Arista Networks".to_string(),
             default_template: "arista-eos".to_string(),
             group_names: vec!["arista".to_string()],
+            ztp_wrapper: ARISTA_ZTP_WRAPPER.to_string(),
         },
         DefaultVendor {
             id: "juniper".to_string(),
@@ -67,6 +89,8 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             backup_command: "show configuration | display set".to_string(),
             deploy_command: "configure\n{CONFIG}\ncommit and-quit".to_string(),
             diff_command: "configure\n{CONFIG}\nshow | compare\nrollback 0\nexit".to_string(),
+            commit_confirm_command: "configure\n{CONFIG}\ncommit confirmed {TIMER_MINUTES}\nexit".to_string(),
+            confirm_command: "configure\ncommit\nexit".to_string(),
             ssh_port: 22,
             mac_prefixes: vec![
                 "00:05:85".to_string(), "00:10:DB".to_string(), "00:12:1E".to_string(),
@@ -79,6 +103,7 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             vendor_class: "Juniper Networks".to_string(),
             default_template: "juniper-junos".to_string(),
             group_names: vec![],
+            ztp_wrapper: String::new(),
         },
         DefaultVendor {
             id: "raspberry-pi".to_string(),
@@ -86,6 +111,8 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             backup_command: "cat /etc/network/interfaces".to_string(),
             deploy_command: String::new(),
             diff_command: String::new(),
+            commit_confirm_command: String::new(),
+            confirm_command: String::new(),
             ssh_port: 22,
             mac_prefixes: vec![
                 "B8:27:EB".to_string(), "DC:A6:32".to_string(), "E4:5F:01".to_string(),
@@ -94,6 +121,7 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             vendor_class: "Raspberry Pi".to_string(),
             default_template: "raspberry-pi".to_string(),
             group_names: vec![],
+            ztp_wrapper: String::new(),
         },
         DefaultVendor {
             id: "frr".to_string(),
@@ -101,11 +129,14 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             backup_command: "vtysh -c 'show running-config'".to_string(),
             deploy_command: "vtysh\nconfigure terminal\n{CONFIG}\nend\nwrite memory".to_string(),
             diff_command: String::new(),
+            commit_confirm_command: String::new(),
+            confirm_command: String::new(),
             ssh_port: 22,
             mac_prefixes: vec![],
             vendor_class: "FRRouting".to_string(),
             default_template: "frr-bgp".to_string(),
             group_names: vec![],
+            ztp_wrapper: String::new(),
         },
         DefaultVendor {
             id: "gobgp".to_string(),
@@ -113,11 +144,14 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             backup_command: "gobgp global; echo '---'; gobgp neighbor".to_string(),
             deploy_command: String::new(),
             diff_command: String::new(),
+            commit_confirm_command: String::new(),
+            confirm_command: String::new(),
             ssh_port: 22,
             mac_prefixes: vec![],
             vendor_class: "GoBGP".to_string(),
             default_template: "gobgp-bgp".to_string(),
             group_names: vec![],
+            ztp_wrapper: String::new(),
         },
         DefaultVendor {
             id: "amd".to_string(),
@@ -125,11 +159,14 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             backup_command: String::new(),
             deploy_command: String::new(),
             diff_command: String::new(),
+            commit_confirm_command: String::new(),
+            confirm_command: String::new(),
             ssh_port: 22,
             mac_prefixes: vec![],
             vendor_class: "AMD".to_string(),
             default_template: String::new(),
             group_names: vec!["amd".to_string()],
+            ztp_wrapper: String::new(),
         },
         DefaultVendor {
             id: "patch panel".to_string(),
@@ -137,11 +174,14 @@ pub(super) fn get_default_vendors_internal() -> Vec<DefaultVendor> {
             backup_command: String::new(),
             deploy_command: String::new(),
             diff_command: String::new(),
+            commit_confirm_command: String::new(),
+            confirm_command: String::new(),
             ssh_port: 0,
             mac_prefixes: vec![],
             vendor_class: String::new(),
             default_template: String::new(),
             group_names: vec![],
+            ztp_wrapper: String::new(),
         },
     ]
 }
@@ -2399,8 +2439,42 @@ pub(super) fn seed_vendor_params() -> Vec<(String, String, String, String, Strin
         .collect()
 }
 
-/// Role template IDs that should be force-updated on startup (INSERT OR REPLACE)
-/// These use device variables and may evolve with new features.
+/// Confirmed-commit commands per seeded vendor name, for vendors that support them
+pub(super) fn seed_vendor_commit_confirm_params() -> Vec<(String, String, String)> {
+    get_default_vendors_internal()
+        .into_iter()
+        .filter(|v| !v.commit_confirm_command.is_empty())
+        .map(|v| (v.name, v.commit_confirm_command, v.confirm_command))
+        .collect()
+}
+
+/// ZTP bootstrap wrappers per seeded vendor name, for vendors whose ZTP runs a script
+pub(super) fn seed_vendor_ztp_wrapper_params() -> Vec<(String, String)> {
+    get_default_vendors_internal()
+        .into_iter()
+        .filter(|v| !v.ztp_wrapper.is_empty())
+        .map(|v| (v.name, v.ztp_wrapper))
+        .collect()
+}
+
+/// Versioned seed sets, applied in this order. Each set runs once per version; bump a set's
+/// version when its seed data changes so existing databases pick up the change on next start.
+pub(super) const SEED_SETS: &[(&str, i64)] = &[
+    ("vendors", 3),
+    ("templates", 1),
+    ("dhcp_options", 1),
+    ("vendor_actions", 1),
+    ("output_parsers", 2),
+    ("device_models", 1),
+    ("ipam_supernets", 1),
+    ("credential", 1),
+    ("device_roles", 1),
+    ("locations", 1),
+    ("probe_commands", 1),
+];
+
+/// Role templates use device variables and evolve with new features. They were overwritten on
+/// every start before seed tracking, so an untracked copy is known to be an earlier seed.
 const ROLE_TEMPLATE_IDS: &[&str] = &[
     "arista-eos-spine",
     "arista-eos-leaf",
@@ -2446,6 +2520,9 @@ pub fn get_default_vendors_models() -> Vec<Vendor> {
             backup_command: v.backup_command,
             deploy_command: v.deploy_command,
             diff_command: v.diff_command,
+            commit_confirm_command: v.commit_confirm_command,
+            confirm_command: v.confirm_command,
+            transport: vendor_transport::SSH.to_string(),
             ssh_port: v.ssh_port,
             ssh_user: None,
             ssh_pass: None,
@@ -2453,6 +2530,7 @@ pub fn get_default_vendors_models() -> Vec<Vendor> {
             vendor_class: v.vendor_class,
             default_template: v.default_template,
             group_names: v.group_names,
+            ztp_wrapper: v.ztp_wrapper,
             device_count: None,
             created_at: now,
             updated_at: now,
@@ -2594,9 +2672,62 @@ pub(super) fn seed_output_parser_data() -> Vec<DefaultOutputParser> {
             extract_names: "pid,vid,serial_number",
             action_id: "arista-inventory",
         },
+        // Hostname for the connect test, from "show hostname", "show running-config | include
+        // hostname" or a bare hostname
+        // Example output:
+        //   Hostname: spine1
+        //   hostname spine1
+        DefaultOutputParser {
+            name: "Device Hostname",
+            description: "Picks the hostname out of hostname command output",
+            pattern: r"^\s*(?:Hostname:\s*|hostname\s+)?([A-Za-z0-9][\w.-]*)\s*$",
+            extract_names: "hostname",
+            action_id: "",
+        },
     ]
 }
 
+// ============================================================
+// Default Probe Commands
+// ============================================================
+
+/// Probe command seed. vendor_id is the old text vendor id; empty is the default set for
+/// vendors without their own. parser names a seeded output parser, empty for none.
+pub(super) struct DefaultProbeCommand {
+    pub vendor_id: &'static str,
+    pub field: &'static str,
+    pub commands: &'static [&'static str],
+    pub parser: &'static str,
+    pub max_lines: i32,
+}
+
+pub(super) fn seed_probe_command_data() -> Vec<DefaultProbeCommand> {
+    use crate::models::probe_field::*;
+
+    fn probe(vendor_id: &'static str, field: &'static str, commands: &'static [&'static str], parser: &'static str, max_lines: i32) -> DefaultProbeCommand {
+        DefaultProbeCommand { vendor_id, field, commands, parser, max_lines }
+    }
+
+    // Network OS CLIs, with fallbacks covering IOS/EOS, Junos and NX-OS style syntax
+    let mut data = vec![
+        probe("", UPTIME, &["show version | include uptime", "show version | match uptime", "show system uptime", "uptime"], "", 0),
+        probe("", HOSTNAME, &["show hostname", "show running-config | include hostname", "hostname"], "Device Hostname", 0),
+        probe("", VERSION, &["show version"], "", 20),
+        probe("", INTERFACES, &["show ip interface brief", "show interfaces terse", "show interface brief", "ip -brief addr show"], "", 30),
+    ];
+
+    // Linux-based devices answer plain shell commands
+    for vendor_id in ["opengear", "raspberry-pi", "frr", "gobgp"] {
+        data.extend([
+            probe(vendor_id, UPTIME, &["uptime"], "", 0),
+            probe(vendor_id, HOSTNAME, &["hostname"], "", 0),
+            probe(vendor_id, VERSION, &["uname -a", "cat /etc/os-release"], "", 20),
+            probe(vendor_id, INTERFACES, &["ip -brief addr show", "ip addr show"], "", 30),
+        ]);
+    }
+    data
+}
+
 pub(super) fn seed_vendor_action_params() -> Vec<(String, String, String, String, i32, String, String, String, String, String)> {
     get_default_vendor_actions_internal()
         .into_iter()
@@ -2623,34 +2754,42 @@ pub(super) fn seed_device_model_params() -> Vec<(String, String, String, String,
         }).collect()
     }
 
-    // Arista 7050CX3-32S: 32x QSFP28 100G + 2x SFP+ 10G + Management
+    // Console port and dual power supplies, placed from `col` on a switch's second row
+    fn console_power_sections(col: usize) -> String {
+        format!(
+            r#"{{"label":"Console","ports":[{{"col":{},"vendor_port_name":"Console","connector":"rj45","role":"console"}}]}},{{"label":"Power","ports":[{{"col":{},"vendor_port_name":"PSU1","connector":"c14","role":"power"}},{{"col":{},"vendor_port_name":"PSU2","connector":"c14","role":"power"}}]}}"#,
+            col, col + 2, col + 3
+        )
+    }
+
+    // Arista 7050CX3-32S: 32x QSFP28 100G + 2x SFP+ 10G + Management + Console + 2x PSU
     let cx3_top: Vec<String> = ports_json(16, 1, &|i| format!("Ethernet{}", i * 2 + 1), "qsfp28", 100000);
     let cx3_bot: Vec<String> = ports_json(16, 1, &|i| format!("Ethernet{}", i * 2 + 2), "qsfp28", 100000);
     let cx3_layout = format!(
-        r#"[{{"row":1,"sections":[{{"label":"QSFP28 100G","ports":[{}]}}]}},{{"row":2,"sections":[{{"label":"QSFP28 100G","ports":[{}]}},{{"label":"SFP+ 10G","ports":[{{"col":18,"vendor_port_name":"Ethernet33","connector":"sfp+","speed":10000}},{{"col":19,"vendor_port_name":"Ethernet34","connector":"sfp+","speed":10000}}]}},{{"label":"Management","ports":[{{"col":21,"vendor_port_name":"Management1","connector":"rj45","speed":1000,"role":"mgmt"}}]}}]}}]"#,
-        cx3_top.join(","), cx3_bot.join(",")
+        r#"[{{"row":1,"sections":[{{"label":"QSFP28 100G","ports":[{}]}}]}},{{"row":2,"sections":[{{"label":"QSFP28 100G","ports":[{}]}},{{"label":"SFP+ 10G","ports":[{{"col":18,"vendor_port_name":"Ethernet33","connector":"sfp+","speed":10000}},{{"col":19,"vendor_port_name":"Ethernet34","connector":"sfp+","speed":10000}}]}},{{"label":"Management","ports":[{{"col":21,"vendor_port_name":"Management1","connector":"rj45","speed":1000,"role":"mgmt"}}]}},{}]}}]"#,
+        cx3_top.join(","), cx3_bot.join(","), console_power_sections(22)
     );
 
-    // Arista 7050SX3-48YC8: 48x SFP28 25G + 8x QSFP28 100G + Management
+    // Arista 7050SX3-48YC8: 48x SFP28 25G + 8x QSFP28 100G + Management + Console + 2x PSU
     let sx3_sfp_top: Vec<String> = ports_json(24, 1, &|i| format!("Ethernet{}", i * 2 + 1), "sfp28", 25000);
     let sx3_qsfp_top: Vec<String> = ports_json(4, 26, &|i| format!("Ethernet{}", 49 + i * 2), "qsfp28", 100000);
     let sx3_sfp_bot: Vec<String> = ports_json(24, 1, &|i| format!("Ethernet{}", i * 2 + 2), "sfp28", 25000);
     let sx3_qsfp_bot: Vec<String> = ports_json(4, 26, &|i| format!("Ethernet{}", 50 + i * 2), "qsfp28", 100000);
     let sx3_layout = format!(
-        r#"[{{"row":1,"sections":[{{"label":"SFP28 25G","ports":[{}]}},{{"label":"QSFP28 100G","ports":[{}]}}]}},{{"row":2,"sections":[{{"label":"SFP28 25G","ports":[{}]}},{{"label":"QSFP28 100G","ports":[{}]}},{{"label":"Management","ports":[{{"col":31,"vendor_port_name":"Management1","connector":"rj45","speed":1000,"role":"mgmt"}}]}}]}}]"#,
+        r#"[{{"row":1,"sections":[{{"label":"SFP28 25G","ports":[{}]}},{{"label":"QSFP28 100G","ports":[{}]}}]}},{{"row":2,"sections":[{{"label":"SFP28 25G","ports":[{}]}},{{"label":"QSFP28 100G","ports":[{}]}},{{"label":"Management","ports":[{{"col":31,"vendor_port_name":"Management1","connector":"rj45","speed":1000,"role":"mgmt"}}]}},{}]}}]"#,
         sx3_sfp_top.join(","), sx3_qsfp_top.join(","),
-        sx3_sfp_bot.join(","), sx3_qsfp_bot.join(",")
+        sx3_sfp_bot.join(","), sx3_qsfp_bot.join(","), console_power_sections(32)
     );
 
-    // Arista 7020TR-48: 48x RJ45 1G + 6x SFP+ 10G + Management
+    // Arista 7020TR-48: 48x RJ45 1G + 6x SFP+ 10G + Management + Console + 2x PSU
     let tr_rj45_top: Vec<String> = ports_json(24, 1, &|i| format!("Ethernet{}", i * 2 + 1), "rj45", 1000);
     let tr_sfp_top: Vec<String> = ports_json(3, 26, &|i| format!("Ethernet{}", 49 + i * 2), "sfp+", 10000);
     let tr_rj45_bot: Vec<String> = ports_json(24, 1, &|i| format!("Ethernet{}", i * 2 + 2), "rj45", 1000);
     let tr_sfp_bot: Vec<String> = ports_json(3, 26, &|i| format!("Ethernet{}", 50 + i * 2), "sfp+", 10000);
     let tr_layout = format!(
-        r#"[{{"row":1,"sections":[{{"label":"RJ45 1G","ports":[{}]}},{{"label":"SFP+ 10G","ports":[{}]}}]}},{{"row":2,"sections":[{{"label":"RJ45 1G","ports":[{}]}},{{"label":"SFP+ 10G","ports":[{}]}},{{"label":"Management","ports":[{{"col":30,"vendor_port_name":"Management1","connector":"rj45","speed":1000,"role":"mgmt"}}]}}]}}]"#,
+        r#"[{{"row":1,"sections":[{{"label":"RJ45 1G","ports":[{}]}},{{"label":"SFP+ 10G","ports":[{}]}}]}},{{"row":2,"sections":[{{"label":"RJ45 1G","ports":[{}]}},{{"label":"SFP+ 10G","ports":[{}]}},{{"label":"Management","ports":[{{"col":30,"vendor_port_name":"Management1","connector":"rj45","speed":1000,"role":"mgmt"}}]}},{}]}}]"#,
         tr_rj45_top.join(","), tr_sfp_top.join(","),
-        tr_rj45_bot.join(","), tr_sfp_bot.join(",")
+        tr_rj45_bot.join(","), tr_sfp_bot.join(","), console_power_sections(31)
     );
 
     // PP-24-RJ45: 1U, single row of 24 rj45 1G
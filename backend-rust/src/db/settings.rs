@@ -30,33 +30,51 @@ impl SettingsRepo {
 pub struct BackupRepo;
 
 impl BackupRepo {
-    pub async fn create(pool: &Pool<Sqlite>, device_id: i64, filename: &str, size: i64) -> Result<Backup> {
-        let now = chrono::Utc::now();
+    pub async fn create(pool: &Pool<Sqlite>, backup: NewBackup) -> Result<Backup> {
+        // Compare against the backup taken just before this one, not the newest, so an upload
+        // dated in the past is judged against its own predecessor
+        let previous: Option<String> = sqlx::query_scalar(
+            "SELECT content_hash FROM backups WHERE device_id = ? AND created_at <= ? ORDER BY created_at DESC, id DESC LIMIT 1",
+        )
+        .bind(backup.device_id)
+        .bind(backup.created_at)
+        .fetch_optional(pool)
+        .await?;
+        let changed = previous.as_deref() != Some(backup.content_hash.as_str());
+
         let result = sqlx::query(
-            "INSERT INTO backups (device_id, filename, size, created_at) VALUES (?, ?, ?, ?)",
+            "INSERT INTO backups (device_id, filename, size, source, description, content_hash, changed, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
         )
-        .bind(device_id)
-        .bind(filename)
-        .bind(size)
-        .bind(now)
+        .bind(backup.device_id)
+        .bind(&backup.filename)
+        .bind(backup.size)
+        .bind(&backup.source)
+        .bind(&backup.description)
+        .bind(&backup.content_hash)
+        .bind(changed as i32)
+        .bind(backup.created_at)
         .execute(pool)
         .await?;
 
         Ok(Backup {
             id: result.last_insert_rowid(),
-            device_id,
-            filename: filename.to_string(),
-            size,
-            created_at: now,
+            device_id: backup.device_id,
+            filename: backup.filename,
+            size: backup.size,
+            source: backup.source,
+            description: backup.description,
+            content_hash: backup.content_hash,
+            changed,
+            created_at: backup.created_at,
         })
     }
 
     pub async fn list(pool: &Pool<Sqlite>, device_id: i64) -> Result<Vec<Backup>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, device_id, filename, size, created_at
+            SELECT id, device_id, filename, size, source, description, content_hash, changed, created_at
             FROM backups WHERE device_id = ?
-            ORDER BY created_at DESC
+            ORDER BY created_at DESC, id DESC
             "#,
         )
         .bind(device_id)
@@ -68,7 +86,7 @@ impl BackupRepo {
 
     pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Backup>> {
         let row = sqlx::query(
-            "SELECT id, device_id, filename, size, created_at FROM backups WHERE id = ?",
+            "SELECT id, device_id, filename, size, source, description, content_hash, changed, created_at FROM backups WHERE id = ?",
         )
         .bind(id)
         .fetch_optional(pool)
@@ -76,6 +94,28 @@ impl BackupRepo {
 
         Ok(row.as_ref().map(map_backup_row))
     }
+
+    /// List backups for searching, newest first. With `latest_only`, only each device's most recent backup.
+    pub async fn list_for_search(pool: &Pool<Sqlite>, latest_only: bool, device_id: Option<i64>) -> Result<Vec<Backup>> {
+        let mut sql = String::from("SELECT b.id, b.device_id, b.filename, b.size, b.source, b.description, b.content_hash, b.changed, b.created_at FROM backups b WHERE 1 = 1");
+        if latest_only {
+            sql.push_str(
+                " AND b.id = (SELECT b2.id FROM backups b2 WHERE b2.device_id = b.device_id ORDER BY b2.created_at DESC, b2.id DESC LIMIT 1)",
+            );
+        }
+        if device_id.is_some() {
+            sql.push_str(" AND b.device_id = ?");
+        }
+        sql.push_str(" ORDER BY b.created_at DESC, b.id DESC");
+
+        let mut query = sqlx::query(&sql);
+        if let Some(id) = device_id {
+            query = query.bind(id);
+        }
+        let rows = query.fetch_all(pool).await?;
+
+        Ok(rows.iter().map(map_backup_row).collect())
+    }
 }
 
 /// NetBox config database operations
@@ -84,7 +124,7 @@ pub struct NetBoxConfigRepo;
 impl NetBoxConfigRepo {
     pub async fn get(pool: &Pool<Sqlite>) -> Result<NetBoxConfig> {
         let row = sqlx::query(
-            "SELECT url, token, site_id, role_id, sync_enabled FROM netbox_config WHERE id = 1",
+            "SELECT url, token, site_id, role_id, sync_enabled, conflict_policy FROM netbox_config WHERE id = 1",
         )
         .fetch_optional(pool)
         .await?;
@@ -98,6 +138,7 @@ impl NetBoxConfigRepo {
                     site_id: row.get("site_id"),
                     role_id: row.get("role_id"),
                     sync_enabled: sync_enabled == 1,
+                    conflict_policy: row.get("conflict_policy"),
                 }
             })
             .unwrap_or_default())
@@ -107,7 +148,7 @@ impl NetBoxConfigRepo {
         let result = sqlx::query(
             r#"
             UPDATE netbox_config
-            SET url = ?, token = ?, site_id = ?, role_id = ?, sync_enabled = ?, updated_at = CURRENT_TIMESTAMP
+            SET url = ?, token = ?, site_id = ?, role_id = ?, sync_enabled = ?, conflict_policy = ?, updated_at = CURRENT_TIMESTAMP
             WHERE id = 1
             "#,
         )
@@ -116,18 +157,20 @@ impl NetBoxConfigRepo {
         .bind(config.site_id)
         .bind(config.role_id)
         .bind(config.sync_enabled as i32)
+        .bind(&config.conflict_policy)
         .execute(pool)
         .await?;
 
         if result.rows_affected() == 0 {
             sqlx::query(
-                "INSERT INTO netbox_config (id, url, token, site_id, role_id, sync_enabled) VALUES (1, ?, ?, ?, ?, ?)",
+                "INSERT INTO netbox_config (id, url, token, site_id, role_id, sync_enabled, conflict_policy) VALUES (1, ?, ?, ?, ?, ?, ?)",
             )
             .bind(&config.url)
             .bind(&config.token)
             .bind(config.site_id)
             .bind(config.role_id)
             .bind(config.sync_enabled as i32)
+            .bind(&config.conflict_policy)
             .execute(pool)
             .await?;
         }
@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+const SELECT_STAGED: &str = r#"
+    SELECT s.*, COALESCE(d.hostname, '') AS hostname
+    FROM staged_configs s
+    LEFT JOIN devices d ON d.id = s.device_id
+"#;
+
+fn map_staged_config_row(row: &SqliteRow) -> StagedConfig {
+    StagedConfig {
+        id: row.get("id"),
+        device_id: row.get("device_id"),
+        hostname: row.get("hostname"),
+        description: row.get("description"),
+        config: row.get("config"),
+        activate_at: row.get("activate_at"),
+        require_maintenance_window: row.get("require_maintenance_window"),
+        status: row.get("status"),
+        job_id: row.get("job_id"),
+        error: row.get("error"),
+        created_by: row.get("created_by"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        activated_at: row.get("activated_at"),
+    }
+}
+
+/// Staged config database operations
+pub struct StagedConfigRepo;
+
+impl StagedConfigRepo {
+    pub async fn list(pool: &Pool<Sqlite>, query: &StagedConfigQuery) -> Result<Vec<StagedConfig>> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE (? IS NULL OR s.device_id = ?) AND (? IS NULL OR s.status = ?) ORDER BY s.activate_at DESC, s.id DESC",
+            SELECT_STAGED
+        ))
+        .bind(query.device_id)
+        .bind(query.device_id)
+        .bind(&query.status)
+        .bind(&query.status)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_staged_config_row).collect())
+    }
+
+    /// Scheduled configs whose activation time has passed, oldest first
+    pub async fn list_due(pool: &Pool<Sqlite>, now: DateTime<Utc>) -> Result<Vec<StagedConfig>> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE s.status = ? AND s.activate_at <= ? ORDER BY s.activate_at, s.id",
+            SELECT_STAGED
+        ))
+        .bind(staged_config_status::SCHEDULED)
+        .bind(now)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_staged_config_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<StagedConfig>> {
+        let row = sqlx::query(&format!("{} WHERE s.id = ?", SELECT_STAGED))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_staged_config_row))
+    }
+
+    pub async fn create(
+        pool: &Pool<Sqlite>,
+        device_id: i64,
+        config: &str,
+        req: &CreateStagedConfigRequest,
+        created_by: &str,
+    ) -> Result<StagedConfig> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO staged_configs (device_id, description, config, activate_at, require_maintenance_window,
+                                        status, created_by, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(device_id)
+        .bind(&req.description)
+        .bind(config)
+        .bind(req.activate_at)
+        .bind(req.require_maintenance_window)
+        .bind(staged_config_status::SCHEDULED)
+        .bind(created_by)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get(pool, result.last_insert_rowid())
+            .await?
+            .context("Staged config not found after creation")
+    }
+
+    /// Update a staged config that is still scheduled. Returns false if it isn't.
+    pub async fn update_scheduled(pool: &Pool<Sqlite>, staged: &StagedConfig) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE staged_configs SET description = ?, config = ?, activate_at = ?, require_maintenance_window = ?, updated_at = ?
+            WHERE id = ? AND status = ?
+            "#,
+        )
+        .bind(&staged.description)
+        .bind(&staged.config)
+        .bind(staged.activate_at)
+        .bind(staged.require_maintenance_window)
+        .bind(Utc::now())
+        .bind(staged.id)
+        .bind(staged_config_status::SCHEDULED)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Move a scheduled config to `status`, recording the job pushing it. Returns false if it
+    /// was no longer scheduled.
+    pub async fn transition_scheduled(pool: &Pool<Sqlite>, id: i64, status: &str, job_id: Option<&str>) -> Result<bool> {
+        let result = sqlx::query("UPDATE staged_configs SET status = ?, job_id = ?, updated_at = ? WHERE id = ? AND status = ?")
+            .bind(status)
+            .bind(job_id)
+            .bind(Utc::now())
+            .bind(id)
+            .bind(staged_config_status::SCHEDULED)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record the outcome of the job that pushed a staged config
+    pub async fn finish(pool: &Pool<Sqlite>, id: i64, status: &str, error: &str) -> Result<()> {
+        let now = Utc::now();
+        sqlx::query("UPDATE staged_configs SET status = ?, error = ?, activated_at = ?, updated_at = ? WHERE id = ?")
+            .bind(status)
+            .bind(error)
+            .bind((status == staged_config_status::ACTIVATED).then_some(now))
+            .bind(now)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+use std::collections::HashMap;
+
+use crate::models::*;
+
+fn map_status_transition_row(row: &SqliteRow) -> StatusTransition {
+    StatusTransition {
+        device_id: row.get("device_id"),
+        previous_status: row.get("previous_status"),
+        status: row.get("status"),
+        changed_at: row.get("changed_at"),
+    }
+}
+
+pub struct StatusHistoryRepo;
+
+impl StatusHistoryRepo {
+    /// Transitions at or after `since`, oldest first
+    pub async fn list_since(pool: &Pool<Sqlite>, since: DateTime<Utc>) -> Result<Vec<StatusTransition>> {
+        let rows = sqlx::query(
+            "SELECT * FROM device_status_history_all WHERE changed_at >= ? ORDER BY changed_at, id",
+        )
+        .bind(since)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_status_transition_row).collect())
+    }
+
+    /// Each device's status as of `at`, for devices with a transition before it
+    pub async fn statuses_at(pool: &Pool<Sqlite>, at: DateTime<Utc>) -> Result<HashMap<i64, String>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT h.device_id, h.status FROM device_status_history_all h
+            WHERE h.id = (
+                SELECT h2.id FROM device_status_history_all h2
+                WHERE h2.device_id = h.device_id AND h2.changed_at < ?
+                ORDER BY h2.changed_at DESC, h2.id DESC LIMIT 1
+            )
+            "#,
+        )
+        .bind(at)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("device_id"), row.get("status")))
+            .collect())
+    }
+}
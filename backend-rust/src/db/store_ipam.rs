@@ -121,6 +121,10 @@ impl Store {
         ipam::IpamRackRepo::list(&self.pool).await
     }
 
+    pub async fn list_ipam_racks_by_datacenter(&self, datacenter_id: i64) -> Result<Vec<IpamRack>> {
+        ipam::IpamRackRepo::list_by_datacenter(&self.pool, datacenter_id).await
+    }
+
     pub async fn get_ipam_rack(&self, id: i64) -> Result<Option<IpamRack>> {
         ipam::IpamRackRepo::get(&self.pool, id).await
     }
@@ -161,10 +165,18 @@ impl Store {
         ipam::IpamPrefixRepo::list(&self.pool).await
     }
 
+    pub async fn list_ipam_prefixes_by_vrf(&self, vrf_id: i64) -> Result<Vec<IpamPrefix>> {
+        ipam::IpamPrefixRepo::list_by_vrf(&self.pool, vrf_id).await
+    }
+
     pub async fn list_ipam_supernets(&self) -> Result<Vec<IpamPrefix>> {
         ipam::IpamPrefixRepo::list_supernets(&self.pool).await
     }
 
+    pub async fn ipam_prefix_tree(&self, root: Option<i64>, max_depth: i32) -> Result<Vec<IpamPrefixTreeNode>> {
+        ipam::IpamPrefixRepo::tree(&self.pool, root, max_depth).await
+    }
+
     pub async fn get_ipam_prefix(&self, id: i64) -> Result<Option<IpamPrefix>> {
         ipam::IpamPrefixRepo::get(&self.pool, id).await
     }
@@ -185,6 +197,10 @@ impl Store {
         ipam::IpamPrefixRepo::delete(&self.pool, id).await
     }
 
+    pub async fn ipam_prefix_availability(&self, id: i64) -> Result<IpamPrefixAvailability> {
+        ipam::IpamPrefixRepo::available_blocks(&self.pool, id).await
+    }
+
     pub async fn next_available_ipam_prefix(&self, parent_id: i64, req: &NextAvailablePrefixRequest) -> Result<IpamPrefix> {
         ipam::IpamPrefixRepo::next_available_prefix(&self.pool, parent_id, req).await
     }
@@ -195,6 +211,10 @@ impl Store {
         ipam::IpamIpAddressRepo::list(&self.pool).await
     }
 
+    pub async fn list_ipam_ip_addresses_by_vrf(&self, vrf_id: i64) -> Result<Vec<IpamIpAddress>> {
+        ipam::IpamIpAddressRepo::list_by_vrf(&self.pool, vrf_id).await
+    }
+
     pub async fn list_ipam_ip_addresses_by_prefix(&self, prefix_id: i64) -> Result<Vec<IpamIpAddress>> {
         ipam::IpamIpAddressRepo::list_by_prefix(&self.pool, prefix_id).await
     }
@@ -219,6 +239,14 @@ impl Store {
         ipam::IpamIpAddressRepo::next_available_ip(&self.pool, prefix_id, req).await
     }
 
+    pub async fn get_ipam_reverse_dns_serial(&self, vrf_id: i64) -> Result<Option<(u32, String)>> {
+        ipam::IpamReverseDnsSerialRepo::get(&self.pool, vrf_id).await
+    }
+
+    pub async fn save_ipam_reverse_dns_serial(&self, vrf_id: i64, serial: u32, fingerprint: &str) -> Result<()> {
+        ipam::IpamReverseDnsSerialRepo::save(&self.pool, vrf_id, serial, fingerprint).await
+    }
+
     // ========== IPAM Tag Operations ==========
 
     pub async fn list_ipam_tags(&self, resource_type: &str, resource_id: &str) -> Result<Vec<IpamTag>> {
@@ -254,4 +282,18 @@ impl Store {
     pub async fn delete_ipam_vrf(&self, id: i64) -> Result<()> {
         ipam::IpamVrfRepo::delete(&self.pool, id).await
     }
+
+    // ========== IPAM Neighbor Discovery Operations ==========
+
+    pub async fn reconcile_neighbor_entries(&self, device_id: i64, entries: &[NeighborEntry]) -> Result<NeighborReconcileSummary> {
+        ipam::IpamNeighborRepo::reconcile(&self.pool, device_id, entries).await
+    }
+
+    pub async fn list_ipam_discovered_addresses(&self) -> Result<Vec<IpamDiscoveredAddress>> {
+        ipam::IpamNeighborRepo::list_discovered(&self.pool).await
+    }
+
+    pub async fn delete_ipam_discovered_address(&self, id: i64) -> Result<()> {
+        ipam::IpamNeighborRepo::delete_discovered(&self.pool, id).await
+    }
 }
@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_support_contract_row(row: &SqliteRow) -> SupportContract {
+    SupportContract {
+        id: row.get("id"),
+        device_id: row.get("device_id"),
+        device_model_id: row.get("device_model_id"),
+        provider: row.get("provider"),
+        support_id: row.get("support_id"),
+        vendor_sla: row.get("vendor_sla"),
+        starts_on: row.get("starts_on"),
+        expires_on: row.get("expires_on"),
+        notes: row.get("notes"),
+        alerted_expires_on: row.get("alerted_expires_on"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Support contract database operations
+pub struct SupportContractRepo;
+
+impl SupportContractRepo {
+    /// All contracts, soonest expiry first
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<SupportContract>> {
+        let rows = sqlx::query("SELECT * FROM support_contracts ORDER BY expires_on, id")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_support_contract_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<SupportContract>> {
+        let row = sqlx::query("SELECT * FROM support_contracts WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_support_contract_row))
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, req: &CreateSupportContractRequest) -> Result<SupportContract> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO support_contracts (device_id, device_model_id, provider, support_id, vendor_sla,
+                starts_on, expires_on, notes, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(req.device_id)
+        .bind(req.device_model_id)
+        .bind(&req.provider)
+        .bind(&req.support_id)
+        .bind(&req.vendor_sla)
+        .bind(req.starts_on)
+        .bind(req.expires_on)
+        .bind(&req.notes)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get(pool, result.last_insert_rowid())
+            .await?
+            .context("Support contract not found after creation")
+    }
+
+    /// Update a contract. The expiry alert stays sent only while expires_on is unchanged.
+    pub async fn update(pool: &Pool<Sqlite>, id: i64, req: &CreateSupportContractRequest) -> Result<SupportContract> {
+        let result = sqlx::query(
+            r#"
+            UPDATE support_contracts SET device_id = ?, device_model_id = ?, provider = ?, support_id = ?,
+                vendor_sla = ?, starts_on = ?, expires_on = ?, notes = ?,
+                alerted_expires_on = CASE WHEN alerted_expires_on = ? THEN alerted_expires_on END,
+                updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(req.device_id)
+        .bind(req.device_model_id)
+        .bind(&req.provider)
+        .bind(&req.support_id)
+        .bind(&req.vendor_sla)
+        .bind(req.starts_on)
+        .bind(req.expires_on)
+        .bind(&req.notes)
+        .bind(req.expires_on)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Support contract", &id.to_string()).into());
+        }
+        Self::get(pool, id)
+            .await?
+            .context("Support contract not found after update")
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM support_contracts WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Support contract", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Record that the expiry notification for `expires_on` was sent
+    pub async fn mark_alerted(pool: &Pool<Sqlite>, id: i64, expires_on: NaiveDate) -> Result<()> {
+        sqlx::query("UPDATE support_contracts SET alerted_expires_on = ? WHERE id = ?")
+            .bind(expires_on)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_syslog_event_row(row: &SqliteRow) -> SyslogEvent {
+    SyslogEvent {
+        id: row.get("id"),
+        device_id: row.get("device_id"),
+        device_hostname: row.get("device_hostname"),
+        source_ip: row.get("source_ip"),
+        facility: row.get("facility"),
+        severity: row.get("severity"),
+        hostname: row.get("hostname"),
+        app_name: row.get("app_name"),
+        message: row.get("message"),
+        received_at: row.get("received_at"),
+    }
+}
+
+fn map_syslog_alert_rule_row(row: &SqliteRow) -> SyslogAlertRule {
+    SyslogAlertRule {
+        id: row.get("id"),
+        name: row.get("name"),
+        pattern: row.get("pattern"),
+        max_severity: row.get("max_severity"),
+        enabled: row.get("enabled"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn map_syslog_alert_row(row: &SqliteRow) -> SyslogAlert {
+    SyslogAlert {
+        id: row.get("id"),
+        rule_id: row.get("rule_id"),
+        rule_name: row.get("rule_name"),
+        event_id: row.get("event_id"),
+        device_id: row.get("device_id"),
+        device_hostname: row.get("device_hostname"),
+        source_ip: row.get("source_ip"),
+        severity: row.get("severity"),
+        message: row.get("message"),
+        acknowledged: row.get("acknowledged"),
+        acknowledged_by: row.get("acknowledged_by"),
+        acknowledged_at: row.get("acknowledged_at"),
+        created_at: row.get("created_at"),
+    }
+}
+
+const SELECT_SYSLOG_EVENT: &str = r#"
+    SELECT e.*, d.hostname AS device_hostname
+    FROM syslog_events_all e
+    LEFT JOIN devices d ON d.id = e.device_id
+"#;
+
+const SELECT_SYSLOG_ALERT: &str = r#"
+    SELECT a.id, a.rule_id, r.name AS rule_name, a.event_id, a.device_id, d.hostname AS device_hostname,
+           e.source_ip, e.severity, e.message, a.acknowledged, a.acknowledged_by, a.acknowledged_at, a.created_at
+    FROM syslog_alerts a
+    JOIN syslog_alert_rules r ON r.id = a.rule_id
+    JOIN syslog_events e ON e.id = a.event_id
+    LEFT JOIN devices d ON d.id = a.device_id
+"#;
+
+pub struct SyslogRepo;
+
+impl SyslogRepo {
+    pub async fn create_event(pool: &Pool<Sqlite>, source_ip: &str, device_id: Option<i64>, msg: &SyslogMessage) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO syslog_events (device_id, source_ip, facility, severity, hostname, app_name, message, received_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(device_id)
+        .bind(source_ip)
+        .bind(msg.facility)
+        .bind(msg.severity)
+        .bind(&msg.hostname)
+        .bind(&msg.app_name)
+        .bind(&msg.message)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Events matching the query, newest first
+    pub async fn list_events(pool: &Pool<Sqlite>, query: &SyslogEventQuery) -> Result<Vec<SyslogEvent>> {
+        let pattern = query.q.as_ref().map(|q| format!("%{}%", q));
+        let rows = sqlx::query(&format!(
+            r#"{} WHERE (? IS NULL OR e.device_id = ?)
+                 AND (? IS NULL OR e.severity <= ?)
+                 AND (? IS NULL OR e.message LIKE ?)
+               ORDER BY e.id DESC LIMIT ?"#,
+            SELECT_SYSLOG_EVENT
+        ))
+        .bind(query.device_id)
+        .bind(query.device_id)
+        .bind(query.max_severity)
+        .bind(query.max_severity)
+        .bind(&pattern)
+        .bind(&pattern)
+        .bind(query.limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_syslog_event_row).collect())
+    }
+
+    /// Delete all but the newest `keep` events, returning how many were removed
+    pub async fn prune_events(pool: &Pool<Sqlite>, keep: i64) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM syslog_events WHERE id <= (SELECT id FROM syslog_events ORDER BY id DESC LIMIT 1 OFFSET ?)",
+        )
+        .bind(keep)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn list_rules(pool: &Pool<Sqlite>) -> Result<Vec<SyslogAlertRule>> {
+        let rows = sqlx::query("SELECT * FROM syslog_alert_rules ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_syslog_alert_rule_row).collect())
+    }
+
+    pub async fn get_rule(pool: &Pool<Sqlite>, id: i64) -> Result<Option<SyslogAlertRule>> {
+        let row = sqlx::query("SELECT * FROM syslog_alert_rules WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_syslog_alert_rule_row))
+    }
+
+    pub async fn create_rule(pool: &Pool<Sqlite>, req: &CreateSyslogAlertRuleRequest) -> Result<SyslogAlertRule> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO syslog_alert_rules (name, pattern, max_severity, enabled, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.pattern)
+        .bind(req.max_severity)
+        .bind(req.enabled)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get_rule(pool, result.last_insert_rowid())
+            .await?
+            .context("Syslog alert rule not found after creation")
+    }
+
+    pub async fn update_rule(pool: &Pool<Sqlite>, id: i64, req: &CreateSyslogAlertRuleRequest) -> Result<SyslogAlertRule> {
+        let result = sqlx::query(
+            "UPDATE syslog_alert_rules SET name = ?, pattern = ?, max_severity = ?, enabled = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(&req.name)
+        .bind(&req.pattern)
+        .bind(req.max_severity)
+        .bind(req.enabled)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Syslog alert rule", &id.to_string()).into());
+        }
+        Self::get_rule(pool, id)
+            .await?
+            .context("Syslog alert rule not found after update")
+    }
+
+    pub async fn delete_rule(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM syslog_alert_rules WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Syslog alert rule", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    pub async fn create_alert(pool: &Pool<Sqlite>, rule_id: i64, event_id: i64, device_id: Option<i64>) -> Result<SyslogAlert> {
+        let result = sqlx::query(
+            "INSERT INTO syslog_alerts (rule_id, event_id, device_id, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(rule_id)
+        .bind(event_id)
+        .bind(device_id)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+
+        Self::get_alert(pool, result.last_insert_rowid())
+            .await?
+            .context("Syslog alert not found after creation")
+    }
+
+    pub async fn get_alert(pool: &Pool<Sqlite>, id: i64) -> Result<Option<SyslogAlert>> {
+        let row = sqlx::query(&format!("{} WHERE a.id = ?", SELECT_SYSLOG_ALERT))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_syslog_alert_row))
+    }
+
+    /// Alerts matching the query, newest first
+    pub async fn list_alerts(pool: &Pool<Sqlite>, query: &SyslogAlertQuery) -> Result<Vec<SyslogAlert>> {
+        let rows = sqlx::query(&format!(
+            r#"{} WHERE (? IS NULL OR a.device_id = ?)
+                 AND (? IS NULL OR a.acknowledged = ?)
+               ORDER BY a.id DESC LIMIT ?"#,
+            SELECT_SYSLOG_ALERT
+        ))
+        .bind(query.device_id)
+        .bind(query.device_id)
+        .bind(query.acknowledged)
+        .bind(query.acknowledged)
+        .bind(query.limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_syslog_alert_row).collect())
+    }
+
+    pub async fn acknowledge_alert(pool: &Pool<Sqlite>, id: i64, username: &str) -> Result<SyslogAlert> {
+        let result = sqlx::query(
+            "UPDATE syslog_alerts SET acknowledged = 1, acknowledged_by = ?, acknowledged_at = ? WHERE id = ?",
+        )
+        .bind(username)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Syslog alert", &id.to_string()).into());
+        }
+        Self::get_alert(pool, id)
+            .await?
+            .context("Syslog alert not found after update")
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_resource_tag_row(row: &SqliteRow) -> ResourceTag {
+    ResourceTag {
+        id: row.get("id"),
+        resource_type: row.get("resource_type"),
+        resource_id: row.get("resource_id"),
+        key: row.get("key"),
+        value: row.get("value"),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// Tag operations for devices, templates, credentials and jobs
+pub struct TagRepo;
+
+impl TagRepo {
+    pub async fn list_for_resource(pool: &Pool<Sqlite>, resource_type: &str, resource_id: &str) -> Result<Vec<ResourceTag>> {
+        let rows = sqlx::query(
+            "SELECT * FROM resource_tags WHERE resource_type = ? AND resource_id = ? ORDER BY key"
+        ).bind(resource_type).bind(resource_id).fetch_all(pool).await?;
+        Ok(rows.iter().map(map_resource_tag_row).collect())
+    }
+
+    pub async fn list_all(pool: &Pool<Sqlite>, resource_type: Option<&str>) -> Result<Vec<ResourceTag>> {
+        let rows = match resource_type {
+            Some(rt) => sqlx::query(
+                "SELECT * FROM resource_tags WHERE resource_type = ? ORDER BY key, resource_id"
+            ).bind(rt).fetch_all(pool).await?,
+            None => sqlx::query(
+                "SELECT * FROM resource_tags ORDER BY key, resource_type, resource_id"
+            ).fetch_all(pool).await?,
+        };
+        Ok(rows.iter().map(map_resource_tag_row).collect())
+    }
+
+    pub async fn list_distinct_keys(pool: &Pool<Sqlite>) -> Result<Vec<String>> {
+        let rows = sqlx::query("SELECT DISTINCT key FROM resource_tags ORDER BY key")
+            .fetch_all(pool).await?;
+        Ok(rows.iter().map(|r| r.get::<String, _>("key")).collect())
+    }
+
+    pub async fn set(pool: &Pool<Sqlite>, resource_type: &str, resource_id: &str, key: &str, value: &str) -> Result<()> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"INSERT INTO resource_tags (resource_type, resource_id, key, value, created_at)
+               VALUES (?, ?, ?, ?, ?)
+               ON CONFLICT(resource_type, resource_id, key) DO UPDATE SET value = excluded.value"#
+        )
+        .bind(resource_type).bind(resource_id).bind(key).bind(value).bind(now)
+        .execute(pool).await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, resource_type: &str, resource_id: &str, key: &str) -> Result<()> {
+        let result = sqlx::query("DELETE FROM resource_tags WHERE resource_type = ? AND resource_id = ? AND key = ?")
+            .bind(resource_type).bind(resource_id).bind(key)
+            .execute(pool).await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Tag", key).into());
+        }
+        Ok(())
+    }
+
+    /// IDs of resources of the given type carrying a tag matching the selector
+    pub async fn find_resource_ids(pool: &Pool<Sqlite>, resource_type: &str, selector: &TagSelector) -> Result<Vec<String>> {
+        let rows = match selector.value {
+            Some(ref value) => sqlx::query(
+                "SELECT resource_id FROM resource_tags WHERE resource_type = ? AND key = ? AND value = ?"
+            ).bind(resource_type).bind(&selector.key).bind(value).fetch_all(pool).await?,
+            None => sqlx::query(
+                "SELECT resource_id FROM resource_tags WHERE resource_type = ? AND key = ?"
+            ).bind(resource_type).bind(&selector.key).fetch_all(pool).await?,
+        };
+        Ok(rows.iter().map(|r| r.get::<String, _>("resource_id")).collect())
+    }
+}
@@ -0,0 +1,228 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_team_row(row: &SqliteRow, members: Vec<TeamMember>) -> Team {
+    Team {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        members,
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn map_resource_row(row: &SqliteRow) -> TeamResource {
+    TeamResource {
+        resource_type: row.get("resource_type"),
+        resource_id: row.get("resource_id"),
+        team_id: row.get("team_id"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn map_rule_row(row: &SqliteRow) -> TeamRule {
+    TeamRule {
+        id: row.get("id"),
+        team_id: row.get("team_id"),
+        action: row.get("action"),
+        scope: row.get("scope"),
+        group_id: row.get("group_id"),
+        command_pattern: row.get("command_pattern"),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// Team, membership, ownership and rule database operations
+pub struct TeamRepo;
+
+impl TeamRepo {
+    async fn members(pool: &Pool<Sqlite>, team_id: i64) -> Result<Vec<TeamMember>> {
+        let rows = sqlx::query(
+            r#"SELECT u.id, u.username FROM team_members m JOIN users u ON u.id = m.user_id
+               WHERE m.team_id = ? ORDER BY u.username"#,
+        )
+        .bind(team_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|r| TeamMember { user_id: r.get("id"), username: r.get("username") })
+            .collect())
+    }
+
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<Team>> {
+        let rows = sqlx::query("SELECT * FROM teams ORDER BY name").fetch_all(pool).await?;
+        let mut teams = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let members = Self::members(pool, row.get("id")).await?;
+            teams.push(map_team_row(row, members));
+        }
+        Ok(teams)
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Team>> {
+        let row = sqlx::query("SELECT * FROM teams WHERE id = ?").bind(id).fetch_optional(pool).await?;
+        match row {
+            Some(row) => Ok(Some(map_team_row(&row, Self::members(pool, id).await?))),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn create(pool: &Pool<Sqlite>, req: &CreateTeamRequest) -> Result<Team> {
+        let now = Utc::now();
+        let result = sqlx::query("INSERT INTO teams (name, description, created_at, updated_at) VALUES (?, ?, ?, ?)")
+            .bind(&req.name)
+            .bind(&req.description)
+            .bind(now)
+            .bind(now)
+            .execute(pool)
+            .await?;
+        let id = result.last_insert_rowid();
+        Self::get(pool, id).await?.ok_or_else(|| super::NotFoundError::new("Team", &id.to_string()).into())
+    }
+
+    pub async fn update(pool: &Pool<Sqlite>, id: i64, req: &CreateTeamRequest) -> Result<Team> {
+        let result = sqlx::query("UPDATE teams SET name = ?, description = ?, updated_at = ? WHERE id = ?")
+            .bind(&req.name)
+            .bind(&req.description)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Team", &id.to_string()).into());
+        }
+        Self::get(pool, id).await?.ok_or_else(|| super::NotFoundError::new("Team", &id.to_string()).into())
+    }
+
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM teams WHERE id = ?").bind(id).execute(pool).await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Team", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    pub async fn set_members(pool: &Pool<Sqlite>, team_id: i64, user_ids: &[i64]) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM team_members WHERE team_id = ?").bind(team_id).execute(&mut *tx).await?;
+        for user_id in user_ids {
+            sqlx::query("INSERT OR IGNORE INTO team_members (team_id, user_id) VALUES (?, ?)")
+                .bind(team_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// IDs of the teams a user belongs to
+    pub async fn team_ids_for_user(pool: &Pool<Sqlite>, username: &str) -> Result<Vec<i64>> {
+        let rows: Vec<(i64,)> = sqlx::query_as(
+            "SELECT m.team_id FROM team_members m JOIN users u ON u.id = m.user_id WHERE u.username = ?",
+        )
+        .bind(username)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
+    // ========== Ownership ==========
+
+    pub async fn list_resources(pool: &Pool<Sqlite>, team_id: i64) -> Result<Vec<TeamResource>> {
+        let rows = sqlx::query("SELECT * FROM team_resources WHERE team_id = ? ORDER BY resource_type, resource_id")
+            .bind(team_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_resource_row).collect())
+    }
+
+    /// Make a team the owner of a resource, replacing any previous owner
+    pub async fn set_owner(pool: &Pool<Sqlite>, resource_type: &str, resource_id: i64, team_id: i64) -> Result<()> {
+        sqlx::query(
+            r#"INSERT INTO team_resources (resource_type, resource_id, team_id, created_at) VALUES (?, ?, ?, ?)
+               ON CONFLICT(resource_type, resource_id) DO UPDATE SET team_id = excluded.team_id, created_at = excluded.created_at"#,
+        )
+        .bind(resource_type)
+        .bind(resource_id)
+        .bind(team_id)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_owner(pool: &Pool<Sqlite>, team_id: i64, resource_type: &str, resource_id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM team_resources WHERE team_id = ? AND resource_type = ? AND resource_id = ?")
+            .bind(team_id)
+            .bind(resource_type)
+            .bind(resource_id)
+            .execute(pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Team resource", &format!("{}/{}", resource_type, resource_id)).into());
+        }
+        Ok(())
+    }
+
+    pub async fn owner_of(pool: &Pool<Sqlite>, resource_type: &str, resource_id: i64) -> Result<Option<i64>> {
+        let row: Option<(i64,)> = sqlx::query_as("SELECT team_id FROM team_resources WHERE resource_type = ? AND resource_id = ?")
+            .bind(resource_type)
+            .bind(resource_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.map(|(id,)| id))
+    }
+
+    // ========== Rules ==========
+
+    pub async fn list_rules(pool: &Pool<Sqlite>, team_ids: &[i64]) -> Result<Vec<TeamRule>> {
+        if team_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = vec!["?"; team_ids.len()].join(", ");
+        let sql = format!("SELECT * FROM team_rules WHERE team_id IN ({}) ORDER BY team_id, id", placeholders);
+        let mut query = sqlx::query(&sql);
+        for id in team_ids {
+            query = query.bind(id);
+        }
+        let rows = query.fetch_all(pool).await?;
+        Ok(rows.iter().map(map_rule_row).collect())
+    }
+
+    pub async fn create_rule(pool: &Pool<Sqlite>, team_id: i64, req: &CreateTeamRuleRequest) -> Result<TeamRule> {
+        let result = sqlx::query(
+            "INSERT INTO team_rules (team_id, action, scope, group_id, command_pattern, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(team_id)
+        .bind(&req.action)
+        .bind(&req.scope)
+        .bind(req.group_id)
+        .bind(&req.command_pattern)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        let row = sqlx::query("SELECT * FROM team_rules WHERE id = ?")
+            .bind(result.last_insert_rowid())
+            .fetch_one(pool)
+            .await?;
+        Ok(map_rule_row(&row))
+    }
+
+    pub async fn delete_rule(pool: &Pool<Sqlite>, team_id: i64, rule_id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM team_rules WHERE id = ? AND team_id = ?")
+            .bind(rule_id)
+            .bind(team_id)
+            .execute(pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Team rule", &rule_id.to_string()).into());
+        }
+        Ok(())
+    }
+}
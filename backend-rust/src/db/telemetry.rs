@@ -0,0 +1,223 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_telemetry_subscription_row(row: &SqliteRow) -> TelemetrySubscription {
+    let paths: String = row.get("paths");
+    TelemetrySubscription {
+        id: row.get("id"),
+        name: row.get("name"),
+        device_id: row.get("device_id"),
+        vendor_id: row.get("vendor_id"),
+        paths: serde_json::from_str(&paths).unwrap_or_default(),
+        mode: row.get("mode"),
+        sample_interval_secs: row.get("sample_interval_secs"),
+        encoding: row.get("encoding"),
+        port: row.get("port"),
+        tls: row.get("tls"),
+        tls_skip_verify: row.get("tls_skip_verify"),
+        tls_ca_cert: row.get("tls_ca_cert"),
+        tls_server_name: row.get("tls_server_name"),
+        credential_id: row.get("credential_id"),
+        store_samples: row.get("store_samples"),
+        enabled: row.get("enabled"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn map_telemetry_sample_row(row: &SqliteRow) -> TelemetrySample {
+    let value: String = row.get("value");
+    TelemetrySample {
+        id: row.get("id"),
+        subscription_id: row.get("subscription_id"),
+        device_id: row.get("device_id"),
+        path: row.get("path"),
+        value: serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value)),
+        timestamp: row.get("timestamp"),
+    }
+}
+
+/// gNMI telemetry subscription and sample database operations
+pub struct TelemetryRepo;
+
+impl TelemetryRepo {
+    pub async fn list_subscriptions(pool: &Pool<Sqlite>) -> Result<Vec<TelemetrySubscription>> {
+        let rows = sqlx::query("SELECT * FROM telemetry_subscriptions ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_telemetry_subscription_row).collect())
+    }
+
+    pub async fn get_subscription(pool: &Pool<Sqlite>, id: i64) -> Result<Option<TelemetrySubscription>> {
+        let row = sqlx::query("SELECT * FROM telemetry_subscriptions WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_telemetry_subscription_row))
+    }
+
+    pub async fn create_subscription(pool: &Pool<Sqlite>, req: &CreateTelemetrySubscriptionRequest) -> Result<TelemetrySubscription> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO telemetry_subscriptions (name, device_id, vendor_id, paths, mode, sample_interval_secs,
+                encoding, port, tls, tls_skip_verify, tls_ca_cert, tls_server_name, credential_id,
+                store_samples, enabled, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&req.name)
+        .bind(req.device_id)
+        .bind(req.vendor_id)
+        .bind(serde_json::to_string(&req.paths)?)
+        .bind(&req.mode)
+        .bind(req.sample_interval_secs)
+        .bind(&req.encoding)
+        .bind(req.port)
+        .bind(req.tls)
+        .bind(req.tls_skip_verify)
+        .bind(&req.tls_ca_cert)
+        .bind(&req.tls_server_name)
+        .bind(req.credential_id)
+        .bind(req.store_samples)
+        .bind(req.enabled)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get_subscription(pool, result.last_insert_rowid())
+            .await?
+            .context("Telemetry subscription not found after creation")
+    }
+
+    pub async fn update_subscription(pool: &Pool<Sqlite>, id: i64, req: &CreateTelemetrySubscriptionRequest) -> Result<TelemetrySubscription> {
+        let result = sqlx::query(
+            r#"
+            UPDATE telemetry_subscriptions SET name = ?, device_id = ?, vendor_id = ?, paths = ?, mode = ?,
+                sample_interval_secs = ?, encoding = ?, port = ?, tls = ?, tls_skip_verify = ?, tls_ca_cert = ?,
+                tls_server_name = ?, credential_id = ?, store_samples = ?, enabled = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&req.name)
+        .bind(req.device_id)
+        .bind(req.vendor_id)
+        .bind(serde_json::to_string(&req.paths)?)
+        .bind(&req.mode)
+        .bind(req.sample_interval_secs)
+        .bind(&req.encoding)
+        .bind(req.port)
+        .bind(req.tls)
+        .bind(req.tls_skip_verify)
+        .bind(&req.tls_ca_cert)
+        .bind(&req.tls_server_name)
+        .bind(req.credential_id)
+        .bind(req.store_samples)
+        .bind(req.enabled)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Telemetry subscription", &id.to_string()).into());
+        }
+        Self::get_subscription(pool, id)
+            .await?
+            .context("Telemetry subscription not found after update")
+    }
+
+    pub async fn delete_subscription(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM telemetry_subscriptions WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Telemetry subscription", &id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Store one notification's updates in one transaction
+    pub async fn insert_samples(
+        pool: &Pool<Sqlite>,
+        subscription_id: i64,
+        device_id: i64,
+        timestamp: DateTime<Utc>,
+        updates: &[TelemetryUpdate],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        for update in updates {
+            sqlx::query(
+                "INSERT INTO telemetry_samples (subscription_id, device_id, path, value, timestamp) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(subscription_id)
+            .bind(device_id)
+            .bind(&update.path)
+            .bind(update.value.to_string())
+            .bind(timestamp)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Samples matching the query, newest first. With `latest`, only the newest sample of each
+    /// device and path.
+    pub async fn list_samples(pool: &Pool<Sqlite>, query: &TelemetrySampleQuery) -> Result<Vec<TelemetrySample>> {
+        let prefix = query.path.as_ref().map(|p| format!("{}%", p));
+        let latest_filter = if query.latest {
+            r#"AND s.id = (SELECT l.id FROM telemetry_samples l
+                           WHERE l.device_id = s.device_id AND l.path = s.path
+                           ORDER BY l.timestamp DESC, l.id DESC LIMIT 1)"#
+        } else {
+            ""
+        };
+        let rows = sqlx::query(&format!(
+            r#"SELECT s.* FROM telemetry_samples s
+               WHERE (? IS NULL OR s.device_id = ?)
+                 AND (? IS NULL OR s.subscription_id = ?)
+                 AND (? IS NULL OR s.path LIKE ?)
+                 AND (? IS NULL OR s.timestamp >= ?)
+                 {}
+               ORDER BY s.timestamp DESC, s.id DESC LIMIT ?"#,
+            latest_filter
+        ))
+        .bind(query.device_id)
+        .bind(query.device_id)
+        .bind(query.subscription_id)
+        .bind(query.subscription_id)
+        .bind(&prefix)
+        .bind(&prefix)
+        .bind(query.since)
+        .bind(query.since)
+        .bind(query.limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_telemetry_sample_row).collect())
+    }
+
+    /// Samples taken at or after `since`, oldest first, for metrics export
+    pub async fn samples_since(pool: &Pool<Sqlite>, since: DateTime<Utc>) -> Result<Vec<TelemetrySample>> {
+        let rows = sqlx::query("SELECT * FROM telemetry_samples WHERE timestamp >= ? ORDER BY timestamp, id")
+            .bind(since)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_telemetry_sample_row).collect())
+    }
+
+    /// Delete samples taken before `before`, returning how many were removed
+    pub async fn prune_samples(pool: &Pool<Sqlite>, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM telemetry_samples WHERE timestamp < ?")
+            .bind(before)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
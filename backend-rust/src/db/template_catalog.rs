@@ -0,0 +1,71 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_provenance_row(row: &SqliteRow) -> TemplateProvenance {
+    TemplateProvenance {
+        template_id: row.get("template_id"),
+        source_url: row.get("source_url"),
+        source_path: row.get("source_path"),
+        commit_sha: row.get("commit_sha"),
+        content: row.get("content"),
+        installed_by: row.get("installed_by"),
+        installed_at: row.get("installed_at"),
+    }
+}
+
+/// Template catalog provenance database operations
+pub struct TemplateCatalogRepo;
+
+impl TemplateCatalogRepo {
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<TemplateProvenance>> {
+        let rows = sqlx::query("SELECT * FROM template_catalog_installs ORDER BY source_path")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_provenance_row).collect())
+    }
+
+    pub async fn get(pool: &Pool<Sqlite>, template_id: i64) -> Result<Option<TemplateProvenance>> {
+        let row = sqlx::query("SELECT * FROM template_catalog_installs WHERE template_id = ?")
+            .bind(template_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_provenance_row))
+    }
+
+    /// Record (or refresh) where a template was installed from
+    pub async fn record(
+        pool: &Pool<Sqlite>,
+        template_id: i64,
+        source_url: &str,
+        source_path: &str,
+        commit_sha: &str,
+        content: &str,
+        installed_by: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"INSERT INTO template_catalog_installs
+                   (template_id, source_url, source_path, commit_sha, content, installed_by, installed_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?)
+               ON CONFLICT(template_id) DO UPDATE SET
+                   source_url = excluded.source_url,
+                   source_path = excluded.source_path,
+                   commit_sha = excluded.commit_sha,
+                   content = excluded.content,
+                   installed_by = excluded.installed_by,
+                   installed_at = excluded.installed_at"#,
+        )
+        .bind(template_id)
+        .bind(source_url)
+        .bind(source_path)
+        .bind(commit_sha)
+        .bind(content)
+        .bind(installed_by)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -34,15 +34,6 @@ impl TemplateRepo {
         Ok(row.as_ref().map(map_template_row))
     }
 
-    pub async fn get_by_name(pool: &Pool<Sqlite>, name: &str) -> Result<Option<Template>> {
-        let row = sqlx::query(&format!("{} WHERE t.name = ? GROUP BY t.id", SELECT_TEMPLATE))
-            .bind(name)
-            .fetch_optional(pool)
-            .await?;
-
-        Ok(row.as_ref().map(map_template_row))
-    }
-
     pub async fn create(pool: &Pool<Sqlite>, req: &CreateTemplateRequest) -> Result<Template> {
         let now = Utc::now();
         let result = sqlx::query(
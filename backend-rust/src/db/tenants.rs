@@ -11,23 +11,40 @@ fn map_tenant_row(row: &SqliteRow) -> Tenant {
         name: row.get("name"),
         description: none_if_empty(row.get("description")),
         status: row.get("status"),
+        device_count: row.get("device_count"),
         created_at: row.get("created_at"),
         updated_at: row.get("updated_at"),
     }
 }
 
+fn map_tenant_variable_row(row: &SqliteRow) -> TenantVariable {
+    TenantVariable {
+        id: row.get("id"),
+        tenant_id: row.get("tenant_id"),
+        key: row.get("key"),
+        value: row.get("value"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+const SELECT_TENANT: &str = r#"
+    SELECT t.*, (SELECT COUNT(*) FROM devices d WHERE d.tenant_id = t.id) as device_count
+    FROM tenants t
+"#;
+
 pub struct TenantRepo;
 
 impl TenantRepo {
     pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<Tenant>> {
-        let rows = sqlx::query("SELECT * FROM tenants ORDER BY name")
+        let rows = sqlx::query(&format!("{} ORDER BY t.name", SELECT_TENANT))
             .fetch_all(pool)
             .await?;
         Ok(rows.iter().map(map_tenant_row).collect())
     }
 
     pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Tenant>> {
-        let row = sqlx::query("SELECT * FROM tenants WHERE id = ?")
+        let row = sqlx::query(&format!("{} WHERE t.id = ?", SELECT_TENANT))
             .bind(id)
             .fetch_optional(pool)
             .await?;
@@ -92,4 +109,84 @@ impl TenantRepo {
         }
         Ok(())
     }
+
+    // ========== Variables ==========
+
+    pub async fn list_variables(pool: &Pool<Sqlite>, tenant_id: i64) -> Result<Vec<TenantVariable>> {
+        let rows = sqlx::query(
+            "SELECT id, tenant_id, key, value, created_at, updated_at FROM tenant_variables WHERE tenant_id = ? ORDER BY key",
+        )
+        .bind(tenant_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_tenant_variable_row).collect())
+    }
+
+    pub async fn set_variable(pool: &Pool<Sqlite>, tenant_id: i64, key: &str, value: &str) -> Result<()> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"INSERT INTO tenant_variables (tenant_id, key, value, created_at, updated_at)
+               VALUES (?, ?, ?, ?, ?)
+               ON CONFLICT(tenant_id, key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at"#,
+        )
+        .bind(tenant_id)
+        .bind(key)
+        .bind(value)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_variable(pool: &Pool<Sqlite>, tenant_id: i64, key: &str) -> Result<()> {
+        sqlx::query("DELETE FROM tenant_variables WHERE tenant_id = ? AND key = ?")
+            .bind(tenant_id)
+            .bind(key)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    // ========== Device Assignment ==========
+
+    /// Assign (`Some`) or unassign (`None`) a device
+    pub async fn assign_device(pool: &Pool<Sqlite>, device_id: i64, tenant_id: Option<i64>) -> Result<()> {
+        let result = sqlx::query("UPDATE devices SET tenant_id = ?, updated_at = ? WHERE id = ?")
+            .bind(tenant_id)
+            .bind(Utc::now())
+            .bind(device_id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::NotFoundError::new("Device", &device_id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Replace the tenant's devices: the listed devices are assigned, every other device it
+    /// had is unassigned
+    pub async fn set_devices(pool: &Pool<Sqlite>, tenant_id: i64, device_ids: &[i64]) -> Result<()> {
+        let now = Utc::now();
+        let mut tx = pool.begin().await?;
+        sqlx::query("UPDATE devices SET tenant_id = NULL, updated_at = ? WHERE tenant_id = ?")
+            .bind(now)
+            .bind(tenant_id)
+            .execute(&mut *tx)
+            .await?;
+        for device_id in device_ids {
+            let result = sqlx::query("UPDATE devices SET tenant_id = ?, updated_at = ? WHERE id = ?")
+                .bind(tenant_id)
+                .bind(now)
+                .bind(device_id)
+                .execute(&mut *tx)
+                .await?;
+            if result.rows_affected() == 0 {
+                return Err(super::NotFoundError::new("Device", &device_id.to_string()).into());
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
 }
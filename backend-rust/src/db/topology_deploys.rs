@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite, sqlite::SqliteRow};
+
+use crate::models::*;
+
+fn map_topology_deploy_row(row: &SqliteRow) -> TopologyDeploy {
+    let stages: String = row.get("stages");
+    TopologyDeploy {
+        id: row.get("id"),
+        topology_id: row.get("topology_id"),
+        status: row.get("status"),
+        stages: serde_json::from_str(&stages).unwrap_or_default(),
+        verify_command: row.get("verify_command"),
+        lab: row.get("lab"),
+        current_stage: row.get("current_stage"),
+        error: row.get("error"),
+        triggered_by: row.get("triggered_by"),
+        created_at: row.get("created_at"),
+        completed_at: row.get("completed_at"),
+    }
+}
+
+fn map_topology_deploy_device_row(row: &SqliteRow) -> TopologyDeployDevice {
+    let deploy_error: Option<String> = row.get("deploy_error");
+    let verify_error: Option<String> = row.get("verify_error");
+    TopologyDeployDevice {
+        device_id: row.get("device_id"),
+        hostname: row.get("hostname"),
+        topology_role: row.get("topology_role"),
+        stage: row.get("stage"),
+        deploy_job_id: row.get("deploy_job_id"),
+        deploy_status: row.get("deploy_status"),
+        verify_job_id: row.get("verify_job_id"),
+        verify_status: row.get("verify_status"),
+        error: deploy_error.or(verify_error).filter(|e| !e.is_empty()),
+    }
+}
+
+/// Topology deploy database operations
+pub struct TopologyDeployRepo;
+
+impl TopologyDeployRepo {
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<Option<TopologyDeploy>> {
+        let row = sqlx::query("SELECT * FROM topology_deploys WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.as_ref().map(map_topology_deploy_row))
+    }
+
+    pub async fn list_for_topology(pool: &Pool<Sqlite>, topology_id: i64) -> Result<Vec<TopologyDeploy>> {
+        let rows = sqlx::query("SELECT * FROM topology_deploys WHERE topology_id = ? ORDER BY created_at DESC, id DESC")
+            .bind(topology_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().map(map_topology_deploy_row).collect())
+    }
+
+    /// Create a running deploy with its planned (device_id, stage) pairs
+    pub async fn create(
+        pool: &Pool<Sqlite>,
+        topology_id: i64,
+        stages: &[Vec<String>],
+        verify_command: &str,
+        lab: bool,
+        triggered_by: &str,
+        plan: &[(i64, i64)],
+    ) -> Result<TopologyDeploy> {
+        let mut tx = pool.begin().await?;
+        let result = sqlx::query(
+            r#"INSERT INTO topology_deploys (topology_id, status, stages, verify_command, lab, triggered_by, created_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(topology_id)
+        .bind(topology_deploy_status::RUNNING)
+        .bind(serde_json::to_string(stages)?)
+        .bind(verify_command)
+        .bind(lab)
+        .bind(triggered_by)
+        .bind(Utc::now())
+        .execute(&mut *tx)
+        .await?;
+        let id = result.last_insert_rowid();
+
+        for (device_id, stage) in plan {
+            sqlx::query("INSERT INTO topology_deploy_devices (deploy_id, device_id, stage) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(device_id)
+                .bind(stage)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Self::get(pool, id)
+            .await?
+            .context("Topology deploy not found after creation")
+    }
+
+    /// Planned devices of a deploy with the status of their jobs, ordered by stage
+    pub async fn list_devices(pool: &Pool<Sqlite>, deploy_id: i64) -> Result<Vec<TopologyDeployDevice>> {
+        let rows = sqlx::query(
+            r#"SELECT p.device_id, p.stage, p.deploy_job_id, p.verify_job_id,
+                      d.hostname, COALESCE(d.topology_role, '') as topology_role,
+                      dj.status as deploy_status, dj.error as deploy_error,
+                      vj.status as verify_status, vj.error as verify_error
+               FROM topology_deploy_devices p
+               JOIN devices d ON d.id = p.device_id
+               LEFT JOIN jobs_all dj ON dj.id = p.deploy_job_id
+               LEFT JOIN jobs_all vj ON vj.id = p.verify_job_id
+               WHERE p.deploy_id = ?
+               ORDER BY p.stage, d.hostname"#,
+        )
+        .bind(deploy_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_topology_deploy_device_row).collect())
+    }
+
+    pub async fn set_stage(pool: &Pool<Sqlite>, id: i64, stage: i64) -> Result<()> {
+        sqlx::query("UPDATE topology_deploys SET current_stage = ? WHERE id = ?")
+            .bind(stage)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_deploy_job(pool: &Pool<Sqlite>, id: i64, device_id: i64, job_id: &str) -> Result<()> {
+        sqlx::query("UPDATE topology_deploy_devices SET deploy_job_id = ? WHERE deploy_id = ? AND device_id = ?")
+            .bind(job_id)
+            .bind(id)
+            .bind(device_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_verify_job(pool: &Pool<Sqlite>, id: i64, device_id: i64, job_id: &str) -> Result<()> {
+        sqlx::query("UPDATE topology_deploy_devices SET verify_job_id = ? WHERE deploy_id = ? AND device_id = ?")
+            .bind(job_id)
+            .bind(id)
+            .bind(device_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn finish(pool: &Pool<Sqlite>, id: i64, status: &str, error: &str) -> Result<()> {
+        sqlx::query("UPDATE topology_deploys SET status = ?, error = ?, completed_at = ? WHERE id = ?")
+            .bind(status)
+            .bind(error)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Fail deploys left running by a previous process; their orchestration didn't survive it
+    pub async fn fail_running(pool: &Pool<Sqlite>, error: &str) -> Result<u64> {
+        let result = sqlx::query("UPDATE topology_deploys SET status = ?, error = ?, completed_at = ? WHERE status = ?")
+            .bind(topology_deploy_status::FAILED)
+            .bind(error)
+            .bind(Utc::now())
+            .bind(topology_deploy_status::RUNNING)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
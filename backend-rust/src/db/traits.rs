@@ -0,0 +1,135 @@
+//! Per-entity persistence traits covering what a config render reads. `Store` (SQLite)
+//! implements them all. The render path (`jobs::render_device` and its helpers, the CLI `render`
+//! command, and render-only handler bodies such as the rendered config outline) is written against
+//! them, so it runs on the in-memory store in unit tests. `AppState` and the other handlers still
+//! use `Store` directly.
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::models::*;
+
+use super::Store;
+
+#[async_trait::async_trait]
+pub trait DeviceStore: Send + Sync {
+    async fn list_devices(&self) -> Result<Vec<Device>>;
+    async fn get_device(&self, id: i64) -> Result<Option<Device>>;
+    async fn get_device_by_mac(&self, mac: &str) -> Result<Option<Device>>;
+}
+
+#[async_trait::async_trait]
+pub trait VendorStore: Send + Sync {
+    async fn get_vendor(&self, id: i64) -> Result<Option<Vendor>>;
+    /// Case-insensitive lookup by vendor name
+    async fn get_vendor_by_name(&self, name: &str) -> Result<Option<Vendor>>;
+
+    /// Resolve a vendor from either an integer ID string or a name (case-insensitive)
+    async fn resolve_vendor(&self, vendor_str: &str) -> Result<Option<Vendor>> {
+        match vendor_str.parse::<i64>() {
+            Ok(id) => self.get_vendor(id).await,
+            Err(_) => self.get_vendor_by_name(vendor_str).await,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+pub trait TemplateStore: Send + Sync {
+    async fn get_template(&self, id: i64) -> Result<Option<Template>>;
+    async fn get_template_by_name(&self, name: &str) -> Result<Option<Template>>;
+}
+
+#[async_trait::async_trait]
+pub trait SettingsStore: Send + Sync {
+    async fn get_settings(&self) -> Result<Settings>;
+}
+
+/// The per-device inputs of a config render beyond the template and settings
+#[async_trait::async_trait]
+pub trait RenderInputStore: Send + Sync {
+    /// Role template layers for a device, in sort order (see `Store::resolve_role_templates`)
+    async fn resolve_role_templates(
+        &self,
+        base: &Template,
+        vendor: Option<&str>,
+        topology_role: Option<&str>,
+    ) -> Result<Vec<Template>>;
+    async fn resolve_device_variables_flat(&self, device_id: i64) -> Result<HashMap<String, String>>;
+    async fn list_port_assignments(&self, device_id: i64) -> Result<Vec<PortAssignment>>;
+    async fn apply_device_config_snippets(&self, device_id: i64, rendered: String) -> Result<String>;
+}
+
+/// Everything needed to render a device config
+pub trait RenderStore: VendorStore + TemplateStore + SettingsStore + RenderInputStore {}
+
+impl<T: VendorStore + TemplateStore + SettingsStore + RenderInputStore> RenderStore for T {}
+
+// The SQLite implementation delegates to Store's inherent methods
+
+#[async_trait::async_trait]
+impl DeviceStore for Store {
+    async fn list_devices(&self) -> Result<Vec<Device>> {
+        Store::list_devices(self).await
+    }
+
+    async fn get_device(&self, id: i64) -> Result<Option<Device>> {
+        Store::get_device(self, id).await
+    }
+
+    async fn get_device_by_mac(&self, mac: &str) -> Result<Option<Device>> {
+        Store::get_device_by_mac(self, mac).await
+    }
+}
+
+#[async_trait::async_trait]
+impl VendorStore for Store {
+    async fn get_vendor(&self, id: i64) -> Result<Option<Vendor>> {
+        Store::get_vendor(self, id).await
+    }
+
+    async fn get_vendor_by_name(&self, name: &str) -> Result<Option<Vendor>> {
+        Store::get_vendor_by_name(self, name).await
+    }
+}
+
+#[async_trait::async_trait]
+impl TemplateStore for Store {
+    async fn get_template(&self, id: i64) -> Result<Option<Template>> {
+        Store::get_template(self, id).await
+    }
+
+    async fn get_template_by_name(&self, name: &str) -> Result<Option<Template>> {
+        Store::get_template_by_name(self, name).await
+    }
+}
+
+#[async_trait::async_trait]
+impl SettingsStore for Store {
+    async fn get_settings(&self) -> Result<Settings> {
+        Store::get_settings(self).await
+    }
+}
+
+#[async_trait::async_trait]
+impl RenderInputStore for Store {
+    async fn resolve_role_templates(
+        &self,
+        base: &Template,
+        vendor: Option<&str>,
+        topology_role: Option<&str>,
+    ) -> Result<Vec<Template>> {
+        Store::resolve_role_templates(self, base, vendor, topology_role).await
+    }
+
+    async fn resolve_device_variables_flat(&self, device_id: i64) -> Result<HashMap<String, String>> {
+        Store::resolve_device_variables_flat(self, device_id).await
+    }
+
+    async fn list_port_assignments(&self, device_id: i64) -> Result<Vec<PortAssignment>> {
+        Store::list_port_assignments(self, device_id).await
+    }
+
+    async fn apply_device_config_snippets(&self, device_id: i64, rendered: String) -> Result<String> {
+        Store::apply_device_config_snippets(self, device_id, rendered).await
+    }
+}
@@ -8,6 +8,8 @@ use crate::models::{
 
 use super::groups::GroupRepo;
 use super::device_variables::DeviceVariableRepo;
+use super::gpu_clusters::GpuClusterRepo;
+use super::tenants::TenantRepo;
 
 /// The "all" group always has integer ID 1 after migration.
 const ALL_GROUP_ID: i64 = 1;
@@ -16,18 +18,168 @@ const ALL_GROUP_ID: i64 = 1;
 ///
 /// Resolution order (lowest → highest priority):
 /// 1. "all" group variables (precedence 0)
-/// 2. Group variables sorted by (depth ASC, precedence ASC)
-/// 3. Host variables (device_variables) — always win
+/// 2. Tenant variables, then GPU cluster variables, for the device's assignments
+/// 3. Group variables sorted by (depth ASC, precedence ASC)
+/// 4. Host variables (device_variables) — always win
 pub struct VariableResolver;
 
 impl VariableResolver {
     /// Full resolution with provenance tracking (for the inspector API).
     pub async fn resolve(
         pool: &Pool<Sqlite>,
+        all_groups: &[Group],
         device_id: i64,
     ) -> Result<ResolvedVariablesResponse> {
-        // 1. Load all groups into a lookup map
-        let all_groups = GroupRepo::list_all_raw(pool).await?;
+        let (groups_by_id, sorted_groups) = Self::ordered_groups(pool, all_groups, device_id).await?;
+
+        // 5. Load group variables for all relevant groups (including "all")
+        let mut var_group_ids: Vec<i64> = vec![ALL_GROUP_ID];
+        var_group_ids.extend(sorted_groups.iter().map(|g| g.id));
+
+        let all_group_vars = GroupRepo::list_variables_for_groups(pool, &var_group_ids).await?;
+
+        // Index group vars by group_id
+        let mut vars_by_group: HashMap<i64, HashMap<String, String>> = HashMap::new();
+        for gv in &all_group_vars {
+            vars_by_group
+                .entry(gv.group_id)
+                .or_default()
+                .insert(gv.key.clone(), gv.value.clone());
+        }
+
+        // 6. Load host variables (device_variables)
+        let host_vars_list = DeviceVariableRepo::list_by_device(pool, device_id).await?;
+        let host_vars: HashMap<String, String> = host_vars_list
+            .into_iter()
+            .map(|v| (v.key, v.value))
+            .collect();
+
+        // 7. Build resolution layers
+        let mut layers: Vec<ResolutionLayer> = Vec::new();
+
+        // Layer 0: "all" group
+        let all_group = groups_by_id.get(&ALL_GROUP_ID);
+        let all_vars = vars_by_group.remove(&ALL_GROUP_ID).unwrap_or_default();
+        layers.push(ResolutionLayer {
+            source: ALL_GROUP_ID.to_string(),
+            source_name: all_group.map(|g| g.name.clone()).unwrap_or_else(|| "all".to_string()),
+            source_type: "all".to_string(),
+            precedence: 0,
+            variables: all_vars,
+        });
+
+        // Tenant and GPU cluster layers, when the device is assigned to one
+        let tenant: Option<(i64, String)> = sqlx::query_as(
+            "SELECT t.id, t.name FROM devices d INNER JOIN tenants t ON t.id = d.tenant_id WHERE d.id = ?",
+        )
+        .bind(device_id)
+        .fetch_optional(pool)
+        .await?;
+        if let Some((tenant_id, name)) = tenant {
+            let variables = TenantRepo::list_variables(pool, tenant_id)
+                .await?
+                .into_iter()
+                .map(|v| (v.key, v.value))
+                .collect();
+            layers.push(ResolutionLayer {
+                source: tenant_id.to_string(),
+                source_name: name,
+                source_type: "tenant".to_string(),
+                precedence: 0,
+                variables,
+            });
+        }
+        let cluster: Option<(i64, String)> = sqlx::query_as(
+            "SELECT c.id, c.name FROM devices d INNER JOIN gpu_clusters c ON c.id = d.gpu_cluster_id WHERE d.id = ?",
+        )
+        .bind(device_id)
+        .fetch_optional(pool)
+        .await?;
+        if let Some((cluster_id, name)) = cluster {
+            let variables = GpuClusterRepo::list_variables(pool, cluster_id)
+                .await?
+                .into_iter()
+                .map(|v| (v.key, v.value))
+                .collect();
+            layers.push(ResolutionLayer {
+                source: cluster_id.to_string(),
+                source_name: name,
+                source_type: "gpu_cluster".to_string(),
+                precedence: 0,
+                variables,
+            });
+        }
+
+        // Group layers in sorted order
+        for group in &sorted_groups {
+            let gvars = vars_by_group.remove(&group.id).unwrap_or_default();
+            layers.push(ResolutionLayer {
+                source: group.id.to_string(),
+                source_name: group.name.clone(),
+                source_type: "group".to_string(),
+                precedence: group.precedence,
+                variables: gvars,
+            });
+        }
+
+        // Last layer: host vars
+        layers.push(ResolutionLayer {
+            source: "host".to_string(),
+            source_name: "Host Variables".to_string(),
+            source_type: "host".to_string(),
+            precedence: i32::MAX,
+            variables: host_vars,
+        });
+
+        // 8. Merge layers left-to-right, tracking provenance
+        let mut merged: HashMap<String, String> = HashMap::new();
+        let mut provenance: HashMap<String, (String, String, String)> = HashMap::new(); // key -> (source, source_name, source_type)
+
+        for layer in &layers {
+            for (key, value) in &layer.variables {
+                merged.insert(key.clone(), value.clone());
+                provenance.insert(
+                    key.clone(),
+                    (layer.source.clone(), layer.source_name.clone(), layer.source_type.clone()),
+                );
+            }
+        }
+
+        // Build resolved list
+        let mut resolved: Vec<ResolvedVariable> = merged
+            .iter()
+            .map(|(key, value)| {
+                let (source, source_name, source_type) = provenance
+                    .get(key)
+                    .cloned()
+                    .unwrap_or(("unknown".to_string(), "Unknown".to_string(), "unknown".to_string()));
+                ResolvedVariable {
+                    key: key.clone(),
+                    value: value.clone(),
+                    source,
+                    source_name,
+                    source_type,
+                }
+            })
+            .collect();
+        resolved.sort_by(|a, b| a.key.cmp(&b.key));
+
+        Ok(ResolvedVariablesResponse {
+            variables: merged,
+            resolved,
+            resolution_order: layers,
+        })
+    }
+
+    /// The device's groups (direct, implied by role/vendor, and their ancestors) in
+    /// resolution order, excluding "all", plus a lookup of every group by id.
+    /// `all_groups` is every group, as returned by `GroupRepo::list_all_raw`.
+    async fn ordered_groups(
+        pool: &Pool<Sqlite>,
+        all_groups: &[Group],
+        device_id: i64,
+    ) -> Result<(HashMap<i64, Group>, Vec<Group>)> {
+        // 1. Index all groups by id
         let groups_by_id: HashMap<i64, Group> = all_groups
             .iter()
             .cloned()
@@ -125,10 +277,11 @@ impl VariableResolver {
 
         // 4. Sort groups by (depth ASC, precedence ASC) — parents before children,
         //    lower precedence evaluated first (overridden by higher)
-        let mut sorted_groups: Vec<&Group> = all_relevant_ids
+        let mut sorted_groups: Vec<Group> = all_relevant_ids
             .iter()
             .filter(|id| **id != ALL_GROUP_ID) // "all" handled separately as layer 0
             .filter_map(|id| groups_by_id.get(id))
+            .cloned()
             .collect();
 
         sorted_groups.sort_by(|a, b| {
@@ -137,110 +290,48 @@ impl VariableResolver {
             depth_a.cmp(&depth_b).then(a.precedence.cmp(&b.precedence))
         });
 
-        // 5. Load group variables for all relevant groups (including "all")
-        let mut var_group_ids: Vec<i64> = vec![ALL_GROUP_ID];
-        var_group_ids.extend(sorted_groups.iter().map(|g| g.id));
-
-        let all_group_vars = GroupRepo::list_variables_for_groups(pool, &var_group_ids).await?;
-
-        // Index group vars by group_id
-        let mut vars_by_group: HashMap<i64, HashMap<String, String>> = HashMap::new();
-        for gv in &all_group_vars {
-            vars_by_group
-                .entry(gv.group_id)
-                .or_default()
-                .insert(gv.key.clone(), gv.value.clone());
-        }
-
-        // 6. Load host variables (device_variables)
-        let host_vars_list = DeviceVariableRepo::list_by_device(pool, device_id).await?;
-        let host_vars: HashMap<String, String> = host_vars_list
-            .into_iter()
-            .map(|v| (v.key, v.value))
-            .collect();
-
-        // 7. Build resolution layers
-        let mut layers: Vec<ResolutionLayer> = Vec::new();
-
-        // Layer 0: "all" group
-        let all_group = groups_by_id.get(&ALL_GROUP_ID);
-        let all_vars = vars_by_group.remove(&ALL_GROUP_ID).unwrap_or_default();
-        layers.push(ResolutionLayer {
-            source: ALL_GROUP_ID.to_string(),
-            source_name: all_group.map(|g| g.name.clone()).unwrap_or_else(|| "all".to_string()),
-            source_type: "all".to_string(),
-            precedence: 0,
-            variables: all_vars,
-        });
-
-        // Layers 1..N: groups in sorted order
-        for group in &sorted_groups {
-            let gvars = vars_by_group.remove(&group.id).unwrap_or_default();
-            layers.push(ResolutionLayer {
-                source: group.id.to_string(),
-                source_name: group.name.clone(),
-                source_type: "group".to_string(),
-                precedence: group.precedence,
-                variables: gvars,
-            });
-        }
-
-        // Layer N+1: host vars
-        layers.push(ResolutionLayer {
-            source: "host".to_string(),
-            source_name: "Host Variables".to_string(),
-            source_type: "host".to_string(),
-            precedence: i32::MAX,
-            variables: host_vars,
-        });
-
-        // 8. Merge layers left-to-right, tracking provenance
-        let mut merged: HashMap<String, String> = HashMap::new();
-        let mut provenance: HashMap<String, (String, String, String)> = HashMap::new(); // key -> (source, source_name, source_type)
-
-        for layer in &layers {
-            for (key, value) in &layer.variables {
-                merged.insert(key.clone(), value.clone());
-                provenance.insert(
-                    key.clone(),
-                    (layer.source.clone(), layer.source_name.clone(), layer.source_type.clone()),
-                );
-            }
-        }
+        Ok((groups_by_id, sorted_groups))
+    }
 
-        // Build resolved list
-        let mut resolved: Vec<ResolvedVariable> = merged
+    /// Credential bound to the device's groups: the last group in resolution order
+    /// with a credential wins, falling back to the "all" group's.
+    pub async fn resolve_group_credential(
+        pool: &Pool<Sqlite>,
+        all_groups: &[Group],
+        device_id: i64,
+    ) -> Result<Option<i64>> {
+        let (groups_by_id, sorted_groups) = Self::ordered_groups(pool, all_groups, device_id).await?;
+        let all_credential = groups_by_id.get(&ALL_GROUP_ID).and_then(|g| g.credential_id);
+        Ok(sorted_groups
             .iter()
-            .map(|(key, value)| {
-                let (source, source_name, source_type) = provenance
-                    .get(key)
-                    .cloned()
-                    .unwrap_or(("unknown".to_string(), "Unknown".to_string(), "unknown".to_string()));
-                ResolvedVariable {
-                    key: key.clone(),
-                    value: value.clone(),
-                    source,
-                    source_name,
-                    source_type,
-                }
-            })
-            .collect();
-        resolved.sort_by(|a, b| a.key.cmp(&b.key));
+            .rev()
+            .find_map(|g| g.credential_id)
+            .or(all_credential))
+    }
 
-        Ok(ResolvedVariablesResponse {
-            variables: merged,
-            resolved,
-            resolution_order: layers,
-        })
+    /// Every key set at any layer: device, group, tenant or GPU cluster
+    pub async fn all_keys(pool: &Pool<Sqlite>) -> Result<HashSet<String>> {
+        let keys: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT key FROM device_variables
+            UNION SELECT key FROM group_variables
+            UNION SELECT key FROM tenant_variables
+            UNION SELECT key FROM gpu_cluster_variables
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(keys.into_iter().collect())
     }
 
     /// Convenience: resolve and return only the merged HashMap.
     /// Drop-in replacement for the old `list_device_variables → HashMap` pattern.
     pub async fn resolve_flat(
         pool: &Pool<Sqlite>,
+        all_groups: &[Group],
         device_id: i64,
     ) -> Result<HashMap<String, String>> {
-        let result = Self::resolve(pool, device_id).await?;
+        let result = Self::resolve(pool, all_groups, device_id).await?;
         Ok(result.variables)
     }
 }
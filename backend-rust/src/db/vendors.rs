@@ -7,8 +7,9 @@ use crate::models::*;
 use super::row_helpers::map_vendor_row;
 
 const SELECT_VENDOR: &str = r#"
-    SELECT v.id, v.name, v.backup_command, v.deploy_command, v.diff_command, v.ssh_port, v.ssh_user, v.ssh_pass,
-           v.mac_prefixes, v.vendor_class, v.default_template, v.group_names,
+    SELECT v.id, v.name, v.backup_command, v.deploy_command, v.diff_command, v.commit_confirm_command,
+           v.confirm_command, v.transport, v.ssh_port, v.ssh_user, v.ssh_pass,
+           v.mac_prefixes, v.vendor_class, v.default_template, v.group_names, v.ztp_wrapper,
            v.created_at, v.updated_at,
            COALESCE(COUNT(d.mac), 0) as device_count
     FROM vendors v
@@ -36,15 +37,6 @@ impl VendorRepo {
         Ok(row.as_ref().map(map_vendor_row))
     }
 
-    pub async fn get_by_name(pool: &Pool<Sqlite>, name: &str) -> Result<Option<Vendor>> {
-        let row = sqlx::query(&format!("{} WHERE LOWER(v.name) = LOWER(?) GROUP BY v.id", SELECT_VENDOR))
-            .bind(name)
-            .fetch_optional(pool)
-            .await?;
-
-        Ok(row.as_ref().map(map_vendor_row))
-    }
-
     pub async fn create(pool: &Pool<Sqlite>, req: &CreateVendorRequest) -> Result<Vendor> {
         let now = Utc::now();
         let mac_prefixes_json = serde_json::to_string(&req.mac_prefixes)?;
@@ -52,15 +44,19 @@ impl VendorRepo {
 
         let result = sqlx::query(
             r#"
-            INSERT INTO vendors (name, backup_command, deploy_command, diff_command, ssh_port, ssh_user, ssh_pass,
-                                 mac_prefixes, vendor_class, default_template, group_names, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO vendors (name, backup_command, deploy_command, diff_command, commit_confirm_command, confirm_command,
+                                 transport, ssh_port, ssh_user, ssh_pass, mac_prefixes, vendor_class, default_template,
+                                 group_names, ztp_wrapper, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&req.name)
         .bind(&req.backup_command)
         .bind(&req.deploy_command)
         .bind(&req.diff_command)
+        .bind(&req.commit_confirm_command)
+        .bind(&req.confirm_command)
+        .bind(&req.transport)
         .bind(req.ssh_port)
         .bind(&req.ssh_user)
         .bind(&req.ssh_pass)
@@ -68,6 +64,7 @@ impl VendorRepo {
         .bind(&req.vendor_class)
         .bind(&req.default_template)
         .bind(&group_names_json)
+        .bind(&req.ztp_wrapper)
         .bind(now)
         .bind(now)
         .execute(pool)
@@ -86,8 +83,9 @@ impl VendorRepo {
 
         let result = sqlx::query(
             r#"
-            UPDATE vendors SET name = ?, backup_command = ?, deploy_command = ?, diff_command = ?, ssh_port = ?, ssh_user = ?, ssh_pass = ?,
-                              mac_prefixes = ?, vendor_class = ?, default_template = ?, group_names = ?, updated_at = ?
+            UPDATE vendors SET name = ?, backup_command = ?, deploy_command = ?, diff_command = ?, commit_confirm_command = ?,
+                              confirm_command = ?, transport = ?, ssh_port = ?, ssh_user = ?, ssh_pass = ?,
+                              mac_prefixes = ?, vendor_class = ?, default_template = ?, group_names = ?, ztp_wrapper = ?, updated_at = ?
             WHERE id = ?
             "#,
         )
@@ -95,6 +93,9 @@ impl VendorRepo {
         .bind(&req.backup_command)
         .bind(&req.deploy_command)
         .bind(&req.diff_command)
+        .bind(&req.commit_confirm_command)
+        .bind(&req.confirm_command)
+        .bind(&req.transport)
         .bind(req.ssh_port)
         .bind(&req.ssh_user)
         .bind(&req.ssh_pass)
@@ -102,6 +103,7 @@ impl VendorRepo {
         .bind(&req.vendor_class)
         .bind(&req.default_template)
         .bind(&group_names_json)
+        .bind(&req.ztp_wrapper)
         .bind(now)
         .bind(id)
         .execute(pool)
@@ -1,7 +1,6 @@
 use anyhow::Result;
 use std::path::Path;
 use std::sync::Arc;
-use tera::{Context, Tera};
 use tokio::fs;
 use tokio::process::Command;
 use tokio::sync::Mutex;
@@ -20,6 +19,10 @@ pub struct ConfigManager {
     dnsmasq_pid_file: String,
     dhcp_interface: String,
     lease_path: String,
+    /// TFTP is served by the built-in server rather than dnsmasq
+    builtin_tftp: bool,
+    /// Signs the per-device HTTP ZTP URLs `${ztp_path}` expands to in bootfile options
+    ztp_secret: String,
     /// Serializes config generation to prevent concurrent writes / dnsmasq restarts
     generate_lock: Arc<Mutex<()>>,
 }
@@ -42,10 +45,23 @@ impl ConfigManager {
             dnsmasq_pid_file: pid_file,
             dhcp_interface,
             lease_path,
+            builtin_tftp: false,
+            ztp_secret: String::new(),
             generate_lock: Arc::new(Mutex::new(())),
         }
     }
 
+    /// Leave TFTP out of the dnsmasq config, for when the built-in server serves it
+    pub fn with_builtin_tftp(mut self, enabled: bool) -> Self {
+        self.builtin_tftp = enabled;
+        self
+    }
+
+    pub fn with_ztp_secret(mut self, secret: String) -> Self {
+        self.ztp_secret = secret;
+        self
+    }
+
     /// Generate all configuration files.
     /// Acquires a mutex to prevent concurrent generation races.
     pub async fn generate_config(&self) -> Result<()> {
@@ -110,19 +126,20 @@ bind-interfaces
 dhcp-range={},{},{},12h
 dhcp-option=option:router,{}
 
-# TFTP Settings
-enable-tftp
-tftp-root={}
-
 "#,
             self.dhcp_interface,
             settings.dhcp_range_start,
             settings.dhcp_range_end,
             settings.dhcp_subnet,
             settings.dhcp_gateway,
-            self.tftp_dir,
         ));
 
+        if self.builtin_tftp {
+            config.push_str("# TFTP is served by ForgeConfig\n\n");
+        } else {
+            config.push_str(&format!("# TFTP Settings\nenable-tftp\ntftp-root={}\n\n", self.tftp_dir));
+        }
+
         // Global DHCP Options
         config.push_str("# Global DHCP Options\n");
         for opt in &global_options {
@@ -145,6 +162,7 @@ tftp-root={}
         // Per-Device DHCP Options (vendor-specific, with per-device bootfile override)
         config.push_str("# Per-Device DHCP Options (vendor-specific)\n");
         config.push_str("# Option 67 (bootfile) is overridden per-device to point to the device's config file\n");
+        config.push_str("# (or its HTTP ZTP URL, where the option value uses ${ztp_path})\n");
         for device in devices {
             if let Some(vendor) = &device.vendor {
                 // Look up vendor options by vendor ID (try direct match first, then name-based)
@@ -155,7 +173,14 @@ tftp-root={}
                     for opt in opts {
                         if opt.enabled {
                             let mac_tag = mac_str.replace(':', "_");
-                            if opt.option_number == 67 {
+                            if opt.option_number == 67 && opt.value.contains("${ztp_path}") {
+                                let ztp_path = crate::services::ztp::config_path(&self.ztp_secret, mac_str);
+                                config.push_str(&format!(
+                                    "dhcp-option=tag:{},67,{}\n",
+                                    mac_tag,
+                                    opt.value.replace("${ztp_path}", &ztp_path)
+                                ));
+                            } else if opt.option_number == 67 {
                                 // Override bootfile with per-device config filename
                                 config.push_str(&format!(
                                     "dhcp-option=tag:{},67,{}\n",
@@ -229,6 +254,14 @@ log-queries
             }
         }
 
+        // Reverse DNS for IPAM addresses (global VRF only; dnsmasq serves a single namespace)
+        if settings.dns_ptr_records {
+            let ips = self.store.list_ipam_ip_addresses_by_vrf(0).await?;
+            let records = super::reverse_dns::ptr_records(&ips);
+            config.push_str("\n# IPAM reverse DNS\n");
+            config.push_str(&super::reverse_dns::dnsmasq_ptr_lines(&records));
+        }
+
         fs::write(&self.config_path, config).await?;
         tracing::info!("Generated dnsmasq config: {}", self.config_path);
 
@@ -254,11 +287,45 @@ log-queries
         Ok(())
     }
 
+    /// Re-render and write config files for the given devices only, without regenerating
+    /// the dnsmasq config. Returns the per-device outcome.
+    pub async fn export_device_configs(&self, devices: &[Device]) -> Result<Vec<TftpExportResult>> {
+        let _guard = self.generate_lock.lock().await;
+        let settings = self.store.get_settings().await?;
+        fs::create_dir_all(&self.tftp_dir).await?;
+
+        let mut results = Vec::with_capacity(devices.len());
+        for device in devices {
+            let outcome = if device.mac.as_deref().unwrap_or("").is_empty() {
+                Err(anyhow::anyhow!("Device has no MAC address; TFTP config files are named by MAC"))
+            } else {
+                self.generate_single_device_config(device, &settings).await
+            };
+            results.push(match outcome {
+                Ok(filename) => TftpExportResult {
+                    device_id: device.id,
+                    hostname: device.hostname.clone(),
+                    filename: Some(filename),
+                    error: None,
+                },
+                Err(e) => TftpExportResult {
+                    device_id: device.id,
+                    hostname: device.hostname.clone(),
+                    filename: None,
+                    error: Some(e.to_string()),
+                },
+            });
+        }
+
+        Ok(results)
+    }
+
+    /// Render a device's config and write it to the TFTP directory, returning the filename
     async fn generate_single_device_config(
         &self,
         device: &Device,
         settings: &Settings,
-    ) -> Result<()> {
+    ) -> Result<String> {
         // Resolve template ID: explicit config_template, or vendor's default_template
         let resolved_template_id = if !device.config_template.is_empty() {
             Some(device.config_template.clone())
@@ -275,87 +342,50 @@ log-queries
             None
         };
 
-        // Determine which template to use
-        let template_content = if let Some(ref tid) = resolved_template_id {
-            // Try to load from database
-            let template_opt = if let Ok(template_id) = tid.parse::<i64>() {
-                self.store.get_template(template_id).await.ok().flatten()
-            } else {
-                None
-            };
-            if let Some(template) = template_opt {
-                template.content
-            } else {
-                // Fallback to file-based template
-                let custom_path = Path::new(&self.templates_dir).join(tid);
-                if let Ok(content) = fs::read_to_string(&custom_path).await {
-                    content
-                } else {
-                    DEFAULT_DEVICE_TEMPLATE.to_string()
-                }
-            }
+        // Determine which template to use; only database templates have role layers
+        let template_opt = match resolved_template_id.as_deref().map(str::parse::<i64>) {
+            Some(Ok(template_id)) => self.store.get_template(template_id).await.ok().flatten(),
+            _ => None,
+        };
+        let (template, role_templates) = if let Some(template) = template_opt {
+            let role_templates = self
+                .store
+                .resolve_role_templates(&template, device.vendor.as_deref(), device.topology_role.as_deref())
+                .await?;
+            (template, role_templates)
         } else {
-            DEFAULT_DEVICE_TEMPLATE.to_string()
+            // Fallback to file-based template, then the built-in default
+            let content = match resolved_template_id {
+                Some(ref tid) => fs::read_to_string(Path::new(&self.templates_dir).join(tid))
+                    .await
+                    .unwrap_or_else(|_| DEFAULT_DEVICE_TEMPLATE.to_string()),
+                None => DEFAULT_DEVICE_TEMPLATE.to_string(),
+            };
+            let now = chrono::Utc::now();
+            let template = Template {
+                id: 0,
+                name: resolved_template_id.unwrap_or_else(|| "default".to_string()),
+                description: None,
+                vendor_id: None,
+                content,
+                device_count: None,
+                created_at: now,
+                updated_at: now,
+            };
+            (template, Vec::new())
         };
 
-        // Convert Go template syntax to Tera syntax (auto-detect)
-        let tera_content = crate::utils::convert_go_template_to_tera(&template_content);
-
-        // Create Tera instance
-        let mut tera = Tera::default();
-        tera.add_raw_template("device", &tera_content)?;
-
-        // Build context
-        let mut context = Context::new();
-        context.insert("Hostname", &device.hostname);
-        context.insert("MAC", &device.mac.as_deref().unwrap_or(""));
-        context.insert("IP", &device.ip);
-        context.insert("Vendor", &device.vendor.clone().unwrap_or_default());
-        context.insert("Model", &device.model.clone().unwrap_or_default());
-        context.insert("SerialNumber", &device.serial_number.clone().unwrap_or_default());
-        context.insert("TopologyId", &device.topology_id.clone().unwrap_or_default());
-        context.insert("TopologyRole", &device.topology_role.clone().unwrap_or_default());
-        context.insert("Subnet", &settings.dhcp_subnet);
-        context.insert("Gateway", &settings.dhcp_gateway);
-
-        // Load resolved variables (group + host inheritance) for template rendering
+        // Load resolved variables (group + host inheritance) and port assignments for VRF context
         let vars = self
             .store
             .resolve_device_variables_flat(device.id)
             .await
             .unwrap_or_default();
-        context.insert("vars", &vars);
-
-        // Build VRF context from port assignments
         let port_assignments = self.store.list_port_assignments(device.id).await.unwrap_or_default();
-        let mut vrf_map: std::collections::HashMap<String, serde_json::Value> = std::collections::HashMap::new();
-        for pa in &port_assignments {
-            if let Some(vrf_id) = pa.vrf_id {
-                let vrf_key = vrf_id.to_string();
-                let iface = serde_json::json!({
-                    "port_name": pa.port_name,
-                    "remote_device": pa.remote_device_hostname.clone().unwrap_or_default(),
-                    "remote_port": pa.remote_port_name,
-                    "description": pa.description.clone().unwrap_or_default(),
-                });
-                if let Some(existing) = vrf_map.get_mut(&vrf_key) {
-                    if let Some(arr) = existing.get_mut("interfaces").and_then(|v| v.as_array_mut()) {
-                        arr.push(iface);
-                    }
-                } else {
-                    vrf_map.insert(vrf_key.clone(), serde_json::json!({
-                        "id": vrf_id,
-                        "name": pa.vrf_name.clone().unwrap_or(vrf_key),
-                        "interfaces": [iface],
-                    }));
-                }
-            }
-        }
-        let vrfs: Vec<serde_json::Value> = vrf_map.into_values().collect();
-        context.insert("VRFs", &vrfs);
 
-        // Render template
-        let config = tera.render("device", &context)?;
+        // Render template, then wrap with the device's pre/post snippets
+        let config = crate::jobs::render_config(device, &template, settings, &role_templates, &vars, Some(&port_assignments))?;
+        let config = self.store.apply_device_config_snippets(device.id, config).await?;
 
         // Generate filename and write
         let filename = format!("{}.cfg", device.mac.as_deref().unwrap_or("").replace(':', "_"));
@@ -364,7 +394,7 @@ log-queries
 
         tracing::debug!("Generated device config: {}", config_path.display());
 
-        Ok(())
+        Ok(filename)
     }
 
     async fn reload_dnsmasq(&self) -> Result<()> {
@@ -424,6 +454,23 @@ log-queries
         Ok(())
     }
 
+    /// Release a DHCP lease via dnsmasq's `dhcp_release`, so the client has to DISCOVER again
+    pub async fn release_lease(&self, ip: &str, mac: &str) -> Result<()> {
+        let output = Command::new("dhcp_release")
+            .args([self.dhcp_interface.as_str(), ip, mac])
+            .output()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to run dhcp_release: {}", e))?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "dhcp_release failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        tracing::info!("Released DHCP lease {} for {}", ip, mac);
+        Ok(())
+    }
+
     /// Get the path to a device's config file
     pub fn get_config_path(&self, mac: &str) -> String {
         let filename = format!("{}.cfg", mac.replace(':', "_"));
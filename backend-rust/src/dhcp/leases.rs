@@ -72,6 +72,11 @@ impl LeaseWatcher {
         }
     }
 
+    /// Forget a single MAC so its next lease is reported even if the expiry didn't move
+    pub async fn forget_mac(&self, mac: &str) {
+        self.known_macs.write().await.remove(mac);
+    }
+
     /// Clear known MACs and re-check leases
     pub async fn clear_known_macs(&self) {
         let mut macs = self.known_macs.write().await;
@@ -155,5 +160,6 @@ fn parse_lease_line(line: &str) -> Option<Lease> {
         circuit_id: None,
         remote_id: None,
         subscriber_id: None,
+        matched_device_id: None,
     })
 }
@@ -1,5 +1,6 @@
 pub mod config;
 pub mod leases;
+pub mod reverse_dns;
 
 pub use config::ConfigManager;
 pub use leases::{parse_lease_file, LeaseWatcher};
@@ -0,0 +1,100 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::models::{IpamIpAddress, ReverseDnsFile};
+use crate::utils::{fqdn, ptr_zone_and_label};
+
+/// PTR records (address, fully qualified name) for addresses with a dns_name, in address order.
+/// When an address appears more than once, the first entry wins.
+pub fn ptr_records(ips: &[IpamIpAddress]) -> Vec<(u32, String)> {
+    let mut records: BTreeMap<u32, String> = BTreeMap::new();
+    for ip in ips {
+        if let Some(name) = ip.dns_name.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+            records.entry(ip.address_int as u32).or_insert_with(|| fqdn(name));
+        }
+    }
+    records.into_iter().collect()
+}
+
+/// Fingerprint of everything the zone files carry, so an unchanged export keeps its serial
+pub fn zone_fingerprint(records: &[(u32, String)], nameserver: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    records.hash(&mut hasher);
+    nameserver.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Next SOA serial in YYYYMMDDnn form: today's first serial, or one past the previous serial
+/// when that is already today's (or later)
+pub fn next_serial(previous: Option<u32>, today: NaiveDate) -> u32 {
+    let first_today = today.year() as u32 * 1_000_000 + today.month() * 10_000 + today.day() * 100 + 1;
+    match previous {
+        Some(prev) if prev >= first_today => prev + 1,
+        _ => first_today,
+    }
+}
+
+/// BIND-style zone files, one per /24 in-addr.arpa zone
+pub fn zone_files(records: &[(u32, String)], nameserver: &str, serial: u32) -> Vec<ReverseDnsFile> {
+    let mut zones: BTreeMap<String, Vec<(String, &str)>> = BTreeMap::new();
+    for (addr, name) in records {
+        let (zone, label) = ptr_zone_and_label(*addr);
+        zones.entry(zone).or_default().push((label, name));
+    }
+
+    let ns = fqdn(nameserver);
+    zones
+        .into_iter()
+        .map(|(zone, entries)| {
+            let mut content = format!(
+                "$ORIGIN {}.\n$TTL 3600\n@\tIN\tSOA\t{} hostmaster.{} ({} 3600 900 604800 3600)\n@\tIN\tNS\t{}\n",
+                zone, ns, ns, serial, ns
+            );
+            for (label, name) in &entries {
+                content.push_str(&format!("{}\tIN\tPTR\t{}\n", label, name));
+            }
+            ReverseDnsFile {
+                name: format!("{}.zone", zone),
+                record_count: entries.len(),
+                content,
+            }
+        })
+        .collect()
+}
+
+/// dnsmasq `ptr-record` lines
+pub fn dnsmasq_ptr_lines(records: &[(u32, String)]) -> String {
+    records
+        .iter()
+        .map(|(addr, name)| {
+            let (zone, label) = ptr_zone_and_label(*addr);
+            format!("ptr-record={}.{},{}\n", label, zone, name.trim_end_matches('.'))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_serial() {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        assert_eq!(next_serial(None, day), 2026101701);
+        assert_eq!(next_serial(Some(2026101602), day), 2026101701);
+        assert_eq!(next_serial(Some(2026101701), day), 2026101702);
+        assert_eq!(next_serial(Some(2026101799), day), 2026101800);
+    }
+
+    #[test]
+    fn test_zone_fingerprint_tracks_records() {
+        let records = vec![(0x0a000001, "a.example.com.".to_string()), (0x0a000002, "b.example.com.".to_string())];
+        let fingerprint = zone_fingerprint(&records, "ns1");
+        assert_eq!(fingerprint, zone_fingerprint(&records.clone(), "ns1"));
+        assert_ne!(fingerprint, zone_fingerprint(&records[..1], "ns1"));
+        assert_ne!(fingerprint, zone_fingerprint(&records, "ns2"));
+    }
+}
@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use futures_util::stream::{self, StreamExt};
+use hyper_util::rt::TokioIo;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio::net::TcpStream;
+use tokio::time::Duration;
+use tonic::codec::{ProstCodec, Streaming};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::MetadataValue;
+use tonic::transport::{Channel, Endpoint, Uri};
+
+use super::proto;
+use crate::models::{telemetry_encoding, telemetry_mode, TelemetrySubscription};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const SUBSCRIBE_PATH: &str = "/gnmi.gNMI/Subscribe";
+
+/// Open a gRPC channel to the device's gNMI server, over TLS unless the subscription disables it
+pub async fn connect(sub: &TelemetrySubscription, ip: &str) -> Result<Channel> {
+    let host = if ip.contains(':') { format!("[{}]", ip) } else { ip.to_string() };
+    // The scheme stays http with TLS: the connector below does the handshake itself
+    let endpoint = Endpoint::from_shared(format!("http://{}:{}", host, sub.port))?
+        .connect_timeout(CONNECT_TIMEOUT)
+        .http2_keep_alive_interval(Duration::from_secs(30))
+        .keep_alive_while_idle(true);
+
+    if !sub.tls {
+        return endpoint.connect().await.context("gNMI connect failed");
+    }
+
+    let connector = tokio_rustls::TlsConnector::from(Arc::new(tls_config(sub)?));
+    let name = if sub.tls_server_name.is_empty() { ip } else { &sub.tls_server_name };
+    let server_name = ServerName::try_from(name.to_string())
+        .map_err(|_| anyhow!("invalid TLS server name '{}'", name))?;
+    let port = sub.port as u16;
+    let ip = ip.to_string();
+
+    endpoint
+        .connect_with_connector(tower::service_fn(move |_: Uri| {
+            let connector = connector.clone();
+            let server_name = server_name.clone();
+            let ip = ip.clone();
+            async move {
+                let tcp = TcpStream::connect((ip.as_str(), port)).await?;
+                let tls = connector.connect(server_name, tcp).await?;
+                Ok::<_, std::io::Error>(TokioIo::new(tls))
+            }
+        }))
+        .await
+        .context("gNMI TLS connect failed")
+}
+
+/// Send the subscription and return the device's notification stream. The request side is
+/// held open, as closing it ends the subscription on most devices.
+pub async fn subscribe(
+    channel: Channel,
+    request: proto::SubscribeRequest,
+    username: &str,
+    password: &str,
+) -> Result<Streaming<proto::SubscribeResponse>> {
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready().await.context("gNMI channel not ready")?;
+
+    let mut req = tonic::Request::new(stream::once(async move { request }).chain(stream::pending()));
+    if !username.is_empty() {
+        req.metadata_mut().insert("username", MetadataValue::try_from(username)?);
+        req.metadata_mut().insert("password", MetadataValue::try_from(password)?);
+    }
+
+    let codec: ProstCodec<proto::SubscribeRequest, proto::SubscribeResponse> = ProstCodec::default();
+    let response = grpc
+        .streaming(req, PathAndQuery::from_static(SUBSCRIBE_PATH), codec)
+        .await
+        .map_err(|status| anyhow!("gNMI subscribe rejected: {}", status.message()))?;
+    Ok(response.into_inner())
+}
+
+/// Build the stream-mode SubscribeRequest for a subscription's paths
+pub fn subscribe_request(sub: &TelemetrySubscription) -> Result<proto::SubscribeRequest> {
+    let mode = match sub.mode.as_str() {
+        telemetry_mode::ON_CHANGE => proto::SubscriptionMode::OnChange,
+        telemetry_mode::TARGET_DEFINED => proto::SubscriptionMode::TargetDefined,
+        _ => proto::SubscriptionMode::Sample,
+    };
+    let encoding = match sub.encoding.as_str() {
+        telemetry_encoding::JSON => proto::Encoding::Json,
+        telemetry_encoding::PROTO => proto::Encoding::Proto,
+        telemetry_encoding::ASCII => proto::Encoding::Ascii,
+        _ => proto::Encoding::JsonIetf,
+    };
+    let sample_interval = if mode == proto::SubscriptionMode::Sample {
+        sub.sample_interval_secs as u64 * 1_000_000_000
+    } else {
+        0
+    };
+
+    let subscription = sub
+        .paths
+        .iter()
+        .map(|path| {
+            let path = super::parse_path(path).ok_or_else(|| anyhow!("invalid gNMI path '{}'", path))?;
+            Ok(proto::Subscription {
+                path: Some(path),
+                mode: mode as i32,
+                sample_interval,
+                ..Default::default()
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(proto::SubscribeRequest {
+        request: Some(proto::subscribe_request::Request::Subscribe(proto::SubscriptionList {
+            prefix: None,
+            subscription,
+            mode: proto::subscription_list::Mode::Stream as i32,
+            encoding: encoding as i32,
+            updates_only: false,
+        })),
+    })
+}
+
+fn tls_config(sub: &TelemetrySubscription) -> Result<ClientConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone()).with_safe_default_protocol_versions()?;
+
+    let mut config = if sub.tls_skip_verify {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(SkipServerVerification(provider)))
+            .with_no_client_auth()
+    } else {
+        let mut roots = RootCertStore::empty();
+        if sub.tls_ca_cert.trim().is_empty() {
+            roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        } else {
+            for cert in rustls_pemfile::certs(&mut sub.tls_ca_cert.as_bytes()) {
+                roots.add(cert.context("invalid tls_ca_cert")?)?;
+            }
+        }
+        builder.with_root_certificates(roots).with_no_client_auth()
+    };
+    config.alpn_protocols = vec![b"h2".to_vec()];
+    Ok(config)
+}
+
+/// Accepts any server certificate, for devices with self-signed certificates. Handshake
+/// signatures are still checked.
+#[derive(Debug)]
+struct SkipServerVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
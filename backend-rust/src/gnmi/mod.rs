@@ -0,0 +1,518 @@
+mod client;
+pub mod proto;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock as StdRwLock};
+
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, sleep, Duration, Instant};
+
+use crate::db::Store;
+use crate::models::{Device, TelemetryStream, TelemetrySubscription, TelemetryUpdate};
+use crate::services::maintenance_mode::MaintenanceMode;
+use crate::ws::{Event, EventType, Hub};
+
+/// How often subscriptions are reloaded and streams reconciled with them
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+/// How often samples older than the retention setting are deleted
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+/// Wait before reconnecting a failed stream, doubling up to MAX_BACKOFF
+const MIN_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A stream is keyed by its subscription and device
+type StreamKey = (i64, i64);
+
+struct RunningStream {
+    /// Changes when the subscription or the device's address does, restarting the stream
+    fingerprint: String,
+    status: Arc<StdRwLock<TelemetryStream>>,
+    handle: JoinHandle<()>,
+}
+
+/// GnmiManager keeps a gNMI Subscribe stream open to each device of every enabled telemetry
+/// subscription, stores the updates as samples and broadcasts them over the WebSocket hub
+pub struct GnmiManager {
+    store: Store,
+    ws_hub: Option<Arc<Hub>>,
+    maintenance_mode: Arc<MaintenanceMode>,
+    streams: Mutex<HashMap<StreamKey, RunningStream>>,
+}
+
+impl GnmiManager {
+    pub fn new(store: Store, ws_hub: Option<Arc<Hub>>, maintenance_mode: Arc<MaintenanceMode>) -> Arc<Self> {
+        Arc::new(Self {
+            store,
+            ws_hub,
+            maintenance_mode,
+            streams: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Open the subscriptions' streams and start the refresh task
+    pub async fn start(self: &Arc<Self>) {
+        self.reload().await;
+
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = interval(REFRESH_INTERVAL);
+            ticker.tick().await;
+            let mut last_prune = Instant::now();
+            loop {
+                ticker.tick().await;
+                manager.reload().await;
+                if last_prune.elapsed() >= PRUNE_INTERVAL {
+                    manager.prune().await;
+                    last_prune = Instant::now();
+                }
+            }
+        });
+    }
+
+    /// Reconcile the running streams with the subscriptions: start new ones, restart changed
+    /// ones and stop the rest. All streams stop during maintenance mode.
+    pub async fn reload(self: &Arc<Self>) {
+        let desired = if self.maintenance_mode.is_enabled() {
+            HashMap::new()
+        } else {
+            match self.desired_streams().await {
+                Ok(desired) => desired,
+                Err(e) => {
+                    tracing::warn!("Failed to load telemetry subscriptions: {}", e);
+                    return;
+                }
+            }
+        };
+
+        let mut streams = self.streams.lock().await;
+        streams.retain(|key, running| {
+            let keep = desired.get(key).is_some_and(|(fingerprint, _, _)| *fingerprint == running.fingerprint);
+            if !keep {
+                running.handle.abort();
+            }
+            keep
+        });
+        for (key, (fingerprint, sub, device)) in desired {
+            if streams.contains_key(&key) {
+                continue;
+            }
+            let status = Arc::new(StdRwLock::new(TelemetryStream {
+                subscription_id: sub.id,
+                subscription_name: sub.name.clone(),
+                device_id: device.id,
+                hostname: device.hostname.clone(),
+                ..Default::default()
+            }));
+            let manager = self.clone();
+            let stream_status = status.clone();
+            let handle = tokio::spawn(async move { manager.run_stream(sub, device, stream_status).await });
+            streams.insert(key, RunningStream { fingerprint, status, handle });
+        }
+    }
+
+    /// State of every running stream
+    pub async fn streams(&self) -> Vec<TelemetryStream> {
+        let streams = self.streams.lock().await;
+        let mut list: Vec<TelemetryStream> = streams
+            .values()
+            .map(|running| running.status.read().unwrap().clone())
+            .collect();
+        list.sort_by(|a, b| (&a.subscription_name, &a.hostname).cmp(&(&b.subscription_name, &b.hostname)));
+        list
+    }
+
+    async fn desired_streams(&self) -> Result<HashMap<StreamKey, (String, TelemetrySubscription, Device)>> {
+        let subscriptions = self.store.list_telemetry_subscriptions().await?;
+        if !subscriptions.iter().any(|sub| sub.enabled) {
+            return Ok(HashMap::new());
+        }
+        let devices = self.store.list_devices().await?;
+
+        let mut desired = HashMap::new();
+        for sub in subscriptions.into_iter().filter(|sub| sub.enabled) {
+            let vendor_id = sub.vendor_id.map(|id| id.to_string());
+            let targets = devices.iter().filter(|device| {
+                !device.ip.is_empty()
+                    && match sub.device_id {
+                        Some(id) => device.id == id,
+                        None => device.vendor_id.is_some() && device.vendor_id == vendor_id,
+                    }
+            });
+            for device in targets {
+                let fingerprint = format!("{}|{}", sub.updated_at.timestamp_micros(), device.ip);
+                desired.insert((sub.id, device.id), (fingerprint, sub.clone(), device.clone()));
+            }
+        }
+        Ok(desired)
+    }
+
+    async fn prune(&self) {
+        let hours = match self.store.get_settings().await {
+            Ok(settings) => settings.telemetry_retention_hours,
+            Err(e) => {
+                tracing::warn!("Failed to load settings for telemetry pruning: {}", e);
+                return;
+            }
+        };
+        if hours <= 0 {
+            return;
+        }
+        match self.store.prune_telemetry_samples(Utc::now() - chrono::Duration::hours(hours as i64)).await {
+            Ok(count) if count > 0 => tracing::debug!("Pruned {} old telemetry samples", count),
+            Err(e) => tracing::warn!("Telemetry sample pruning failed: {}", e),
+            _ => {}
+        }
+    }
+
+    /// Keep one device's stream open, reconnecting with backoff until the task is aborted
+    async fn run_stream(self: Arc<Self>, sub: TelemetrySubscription, device: Device, status: Arc<StdRwLock<TelemetryStream>>) {
+        let mut backoff = MIN_BACKOFF;
+        loop {
+            let error = match self.subscribe(&sub, &device, &status).await {
+                Ok(()) => "stream closed by device".to_string(),
+                Err(e) => format!("{:#}", e),
+            };
+            let was_connected = {
+                let mut status = status.write().unwrap();
+                let was_connected = status.connected;
+                status.connected = false;
+                status.last_error = Some(error.clone());
+                was_connected
+            };
+            if was_connected {
+                backoff = MIN_BACKOFF;
+            }
+            tracing::debug!(
+                "Telemetry subscription '{}' on {}: {}; retrying in {}s",
+                sub.name,
+                device.hostname,
+                error,
+                backoff.as_secs()
+            );
+            sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    async fn subscribe(&self, sub: &TelemetrySubscription, device: &Device, status: &StdRwLock<TelemetryStream>) -> Result<()> {
+        let request = client::subscribe_request(sub)?;
+        let (username, password) = self.credentials(sub, device).await;
+        let channel = client::connect(sub, &device.ip).await?;
+        let mut stream = client::subscribe(channel, request, &username, &password).await?;
+        {
+            let mut status = status.write().unwrap();
+            status.connected = true;
+            status.last_error = None;
+        }
+        tracing::info!("Telemetry subscription '{}' streaming from {}", sub.name, device.hostname);
+
+        while let Some(response) = stream.message().await? {
+            if let Some(proto::subscribe_response::Response::Update(notification)) = response.response {
+                self.handle_notification(sub, device, status, &notification).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// The subscription's credential, else the device's SSH credentials
+    async fn credentials(&self, sub: &TelemetrySubscription, device: &Device) -> (String, String) {
+        if let Some(id) = sub.credential_id {
+            if let Ok(Some(credential)) = self.store.get_credential(id).await {
+                return (credential.username, credential.password);
+            }
+        }
+        let creds = crate::utils::resolve_ssh_credentials(&self.store, device).await;
+        (creds.user, creds.pass)
+    }
+
+    async fn handle_notification(
+        &self,
+        sub: &TelemetrySubscription,
+        device: &Device,
+        status: &StdRwLock<TelemetryStream>,
+        notification: &proto::Notification,
+    ) {
+        let updates = notification_updates(notification);
+        if updates.is_empty() {
+            return;
+        }
+        let timestamp = match notification.timestamp {
+            0 => Utc::now(),
+            nanos => DateTime::from_timestamp_nanos(nanos),
+        };
+        {
+            let mut status = status.write().unwrap();
+            status.updates += 1;
+            status.last_update = Some(timestamp);
+        }
+
+        if sub.store_samples {
+            if let Err(e) = self.store.insert_telemetry_samples(sub.id, device.id, timestamp, &updates).await {
+                tracing::warn!("Failed to store telemetry from {}: {}", device.hostname, e);
+            }
+        }
+        if let Some(hub) = &self.ws_hub {
+            hub.broadcast_event(Event {
+                event_type: EventType::TelemetryUpdate,
+                payload: serde_json::json!({
+                    "subscription_id": sub.id,
+                    "device_id": device.id,
+                    "hostname": device.hostname,
+                    "timestamp": timestamp,
+                    "updates": updates,
+                }),
+            })
+            .await;
+        }
+    }
+}
+
+/// Parse a gNMI path string, e.g. `openconfig:/interfaces/interface[name=Ethernet1/1]/state`.
+/// Key values may contain `/`; a `]` or `\` in a key value is escaped with `\`.
+pub fn parse_path(path: &str) -> Option<proto::Path> {
+    let path = path.trim();
+    let (origin, rest) = match path.find(":/") {
+        Some(i) if !path[..i].is_empty() && !path[..i].contains(['/', '[']) => (&path[..i], &path[i + 1..]),
+        _ => ("", path),
+    };
+    let rest = rest.strip_prefix('/')?;
+
+    let mut elem = Vec::new();
+    let mut chars = rest.chars().peekable();
+    while chars.peek().is_some() {
+        let mut name = String::new();
+        let mut key = BTreeMap::new();
+        while let Some(&c) = chars.peek() {
+            match c {
+                '/' => break,
+                '[' => {
+                    chars.next();
+                    let mut k = String::new();
+                    loop {
+                        match chars.next()? {
+                            '=' => break,
+                            '[' | ']' | '/' => return None,
+                            c => k.push(c),
+                        }
+                    }
+                    let mut v = String::new();
+                    loop {
+                        match chars.next()? {
+                            '\\' => v.push(chars.next()?),
+                            ']' => break,
+                            c => v.push(c),
+                        }
+                    }
+                    if k.is_empty() || key.insert(k, v).is_some() {
+                        return None;
+                    }
+                }
+                ']' => return None,
+                _ if !key.is_empty() => return None,
+                c => {
+                    chars.next();
+                    name.push(c);
+                }
+            }
+        }
+        if name.is_empty() {
+            return None;
+        }
+        elem.push(proto::PathElem { name, key });
+        chars.next();
+    }
+
+    Some(proto::Path { origin: origin.to_string(), elem, target: String::new() })
+}
+
+/// Render a prefix and path as one path string, without the origin
+pub fn path_string(prefix: Option<&proto::Path>, path: Option<&proto::Path>) -> String {
+    let mut out = String::new();
+    for elem in prefix.into_iter().chain(path).flat_map(|p| &p.elem) {
+        out.push('/');
+        out.push_str(&elem.name);
+        for (k, v) in &elem.key {
+            out.push_str(&format!("[{}={}]", k, v.replace('\\', "\\\\").replace(']', "\\]")));
+        }
+    }
+    if out.is_empty() {
+        out.push('/');
+    }
+    out
+}
+
+/// Convert a gNMI typed value to JSON. JSON-encoded values are parsed; bytes become base64.
+pub fn typed_value_to_json(value: &proto::TypedValue) -> serde_json::Value {
+    use proto::typed_value::Value;
+    use serde_json::Value as Json;
+
+    let float = |f: f64| serde_json::Number::from_f64(f).map(Json::Number).unwrap_or(Json::Null);
+    match &value.value {
+        None => Json::Null,
+        Some(Value::StringVal(s)) | Some(Value::AsciiVal(s)) => Json::String(s.clone()),
+        Some(Value::IntVal(i)) => Json::from(*i),
+        Some(Value::UintVal(u)) => Json::from(*u),
+        Some(Value::BoolVal(b)) => Json::Bool(*b),
+        Some(Value::BytesVal(b)) | Some(Value::ProtoBytes(b)) => Json::String(STANDARD.encode(b)),
+        Some(Value::FloatVal(f)) => float(*f as f64),
+        Some(Value::DoubleVal(f)) => float(*f),
+        Some(Value::DecimalVal(d)) => float(d.digits as f64 / 10f64.powi(d.precision as i32)),
+        Some(Value::LeaflistVal(list)) => Json::Array(list.element.iter().map(typed_value_to_json).collect()),
+        Some(Value::JsonVal(b)) | Some(Value::JsonIetfVal(b)) => serde_json::from_slice(b)
+            .unwrap_or_else(|_| Json::String(String::from_utf8_lossy(b).into_owned())),
+    }
+}
+
+/// Flatten a value into leaf updates under `path`. Containers become child paths with their
+/// YANG module prefixes dropped, and lists whose entries have a `name` become keyed elements.
+pub fn flatten_value(path: &str, value: serde_json::Value, out: &mut Vec<TelemetryUpdate>) {
+    use serde_json::Value as Json;
+
+    let child = |name: &str| {
+        let name = name.rsplit_once(':').map_or(name, |(_, local)| local);
+        if path.ends_with('/') { format!("{}{}", path, name) } else { format!("{}/{}", path, name) }
+    };
+    match value {
+        Json::Object(map) => {
+            for (name, value) in map {
+                flatten_value(&child(&name), value, out);
+            }
+        }
+        Json::Array(list) if !list.is_empty() && list.iter().all(|entry| entry.get("name").is_some_and(|n| n.is_string())) => {
+            for entry in list {
+                let name = entry["name"].as_str().unwrap_or_default().replace('\\', "\\\\").replace(']', "\\]");
+                let entry_path = format!("{}[name={}]", path, name);
+                flatten_value(&entry_path, entry, out);
+            }
+        }
+        value => out.push(TelemetryUpdate { path: path.to_string(), value }),
+    }
+}
+
+/// A notification's updates as leaf path/value pairs
+pub fn notification_updates(notification: &proto::Notification) -> Vec<TelemetryUpdate> {
+    let mut out = Vec::new();
+    for update in &notification.update {
+        let path = path_string(notification.prefix.as_ref(), update.path.as_ref());
+        let value = update.val.as_ref().map(typed_value_to_json).unwrap_or(serde_json::Value::Null);
+        flatten_value(&path, value, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto::typed_value::Value;
+
+    fn typed(value: Value) -> proto::TypedValue {
+        proto::TypedValue { value: Some(value) }
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let path = parse_path("/interfaces/interface[name=Ethernet1/1]/state/counters").unwrap();
+        assert_eq!(path.origin, "");
+        assert_eq!(path.elem.len(), 4);
+        assert_eq!(path.elem[1].name, "interface");
+        assert_eq!(path.elem[1].key.get("name").map(String::as_str), Some("Ethernet1/1"));
+        assert_eq!(path_string(None, Some(&path)), "/interfaces/interface[name=Ethernet1/1]/state/counters");
+
+        let path = parse_path("openconfig:/network-instances/network-instance[name=default][type=L3VRF]/").unwrap();
+        assert_eq!(path.origin, "openconfig");
+        assert_eq!(path.elem.len(), 2);
+        assert_eq!(path.elem[1].key.len(), 2);
+        assert_eq!(path_string(None, Some(&path)), "/network-instances/network-instance[name=default][type=L3VRF]");
+
+        let path = parse_path(r"/a[k=x\]y]").unwrap();
+        assert_eq!(path.elem[0].key["k"], "x]y");
+        assert_eq!(path_string(None, Some(&path)), r"/a[k=x\]y]");
+
+        assert_eq!(parse_path("/").unwrap().elem.len(), 0);
+        for invalid in ["", "interfaces", "//a", "/a//b", "/a[k]", "/a[=v]", "/a[k=v", "/a[k=v]b", "/a]", "/a[k=1][k=2]", "/a//"] {
+            assert!(parse_path(invalid).is_none(), "{} should not parse", invalid);
+        }
+    }
+
+    #[test]
+    fn test_typed_value_to_json() {
+        use serde_json::json;
+
+        assert_eq!(typed_value_to_json(&typed(Value::UintVal(42))), json!(42));
+        assert_eq!(typed_value_to_json(&typed(Value::IntVal(-1))), json!(-1));
+        assert_eq!(typed_value_to_json(&typed(Value::StringVal("UP".into()))), json!("UP"));
+        assert_eq!(typed_value_to_json(&typed(Value::DoubleVal(1.5))), json!(1.5));
+        assert_eq!(typed_value_to_json(&typed(Value::DoubleVal(f64::NAN))), json!(null));
+        assert_eq!(
+            typed_value_to_json(&typed(Value::DecimalVal(proto::Decimal64 { digits: 1234, precision: 2 }))),
+            json!(12.34)
+        );
+        assert_eq!(typed_value_to_json(&typed(Value::BytesVal(vec![1, 2, 3]))), json!("AQID"));
+        assert_eq!(
+            typed_value_to_json(&typed(Value::JsonIetfVal(br#"{"openconfig-interfaces:in-octets": "5"}"#.to_vec()))),
+            json!({"openconfig-interfaces:in-octets": "5"})
+        );
+        assert_eq!(
+            typed_value_to_json(&typed(Value::LeaflistVal(proto::ScalarArray {
+                element: vec![typed(Value::UintVal(1)), typed(Value::BoolVal(true))],
+            }))),
+            json!([1, true])
+        );
+    }
+
+    #[test]
+    fn test_notification_updates() {
+        use serde_json::json;
+
+        let notification = proto::Notification {
+            timestamp: 1,
+            prefix: parse_path("/interfaces/interface[name=Ethernet1]"),
+            update: vec![
+                proto::Update {
+                    path: parse_path("/state/oper-status"),
+                    val: Some(typed(Value::StringVal("UP".into()))),
+                    duplicates: 0,
+                },
+                proto::Update {
+                    path: parse_path("/state/counters"),
+                    val: Some(typed(Value::JsonIetfVal(
+                        br#"{"openconfig-interfaces:in-octets": 5, "out-octets": 7}"#.to_vec(),
+                    ))),
+                    duplicates: 0,
+                },
+            ],
+            ..Default::default()
+        };
+        let updates: Vec<(String, serde_json::Value)> =
+            notification_updates(&notification).into_iter().map(|u| (u.path, u.value)).collect();
+        assert_eq!(
+            updates,
+            vec![
+                ("/interfaces/interface[name=Ethernet1]/state/oper-status".to_string(), json!("UP")),
+                ("/interfaces/interface[name=Ethernet1]/state/counters/in-octets".to_string(), json!(5)),
+                ("/interfaces/interface[name=Ethernet1]/state/counters/out-octets".to_string(), json!(7)),
+            ]
+        );
+
+        let mut out = Vec::new();
+        flatten_value(
+            "/interfaces",
+            json!({"interface": [{"name": "Ethernet1", "state": {"mtu": 9214}}], "tags": ["a", "b"]}),
+            &mut out,
+        );
+        let out: Vec<(String, serde_json::Value)> = out.into_iter().map(|u| (u.path, u.value)).collect();
+        assert_eq!(
+            out,
+            vec![
+                ("/interfaces/interface[name=Ethernet1]/name".to_string(), json!("Ethernet1")),
+                ("/interfaces/interface[name=Ethernet1]/state/mtu".to_string(), json!(9214)),
+                ("/interfaces/tags".to_string(), json!(["a", "b"])),
+            ]
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! The subset of the gNMI protobuf messages (gnmi.proto, v0.10) used to subscribe.
+//! Field tags must match the upstream definitions; fields not listed are skipped on decode.
+
+/// Path is a gNMI path, e.g. /interfaces/interface[name=Ethernet1]/state
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Path {
+    #[prost(string, tag = "2")]
+    pub origin: String,
+    #[prost(message, repeated, tag = "3")]
+    pub elem: Vec<PathElem>,
+    #[prost(string, tag = "4")]
+    pub target: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PathElem {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(btree_map = "string, string", tag = "2")]
+    pub key: ::std::collections::BTreeMap<String, String>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TypedValue {
+    #[prost(oneof = "typed_value::Value", tags = "1, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14")]
+    pub value: Option<typed_value::Value>,
+}
+
+pub mod typed_value {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Value {
+        #[prost(string, tag = "1")]
+        StringVal(String),
+        #[prost(int64, tag = "2")]
+        IntVal(i64),
+        #[prost(uint64, tag = "3")]
+        UintVal(u64),
+        #[prost(bool, tag = "4")]
+        BoolVal(bool),
+        #[prost(bytes, tag = "5")]
+        BytesVal(Vec<u8>),
+        #[prost(float, tag = "6")]
+        FloatVal(f32),
+        #[prost(message, tag = "7")]
+        DecimalVal(super::Decimal64),
+        #[prost(message, tag = "8")]
+        LeaflistVal(super::ScalarArray),
+        #[prost(bytes, tag = "10")]
+        JsonVal(Vec<u8>),
+        #[prost(bytes, tag = "11")]
+        JsonIetfVal(Vec<u8>),
+        #[prost(string, tag = "12")]
+        AsciiVal(String),
+        #[prost(bytes, tag = "13")]
+        ProtoBytes(Vec<u8>),
+        #[prost(double, tag = "14")]
+        DoubleVal(f64),
+    }
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Decimal64 {
+    #[prost(int64, tag = "1")]
+    pub digits: i64,
+    #[prost(uint32, tag = "2")]
+    pub precision: u32,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScalarArray {
+    #[prost(message, repeated, tag = "1")]
+    pub element: Vec<TypedValue>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Update {
+    #[prost(message, optional, tag = "1")]
+    pub path: Option<Path>,
+    #[prost(message, optional, tag = "3")]
+    pub val: Option<TypedValue>,
+    #[prost(uint32, tag = "4")]
+    pub duplicates: u32,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Notification {
+    /// Nanoseconds since the Unix epoch
+    #[prost(int64, tag = "1")]
+    pub timestamp: i64,
+    #[prost(message, optional, tag = "2")]
+    pub prefix: Option<Path>,
+    #[prost(message, repeated, tag = "4")]
+    pub update: Vec<Update>,
+    #[prost(message, repeated, tag = "5")]
+    pub delete: Vec<Path>,
+    #[prost(bool, tag = "6")]
+    pub atomic: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Encoding {
+    Json = 0,
+    Bytes = 1,
+    Proto = 2,
+    Ascii = 3,
+    JsonIetf = 4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SubscriptionMode {
+    TargetDefined = 0,
+    OnChange = 1,
+    Sample = 2,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Subscription {
+    #[prost(message, optional, tag = "1")]
+    pub path: Option<Path>,
+    #[prost(enumeration = "SubscriptionMode", tag = "2")]
+    pub mode: i32,
+    /// Nanoseconds between samples
+    #[prost(uint64, tag = "3")]
+    pub sample_interval: u64,
+    #[prost(bool, tag = "4")]
+    pub suppress_redundant: bool,
+    #[prost(uint64, tag = "5")]
+    pub heartbeat_interval: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscriptionList {
+    #[prost(message, optional, tag = "1")]
+    pub prefix: Option<Path>,
+    #[prost(message, repeated, tag = "2")]
+    pub subscription: Vec<Subscription>,
+    #[prost(enumeration = "subscription_list::Mode", tag = "5")]
+    pub mode: i32,
+    #[prost(enumeration = "Encoding", tag = "8")]
+    pub encoding: i32,
+    #[prost(bool, tag = "9")]
+    pub updates_only: bool,
+}
+
+pub mod subscription_list {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Mode {
+        Stream = 0,
+        Once = 1,
+        Poll = 2,
+    }
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeRequest {
+    #[prost(oneof = "subscribe_request::Request", tags = "1")]
+    pub request: Option<subscribe_request::Request>,
+}
+
+pub mod subscribe_request {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Request {
+        #[prost(message, tag = "1")]
+        Subscribe(super::SubscriptionList),
+    }
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeResponse {
+    #[prost(oneof = "subscribe_response::Response", tags = "1, 3")]
+    pub response: Option<subscribe_response::Response>,
+}
+
+pub mod subscribe_response {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Response {
+        #[prost(message, tag = "1")]
+        Update(super::Notification),
+        /// The device has sent the current value of every subscribed path
+        #[prost(bool, tag = "3")]
+        SyncResponse(bool),
+    }
+}
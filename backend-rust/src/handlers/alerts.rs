@@ -0,0 +1,168 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::alerting;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+const MAX_ALERT_LIMIT: i64 = 1000;
+
+async fn validate_rule(state: &AppState, id: Option<i64>, req: &CreateAlertRuleRequest) -> Result<(), ApiError> {
+    for channel_id in &req.channel_ids {
+        if state.store.get_notification_channel(*channel_id).await?.is_none() {
+            return Err(ApiError::bad_request(format!("notification channel {} not found", channel_id)));
+        }
+    }
+    let rules = state.store.list_alert_rules().await?;
+    if rules.iter().any(|r| r.name == req.name && Some(r.id) != id) {
+        return Err(ApiError::conflict(format!("alert rule '{}' already exists", req.name)));
+    }
+    Ok(())
+}
+
+/// List alerts, newest first. Without `status`, every unresolved one.
+pub async fn list_alerts(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(mut query): Query<AlertQuery>,
+) -> Result<Json<Vec<Alert>>, ApiError> {
+    if let Some(status) = query.status.as_deref() {
+        if !alert_status::ALL.contains(&status) {
+            return Err(ApiError::bad_request(format!("unknown alert status '{}'", status)));
+        }
+    }
+    query.limit = query.limit.clamp(1, MAX_ALERT_LIMIT);
+    Ok(Json(state.store.list_alerts(&query).await?))
+}
+
+/// Get a single alert by ID
+pub async fn get_alert(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Alert>, ApiError> {
+    let alert = state
+        .store
+        .get_alert(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("alert"))?;
+    Ok(Json(alert))
+}
+
+/// Acknowledge a pending or firing alert. It still resolves on its own once the value drops back.
+pub async fn acknowledge_alert(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Alert>, ApiError> {
+    match state.store.acknowledge_alert(id, &auth.claims.username).await? {
+        Some(alert) => Ok(Json(alert)),
+        None => Err(not_open(&state, id, "acknowledged").await),
+    }
+}
+
+/// Assign an alert to a user, or unassign it
+pub async fn assign_alert(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<AssignAlertRequest>,
+) -> Result<Json<Alert>, ApiError> {
+    let assigned_to = req.assigned_to.trim();
+    if !assigned_to.is_empty() && state.store.get_user_by_username(assigned_to).await?.is_none() {
+        return Err(ApiError::bad_request(format!("user '{}' not found", assigned_to)));
+    }
+    Ok(Json(state.store.assign_alert(id, assigned_to).await?))
+}
+
+/// Resolve an alert by hand. If its rule still sees the value over the threshold, the next
+/// evaluation opens a new alert.
+pub async fn resolve_alert(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Alert>, ApiError> {
+    match state.store.resolve_alert(id, &auth.claims.username).await? {
+        Some(alert) => Ok(Json(alert)),
+        None => Err(not_open(&state, id, "resolved").await),
+    }
+}
+
+/// Error for a lifecycle change that didn't apply: the alert is missing or in the wrong state
+async fn not_open(state: &AppState, id: i64, action: &str) -> ApiError {
+    match state.store.get_alert(id).await {
+        Ok(Some(alert)) => ApiError::conflict(format!("alert is {} and can't be {}", alert.status, action)),
+        Ok(None) => ApiError::not_found("alert"),
+        Err(e) => e.into(),
+    }
+}
+
+/// Evaluate the enabled alert rules now, as the scheduler does every five minutes
+pub async fn evaluate_alert_rules(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<AlertEvaluation>, ApiError> {
+    let result = alerting::evaluate(&state.store, &state.config)
+        .await
+        .map_err(|e| ApiError::internal(format!("{:#}", e)))?;
+    Ok(Json(result))
+}
+
+/// List alert rules
+pub async fn list_alert_rules(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<AlertRule>>, ApiError> {
+    Ok(Json(state.store.list_alert_rules().await?))
+}
+
+/// Get a single alert rule by ID
+pub async fn get_alert_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<AlertRule>, ApiError> {
+    let rule = state
+        .store
+        .get_alert_rule(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("alert rule"))?;
+    Ok(Json(rule))
+}
+
+/// Create an alert rule
+pub async fn create_alert_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateAlertRuleRequest>,
+) -> Result<(StatusCode, Json<AlertRule>), ApiError> {
+    validate_rule(&state, None, &req).await?;
+    Ok(created(state.store.create_alert_rule(&req).await?))
+}
+
+/// Update an alert rule
+pub async fn update_alert_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateAlertRuleRequest>,
+) -> Result<Json<AlertRule>, ApiError> {
+    validate_rule(&state, Some(id), &req).await?;
+    Ok(Json(state.store.update_alert_rule(id, &req).await?))
+}
+
+/// Delete an alert rule and its alerts
+pub async fn delete_alert_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_alert_rule(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,77 @@
+//! API usage metering: a middleware counting each authenticated API request against its user and
+//! login token, and the report of those counts.
+
+use axum::{
+    body::HttpBody,
+    extract::{FromRequestParts, Query, Request, State},
+    http::{header, Method},
+    middleware::Next,
+    response::Response,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::auth::AuthUser;
+use crate::models::*;
+use crate::services::api_usage;
+use crate::AppState;
+
+use super::ApiError;
+
+/// Longest report window, matching how long hourly counts are kept
+const MAX_REPORT_HOURS: i64 = 90 * 24;
+
+fn content_length(headers: &axum::http::HeaderMap) -> Option<u64> {
+    headers.get(header::CONTENT_LENGTH)?.to_str().ok()?.parse().ok()
+}
+
+/// Middleware counting every API request made with a valid login: its resource (the first path
+/// segment after `/api/`), read or write, whether it failed, and the request and response body
+/// sizes. Streamed responses of unknown length count the bytes known up front.
+pub async fn usage_meter(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let Some(resource) = crate::utils::api_resource(request.uri().path()).map(str::to_string) else {
+        return next.run(request).await;
+    };
+    let access = if matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        permission_action::READ
+    } else {
+        permission_action::WRITE
+    };
+    let request_bytes = content_length(request.headers()).unwrap_or(0);
+
+    let (mut parts, body) = request.into_parts();
+    let auth = AuthUser::from_request_parts(&mut parts, &state).await;
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    if let Ok(auth) = auth {
+        let response_bytes = content_length(response.headers()).unwrap_or_else(|| {
+            let hint = response.body().size_hint();
+            hint.exact().unwrap_or(hint.lower())
+        });
+        let is_error = response.status().is_client_error() || response.status().is_server_error();
+        api_usage::record(&auth.claims, &resource, access, is_error, request_bytes, response_bytes);
+    }
+    response
+}
+
+/// API usage per user over the last `hours` (default 24), with each user's login tokens and
+/// endpoint classes, busiest first. Counts from the last minute may not be in yet.
+pub async fn api_usage_report(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ApiUsageQuery>,
+) -> Result<Json<ApiUsageReport>, ApiError> {
+    if !(1..=MAX_REPORT_HOURS).contains(&query.hours) {
+        return Err(ApiError::bad_request(format!("hours must be between 1 and {}", MAX_REPORT_HOURS)));
+    }
+    if let Err(e) = api_usage::flush(&state.store).await {
+        tracing::warn!("Failed to record API usage before reporting: {:#}", e);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let current_hour = now - now.rem_euclid(3600);
+    let since = chrono::DateTime::from_timestamp(current_hour - (query.hours - 1) * 3600, 0).unwrap_or_default();
+    let username = query.username.as_deref().filter(|u| !u.is_empty());
+    let consumers = state.store.api_usage_report(since, username).await?;
+    Ok(Json(ApiUsageReport { since, hours: query.hours, consumers }))
+}
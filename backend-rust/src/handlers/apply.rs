@@ -0,0 +1,587 @@
+//! Declarative apply: reconcile devices and groups to a desired-state document, sent as JSON or
+//! YAML. The document is validated and diffed first; the plan is returned as-is for a dry run, or
+//! committed through the regular handlers so validation, team checks and side effects match the
+//! individual endpoints.
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::auth::AuthUser;
+use crate::models::*;
+use crate::utils::{is_valid_hostname, is_valid_ipv4, normalize_mac};
+use crate::AppState;
+
+use super::{ApiError, ValidJson};
+
+/// The "all" group's invariants are enforced by update_group
+const ALL_GROUP_ID: i64 = 1;
+
+struct GroupChange {
+    desired: DesiredGroup,
+    /// Creates the group without a parent, which may not exist yet
+    create: Option<ValidJson<CreateGroupRequest>>,
+    /// Sets the group's fields once every group exists. A parent named in the document is
+    /// resolved to its ID then.
+    update: Option<ValidJson<CreateGroupRequest>>,
+    vars_set: Vec<(String, String)>,
+    vars_delete: Vec<String>,
+}
+
+/// How a device change is written
+enum DeviceWrite {
+    Create(ValidJson<CreateDeviceRequest>),
+    /// An existing device, with the update to its fields when they differ
+    Update { id: i64, fields: Option<ValidJson<UpdateDeviceRequest>> },
+}
+
+struct DeviceChange {
+    hostname: String,
+    write: DeviceWrite,
+    /// Replacement group membership, when it differs
+    groups: Option<Vec<String>>,
+    vars_set: Vec<(String, String)>,
+    vars_delete: Vec<String>,
+}
+
+struct Reconciliation {
+    plan: ApplyPlan,
+    groups: Vec<GroupChange>,
+    devices: Vec<DeviceChange>,
+    /// In the order of the plan's delete names
+    delete_groups: Vec<i64>,
+    delete_devices: Vec<i64>,
+}
+
+/// Variable keys to set and delete to turn `current` into `desired`
+fn diff_vars(
+    current: &HashMap<String, String>,
+    desired: &BTreeMap<String, String>,
+) -> (Vec<(String, String)>, Vec<String>) {
+    let set = desired
+        .iter()
+        .filter(|(k, v)| current.get(*k) != Some(*v))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    let mut delete: Vec<String> = current.keys().filter(|k| !desired.contains_key(*k)).cloned().collect();
+    delete.sort();
+    (set, delete)
+}
+
+/// Run a request's field checks while planning, naming the document entry in any failure, so a
+/// document that the handlers would reject partway through is refused before anything is written
+fn validated<T: Validate>(section: &str, name: &str, req: T) -> Result<ValidJson<T>, ApiError> {
+    let mut validator = Validator::new();
+    req.validate(&mut validator);
+    validator.finish().map_err(|fields| {
+        ApiError::unprocessable(
+            fields
+                .into_iter()
+                .map(|f| FieldError { field: format!("{}.{}.{}", section, name, f.field), message: f.message })
+                .collect(),
+        )
+    })?;
+    Ok(ValidJson(req))
+}
+
+fn var_changes(set: &[(String, String)], delete: &[String]) -> Vec<String> {
+    let mut keys: Vec<&str> = set.iter().map(|(k, _)| k.as_str()).chain(delete.iter().map(String::as_str)).collect();
+    keys.sort_unstable();
+    keys.into_iter().map(|k| format!("vars.{}", k)).collect()
+}
+
+fn differs(current: Option<&str>, desired: Option<&str>) -> bool {
+    desired.is_some_and(|d| current.unwrap_or("") != d)
+}
+
+async fn reconcile_groups(
+    state: &AppState,
+    desired: &[DesiredGroup],
+    prune: bool,
+    plan: &mut ApplyResourcePlan,
+) -> Result<(Vec<GroupChange>, Vec<i64>), ApiError> {
+    let existing = state.store.list_groups().await?;
+    let by_name: HashMap<&str, &Group> = existing.iter().map(|g| (g.name.as_str(), g)).collect();
+    let names_by_id: HashMap<i64, &str> = existing.iter().map(|g| (g.id, g.name.as_str())).collect();
+
+    let mut seen = HashSet::new();
+    for group in desired {
+        if group.name.is_empty() {
+            return Err(ApiError::bad_request("group name is required"));
+        }
+        if !seen.insert(group.name.as_str()) {
+            return Err(ApiError::bad_request(format!("duplicate group '{}'", group.name)));
+        }
+    }
+    for group in desired {
+        let Some(parent) = group.parent.as_deref().filter(|p| !p.is_empty()) else { continue };
+        if parent == group.name {
+            return Err(ApiError::bad_request(format!("group '{}' cannot be its own parent", group.name)));
+        }
+        if !seen.contains(parent) && (prune || !by_name.contains_key(parent)) {
+            return Err(ApiError::bad_request(format!("group '{}': unknown parent '{}'", group.name, parent)));
+        }
+    }
+
+    let mut changes = Vec::new();
+    for group in desired {
+        let current = by_name.get(group.name.as_str()).copied();
+        let Some(current) = current else {
+            plan.create.push(group.name.clone());
+            let req = CreateGroupRequest {
+                name: group.name.clone(),
+                description: group.description.clone(),
+                parent_id: None,
+                precedence: group.precedence.unwrap_or(DEFAULT_GROUP_PRECEDENCE),
+                credential_id: None,
+            };
+            let update = match group.parent.as_deref().filter(|p| !p.is_empty()) {
+                Some(_) => Some(validated("groups", &group.name, req.clone())?),
+                None => None,
+            };
+            let vars_set = group.vars.clone().unwrap_or_default().into_iter().collect();
+            changes.push(GroupChange {
+                desired: group.clone(),
+                create: Some(validated("groups", &group.name, req)?),
+                update,
+                vars_set,
+                vars_delete: vec![],
+            });
+            continue;
+        };
+        if current.id == ALL_GROUP_ID && (group.parent.as_deref().is_some_and(|p| !p.is_empty()) || group.precedence.is_some_and(|p| p != 0)) {
+            return Err(ApiError::bad_request(format!("group '{}' cannot have a parent or non-zero precedence", group.name)));
+        }
+
+        let mut fields = Vec::new();
+        if differs(current.description.as_deref(), group.description.as_deref()) {
+            fields.push("description".to_string());
+        }
+        let current_parent = current.parent_id.and_then(|id| names_by_id.get(&id).copied());
+        if differs(current_parent, group.parent.as_deref()) {
+            fields.push("parent".to_string());
+        }
+        if group.precedence.is_some_and(|p| p != current.precedence) {
+            fields.push("precedence".to_string());
+        }
+        let fields_changed = !fields.is_empty();
+
+        let (vars_set, vars_delete) = match &group.vars {
+            Some(vars) => {
+                let current_vars = state.store.list_group_variables(current.id).await?;
+                diff_vars(&current_vars.into_iter().map(|v| (v.key, v.value)).collect(), vars)
+            }
+            None => (vec![], vec![]),
+        };
+        fields.extend(var_changes(&vars_set, &vars_delete));
+
+        if fields.is_empty() {
+            plan.unchanged += 1;
+            continue;
+        }
+        plan.update.push(ApplyUpdate { name: group.name.clone(), changes: fields });
+        let update = if fields_changed {
+            let req = CreateGroupRequest {
+                name: group.name.clone(),
+                description: group.description.clone().or_else(|| current.description.clone()),
+                parent_id: match group.parent.as_deref() {
+                    Some(_) => None,
+                    None => current.parent_id,
+                },
+                precedence: group.precedence.unwrap_or(current.precedence),
+                credential_id: current.credential_id,
+            };
+            Some(validated("groups", &group.name, req)?)
+        } else {
+            None
+        };
+        changes.push(GroupChange { desired: group.clone(), create: None, update, vars_set, vars_delete });
+    }
+
+    let mut delete = Vec::new();
+    if prune {
+        let mut remaining: Vec<&Group> =
+            existing.iter().filter(|g| g.id != ALL_GROUP_ID && !seen.contains(g.name.as_str())).collect();
+        // Children before their parents, which can't be deleted while referenced
+        while !remaining.is_empty() {
+            let parents: HashSet<i64> = remaining.iter().filter_map(|g| g.parent_id).collect();
+            let (leaves, rest): (Vec<&Group>, Vec<&Group>) = remaining.into_iter().partition(|g| !parents.contains(&g.id));
+            let leaves = if leaves.is_empty() { rest.clone() } else { leaves };
+            for group in &leaves {
+                plan.delete.push(group.name.clone());
+                delete.push(group.id);
+            }
+            remaining = rest.into_iter().filter(|g| !delete.contains(&g.id)).collect();
+        }
+    }
+    Ok((changes, delete))
+}
+
+async fn reconcile_devices(
+    state: &AppState,
+    desired: &[DesiredDevice],
+    desired_groups: Option<&[DesiredGroup]>,
+    prune: bool,
+    plan: &mut ApplyResourcePlan,
+) -> Result<(Vec<DeviceChange>, Vec<i64>), ApiError> {
+    let existing = state.store.list_devices().await?;
+    let mut by_hostname: HashMap<&str, &Device> = HashMap::new();
+    for device in &existing {
+        if by_hostname.insert(device.hostname.as_str(), device).is_some() {
+            return Err(ApiError::bad_request(format!("hostname '{}' matches more than one device", device.hostname)));
+        }
+    }
+
+    // Groups a device may reference once the groups section is applied
+    let db_groups = state.store.list_groups().await?;
+    let group_names: HashMap<i64, &str> = db_groups.iter().map(|g| (g.id, g.name.as_str())).collect();
+    let available: HashSet<&str> = match desired_groups {
+        Some(groups) if prune => groups
+            .iter()
+            .map(|g| g.name.as_str())
+            .chain(db_groups.iter().filter(|g| g.id == ALL_GROUP_ID).map(|g| g.name.as_str()))
+            .collect(),
+        Some(groups) => groups.iter().map(|g| g.name.as_str()).chain(db_groups.iter().map(|g| g.name.as_str())).collect(),
+        None => db_groups.iter().map(|g| g.name.as_str()).collect(),
+    };
+    let mut memberships: HashMap<i64, BTreeSet<&str>> = HashMap::new();
+    for (device_id, group_id) in state.store.list_group_memberships().await? {
+        if let Some(name) = group_names.get(&group_id) {
+            memberships.entry(device_id).or_default().insert(name);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut changes = Vec::new();
+    for device in desired {
+        let hostname = &device.hostname;
+        if !is_valid_hostname(hostname) {
+            return Err(ApiError::bad_request(format!("invalid hostname '{}'", hostname)));
+        }
+        if !seen.insert(hostname.as_str()) {
+            return Err(ApiError::bad_request(format!("duplicate device '{}'", hostname)));
+        }
+        if let Some(ip) = device.ip.as_deref().filter(|ip| !ip.is_empty() && !is_valid_ipv4(ip)) {
+            return Err(ApiError::bad_request(format!("device '{}': invalid IPv4 address '{}'", hostname, ip)));
+        }
+        if let Some(role) = device.topology_role.as_deref().filter(|r| !r.is_empty() && !topology_role::is_valid(r)) {
+            return Err(ApiError::bad_request(format!("device '{}': invalid topology_role '{}'", hostname, role)));
+        }
+        for group in device.groups.iter().flatten() {
+            if !available.contains(group.as_str()) {
+                return Err(ApiError::bad_request(format!("device '{}': unknown group '{}'", hostname, group)));
+            }
+        }
+        let template = match device.template.as_deref().filter(|t| !t.is_empty()) {
+            Some(name) => Some(
+                state
+                    .store
+                    .get_template_by_name(name)
+                    .await?
+                    .ok_or_else(|| ApiError::bad_request(format!("device '{}': unknown template '{}'", hostname, name)))?
+                    .id
+                    .to_string(),
+            ),
+            None => device.template.clone(),
+        };
+        let vendor = match device.vendor.as_deref().filter(|v| !v.is_empty() && v.parse::<i64>().is_err()) {
+            Some(name) => Some(state.store.get_vendor_by_name(name).await?.map(|v| v.id.to_string()).unwrap_or_else(|| name.to_string())),
+            None => device.vendor.clone(),
+        };
+        let mac = device.mac.as_deref().map(normalize_mac);
+
+        let Some(current) = by_hostname.get(hostname.as_str()).copied() else {
+            plan.create.push(hostname.clone());
+            let req = CreateDeviceRequest {
+                mac: mac.unwrap_or_default(),
+                ip: device.ip.clone().unwrap_or_default(),
+                hostname: hostname.clone(),
+                vendor,
+                model: device.model.clone(),
+                serial_number: device.serial_number.clone(),
+                config_template: template.unwrap_or_default(),
+                credential_id: None,
+                ssh_user: None,
+                ssh_pass: None,
+                topology_id: None,
+                topology_role: device.topology_role.clone(),
+                hall_id: None,
+                row_id: None,
+                rack_id: None,
+                rack_position: None,
+                device_type: None,
+            };
+            let vars_set = device.vars.clone().unwrap_or_default().into_iter().collect();
+            changes.push(DeviceChange {
+                hostname: hostname.clone(),
+                write: DeviceWrite::Create(validated("devices", hostname, req)?),
+                groups: device.groups.clone(),
+                vars_set,
+                vars_delete: vec![],
+            });
+            continue;
+        };
+
+        // The API has no way to change a device's MAC
+        if differs(current.mac.as_deref(), mac.as_deref()) {
+            return Err(ApiError::bad_request(format!(
+                "device '{}': mac cannot be changed from {}",
+                hostname,
+                current.mac.as_deref().unwrap_or("none")
+            )));
+        }
+
+        let mut fields = Vec::new();
+        let compared = [
+            ("ip", Some(current.ip.as_str()), device.ip.as_deref()),
+            ("vendor", current.vendor.as_deref(), vendor.as_deref()),
+            ("model", current.model.as_deref(), device.model.as_deref()),
+            ("serial_number", current.serial_number.as_deref(), device.serial_number.as_deref()),
+            ("topology_role", current.topology_role.as_deref(), device.topology_role.as_deref()),
+            ("template", Some(current.config_template.as_str()), template.as_deref()),
+        ];
+        for (field, current, desired) in compared {
+            if differs(current, desired) {
+                fields.push(field.to_string());
+            }
+        }
+        let fields_changed = !fields.is_empty();
+
+        let groups = device.groups.as_ref().filter(|groups| {
+            let desired: BTreeSet<&str> = groups.iter().map(String::as_str).collect();
+            memberships.get(&current.id).cloned().unwrap_or_default() != desired
+        });
+        if groups.is_some() {
+            fields.push("groups".to_string());
+        }
+
+        let (vars_set, vars_delete) = match &device.vars {
+            Some(vars) => {
+                let current_vars = state.store.list_device_variables(current.id).await?;
+                diff_vars(&current_vars.into_iter().map(|v| (v.key, v.value)).collect(), vars)
+            }
+            None => (vec![], vec![]),
+        };
+        fields.extend(var_changes(&vars_set, &vars_delete));
+
+        if fields.is_empty() {
+            plan.unchanged += 1;
+            continue;
+        }
+        plan.update.push(ApplyUpdate { name: hostname.clone(), changes: fields });
+        let update = if fields_changed {
+            let req = UpdateDeviceRequest {
+                ip: device.ip.clone().unwrap_or_else(|| current.ip.clone()),
+                hostname: current.hostname.clone(),
+                vendor: vendor.or_else(|| current.vendor.clone()),
+                model: device.model.clone().or_else(|| current.model.clone()),
+                serial_number: device.serial_number.clone().or_else(|| current.serial_number.clone()),
+                config_template: template.unwrap_or_else(|| current.config_template.clone()),
+                credential_id: current.credential_id,
+                ssh_user: current.ssh_user.clone(),
+                ssh_pass: current.ssh_pass.clone(),
+                topology_id: current.topology_id,
+                topology_role: device.topology_role.clone().or_else(|| current.topology_role.clone()),
+                hall_id: current.hall_id,
+                row_id: current.row_id,
+                rack_id: current.rack_id,
+                rack_position: current.rack_position,
+                device_type: Some(current.device_type.clone()),
+            };
+            Some(validated("devices", hostname, req)?)
+        } else {
+            None
+        };
+        changes.push(DeviceChange {
+            hostname: hostname.clone(),
+            write: DeviceWrite::Update { id: current.id, fields: update },
+            groups: groups.cloned(),
+            vars_set,
+            vars_delete,
+        });
+    }
+
+    let mut delete = Vec::new();
+    if prune {
+        for device in &existing {
+            if !seen.contains(device.hostname.as_str()) {
+                plan.delete.push(device.hostname.clone());
+                delete.push(device.id);
+            }
+        }
+    }
+    Ok((changes, delete))
+}
+
+async fn reconcile(state: &AppState, doc: &DesiredState) -> Result<Reconciliation, ApiError> {
+    let mut plan = ApplyPlan::default();
+    let (groups, delete_groups) = match &doc.groups {
+        Some(groups) => reconcile_groups(state, groups, doc.prune, &mut plan.groups).await?,
+        None => (vec![], vec![]),
+    };
+    let (devices, delete_devices) = match &doc.devices {
+        Some(devices) => reconcile_devices(state, devices, doc.groups.as_deref(), doc.prune, &mut plan.devices).await?,
+        None => (vec![], vec![]),
+    };
+    Ok(Reconciliation { plan, groups, devices, delete_groups, delete_devices })
+}
+
+fn forward(auth: &AuthUser) -> AuthUser {
+    AuthUser { claims: auth.claims.clone() }
+}
+
+/// Groups first (created without parents, which may not exist yet), then devices, then deletions.
+/// Every request was validated while planning. Each write is recorded in `committed` as it
+/// lands, so a failure part way can report what is already in place.
+async fn commit(
+    state: &Arc<AppState>,
+    auth: &AuthUser,
+    changes: Reconciliation,
+    committed: &mut ApplyCommitted,
+) -> Result<(), ApiError> {
+    let mut group_ids: HashMap<String, i64> =
+        state.store.list_groups().await?.into_iter().map(|g| (g.name, g.id)).collect();
+
+    let mut groups = changes.groups;
+    for change in &mut groups {
+        if let Some(req) = change.create.take() {
+            committed.groups.failed = Some(change.desired.name.clone());
+            let (_, Json(group)) = super::groups::create_group(forward(auth), State(state.clone()), req).await?;
+            committed.groups.created.push(group.name.clone());
+            group_ids.insert(group.name, group.id);
+        }
+    }
+    for change in groups {
+        let desired = &change.desired;
+        committed.groups.failed = Some(desired.name.clone());
+        let id = group_ids[&desired.name];
+        let created = committed.groups.created.contains(&desired.name);
+        if let Some(ValidJson(mut req)) = change.update {
+            if let Some(parent) = desired.parent.as_deref().filter(|p| !p.is_empty()) {
+                req.parent_id = Some(group_ids[parent]);
+            }
+            let _ = super::groups::update_group(forward(auth), State(state.clone()), Path(id), ValidJson(req)).await?;
+        }
+        for (key, value) in &change.vars_set {
+            let req = super::groups::SetVariableRequest { value: value.clone() };
+            let _ = super::groups::set_group_variable(forward(auth), State(state.clone()), Path((id, key.clone())), Json(req)).await?;
+        }
+        for key in &change.vars_delete {
+            let _ = super::groups::delete_group_variable(forward(auth), State(state.clone()), Path((id, key.clone()))).await?;
+        }
+        if !created {
+            committed.groups.updated.push(desired.name.clone());
+        }
+    }
+    committed.groups.failed = None;
+
+    for change in changes.devices {
+        committed.devices.failed = Some(change.hostname.clone());
+        let id = match change.write {
+            DeviceWrite::Create(req) => {
+                let (_, Json(device)) = super::devices::create_device(forward(auth), State(state.clone()), req).await?;
+                committed.devices.created.push(change.hostname.clone());
+                device.id
+            }
+            DeviceWrite::Update { id, fields } => {
+                if let Some(req) = fields {
+                    let _ = super::devices::update_device(forward(auth), State(state.clone()), Path(id), req).await?;
+                }
+                id
+            }
+        };
+        if let Some(groups) = &change.groups {
+            let group_ids: Vec<i64> = groups.iter().map(|name| group_ids[name]).collect();
+            state.store.set_device_groups(id, &group_ids).await?;
+        }
+        for (key, value) in change.vars_set {
+            let req = super::device_variables::SetVariableRequest { value };
+            let _ = super::device_variables::set_device_variable(forward(auth), State(state.clone()), Path((id, key)), Json(req)).await?;
+        }
+        for key in change.vars_delete {
+            let _ = super::device_variables::delete_device_variable(forward(auth), State(state.clone()), Path((id, key))).await?;
+        }
+        if !committed.devices.created.contains(&change.hostname) {
+            committed.devices.updated.push(change.hostname);
+        }
+    }
+    committed.devices.failed = None;
+
+    for (id, hostname) in changes.delete_devices.into_iter().zip(changes.plan.devices.delete) {
+        committed.devices.failed = Some(hostname.clone());
+        super::devices::delete_device(forward(auth), State(state.clone()), Path(id)).await?;
+        committed.devices.deleted.push(hostname);
+    }
+    committed.devices.failed = None;
+    // Pruning is authoritative, so remaining memberships and child references are cleared
+    for (id, name) in changes.delete_groups.into_iter().zip(changes.plan.groups.delete) {
+        committed.groups.failed = Some(name.clone());
+        let query = DeleteQuery { force: true };
+        let _ = super::groups::delete_group(forward(auth), State(state.clone()), Path(id), Query(query)).await?;
+        committed.groups.deleted.push(name);
+    }
+    committed.groups.failed = None;
+    Ok(())
+}
+
+/// Parse a desired-state document as JSON or YAML, by its Content-Type
+fn parse_document(headers: &HeaderMap, body: &[u8]) -> Result<DesiredState, ApiError> {
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "application/json" => serde_json::from_slice(body)
+            .map_err(|e| ApiError::bad_request(format!("invalid JSON document: {}", e))),
+        "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => serde_yaml::from_slice(body)
+            .map_err(|e| ApiError::bad_request(format!("invalid YAML document: {}", e))),
+        _ => Err(ApiError::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "expected a Content-Type of application/json or application/yaml",
+        )),
+    }
+}
+
+/// Plan (and with `dry_run=false`, apply) a desired-state document. Changes are written one at a
+/// time rather than in one transaction, so a commit that fails part way responds with the failing
+/// status and the plan, naming what was committed before the error.
+pub async fn apply(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ApplyQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ApplyPlan>), ApiError> {
+    let doc = parse_document(&headers, &body)?;
+    let mut changes = reconcile(&state, &doc).await?;
+    changes.plan.dry_run = query.dry_run;
+    let mut plan = changes.plan.clone();
+    if query.dry_run {
+        return Ok((StatusCode::OK, Json(plan)));
+    }
+
+    let mut committed = ApplyCommitted::default();
+    let result = if plan.is_empty() { Ok(()) } else { commit(&state, &auth, changes, &mut committed).await };
+    let status = match result {
+        Ok(()) if plan.is_empty() => StatusCode::OK,
+        Ok(()) => {
+            tracing::info!(
+                "Applied desired state: {} created, {} updated, {} deleted",
+                committed.groups.created.len() + committed.devices.created.len(),
+                committed.groups.updated.len() + committed.devices.updated.len(),
+                committed.groups.deleted.len() + committed.devices.deleted.len()
+            );
+            StatusCode::OK
+        }
+        Err(err) => {
+            tracing::warn!("Apply stopped part way: {}", err.message);
+            plan.error = Some(err.message);
+            err.status
+        }
+    };
+    plan.committed = Some(committed);
+    Ok((status, Json(plan)))
+}
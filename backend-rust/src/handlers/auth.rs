@@ -37,17 +37,20 @@ pub async fn login(
         username: user.username.clone(),
         exp: exp.timestamp() as usize,
         iat: now.timestamp() as usize,
+        impersonation: None,
     };
 
-    let token = jsonwebtoken::encode(
-        &jsonwebtoken::Header::default(),
-        &claims,
-        &jsonwebtoken::EncodingKey::from_secret(state.config.jwt_secret.as_bytes()),
-    )
-    .map_err(|e| ApiError::internal(format!("token generation error: {}", e)))?;
-
     Ok(Json(LoginResponse {
-        token,
+        token: encode_token(&state, &claims)?,
         username: user.username,
     }))
 }
+
+pub(super) fn encode_token(state: &AppState, claims: &Claims) -> Result<String, ApiError> {
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        claims,
+        &jsonwebtoken::EncodingKey::from_secret(state.config.jwt_secret.as_bytes()),
+    )
+    .map_err(|e| ApiError::internal(format!("token generation error: {}", e)))
+}
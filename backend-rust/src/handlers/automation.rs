@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::automation;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+const MAX_EXECUTION_LIMIT: i64 = 1000;
+
+async fn validate_rule(state: &AppState, id: Option<i64>, req: &CreateAutomationRuleRequest) -> Result<(), ApiError> {
+    automation::validate_action(&req.action_type, &req.action)
+        .map_err(|e| ApiError::bad_request(format!("invalid {} action: {:#}", req.action_type, e)))?;
+    if req.action_type == automation_action::JOB_TEMPLATE {
+        let action: JobTemplateAutomationAction = serde_json::from_value(req.action.clone())
+            .map_err(|e| ApiError::bad_request(e.to_string()))?;
+        if state.store.get_job_template(action.job_template_id).await?.is_none() {
+            return Err(ApiError::bad_request(format!("job template {} not found", action.job_template_id)));
+        }
+    }
+    let rules = state.store.list_automation_rules().await?;
+    if rules.iter().any(|r| r.name == req.name && Some(r.id) != id) {
+        return Err(ApiError::conflict(format!("automation rule '{}' already exists", req.name)));
+    }
+    Ok(())
+}
+
+/// List automation rules
+pub async fn list_automation_rules(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<AutomationRule>>, ApiError> {
+    Ok(Json(state.store.list_automation_rules().await?))
+}
+
+/// Get a single automation rule by ID
+pub async fn get_automation_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<AutomationRule>, ApiError> {
+    let rule = state
+        .store
+        .get_automation_rule(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("automation rule"))?;
+    Ok(Json(rule))
+}
+
+/// Create an automation rule
+pub async fn create_automation_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateAutomationRuleRequest>,
+) -> Result<(StatusCode, Json<AutomationRule>), ApiError> {
+    validate_rule(&state, None, &req).await?;
+    Ok(created(state.store.create_automation_rule(&req).await?))
+}
+
+/// Update an automation rule; set `enabled` to false to stop it firing
+pub async fn update_automation_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateAutomationRuleRequest>,
+) -> Result<Json<AutomationRule>, ApiError> {
+    validate_rule(&state, Some(id), &req).await?;
+    Ok(Json(state.store.update_automation_rule(id, &req).await?))
+}
+
+/// Delete an automation rule and its execution history
+pub async fn delete_automation_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_automation_rule(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List a rule's executions, newest first
+pub async fn list_automation_executions(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(query): Query<AutomationExecutionQuery>,
+) -> Result<Json<Vec<AutomationExecution>>, ApiError> {
+    if state.store.get_automation_rule(id).await?.is_none() {
+        return Err(ApiError::not_found("automation rule"));
+    }
+    let limit = query.limit.clamp(1, MAX_EXECUTION_LIMIT);
+    Ok(Json(state.store.list_automation_executions(id, limit).await?))
+}
@@ -1,13 +1,15 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::AppState;
 
-use super::ApiError;
+use super::{created, ApiError};
 
 /// List backups for a device
 pub async fn list_backups(
@@ -38,31 +40,79 @@ pub async fn get_backup(
         .await?
         .ok_or_else(|| ApiError::not_found("backup"))?;
 
-    // Read backup content from file
-    let backup_path = std::path::Path::new(&state.config.backup_dir).join(&backup.filename);
-    let content = match tokio::fs::read_to_string(&backup_path).await {
-        Ok(content) => Some(content),
-        Err(_) => None,
-    };
+    let content = crate::backup::read_backup(&state.config.backup_dir, &backup).await.ok();
 
     Ok(Json(BackupWithContent {
         id: backup.id,
         device_id: backup.device_id,
         filename: backup.filename,
         size: backup.size,
+        source: backup.source,
+        description: backup.description,
+        content_hash: backup.content_hash,
+        changed: backup.changed,
         created_at: backup.created_at,
         exists: content.is_some(),
         content,
     }))
 }
 
+/// Read a backup's config file
+async fn read_backup_content(state: &AppState, backup: &crate::models::Backup) -> Result<String, ApiError> {
+    crate::backup::read_backup(&state.config.backup_dir, backup)
+        .await
+        .map_err(|_| ApiError::not_found("backup file"))
+}
+
+/// A backup of the given device
+async fn device_backup(state: &AppState, device_id: i64, id: i64) -> Result<crate::models::Backup, ApiError> {
+    state
+        .store
+        .get_backup(id)
+        .await?
+        .filter(|b| b.device_id == device_id)
+        .ok_or_else(|| ApiError::not_found(&format!("backup {}", id)))
+}
+
+/// Diff two of a device's backups, or a backup against the device's currently rendered config
+/// when `other` is `rendered`, as structured hunks
+pub async fn diff_backups(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((device_id, backup_id, other)): Path<(i64, i64, String)>,
+) -> Result<Json<crate::models::ConfigDiff>, ApiError> {
+    let device = state
+        .store
+        .get_device(device_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    let old = device_backup(&state, device_id, backup_id).await?;
+    let old_content = read_backup_content(&state, &old).await?;
+    let (new_label, new_content) = if other == "rendered" {
+        let (_, rendered) = crate::jobs::render_device(&state.store, &state.render_cache, &device)
+            .await
+            .map_err(|e| ApiError::bad_request(format!("Failed to render config: {}", e)))?;
+        ("rendered".to_string(), rendered)
+    } else {
+        let id = other
+            .parse::<i64>()
+            .map_err(|_| ApiError::bad_request("compare against a backup ID or 'rendered'"))?;
+        let new = device_backup(&state, device_id, id).await?;
+        let content = read_backup_content(&state, &new).await?;
+        (new.filename, content)
+    };
+
+    Ok(Json(crate::utils::diff_lines(&old_content, &new_content, &old.filename, &new_label, 3)))
+}
+
 /// Trigger a manual backup for a device
 /// Returns 202 Accepted since backup runs asynchronously
 pub async fn trigger_backup(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
 ) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    super::teams::require_device_action(&state, &auth, &[id], crate::models::team_action::BACKUP, None).await?;
     // Verify device exists
     let _device = state
         .store
@@ -80,6 +130,110 @@ pub async fn trigger_backup(
     }))))
 }
 
+/// Upload a config collected out-of-band as a backup of a device, for devices without SSH
+/// access or historical archives. It is stored and diffed like a fetched backup.
+pub async fn upload_backup(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<crate::models::UploadBackupRequest>,
+) -> Result<(StatusCode, Json<crate::models::Backup>), ApiError> {
+    super::teams::require_device_action(&state, &auth, &[id], crate::models::team_action::BACKUP, None).await?;
+    let device = state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    if req.content.trim().is_empty() {
+        return Err(ApiError::bad_request("content is empty"));
+    }
+    let now = chrono::Utc::now();
+    let collected_at = req.collected_at.unwrap_or(now);
+    if collected_at > now {
+        return Err(ApiError::bad_request("collected_at is in the future"));
+    }
+
+    let backup = crate::backup::store_backup(
+        &state.store,
+        &state.config.backup_dir,
+        &device,
+        &req.content,
+        crate::models::backup_source::UPLOAD,
+        &req.description,
+        collected_at,
+    )
+    .await?;
+    // Only a config collected now is the device's current one; archived uploads stay out of Git
+    if let (Some(history), None) = (&state.config_history, req.collected_at) {
+        if let Err(e) = history.record_backup(&device.hostname, &req.content, Some(&auth.claims.username)).await {
+            tracing::warn!("Failed to commit backup of {} to history: {}", device.hostname, e);
+        }
+    }
+    Ok(created(backup))
+}
+
+/// What restoring a backup would change: the backup diffed against the device's live running
+/// config and against its rendered intent. Either side reports an error instead of a diff when it
+/// can't be produced (device unreachable, no template assigned); an empty diff means no change.
+#[derive(serde::Serialize)]
+pub struct RestorePreview {
+    pub backup_id: i64,
+    pub device_id: i64,
+    pub hostname: String,
+    pub filename: String,
+    pub backup_created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub running_diff: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub running_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intent_diff: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intent_error: Option<String>,
+}
+
+/// Preview a restore: diff the backup against the config fetched live from the device and
+/// against the rendered intent, so the change a rollback makes is known before it runs
+pub async fn preview_restore(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<RestorePreview>, ApiError> {
+    let backup = state
+        .store
+        .get_backup(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("backup"))?;
+    let device = state
+        .store
+        .get_device(backup.device_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+
+    let content = read_backup_content(&state, &backup).await?;
+
+    let (running_diff, running_error) = match crate::backup::fetch_running_config(&state.store, &device).await {
+        Ok(running) => (Some(crate::utils::unified_diff(&running, &content, "running", &backup.filename, 3)), None),
+        Err(e) => (None, Some(format!("Failed to fetch running config: {}", e))),
+    };
+    let (intent_diff, intent_error) = match crate::jobs::render_device(&state.store, &state.render_cache, &device).await {
+        Ok((_, rendered)) => (Some(crate::utils::unified_diff(&rendered, &content, "intent", &backup.filename, 3)), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+
+    Ok(Json(RestorePreview {
+        backup_id: backup.id,
+        device_id: device.id,
+        hostname: device.hostname,
+        filename: backup.filename,
+        backup_created_at: backup.created_at,
+        running_diff,
+        running_error,
+        intent_diff,
+        intent_error,
+    }))
+}
+
 /// Backup response with content
 #[derive(serde::Serialize)]
 pub struct BackupWithContent {
@@ -87,8 +241,97 @@ pub struct BackupWithContent {
     pub device_id: i64,
     pub filename: String,
     pub size: i64,
+    pub source: String,
+    pub description: String,
+    pub content_hash: String,
+    pub changed: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub exists: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct BackupSearchQuery {
+    pub q: String,
+    /// Treat `q` as a regular expression instead of a case-insensitive substring
+    #[serde(default)]
+    pub regex: bool,
+    /// Search every stored version instead of only each device's latest backup
+    #[serde(default)]
+    pub all_versions: bool,
+    #[serde(default)]
+    pub device_id: Option<i64>,
+    #[serde(default = "default_search_context")]
+    pub context: usize,
+    #[serde(default = "default_search_limit")]
+    pub limit: usize,
+}
+
+fn default_search_context() -> usize {
+    2
+}
+
+fn default_search_limit() -> usize {
+    100
+}
+
+/// Search the contents of stored backups
+pub async fn search_backups(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BackupSearchQuery>,
+) -> Result<Json<Vec<crate::models::BackupSearchResult>>, ApiError> {
+    if query.q.is_empty() {
+        return Err(ApiError::bad_request("q is required"));
+    }
+    let context = query.context.min(10);
+    let limit = query.limit.clamp(1, 1000);
+
+    let is_match: Box<dyn Fn(&str) -> bool + Send + Sync> = if query.regex {
+        let re = regex_lite::Regex::new(&query.q)
+            .map_err(|e| ApiError::bad_request(format!("invalid regex: {}", e)))?;
+        Box::new(move |line: &str| re.is_match(line))
+    } else {
+        let needle = query.q.to_lowercase();
+        Box::new(move |line: &str| line.to_lowercase().contains(&needle))
+    };
+
+    let hostnames: HashMap<i64, String> = state
+        .store
+        .list_devices()
+        .await?
+        .into_iter()
+        .map(|d| (d.id, d.hostname))
+        .collect();
+
+    let backups = state
+        .store
+        .list_backups_for_search(!query.all_versions, query.device_id)
+        .await?;
+
+    let mut results = Vec::new();
+    for backup in backups {
+        let content = match crate::backup::read_backup(&state.config.backup_dir, &backup).await {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let matches = crate::utils::find_line_matches(&content, &is_match, context);
+        if matches.is_empty() {
+            continue;
+        }
+        results.push(crate::models::BackupSearchResult {
+            backup_id: backup.id,
+            device_id: backup.device_id,
+            hostname: hostnames.get(&backup.device_id).cloned().unwrap_or_default(),
+            filename: backup.filename,
+            created_at: backup.created_at,
+            matches,
+        });
+        if results.len() >= limit {
+            break;
+        }
+    }
+
+    Ok(Json(results))
+}
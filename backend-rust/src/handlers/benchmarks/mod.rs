@@ -1,5 +1,9 @@
 use axum::Json;
 
+mod store;
+
+pub use store::*;
+
 /// Static benchmark handler - no DB operations
 pub async fn benchmark_handler(_auth: crate::auth::AuthUser) -> Json<serde_json::Value> {
     Json(serde_json::json!({
@@ -0,0 +1,261 @@
+use std::future::Future;
+use std::path::PathBuf;
+
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+use crate::db::Store;
+use crate::handlers::ApiError;
+use crate::jobs::RenderCache;
+use crate::models::*;
+
+const MAX_DEVICES: usize = 2000;
+const MAX_VARIABLES_PER_DEVICE: usize = 50;
+const MAX_JOBS_PER_DEVICE: usize = 20;
+const MAX_GROUPS: usize = 100;
+const MAX_ITERATIONS: usize = 1000;
+
+fn default_devices() -> usize { 200 }
+fn default_variables_per_device() -> usize { 10 }
+fn default_jobs_per_device() -> usize { 5 }
+fn default_groups() -> usize { 10 }
+fn default_iterations() -> usize { 50 }
+
+/// Sizes of the synthetic dataset and how often each operation is measured
+#[derive(Deserialize)]
+pub struct StoreBenchmarkRequest {
+    #[serde(default = "default_devices")]
+    pub devices: usize,
+    #[serde(default = "default_variables_per_device")]
+    pub variables_per_device: usize,
+    #[serde(default = "default_jobs_per_device")]
+    pub jobs_per_device: usize,
+    #[serde(default = "default_groups")]
+    pub groups: usize,
+    #[serde(default = "default_iterations")]
+    pub iterations: usize,
+}
+
+/// Latency distribution of one measured operation
+#[derive(Serialize)]
+pub struct OperationTiming {
+    pub operation: &'static str,
+    pub iterations: usize,
+    pub min_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+    pub mean_ms: f64,
+    pub ops_per_sec: f64,
+}
+
+#[derive(Serialize)]
+pub struct StoreBenchmarkResponse {
+    pub status: &'static str,
+    pub devices: usize,
+    pub variables: usize,
+    pub jobs: usize,
+    pub groups: usize,
+    pub seed_ms: f64,
+    pub elapsed_ms: f64,
+    pub results: Vec<OperationTiming>,
+}
+
+/// A throwaway database file, removed when dropped
+struct ScratchStore {
+    store: Store,
+    path: PathBuf,
+}
+
+impl ScratchStore {
+    async fn open() -> anyhow::Result<Self> {
+        let path = std::env::temp_dir().join(format!("forge-bench-{}.db", uuid::Uuid::new_v4()));
+        let store = Store::new(&path.to_string_lossy()).await?;
+        Ok(Self { store, path })
+    }
+}
+
+impl Drop for ScratchStore {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm", "-journal"] {
+            let mut file = self.path.clone().into_os_string();
+            file.push(suffix);
+            let _ = std::fs::remove_file(file);
+        }
+    }
+}
+
+fn elapsed_ms(start: std::time::Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Run `op` `iterations` times and summarize the per-call latency
+async fn measure<F, Fut, T>(operation: &'static str, iterations: usize, mut op: F) -> Result<OperationTiming, ApiError>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut samples = Vec::with_capacity(iterations);
+    for i in 0..iterations {
+        let start = std::time::Instant::now();
+        op(i).await
+            .map_err(|e| ApiError::internal(format!("{} failed: {}", operation, e)))?;
+        samples.push(elapsed_ms(start));
+    }
+    samples.sort_by(|a, b| a.total_cmp(b));
+
+    let total: f64 = samples.iter().sum();
+    let mean_ms = total / iterations as f64;
+    Ok(OperationTiming {
+        operation,
+        iterations,
+        min_ms: samples[0],
+        p50_ms: crate::utils::percentile(&samples, 50.0),
+        p90_ms: crate::utils::percentile(&samples, 90.0),
+        p99_ms: crate::utils::percentile(&samples, 99.0),
+        max_ms: samples[iterations - 1],
+        mean_ms,
+        ops_per_sec: if total > 0.0 { iterations as f64 * 1000.0 / total } else { 0.0 },
+    })
+}
+
+/// Seed groups, devices, variables and jobs into the scratch store
+async fn seed(store: &Store, req: &StoreBenchmarkRequest) -> anyhow::Result<(Vec<Group>, Vec<Device>)> {
+    let mut groups = Vec::with_capacity(req.groups);
+    for g in 0..req.groups {
+        let group = store
+            .create_group(&CreateGroupRequest {
+                name: format!("bench-group-{:03}", g),
+                description: None,
+                parent_id: None,
+                precedence: 1000 + g as i32,
+                credential_id: None,
+            })
+            .await?;
+        store.set_group_variable(group.id, "ntp_server", &format!("10.0.{}.1", g)).await?;
+        store.set_group_variable(group.id, "site", &format!("site-{}", g)).await?;
+        groups.push(group);
+    }
+
+    let mut devices = Vec::with_capacity(req.devices);
+    for d in 0..req.devices {
+        let device = store
+            .create_device(&CreateDeviceRequest {
+                mac: format!("02:be:00:{:02x}:{:02x}:{:02x}", (d >> 16) & 0xff, (d >> 8) & 0xff, d & 0xff),
+                ip: format!("10.{}.{}.{}", 100 + (d >> 16), (d >> 8) & 0xff, d & 0xff),
+                hostname: format!("bench-{:05}", d),
+                vendor: Some("arista".to_string()),
+                model: None,
+                serial_number: None,
+                config_template: String::new(),
+                credential_id: None,
+                ssh_user: None,
+                ssh_pass: None,
+                topology_id: None,
+                topology_role: Some(if d % 8 == 0 { "spine" } else { "leaf" }.to_string()),
+                device_type: None,
+                hall_id: None,
+                row_id: None,
+                rack_id: None,
+                rack_position: None,
+            })
+            .await?;
+        if let Some(group) = groups.get(d % req.groups.max(1)) {
+            store.add_device_to_group(device.id, group.id).await?;
+        }
+        for v in 0..req.variables_per_device {
+            store.set_device_variable(device.id, &format!("var_{:02}", v), &format!("value-{}-{}", d, v)).await?;
+        }
+        for _ in 0..req.jobs_per_device {
+            let job_req = CreateJobRequest {
+                device_id: device.id,
+                job_type: job_type::COMMAND.to_string(),
+                command: "show version".to_string(),
+                credential_id: String::new(),
+                triggered_by: "benchmark".to_string(),
+                timeout_secs: None,
+            };
+            store.create_job(&uuid::Uuid::new_v4().to_string(), &job_req).await?;
+        }
+        devices.push(device);
+    }
+    Ok((groups, devices))
+}
+
+/// Store throughput benchmark. Seeds a synthetic dataset into a scratch database (the live
+/// database is never touched), then times list, filter, variable resolution, job history and
+/// render operations and reports latency percentiles for each.
+pub async fn store_benchmark_handler(
+    _auth: crate::auth::AuthUser,
+    body: Option<Json<StoreBenchmarkRequest>>,
+) -> Result<Json<StoreBenchmarkResponse>, ApiError> {
+    let req = body.map(|b| b.0).unwrap_or(StoreBenchmarkRequest {
+        devices: default_devices(),
+        variables_per_device: default_variables_per_device(),
+        jobs_per_device: default_jobs_per_device(),
+        groups: default_groups(),
+        iterations: default_iterations(),
+    });
+    if req.devices == 0 || req.devices > MAX_DEVICES {
+        return Err(ApiError::bad_request(format!("devices must be between 1 and {}", MAX_DEVICES)));
+    }
+    if req.iterations == 0 || req.iterations > MAX_ITERATIONS {
+        return Err(ApiError::bad_request(format!("iterations must be between 1 and {}", MAX_ITERATIONS)));
+    }
+    if req.variables_per_device > MAX_VARIABLES_PER_DEVICE
+        || req.jobs_per_device > MAX_JOBS_PER_DEVICE
+        || req.groups > MAX_GROUPS
+    {
+        return Err(ApiError::bad_request(format!(
+            "at most {} variables and {} jobs per device, and {} groups",
+            MAX_VARIABLES_PER_DEVICE, MAX_JOBS_PER_DEVICE, MAX_GROUPS
+        )));
+    }
+
+    let start = std::time::Instant::now();
+    let scratch = ScratchStore::open().await?;
+    let store = &scratch.store;
+
+    let (groups, devices) = seed(store, &req).await?;
+    let seed_ms = elapsed_ms(start);
+
+    let n = req.iterations;
+    let device_at = |i: usize| &devices[(i * 7919) % devices.len()];
+    let cache = RenderCache::new();
+
+    let mut results = vec![
+        measure("list_devices", n, |_| store.list_devices()).await?,
+        measure("list_devices_filtered", n, |i| {
+            let filter = DeviceFilter {
+                group_id: groups.get(i % groups.len().max(1)).map(|g| g.id),
+                topology_role: Some("leaf".to_string()),
+                ..Default::default()
+            };
+            async move { store.list_devices_filtered(&filter).await }
+        })
+        .await?,
+        measure("resolve_variables", n, |i| store.resolve_device_variables_flat(device_at(i).id)).await?,
+        measure("list_jobs_by_device", n, |i| store.list_jobs_by_device(device_at(i).id, 50)).await?,
+        measure("list_jobs_recent", n, |_| store.list_jobs_recent(100)).await?,
+    ];
+    results.push(
+        measure("render_uncached", n, |i| {
+            cache.clear();
+            crate::jobs::render_device(store, &cache, device_at(i))
+        })
+        .await?,
+    );
+    results.push(measure("render_cached", n, |_| crate::jobs::render_device(store, &cache, &devices[0])).await?);
+
+    Ok(Json(StoreBenchmarkResponse {
+        status: "ok",
+        devices: devices.len(),
+        variables: devices.len() * req.variables_per_device,
+        jobs: devices.len() * req.jobs_per_device,
+        groups: groups.len(),
+        seed_ms,
+        elapsed_ms: elapsed_ms(start),
+        results,
+    }))
+}
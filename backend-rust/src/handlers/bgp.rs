@@ -0,0 +1,225 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::db::Store;
+use crate::models::*;
+use crate::AppState;
+
+use super::ApiError;
+
+/// Devices queried at the same time while building the session matrix
+const BGP_COLLECT_CONCURRENCY: usize = 8;
+
+const DEFAULT_BGP_SUMMARY_COMMAND: &str = "show ip bgp summary";
+
+/// A session the topology data says a device should have
+struct ExpectedPeer {
+    ip: String,
+    name: String,
+    asn: Option<i64>,
+}
+
+/// Expected peers from the `PeerN` / `PeerNName` / `PeerNASN` variables the topology builders set
+fn expected_peers(vars: &HashMap<String, String>) -> Vec<ExpectedPeer> {
+    let mut peers: Vec<(u32, ExpectedPeer)> = vars
+        .iter()
+        .filter_map(|(key, ip)| {
+            let n = key.strip_prefix("Peer")?.parse::<u32>().ok()?;
+            Some((n, ExpectedPeer {
+                ip: ip.trim().to_string(),
+                name: vars.get(&format!("Peer{}Name", n)).cloned().unwrap_or_default(),
+                asn: vars.get(&format!("Peer{}ASN", n)).and_then(|a| a.trim().parse().ok()),
+            }))
+        })
+        .filter(|(_, peer)| !peer.ip.is_empty())
+        .collect();
+    peers.sort_by_key(|(n, _)| *n);
+    peers.into_iter().map(|(_, peer)| peer).collect()
+}
+
+/// The vendor's "BGP summary" SSH action, else the common `show ip bgp summary`
+async fn bgp_summary_command(store: &Store, device: &Device) -> String {
+    let vendor = match device.vendor.as_deref() {
+        Some(v) if !v.is_empty() => store.resolve_vendor(v).await.ok().flatten(),
+        _ => None,
+    };
+    if let Some(vendor) = vendor {
+        if let Ok(actions) = store.list_vendor_actions_by_vendor(vendor.id).await {
+            let action = actions.into_iter().find(|a| {
+                let command = a.command.to_lowercase();
+                a.action_type == "ssh" && command.contains("bgp summary") && !command.contains("json")
+            });
+            if let Some(action) = action {
+                return action.command;
+            }
+        }
+    }
+    DEFAULT_BGP_SUMMARY_COMMAND.to_string()
+}
+
+async fn collect_bgp_summary(store: &Store, device: &Device) -> Result<Vec<BgpSummaryEntry>, String> {
+    let crate::utils::ResolvedSshCredentials { user, pass, credential_id } =
+        crate::utils::resolve_ssh_credentials(store, device).await;
+    if user.is_empty() || pass.is_empty() {
+        return Err("No SSH credentials configured".to_string());
+    }
+    if let Some(cred_id) = credential_id {
+        if let Err(e) = store.record_credential_usage(cred_id, Some(device.id), "").await {
+            tracing::warn!("Failed to record usage of credential {}: {}", cred_id, e);
+        }
+    }
+    let command = bgp_summary_command(store, device).await;
+    let output = crate::utils::ssh_run_command_async(&device.ip, &user, &pass, &command).await?;
+    Ok(crate::utils::parse_bgp_summary(&output))
+}
+
+/// Rank statuses so the matrix cell of several sessions to one peer shows the worst
+fn status_severity(status: &str) -> u8 {
+    match status {
+        bgp_session_status::DOWN => 4,
+        bgp_session_status::MISSING => 3,
+        bgp_session_status::UNKNOWN => 2,
+        bgp_session_status::UNEXPECTED => 1,
+        _ => 0,
+    }
+}
+
+/// Fabric BGP health: collects the BGP summary from every topology member over SSH and
+/// correlates it with the peerings expected from the topology variables. Each session is up,
+/// down, missing (expected but not configured), unexpected (configured but not expected) or
+/// unknown (the device couldn't be queried).
+pub async fn bgp_session_matrix(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<BgpSessionMatrix>, ApiError> {
+    state
+        .store
+        .get_topology(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("topology"))?;
+
+    let filter = DeviceFilter { topology_id: Some(id), ..Default::default() };
+    let devices: Vec<Device> = state
+        .store
+        .list_devices_filtered(&filter)
+        .await?
+        .into_iter()
+        .filter(|d| d.topology_role.as_deref() != Some(topology_role::PATCH_PANEL) && !d.ip.is_empty())
+        .collect();
+
+    // Which device owns an address: management IPs, the local side of each PeerN link
+    // (PeerNAddr) and collected interface addresses
+    let mut expected: HashMap<i64, Vec<ExpectedPeer>> = HashMap::new();
+    let mut address_owner: HashMap<String, i64> = HashMap::new();
+    for device in &devices {
+        address_owner.insert(device.ip.clone(), device.id);
+        let vars = state.store.resolve_device_variables_flat(device.id).await.unwrap_or_default();
+        for (key, value) in &vars {
+            if key.starts_with("Peer") && key.ends_with("Addr") {
+                address_owner.insert(value.trim().to_string(), device.id);
+            }
+        }
+        for interface in state.store.list_device_interfaces(device.id).await? {
+            if let Some(ip) = interface.ip.split('/').next().filter(|ip| !ip.is_empty()) {
+                address_owner.insert(ip.to_string(), device.id);
+            }
+        }
+        expected.insert(device.id, expected_peers(&vars));
+    }
+    let hostnames: HashMap<i64, &str> = devices.iter().map(|d| (d.id, d.hostname.as_str())).collect();
+    let by_hostname: HashMap<&str, i64> = devices.iter().map(|d| (d.hostname.as_str(), d.id)).collect();
+
+    let collected: HashMap<i64, Result<Vec<BgpSummaryEntry>, String>> = stream::iter(devices.clone())
+        .map(|device| {
+            let store = state.store.clone();
+            async move { (device.id, collect_bgp_summary(&store, &device).await) }
+        })
+        .buffer_unordered(BGP_COLLECT_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut matrix_devices = Vec::with_capacity(devices.len());
+    let mut sessions = Vec::new();
+    for device in &devices {
+        let result = &collected[&device.id];
+        matrix_devices.push(BgpMatrixDevice {
+            device_id: device.id,
+            hostname: device.hostname.clone(),
+            topology_role: device.topology_role.clone().unwrap_or_default(),
+            collected: result.is_ok(),
+            error: result.as_ref().err().cloned(),
+        });
+        let actual = result.as_ref().ok();
+
+        let session = |peer_ip: &str, peer_device_id: Option<i64>, peer_name: &str, expected_asn: Option<i64>, status: &str, entry: Option<&BgpSummaryEntry>| BgpSession {
+            device_id: device.id,
+            hostname: device.hostname.clone(),
+            peer_ip: peer_ip.to_string(),
+            peer_device_id,
+            peer_hostname: peer_device_id
+                .and_then(|pid| hostnames.get(&pid).map(|h| h.to_string()))
+                .unwrap_or_else(|| peer_name.to_string()),
+            expected_asn,
+            remote_asn: entry.and_then(|e| e.remote_asn),
+            status: status.to_string(),
+            state: entry.map(|e| e.state.clone()).unwrap_or_default(),
+            prefixes_received: entry.and_then(|e| e.prefixes_received),
+            up_down: entry.map(|e| e.up_down.clone()).unwrap_or_default(),
+        };
+
+        let peers = expected.get(&device.id).map(Vec::as_slice).unwrap_or_default();
+        for peer in peers {
+            let peer_device_id = address_owner
+                .get(&peer.ip)
+                .or_else(|| by_hostname.get(peer.name.as_str()))
+                .copied();
+            let entry = actual.and_then(|entries| entries.iter().find(|e| e.neighbor == peer.ip));
+            let status = match (actual, entry) {
+                (None, _) => bgp_session_status::UNKNOWN,
+                (Some(_), Some(e)) if e.established => bgp_session_status::UP,
+                (Some(_), Some(_)) => bgp_session_status::DOWN,
+                (Some(_), None) => bgp_session_status::MISSING,
+            };
+            sessions.push(session(&peer.ip, peer_device_id, &peer.name, peer.asn, status, entry));
+        }
+        for entry in actual.into_iter().flatten() {
+            if peers.iter().any(|p| p.ip == entry.neighbor) {
+                continue;
+            }
+            let peer_device_id = address_owner.get(&entry.neighbor).copied();
+            sessions.push(session(&entry.neighbor, peer_device_id, "", None, bgp_session_status::UNEXPECTED, Some(entry)));
+        }
+    }
+
+    let mut summary = BgpMatrixSummary::default();
+    let mut matrix: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    for s in &sessions {
+        match s.status.as_str() {
+            bgp_session_status::UP => summary.up += 1,
+            bgp_session_status::DOWN => summary.down += 1,
+            bgp_session_status::MISSING => summary.missing += 1,
+            bgp_session_status::UNEXPECTED => summary.unexpected += 1,
+            _ => summary.unknown += 1,
+        }
+        let peer = if s.peer_hostname.is_empty() { s.peer_ip.clone() } else { s.peer_hostname.clone() };
+        let cell = matrix.entry(s.hostname.clone()).or_default().entry(peer).or_insert_with(|| s.status.clone());
+        if status_severity(&s.status) > status_severity(cell) {
+            *cell = s.status.clone();
+        }
+    }
+
+    Ok(Json(BgpSessionMatrix {
+        topology_id: id,
+        collected_at: chrono::Utc::now(),
+        devices: matrix_devices,
+        sessions,
+        matrix,
+        summary,
+    }))
+}
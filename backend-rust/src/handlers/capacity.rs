@@ -0,0 +1,225 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::ApiError;
+
+/// Leaf ASNs have to stay below the super-spines' shared ASN
+const LEAF_ASN_LIMIT: u32 = 65500;
+
+/// Largest expansion that can be planned at once
+const MAX_ADD_LEAVES: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct CapacityQuery {
+    /// Leaves to plan for; omitted or 0 skips the expansion
+    #[serde(default)]
+    pub add_leaves: usize,
+}
+
+/// The role a role's uplinks go to
+fn parent_role(role: &str) -> Option<&'static str> {
+    match role {
+        topology_role::LEAF => Some(topology_role::SPINE),
+        topology_role::SPINE => Some(topology_role::SUPER_SPINE),
+        topology_role::ACCESS => Some(topology_role::DISTRIBUTION),
+        topology_role::DISTRIBUTION => Some(topology_role::CORE),
+        topology_role::GPU_NODE => Some(topology_role::LEAF),
+        _ => None,
+    }
+}
+
+fn ratio(downlink: i64, uplink: i64) -> Option<f64> {
+    (uplink > 0).then(|| (downlink as f64 / uplink as f64 * 100.0).round() / 100.0)
+}
+
+/// Port budget and oversubscription per role of a topology, and with `?add_leaves=N` what adding
+/// N leaves cabled like the existing ones would take: spine ports, /31 links and ASNs. Read-only.
+pub async fn topology_capacity(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(query): Query<CapacityQuery>,
+) -> Result<Json<TopologyCapacity>, ApiError> {
+    state
+        .store
+        .get_topology(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("topology"))?;
+
+    let filter = DeviceFilter { topology_id: Some(id), ..Default::default() };
+    let devices: Vec<Device> = state
+        .store
+        .list_devices_filtered(&filter)
+        .await?
+        .into_iter()
+        .filter(|d| d.topology_role.as_deref().is_some_and(|r| !r.is_empty() && r != topology_role::PATCH_PANEL))
+        .collect();
+    let layouts: HashMap<String, String> = state
+        .store
+        .list_device_models()
+        .await?
+        .into_iter()
+        .map(|m| (m.model, m.layout))
+        .collect();
+    let role_of: HashMap<i64, &str> = devices
+        .iter()
+        .map(|d| (d.id, d.topology_role.as_deref().unwrap_or_default()))
+        .collect();
+
+    let mut roles: BTreeMap<String, RoleCapacity> = BTreeMap::new();
+    // Per leaf: (distinct spines, links to spines); per spine: free ports
+    let mut leaf_uplinks: Vec<(usize, usize)> = Vec::new();
+    let mut spine_free: Vec<(String, usize)> = Vec::new();
+    let mut spines_without_layout = 0;
+    let mut used_asns: HashSet<u32> = HashSet::new();
+    let mut max_leaf_asn: Option<u32> = None;
+
+    for device in &devices {
+        let role = role_of[&device.id];
+        let parent = parent_role(role);
+        let layout = device.model.as_deref().and_then(|m| layouts.get(m));
+        let mut speeds = layout.map(|l| crate::utils::layout_port_speeds(l)).unwrap_or_default();
+        if let Some(layout) = layout {
+            for port in crate::utils::layout_ports_with_role(layout, "mgmt") {
+                speeds.remove(&port);
+            }
+        }
+        let assignments = state.store.list_port_assignments(device.id).await?;
+
+        let used: Vec<&PortAssignment> = assignments.iter().filter(|a| speeds.contains_key(&a.port_name)).collect();
+        let uplinks: Vec<&PortAssignment> = assignments
+            .iter()
+            .filter(|a| parent.is_some() && a.remote_device_id.and_then(|r| role_of.get(&r).copied()) == parent)
+            .collect();
+        let uplink_mbps: i64 = uplinks.iter().filter_map(|a| speeds.get(&a.port_name)).sum();
+        let downlink_mbps = speeds.values().sum::<i64>() - uplink_mbps;
+        let ports_free = speeds.len() - used.len();
+
+        let entry = roles.entry(role.to_string()).or_insert_with(|| RoleCapacity {
+            role: role.to_string(),
+            devices: 0,
+            devices_without_layout: 0,
+            ports_total: 0,
+            ports_used: 0,
+            ports_free: 0,
+            uplink_mbps: 0,
+            downlink_mbps: 0,
+            oversubscription: None,
+            worst_hostname: None,
+            worst_oversubscription: None,
+        });
+        entry.devices += 1;
+        if layout.is_none() {
+            entry.devices_without_layout += 1;
+        }
+        entry.ports_total += speeds.len();
+        entry.ports_used += used.len();
+        entry.ports_free += ports_free;
+        entry.uplink_mbps += uplink_mbps;
+        entry.downlink_mbps += downlink_mbps;
+        if let Some(device_ratio) = ratio(downlink_mbps, uplink_mbps) {
+            if entry.worst_oversubscription.is_none_or(|worst| device_ratio > worst) {
+                entry.worst_oversubscription = Some(device_ratio);
+                entry.worst_hostname = Some(device.hostname.clone());
+            }
+        }
+
+        let vars = state.store.resolve_device_variables_flat(device.id).await.unwrap_or_default();
+        let asn = vars.get("ASN").and_then(|a| a.trim().parse::<u32>().ok());
+        if let Some(asn) = asn {
+            used_asns.insert(asn);
+        }
+        match role {
+            topology_role::LEAF => {
+                let spines: HashSet<i64> = uplinks.iter().filter_map(|a| a.remote_device_id).collect();
+                leaf_uplinks.push((spines.len(), uplinks.len()));
+                if let Some(asn) = asn {
+                    max_leaf_asn = Some(max_leaf_asn.map_or(asn, |m| m.max(asn)));
+                }
+            }
+            topology_role::SPINE => {
+                if layout.is_none() {
+                    spines_without_layout += 1;
+                }
+                spine_free.push((device.hostname.clone(), ports_free));
+            }
+            _ => {}
+        }
+    }
+    for role in roles.values_mut() {
+        role.oversubscription = ratio(role.downlink_mbps, role.uplink_mbps);
+    }
+
+    let leaf_expansion = if query.add_leaves == 0 {
+        None
+    } else {
+        if query.add_leaves > MAX_ADD_LEAVES {
+            return Err(ApiError::bad_request(format!("add_leaves must be at most {}", MAX_ADD_LEAVES)));
+        }
+        if leaf_uplinks.is_empty() || spine_free.is_empty() {
+            return Err(ApiError::bad_request("add_leaves needs a topology with leaves and spines"));
+        }
+        let n = query.add_leaves;
+        let pairs: usize = leaf_uplinks.iter().map(|(spines, _)| spines).sum();
+        let links: usize = leaf_uplinks.iter().map(|(_, links)| links).sum();
+        let spines_per_leaf = ((pairs as f64 / leaf_uplinks.len() as f64).round() as usize).max(1);
+        let links_per_spine = if pairs == 0 { 1 } else { ((links as f64 / pairs as f64).round() as usize).max(1) };
+        let spine_ports_required = n * spines_per_leaf * links_per_spine;
+        let spine_ports_free: usize = spine_free.iter().map(|(_, free)| free).sum();
+
+        let mut asns = Vec::with_capacity(n);
+        let mut next = max_leaf_asn.unwrap_or(65000) + 1;
+        while asns.len() < n {
+            if !used_asns.contains(&next) {
+                asns.push(next);
+            }
+            next += 1;
+        }
+
+        let mut warnings = Vec::new();
+        if spines_without_layout > 0 {
+            warnings.push(format!("{} spines have no port layout; their free ports aren't counted", spines_without_layout));
+        }
+        // When every leaf reaches every spine, each spine needs its own share of the new links
+        let mut spines_short = false;
+        if spines_per_leaf == spine_free.len() {
+            for (hostname, free) in &spine_free {
+                if *free < n * links_per_spine {
+                    spines_short = true;
+                    warnings.push(format!("{} has {} free ports, needs {}", hostname, free, n * links_per_spine));
+                }
+            }
+        }
+        let asns_fit = asns.last().is_some_and(|asn| *asn < LEAF_ASN_LIMIT);
+        if !asns_fit {
+            warnings.push(format!("leaf ASNs would reach {}, the super-spine ASN", LEAF_ASN_LIMIT));
+        }
+
+        Some(LeafExpansion {
+            add_leaves: n,
+            spines_per_leaf,
+            links_per_spine,
+            spine_ports_required,
+            spine_ports_free,
+            leaf_uplinks_required: spines_per_leaf * links_per_spine,
+            new_p2p_links: spine_ports_required,
+            fits: spine_ports_free >= spine_ports_required && !spines_short && asns_fit,
+            asns,
+            warnings,
+        })
+    };
+
+    Ok(Json(TopologyCapacity {
+        topology_id: id,
+        roles: roles.into_values().collect(),
+        leaf_expansion,
+    }))
+}
@@ -0,0 +1,39 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::ApiError;
+
+const MAX_CHANGELOG_LIMIT: i64 = 1000;
+
+/// Chronological feed of deploys, template edits, variable changes and settings changes,
+/// newest first, filterable by device, group, user, category and time range
+pub async fn list_changelog(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(mut query): Query<ChangeLogQuery>,
+) -> Result<Json<Vec<ChangeLogEntry>>, ApiError> {
+    if let Some(ref category) = query.category {
+        let known = [
+            change_category::DEPLOY,
+            change_category::TEMPLATE,
+            change_category::VARIABLE,
+            change_category::SETTINGS,
+            change_category::AUTH,
+        ];
+        if !known.contains(&category.as_str()) {
+            return Err(ApiError::bad_request(format!(
+                "unknown category '{}': expected one of {}",
+                category,
+                known.join(", ")
+            )));
+        }
+    }
+    query.limit = query.limit.clamp(1, MAX_CHANGELOG_LIMIT);
+    Ok(Json(state.store.list_changes(&query).await?))
+}
@@ -0,0 +1,136 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use std::sync::Arc;
+
+use crate::db::{DeviceStore, RenderStore, VendorStore};
+use crate::jobs::RenderCache;
+use crate::models::*;
+use crate::AppState;
+
+use super::ApiError;
+
+/// Resolve a device's vendor to its display name (devices store the vendor ID)
+async fn device_vendor_name<S: VendorStore + ?Sized>(store: &S, device: &Device) -> Result<String, ApiError> {
+    Ok(match device.vendor.as_deref() {
+        Some(v) if !v.is_empty() => store.resolve_vendor(v).await?
+            .map(|vendor| vendor.name)
+            .unwrap_or_else(|| v.to_string()),
+        _ => String::new(),
+    })
+}
+
+fn outline(source: &str, vendor: String, content: String) -> ConfigOutline {
+    let syntax = crate::utils::config_syntax(&vendor, &content);
+    let sections = crate::utils::parse_config_sections(&content, &syntax.block_style);
+    ConfigOutline {
+        source: source.to_string(),
+        device_id: None,
+        backup_id: None,
+        vendor,
+        syntax,
+        line_count: content.lines().count(),
+        sections,
+        content,
+    }
+}
+
+/// Render a device's config and return it with syntax hints and its section structure
+pub async fn rendered_config_outline(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<ConfigOutline>, ApiError> {
+    Ok(Json(render_outline(&state.store, &state.render_cache, id).await?))
+}
+
+/// The body of `rendered_config_outline`, on any store that can render a device
+async fn render_outline<S: DeviceStore + RenderStore + ?Sized>(
+    store: &S,
+    cache: &RenderCache,
+    id: i64,
+) -> Result<ConfigOutline, ApiError> {
+    let device = store.get_device(id).await?.ok_or_else(|| ApiError::not_found("device"))?;
+    let (_, rendered) = crate::jobs::render_device(store, cache, &device)
+        .await
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+
+    let vendor = device_vendor_name(store, &device).await?;
+    let mut result = outline("rendered", vendor, rendered);
+    result.device_id = Some(device.id);
+    Ok(result)
+}
+
+/// Return a backup's content with syntax hints and its section structure
+pub async fn backup_config_outline(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<ConfigOutline>, ApiError> {
+    let backup = state
+        .store
+        .get_backup(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("backup"))?;
+    let content = crate::backup::read_backup(&state.config.backup_dir, &backup)
+        .await
+        .map_err(|_| ApiError::not_found("backup file"))?;
+
+    let vendor = match state.store.get_device(backup.device_id).await? {
+        Some(device) => device_vendor_name(&state.store, &device).await?,
+        None => String::new(),
+    };
+    let mut result = outline("backup", vendor, content);
+    result.device_id = Some(backup.device_id);
+    result.backup_id = Some(backup.id);
+    Ok(Json(result))
+}
+
+/// Outline arbitrary config text for the given vendor (by name or ID)
+pub async fn text_config_outline(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ConfigOutlineRequest>,
+) -> Result<Json<ConfigOutline>, ApiError> {
+    let vendor = if req.vendor.is_empty() {
+        String::new()
+    } else {
+        state.store.resolve_vendor(&req.vendor).await?
+            .map(|vendor| vendor.name)
+            .unwrap_or(req.vendor)
+    };
+    Ok(Json(outline("text", vendor, req.content)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memory::MemoryStore;
+
+    #[tokio::test]
+    async fn test_render_outline_on_memory_store() {
+        let store = MemoryStore::new();
+        let template = store.add_template(&serde_json::from_value(serde_json::json!({
+            "name": "leaf",
+            "content": "hostname {{ Hostname }}\ninterface Ethernet1\n   description uplink",
+        })).unwrap());
+        let vendor = store.add_vendor(&serde_json::from_value(serde_json::json!({ "name": "Arista" })).unwrap());
+        let device = store.add_device(&serde_json::from_value(serde_json::json!({
+            "hostname": "leaf1",
+            "vendor": vendor.id.to_string(),
+            "config_template": template.id.to_string(),
+        })).unwrap());
+
+        let Ok(result) = render_outline(&store, &RenderCache::new(), device.id).await else {
+            panic!("render failed");
+        };
+        assert_eq!(result.source, "rendered");
+        assert_eq!(result.device_id, Some(device.id));
+        assert_eq!(result.vendor, "Arista");
+        assert_eq!(result.content, "hostname leaf1\ninterface Ethernet1\n   description uplink");
+        assert_eq!(result.line_count, 3);
+
+        assert!(render_outline(&store, &RenderCache::new(), device.id + 100).await.is_err());
+    }
+}
@@ -1,13 +1,16 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::header,
     response::{IntoResponse, Response},
 };
+use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::utils::normalize_mac;
 use crate::AppState;
 
+use super::ApiError;
+
 /// Serve a device configuration file (HTTP config server)
 pub async fn serve_config(
     State(state): State<Arc<AppState>>,
@@ -26,27 +29,14 @@ pub async fn serve_config(
 
     match tokio::fs::read_to_string(&config_path).await {
         Ok(content) => {
-            // Try to extract MAC from filename and broadcast config_pulled event
-            if filename.ends_with(".cfg") {
-                let mac_part = filename.trim_end_matches(".cfg").replace('_', ":");
-                let mac = normalize_mac(&mac_part);
-
-                // Get device info if available
-                if let Ok(Some(device)) = state.store.get_device_by_mac(&mac).await {
-                    // Broadcast config pulled event via WebSocket
-                    if let Some(ws_hub) = &state.ws_hub {
-                        ws_hub
-                            .broadcast_config_pulled(
-                                &mac,
-                                &device.ip,
-                                &device.hostname,
-                                &filename,
-                                "http",
-                            )
-                            .await;
-                    }
-                }
-            }
+            crate::services::config_pull::on_config_pulled(
+                &state.store,
+                state.ws_hub.as_ref(),
+                &filename,
+                None,
+                "http",
+            )
+            .await;
 
             (
                 [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
@@ -57,3 +47,63 @@ pub async fn serve_config(
         Err(_) => (axum::http::StatusCode::NOT_FOUND, "Config not found").into_response(),
     }
 }
+
+#[derive(Debug, Deserialize)]
+pub struct ZtpConfigQuery {
+    #[serde(default)]
+    pub token: String,
+    /// Serve the config without the vendor's ZTP wrapper
+    #[serde(default)]
+    pub raw: bool,
+}
+
+/// Render a device's config on the fly for HTTP ZTP, wrapped in its vendor's bootstrap
+/// wrapper if it has one. Authorized by the device's token rather than a login.
+pub async fn serve_ztp_config(
+    State(state): State<Arc<AppState>>,
+    Path(mac): Path<String>,
+    Query(query): Query<ZtpConfigQuery>,
+) -> Result<Response, ApiError> {
+    let mac = normalize_mac(&mac);
+    if !crate::services::ztp::verify_token(&state.config.jwt_secret, &mac, &query.token) {
+        return Err(ApiError::forbidden("invalid ZTP token"));
+    }
+    let device = state
+        .store
+        .get_device_by_mac(&mac)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+
+    let (_, config) = crate::jobs::render_device(&state.store, &state.render_cache, &device)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    let wrapper = match device.vendor.as_deref() {
+        Some(v) if !v.is_empty() && !query.raw => state
+            .store
+            .resolve_vendor(v)
+            .await?
+            .map(|vendor| vendor.ztp_wrapper)
+            .unwrap_or_default(),
+        _ => String::new(),
+    };
+    let body = if wrapper.trim().is_empty() {
+        config
+    } else {
+        let settings = state.store.get_settings().await?;
+        let vars = state.store.resolve_device_variables_flat(device.id).await.unwrap_or_default();
+        crate::services::ztp::wrap_config(&wrapper, &device, &settings, &vars, &config)
+            .map_err(|e| ApiError::internal(e.to_string()))?
+    };
+
+    crate::services::config_pull::record_config_pull(
+        &state.store,
+        state.ws_hub.as_ref(),
+        &device,
+        &format!("/ztp/config/{}", mac),
+        None,
+        "http",
+    )
+    .await;
+
+    Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], body).into_response())
+}
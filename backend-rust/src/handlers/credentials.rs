@@ -1,15 +1,28 @@
 use std::sync::Arc;
-use axum::{extract::{Path, State}, http::StatusCode, Json};
-use crate::{models::*, handlers::ApiError, AppState};
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use crate::{models::*, handlers::{ApiError, ValidJson}, AppState};
+use crate::handlers::tags::{retain_tagged, TagFilterQuery};
 
 fn created<T: serde::Serialize>(item: T) -> (StatusCode, Json<T>) {
     (StatusCode::CREATED, Json(item))
 }
 
+/// Reject a device or group binding to a credential that doesn't exist
+pub(crate) async fn check_credential_binding(state: &AppState, credential_id: Option<i64>) -> Result<(), ApiError> {
+    if let Some(id) = credential_id {
+        if state.store.get_credential(id).await?.is_none() {
+            return Err(ApiError::bad_request("credential_id does not match a credential"));
+        }
+    }
+    Ok(())
+}
+
 pub async fn list_credentials(
     State(state): State<Arc<AppState>>,
+    Query(tag): Query<TagFilterQuery>,
 ) -> Result<Json<Vec<Credential>>, ApiError> {
     let credentials = state.store.list_credentials().await?;
+    let credentials = retain_tagged(&state, tag_resource::CREDENTIAL, tag.tag.as_deref(), credentials, |c| c.id.to_string()).await?;
     Ok(Json(credentials))
 }
 
@@ -25,11 +38,8 @@ pub async fn get_credential(
 pub async fn create_credential(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateCredentialRequest>,
+    ValidJson(req): ValidJson<CreateCredentialRequest>,
 ) -> Result<(StatusCode, Json<Credential>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
     let credential = state.store.create_credential(&req).await?;
     Ok(created(credential))
 }
@@ -38,7 +48,7 @@ pub async fn update_credential(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateCredentialRequest>,
+    ValidJson(req): ValidJson<CreateCredentialRequest>,
 ) -> Result<Json<Credential>, ApiError> {
     let credential = state.store.update_credential(id, &req).await?;
     Ok(Json(credential))
@@ -52,3 +62,98 @@ pub async fn delete_credential(
     state.store.delete_credential(id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Usage records kept in a usage report
+const CREDENTIAL_USAGE_LIMIT: i64 = 100;
+
+/// Usage counts and staleness warnings for every credential
+pub async fn audit_credentials(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<CredentialAudit>>, ApiError> {
+    let settings = state.store.get_settings().await?;
+    let audits = state.store.audit_credentials(None, settings.credential_max_age_days as i64).await?;
+    Ok(Json(audits))
+}
+
+/// Which devices and jobs used a credential, and when it was last used
+pub async fn get_credential_usage(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<CredentialUsageReport>, ApiError> {
+    let settings = state.store.get_settings().await?;
+    let audit = state.store.audit_credentials(Some(id), settings.credential_max_age_days as i64).await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| ApiError::not_found("Credential"))?;
+    let recent = state.store.list_credential_usage(id, CREDENTIAL_USAGE_LIMIT).await?;
+    Ok(Json(CredentialUsageReport { audit, recent }))
+}
+
+/// Set a new password, optionally queueing jobs that change it on devices.
+/// Push jobs log in with the replaced password.
+pub async fn rotate_credential(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<RotateCredentialRequest>,
+) -> Result<Json<RotateCredentialResponse>, ApiError> {
+    if req.password.is_empty() {
+        return Err(ApiError::bad_request("password is required"));
+    }
+    let existing = state.store.get_credential(id).await?
+        .ok_or_else(|| ApiError::not_found("Credential"))?;
+    if req.password == existing.password {
+        return Err(ApiError::bad_request("new password must differ from the current one"));
+    }
+    if req.push && existing.username.is_empty() {
+        return Err(ApiError::bad_request("credential has no username to push a password for"));
+    }
+    if req.push {
+        crate::jobs::validate_password_push(&existing.username, &req.password).map_err(ApiError::bad_request)?;
+    }
+
+    // Resolve push targets before rotating so a bad device id leaves the credential untouched
+    let mut devices = Vec::new();
+    if req.push {
+        let device_ids = if req.device_ids.is_empty() {
+            state.store.credential_used_device_ids(id).await?
+        } else {
+            req.device_ids.clone()
+        };
+        for device_id in device_ids {
+            let device = state.store.get_device(device_id).await?
+                .ok_or_else(|| ApiError::not_found("Device"))?;
+            devices.push(device);
+        }
+    }
+
+    super::jobs::require_queue_capacity(&state, devices.len())?;
+    let credential = state.store.rotate_credential(id, &req.password).await?;
+
+    let mut jobs = Vec::with_capacity(devices.len());
+    for device in devices {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        // The job holds the credential reference; the worker reads the password when it runs
+        let job_req = CreateJobRequest {
+            device_id: device.id,
+            job_type: job_type::PASSWORD_PUSH.to_string(),
+            command: credential.username.clone(),
+            credential_id: id.to_string(),
+            triggered_by: TRIGGERED_BY_CREDENTIAL_ROTATION.to_string(),
+            timeout_secs: None,
+        };
+        let job = state.store.create_job(&job_id, &job_req).await?;
+
+        if let Some(ref hub) = state.ws_hub {
+            hub.broadcast_job_update(crate::ws::EventType::JobQueued, &job).await;
+        }
+        if let Some(ref job_service) = state.job_service {
+            job_service.submit(job_id).await;
+        }
+        jobs.push(job);
+    }
+
+    Ok(Json(RotateCredentialResponse { credential, jobs }))
+}
@@ -0,0 +1,190 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+/// Validate `values` (by field name; null or "" clears) against the field definitions, returning
+/// the stored form of each. Clearing a required field is refused.
+pub(crate) fn resolve_values(
+    fields: &[CustomField],
+    values: &BTreeMap<String, serde_json::Value>,
+) -> Result<Vec<(i64, Option<String>)>, String> {
+    let mut resolved = Vec::with_capacity(values.len());
+    for (name, value) in values {
+        let field = fields
+            .iter()
+            .find(|f| &f.name == name)
+            .ok_or_else(|| format!("unknown custom field '{}'", name))?;
+        let cleared = match value {
+            serde_json::Value::Null => true,
+            serde_json::Value::String(s) => s.trim().is_empty(),
+            _ => false,
+        };
+        if cleared {
+            if field.required {
+                return Err(format!("{}: a value is required", name));
+            }
+            resolved.push((field.id, None));
+        } else {
+            resolved.push((field.id, Some(field.normalize(value)?)));
+        }
+    }
+    Ok(resolved)
+}
+
+/// Check a filter's custom field predicates name known fields, and bring eq/ne values into the
+/// stored form so `yes` matches a boolean stored as `true`
+pub(crate) async fn normalize_filter(state: &AppState, filter: &mut DeviceFilter) -> Result<(), ApiError> {
+    if filter.custom_fields.is_empty() {
+        return Ok(());
+    }
+    let fields = state.store.list_custom_fields().await?;
+    for pred in &mut filter.custom_fields {
+        let field = fields
+            .iter()
+            .find(|f| f.name == pred.key)
+            .ok_or_else(|| ApiError::bad_request(format!("unknown custom field '{}'", pred.key)))?;
+        if !variable_op::is_valid(&pred.op) {
+            return Err(ApiError::bad_request(format!(
+                "invalid custom field op '{}': must be one of eq, ne, contains, exists, missing",
+                pred.op
+            )));
+        }
+        if matches!(pred.op.as_str(), variable_op::EQ | variable_op::NE) {
+            pred.value = field.parse_text(&pred.value).map_err(ApiError::bad_request)?;
+        }
+    }
+    Ok(())
+}
+
+/// List custom field definitions
+pub async fn list_custom_fields(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<CustomField>>, ApiError> {
+    Ok(Json(state.store.list_custom_fields().await?))
+}
+
+/// Get a single custom field definition by ID
+pub async fn get_custom_field(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<CustomField>, ApiError> {
+    let field = state
+        .store
+        .get_custom_field(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("custom field"))?;
+    Ok(Json(field))
+}
+
+/// Create a custom field definition
+pub async fn create_custom_field(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateCustomFieldRequest>,
+) -> Result<(StatusCode, Json<CustomField>), ApiError> {
+    if state.store.list_custom_fields().await?.iter().any(|f| f.name == req.name) {
+        return Err(ApiError::conflict(format!("custom field '{}' already exists", req.name)));
+    }
+    Ok(created(state.store.create_custom_field(&req).await?))
+}
+
+/// Update a custom field definition. Existing values must still be valid under the new
+/// definition; they are rewritten into its stored form (e.g. `1` becomes `true` for a boolean).
+pub async fn update_custom_field(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateCustomFieldRequest>,
+) -> Result<Json<CustomField>, ApiError> {
+    let current = state
+        .store
+        .get_custom_field(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("custom field"))?;
+    if state.store.list_custom_fields().await?.iter().any(|f| f.id != id && f.name == req.name) {
+        return Err(ApiError::conflict(format!("custom field '{}' already exists", req.name)));
+    }
+
+    let updated = CustomField {
+        name: req.name.clone(),
+        field_type: req.field_type.clone(),
+        choices: req.choices.clone(),
+        pattern: req.pattern.clone(),
+        min_value: req.min_value,
+        max_value: req.max_value,
+        ..current
+    };
+    let mut rewrites = Vec::new();
+    for (device_id, value) in state.store.list_custom_field_values(id).await? {
+        let normalized = updated
+            .parse_text(&value)
+            .map_err(|e| ApiError::bad_request(format!("device {} has an incompatible value: {}", device_id, e)))?;
+        if normalized != value {
+            rewrites.push((device_id, normalized));
+        }
+    }
+
+    let field = state.store.update_custom_field(id, &req).await?;
+    for (device_id, value) in rewrites {
+        state.store.set_device_custom_field_values(device_id, &[(id, Some(value))]).await?;
+    }
+    Ok(Json(field))
+}
+
+/// Delete a custom field definition and every device's value for it
+pub async fn delete_custom_field(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_custom_field(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A device's custom field values, by field name
+pub async fn get_device_custom_fields(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<BTreeMap<String, serde_json::Value>>, ApiError> {
+    let device = state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    Ok(Json(device.custom_fields))
+}
+
+/// Set a device's custom field values. Fields not named are left unchanged; null clears one.
+pub async fn set_device_custom_fields(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<SetCustomFieldValuesRequest>,
+) -> Result<Json<BTreeMap<String, serde_json::Value>>, ApiError> {
+    super::teams::require_modify(&state, &auth, team_resource_type::DEVICE, id).await?;
+    if state.store.get_device(id).await?.is_none() {
+        return Err(ApiError::not_found("device"));
+    }
+    let fields = state.store.list_custom_fields().await?;
+    let values = resolve_values(&fields, &req.values).map_err(ApiError::bad_request)?;
+    state.store.set_device_custom_field_values(id, &values).await?;
+
+    let device = state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    Ok(Json(device.custom_fields))
+}
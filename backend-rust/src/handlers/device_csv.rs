@@ -0,0 +1,259 @@
+//! Device CSV export and import. Custom fields appear as `cf_<name>` columns after the device
+//! columns, so a spreadsheet can round-trip asset tags, cost centers and the like.
+
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::auth::AuthUser;
+use crate::models::*;
+use crate::utils::{is_valid_hostname, is_valid_ipv4, normalize_mac, normalize_serial, parse_csv, parse_mac};
+use crate::AppState;
+
+use super::reports::csv_field;
+use super::{ApiError, ValidJson};
+
+const DEVICE_COLUMNS: &[&str] = &[
+    "hostname", "mac", "ip", "vendor", "model", "serial_number", "template", "topology_role", "device_type",
+];
+const CUSTOM_FIELD_PREFIX: &str = "cf_";
+
+/// A custom field value in its stored text form
+fn custom_field_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Export every device as CSV, one `cf_<name>` column per custom field
+pub async fn export_devices_csv(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response, ApiError> {
+    let devices = state.store.list_devices().await?;
+    let fields = state.store.list_custom_fields().await?;
+    let templates: HashMap<String, String> = state
+        .store
+        .list_templates()
+        .await?
+        .into_iter()
+        .map(|t| (t.id.to_string(), t.name))
+        .collect();
+
+    let mut header_row: Vec<String> = DEVICE_COLUMNS.iter().map(|c| c.to_string()).collect();
+    header_row.extend(fields.iter().map(|f| format!("{}{}", CUSTOM_FIELD_PREFIX, f.name)));
+    let mut out = header_row.join(",");
+    out.push('\n');
+    for device in &devices {
+        let mut row = vec![
+            device.hostname.clone(),
+            device.mac.clone().unwrap_or_default(),
+            device.ip.clone(),
+            device.vendor.clone().unwrap_or_default(),
+            device.model.clone().unwrap_or_default(),
+            device.serial_number.clone().unwrap_or_default(),
+            templates.get(&device.config_template).cloned().unwrap_or_default(),
+            device.topology_role.clone().unwrap_or_default(),
+            device.device_type.clone(),
+        ];
+        row.extend(fields.iter().map(|f| device.custom_fields.get(&f.name).map(custom_field_text).unwrap_or_default()));
+        out.push_str(&row.iter().map(|v| csv_field(v)).collect::<Vec<_>>().join(","));
+        out.push('\n');
+    }
+
+    let filename = format!("devices-{}.csv", chrono::Utc::now().format("%Y%m%d"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        out,
+    )
+        .into_response())
+}
+
+/// One validated CSV row, ready to commit
+struct ImportRow {
+    existing: Option<Device>,
+    cells: HashMap<&'static str, String>,
+    template: Option<String>,
+    values: Vec<(i64, Option<String>)>,
+}
+
+/// Import devices from CSV (with a header row). Rows whose hostname exists update that device,
+/// others create one. Empty cells leave the current value unchanged. Every row is validated
+/// before anything is written.
+pub async fn import_devices_csv(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    body: String,
+) -> Result<Json<DeviceImportResult>, ApiError> {
+    let mut rows = parse_csv(&body).map_err(ApiError::bad_request)?.into_iter();
+    let header_row = rows.next().ok_or_else(|| ApiError::bad_request("CSV is empty"))?;
+    let fields = state.store.list_custom_fields().await?;
+
+    // Map each column to a device column or a custom field
+    let mut device_columns: Vec<(usize, &'static str)> = Vec::new();
+    let mut field_columns: Vec<(usize, &CustomField)> = Vec::new();
+    for (i, name) in header_row.iter().enumerate() {
+        let name = name.trim();
+        if let Some(column) = DEVICE_COLUMNS.iter().find(|c| **c == name) {
+            device_columns.push((i, column));
+        } else if let Some(field) = name
+            .strip_prefix(CUSTOM_FIELD_PREFIX)
+            .and_then(|n| fields.iter().find(|f| f.name == n))
+        {
+            field_columns.push((i, field));
+        } else {
+            return Err(ApiError::bad_request(format!("unknown column '{}'", name)));
+        }
+    }
+    if !device_columns.iter().any(|(_, c)| *c == "hostname") {
+        return Err(ApiError::bad_request("a hostname column is required"));
+    }
+
+    let existing: HashMap<String, Device> = state
+        .store
+        .list_devices()
+        .await?
+        .into_iter()
+        .map(|d| (d.hostname.clone(), d))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut seen_serials = HashSet::new();
+    let mut planned = Vec::new();
+    // The header is row 1
+    for (row_number, row) in (2..).zip(rows) {
+        let cell = |i: usize| row.get(i).map(|v| v.trim()).unwrap_or("");
+        let cells: HashMap<&'static str, String> = device_columns
+            .iter()
+            .map(|(i, column)| (*column, cell(*i).to_string()))
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+        let row_error = |msg: String| ApiError::bad_request(format!("row {}: {}", row_number, msg));
+
+        let hostname = cells.get("hostname").cloned().unwrap_or_default();
+        if !is_valid_hostname(&hostname) {
+            return Err(row_error(format!("invalid hostname '{}'", hostname)));
+        }
+        if !seen.insert(hostname.clone()) {
+            return Err(row_error(format!("duplicate hostname '{}'", hostname)));
+        }
+        if let Some(ip) = cells.get("ip").filter(|ip| !is_valid_ipv4(ip)) {
+            return Err(row_error(format!("invalid IPv4 address '{}'", ip)));
+        }
+        if let Some(serial) = cells.get("serial_number").filter(|s| !seen_serials.insert(normalize_serial(s))) {
+            return Err(row_error(format!("duplicate serial_number '{}'", serial)));
+        }
+        if let Some(mac) = cells.get("mac").filter(|mac| parse_mac(mac).is_none()) {
+            return Err(row_error(format!("invalid MAC address '{}'", mac)));
+        }
+        if let Some(role) = cells.get("topology_role").filter(|r| !topology_role::is_valid(r)) {
+            return Err(row_error(format!("invalid topology_role '{}'", role)));
+        }
+        let template = match cells.get("template") {
+            Some(name) => Some(
+                state
+                    .store
+                    .get_template_by_name(name)
+                    .await?
+                    .ok_or_else(|| row_error(format!("unknown template '{}'", name)))?
+                    .id
+                    .to_string(),
+            ),
+            None => None,
+        };
+
+        let current = existing.get(&hostname).cloned();
+        // The API has no way to change a device's MAC
+        if let (Some(device), Some(mac)) = (&current, cells.get("mac")) {
+            if device.mac.as_deref() != Some(normalize_mac(mac).as_str()) {
+                return Err(row_error(format!("mac cannot be changed from {}", device.mac.as_deref().unwrap_or("none"))));
+            }
+        }
+        let mut raw_values = BTreeMap::new();
+        for (i, field) in &field_columns {
+            let value = cell(*i);
+            if !value.is_empty() {
+                raw_values.insert(field.name.clone(), serde_json::Value::String(value.to_string()));
+            }
+        }
+        if current.is_none() {
+            if let Some(field) = fields.iter().find(|f| f.required && !raw_values.contains_key(&f.name)) {
+                return Err(row_error(format!("{}: a value is required", field.name)));
+            }
+        }
+        let values = super::custom_fields::resolve_values(&fields, &raw_values)
+            .map_err(row_error)?;
+
+        planned.push(ImportRow { existing: current, cells, template, values });
+    }
+
+    let mut result = DeviceImportResult { created: 0, updated: 0 };
+    for row in planned {
+        let cell = |column: &str| row.cells.get(column).cloned();
+        let id = match row.existing {
+            None => {
+                let req = CreateDeviceRequest {
+                    mac: cell("mac").unwrap_or_default(),
+                    ip: cell("ip").unwrap_or_default(),
+                    hostname: cell("hostname").unwrap_or_default(),
+                    vendor: cell("vendor"),
+                    model: cell("model"),
+                    serial_number: cell("serial_number"),
+                    config_template: row.template.unwrap_or_default(),
+                    credential_id: None,
+                    ssh_user: None,
+                    ssh_pass: None,
+                    topology_id: None,
+                    topology_role: cell("topology_role"),
+                    hall_id: None,
+                    row_id: None,
+                    rack_id: None,
+                    rack_position: None,
+                    device_type: cell("device_type"),
+                };
+                let (_, Json(device)) = super::devices::create_device(forward(&auth), State(state.clone()), ValidJson::validated(req)?).await?;
+                result.created += 1;
+                device.id
+            }
+            Some(current) => {
+                let req = UpdateDeviceRequest {
+                    ip: cell("ip").unwrap_or_else(|| current.ip.clone()),
+                    hostname: current.hostname.clone(),
+                    vendor: cell("vendor").or_else(|| current.vendor_id.clone()),
+                    model: cell("model").or_else(|| current.model.clone()),
+                    serial_number: cell("serial_number").or_else(|| current.serial_number.clone()),
+                    config_template: row.template.unwrap_or_else(|| current.config_template.clone()),
+                    credential_id: current.credential_id,
+                    ssh_user: current.ssh_user.clone(),
+                    ssh_pass: current.ssh_pass.clone(),
+                    topology_id: current.topology_id,
+                    topology_role: cell("topology_role").or_else(|| current.topology_role.clone()),
+                    hall_id: current.hall_id,
+                    row_id: current.row_id,
+                    rack_id: current.rack_id,
+                    rack_position: current.rack_position,
+                    device_type: cell("device_type").or_else(|| Some(current.device_type.clone())),
+                };
+                let _ = super::devices::update_device(forward(&auth), State(state.clone()), axum::extract::Path(current.id), ValidJson::validated(req)?).await?;
+                result.updated += 1;
+                current.id
+            }
+        };
+        if !row.values.is_empty() {
+            state.store.set_device_custom_field_values(id, &row.values).await?;
+        }
+    }
+    Ok(Json(result))
+}
+
+fn forward(auth: &AuthUser) -> AuthUser {
+    AuthUser { claims: auth.claims.clone() }
+}
@@ -7,7 +7,7 @@ use std::sync::Arc;
 use crate::models::*;
 use crate::AppState;
 
-use super::{created, ApiError};
+use super::{created, ApiError, ValidJson};
 
 /// List all device models
 pub async fn list_device_models(
@@ -36,12 +36,8 @@ pub async fn get_device_model(
 pub async fn create_device_model(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateDeviceModelRequest>,
+    ValidJson(req): ValidJson<CreateDeviceModelRequest>,
 ) -> Result<(axum::http::StatusCode, Json<DeviceModel>), ApiError> {
-    if req.vendor_id == 0 || req.model.is_empty() || req.display_name.is_empty() {
-        return Err(ApiError::bad_request("vendor_id, model, and display_name are required"));
-    }
-
     let model = state.store.create_device_model(&req).await?;
     Ok(created(model))
 }
@@ -51,7 +47,7 @@ pub async fn update_device_model(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateDeviceModelRequest>,
+    ValidJson(req): ValidJson<CreateDeviceModelRequest>,
 ) -> Result<Json<DeviceModel>, ApiError> {
     let model = state.store.update_device_model(id, &req).await?;
     Ok(Json(model))
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 use axum::{extract::{Path, State}, http::StatusCode, Json};
-use crate::{models::*, handlers::ApiError, AppState};
+use crate::{models::*, handlers::{ApiError, ValidJson}, AppState};
 
 fn created<T: serde::Serialize>(item: T) -> (StatusCode, Json<T>) {
     (StatusCode::CREATED, Json(item))
@@ -25,11 +25,8 @@ pub async fn get_device_role(
 pub async fn create_device_role(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateDeviceRoleRequest>,
+    ValidJson(req): ValidJson<CreateDeviceRoleRequest>,
 ) -> Result<(StatusCode, Json<DeviceRole>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
     let role = state.store.create_device_role(&req).await?;
     Ok(created(role))
 }
@@ -38,7 +35,7 @@ pub async fn update_device_role(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateDeviceRoleRequest>,
+    ValidJson(req): ValidJson<CreateDeviceRoleRequest>,
 ) -> Result<Json<DeviceRole>, ApiError> {
     let role = state.store.update_device_role(id, &req).await?;
     Ok(Json(role))
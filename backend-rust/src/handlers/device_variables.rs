@@ -3,11 +3,13 @@ use axum::{
     Json,
 };
 use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
+use crate::models::{change_action, NewChangeLogEntry};
 use crate::AppState;
 
-use super::ApiError;
+use super::{record_change, ApiError};
 
 /// List all variables for a device
 pub async fn list_device_variables(
@@ -26,7 +28,7 @@ pub struct SetVariablesRequest {
 
 /// Bulk set variables for a device (replaces all)
 pub async fn set_device_variables(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
     Json(req): Json<SetVariablesRequest>,
@@ -36,6 +38,10 @@ pub async fn set_device_variables(
     for (key, value) in &req.variables {
         state.store.set_device_variable(id, key, value).await?;
     }
+    let mut keys: Vec<&str> = req.variables.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    let entry = NewChangeLogEntry::variable(change_action::UPDATE, "", format!("replaced variables: {}", keys.join(", ")));
+    record_change(&state, &auth, NewChangeLogEntry { device_id: Some(id), ..entry }).await;
 
     let vars = state.store.list_device_variables(id).await?;
     Ok(Json(vars))
@@ -48,22 +54,26 @@ pub struct SetVariableRequest {
 
 /// Set a single variable for a device
 pub async fn set_device_variable(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path((id, key)): Path<(i64, String)>,
     Json(req): Json<SetVariableRequest>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     state.store.set_device_variable(id, &key, &req.value).await?;
+    let entry = NewChangeLogEntry::variable(change_action::UPDATE, &key, format!("set {}", key));
+    record_change(&state, &auth, NewChangeLogEntry { device_id: Some(id), ..entry }).await;
     Ok(Json(serde_json::json!({"message": "variable set"})))
 }
 
 /// Delete a single variable for a device
 pub async fn delete_device_variable(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path((id, key)): Path<(i64, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     state.store.delete_device_variable(id, &key).await?;
+    let entry = NewChangeLogEntry::variable(change_action::DELETE, &key, format!("deleted {}", key));
+    record_change(&state, &auth, NewChangeLogEntry { device_id: Some(id), ..entry }).await;
     Ok(Json(serde_json::json!({"message": "variable deleted"})))
 }
 
@@ -105,24 +115,56 @@ pub struct BulkSetEntry {
 
 #[derive(Deserialize)]
 pub struct BulkSetRequest {
+    #[serde(default)]
     pub entries: Vec<BulkSetEntry>,
+    /// Apply `variables` to every device matched by this saved search
+    #[serde(default)]
+    pub saved_search_id: Option<i64>,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
 }
 
 /// Bulk set variables across multiple devices
 pub async fn bulk_set_variables(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Json(req): Json<BulkSetRequest>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let entries: Vec<(i64, String, String)> = req
+    let mut entries: Vec<(i64, String, String)> = req
         .entries
         .into_iter()
         .map(|e| (e.device_id, e.key, e.value))
         .collect();
 
+    if let Some(search_id) = req.saved_search_id {
+        let search = state
+            .store
+            .get_saved_search(search_id)
+            .await?
+            .ok_or_else(|| ApiError::not_found("saved search"))?;
+        let devices = state.store.list_devices_filtered(&search.filter).await?;
+        for device in &devices {
+            for (key, value) in &req.variables {
+                entries.push((device.id, key.clone(), value.clone()));
+            }
+        }
+    }
+
     let count = entries.len();
     state.store.bulk_set_device_variables(&entries).await?;
 
+    // One entry per device so the feed can be filtered by device or group
+    let mut by_device: BTreeMap<i64, Vec<&str>> = BTreeMap::new();
+    for (device_id, key, _) in &entries {
+        by_device.entry(*device_id).or_default().push(key);
+    }
+    for (device_id, mut keys) in by_device {
+        keys.sort_unstable();
+        keys.dedup();
+        let entry = NewChangeLogEntry::variable(change_action::UPDATE, "", format!("bulk set {}", keys.join(", ")));
+        record_change(&state, &auth, NewChangeLogEntry { device_id: Some(device_id), ..entry }).await;
+    }
+
     Ok(Json(serde_json::json!({
         "message": format!("{} variables set", count),
         "count": count,
@@ -131,10 +173,12 @@ pub async fn bulk_set_variables(
 
 /// Delete a key from all devices
 pub async fn delete_variable_key(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(key): Path<String>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     state.store.delete_variable_key(&key).await?;
+    let entry = NewChangeLogEntry::variable(change_action::DELETE, &key, format!("deleted {} from all devices", key));
+    record_change(&state, &auth, entry).await;
     Ok(Json(serde_json::json!({"message": "key deleted from all devices"})))
 }
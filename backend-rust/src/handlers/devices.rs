@@ -1,28 +1,87 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
+    response::{IntoResponse, Response},
     Json,
 };
 use std::sync::Arc;
-use tera::{Context, Tera};
-use tokio::process::Command;
 
 use serde::Deserialize;
 
 use crate::models::*;
-use crate::utils::{normalize_mac, is_valid_ipv4, is_valid_hostname};
+use crate::utils::{mac_forms, normalize_mac, is_valid_ipv4};
 use crate::AppState;
 
-use super::{created, trigger_reload, ApiError, PaginationQuery};
+use super::tags::TagFilterQuery;
+use super::{created, record_change, trigger_reload, ApiError, PaginationQuery, ValidJson};
 
-/// List all devices (with optional pagination)
+/// `wait=true` runs a connect test in the request, the blocking behavior from before connect
+/// tests were jobs
+#[derive(Debug, Default, Deserialize)]
+pub struct ConnectQuery {
+    #[serde(default)]
+    pub wait: bool,
+}
+
+/// Query parameter filtering the device list by a custom field
+#[derive(Debug, Default, Deserialize)]
+pub struct CustomFieldFilterQuery {
+    /// `name` (has a value) or `name=value`
+    #[serde(default)]
+    pub custom_field: Option<String>,
+}
+
+/// Query parameters filtering the device list by tenant or GPU cluster
+#[derive(Debug, Default, Deserialize)]
+pub struct AssignmentFilterQuery {
+    #[serde(default)]
+    pub tenant_id: Option<i64>,
+    #[serde(default)]
+    pub gpu_cluster_id: Option<i64>,
+}
+
+/// List all devices (with optional pagination, tag, custom field, tenant and cluster filters)
 pub async fn list_devices(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Query(page): Query<PaginationQuery>,
+    Query(tag): Query<TagFilterQuery>,
+    Query(field): Query<CustomFieldFilterQuery>,
+    Query(assignment): Query<AssignmentFilterQuery>,
 ) -> Result<Json<Vec<Device>>, ApiError> {
     let (limit, offset) = page.sanitize();
-    let devices = state.store.list_devices_paged(limit, offset).await?;
+    if tag.tag.is_none()
+        && field.custom_field.is_none()
+        && assignment.tenant_id.is_none()
+        && assignment.gpu_cluster_id.is_none()
+    {
+        return Ok(Json(state.store.list_devices_paged(limit, offset).await?));
+    }
+
+    let mut filter = DeviceFilter {
+        tenant_id: assignment.tenant_id,
+        gpu_cluster_id: assignment.gpu_cluster_id,
+        ..Default::default()
+    };
+    if let Some(tag) = tag.tag {
+        super::tags::parse_tag_selector(&tag)?;
+        filter.tags.push(tag);
+    }
+    if let Some(selector) = field.custom_field {
+        let selector = TagSelector::parse(&selector)
+            .ok_or_else(|| ApiError::bad_request("custom_field must be 'name' or 'name=value'"))?;
+        filter.custom_fields.push(VariablePredicate {
+            key: selector.key,
+            op: if selector.value.is_some() { variable_op::EQ } else { variable_op::EXISTS }.to_string(),
+            value: selector.value.unwrap_or_default(),
+        });
+        super::custom_fields::normalize_filter(&state, &mut filter).await?;
+    }
+    let devices = state.store.list_devices_filtered(&filter).await?
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect();
     Ok(Json(devices))
 }
 
@@ -44,30 +103,14 @@ pub async fn get_device(
 pub async fn create_device(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(mut req): Json<CreateDeviceRequest>,
+    ValidJson(mut req): ValidJson<CreateDeviceRequest>,
 ) -> Result<(axum::http::StatusCode, Json<Device>), ApiError> {
-    // Normalize MAC if provided and non-empty
+    // Store the MAC canonically; one already on a device, in any notation or width, is a conflict
     if !req.mac.is_empty() {
         req.mac = normalize_mac(&req.mac);
+        check_mac_unused(&state, &req.mac, None).await?;
     }
-
-    if req.hostname.is_empty() {
-        return Err(ApiError::bad_request("hostname is required"));
-    }
-    // Validate IP only if non-empty (patch panels may have no IP)
-    if !req.ip.is_empty() && !is_valid_ipv4(&req.ip) {
-        return Err(ApiError::bad_request("invalid IPv4 address"));
-    }
-    if !is_valid_hostname(&req.hostname) {
-        return Err(ApiError::bad_request("invalid hostname: only alphanumeric, hyphens, dots, and underscores allowed"));
-    }
-
-    // Validate topology_role if provided
-    if let Some(ref role) = req.topology_role {
-        if !crate::models::topology_role::is_valid(role) {
-            return Err(ApiError::bad_request("topology_role must be one of: super-spine, spine, leaf, core, distribution, access, gpu-node, patch panel"));
-        }
-    }
+    check_serial_unused(&state, req.serial_number.as_deref(), None).await?;
 
     // Resolve vendor name to ID if the value isn't already a numeric ID
     if let Some(ref vendor_val) = req.vendor {
@@ -78,6 +121,10 @@ pub async fn create_device(
         }
     }
 
+    super::credentials::check_credential_binding(&state, req.credential_id).await?;
+    super::ipam::check_rack_placement(&state, None, req.rack_id, req.rack_position, req.vendor.as_deref(), req.model.as_deref()).await?;
+    warn_inline_password(&req.hostname, req.ssh_pass.as_deref());
+
     let device = state.store.create_device(&req).await?;
 
     // Remove from discovered_devices since it's now a configured device
@@ -89,18 +136,12 @@ pub async fn create_device(
 
 /// Update an existing device
 pub async fn update_device(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(mut req): Json<UpdateDeviceRequest>,
+    ValidJson(mut req): ValidJson<UpdateDeviceRequest>,
 ) -> Result<Json<Device>, ApiError> {
-    // Validate topology_role if provided
-    if let Some(ref role) = req.topology_role {
-        if !crate::models::topology_role::is_valid(role) {
-            return Err(ApiError::bad_request("topology_role must be one of: super-spine, spine, leaf, core, distribution, access, gpu-node, patch panel"));
-        }
-    }
-
+    super::teams::require_modify(&state, &auth, team_resource_type::DEVICE, id).await?;
     // Resolve vendor name to ID if the value isn't already a numeric ID
     if let Some(ref vendor_val) = req.vendor {
         if !vendor_val.is_empty() && vendor_val.parse::<i64>().is_err() {
@@ -110,67 +151,184 @@ pub async fn update_device(
         }
     }
 
+    check_serial_unused(&state, req.serial_number.as_deref(), Some(id)).await?;
+    super::credentials::check_credential_binding(&state, req.credential_id).await?;
+    super::ipam::check_rack_placement(&state, Some(id), req.rack_id, req.rack_position, req.vendor.as_deref(), req.model.as_deref()).await?;
+    warn_inline_password(&req.hostname, req.ssh_pass.as_deref());
+
     let device = state.store.update_device(id, &req).await?;
     trigger_reload(&state).await;
     Ok(Json(device))
 }
 
+/// Reject a MAC already used by another device, as either the MAC or the EUI-64 built from it
+async fn check_mac_unused(state: &AppState, mac: &str, device_id: Option<i64>) -> Result<(), ApiError> {
+    for form in mac_forms(mac) {
+        if let Some(device) = state.store.get_device_by_mac(&form).await?.filter(|d| Some(d.id) != device_id) {
+            return Err(ApiError::conflict(format!("MAC {} is already used by device {}", mac, device.hostname)));
+        }
+    }
+    Ok(())
+}
+
+/// Serial numbers identify devices alongside MACs, so one can't be on two devices
+pub(super) async fn check_serial_unused(state: &AppState, serial: Option<&str>, device_id: Option<i64>) -> Result<(), ApiError> {
+    let Some(serial) = serial.filter(|s| !s.trim().is_empty()) else {
+        return Ok(());
+    };
+    if let Some(device) = state.store.get_device_by_serial(serial).await?.filter(|d| Some(d.id) != device_id) {
+        return Err(ApiError::conflict(format!("Serial number {} is already used by device {}", serial.trim(), device.hostname)));
+    }
+    Ok(())
+}
+
+/// Move a device to a new MAC, e.g. when a discovered device matched it by serial number after a
+/// supervisor swap, or for RMA replacement hardware. Its ZTP config and DHCP reservation follow.
+pub async fn change_device_mac(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<ChangeDeviceMacRequest>,
+) -> Result<Json<Device>, ApiError> {
+    super::teams::require_modify(&state, &auth, team_resource_type::DEVICE, id).await?;
+    state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    let mac = normalize_mac(&req.mac);
+    check_mac_unused(&state, &mac, Some(id)).await?;
+
+    state.store.update_device_mac(id, &mac).await?;
+    let _ = state.store.delete_discovered_device(&mac).await;
+    state.render_cache.invalidate_device(id);
+    trigger_reload(&state).await;
+
+    let device = state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    Ok(Json(device))
+}
+
+/// Inline device passwords still work but are deprecated in favour of credential bindings
+fn warn_inline_password(hostname: &str, ssh_pass: Option<&str>) {
+    if ssh_pass.is_some_and(|p| !p.is_empty()) {
+        tracing::warn!("Device {}: inline ssh_pass is deprecated, bind a credential with credential_id instead", hostname);
+    }
+}
+
 /// Delete a device
 pub async fn delete_device(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
 ) -> Result<axum::http::StatusCode, ApiError> {
+    super::teams::require_modify(&state, &auth, team_resource_type::DEVICE, id).await?;
     state.store.delete_device(id).await?;
+    state.render_cache.invalidate_device(id);
+    super::documentation::prune_attachment_files(&state).await;
     trigger_reload(&state).await;
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
 
-/// Test connectivity to a device via ping and SSH
+/// List sets of devices that share a MAC, serial number or IP
+pub async fn list_duplicate_devices(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<DuplicateDeviceSet>>, ApiError> {
+    let devices = state.store.list_devices().await?;
+    Ok(Json(crate::utils::find_duplicate_devices(&devices)))
+}
+
+/// Merge duplicate devices into this one: their variables, group memberships, jobs, backups,
+/// notes and attachments move here and the duplicates are deleted
+pub async fn merge_devices(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<MergeDevicesRequest>,
+) -> Result<Json<MergeDevicesResult>, ApiError> {
+    let mut source_ids = req.source_ids;
+    source_ids.sort_unstable();
+    source_ids.dedup();
+    if source_ids.is_empty() {
+        return Err(ApiError::bad_request("source_ids is required"));
+    }
+    if source_ids.contains(&id) {
+        return Err(ApiError::bad_request("a device can't be merged into itself"));
+    }
+    state.store.get_device(id).await?.ok_or_else(|| ApiError::not_found("device"))?;
+    for &source_id in &source_ids {
+        if state.store.get_device(source_id).await?.is_none() {
+            return Err(ApiError::bad_request(format!("device {} not found", source_id)));
+        }
+    }
+
+    let stats = state.store.merge_devices(id, &source_ids).await?;
+    state.render_cache.invalidate_device(id);
+    for &source_id in &source_ids {
+        state.render_cache.invalidate_device(source_id);
+    }
+    let device = state.store.get_device(id).await?.ok_or_else(|| ApiError::not_found("device"))?;
+    tracing::info!("Merged devices {:?} into {} ({})", source_ids, device.hostname, id);
+    trigger_reload(&state).await;
+    Ok(Json(MergeDevicesResult { device, merged_ids: source_ids, stats }))
+}
+
+/// Test connectivity to a device via ping and SSH. Queues a connect test job and returns 202;
+/// `?wait=true` runs the test in the request and returns its result instead.
 pub async fn connect_device(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-) -> Result<Json<ConnectResult>, ApiError> {
+    Query(query): Query<ConnectQuery>,
+) -> Result<Response, ApiError> {
     let device = state
         .store
         .get_device(id)
         .await?
         .ok_or_else(|| ApiError::not_found("device"))?;
 
-    let (ssh_user, ssh_pass) = crate::utils::resolve_ssh_credentials(
-        &state.store, device.ssh_user.clone(), device.ssh_pass.clone(), device.vendor.as_deref(),
-    ).await;
-
-    // Ping check
-    let ping_result = ping_device(&device.ip).await;
+    if query.wait {
+        return Ok(Json(crate::jobs::connect_device_test(&state.store, &device).await).into_response());
+    }
+    let job = queue_connect_test(&state, device.id, String::new(), None).await?;
+    Ok((StatusCode::ACCEPTED, Json(job)).into_response())
+}
 
-    // SSH check with vendor-aware probe
-    let ssh_result = if !ssh_user.is_empty() && !ssh_pass.is_empty() {
-        ssh_probe(&device.ip, &ssh_user, &ssh_pass, device.vendor.as_deref()).await
-    } else {
-        SshResult {
-            connected: false,
-            uptime: None,
-            hostname: None,
-            version: None,
-            interfaces: None,
-            error: Some("No SSH credentials configured".to_string()),
-        }
+/// Create and submit a connect test job, holding an inline password in memory for it
+async fn queue_connect_test(
+    state: &AppState,
+    device_id: i64,
+    command: String,
+    ssh_pass: Option<String>,
+) -> Result<Job, ApiError> {
+    let job_service = state
+        .job_service
+        .as_ref()
+        .ok_or_else(|| ApiError::service_unavailable("job service is not running"))?;
+    job_service.check_capacity(1)?;
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let req = CreateJobRequest {
+        device_id,
+        job_type: job_type::CONNECT_TEST.to_string(),
+        command,
+        credential_id: String::new(),
+        triggered_by: "manual".to_string(),
+        timeout_secs: None,
     };
-
-    let success = ping_result.reachable && ssh_result.connected;
-
-    // Update device status based on connectivity
-    if ping_result.reachable {
-        let _ = state.store.update_device_status(device.id, crate::models::device_status::ONLINE).await;
+    let job = state.store.create_job(&job_id, &req).await?;
+    if let Some(pass) = ssh_pass.filter(|p| !p.is_empty()) {
+        job_service.hold_connect_password(&job_id, pass);
     }
 
-    Ok(Json(ConnectResult {
-        ping: ping_result,
-        ssh: ssh_result,
-        success,
-    }))
+    if let Some(ref hub) = state.ws_hub {
+        hub.broadcast_job_update(crate::ws::EventType::JobQueued, &job).await;
+    }
+    job_service.submit(job_id).await;
+    Ok(job)
 }
 
 /// Get the generated configuration for a device
@@ -204,105 +362,59 @@ pub async fn get_device_config(
     }))
 }
 
-async fn ping_device(ip: &str) -> PingResult {
-    if !crate::utils::is_valid_ipv4(ip) {
-        return PingResult {
-            reachable: false,
-            latency: None,
-            error: Some("Invalid IP address".to_string()),
-        };
-    }
-
-    let output = Command::new("ping")
-        .args(["-c", "3", "-W", "2", ip])
-        .output()
-        .await;
-
-    match output {
-        Ok(output) if output.status.success() => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let latency = parse_ping_latency(&stdout);
-            PingResult {
-                reachable: true,
-                latency,
-                error: None,
-            }
-        }
-        _ => PingResult {
-            reachable: false,
-            latency: None,
-            error: Some("Host unreachable".to_string()),
-        },
-    }
-}
+/// Get the tokenized HTTP ZTP config URL of a device
+pub async fn get_device_ztp_url(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<DeviceZtpUrl>, ApiError> {
+    let device = state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    let mac = device
+        .mac
+        .filter(|m| !m.is_empty())
+        .ok_or_else(|| ApiError::bad_request("device has no MAC address; ZTP config URLs are per MAC"))?;
 
-fn parse_ping_latency(output: &str) -> Option<String> {
-    // Parse average time from ping stats line
-    for line in output.lines() {
-        if line.contains("avg") {
-            let parts: Vec<&str> = line.split('/').collect();
-            if parts.len() >= 5 {
-                let val = parts[4].trim_end_matches(" ms").trim_end_matches("ms");
-                return Some(format!("{}ms", val));
-            }
-        }
-    }
-    None
-}
-
-async fn ssh_probe(ip: &str, user: &str, pass: &str, vendor_hint: Option<&str>) -> SshResult {
-    let (connected, probe, error) = crate::utils::ssh_probe_device(ip, user, pass, vendor_hint).await;
-    SshResult {
-        connected,
-        uptime: probe.uptime,
-        hostname: probe.hostname,
-        version: probe.version,
-        interfaces: probe.interfaces,
-        error,
-    }
+    let path = crate::services::ztp::config_path(&state.config.jwt_secret, &mac);
+    let settings = state.store.get_settings().await?;
+    let url = state
+        .config
+        .listen_addr
+        .parse::<std::net::SocketAddr>()
+        .ok()
+        .filter(|_| !settings.tftp_server_ip.is_empty())
+        .map(|addr| format!("http://{}:{}{}", settings.tftp_server_ip, addr.port(), path));
+    Ok(Json(DeviceZtpUrl { mac, path, url }))
 }
 
-/// Test connectivity to an arbitrary IP (for discovery/containers that aren't registered devices)
+/// Test connectivity to an arbitrary IP (for discovery/containers that aren't registered
+/// devices). Queues a connect test job like `connect_device`, or runs it in the request with
+/// `?wait=true`.
 pub async fn connect_ip(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
+    Query(query): Query<ConnectQuery>,
     Json(body): Json<ConnectIpRequest>,
-) -> Result<Json<ConnectResult>, ApiError> {
+) -> Result<Response, ApiError> {
     if !is_valid_ipv4(&body.ip) {
         return Err(ApiError::bad_request("Invalid IP address"));
     }
 
-    let (ssh_user, ssh_pass) = crate::utils::resolve_ssh_credentials(
-        &state.store, body.ssh_user, body.ssh_pass, body.vendor.as_deref(),
-    ).await;
-
-    let ping_result = ping_device(&body.ip).await;
-
-    let ssh_result = if !ssh_user.is_empty() && !ssh_pass.is_empty() {
-        ssh_probe(&body.ip, &ssh_user, &ssh_pass, body.vendor.as_deref()).await
-    } else {
-        SshResult {
-            connected: false,
-            uptime: None,
-            hostname: None,
-            version: None,
-            interfaces: None,
-            error: Some("No SSH credentials configured".to_string()),
-        }
-    };
-
-    let success = ping_result.reachable && ssh_result.connected;
-
-    Ok(Json(ConnectResult {
-        ping: ping_result,
-        ssh: ssh_result,
-        success,
-    }))
+    let target = crate::jobs::ConnectTarget { ip: body.ip, vendor: body.vendor, ssh_user: body.ssh_user };
+    if query.wait {
+        return Ok(Json(crate::jobs::connect_address_test(&state.store, target, body.ssh_pass).await).into_response());
+    }
+    let command = serde_json::to_string(&target).map_err(|e| ApiError::internal(e.to_string()))?;
+    let job = queue_connect_test(&state, 0, command, body.ssh_pass).await?;
+    Ok((StatusCode::ACCEPTED, Json(job)).into_response())
 }
 
 /// Execute a command on a device via SSH or webhook — creates a job and returns 202 Accepted
 pub async fn exec_command(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
     Json(body): Json<ExecRequest>,
@@ -332,6 +444,12 @@ pub async fn exec_command(
         }
         (job_type::COMMAND.to_string(), body.command.clone())
     };
+    if body.timeout_secs.is_some_and(|secs| secs < 1) {
+        return Err(ApiError::bad_request("timeout_secs must be at least 1"));
+    }
+    let checked_command = (jt == job_type::COMMAND).then_some(command.as_str());
+    super::teams::require_device_action(&state, &auth, &[id], team_action::EXEC, checked_command).await?;
+    super::jobs::require_queue_capacity(&state, 1)?;
 
     let job_id = uuid::Uuid::new_v4().to_string();
     let req = CreateJobRequest {
@@ -340,6 +458,7 @@ pub async fn exec_command(
         command,
         credential_id: String::new(),
         triggered_by: "manual".to_string(),
+        timeout_secs: body.timeout_secs,
     };
 
     let job = state.store.create_job(&job_id, &req).await?;
@@ -357,79 +476,66 @@ pub async fn exec_command(
     Ok((StatusCode::ACCEPTED, Json(job)))
 }
 
-/// Render a device's template with its real data and return the preview
+/// Render a device config through the render cache, mapping render failures to 400s
 fn render_device_config(
+    cache: &crate::jobs::RenderCache,
     device: &Device,
     template: &Template,
     settings: &Settings,
-    role_template: Option<&Template>,
+    role_templates: &[Template],
     vars: &std::collections::HashMap<String, String>,
     port_assignments: Option<&[crate::models::PortAssignment]>,
 ) -> Result<String, ApiError> {
-    let tera_content = crate::utils::convert_go_template_to_tera(&template.content);
-
-    let mut tera = Tera::default();
-    tera.add_raw_template("device", &tera_content)
-        .map_err(|e| ApiError::bad_request(format!("Invalid template: {}", e)))?;
+    cache
+        .render(device, template, settings, role_templates, vars, port_assignments)
+        .map_err(|e| ApiError::bad_request(e.to_string()))
+}
 
-    // Register role template as "role" so the device template can {% include "role" %}
-    if let Some(role_tmpl) = role_template {
-        let role_content = crate::utils::convert_go_template_to_tera(&role_tmpl.content);
-        tera.add_raw_template("role", &role_content)
-            .map_err(|e| ApiError::bad_request(format!("Invalid role template: {}", e)))?;
-    } else {
-        // Register an empty role template so {% include "role" %} doesn't fail
-        tera.add_raw_template("role", "")
-            .map_err(|e| ApiError::bad_request(format!("Failed to add empty role template: {}", e)))?;
-    }
+/// Get a device's pre/post config snippets (empty when none are set)
+pub async fn get_device_config_snippets(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<DeviceConfigSnippets>, ApiError> {
+    state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    let snippets = state
+        .store
+        .get_device_config_snippets(id)
+        .await?
+        .unwrap_or(DeviceConfigSnippets { device_id: id, ..Default::default() });
+    Ok(Json(snippets))
+}
 
-    let mut context = Context::new();
-    context.insert("Hostname", &device.hostname);
-    context.insert("MAC", &device.mac.clone().unwrap_or_default());
-    context.insert("IP", &device.ip);
-    context.insert("Vendor", &device.vendor.clone().unwrap_or_default());
-    context.insert("Model", &device.model.clone().unwrap_or_default());
-    context.insert("SerialNumber", &device.serial_number.clone().unwrap_or_default());
-    context.insert("TopologyId", &device.topology_id.clone().unwrap_or_default());
-    context.insert("TopologyRole", &device.topology_role.clone().unwrap_or_default());
-    context.insert("Subnet", &settings.dhcp_subnet);
-    context.insert("Gateway", &settings.dhcp_gateway);
-    context.insert("vars", vars);
-
-    // Build VRF context from port assignments
-    if let Some(assignments) = port_assignments {
-        let mut vrf_map: std::collections::HashMap<String, serde_json::Value> = std::collections::HashMap::new();
-        for pa in assignments {
-            if let Some(vrf_id) = pa.vrf_id {
-                let vrf_key = vrf_id.to_string();
-                let iface = serde_json::json!({
-                    "port_name": pa.port_name,
-                    "remote_device": pa.remote_device_hostname.clone().unwrap_or_default(),
-                    "remote_port": pa.remote_port_name,
-                    "description": pa.description.clone().unwrap_or_default(),
-                });
-                if let Some(existing) = vrf_map.get_mut(&vrf_key) {
-                    if let Some(arr) = existing.get_mut("interfaces").and_then(|v| v.as_array_mut()) {
-                        arr.push(iface);
-                    }
-                } else {
-                    vrf_map.insert(vrf_key.clone(), serde_json::json!({
-                        "id": vrf_id,
-                        "name": pa.vrf_name.clone().unwrap_or(vrf_key),
-                        "interfaces": [iface],
-                    }));
-                }
-            }
-        }
-        let vrfs: Vec<serde_json::Value> = vrf_map.into_values().collect();
-        context.insert("VRFs", &vrfs);
-    } else {
-        let empty: Vec<serde_json::Value> = vec![];
-        context.insert("VRFs", &empty);
-    }
+/// Set a device's pre/post config snippets
+pub async fn set_device_config_snippets(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<SetDeviceConfigSnippetsRequest>,
+) -> Result<Json<DeviceConfigSnippets>, ApiError> {
+    state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    let snippets = state.store.set_device_config_snippets(id, &req).await?;
+    trigger_reload(&state).await;
+    Ok(Json(snippets))
+}
 
-    tera.render("device", &context)
-        .map_err(|e| ApiError::bad_request(format!("Template rendering failed: {}", e)))
+/// Remove a device's config snippets
+pub async fn delete_device_config_snippets(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_device_config_snippets(id).await?;
+    trigger_reload(&state).await;
+    Ok(StatusCode::NO_CONTENT)
 }
 
 /// Preview the rendered configuration for a device
@@ -472,18 +578,11 @@ pub async fn preview_device_config(
 
     let settings = state.store.get_settings().await?;
 
-    // Look up role-specific template by name convention
-    let role_template = if let Some(role) = &device.topology_role {
-        let capitalized_role = format!("{}{}", &role[..1].to_uppercase(), &role[1..]);
-        let role_name = if template.name.ends_with(" Default") {
-            format!("{} {}", template.name.trim_end_matches(" Default"), capitalized_role)
-        } else {
-            format!("{} {}", template.name, capitalized_role)
-        };
-        state.store.get_template_by_name(&role_name).await.ok().flatten()
-    } else {
-        None
-    };
+    // Role templates (layers) from the device role's explicit template mapping
+    let role_templates = state
+        .store
+        .resolve_role_templates(&template, device.vendor.as_deref(), device.topology_role.as_deref())
+        .await?;
 
     // Load resolved variables (group + host inheritance) for template rendering
     let vars = state
@@ -495,7 +594,8 @@ pub async fn preview_device_config(
     // Load port assignments for VRF context
     let port_assignments = state.store.list_port_assignments(device.id).await.unwrap_or_default();
 
-    let content = render_device_config(&device, &template, &settings, role_template.as_ref(), &vars, Some(&port_assignments))?;
+    let content = render_device_config(&state.render_cache, &device, &template, &settings, &role_templates, &vars, Some(&port_assignments))?;
+    let content = state.store.apply_device_config_snippets(device.id, content).await?;
 
     Ok(Json(DeviceConfigPreviewResponse {
         mac: device.mac.unwrap_or_default(),
@@ -506,12 +606,21 @@ pub async fn preview_device_config(
     }))
 }
 
+#[derive(Deserialize)]
+pub struct DeployConfigQuery {
+    /// Commit with the vendor's rollback timer and confirm only once the device is reachable again
+    #[serde(default)]
+    pub confirmed: bool,
+}
+
 /// Deploy rendered configuration to a device via SSH — creates a job and returns 202 Accepted
 pub async fn deploy_device_config(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
+    Query(query): Query<DeployConfigQuery>,
 ) -> Result<(StatusCode, Json<Job>), ApiError> {
+    super::teams::require_device_action(&state, &auth, &[id], team_action::DEPLOY, None).await?;
     let device = state
         .store
         .get_device(id)
@@ -521,16 +630,34 @@ pub async fn deploy_device_config(
     // Resolve template name for job metadata
     let template_name = resolve_job_template_name(&state, &device).await;
 
+    let job_type = if query.confirmed {
+        let vendor = match device.vendor.as_deref() {
+            Some(v) if !v.is_empty() => state.store.resolve_vendor(v).await?,
+            _ => None,
+        };
+        if !vendor.is_some_and(|v| {
+            v.transport == vendor_transport::NETCONF || (!v.commit_confirm_command.is_empty() && !v.confirm_command.is_empty())
+        }) {
+            return Err(ApiError::bad_request("device's vendor has no confirmed commit commands"));
+        }
+        job_type::CONFIRMED_DEPLOY
+    } else {
+        job_type::DEPLOY
+    };
+    super::jobs::require_queue_capacity(&state, 1)?;
+
     let job_id = uuid::Uuid::new_v4().to_string();
     let req = CreateJobRequest {
         device_id: id,
-        job_type: job_type::DEPLOY.to_string(),
+        job_type: job_type.to_string(),
         command: template_name,
         credential_id: String::new(),
         triggered_by: "manual".to_string(),
+        timeout_secs: None,
     };
 
     let job = state.store.create_job(&job_id, &req).await?;
+    record_change(&state, &auth, NewChangeLogEntry::deploy(&job)).await;
 
     // Broadcast queued event
     if let Some(ref hub) = state.ws_hub {
@@ -545,12 +672,38 @@ pub async fn deploy_device_config(
     Ok((StatusCode::ACCEPTED, Json(job)))
 }
 
+/// Run the pre-flight checks on a device and return the go/no-go result
+pub async fn preflight_device(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<PreflightResult>, ApiError> {
+    super::teams::require_device_action(&state, &auth, &[id], team_action::DEPLOY, None).await?;
+    let device = state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+
+    let crate::utils::ResolvedSshCredentials { user, pass, credential_id } =
+        crate::utils::resolve_ssh_credentials(&state.store, &device).await;
+    if let Some(cred_id) = credential_id {
+        if let Err(e) = state.store.record_credential_usage(cred_id, Some(device.id), "").await {
+            tracing::warn!("Failed to record usage of credential {}: {}", cred_id, e);
+        }
+    }
+    let settings = state.store.get_settings().await?;
+    let result = crate::jobs::run_preflight(&state.store, &device, &user, &pass, settings.preflight_max_clock_skew_secs as i64).await?;
+    Ok(Json(result))
+}
+
 /// Show a diff of the pending configuration on a device via SSH — creates a job and returns 202 Accepted
 pub async fn diff_device_config(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
 ) -> Result<(StatusCode, Json<Job>), ApiError> {
+    super::teams::require_device_action(&state, &auth, &[id], team_action::DEPLOY, None).await?;
     let device = state
         .store
         .get_device(id)
@@ -559,6 +712,7 @@ pub async fn diff_device_config(
 
     // Resolve template name for job metadata
     let template_name = resolve_job_template_name(&state, &device).await;
+    super::jobs::require_queue_capacity(&state, 1)?;
 
     let job_id = uuid::Uuid::new_v4().to_string();
     let req = CreateJobRequest {
@@ -567,6 +721,7 @@ pub async fn diff_device_config(
         command: template_name,
         credential_id: String::new(),
         triggered_by: "manual".to_string(),
+        timeout_secs: None,
     };
 
     let job = state.store.create_job(&job_id, &req).await?;
@@ -657,8 +812,8 @@ pub async fn resolve_next_hostname(
 }
 
 /// Resolve the template name for a device (for job metadata).
-/// Returns "template_name" or "template_name (role-variant)" or empty string.
-async fn resolve_job_template_name(state: &AppState, device: &Device) -> String {
+/// Returns "template_name" or "template_name (role-layer, ...)" or empty string.
+pub(super) async fn resolve_job_template_name(state: &AppState, device: &Device) -> String {
     let template_id: i64 = if !device.config_template.is_empty() {
         match device.config_template.parse::<i64>() {
             Ok(id) => id,
@@ -683,23 +838,91 @@ async fn resolve_job_template_name(state: &AppState, device: &Device) -> String
         return String::new();
     };
 
-    let template_name = match state.store.get_template(template_id).await {
-        Ok(Some(t)) => t.name,
+    let template = match state.store.get_template(template_id).await {
+        Ok(Some(t)) => t,
         _ => return template_id.to_string(),
     };
 
-    // Check for role-specific variant by name convention
-    if let Some(ref role) = device.topology_role {
-        let capitalized_role = format!("{}{}", &role[..1].to_uppercase(), &role[1..]);
-        let role_name = if template_name.ends_with(" Default") {
-            format!("{} {}", template_name.trim_end_matches(" Default"), capitalized_role)
-        } else {
-            format!("{} {}", template_name, capitalized_role)
-        };
-        if let Ok(Some(role_tmpl)) = state.store.get_template_by_name(&role_name).await {
-            return format!("{} ({})", template_name, role_tmpl.name);
+    let role_templates = state
+        .store
+        .resolve_role_templates(&template, device.vendor.as_deref(), device.topology_role.as_deref())
+        .await
+        .unwrap_or_default();
+    if role_templates.is_empty() {
+        template.name
+    } else {
+        let names: Vec<&str> = role_templates.iter().map(|t| t.name.as_str()).collect();
+        format!("{} ({})", template.name, names.join(", "))
+    }
+}
+
+/// Reprovision a device: rewrite its TFTP config, clear last_error, optionally release its
+/// DHCP lease, and open a provisioning session that tracks it re-bootstrapping
+pub async fn reprovision_device(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(query): Query<ReprovisionQuery>,
+) -> Result<Json<ReprovisionResponse>, ApiError> {
+    let device = state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    let mac = device.mac.clone().unwrap_or_default();
+    if mac.is_empty() {
+        return Err(ApiError::bad_request("Device has no MAC address to provision"));
+    }
+
+    let export = state
+        .config_manager
+        .export_device_configs(std::slice::from_ref(&device))
+        .await?
+        .remove(0);
+    if let Some(error) = export.error {
+        return Err(ApiError::bad_request(format!("Failed to regenerate config: {}", error)));
+    }
+    state.store.clear_device_error(id).await?;
+
+    let mut lease_error = None;
+    if query.release_lease {
+        // Release the active lease's IP, falling back to the reserved IP
+        let lease_ip = crate::dhcp::parse_lease_file(&state.config.lease_path)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .find(|l| l.mac.eq_ignore_ascii_case(&mac))
+            .map(|l| l.ip)
+            .unwrap_or_else(|| device.ip.clone());
+        match state.config_manager.release_lease(&lease_ip, &mac).await {
+            Ok(()) => {
+                if let Some(ref watcher) = state.lease_watcher {
+                    watcher.read().await.forget_mac(&mac).await;
+                }
+            }
+            Err(e) => lease_error = Some(e.to_string()),
         }
     }
 
-    template_name
+    state.store.update_device_status(id, device_status::PROVISIONING).await?;
+    let session = state
+        .store
+        .open_provisioning_session(id, &export.filename.unwrap_or_default(), query.release_lease && lease_error.is_none())
+        .await?;
+
+    Ok(Json(ReprovisionResponse { session, lease_error }))
+}
+
+/// List a device's provisioning sessions, newest first
+pub async fn list_provisioning_sessions(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<ProvisioningSession>>, ApiError> {
+    state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    Ok(Json(state.store.list_provisioning_sessions(id).await?))
 }
@@ -8,7 +8,7 @@ use crate::db::get_default_dhcp_options_models;
 use crate::models::*;
 use crate::AppState;
 
-use super::{created, trigger_reload, ApiError};
+use super::{created, trigger_reload, ApiError, ValidJson};
 
 /// List all DHCP options
 pub async fn list_dhcp_options(
@@ -45,12 +45,8 @@ pub async fn get_dhcp_option(
 pub async fn create_dhcp_option(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateDhcpOptionRequest>,
+    ValidJson(req): ValidJson<CreateDhcpOptionRequest>,
 ) -> Result<(axum::http::StatusCode, Json<DhcpOption>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
-
     let option = state.store.create_dhcp_option(&req).await?;
     trigger_reload(&state).await;
     Ok(created(option))
@@ -61,7 +57,7 @@ pub async fn update_dhcp_option(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateDhcpOptionRequest>,
+    ValidJson(req): ValidJson<CreateDhcpOptionRequest>,
 ) -> Result<Json<DhcpOption>, ApiError> {
     let option = state.store.update_dhcp_option(id, &req).await?;
     trigger_reload(&state).await;
@@ -2,10 +2,15 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
     http::StatusCode,
+    response::Response,
     Json,
 };
+use futures::StreamExt;
 
 use super::helpers::*;
 use crate::handlers::ApiError;
@@ -16,8 +21,7 @@ pub async fn list_containers(
     _auth: crate::auth::AuthUser,
     State(_state): State<Arc<AppState>>,
 ) -> Result<Json<Vec<TestContainer>>, ApiError> {
-    let docker = bollard::Docker::connect_with_socket_defaults()
-        .map_err(|e| ApiError::internal(format!("Docker not available: {}", e)))?;
+    let docker = connect_docker()?;
 
     let network_name = get_network_name();
 
@@ -107,8 +111,7 @@ pub async fn spawn_container(
     State(state): State<Arc<AppState>>,
     body: Option<Json<SpawnRequest>>,
 ) -> Result<(StatusCode, Json<TestContainer>), ApiError> {
-    let docker = bollard::Docker::connect_with_socket_defaults()
-        .map_err(|e| ApiError::internal(format!("Docker not available: {}", e)))?;
+    let docker = connect_docker()?;
 
     let req = body.map(|b| b.0).unwrap_or(SpawnRequest {
         hostname: String::new(),
@@ -308,54 +311,9 @@ pub async fn spawn_container(
         .await
         .map_err(|e| ApiError::internal(format!("Failed to create container: {}", e)))?;
 
-    // For cEOS, inject startup-config and modprobe wrapper before starting.
-    // Docker Desktop's Linux VM doesn't have /lib/modules, so `modprobe tun`
-    // fails during EosStage2 init, preventing SSH and other agents from starting.
-    // We inject a wrapper that silently succeeds for known-safe modules.
     let serial_number = format!("SN-cEOS-{}", &resp.id[..resp.id.len().min(8)]);
     if ceos {
-        let config_content = CEOS_STARTUP_CONFIG
-            .replace("{hostname}", &hostname)
-            .replace("{serial_number}", &serial_number);
-
-        // Inject startup-config into /mnt/flash
-        match build_tar(&[("startup-config", config_content.as_bytes(), 0o644)]) {
-            Ok(tar_bytes) => {
-                let options = bollard::container::UploadToContainerOptions {
-                    path: "/mnt/flash".to_string(),
-                    ..Default::default()
-                };
-                if let Err(e) = docker
-                    .upload_to_container(&resp.id, Some(options), tar_bytes.into())
-                    .await
-                {
-                    tracing::warn!("Failed to upload startup-config to cEOS: {}", e);
-                }
-            }
-            Err(e) => {
-                tracing::warn!("Failed to build startup-config tar: {}", e);
-            }
-        }
-
-        // Inject modprobe wrapper that always succeeds.
-        let modprobe_wrapper = b"#!/bin/sh\nexit 0\n";
-        match build_tar(&[("modprobe", modprobe_wrapper.as_slice(), 0o755)]) {
-            Ok(tar_bytes) => {
-                let options = bollard::container::UploadToContainerOptions {
-                    path: "/sbin".to_string(),
-                    ..Default::default()
-                };
-                if let Err(e) = docker
-                    .upload_to_container(&resp.id, Some(options), tar_bytes.into())
-                    .await
-                {
-                    tracing::warn!("Failed to upload modprobe wrapper to cEOS: {}", e);
-                }
-            }
-            Err(e) => {
-                tracing::warn!("Failed to build modprobe wrapper tar: {}", e);
-            }
-        }
+        inject_ceos_files(&docker, &resp.id, &hostname, &serial_number).await;
     }
 
     // Start the container
@@ -423,6 +381,7 @@ pub async fn spawn_container(
             circuit_id: None,
             remote_id: None,
             subscriber_id: None,
+            matched_device_id: None,
         };
         if let Err(e) = state.store.upsert_discovered_device(&lease).await {
             tracing::warn!("Failed to register cEOS in discovery: {}", e);
@@ -439,6 +398,7 @@ pub async fn spawn_container(
             model: if ceos { Some("cEOS-lab".to_string()) } else { None },
             serial_number: if ceos { Some(serial_number.clone()) } else { None },
             config_template: if ceos { "arista-eos".to_string() } else { String::new() },
+            credential_id: None,
             ssh_user: None,
             ssh_pass: None,
             topology_id: Some(req.topology_id),
@@ -480,31 +440,49 @@ pub async fn start_container(
     State(_state): State<Arc<AppState>>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let docker = bollard::Docker::connect_with_socket_defaults()
-        .map_err(|e| ApiError::internal(format!("Docker not available: {}", e)))?;
+    let docker = connect_docker()?;
+    inspect_managed_container(&docker, &id).await?;
 
     docker
         .start_container::<String>(&id, None)
         .await
-        .map_err(|e| ApiError::internal(format!("Failed to start container: {}", e)))?;
+        .map_err(|e| docker_error("start", e))?;
 
     Ok(Json(serde_json::json!({"message": "Container started"})))
 }
 
+/// Stop a running test container
+pub async fn stop_container(
+    _auth: crate::auth::AuthUser,
+    State(_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let docker = connect_docker()?;
+    inspect_managed_container(&docker, &id).await?;
+
+    let options = bollard::container::StopContainerOptions { t: 5 };
+    docker
+        .stop_container(&id, Some(options))
+        .await
+        .map_err(|e| docker_error("stop", e))?;
+
+    Ok(Json(serde_json::json!({"message": "Container stopped"})))
+}
+
 /// Restart a test container
 pub async fn restart_container(
     _auth: crate::auth::AuthUser,
     State(_state): State<Arc<AppState>>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let docker = bollard::Docker::connect_with_socket_defaults()
-        .map_err(|e| ApiError::internal(format!("Docker not available: {}", e)))?;
+    let docker = connect_docker()?;
+    inspect_managed_container(&docker, &id).await?;
 
     let options = bollard::container::RestartContainerOptions { t: 5 };
     docker
         .restart_container(&id, Some(options))
         .await
-        .map_err(|e| ApiError::internal(format!("Failed to restart container: {}", e)))?;
+        .map_err(|e| docker_error("restart", e))?;
 
     Ok(Json(serde_json::json!({"message": "Container restarted"})))
 }
@@ -515,23 +493,19 @@ pub async fn remove_container(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let docker = bollard::Docker::connect_with_socket_defaults()
-        .map_err(|e| ApiError::internal(format!("Docker not available: {}", e)))?;
+    let docker = connect_docker()?;
 
     let network_name = get_network_name();
 
     // Inspect before removing to get the MAC address
-    let mac = if let Ok(inspect) = docker.inspect_container(&id, None).await {
-        inspect
-            .network_settings
-            .as_ref()
-            .and_then(|ns| ns.networks.as_ref())
-            .and_then(|nets| nets.get(&network_name))
-            .and_then(|net| net.mac_address.clone())
-            .unwrap_or_default()
-    } else {
-        String::new()
-    };
+    let inspect = inspect_managed_container(&docker, &id).await?;
+    let mac = inspect
+        .network_settings
+        .as_ref()
+        .and_then(|ns| ns.networks.as_ref())
+        .and_then(|nets| nets.get(&network_name))
+        .and_then(|net| net.mac_address.clone())
+        .unwrap_or_default();
 
     // Stop first (with timeout)
     let stop_options = bollard::container::StopContainerOptions { t: 5 };
@@ -547,7 +521,7 @@ pub async fn remove_container(
             }),
         )
         .await
-        .map_err(|e| ApiError::internal(format!("Failed to remove container: {}", e)))?;
+        .map_err(|e| docker_error("remove", e))?;
 
     // Clean up discovered device record so it doesn't linger in discovery
     if !mac.is_empty() {
@@ -558,3 +532,53 @@ pub async fn remove_container(
 
     Ok(Json(serde_json::json!({"message": "Container removed"})))
 }
+
+/// Stream a container's stdout/stderr over a WebSocket, starting with the last `tail` lines.
+/// Browsers can't set headers on WebSocket requests, so authenticate with `?token=`.
+pub async fn container_logs_ws(
+    _auth: crate::auth::AuthUser,
+    State(_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<ContainerLogsQuery>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let docker = connect_docker()?;
+    inspect_managed_container(&docker, &id).await?;
+
+    Ok(ws.on_upgrade(move |socket| stream_container_logs(socket, docker, id, query.tail)))
+}
+
+async fn stream_container_logs(mut socket: WebSocket, docker: bollard::Docker, id: String, tail: u32) {
+    let options = bollard::container::LogsOptions::<String> {
+        follow: true,
+        stdout: true,
+        stderr: true,
+        tail: tail.to_string(),
+        ..Default::default()
+    };
+    let mut logs = docker.logs(&id, Some(options));
+
+    loop {
+        tokio::select! {
+            chunk = logs.next() => match chunk {
+                Some(Ok(output)) => {
+                    if socket.send(Message::Text(output.to_string())).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Err(e)) => {
+                    let _ = socket.send(Message::Text(format!("log stream error: {}", e))).await;
+                    break;
+                }
+                // Container exited and the stream is drained
+                None => break,
+            },
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                _ => {}
+            },
+        }
+    }
+
+    let _ = socket.send(Message::Close(None)).await;
+}
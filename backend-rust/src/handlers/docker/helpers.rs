@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::handlers::ApiError;
+
 #[derive(Serialize)]
 pub struct TestContainer {
     pub id: String,
@@ -114,6 +116,9 @@ pub struct UnifiedTopologyRequest {
     pub spine_to_super_spine_ratio: usize,
     #[serde(default = "default_one")]
     pub pods: usize,
+    /// How spines get ASNs: "per-pod" (default) or "per-device"; see `clos_spine_asn`
+    #[serde(default)]
+    pub asn_scheme: String,
 
     // Physical spacing for cable length estimation
     #[serde(default = "default_row_spacing")]
@@ -186,6 +191,118 @@ fn default_rack_width() -> i32 { 60 }
 fn default_rack_height_ru() -> i32 { 42 }
 fn default_rack_depth() -> i32 { 100 }
 
+/// Spine ASN schemes for CLOS fabrics
+pub mod asn_scheme {
+    /// Spines of a pod share one ASN
+    pub const PER_POD: &str = "per-pod";
+    /// Every spine has its own ASN
+    pub const PER_DEVICE: &str = "per-device";
+}
+
+/// First ASN of the per-pod spine range (pod N's spines get this + N)
+const POD_SPINE_ASN_BASE: u32 = 64600;
+/// First ASN of the per-device spine range (spine N gets this + N)
+const DEVICE_SPINE_ASN_BASE: u32 = 64700;
+/// Leaf N gets this + N
+const LEAF_ASN_BASE: u32 = 65000;
+/// Shared by all super-spines
+pub const SUPER_SPINE_ASN: u32 = 65500;
+/// External N gets this - (N - 1)
+const EXTERNAL_ASN_TOP: u32 = 64999;
+
+/// ASN of the `index`th spine (1-based) of a CLOS fabric with `pod_count` pods. Under
+/// "per-pod", a single-pod fabric's spines share 65000 and a multi-pod fabric's pod N spines
+/// share 64600 + N, so each pod's leaves accept routes learned through other pods' spines.
+/// Under "per-device" spine N gets 64700 + N.
+pub fn clos_spine_asn(req: &UnifiedTopologyRequest, pod_count: usize, index: usize) -> u32 {
+    if req.asn_scheme == asn_scheme::PER_DEVICE {
+        DEVICE_SPINE_ASN_BASE + index as u32
+    } else if pod_count > 1 {
+        POD_SPINE_ASN_BASE + ((index - 1) / req.tier1_count.max(1) + 1) as u32
+    } else {
+        LEAF_ASN_BASE
+    }
+}
+
+/// Check the CLOS fields that don't depend on device models: the ASN scheme, and that the
+/// fabric's ASN ranges fit without overlapping each other
+pub fn validate_clos_request(req: &UnifiedTopologyRequest) -> Result<(), ApiError> {
+    if !req.asn_scheme.is_empty() && req.asn_scheme != asn_scheme::PER_POD && req.asn_scheme != asn_scheme::PER_DEVICE {
+        return Err(ApiError::bad_request(format!(
+            "asn_scheme must be '{}' or '{}'",
+            asn_scheme::PER_POD,
+            asn_scheme::PER_DEVICE
+        )));
+    }
+    let pod_count = if req.super_spine_enabled { req.pods.max(2) } else { 1 };
+    let total_spines = req.tier1_count * pod_count;
+    let total_leaves = req.tier2_count * pod_count;
+    if req.super_spine_enabled && req.spine_to_super_spine_ratio == 0 {
+        return Err(ApiError::bad_request("spine_to_super_spine_ratio must be at least 1"));
+    }
+    if LEAF_ASN_BASE as usize + total_leaves >= SUPER_SPINE_ASN as usize {
+        return Err(ApiError::bad_request(format!(
+            "{} leaves exceed the leaf ASN range ({}-{})",
+            total_leaves,
+            LEAF_ASN_BASE + 1,
+            SUPER_SPINE_ASN - 1
+        )));
+    }
+    if req.asn_scheme == asn_scheme::PER_DEVICE {
+        if DEVICE_SPINE_ASN_BASE as usize + total_spines > EXTERNAL_ASN_TOP as usize - req.external_count {
+            return Err(ApiError::bad_request(format!(
+                "{} spines with per-device ASNs overlap the external ASN range",
+                total_spines
+            )));
+        }
+    } else if POD_SPINE_ASN_BASE as usize + pod_count >= DEVICE_SPINE_ASN_BASE as usize {
+        return Err(ApiError::bad_request(format!(
+            "{} pods exceed the per-pod spine ASN range ({}-{})",
+            pod_count,
+            POD_SPINE_ASN_BASE + 1,
+            DEVICE_SPINE_ASN_BASE - 1
+        )));
+    }
+    Ok(())
+}
+
+/// Check that spine and super-spine models have the 100G ports a super-spine tier needs: each
+/// spine's leaf links plus its super-spine uplinks, and each super-spine's spine downlinks plus
+/// its external uplinks. Models without a known port layout are not checked.
+pub fn validate_super_spine_ports(
+    req: &UnifiedTopologyRequest,
+    spine_ports: usize,
+    super_spine_ports: usize,
+) -> Result<(), ApiError> {
+    if !req.super_spine_enabled {
+        return Ok(());
+    }
+    let pod_count = req.pods.max(2);
+    let ss_count = req.super_spine_count.max(1);
+    let spine_needed = req.tier2_count * req.tier1_to_tier2_ratio + ss_count * req.spine_to_super_spine_ratio;
+    if spine_ports > 0 && spine_needed > spine_ports {
+        return Err(ApiError::bad_request(format!(
+            "Spines need {} 100G ports ({} to leaves, {} to super-spines) but the spine model has {}",
+            spine_needed,
+            req.tier2_count * req.tier1_to_tier2_ratio,
+            ss_count * req.spine_to_super_spine_ratio,
+            spine_ports
+        )));
+    }
+    let downlinks = req.tier1_count * pod_count * req.spine_to_super_spine_ratio;
+    let ss_needed = downlinks + req.external_count * req.external_to_tier1_ratio;
+    if super_spine_ports > 0 && ss_needed > super_spine_ports {
+        return Err(ApiError::bad_request(format!(
+            "Super-spines need {} 100G ports ({} to spines, {} to externals) but the super-spine model has {}",
+            ss_needed,
+            downlinks,
+            ss_needed - downlinks,
+            super_spine_ports
+        )));
+    }
+    Ok(())
+}
+
 /// Response from the topology builder endpoint
 #[derive(Serialize)]
 pub struct TopologyBuildResponse {
@@ -204,6 +321,33 @@ pub struct TopologyBuildDevice {
     pub container_name: String,
 }
 
+/// Runtime state of one stack service's container
+#[derive(Serialize)]
+pub struct StackContainer {
+    pub service: String,
+    pub container_name: String,
+    pub id: String,
+    pub status: String,
+    pub ip: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct StackStatus {
+    pub stack: String,
+    pub containers: Vec<StackContainer>,
+}
+
+#[derive(Deserialize)]
+pub struct ContainerLogsQuery {
+    /// Number of existing lines to send before following
+    #[serde(default = "default_log_tail")]
+    pub tail: u32,
+}
+
+fn default_log_tail() -> u32 { 200 }
+
 pub(super) fn get_network_name() -> String {
     std::env::var("DOCKER_NETWORK").unwrap_or_else(|_| "forge_fc-net".to_string())
 }
@@ -212,6 +356,63 @@ pub(super) fn get_image_name() -> String {
     std::env::var("TEST_CLIENT_IMAGE").unwrap_or_else(|_| "forge-config-test-client".to_string())
 }
 
+pub(super) fn connect_docker() -> Result<bollard::Docker, ApiError> {
+    bollard::Docker::connect_with_socket_defaults()
+        .map_err(|e| ApiError::internal(format!("Docker not available: {}", e)))
+}
+
+/// Map a Docker API error to an HTTP status: unknown containers are 404s, and
+/// "already started/stopped" (304) or name clashes (409) are conflicts
+pub(super) fn docker_error(action: &str, err: bollard::errors::Error) -> ApiError {
+    match err {
+        bollard::errors::Error::DockerResponseServerError { status_code: 404, .. } => {
+            ApiError::not_found("container")
+        }
+        bollard::errors::Error::DockerResponseServerError { status_code: 304, .. } => {
+            ApiError::conflict(format!("Failed to {} container: already in requested state", action))
+        }
+        bollard::errors::Error::DockerResponseServerError { status_code: 409, message } => {
+            ApiError::conflict(format!("Failed to {} container: {}", action, message))
+        }
+        e => ApiError::internal(format!("Failed to {} container: {}", action, e)),
+    }
+}
+
+/// Inspect a container and refuse to touch it unless forge-config created it
+/// (test clients, topology nodes and stack services all carry one of these labels)
+pub(super) async fn inspect_managed_container(
+    docker: &bollard::Docker,
+    id: &str,
+) -> Result<bollard::models::ContainerInspectResponse, ApiError> {
+    let inspect = docker
+        .inspect_container(id, None)
+        .await
+        .map_err(|e| docker_error("inspect", e))?;
+    let managed = inspect
+        .config
+        .as_ref()
+        .and_then(|c| c.labels.as_ref())
+        .is_some_and(|labels| labels.contains_key("fc-test-client") || labels.contains_key("fc-stack"));
+    if !managed {
+        return Err(ApiError::bad_request(format!(
+            "container {} is not managed by forge-config",
+            id
+        )));
+    }
+    Ok(inspect)
+}
+
+/// IP address of a container on the forge-config network, empty when not attached
+pub(super) fn container_ip(inspect: &bollard::models::ContainerInspectResponse, network_name: &str) -> String {
+    inspect
+        .network_settings
+        .as_ref()
+        .and_then(|ns| ns.networks.as_ref())
+        .and_then(|nets| nets.get(network_name))
+        .and_then(|net| net.ip_address.clone())
+        .unwrap_or_default()
+}
+
 /// Generate a random MAC address with the locally administered bit set
 pub(super) fn generate_mac() -> String {
     use rand::Rng;
@@ -487,6 +688,125 @@ pub struct TopologyBuildWithOverrides {
     pub overrides: Option<TopologyOverrides>,
 }
 
+/// Hierarchical builder request in core/distribution/access terms. Each named field replaces its
+/// generic tier field; the rest of the unified request (location, rack layout, containers, GPU
+/// clusters) applies as-is, and `architecture` is always hierarchical.
+#[derive(Deserialize)]
+pub struct HierarchicalTopologyRequest {
+    #[serde(default)]
+    pub core_count: Option<usize>,
+    #[serde(default)]
+    pub distribution_count: Option<usize>,
+    #[serde(default)]
+    pub access_count: Option<usize>,
+    /// Parallel links between each core and distribution switch
+    #[serde(default)]
+    pub core_links_per_distribution: Option<usize>,
+    /// Parallel links between each distribution and access switch
+    #[serde(default)]
+    pub distribution_links_per_access: Option<usize>,
+    #[serde(default)]
+    pub core_model: Option<String>,
+    #[serde(default)]
+    pub distribution_model: Option<String>,
+    #[serde(default)]
+    pub access_model: Option<String>,
+    #[serde(flatten)]
+    pub config: UnifiedTopologyRequest,
+    #[serde(default)]
+    pub overrides: Option<TopologyOverrides>,
+}
+
+impl HierarchicalTopologyRequest {
+    /// The unified request the hierarchical builder takes, rejecting a fabric with an empty tier
+    pub fn into_unified(self) -> Result<(UnifiedTopologyRequest, Option<TopologyOverrides>), ApiError> {
+        let mut config = self.config;
+        config.architecture = "hierarchical".to_string();
+        config.tier1_count = self.core_count.unwrap_or(config.tier1_count);
+        config.tier2_count = self.distribution_count.unwrap_or(config.tier2_count);
+        config.tier3_count = self.access_count.unwrap_or(config.tier3_count);
+        config.tier1_to_tier2_ratio = self.core_links_per_distribution.unwrap_or(config.tier1_to_tier2_ratio);
+        config.tier2_to_tier3_ratio = self.distribution_links_per_access.unwrap_or(config.tier2_to_tier3_ratio);
+        if let Some(model) = self.core_model {
+            config.tier1_model = model;
+        }
+        if let Some(model) = self.distribution_model {
+            config.tier2_model = model;
+        }
+        if let Some(model) = self.access_model {
+            config.tier3_model = model;
+        }
+
+        for (field, value) in [
+            ("core_count", config.tier1_count),
+            ("distribution_count", config.tier2_count),
+            ("access_count", config.tier3_count),
+            ("core_links_per_distribution", config.tier1_to_tier2_ratio),
+            ("distribution_links_per_access", config.tier2_to_tier3_ratio),
+        ] {
+            if value == 0 {
+                return Err(ApiError::bad_request(format!("{} must be at least 1", field)));
+            }
+        }
+        Ok((config, self.overrides))
+    }
+}
+
+/// Multi-pod CLOS builder request: pods of spines and leaves joined by a super-spine tier.
+/// Named fields replace their generic tier fields as in `HierarchicalTopologyRequest`;
+/// `architecture` is always clos and the super-spine tier always enabled.
+#[derive(Deserialize)]
+pub struct MultiPodTopologyRequest {
+    #[serde(default)]
+    pub spines_per_pod: Option<usize>,
+    #[serde(default)]
+    pub leaves_per_pod: Option<usize>,
+    /// Parallel links between each spine and leaf of a pod
+    #[serde(default)]
+    pub links_per_leaf: Option<usize>,
+    #[serde(default)]
+    pub spine_model: Option<String>,
+    #[serde(default)]
+    pub leaf_model: Option<String>,
+    #[serde(flatten)]
+    pub config: UnifiedTopologyRequest,
+    #[serde(default)]
+    pub overrides: Option<TopologyOverrides>,
+}
+
+impl MultiPodTopologyRequest {
+    /// The unified request the CLOS builder takes, rejecting fewer than two pods or an empty tier
+    pub fn into_unified(self) -> Result<(UnifiedTopologyRequest, Option<TopologyOverrides>), ApiError> {
+        let mut config = self.config;
+        config.architecture = "clos".to_string();
+        config.super_spine_enabled = true;
+        config.tier1_count = self.spines_per_pod.unwrap_or(config.tier1_count);
+        config.tier2_count = self.leaves_per_pod.unwrap_or(config.tier2_count);
+        config.tier1_to_tier2_ratio = self.links_per_leaf.unwrap_or(config.tier1_to_tier2_ratio);
+        if let Some(model) = self.spine_model {
+            config.tier1_model = model;
+        }
+        if let Some(model) = self.leaf_model {
+            config.tier2_model = model;
+        }
+
+        if config.pods < 2 {
+            return Err(ApiError::bad_request("pods must be at least 2"));
+        }
+        for (field, value) in [
+            ("spines_per_pod", config.tier1_count),
+            ("leaves_per_pod", config.tier2_count),
+            ("links_per_leaf", config.tier1_to_tier2_ratio),
+            ("super_spine_count", config.super_spine_count),
+        ] {
+            if value == 0 {
+                return Err(ApiError::bad_request(format!("{} must be at least 1", field)));
+            }
+        }
+        Ok((config, self.overrides))
+    }
+}
+
 #[derive(Deserialize)]
 pub struct TopologyOverrides {
     pub devices: Vec<TopologyPreviewDevice>,
@@ -494,6 +814,60 @@ pub struct TopologyOverrides {
     pub racks: Option<Vec<TopologyPreviewRack>>,
 }
 
+/// Inject the startup-config and a modprobe wrapper into a created cEOS container before it starts.
+/// Docker Desktop's Linux VM doesn't have /lib/modules, so `modprobe tun`
+/// fails during EosStage2 init, preventing SSH and other agents from starting.
+/// We inject a wrapper that silently succeeds for known-safe modules.
+pub(super) async fn inject_ceos_files(
+    docker: &bollard::Docker,
+    container_id: &str,
+    hostname: &str,
+    serial_number: &str,
+) {
+    let config_content = CEOS_STARTUP_CONFIG
+        .replace("{hostname}", hostname)
+        .replace("{serial_number}", serial_number);
+
+    // Inject startup-config into /mnt/flash
+    match build_tar(&[("startup-config", config_content.as_bytes(), 0o644)]) {
+        Ok(tar_bytes) => {
+            let options = bollard::container::UploadToContainerOptions {
+                path: "/mnt/flash".to_string(),
+                ..Default::default()
+            };
+            if let Err(e) = docker
+                .upload_to_container(container_id, Some(options), tar_bytes.into())
+                .await
+            {
+                tracing::warn!("Failed to upload startup-config to cEOS: {}", e);
+            }
+        }
+        Err(e) => {
+            tracing::warn!("Failed to build startup-config tar: {}", e);
+        }
+    }
+
+    // Inject modprobe wrapper that always succeeds.
+    let modprobe_wrapper = b"#!/bin/sh\nexit 0\n";
+    match build_tar(&[("modprobe", modprobe_wrapper.as_slice(), 0o755)]) {
+        Ok(tar_bytes) => {
+            let options = bollard::container::UploadToContainerOptions {
+                path: "/sbin".to_string(),
+                ..Default::default()
+            };
+            if let Err(e) = docker
+                .upload_to_container(container_id, Some(options), tar_bytes.into())
+                .await
+            {
+                tracing::warn!("Failed to upload modprobe wrapper to cEOS: {}", e);
+            }
+        }
+        Err(e) => {
+            tracing::warn!("Failed to build modprobe wrapper tar: {}", e);
+        }
+    }
+}
+
 /// Build a tar archive in memory containing files
 pub(super) fn build_tar(files: &[(&str, &[u8], u32)]) -> Result<Vec<u8>, String> {
     let mut archive = tar::Builder::new(Vec::new());
@@ -0,0 +1,397 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use super::helpers::*;
+use crate::handlers::ApiError;
+use crate::models::*;
+use crate::AppState;
+
+/// A cabled port pair between two lab nodes
+struct LabLink {
+    a: Device,
+    a_port: String,
+    b: Device,
+    b_port: String,
+}
+
+fn lab_image(requested: &str) -> String {
+    if requested.is_empty() {
+        std::env::var("CEOS_IMAGE").unwrap_or_else(|_| "ceosimage:latest".to_string())
+    } else {
+        requested.to_string()
+    }
+}
+
+fn lab_container_name(topology_id: i64, hostname: &str) -> String {
+    format!("lab-{}-{}", topology_id, hostname)
+}
+
+/// The topology's network devices (external peers and patch panels have no lab node)
+/// and the links cabled between them, each link listed once
+async fn lab_members(state: &AppState, topology_id: i64) -> Result<(Vec<Device>, Vec<LabLink>), ApiError> {
+    state
+        .store
+        .get_topology(topology_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("topology"))?;
+
+    let filter = DeviceFilter {
+        topology_id: Some(topology_id),
+        ..Default::default()
+    };
+    let devices: Vec<Device> = state
+        .store
+        .list_devices_filtered(&filter)
+        .await?
+        .into_iter()
+        .filter(|d| d.device_type != "external" && d.topology_role.as_deref() != Some("patch panel"))
+        .collect();
+    let by_id: HashMap<i64, &Device> = devices.iter().map(|d| (d.id, d)).collect();
+
+    let mut links = Vec::new();
+    let mut seen = HashSet::new();
+    for device in &devices {
+        for pa in state.store.list_port_assignments(device.id).await? {
+            let Some(remote) = pa.remote_device_id.and_then(|id| by_id.get(&id)) else { continue };
+            let key = if device.id < remote.id {
+                (device.id, pa.port_name.clone(), remote.id, pa.remote_port_name.clone())
+            } else {
+                (remote.id, pa.remote_port_name.clone(), device.id, pa.port_name.clone())
+            };
+            if !seen.insert(key) {
+                continue;
+            }
+            links.push(LabLink {
+                a: device.clone(),
+                a_port: pa.port_name,
+                b: (*remote).clone(),
+                b_port: pa.remote_port_name,
+            });
+        }
+    }
+    Ok((devices, links))
+}
+
+fn lab_container_config(image: &str, hostname: &str, topology_id: i64, network_name: &str) -> bollard::container::Config<String> {
+    let mut labels = HashMap::new();
+    labels.insert("fc-test-client".to_string(), "true".to_string());
+    labels.insert("fc-lab".to_string(), topology_id.to_string());
+
+    let mut endpoints = HashMap::new();
+    endpoints.insert(network_name.to_string(), bollard::models::EndpointSettings::default());
+    let networking_config = Some(bollard::container::NetworkingConfig {
+        endpoints_config: endpoints,
+    });
+
+    if is_frr_image(image) {
+        labels.insert("fc-frr".to_string(), "true".to_string());
+        let mut sysctls = HashMap::new();
+        sysctls.insert("net.ipv4.ip_forward".to_string(), "1".to_string());
+        sysctls.insert("net.ipv6.conf.all.forwarding".to_string(), "1".to_string());
+        bollard::container::Config {
+            image: Some(image.to_string()),
+            hostname: Some(hostname.to_string()),
+            env: Some(vec![format!("DEVICE_HOSTNAME={}", hostname)]),
+            labels: Some(labels),
+            host_config: Some(bollard::models::HostConfig {
+                cap_add: Some(vec!["NET_ADMIN".to_string(), "SYS_ADMIN".to_string()]),
+                privileged: Some(true),
+                sysctls: Some(sysctls),
+                ..Default::default()
+            }),
+            networking_config,
+            ..Default::default()
+        }
+    } else {
+        labels.insert("fc-ceos".to_string(), "true".to_string());
+        bollard::container::Config {
+            image: Some(image.to_string()),
+            hostname: Some(hostname.to_string()),
+            env: Some(vec![
+                "CEOS=1".to_string(),
+                "container=docker".to_string(),
+                "INTFTYPE=eth".to_string(),
+                "ETBA=1".to_string(),
+                "SKIP_STARTUP_CONFIG=false".to_string(),
+                "MAPETH0=1".to_string(),
+            ]),
+            entrypoint: Some(vec![
+                "/bin/bash".to_string(),
+                "-c".to_string(),
+                "mknod -m 600 /dev/console c 5 1 2>/dev/null; exec /sbin/init \"$@\"".to_string(),
+                "--".to_string(),
+            ]),
+            cmd: Some(vec![
+                "systemd.setenv=INTFTYPE=eth".to_string(),
+                "systemd.setenv=ETBA=1".to_string(),
+                "systemd.setenv=CEOS=1".to_string(),
+                "systemd.setenv=container=docker".to_string(),
+            ]),
+            labels: Some(labels),
+            host_config: Some(bollard::models::HostConfig {
+                privileged: Some(true),
+                devices: Some(vec![bollard::models::DeviceMapping {
+                    path_on_host: Some("/dev/net/tun".to_string()),
+                    path_in_container: Some("/dev/net/tun".to_string()),
+                    cgroup_permissions: Some("rwm".to_string()),
+                }]),
+                ..Default::default()
+            }),
+            networking_config,
+            ..Default::default()
+        }
+    }
+}
+
+/// Generate a containerlab topology file for running the lab outside forge-config.
+/// Pass ?image= to override the node image.
+pub async fn get_containerlab_definition(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(req): Query<LabDeployRequest>,
+) -> Result<Response, ApiError> {
+    let topology = state
+        .store
+        .get_topology(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("topology"))?;
+    let (devices, links) = lab_members(&state, id).await?;
+
+    let image = lab_image(&req.image);
+    let kind = if is_frr_image(&image) { "linux" } else { "ceos" };
+    let nodes: Vec<(&str, &str, &str)> = devices
+        .iter()
+        .map(|d| (d.hostname.as_str(), kind, image.as_str()))
+        .collect();
+    let link_refs: Vec<(&str, &str, &str, &str)> = links
+        .iter()
+        .map(|l| (l.a.hostname.as_str(), l.a_port.as_str(), l.b.hostname.as_str(), l.b_port.as_str()))
+        .collect();
+
+    let name: String = topology
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    let yaml = crate::utils::containerlab_topology(&name, &nodes, &link_refs);
+    let filename = format!("{}.clab.yml", name);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/yaml".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        yaml,
+    )
+        .into_response())
+}
+
+/// List the lab nodes of a topology
+pub async fn list_lab_nodes(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<LabNode>>, ApiError> {
+    Ok(Json(state.store.list_lab_nodes(id).await?))
+}
+
+/// Launch a topology as a virtual lab: one cEOS (or FRR) container per device on the
+/// management network, plus a point-to-point bridge network per cabled link. Links are
+/// attached in port order, so container interfaces follow the device's port numbering
+/// when the cabled ports are contiguous.
+pub async fn deploy_lab(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    body: Option<Json<LabDeployRequest>>,
+) -> Result<(StatusCode, Json<Vec<LabNode>>), ApiError> {
+    let req = body.map(|b| b.0).unwrap_or_default();
+    let (devices, mut links) = lab_members(&state, id).await?;
+    if devices.is_empty() {
+        return Err(ApiError::bad_request("topology has no devices to virtualize"));
+    }
+    if !state.store.list_lab_nodes(id).await?.is_empty() {
+        return Err(ApiError::conflict("lab is already deployed; destroy it first"));
+    }
+
+    let docker = connect_docker()?;
+    let network_name = get_network_name();
+    let image = lab_image(&req.image);
+    let ceos = !is_frr_image(&image);
+
+    let mut containers: HashMap<i64, (String, String)> = HashMap::new();
+    for device in &devices {
+        let container_name = lab_container_name(id, &device.hostname);
+        let options = bollard::container::CreateContainerOptions {
+            name: container_name.clone(),
+            platform: None,
+        };
+        let resp = docker
+            .create_container(Some(options), lab_container_config(&image, &device.hostname, id, &network_name))
+            .await
+            .map_err(|e| docker_error("create", e))?;
+        // Record the node right away so a failed deploy can still be destroyed
+        state
+            .store
+            .upsert_lab_node(id, device.id, &container_name, &resp.id, &image, "")
+            .await?;
+        if ceos {
+            let serial = format!("SN-LAB-{}", device.hostname);
+            inject_ceos_files(&docker, &resp.id, &device.hostname, &serial).await;
+        }
+        containers.insert(device.id, (container_name, resp.id));
+    }
+
+    links.sort_by_key(|l| {
+        crate::utils::containerlab_interface(&l.a_port)
+            .and_then(|i| i.trim_start_matches("eth").split('_').next()?.parse::<u32>().ok())
+            .unwrap_or(u32::MAX)
+    });
+    for (n, link) in links.iter().enumerate() {
+        let net_name = format!("lab-{}-link{}", id, n + 1);
+        let mut labels = HashMap::new();
+        labels.insert("fc-lab".to_string(), id.to_string());
+        let create_opts = bollard::network::CreateNetworkOptions {
+            name: net_name.clone(),
+            driver: "bridge".to_string(),
+            labels,
+            ..Default::default()
+        };
+        if let Err(e) = docker.create_network(create_opts).await {
+            tracing::warn!("Failed to create lab network {}: {}", net_name, e);
+            continue;
+        }
+        for device in [&link.a, &link.b] {
+            let Some((container_name, _)) = containers.get(&device.id) else { continue };
+            let connect = bollard::network::ConnectNetworkOptions {
+                container: container_name.clone(),
+                ..Default::default()
+            };
+            if let Err(e) = docker.connect_network(&net_name, connect).await {
+                tracing::warn!("Failed to connect {} to {}: {}", container_name, net_name, e);
+            }
+        }
+    }
+
+    for device in &devices {
+        let Some((container_name, container_id)) = containers.get(&device.id) else { continue };
+        if let Err(e) = docker.start_container::<String>(container_id, None).await {
+            tracing::warn!("Failed to start lab container {}: {}", container_name, e);
+        }
+        let mgmt_ip = match docker.inspect_container(container_id, None).await {
+            Ok(inspect) => container_ip(&inspect, &network_name),
+            Err(_) => String::new(),
+        };
+        state
+            .store
+            .upsert_lab_node(id, device.id, container_name, container_id, &image, &mgmt_ip)
+            .await?;
+    }
+
+    tracing::info!("Deployed lab for topology {} with {} nodes and {} links", id, devices.len(), links.len());
+    Ok((StatusCode::CREATED, Json(state.store.list_lab_nodes(id).await?)))
+}
+
+/// Remove a topology's lab containers and link networks
+pub async fn destroy_lab(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let docker = connect_docker()?;
+    let label = format!("fc-lab={}", id);
+
+    let mut filters = HashMap::new();
+    filters.insert("label", vec![label.as_str()]);
+    let containers = docker
+        .list_containers(Some(bollard::container::ListContainersOptions {
+            all: true,
+            filters: filters.clone(),
+            ..Default::default()
+        }))
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to list containers: {}", e)))?;
+
+    let mut removed = 0;
+    for ctr in containers {
+        let Some(ctr_id) = ctr.id else { continue };
+        let options = bollard::container::RemoveContainerOptions {
+            force: true,
+            ..Default::default()
+        };
+        match docker.remove_container(&ctr_id, Some(options)).await {
+            Ok(()) => removed += 1,
+            Err(e) => tracing::warn!("Failed to remove lab container {}: {}", ctr_id, e),
+        }
+    }
+
+    let networks = docker
+        .list_networks(Some(bollard::network::ListNetworksOptions { filters }))
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to list networks: {}", e)))?;
+    for net in networks {
+        let Some(name) = net.name else { continue };
+        if let Err(e) = docker.remove_network(&name).await {
+            tracing::warn!("Failed to remove lab network {}: {}", name, e);
+        }
+    }
+
+    state.store.delete_lab_nodes(id).await?;
+
+    Ok(Json(serde_json::json!({
+        "message": format!("Removed {} lab container(s)", removed),
+        "removed": removed,
+    })))
+}
+
+/// Queue deploy or diff jobs against every lab node. The jobs render each device's
+/// normal config but connect to its lab container instead of the hardware.
+pub async fn run_lab_jobs(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<LabJobRequest>,
+) -> Result<(StatusCode, Json<Vec<Job>>), ApiError> {
+    if req.job_type != job_type::DEPLOY && req.job_type != job_type::DIFF {
+        return Err(ApiError::bad_request("job_type must be deploy or diff"));
+    }
+    let nodes = state.store.list_lab_nodes(id).await?;
+    if nodes.is_empty() {
+        return Err(ApiError::bad_request("topology has no lab deployed"));
+    }
+
+    let nodes: Vec<_> = nodes.iter().filter(|n| !n.mgmt_ip.is_empty()).collect();
+    crate::handlers::jobs::require_queue_capacity(&state, nodes.len())?;
+
+    let mut jobs = Vec::new();
+    for node in nodes {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let job_req = CreateJobRequest {
+            device_id: node.device_id,
+            job_type: req.job_type.clone(),
+            command: String::new(),
+            credential_id: req.credential_id.clone(),
+            triggered_by: TRIGGERED_BY_LAB.to_string(),
+            timeout_secs: None,
+        };
+        let job = state.store.create_job(&job_id, &job_req).await?;
+
+        if let Some(ref hub) = state.ws_hub {
+            hub.broadcast_job_update(crate::ws::EventType::JobQueued, &job).await;
+        }
+        if let Some(ref job_service) = state.job_service {
+            job_service.submit(job_id).await;
+        }
+        jobs.push(job);
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(jobs)))
+}
@@ -1,8 +1,12 @@
 mod containers;
+mod lab;
+mod stacks;
 mod virtual_clos;
 mod three_tier;
 mod helpers;
 
 pub use containers::*;
+pub use lab::*;
+pub use stacks::*;
 pub use virtual_clos::*;
 pub use three_tier::*;
@@ -0,0 +1,478 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use super::helpers::*;
+use crate::handlers::{created, ApiError, ValidJson};
+use crate::models::*;
+use crate::AppState;
+
+/// Docker container names allow [a-zA-Z0-9][a-zA-Z0-9_.-]*
+fn is_valid_container_name_part(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphanumeric())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+fn stack_container_name(stack: &str, service: &str) -> String {
+    format!("fc-{}-{}", stack, service)
+}
+
+async fn validate_stack(state: &AppState, req: &CreateDockerStackRequest) -> Result<(), ApiError> {
+    if !is_valid_container_name_part(&req.name) {
+        return Err(ApiError::bad_request(
+            "name is required and may only contain letters, digits, '_', '.' and '-'",
+        ));
+    }
+    let mut seen = HashSet::new();
+    for svc in &req.services {
+        if !is_valid_container_name_part(&svc.name) {
+            return Err(ApiError::bad_request(format!(
+                "invalid service name '{}': may only contain letters, digits, '_', '.' and '-'",
+                svc.name
+            )));
+        }
+        if !seen.insert(svc.name.as_str()) {
+            return Err(ApiError::bad_request(format!("duplicate service name '{}'", svc.name)));
+        }
+        if let Some(device_id) = svc.device_id {
+            if state.store.get_device(device_id).await?.is_none() {
+                return Err(ApiError::bad_request(format!(
+                    "service '{}': device {} not found",
+                    svc.name, device_id
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn ensure_unique_name(state: &AppState, name: &str, id: Option<i64>) -> Result<(), ApiError> {
+    if let Some(existing) = state.store.get_docker_stack_by_name(name).await? {
+        if Some(existing.id) != id {
+            return Err(ApiError::conflict(format!("stack '{}' already exists", name)));
+        }
+    }
+    Ok(())
+}
+
+async fn load_stack(state: &AppState, id: i64) -> Result<DockerStack, ApiError> {
+    state
+        .store
+        .get_docker_stack(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("docker stack"))
+}
+
+/// Containers of a stack keyed by service name, including containers of services
+/// that have since been removed from the definition
+async fn stack_containers(
+    docker: &bollard::Docker,
+    stack: &str,
+) -> Result<HashMap<String, bollard::models::ContainerSummary>, ApiError> {
+    let label = format!("fc-stack={}", stack);
+    let mut filters = HashMap::new();
+    filters.insert("label", vec![label.as_str()]);
+    let options = bollard::container::ListContainersOptions {
+        all: true,
+        filters,
+        ..Default::default()
+    };
+    let containers = docker
+        .list_containers(Some(options))
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to list containers: {}", e)))?;
+
+    Ok(containers
+        .into_iter()
+        .filter_map(|ctr| {
+            let service = ctr.labels.as_ref()?.get("fc-stack-service")?.clone();
+            Some((service, ctr))
+        })
+        .collect())
+}
+
+fn service_config(stack: &DockerStack, svc: &StackService, network_name: &str) -> bollard::container::Config<String> {
+    let mut labels = HashMap::new();
+    labels.insert("fc-stack".to_string(), stack.name.clone());
+    labels.insert("fc-stack-service".to_string(), svc.name.clone());
+    labels.insert("fc-stack-kind".to_string(), svc.kind.clone());
+
+    // FRR nodes route between fabric networks; dnsmasq needs raw sockets for DHCP
+    let mut host_config = match svc.kind.as_str() {
+        stack_service_kind::FRR => {
+            let mut sysctls = HashMap::new();
+            sysctls.insert("net.ipv4.ip_forward".to_string(), "1".to_string());
+            bollard::models::HostConfig {
+                cap_add: Some(vec!["NET_ADMIN".to_string(), "SYS_ADMIN".to_string()]),
+                sysctls: Some(sysctls),
+                ..Default::default()
+            }
+        }
+        stack_service_kind::DNSMASQ => bollard::models::HostConfig {
+            cap_add: Some(vec!["NET_ADMIN".to_string(), "NET_RAW".to_string()]),
+            ..Default::default()
+        },
+        _ => bollard::models::HostConfig::default(),
+    };
+    if svc.privileged {
+        host_config.privileged = Some(true);
+    }
+
+    let mut endpoints = HashMap::new();
+    endpoints.insert(network_name.to_string(), bollard::models::EndpointSettings::default());
+
+    bollard::container::Config {
+        image: Some(svc.image.clone()),
+        hostname: Some(svc.name.clone()),
+        env: Some(svc.env.clone()),
+        cmd: if svc.command.is_empty() { None } else { Some(svc.command.clone()) },
+        labels: Some(labels),
+        host_config: Some(host_config),
+        networking_config: Some(bollard::container::NetworkingConfig {
+            endpoints_config: endpoints,
+        }),
+        ..Default::default()
+    }
+}
+
+/// Inspect each service's container
+async fn collect_status(
+    docker: &bollard::Docker,
+    stack: &DockerStack,
+) -> Result<StackStatus, ApiError> {
+    let network_name = get_network_name();
+    let existing = stack_containers(docker, &stack.name).await?;
+
+    let mut containers = Vec::new();
+    for svc in &stack.services {
+        let container_name = stack_container_name(&stack.name, &svc.name);
+        let Some(summary) = existing.get(&svc.name) else {
+            containers.push(StackContainer {
+                service: svc.name.clone(),
+                container_name,
+                id: String::new(),
+                status: "absent".to_string(),
+                ip: String::new(),
+                device_id: svc.device_id,
+            });
+            continue;
+        };
+
+        let id = summary.id.clone().unwrap_or_default();
+        let (status, ip) = match docker.inspect_container(&id, None).await {
+            Ok(inspect) => (
+                inspect
+                    .state
+                    .as_ref()
+                    .and_then(|s| s.status)
+                    .map(|s| s.to_string())
+                    .unwrap_or_default(),
+                container_ip(&inspect, &network_name),
+            ),
+            Err(_) => (summary.state.clone().unwrap_or_default(), String::new()),
+        };
+
+        containers.push(StackContainer {
+            service: svc.name.clone(),
+            container_name,
+            id: id.chars().take(12).collect(),
+            status,
+            ip,
+            device_id: svc.device_id,
+        });
+    }
+
+    Ok(StackStatus {
+        stack: stack.name.clone(),
+        containers,
+    })
+}
+
+/// Point each service's bound device at its container's address, which Docker may change when
+/// the container starts
+async fn sync_device_ips(state: &AppState, status: &StackStatus) {
+    for ctr in &status.containers {
+        let Some(device_id) = ctr.device_id else { continue };
+        if ctr.ip.is_empty() {
+            continue;
+        }
+        match state.store.get_device(device_id).await {
+            Ok(Some(device)) if device.ip != ctr.ip => {
+                if let Err(e) = state.store.update_device_ip(device_id, &ctr.ip).await {
+                    tracing::warn!("Failed to attach {} to device {}: {}", ctr.ip, device_id, e);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to load device {}: {}", device_id, e),
+        }
+    }
+}
+
+/// List all docker stacks
+pub async fn list_stacks(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<DockerStack>>, ApiError> {
+    let stacks = state.store.list_docker_stacks().await?;
+    Ok(Json(stacks))
+}
+
+/// Get a single docker stack by ID
+pub async fn get_stack(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<DockerStack>, ApiError> {
+    Ok(Json(load_stack(&state, id).await?))
+}
+
+/// Define a new docker stack
+pub async fn create_stack(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateDockerStackRequest>,
+) -> Result<(StatusCode, Json<DockerStack>), ApiError> {
+    validate_stack(&state, &req).await?;
+    ensure_unique_name(&state, &req.name, None).await?;
+    let stack = state.store.create_docker_stack(&req).await?;
+    Ok(created(stack))
+}
+
+/// Update a stack definition — running containers keep their old settings until the next `up`
+pub async fn update_stack(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateDockerStackRequest>,
+) -> Result<Json<DockerStack>, ApiError> {
+    let existing = load_stack(&state, id).await?;
+    validate_stack(&state, &req).await?;
+    ensure_unique_name(&state, &req.name, Some(id)).await?;
+    if existing.name != req.name {
+        // Containers are found by the stack name label, so renaming would orphan them
+        if let Ok(docker) = connect_docker() {
+            if !stack_containers(&docker, &existing.name).await?.is_empty() {
+                return Err(ApiError::conflict("stack has containers; bring it down before renaming"));
+            }
+        }
+    }
+    let stack = state.store.update_docker_stack(id, &req).await?;
+    Ok(Json(stack))
+}
+
+/// Delete a stack definition — refused while its containers exist
+pub async fn delete_stack(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let stack = load_stack(&state, id).await?;
+    if let Ok(docker) = connect_docker() {
+        if !stack_containers(&docker, &stack.name).await?.is_empty() {
+            return Err(ApiError::conflict("stack has containers; bring it down before deleting"));
+        }
+    }
+    state.store.delete_docker_stack(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Current container state for each service of a stack
+pub async fn stack_status(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<StackStatus>, ApiError> {
+    let stack = load_stack(&state, id).await?;
+    let docker = connect_docker()?;
+    Ok(Json(collect_status(&docker, &stack).await?))
+}
+
+/// Re-read each service container's state and point bound devices at the containers' addresses
+pub async fn refresh_stack(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<StackStatus>, ApiError> {
+    let stack = load_stack(&state, id).await?;
+    let docker = connect_docker()?;
+    let status = collect_status(&docker, &stack).await?;
+    sync_device_ips(&state, &status).await;
+    Ok(Json(status))
+}
+
+/// Create and start any missing service containers, start stopped ones, and remove
+/// containers of services no longer in the definition
+pub async fn stack_up(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<StackStatus>, ApiError> {
+    let stack = load_stack(&state, id).await?;
+    let docker = connect_docker()?;
+    let network_name = get_network_name();
+    let existing = stack_containers(&docker, &stack.name).await?;
+
+    let defined: HashSet<&str> = stack.services.iter().map(|s| s.name.as_str()).collect();
+    for (service, ctr) in &existing {
+        if defined.contains(service.as_str()) {
+            continue;
+        }
+        if let Some(ctr_id) = &ctr.id {
+            let options = bollard::container::RemoveContainerOptions {
+                force: true,
+                ..Default::default()
+            };
+            if let Err(e) = docker.remove_container(ctr_id, Some(options)).await {
+                tracing::warn!("Failed to remove stale stack container {}: {}", ctr_id, e);
+            }
+        }
+    }
+
+    for svc in &stack.services {
+        let ctr_id = match existing.get(&svc.name).and_then(|c| c.id.clone()) {
+            Some(ctr_id) => ctr_id,
+            None => {
+                let options = bollard::container::CreateContainerOptions {
+                    name: stack_container_name(&stack.name, &svc.name),
+                    platform: None,
+                };
+                docker
+                    .create_container(Some(options), service_config(&stack, svc, &network_name))
+                    .await
+                    .map_err(|e| docker_error("create", e))?
+                    .id
+            }
+        };
+
+        match docker.start_container::<String>(&ctr_id, None).await {
+            Ok(()) => {}
+            // Already running
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 304, .. }) => {}
+            Err(e) => return Err(docker_error("start", e)),
+        }
+    }
+
+    let status = collect_status(&docker, &stack).await?;
+    sync_device_ips(&state, &status).await;
+    Ok(Json(status))
+}
+
+/// Stop and remove every container of a stack
+pub async fn stack_down(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let stack = load_stack(&state, id).await?;
+    let docker = connect_docker()?;
+
+    let mut removed = 0;
+    for ctr in stack_containers(&docker, &stack.name).await?.values() {
+        let Some(ctr_id) = &ctr.id else { continue };
+        let _ = docker
+            .stop_container(ctr_id, Some(bollard::container::StopContainerOptions { t: 5 }))
+            .await;
+        let options = bollard::container::RemoveContainerOptions {
+            force: true,
+            ..Default::default()
+        };
+        match docker.remove_container(ctr_id, Some(options)).await {
+            Ok(()) => removed += 1,
+            Err(e) => tracing::warn!("Failed to remove stack container {}: {}", ctr_id, e),
+        }
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": format!("Removed {} container(s)", removed),
+        "removed": removed,
+    })))
+}
+
+/// What for_each_stack_container does to each of a stack's containers
+#[derive(Debug, Clone, Copy)]
+enum StackAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl StackAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Restart => "restart",
+        }
+    }
+}
+
+async fn for_each_stack_container(
+    state: &AppState,
+    id: i64,
+    action: StackAction,
+) -> Result<Json<StackStatus>, ApiError> {
+    let stack = load_stack(state, id).await?;
+    let docker = connect_docker()?;
+
+    for ctr in stack_containers(&docker, &stack.name).await?.values() {
+        let Some(ctr_id) = &ctr.id else { continue };
+        let result = match action {
+            StackAction::Start => docker.start_container::<String>(ctr_id, None).await,
+            StackAction::Stop => {
+                docker
+                    .stop_container(ctr_id, Some(bollard::container::StopContainerOptions { t: 5 }))
+                    .await
+            }
+            StackAction::Restart => {
+                docker
+                    .restart_container(ctr_id, Some(bollard::container::RestartContainerOptions { t: 5 }))
+                    .await
+            }
+        };
+        match result {
+            Ok(()) => {}
+            // Already in the requested state
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 304, .. }) => {}
+            Err(e) => return Err(docker_error(action.as_str(), e)),
+        }
+    }
+
+    let status = collect_status(&docker, &stack).await?;
+    if !matches!(action, StackAction::Stop) {
+        sync_device_ips(state, &status).await;
+    }
+    Ok(Json(status))
+}
+
+/// Start all existing containers of a stack
+pub async fn start_stack(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<StackStatus>, ApiError> {
+    for_each_stack_container(&state, id, StackAction::Start).await
+}
+
+/// Stop all containers of a stack, keeping them for a later start
+pub async fn stop_stack(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<StackStatus>, ApiError> {
+    for_each_stack_container(&state, id, StackAction::Stop).await
+}
+
+/// Restart all containers of a stack
+pub async fn restart_stack(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<StackStatus>, ApiError> {
+    for_each_stack_container(&state, id, StackAction::Restart).await
+}
@@ -645,6 +645,26 @@ pub(super) async fn compute_three_tier_preview(
     })
 }
 
+/// Build a hierarchical fabric from core/distribution/access counts and link multiplicities
+pub async fn build_hierarchical_topology(
+    auth: crate::auth::AuthUser,
+    state: State<Arc<AppState>>,
+    Json(req): Json<HierarchicalTopologyRequest>,
+) -> Result<Json<TopologyBuildResponse>, ApiError> {
+    let (config, overrides) = req.into_unified()?;
+    build_three_tier(auth, state, config, overrides).await
+}
+
+/// Preview the hierarchical fabric `build_hierarchical_topology` would create
+pub async fn preview_hierarchical_topology(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<HierarchicalTopologyRequest>,
+) -> Result<Json<TopologyPreviewResponse>, ApiError> {
+    let (config, _) = req.into_unified()?;
+    Ok(Json(compute_three_tier_preview(&state, &config).await?))
+}
+
 /// Build a hierarchical (3-tier) topology from a UnifiedTopologyRequest
 pub async fn build_three_tier(
     _auth: crate::auth::AuthUser,
@@ -870,6 +890,7 @@ pub async fn build_three_tier(
                     model: Some("PP-192-RJ45".to_string()),
                     serial_number: Some(format!("SN-HIER-{}", final_hostname)),
                     config_template: String::new(),
+                    credential_id: None,
                     ssh_user: None,
                     ssh_pass: None,
                     topology_id: Some(topo_id),
@@ -908,6 +929,7 @@ pub async fn build_three_tier(
                 model: Some(mgmt_model.clone()),
                 serial_number: Some(format!("SN-HIER-{}", hostname)),
                 config_template: String::new(),
+                credential_id: None,
                 ssh_user: Some("admin".to_string()),
                 ssh_pass: Some("admin".to_string()),
                 topology_id: Some(topo_id),
@@ -1103,6 +1125,7 @@ pub async fn build_three_tier(
             model: Some(node.model.to_string()),
             serial_number: Some(serial),
             config_template: vendor_base_template_id.clone(),
+            credential_id: None,
             ssh_user: Some("admin".to_string()),
             ssh_pass: Some("admin".to_string()),
             topology_id: Some(topo_id),
@@ -1128,6 +1151,7 @@ pub async fn build_three_tier(
                                     description: Some("Auto-created for device role".to_string()),
                                     parent_id: None,
                                     precedence: 100,
+                                    credential_id: None,
                                 };
                                 match state.store.create_group(&group_req).await {
                                     Ok(g) => g.id,
@@ -1748,6 +1772,7 @@ pub async fn build_three_tier(
                                             model: Some(node.model.clone()),
                                             serial_number: Some(serial.clone()),
                                             config_template: vendor_base_template_id.clone(),
+                                            credential_id: None,
                                             ssh_user: Some("admin".to_string()),
                                             ssh_pass: Some("admin".to_string()),
                                             topology_id: Some(topo_id),
@@ -1928,6 +1953,7 @@ pub async fn build_three_tier(
                     model: Some(gpu_model_name.clone()),
                     serial_number: Some(format!("SN-GPU-{}", gpu_hostname)),
                     config_template: String::new(),
+                    credential_id: None,
                     ssh_user: None,
                     ssh_pass: None,
                     topology_id: Some(topo_id),
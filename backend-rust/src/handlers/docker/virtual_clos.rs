@@ -40,6 +40,49 @@ pub async fn build_topology(
     }
 }
 
+/// Build a multi-pod CLOS fabric with a super-spine tier
+pub async fn build_multi_pod_topology(
+    auth: crate::auth::AuthUser,
+    state: State<Arc<AppState>>,
+    Json(req): Json<MultiPodTopologyRequest>,
+) -> Result<Json<TopologyBuildResponse>, ApiError> {
+    let (config, overrides) = req.into_unified()?;
+    build_virtual_clos(auth, state, config, overrides).await
+}
+
+/// Preview the multi-pod fabric `build_multi_pod_topology` would create
+pub async fn preview_multi_pod_topology(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MultiPodTopologyRequest>,
+) -> Result<Json<TopologyPreviewResponse>, ApiError> {
+    let (config, _) = req.into_unified()?;
+    Ok(Json(compute_clos_preview(&state, &config).await?))
+}
+
+/// Reject a CLOS request whose ASN ranges collide or whose super-spine tier doesn't fit the
+/// spine and super-spine models' ports, before anything is built
+async fn validate_clos(state: &AppState, req: &UnifiedTopologyRequest) -> Result<(), ApiError> {
+    validate_clos_request(req)?;
+    if !req.super_spine_enabled {
+        return Ok(());
+    }
+    let models = state.store.list_device_models().await
+        .map_err(|e| ApiError::internal(format!("Failed to load device models: {}", e)))?;
+    let port_count = |model: &str, default: &str| {
+        let model = if model.is_empty() { default } else { model };
+        models.iter()
+            .find(|m| m.model == model)
+            .map(|m| get_ports_by_min_speed(&m.layout, 100_000).len())
+            .unwrap_or(0)
+    };
+    validate_super_spine_ports(
+        req,
+        port_count(&req.tier1_model, "7050CX3-32S"),
+        port_count(&req.super_spine_model, "7050CX3-32S"),
+    )
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Topology Preview — read-only computation of what a CLOS build will produce
 // ─────────────────────────────────────────────────────────────────────────────
@@ -50,6 +93,8 @@ pub(super) async fn compute_clos_preview(
     state: &Arc<AppState>,
     req: &UnifiedTopologyRequest,
 ) -> Result<TopologyPreviewResponse, ApiError> {
+    validate_clos(state, req).await?;
+
     // ── 1. Read hostname_pattern from settings (read-only) ──────────────
     let settings = state.store.get_settings().await.unwrap_or_default();
     let hostname_pattern = &settings.hostname_pattern;
@@ -181,7 +226,7 @@ pub(super) async fn compute_clos_preview(
             hostname: resolve_hostname(hostname_pattern, dc, region, &hall_name, &row_num, "spine", i),
             role: "spine".to_string(),
             loopback: format!("10.255.0.{}", i),
-            asn: 65000,
+            asn: clos_spine_asn(req, pod_count, i),
             model: spine_model.clone(),
             mgmt_ip: format!("172.20.0.{}", 10 + i),
             rack_name,
@@ -241,7 +286,7 @@ pub(super) async fn compute_clos_preview(
             hostname: resolve_hostname(hostname_pattern, dc, region, "", "", "super-spine", i),
             role: "super-spine".to_string(),
             loopback: format!("10.255.3.{}", i),
-            asn: 65500,
+            asn: SUPER_SPINE_ASN,
             model: ss_model.clone(),
             mgmt_ip: format!("172.20.4.{}", 10 + i),
             rack_name,
@@ -794,6 +839,8 @@ pub async fn build_virtual_clos(
     req: UnifiedTopologyRequest,
     overrides: Option<TopologyOverrides>,
 ) -> Result<Json<TopologyBuildResponse>, ApiError> {
+    validate_clos(&state, &req).await?;
+
     // Teardown any existing virtual CLOS first
     teardown_virtual_clos_inner(&state).await;
 
@@ -1024,6 +1071,7 @@ pub async fn build_virtual_clos(
                     model: Some("PP-192-RJ45".to_string()),
                     serial_number: Some(format!("SN-VCLOS-{}", final_hostname)),
                     config_template: String::new(),
+                    credential_id: None,
                     ssh_user: None,
                     ssh_pass: None,
                     topology_id: Some(topo_id),
@@ -1064,6 +1112,7 @@ pub async fn build_virtual_clos(
                 model: Some(mgmt_model.clone()),
                 serial_number: Some(format!("SN-VCLOS-{}", hostname)),
                 config_template: String::new(),
+                credential_id: None,
                 ssh_user: Some("admin".to_string()),
                 ssh_pass: Some("admin".to_string()),
                 topology_id: Some(topo_id),
@@ -1153,7 +1202,7 @@ pub async fn build_virtual_clos(
             hostname: resolve_hostname(hostname_pattern, dc, region, &hall_name, &row_name, "spine", i),
             role: "spine".to_string(),
             loopback: format!("10.255.0.{}", i),
-            asn: 65000,
+            asn: clos_spine_asn(&req, pod_count, i),
             model: spine_model.clone(),
             mgmt_ip: format!("172.20.0.{}", 10 + i),
             hall_id: h,
@@ -1235,7 +1284,7 @@ pub async fn build_virtual_clos(
             hostname: resolve_hostname(hostname_pattern, dc, region, "", "", "super-spine", i),
             role: "super-spine".to_string(),
             loopback: format!("10.255.3.{}", i),
-            asn: 65500,
+            asn: SUPER_SPINE_ASN,
             model: ss_model_name.clone(),
             mgmt_ip: format!("172.20.4.{}", 10 + i),
             hall_id: h,
@@ -1291,6 +1340,7 @@ pub async fn build_virtual_clos(
             model: Some(node.model.to_string()),
             serial_number: Some(serial),
             config_template: vendor_base_template_id.clone(),
+            credential_id: None,
             ssh_user: Some("admin".to_string()),
             ssh_pass: Some("admin".to_string()),
             topology_id: Some(topo_id),
@@ -1316,6 +1366,7 @@ pub async fn build_virtual_clos(
                                     description: Some("Auto-created for device role".to_string()),
                                     parent_id: None,
                                     precedence: 100,
+                                    credential_id: None,
                                 };
                                 match state.store.create_group(&group_req).await {
                                     Ok(g) => g.id,
@@ -1778,6 +1829,9 @@ pub async fn build_virtual_clos(
             (*device_id, "Loopback".to_string(), node.loopback.clone()),
             (*device_id, "ASN".to_string(), node.asn.to_string()),
         ];
+        if pod_count > 1 {
+            entries.push((*device_id, "Pod".to_string(), (si / spines_per_pod + 1).to_string()));
+        }
         for (idx, peer_ip, peer_asn, peer_name, local_addr) in &spine_vars[si] {
             entries.push((*device_id, format!("Peer{}", idx), peer_ip.clone()));
             entries.push((*device_id, format!("Peer{}ASN", idx), peer_asn.clone()));
@@ -1795,6 +1849,9 @@ pub async fn build_virtual_clos(
             (*device_id, "Loopback".to_string(), node.loopback.clone()),
             (*device_id, "ASN".to_string(), node.asn.to_string()),
         ];
+        if pod_count > 1 {
+            entries.push((*device_id, "Pod".to_string(), (li / leaves_per_pod + 1).to_string()));
+        }
         for (idx, peer_ip, peer_asn, peer_name, local_addr) in &leaf_vars[li] {
             entries.push((*device_id, format!("Peer{}", idx), peer_ip.clone()));
             entries.push((*device_id, format!("Peer{}ASN", idx), peer_asn.clone()));
@@ -2268,6 +2325,7 @@ pub async fn build_virtual_clos(
                                             model: Some(node.model.clone()),
                                             serial_number: Some(serial.clone()),
                                             config_template: vendor_base_template_id.clone(),
+                                            credential_id: None,
                                             ssh_user: Some("admin".to_string()),
                                             ssh_pass: Some("admin".to_string()),
                                             topology_id: Some(topo_id),
@@ -2440,6 +2498,7 @@ pub async fn build_virtual_clos(
                     model: Some(gpu_model_name.clone()),
                     serial_number: Some(format!("SN-GPU-{}", gpu_hostname)),
                     config_template: String::new(),
+                    credential_id: None,
                     ssh_user: None,
                     ssh_pass: None,
                     topology_id: Some(topo_id),
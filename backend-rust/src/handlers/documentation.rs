@@ -0,0 +1,334 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use base64::Engine;
+use chrono::Utc;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+/// Attachment files live here, under the backup directory
+const ATTACHMENTS_SUBDIR: &str = "attachments";
+const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+/// Request body limit for uploads: the base64-encoded file plus its metadata
+pub const MAX_ATTACHMENT_UPLOAD_BYTES: usize = MAX_ATTACHMENT_BYTES / 3 * 4 + 64 * 1024;
+
+fn attachments_dir(state: &AppState) -> PathBuf {
+    std::path::Path::new(&state.config.backup_dir).join(ATTACHMENTS_SUBDIR)
+}
+
+/// Content type for an attachment without one, from its filename's extension
+fn guess_content_type(filename: &str) -> &'static str {
+    let ext = filename.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "txt" | "cfg" | "conf" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+async fn require_owner(state: &AppState, auth: &crate::auth::AuthUser, owner: DocOwner, modify: bool) -> Result<(), ApiError> {
+    match owner {
+        DocOwner::Device(id) => {
+            state.store.get_device(id).await?.ok_or_else(|| ApiError::not_found("device"))?;
+            if modify {
+                super::teams::require_modify(state, auth, team_resource_type::DEVICE, id).await?;
+            }
+        }
+        DocOwner::Topology(id) => {
+            state.store.get_topology(id).await?.ok_or_else(|| ApiError::not_found("topology"))?;
+        }
+    }
+    Ok(())
+}
+
+fn owner_of(device_id: Option<i64>, topology_id: Option<i64>) -> DocOwner {
+    match (device_id, topology_id) {
+        (Some(id), _) => DocOwner::Device(id),
+        (None, Some(id)) => DocOwner::Topology(id),
+        (None, None) => unreachable!("notes and attachments have an owner"),
+    }
+}
+
+/// Remove attachment files whose rows are gone, such as those of a deleted device or topology
+pub(super) async fn prune_attachment_files(state: &AppState) {
+    let dir = attachments_dir(state);
+    let Ok(mut entries) = tokio::fs::read_dir(&dir).await else { return };
+    let known: HashSet<String> = match state.store.list_attachment_stored_names().await {
+        Ok(names) => names.into_iter().collect(),
+        Err(e) => {
+            tracing::warn!("Failed to list attachments: {}", e);
+            return;
+        }
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !known.contains(&name) {
+            if let Err(e) = tokio::fs::remove_file(entry.path()).await {
+                tracing::warn!("Failed to remove orphaned attachment {}: {}", name, e);
+            }
+        }
+    }
+}
+
+async fn list_notes(state: &AppState, owner: DocOwner) -> Result<Json<Vec<Note>>, ApiError> {
+    Ok(Json(state.store.list_notes(owner).await?))
+}
+
+async fn create_note(
+    state: &AppState,
+    auth: &crate::auth::AuthUser,
+    owner: DocOwner,
+    req: CreateNoteRequest,
+) -> Result<(StatusCode, Json<Note>), ApiError> {
+    require_owner(state, auth, owner, true).await?;
+    Ok(created(state.store.create_note(owner, &req, &auth.claims.username).await?))
+}
+
+/// List a device's notes, most recently updated first
+pub async fn list_device_notes(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Note>>, ApiError> {
+    require_owner(&state, &auth, DocOwner::Device(id), false).await?;
+    list_notes(&state, DocOwner::Device(id)).await
+}
+
+/// Add a markdown note to a device
+pub async fn create_device_note(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateNoteRequest>,
+) -> Result<(StatusCode, Json<Note>), ApiError> {
+    create_note(&state, &auth, DocOwner::Device(id), req).await
+}
+
+/// List a topology's notes, most recently updated first
+pub async fn list_topology_notes(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Note>>, ApiError> {
+    require_owner(&state, &auth, DocOwner::Topology(id), false).await?;
+    list_notes(&state, DocOwner::Topology(id)).await
+}
+
+/// Add a markdown note to a topology
+pub async fn create_topology_note(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateNoteRequest>,
+) -> Result<(StatusCode, Json<Note>), ApiError> {
+    create_note(&state, &auth, DocOwner::Topology(id), req).await
+}
+
+/// Change a note's title or body
+pub async fn update_note(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<UpdateNoteRequest>,
+) -> Result<Json<Note>, ApiError> {
+    let mut note = state.store.get_note(id).await?.ok_or_else(|| ApiError::not_found("note"))?;
+    require_owner(&state, &auth, owner_of(note.device_id, note.topology_id), true).await?;
+    if let Some(title) = req.title {
+        note.title = title;
+    }
+    if let Some(body) = req.body {
+        note.body = body;
+    }
+    Ok(Json(state.store.update_note(&note, &auth.claims.username).await?))
+}
+
+/// Delete a note
+pub async fn delete_note(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let note = state.store.get_note(id).await?.ok_or_else(|| ApiError::not_found("note"))?;
+    require_owner(&state, &auth, owner_of(note.device_id, note.topology_id), true).await?;
+    state.store.delete_note(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn upload_attachment(
+    state: &AppState,
+    auth: &crate::auth::AuthUser,
+    owner: DocOwner,
+    req: UploadAttachmentRequest,
+) -> Result<(StatusCode, Json<Attachment>), ApiError> {
+    require_owner(state, auth, owner, true).await?;
+    let filename = req.filename.trim().rsplit(['/', '\\']).next().unwrap_or_default().to_string();
+    if filename.is_empty() {
+        return Err(ApiError::bad_request("filename is required"));
+    }
+    let content = base64::engine::general_purpose::STANDARD
+        .decode(req.content.trim())
+        .map_err(|e| ApiError::bad_request(format!("content is not valid base64: {}", e)))?;
+    if content.is_empty() {
+        return Err(ApiError::bad_request("content is empty"));
+    }
+    if content.len() > MAX_ATTACHMENT_BYTES {
+        return Err(ApiError::bad_request(format!(
+            "attachment is larger than {} MiB",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+    let content_type = match req.content_type {
+        Some(ct) if !ct.trim().is_empty() => ct.trim().to_string(),
+        _ => guess_content_type(&filename).to_string(),
+    };
+
+    let dir = attachments_dir(state);
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| ApiError::internal(format!("failed to create attachments directory: {}", e)))?;
+    let stored_name = uuid::Uuid::new_v4().to_string();
+    tokio::fs::write(dir.join(&stored_name), &content)
+        .await
+        .map_err(|e| ApiError::internal(format!("failed to write attachment: {}", e)))?;
+
+    let attachment = Attachment {
+        id: 0,
+        device_id: owner.device_id(),
+        topology_id: owner.topology_id(),
+        filename,
+        stored_name,
+        content_type,
+        size: content.len() as i64,
+        description: req.description,
+        uploaded_by: auth.claims.username.clone(),
+        created_at: Utc::now(),
+    };
+    match state.store.create_attachment(&attachment).await {
+        Ok(attachment) => Ok(created(attachment)),
+        Err(e) => {
+            let _ = tokio::fs::remove_file(dir.join(&attachment.stored_name)).await;
+            Err(e.into())
+        }
+    }
+}
+
+/// List a device's attachments, newest first
+pub async fn list_device_attachments(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Attachment>>, ApiError> {
+    require_owner(&state, &auth, DocOwner::Device(id), false).await?;
+    Ok(Json(state.store.list_attachments(DocOwner::Device(id)).await?))
+}
+
+/// Attach a base64-encoded file to a device
+pub async fn upload_device_attachment(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<UploadAttachmentRequest>,
+) -> Result<(StatusCode, Json<Attachment>), ApiError> {
+    upload_attachment(&state, &auth, DocOwner::Device(id), req).await
+}
+
+/// List a topology's attachments, newest first
+pub async fn list_topology_attachments(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Attachment>>, ApiError> {
+    require_owner(&state, &auth, DocOwner::Topology(id), false).await?;
+    Ok(Json(state.store.list_attachments(DocOwner::Topology(id)).await?))
+}
+
+/// Attach a base64-encoded file to a topology
+pub async fn upload_topology_attachment(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<UploadAttachmentRequest>,
+) -> Result<(StatusCode, Json<Attachment>), ApiError> {
+    upload_attachment(&state, &auth, DocOwner::Topology(id), req).await
+}
+
+/// Get an attachment's metadata
+pub async fn get_attachment(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Attachment>, ApiError> {
+    Ok(Json(state.store.get_attachment(id).await?.ok_or_else(|| ApiError::not_found("attachment"))?))
+}
+
+/// Download an attachment's file
+pub async fn download_attachment(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    let attachment = state.store.get_attachment(id).await?.ok_or_else(|| ApiError::not_found("attachment"))?;
+    let content = tokio::fs::read(attachments_dir(&state).join(&attachment.stored_name))
+        .await
+        .map_err(|_| ApiError::not_found("attachment file"))?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", attachment.filename.replace(['"', '\r', '\n'], "_")),
+            ),
+        ],
+        content,
+    ))
+}
+
+/// Delete an attachment and its file
+pub async fn delete_attachment(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let attachment = state.store.get_attachment(id).await?.ok_or_else(|| ApiError::not_found("attachment"))?;
+    require_owner(&state, &auth, owner_of(attachment.device_id, attachment.topology_id), true).await?;
+    state.store.delete_attachment(id).await?;
+    if let Err(e) = tokio::fs::remove_file(attachments_dir(&state).join(&attachment.stored_name)).await {
+        tracing::warn!("Failed to remove attachment file {}: {}", attachment.stored_name, e);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get a device with its groups, latest backup, notes and attachments
+pub async fn get_device_detail(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<DeviceDetail>, ApiError> {
+    let device = state.store.get_device(id).await?.ok_or_else(|| ApiError::not_found("device"))?;
+    let owner = DocOwner::Device(id);
+    Ok(Json(DeviceDetail {
+        device,
+        groups: state.store.list_device_groups(id).await?,
+        latest_backup: state.store.list_backups(id).await?.into_iter().next(),
+        notes: state.store.list_notes(owner).await?,
+        attachments: state.store.list_attachments(owner).await?,
+    }))
+}
@@ -0,0 +1,273 @@
+//! Upsert, read and delete by client-supplied external ID, for declarative clients such as a
+//! Terraform provider. Each call forwards to the regular handler, so validation, team checks and
+//! side effects are the same as the id-based endpoints. PUT returns 201 when it created the
+//! resource and 200 when it updated it, with the stored resource (including computed fields).
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+use crate::auth::AuthUser;
+use crate::models::*;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+fn parse<T: DeserializeOwned>(body: serde_json::Value) -> Result<T, ApiError> {
+    serde_json::from_value(body).map_err(|e| ApiError::bad_request(format!("invalid request body: {}", e)))
+}
+
+/// The caller's identity, for forwarding to the id-based handler
+fn forward(auth: &AuthUser) -> AuthUser {
+    AuthUser { claims: auth.claims.clone() }
+}
+
+async fn resolve(state: &AppState, resource_type: &str, external_id: &str) -> Result<i64, ApiError> {
+    state
+        .store
+        .resolve_external_id(resource_type, external_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("{} with external ID '{}'", resource_type, external_id)))
+}
+
+// ========== Mappings ==========
+
+pub async fn list_external_ids(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExternalIdQuery>,
+) -> Result<Json<Vec<ExternalId>>, ApiError> {
+    Ok(Json(state.store.list_external_ids(query.resource_type.as_deref()).await?))
+}
+
+/// Bind an existing resource to an external ID (adopt/import)
+pub async fn create_external_id(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateExternalIdRequest>,
+) -> Result<(StatusCode, Json<ExternalId>), ApiError> {
+    let exists = match req.resource_type.as_str() {
+        external_resource_type::DEVICE => state.store.get_device(req.resource_id).await?.is_some(),
+        external_resource_type::GROUP => state.store.get_group(req.resource_id).await?.is_some(),
+        external_resource_type::TEMPLATE => state.store.get_template(req.resource_id).await?.is_some(),
+        external_resource_type::IPAM_PREFIX => state.store.get_ipam_prefix(req.resource_id).await?.is_some(),
+        external_resource_type::IPAM_IP_ADDRESS => state.store.get_ipam_ip_address(req.resource_id).await?.is_some(),
+        other => {
+            return Err(ApiError::bad_request(format!(
+                "unknown resource_type '{}': expected one of {}",
+                other,
+                external_resource_type::ALL.join(", ")
+            )))
+        }
+    };
+    if !exists {
+        return Err(ApiError::not_found(&format!("{} {}", req.resource_type, req.resource_id)));
+    }
+    Ok(created(state.store.bind_external_id(&req.resource_type, &req.external_id, req.resource_id).await?))
+}
+
+/// Forget an external ID without deleting the resource
+pub async fn delete_external_id(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((resource_type, external_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    state.store.unbind_external_id(&resource_type, &external_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ========== Devices ==========
+
+pub async fn get_device(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+) -> Result<Json<Device>, ApiError> {
+    let id = resolve(&state, external_resource_type::DEVICE, &external_id).await?;
+    super::devices::get_device(auth, State(state), Path(id)).await
+}
+
+pub async fn put_device(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> Result<Response, ApiError> {
+    if let Some(id) = state.store.resolve_external_id(external_resource_type::DEVICE, &external_id).await? {
+        let req = parse::<UpdateDeviceRequest>(body)?;
+        return Ok(super::devices::update_device(auth, State(state), Path(id), ValidJson::validated(req)?).await?.into_response());
+    }
+    let req = parse::<CreateDeviceRequest>(body)?;
+    let (status, Json(device)) = super::devices::create_device(forward(&auth), State(state.clone()), ValidJson::validated(req)?).await?;
+    if let Err(e) = state.store.bind_external_id(external_resource_type::DEVICE, &external_id, device.id).await {
+        let _ = state.store.delete_device(device.id).await;
+        return Err(e.into());
+    }
+    Ok((status, Json(device)).into_response())
+}
+
+pub async fn delete_device(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let id = resolve(&state, external_resource_type::DEVICE, &external_id).await?;
+    Ok(super::devices::delete_device(auth, State(state), Path(id)).await?.into_response())
+}
+
+// ========== Groups ==========
+
+pub async fn get_group(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+) -> Result<Json<Group>, ApiError> {
+    let id = resolve(&state, external_resource_type::GROUP, &external_id).await?;
+    super::groups::get_group(auth, State(state), Path(id)).await
+}
+
+pub async fn put_group(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> Result<Response, ApiError> {
+    let req = parse::<CreateGroupRequest>(body)?;
+    if let Some(id) = state.store.resolve_external_id(external_resource_type::GROUP, &external_id).await? {
+        return Ok(super::groups::update_group(auth, State(state), Path(id), ValidJson::validated(req)?).await?.into_response());
+    }
+    let (status, Json(group)) = super::groups::create_group(forward(&auth), State(state.clone()), ValidJson::validated(req)?).await?;
+    if let Err(e) = state.store.bind_external_id(external_resource_type::GROUP, &external_id, group.id).await {
+        let _ = state.store.delete_group(group.id).await;
+        return Err(e.into());
+    }
+    Ok((status, Json(group)).into_response())
+}
+
+pub async fn delete_group(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+    query: Query<DeleteQuery>,
+) -> Result<Response, ApiError> {
+    let id = resolve(&state, external_resource_type::GROUP, &external_id).await?;
+    Ok(super::groups::delete_group(auth, State(state), Path(id), query).await?.into_response())
+}
+
+// ========== Templates ==========
+
+pub async fn get_template(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+) -> Result<Json<Template>, ApiError> {
+    let id = resolve(&state, external_resource_type::TEMPLATE, &external_id).await?;
+    super::templates::get_template(auth, State(state), Path(id)).await
+}
+
+pub async fn put_template(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> Result<Response, ApiError> {
+    let req = parse::<CreateTemplateRequest>(body)?;
+    if let Some(id) = state.store.resolve_external_id(external_resource_type::TEMPLATE, &external_id).await? {
+        return Ok(super::templates::update_template(auth, State(state), Path(id), ValidJson::validated(req)?).await?.into_response());
+    }
+    let (status, Json(template)) = super::templates::create_template(forward(&auth), State(state.clone()), ValidJson::validated(req)?).await?;
+    if let Err(e) = state.store.bind_external_id(external_resource_type::TEMPLATE, &external_id, template.id).await {
+        let _ = state.store.delete_template(template.id).await;
+        return Err(e.into());
+    }
+    Ok((status, Json(template)).into_response())
+}
+
+pub async fn delete_template(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+    query: Query<DeleteQuery>,
+) -> Result<Response, ApiError> {
+    let id = resolve(&state, external_resource_type::TEMPLATE, &external_id).await?;
+    Ok(super::templates::delete_template(auth, State(state), Path(id), query).await?.into_response())
+}
+
+// ========== IPAM ==========
+
+pub async fn get_ipam_prefix(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+) -> Result<Json<IpamPrefix>, ApiError> {
+    let id = resolve(&state, external_resource_type::IPAM_PREFIX, &external_id).await?;
+    super::ipam::get_prefix(auth, State(state), Path(id)).await
+}
+
+pub async fn put_ipam_prefix(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> Result<Response, ApiError> {
+    let req = parse::<CreateIpamPrefixRequest>(body)?;
+    if let Some(id) = state.store.resolve_external_id(external_resource_type::IPAM_PREFIX, &external_id).await? {
+        return Ok(super::ipam::update_prefix(auth, State(state), Path(id), ValidJson::validated(req)?).await?.into_response());
+    }
+    let (status, Json(prefix)) = super::ipam::create_prefix(forward(&auth), State(state.clone()), ValidJson::validated(req)?).await?;
+    if let Err(e) = state.store.bind_external_id(external_resource_type::IPAM_PREFIX, &external_id, prefix.id).await {
+        let _ = state.store.delete_ipam_prefix(prefix.id).await;
+        return Err(e.into());
+    }
+    Ok((status, Json(prefix)).into_response())
+}
+
+pub async fn delete_ipam_prefix(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let id = resolve(&state, external_resource_type::IPAM_PREFIX, &external_id).await?;
+    Ok(super::ipam::delete_prefix(auth, State(state), Path(id)).await?.into_response())
+}
+
+pub async fn get_ipam_ip_address(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+) -> Result<Json<IpamIpAddress>, ApiError> {
+    let id = resolve(&state, external_resource_type::IPAM_IP_ADDRESS, &external_id).await?;
+    super::ipam::get_ip_address(auth, State(state), Path(id)).await
+}
+
+pub async fn put_ipam_ip_address(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> Result<Response, ApiError> {
+    let req = parse::<CreateIpamIpAddressRequest>(body)?;
+    if let Some(id) = state.store.resolve_external_id(external_resource_type::IPAM_IP_ADDRESS, &external_id).await? {
+        return Ok(super::ipam::update_ip_address(auth, State(state), Path(id), ValidJson::validated(req)?).await?.into_response());
+    }
+    let (status, Json(ip)) = super::ipam::create_ip_address(forward(&auth), State(state.clone()), ValidJson::validated(req)?).await?;
+    if let Err(e) = state.store.bind_external_id(external_resource_type::IPAM_IP_ADDRESS, &external_id, ip.id).await {
+        let _ = state.store.delete_ipam_ip_address(ip.id).await;
+        return Err(e.into());
+    }
+    Ok((status, Json(ip)).into_response())
+}
+
+pub async fn delete_ipam_ip_address(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(external_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let id = resolve(&state, external_resource_type::IPAM_IP_ADDRESS, &external_id).await?;
+    Ok(super::ipam::delete_ip_address(auth, State(state), Path(id)).await?.into_response())
+}
@@ -7,7 +7,8 @@ use std::sync::Arc;
 use crate::models::*;
 use crate::AppState;
 
-use super::{created, ApiError};
+use super::groups::{SetMembersRequest, SetVariableRequest};
+use super::{created, record_change, ApiError, ValidJson};
 
 /// List all GPU clusters
 pub async fn list_gpu_clusters(
@@ -36,12 +37,8 @@ pub async fn get_gpu_cluster(
 pub async fn create_gpu_cluster(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateGpuClusterRequest>,
+    ValidJson(req): ValidJson<CreateGpuClusterRequest>,
 ) -> Result<(axum::http::StatusCode, Json<GpuCluster>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
-
     let cluster = state.store.create_gpu_cluster(&req).await?;
     Ok(created(cluster))
 }
@@ -51,7 +48,7 @@ pub async fn update_gpu_cluster(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateGpuClusterRequest>,
+    ValidJson(req): ValidJson<CreateGpuClusterRequest>,
 ) -> Result<Json<GpuCluster>, ApiError> {
     let cluster = state.store.update_gpu_cluster(id, &req).await?;
     Ok(Json(cluster))
@@ -66,3 +63,107 @@ pub async fn delete_gpu_cluster(
     state.store.delete_gpu_cluster(id).await?;
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
+
+// ========== GPU Cluster Variables ==========
+
+/// Variables applied to every device assigned to the cluster
+pub async fn list_gpu_cluster_variables(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<GpuClusterVariable>>, ApiError> {
+    Ok(Json(state.store.list_gpu_cluster_variables(id).await?))
+}
+
+pub async fn set_gpu_cluster_variable(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, key)): Path<(i64, String)>,
+    Json(req): Json<SetVariableRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let cluster = state
+        .store
+        .get_gpu_cluster(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("GPU cluster"))?;
+    state.store.set_gpu_cluster_variable(id, &key, &req.value).await?;
+    let summary = format!("set {} on cluster {}", key, cluster.name);
+    record_change(&state, &auth, NewChangeLogEntry::variable(change_action::UPDATE, &key, summary)).await;
+    Ok(Json(serde_json::json!({"message": "variable set"})))
+}
+
+pub async fn delete_gpu_cluster_variable(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, key)): Path<(i64, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let cluster = state
+        .store
+        .get_gpu_cluster(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("GPU cluster"))?;
+    state.store.delete_gpu_cluster_variable(id, &key).await?;
+    let summary = format!("deleted {} on cluster {}", key, cluster.name);
+    record_change(&state, &auth, NewChangeLogEntry::variable(change_action::DELETE, &key, summary)).await;
+    Ok(Json(serde_json::json!({"message": "variable deleted"})))
+}
+
+// ========== Device Assignment ==========
+
+/// Devices assigned to the cluster
+pub async fn list_gpu_cluster_devices(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Device>>, ApiError> {
+    if state.store.get_gpu_cluster(id).await?.is_none() {
+        return Err(ApiError::not_found("GPU cluster"));
+    }
+    let filter = DeviceFilter { gpu_cluster_id: Some(id), ..Default::default() };
+    Ok(Json(state.store.list_devices_filtered(&filter).await?))
+}
+
+/// Replace the cluster's devices; devices not listed are unassigned
+pub async fn set_gpu_cluster_devices(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<SetMembersRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if state.store.get_gpu_cluster(id).await?.is_none() {
+        return Err(ApiError::not_found("GPU cluster"));
+    }
+    state.store.set_gpu_cluster_devices(id, &req.device_ids).await?;
+    Ok(Json(serde_json::json!({"message": "devices updated"})))
+}
+
+/// Assign a device to the cluster, moving it from any other cluster
+pub async fn assign_gpu_cluster_device(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, device_id)): Path<(i64, i64)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if state.store.get_gpu_cluster(id).await?.is_none() {
+        return Err(ApiError::not_found("GPU cluster"));
+    }
+    state.store.assign_device_gpu_cluster(device_id, Some(id)).await?;
+    Ok(Json(serde_json::json!({"message": "device assigned to cluster"})))
+}
+
+/// Unassign a device, if it belongs to the cluster
+pub async fn unassign_gpu_cluster_device(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, device_id)): Path<(i64, i64)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let device = state
+        .store
+        .get_device(device_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    if device.gpu_cluster_id != Some(id) {
+        return Err(ApiError::bad_request("device is not assigned to this cluster"));
+    }
+    state.store.assign_device_gpu_cluster(device_id, None).await?;
+    Ok(Json(serde_json::json!({"message": "device removed from cluster"})))
+}
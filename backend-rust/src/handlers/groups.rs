@@ -1,14 +1,14 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
 use serde::Deserialize;
 use std::sync::Arc;
 
-use crate::models::{CreateGroupRequest, Group, GroupVariable, ResolvedVariablesResponse};
+use crate::models::{change_action, CreateGroupRequest, DeleteQuery, Group, GroupVariable, NewChangeLogEntry, ResolvedVariablesResponse, team_resource_type};
 use crate::AppState;
 
-use super::{ApiError, created};
+use super::{created, record_change, ApiError, ValidJson};
 
 // ========== Group CRUD ==========
 
@@ -33,7 +33,7 @@ pub async fn get_group(
 pub async fn create_group(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateGroupRequest>,
+    ValidJson(req): ValidJson<CreateGroupRequest>,
 ) -> Result<(axum::http::StatusCode, Json<Group>), ApiError> {
     // Validate parent wouldn't create a cycle (new group has no ID yet, so no cycle possible from self)
     // Just validate parent exists if provided
@@ -43,16 +43,19 @@ pub async fn create_group(
         }
     }
 
+    super::credentials::check_credential_binding(&state, req.credential_id).await?;
+
     let group = state.store.create_group(&req).await?;
     Ok(created(group))
 }
 
 pub async fn update_group(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateGroupRequest>,
+    ValidJson(req): ValidJson<CreateGroupRequest>,
 ) -> Result<Json<Group>, ApiError> {
+    super::teams::require_modify(&state, &auth, team_resource_type::GROUP, id).await?;
     // Protect "all" group invariants (id == 1)
     if id == 1 {
         if req.parent_id.is_some() {
@@ -70,15 +73,21 @@ pub async fn update_group(
         }
     }
 
+    super::credentials::check_credential_binding(&state, req.credential_id).await?;
+
     let group = state.store.update_group(id, &req).await?;
     Ok(Json(group))
 }
 
 pub async fn delete_group(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
+    Query(query): Query<DeleteQuery>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
+    super::teams::require_modify(&state, &auth, team_resource_type::GROUP, id).await?;
+    let refs = super::references::group_references(&state, id).await?;
+    super::references::ensure_deletable(&refs, &query)?;
     state.store.delete_group(id).await?;
     Ok(Json(serde_json::json!({"message": "group deleted"})))
 }
@@ -100,21 +109,25 @@ pub struct SetVariableRequest {
 }
 
 pub async fn set_group_variable(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path((id, key)): Path<(i64, String)>,
     Json(req): Json<SetVariableRequest>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     state.store.set_group_variable(id, &key, &req.value).await?;
+    let entry = NewChangeLogEntry::variable(change_action::UPDATE, &key, format!("set {}", key));
+    record_change(&state, &auth, NewChangeLogEntry { group_id: Some(id), ..entry }).await;
     Ok(Json(serde_json::json!({"message": "variable set"})))
 }
 
 pub async fn delete_group_variable(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path((id, key)): Path<(i64, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     state.store.delete_group_variable(id, &key).await?;
+    let entry = NewChangeLogEntry::variable(change_action::DELETE, &key, format!("deleted {}", key));
+    record_change(&state, &auth, NewChangeLogEntry { group_id: Some(id), ..entry }).await;
     Ok(Json(serde_json::json!({"message": "variable deleted"})))
 }
 
@@ -0,0 +1,108 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::ApiError;
+
+/// List a device's collected hardware inventory
+pub async fn list_device_hardware(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<DeviceHardware>>, ApiError> {
+    state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    Ok(Json(state.store.list_device_hardware(id).await?))
+}
+
+/// Queue hardware inventory jobs for the selected devices (default: all devices with a
+/// topology role). Backups also refresh the inventory of the backed-up device.
+pub async fn collect_hardware(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<HardwareCollectRequest>,
+) -> Result<(StatusCode, Json<Vec<Job>>), ApiError> {
+    let device_ids: Vec<i64> = if !req.device_ids.is_empty() {
+        req.device_ids.clone()
+    } else if let Some(group_id) = req.group_id {
+        let filter = DeviceFilter { group_id: Some(group_id), ..Default::default() };
+        state.store.list_devices_filtered(&filter).await?.iter().map(|d| d.id).collect()
+    } else {
+        state.store.list_devices().await?
+            .iter()
+            .filter(|d| d.topology_role.as_deref().is_some_and(|r| !r.is_empty()))
+            .map(|d| d.id)
+            .collect()
+    };
+
+    if device_ids.is_empty() {
+        return Err(ApiError::bad_request("no devices selected for hardware collection"));
+    }
+
+    for device_id in &device_ids {
+        if state.store.get_device(*device_id).await?.is_none() {
+            return Err(ApiError::not_found("Device"));
+        }
+    }
+    super::jobs::require_queue_capacity(&state, device_ids.len())?;
+
+    let mut jobs = Vec::with_capacity(device_ids.len());
+    for device_id in device_ids {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let job_req = CreateJobRequest {
+            device_id,
+            job_type: job_type::HARDWARE_COLLECT.to_string(),
+            command: String::new(),
+            credential_id: String::new(),
+            triggered_by: "manual".to_string(),
+            timeout_secs: None,
+        };
+        let job = state.store.create_job(&job_id, &job_req).await?;
+
+        if let Some(ref hub) = state.ws_hub {
+            hub.broadcast_job_update(crate::ws::EventType::JobQueued, &job).await;
+        }
+        if let Some(ref job_service) = state.job_service {
+            job_service.submit(job_id).await;
+        }
+        jobs.push(job);
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(jobs)))
+}
+
+/// List chassis serial mismatches, newest first
+pub async fn list_serial_mismatches(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SerialMismatchQuery>,
+) -> Result<Json<Vec<SerialMismatch>>, ApiError> {
+    Ok(Json(state.store.list_serial_mismatches(query.include_resolved).await?))
+}
+
+/// Accept the observed chassis serial as the device's serial number
+pub async fn accept_serial_mismatch(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<SerialMismatch>, ApiError> {
+    let mismatch = state
+        .store
+        .get_serial_mismatch(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("serial mismatch"))?;
+    if mismatch.resolved_at.is_some() {
+        return Err(ApiError::conflict("serial mismatch is already resolved"));
+    }
+    super::devices::check_serial_unused(&state, Some(&mismatch.observed_serial), Some(mismatch.device_id)).await?;
+    Ok(Json(state.store.accept_serial_mismatch(id, &auth.claims.username).await?))
+}
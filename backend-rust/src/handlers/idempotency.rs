@@ -0,0 +1,138 @@
+//! Idempotency-Key support for endpoints that start jobs. A client retrying a request with the
+//! same key gets the first response back instead of a second job. Keys are scoped to the user
+//! and remembered for a day; a request whose first attempt failed runs again on retry.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{FromRequestParts, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use std::sync::Arc;
+
+use crate::auth::AuthUser;
+use crate::models::IdempotencyReservation;
+use crate::AppState;
+
+use super::ApiError;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Set on responses replayed from an earlier request
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const MAX_KEY_LEN: usize = 255;
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+/// How long a key's response is replayed
+const REPLAY_WINDOW_HOURS: i64 = 24;
+/// A key whose request hasn't finished after this long is taken to be abandoned
+const ABANDONED_AFTER_MINUTES: i64 = 5;
+
+fn request_hash(method: &str, path: &str, body: &[u8]) -> String {
+    let mut ctx = ring::digest::Context::new(&ring::digest::SHA256);
+    ctx.update(method.as_bytes());
+    ctx.update(b"\n");
+    ctx.update(path.as_bytes());
+    ctx.update(b"\n");
+    ctx.update(body);
+    URL_SAFE_NO_PAD.encode(ctx.finish())
+}
+
+fn replay(status_code: u16, content_type: String, body: Vec<u8>) -> Response {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = StatusCode::from_u16(status_code).unwrap_or(StatusCode::OK);
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&content_type) {
+        headers.insert(header::CONTENT_TYPE, value);
+    }
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+/// Middleware for job-creating routes. Requests without an Idempotency-Key header, or without
+/// a valid login, pass straight through.
+pub async fn idempotency_guard(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+    let key = match key.to_str().map(str::trim) {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LEN => key.to_string(),
+        _ => {
+            return ApiError::bad_request(format!(
+                "Idempotency-Key must be 1-{} visible ASCII characters",
+                MAX_KEY_LEN
+            ))
+            .into_response()
+        }
+    };
+
+    let (mut parts, body) = request.into_parts();
+    let Ok(auth) = AuthUser::from_request_parts(&mut parts, &state).await else {
+        return next.run(Request::from_parts(parts, body)).await;
+    };
+    let username = auth.claims.username;
+    let body = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(_) => return ApiError::bad_request("request body too large").into_response(),
+    };
+    let hash = request_hash(parts.method.as_str(), parts.uri.path(), &body);
+
+    let now = chrono::Utc::now();
+    let reservation = state
+        .store
+        .reserve_idempotency_key(
+            &username,
+            &key,
+            &hash,
+            now - chrono::Duration::hours(REPLAY_WINDOW_HOURS),
+            now - chrono::Duration::minutes(ABANDONED_AFTER_MINUTES),
+        )
+        .await;
+    match reservation {
+        Ok(IdempotencyReservation::Reserved) => {}
+        Ok(IdempotencyReservation::InProgress) => {
+            return ApiError::conflict("a request with this Idempotency-Key is still in progress").into_response()
+        }
+        Ok(IdempotencyReservation::Mismatch) => {
+            return ApiError::conflict("Idempotency-Key was already used for a different request").into_response()
+        }
+        Ok(IdempotencyReservation::Completed { status_code, content_type, body }) => {
+            return replay(status_code, content_type, body)
+        }
+        Err(e) => return ApiError::internal(format!("failed to reserve Idempotency-Key: {}", e)).into_response(),
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if !response.status().is_success() {
+        if let Err(e) = state.store.release_idempotency_key(&username, &key).await {
+            tracing::warn!("Failed to release Idempotency-Key {}: {}", key, e);
+        }
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            if let Err(e) = state.store.release_idempotency_key(&username, &key).await {
+                tracing::warn!("Failed to release Idempotency-Key {}: {}", key, e);
+            }
+            return ApiError::internal(format!("failed to read response: {}", e)).into_response();
+        }
+    };
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if let Err(e) = state
+        .store
+        .complete_idempotency_key(&username, &key, parts.status.as_u16(), content_type, &body)
+        .await
+    {
+        tracing::warn!("Failed to store response for Idempotency-Key {}: {}", key, e);
+    }
+    Response::from_parts(parts, Body::from(body))
+}
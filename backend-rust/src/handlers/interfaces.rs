@@ -0,0 +1,163 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::interface_drift;
+use crate::AppState;
+
+use super::ApiError;
+
+/// List a device's collected interface inventory
+pub async fn list_device_interfaces(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<DeviceInterface>>, ApiError> {
+    state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    Ok(Json(state.store.list_device_interfaces(id).await?))
+}
+
+/// Queue interface collection jobs for the selected devices (default: all devices with a
+/// topology role). Schedule a job template with the interface_collect job type for periodic runs.
+pub async fn collect_interfaces(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<InterfaceCollectRequest>,
+) -> Result<(StatusCode, Json<Vec<Job>>), ApiError> {
+    let device_ids: Vec<i64> = if !req.device_ids.is_empty() {
+        req.device_ids.clone()
+    } else if let Some(group_id) = req.group_id {
+        let filter = DeviceFilter { group_id: Some(group_id), ..Default::default() };
+        state.store.list_devices_filtered(&filter).await?.iter().map(|d| d.id).collect()
+    } else {
+        state.store.list_devices().await?
+            .iter()
+            .filter(|d| d.topology_role.as_deref().is_some_and(|r| !r.is_empty()))
+            .map(|d| d.id)
+            .collect()
+    };
+
+    if device_ids.is_empty() {
+        return Err(ApiError::bad_request("no devices selected for interface collection"));
+    }
+
+    for device_id in &device_ids {
+        if state.store.get_device(*device_id).await?.is_none() {
+            return Err(ApiError::not_found("Device"));
+        }
+    }
+    super::jobs::require_queue_capacity(&state, device_ids.len())?;
+
+    let mut jobs = Vec::with_capacity(device_ids.len());
+    for device_id in device_ids {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let job_req = CreateJobRequest {
+            device_id,
+            job_type: job_type::INTERFACE_COLLECT.to_string(),
+            command: String::new(),
+            credential_id: String::new(),
+            triggered_by: "manual".to_string(),
+            timeout_secs: None,
+        };
+        let job = state.store.create_job(&job_id, &job_req).await?;
+
+        if let Some(ref hub) = state.ws_hub {
+            hub.broadcast_job_update(crate::ws::EventType::JobQueued, &job).await;
+        }
+        if let Some(ref job_service) = state.job_service {
+            job_service.submit(job_id).await;
+        }
+        jobs.push(job);
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(jobs)))
+}
+
+/// Check a device's port assignments against its collected interfaces
+pub async fn validate_port_assignments(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<PortAssignmentValidation>, ApiError> {
+    state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+
+    let interfaces = state.store.list_device_interfaces(id).await?;
+    let mut validation = PortAssignmentValidation {
+        device_id: id,
+        inventory_collected: !interfaces.is_empty(),
+        collected_at: interfaces.iter().map(|i| i.collected_at).max(),
+        checks: Vec::new(),
+        unknown_ports: Vec::new(),
+    };
+    if interfaces.is_empty() {
+        return Ok(Json(validation));
+    }
+
+    for assignment in state.store.list_port_assignments(id).await? {
+        let interface = interfaces.iter().find(|i| i.name.eq_ignore_ascii_case(&assignment.port_name));
+        if interface.is_none() {
+            validation.unknown_ports.push(assignment.port_name.clone());
+        }
+        validation.checks.push(PortAssignmentCheck {
+            port_name: assignment.port_name,
+            exists: interface.is_some(),
+            status: interface.map(|i| i.status.clone()),
+            speed_mbps: interface.and_then(|i| i.speed_mbps),
+        });
+    }
+    Ok(Json(validation))
+}
+
+/// Compare a device's port assignments (descriptions, VRFs, model port speeds) with its
+/// collected interface state
+pub async fn device_interface_drift(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<InterfaceIntentReport>, ApiError> {
+    let device = state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    let models = state.store.list_device_models().await?;
+    Ok(Json(interface_drift::device_report(&state.store, &device, &models).await?))
+}
+
+/// Interface intent vs. operational state across devices with a collected inventory,
+/// optionally limited to a group. Devices without mismatches are omitted unless include_clean is set.
+pub async fn interface_drift_report(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<InterfaceDriftQuery>,
+) -> Result<Json<Vec<InterfaceIntentReport>>, ApiError> {
+    let devices = match query.group_id {
+        Some(group_id) => {
+            let filter = DeviceFilter { group_id: Some(group_id), ..Default::default() };
+            state.store.list_devices_filtered(&filter).await?
+        }
+        None => state.store.list_devices().await?,
+    };
+    let models = state.store.list_device_models().await?;
+
+    let mut reports = Vec::new();
+    for device in &devices {
+        let report = interface_drift::device_report(&state.store, device, &models).await?;
+        if report.inventory_collected && (query.include_clean || !report.mismatches.is_empty()) {
+            reports.push(report);
+        }
+    }
+    Ok(Json(reports))
+}
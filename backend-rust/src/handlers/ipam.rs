@@ -1,13 +1,14 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
+use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::models::*;
 use crate::AppState;
-use super::{created, ApiError};
+use super::{created, ApiError, ValidJson};
 
 // ========== Regions ==========
 
@@ -32,11 +33,8 @@ pub async fn get_region(
 pub async fn create_region(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateIpamRegionRequest>,
+    ValidJson(req): ValidJson<CreateIpamRegionRequest>,
 ) -> Result<(StatusCode, Json<IpamRegion>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
     let region = state.store.create_ipam_region(&req).await?;
     Ok(created(region))
 }
@@ -45,7 +43,7 @@ pub async fn update_region(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateIpamRegionRequest>,
+    ValidJson(req): ValidJson<CreateIpamRegionRequest>,
 ) -> Result<Json<IpamRegion>, ApiError> {
     let region = state.store.update_ipam_region(id, &req).await?;
     Ok(Json(region))
@@ -83,11 +81,8 @@ pub async fn get_campus(
 pub async fn create_campus(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateIpamCampusRequest>,
+    ValidJson(req): ValidJson<CreateIpamCampusRequest>,
 ) -> Result<(StatusCode, Json<IpamCampus>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
     if state.store.get_ipam_region(req.region_id).await?.is_none() {
         return Err(ApiError::bad_request("Region not found"));
     }
@@ -99,7 +94,7 @@ pub async fn update_campus(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateIpamCampusRequest>,
+    ValidJson(req): ValidJson<CreateIpamCampusRequest>,
 ) -> Result<Json<IpamCampus>, ApiError> {
     let campus = state.store.update_ipam_campus(id, &req).await?;
     Ok(Json(campus))
@@ -137,11 +132,8 @@ pub async fn get_datacenter(
 pub async fn create_datacenter(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateIpamDatacenterRequest>,
+    ValidJson(req): ValidJson<CreateIpamDatacenterRequest>,
 ) -> Result<(StatusCode, Json<IpamDatacenter>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
     if state.store.get_ipam_campus(req.campus_id).await?.is_none() {
         return Err(ApiError::bad_request("Campus not found"));
     }
@@ -153,7 +145,7 @@ pub async fn update_datacenter(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateIpamDatacenterRequest>,
+    ValidJson(req): ValidJson<CreateIpamDatacenterRequest>,
 ) -> Result<Json<IpamDatacenter>, ApiError> {
     let datacenter = state.store.update_ipam_datacenter(id, &req).await?;
     Ok(Json(datacenter))
@@ -191,11 +183,8 @@ pub async fn get_hall(
 pub async fn create_hall(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateIpamHallRequest>,
+    ValidJson(req): ValidJson<CreateIpamHallRequest>,
 ) -> Result<(StatusCode, Json<IpamHall>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
     if state.store.get_ipam_datacenter(req.datacenter_id).await?.is_none() {
         return Err(ApiError::bad_request("Datacenter not found"));
     }
@@ -207,7 +196,7 @@ pub async fn update_hall(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateIpamHallRequest>,
+    ValidJson(req): ValidJson<CreateIpamHallRequest>,
 ) -> Result<Json<IpamHall>, ApiError> {
     let hall = state.store.update_ipam_hall(id, &req).await?;
     Ok(Json(hall))
@@ -245,11 +234,8 @@ pub async fn get_row(
 pub async fn create_row(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateIpamRowRequest>,
+    ValidJson(req): ValidJson<CreateIpamRowRequest>,
 ) -> Result<(StatusCode, Json<IpamRow>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
     if state.store.get_ipam_hall(req.hall_id).await?.is_none() {
         return Err(ApiError::bad_request("Hall not found"));
     }
@@ -261,7 +247,7 @@ pub async fn update_row(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateIpamRowRequest>,
+    ValidJson(req): ValidJson<CreateIpamRowRequest>,
 ) -> Result<Json<IpamRow>, ApiError> {
     let row = state.store.update_ipam_row(id, &req).await?;
     Ok(Json(row))
@@ -299,11 +285,8 @@ pub async fn get_rack(
 pub async fn create_rack(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateIpamRackRequest>,
+    ValidJson(req): ValidJson<CreateIpamRackRequest>,
 ) -> Result<(StatusCode, Json<IpamRack>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
     if state.store.get_ipam_row(req.row_id).await?.is_none() {
         return Err(ApiError::bad_request("Row not found"));
     }
@@ -315,8 +298,18 @@ pub async fn update_rack(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateIpamRackRequest>,
+    ValidJson(req): ValidJson<CreateIpamRackRequest>,
 ) -> Result<Json<IpamRack>, ApiError> {
+    let current = state.store.get_ipam_rack(id).await?
+        .ok_or_else(|| ApiError::not_found("Rack"))?;
+    if req.height_ru < current.height_ru {
+        let elevation = rack_elevation(&state, current).await?;
+        if let Some(top) = elevation.devices.iter().map(|d| d.position.unwrap_or(0) + d.rack_units - 1).max() {
+            if req.height_ru < top {
+                return Err(ApiError::conflict(format!("rack units up to U{} are in use", top)));
+            }
+        }
+    }
     let rack = state.store.update_ipam_rack(id, &req).await?;
     Ok(Json(rack))
 }
@@ -330,6 +323,87 @@ pub async fn delete_rack(
     Ok(StatusCode::NO_CONTENT)
 }
 
+// ========== Rack Elevations ==========
+
+async fn rack_elevation(state: &AppState, rack: IpamRack) -> Result<RackElevation, ApiError> {
+    let devices = state.store.list_rack_devices(rack.id).await?;
+    let models = state.store.list_device_models().await?;
+    Ok(crate::utils::build_rack_elevation(rack, &devices, &models))
+}
+
+/// Check a device can sit at `rack_position` in its rack: the rack exists, the device fits below
+/// the top, and no other device occupies its units. A device without a position only needs the
+/// rack to exist.
+pub(super) async fn check_rack_placement(
+    state: &AppState,
+    device_id: Option<i64>,
+    rack_id: Option<i64>,
+    rack_position: Option<i32>,
+    vendor: Option<&str>,
+    model: Option<&str>,
+) -> Result<(), ApiError> {
+    let Some(rack_id) = rack_id else {
+        if rack_position.is_some_and(|p| p != 0) {
+            return Err(ApiError::bad_request("rack_position requires rack_id"));
+        }
+        return Ok(());
+    };
+    let rack = state.store.get_ipam_rack(rack_id).await?
+        .ok_or_else(|| ApiError::bad_request(format!("rack {} does not exist", rack_id)))?;
+    let position = match rack_position {
+        None | Some(0) => return Ok(()),
+        Some(p) if p < 0 => return Err(ApiError::bad_request("rack_position must be at least 1")),
+        Some(p) => p,
+    };
+
+    let models = state.store.list_device_models().await?;
+    let units = crate::utils::device_rack_units(vendor, model, &models);
+    let top = position + units - 1;
+    if top > rack.height_ru {
+        return Err(ApiError::bad_request(format!(
+            "a {}U device at U{} extends past the top of {} (U{})",
+            units, position, rack.name, rack.height_ru
+        )));
+    }
+    let devices = state.store.list_rack_devices(rack_id).await?;
+    let elevation = crate::utils::build_rack_elevation(rack, &devices, &models);
+    if let Some(other) = crate::utils::find_rack_collision(&elevation, device_id, position, units) {
+        return Err(ApiError::conflict(format!(
+            "U{}-U{} of {} overlap {} at U{}",
+            position, top, elevation.rack.name, other.hostname, other.position.unwrap_or(0)
+        )));
+    }
+    Ok(())
+}
+
+/// Get a rack's elevation: its devices laid out by rack unit
+pub async fn get_rack_elevation(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<RackElevation>, ApiError> {
+    let rack = state.store.get_ipam_rack(id).await?
+        .ok_or_else(|| ApiError::not_found("Rack"))?;
+    Ok(Json(rack_elevation(&state, rack).await?))
+}
+
+/// Get the elevations of every rack in a datacenter
+pub async fn get_datacenter_elevation(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<RackElevation>>, ApiError> {
+    state.store.get_ipam_datacenter(id).await?
+        .ok_or_else(|| ApiError::not_found("Datacenter"))?;
+    let models = state.store.list_device_models().await?;
+    let mut elevations = Vec::new();
+    for rack in state.store.list_ipam_racks_by_datacenter(id).await? {
+        let devices = state.store.list_rack_devices(rack.id).await?;
+        elevations.push(crate::utils::build_rack_elevation(rack, &devices, &models));
+    }
+    Ok(Json(elevations))
+}
+
 // ========== Roles ==========
 
 pub async fn list_roles(
@@ -343,11 +417,8 @@ pub async fn list_roles(
 pub async fn create_role(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateIpamRoleRequest>,
+    ValidJson(req): ValidJson<CreateIpamRoleRequest>,
 ) -> Result<(StatusCode, Json<IpamRole>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
     let role = state.store.create_ipam_role(&req).await?;
     Ok(created(role))
 }
@@ -363,11 +434,21 @@ pub async fn delete_role(
 
 // ========== Prefixes ==========
 
+#[derive(Debug, Deserialize)]
+pub struct VrfFilterQuery {
+    /// Only list entries in this VRF; 0 selects entries without a VRF
+    pub vrf_id: Option<i64>,
+}
+
 pub async fn list_prefixes(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
+    Query(query): Query<VrfFilterQuery>,
 ) -> Result<Json<Vec<IpamPrefix>>, ApiError> {
-    let prefixes = state.store.list_ipam_prefixes().await?;
+    let prefixes = match query.vrf_id {
+        Some(vrf_id) => state.store.list_ipam_prefixes_by_vrf(vrf_id).await?,
+        None => state.store.list_ipam_prefixes().await?,
+    };
     Ok(Json(prefixes))
 }
 
@@ -379,6 +460,33 @@ pub async fn list_supernets(
     Ok(Json(prefixes))
 }
 
+/// Default and maximum depth of the prefix tree
+const PREFIX_TREE_DEFAULT_DEPTH: i32 = 3;
+const PREFIX_TREE_MAX_DEPTH: i32 = 32;
+
+#[derive(Debug, Deserialize)]
+pub struct PrefixTreeQuery {
+    /// Prefix to start from; defaults to all top-level prefixes
+    pub root: Option<i64>,
+    /// Levels of children to load below the root
+    pub depth: Option<i32>,
+}
+
+/// Get the prefix hierarchy with counts and utilization, loading deeper levels on demand via `root`
+pub async fn get_prefix_tree(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PrefixTreeQuery>,
+) -> Result<Json<Vec<IpamPrefixTreeNode>>, ApiError> {
+    if let Some(root) = query.root {
+        state.store.get_ipam_prefix(root).await?
+            .ok_or_else(|| ApiError::not_found("Prefix"))?;
+    }
+    let depth = query.depth.unwrap_or(PREFIX_TREE_DEFAULT_DEPTH).clamp(0, PREFIX_TREE_MAX_DEPTH);
+    let tree = state.store.ipam_prefix_tree(query.root, depth).await?;
+    Ok(Json(tree))
+}
+
 pub async fn get_prefix(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
@@ -392,11 +500,8 @@ pub async fn get_prefix(
 pub async fn create_prefix(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateIpamPrefixRequest>,
+    ValidJson(req): ValidJson<CreateIpamPrefixRequest>,
 ) -> Result<(StatusCode, Json<IpamPrefix>), ApiError> {
-    if req.prefix.is_empty() {
-        return Err(ApiError::bad_request("prefix is required"));
-    }
     let prefix = state.store.create_ipam_prefix(&req).await?;
     Ok(created(prefix))
 }
@@ -405,7 +510,7 @@ pub async fn update_prefix(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateIpamPrefixRequest>,
+    ValidJson(req): ValidJson<CreateIpamPrefixRequest>,
 ) -> Result<Json<IpamPrefix>, ApiError> {
     let prefix = state.store.update_ipam_prefix(id, &req).await?;
     Ok(Json(prefix))
@@ -420,6 +525,16 @@ pub async fn delete_prefix(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// List the free CIDR blocks remaining inside a prefix
+pub async fn get_prefix_availability(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<IpamPrefixAvailability>, ApiError> {
+    let availability = state.store.ipam_prefix_availability(id).await?;
+    Ok(Json(availability))
+}
+
 pub async fn next_available_prefix(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
@@ -448,8 +563,12 @@ pub async fn next_available_ip(
 pub async fn list_ip_addresses(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
+    Query(query): Query<VrfFilterQuery>,
 ) -> Result<Json<Vec<IpamIpAddress>>, ApiError> {
-    let ips = state.store.list_ipam_ip_addresses().await?;
+    let ips = match query.vrf_id {
+        Some(vrf_id) => state.store.list_ipam_ip_addresses_by_vrf(vrf_id).await?,
+        None => state.store.list_ipam_ip_addresses().await?,
+    };
     Ok(Json(ips))
 }
 
@@ -466,11 +585,8 @@ pub async fn get_ip_address(
 pub async fn create_ip_address(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateIpamIpAddressRequest>,
+    ValidJson(req): ValidJson<CreateIpamIpAddressRequest>,
 ) -> Result<(StatusCode, Json<IpamIpAddress>), ApiError> {
-    if req.address.is_empty() || req.prefix_id == 0 {
-        return Err(ApiError::bad_request("address and prefix_id are required"));
-    }
     let ip = state.store.create_ipam_ip_address(&req).await?;
     Ok(created(ip))
 }
@@ -479,7 +595,7 @@ pub async fn update_ip_address(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateIpamIpAddressRequest>,
+    ValidJson(req): ValidJson<CreateIpamIpAddressRequest>,
 ) -> Result<Json<IpamIpAddress>, ApiError> {
     let ip = state.store.update_ipam_ip_address(id, &req).await?;
     Ok(Json(ip))
@@ -543,6 +659,134 @@ pub async fn list_tag_keys(
     Ok(Json(keys))
 }
 
+// ========== Reverse DNS ==========
+
+#[derive(Debug, Deserialize)]
+pub struct ReverseDnsQuery {
+    /// "zone" (default) or "dnsmasq"
+    pub format: Option<String>,
+    /// VRF to export; defaults to addresses without a VRF
+    pub vrf_id: Option<i64>,
+    /// Nameserver for the zone SOA/NS records
+    pub nameserver: Option<String>,
+}
+
+/// Generate PTR zone files or dnsmasq ptr-record lines from IP addresses with a dns_name
+pub async fn export_reverse_dns(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ReverseDnsQuery>,
+) -> Result<Json<ReverseDnsExport>, ApiError> {
+    let format = query.format.unwrap_or_else(|| reverse_dns_format::ZONE.to_string());
+    let vrf_id = query.vrf_id.unwrap_or(0);
+    let ips = state.store.list_ipam_ip_addresses_by_vrf(vrf_id).await?;
+    let records = crate::dhcp::reverse_dns::ptr_records(&ips);
+
+    let files = match format.as_str() {
+        reverse_dns_format::ZONE => {
+            let nameserver = query.nameserver.unwrap_or_else(|| "localhost".to_string());
+            // The serial moves only when the PTR set or nameserver changes, deletes included
+            let fingerprint = crate::dhcp::reverse_dns::zone_fingerprint(&records, &nameserver);
+            let serial = match state.store.get_ipam_reverse_dns_serial(vrf_id).await? {
+                Some((serial, previous)) if previous == fingerprint => serial,
+                previous => {
+                    let today = chrono::Utc::now().date_naive();
+                    let serial = crate::dhcp::reverse_dns::next_serial(previous.map(|(s, _)| s), today);
+                    state.store.save_ipam_reverse_dns_serial(vrf_id, serial, &fingerprint).await?;
+                    serial
+                }
+            };
+            crate::dhcp::reverse_dns::zone_files(&records, &nameserver, serial)
+        }
+        reverse_dns_format::DNSMASQ => vec![ReverseDnsFile {
+            name: "ipam-ptr.conf".to_string(),
+            record_count: records.len(),
+            content: crate::dhcp::reverse_dns::dnsmasq_ptr_lines(&records),
+        }],
+        other => return Err(ApiError::bad_request(format!("unsupported format '{}': expected zone or dnsmasq", other))),
+    };
+
+    Ok(Json(ReverseDnsExport { format, files }))
+}
+
+// ========== Neighbor Discovery ==========
+
+/// Topology roles that route, and so hold the ARP/ND tables worth collecting by default
+const NEIGHBOR_COLLECT_ROLES: &[&str] = &["super-spine", "spine", "leaf", "core", "distribution"];
+
+/// Queue ARP/ND collection jobs for the selected devices (default: all routed fabric devices)
+pub async fn collect_neighbors(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<NeighborCollectRequest>,
+) -> Result<(StatusCode, Json<Vec<Job>>), ApiError> {
+    let device_ids: Vec<i64> = if !req.device_ids.is_empty() {
+        req.device_ids.clone()
+    } else if let Some(group_id) = req.group_id {
+        let filter = DeviceFilter { group_id: Some(group_id), ..Default::default() };
+        state.store.list_devices_filtered(&filter).await?.iter().map(|d| d.id).collect()
+    } else {
+        state.store.list_devices().await?
+            .iter()
+            .filter(|d| d.topology_role.as_deref().is_some_and(|r| NEIGHBOR_COLLECT_ROLES.contains(&r)))
+            .map(|d| d.id)
+            .collect()
+    };
+
+    if device_ids.is_empty() {
+        return Err(ApiError::bad_request("no devices selected for neighbor collection"));
+    }
+
+    for device_id in &device_ids {
+        if state.store.get_device(*device_id).await?.is_none() {
+            return Err(ApiError::not_found("Device"));
+        }
+    }
+    super::jobs::require_queue_capacity(&state, device_ids.len())?;
+
+    let mut jobs = Vec::with_capacity(device_ids.len());
+    for device_id in device_ids {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let job_req = CreateJobRequest {
+            device_id,
+            job_type: job_type::NEIGHBOR_COLLECT.to_string(),
+            command: String::new(),
+            credential_id: String::new(),
+            triggered_by: "manual".to_string(),
+            timeout_secs: None,
+        };
+        let job = state.store.create_job(&job_id, &job_req).await?;
+
+        if let Some(ref hub) = state.ws_hub {
+            hub.broadcast_job_update(crate::ws::EventType::JobQueued, &job).await;
+        }
+        if let Some(ref job_service) = state.job_service {
+            job_service.submit(job_id).await;
+        }
+        jobs.push(job);
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(jobs)))
+}
+
+/// List addresses seen in device ARP/ND tables that aren't managed in IPAM
+pub async fn list_discovered_addresses(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<IpamDiscoveredAddress>>, ApiError> {
+    let addresses = state.store.list_ipam_discovered_addresses().await?;
+    Ok(Json(addresses))
+}
+
+pub async fn delete_discovered_address(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_ipam_discovered_address(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // ========== VRFs ==========
 
 pub async fn list_vrfs(
@@ -556,11 +800,8 @@ pub async fn list_vrfs(
 pub async fn create_vrf(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateIpamVrfRequest>,
+    ValidJson(req): ValidJson<CreateIpamVrfRequest>,
 ) -> Result<(StatusCode, Json<IpamVrf>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
     let vrf = state.store.create_ipam_vrf(&req).await?;
     Ok(created(vrf))
 }
@@ -8,7 +8,14 @@ use std::sync::Arc;
 use crate::models::*;
 use crate::AppState;
 
-use super::{created, ApiError};
+use super::{created, record_change, ApiError, ValidJson};
+
+fn validate_target_tag(req: &CreateJobTemplateRequest) -> Result<(), ApiError> {
+    if req.target_mode == "tag" {
+        super::tags::parse_tag_selector(&req.target_tag)?;
+    }
+    Ok(())
+}
 
 /// List all job templates
 pub async fn list_job_templates(
@@ -37,11 +44,9 @@ pub async fn get_job_template(
 pub async fn create_job_template(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateJobTemplateRequest>,
+    ValidJson(req): ValidJson<CreateJobTemplateRequest>,
 ) -> Result<(StatusCode, Json<JobTemplate>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
+    validate_target_tag(&req)?;
     let template = state.store.create_job_template(&req).await?;
     Ok(created(template))
 }
@@ -51,8 +56,9 @@ pub async fn update_job_template(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateJobTemplateRequest>,
+    ValidJson(req): ValidJson<CreateJobTemplateRequest>,
 ) -> Result<Json<JobTemplate>, ApiError> {
+    validate_target_tag(&req)?;
     let template = state.store.update_job_template(id, &req).await?;
     Ok(Json(template))
 }
@@ -67,9 +73,18 @@ pub async fn delete_job_template(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Team rule action a template's jobs need, and the command checked against the rules
+pub(super) fn template_team_action(template: &JobTemplate) -> (&'static str, Option<&str>) {
+    match template.job_type.as_str() {
+        job_type::DEPLOY | job_type::CONFIRMED_DEPLOY | job_type::DIFF | job_type::APPLY_TEMPLATE | job_type::PREFLIGHT => (team_action::DEPLOY, None),
+        job_type::COMMAND if template.action_id == 0 => (team_action::EXEC, Some(template.command.as_str())),
+        _ => (team_action::EXEC, None),
+    }
+}
+
 /// Run a job template immediately — creates jobs for each target device
 pub async fn run_job_template(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
 ) -> Result<Json<Vec<Job>>, ApiError> {
@@ -80,17 +95,17 @@ pub async fn run_job_template(
         .ok_or_else(|| ApiError::not_found("job template"))?;
 
     // Resolve target device IDs
-    let device_ids: Vec<i64> = if template.target_mode == "group" && template.target_group_id != 0 {
-        state.store.list_group_members(template.target_group_id).await
-            .map_err(|e| ApiError::internal(e.to_string()))?
-    } else {
-        template.target_device_ids.clone()
-    };
+    let device_ids: Vec<i64> = state.store.resolve_job_template_targets(&template).await?;
 
     // For webhook actions with no device targets (static webhooks), run once
     let is_webhook = template.job_type == job_type::WEBHOOK;
     let is_static_webhook = is_webhook && device_ids.is_empty();
 
+    // Refuse the whole run if any target is outside the user's team rules
+    let (action, checked_command) = template_team_action(&template);
+    super::teams::require_device_action(&state, &auth, &device_ids, action, checked_command).await?;
+    super::jobs::require_queue_capacity(&state, device_ids.len().max(1))?;
+
     let credential_id_str = template.credential_id.to_string();
     let mut jobs = Vec::new();
 
@@ -103,6 +118,7 @@ pub async fn run_job_template(
             command: template.action_id.to_string(),
             credential_id: credential_id_str.clone(),
             triggered_by: "manual".to_string(),
+            timeout_secs: None,
         };
         let job = state.store.create_job(&job_id, &req).await
             .map_err(|e| ApiError::internal(e.to_string()))?;
@@ -142,10 +158,14 @@ pub async fn run_job_template(
                 command,
                 credential_id: credential_id_str.clone(),
                 triggered_by: "manual".to_string(),
+                timeout_secs: None,
             };
 
             match state.store.create_job(&job_id, &req).await {
                 Ok(job) => {
+                    if job.job_type == job_type::DEPLOY || job.job_type == job_type::CONFIRMED_DEPLOY || job.job_type == job_type::APPLY_TEMPLATE {
+                        record_change(&state, &auth, NewChangeLogEntry::deploy(&job)).await;
+                    }
                     if let Some(ref hub) = state.ws_hub {
                         hub.broadcast_job_update(crate::ws::EventType::JobQueued, &job).await;
                     }
@@ -1,12 +1,15 @@
 use axum::{
     extract::{Path, Query, State},
+    http::StatusCode,
     Json,
 };
 use serde::Deserialize;
 use std::sync::Arc;
 
-use super::ApiError;
-use crate::models::Job;
+use super::{record_change, ApiError, ValidJson};
+use crate::models::{
+    job_type, tag_resource, team_action, BulkJobRequest, CreateJobRequest, Job, JobBatch, NewChangeLogEntry,
+};
 use crate::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -15,12 +18,35 @@ pub struct JobsQuery {
     pub device_id: Option<i64>,
     #[serde(default = "default_limit")]
     pub limit: i32,
+    /// `key` or `key=value`, applied to the most recent `limit` jobs
+    #[serde(default)]
+    pub tag: Option<String>,
 }
 
 fn default_limit() -> i32 {
     50
 }
 
+/// Refuse with 429 when `jobs` more wouldn't fit in the job queue, before any are created
+pub(super) fn require_queue_capacity(state: &AppState, jobs: usize) -> Result<(), ApiError> {
+    match state.job_service {
+        Some(ref job_service) => Ok(job_service.check_capacity(jobs)?),
+        None => Ok(()),
+    }
+}
+
+/// GET /api/jobs/queue — jobs waiting and running, the queue's capacity and refusals since startup
+pub async fn get_job_queue(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<crate::jobs::JobQueueStats>, ApiError> {
+    let job_service = state
+        .job_service
+        .as_ref()
+        .ok_or_else(|| ApiError::service_unavailable("job service not available"))?;
+    Ok(Json(job_service.queue_stats()))
+}
+
 /// GET /api/jobs/:id — get a single job
 pub async fn get_job(
     _auth: crate::auth::AuthUser,
@@ -35,7 +61,137 @@ pub async fn get_job(
     Ok(Json(job))
 }
 
-/// GET /api/jobs — list jobs, optionally filtered by device_id
+/// POST /api/jobs/:id/cancel — cancel a queued or running job
+pub async fn cancel_job(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Job>, ApiError> {
+    let job_service = state
+        .job_service
+        .as_ref()
+        .ok_or_else(|| ApiError::service_unavailable("job service not available"))?;
+    let cancelled = job_service.cancel(&id, &auth.claims.username).await?;
+    let job = state
+        .store
+        .get_job(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("job"))?;
+    if !cancelled {
+        return Err(ApiError::conflict(format!("job has already finished ({})", job.status)));
+    }
+    Ok(Json(job))
+}
+
+/// POST /api/jobs/bulk — queue the same job on every member of a group or on a list of devices,
+/// returning the batch that tracks them
+pub async fn create_bulk_job(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<BulkJobRequest>,
+) -> Result<(StatusCode, Json<JobBatch>), ApiError> {
+    let job_service = state
+        .job_service
+        .as_ref()
+        .ok_or_else(|| ApiError::service_unavailable("job service not available"))?;
+
+    let device_ids = if req.group_id != 0 {
+        state
+            .store
+            .get_group(req.group_id)
+            .await?
+            .ok_or_else(|| ApiError::not_found("group"))?;
+        state.store.list_group_members(req.group_id).await?
+    } else {
+        let mut ids = req.device_ids.clone();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    };
+    if device_ids.is_empty() {
+        return Err(ApiError::bad_request("group has no devices"));
+    }
+    let mut devices = Vec::with_capacity(device_ids.len());
+    for id in &device_ids {
+        let device = state
+            .store
+            .get_device(*id)
+            .await?
+            .ok_or_else(|| ApiError::not_found(&format!("device {}", id)))?;
+        devices.push(device);
+    }
+
+    // Refuse the whole batch if any device is outside the user's team rules
+    match req.job_type.as_str() {
+        job_type::COMMAND => {
+            super::teams::require_device_action(&state, &auth, &device_ids, team_action::EXEC, Some(&req.command)).await?
+        }
+        job_type::DEPLOY | job_type::DIFF => {
+            super::teams::require_device_action(&state, &auth, &device_ids, team_action::DEPLOY, None).await?
+        }
+        _ => {}
+    }
+
+    job_service.check_capacity(devices.len())?;
+
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    state.store.create_job_batch(&batch_id, &req, &auth.claims.username).await?;
+
+    let mut jobs = Vec::with_capacity(devices.len());
+    for device in &devices {
+        let command = match req.job_type.as_str() {
+            job_type::COMMAND => req.command.clone(),
+            job_type::DEPLOY | job_type::DIFF => super::devices::resolve_job_template_name(&state, device).await,
+            _ => String::new(),
+        };
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let job_req = CreateJobRequest {
+            device_id: device.id,
+            job_type: req.job_type.clone(),
+            command,
+            credential_id: String::new(),
+            triggered_by: "manual".to_string(),
+            timeout_secs: req.timeout_secs,
+        };
+        let job = state.store.create_batch_job(&job_id, &job_req, &batch_id).await?;
+        if job.job_type == job_type::DEPLOY {
+            record_change(&state, &auth, NewChangeLogEntry::deploy(&job)).await;
+        }
+        if let Some(ref hub) = state.ws_hub {
+            hub.broadcast_job_update(crate::ws::EventType::JobQueued, &job).await;
+        }
+        job_service.submit(job_id).await;
+        jobs.push(job);
+    }
+
+    let mut batch = state
+        .store
+        .get_job_batch(&batch_id)
+        .await?
+        .ok_or_else(|| ApiError::internal("job batch not found after creation"))?;
+    if let Some(ref hub) = state.ws_hub {
+        hub.broadcast_job_batch_progress(&batch).await;
+    }
+    batch.jobs = Some(jobs);
+    Ok((StatusCode::ACCEPTED, Json(batch)))
+}
+
+/// GET /api/jobs/batches/:id — get a bulk job batch's progress and its jobs
+pub async fn get_job_batch(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<JobBatch>, ApiError> {
+    let mut batch = state
+        .store
+        .get_job_batch(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("job batch"))?;
+    batch.jobs = Some(state.store.list_jobs_by_batch(&id).await?);
+    Ok(Json(batch))
+}
+
+/// GET /api/jobs — list jobs, optionally filtered by device_id or tag
 pub async fn list_jobs(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
@@ -47,5 +203,6 @@ pub async fn list_jobs(
     } else {
         state.store.list_jobs_recent(limit).await?
     };
+    let jobs = super::tags::retain_tagged(&state, tag_resource::JOB, query.tag.as_deref(), jobs, |j| j.id.clone()).await?;
     Ok(Json(jobs))
 }
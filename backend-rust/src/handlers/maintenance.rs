@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::{created, record_change, ApiError, ValidJson};
+
+async fn validate_window(state: &AppState, req: &CreateMaintenanceWindowRequest) -> Result<(), ApiError> {
+    if let Some(device_id) = req.device_id {
+        state.store.get_device(device_id).await?.ok_or_else(|| ApiError::bad_request("device not found"))?;
+    }
+    if let Some(group_id) = req.group_id {
+        state.store.get_group(group_id).await?.ok_or_else(|| ApiError::bad_request("group not found"))?;
+    }
+    Ok(())
+}
+
+/// List all maintenance windows, newest first
+pub async fn list_maintenance_windows(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<MaintenanceWindow>>, ApiError> {
+    Ok(Json(state.store.list_maintenance_windows().await?))
+}
+
+/// Get a single maintenance window by ID
+pub async fn get_maintenance_window(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<MaintenanceWindow>, ApiError> {
+    let window = state
+        .store
+        .get_maintenance_window(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("maintenance window"))?;
+    Ok(Json(window))
+}
+
+/// Schedule a maintenance window
+pub async fn create_maintenance_window(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateMaintenanceWindowRequest>,
+) -> Result<(StatusCode, Json<MaintenanceWindow>), ApiError> {
+    validate_window(&state, &req).await?;
+    let window = state.store.create_maintenance_window(&req).await?;
+    Ok(created(window))
+}
+
+/// Update a maintenance window
+pub async fn update_maintenance_window(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateMaintenanceWindowRequest>,
+) -> Result<Json<MaintenanceWindow>, ApiError> {
+    validate_window(&state, &req).await?;
+    let window = state.store.update_maintenance_window(id, &req).await?;
+    Ok(Json(window))
+}
+
+/// Delete a maintenance window
+pub async fn delete_maintenance_window(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_maintenance_window(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get the global maintenance mode switch
+pub async fn get_maintenance_mode(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Json<MaintenanceModeState> {
+    Json(state.maintenance_mode.state())
+}
+
+/// Turn maintenance mode on or off. While on, mutating API calls are rejected and the job
+/// scheduler, lease-triggered automation and status checks are paused, and telemetry streams
+/// are closed.
+pub async fn set_maintenance_mode(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SetMaintenanceModeRequest>,
+) -> Result<Json<MaintenanceModeState>, ApiError> {
+    let mode = MaintenanceModeState {
+        enabled: req.enabled,
+        reason: if req.enabled { req.reason.trim().to_string() } else { String::new() },
+        changed_by: auth.claims.username.clone(),
+        changed_at: Some(chrono::Utc::now()),
+    };
+    state.store.set_maintenance_mode(&mode).await?;
+    state.maintenance_mode.set(mode.clone());
+    state.gnmi.reload().await;
+
+    let summary = if mode.enabled {
+        if mode.reason.is_empty() {
+            "enabled maintenance mode".to_string()
+        } else {
+            format!("enabled maintenance mode: {}", mode.reason)
+        }
+    } else {
+        "disabled maintenance mode".to_string()
+    };
+    tracing::warn!("{} (by {})", summary, mode.changed_by);
+    let entry = NewChangeLogEntry {
+        category: change_category::SETTINGS,
+        action: change_action::UPDATE,
+        resource_type: "maintenance_mode",
+        summary,
+        ..Default::default()
+    };
+    record_change(&state, &auth, entry).await;
+    Ok(Json(mode))
+}
@@ -0,0 +1,26 @@
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::metrics_export;
+use crate::AppState;
+
+use super::ApiError;
+
+/// Device, interface and job metrics in InfluxDB line protocol, for pull-based collectors such
+/// as Telegraf. Job counts cover jobs finished in the last `since_secs`.
+pub async fn influx_metrics(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<MetricsExportQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let secs = query.since_secs.unwrap_or(state.config.metrics_export_interval_secs as i64).max(1);
+    let now = chrono::Utc::now();
+    let job_queue = state.job_service.as_ref().map(|s| s.queue_stats());
+    let body = metrics_export::collect(&state.store, job_queue, now - chrono::Duration::seconds(secs), now).await?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], body))
+}
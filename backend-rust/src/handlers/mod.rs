@@ -1,25 +1,60 @@
+pub mod alerts;
+pub mod api_usage;
+pub mod apply;
 pub mod auth;
+pub mod automation;
 pub mod benchmarks;
+pub mod changelog;
+pub mod config_outline;
 pub mod credentials;
+pub mod custom_fields;
+pub mod device_csv;
 pub mod device_models;
 pub mod device_roles;
 pub mod devices;
 pub mod device_variables;
+pub mod external_ids;
 pub mod groups;
+pub mod hardware;
+pub mod idempotency;
+pub mod interfaces;
 pub mod ipam;
 pub mod job_templates;
 pub mod jobs;
+pub mod maintenance;
+pub mod permissions;
 pub mod settings;
+pub mod system;
+pub mod tags;
+pub mod teams;
+pub mod tls;
 pub mod vendors;
 pub mod templates;
+pub mod template_catalog;
 pub mod dhcp_options;
 pub mod backups;
+pub mod bgp;
+pub mod capacity;
 pub mod discovery;
+pub mod documentation;
 pub mod configs;
 pub mod docker;
+pub mod metrics;
 pub mod netbox;
+pub mod notifications;
 pub mod port_assignments;
+pub mod references;
+pub mod render;
+pub mod reports;
+pub mod runbooks;
 pub mod output_parsers;
+pub mod saved_searches;
+pub mod search;
+pub mod seeds;
+pub mod staged_configs;
+pub mod support_contracts;
+pub mod syslog;
+pub mod telemetry;
 pub mod gpu_clusters;
 pub mod tenants;
 pub mod topologies;
@@ -27,11 +62,14 @@ pub mod users;
 pub mod ws_broadcast;
 
 use axum::{
+    extract::{FromRequest, Request},
     http::StatusCode,
     response::{IntoResponse, Response},
     Json,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::models::{FieldError, Validate, Validator};
 
 /// Shared pagination query parameters for list endpoints.
 /// Defaults: limit=100, offset=0. Max limit=1000.
@@ -56,16 +94,20 @@ fn default_page_limit() -> i32 {
     100
 }
 
-/// Error response - matches Go's {"error": "message"} format
+/// Error response - matches Go's {"error": "message"} format, plus the invalid fields of a
+/// rejected request body
 #[derive(Serialize)]
 pub struct ErrorResponse {
     pub error: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
 }
 
 impl ErrorResponse {
     pub fn new(error: impl Into<String>) -> Self {
         Self {
             error: error.into(),
+            fields: Vec::new(),
         }
     }
 }
@@ -74,42 +116,58 @@ impl ErrorResponse {
 pub struct ApiError {
     status: StatusCode,
     message: String,
+    fields: Vec<FieldError>,
 }
 
 impl ApiError {
-    pub fn bad_request(msg: impl Into<String>) -> Self {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
         Self {
-            status: StatusCode::BAD_REQUEST,
-            message: msg.into(),
+            status,
+            message: message.into(),
+            fields: Vec::new(),
         }
     }
 
-    pub fn not_found(resource: &str) -> Self {
+    pub fn bad_request(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, msg)
+    }
+
+    /// 422 for a request body that failed validation
+    pub fn unprocessable(fields: Vec<FieldError>) -> Self {
+        let summary: Vec<String> = fields.iter().map(|f| format!("{} {}", f.field, f.message)).collect();
         Self {
-            status: StatusCode::NOT_FOUND,
-            message: format!("{} not found", resource),
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            message: format!("invalid request: {}", summary.join("; ")),
+            fields,
         }
     }
 
+    pub fn not_found(resource: &str) -> Self {
+        Self::new(StatusCode::NOT_FOUND, format!("{} not found", resource))
+    }
+
     pub fn conflict(msg: impl Into<String>) -> Self {
-        Self {
-            status: StatusCode::CONFLICT,
-            message: msg.into(),
-        }
+        Self::new(StatusCode::CONFLICT, msg)
     }
 
     pub fn unauthorized(msg: impl Into<String>) -> Self {
-        Self {
-            status: StatusCode::UNAUTHORIZED,
-            message: msg.into(),
-        }
+        Self::new(StatusCode::UNAUTHORIZED, msg)
+    }
+
+    pub fn forbidden(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, msg)
+    }
+
+    pub fn too_many_requests(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::TOO_MANY_REQUESTS, msg)
+    }
+
+    pub fn service_unavailable(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::SERVICE_UNAVAILABLE, msg)
     }
 
     pub fn internal(msg: impl Into<String>) -> Self {
-        Self {
-            status: StatusCode::INTERNAL_SERVER_ERROR,
-            message: msg.into(),
-        }
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, msg)
     }
 }
 
@@ -117,22 +175,70 @@ impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         (
             self.status,
-            Json(ErrorResponse::new(self.message)),
+            Json(ErrorResponse {
+                error: self.message,
+                fields: self.fields,
+            }),
         )
             .into_response()
     }
 }
 
+impl From<crate::jobs::QueueFull> for ApiError {
+    fn from(err: crate::jobs::QueueFull) -> Self {
+        Self::too_many_requests(err.to_string())
+    }
+}
+
 impl From<anyhow::Error> for ApiError {
     fn from(err: anyhow::Error) -> Self {
         // Check for typed NotFoundError first (no fragile string matching)
         if let Some(nf) = err.downcast_ref::<crate::db::NotFoundError>() {
             return Self::not_found(&nf.to_string());
         }
+        if let Some(conflict) = err.downcast_ref::<crate::db::ConflictError>() {
+            return Self::conflict(conflict.to_string());
+        }
+        // Unique constraints are the last line of defence against duplicates
+        if let Some(sqlx::Error::Database(db)) = err.downcast_ref::<sqlx::Error>() {
+            if db.is_unique_violation() {
+                return Self::conflict(format!("already exists: {}", db.message()));
+            }
+        }
         Self::internal(err.to_string())
     }
 }
 
+/// JSON body extractor that runs the body's `Validate` checks, rejecting it with a 422 listing
+/// the invalid fields. Bodies that don't parse get the same JSON error shape as other failures.
+pub struct ValidJson<T>(pub T);
+
+impl<T: Validate> ValidJson<T> {
+    /// Validate a body built in-process, for handlers that call other handlers directly
+    pub fn validated(value: T) -> Result<Self, ApiError> {
+        let mut validator = Validator::new();
+        value.validate(&mut validator);
+        validator.finish().map_err(ApiError::unprocessable)?;
+        Ok(Self(value))
+    }
+}
+
+#[axum::async_trait]
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(|e| ApiError::new(e.status(), e.body_text()))?;
+        Self::validated(value)
+    }
+}
+
 /// Message response for simple status messages
 #[derive(Serialize)]
 pub struct MessageResponse {
@@ -165,3 +271,18 @@ pub async fn trigger_reload(state: &std::sync::Arc<crate::AppState>) {
         tracing::warn!("Failed to reload config: {}", e);
     }
 }
+
+/// Helper to record a change log entry for the requesting user with error logging
+pub async fn record_change(
+    state: &std::sync::Arc<crate::AppState>,
+    auth: &crate::auth::AuthUser,
+    entry: crate::models::NewChangeLogEntry,
+) {
+    let username = match &auth.claims.impersonation {
+        Some(imp) => format!("{} (impersonated by {})", auth.claims.username, imp.by),
+        None => auth.claims.username.clone(),
+    };
+    if let Err(e) = state.store.record_change(&entry, &username).await {
+        tracing::warn!("Failed to record {} change: {}", entry.category, e);
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     Json,
 };
 use std::sync::Arc;
@@ -61,6 +61,9 @@ pub async fn update_config(
     State(state): State<Arc<AppState>>,
     Json(config): Json<NetBoxConfig>,
 ) -> Result<Json<NetBoxConfig>, ApiError> {
+    if !netbox_conflict_policy::is_valid(&config.conflict_policy) {
+        return Err(ApiError::bad_request("conflict_policy must be one of: queue, prefer_local, prefer_remote"));
+    }
     state.store.save_netbox_config(&config).await?;
     Ok(Json(config))
 }
@@ -87,10 +90,61 @@ pub async fn sync_pull(
     require_config(&config)?;
 
     let nb = make_client(&config)?;
-    let result = netbox::sync_pull(&state.store, &nb).await?;
+    let result = netbox::sync_pull(&state.store, &nb, &config).await?;
     Ok(Json(result))
 }
 
+/// List NetBox pull conflicts awaiting review (or resolved ones with ?status=resolved)
+pub async fn list_conflicts(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SyncConflictQuery>,
+) -> Result<Json<Vec<SyncConflict>>, ApiError> {
+    let status = query.status.unwrap_or_else(|| sync_conflict_status::PENDING.to_string());
+    let conflicts = state.store.list_sync_conflicts(&status).await?;
+    Ok(Json(conflicts))
+}
+
+pub async fn get_conflict(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<SyncConflict>, ApiError> {
+    let conflict = state.store.get_sync_conflict(id).await?
+        .ok_or_else(|| ApiError::not_found("Sync conflict"))?;
+    Ok(Json(conflict))
+}
+
+/// Resolve a conflict by keeping local values, taking NetBox's, or choosing per field
+pub async fn resolve_conflict(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<ResolveSyncConflictRequest>,
+) -> Result<Json<SyncConflict>, ApiError> {
+    let conflict = state.store.get_sync_conflict(id).await?
+        .ok_or_else(|| ApiError::not_found("Sync conflict"))?;
+    if conflict.status != sync_conflict_status::PENDING {
+        return Err(ApiError::conflict("sync conflict is already resolved"));
+    }
+
+    match req.resolution.as_str() {
+        sync_resolution::LOCAL | sync_resolution::REMOTE => {}
+        sync_resolution::MERGE => {
+            for f in &conflict.fields {
+                match req.fields.get(&f.field).map(String::as_str) {
+                    Some(sync_resolution::LOCAL) | Some(sync_resolution::REMOTE) => {}
+                    _ => return Err(ApiError::bad_request(format!("merge needs \"local\" or \"remote\" for field '{}'", f.field))),
+                }
+            }
+        }
+        _ => return Err(ApiError::bad_request("resolution must be one of: local, remote, merge")),
+    }
+
+    let resolved = netbox::resolve_conflict(&state.store, &conflict, &req).await?;
+    Ok(Json(resolved))
+}
+
 /// Push vendors to NetBox as manufacturers
 pub async fn sync_vendors_push(
     _auth: crate::auth::AuthUser,
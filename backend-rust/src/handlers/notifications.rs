@@ -0,0 +1,97 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::notifications;
+use crate::AppState;
+
+use super::{created, ApiError, MessageResponse, ValidJson};
+
+async fn validate_channel(state: &AppState, id: Option<i64>, req: &CreateNotificationChannelRequest) -> Result<(), ApiError> {
+    notifications::validate_channel(&req.channel_type, &req.config)
+        .map_err(|e| ApiError::bad_request(format!("invalid {} channel: {:#}", req.channel_type, e)))?;
+    let channels = state.store.list_notification_channels().await?;
+    if channels.iter().any(|c| c.name == req.name && Some(c.id) != id) {
+        return Err(ApiError::conflict(format!("notification channel '{}' already exists", req.name)));
+    }
+    Ok(())
+}
+
+/// List notification channels
+pub async fn list_notification_channels(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<NotificationChannel>>, ApiError> {
+    Ok(Json(state.store.list_notification_channels().await?))
+}
+
+/// Get a single notification channel by ID
+pub async fn get_notification_channel(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<NotificationChannel>, ApiError> {
+    let channel = state
+        .store
+        .get_notification_channel(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("notification channel"))?;
+    Ok(Json(channel))
+}
+
+/// Create a notification channel
+pub async fn create_notification_channel(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateNotificationChannelRequest>,
+) -> Result<(StatusCode, Json<NotificationChannel>), ApiError> {
+    validate_channel(&state, None, &req).await?;
+    Ok(created(state.store.create_notification_channel(&req).await?))
+}
+
+/// Update a notification channel
+pub async fn update_notification_channel(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateNotificationChannelRequest>,
+) -> Result<Json<NotificationChannel>, ApiError> {
+    validate_channel(&state, Some(id), &req).await?;
+    Ok(Json(state.store.update_notification_channel(id, &req).await?))
+}
+
+/// Delete a notification channel; report schedules that use it skip it from then on
+pub async fn delete_notification_channel(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_notification_channel(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Send a test message to a channel
+pub async fn test_notification_channel(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    let channel = state
+        .store
+        .get_notification_channel(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("notification channel"))?;
+    let notification = Notification {
+        subject: "[ForgeConfig] Test notification".to_string(),
+        text: format!("Test message for notification channel '{}'.\n", channel.name),
+        payload: serde_json::json!({ "test": true, "channel": channel.name }),
+    };
+    notifications::send(&state.config, &channel, &notification)
+        .await
+        .map_err(|e| ApiError::bad_request(format!("delivery failed: {:#}", e)))?;
+    Ok(MessageResponse::new(format!("test message sent to '{}'", channel.name)))
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 use axum::{extract::{Path, State}, http::StatusCode, Json};
 use crate::{models::*, handlers::ApiError, AppState};
 
-use super::created;
+use super::{created, ValidJson};
 
 pub async fn list_output_parsers(
     State(state): State<Arc<AppState>>,
@@ -23,11 +23,8 @@ pub async fn get_output_parser(
 pub async fn create_output_parser(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateOutputParserRequest>,
+    ValidJson(req): ValidJson<CreateOutputParserRequest>,
 ) -> Result<(StatusCode, Json<OutputParser>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
     let parser = state.store.create_output_parser(&req).await?;
     Ok(created(parser))
 }
@@ -36,7 +33,7 @@ pub async fn update_output_parser(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateOutputParserRequest>,
+    ValidJson(req): ValidJson<CreateOutputParserRequest>,
 ) -> Result<Json<OutputParser>, ApiError> {
     let parser = state.store.update_output_parser(id, &req).await?;
     Ok(Json(parser))
@@ -0,0 +1,209 @@
+use axum::{
+    extract::{FromRequestParts, Path, State},
+    http::{Method, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::auth::AuthUser;
+use crate::models::*;
+use crate::services::permissions::UserPermissions;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+/// Paths every signed-in user can reach regardless of the matrix
+const EXEMPT_RESOURCES: &[&str] = &["auth", "health"];
+const EXEMPT_PATHS: &[&str] = &["/api/permissions/me"];
+
+async fn load_permissions(state: &AppState, auth: &AuthUser) -> anyhow::Result<UserPermissions> {
+    let user_id = auth.claims.sub.parse().unwrap_or_default();
+    UserPermissions::load(&state.store, user_id, &auth.claims.username).await
+}
+
+/// Middleware that enforces the permissions matrix for every authenticated API request.
+/// Unauthenticated requests pass through so the handler's own auth check answers them.
+pub async fn permission_guard(
+    State(state): State<Arc<AppState>>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let path = request.uri().path();
+    let resource = match crate::utils::api_resource(path) {
+        Some(resource) if !EXEMPT_RESOURCES.contains(&resource) && !EXEMPT_PATHS.contains(&path) => {
+            resource.to_string()
+        }
+        _ => return next.run(request).await,
+    };
+    let action = if matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        permission_action::READ
+    } else {
+        permission_action::WRITE
+    };
+
+    let (mut parts, body) = request.into_parts();
+    let auth = AuthUser::from_request_parts(&mut parts, &state).await;
+    let request = axum::extract::Request::from_parts(parts, body);
+    let Ok(auth) = auth else {
+        return next.run(request).await;
+    };
+    if action == permission_action::WRITE && auth.claims.impersonation.as_ref().is_some_and(|imp| imp.read_only) {
+        return ApiError::forbidden("read-only impersonation token").into_response();
+    }
+
+    match load_permissions(&state, &auth).await {
+        Ok(permissions) if permissions.allows(&resource, action) => next.run(request).await,
+        Ok(_) => ApiError::forbidden(format!("permission denied: {} on {}", action, resource)).into_response(),
+        Err(e) => ApiError::internal(format!("failed to load permissions: {}", e)).into_response(),
+    }
+}
+
+fn validate_permissions(permissions: &[Permission]) -> Result<(), ApiError> {
+    let actions = [permission_action::READ, permission_action::WRITE, permission_action::ANY];
+    for p in permissions {
+        let valid_resource = p.resource == permission_resource::ANY
+            || (!p.resource.is_empty() && p.resource.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'));
+        if !valid_resource {
+            return Err(ApiError::bad_request(format!("invalid resource '{}'", p.resource)));
+        }
+        if !actions.contains(&p.action.as_str()) {
+            return Err(ApiError::bad_request(format!(
+                "invalid action '{}': expected {}",
+                p.action,
+                actions.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+// ========== Matrix ==========
+
+/// The full permissions matrix across roles and teams
+pub async fn get_permission_matrix(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<PermissionMatrix>, ApiError> {
+    Ok(Json(PermissionMatrix {
+        resources: permission_resource::KNOWN.iter().map(|r| r.to_string()).collect(),
+        actions: [permission_action::READ, permission_action::WRITE, permission_action::ANY]
+            .iter()
+            .map(|a| a.to_string())
+            .collect(),
+        subjects: state.store.permission_subjects().await?,
+    }))
+}
+
+/// The signed-in user's effective permissions
+pub async fn get_my_permissions(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<EffectivePermissions>, ApiError> {
+    Ok(Json(load_permissions(&state, &auth).await?.effective()))
+}
+
+pub async fn get_team_permissions(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Permission>>, ApiError> {
+    state.store.get_team(id).await?.ok_or_else(|| ApiError::not_found("team"))?;
+    Ok(Json(state.store.list_permissions(permission_subject::TEAM, id).await?))
+}
+
+/// Replace a team's grants
+pub async fn set_team_permissions(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<SetPermissionsRequest>,
+) -> Result<Json<Vec<Permission>>, ApiError> {
+    state.store.get_team(id).await?.ok_or_else(|| ApiError::not_found("team"))?;
+    validate_permissions(&req.permissions)?;
+    state.store.set_permissions(permission_subject::TEAM, id, &req.permissions).await?;
+    Ok(Json(state.store.list_permissions(permission_subject::TEAM, id).await?))
+}
+
+// ========== Roles ==========
+
+async fn get_role_or_404(state: &AppState, id: i64) -> Result<Role, ApiError> {
+    state.store.get_role(id).await?.ok_or_else(|| ApiError::not_found("role"))
+}
+
+pub async fn list_roles(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Role>>, ApiError> {
+    Ok(Json(state.store.list_roles().await?))
+}
+
+pub async fn get_role(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Role>, ApiError> {
+    Ok(Json(get_role_or_404(&state, id).await?))
+}
+
+pub async fn create_role(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateRoleRequest>,
+) -> Result<(StatusCode, Json<Role>), ApiError> {
+    if state.store.list_roles().await?.iter().any(|r| r.name == req.name) {
+        return Err(ApiError::conflict("a role with this name already exists"));
+    }
+    Ok(created(state.store.create_role(&req).await?))
+}
+
+pub async fn update_role(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateRoleRequest>,
+) -> Result<Json<Role>, ApiError> {
+    if state.store.list_roles().await?.iter().any(|r| r.name == req.name && r.id != id) {
+        return Err(ApiError::conflict("a role with this name already exists"));
+    }
+    Ok(Json(state.store.update_role(id, &req).await?))
+}
+
+pub async fn delete_role(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_role(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Replace the role's members
+pub async fn set_role_members(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<SetRoleMembersRequest>,
+) -> Result<Json<Role>, ApiError> {
+    get_role_or_404(&state, id).await?;
+    for &user_id in &req.user_ids {
+        if state.store.get_user(user_id).await?.is_none() {
+            return Err(ApiError::bad_request(format!("user {} not found", user_id)));
+        }
+    }
+    state.store.set_role_members(id, &req.user_ids).await?;
+    Ok(Json(get_role_or_404(&state, id).await?))
+}
+
+/// Replace the role's grants
+pub async fn set_role_permissions(
+    _auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<SetPermissionsRequest>,
+) -> Result<Json<Role>, ApiError> {
+    get_role_or_404(&state, id).await?;
+    validate_permissions(&req.permissions)?;
+    state.store.set_permissions(permission_subject::ROLE, id, &req.permissions).await?;
+    Ok(Json(get_role_or_404(&state, id).await?))
+}
@@ -64,3 +64,153 @@ pub async fn delete_port_assignment(
     state.store.delete_port_assignment(id, &port_name).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+// ========== Power & Console Connections ==========
+
+async fn require_device(state: &AppState, id: i64) -> Result<Device, ApiError> {
+    state.store.get_device(id).await?.ok_or_else(|| ApiError::not_found("device"))
+}
+
+/// Power or console ports in the layout of the device's model
+fn model_aux_ports(models: &[DeviceModel], device: &Device, kind: &str) -> Vec<String> {
+    crate::utils::find_device_model(device.vendor_id.as_deref(), device.model.as_deref(), models)
+        .map(|m| crate::utils::layout_ports_with_role(&m.layout, kind))
+        .unwrap_or_default()
+}
+
+async fn list_aux_connections(state: &AppState, id: i64, kind: &str) -> Result<Json<Vec<AuxConnection>>, ApiError> {
+    require_device(state, id).await?;
+    Ok(Json(state.store.list_aux_connections(id, kind).await?))
+}
+
+/// Set a power or console connection. The port must be one of the model's ports of that kind
+/// when its layout lists any, and the PDU outlet or console server port must be free.
+async fn set_aux_connection(
+    state: &AppState,
+    id: i64,
+    kind: &str,
+    port_name: &str,
+    req: &SetAuxConnectionRequest,
+) -> Result<Json<AuxConnection>, ApiError> {
+    let device = require_device(state, id).await?;
+    let models = state.store.list_device_models().await?;
+    let ports = model_aux_ports(&models, &device, kind);
+    if !ports.is_empty() && !ports.iter().any(|p| p.eq_ignore_ascii_case(port_name)) {
+        return Err(ApiError::bad_request(format!(
+            "{} is not a {} port of {} (expected one of {})",
+            port_name, kind, device.model.as_deref().unwrap_or_default(), ports.join(", ")
+        )));
+    }
+
+    if let Some(remote_id) = req.remote_device_id {
+        if remote_id == id {
+            return Err(ApiError::bad_request("a device can't feed itself"));
+        }
+        let remote = state
+            .store
+            .get_device(remote_id)
+            .await?
+            .ok_or_else(|| ApiError::bad_request(format!("remote device {} does not exist", remote_id)))?;
+        if req.remote_port_name.is_empty() {
+            return Err(ApiError::bad_request("remote_port_name is required with remote_device_id"));
+        }
+        if let Some(existing) = state.store.find_aux_connection_by_remote(kind, remote_id, &req.remote_port_name).await? {
+            if existing.device_id != id || !existing.port_name.eq_ignore_ascii_case(port_name) {
+                return Err(ApiError::conflict(format!(
+                    "{} {} already connects to {} {}",
+                    remote.hostname, req.remote_port_name, existing.device_hostname, existing.port_name
+                )));
+            }
+        }
+    }
+
+    Ok(Json(state.store.set_aux_connection(id, kind, port_name, req).await?))
+}
+
+/// List a device's power feeds, and those it supplies as a PDU
+pub async fn list_power_connections(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<AuxConnection>>, ApiError> {
+    list_aux_connections(&state, id, aux_connection_kind::POWER).await
+}
+
+/// Connect one of a device's power supplies to a PDU outlet
+pub async fn set_power_connection(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, port_name)): Path<(i64, String)>,
+    Json(req): Json<SetAuxConnectionRequest>,
+) -> Result<Json<AuxConnection>, ApiError> {
+    set_aux_connection(&state, id, aux_connection_kind::POWER, &port_name, &req).await
+}
+
+/// Delete a power feed
+pub async fn delete_power_connection(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, port_name)): Path<(i64, String)>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_aux_connection(id, aux_connection_kind::POWER, &port_name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List a device's console lines, and those it serves as a console server
+pub async fn list_console_connections(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<AuxConnection>>, ApiError> {
+    list_aux_connections(&state, id, aux_connection_kind::CONSOLE).await
+}
+
+/// Connect a device's console port to a console server port
+pub async fn set_console_connection(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, port_name)): Path<(i64, String)>,
+    Json(req): Json<SetAuxConnectionRequest>,
+) -> Result<Json<AuxConnection>, ApiError> {
+    set_aux_connection(&state, id, aux_connection_kind::CONSOLE, &port_name, &req).await
+}
+
+/// Delete a console line
+pub async fn delete_console_connection(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, port_name)): Path<(i64, String)>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_aux_connection(id, aux_connection_kind::CONSOLE, &port_name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get a device's cabling plan: its data, power and console connections in both directions,
+/// and the power and console ports still to be cabled
+pub async fn get_device_cabling(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<CablingPlan>, ApiError> {
+    let device = require_device(&state, id).await?;
+    let Json(data) = list_port_assignments(auth, State(state.clone()), Path(id)).await?;
+    let power = state.store.list_aux_connections(id, aux_connection_kind::POWER).await?;
+    let console = state.store.list_aux_connections(id, aux_connection_kind::CONSOLE).await?;
+
+    let models = state.store.list_device_models().await?;
+    let mut unconnected_ports = Vec::new();
+    for (kind, connections) in [(aux_connection_kind::POWER, &power), (aux_connection_kind::CONSOLE, &console)] {
+        unconnected_ports.extend(model_aux_ports(&models, &device, kind).into_iter().filter(|port| {
+            !connections.iter().any(|c| c.device_id == id && c.port_name.eq_ignore_ascii_case(port))
+        }));
+    }
+
+    Ok(Json(CablingPlan {
+        device_id: id,
+        hostname: device.hostname,
+        data,
+        power,
+        console,
+        unconnected_ports,
+    }))
+}
@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::ApiError;
+
+fn summarize(references: &[ReferenceCount]) -> String {
+    references
+        .iter()
+        .filter(|r| !r.deleted_with_entity)
+        .map(|r| format!("{} {}", r.count, r.kind.replace('_', " ")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn entity_references(
+    resource_type: &str,
+    id: i64,
+    name: &str,
+    on_delete: &str,
+    references: Vec<ReferenceCount>,
+) -> EntityReferences {
+    EntityReferences {
+        resource_type: resource_type.to_string(),
+        id,
+        name: name.to_string(),
+        on_delete: on_delete.to_string(),
+        in_use: references.iter().any(|r| !r.deleted_with_entity),
+        references,
+    }
+}
+
+/// Refuse a delete when the restrict policy applies and something still refers to the entity.
+/// Rows deleted along with the entity don't block it.
+pub(super) fn ensure_deletable(refs: &EntityReferences, query: &DeleteQuery) -> Result<(), ApiError> {
+    if query.force || refs.on_delete != delete_policy::RESTRICT || !refs.in_use {
+        return Ok(());
+    }
+    Err(ApiError::conflict(format!(
+        "{} '{}' is still referenced by {}; remove the references or delete with ?force=true",
+        refs.resource_type,
+        refs.name,
+        summarize(&refs.references)
+    )))
+}
+
+pub(super) async fn vendor_references(state: &AppState, id: i64) -> Result<EntityReferences, ApiError> {
+    let vendor = state.store.get_vendor(id).await?.ok_or_else(|| ApiError::not_found("vendor"))?;
+    let policy = state.store.get_settings().await?.delete_policy;
+    let references = state.store.vendor_references(id, &vendor.name).await?;
+    Ok(entity_references("vendor", id, &vendor.name, &policy.vendors, references))
+}
+
+pub(super) async fn template_references(state: &AppState, id: i64) -> Result<EntityReferences, ApiError> {
+    let template = state.store.get_template(id).await?.ok_or_else(|| ApiError::not_found("template"))?;
+    let policy = state.store.get_settings().await?.delete_policy;
+    let references = state.store.template_references(id).await?;
+    Ok(entity_references("template", id, &template.name, &policy.templates, references))
+}
+
+pub(super) async fn group_references(state: &AppState, id: i64) -> Result<EntityReferences, ApiError> {
+    let group = state.store.get_group(id).await?.ok_or_else(|| ApiError::not_found("group"))?;
+    let policy = state.store.get_settings().await?.delete_policy;
+    let references = state.store.group_references(id).await?;
+    Ok(entity_references("group", id, &group.name, &policy.groups, references))
+}
+
+/// What refers to a vendor, checked before it is deleted
+pub async fn get_vendor_references(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<EntityReferences>, ApiError> {
+    Ok(Json(vendor_references(&state, id).await?))
+}
+
+/// What refers to a template, checked before it is deleted
+pub async fn get_template_references(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<EntityReferences>, ApiError> {
+    Ok(Json(template_references(&state, id).await?))
+}
+
+/// What refers to a group, checked before it is deleted
+pub async fn get_group_references(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<EntityReferences>, ApiError> {
+    Ok(Json(group_references(&state, id).await?))
+}
@@ -0,0 +1,184 @@
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::StreamExt;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::inventory_render;
+use crate::AppState;
+
+use super::ApiError;
+
+/// Get render cache statistics
+pub async fn get_render_cache_stats(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<RenderCacheStats>, ApiError> {
+    Ok(Json(state.render_cache.stats()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClearRenderCacheQuery {
+    /// Only drop this device's cached render
+    pub device_id: Option<i64>,
+}
+
+/// Invalidate cached renders, for one device or all of them
+pub async fn clear_render_cache(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ClearRenderCacheQuery>,
+) -> Result<axum::http::StatusCode, ApiError> {
+    match query.device_id {
+        Some(device_id) => state.render_cache.invalidate_device(device_id),
+        None => state.render_cache.clear(),
+    }
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+/// Maximum number of devices rendered at once by a batch render
+const BATCH_RENDER_CONCURRENCY: usize = 8;
+
+/// Whether a batch render's `format` asks for a zip archive
+fn wants_zip(format: Option<&str>) -> Result<bool, ApiError> {
+    match format.unwrap_or("json") {
+        "json" => Ok(false),
+        "zip" => Ok(true),
+        other => Err(ApiError::bad_request(format!("unsupported format '{}': expected json or zip", other))),
+    }
+}
+
+/// Render configs for every device in a group or topology, as a JSON map or a zip archive
+pub async fn batch_render(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BatchRenderRequest>,
+) -> Result<Response, ApiError> {
+    let filter = match (req.group_id, req.topology_id) {
+        (Some(group_id), None) => {
+            state.store.get_group(group_id).await?.ok_or_else(|| ApiError::not_found("group"))?;
+            DeviceFilter { group_id: Some(group_id), ..Default::default() }
+        }
+        (None, Some(topology_id)) => {
+            state.store.get_topology(topology_id).await?.ok_or_else(|| ApiError::not_found("topology"))?;
+            DeviceFilter { topology_id: Some(topology_id), ..Default::default() }
+        }
+        _ => return Err(ApiError::bad_request("exactly one of group_id or topology_id is required")),
+    };
+    let as_zip = wants_zip(req.format.as_deref())?;
+
+    let devices = state.store.list_devices_filtered(&filter).await?;
+    let results: Vec<(String, anyhow::Result<String>)> = futures::stream::iter(devices)
+        .map(|device| {
+            let state = &state;
+            async move {
+                let rendered = crate::jobs::render_device(&state.store, &state.render_cache, &device)
+                    .await
+                    .map(|(_, config)| config);
+                (device.hostname, rendered)
+            }
+        })
+        .buffer_unordered(BATCH_RENDER_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut response = BatchRenderResponse { configs: BTreeMap::new(), errors: BTreeMap::new() };
+    for (hostname, result) in results {
+        match result {
+            Ok(config) => { response.configs.insert(hostname, config); }
+            Err(e) => { response.errors.insert(hostname, e.to_string()); }
+        }
+    }
+
+    if !as_zip {
+        return Ok(Json(response).into_response());
+    }
+
+    render_archive_response(&response)
+}
+
+fn render_archive_response(batch: &BatchRenderResponse) -> Result<Response, ApiError> {
+    let archive = build_render_archive(batch)
+        .map_err(|e| ApiError::internal(format!("Failed to build zip archive: {}", e)))?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"configs.zip\""),
+        ],
+        archive,
+    )
+        .into_response())
+}
+
+/// Render an inventory document without touching stored devices, as a JSON map or a zip archive
+pub async fn render_inventory(
+    _auth: crate::auth::AuthUser,
+    Json(inventory): Json<RenderInventory>,
+) -> Result<Response, ApiError> {
+    let as_zip = wants_zip(inventory.format.as_deref())?;
+    // Template files are only read by the CLI; the server never opens paths from a request
+    if let Some(t) = inventory.templates.iter().find(|t| t.content.is_none()) {
+        return Err(ApiError::bad_request(format!("template '{}' needs inline content", t.name)));
+    }
+
+    let response = inventory_render::render(&inventory).map_err(|e| ApiError::bad_request(e.to_string()))?;
+    if !as_zip {
+        return Ok(Json(response).into_response());
+    }
+    render_archive_response(&response)
+}
+
+/// Zip rendered configs as `<hostname>.cfg`, with failures listed in `errors.txt`
+fn build_render_archive(batch: &BatchRenderResponse) -> anyhow::Result<Vec<u8>> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+
+    for (hostname, config) in &batch.configs {
+        zip.start_file(format!("{}.cfg", hostname.replace(['/', '\\'], "_")), options)?;
+        zip.write_all(config.as_bytes())?;
+    }
+    if !batch.errors.is_empty() {
+        zip.start_file("errors.txt", options)?;
+        for (hostname, error) in &batch.errors {
+            writeln!(zip, "{}: {}", hostname, error)?;
+        }
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Re-render and write TFTP config files for one device or a group, without a full config regeneration
+pub async fn export_tftp_configs(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TftpExportRequest>,
+) -> Result<Json<TftpExportResponse>, ApiError> {
+    let devices = match (req.device_id, req.group_id) {
+        (Some(device_id), None) => {
+            vec![state.store.get_device(device_id).await?.ok_or_else(|| ApiError::not_found("device"))?]
+        }
+        (None, Some(group_id)) => {
+            state.store.get_group(group_id).await?.ok_or_else(|| ApiError::not_found("group"))?;
+            state
+                .store
+                .list_devices_filtered(&DeviceFilter { group_id: Some(group_id), ..Default::default() })
+                .await?
+        }
+        _ => return Err(ApiError::bad_request("exactly one of device_id or group_id is required")),
+    };
+
+    let results = state.config_manager.export_device_configs(&devices).await?;
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    Ok(Json(TftpExportResponse {
+        written: results.len() - failed,
+        failed,
+        results,
+    }))
+}
@@ -0,0 +1,428 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::fleet_report;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+/// Quote a CSV field when it contains a delimiter, quote or newline
+pub(super) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn availability_csv(report: &AvailabilityReport) -> String {
+    let mut out = String::from("scope,id,name,devices,monitored_secs,downtime_secs,maintenance_secs,outages,availability_pct\n");
+    let pct = |p: Option<f64>| p.map(|p| format!("{:.3}", p)).unwrap_or_default();
+    for d in &report.devices {
+        out.push_str(&format!(
+            "device,{},{},1,{},{},{},{},{}\n",
+            d.device_id,
+            csv_field(&d.hostname),
+            d.span.monitored_secs,
+            d.span.downtime_secs,
+            d.span.maintenance_secs,
+            d.span.outages,
+            pct(d.availability_pct),
+        ));
+    }
+    for g in &report.groups {
+        out.push_str(&format!(
+            "group,{},{},{},{},{},{},{},{}\n",
+            g.group_id,
+            csv_field(&g.name),
+            g.devices,
+            g.span.monitored_secs,
+            g.span.downtime_secs,
+            g.span.maintenance_secs,
+            g.span.outages,
+            pct(g.availability_pct),
+        ));
+    }
+    out
+}
+
+/// Monthly uptime/SLA report per device and per group, built from the status history.
+/// Offline time inside a maintenance window is reported separately and not counted as downtime.
+/// Time before a device's first known status (or before it was created) is not monitored.
+pub async fn availability_report(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AvailabilityQuery>,
+) -> Result<Response, ApiError> {
+    let now = chrono::Utc::now();
+    let month = query.month.unwrap_or_else(|| now.format("%Y-%m").to_string());
+    let (period_start, month_end) = crate::utils::month_bounds(&month)
+        .ok_or_else(|| ApiError::bad_request("month must be formatted YYYY-MM"))?;
+    if period_start >= now {
+        return Err(ApiError::bad_request("month is in the future"));
+    }
+    let period_end = month_end.min(now);
+    let as_csv = match query.format.as_deref().unwrap_or("json") {
+        "json" => false,
+        "csv" => true,
+        other => return Err(ApiError::bad_request(format!("unsupported format '{}': expected json or csv", other))),
+    };
+
+    let devices = match query.group_id {
+        Some(group_id) => {
+            state.store.get_group(group_id).await?.ok_or_else(|| ApiError::not_found("group"))?;
+            let filter = DeviceFilter { group_id: Some(group_id), ..Default::default() };
+            state.store.list_devices_filtered(&filter).await?
+        }
+        None => state.store.list_devices().await?,
+    };
+
+    let statuses_at_start = state.store.device_statuses_at(period_start).await?;
+    let mut transitions: HashMap<i64, Vec<StatusTransition>> = HashMap::new();
+    for t in state.store.list_status_transitions_since(period_start).await? {
+        transitions.entry(t.device_id).or_default().push(t);
+    }
+    let windows = state.store.list_maintenance_windows_overlapping(period_start, period_end).await?;
+    let mut device_groups: HashMap<i64, Vec<i64>> = HashMap::new();
+    for (device_id, group_id) in state.store.list_group_memberships().await? {
+        device_groups.entry(device_id).or_default().push(group_id);
+    }
+    let no_groups = Vec::new();
+
+    let mut device_rows = Vec::with_capacity(devices.len());
+    for device in &devices {
+        let history = transitions.get(&device.id).map(Vec::as_slice).unwrap_or_default();
+        // Status at the start of the period: the last transition before it, else what the first
+        // later transition moved away from, else (no history at all) the current status
+        let initial = match statuses_at_start.get(&device.id) {
+            Some(status) => Some(status.as_str()),
+            None => match history.first() {
+                Some(first) if !first.previous_status.is_empty() => Some(first.previous_status.as_str()),
+                Some(_) => None,
+                None => Some(device.status.as_str()),
+            },
+        };
+        let in_period: Vec<(i64, &str)> = history
+            .iter()
+            .filter(|t| t.changed_at < period_end)
+            .map(|t| (t.changed_at.timestamp(), t.status.as_str()))
+            .collect();
+
+        let groups = device_groups.get(&device.id).unwrap_or(&no_groups);
+        let maintenance: Vec<(i64, i64)> = windows
+            .iter()
+            .filter(|w| w.applies_to(device.id, groups))
+            .map(|w| (w.starts_at.timestamp(), w.ends_at.timestamp()))
+            .collect();
+
+        let start = period_start.max(device.created_at).timestamp();
+        let span = crate::utils::availability_span(initial, &in_period, start, period_end.timestamp(), &maintenance);
+        device_rows.push(DeviceAvailability {
+            device_id: device.id,
+            hostname: device.hostname.clone(),
+            span,
+            availability_pct: span.availability_pct(),
+        });
+    }
+
+    let mut group_totals: BTreeMap<i64, (usize, AvailabilitySpan)> = BTreeMap::new();
+    for row in &device_rows {
+        for group_id in device_groups.get(&row.device_id).unwrap_or(&no_groups) {
+            if query.group_id.is_some_and(|g| g != *group_id) {
+                continue;
+            }
+            let entry = group_totals.entry(*group_id).or_default();
+            entry.0 += 1;
+            entry.1.add(&row.span);
+        }
+    }
+    let group_names: HashMap<i64, String> = state
+        .store
+        .list_groups()
+        .await?
+        .into_iter()
+        .map(|g| (g.id, g.name))
+        .collect();
+    let group_rows = group_totals
+        .into_iter()
+        .map(|(group_id, (count, span))| GroupAvailability {
+            group_id,
+            name: group_names.get(&group_id).cloned().unwrap_or_default(),
+            devices: count,
+            span,
+            availability_pct: span.availability_pct(),
+        })
+        .collect();
+
+    let report = AvailabilityReport {
+        month,
+        period_start,
+        period_end,
+        devices: device_rows,
+        groups: group_rows,
+    };
+
+    if as_csv {
+        let filename = format!("availability-{}.csv", report.month);
+        return Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+            ],
+            availability_csv(&report),
+        )
+            .into_response());
+    }
+    Ok(Json(report).into_response())
+}
+
+fn assets_csv(report: &AssetReport) -> String {
+    let mut out = String::from("device_id,hostname,vendor,model,component_type,name,part_number,serial_number,description,collected_at\n");
+    for row in &report.rows {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            row.device_id,
+            csv_field(&row.hostname),
+            csv_field(&row.vendor),
+            csv_field(&row.model),
+            row.component_type,
+            csv_field(&row.name),
+            csv_field(&row.part_number),
+            csv_field(&row.serial_number),
+            csv_field(&row.description),
+            row.collected_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+        ));
+    }
+    out
+}
+
+/// Hardware asset report: every collected component (chassis, modules, PSUs, fans,
+/// transceivers) per device, plus the unresolved chassis serial mismatches
+pub async fn asset_report(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AssetReportQuery>,
+) -> Result<Response, ApiError> {
+    let as_csv = match query.format.as_deref().unwrap_or("json") {
+        "json" => false,
+        "csv" => true,
+        other => return Err(ApiError::bad_request(format!("unsupported format '{}': expected json or csv", other))),
+    };
+    let known_types = [
+        hardware_component::CHASSIS,
+        hardware_component::MODULE,
+        hardware_component::POWER_SUPPLY,
+        hardware_component::FAN,
+        hardware_component::TRANSCEIVER,
+    ];
+    if let Some(component_type) = query.component_type.as_deref() {
+        if !known_types.contains(&component_type) {
+            return Err(ApiError::bad_request(format!(
+                "unknown component_type '{}': expected one of {}",
+                component_type,
+                known_types.join(", ")
+            )));
+        }
+    }
+
+    let devices = match query.group_id {
+        Some(group_id) => {
+            state.store.get_group(group_id).await?.ok_or_else(|| ApiError::not_found("group"))?;
+            let filter = DeviceFilter { group_id: Some(group_id), ..Default::default() };
+            state.store.list_devices_filtered(&filter).await?
+        }
+        None => state.store.list_devices().await?,
+    };
+
+    let mut hardware: HashMap<i64, Vec<DeviceHardware>> = HashMap::new();
+    for component in state.store.list_all_hardware().await? {
+        hardware.entry(component.device_id).or_default().push(component);
+    }
+
+    let mut rows = Vec::new();
+    let mut uncollected = 0;
+    for device in &devices {
+        let vendor = device.vendor.clone().unwrap_or_default();
+        let model = device.model.clone().unwrap_or_default();
+        let components = hardware.remove(&device.id).unwrap_or_default();
+        if components.is_empty() {
+            uncollected += 1;
+            rows.push(AssetReportRow {
+                device_id: device.id,
+                hostname: device.hostname.clone(),
+                vendor,
+                model: model.clone(),
+                component_type: hardware_component::CHASSIS.to_string(),
+                name: "Chassis".to_string(),
+                part_number: model,
+                serial_number: device.serial_number.clone().unwrap_or_default(),
+                description: String::new(),
+                collected_at: None,
+            });
+            continue;
+        }
+        rows.extend(components.into_iter().map(|c| AssetReportRow {
+            device_id: device.id,
+            hostname: device.hostname.clone(),
+            vendor: vendor.clone(),
+            model: model.clone(),
+            component_type: c.component_type,
+            name: c.name,
+            part_number: c.part_number,
+            serial_number: c.serial_number,
+            description: c.description,
+            collected_at: Some(c.collected_at),
+        }));
+    }
+    if let Some(component_type) = query.component_type.as_deref() {
+        rows.retain(|r| r.component_type == component_type);
+    }
+
+    let mismatches = state
+        .store
+        .list_serial_mismatches(false)
+        .await?
+        .into_iter()
+        .filter(|m| devices.iter().any(|d| d.id == m.device_id))
+        .collect();
+
+    let report = AssetReport {
+        generated_at: chrono::Utc::now(),
+        devices: devices.len(),
+        uncollected,
+        rows,
+        mismatches,
+    };
+
+    if as_csv {
+        let filename = format!("assets-{}.csv", report.generated_at.format("%Y%m%d"));
+        return Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+            ],
+            assets_csv(&report),
+        )
+            .into_response());
+    }
+    Ok(Json(report).into_response())
+}
+
+/// Fleet health summary: interface drift, syslog alert rules with unacknowledged alerts, jobs that
+/// failed in the last `since_hours`, stale backups and offline devices. `format=text` returns the
+/// body sent to email channels.
+pub async fn fleet_report(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<FleetReportQuery>,
+) -> Result<Response, ApiError> {
+    let as_text = match query.format.as_deref().unwrap_or("json") {
+        "json" => false,
+        "text" => true,
+        other => return Err(ApiError::bad_request(format!("unsupported format '{}': expected json or text", other))),
+    };
+    if query.stale_backup_days < 1 {
+        return Err(ApiError::bad_request("stale_backup_days must be at least 1"));
+    }
+    let hours = query.since_hours.unwrap_or(fleet_report::DEFAULT_REPORT_WINDOW_HOURS).max(1);
+    let since = chrono::Utc::now() - chrono::Duration::hours(hours);
+    let report = fleet_report::generate(&state.store, query.stale_backup_days, since, &[]).await?;
+    if as_text {
+        return Ok((
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            fleet_report::render_text(&report),
+        )
+            .into_response());
+    }
+    Ok(Json(report).into_response())
+}
+
+async fn validate_schedule(state: &AppState, id: Option<i64>, req: &CreateReportScheduleRequest) -> Result<(), ApiError> {
+    for channel_id in &req.channel_ids {
+        if state.store.get_notification_channel(*channel_id).await?.is_none() {
+            return Err(ApiError::bad_request(format!("notification channel {} not found", channel_id)));
+        }
+    }
+    let schedules = state.store.list_report_schedules().await?;
+    if schedules.iter().any(|s| s.name == req.name && Some(s.id) != id) {
+        return Err(ApiError::conflict(format!("report schedule '{}' already exists", req.name)));
+    }
+    Ok(())
+}
+
+/// List report schedules
+pub async fn list_report_schedules(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ReportSchedule>>, ApiError> {
+    Ok(Json(state.store.list_report_schedules().await?))
+}
+
+/// Get a single report schedule by ID
+pub async fn get_report_schedule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<ReportSchedule>, ApiError> {
+    let schedule = state
+        .store
+        .get_report_schedule(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("report schedule"))?;
+    Ok(Json(schedule))
+}
+
+/// Create a report schedule
+pub async fn create_report_schedule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateReportScheduleRequest>,
+) -> Result<(StatusCode, Json<ReportSchedule>), ApiError> {
+    validate_schedule(&state, None, &req).await?;
+    Ok(created(state.store.create_report_schedule(&req).await?))
+}
+
+/// Update a report schedule
+pub async fn update_report_schedule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateReportScheduleRequest>,
+) -> Result<Json<ReportSchedule>, ApiError> {
+    validate_schedule(&state, Some(id), &req).await?;
+    Ok(Json(state.store.update_report_schedule(id, &req).await?))
+}
+
+/// Delete a report schedule
+pub async fn delete_report_schedule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_report_schedule(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Run a report schedule now and send it to its channels; counts as the schedule's latest run
+pub async fn run_report_schedule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<FleetReport>, ApiError> {
+    let schedule = state
+        .store
+        .get_report_schedule(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("report schedule"))?;
+    let report = fleet_report::run_schedule(&state.store, &state.config, &schedule)
+        .await
+        .map_err(|e| ApiError::internal(format!("{:#}", e)))?;
+    Ok(Json(report))
+}
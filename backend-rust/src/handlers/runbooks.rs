@@ -0,0 +1,315 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+const MAX_RUN_LIMIT: i64 = 1000;
+
+async fn validate_runbook(state: &AppState, id: Option<i64>, req: &CreateRunbookRequest) -> Result<(), ApiError> {
+    if let Some(role_id) = req.device_role_id {
+        if state.store.get_device_role(role_id).await?.is_none() {
+            return Err(ApiError::bad_request(format!("device role {} not found", role_id)));
+        }
+    }
+    for (i, step) in req.steps.iter().enumerate() {
+        let position = i + 1;
+        if let Some(id) = step.vendor_action_id.filter(|_| step.step_type == runbook_step_type::VENDOR_ACTION) {
+            if state.store.get_vendor_action(id).await?.is_none() {
+                return Err(ApiError::bad_request(format!("step {}: vendor action {} not found", position, id)));
+            }
+        }
+        if let Some(id) = step.job_template_id.filter(|_| step.step_type == runbook_step_type::JOB_TEMPLATE) {
+            if state.store.get_job_template(id).await?.is_none() {
+                return Err(ApiError::bad_request(format!("step {}: job template {} not found", position, id)));
+            }
+        }
+    }
+    let runbooks = state.store.list_runbooks(None).await?;
+    if runbooks.iter().any(|r| r.name == req.name && Some(r.id) != id) {
+        return Err(ApiError::conflict(format!("runbook '{}' already exists", req.name)));
+    }
+    Ok(())
+}
+
+/// The device role a device's runbooks attach to, matched like its role templates
+async fn device_role(state: &AppState, device: &Device) -> Result<Option<DeviceRole>, ApiError> {
+    let vendor = match device.vendor_id.as_deref().or(device.vendor.as_deref()) {
+        Some(v) if !v.is_empty() => state.store.resolve_vendor(v).await?,
+        _ => None,
+    };
+    Ok(state.store.resolve_device_role(vendor.as_ref(), device.topology_role.as_deref()).await?)
+}
+
+/// List runbooks, optionally only those attached to a device role
+pub async fn list_runbooks(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RunbookQuery>,
+) -> Result<Json<Vec<Runbook>>, ApiError> {
+    Ok(Json(state.store.list_runbooks(query.device_role_id).await?))
+}
+
+/// Get a single runbook by ID
+pub async fn get_runbook(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Runbook>, ApiError> {
+    let runbook = state
+        .store
+        .get_runbook(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("runbook"))?;
+    Ok(Json(runbook))
+}
+
+/// Create a runbook
+pub async fn create_runbook(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateRunbookRequest>,
+) -> Result<(StatusCode, Json<Runbook>), ApiError> {
+    validate_runbook(&state, None, &req).await?;
+    Ok(created(state.store.create_runbook(&req).await?))
+}
+
+/// Update a runbook; runs already started keep the steps they started with
+pub async fn update_runbook(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateRunbookRequest>,
+) -> Result<Json<Runbook>, ApiError> {
+    validate_runbook(&state, Some(id), &req).await?;
+    Ok(Json(state.store.update_runbook(id, &req).await?))
+}
+
+/// Delete a runbook and its runs
+pub async fn delete_runbook(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_runbook(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Runbooks that can run on a device: those attached to its device role and those attached to none
+pub async fn list_device_runbooks(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Runbook>>, ApiError> {
+    let device = state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    let role_id = device_role(&state, &device).await?.map(|r| r.id);
+    let mut runbooks = state.store.list_runbooks(None).await?;
+    runbooks.retain(|r| r.device_role_id.is_none() || r.device_role_id == role_id);
+    Ok(Json(runbooks))
+}
+
+/// Start a runbook against a device
+pub async fn start_runbook_run(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<StartRunbookRunRequest>,
+) -> Result<(StatusCode, Json<RunbookRun>), ApiError> {
+    let runbook = state
+        .store
+        .get_runbook(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("runbook"))?;
+    let device = state
+        .store
+        .get_device(req.device_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    if let Some(role_id) = runbook.device_role_id {
+        if device_role(&state, &device).await?.map(|r| r.id) != Some(role_id) {
+            return Err(ApiError::bad_request(format!(
+                "runbook '{}' is for another device role than {}",
+                runbook.name, device.hostname
+            )));
+        }
+    }
+    let run = state.store.create_runbook_run(&runbook, device.id, &auth.claims.username).await?;
+    Ok(created(run))
+}
+
+/// List runbook runs, newest first
+pub async fn list_runbook_runs(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(mut query): Query<RunbookRunQuery>,
+) -> Result<Json<Vec<RunbookRun>>, ApiError> {
+    query.limit = query.limit.clamp(1, MAX_RUN_LIMIT);
+    Ok(Json(state.store.list_runbook_runs(&query).await?))
+}
+
+async fn get_run_or_404(state: &AppState, id: i64) -> Result<RunbookRun, ApiError> {
+    state
+        .store
+        .get_runbook_run(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("runbook run"))
+}
+
+/// Get a runbook run with its steps
+pub async fn get_runbook_run(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<RunbookRun>, ApiError> {
+    Ok(Json(get_run_or_404(&state, id).await?))
+}
+
+/// The run's current step, which must be at `position`
+fn current_step(run: &RunbookRun, position: i64) -> Result<&RunbookRunStep, ApiError> {
+    if run.status != runbook_run_status::IN_PROGRESS {
+        return Err(ApiError::conflict(format!("run is {}", run.status)));
+    }
+    let step = run
+        .steps
+        .iter()
+        .find(|s| s.position == position)
+        .ok_or_else(|| ApiError::not_found("runbook step"))?;
+    match run.current_step() {
+        Some(current) if current.position == position => Ok(step),
+        Some(current) => Err(ApiError::conflict(format!(
+            "step {} is signed off already or not reached; the current step is {}",
+            position, current.position
+        ))),
+        None => Err(ApiError::conflict("all steps are signed off")),
+    }
+}
+
+/// Jobs of a step's last execution that are still queued or running, and those that failed
+async fn step_jobs(state: &AppState, step: &RunbookRunStep) -> Result<(usize, usize), ApiError> {
+    let (mut active, mut failed) = (0, 0);
+    for job_id in &step.job_ids {
+        match state.store.get_job(job_id).await?.map(|j| j.status) {
+            Some(status) if status == job_status::QUEUED || status == job_status::RUNNING => active += 1,
+            Some(status) if status == job_status::FAILED || status == job_status::CANCELLED => failed += 1,
+            _ => {}
+        }
+    }
+    Ok((active, failed))
+}
+
+/// Run the current step's vendor action or job template on the run's device. A step can be
+/// executed again once its previous jobs have finished.
+pub async fn execute_runbook_step(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, position)): Path<(i64, i64)>,
+) -> Result<Json<RunbookRun>, ApiError> {
+    let run = get_run_or_404(&state, id).await?;
+    let step = current_step(&run, position)?;
+    if step_jobs(&state, step).await?.0 > 0 {
+        return Err(ApiError::conflict("the step's jobs are still running"));
+    }
+
+    let jobs = match step.step_type.as_str() {
+        runbook_step_type::VENDOR_ACTION => {
+            let action_id = step.vendor_action_id.unwrap_or_default();
+            let req = ExecRequest { command: String::new(), action_id: Some(action_id), timeout_secs: None };
+            let auth = crate::auth::AuthUser { claims: auth.claims.clone() };
+            let (_, Json(job)) =
+                super::devices::exec_command(auth, State(state.clone()), Path(run.device_id), Json(req)).await?;
+            vec![job]
+        }
+        runbook_step_type::JOB_TEMPLATE => {
+            let template_id = step.job_template_id.unwrap_or_default();
+            let template = state
+                .store
+                .get_job_template(template_id)
+                .await?
+                .ok_or_else(|| ApiError::bad_request(format!("job template {} no longer exists", template_id)))?;
+            let (action, checked_command) = super::job_templates::template_team_action(&template);
+            super::teams::require_device_action(&state, &auth, &[run.device_id], action, checked_command).await?;
+            let job_service = state
+                .job_service
+                .as_ref()
+                .ok_or_else(|| ApiError::service_unavailable("job service is not running"))?;
+            let jobs = job_service.queue_template_jobs(&template, &[run.device_id], "manual").await?;
+            if jobs.is_empty() {
+                return Err(ApiError::internal(format!("job template '{}' queued no jobs", template.name)));
+            }
+            jobs
+        }
+        _ => return Err(ApiError::bad_request("manual steps are signed off, not executed")),
+    };
+
+    let job_ids: Vec<String> = jobs.into_iter().map(|j| j.id).collect();
+    state.store.set_runbook_step_executed(id, position, &job_ids).await?;
+    Ok(Json(get_run_or_404(&state, id).await?))
+}
+
+/// Sign off the current step as done, skipped or failed. Automated steps are signed off as done
+/// once their jobs have completed; signing off as failed fails the run.
+pub async fn sign_off_runbook_step(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, position)): Path<(i64, i64)>,
+    Json(req): Json<RunbookSignOffRequest>,
+) -> Result<Json<RunbookRun>, ApiError> {
+    if !runbook_step_status::SIGN_OFF.contains(&req.status.as_str()) {
+        return Err(ApiError::bad_request(format!(
+            "status must be one of {}",
+            runbook_step_status::SIGN_OFF.join(", ")
+        )));
+    }
+    let run = get_run_or_404(&state, id).await?;
+    let step = current_step(&run, position)?;
+    if req.status == runbook_step_status::DONE && step.step_type != runbook_step_type::MANUAL {
+        if step.status != runbook_step_status::EXECUTED {
+            return Err(ApiError::bad_request("execute the step before signing it off as done"));
+        }
+        match step_jobs(&state, step).await? {
+            (active, _) if active > 0 => return Err(ApiError::conflict("the step's jobs are still running")),
+            (_, failed) if failed > 0 => {
+                return Err(ApiError::bad_request(
+                    "a job of this step failed: execute it again, or sign it off as skipped or failed",
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    state
+        .store
+        .sign_off_runbook_step(id, position, &req.status, &auth.claims.username, &req.notes)
+        .await?;
+    let is_last = run.steps.last().is_some_and(|s| s.position == position);
+    if req.status == runbook_step_status::FAILED {
+        state.store.finish_runbook_run(id, runbook_run_status::FAILED).await?;
+    } else if is_last {
+        state.store.finish_runbook_run(id, runbook_run_status::COMPLETED).await?;
+    }
+    Ok(Json(get_run_or_404(&state, id).await?))
+}
+
+/// Abort a run in progress; its remaining steps stay pending
+pub async fn abort_runbook_run(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<RunbookRun>, ApiError> {
+    let run = get_run_or_404(&state, id).await?;
+    if run.status != runbook_run_status::IN_PROGRESS {
+        return Err(ApiError::conflict(format!("run is {}", run.status)));
+    }
+    state.store.finish_runbook_run(id, runbook_run_status::ABORTED).await?;
+    Ok(Json(get_run_or_404(&state, id).await?))
+}
@@ -0,0 +1,100 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+/// List all saved searches
+pub async fn list_saved_searches(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<SavedSearch>>, ApiError> {
+    let searches = state.store.list_saved_searches().await?;
+    Ok(Json(searches))
+}
+
+/// Get a single saved search by ID
+pub async fn get_saved_search(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<SavedSearch>, ApiError> {
+    let search = state
+        .store
+        .get_saved_search(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("saved search"))?;
+    Ok(Json(search))
+}
+
+/// Create a new saved search
+pub async fn create_saved_search(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(mut req): ValidJson<CreateSavedSearchRequest>,
+) -> Result<(StatusCode, Json<SavedSearch>), ApiError> {
+    super::custom_fields::normalize_filter(&state, &mut req.filter).await?;
+    let search = state.store.create_saved_search(&req).await?;
+    Ok(created(search))
+}
+
+/// Update an existing saved search
+pub async fn update_saved_search(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(mut req): ValidJson<CreateSavedSearchRequest>,
+) -> Result<Json<SavedSearch>, ApiError> {
+    super::custom_fields::normalize_filter(&state, &mut req.filter).await?;
+    let search = state.store.update_saved_search(id, &req).await?;
+    Ok(Json(search))
+}
+
+/// Delete a saved search — refused while a job template still targets it
+pub async fn delete_saved_search(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let templates = state.store.list_job_templates().await?;
+    if let Some(t) = templates.iter().find(|t| t.target_saved_search_id == id) {
+        return Err(ApiError::conflict(format!(
+            "saved search is targeted by job template '{}'",
+            t.name
+        )));
+    }
+    state.store.delete_saved_search(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Evaluate a saved search and return the matching devices
+pub async fn list_saved_search_devices(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Device>>, ApiError> {
+    let search = state
+        .store
+        .get_saved_search(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("saved search"))?;
+    let devices = state.store.list_devices_filtered(&search.filter).await?;
+    Ok(Json(devices))
+}
+
+/// Evaluate an ad-hoc filter without saving it
+pub async fn preview_device_filter(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(mut filter): ValidJson<DeviceFilter>,
+) -> Result<Json<Vec<Device>>, ApiError> {
+    super::custom_fields::normalize_filter(&state, &mut filter).await?;
+    let devices = state.store.list_devices_filtered(&filter).await?;
+    Ok(Json(devices))
+}
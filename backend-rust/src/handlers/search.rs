@@ -0,0 +1,35 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::models::SearchResults;
+use crate::AppState;
+
+use super::ApiError;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    /// Maximum hits per result bucket
+    #[serde(default = "default_search_limit")]
+    pub limit: i32,
+}
+
+fn default_search_limit() -> i32 {
+    20
+}
+
+/// Global search across devices, templates, job output and discovery logs
+pub async fn search(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<SearchResults>, ApiError> {
+    let fts_query = crate::utils::fts_match_query(&query.q)
+        .ok_or_else(|| ApiError::bad_request("q is required"))?;
+    let results = state.store.search(&fts_query, query.limit.clamp(1, 100)).await?;
+    Ok(Json(results))
+}
@@ -0,0 +1,115 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::templates::record_template_change;
+use super::{trigger_reload, ApiError, ValidJson};
+
+/// Seed set versions and seed updates held back because the local copy was customized
+pub async fn get_seed_status(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SeedStatus>, ApiError> {
+    let mut pending = Vec::new();
+    for item in state.store.list_pending_seed_items().await? {
+        let template = match item.resource_id {
+            Some(id) => state.store.get_template(id).await?,
+            None => None,
+        };
+        let (name, current) = match template {
+            Some(t) => (t.name, t.content),
+            None => (item.seed_key.clone(), String::new()),
+        };
+        let diff = crate::utils::unified_diff(
+            &current,
+            item.pending_content.as_deref().unwrap_or_default(),
+            "local",
+            "seed",
+            3,
+        );
+        pending.push(PendingSeedUpdate { item, name, diff });
+    }
+    Ok(Json(SeedStatus {
+        sets: state.store.list_seed_versions().await?,
+        pending,
+    }))
+}
+
+async fn pending_seed_item(state: &AppState, id: i64) -> Result<(SeedItem, String), ApiError> {
+    let item = state
+        .store
+        .get_seed_item(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("seed item"))?;
+    let pending = item
+        .pending_content
+        .clone()
+        .ok_or_else(|| ApiError::bad_request("seed item has no pending update"))?;
+    Ok((item, pending))
+}
+
+/// Overwrite the local copy with the pending seed content
+pub async fn apply_seed_update(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<SeedItem>, ApiError> {
+    let (mut item, pending) = pending_seed_item(&state, id).await?;
+    let template = match item.resource_id {
+        Some(template_id) => state.store.get_template(template_id).await?,
+        None => None,
+    }
+    .ok_or_else(|| ApiError::not_found("template"))?;
+
+    let req = CreateTemplateRequest {
+        name: template.name,
+        description: template.description,
+        vendor_id: template.vendor_id,
+        content: pending.clone(),
+    };
+    let template = state.store.update_template(template.id, &req).await?;
+    record_template_change(&state, &auth, change_action::UPDATE, &template).await;
+
+    item.applied_content = pending;
+    item.pending_content = None;
+    item.user_modified = false;
+    item.updated_at = chrono::Utc::now();
+    state.store.save_seed_item(&item).await?;
+    trigger_reload(&state).await;
+    Ok(Json(item))
+}
+
+/// Keep the local copy and acknowledge the pending seed content, so it isn't offered again
+pub async fn dismiss_seed_update(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<SeedItem>, ApiError> {
+    let (mut item, pending) = pending_seed_item(&state, id).await?;
+    item.applied_content = pending;
+    item.pending_content = None;
+    item.user_modified = true;
+    item.updated_at = chrono::Utc::now();
+    state.store.save_seed_item(&item).await?;
+    Ok(Json(item))
+}
+
+/// Set or clear an item's protection from seed upgrades
+pub async fn update_seed_item(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<UpdateSeedItemRequest>,
+) -> Result<Json<SeedItem>, ApiError> {
+    state
+        .store
+        .get_seed_item(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("seed item"))?;
+    Ok(Json(state.store.set_seed_item_user_modified(id, req.user_modified).await?))
+}
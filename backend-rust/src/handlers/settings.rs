@@ -11,7 +11,24 @@ use std::sync::Arc;
 use crate::models::*;
 use crate::AppState;
 
-use super::{trigger_reload, ApiError, MessageResponse};
+use super::{record_change, trigger_reload, ApiError, MessageResponse};
+
+/// Names of the top-level settings fields that differ between two snapshots
+fn changed_settings_fields(before: &Settings, after: &Settings) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(before)), Ok(serde_json::Value::Object(after))) =
+        (serde_json::to_value(before), serde_json::to_value(after))
+    else {
+        return Vec::new();
+    };
+    let mut changed: Vec<String> = after
+        .iter()
+        .filter(|(key, value)| before.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .chain(before.keys().filter(|key| !after.contains_key(*key)).cloned())
+        .collect();
+    changed.sort();
+    changed
+}
 
 /// Get the global settings
 pub async fn get_settings(
@@ -24,15 +41,81 @@ pub async fn get_settings(
 
 /// Update the global settings
 pub async fn update_settings(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Json(settings): Json<Settings>,
 ) -> Result<Json<Settings>, ApiError> {
+    let policy = &settings.delete_policy;
+    for value in [&policy.vendors, &policy.templates, &policy.groups] {
+        if value != delete_policy::RESTRICT && value != delete_policy::CASCADE {
+            return Err(ApiError::bad_request(format!(
+                "delete_policy values must be '{}' or '{}'",
+                delete_policy::RESTRICT,
+                delete_policy::CASCADE
+            )));
+        }
+    }
+    if settings.contract_alert_days < 1 {
+        return Err(ApiError::bad_request("contract_alert_days must be at least 1"));
+    }
+    for channel_id in &settings.contract_alert_channel_ids {
+        if state.store.get_notification_channel(*channel_id).await?.is_none() {
+            return Err(ApiError::bad_request(format!("notification channel {} not found", channel_id)));
+        }
+    }
+    let previous = state.store.get_settings().await?;
     state.store.update_settings(&settings).await?;
+    let changed = changed_settings_fields(&previous, &settings);
+    if !changed.is_empty() {
+        let entry = NewChangeLogEntry {
+            category: change_category::SETTINGS,
+            action: change_action::UPDATE,
+            resource_type: "settings",
+            summary: format!("changed {}", changed.join(", ")),
+            ..Default::default()
+        };
+        record_change(&state, &auth, entry).await;
+    }
     trigger_reload(&state).await;
     Ok(Json(settings))
 }
 
+/// Middleware rejecting mutating API calls while maintenance mode or the read_only feature flag
+/// is on. Login, database backups and the endpoint that clears each switch stay available.
+pub async fn read_only_guard(
+    State(state): State<Arc<AppState>>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    use axum::http::Method;
+    use axum::response::IntoResponse;
+
+    let path = request.uri().path();
+    let mutating = !matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    if !mutating || !path.starts_with("/api/") || path == "/api/auth/login" || path == "/api/system/db-backup" {
+        return next.run(request).await;
+    }
+    if state.maintenance_mode.is_enabled() && path != "/api/maintenance-mode" {
+        let mode = state.maintenance_mode.state();
+        let message = if mode.reason.is_empty() {
+            "server is in maintenance mode".to_string()
+        } else {
+            format!("server is in maintenance mode: {}", mode.reason)
+        };
+        return ApiError::service_unavailable(message).into_response();
+    }
+    if path != "/api/settings" && path != "/api/maintenance-mode" {
+        match state.store.get_settings().await {
+            Ok(settings) if settings.features.read_only => {
+                return ApiError::service_unavailable("server is in read-only mode").into_response();
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to load settings for read-only check: {}", e),
+        }
+    }
+    next.run(request).await
+}
+
 /// Trigger a manual config regeneration
 pub async fn reload_config(
     _auth: crate::auth::AuthUser,
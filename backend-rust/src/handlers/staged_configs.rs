@@ -0,0 +1,128 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+async fn get_staged(state: &AppState, id: i64) -> Result<StagedConfig, ApiError> {
+    state
+        .store
+        .get_staged_config(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("staged config"))
+}
+
+/// List staged configs, latest activation first
+pub async fn list_staged_configs(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StagedConfigQuery>,
+) -> Result<Json<Vec<StagedConfig>>, ApiError> {
+    Ok(Json(state.store.list_staged_configs(&query).await?))
+}
+
+/// Get a staged config
+pub async fn get_staged_config(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<StagedConfig>, ApiError> {
+    Ok(Json(get_staged(&state, id).await?))
+}
+
+/// Stage a config for a device, pushed at `activate_at`. Without a config, the device's
+/// config is rendered now.
+pub async fn create_staged_config(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(device_id): Path<i64>,
+    ValidJson(req): ValidJson<CreateStagedConfigRequest>,
+) -> Result<(StatusCode, Json<StagedConfig>), ApiError> {
+    super::teams::require_device_action(&state, &auth, &[device_id], team_action::DEPLOY, None).await?;
+    let device = state
+        .store
+        .get_device(device_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    if req.activate_at <= Utc::now() {
+        return Err(ApiError::bad_request("activate_at must be in the future"));
+    }
+
+    let config = match req.config.as_deref() {
+        Some(config) => config.to_string(),
+        None => {
+            let (_, rendered) = crate::jobs::render_device(&state.store, &state.render_cache, &device)
+                .await
+                .map_err(|e| ApiError::bad_request(format!("failed to render config: {:#}", e)))?;
+            rendered
+        }
+    };
+
+    let staged = state
+        .store
+        .create_staged_config(device_id, &config, &req, &auth.claims.username)
+        .await?;
+    Ok(created(staged))
+}
+
+/// Change a staged config's activation time, config, description or maintenance window
+/// requirement. Only scheduled configs can be changed.
+pub async fn update_staged_config(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<UpdateStagedConfigRequest>,
+) -> Result<Json<StagedConfig>, ApiError> {
+    let mut staged = get_staged(&state, id).await?;
+    super::teams::require_device_action(&state, &auth, &[staged.device_id], team_action::DEPLOY, None).await?;
+    if staged.status != staged_config_status::SCHEDULED {
+        return Err(ApiError::conflict(format!("staged config is {}", staged.status)));
+    }
+
+    if let Some(activate_at) = req.activate_at {
+        if activate_at <= Utc::now() {
+            return Err(ApiError::bad_request("activate_at must be in the future"));
+        }
+        staged.activate_at = activate_at;
+    }
+    if let Some(config) = req.config {
+        staged.config = config;
+    }
+    if let Some(description) = req.description {
+        staged.description = description;
+    }
+    if let Some(require) = req.require_maintenance_window {
+        staged.require_maintenance_window = require;
+    }
+
+    if !state.store.update_scheduled_staged_config(&staged).await? {
+        return Err(ApiError::conflict("staged config is no longer scheduled"));
+    }
+    Ok(Json(get_staged(&state, id).await?))
+}
+
+/// Cancel a scheduled staged config
+pub async fn cancel_staged_config(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<StagedConfig>, ApiError> {
+    let staged = get_staged(&state, id).await?;
+    super::teams::require_device_action(&state, &auth, &[staged.device_id], team_action::DEPLOY, None).await?;
+    if !state
+        .store
+        .transition_scheduled_staged_config(id, staged_config_status::CANCELLED, None)
+        .await?
+    {
+        let staged = get_staged(&state, id).await?;
+        return Err(ApiError::conflict(format!("staged config is {}", staged.status)));
+    }
+    Ok(Json(get_staged(&state, id).await?))
+}
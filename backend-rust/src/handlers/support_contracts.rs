@@ -0,0 +1,140 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::contract_expiry;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+async fn validate_contract(state: &AppState, id: Option<i64>, req: &CreateSupportContractRequest) -> Result<(), ApiError> {
+    if let Some(device_id) = req.device_id {
+        if state.store.get_device(device_id).await?.is_none() {
+            return Err(ApiError::bad_request(format!("device {} not found", device_id)));
+        }
+    }
+    if let Some(model_id) = req.device_model_id {
+        if state.store.get_device_model(model_id).await?.is_none() {
+            return Err(ApiError::bad_request(format!("device model {} not found", model_id)));
+        }
+    }
+    let contracts = state.store.list_support_contracts().await?;
+    if let Some(existing) = contracts.iter().find(|c| {
+        Some(c.id) != id
+            && ((req.device_id.is_some() && c.device_id == req.device_id)
+                || (req.device_model_id.is_some() && c.device_model_id == req.device_model_id))
+    }) {
+        return Err(ApiError::conflict(format!(
+            "the {} already has support contract {}",
+            existing.scope(),
+            existing.id
+        )));
+    }
+    Ok(())
+}
+
+/// List support contracts, soonest expiry first
+pub async fn list_support_contracts(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<SupportContract>>, ApiError> {
+    Ok(Json(state.store.list_support_contracts().await?))
+}
+
+/// Get a single support contract by ID
+pub async fn get_support_contract(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<SupportContract>, ApiError> {
+    let contract = state
+        .store
+        .get_support_contract(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("support contract"))?;
+    Ok(Json(contract))
+}
+
+/// Create a support contract for a device or a device model
+pub async fn create_support_contract(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateSupportContractRequest>,
+) -> Result<(StatusCode, Json<SupportContract>), ApiError> {
+    validate_contract(&state, None, &req).await?;
+    Ok(created(state.store.create_support_contract(&req).await?))
+}
+
+/// Update a support contract; a new expires_on re-arms its expiry alert
+pub async fn update_support_contract(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateSupportContractRequest>,
+) -> Result<Json<SupportContract>, ApiError> {
+    validate_contract(&state, Some(id), &req).await?;
+    Ok(Json(state.store.update_support_contract(id, &req).await?))
+}
+
+/// Delete a support contract
+pub async fn delete_support_contract(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_support_contract(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Devices whose contract expires within `days` (default: the contract_alert_days setting) or
+/// already expired, soonest first
+pub async fn expiring_support_contracts(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ContractExpiryQuery>,
+) -> Result<Json<ContractExpiryReport>, ApiError> {
+    let days = match query.days {
+        Some(days) if days < 0 => return Err(ApiError::bad_request("days must not be negative")),
+        Some(days) => days,
+        None => state.store.get_settings().await?.contract_alert_days as i64,
+    };
+    Ok(Json(contract_expiry::report(&state.store, days, query.include_uncovered).await?))
+}
+
+/// Run the expiry check now, notifying the contract alert channels about contracts not yet alerted
+pub async fn check_support_contract_expiry(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ContractAlertResult>, ApiError> {
+    let alerted = contract_expiry::send_alerts(&state.store, &state.config)
+        .await
+        .map_err(|e| ApiError::internal(format!("{:#}", e)))?;
+    Ok(Json(ContractAlertResult { alerted }))
+}
+
+/// The contract covering a device: its own, else its model's
+pub async fn get_device_support_contract(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<SupportContract>, ApiError> {
+    let device = state
+        .store
+        .get_device(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    let contracts = state.store.list_support_contracts().await?;
+    let by_device: HashMap<i64, &SupportContract> =
+        contracts.iter().filter_map(|c| Some((c.device_id?, c))).collect();
+    let by_model: HashMap<i64, &SupportContract> =
+        contracts.iter().filter_map(|c| Some((c.device_model_id?, c))).collect();
+    let models = state.store.list_device_models().await?;
+    let contract = contract_expiry::effective_contract(&device, &by_device, &by_model, &models)
+        .ok_or_else(|| ApiError::not_found("support contract"))?;
+    Ok(Json(contract.clone()))
+}
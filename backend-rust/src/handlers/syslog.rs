@@ -0,0 +1,116 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+const MAX_SYSLOG_LIMIT: i64 = 1000;
+
+/// Reload the receiver's compiled rules after a rule change
+async fn reload_receiver(state: &AppState) {
+    if let Some(ref receiver) = state.syslog_receiver {
+        receiver.reload().await;
+    }
+}
+
+async fn validate_rule(state: &AppState, id: Option<i64>, req: &CreateSyslogAlertRuleRequest) -> Result<(), ApiError> {
+    let rules = state.store.list_syslog_alert_rules().await?;
+    if rules.iter().any(|r| r.name == req.name && Some(r.id) != id) {
+        return Err(ApiError::conflict(format!("syslog alert rule '{}' already exists", req.name)));
+    }
+    Ok(())
+}
+
+/// List received syslog events, newest first
+pub async fn list_syslog_events(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(mut query): Query<SyslogEventQuery>,
+) -> Result<Json<Vec<SyslogEvent>>, ApiError> {
+    query.limit = query.limit.clamp(1, MAX_SYSLOG_LIMIT);
+    Ok(Json(state.store.list_syslog_events(&query).await?))
+}
+
+/// List syslog alerts, newest first
+pub async fn list_syslog_alerts(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(mut query): Query<SyslogAlertQuery>,
+) -> Result<Json<Vec<SyslogAlert>>, ApiError> {
+    query.limit = query.limit.clamp(1, MAX_SYSLOG_LIMIT);
+    Ok(Json(state.store.list_syslog_alerts(&query).await?))
+}
+
+/// Acknowledge a syslog alert
+pub async fn acknowledge_syslog_alert(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<SyslogAlert>, ApiError> {
+    let alert = state.store.acknowledge_syslog_alert(id, &auth.claims.username).await?;
+    Ok(Json(alert))
+}
+
+/// List syslog alert rules
+pub async fn list_syslog_alert_rules(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<SyslogAlertRule>>, ApiError> {
+    Ok(Json(state.store.list_syslog_alert_rules().await?))
+}
+
+/// Get a single syslog alert rule by ID
+pub async fn get_syslog_alert_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<SyslogAlertRule>, ApiError> {
+    let rule = state
+        .store
+        .get_syslog_alert_rule(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("syslog alert rule"))?;
+    Ok(Json(rule))
+}
+
+/// Create a syslog alert rule
+pub async fn create_syslog_alert_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateSyslogAlertRuleRequest>,
+) -> Result<(StatusCode, Json<SyslogAlertRule>), ApiError> {
+    validate_rule(&state, None, &req).await?;
+    let rule = state.store.create_syslog_alert_rule(&req).await?;
+    reload_receiver(&state).await;
+    Ok(created(rule))
+}
+
+/// Update a syslog alert rule
+pub async fn update_syslog_alert_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateSyslogAlertRuleRequest>,
+) -> Result<Json<SyslogAlertRule>, ApiError> {
+    validate_rule(&state, Some(id), &req).await?;
+    let rule = state.store.update_syslog_alert_rule(id, &req).await?;
+    reload_receiver(&state).await;
+    Ok(Json(rule))
+}
+
+/// Delete a syslog alert rule and its alerts
+pub async fn delete_syslog_alert_rule(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_syslog_alert_rule(id).await?;
+    reload_receiver(&state).await;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,166 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::{db_maintenance, partitions, runtime_config};
+use crate::AppState;
+
+use super::{created, ApiError};
+
+const MAX_HISTORY_LIMIT: i64 = 500;
+
+/// Back up the database to a timestamped file, optionally uploading it to db_backup_upload_url
+pub async fn create_db_backup(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    body: Option<Json<DbBackupRequest>>,
+) -> Result<(StatusCode, Json<DbMaintenanceRun>), ApiError> {
+    let req = body.map(|b| b.0).unwrap_or_default();
+    let run = db_maintenance::backup_database(&state.store, &state.config.backup_dir, req.upload, &auth.claims.username)
+        .await
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+    if run.filename.is_none() {
+        return Err(ApiError::internal(run.error.unwrap_or_else(|| "database backup failed".to_string())));
+    }
+    Ok(created(run))
+}
+
+/// List past database backups, newest first
+pub async fn list_db_backups(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DbMaintenanceQuery>,
+) -> Result<Json<Vec<DbMaintenanceRun>>, ApiError> {
+    let limit = query.limit.clamp(1, MAX_HISTORY_LIMIT);
+    Ok(Json(state.store.list_db_maintenance_runs(db_maintenance_kind::BACKUP, limit).await?))
+}
+
+/// Run VACUUM and ANALYZE now
+pub async fn run_db_vacuum(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<DbMaintenanceRun>, ApiError> {
+    let run = db_maintenance::vacuum_database(&state.store, &auth.claims.username).await?;
+    if let Some(error) = run.error {
+        return Err(ApiError::internal(error));
+    }
+    Ok(Json(run))
+}
+
+/// List past VACUUM/ANALYZE runs, newest first
+pub async fn list_db_vacuums(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DbMaintenanceQuery>,
+) -> Result<Json<Vec<DbMaintenanceRun>>, ApiError> {
+    let limit = query.limit.clamp(1, MAX_HISTORY_LIMIT);
+    Ok(Json(state.store.list_db_maintenance_runs(db_maintenance_kind::VACUUM, limit).await?))
+}
+
+/// List the monthly partitions of the history tables, newest month first per table
+pub async fn list_partitions(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<TablePartition>>, ApiError> {
+    Ok(Json(state.store.list_table_partitions().await?))
+}
+
+/// Run the partition rollover now, as the scheduler does every hour
+pub async fn run_partition_rollover(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<PartitionRollover>, ApiError> {
+    let result = partitions::rollover(&state.store).await?;
+    tracing::info!(
+        "Partition rollover by {}: moved {} rows, dropped {} partitions",
+        auth.claims.username, result.moved_rows, result.dropped.len()
+    );
+    Ok(Json(result))
+}
+
+/// Scan the database for orphaned rows
+pub async fn check_integrity(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<IntegrityReport>, ApiError> {
+    Ok(Json(state.store.check_integrity().await?))
+}
+
+/// Delete (or detach) orphaned rows found by the integrity check
+pub async fn cleanup_integrity(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    body: Option<Json<IntegrityCleanupRequest>>,
+) -> Result<Json<Vec<IntegrityCleanupResult>>, ApiError> {
+    let req = body.map(|b| b.0).unwrap_or_default();
+    if let Some(unknown) = req.checks.iter().find(|c| !state.store.is_known_integrity_check(c)) {
+        return Err(ApiError::bad_request(format!("unknown integrity check '{}'", unknown)));
+    }
+    let results = state.store.cleanup_integrity_issues(&req.checks).await?;
+    for result in &results {
+        tracing::warn!(
+            "Integrity cleanup by {}: {} {} rows ({})",
+            auth.claims.username, result.action, result.rows_affected, result.check
+        );
+    }
+    Ok(Json(results))
+}
+
+/// Re-read the configuration and apply the values that can change without a restart
+pub async fn reload_config(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ConfigReloadResult>, ApiError> {
+    let result = state
+        .runtime_config
+        .reload()
+        .await
+        .map_err(|e| ApiError::bad_request(format!("configuration reload failed: {:#}", e)))?;
+    tracing::info!("Configuration reloaded by {}", auth.claims.username);
+    runtime_config::log_result(&result);
+    Ok(Json(result))
+}
+
+/// Current SSH pool usage: concurrency limits, sessions in use, idle sessions kept for reuse and
+/// operations throttled by the per-device limit
+pub async fn get_ssh_pool(_auth: crate::auth::AuthUser) -> Json<crate::utils::SshPoolStats> {
+    Json(crate::utils::ssh_pool_stats())
+}
+
+/// Devices whose SSH connections have been failing, with open circuit breakers first
+pub async fn list_ssh_breakers(_auth: crate::auth::AuthUser) -> Json<Vec<crate::utils::SshBreaker>> {
+    Json(crate::utils::ssh_breakers())
+}
+
+/// Close a device's SSH circuit breaker so the next operation connects again
+pub async fn reset_ssh_breaker(
+    auth: crate::auth::AuthUser,
+    Path(host): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    if !crate::utils::reset_ssh_breaker(&host) {
+        return Err(ApiError::not_found("SSH circuit breaker"));
+    }
+    tracing::info!("SSH circuit breaker for {} reset by {}", host, auth.claims.username);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Hits and misses of the Store's lookup cache (vendors, templates, settings, group hierarchy)
+pub async fn get_lookup_cache(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Json<Vec<LookupCacheStats>> {
+    Json(state.store.lookup_cache_stats())
+}
+
+/// Drop every cached lookup, e.g. after editing the database by hand
+pub async fn clear_lookup_cache(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> StatusCode {
+    state.store.clear_lookup_cache();
+    StatusCode::NO_CONTENT
+}
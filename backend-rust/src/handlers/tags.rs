@@ -0,0 +1,125 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::ApiError;
+
+/// Query parameter accepted by list endpoints that support tag filtering
+#[derive(Debug, Default, Deserialize)]
+pub struct TagFilterQuery {
+    /// `key` or `key=value`
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListTagsQuery {
+    #[serde(default)]
+    pub resource_type: Option<String>,
+}
+
+fn check_resource_type(resource_type: &str) -> Result<(), ApiError> {
+    if !tag_resource::is_valid(resource_type) {
+        return Err(ApiError::bad_request(format!(
+            "invalid resource type '{}': must be one of device, template, credential, job",
+            resource_type
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_tag_selector(tag: &str) -> Result<TagSelector, ApiError> {
+    TagSelector::parse(tag).ok_or_else(|| ApiError::bad_request("tag must be 'key' or 'key=value'"))
+}
+
+/// Keep only the items whose resource carries a tag matching `tag`; no-op when no tag is given
+pub(crate) async fn retain_tagged<T>(
+    state: &AppState,
+    resource_type: &str,
+    tag: Option<&str>,
+    items: Vec<T>,
+    id_of: impl Fn(&T) -> String,
+) -> Result<Vec<T>, ApiError> {
+    let Some(tag) = tag else {
+        return Ok(items);
+    };
+    let selector = parse_tag_selector(tag)?;
+    let ids: HashSet<String> = state
+        .store
+        .find_tagged_resource_ids(resource_type, &selector)
+        .await?
+        .into_iter()
+        .collect();
+    Ok(items.into_iter().filter(|item| ids.contains(&id_of(item))).collect())
+}
+
+/// List all tags, optionally restricted to one resource type
+pub async fn list_all_tags(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListTagsQuery>,
+) -> Result<Json<Vec<ResourceTag>>, ApiError> {
+    if let Some(ref rt) = query.resource_type {
+        check_resource_type(rt)?;
+    }
+    let tags = state.store.list_all_resource_tags(query.resource_type.as_deref()).await?;
+    Ok(Json(tags))
+}
+
+/// List distinct tag keys in use
+pub async fn list_tag_keys(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    let keys = state.store.list_resource_tag_keys().await?;
+    Ok(Json(keys))
+}
+
+/// List tags on a single resource
+pub async fn list_resource_tags(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((resource_type, resource_id)): Path<(String, String)>,
+) -> Result<Json<Vec<ResourceTag>>, ApiError> {
+    check_resource_type(&resource_type)?;
+    let tags = state.store.list_resource_tags(&resource_type, &resource_id).await?;
+    Ok(Json(tags))
+}
+
+/// Set (create or overwrite) a tag on a resource
+pub async fn set_resource_tag(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((resource_type, resource_id)): Path<(String, String)>,
+    Json(req): Json<SetResourceTagRequest>,
+) -> Result<Json<Vec<ResourceTag>>, ApiError> {
+    check_resource_type(&resource_type)?;
+    if req.key.is_empty() {
+        return Err(ApiError::bad_request("key is required"));
+    }
+    if req.key.contains('=') {
+        return Err(ApiError::bad_request("key must not contain '='"));
+    }
+    state.store.set_resource_tag(&resource_type, &resource_id, &req.key, &req.value).await?;
+    let tags = state.store.list_resource_tags(&resource_type, &resource_id).await?;
+    Ok(Json(tags))
+}
+
+/// Remove a tag from a resource
+pub async fn delete_resource_tag(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((resource_type, resource_id, key)): Path<(String, String, String)>,
+) -> Result<Json<Vec<ResourceTag>>, ApiError> {
+    check_resource_type(&resource_type)?;
+    state.store.delete_resource_tag(&resource_type, &resource_id, &key).await?;
+    let tags = state.store.list_resource_tags(&resource_type, &resource_id).await?;
+    Ok(Json(tags))
+}
@@ -0,0 +1,229 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::team_access::TeamAccess;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+// ========== Access Checks ==========
+
+async fn load_access(state: &AppState, auth: &crate::auth::AuthUser) -> Result<TeamAccess, ApiError> {
+    Ok(TeamAccess::load(&state.store, &auth.claims.username).await?)
+}
+
+/// Teams, ownership and rules are managed by users outside any team, so members can't widen
+/// their own access
+async fn require_team_admin(state: &AppState, auth: &crate::auth::AuthUser) -> Result<(), ApiError> {
+    if !load_access(state, auth).await?.is_unrestricted() {
+        return Err(ApiError::forbidden("team members can't manage teams"));
+    }
+    Ok(())
+}
+
+/// Refuse to modify a resource owned by a team the user isn't in
+pub(super) async fn require_modify(
+    state: &AppState,
+    auth: &crate::auth::AuthUser,
+    resource_type: &str,
+    resource_id: i64,
+) -> Result<(), ApiError> {
+    if !load_access(state, auth).await?.can_modify(&state.store, resource_type, resource_id).await? {
+        return Err(ApiError::forbidden(format!("this {} is owned by another team", resource_type)));
+    }
+    Ok(())
+}
+
+/// Refuse a device action none of the user's team rules grant
+pub(super) async fn require_device_action(
+    state: &AppState,
+    auth: &crate::auth::AuthUser,
+    device_ids: &[i64],
+    action: &str,
+    command: Option<&str>,
+) -> Result<(), ApiError> {
+    let access = load_access(state, auth).await?;
+    for &device_id in device_ids {
+        if !access.can_run(&state.store, device_id, action, command).await? {
+            return Err(ApiError::forbidden(format!(
+                "your teams aren't allowed to {} on device {}",
+                match command {
+                    Some(command) => format!("run '{}'", command),
+                    None => action.to_string(),
+                },
+                device_id
+            )));
+        }
+    }
+    Ok(())
+}
+
+// ========== Team CRUD ==========
+
+async fn get_team_or_404(state: &AppState, id: i64) -> Result<Team, ApiError> {
+    state.store.get_team(id).await?.ok_or_else(|| ApiError::not_found("team"))
+}
+
+pub async fn list_teams(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Team>>, ApiError> {
+    Ok(Json(state.store.list_teams().await?))
+}
+
+pub async fn get_team(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Team>, ApiError> {
+    Ok(Json(get_team_or_404(&state, id).await?))
+}
+
+pub async fn create_team(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateTeamRequest>,
+) -> Result<(StatusCode, Json<Team>), ApiError> {
+    require_team_admin(&state, &auth).await?;
+    if state.store.list_teams().await?.iter().any(|t| t.name == req.name) {
+        return Err(ApiError::conflict("a team with this name already exists"));
+    }
+    Ok(created(state.store.create_team(&req).await?))
+}
+
+pub async fn update_team(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateTeamRequest>,
+) -> Result<Json<Team>, ApiError> {
+    require_team_admin(&state, &auth).await?;
+    if state.store.list_teams().await?.iter().any(|t| t.name == req.name && t.id != id) {
+        return Err(ApiError::conflict("a team with this name already exists"));
+    }
+    Ok(Json(state.store.update_team(id, &req).await?))
+}
+
+pub async fn delete_team(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    require_team_admin(&state, &auth).await?;
+    state.store.delete_team(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Replace the team's members
+pub async fn set_team_members(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<SetTeamMembersRequest>,
+) -> Result<Json<Team>, ApiError> {
+    require_team_admin(&state, &auth).await?;
+    get_team_or_404(&state, id).await?;
+    for &user_id in &req.user_ids {
+        if state.store.get_user(user_id).await?.is_none() {
+            return Err(ApiError::bad_request(format!("user {} not found", user_id)));
+        }
+    }
+    state.store.set_team_members(id, &req.user_ids).await?;
+    Ok(Json(get_team_or_404(&state, id).await?))
+}
+
+// ========== Ownership ==========
+
+pub async fn list_team_resources(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<TeamResource>>, ApiError> {
+    get_team_or_404(&state, id).await?;
+    Ok(Json(state.store.list_team_resources(id).await?))
+}
+
+/// Make the team the owner of a device, group or template
+pub async fn add_team_resource(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<TeamResourceRequest>,
+) -> Result<Json<Vec<TeamResource>>, ApiError> {
+    require_team_admin(&state, &auth).await?;
+    get_team_or_404(&state, id).await?;
+    let exists = match req.resource_type.as_str() {
+        team_resource_type::DEVICE => state.store.get_device(req.resource_id).await?.is_some(),
+        team_resource_type::GROUP => state.store.get_group(req.resource_id).await?.is_some(),
+        team_resource_type::TEMPLATE => state.store.get_template(req.resource_id).await?.is_some(),
+        other => {
+            return Err(ApiError::bad_request(format!(
+                "unknown resource_type '{}': expected device, group or template",
+                other
+            )))
+        }
+    };
+    if !exists {
+        return Err(ApiError::bad_request(format!("{} {} not found", req.resource_type, req.resource_id)));
+    }
+    state.store.set_resource_owner(&req.resource_type, req.resource_id, id).await?;
+    Ok(Json(state.store.list_team_resources(id).await?))
+}
+
+pub async fn remove_team_resource(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, resource_type, resource_id)): Path<(i64, String, i64)>,
+) -> Result<StatusCode, ApiError> {
+    require_team_admin(&state, &auth).await?;
+    state.store.remove_resource_owner(id, &resource_type, resource_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ========== Rules ==========
+
+pub async fn list_team_rules(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<TeamRule>>, ApiError> {
+    get_team_or_404(&state, id).await?;
+    Ok(Json(state.store.list_team_rules(&[id]).await?))
+}
+
+pub async fn create_team_rule(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(mut req): ValidJson<CreateTeamRuleRequest>,
+) -> Result<(StatusCode, Json<TeamRule>), ApiError> {
+    require_team_admin(&state, &auth).await?;
+    get_team_or_404(&state, id).await?;
+    match req.group_id {
+        Some(group_id) if req.scope == team_rule_scope::GROUP => {
+            if state.store.get_group(group_id).await?.is_none() {
+                return Err(ApiError::bad_request("group not found"));
+            }
+        }
+        _ => req.group_id = None,
+    }
+    if req.command_pattern.trim().is_empty() {
+        req.command_pattern = "*".to_string();
+    }
+    Ok(created(state.store.create_team_rule(id, &req).await?))
+}
+
+pub async fn delete_team_rule(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, rule_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, ApiError> {
+    require_team_admin(&state, &auth).await?;
+    state.store.delete_team_rule(id, rule_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
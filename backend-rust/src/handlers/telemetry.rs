@@ -0,0 +1,112 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::AppState;
+
+use super::{created, ApiError, ValidJson};
+
+const MAX_SAMPLE_LIMIT: i64 = 5000;
+
+async fn validate_subscription(state: &AppState, id: Option<i64>, req: &CreateTelemetrySubscriptionRequest) -> Result<(), ApiError> {
+    let subscriptions = state.store.list_telemetry_subscriptions().await?;
+    if subscriptions.iter().any(|s| s.name == req.name && Some(s.id) != id) {
+        return Err(ApiError::conflict(format!("telemetry subscription '{}' already exists", req.name)));
+    }
+    if let Some(device_id) = req.device_id {
+        if state.store.get_device(device_id).await?.is_none() {
+            return Err(ApiError::bad_request("Device not found"));
+        }
+    }
+    if let Some(vendor_id) = req.vendor_id {
+        if state.store.get_vendor(vendor_id).await?.is_none() {
+            return Err(ApiError::bad_request("Vendor not found"));
+        }
+    }
+    if let Some(credential_id) = req.credential_id {
+        if state.store.get_credential(credential_id).await?.is_none() {
+            return Err(ApiError::bad_request("Credential not found"));
+        }
+    }
+    Ok(())
+}
+
+/// List telemetry subscriptions
+pub async fn list_telemetry_subscriptions(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<TelemetrySubscription>>, ApiError> {
+    Ok(Json(state.store.list_telemetry_subscriptions().await?))
+}
+
+/// Get a single telemetry subscription by ID
+pub async fn get_telemetry_subscription(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<TelemetrySubscription>, ApiError> {
+    let subscription = state
+        .store
+        .get_telemetry_subscription(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("telemetry subscription"))?;
+    Ok(Json(subscription))
+}
+
+/// Create a telemetry subscription and open its streams
+pub async fn create_telemetry_subscription(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateTelemetrySubscriptionRequest>,
+) -> Result<(StatusCode, Json<TelemetrySubscription>), ApiError> {
+    validate_subscription(&state, None, &req).await?;
+    let subscription = state.store.create_telemetry_subscription(&req).await?;
+    state.gnmi.reload().await;
+    Ok(created(subscription))
+}
+
+/// Update a telemetry subscription; its streams restart with the new settings
+pub async fn update_telemetry_subscription(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateTelemetrySubscriptionRequest>,
+) -> Result<Json<TelemetrySubscription>, ApiError> {
+    validate_subscription(&state, Some(id), &req).await?;
+    let subscription = state.store.update_telemetry_subscription(id, &req).await?;
+    state.gnmi.reload().await;
+    Ok(Json(subscription))
+}
+
+/// Delete a telemetry subscription and its samples
+pub async fn delete_telemetry_subscription(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_telemetry_subscription(id).await?;
+    state.gnmi.reload().await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the open telemetry streams and their state
+pub async fn list_telemetry_streams(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Json<Vec<TelemetryStream>> {
+    Json(state.gnmi.streams().await)
+}
+
+/// List stored telemetry samples, newest first
+pub async fn list_telemetry_samples(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Query(mut query): Query<TelemetrySampleQuery>,
+) -> Result<Json<Vec<TelemetrySample>>, ApiError> {
+    query.limit = query.limit.clamp(1, MAX_SAMPLE_LIMIT);
+    Ok(Json(state.store.list_telemetry_samples(&query).await?))
+}
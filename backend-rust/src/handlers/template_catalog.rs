@@ -0,0 +1,228 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::template_catalog::{self, CatalogEntry};
+use crate::AppState;
+
+use super::templates::record_template_change;
+use super::{trigger_reload, ApiError};
+
+/// The configured catalog URL and branch
+async fn catalog_source(state: &AppState) -> Result<(String, Option<String>), ApiError> {
+    let settings = state.store.get_settings().await?;
+    let url = settings
+        .template_catalog_url
+        .filter(|u| !u.trim().is_empty())
+        .ok_or_else(|| ApiError::bad_request("template_catalog_url is not set in settings"))?;
+    let branch = settings.template_catalog_branch.filter(|b| !b.trim().is_empty());
+
+    let url_ok = ["https://", "http://", "ssh://", "git://", "file://"].iter().any(|s| url.starts_with(s))
+        || (url.contains('@') && url.contains(':') && !url.starts_with('-'));
+    if !url_ok {
+        return Err(ApiError::bad_request("template_catalog_url must be an http(s), ssh, git or file URL"));
+    }
+    if branch.as_deref().is_some_and(|b| b.starts_with('-')) {
+        return Err(ApiError::bad_request("invalid template_catalog_branch"));
+    }
+    Ok((url, branch))
+}
+
+/// Catalog entries with their local status
+async fn catalog_listing(
+    state: &AppState,
+    url: String,
+    branch: Option<String>,
+    commit: String,
+    entries: Vec<CatalogEntry>,
+) -> Result<TemplateCatalog, ApiError> {
+    let templates = state.store.list_templates().await?;
+    let provenance: HashMap<String, TemplateProvenance> = state
+        .store
+        .list_template_provenance()
+        .await?
+        .into_iter()
+        .filter(|p| p.source_url == url)
+        .map(|p| (p.source_path.clone(), p))
+        .collect();
+    let mut vendors: HashMap<String, Option<i64>> = HashMap::new();
+
+    let mut listed = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let vendor_id = match &entry.vendor_dir {
+            Some(dir) => match vendors.get(dir) {
+                Some(id) => *id,
+                None => {
+                    let id = state.store.resolve_vendor(dir).await?.map(|v| v.id);
+                    vendors.insert(dir.clone(), id);
+                    id
+                }
+            },
+            None => None,
+        };
+
+        let installed = provenance
+            .get(&entry.path)
+            .and_then(|p| templates.iter().find(|t| t.id == p.template_id).map(|t| (p, t)));
+        let (status, template_id, installed_commit) = match installed {
+            Some((p, template)) => {
+                let status = if template.content != p.content {
+                    catalog_template_status::LOCALLY_MODIFIED
+                } else if entry.content != p.content {
+                    catalog_template_status::UPDATE_AVAILABLE
+                } else {
+                    catalog_template_status::INSTALLED
+                };
+                (status, Some(template.id), Some(p.commit_sha.clone()))
+            }
+            None => match templates.iter().find(|t| t.name == entry.name) {
+                Some(template) => (catalog_template_status::CONFLICT, Some(template.id), None),
+                None => (catalog_template_status::AVAILABLE, None, None),
+            },
+        };
+
+        listed.push(CatalogTemplate {
+            path: entry.path,
+            name: entry.name,
+            description: entry.description,
+            vendor_id,
+            status: status.to_string(),
+            template_id,
+            installed_commit,
+            content: entry.content,
+        });
+    }
+
+    Ok(TemplateCatalog { url, branch, commit, templates: listed })
+}
+
+/// Browse the synced catalog. Doesn't contact the remote; sync first to pick up changes.
+pub async fn get_template_catalog(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<TemplateCatalog>, ApiError> {
+    let (url, branch) = catalog_source(&state).await?;
+    let commit = template_catalog::head_commit(&state.config.templates_dir)
+        .await?
+        .ok_or_else(|| ApiError::bad_request("template catalog has not been synced"))?;
+    let entries = template_catalog::scan(&state.config.templates_dir)?;
+    Ok(Json(catalog_listing(&state, url, branch, commit, entries).await?))
+}
+
+/// Fetch the latest catalog from the configured Git repository and list it
+pub async fn sync_template_catalog(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<TemplateCatalog>, ApiError> {
+    let (url, branch) = catalog_source(&state).await?;
+    let commit = template_catalog::sync(&state.config.templates_dir, &url, branch.as_deref())
+        .await
+        .map_err(|e| ApiError::bad_request(format!("catalog sync failed: {}", e)))?;
+    let entries = template_catalog::scan(&state.config.templates_dir)?;
+    Ok(Json(catalog_listing(&state, url, branch, commit, entries).await?))
+}
+
+/// Install or update catalog templates, recording their provenance
+pub async fn install_catalog_templates(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TemplateCatalogInstallRequest>,
+) -> Result<Json<Vec<CatalogInstallResult>>, ApiError> {
+    if req.paths.is_empty() {
+        return Err(ApiError::bad_request("no catalog templates selected"));
+    }
+    let (url, branch) = catalog_source(&state).await?;
+    let commit = template_catalog::head_commit(&state.config.templates_dir)
+        .await?
+        .ok_or_else(|| ApiError::bad_request("template catalog has not been synced"))?;
+    let entries = template_catalog::scan(&state.config.templates_dir)?;
+    let catalog = catalog_listing(&state, url.clone(), branch, commit.clone(), entries).await?;
+
+    let mut results = Vec::with_capacity(req.paths.len());
+    let mut changed = false;
+    for path in &req.paths {
+        let Some(entry) = catalog.templates.iter().find(|t| &t.path == path) else {
+            return Err(ApiError::not_found("catalog template"));
+        };
+        let skip = |message: &str| CatalogInstallResult {
+            path: path.clone(),
+            action: catalog_install_action::SKIPPED.to_string(),
+            template_id: entry.template_id,
+            message: Some(message.to_string()),
+        };
+
+        let action = match (entry.status.as_str(), entry.template_id) {
+            (catalog_template_status::INSTALLED, _) => catalog_install_action::UNCHANGED,
+            (catalog_template_status::LOCALLY_MODIFIED, _) if !req.force => {
+                results.push(skip("template was modified locally; use force to overwrite"));
+                continue;
+            }
+            (catalog_template_status::CONFLICT, _) if !req.force => {
+                results.push(skip("a local template with this name exists; use force to overwrite"));
+                continue;
+            }
+            (_, Some(_)) => catalog_install_action::UPDATED,
+            (_, None) => catalog_install_action::CREATED,
+        };
+
+        let template_req = CreateTemplateRequest {
+            name: entry.name.clone(),
+            description: Some(entry.description.clone()),
+            vendor_id: entry.vendor_id,
+            content: entry.content.clone(),
+        };
+        let template = match (action, entry.template_id) {
+            (catalog_install_action::CREATED, _) => {
+                let template = state.store.create_template(&template_req).await?;
+                record_template_change(&state, &auth, change_action::CREATE, &template).await;
+                changed = true;
+                template
+            }
+            (catalog_install_action::UPDATED, Some(id)) => {
+                let template = state.store.update_template(id, &template_req).await?;
+                record_template_change(&state, &auth, change_action::UPDATE, &template).await;
+                changed = true;
+                template
+            }
+            (_, id) => state
+                .store
+                .get_template(id.unwrap_or_default())
+                .await?
+                .ok_or_else(|| ApiError::not_found("template"))?,
+        };
+        state
+            .store
+            .record_template_provenance(template.id, &url, path, &commit, &entry.content, &auth.claims.username)
+            .await?;
+
+        results.push(CatalogInstallResult {
+            path: path.clone(),
+            action: action.to_string(),
+            template_id: Some(template.id),
+            message: None,
+        });
+    }
+
+    if changed {
+        trigger_reload(&state).await;
+    }
+    Ok(Json(results))
+}
+
+/// Where a template was installed from, if it came from the catalog
+pub async fn get_template_provenance(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<TemplateProvenance>, ApiError> {
+    state
+        .store
+        .get_template_provenance(id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("template provenance"))
+}
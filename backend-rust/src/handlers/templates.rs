@@ -1,21 +1,68 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
-use tera::{Context, Tera};
+use tera::Context;
 
 use crate::models::*;
 use crate::AppState;
 
-use super::{created, trigger_reload, ApiError};
+use super::tags::{retain_tagged, TagFilterQuery};
+use super::{created, record_change, trigger_reload, ApiError, ValidJson};
+
+pub(super) fn template_change(action: &'static str, template: &Template) -> NewChangeLogEntry {
+    NewChangeLogEntry {
+        category: change_category::TEMPLATE,
+        action,
+        resource_type: "template",
+        resource_id: template.id.to_string(),
+        resource_name: template.name.clone(),
+        summary: format!("template {}d", action),
+        ..Default::default()
+    }
+}
+
+/// Record a template change in the change log and, when Git history is enabled, commit it
+pub(super) async fn record_template_change(
+    state: &Arc<AppState>,
+    auth: &crate::auth::AuthUser,
+    action: &'static str,
+    template: &Template,
+) {
+    record_change(state, auth, template_change(action, template)).await;
+    let Some(history) = &state.config_history else {
+        return;
+    };
+    let author = &auth.claims.username;
+    let result = if action == change_action::DELETE {
+        history.remove_template(template, author).await
+    } else {
+        let verb = if action == change_action::CREATE { "Create" } else { "Update" };
+        history.record_template(template, author, &format!("{} template {}", verb, template.name)).await
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to commit template {} to history: {}", template.name, e);
+    }
+}
+
+fn config_history(state: &AppState) -> Result<&crate::services::config_history::ConfigHistory, ApiError> {
+    state
+        .config_history
+        .as_deref()
+        .ok_or_else(|| ApiError::bad_request("Git history is not enabled (set GIT_HISTORY_DIR)"))
+}
 
 /// List all templates
 pub async fn list_templates(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
+    Query(tag): Query<TagFilterQuery>,
 ) -> Result<Json<Vec<Template>>, ApiError> {
     let templates = state.store.list_templates().await?;
+    let templates = retain_tagged(&state, tag_resource::TEMPLATE, tag.tag.as_deref(), templates, |t| t.id.to_string()).await?;
     Ok(Json(templates))
 }
 
@@ -35,38 +82,96 @@ pub async fn get_template(
 
 /// Create a new template
 pub async fn create_template(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateTemplateRequest>,
+    ValidJson(req): ValidJson<CreateTemplateRequest>,
 ) -> Result<(axum::http::StatusCode, Json<Template>), ApiError> {
-    if req.name.is_empty() || req.content.is_empty() {
-        return Err(ApiError::bad_request("name and content are required"));
-    }
-
     let template = state.store.create_template(&req).await?;
+    record_template_change(&state, &auth, change_action::CREATE, &template).await;
     trigger_reload(&state).await;
     Ok(created(template))
 }
 
 /// Update an existing template
 pub async fn update_template(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateTemplateRequest>,
+) -> Result<Json<Template>, ApiError> {
+    super::teams::require_modify(&state, &auth, team_resource_type::TEMPLATE, id).await?;
+    let template = state.store.update_template(id, &req).await?;
+    record_template_change(&state, &auth, change_action::UPDATE, &template).await;
+    trigger_reload(&state).await;
+    Ok(Json(template))
+}
+
+/// List the commits that changed a template in the Git history, newest first
+pub async fn get_template_history(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateTemplateRequest>,
+) -> Result<Json<Vec<TemplateRevision>>, ApiError> {
+    let history = config_history(&state)?;
+    state
+        .store
+        .get_template(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("template"))?;
+    Ok(Json(history.template_history(id).await?))
+}
+
+/// Restore a template's content from a commit in its Git history. The revert is itself a new
+/// commit, so it can be undone the same way.
+pub async fn revert_template(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, commit)): Path<(i64, String)>,
 ) -> Result<Json<Template>, ApiError> {
+    let history = config_history(&state)?;
+    super::teams::require_modify(&state, &auth, team_resource_type::TEMPLATE, id).await?;
+    let template = state
+        .store
+        .get_template(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("template"))?;
+    let content = history
+        .template_at(id, &commit)
+        .await
+        .map_err(|e| ApiError::bad_request(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found(&format!("template {} at commit {}", id, commit)))?;
+
+    let req = CreateTemplateRequest {
+        name: template.name,
+        description: template.description,
+        vendor_id: template.vendor_id,
+        content,
+    };
     let template = state.store.update_template(id, &req).await?;
+    record_change(&state, &auth, template_change(change_action::UPDATE, &template)).await;
+    let message = format!("Revert template {} to {}", template.name, &commit[..commit.len().min(12)]);
+    if let Err(e) = history.record_template(&template, &auth.claims.username, &message).await {
+        tracing::warn!("Failed to commit template {} to history: {}", template.name, e);
+    }
     trigger_reload(&state).await;
     Ok(Json(template))
 }
 
 /// Delete a template
 pub async fn delete_template(
-    _auth: crate::auth::AuthUser,
+    auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
+    Query(query): Query<DeleteQuery>,
 ) -> Result<axum::http::StatusCode, ApiError> {
+    super::teams::require_modify(&state, &auth, team_resource_type::TEMPLATE, id).await?;
+    let refs = super::references::template_references(&state, id).await?;
+    super::references::ensure_deletable(&refs, &query)?;
+    let template = state.store.get_template(id).await?;
     state.store.delete_template(id).await?;
+    if let Some(template) = template {
+        record_template_change(&state, &auth, change_action::DELETE, &template).await;
+    }
     trigger_reload(&state).await;
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
@@ -89,24 +194,25 @@ pub async fn preview_template(
     let tera_content = convert_go_template_to_tera(&template.content);
 
     // Create a Tera instance and add the template
-    let mut tera = Tera::default();
+    let mut tera = crate::utils::template_engine();
     tera.add_raw_template("preview", &tera_content)
         .map_err(|e| ApiError::bad_request(format!("Invalid template: {}", e)))?;
 
-    // Look up role-specific template for {% include "role" %} support
-    // Role templates follow naming: "Arista EOS Spine" for base "Arista EOS Default" + role "spine"
-    let role = req.device.topology_role.as_deref().unwrap_or("");
-    if !role.is_empty() {
-        let capitalized_role = format!("{}{}", &role[..1].to_uppercase(), &role[1..]);
-        let role_name = if template.name.ends_with(" Default") {
-            format!("{} {}", template.name.trim_end_matches(" Default"), capitalized_role)
-        } else {
-            format!("{} {}", template.name, capitalized_role)
-        };
-        if let Ok(Some(role_tmpl)) = state.store.get_template_by_name(&role_name).await {
+    // Role template layers for {% include "role" %} support, from the device role's template mapping
+    let role_templates = state
+        .store
+        .resolve_role_templates(&template, req.device.vendor.as_deref(), req.device.topology_role.as_deref())
+        .await?;
+    if !role_templates.is_empty() {
+        let mut role_layers = Vec::new();
+        for role_tmpl in &role_templates {
             let role_content = convert_go_template_to_tera(&role_tmpl.content);
-            let _ = tera.add_raw_template("role", &role_content);
+            if role_tmpl.name != "preview" && role_tmpl.name != "role" {
+                let _ = tera.add_raw_template(&role_tmpl.name, &role_content);
+            }
+            role_layers.push(role_content);
         }
+        let _ = tera.add_raw_template("role", &role_layers.join("\n"));
     }
     // Ensure "role" always exists so {% include "role" %} doesn't fail
     if tera.get_template("role").is_err() {
@@ -127,13 +233,19 @@ pub async fn preview_template(
     context.insert("Subnet", &req.subnet);
     context.insert("Gateway", &req.gateway);
 
-    // Load resolved variables (group + host inheritance) as {{vars.KeyName}}
-    let vars_map = if let Ok(device_id) = req.device.id.parse::<i64>() {
-        state.store.resolve_device_variables_flat(device_id).await.unwrap_or_default()
+    // Load resolved variables (group + host inheritance) as {{vars.KeyName}} and custom fields
+    // as {{custom_fields.name}}
+    let (vars_map, custom_fields) = if let Ok(device_id) = req.device.id.parse::<i64>() {
+        let custom_fields = match state.store.get_device(device_id).await? {
+            Some(device) => device.custom_fields,
+            None => BTreeMap::new(),
+        };
+        (state.store.resolve_device_variables_flat(device_id).await.unwrap_or_default(), custom_fields)
     } else {
-        std::collections::HashMap::new()
+        (HashMap::new(), BTreeMap::new())
     };
     context.insert("vars", &vars_map);
+    context.insert("custom_fields", &custom_fields);
 
     // Render the template
     let rendered = tera
@@ -143,6 +255,81 @@ pub async fn preview_template(
     Ok(Json(TemplatePreviewResponse { output: rendered }))
 }
 
+/// Check template content without saving it: Tera syntax errors with their line, the variables
+/// it reads, and those neither in the render context nor set as a variable or custom field
+pub async fn validate_template(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TemplateValidationRequest>,
+) -> Result<Json<TemplateValidation>, ApiError> {
+    let tera_content = convert_go_template_to_tera(&req.content);
+    let mut tera = crate::utils::template_engine();
+    if let Err(e) = tera.add_raw_template("template", &tera_content) {
+        // The parser's position is in the source error, not the top-level "Failed to parse"
+        let mut message = e.to_string();
+        let mut source = std::error::Error::source(&e);
+        while let Some(inner) = source {
+            message = format!("{}\n{}", message, inner);
+            source = inner.source();
+        }
+        let location = crate::utils::tera_error_location(&message);
+        return Ok(Json(TemplateValidation {
+            valid: false,
+            errors: vec![TemplateIssue {
+                line: location.map(|(line, _)| line),
+                column: location.map(|(_, column)| column),
+                message,
+            }],
+            variables: Vec::new(),
+            warnings: Vec::new(),
+        }));
+    }
+
+    let var_keys: std::collections::HashSet<String> = match req.device_id {
+        Some(device_id) => {
+            state
+                .store
+                .get_device(device_id)
+                .await?
+                .ok_or_else(|| ApiError::not_found("device"))?;
+            state.store.resolve_device_variables_flat(device_id).await?.into_keys().collect()
+        }
+        None => state.store.list_all_variable_keys().await?,
+    };
+    let custom_fields: std::collections::HashSet<String> =
+        state.store.list_custom_fields().await?.into_iter().map(|f| f.name).collect();
+    let standard = match sample_render_context().into_json() {
+        serde_json::Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+
+    let mut references = crate::utils::template_references(&tera_content);
+    let mut warnings = Vec::new();
+    for reference in &references {
+        let message = match reference.path.split_once('.') {
+            Some(("vars", key)) if !var_keys.contains(key) => match req.device_id {
+                Some(device_id) => format!("'{}' is not set for device {}", reference.path, device_id),
+                None => format!("'{}' is not set on any device, group, tenant or GPU cluster", reference.path),
+            },
+            Some(("custom_fields", name)) if !custom_fields.contains(name) => {
+                format!("'{}' is not a defined custom field", reference.path)
+            }
+            Some(_) => continue,
+            None if standard.contains_key(&reference.path) => continue,
+            None => format!("'{}' is not in the render context", reference.path),
+        };
+        warnings.push(TemplateIssue { line: Some(reference.line), column: None, message });
+    }
+    references.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(Json(TemplateValidation {
+        valid: true,
+        errors: Vec::new(),
+        variables: references.into_iter().map(|r| r.path).collect(),
+        warnings,
+    }))
+}
+
 /// Get available template variables
 pub async fn get_template_variables(
     _auth: crate::auth::AuthUser,
@@ -159,11 +346,339 @@ pub async fn get_template_variables(
         TemplateVariable { name: "SSHPass".into(), description: "SSH password (if set)".into(), example: "password".into() },
         TemplateVariable { name: "TopologyId".into(), description: "CLOS topology ID".into(), example: "dc1-fabric".into() },
         TemplateVariable { name: "TopologyRole".into(), description: "CLOS role: super-spine, spine, or leaf".into(), example: "leaf".into() },
-        TemplateVariable { name: r#"{% include "role" %}"#.into(), description: "Include role-specific template (e.g., arista-eos-spine)".into(), example: r#"{% include "role" %}"#.into() },
+        TemplateVariable { name: r#"{% include "role" %}"#.into(), description: "Include all templates mapped to the device's role, in order; a single layer can be included by its template name".into(), example: r#"{% include "role" %}"#.into() },
         TemplateVariable { name: "vars.*".into(), description: "Device-specific key-value variables".into(), example: "{{vars.Loopback}}".into() },
+        TemplateVariable { name: "custom_fields.*".into(), description: "Device custom field values by field name".into(), example: "{{custom_fields.asset_tag}}".into() },
     ]))
 }
 
+/// Render context of a sample leaf with one VRF port, covering every variable a device render gets
+fn sample_render_context() -> Context {
+    let now = chrono::Utc::now();
+    let device = Device {
+        id: 1,
+        mac: Some("02:42:ac:1e:00:99".into()),
+        ip: "172.30.0.99".into(),
+        hostname: "leaf-01".into(),
+        vendor: Some("arista".into()),
+        vendor_id: None,
+        model: Some("7050SX3-48YC8".into()),
+        serial_number: Some("SN12345".into()),
+        config_template: String::new(),
+        credential_id: None,
+        ssh_user: None,
+        ssh_pass: None,
+        topology_id: Some(1),
+        topology_role: Some("leaf".into()),
+        hall_id: None,
+        row_id: None,
+        rack_id: None,
+        rack_position: None,
+        tenant_id: None,
+        gpu_cluster_id: None,
+        status: "online".into(),
+        device_type: "internal".into(),
+        last_seen: None,
+        last_backup: None,
+        last_error: None,
+        custom_fields: BTreeMap::from([("asset_tag".to_string(), serde_json::json!("AT-0042"))]),
+        created_at: now,
+        updated_at: now,
+    };
+    let settings = Settings {
+        dhcp_subnet: "255.255.255.0".into(),
+        dhcp_gateway: "172.30.0.1".into(),
+        ..Settings::default()
+    };
+    let vars = HashMap::from([("Loopback".to_string(), "10.255.0.1".to_string())]);
+    let port_assignments = vec![PortAssignment {
+        id: 1,
+        device_id: 1,
+        port_name: "Ethernet1".into(),
+        remote_device_id: Some(2),
+        remote_port_name: "Ethernet49".into(),
+        description: Some("uplink to spine-01".into()),
+        patch_panel_a_id: None,
+        patch_panel_a_port: None,
+        patch_panel_b_id: None,
+        patch_panel_b_port: None,
+        remote_device_hostname: Some("spine-01".into()),
+        remote_device_type: Some("internal".into()),
+        patch_panel_a_hostname: None,
+        patch_panel_b_hostname: None,
+        vrf_id: Some(10),
+        vrf_name: Some("TENANT-A".into()),
+        cable_length_meters: None,
+        created_at: now,
+        updated_at: now,
+    }];
+
+    crate::jobs::build_render_context(&device, &settings, &vars, Some(&port_assignments))
+}
+
+/// Describe every variable, include, and filter available to templates, built from a sample render context
+pub async fn get_template_context_schema(
+    _auth: crate::auth::AuthUser,
+) -> Result<Json<TemplateContextSchema>, ApiError> {
+    let context = sample_render_context();
+    let variables = match context.clone().into_json() {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(name, value)| describe_context_field(name, name, value))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let includes = vec![
+        ContextSchemaInclude {
+            name: "role".into(),
+            description: "All templates mapped to the device's role, rendered as layers in sort order".into(),
+            example: r#"{% include "role" %}"#.into(),
+        },
+        ContextSchemaInclude {
+            name: "<template name>".into(),
+            description: "A single role layer, included by its template name".into(),
+            example: r#"{% include "arista-leaf-bgp" %}"#.into(),
+        },
+    ];
+
+    let mut tera = crate::utils::template_engine();
+    let filters = CONTEXT_SCHEMA_FILTERS
+        .iter()
+        .map(|(name, description, example)| ContextSchemaFilter {
+            name: name.to_string(),
+            description: description.to_string(),
+            example: example.to_string(),
+            output: tera.render_str(example, &context).unwrap_or_else(|e| format!("error: {}", e)),
+        })
+        .collect();
+
+    Ok(Json(TemplateContextSchema { variables, includes, filters }))
+}
+
+/// Tera filters most useful in device configs: (name, description, example)
+const CONTEXT_SCHEMA_FILTERS: &[(&str, &str, &str)] = &[
+    ("upper", "Uppercase a string", "{{ Hostname | upper }}"),
+    ("lower", "Lowercase a string", "{{ Vendor | lower }}"),
+    ("capitalize", "Uppercase the first character, lowercase the rest", "{{ TopologyRole | capitalize }}"),
+    ("title", "Capitalize each word", "{{ TopologyRole | title }}"),
+    ("trim", "Strip leading and trailing whitespace", "{{ Hostname | trim }}"),
+    ("replace", "Replace every occurrence of a substring", r#"{{ Hostname | replace(from="-", to="_") }}"#),
+    ("split", "Split a string into an array", r#"{{ IP | split(pat=".") | last }}"#),
+    ("truncate", "Shorten a string to a number of characters", "{{ Hostname | truncate(length=4) }}"),
+    ("default", "Fallback when a variable is undefined", r#"{{ vars.Missing | default(value="none") }}"#),
+    ("length", "Number of elements in an array or characters in a string", "{{ VRFs | length }}"),
+    ("first", "First element of an array", r#"{% set vrf = VRFs | first %}{{ vrf.name }}"#),
+    ("last", "Last element of an array", r#"{% set vrf = VRFs | last %}{{ vrf.name }}"#),
+    ("join", "Join an array of strings", r#"{{ VRFs | map(attribute="name") | join(sep=",") }}"#),
+    ("map", "Extract one attribute from each element of an array", r#"{{ VRFs | map(attribute="id") | join(sep=" ") }}"#),
+    ("sort", "Sort an array, optionally by attribute", r#"{{ VRFs | sort(attribute="name") | map(attribute="name") | join(sep=",") }}"#),
+    ("filter", "Keep array elements whose attribute equals a value", r#"{{ VRFs | filter(attribute="name", value="TENANT-A") | length }}"#),
+    ("int", "Convert a string to an integer", r#"{{ "65001" | int }}"#),
+    ("json_encode", "Serialize a value as JSON", "{{ VRFs | json_encode() | safe }}"),
+    ("safe", "Mark a value as safe so it is not HTML-escaped", "{{ vars.Loopback | safe }}"),
+    ("ipaddr", "Part of an address or prefix (query: address, network, netmask, wildcard, prefix, broadcast, cidr, peer), or with index=N its Nth address", r#"{{ "10.0.0.0/31" | ipaddr(query="peer") }}"#),
+    ("ipmath", "Move an address by n", r#"{{ vars.Loopback | ipmath(n=100) }}"#),
+    ("prefix_to_mask", "Dotted netmask of a prefix length", "{{ 24 | prefix_to_mask }}"),
+    ("hwaddr", "Format a MAC address: linux, unix, cisco, eui48, bare or pgsql", r#"{{ MAC | hwaddr(format="cisco") }}"#),
+    ("vlan_expand", "VLAN IDs of a list with ranges", r#"{{ "10,20-22" | vlan_expand | join(sep=" ") }}"#),
+    ("vlan_ranges", "Compact VLAN IDs into ranges", "{{ [10, 20, 21, 22] | vlan_ranges }}"),
+    ("b64encode", "Base64-encode a string", "{{ Hostname | b64encode }}"),
+    ("b64decode", "Decode a base64 string", r#"{{ "bGVhZi0wMQ==" | b64decode }}"#),
+];
+
+/// Build a schema entry for a render context value, recursing into objects and array elements
+fn describe_context_field(name: &str, path: &str, value: &serde_json::Value) -> ContextSchemaField {
+    let (kind, fields) = match value {
+        serde_json::Value::String(_) => ("string", Vec::new()),
+        serde_json::Value::Number(n) if n.is_i64() || n.is_u64() => ("integer", Vec::new()),
+        serde_json::Value::Number(_) => ("number", Vec::new()),
+        serde_json::Value::Bool(_) => ("boolean", Vec::new()),
+        serde_json::Value::Null => ("null", Vec::new()),
+        // vars and custom_fields are free-form maps, so describe their entries generically
+        serde_json::Value::Object(map) if path == "vars" || path == "custom_fields" => {
+            let example = map.values().next().cloned().unwrap_or_default();
+            ("object", vec![describe_context_field("*", &format!("{}.*", path), &example)])
+        }
+        serde_json::Value::Object(map) => (
+            "object",
+            map.iter()
+                .map(|(k, v)| describe_context_field(k, &format!("{}.{}", path, k), v))
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => match items.first() {
+            Some(serde_json::Value::Object(map)) => (
+                "array",
+                map.iter()
+                    .map(|(k, v)| describe_context_field(k, &format!("{}[].{}", path, k), v))
+                    .collect(),
+            ),
+            _ => ("array", Vec::new()),
+        },
+    };
+
+    ContextSchemaField {
+        name: name.to_string(),
+        path: path.to_string(),
+        kind: kind.to_string(),
+        description: context_field_description(path).to_string(),
+        example: match value {
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) if !fields.is_empty() => serde_json::Value::Null,
+            _ => value.clone(),
+        },
+        fields,
+    }
+}
+
+fn context_field_description(path: &str) -> &'static str {
+    match path {
+        "Hostname" => "Device hostname",
+        "MAC" => "Device MAC address (empty if unknown)",
+        "IP" => "Device management IP address",
+        "Vendor" => "Device vendor (empty if unset)",
+        "Model" => "Device model (empty if unset)",
+        "SerialNumber" => "Device serial number (empty if unset)",
+        "TopologyId" => "ID of the device's topology (0 if unassigned)",
+        "TopologyRole" => "Topology role: super-spine, spine, or leaf (empty if unassigned)",
+        "Subnet" => "DHCP subnet mask from settings",
+        "Gateway" => "DHCP default gateway from settings",
+        "vars" => "Resolved device variables (group inheritance, then host overrides)",
+        "vars.*" => "A device variable by key, e.g. {{vars.Loopback}}",
+        "custom_fields" => "The device's custom field values; integers and booleans keep their type",
+        "custom_fields.*" => "A custom field value by field name, e.g. {{custom_fields.asset_tag}}",
+        "VRFs" => "VRFs with at least one of the device's port assignments",
+        "VRFs[].id" => "VRF ID",
+        "VRFs[].name" => "VRF name",
+        "VRFs[].interfaces" => "Port assignments in this VRF",
+        "VRFs[].interfaces[].port_name" => "Local port name",
+        "VRFs[].interfaces[].remote_device" => "Hostname of the connected device (empty if none)",
+        "VRFs[].interfaces[].remote_port" => "Port name on the connected device",
+        "VRFs[].interfaces[].description" => "Port assignment description",
+        _ => "",
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TemplateUsageQuery {
+    /// Render each affected device and diff it against its latest backup
+    #[serde(default)]
+    pub preview: bool,
+}
+
+/// List the devices that use a template directly, via vendor default, or as a role template
+pub async fn get_template_usage(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(query): Query<TemplateUsageQuery>,
+) -> Result<Json<TemplateUsage>, ApiError> {
+    let template = state
+        .store
+        .get_template(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("template"))?;
+
+    let templates: HashMap<i64, Template> = state
+        .store
+        .list_templates()
+        .await?
+        .into_iter()
+        .map(|t| (t.id, t))
+        .collect();
+    let vendors = state.store.list_vendors().await?;
+    let devices = state.store.list_devices().await?;
+
+    let vendor_names: Vec<String> = vendors
+        .iter()
+        .filter(|v| v.default_template == id.to_string())
+        .map(|v| v.name.clone())
+        .collect();
+
+    let mut usage = Vec::new();
+    for device in devices {
+        let (base_id, base_via) = if !device.config_template.is_empty() {
+            match device.config_template.parse::<i64>() {
+                Ok(tid) => (tid, template_usage_via::DIRECT),
+                Err(_) => continue,
+            }
+        } else {
+            let vendor = vendors.iter().find(|v| {
+                device.vendor_id.as_deref() == Some(v.id.to_string().as_str())
+                    || device.vendor.as_deref() == Some(v.name.as_str())
+            });
+            match vendor.and_then(|v| v.default_template.parse::<i64>().ok()) {
+                Some(tid) => (tid, template_usage_via::VENDOR_DEFAULT),
+                None => continue,
+            }
+        };
+
+        let base = match templates.get(&base_id) {
+            Some(b) => b,
+            None => continue,
+        };
+        let role_templates = state
+            .store
+            .resolve_role_templates(base, device.vendor.as_deref(), device.topology_role.as_deref())
+            .await?;
+
+        let via = if base_id == id {
+            base_via
+        } else if role_templates.iter().any(|t| t.id == id) {
+            template_usage_via::ROLE
+        } else {
+            continue;
+        };
+
+        let mut entry = TemplateUsageDevice {
+            device_id: device.id,
+            hostname: device.hostname.clone(),
+            via: via.to_string(),
+            base_template_id: base_id,
+            diff: None,
+            preview_error: None,
+        };
+        if query.preview {
+            match preview_usage_diff(&state, &device, base, &role_templates).await {
+                Ok(diff) => entry.diff = Some(diff),
+                Err(e) => entry.preview_error = Some(e.to_string()),
+            }
+        }
+        usage.push(entry);
+    }
+
+    Ok(Json(TemplateUsage {
+        template_id: template.id,
+        template_name: template.name,
+        vendors: vendor_names,
+        devices: usage,
+    }))
+}
+
+/// Render a device's config and diff it against its most recent backup
+async fn preview_usage_diff(
+    state: &AppState,
+    device: &Device,
+    base: &Template,
+    role_templates: &[Template],
+) -> anyhow::Result<String> {
+    let settings = state.store.get_settings().await?;
+    let vars = state.store.resolve_device_variables_flat(device.id).await.unwrap_or_default();
+    let port_assignments = state.store.list_port_assignments(device.id).await.unwrap_or_default();
+    let rendered = state.render_cache.render(device, base, &settings, role_templates, &vars, Some(&port_assignments))?;
+    let rendered = state.store.apply_device_config_snippets(device.id, rendered).await?;
+
+    let backup = state
+        .store
+        .list_backups(device.id)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Device has no backups to compare against"))?;
+    let current = crate::backup::read_backup(&state.config.backup_dir, &backup).await?;
+
+    Ok(crate::utils::unified_diff(&current, &rendered, &backup.filename, "rendered", 3))
+}
+
 /// Convert Go template syntax to Tera syntax (delegates to shared utility)
 fn convert_go_template_to_tera(content: &str) -> String {
     crate::utils::convert_go_template_to_tera(content)
@@ -7,7 +7,8 @@ use std::sync::Arc;
 use crate::models::*;
 use crate::AppState;
 
-use super::{created, ApiError};
+use super::groups::{SetMembersRequest, SetVariableRequest};
+use super::{created, record_change, ApiError, ValidJson};
 
 pub async fn list_tenants(
     _auth: crate::auth::AuthUser,
@@ -33,12 +34,8 @@ pub async fn get_tenant(
 pub async fn create_tenant(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateTenantRequest>,
+    ValidJson(req): ValidJson<CreateTenantRequest>,
 ) -> Result<(axum::http::StatusCode, Json<Tenant>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
-
     let tenant = state.store.create_tenant(&req).await?;
     Ok(created(tenant))
 }
@@ -47,7 +44,7 @@ pub async fn update_tenant(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateTenantRequest>,
+    ValidJson(req): ValidJson<CreateTenantRequest>,
 ) -> Result<Json<Tenant>, ApiError> {
     let tenant = state.store.update_tenant(id, &req).await?;
     Ok(Json(tenant))
@@ -61,3 +58,107 @@ pub async fn delete_tenant(
     state.store.delete_tenant(id).await?;
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
+
+// ========== Tenant Variables ==========
+
+/// Variables applied to every device assigned to the tenant
+pub async fn list_tenant_variables(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<TenantVariable>>, ApiError> {
+    Ok(Json(state.store.list_tenant_variables(id).await?))
+}
+
+pub async fn set_tenant_variable(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, key)): Path<(i64, String)>,
+    Json(req): Json<SetVariableRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tenant = state
+        .store
+        .get_tenant(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Tenant"))?;
+    state.store.set_tenant_variable(id, &key, &req.value).await?;
+    let summary = format!("set {} on tenant {}", key, tenant.name);
+    record_change(&state, &auth, NewChangeLogEntry::variable(change_action::UPDATE, &key, summary)).await;
+    Ok(Json(serde_json::json!({"message": "variable set"})))
+}
+
+pub async fn delete_tenant_variable(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, key)): Path<(i64, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tenant = state
+        .store
+        .get_tenant(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Tenant"))?;
+    state.store.delete_tenant_variable(id, &key).await?;
+    let summary = format!("deleted {} on tenant {}", key, tenant.name);
+    record_change(&state, &auth, NewChangeLogEntry::variable(change_action::DELETE, &key, summary)).await;
+    Ok(Json(serde_json::json!({"message": "variable deleted"})))
+}
+
+// ========== Device Assignment ==========
+
+/// Devices assigned to the tenant
+pub async fn list_tenant_devices(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Device>>, ApiError> {
+    if state.store.get_tenant(id).await?.is_none() {
+        return Err(ApiError::not_found("Tenant"));
+    }
+    let filter = DeviceFilter { tenant_id: Some(id), ..Default::default() };
+    Ok(Json(state.store.list_devices_filtered(&filter).await?))
+}
+
+/// Replace the tenant's devices; devices not listed are unassigned
+pub async fn set_tenant_devices(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<SetMembersRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if state.store.get_tenant(id).await?.is_none() {
+        return Err(ApiError::not_found("Tenant"));
+    }
+    state.store.set_tenant_devices(id, &req.device_ids).await?;
+    Ok(Json(serde_json::json!({"message": "devices updated"})))
+}
+
+/// Assign a device to the tenant, moving it from any other tenant
+pub async fn assign_tenant_device(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, device_id)): Path<(i64, i64)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if state.store.get_tenant(id).await?.is_none() {
+        return Err(ApiError::not_found("Tenant"));
+    }
+    state.store.assign_device_tenant(device_id, Some(id)).await?;
+    Ok(Json(serde_json::json!({"message": "device assigned to tenant"})))
+}
+
+/// Unassign a device, if it belongs to the tenant
+pub async fn unassign_tenant_device(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, device_id)): Path<(i64, i64)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let device = state
+        .store
+        .get_device(device_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("device"))?;
+    if device.tenant_id != Some(id) {
+        return Err(ApiError::bad_request("device is not assigned to this tenant"));
+    }
+    state.store.assign_device_tenant(device_id, None).await?;
+    Ok(Json(serde_json::json!({"message": "device removed from tenant"})))
+}
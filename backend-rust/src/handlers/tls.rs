@@ -0,0 +1,48 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::models::*;
+use crate::services::tls::TlsManager;
+use crate::AppState;
+
+use super::ApiError;
+
+fn tls_manager(state: &AppState) -> Result<&Arc<TlsManager>, ApiError> {
+    state.tls.as_ref().ok_or_else(|| ApiError::not_found("TLS is not enabled"))
+}
+
+/// The HTTPS listener and its certificate
+pub async fn get_tls_status(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<TlsStatus>, ApiError> {
+    Ok(Json(tls_manager(&state)?.status()))
+}
+
+/// Renew the ACME certificate now, or reload the certificate files
+pub async fn renew_tls_certificate(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<TlsStatus>, ApiError> {
+    let tls = tls_manager(&state)?;
+    tls.refresh(true)
+        .await
+        .map_err(|e| ApiError::bad_request(format!("certificate refresh failed: {:#}", e)))?;
+    Ok(Json(tls.status()))
+}
+
+/// Serve the key authorization for a pending ACME HTTP-01 challenge
+pub async fn acme_challenge(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Result<String, StatusCode> {
+    state
+        .tls
+        .as_ref()
+        .and_then(|tls| tls.http_challenge(&token))
+        .ok_or(StatusCode::NOT_FOUND)
+}
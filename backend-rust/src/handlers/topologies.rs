@@ -1,5 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
+    http::StatusCode,
     Json,
 };
 use std::sync::Arc;
@@ -7,7 +8,7 @@ use std::sync::Arc;
 use crate::models::*;
 use crate::AppState;
 
-use super::{created, ApiError};
+use super::{created, ApiError, ValidJson};
 
 /// List all topologies (with device count stats)
 pub async fn list_topologies(
@@ -36,12 +37,8 @@ pub async fn get_topology(
 pub async fn create_topology(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateTopologyRequest>,
+    ValidJson(req): ValidJson<CreateTopologyRequest>,
 ) -> Result<(axum::http::StatusCode, Json<Topology>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
-
     let topology = state.store.create_topology(&req).await?;
     Ok(created(topology))
 }
@@ -51,7 +48,7 @@ pub async fn update_topology(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateTopologyRequest>,
+    ValidJson(req): ValidJson<CreateTopologyRequest>,
 ) -> Result<Json<Topology>, ApiError> {
     let topology = state.store.update_topology(id, &req).await?;
     Ok(Json(topology))
@@ -68,5 +65,214 @@ pub async fn delete_topology(
         state.store.delete_devices_by_topology(id).await?;
     }
     state.store.delete_topology(id).await?;
+    super::documentation::prune_attachment_files(&state).await;
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
+
+/// Check custom deploy stages: non-empty, known roles, each role in at most one stage
+fn validate_deploy_stages(stages: &[Vec<String>]) -> Result<(), ApiError> {
+    if stages.is_empty() || stages.iter().any(|s| s.is_empty()) {
+        return Err(ApiError::bad_request("stages must be a non-empty list of non-empty role lists"));
+    }
+    let mut seen = std::collections::HashSet::new();
+    for role in stages.iter().flatten() {
+        if role.is_empty() || !topology_role::is_valid(role) {
+            return Err(ApiError::bad_request(format!(
+                "unknown topology role '{}': expected one of {}",
+                role,
+                topology_role::ALL.join(", ")
+            )));
+        }
+        if !seen.insert(role.as_str()) {
+            return Err(ApiError::bad_request(format!("role '{}' appears in more than one stage", role)));
+        }
+    }
+    Ok(())
+}
+
+/// Assemble the per-stage progress view of a topology deploy
+async fn deploy_progress(state: &AppState, deploy: TopologyDeploy) -> Result<TopologyDeployProgress, ApiError> {
+    use topology_deploy_stage_status as stage_status;
+
+    let devices = state.store.list_topology_deploy_devices(deploy.id).await?;
+    let verify = !deploy.verify_command.is_empty();
+    let is_status = |status: &Option<String>, expected: &str| status.as_deref() == Some(expected);
+    let succeeded_devices = devices
+        .iter()
+        .filter(|d| {
+            is_status(&d.deploy_status, job_status::COMPLETED)
+                && (!verify || is_status(&d.verify_status, job_status::COMPLETED))
+        })
+        .count();
+    let failed_devices = devices
+        .iter()
+        .filter(|d| is_status(&d.deploy_status, job_status::FAILED) || is_status(&d.verify_status, job_status::FAILED))
+        .count();
+
+    let stage_progress = deploy
+        .stages
+        .iter()
+        .enumerate()
+        .map(|(index, roles)| {
+            let index = index as i64;
+            let stage_devices: Vec<TopologyDeployDevice> =
+                devices.iter().filter(|d| d.stage == index).cloned().collect();
+            let status = if stage_devices.is_empty() {
+                stage_status::SKIPPED
+            } else {
+                match (index.cmp(&deploy.current_stage), deploy.status.as_str()) {
+                    (std::cmp::Ordering::Less, _) => stage_status::COMPLETED,
+                    (std::cmp::Ordering::Equal, topology_deploy_status::RUNNING) => stage_status::RUNNING,
+                    (std::cmp::Ordering::Equal, topology_deploy_status::FAILED) => stage_status::FAILED,
+                    (std::cmp::Ordering::Equal, _) => stage_status::COMPLETED,
+                    (std::cmp::Ordering::Greater, topology_deploy_status::RUNNING) => stage_status::PENDING,
+                    (std::cmp::Ordering::Greater, _) => stage_status::SKIPPED,
+                }
+            };
+            TopologyDeployStage {
+                index,
+                roles: roles.clone(),
+                status: status.to_string(),
+                devices: stage_devices,
+            }
+        })
+        .collect();
+
+    Ok(TopologyDeployProgress {
+        deploy,
+        total_devices: devices.len(),
+        succeeded_devices,
+        failed_devices,
+        stage_progress,
+    })
+}
+
+/// Deploy a whole topology in stages of roles (default: spines before leaves). Each stage's
+/// deploy jobs, and its verification jobs when a verify command is given, must all succeed
+/// before the next stage starts. Devices whose role isn't in a stage are not deployed.
+pub async fn deploy_topology(
+    auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    body: Option<Json<TopologyDeployRequest>>,
+) -> Result<(StatusCode, Json<TopologyDeployProgress>), ApiError> {
+    let req = body.map(|b| b.0).unwrap_or_default();
+    state
+        .store
+        .get_topology(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("topology"))?;
+    let job_service = state
+        .job_service
+        .clone()
+        .ok_or_else(|| ApiError::internal("job service is not running"))?;
+
+    let stages = match req.stages {
+        Some(stages) => {
+            validate_deploy_stages(&stages)?;
+            stages
+        }
+        None => default_deploy_stages(),
+    };
+    let verify_command = req.verify_command.trim().to_string();
+
+    if state
+        .store
+        .list_topology_deploys(id)
+        .await?
+        .iter()
+        .any(|d| d.status == topology_deploy_status::RUNNING)
+    {
+        return Err(ApiError::conflict("a deploy of this topology is already running"));
+    }
+    if req.lab && state.store.list_lab_nodes(id).await?.is_empty() {
+        return Err(ApiError::bad_request("topology has no virtual lab to deploy to"));
+    }
+
+    let filter = DeviceFilter { topology_id: Some(id), ..Default::default() };
+    let plan: Vec<(i64, i64)> = state
+        .store
+        .list_devices_filtered(&filter)
+        .await?
+        .iter()
+        .filter_map(|device| {
+            let role = device.topology_role.as_deref()?;
+            let stage = stages.iter().position(|s| s.iter().any(|r| r == role))?;
+            Some((device.id, stage as i64))
+        })
+        .collect();
+    if plan.is_empty() {
+        return Err(ApiError::bad_request("no devices in this topology have a role in the deploy stages"));
+    }
+
+    let deploy = state
+        .store
+        .create_topology_deploy(id, &stages, &verify_command, req.lab, &auth.claims.username, &plan)
+        .await?;
+    job_service.start_topology_deploy(deploy.id);
+
+    Ok((StatusCode::ACCEPTED, Json(deploy_progress(&state, deploy).await?)))
+}
+
+/// List a topology's deploys, newest first
+pub async fn list_topology_deploys(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<TopologyDeploy>>, ApiError> {
+    state
+        .store
+        .get_topology(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("topology"))?;
+    Ok(Json(state.store.list_topology_deploys(id).await?))
+}
+
+/// Progress of a topology deploy: its stages with each device's deploy and verification job status
+pub async fn get_topology_deploy(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, deploy_id)): Path<(i64, i64)>,
+) -> Result<Json<TopologyDeployProgress>, ApiError> {
+    let deploy = state
+        .store
+        .get_topology_deploy(deploy_id)
+        .await?
+        .filter(|d| d.topology_id == id)
+        .ok_or_else(|| ApiError::not_found("topology deploy"))?;
+    Ok(Json(deploy_progress(&state, deploy).await?))
+}
+
+/// Allocate a topology's underlay from an IPAM supernet: a loopback per spine and leaf, a /31
+/// per spine-leaf link and the ASNs, written as the device variables the CLOS templates use
+pub async fn generate_underlay(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<GenerateUnderlayRequest>,
+) -> Result<Json<UnderlayResult>, ApiError> {
+    let topology = state
+        .store
+        .get_topology(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("topology"))?;
+    let supernet = state
+        .store
+        .get_ipam_prefix(req.supernet_id)
+        .await?
+        .ok_or_else(|| ApiError::bad_request(format!("IPAM prefix {} not found", req.supernet_id)))?;
+
+    let filter = DeviceFilter { topology_id: Some(id), ..Default::default() };
+    let devices = state.store.list_devices_filtered(&filter).await?;
+    let count = |role: &str| devices.iter().filter(|d| d.topology_role.as_deref() == Some(role)).count();
+    let (spines, leaves) = (count(topology_role::SPINE), count(topology_role::LEAF));
+    if spines == 0 || leaves == 0 {
+        return Err(ApiError::bad_request("topology needs at least one spine and one leaf"));
+    }
+    if req.leaf_asn_base + leaves as i64 > u32::MAX as i64 {
+        return Err(ApiError::bad_request("leaf_asn_base leaves no room for an ASN per leaf"));
+    }
+
+    let result = crate::services::underlay::generate(&state.store, &topology, &supernet, devices, &req).await?;
+    Ok(Json(result))
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 use axum::{extract::{Path, State}, http::StatusCode, Json};
-use crate::{auth::AuthUser, models::*, handlers::{ApiError, created}, AppState};
+use crate::{auth::AuthUser, db::Store, models::*, handlers::{ApiError, ValidJson, created}, AppState};
 
 pub async fn list_users(
     _auth: AuthUser,
@@ -23,11 +23,8 @@ pub async fn get_user(
 pub async fn create_user(
     _auth: AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateUserRequest>,
+    ValidJson(req): ValidJson<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<User>), ApiError> {
-    if req.username.is_empty() || req.password.is_empty() {
-        return Err(ApiError::bad_request("username and password are required"));
-    }
     if state.store.get_user_by_username(&req.username).await?.is_some() {
         return Err(ApiError::conflict("A user with this username already exists"));
     }
@@ -39,11 +36,8 @@ pub async fn update_user(
     _auth: AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<UpdateUserRequest>,
+    ValidJson(req): ValidJson<UpdateUserRequest>,
 ) -> Result<Json<User>, ApiError> {
-    if req.username.is_empty() {
-        return Err(ApiError::bad_request("username is required"));
-    }
     // Check for username uniqueness (excluding self)
     if let Some(existing) = state.store.get_user_by_username(&req.username).await? {
         if existing.id != id {
@@ -66,3 +60,115 @@ pub async fn delete_user(
     state.store.delete_user(id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+const MAX_IMPERSONATION_MINUTES: i64 = 240;
+
+/// Impersonation needs an explicit grant: unrestricted users (no role or team grants) are
+/// refused, as are impersonation tokens themselves
+async fn authorize_impersonation(store: &Store, auth: &AuthUser) -> Result<(), ApiError> {
+    if auth.claims.impersonation.is_some() {
+        return Err(ApiError::forbidden("impersonation tokens can't impersonate other users"));
+    }
+    let user_id = auth.claims.sub.parse().unwrap_or_default();
+    let permissions = crate::services::permissions::UserPermissions::load(store, user_id, &auth.claims.username).await?;
+    if !permissions.grants(permission_resource::IMPERSONATE, permission_action::WRITE) {
+        return Err(ApiError::forbidden("impersonation requires an impersonate grant in the permissions matrix"));
+    }
+    Ok(())
+}
+
+/// Generate a short-lived token that acts as another user, for reproducing permission issues
+/// without their password. Only users granted `impersonate` in the permissions matrix can
+/// impersonate, and every token is recorded in the change log.
+pub async fn impersonate_user(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<ImpersonateRequest>,
+) -> Result<(StatusCode, Json<ImpersonationTokenResponse>), ApiError> {
+    authorize_impersonation(&state.store, &auth).await?;
+    if req.ttl_minutes < 1 || req.ttl_minutes > MAX_IMPERSONATION_MINUTES {
+        return Err(ApiError::bad_request(format!("ttl_minutes must be between 1 and {}", MAX_IMPERSONATION_MINUTES)));
+    }
+    if auth.claims.sub == id.to_string() {
+        return Err(ApiError::bad_request("Cannot impersonate your own account"));
+    }
+    let user = state.store.get_user(id).await?
+        .ok_or_else(|| ApiError::not_found("User"))?;
+    if !user.enabled {
+        return Err(ApiError::bad_request("Cannot impersonate a disabled user"));
+    }
+
+    let now = chrono::Utc::now();
+    let expires_at = now + chrono::TimeDelta::minutes(req.ttl_minutes);
+    let claims = Claims {
+        sub: user.id.to_string(),
+        username: user.username.clone(),
+        exp: expires_at.timestamp() as usize,
+        iat: now.timestamp() as usize,
+        impersonation: Some(Impersonation { by: auth.claims.username.clone(), read_only: req.read_only }),
+    };
+    let token = super::auth::encode_token(&state, &claims)?;
+
+    let mut summary = format!(
+        "Generated {} impersonation token for {} valid for {} minutes",
+        if req.read_only { "read-only" } else { "read-write" },
+        user.username,
+        req.ttl_minutes
+    );
+    if !req.reason.trim().is_empty() {
+        summary.push_str(&format!(": {}", req.reason.trim()));
+    }
+    super::record_change(&state, &auth, NewChangeLogEntry {
+        category: change_category::AUTH,
+        action: change_action::CREATE,
+        resource_type: "user",
+        resource_id: user.id.to_string(),
+        resource_name: user.username.clone(),
+        summary,
+        ..Default::default()
+    }).await;
+
+    Ok(created(ImpersonationTokenResponse {
+        token,
+        username: user.username,
+        impersonated_by: auth.claims.username,
+        read_only: req.read_only,
+        expires_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth_for(user: &User) -> AuthUser {
+        AuthUser {
+            claims: Claims { sub: user.id.to_string(), username: user.username.clone(), exp: 0, iat: 0, impersonation: None },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_impersonation_requires_grant() {
+        let path = std::env::temp_dir().join(format!("forge-impersonate-{}.db", uuid::Uuid::new_v4()));
+        let store = Store::new(&path.to_string_lossy()).await.expect("open store");
+        let new_user = |name: &str| CreateUserRequest { username: name.to_string(), password: "secret".to_string(), enabled: true };
+        let user = store.create_user_full(&new_user("operator")).await.expect("create user");
+        let lead = store.create_user_full(&new_user("lead")).await.expect("create lead");
+
+        // A default user, with no role or team grants, is unrestricted but can't impersonate
+        let Err(err) = authorize_impersonation(&store, &auth_for(&user)).await else { panic!("default user impersonated") };
+        assert_eq!(err.status, StatusCode::FORBIDDEN);
+
+        let role = store.create_role(&CreateRoleRequest { name: "admins".to_string(), description: String::new() }).await.expect("create role");
+        let grant = Permission { resource: permission_resource::IMPERSONATE.to_string(), action: permission_action::WRITE.to_string() };
+        store.set_permissions(permission_subject::ROLE, role.id, &[grant]).await.expect("grant");
+        store.set_role_members(role.id, &[lead.id]).await.expect("assign role");
+        assert!(authorize_impersonation(&store, &auth_for(&lead)).await.is_ok());
+
+        drop(store);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.to_string_lossy(), suffix));
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
@@ -9,7 +9,7 @@ use crate::db::get_default_vendors_models;
 use crate::models::*;
 use crate::AppState;
 
-use super::{created, ApiError};
+use super::{created, ApiError, ValidJson};
 
 /// List all vendors
 pub async fn list_vendors(
@@ -60,12 +60,8 @@ pub async fn get_vendor_by_name(
 pub async fn create_vendor(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateVendorRequest>,
+    ValidJson(req): ValidJson<CreateVendorRequest>,
 ) -> Result<(axum::http::StatusCode, Json<Vendor>), ApiError> {
-    if req.name.is_empty() {
-        return Err(ApiError::bad_request("name is required"));
-    }
-
     let vendor = state.store.create_vendor(&req).await?;
     Ok(created(vendor))
 }
@@ -75,7 +71,7 @@ pub async fn update_vendor(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateVendorRequest>,
+    ValidJson(req): ValidJson<CreateVendorRequest>,
 ) -> Result<Json<Vendor>, ApiError> {
     let vendor = state.store.update_vendor(id, &req).await?;
     Ok(Json(vendor))
@@ -86,7 +82,10 @@ pub async fn delete_vendor(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
+    Query(query): Query<DeleteQuery>,
 ) -> Result<axum::http::StatusCode, ApiError> {
+    let refs = super::references::vendor_references(&state, id).await?;
+    super::references::ensure_deletable(&refs, &query)?;
     state.store.delete_vendor(id).await?;
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
@@ -116,18 +115,8 @@ pub async fn list_vendor_actions_by_vendor(
 pub async fn create_vendor_action(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateVendorActionRequest>,
+    ValidJson(req): ValidJson<CreateVendorActionRequest>,
 ) -> Result<(axum::http::StatusCode, Json<VendorAction>), ApiError> {
-    if req.vendor_id == 0 || req.label.is_empty() {
-        return Err(ApiError::bad_request("vendor_id and label are required"));
-    }
-    if req.action_type == "webhook" {
-        if req.webhook_url.is_empty() {
-            return Err(ApiError::bad_request("webhook_url is required for webhook actions"));
-        }
-    } else if req.command.is_empty() {
-        return Err(ApiError::bad_request("command is required for SSH actions"));
-    }
     let action = state.store.create_vendor_action(&req).await?;
     Ok(created(action))
 }
@@ -137,7 +126,7 @@ pub async fn update_vendor_action(
     _auth: crate::auth::AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<CreateVendorActionRequest>,
+    ValidJson(req): ValidJson<CreateVendorActionRequest>,
 ) -> Result<Json<VendorAction>, ApiError> {
     let action = state.store.update_vendor_action(id, &req).await?;
     Ok(Json(action))
@@ -166,6 +155,7 @@ pub async fn run_vendor_action(
     if action.action_type != "webhook" {
         return Err(ApiError::bad_request("only webhook actions can run without a device"));
     }
+    super::jobs::require_queue_capacity(&state, 1)?;
 
     let job_id = uuid::Uuid::new_v4().to_string();
     let req = CreateJobRequest {
@@ -174,6 +164,7 @@ pub async fn run_vendor_action(
         command: action.id.to_string(),
         credential_id: String::new(),
         triggered_by: "manual".to_string(),
+        timeout_secs: None,
     };
 
     let job = state.store.create_job(&job_id, &req).await
@@ -189,3 +180,76 @@ pub async fn run_vendor_action(
 
     Ok((StatusCode::ACCEPTED, Json(job)))
 }
+
+// ========== Probe Command Endpoints ==========
+
+async fn validate_probe_command(state: &AppState, id: Option<i64>, req: &CreateProbeCommandRequest) -> Result<(), ApiError> {
+    let existing = state.store.list_probe_commands().await?;
+    if existing.iter().any(|p| p.vendor_id == req.vendor_id && p.field == req.field && Some(p.id) != id) {
+        return Err(ApiError::conflict(format!("a '{}' probe command set already exists for this vendor", req.field)));
+    }
+    if let Some(vendor_id) = req.vendor_id {
+        if state.store.get_vendor(vendor_id).await?.is_none() {
+            return Err(ApiError::bad_request("Vendor not found"));
+        }
+    }
+    if let Some(parser_id) = req.output_parser_id {
+        if state.store.get_output_parser(parser_id).await?.is_none() {
+            return Err(ApiError::bad_request("Output parser not found"));
+        }
+    }
+    Ok(())
+}
+
+/// List all probe command sets, defaults first
+pub async fn list_probe_commands(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ProbeCommand>>, ApiError> {
+    Ok(Json(state.store.list_probe_commands().await?))
+}
+
+/// The probe steps the connect test runs for a vendor, after falling back to the defaults
+pub async fn get_vendor_probe_steps(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<ProbeStep>>, ApiError> {
+    if state.store.get_vendor(id).await?.is_none() {
+        return Err(ApiError::not_found("vendor"));
+    }
+    Ok(Json(state.store.probe_steps(Some(&id.to_string())).await?))
+}
+
+/// Create a probe command set
+pub async fn create_probe_command(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidJson(req): ValidJson<CreateProbeCommandRequest>,
+) -> Result<(StatusCode, Json<ProbeCommand>), ApiError> {
+    validate_probe_command(&state, None, &req).await?;
+    let probe = state.store.create_probe_command(&req).await?;
+    Ok(created(probe))
+}
+
+/// Update a probe command set
+pub async fn update_probe_command(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    ValidJson(req): ValidJson<CreateProbeCommandRequest>,
+) -> Result<Json<ProbeCommand>, ApiError> {
+    validate_probe_command(&state, Some(id), &req).await?;
+    let probe = state.store.update_probe_command(id, &req).await?;
+    Ok(Json(probe))
+}
+
+/// Delete a probe command set; the field falls back to the default set
+pub async fn delete_probe_command(
+    _auth: crate::auth::AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.store.delete_probe_command(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
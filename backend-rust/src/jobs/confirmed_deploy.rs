@@ -0,0 +1,120 @@
+use anyhow::Result;
+use std::time::Duration;
+
+use crate::models::*;
+
+use super::JobService;
+
+/// Shortest rollback timer, leaving time to verify before the device rolls back
+const MIN_TIMER_SECS: u64 = 120;
+const VERIFY_ATTEMPTS: u32 = 3;
+const VERIFY_DELAY: Duration = Duration::from_secs(5);
+
+/// Fill in a confirmed-commit command's session name and rollback timer
+fn commit_confirm_payload(command: &str, session: &str, timer_secs: u64) -> String {
+    command
+        .replace("{SESSION}", session)
+        .replace("{TIMER_HMS}", &format!("{:02}:{:02}:{:02}", timer_secs / 3600, timer_secs / 60 % 60, timer_secs % 60))
+        .replace("{TIMER_MINUTES}", &timer_secs.div_ceil(60).to_string())
+}
+
+/// Log in again over SSH after a confirmed commit, retrying a few times. An error means the
+/// device is unreachable and the commit must be left to roll back.
+async fn verify_management(device: &Device, ssh_user: &str, ssh_pass: &str, timer_secs: u64) -> Result<()> {
+    let mut verify_error = String::new();
+    for _ in 0..VERIFY_ATTEMPTS {
+        tokio::time::sleep(VERIFY_DELAY).await;
+        match crate::utils::ssh_run_commands_async(&device.ip, ssh_user, ssh_pass, &[]).await {
+            Ok(_) => return Ok(()),
+            Err(e) => verify_error = e,
+        }
+    }
+    Err(anyhow::anyhow!(
+        "Device unreachable after commit ({}); not confirmed, it rolls back within {}s",
+        verify_error, timer_secs
+    ))
+}
+
+impl JobService {
+    /// Deploy with the vendor's confirmed commit: commit with a rollback timer, log in again to
+    /// verify management connectivity, then confirm. A device that can't be reached after the
+    /// commit is left unconfirmed and rolls itself back when the timer expires.
+    pub(super) async fn execute_confirmed_deploy_job(&self, job: &Job) -> Result<String> {
+        let device = self.load_job_device(job).await?;
+        let vendor = match device.vendor.as_deref() {
+            Some(v) if !v.is_empty() => self.store.resolve_vendor(v).await?,
+            _ => None,
+        }
+        .ok_or_else(|| anyhow::anyhow!("Device has no vendor; confirmed deploys use the vendor's commit commands"))?;
+        let netconf = vendor.transport == vendor_transport::NETCONF;
+        if !netconf && (vendor.commit_confirm_command.is_empty() || vendor.confirm_command.is_empty()) {
+            return Err(anyhow::anyhow!("Vendor {} has no commit_confirm_command and confirm_command", vendor.name));
+        }
+
+        let (settings, rendered_config) = self.render_job_config(&device).await?;
+        let (ssh_user, ssh_pass) = self.resolve_job_credentials(job, &device).await?;
+        self.preflight_gate(&device, &ssh_user, &ssh_pass, &settings).await?;
+
+        let timer_secs = (settings.confirmed_deploy_timer_secs.max(0) as u64).max(MIN_TIMER_SECS);
+        if netconf {
+            return self.netconf_confirmed_deploy(job, &device, &ssh_user, &ssh_pass, &rendered_config, timer_secs).await;
+        }
+        let session = format!("forge-{}", job.id.split('-').next().unwrap_or(&job.id));
+        let commit = commit_confirm_payload(&vendor.commit_confirm_command, &session, timer_secs)
+            .replace("{CONFIG}", &rendered_config);
+        let commit_output = crate::utils::ssh_run_interactive_async(&device.ip, &ssh_user, &ssh_pass, &commit)
+            .await
+            .map_err(|e| anyhow::anyhow!("Commit failed: {}", e))?;
+
+        verify_management(&device, &ssh_user, &ssh_pass, timer_secs).await?;
+
+        let confirm = commit_confirm_payload(&vendor.confirm_command, &session, timer_secs);
+        let confirm_output = crate::utils::ssh_run_interactive_async(&device.ip, &ssh_user, &ssh_pass, &confirm)
+            .await
+            .map_err(|e| anyhow::anyhow!("Confirm failed, the device rolls back within {}s: {}", timer_secs, e))?;
+
+        self.mark_device_online(job, device.id).await;
+        Ok(format!(
+            "{}\n--- management connectivity verified, commit confirmed ---\n{}",
+            commit_output, confirm_output
+        ))
+    }
+
+    /// Confirmed deploy over NETCONF. The confirming commit has to come from the session that
+    /// made the confirmed commit, so that session stays open while management connectivity is
+    /// verified; if it drops, the device rolls back straight away rather than at the timeout.
+    async fn netconf_confirmed_deploy(
+        &self,
+        job: &Job,
+        device: &Device,
+        ssh_user: &str,
+        ssh_pass: &str,
+        rendered_config: &str,
+        timer_secs: u64,
+    ) -> Result<String> {
+        let mut netconf = crate::utils::NetconfSession::open(&device.ip, ssh_user, ssh_pass)
+            .await
+            .map_err(|e| anyhow::anyhow!("NETCONF session failed: {}", e))?;
+        let committed = match netconf.load(rendered_config).await {
+            Ok(()) => netconf.commit(Some(timer_secs)).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = committed {
+            let log = netconf.close().await;
+            return Err(anyhow::anyhow!("Commit failed: {}\n{}", e, log));
+        }
+
+        if let Err(e) = verify_management(device, ssh_user, ssh_pass, timer_secs).await {
+            // Closing the session rolls back the confirmed commit
+            netconf.close().await;
+            return Err(e);
+        }
+
+        let confirmed = netconf.commit(None).await;
+        let log = netconf.close().await;
+        confirmed.map_err(|e| anyhow::anyhow!("Confirm failed, the device rolls back within {}s: {}\n{}", timer_secs, e, log))?;
+
+        self.mark_device_online(job, device.id).await;
+        Ok(format!("{}\n--- management connectivity verified, commit confirmed ---", log))
+    }
+}
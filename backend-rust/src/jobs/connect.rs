@@ -0,0 +1,149 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::db::Store;
+use crate::models::*;
+
+use super::JobService;
+
+/// What a connect test job on an address rather than a device connects to, stored as the job's
+/// command. An inline password is never stored with the job; the service holds it until the job
+/// runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectTarget {
+    pub ip: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_user: Option<String>,
+}
+
+/// Ping a device and log in over SSH with its resolved credentials, marking it online when it
+/// answers the ping
+pub async fn connect_device_test(store: &Store, device: &Device) -> ConnectResult {
+    let creds = crate::utils::resolve_ssh_credentials(store, device).await;
+    let result = connect_test(store, &device.ip, &creds.user, &creds.pass, device.vendor.as_deref()).await;
+    if result.ping.reachable {
+        let _ = store.update_device_status(device.id, device_status::ONLINE).await;
+    }
+    result
+}
+
+/// Ping an address and log in over SSH, falling back to the vendor's and then the default
+/// credentials for a missing user or password
+pub async fn connect_address_test(store: &Store, target: ConnectTarget, ssh_pass: Option<String>) -> ConnectResult {
+    let (ssh_user, ssh_pass) =
+        crate::utils::resolve_inline_ssh_credentials(store, target.ssh_user, ssh_pass, target.vendor.as_deref()).await;
+    connect_test(store, &target.ip, &ssh_user, &ssh_pass, target.vendor.as_deref()).await
+}
+
+async fn connect_test(store: &Store, ip: &str, ssh_user: &str, ssh_pass: &str, vendor: Option<&str>) -> ConnectResult {
+    let ping = ping_device(ip).await;
+
+    // SSH check with the vendor's probe commands
+    let ssh = if !ssh_user.is_empty() && !ssh_pass.is_empty() {
+        let steps = store.probe_steps(vendor).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to load probe commands for {}: {}", ip, e);
+            Vec::new()
+        });
+        ssh_probe(ip, ssh_user, ssh_pass, &steps).await
+    } else {
+        SshResult {
+            connected: false,
+            uptime: None,
+            hostname: None,
+            version: None,
+            interfaces: None,
+            error: Some("No SSH credentials configured".to_string()),
+        }
+    };
+
+    let success = ping.reachable && ssh.connected;
+    ConnectResult { ping, ssh, success }
+}
+
+async fn ping_device(ip: &str) -> PingResult {
+    if !crate::utils::is_valid_ipv4(ip) {
+        return PingResult {
+            reachable: false,
+            latency: None,
+            error: Some("Invalid IP address".to_string()),
+        };
+    }
+
+    let output = Command::new("ping")
+        .args(["-c", "3", "-W", "2", ip])
+        .output()
+        .await;
+
+    match output {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let latency = parse_ping_latency(&stdout);
+            PingResult {
+                reachable: true,
+                latency,
+                error: None,
+            }
+        }
+        _ => PingResult {
+            reachable: false,
+            latency: None,
+            error: Some("Host unreachable".to_string()),
+        },
+    }
+}
+
+fn parse_ping_latency(output: &str) -> Option<String> {
+    // Parse average time from ping stats line
+    for line in output.lines() {
+        if line.contains("avg") {
+            let parts: Vec<&str> = line.split('/').collect();
+            if parts.len() >= 5 {
+                let val = parts[4].trim_end_matches(" ms").trim_end_matches("ms");
+                return Some(format!("{}ms", val));
+            }
+        }
+    }
+    None
+}
+
+async fn ssh_probe(ip: &str, user: &str, pass: &str, steps: &[ProbeStep]) -> SshResult {
+    let (connected, probe, error) = crate::utils::ssh_probe_device(ip, user, pass, steps).await;
+    SshResult {
+        connected,
+        uptime: probe.uptime,
+        hostname: probe.hostname,
+        version: probe.version,
+        interfaces: probe.interfaces,
+        error,
+    }
+}
+
+impl JobService {
+    /// Hold the inline SSH password of a queued address connect test until it runs
+    pub fn hold_connect_password(&self, job_id: &str, ssh_pass: String) {
+        self.connect_passwords.lock().unwrap_or_else(|e| e.into_inner()).insert(job_id.to_string(), ssh_pass);
+    }
+
+    pub(super) fn take_connect_password(&self, job_id: &str) -> Option<String> {
+        self.connect_passwords.lock().unwrap_or_else(|e| e.into_inner()).remove(job_id)
+    }
+
+    /// Run a connect test against the job's device, or the address in its command when it has
+    /// none. The output is the JSON ConnectResult; the job completes whether or not the target
+    /// answered.
+    pub(super) async fn execute_connect_test_job(&self, job: &Job) -> Result<String> {
+        let result = if job.device_id == 0 {
+            let target: ConnectTarget = serde_json::from_str(&job.command)
+                .map_err(|e| anyhow::anyhow!("Invalid connect test target: {}", e))?;
+            let ssh_pass = self.take_connect_password(&job.id);
+            connect_address_test(&self.store, target, ssh_pass).await
+        } else {
+            let device = self.load_job_device(job).await?;
+            connect_device_test(&self.store, &device).await
+        };
+        Ok(serde_json::to_string(&result)?)
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+
+use crate::db::Store;
+use crate::models::*;
+
+use super::JobService;
+
+/// Hardware inventory command per vendor, matched on the vendor name
+fn inventory_command(vendor_name: &str) -> Option<&'static str> {
+    let name = vendor_name.to_lowercase();
+    if name.contains("arista") {
+        Some("show inventory | json")
+    } else if name.contains("cisco") {
+        Some("show inventory")
+    } else if name.contains("juniper") {
+        Some("show chassis hardware")
+    } else {
+        None
+    }
+}
+
+/// Collect a device's hardware inventory over SSH and check the chassis serial against the
+/// recorded one. A device without a recorded serial takes the chassis serial; a different one
+/// is recorded as a serial mismatch. Returns Ok(None) for vendors without an inventory command.
+pub async fn collect_hardware_inventory(
+    store: &Store,
+    device: &Device,
+    ssh_user: &str,
+    ssh_pass: &str,
+) -> Result<Option<HardwareInventorySummary>> {
+    let vendor_name = match device.vendor.as_deref() {
+        Some(v) if !v.is_empty() => store.resolve_vendor(v).await?
+            .map(|vendor| vendor.name)
+            .unwrap_or_else(|| v.to_string()),
+        _ => String::new(),
+    };
+    let Some(command) = inventory_command(&vendor_name) else {
+        return Ok(None);
+    };
+
+    let output = crate::utils::ssh_run_command_async(&device.ip, ssh_user, ssh_pass, command)
+        .await
+        .map_err(|e| anyhow::anyhow!("{}: {}", command, e))?;
+    let entries = crate::utils::parse_hardware_inventory(&output);
+    if entries.is_empty() {
+        return Err(anyhow::anyhow!("No hardware found in '{}' output", command));
+    }
+
+    let (components, removed) = store.replace_device_hardware(device.id, &entries).await?;
+    let mut summary = HardwareInventorySummary { components, removed, ..Default::default() };
+
+    let chassis_serial = entries
+        .iter()
+        .find(|e| e.component_type == hardware_component::CHASSIS && !e.serial_number.is_empty())
+        .map(|e| e.serial_number.clone());
+    if let Some(observed) = &chassis_serial {
+        match device.serial_number.as_deref().map(str::trim) {
+            None | Some("") => {
+                store.update_device_serial(device.id, observed).await?;
+                summary.serial_recorded = true;
+            }
+            Some(recorded) if !recorded.eq_ignore_ascii_case(observed) => {
+                tracing::warn!(
+                    "Serial mismatch on {}: recorded {}, chassis reports {}",
+                    device.hostname, recorded, observed
+                );
+                summary.mismatch = Some(store.record_serial_mismatch(device.id, recorded, observed).await?);
+            }
+            Some(_) => {}
+        }
+    }
+    summary.chassis_serial = chassis_serial;
+    Ok(Some(summary))
+}
+
+impl JobService {
+    /// Collect the device's hardware inventory and check its chassis serial
+    pub(super) async fn execute_hardware_collect_job(&self, job: &Job) -> Result<String> {
+        let device = self.load_job_device(job).await?;
+        let (ssh_user, ssh_pass) = self.resolve_job_credentials(job, &device).await?;
+
+        let summary = collect_hardware_inventory(&self.store, &device, &ssh_user, &ssh_pass)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No hardware inventory command for vendor '{}'", device.vendor.as_deref().unwrap_or("")))?;
+
+        let mut output = format!("Collected {} components ({} removed)", summary.components, summary.removed);
+        if summary.serial_recorded {
+            output.push_str(&format!(", recorded serial {}", summary.chassis_serial.as_deref().unwrap_or("")));
+        }
+        if let Some(mismatch) = &summary.mismatch {
+            output.push_str(&format!(
+                "\nWARNING: chassis serial {} differs from recorded serial {}",
+                mismatch.observed_serial, mismatch.recorded_serial
+            ));
+        }
+        Ok(output)
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::Result;
+
+use crate::models::*;
+
+use super::JobService;
+
+/// Interface listing command per vendor, matched on the vendor name. Unknown vendors fall back
+/// to Linux `ip -j addr show` (FRR, SONiC, Cumulus).
+fn interface_command(vendor_name: &str) -> &'static str {
+    let name = vendor_name.to_lowercase();
+    if name.contains("cisco") || name.contains("arista") || name.contains("juniper") {
+        "show interfaces"
+    } else {
+        "ip -j addr show"
+    }
+}
+
+/// VRF membership commands per vendor, tried in order until one produces recognizable output.
+/// Cisco NX-OS and IOS use different commands; Juniper isn't supported.
+fn vrf_commands(vendor_name: &str) -> &'static [&'static str] {
+    let name = vendor_name.to_lowercase();
+    if name.contains("arista") {
+        &["show vrf | json"]
+    } else if name.contains("cisco") {
+        &["show vrf interface", "show ip vrf interfaces"]
+    } else if name.contains("juniper") {
+        &[]
+    } else {
+        &["ip -j -d link show"]
+    }
+}
+
+impl JobService {
+    /// Collect the device's interfaces into its inventory and bind matching IPAM addresses
+    pub(super) async fn execute_interface_collect_job(&self, job: &Job) -> Result<String> {
+        let device = self.load_job_device(job).await?;
+        let (ssh_user, ssh_pass) = self.resolve_job_credentials(job, &device).await?;
+
+        let vendor_name = match device.vendor.as_deref() {
+            Some(v) if !v.is_empty() => self.store.resolve_vendor(v).await?
+                .map(|vendor| vendor.name)
+                .unwrap_or_else(|| v.to_string()),
+            _ => String::new(),
+        };
+
+        let command = interface_command(&vendor_name);
+        let output = crate::utils::ssh_run_command_async(&device.ip, &ssh_user, &ssh_pass, command)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}: {}", command, e))?;
+
+        let mut entries = crate::utils::parse_interface_inventory(&output);
+        if entries.is_empty() {
+            return Err(anyhow::anyhow!("No interfaces found in '{}' output", command));
+        }
+
+        // VRF membership is best-effort: without it the interfaces are stored with no VRF
+        for vrf_command in vrf_commands(&vendor_name) {
+            let vrfs = match crate::utils::ssh_run_command_async(&device.ip, &ssh_user, &ssh_pass, vrf_command).await {
+                Ok(output) => crate::utils::parse_interface_vrfs(&output),
+                Err(e) => {
+                    tracing::debug!("{}: {} failed: {}", device.hostname, vrf_command, e);
+                    None
+                }
+            };
+            if let Some(vrfs) = vrfs {
+                for entry in &mut entries {
+                    entry.vrf = Some(vrfs.get(&entry.name).cloned().unwrap_or_default());
+                }
+                break;
+            }
+        }
+
+        let summary = self.store.replace_device_interfaces(device.id, &entries).await?;
+        Ok(format!(
+            "Collected {} interfaces ({} removed), {} IPAM addresses bound",
+            summary.interfaces, summary.removed, summary.ipam_bound
+        ))
+    }
+}
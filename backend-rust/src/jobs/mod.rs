@@ -1,28 +1,115 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tera::{Context, Tera};
-use tokio::sync::mpsc;
+use std::time::Duration;
+use tera::Context;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinSet;
 
-use crate::db::Store;
+use crate::backup::BackupService;
+use crate::db::{RenderStore, Store};
 use crate::models::*;
+use crate::services::maintenance_mode::MaintenanceMode;
 use crate::ws::{EventType, Hub};
 
+mod confirmed_deploy;
+mod connect;
+mod hardware;
+mod interfaces;
+mod neighbors;
+mod password_push;
+mod preflight;
+mod render_cache;
+mod staged_configs;
+mod topology_deploy;
+pub use connect::{connect_address_test, connect_device_test, ConnectTarget};
+pub use hardware::collect_hardware_inventory;
+pub use password_push::validate_password_push;
+pub use preflight::run_preflight;
+pub use render_cache::RenderCache;
+
+/// Login for virtual lab nodes, matching the account in the cEOS lab startup config
+const LAB_SSH_USER: &str = "admin";
+const LAB_SSH_PASS: &str = "admin";
+
+/// Returned when accepting more jobs would take the queue past its capacity
+#[derive(Debug)]
+pub struct QueueFull {
+    pub queued: usize,
+    pub capacity: usize,
+}
+
+impl std::fmt::Display for QueueFull {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "job queue is full ({} of {} jobs waiting); retry later", self.queued, self.capacity)
+    }
+}
+
+impl std::error::Error for QueueFull {}
+
+/// Depth of the job queue as the worker sees it
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct JobQueueStats {
+    /// Jobs submitted that haven't started yet
+    pub queued: usize,
+    pub running: usize,
+    /// Queued jobs accepted before new ones are refused
+    pub capacity: usize,
+    pub concurrency: usize,
+    /// Jobs refused since startup because the queue was full
+    pub rejected: u64,
+}
+
 /// JobService manages async command execution and config deploy jobs
 pub struct JobService {
     store: Store,
     ws_hub: Option<Arc<Hub>>,
+    render_cache: Arc<RenderCache>,
+    maintenance_mode: Arc<MaintenanceMode>,
+    backup_service: Option<Arc<BackupService>>,
     pending_tx: mpsc::Sender<String>,
+    /// Jobs allowed to run at once
+    concurrency: usize,
+    /// Queued jobs accepted before `check_capacity` refuses more
+    queue_capacity: usize,
+    /// Jobs submitted that the worker hasn't started
+    queued: AtomicUsize,
+    running_count: AtomicUsize,
+    rejected: AtomicU64,
+    /// Cancel handles of jobs the worker has started; dropping one stops the job
+    running: Mutex<HashMap<String, oneshot::Sender<()>>>,
+    /// Inline SSH passwords of queued address connect tests, by job ID
+    connect_passwords: std::sync::Mutex<HashMap<String, String>>,
 }
 
 impl JobService {
-    pub fn new(store: Store, ws_hub: Option<Arc<Hub>>) -> Arc<Self> {
-        let (pending_tx, pending_rx) = mpsc::channel(100);
+    pub fn new(
+        store: Store,
+        ws_hub: Option<Arc<Hub>>,
+        render_cache: Arc<RenderCache>,
+        maintenance_mode: Arc<MaintenanceMode>,
+        backup_service: Option<Arc<BackupService>>,
+        concurrency: usize,
+        queue_capacity: usize,
+    ) -> Arc<Self> {
+        let queue_capacity = queue_capacity.max(1);
+        let (pending_tx, pending_rx) = mpsc::channel(queue_capacity);
 
         let service = Arc::new(Self {
             store,
             ws_hub,
+            render_cache,
+            maintenance_mode,
+            backup_service,
             pending_tx,
+            concurrency: concurrency.max(1),
+            queue_capacity,
+            queued: AtomicUsize::new(0),
+            running_count: AtomicUsize::new(0),
+            rejected: AtomicU64::new(0),
+            running: Mutex::new(HashMap::new()),
+            connect_passwords: std::sync::Mutex::new(HashMap::new()),
         });
 
         // Start the worker
@@ -34,28 +121,72 @@ impl JobService {
         // Re-queue stuck jobs from a previous crash
         let requeue_service = service.clone();
         tokio::spawn(async move {
+            requeue_service.fail_interrupted_topology_deploys().await;
             requeue_service.requeue_stuck_jobs().await;
         });
 
         service
     }
 
-    /// Submit a job ID for processing
+    /// Submit a job ID for processing. Callers that can refuse work check `check_capacity` before
+    /// creating the job; a submitted job waits for room rather than being dropped.
     pub async fn submit(&self, job_id: String) {
+        self.queued.fetch_add(1, Ordering::Relaxed);
         if let Err(e) = self.pending_tx.send(job_id.clone()).await {
-            tracing::warn!("Failed to submit job {}: {}", job_id, e);
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+            tracing::error!("Failed to submit job {}: {}", job_id, e);
+        }
+    }
+
+    /// Whether `jobs` more fit in the queue, counting a refusal against the rejected total
+    pub fn check_capacity(&self, jobs: usize) -> Result<(), QueueFull> {
+        let queued = self.queued.load(Ordering::Relaxed);
+        if queued + jobs > self.queue_capacity {
+            self.rejected.fetch_add(jobs as u64, Ordering::Relaxed);
+            tracing::warn!("Job queue full ({}/{}), refusing {} job(s)", queued, self.queue_capacity, jobs);
+            return Err(QueueFull { queued, capacity: self.queue_capacity });
+        }
+        Ok(())
+    }
+
+    pub fn queue_stats(&self) -> JobQueueStats {
+        JobQueueStats {
+            queued: self.queued.load(Ordering::Relaxed),
+            running: self.running_count.load(Ordering::Relaxed),
+            capacity: self.queue_capacity,
+            concurrency: self.concurrency,
+            rejected: self.rejected.load(Ordering::Relaxed),
         }
     }
 
+    /// Cancel a queued or running job. A running job's task is dropped, which disconnects its SSH
+    /// session. Returns false when the job had already finished.
+    pub async fn cancel(&self, job_id: &str, cancelled_by: &str) -> Result<bool> {
+        // Held across the update so the worker can't start or finish the job in between
+        let mut running = self.running.lock().await;
+        let cancelled = self
+            .store
+            .update_job_cancelled(job_id, &format!("cancelled by {}", cancelled_by))
+            .await?;
+        if cancelled {
+            running.remove(job_id);
+            self.take_connect_password(job_id);
+        }
+        drop(running);
+        if cancelled {
+            tracing::info!("Job {} cancelled by {}", job_id, cancelled_by);
+            self.broadcast_job(EventType::JobCancelled, job_id).await;
+        }
+        Ok(cancelled)
+    }
+
     /// Re-queue jobs that were stuck (queued/running) from a previous crash
     async fn requeue_stuck_jobs(&self) {
         match self.store.list_jobs_stuck().await {
             Ok(jobs) => {
                 for job in &jobs {
                     tracing::info!("Re-queuing stuck job {} (status={})", job.id, job.status);
-                    if let Err(e) = self.pending_tx.send(job.id.clone()).await {
-                        tracing::warn!("Failed to re-queue job {}: {}", job.id, e);
-                    }
+                    self.submit(job.id.clone()).await;
                 }
                 if !jobs.is_empty() {
                     tracing::info!("Re-queued {} stuck jobs", jobs.len());
@@ -78,6 +209,16 @@ impl JobService {
             loop {
                 interval.tick().await;
 
+                if svc.maintenance_mode.is_enabled() {
+                    continue;
+                }
+
+                match svc.store.get_settings().await {
+                    Ok(settings) if settings.features.disable_scheduler => continue,
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Scheduler: failed to load settings: {}", e),
+                }
+
                 let templates = match svc.store.list_scheduled_job_templates().await {
                     Ok(t) => t,
                     Err(e) => {
@@ -115,74 +256,18 @@ impl JobService {
                     tracing::info!("Scheduler: running template '{}' ({})", tmpl.name, tmpl.id);
 
                     // Resolve target device IDs
-                    let device_ids: Vec<i64> = if tmpl.target_mode == "group" && tmpl.target_group_id != 0 {
-                        match svc.store.list_group_members(tmpl.target_group_id).await {
-                            Ok(ids) => ids,
-                            Err(e) => {
-                                tracing::warn!("Scheduler: failed to resolve group {}: {}", tmpl.target_group_id, e);
-                                continue;
-                            }
+                    let device_ids: Vec<i64> = match svc.store.resolve_job_template_targets(tmpl).await {
+                        Ok(ids) => ids,
+                        Err(e) => {
+                            tracing::warn!("Scheduler: failed to resolve targets for template {}: {}", tmpl.id, e);
+                            continue;
                         }
-                    } else {
-                        tmpl.target_device_ids.clone()
                     };
 
-                    let is_webhook = tmpl.job_type == crate::models::job_type::WEBHOOK;
-
-                    let credential_id_str = tmpl.credential_id.to_string();
-
-                    if is_webhook && device_ids.is_empty() {
-                        // Static webhook — run once without device
-                        let job_id = uuid::Uuid::new_v4().to_string();
-                        let req = CreateJobRequest {
-                            device_id: 0,
-                            job_type: crate::models::job_type::WEBHOOK.to_string(),
-                            command: tmpl.action_id.to_string(),
-                            credential_id: credential_id_str.clone(),
-                            triggered_by: "scheduled".to_string(),
-                        };
-                        if let Ok(job) = svc.store.create_job(&job_id, &req).await {
-                            if let Some(ref hub) = svc.ws_hub {
-                                hub.broadcast_job_update(EventType::JobQueued, &job).await;
-                            }
-                            svc.submit(job_id).await;
-                        }
-                    } else {
-                        // Create a job for each target device
-                        for device_id in &device_ids {
-                            let job_id = uuid::Uuid::new_v4().to_string();
-                            let command = if is_webhook {
-                                tmpl.action_id.to_string()
-                            } else if tmpl.action_id != 0 {
-                                match svc.store.get_vendor_action(tmpl.action_id).await {
-                                    Ok(Some(action)) => action.command.clone(),
-                                    _ => tmpl.command.clone(),
-                                }
-                            } else {
-                                tmpl.command.clone()
-                            };
-
-                            let jt = if is_webhook {
-                                crate::models::job_type::WEBHOOK.to_string()
-                            } else {
-                                tmpl.job_type.clone()
-                            };
-
-                            let req = CreateJobRequest {
-                                device_id: *device_id,
-                                job_type: jt,
-                                command,
-                                credential_id: credential_id_str.clone(),
-                                triggered_by: "scheduled".to_string(),
-                            };
-
-                            if let Ok(job) = svc.store.create_job(&job_id, &req).await {
-                                if let Some(ref hub) = svc.ws_hub {
-                                    hub.broadcast_job_update(EventType::JobQueued, &job).await;
-                                }
-                                svc.submit(job_id).await;
-                            }
-                        }
+                    // A full queue leaves last_run_at alone so the template runs on a later tick
+                    if let Err(e) = svc.queue_template_jobs(tmpl, &device_ids, "scheduled").await {
+                        tracing::warn!("Scheduler: skipping template {}: {}", tmpl.id, e);
+                        continue;
                     }
 
                     // Update last_run_at
@@ -192,10 +277,132 @@ impl JobService {
         });
     }
 
-    async fn worker(&self, mut rx: mpsc::Receiver<String>) {
-        while let Some(job_id) = rx.recv().await {
-            if let Err(e) = self.process_job(&job_id).await {
-                tracing::error!("Job {} processing error: {}", job_id, e);
+    /// Queue a job template's jobs for `device_ids`; a webhook template with no targets runs once.
+    /// Nothing is queued when the jobs don't all fit in the queue.
+    pub async fn queue_template_jobs(
+        &self,
+        tmpl: &JobTemplate,
+        device_ids: &[i64],
+        triggered_by: &str,
+    ) -> Result<Vec<Job>, QueueFull> {
+        let is_webhook = tmpl.job_type == job_type::WEBHOOK;
+        let credential_id = tmpl.credential_id.to_string();
+
+        let mut requests = Vec::new();
+        if is_webhook && device_ids.is_empty() {
+            // Static webhook — run once without device
+            requests.push(CreateJobRequest {
+                device_id: 0,
+                job_type: job_type::WEBHOOK.to_string(),
+                command: tmpl.action_id.to_string(),
+                credential_id: credential_id.clone(),
+                triggered_by: triggered_by.to_string(),
+                timeout_secs: None,
+            });
+        } else {
+            let command = if is_webhook {
+                tmpl.action_id.to_string()
+            } else if tmpl.action_id != 0 {
+                match self.store.get_vendor_action(tmpl.action_id).await {
+                    Ok(Some(action)) => action.command.clone(),
+                    _ => tmpl.command.clone(),
+                }
+            } else {
+                tmpl.command.clone()
+            };
+            let jt = if is_webhook { job_type::WEBHOOK.to_string() } else { tmpl.job_type.clone() };
+            for device_id in device_ids {
+                requests.push(CreateJobRequest {
+                    device_id: *device_id,
+                    job_type: jt.clone(),
+                    command: command.clone(),
+                    credential_id: credential_id.clone(),
+                    triggered_by: triggered_by.to_string(),
+                    timeout_secs: None,
+                });
+            }
+        }
+        self.check_capacity(requests.len())?;
+
+        let mut jobs = Vec::new();
+        for req in requests {
+            let job_id = uuid::Uuid::new_v4().to_string();
+            match self.store.create_job(&job_id, &req).await {
+                Ok(job) => {
+                    if let Some(ref hub) = self.ws_hub {
+                        hub.broadcast_job_update(EventType::JobQueued, &job).await;
+                    }
+                    self.submit(job_id).await;
+                    jobs.push(job);
+                }
+                Err(e) => tracing::warn!("Failed to create job for template {}: {}", tmpl.id, e),
+            }
+        }
+        Ok(jobs)
+    }
+
+    /// Run queued jobs on up to `concurrency` workers. Jobs on the same device run one at a time
+    /// in the order they were queued; jobs without a device start as soon as a worker is free.
+    async fn worker(self: Arc<Self>, mut rx: mpsc::Receiver<String>) {
+        let mut running: JoinSet<()> = JoinSet::new();
+        // Job and device of each running task, so a panicked task still releases its device
+        let mut tasks: HashMap<tokio::task::Id, (String, i64)> = HashMap::new();
+        let mut busy_devices: HashSet<i64> = HashSet::new();
+        let mut waiting: VecDeque<(String, i64)> = VecDeque::new();
+
+        loop {
+            // Start the oldest waiting jobs whose device is free
+            let mut i = 0;
+            while running.len() < self.concurrency && i < waiting.len() {
+                let device_id = waiting[i].1;
+                if device_id != 0 && busy_devices.contains(&device_id) {
+                    i += 1;
+                    continue;
+                }
+                let Some((job_id, device_id)) = waiting.remove(i) else { break };
+                if device_id != 0 {
+                    busy_devices.insert(device_id);
+                }
+                self.queued.fetch_sub(1, Ordering::Relaxed);
+                let svc = self.clone();
+                let task_job_id = job_id.clone();
+                let handle = running.spawn(async move {
+                    if let Err(e) = svc.process_job(&task_job_id).await {
+                        tracing::error!("Job {} processing error: {}", task_job_id, e);
+                    }
+                });
+                tasks.insert(handle.id(), (job_id, device_id));
+            }
+            self.running_count.store(running.len(), Ordering::Relaxed);
+
+            tokio::select! {
+                received = rx.recv() => match received {
+                    Some(job_id) => match self.store.get_job(&job_id).await {
+                        Ok(Some(job)) => waiting.push_back((job_id, job.device_id)),
+                        Ok(None) => {
+                            self.queued.fetch_sub(1, Ordering::Relaxed);
+                            tracing::warn!("Job {} not found, skipping", job_id);
+                        }
+                        Err(e) => {
+                            self.queued.fetch_sub(1, Ordering::Relaxed);
+                            tracing::error!("Failed to load job {}: {}", job_id, e);
+                        }
+                    },
+                    None => break,
+                },
+                Some(finished) = running.join_next_with_id() => {
+                    self.running_count.store(running.len(), Ordering::Relaxed);
+                    let task_id = match &finished {
+                        Ok((id, ())) => *id,
+                        Err(e) => e.id(),
+                    };
+                    let Some((job_id, device_id)) = tasks.remove(&task_id) else { continue };
+                    busy_devices.remove(&device_id);
+                    if let Err(e) = finished {
+                        tracing::error!("Job {} worker panicked: {}", job_id, e);
+                        self.fail_panicked_job(&job_id).await;
+                    }
+                },
             }
         }
     }
@@ -209,55 +416,192 @@ impl JobService {
             }
         };
 
-        // Mark as running
-        self.store.update_job_started(job_id).await?;
+        // Register the cancel handle and mark the job running together, so a cancel either
+        // finds the job still queued or can stop it
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        {
+            let mut running = self.running.lock().await;
+            if !self.store.update_job_started(job_id).await? {
+                tracing::info!("Job {} was cancelled or has finished, skipping", job_id);
+                return Ok(());
+            }
+            running.insert(job_id.to_string(), cancel_tx);
+        }
         self.broadcast_job(EventType::JobStarted, job_id).await;
 
-        // Execute based on job type
-        let result = match job.job_type.as_str() {
-            job_type::COMMAND => self.execute_command_job(&job).await,
-            job_type::DEPLOY => self.execute_deploy_job(&job).await,
-            job_type::DIFF => self.execute_diff_job(&job).await,
-            job_type::WEBHOOK => self.execute_webhook_job(&job).await,
-            job_type::APPLY_TEMPLATE => self.execute_apply_template_job(&job).await,
-            _ => Err(anyhow::anyhow!("Unknown job type: {}", job.job_type)),
+        let timeout = self.job_timeout(&job).await;
+        let execute = async {
+            match timeout {
+                Some(limit) => tokio::time::timeout(limit, self.execute_job(&job))
+                    .await
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("Job timed out after {}s", limit.as_secs()))),
+                None => self.execute_job(&job).await,
+            }
         };
-
-        // Update job result
+        let result = tokio::select! {
+            result = execute => result,
+            // The cancel API has recorded the cancellation
+            _ = cancel_rx => return Ok(()),
+        };
+        // Update job result; the lock keeps a cancel from landing between the check and the write
+        let mut running = self.running.lock().await;
+        if running.remove(job_id).is_none() {
+            // Cancelled as the job finished; the cancellation stands
+            return Ok(());
+        }
+        let succeeded = result.is_ok();
         match result {
-            Ok(output) => {
-                self.store.update_job_completed(job_id, &output).await?;
-                self.broadcast_job(EventType::JobCompleted, job_id).await;
-            }
-            Err(e) => {
-                let error_msg = e.to_string();
-                self.store.update_job_failed(job_id, &error_msg).await?;
-                self.broadcast_job(EventType::JobFailed, job_id).await;
-            }
+            Ok(output) => self.store.update_job_completed(job_id, &output).await?,
+            Err(e) => self.store.update_job_failed(job_id, &e.to_string()).await?,
+        }
+        drop(running);
+
+        if succeeded {
+            self.broadcast_job(EventType::JobCompleted, job_id).await;
+            self.backup_after_change(&job).await;
+        } else {
+            self.broadcast_job(EventType::JobFailed, job_id).await;
         }
 
         Ok(())
     }
 
-    async fn execute_command_job(&self, job: &Job) -> Result<String> {
-        let device = self.store.get_device(job.device_id).await?
+    /// Record a job whose task panicked as failed, so it doesn't stay running forever
+    async fn fail_panicked_job(&self, job_id: &str) {
+        if self.running.lock().await.remove(job_id).is_none() {
+            // Not started, or already finished or cancelled
+            return;
+        }
+        match self.store.update_job_failed(job_id, "Job worker panicked").await {
+            Ok(()) => self.broadcast_job(EventType::JobFailed, job_id).await,
+            Err(e) => tracing::error!("Failed to mark panicked job {} failed: {}", job_id, e),
+        }
+    }
+
+    /// The job's own run time limit, else the job_timeout_secs setting; None when unlimited
+    async fn job_timeout(&self, job: &Job) -> Option<Duration> {
+        let secs = match job.timeout_secs {
+            Some(secs) => secs,
+            None => match self.store.get_settings().await {
+                Ok(settings) => settings.job_timeout_secs as i64,
+                Err(e) => {
+                    tracing::warn!("Failed to load settings for job {} timeout: {}", job.id, e);
+                    Settings::default().job_timeout_secs as i64
+                }
+            },
+        };
+        (secs > 0).then(|| Duration::from_secs(secs as u64))
+    }
+
+    async fn execute_job(&self, job: &Job) -> Result<String> {
+        match job.job_type.as_str() {
+            job_type::COMMAND => self.execute_command_job(job).await,
+            job_type::DEPLOY => self.execute_deploy_job(job).await,
+            job_type::CONFIRMED_DEPLOY => self.execute_confirmed_deploy_job(job).await,
+            job_type::DIFF => self.execute_diff_job(job).await,
+            job_type::WEBHOOK => self.execute_webhook_job(job).await,
+            job_type::APPLY_TEMPLATE => self.execute_apply_template_job(job).await,
+            job_type::NEIGHBOR_COLLECT => self.execute_neighbor_collect_job(job).await,
+            job_type::INTERFACE_COLLECT => self.execute_interface_collect_job(job).await,
+            job_type::HARDWARE_COLLECT => self.execute_hardware_collect_job(job).await,
+            job_type::PREFLIGHT => self.execute_preflight_job(job).await,
+            job_type::STAGED_DEPLOY => self.execute_staged_deploy_job(job).await,
+            job_type::CONNECT_TEST => self.execute_connect_test_job(job).await,
+            job_type::PASSWORD_PUSH => self.execute_password_push_job(job).await,
+            _ => Err(anyhow::anyhow!("Unknown job type: {}", job.job_type)),
+        }
+    }
+
+    /// Schedule a backup of the job's device after a job that changed its config, so every
+    /// change made through the system is followed by a backup
+    async fn backup_after_change(&self, job: &Job) {
+        let changes_config = matches!(
+            job.job_type.as_str(),
+            job_type::DEPLOY | job_type::CONFIRMED_DEPLOY | job_type::STAGED_DEPLOY | job_type::APPLY_TEMPLATE
+        );
+        // Lab jobs change the lab node, not the device
+        if !changes_config || job.triggered_by == TRIGGERED_BY_LAB {
+            return;
+        }
+        let Some(ref backup_service) = self.backup_service else { return };
+        match self.store.get_settings().await {
+            Ok(settings) if settings.backup_after_change => {
+                tracing::info!("Scheduling backup of device {} after {} job {}", job.device_id, job.job_type, job.id);
+                backup_service.schedule_backup(job.device_id, settings.backup_after_change_delay_secs.max(0) as u64);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to load settings for backup after job {}: {}", job.id, e),
+        }
+    }
+
+    /// Mark the job's device online after it answered. Lab jobs reached the lab node, not the
+    /// device, so they leave its status and status history alone.
+    async fn mark_device_online(&self, job: &Job, device_id: i64) {
+        if job.triggered_by == TRIGGERED_BY_LAB {
+            return;
+        }
+        let _ = self.store.update_device_status(device_id, device_status::ONLINE).await;
+    }
+
+    /// Load the job's target device. Lab jobs connect to the device's virtual lab node instead,
+    /// so the management IP is swapped for the container's.
+    async fn load_job_device(&self, job: &Job) -> Result<Device> {
+        let mut device = self.store.get_device(job.device_id).await?
             .ok_or_else(|| anyhow::anyhow!("Device not found: {}", job.device_id))?;
 
-        let (mut ssh_user, mut ssh_pass) = crate::utils::resolve_ssh_credentials(&self.store, device.ssh_user.clone(), device.ssh_pass.clone(), device.vendor.as_deref()).await;
+        if job.triggered_by == TRIGGERED_BY_LAB {
+            let node = self.store.get_lab_node_for_device(device.id).await?
+                .ok_or_else(|| anyhow::anyhow!("Device {} has no lab node", device.hostname))?;
+            if node.mgmt_ip.is_empty() {
+                return Err(anyhow::anyhow!("Lab node {} has no management IP", node.container_name));
+            }
+            device.ip = node.mgmt_ip;
+        }
+        Ok(device)
+    }
+
+    /// Resolve SSH credentials for a job: the device's resolved credentials, overridden by the
+    /// job's credential if set. Use of a stored credential is recorded for the usage audit.
+    async fn resolve_job_credentials(&self, job: &Job, device: &Device) -> Result<(String, String)> {
+        let (mut ssh_user, mut ssh_pass, mut used_credential) = if job.triggered_by == TRIGGERED_BY_LAB {
+            // Lab images boot with the admin account from the cEOS startup config
+            (LAB_SSH_USER.to_string(), LAB_SSH_PASS.to_string(), None)
+        } else {
+            let resolved = crate::utils::resolve_ssh_credentials(&self.store, device).await;
+            (resolved.user, resolved.pass, resolved.credential_id)
+        };
 
         // Override with job-specific credential if set
-        if !job.credential_id.is_empty() {
-            if let Ok(cred_id) = job.credential_id.parse::<i64>() {
-                if let Some(cred) = self.store.get_credential(cred_id).await? {
-                    if !cred.username.is_empty() { ssh_user = cred.username; }
-                    if !cred.password.is_empty() { ssh_pass = cred.password; }
-                }
+        if let Ok(cred_id) = job.credential_id.parse::<i64>() {
+            if let Some(cred) = self.store.get_credential(cred_id).await? {
+                // Rotation pushes still have to log in with the password being replaced
+                let password = if job.triggered_by == TRIGGERED_BY_CREDENTIAL_ROTATION && !cred.previous_password.is_empty() {
+                    cred.previous_password
+                } else {
+                    cred.password
+                };
+                if !cred.username.is_empty() { ssh_user = cred.username; }
+                if !password.is_empty() { ssh_pass = password; }
+                used_credential = Some(cred.id);
+            }
+        }
+
+        if let Some(cred_id) = used_credential {
+            if let Err(e) = self.store.record_credential_usage(cred_id, Some(device.id), &job.id).await {
+                tracing::warn!("Failed to record usage of credential {}: {}", cred_id, e);
             }
         }
 
         if ssh_user.is_empty() || ssh_pass.is_empty() {
             return Err(anyhow::anyhow!("No SSH credentials available for this device"));
         }
+        Ok((ssh_user, ssh_pass))
+    }
+
+    async fn execute_command_job(&self, job: &Job) -> Result<String> {
+        let device = self.load_job_device(job).await?;
+
+        let (ssh_user, ssh_pass) = self.resolve_job_credentials(job, &device).await?;
 
         crate::utils::ssh_run_command_async(&device.ip, &ssh_user, &ssh_pass, &job.command)
             .await
@@ -265,9 +609,14 @@ impl JobService {
     }
 
     async fn execute_deploy_job(&self, job: &Job) -> Result<String> {
-        let device = self.store.get_device(job.device_id).await?
-            .ok_or_else(|| anyhow::anyhow!("Device not found: {}", job.device_id))?;
+        let device = self.load_job_device(job).await?;
+        let (settings, rendered_config) = self.render_job_config(&device).await?;
+        self.push_config(job, &device, &settings, rendered_config).await
+    }
 
+    /// Render the device's config for a deploy: its template (or the vendor default), role
+    /// templates, resolved variables, port assignments and config snippets
+    async fn render_job_config(&self, device: &Device) -> Result<(Settings, String)> {
         // Resolve template: use device's config_template, or fall back to vendor's default_template
         let template_id = if !device.config_template.is_empty() {
             device.config_template.parse::<i64>()
@@ -290,18 +639,11 @@ impl JobService {
 
         let settings = self.store.get_settings().await?;
 
-        // Look up role-specific template by name convention
-        let role_template = if let Some(ref role) = device.topology_role {
-            let capitalized_role = format!("{}{}", &role[..1].to_uppercase(), &role[1..]);
-            let role_name = if template.name.ends_with(" Default") {
-                format!("{} {}", template.name.trim_end_matches(" Default"), capitalized_role)
-            } else {
-                format!("{} {}", template.name, capitalized_role)
-            };
-            self.store.get_template_by_name(&role_name).await.ok().flatten()
-        } else {
-            None
-        };
+        // Role templates (layers) from the device role's explicit template mapping
+        let role_templates = self
+            .store
+            .resolve_role_templates(&template, device.vendor.as_deref(), device.topology_role.as_deref())
+            .await?;
 
         // Load resolved variables (group + host inheritance) for template rendering
         let vars = self
@@ -314,24 +656,20 @@ impl JobService {
         let port_assignments = self.store.list_port_assignments(device.id).await.unwrap_or_default();
 
         // Render the template
-        let rendered_config = render_config(&device, &template, &settings, role_template.as_ref(), &vars, Some(&port_assignments))?;
+        let rendered_config = self.render_cache.render(device, &template, &settings, &role_templates, &vars, Some(&port_assignments))?;
+        let rendered_config = self.store.apply_device_config_snippets(device.id, rendered_config).await?;
 
-        // Resolve SSH credentials
-        let (mut ssh_user, mut ssh_pass) = crate::utils::resolve_ssh_credentials(&self.store, device.ssh_user.clone(), device.ssh_pass.clone(), device.vendor.as_deref()).await;
+        Ok((settings, rendered_config))
+    }
 
-        // Override with job-specific credential if set
-        if !job.credential_id.is_empty() {
-            if let Ok(cred_id) = job.credential_id.parse::<i64>() {
-                if let Some(cred) = self.store.get_credential(cred_id).await? {
-                    if !cred.username.is_empty() { ssh_user = cred.username; }
-                    if !cred.password.is_empty() { ssh_pass = cred.password; }
-                }
-            }
-        }
+    /// Push a config to the job's device, wrapped in the vendor's deploy_command, or loaded and
+    /// committed over NETCONF for vendors with the netconf transport. With
+    /// preflight_before_deploy set, a pre-flight no-go fails the job before anything is pushed.
+    async fn push_config(&self, job: &Job, device: &Device, settings: &Settings, rendered_config: String) -> Result<String> {
+        // Resolve SSH credentials
+        let (ssh_user, ssh_pass) = self.resolve_job_credentials(job, device).await?;
 
-        if ssh_user.is_empty() || ssh_pass.is_empty() {
-            return Err(anyhow::anyhow!("No SSH credentials available for this device"));
-        }
+        self.preflight_gate(device, &ssh_user, &ssh_pass, settings).await?;
 
         // Resolve vendor deploy_command wrapper
         let vendor = match device.vendor.as_deref() {
@@ -339,6 +677,14 @@ impl JobService {
             _ => None,
         };
 
+        if vendor.as_ref().is_some_and(|v| v.transport == vendor_transport::NETCONF) {
+            let output = crate::utils::netconf_deploy(&device.ip, &ssh_user, &ssh_pass, &rendered_config)
+                .await
+                .map_err(|e| anyhow::anyhow!("NETCONF deploy failed: {}", e))?;
+            self.mark_device_online(job, device.id).await;
+            return Ok(output);
+        }
+
         let has_deploy_command = vendor.as_ref().map_or(false, |v| !v.deploy_command.is_empty());
 
         let deploy_payload = if let Some(ref v) = vendor {
@@ -363,14 +709,24 @@ impl JobService {
         };
 
         // Update device status on successful deploy
-        let _ = self.store.update_device_status(device.id, device_status::ONLINE).await;
+        self.mark_device_online(job, device.id).await;
 
         Ok(output)
     }
 
+    /// With preflight_before_deploy set, fail on a pre-flight no-go
+    async fn preflight_gate(&self, device: &Device, ssh_user: &str, ssh_pass: &str, settings: &Settings) -> Result<()> {
+        if settings.preflight_before_deploy {
+            let preflight = run_preflight(&self.store, device, ssh_user, ssh_pass, settings.preflight_max_clock_skew_secs as i64).await?;
+            if !preflight.go {
+                return Err(anyhow::anyhow!("Pre-flight no-go: {}", preflight.failures()));
+            }
+        }
+        Ok(())
+    }
+
     async fn execute_diff_job(&self, job: &Job) -> Result<String> {
-        let device = self.store.get_device(job.device_id).await?
-            .ok_or_else(|| anyhow::anyhow!("Device not found: {}", job.device_id))?;
+        let device = self.load_job_device(job).await?;
 
         // Resolve template: use device's config_template, or fall back to vendor's default_template
         let template_id = if !device.config_template.is_empty() {
@@ -394,18 +750,11 @@ impl JobService {
 
         let settings = self.store.get_settings().await?;
 
-        // Look up role-specific template by name convention
-        let role_template = if let Some(ref role) = device.topology_role {
-            let capitalized_role = format!("{}{}", &role[..1].to_uppercase(), &role[1..]);
-            let role_name = if template.name.ends_with(" Default") {
-                format!("{} {}", template.name.trim_end_matches(" Default"), capitalized_role)
-            } else {
-                format!("{} {}", template.name, capitalized_role)
-            };
-            self.store.get_template_by_name(&role_name).await.ok().flatten()
-        } else {
-            None
-        };
+        // Role templates (layers) from the device role's explicit template mapping
+        let role_templates = self
+            .store
+            .resolve_role_templates(&template, device.vendor.as_deref(), device.topology_role.as_deref())
+            .await?;
 
         // Load resolved variables (group + host inheritance) for template rendering
         let vars = self
@@ -418,24 +767,18 @@ impl JobService {
         let port_assignments = self.store.list_port_assignments(device.id).await.unwrap_or_default();
 
         // Render the template
-        let rendered_config = render_config(&device, &template, &settings, role_template.as_ref(), &vars, Some(&port_assignments))?;
+        let rendered_config = self.render_cache.render(&device, &template, &settings, &role_templates, &vars, Some(&port_assignments))?;
+        let rendered_config = self.store.apply_device_config_snippets(device.id, rendered_config).await?;
 
         // Resolve SSH credentials
-        let (mut ssh_user, mut ssh_pass) = crate::utils::resolve_ssh_credentials(&self.store, device.ssh_user.clone(), device.ssh_pass.clone(), device.vendor.as_deref()).await;
+        let (ssh_user, ssh_pass) = self.resolve_job_credentials(job, &device).await?;
 
-        // Override with job-specific credential if set
-        if !job.credential_id.is_empty() {
-            if let Ok(cred_id) = job.credential_id.parse::<i64>() {
-                if let Some(cred) = self.store.get_credential(cred_id).await? {
-                    if !cred.username.is_empty() { ssh_user = cred.username; }
-                    if !cred.password.is_empty() { ssh_pass = cred.password; }
-                }
-            }
-        }
-
-        if ssh_user.is_empty() || ssh_pass.is_empty() {
-            return Err(anyhow::anyhow!("No SSH credentials available for this device"));
-        }
+        // Diff what the device is running against the rendered config
+        let backup_command = crate::backup::resolve_backup_command(&self.store, &device, &settings).await;
+        let running_config = crate::utils::ssh_run_command_async(&device.ip, &ssh_user, &ssh_pass, &backup_command)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to fetch running config: {}", e))?;
+        let diff = crate::utils::diff_lines(&running_config, &rendered_config, "running", "rendered", 3);
 
         // Resolve vendor diff_command wrapper
         let vendor = match device.vendor.as_deref() {
@@ -443,27 +786,27 @@ impl JobService {
             _ => None,
         };
 
-        let has_diff_command = vendor.as_ref().map_or(false, |v| !v.diff_command.is_empty());
-
-        if !has_diff_command {
-            return Err(anyhow::anyhow!("Vendor has no diff_command configured"));
-        }
-
-        // Strip "end" lines that would exit config mode entirely since the diff_command
-        // wrapper manages the session lifecycle (e.g., show session-config diffs / abort for Arista)
-        let config_for_diff: String = rendered_config
-            .lines()
-            .filter(|line| !line.trim().eq_ignore_ascii_case("end"))
-            .collect::<Vec<_>>()
-            .join("\n");
-        let diff_payload = vendor.as_ref().unwrap().diff_command.replace("{CONFIG}", &config_for_diff);
-
-        // Use interactive shell for multi-line diff commands (network devices need PTY)
-        let output = crate::utils::ssh_run_interactive_async(&device.ip, &ssh_user, &ssh_pass, &diff_payload)
-            .await
-            .map_err(|e| anyhow::anyhow!(e))?;
+        // The device's own view of the change, from the vendor's diff_command when it has one
+        let device_output = match vendor.filter(|v| !v.diff_command.is_empty()) {
+            Some(vendor) => {
+                // Strip "end" lines that would exit config mode entirely since the diff_command
+                // wrapper manages the session lifecycle (e.g., show session-config diffs / abort for Arista)
+                let config_for_diff: String = rendered_config
+                    .lines()
+                    .filter(|line| !line.trim().eq_ignore_ascii_case("end"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let diff_payload = vendor.diff_command.replace("{CONFIG}", &config_for_diff);
+
+                // Use interactive shell for multi-line diff commands (network devices need PTY)
+                crate::utils::ssh_run_interactive_async(&device.ip, &ssh_user, &ssh_pass, &diff_payload)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))?
+            }
+            None => String::new(),
+        };
 
-        Ok(output)
+        Ok(serde_json::to_string(&DiffJobOutput { diff, device_output })?)
     }
 
     async fn execute_apply_template_job(&self, job: &Job) -> Result<String> {
@@ -475,26 +818,18 @@ impl JobService {
             return Err(anyhow::anyhow!("No template ID specified in apply_template job"));
         };
 
-        let device = self.store.get_device(job.device_id).await?
-            .ok_or_else(|| anyhow::anyhow!("Device not found: {}", job.device_id))?;
+        let device = self.load_job_device(job).await?;
 
         let template = self.store.get_template(template_id).await?
             .ok_or_else(|| anyhow::anyhow!("Template not found: {}", template_id))?;
 
         let settings = self.store.get_settings().await?;
 
-        // Look up role-specific template by name convention
-        let role_template = if let Some(ref role) = device.topology_role {
-            let capitalized_role = format!("{}{}", &role[..1].to_uppercase(), &role[1..]);
-            let role_name = if template.name.ends_with(" Default") {
-                format!("{} {}", template.name.trim_end_matches(" Default"), capitalized_role)
-            } else {
-                format!("{} {}", template.name, capitalized_role)
-            };
-            self.store.get_template_by_name(&role_name).await.ok().flatten()
-        } else {
-            None
-        };
+        // Role templates (layers) from the device role's explicit template mapping
+        let role_templates = self
+            .store
+            .resolve_role_templates(&template, device.vendor.as_deref(), device.topology_role.as_deref())
+            .await?;
 
         let vars = self
             .store
@@ -504,29 +839,24 @@ impl JobService {
 
         let port_assignments = self.store.list_port_assignments(device.id).await.unwrap_or_default();
 
-        let rendered_config = render_config(&device, &template, &settings, role_template.as_ref(), &vars, Some(&port_assignments))?;
+        let rendered_config = self.render_cache.render(&device, &template, &settings, &role_templates, &vars, Some(&port_assignments))?;
+        let rendered_config = self.store.apply_device_config_snippets(device.id, rendered_config).await?;
 
-        let (mut ssh_user, mut ssh_pass) = crate::utils::resolve_ssh_credentials(&self.store, device.ssh_user.clone(), device.ssh_pass.clone(), device.vendor.as_deref()).await;
-
-        // Override with job-specific credential if set
-        if !job.credential_id.is_empty() {
-            if let Ok(cred_id) = job.credential_id.parse::<i64>() {
-                if let Some(cred) = self.store.get_credential(cred_id).await? {
-                    if !cred.username.is_empty() { ssh_user = cred.username; }
-                    if !cred.password.is_empty() { ssh_pass = cred.password; }
-                }
-            }
-        }
-
-        if ssh_user.is_empty() || ssh_pass.is_empty() {
-            return Err(anyhow::anyhow!("No SSH credentials available for this device"));
-        }
+        let (ssh_user, ssh_pass) = self.resolve_job_credentials(job, &device).await?;
 
         let vendor = match device.vendor.as_deref() {
             Some(v) if !v.is_empty() => self.store.resolve_vendor(v).await.ok().flatten(),
             _ => None,
         };
 
+        if vendor.as_ref().is_some_and(|v| v.transport == vendor_transport::NETCONF) {
+            let output = crate::utils::netconf_deploy(&device.ip, &ssh_user, &ssh_pass, &rendered_config)
+                .await
+                .map_err(|e| anyhow::anyhow!("NETCONF deploy failed: {}", e))?;
+            self.mark_device_online(job, device.id).await;
+            return Ok(output);
+        }
+
         let has_deploy_command = vendor.as_ref().map_or(false, |v| !v.deploy_command.is_empty());
 
         let deploy_payload = if let Some(ref v) = vendor {
@@ -550,7 +880,7 @@ impl JobService {
         };
 
         // Update device's config_template to the applied template
-        let _ = self.store.update_device_status(device.id, device_status::ONLINE).await;
+        self.mark_device_online(job, device.id).await;
 
         Ok(output)
     }
@@ -631,6 +961,11 @@ impl JobService {
         if let Some(ref hub) = self.ws_hub {
             if let Ok(Some(job)) = self.store.get_job(job_id).await {
                 hub.broadcast_job_update(event_type, &job).await;
+                if !job.batch_id.is_empty() {
+                    if let Ok(Some(batch)) = self.store.get_job_batch(&job.batch_id).await {
+                        hub.broadcast_job_batch_progress(&batch).await;
+                    }
+                }
             }
         }
     }
@@ -641,26 +976,91 @@ pub fn render_config(
     device: &Device,
     template: &Template,
     settings: &Settings,
-    role_template: Option<&Template>,
+    role_templates: &[Template],
     vars: &std::collections::HashMap<String, String>,
     port_assignments: Option<&[PortAssignment]>,
 ) -> Result<String> {
     let tera_content = crate::utils::convert_go_template_to_tera(&template.content);
 
-    let mut tera = Tera::default();
+    let mut tera = crate::utils::template_engine();
     tera.add_raw_template("device", &tera_content)
         .map_err(|e| anyhow::anyhow!("Invalid template: {}", e))?;
 
-    // Register role template as "role" so the device template can {% include "role" %}
-    if let Some(role_tmpl) = role_template {
+    // Role templates are layers: {% include "role" %} renders all of them in sort order,
+    // and each layer can also be included on its own by template name
+    let mut role_layers = Vec::with_capacity(role_templates.len());
+    for role_tmpl in role_templates {
         let role_content = crate::utils::convert_go_template_to_tera(&role_tmpl.content);
-        tera.add_raw_template("role", &role_content)
-            .map_err(|e| anyhow::anyhow!("Invalid role template: {}", e))?;
-    } else {
-        tera.add_raw_template("role", "")
-            .map_err(|e| anyhow::anyhow!("Failed to add empty role template: {}", e))?;
+        if role_tmpl.name != "device" && role_tmpl.name != "role" {
+            tera.add_raw_template(&role_tmpl.name, &role_content)
+                .map_err(|e| anyhow::anyhow!("Invalid role template '{}': {}", role_tmpl.name, e))?;
+        }
+        role_layers.push(role_content);
     }
+    tera.add_raw_template("role", &role_layers.join("\n"))
+        .map_err(|e| anyhow::anyhow!("Invalid role templates: {}", e))?;
+
+    let context = build_render_context(device, settings, vars, port_assignments);
+
+    tera.render("device", &context)
+        .map_err(|e| anyhow::anyhow!("Template rendering failed: {}", e))
+}
+
+/// Resolve a device's template (its own, else its vendor's default), role layers, variables and
+/// port assignments, render through the cache, and apply the device's config snippets
+pub async fn render_device<S: RenderStore + ?Sized>(store: &S, cache: &RenderCache, device: &Device) -> Result<(Template, String)> {
+    let template = resolve_device_template(store, device).await?;
+    let rendered = render_device_with_template(store, cache, device, &template).await?;
+    Ok((template, rendered))
+}
 
+/// The device's own template, else its vendor's default
+pub async fn resolve_device_template<S: RenderStore + ?Sized>(store: &S, device: &Device) -> Result<Template> {
+    let template_id = if !device.config_template.is_empty() {
+        device.config_template.parse::<i64>()
+            .map_err(|_| anyhow::anyhow!("Invalid template ID: {}", device.config_template))?
+    } else if let Some(vendor) = match device.vendor.as_deref() {
+        Some(v) if !v.is_empty() => store.resolve_vendor(v).await?,
+        _ => None,
+    } {
+        if vendor.default_template.is_empty() {
+            return Err(anyhow::anyhow!("Device has no template and vendor has no default template"));
+        }
+        vendor.default_template.parse::<i64>()
+            .map_err(|_| anyhow::anyhow!("Invalid default template ID: {}", vendor.default_template))?
+    } else {
+        return Err(anyhow::anyhow!("Device has no template assigned and no vendor to infer from"));
+    };
+
+    store.get_template(template_id).await?
+        .ok_or_else(|| anyhow::anyhow!("Template not found: {}", template_id))
+}
+
+/// Render `template` for a device with its role layers, variables, port assignments and config snippets
+pub async fn render_device_with_template<S: RenderStore + ?Sized>(
+    store: &S,
+    cache: &RenderCache,
+    device: &Device,
+    template: &Template,
+) -> Result<String> {
+    let settings = store.get_settings().await?;
+    let role_templates = store
+        .resolve_role_templates(template, device.vendor.as_deref(), device.topology_role.as_deref())
+        .await?;
+    let vars = store.resolve_device_variables_flat(device.id).await.unwrap_or_default();
+    let port_assignments = store.list_port_assignments(device.id).await.unwrap_or_default();
+
+    let rendered = cache.render(device, template, &settings, &role_templates, &vars, Some(&port_assignments))?;
+    store.apply_device_config_snippets(device.id, rendered).await
+}
+
+/// Build the Tera context shared by every template render.
+pub fn build_render_context(
+    device: &Device,
+    settings: &Settings,
+    vars: &std::collections::HashMap<String, String>,
+    port_assignments: Option<&[PortAssignment]>,
+) -> Context {
     let mut context = Context::new();
     context.insert("Hostname", &device.hostname);
     context.insert("MAC", &device.mac.clone().unwrap_or_default());
@@ -673,29 +1073,30 @@ pub fn render_config(
     context.insert("Subnet", &settings.dhcp_subnet);
     context.insert("Gateway", &settings.dhcp_gateway);
     context.insert("vars", vars);
+    context.insert("custom_fields", &device.custom_fields);
 
     // Build VRF context from port assignments
     // VRFs = list of unique VRFs with their interfaces
     // Each VRF: { id, name, rd, interfaces: [{ port_name, remote_device, remote_port }] }
+    // Ordered by VRF id so renders are deterministic
     if let Some(assignments) = port_assignments {
-        let mut vrf_map: HashMap<String, serde_json::Value> = HashMap::new();
+        let mut vrf_map: std::collections::BTreeMap<i64, serde_json::Value> = std::collections::BTreeMap::new();
         for pa in assignments {
             if let Some(vrf_id) = pa.vrf_id {
-                let vrf_key = vrf_id.to_string();
                 let iface = serde_json::json!({
                     "port_name": pa.port_name,
                     "remote_device": pa.remote_device_hostname.clone().unwrap_or_default(),
                     "remote_port": pa.remote_port_name,
                     "description": pa.description.clone().unwrap_or_default(),
                 });
-                if let Some(existing) = vrf_map.get_mut(&vrf_key) {
+                if let Some(existing) = vrf_map.get_mut(&vrf_id) {
                     if let Some(arr) = existing.get_mut("interfaces").and_then(|v| v.as_array_mut()) {
                         arr.push(iface);
                     }
                 } else {
-                    vrf_map.insert(vrf_key.clone(), serde_json::json!({
+                    vrf_map.insert(vrf_id, serde_json::json!({
                         "id": vrf_id,
-                        "name": pa.vrf_name.clone().unwrap_or(vrf_key),
+                        "name": pa.vrf_name.clone().unwrap_or_else(|| vrf_id.to_string()),
                         "interfaces": [iface],
                     }));
                 }
@@ -708,8 +1109,7 @@ pub fn render_config(
         context.insert("VRFs", &empty);
     }
 
-    tera.render("device", &context)
-        .map_err(|e| anyhow::anyhow!("Template rendering failed: {}", e))
+    context
 }
 
 /// Variable substitution for webhook URLs/bodies.
@@ -737,3 +1137,54 @@ fn substitute_device_vars(template: &str, device: &Device) -> String {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memory::MemoryStore;
+
+    fn seed(store: &MemoryStore) -> Device {
+        let base = store.add_template(&serde_json::from_value(serde_json::json!({
+            "name": "base",
+            "content": "hostname {{ Hostname }}\nntp {{ vars.ntp }}\n{% include \"role\" %}",
+        })).unwrap());
+        let spine = store.add_template(&serde_json::from_value(serde_json::json!({
+            "name": "spine-bgp",
+            "content": "router bgp {{ vars.asn }}",
+        })).unwrap());
+        let vendor = store.add_vendor(&serde_json::from_value(serde_json::json!({
+            "name": "Arista",
+            "default_template": base.id.to_string(),
+        })).unwrap());
+        let device = store.add_device(&serde_json::from_value(serde_json::json!({
+            "hostname": "spine1",
+            "vendor": vendor.id.to_string(),
+            "topology_role": "spine",
+        })).unwrap());
+        store.set_role_templates("spine", vec![spine.id]);
+        store.set_device_variables(device.id, [
+            ("ntp".to_string(), "10.0.0.1".to_string()),
+            ("asn".to_string(), "65001".to_string()),
+        ].into());
+        device
+    }
+
+    #[tokio::test]
+    async fn test_render_device_uses_vendor_default_and_role_layers() {
+        let store = MemoryStore::new();
+        let device = seed(&store);
+
+        let (template, config) = render_device(&store, &RenderCache::new(), &device).await.unwrap();
+        assert_eq!(template.name, "base");
+        assert_eq!(config, "hostname spine1\nntp 10.0.0.1\nrouter bgp 65001");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_device_template_without_vendor() {
+        let store = MemoryStore::new();
+        let device = store.add_device(&serde_json::from_value(serde_json::json!({ "hostname": "leaf1" })).unwrap());
+
+        let err = resolve_device_template(&store, &device).await.unwrap_err();
+        assert!(err.to_string().contains("no vendor"));
+    }
+}
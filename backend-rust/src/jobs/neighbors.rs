@@ -0,0 +1,59 @@
+use anyhow::Result;
+
+use crate::models::*;
+
+use super::JobService;
+
+/// ARP and IPv6 neighbor table commands per vendor, matched on the vendor name.
+/// Unknown vendors fall back to the Linux `ip neigh` command (FRR, SONiC, Cumulus).
+fn neighbor_commands(vendor_name: &str) -> &'static [&'static str] {
+    let name = vendor_name.to_lowercase();
+    if name.contains("cisco") || name.contains("arista") {
+        &["show ip arp", "show ipv6 neighbors"]
+    } else if name.contains("juniper") {
+        &["show arp no-resolve", "show ipv6 neighbors"]
+    } else {
+        &["ip neigh show"]
+    }
+}
+
+impl JobService {
+    /// Collect the device's ARP/ND tables and reconcile them against IPAM
+    pub(super) async fn execute_neighbor_collect_job(&self, job: &Job) -> Result<String> {
+        let device = self.store.get_device(job.device_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Device not found: {}", job.device_id))?;
+
+        let (ssh_user, ssh_pass) = self.resolve_job_credentials(job, &device).await?;
+
+        let vendor_name = match device.vendor.as_deref() {
+            Some(v) if !v.is_empty() => self.store.resolve_vendor(v).await?
+                .map(|vendor| vendor.name)
+                .unwrap_or_else(|| v.to_string()),
+            _ => String::new(),
+        };
+
+        let mut entries = Vec::new();
+        let mut failures = Vec::new();
+        for command in neighbor_commands(&vendor_name) {
+            match crate::utils::ssh_run_command_async(&device.ip, &ssh_user, &ssh_pass, command).await {
+                Ok(output) => entries.extend(crate::utils::parse_neighbor_table(&output)),
+                Err(e) => failures.push(format!("{}: {}", command, e)),
+            }
+        }
+
+        // Only fail when nothing could be collected; a missing IPv6 table is common
+        if entries.is_empty() && !failures.is_empty() {
+            return Err(anyhow::anyhow!("Neighbor collection failed: {}", failures.join("; ")));
+        }
+
+        let summary = self.store.reconcile_neighbor_entries(device.id, &entries).await?;
+        let mut output = format!(
+            "Collected {} neighbor entries: {} managed in IPAM, {} discovered in network",
+            summary.entries, summary.managed, summary.discovered
+        );
+        for failure in failures {
+            output.push_str(&format!("\nwarning: {}", failure));
+        }
+        Ok(output)
+    }
+}
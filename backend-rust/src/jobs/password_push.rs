@@ -0,0 +1,133 @@
+use anyhow::Result;
+
+use crate::models::*;
+
+use super::JobService;
+
+/// Shown in place of the password in job output and errors
+const MASK: &str = "********";
+
+/// Check a credential can be pushed: the username and password are written into device CLI
+/// and shell commands, so neither may carry line breaks or other control characters
+pub fn validate_password_push(username: &str, password: &str) -> Result<(), &'static str> {
+    let username_ok = !username.is_empty()
+        && !username.starts_with('-')
+        && username.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '@'));
+    if !username_ok {
+        return Err("username may only contain letters, digits, '.', '_', '-' and '@'");
+    }
+    if password.chars().any(char::is_control) {
+        return Err("password must not contain line breaks or control characters");
+    }
+    Ok(())
+}
+
+/// Quote a value as a single shell word
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// How a password change command is sent to the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SshRunner {
+    /// A single exec, for a shell command line
+    Exec,
+    /// Typed into an interactive shell, for network CLIs that only enter config mode there
+    Interactive,
+}
+
+/// Vendor command that sets a user's password on the device, and how to send it
+fn password_change_command(vendor_name: &str, username: &str, password: &str) -> Result<(SshRunner, String)> {
+    validate_password_push(username, password).map_err(|e| anyhow::anyhow!(e))?;
+    let name = vendor_name.to_lowercase();
+    let command = if name.contains("cisco") || name.contains("arista") {
+        let script = format!("configure terminal\nusername {} secret {}\nend\nwrite memory", username, password);
+        (SshRunner::Interactive, script)
+    } else if name.contains("juniper") {
+        let script = format!(
+            "configure\nset system login user {} authentication plain-text-password-value \"{}\"\ncommit and-quit",
+            username,
+            password.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        (SshRunner::Interactive, script)
+    } else {
+        let line = format!("printf '%s\\n' {} | sudo chpasswd", shell_quote(&format!("{}:{}", username, password)));
+        (SshRunner::Exec, line)
+    };
+    Ok(command)
+}
+
+impl JobService {
+    /// Set the job credential's current password on the device, logging in with the password it
+    /// replaced. The password is read from the credential here rather than stored with the job,
+    /// so it never reaches the job's command, output or events.
+    pub(super) async fn execute_password_push_job(&self, job: &Job) -> Result<String> {
+        let credential_id = job.credential_id.parse::<i64>()
+            .map_err(|_| anyhow::anyhow!("Password push job has no credential"))?;
+        let credential = self.store.get_credential(credential_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Credential not found: {}", credential_id))?;
+
+        let device = self.load_job_device(job).await?;
+        let vendor_name = match device.vendor.as_deref() {
+            Some(v) if !v.is_empty() => self.store.resolve_vendor(v).await?
+                .map(|vendor| vendor.name)
+                .unwrap_or_else(|| v.to_string()),
+            _ => String::new(),
+        };
+        let (runner, command) = password_change_command(&vendor_name, &credential.username, &credential.password)?;
+        let (ssh_user, ssh_pass) = self.resolve_job_credentials(job, &device).await?;
+
+        let mask = |text: String| {
+            if credential.password.is_empty() { text } else { text.replace(&credential.password, MASK) }
+        };
+        let output = match runner {
+            SshRunner::Exec => crate::utils::ssh_run_command_async(&device.ip, &ssh_user, &ssh_pass, &command).await,
+            SshRunner::Interactive => {
+                crate::utils::ssh_run_interactive_async(&device.ip, &ssh_user, &ssh_pass, &command).await
+            }
+        };
+        output
+            .map(mask)
+            .map_err(|e| anyhow::anyhow!(mask(e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_password_push() {
+        assert!(validate_password_push("admin", "s3cret 'x\"").is_ok());
+        assert!(validate_password_push("ops.user@lab", "pw").is_ok());
+        assert!(validate_password_push("", "pw").is_err());
+        assert!(validate_password_push("-admin", "pw").is_err());
+        assert!(validate_password_push("admin\nusername evil", "pw").is_err());
+        assert!(validate_password_push("ad min", "pw").is_err());
+        assert!(validate_password_push("admin", "pw\nusername evil secret x").is_err());
+        assert!(validate_password_push("admin", "pw\r").is_err());
+    }
+
+    #[test]
+    fn test_password_change_command_quoting() {
+        assert_eq!(
+            password_change_command("Ubuntu", "admin", "it's").unwrap().1,
+            r#"printf '%s\n' 'admin:it'\''s' | sudo chpasswd"#
+        );
+        assert_eq!(
+            password_change_command("Juniper", "admin", r#"a"b\"#).unwrap().1,
+            "configure\nset system login user admin authentication plain-text-password-value \"a\\\"b\\\\\"\ncommit and-quit"
+        );
+        assert!(password_change_command("Arista", "admin", "x\nusername evil secret y").is_err());
+    }
+
+    #[test]
+    fn test_password_change_runner() {
+        let runner = |vendor| password_change_command(vendor, "admin", "pw").unwrap().0;
+        assert_eq!(runner("Arista"), SshRunner::Interactive);
+        assert_eq!(runner("Cisco"), SshRunner::Interactive);
+        assert_eq!(runner("Juniper"), SshRunner::Interactive);
+        assert_eq!(runner("FRR"), SshRunner::Exec);
+        assert_eq!(runner(""), SshRunner::Exec);
+    }
+}
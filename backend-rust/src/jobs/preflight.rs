@@ -0,0 +1,168 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::time::{Duration, Instant};
+
+use crate::db::Store;
+use crate::models::*;
+
+use super::JobService;
+
+const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Commands the pre-flight checks run on a vendor's devices
+struct PreflightCommands {
+    clock: &'static str,
+    ntp: Option<&'static str>,
+    config_session: Option<&'static str>,
+}
+
+/// Pre-flight commands per vendor, matched on the vendor name
+fn preflight_commands(vendor_name: &str) -> PreflightCommands {
+    let name = vendor_name.to_lowercase();
+    if name.contains("arista") {
+        PreflightCommands { clock: "show clock", ntp: Some("show ntp status"), config_session: Some("show configuration sessions") }
+    } else if name.contains("cisco") {
+        PreflightCommands { clock: "show clock", ntp: Some("show ntp status"), config_session: Some("show configuration lock") }
+    } else if name.contains("juniper") {
+        PreflightCommands { clock: "show system uptime", ntp: Some("show ntp status"), config_session: None }
+    } else if matches!(name.as_str(), "opengear" | "raspberry pi" | "linux" | "frr" | "gobgp") {
+        PreflightCommands { clock: "date -u '+%Y-%m-%d %H:%M:%S UTC'", ntp: None, config_session: None }
+    } else {
+        PreflightCommands { clock: "show clock", ntp: Some("show ntp status"), config_session: None }
+    }
+}
+
+fn check(name: &str, status: &str, message: impl Into<String>) -> PreflightCheck {
+    PreflightCheck { name: name.to_string(), status: status.to_string(), message: message.into() }
+}
+
+/// Run the pre-flight checks on a device: SSH port reachability, login, config session
+/// availability and clock sanity. Checks after a failed reachability or login are skipped.
+pub async fn run_preflight(
+    store: &Store,
+    device: &Device,
+    ssh_user: &str,
+    ssh_pass: &str,
+    max_clock_skew_secs: i64,
+) -> Result<PreflightResult> {
+    let vendor_name = match device.vendor.as_deref() {
+        Some(v) if !v.is_empty() => store.resolve_vendor(v).await?
+            .map(|vendor| vendor.name)
+            .unwrap_or_else(|| v.to_string()),
+        _ => String::new(),
+    };
+    let commands = preflight_commands(&vendor_name);
+    let mut checks = Vec::new();
+
+    let addr = format!("{}:22", device.ip);
+    let started = Instant::now();
+    let reachable = match tokio::time::timeout(REACHABILITY_TIMEOUT, tokio::net::TcpStream::connect(&addr)).await {
+        Ok(Ok(_)) => {
+            checks.push(check(preflight_check::REACHABILITY, preflight_status::PASS,
+                format!("{} answered in {}ms", addr, started.elapsed().as_millis())));
+            true
+        }
+        Ok(Err(e)) => {
+            checks.push(check(preflight_check::REACHABILITY, preflight_status::FAIL, format!("{}: {}", addr, e)));
+            false
+        }
+        Err(_) => {
+            checks.push(check(preflight_check::REACHABILITY, preflight_status::FAIL,
+                format!("{}: no answer within {}s", addr, REACHABILITY_TIMEOUT.as_secs())));
+            false
+        }
+    };
+
+    let mut to_run = vec![commands.clock];
+    to_run.extend(commands.ntp);
+    to_run.extend(commands.config_session);
+    let outputs = if !reachable {
+        Err("device unreachable".to_string())
+    } else if ssh_user.is_empty() || ssh_pass.is_empty() {
+        checks.push(check(preflight_check::CREDENTIALS, preflight_status::FAIL, "No SSH credentials available for this device"));
+        Err("no SSH credentials".to_string())
+    } else {
+        match crate::utils::ssh_run_commands_async(&device.ip, ssh_user, ssh_pass, &to_run).await {
+            Ok(outputs) => {
+                checks.push(check(preflight_check::CREDENTIALS, preflight_status::PASS, format!("logged in as {}", ssh_user)));
+                Ok(outputs)
+            }
+            Err(e) => {
+                checks.push(check(preflight_check::CREDENTIALS, preflight_status::FAIL, e));
+                Err("SSH login failed".to_string())
+            }
+        }
+    };
+    let checked_at = Utc::now();
+
+    match outputs {
+        Err(reason) => {
+            for name in [preflight_check::CREDENTIALS, preflight_check::CONFIG_SESSION, preflight_check::CLOCK] {
+                if !checks.iter().any(|c| c.name == name) {
+                    checks.push(check(name, preflight_status::SKIPPED, reason.clone()));
+                }
+            }
+        }
+        Ok(outputs) => {
+            let output = |command: &str| {
+                to_run.iter().position(|c| *c == command).and_then(|i| outputs[i].as_deref())
+            };
+
+            checks.push(match commands.config_session {
+                None => check(preflight_check::CONFIG_SESSION, preflight_status::SKIPPED,
+                    format!("no config session check for vendor '{}'", vendor_name)),
+                Some(command) => match output(command).map(crate::utils::parse_config_session_status) {
+                    None => check(preflight_check::CONFIG_SESSION, preflight_status::WARN, format!("'{}' failed", command)),
+                    Some(None) => check(preflight_check::CONFIG_SESSION, preflight_status::WARN,
+                        format!("unrecognized '{}' output", command)),
+                    Some(Some((true, message))) => check(preflight_check::CONFIG_SESSION, preflight_status::PASS, message),
+                    Some(Some((false, message))) => check(preflight_check::CONFIG_SESSION, preflight_status::FAIL, message),
+                },
+            });
+
+            let clock = output(commands.clock).and_then(crate::utils::parse_device_clock);
+            let ntp_synced = commands.ntp.and_then(output).and_then(crate::utils::parse_ntp_synchronized);
+            checks.push(match clock {
+                None => check(preflight_check::CLOCK, preflight_status::WARN,
+                    format!("could not read a UTC time from '{}'", commands.clock)),
+                Some(device_time) => {
+                    let offset = (device_time - checked_at).num_seconds();
+                    let skew = format!("clock {}s {}", offset.abs(), if offset < 0 { "behind" } else { "ahead" });
+                    if offset.abs() > max_clock_skew_secs {
+                        check(preflight_check::CLOCK, preflight_status::FAIL,
+                            format!("{}, more than the allowed {}s", skew, max_clock_skew_secs))
+                    } else if ntp_synced == Some(false) {
+                        check(preflight_check::CLOCK, preflight_status::WARN, format!("{}, NTP not synchronized", skew))
+                    } else if ntp_synced == Some(true) {
+                        check(preflight_check::CLOCK, preflight_status::PASS, format!("{}, NTP synchronized", skew))
+                    } else {
+                        check(preflight_check::CLOCK, preflight_status::PASS, skew)
+                    }
+                }
+            });
+        }
+    }
+
+    Ok(PreflightResult {
+        device_id: device.id,
+        hostname: device.hostname.clone(),
+        go: checks.iter().all(|c| c.status != preflight_status::FAIL),
+        checks,
+        checked_at,
+    })
+}
+
+impl JobService {
+    /// Run the pre-flight checks with the job's credentials. The job fails on a no-go; missing
+    /// credentials fail the credentials check rather than the job.
+    pub(super) async fn execute_preflight_job(&self, job: &Job) -> Result<String> {
+        let device = self.load_job_device(job).await?;
+        let settings = self.store.get_settings().await?;
+        let (ssh_user, ssh_pass) = self.resolve_job_credentials(job, &device).await.unwrap_or_default();
+        let result = run_preflight(&self.store, &device, &ssh_user, &ssh_pass, settings.preflight_max_clock_skew_secs as i64).await?;
+        if !result.go {
+            return Err(anyhow::anyhow!("Pre-flight no-go: {}", result.failures()));
+        }
+        Ok(serde_json::to_string_pretty(&result)?)
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::models::*;
+
+use super::{build_render_context, render_config};
+
+/// RenderCache keeps the last rendered config per device, reused while none of its inputs change.
+///
+/// The key hashes the render context (device fields, settings, resolved variables and VRFs) together
+/// with the id and updated_at of the base and role templates. Hashing the context rather than the
+/// device's updated_at keeps entries valid across status/backup bookkeeping updates that don't
+/// affect the rendered output.
+#[derive(Default)]
+pub struct RenderCache {
+    entries: Mutex<HashMap<i64, (u64, String)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RenderCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render a device config, returning the cached output when the inputs are unchanged
+    pub fn render(
+        &self,
+        device: &Device,
+        template: &Template,
+        settings: &Settings,
+        role_templates: &[Template],
+        vars: &HashMap<String, String>,
+        port_assignments: Option<&[PortAssignment]>,
+    ) -> Result<String> {
+        let key = Self::key(device, template, settings, role_templates, vars, port_assignments);
+
+        if let Some((cached_key, rendered)) = self.lock().get(&device.id) {
+            if *cached_key == key {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(rendered.clone());
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let rendered = render_config(device, template, settings, role_templates, vars, port_assignments)?;
+        self.lock().insert(device.id, (key, rendered.clone()));
+        Ok(rendered)
+    }
+
+    /// Drop the cached render for one device
+    pub fn invalidate_device(&self, device_id: i64) {
+        self.lock().remove(&device_id);
+    }
+
+    /// Drop every cached render
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    pub fn stats(&self) -> RenderCacheStats {
+        RenderCacheStats {
+            entries: self.lock().len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<i64, (u64, String)>> {
+        // A panic while holding the lock can't leave the map inconsistent, so recover from poisoning
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn key(
+        device: &Device,
+        template: &Template,
+        settings: &Settings,
+        role_templates: &[Template],
+        vars: &HashMap<String, String>,
+        port_assignments: Option<&[PortAssignment]>,
+    ) -> u64 {
+        let context = build_render_context(device, settings, vars, port_assignments).into_json();
+
+        let mut hasher = DefaultHasher::new();
+        context.to_string().hash(&mut hasher);
+        for tmpl in std::iter::once(template).chain(role_templates) {
+            tmpl.id.hash(&mut hasher);
+            tmpl.updated_at.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
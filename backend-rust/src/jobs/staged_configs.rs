@@ -0,0 +1,103 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::*;
+use crate::ws::EventType;
+
+use super::JobService;
+
+const STAGED_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+impl JobService {
+    /// Queue staged_deploy jobs for staged configs whose activation time has passed. A config
+    /// that requires a maintenance window waits until one covers its device.
+    pub fn start_staged_config_scheduler(self: &Arc<Self>) {
+        let svc = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(STAGED_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                if svc.maintenance_mode.is_enabled() {
+                    continue;
+                }
+                if let Err(e) = svc.activate_due_staged_configs(Utc::now()).await {
+                    tracing::warn!("Staged config scheduler: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn activate_due_staged_configs(&self, now: DateTime<Utc>) -> Result<()> {
+        let due = self.store.list_due_staged_configs(now).await?;
+        if due.is_empty() {
+            return Ok(());
+        }
+        let windows = self.store.list_maintenance_windows_overlapping(now, now + chrono::Duration::seconds(1)).await?;
+
+        for staged in due {
+            if staged.require_maintenance_window {
+                let group_ids: Vec<i64> = self.store.list_device_groups(staged.device_id).await?
+                    .iter()
+                    .map(|g| g.id)
+                    .collect();
+                if !windows.iter().any(|w| w.applies_to(staged.device_id, &group_ids)) {
+                    continue;
+                }
+            }
+
+            let job_id = uuid::Uuid::new_v4().to_string();
+            // Claim it first, so a cancel or edit racing the scheduler either wins or fails
+            if !self.store.transition_scheduled_staged_config(staged.id, staged_config_status::ACTIVATING, Some(&job_id)).await? {
+                continue;
+            }
+            let req = CreateJobRequest {
+                device_id: staged.device_id,
+                job_type: job_type::STAGED_DEPLOY.to_string(),
+                command: staged.id.to_string(),
+                credential_id: String::new(),
+                triggered_by: TRIGGERED_BY_STAGED_CONFIG.to_string(),
+                timeout_secs: None,
+            };
+            let job = match self.store.create_job(&job_id, &req).await {
+                Ok(job) => job,
+                Err(e) => {
+                    self.store.finish_staged_config(staged.id, staged_config_status::FAILED, &format!("failed to queue job: {}", e)).await?;
+                    continue;
+                }
+            };
+            tracing::info!("Activating staged config {} on {} (job {})", staged.id, staged.hostname, job_id);
+            if let Err(e) = self.store.record_change(&NewChangeLogEntry::deploy(&job), &staged.created_by).await {
+                tracing::warn!("Failed to record deploy change: {}", e);
+            }
+            if let Some(ref hub) = self.ws_hub {
+                hub.broadcast_job_update(EventType::JobQueued, &job).await;
+            }
+            self.submit(job_id).await;
+        }
+        Ok(())
+    }
+
+    /// Push a staged config and record the outcome on it
+    pub(super) async fn execute_staged_deploy_job(&self, job: &Job) -> Result<String> {
+        let id: i64 = job.command.parse()
+            .map_err(|_| anyhow::anyhow!("Invalid staged config ID: {}", job.command))?;
+        let staged = self.store.get_staged_config(id).await?
+            .ok_or_else(|| anyhow::anyhow!("Staged config not found: {}", id))?;
+
+        let result = async {
+            let device = self.load_job_device(job).await?;
+            let settings = self.store.get_settings().await?;
+            self.push_config(job, &device, &settings, staged.config.clone()).await
+        }
+        .await;
+
+        let (status, error) = match &result {
+            Ok(_) => (staged_config_status::ACTIVATED, String::new()),
+            Err(e) => (staged_config_status::FAILED, e.to_string()),
+        };
+        self.store.finish_staged_config(id, status, &error).await?;
+        result
+    }
+}
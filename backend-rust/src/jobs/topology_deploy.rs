@@ -0,0 +1,132 @@
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::*;
+use crate::ws::EventType;
+
+use super::JobService;
+
+/// How often a running stage checks on its jobs
+const STAGE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+impl JobService {
+    /// Run a topology deploy in the background
+    pub fn start_topology_deploy(self: &Arc<Self>, deploy_id: i64) {
+        let svc = self.clone();
+        tokio::spawn(async move {
+            let (status, error) = match svc.run_topology_deploy(deploy_id).await {
+                Ok(()) => (topology_deploy_status::COMPLETED, String::new()),
+                Err(e) => (topology_deploy_status::FAILED, e.to_string()),
+            };
+            tracing::info!("Topology deploy {} {}{}", deploy_id, status, if error.is_empty() { String::new() } else { format!(": {}", error) });
+            if let Err(e) = svc.store.finish_topology_deploy(deploy_id, status, &error).await {
+                tracing::error!("Failed to finish topology deploy {}: {}", deploy_id, e);
+            }
+        });
+    }
+
+    /// Fail deploys a previous process left running. Their queued jobs are re-queued like any
+    /// other stuck job, but later stages are never started.
+    pub(super) async fn fail_interrupted_topology_deploys(&self) {
+        match self.store.fail_running_topology_deploys("interrupted by a restart").await {
+            Ok(0) => {}
+            Ok(n) => tracing::warn!("Marked {} interrupted topology deploys as failed", n),
+            Err(e) => tracing::error!("Failed to fail interrupted topology deploys: {}", e),
+        }
+    }
+
+    /// Deploy each stage in turn. A stage passes when every device's deploy job, and its
+    /// verification job when the deploy has a verify command, completed; otherwise the
+    /// deploy stops there.
+    async fn run_topology_deploy(&self, deploy_id: i64) -> Result<()> {
+        let deploy = self.store.get_topology_deploy(deploy_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Topology deploy {} not found", deploy_id))?;
+        let planned = self.store.list_topology_deploy_devices(deploy_id).await?;
+        let triggered_by = if deploy.lab { TRIGGERED_BY_LAB } else { TRIGGERED_BY_TOPOLOGY_DEPLOY };
+
+        for (stage, roles) in deploy.stages.iter().enumerate() {
+            let stage = stage as i64;
+            let label = format!("stage {} ({})", stage + 1, roles.join(", "));
+            let devices: Vec<&TopologyDeployDevice> = planned.iter().filter(|d| d.stage == stage).collect();
+            if devices.is_empty() {
+                continue;
+            }
+            self.store.set_topology_deploy_stage(deploy_id, stage).await?;
+            tracing::info!("Topology deploy {}: {}, {} devices", deploy_id, label, devices.len());
+
+            let mut jobs = Vec::with_capacity(devices.len());
+            for device in &devices {
+                let job = self.queue_stage_job(device.device_id, job_type::DEPLOY, "", triggered_by).await?;
+                self.store.set_topology_deploy_job(deploy_id, device.device_id, &job.id).await?;
+                let mut entry = NewChangeLogEntry::deploy(&job);
+                entry.summary = format!("deploy job queued by topology deploy #{}", deploy_id);
+                if let Err(e) = self.store.record_change(&entry, &deploy.triggered_by).await {
+                    tracing::warn!("Failed to record deploy change: {}", e);
+                }
+                jobs.push((device.hostname.as_str(), job.id));
+            }
+            let failed = self.wait_for_jobs(&jobs).await?;
+            if !failed.is_empty() {
+                return Err(anyhow::anyhow!("{}: deploy failed on {}", label, failed.join(", ")));
+            }
+
+            if deploy.verify_command.is_empty() {
+                continue;
+            }
+            let mut jobs = Vec::with_capacity(devices.len());
+            for device in &devices {
+                let job = self.queue_stage_job(device.device_id, job_type::COMMAND, &deploy.verify_command, triggered_by).await?;
+                self.store.set_topology_verify_job(deploy_id, device.device_id, &job.id).await?;
+                jobs.push((device.hostname.as_str(), job.id));
+            }
+            let failed = self.wait_for_jobs(&jobs).await?;
+            if !failed.is_empty() {
+                return Err(anyhow::anyhow!("{}: verification failed on {}", label, failed.join(", ")));
+            }
+        }
+        Ok(())
+    }
+
+    async fn queue_stage_job(&self, device_id: i64, job_type: &str, command: &str, triggered_by: &str) -> Result<Job> {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let req = CreateJobRequest {
+            device_id,
+            job_type: job_type.to_string(),
+            command: command.to_string(),
+            credential_id: String::new(),
+            triggered_by: triggered_by.to_string(),
+            timeout_secs: None,
+        };
+        let job = self.store.create_job(&job_id, &req).await?;
+        if let Some(ref hub) = self.ws_hub {
+            hub.broadcast_job_update(EventType::JobQueued, &job).await;
+        }
+        self.submit(job_id).await;
+        Ok(job)
+    }
+
+    /// Wait for every (hostname, job_id) to finish, returning the hostnames whose job failed or
+    /// was cancelled
+    async fn wait_for_jobs<'a>(&self, jobs: &[(&'a str, String)]) -> Result<Vec<&'a str>> {
+        loop {
+            let mut failed = Vec::new();
+            let mut pending = false;
+            for (hostname, job_id) in jobs {
+                match self.store.get_job(job_id).await?.map(|j| j.status) {
+                    Some(status) if status == job_status::COMPLETED => {}
+                    Some(status) if status == job_status::FAILED || status == job_status::CANCELLED => {
+                        failed.push(*hostname)
+                    }
+                    Some(_) => pending = true,
+                    // Deleted jobs can't be checked
+                    None => failed.push(*hostname),
+                }
+            }
+            if !pending {
+                return Ok(failed);
+            }
+            tokio::time::sleep(STAGE_POLL_INTERVAL).await;
+        }
+    }
+}
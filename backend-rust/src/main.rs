@@ -1,8 +1,10 @@
 mod auth;
 mod backup;
+mod cli;
 mod config;
 mod db;
 mod dhcp;
+mod gnmi;
 mod handlers;
 mod jobs;
 mod models;
@@ -10,6 +12,8 @@ mod netbox;
 mod router;
 mod services;
 mod status;
+mod syslog;
+mod tftp;
 mod utils;
 mod ws;
 
@@ -21,8 +25,15 @@ use backup::BackupService;
 use config::Config;
 use db::Store;
 use dhcp::{ConfigManager, LeaseWatcher};
-use jobs::JobService;
+use gnmi::GnmiManager;
+use jobs::{JobService, RenderCache};
+use services::config_history::ConfigHistory;
+use services::maintenance_mode::MaintenanceMode;
+use services::runtime_config::RuntimeConfig;
+use services::tls::TlsManager;
 use status::StatusChecker;
+use syslog::SyslogReceiver;
+use tftp::TftpServer;
 use ws::Hub;
 
 /// Application state shared across handlers
@@ -33,7 +44,14 @@ pub struct AppState {
     pub ws_hub: Option<Arc<Hub>>,
     pub backup_service: Option<Arc<BackupService>>,
     pub job_service: Option<Arc<JobService>>,
+    pub render_cache: Arc<RenderCache>,
+    pub maintenance_mode: Arc<MaintenanceMode>,
     pub lease_watcher: Option<Arc<tokio::sync::RwLock<LeaseWatcher>>>,
+    pub syslog_receiver: Option<Arc<SyslogReceiver>>,
+    pub gnmi: Arc<GnmiManager>,
+    pub tls: Option<Arc<TlsManager>>,
+    pub runtime_config: Arc<RuntimeConfig>,
+    pub config_history: Option<Arc<ConfigHistory>>,
 }
 
 impl AppState {
@@ -45,17 +63,33 @@ impl AppState {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if cli::is_command(&args) {
+        // Keep stdout for command output
+        tracing_subscriber::fmt()
+            .with_env_filter(tracing_subscriber::EnvFilter::new("warn"))
+            .with_writer(std::io::stderr)
+            .init();
+        if let Err(e) = cli::run(args).await {
+            eprintln!("error: {:#}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Load configuration
+    let mut cfg = Config::load()?;
+
+    // Initialize tracing; the filter can be swapped by a config reload
+    let (log_filter, log_filter_handle) = tracing_subscriber::reload::Layer::new(
+        tracing_subscriber::EnvFilter::try_new(&cfg.log_filter)
+            .unwrap_or_else(|_| "forge_config=info,tower_http=debug".into()),
+    );
     tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "forge_config=info,tower_http=debug".into()),
-        )
+        .with(log_filter)
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    // Load configuration
-    let mut cfg = Config::load();
     if cfg.jwt_secret.is_empty() {
         tracing::warn!("JWT_SECRET not set - generating random secret (tokens will be invalidated on restart)");
         cfg.jwt_secret = uuid::Uuid::new_v4().to_string();
@@ -64,11 +98,50 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Database: {}", cfg.db_path);
     tracing::info!("TFTP Dir: {}", cfg.tftp_dir);
     tracing::info!("Listen: {}", cfg.listen_addr);
+    if !cfg.config_file.is_empty() {
+        tracing::info!("Config file: {}", cfg.config_file);
+    }
+    let runtime_config = RuntimeConfig::new(cfg.clone(), log_filter_handle);
+    runtime_config.start_signal_handler();
+
+    // Shared SSH sessions for backups, jobs and probes
+    utils::init_ssh_pool(
+        cfg.ssh_max_concurrency,
+        cfg.ssh_max_per_device,
+        cfg.ssh_pool_idle_secs,
+        cfg.ssh_breaker_threshold,
+        cfg.ssh_breaker_cooldown_secs,
+    );
+    tracing::info!(
+        "SSH pool initialized (max_concurrency={}, max_per_device={}, idle_secs={}, breaker_threshold={})",
+        cfg.ssh_max_concurrency, cfg.ssh_max_per_device, cfg.ssh_pool_idle_secs, cfg.ssh_breaker_threshold
+    );
 
     // Initialize database
     let store = Store::with_pool_size(&cfg.db_path, cfg.db_max_connections).await?;
     tracing::info!("Database initialized (pool_size={})", cfg.db_max_connections);
 
+    // Report orphaned rows left behind by earlier deletes (cleanup is on demand)
+    match store.check_integrity().await {
+        Ok(report) if report.total_orphans > 0 => {
+            for issue in &report.issues {
+                tracing::warn!("Integrity check: {} {} ({})", issue.count, issue.description, issue.check);
+            }
+            tracing::warn!("Found {} orphaned rows; see /api/system/integrity", report.total_orphans);
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Integrity check failed: {}", e),
+    }
+
+    // Restore maintenance mode so a freeze survives restarts
+    let maintenance_mode = MaintenanceMode::new(store.get_maintenance_mode().await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load maintenance mode: {}", e);
+        Default::default()
+    }));
+    if maintenance_mode.is_enabled() {
+        tracing::warn!("Maintenance mode is enabled - mutating API calls and background automation are paused");
+    }
+
     // Initialize DHCP config manager
     let config_manager = ConfigManager::new(
         store.clone(),
@@ -78,20 +151,58 @@ async fn main() -> anyhow::Result<()> {
         cfg.dnsmasq_pid.clone(),
         cfg.dhcp_interface.clone(),
         cfg.lease_path.clone(),
-    );
+    )
+    .with_builtin_tftp(!cfg.tftp_listen_addr.is_empty())
+    .with_ztp_secret(cfg.jwt_secret.clone());
 
     // Initialize WebSocket hub
     let ws_hub = Arc::new(Hub::new());
+    ws_hub.forward_ssh_breakers();
+
+    // Open the Git history of templates and backups, when enabled
+    let config_history = ConfigHistory::from_config(&cfg).await;
 
     // Initialize backup service
-    let backup_service = BackupService::new(store.clone(), cfg.backup_dir.clone());
+    let backup_service = BackupService::new(
+        store.clone(),
+        cfg.backup_dir.clone(),
+        Some(ws_hub.clone()),
+        config_history.clone(),
+    );
 
     // Initialize job service
-    let job_service = JobService::new(store.clone(), Some(ws_hub.clone()));
+    let render_cache = Arc::new(RenderCache::new());
+    let job_service = JobService::new(
+        store.clone(),
+        Some(ws_hub.clone()),
+        render_cache.clone(),
+        maintenance_mode.clone(),
+        Some(backup_service.clone()),
+        cfg.jobs_concurrency,
+        cfg.jobs_queue_capacity,
+    );
 
     // Start job template scheduler
     job_service.start_scheduler();
 
+    // Push staged configs at their activation time
+    job_service.start_staged_config_scheduler();
+
+    // Run automation rules on hub events
+    services::automation::AutomationEngine::start(store.clone(), &ws_hub, job_service.clone(), maintenance_mode.clone());
+
+    // Send scheduled fleet reports
+    services::fleet_report::start_scheduler(store.clone(), cfg.clone(), maintenance_mode.clone());
+
+    // Notify ahead of support contract expiry
+    services::contract_expiry::start_scheduler(store.clone(), cfg.clone(), maintenance_mode.clone());
+
+    // Evaluate metric alert rules
+    services::alerting::start_scheduler(store.clone(), cfg.clone(), maintenance_mode.clone());
+
+    // Push metrics to the time-series database
+    services::metrics_export::start(store.clone(), cfg.clone(), maintenance_mode.clone(), job_service.clone());
+
     // Initialize lease watcher
     let mut lease_watcher = LeaseWatcher::new(cfg.lease_path.clone());
 
@@ -99,10 +210,12 @@ async fn main() -> anyhow::Result<()> {
     let backup_svc_clone = backup_service.clone();
     let store_clone = store.clone();
     let ws_hub_clone = ws_hub.clone();
+    let maintenance_clone = maintenance_mode.clone();
 
     lease_watcher.add_callback(Arc::new(move |lease| {
         services::lease_handler::on_lease_event(
             store_clone.clone(),
+            maintenance_clone.clone(),
             backup_svc_clone.clone(),
             ws_hub_clone.clone(),
             lease.clone(),
@@ -112,17 +225,48 @@ async fn main() -> anyhow::Result<()> {
     lease_watcher.start();
     let lease_watcher = Arc::new(tokio::sync::RwLock::new(lease_watcher));
 
+    // Start scheduled database VACUUM/ANALYZE
+    services::db_maintenance::start_scheduler(store.clone(), maintenance_mode.clone());
+
+    // Start monthly partitioning of the history tables
+    services::partitions::start_scheduler(store.clone(), maintenance_mode.clone());
+
+    // Start writing API usage counts
+    services::api_usage::start_flusher(store.clone(), maintenance_mode.clone());
+
     // Initialize status checker
-    let mut status_checker = StatusChecker::new(store.clone(), 60);
+    let mut status_checker = StatusChecker::new(store.clone(), runtime_config.clone(), maintenance_mode.clone(), ws_hub.clone());
     status_checker.start();
 
+    // Start syslog receiver
+    let syslog_receiver = if cfg.syslog_listen_addr.is_empty() {
+        None
+    } else {
+        let receiver = SyslogReceiver::new(store.clone(), Some(ws_hub.clone()));
+        receiver.start(&cfg.syslog_listen_addr).await;
+        Some(receiver)
+    };
+
+    // Open gNMI telemetry streams
+    let gnmi = GnmiManager::new(store.clone(), Some(ws_hub.clone()), maintenance_mode.clone());
+    gnmi.start().await;
+
+    // Start the built-in TFTP server
+    if !cfg.tftp_listen_addr.is_empty() {
+        TftpServer::new(store.clone(), &cfg.tftp_dir, &cfg.lease_path, Some(ws_hub.clone()))
+            .start(&cfg.tftp_listen_addr)
+            .await;
+    }
+
+    // Prepare the HTTPS listener's certificate
+    let tls = TlsManager::from_config(&cfg).await?;
+
     // Start discovery cleanup task (removes items not seen in 5 minutes)
     {
         let store_cleanup = store.clone();
+        let runtime_cleanup = runtime_config.clone();
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
             loop {
-                interval.tick().await;
                 match store_cleanup.cleanup_stale_discovered_devices().await {
                     Ok(count) if count > 0 => {
                         tracing::info!("Cleaned up {} stale discovered devices", count);
@@ -132,6 +276,7 @@ async fn main() -> anyhow::Result<()> {
                     }
                     _ => {}
                 }
+                tokio::time::sleep(runtime_cleanup.discovery_cleanup_interval()).await;
             }
         });
     }
@@ -152,19 +297,46 @@ async fn main() -> anyhow::Result<()> {
         ws_hub: Some(ws_hub.clone()),
         backup_service: Some(backup_service),
         job_service: Some(job_service),
+        render_cache,
+        maintenance_mode,
         lease_watcher: Some(lease_watcher),
+        syslog_receiver,
+        gnmi,
+        tls: tls.clone(),
+        runtime_config,
+        config_history,
     });
 
     // Build router
     let app = router::build(state, &cfg.frontend_dir);
 
-    // Start server
-    let listener = tokio::net::TcpListener::bind(&cfg.listen_addr).await?;
-    tracing::info!("ForgeConfig listening on {}", cfg.listen_addr);
+    // Start servers
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let servers: Vec<_> = services::listeners::open(&cfg.listen_addr)
+        .await?
+        .into_iter()
+        .map(|listener| tokio::spawn(services::listeners::serve(listener, app.clone(), shutdown.clone())))
+        .collect();
+
+    let tls_handle = match &tls {
+        Some(tls) => {
+            let handle = tls.serve(app.clone())?;
+            tls.start_renewal();
+            Some(handle)
+        }
+        None => None,
+    };
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    shutdown_signal().await;
+    shutdown.cancel();
+    if let Some(handle) = &tls_handle {
+        TlsManager::shutdown(handle);
+    }
+    for server in servers {
+        if let Err(e) = server.await? {
+            tracing::error!("Server error: {}", e);
+        }
+    }
 
     tracing::info!("ForgeConfig shutting down");
     Ok(())
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// Metrics an alert rule can watch. Each is evaluated per subject and compared with the
+/// rule's threshold; the alert is raised when the value exceeds it.
+pub mod alert_metric {
+    /// Hours since a device's last backup; devices never backed up count from their creation
+    pub const BACKUP_AGE_HOURS: &str = "backup_age_hours";
+    /// Interface mismatches between a device's port assignments and its collected interfaces
+    pub const INTERFACE_DRIFT: &str = "interface_drift";
+    /// A device's online/offline transitions over the last 24 hours
+    pub const STATUS_CHANGES_PER_DAY: &str = "status_changes_per_day";
+    /// Percentage of an IPAM prefix taken by child prefixes and addresses
+    pub const PREFIX_UTILIZATION: &str = "prefix_utilization";
+
+    pub const ALL: &[&str] = &[BACKUP_AGE_HOURS, INTERFACE_DRIFT, STATUS_CHANGES_PER_DAY, PREFIX_UTILIZATION];
+
+    /// The kind of subject the metric is measured on
+    pub fn subject_type(metric: &str) -> &'static str {
+        match metric {
+            PREFIX_UTILIZATION => super::alert_subject_type::PREFIX,
+            _ => super::alert_subject_type::DEVICE,
+        }
+    }
+}
+
+pub mod alert_subject_type {
+    pub const DEVICE: &str = "device";
+    pub const PREFIX: &str = "prefix";
+}
+
+pub mod alert_status {
+    /// Threshold exceeded, waiting out the rule's for_minutes
+    pub const PENDING: &str = "pending";
+    pub const FIRING: &str = "firing";
+    /// Firing, and someone is on it; not notified again until it resolves
+    pub const ACKNOWLEDGED: &str = "acknowledged";
+    pub const RESOLVED: &str = "resolved";
+
+    pub const ALL: &[&str] = &[PENDING, FIRING, ACKNOWLEDGED, RESOLVED];
+}
+
+/// Dedupe key of a rule's alert on a subject: one unresolved alert exists per key
+pub fn alert_dedupe_key(rule_id: i64, subject_type: &str, subject_id: i64) -> String {
+    format!("{}:{}:{}", rule_id, subject_type, subject_id)
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// AlertRule raises an alert for every subject whose `metric` exceeds `threshold` for at least
+/// `for_minutes`, and notifies `channel_ids` when it fires and when it resolves
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertRule {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub metric: String,
+    pub threshold: f64,
+    pub for_minutes: i64,
+    pub channel_ids: Vec<i64>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateAlertRuleRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub metric: String,
+    pub threshold: f64,
+    #[serde(default)]
+    pub for_minutes: i64,
+    #[serde(default)]
+    pub channel_ids: Vec<i64>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl Validate for CreateAlertRuleRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.required("metric", &self.metric);
+        v.one_of("metric", &self.metric, alert_metric::ALL);
+        v.check(self.threshold.is_finite() && self.threshold >= 0.0, "threshold", "must be a non-negative number");
+        v.min("for_minutes", self.for_minutes, 0);
+    }
+}
+
+/// Alert is one rule exceeded by one subject, from the first evaluation that saw it until the
+/// first that didn't or until someone resolves it
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub id: i64,
+    pub dedupe_key: String,
+    pub rule_id: i64,
+    pub rule_name: String,
+    pub metric: String,
+    pub threshold: f64,
+    pub subject_type: String,
+    pub subject_id: i64,
+    pub subject_name: String,
+    /// Latest evaluated value
+    pub value: f64,
+    pub status: String,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fired_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub acknowledged_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    /// Username the alert is assigned to
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub assigned_to: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Who resolved it by hand; empty when it resolved on its own
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub resolved_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssignAlertRequest {
+    /// Username to assign to; empty or omitted unassigns
+    #[serde(default)]
+    pub assigned_to: String,
+}
+
+fn default_alert_limit() -> i64 {
+    200
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlertQuery {
+    #[serde(default)]
+    pub rule_id: Option<i64>,
+    /// One of alert_status; omitted lists every unresolved alert
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub assigned_to: Option<String>,
+    #[serde(default = "default_alert_limit")]
+    pub limit: i64,
+}
+
+/// Result of one evaluation pass over the enabled rules
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AlertEvaluation {
+    pub rules: usize,
+    pub opened: usize,
+    pub fired: usize,
+    pub resolved: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
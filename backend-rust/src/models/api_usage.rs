@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Request counts and bytes transferred
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApiUsageTotals {
+    pub requests: i64,
+    /// Responses with a 4xx or 5xx status
+    pub errors: i64,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+}
+
+impl ApiUsageTotals {
+    pub fn add(&mut self, other: &ApiUsageTotals) {
+        self.requests += other.requests;
+        self.errors += other.errors;
+        self.request_bytes += other.request_bytes;
+        self.response_bytes += other.response_bytes;
+    }
+}
+
+/// One consumer's requests to one endpoint class within an hour
+#[derive(Debug, Clone)]
+pub struct ApiUsageEntry {
+    /// Start of the hour
+    pub hour: DateTime<Utc>,
+    pub username: String,
+    /// Short hash identifying the login token
+    pub token_id: String,
+    pub token_issued_at: DateTime<Utc>,
+    /// First path segment after /api/, e.g. `devices`
+    pub resource: String,
+    /// `read` (GET, HEAD, OPTIONS) or `write`
+    pub access: String,
+    pub totals: ApiUsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiTokenUsage {
+    pub token_id: String,
+    pub issued_at: DateTime<Utc>,
+    /// Start of the last hour the token was used in
+    pub last_used_hour: DateTime<Utc>,
+    #[serde(flatten)]
+    pub totals: ApiUsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiEndpointUsage {
+    pub resource: String,
+    pub access: String,
+    #[serde(flatten)]
+    pub totals: ApiUsageTotals,
+}
+
+/// A user's API usage, broken down by login token and endpoint class, busiest first
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiConsumerUsage {
+    pub username: String,
+    #[serde(flatten)]
+    pub totals: ApiUsageTotals,
+    pub tokens: Vec<ApiTokenUsage>,
+    pub endpoints: Vec<ApiEndpointUsage>,
+}
+
+/// ApiUsageReport covers the hours from `since`, busiest consumer first
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiUsageReport {
+    pub since: DateTime<Utc>,
+    pub hours: i64,
+    pub consumers: Vec<ApiConsumerUsage>,
+}
+
+fn default_api_usage_hours() -> i64 {
+    24
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiUsageQuery {
+    /// Hours to report, counting the current one (default 24)
+    #[serde(default = "default_api_usage_hours")]
+    pub hours: i64,
+    #[serde(default)]
+    pub username: Option<String>,
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+/// Variable values as strings, accepting the numbers and booleans a YAML document writes unquoted
+fn scalar_vars<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<BTreeMap<String, String>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Scalar {
+        String(String),
+        Integer(i64),
+        Float(f64),
+        Bool(bool),
+    }
+    let vars: Option<BTreeMap<String, Scalar>> = Option::deserialize(deserializer)?;
+    Ok(vars.map(|vars| {
+        vars.into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Scalar::String(s) => s,
+                    Scalar::Integer(n) => n.to_string(),
+                    Scalar::Float(n) => n.to_string(),
+                    Scalar::Bool(b) => b.to_string(),
+                };
+                (key, value)
+            })
+            .collect()
+    }))
+}
+
+/// DesiredState is a declarative document of devices and groups to reconcile the database to.
+/// An omitted section (or field) is left unmanaged; a present one is authoritative.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DesiredState {
+    #[serde(default)]
+    pub groups: Option<Vec<DesiredGroup>>,
+    #[serde(default)]
+    pub devices: Option<Vec<DesiredDevice>>,
+    /// Delete devices and groups that are missing from their section
+    #[serde(default)]
+    pub prune: bool,
+}
+
+/// DesiredGroup is a group by name; `parent` names another group
+#[derive(Debug, Clone, Deserialize)]
+pub struct DesiredGroup {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub precedence: Option<i32>,
+    #[serde(default, deserialize_with = "scalar_vars")]
+    pub vars: Option<BTreeMap<String, String>>,
+}
+
+/// DesiredDevice is a device by hostname; `template` and `groups` are referenced by name
+#[derive(Debug, Clone, Deserialize)]
+pub struct DesiredDevice {
+    pub hostname: String,
+    #[serde(default)]
+    pub ip: Option<String>,
+    #[serde(default)]
+    pub mac: Option<String>,
+    #[serde(default)]
+    pub vendor: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub serial_number: Option<String>,
+    #[serde(default)]
+    pub topology_role: Option<String>,
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub groups: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "scalar_vars")]
+    pub vars: Option<BTreeMap<String, String>>,
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApplyQuery {
+    /// Only return the plan; pass `dry_run=false` to commit it
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+}
+
+/// ApplyPlan lists what reconciling to a DesiredState changes
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApplyPlan {
+    pub dry_run: bool,
+    pub groups: ApplyResourcePlan,
+    pub devices: ApplyResourcePlan,
+    /// What the commit wrote; set when `dry_run` is false
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub committed: Option<ApplyCommitted>,
+    /// Why the commit stopped part way; `committed` lists what was written before it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ApplyPlan {
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty() && self.devices.is_empty()
+    }
+}
+
+/// ApplyResourcePlan holds the names to create, update and delete for one resource type
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApplyResourcePlan {
+    pub create: Vec<String>,
+    pub update: Vec<ApplyUpdate>,
+    pub delete: Vec<String>,
+    pub unchanged: usize,
+}
+
+impl ApplyResourcePlan {
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.update.is_empty() && self.delete.is_empty()
+    }
+}
+
+/// ApplyUpdate names the fields that differ, e.g. `ip`, `groups` or `vars.ntp_server`
+#[derive(Debug, Clone, Serialize)]
+pub struct ApplyUpdate {
+    pub name: String,
+    pub changes: Vec<String>,
+}
+
+/// ApplyCommitted lists the names a commit wrote, per resource type. Changes are written one at a
+/// time, so a commit that fails part way leaves the earlier ones in place.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApplyCommitted {
+    pub groups: ApplyCommittedResource,
+    pub devices: ApplyCommittedResource,
+}
+
+/// ApplyCommittedResource holds the names created, updated and deleted for one resource type.
+/// `failed` names the entry being written when the commit stopped, which may be partly applied.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApplyCommittedResource {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub deleted: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed: Option<String>,
+}
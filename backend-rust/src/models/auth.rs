@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
 
 /// User represents an authenticated user
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +23,13 @@ pub struct CreateUserRequest {
     pub enabled: bool,
 }
 
+impl Validate for CreateUserRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("username", &self.username);
+        v.required("password", &self.password);
+    }
+}
+
 /// Request to update an existing user
 #[derive(Debug, Clone, Deserialize)]
 pub struct UpdateUserRequest {
@@ -32,6 +40,12 @@ pub struct UpdateUserRequest {
     pub enabled: bool,
 }
 
+impl Validate for UpdateUserRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("username", &self.username);
+    }
+}
+
 fn default_enabled() -> bool {
     true
 }
@@ -57,4 +71,43 @@ pub struct Claims {
     pub username: String,
     pub exp: usize,
     pub iat: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonation: Option<Impersonation>,
+}
+
+/// Marks a token an admin generated to act as another user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Impersonation {
+    /// Username of the admin who generated the token
+    pub by: String,
+    /// Only GET and HEAD requests are allowed
+    pub read_only: bool,
+}
+
+fn default_impersonation_minutes() -> i64 {
+    30
+}
+
+fn default_read_only() -> bool {
+    true
+}
+
+/// Request to generate a token that acts as another user
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImpersonateRequest {
+    #[serde(default = "default_impersonation_minutes")]
+    pub ttl_minutes: i64,
+    #[serde(default = "default_read_only")]
+    pub read_only: bool,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpersonationTokenResponse {
+    pub token: String,
+    pub username: String,
+    pub impersonated_by: String,
+    pub read_only: bool,
+    pub expires_at: DateTime<Utc>,
 }
@@ -0,0 +1,151 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use super::{Validate, Validator};
+
+/// Hub events an automation rule can trigger on; `*` matches any of them
+pub mod automation_event {
+    pub const ANY: &str = "*";
+
+    pub const ALL: &[&str] = &[
+        "device_discovered",
+        "device_online",
+        "device_offline",
+        "config_pulled",
+        "job_queued",
+        "job_started",
+        "job_completed",
+        "job_failed",
+        "job_cancelled",
+        "syslog_alert",
+    ];
+
+    pub fn is_valid(event_type: &str) -> bool {
+        event_type == ANY || ALL.contains(&event_type)
+    }
+}
+
+/// What a rule does when it fires
+pub mod automation_action {
+    /// Queue a job template's jobs
+    pub const JOB_TEMPLATE: &str = "job_template";
+    /// POST the event to a URL
+    pub const WEBHOOK: &str = "webhook";
+    /// Set a variable on the event's device or a group
+    pub const SET_VARIABLE: &str = "set_variable";
+
+    pub const ALL: &[&str] = &[JOB_TEMPLATE, WEBHOOK, SET_VARIABLE];
+}
+
+pub mod automation_execution_status {
+    pub const SUCCESS: &str = "success";
+    pub const FAILED: &str = "failed";
+}
+
+/// triggered_by value for jobs queued by an automation rule; their events don't trigger rules
+pub const TRIGGERED_BY_AUTOMATION: &str = "automation";
+
+fn default_true() -> bool {
+    true
+}
+
+/// AutomationRule runs `action` when a hub event of `event_type` whose payload matches `filter`
+/// occurs. Filter keys are payload fields (dotted for nested objects) compared as strings.
+#[derive(Debug, Clone, Serialize)]
+pub struct AutomationRule {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub event_type: String,
+    pub filter: BTreeMap<String, String>,
+    pub action_type: String,
+    pub action: serde_json::Value,
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateAutomationRuleRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub event_type: String,
+    #[serde(default)]
+    pub filter: BTreeMap<String, String>,
+    pub action_type: String,
+    #[serde(default)]
+    pub action: serde_json::Value,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl Validate for CreateAutomationRuleRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.check(
+            automation_event::is_valid(&self.event_type),
+            "event_type",
+            format!("must be {} or one of {}", automation_event::ANY, automation_event::ALL.join(", ")),
+        );
+    }
+}
+
+/// Settings of a job_template action
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobTemplateAutomationAction {
+    pub job_template_id: i64,
+    /// Run against the event's device instead of the template's targets
+    #[serde(default)]
+    pub event_device: bool,
+}
+
+fn default_webhook_method() -> String {
+    "POST".to_string()
+}
+
+/// Settings of a webhook action; the event is sent as the JSON body
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookAutomationAction {
+    pub url: String,
+    #[serde(default = "default_webhook_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+/// Settings of a set_variable action. `{{field}}` in the value is replaced with the payload
+/// field; the variable is set on `group_id` when given, otherwise on the event's device.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetVariableAutomationAction {
+    pub key: String,
+    pub value: String,
+    #[serde(default)]
+    pub group_id: Option<i64>,
+}
+
+/// AutomationExecution records one firing of a rule
+#[derive(Debug, Clone, Serialize)]
+pub struct AutomationExecution {
+    pub id: i64,
+    pub rule_id: i64,
+    pub event_type: String,
+    pub event_payload: serde_json::Value,
+    pub status: String,
+    pub output: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+fn default_execution_limit() -> i64 {
+    100
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AutomationExecutionQuery {
+    #[serde(default = "default_execution_limit")]
+    pub limit: i64,
+}
@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// BGP session status values in the fabric session matrix
+pub mod bgp_session_status {
+    /// Expected and established
+    pub const UP: &str = "up";
+    /// Expected, configured on the device, but not established
+    pub const DOWN: &str = "down";
+    /// Expected but not configured on the device
+    pub const MISSING: &str = "missing";
+    /// Configured on the device but not in the topology data
+    pub const UNEXPECTED: &str = "unexpected";
+    /// Expected, but the device's BGP summary couldn't be collected
+    pub const UNKNOWN: &str = "unknown";
+}
+
+/// BgpSummaryEntry is one neighbor parsed from `show ip bgp summary` style output
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BgpSummaryEntry {
+    pub neighbor: String,
+    pub remote_asn: Option<i64>,
+    /// Session state as reported (`Established`, `Active`, `Idle`, ...)
+    pub state: String,
+    pub established: bool,
+    pub prefixes_received: Option<i64>,
+    pub up_down: String,
+}
+
+/// BgpMatrixDevice reports whether a fabric member's BGP summary was collected
+#[derive(Debug, Clone, Serialize)]
+pub struct BgpMatrixDevice {
+    pub device_id: i64,
+    pub hostname: String,
+    pub topology_role: String,
+    pub collected: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// BgpSession is one expected or observed session of a fabric member
+#[derive(Debug, Clone, Serialize)]
+pub struct BgpSession {
+    pub device_id: i64,
+    pub hostname: String,
+    pub peer_ip: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_device_id: Option<i64>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub peer_hostname: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_asn: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_asn: Option<i64>,
+    pub status: String,
+    /// Raw session state from the device, empty when missing or unknown
+    pub state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefixes_received: Option<i64>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub up_down: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BgpMatrixSummary {
+    pub up: usize,
+    pub down: usize,
+    pub missing: usize,
+    pub unexpected: usize,
+    pub unknown: usize,
+}
+
+/// BgpSessionMatrix is the fabric-wide BGP session health of a topology
+#[derive(Debug, Clone, Serialize)]
+pub struct BgpSessionMatrix {
+    pub topology_id: i64,
+    pub collected_at: DateTime<Utc>,
+    pub devices: Vec<BgpMatrixDevice>,
+    pub sessions: Vec<BgpSession>,
+    /// hostname -> peer (hostname, else IP) -> worst status of their sessions
+    pub matrix: BTreeMap<String, BTreeMap<String, String>>,
+    pub summary: BgpMatrixSummary,
+}
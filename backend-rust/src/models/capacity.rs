@@ -0,0 +1,60 @@
+use serde::Serialize;
+
+/// What-if capacity report of a topology: port budget and oversubscription per role, and what
+/// adding leaves would take. Computed from the topology's devices, their models' port layouts
+/// and port assignments; nothing is changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct TopologyCapacity {
+    pub topology_id: i64,
+    pub roles: Vec<RoleCapacity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leaf_expansion: Option<LeafExpansion>,
+}
+
+/// Port budget and oversubscription of one topology role
+#[derive(Debug, Clone, Serialize)]
+pub struct RoleCapacity {
+    pub role: String,
+    pub devices: usize,
+    /// Devices whose model has no port layout, so their ports aren't counted
+    pub devices_without_layout: usize,
+    /// Data ports in the devices' models (management, console and power ports excluded)
+    pub ports_total: usize,
+    /// Ports with a port assignment
+    pub ports_used: usize,
+    pub ports_free: usize,
+    /// Speed of the ports assigned to the tier above (spines for leaves, and so on), in Mbps
+    pub uplink_mbps: i64,
+    /// Speed of every other data port, in Mbps
+    pub downlink_mbps: i64,
+    /// downlink_mbps / uplink_mbps; absent for the top tier or when nothing is uplinked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oversubscription: Option<f64>,
+    /// The role's most oversubscribed device and its ratio
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worst_hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worst_oversubscription: Option<f64>,
+}
+
+/// Impact of adding leaves cabled like the existing ones
+#[derive(Debug, Clone, Serialize)]
+pub struct LeafExpansion {
+    pub add_leaves: usize,
+    /// Spines each existing leaf connects to, on average
+    pub spines_per_leaf: usize,
+    /// Links from a leaf to each of its spines, on average
+    pub links_per_spine: usize,
+    /// Spine ports the new leaves take, which is also the number of new /31 links
+    pub spine_ports_required: usize,
+    pub spine_ports_free: usize,
+    /// Leaf uplink ports each new leaf needs
+    pub leaf_uplinks_required: usize,
+    pub new_p2p_links: usize,
+    /// ASNs the new leaves would get, after the highest leaf ASN in use
+    pub asns: Vec<u32>,
+    /// Whether the spines have the free ports and the ASNs stay in the private range
+    pub fits: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Canonical change log categories
+pub mod change_category {
+    pub const DEPLOY: &str = "deploy";
+    pub const TEMPLATE: &str = "template";
+    pub const VARIABLE: &str = "variable";
+    pub const SETTINGS: &str = "settings";
+    pub const AUTH: &str = "auth";
+}
+
+/// Canonical change log actions
+pub mod change_action {
+    pub const CREATE: &str = "create";
+    pub const UPDATE: &str = "update";
+    pub const DELETE: &str = "delete";
+    pub const DEPLOY: &str = "deploy";
+}
+
+/// ChangeLogEntry is one recorded change, with the device and group names resolved
+/// and, for deploys, the current status of the deploy job
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeLogEntry {
+    pub id: i64,
+    pub category: String,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: String,
+    pub resource_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_name: Option<String>,
+    pub username: String,
+    pub summary: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_status: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// NewChangeLogEntry is a change to record; the username is filled in from the request's user
+#[derive(Debug, Clone, Default)]
+pub struct NewChangeLogEntry {
+    pub category: &'static str,
+    pub action: &'static str,
+    pub resource_type: &'static str,
+    pub resource_id: String,
+    pub resource_name: String,
+    pub device_id: Option<i64>,
+    pub group_id: Option<i64>,
+    pub summary: String,
+}
+
+fn default_changelog_limit() -> i64 {
+    100
+}
+
+/// Filters for the change log feed. `group_id` matches changes to the group itself
+/// and to devices that are currently direct members of it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChangeLogQuery {
+    #[serde(default)]
+    pub device_id: Option<i64>,
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default = "default_changelog_limit")]
+    pub limit: i64,
+}
+
+impl NewChangeLogEntry {
+    /// Entry for a variable change; callers scope it with `device_id` or `group_id`.
+    /// Values are left out since variables may hold secrets.
+    pub fn variable(action: &'static str, key: &str, summary: String) -> Self {
+        Self {
+            category: change_category::VARIABLE,
+            action,
+            resource_type: "variable",
+            resource_name: key.to_string(),
+            summary,
+            ..Default::default()
+        }
+    }
+
+    /// Entry for a queued deploy or apply_template job; the feed reports the job's live status
+    pub fn deploy(job: &super::Job) -> Self {
+        Self {
+            category: change_category::DEPLOY,
+            action: change_action::DEPLOY,
+            resource_type: "job",
+            resource_id: job.id.clone(),
+            resource_name: job.command.clone(),
+            device_id: (job.device_id != 0).then_some(job.device_id),
+            group_id: None,
+            summary: format!("{} job queued", job.job_type),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+/// Canonical config section kinds
+pub mod config_section_kind {
+    pub const INTERFACES: &str = "interfaces";
+    pub const BGP: &str = "bgp";
+    pub const ROUTING: &str = "routing";
+    pub const SWITCHING: &str = "switching";
+    pub const ACL: &str = "acl";
+    pub const SYSTEM: &str = "system";
+    pub const OTHER: &str = "other";
+}
+
+/// How a config dialect nests statements
+pub mod config_block_style {
+    /// Cisco/Arista/FRR: stanzas open on an unindented line, children are indented
+    pub const INDENT: &str = "indent";
+    /// Junos curly-brace hierarchy
+    pub const BRACES: &str = "braces";
+    /// Flat `set ...` statements (Junos display set, VyOS)
+    pub const SET: &str = "set";
+}
+
+/// ConfigSyntax holds lexer hints for highlighting a vendor's config text
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigSyntax {
+    /// eos, ios, junos or generic
+    pub dialect: String,
+    pub block_style: String,
+    pub comment_prefixes: Vec<String>,
+    /// Prefix that negates a statement ("no " or "delete ")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub negation_prefix: Option<String>,
+    /// Statements that open a top-level section
+    pub keywords: Vec<String>,
+    /// Regex matching interface names
+    pub interface_pattern: String,
+}
+
+/// ConfigSection is a collapsible range of config lines. Lines are 1-based and inclusive.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigSection {
+    pub kind: String,
+    /// The line that opens the section, or its key for `set` configs
+    pub title: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ConfigSection>,
+}
+
+/// ConfigOutline is a config with its syntax hints and section structure
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigOutline {
+    /// rendered, backup or text
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_id: Option<i64>,
+    pub vendor: String,
+    pub syntax: ConfigSyntax,
+    pub line_count: usize,
+    pub sections: Vec<ConfigSection>,
+    pub content: String,
+}
+
+/// ConfigOutlineRequest outlines arbitrary config text, e.g. a template preview
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfigOutlineRequest {
+    #[serde(default)]
+    pub vendor: String,
+    pub content: String,
+}
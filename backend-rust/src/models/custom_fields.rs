@@ -0,0 +1,156 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use super::{Validate, Validator};
+
+/// Value types a custom field can hold
+pub mod custom_field_type {
+    pub const TEXT: &str = "text";
+    pub const INTEGER: &str = "integer";
+    pub const BOOLEAN: &str = "boolean";
+    pub const DATE: &str = "date";
+    pub const SELECT: &str = "select";
+
+    pub fn is_valid(field_type: &str) -> bool {
+        matches!(field_type, TEXT | INTEGER | BOOLEAN | DATE | SELECT)
+    }
+}
+
+/// CustomField is an operator-defined device attribute such as an asset tag or cost center
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomField {
+    pub id: i64,
+    pub name: String,
+    pub label: String,
+    pub description: String,
+    pub field_type: String,
+    /// Devices can't have the value cleared, and CSV imports must supply it for new devices
+    pub required: bool,
+    /// Allowed values of a select field
+    pub choices: Vec<String>,
+    /// Regex a text value must match (empty = any)
+    pub pattern: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_value: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn default_field_type() -> String {
+    custom_field_type::TEXT.to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateCustomFieldRequest {
+    pub name: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_field_type")]
+    pub field_type: String,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub choices: Vec<String>,
+    #[serde(default)]
+    pub pattern: String,
+    #[serde(default)]
+    pub min_value: Option<i64>,
+    #[serde(default)]
+    pub max_value: Option<i64>,
+}
+
+impl Validate for CreateCustomFieldRequest {
+    fn validate(&self, v: &mut Validator) {
+        let mut chars = self.name.chars();
+        let valid_name = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        v.check(valid_name, "name", "must start with a letter or underscore and contain only letters, digits and underscores");
+        v.check(
+            custom_field_type::is_valid(&self.field_type),
+            "field_type",
+            "must be one of text, integer, boolean, date, select",
+        );
+        if self.field_type == custom_field_type::SELECT && self.choices.is_empty() {
+            v.error("choices", "select fields need at least one choice");
+        }
+        if !self.pattern.is_empty() {
+            v.regex("pattern", &self.pattern);
+        }
+        if let (Some(min), Some(max)) = (self.min_value, self.max_value) {
+            v.check(min <= max, "min_value", "must not be greater than max_value");
+        }
+    }
+}
+
+impl CustomField {
+    /// Validate a JSON value (string, number or boolean) and convert it to its stored text form
+    pub fn normalize(&self, value: &serde_json::Value) -> Result<String, String> {
+        match value {
+            serde_json::Value::String(s) => self.parse_text(s),
+            serde_json::Value::Number(n) => self.parse_text(&n.to_string()),
+            serde_json::Value::Bool(b) => self.parse_text(&b.to_string()),
+            _ => Err(format!("{}: expected a string, number or boolean", self.name)),
+        }
+    }
+
+    /// Validate a text value (as entered in a CSV cell) and convert it to its stored text form
+    pub fn parse_text(&self, raw: &str) -> Result<String, String> {
+        let raw = raw.trim();
+        match self.field_type.as_str() {
+            custom_field_type::INTEGER => {
+                let n: i64 = raw
+                    .parse()
+                    .map_err(|_| format!("{}: '{}' is not an integer", self.name, raw))?;
+                if let Some(min) = self.min_value.filter(|min| n < *min) {
+                    return Err(format!("{}: {} is below the minimum of {}", self.name, n, min));
+                }
+                if let Some(max) = self.max_value.filter(|max| n > *max) {
+                    return Err(format!("{}: {} is above the maximum of {}", self.name, n, max));
+                }
+                Ok(n.to_string())
+            }
+            custom_field_type::BOOLEAN => match raw.to_ascii_lowercase().as_str() {
+                "true" | "yes" | "1" => Ok("true".to_string()),
+                "false" | "no" | "0" => Ok("false".to_string()),
+                _ => Err(format!("{}: '{}' is not a boolean", self.name, raw)),
+            },
+            custom_field_type::DATE => NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                .map(|d| d.to_string())
+                .map_err(|_| format!("{}: '{}' is not a YYYY-MM-DD date", self.name, raw)),
+            custom_field_type::SELECT => {
+                if self.choices.iter().any(|c| c == raw) {
+                    Ok(raw.to_string())
+                } else {
+                    Err(format!("{}: '{}' must be one of {}", self.name, raw, self.choices.join(", ")))
+                }
+            }
+            _ => {
+                if !self.pattern.is_empty() {
+                    let re = regex_lite::Regex::new(&self.pattern)
+                        .map_err(|e| format!("{}: invalid pattern: {}", self.name, e))?;
+                    if !re.is_match(raw) {
+                        return Err(format!("{}: '{}' does not match {}", self.name, raw, self.pattern));
+                    }
+                }
+                Ok(raw.to_string())
+            }
+        }
+    }
+}
+
+/// Values to set on a device, by field name. A null or empty value clears the field.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetCustomFieldValuesRequest {
+    pub values: BTreeMap<String, serde_json::Value>,
+}
+
+/// DeviceImportResult reports a device CSV import
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceImportResult {
+    pub created: usize,
+    pub updated: usize,
+}
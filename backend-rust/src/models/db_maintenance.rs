@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Kinds of database maintenance run
+pub mod db_maintenance_kind {
+    /// Online copy of the database to a timestamped file
+    pub const BACKUP: &str = "backup";
+    /// VACUUM followed by ANALYZE
+    pub const VACUUM: &str = "vacuum";
+}
+
+/// DbMaintenanceRun records one database backup or VACUUM/ANALYZE run
+#[derive(Debug, Clone, Serialize)]
+pub struct DbMaintenanceRun {
+    pub id: i64,
+    pub kind: String,
+    /// Backup file name, relative to the DB backup directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Backup file size, or the database size after a vacuum
+    pub size_bytes: i64,
+    pub duration_ms: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uploaded_to: Option<String>,
+    /// Set when the run (or its upload) failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub triggered_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// NewDbMaintenanceRun is a run to record
+#[derive(Debug, Clone, Default)]
+pub struct NewDbMaintenanceRun {
+    pub kind: &'static str,
+    pub filename: Option<String>,
+    pub size_bytes: i64,
+    pub duration_ms: i64,
+    pub uploaded_to: Option<String>,
+    pub error: Option<String>,
+    pub triggered_by: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DbBackupRequest {
+    /// Also upload the backup to the configured db_backup_upload_url
+    #[serde(default)]
+    pub upload: bool,
+}
+
+fn default_history_limit() -> i64 { 50 }
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DbMaintenanceQuery {
+    #[serde(default = "default_history_limit")]
+    pub limit: i64,
+}
+
+/// TablePartition is one month of a history table (jobs, syslog events, DHCP lease history or
+/// device status history), moved out of the live table by the monthly rollover
+#[derive(Debug, Clone, Serialize)]
+pub struct TablePartition {
+    pub id: i64,
+    pub table_name: String,
+    /// Table holding the month's rows, `{table_name}_p{YYYYMM}`
+    pub partition_name: String,
+    /// YYYY-MM
+    pub month: String,
+    pub row_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Result of one partition rollover
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PartitionRollover {
+    /// Rows moved out of the live tables
+    pub moved_rows: i64,
+    pub created: Vec<String>,
+    /// Partitions dropped for being older than partition_retention_months
+    pub dropped: Vec<String>,
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceModel {
@@ -26,6 +27,15 @@ pub struct CreateDeviceModelRequest {
     pub layout: String,
 }
 
+impl Validate for CreateDeviceModelRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required_id("vendor_id", self.vendor_id);
+        v.required("model", &self.model);
+        v.required("display_name", &self.display_name);
+        v.min("rack_units", self.rack_units.into(), 1);
+    }
+}
+
 fn default_rack_units() -> i32 {
     1
 }
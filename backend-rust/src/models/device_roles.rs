@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceRole {
@@ -27,3 +28,9 @@ pub struct CreateDeviceRoleRequest {
     #[serde(default)]
     pub group_names: Vec<String>,
 }
+
+impl Validate for CreateDeviceRoleRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+    }
+}
@@ -1,5 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use super::{Validate, Validator};
 
 /// Canonical device status values
 pub mod device_status {
@@ -24,8 +26,13 @@ pub struct Device {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub serial_number: Option<String>,
     pub config_template: String,
+    /// Stored credential used for SSH; takes priority over the inline ssh_user/ssh_pass
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_id: Option<i64>,
+    /// Deprecated: bind a credential with credential_id instead
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ssh_user: Option<String>,
+    /// Deprecated: bind a credential with credential_id instead
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ssh_pass: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -40,6 +47,12 @@ pub struct Device {
     pub rack_id: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rack_position: Option<i32>,
+    /// Tenant whose variables apply to this device
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<i64>,
+    /// GPU cluster whose variables apply to this device
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpu_cluster_id: Option<i64>,
     pub status: String, // online, offline, provisioning
     pub device_type: String, // internal, external
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -48,6 +61,9 @@ pub struct Device {
     pub last_backup: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_error: Option<String>,
+    /// Custom field values by field name, typed per the field definition
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom_fields: BTreeMap<String, serde_json::Value>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -69,7 +85,11 @@ pub struct CreateDeviceRequest {
     #[serde(default)]
     pub config_template: String,
     #[serde(default)]
+    pub credential_id: Option<i64>,
+    /// Deprecated: use credential_id
+    #[serde(default)]
     pub ssh_user: Option<String>,
+    /// Deprecated: use credential_id
     #[serde(default)]
     pub ssh_pass: Option<String>,
     #[serde(default)]
@@ -88,6 +108,27 @@ pub struct CreateDeviceRequest {
     pub device_type: Option<String>,
 }
 
+impl Validate for CreateDeviceRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.mac("mac", &self.mac);
+        validate_device_fields(v, &self.ip, &self.hostname, self.topology_role.as_deref());
+    }
+}
+
+fn validate_device_fields(v: &mut Validator, ip: &str, hostname: &str, topology_role: Option<&str>) {
+    v.required("hostname", hostname);
+    v.hostname("hostname", hostname);
+    // Patch panels may have no IP
+    v.ipv4("ip", ip);
+    if let Some(role) = topology_role {
+        v.check(
+            super::topology_role::is_valid(role),
+            "topology_role",
+            format!("must be one of {}", super::topology_role::ALL.join(", ")),
+        );
+    }
+}
+
 /// UpdateDeviceRequest for updating devices
 #[derive(Debug, Clone, Deserialize)]
 pub struct UpdateDeviceRequest {
@@ -102,7 +143,11 @@ pub struct UpdateDeviceRequest {
     #[serde(default)]
     pub config_template: String,
     #[serde(default)]
+    pub credential_id: Option<i64>,
+    /// Deprecated: use credential_id
+    #[serde(default)]
     pub ssh_user: Option<String>,
+    /// Deprecated: use credential_id
     #[serde(default)]
     pub ssh_pass: Option<String>,
     #[serde(default)]
@@ -121,6 +166,34 @@ pub struct UpdateDeviceRequest {
     pub device_type: Option<String>,
 }
 
+impl Validate for UpdateDeviceRequest {
+    fn validate(&self, v: &mut Validator) {
+        validate_device_fields(v, &self.ip, &self.hostname, self.topology_role.as_deref());
+    }
+}
+
+/// ChangeDeviceMacRequest moves a device to new hardware's MAC, e.g. after a supervisor swap or
+/// an RMA replacement
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChangeDeviceMacRequest {
+    pub mac: String,
+}
+
+impl Validate for ChangeDeviceMacRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("mac", &self.mac);
+        v.mac("mac", &self.mac);
+    }
+}
+
+/// Where a backup's config came from
+pub mod backup_source {
+    /// Fetched from the device over SSH
+    pub const SSH: &str = "ssh";
+    /// Collected out-of-band and uploaded by an operator
+    pub const UPLOAD: &str = "upload";
+}
+
 /// Backup represents a config backup record
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Backup {
@@ -128,7 +201,109 @@ pub struct Backup {
     pub device_id: i64,
     pub filename: String,
     pub size: i64,
+    pub source: String,
+    pub description: String,
+    /// SHA-256 of the config; empty for backups stored before content deduplication
+    pub content_hash: String,
+    /// Whether the config differed from the device's previous backup
+    pub changed: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// NewBackup is a backup to record once its config is stored under `content_hash`
+#[derive(Debug, Clone)]
+pub struct NewBackup {
+    pub device_id: i64,
+    pub filename: String,
+    /// Size of the uncompressed config
+    pub size: i64,
+    pub source: String,
+    pub description: String,
+    pub content_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// UploadBackupRequest attaches a config collected out-of-band as a backup of a device
+#[derive(Debug, Clone, Deserialize)]
+pub struct UploadBackupRequest {
+    pub content: String,
+    /// When the config was collected; defaults to now. Older backups slot into the history
+    /// at this time.
+    #[serde(default)]
+    pub collected_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// DeviceConfigSnippets are one-off config blocks wrapped around a device's rendered template
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceConfigSnippets {
+    pub device_id: i64,
+    /// Prepended before the rendered template
+    pub pre_config: String,
+    /// Appended after the rendered template
+    pub post_config: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl DeviceConfigSnippets {
+    /// Wrap rendered template output with the pre/post blocks. A trailing `end` line stays
+    /// last so the post block is still applied inside config mode.
+    pub fn apply(&self, rendered: &str) -> String {
+        let mut body: Vec<&str> = rendered.lines().collect();
+        while body.last().is_some_and(|l| l.trim().is_empty()) {
+            body.pop();
+        }
+        let end_line = match body.last() {
+            Some(l) if l.trim().eq_ignore_ascii_case("end") => body.pop(),
+            _ => None,
+        };
+
+        let mut lines: Vec<&str> = Vec::new();
+        if !self.pre_config.trim().is_empty() {
+            lines.extend(self.pre_config.trim_end().lines());
+        }
+        lines.extend(body);
+        if !self.post_config.trim().is_empty() {
+            lines.extend(self.post_config.trim_end().lines());
+        }
+        lines.extend(end_line);
+
+        let mut out = lines.join("\n");
+        out.push('\n');
+        out
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetDeviceConfigSnippetsRequest {
+    #[serde(default)]
+    pub pre_config: String,
+    #[serde(default)]
+    pub post_config: String,
+}
+
+/// LineMatch is one matching line of a text search, with surrounding context
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineMatch {
+    pub line_number: usize,
+    pub line: String,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub before: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub after: Vec<String>,
+}
+
+/// BackupSearchResult lists the matches found in a single backup file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSearchResult {
+    pub backup_id: i64,
+    pub device_id: i64,
+    pub hostname: String,
+    pub filename: String,
     pub created_at: DateTime<Utc>,
+    pub matches: Vec<LineMatch>,
 }
 
 /// ConnectResult represents the result of a device connectivity check
@@ -187,6 +362,17 @@ pub struct DeviceConfigResponse {
     pub exists: bool,
 }
 
+/// DeviceZtpUrl is the tokenized URL a device fetches its config from over HTTP ZTP
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceZtpUrl {
+    pub mac: String,
+    /// Path and query, relative to the ForgeConfig HTTP listener
+    pub path: String,
+    /// Full URL on the TFTP server IP setting and the listen port, when LISTEN_ADDR is host:port
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
 /// DeviceConfigPreviewResponse represents a rendered template preview for a device
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceConfigPreviewResponse {
@@ -207,3 +393,44 @@ pub struct DeployConfigResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
+
+/// Fields compared when looking for duplicate devices
+pub mod duplicate_match {
+    pub const MAC: &str = "mac";
+    pub const SERIAL_NUMBER: &str = "serial_number";
+    pub const IP: &str = "ip";
+}
+
+/// DuplicateDeviceSet is a set of devices sharing a MAC, serial number or IP
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateDeviceSet {
+    pub match_field: String,
+    pub value: String,
+    pub devices: Vec<Device>,
+}
+
+/// MergeDevicesRequest folds the source devices into the device named in the path
+#[derive(Debug, Clone, Deserialize)]
+pub struct MergeDevicesRequest {
+    pub source_ids: Vec<i64>,
+}
+
+/// MergeDevicesStats counts the rows moved onto the surviving device
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MergeDevicesStats {
+    pub variables_moved: u64,
+    pub group_memberships_moved: u64,
+    pub jobs_moved: u64,
+    pub backups_moved: u64,
+    pub ip_addresses_moved: u64,
+    pub notes_moved: u64,
+    pub attachments_moved: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeDevicesResult {
+    pub device: Device,
+    pub merged_ids: Vec<i64>,
+    #[serde(flatten)]
+    pub stats: MergeDevicesStats,
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+/// Kind of a line in a diff hunk
+pub mod diff_line_kind {
+    pub const ADDED: &str = "added";
+    pub const REMOVED: &str = "removed";
+    pub const CONTEXT: &str = "context";
+}
+
+/// Line diff of two configs as hunks of changes with surrounding context lines.
+/// No hunks means the configs are identical.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigDiff {
+    pub old_label: String,
+    pub new_label: String,
+    /// Lines only in the new config
+    pub added: usize,
+    /// Lines only in the old config
+    pub removed: usize,
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffHunk {
+    /// First line of the hunk in the old config (1-based), or the line it follows when it has none
+    pub old_start: usize,
+    pub old_count: usize,
+    pub new_start: usize,
+    pub new_count: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+    /// One of diff_line_kind
+    pub kind: String,
+    pub text: String,
+    /// Line number in the old config, absent on added lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_line: Option<usize>,
+    /// Line number in the new config, absent on removed lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_line: Option<usize>,
+}
+
+/// Output of a diff job: the device's running config diffed against its rendered config, and
+/// what the vendor's diff_command printed when the vendor has one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffJobOutput {
+    pub diff: ConfigDiff,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub device_output: String,
+}
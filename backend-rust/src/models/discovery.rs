@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 pub mod discovery_event {
     pub const DISCOVERED: &str = "discovered";
     pub const LEASE_RENEWED: &str = "lease_renewed";
+    /// An unknown MAC whose serial number is a configured device's
+    pub const SERIAL_MATCHED: &str = "serial_matched";
 }
 
 /// Lease represents a DHCP lease from dnsmasq, enriched with DHCP request metadata
@@ -46,6 +48,9 @@ pub struct Lease {
     pub remote_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub subscriber_id: Option<String>,
+    /// Configured device with the same serial number, for a lease from a MAC no device has
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_device_id: Option<i64>,
 }
 
 /// DiscoveryLog represents a discovery event log entry
@@ -78,8 +83,20 @@ pub struct CreateDiscoveryLogRequest {
     pub message: Option<String>,
 }
 
+/// How a NetBox pull handles fields changed both locally and in NetBox
+pub mod netbox_conflict_policy {
+    /// Keep the local value and queue the conflict for review
+    pub const QUEUE: &str = "queue";
+    pub const PREFER_LOCAL: &str = "prefer_local";
+    pub const PREFER_REMOTE: &str = "prefer_remote";
+
+    pub fn is_valid(policy: &str) -> bool {
+        matches!(policy, QUEUE | PREFER_LOCAL | PREFER_REMOTE)
+    }
+}
+
 /// NetBoxConfig holds the NetBox integration settings
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetBoxConfig {
     #[serde(default)]
     pub url: String,
@@ -91,6 +108,83 @@ pub struct NetBoxConfig {
     pub role_id: i32,
     #[serde(default)]
     pub sync_enabled: bool,
+    #[serde(default = "default_conflict_policy")]
+    pub conflict_policy: String,
+}
+
+fn default_conflict_policy() -> String {
+    netbox_conflict_policy::QUEUE.to_string()
+}
+
+impl Default for NetBoxConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            token: String::new(),
+            site_id: 0,
+            role_id: 0,
+            sync_enabled: false,
+            conflict_policy: default_conflict_policy(),
+        }
+    }
+}
+
+/// Canonical sync conflict status values
+pub mod sync_conflict_status {
+    pub const PENDING: &str = "pending";
+    pub const RESOLVED: &str = "resolved";
+}
+
+/// Ways to resolve a sync conflict
+pub mod sync_resolution {
+    pub const LOCAL: &str = "local";
+    pub const REMOTE: &str = "remote";
+    /// Pick local or remote per field
+    pub const MERGE: &str = "merge";
+}
+
+/// SyncConflictField is one field changed both locally and in NetBox since the last sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflictField {
+    pub field: String,
+    pub local: String,
+    pub remote: String,
+    /// Value both sides last agreed on; absent for devices never synced before
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<String>,
+}
+
+/// SyncConflict is a queued NetBox pull that would have overwritten local changes
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncConflict {
+    pub id: i64,
+    pub device_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_hostname: Option<String>,
+    pub netbox_id: i64,
+    pub fields: Vec<SyncConflictField>,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// ResolveSyncConflictRequest picks local, remote, or a per-field merge
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResolveSyncConflictRequest {
+    pub resolution: String,
+    /// For merge: field name -> "local" or "remote", covering every conflicting field
+    #[serde(default)]
+    pub fields: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyncConflictQuery {
+    /// Defaults to pending
+    pub status: Option<String>,
 }
 
 /// NetworkInterface represents a network interface with its addresses
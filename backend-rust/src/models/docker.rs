@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// Companion container kinds a stack service can run as
+pub mod stack_service_kind {
+    pub const DNSMASQ: &str = "dnsmasq";
+    pub const FRR: &str = "frr";
+    pub const GPU_SIM: &str = "gpu-sim";
+    pub const CUSTOM: &str = "custom";
+
+    pub fn is_valid(kind: &str) -> bool {
+        matches!(kind, DNSMASQ | FRR | GPU_SIM | CUSTOM)
+    }
+}
+
+fn default_service_kind() -> String {
+    stack_service_kind::CUSTOM.to_string()
+}
+
+/// One container in a stack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackService {
+    pub name: String,
+    pub image: String,
+    #[serde(default = "default_service_kind")]
+    pub kind: String,
+    /// `KEY=value` entries
+    #[serde(default)]
+    pub env: Vec<String>,
+    /// Overrides the image CMD when non-empty
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub privileged: bool,
+    /// Device whose IP is set to the container's address once it is running
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+}
+
+impl Validate for StackService {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.required("image", &self.image);
+        v.check(stack_service_kind::is_valid(&self.kind), "kind", "must be one of dnsmasq, frr, gpu-sim, custom");
+    }
+}
+
+/// DockerStack is a named set of companion containers managed as a unit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DockerStack {
+    pub id: i64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub services: Vec<StackService>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateDockerStackRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub services: Vec<StackService>,
+}
+
+impl Validate for CreateDockerStackRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        for (i, svc) in self.services.iter().enumerate() {
+            v.nested(&format!("services[{}]", i), svc);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Backup, Device, Group};
+use super::{Validate, Validator};
+
+/// What a note or attachment documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocOwner {
+    Device(i64),
+    Topology(i64),
+}
+
+impl DocOwner {
+    pub fn device_id(self) -> Option<i64> {
+        match self {
+            DocOwner::Device(id) => Some(id),
+            DocOwner::Topology(_) => None,
+        }
+    }
+
+    pub fn topology_id(self) -> Option<i64> {
+        match self {
+            DocOwner::Device(_) => None,
+            DocOwner::Topology(id) => Some(id),
+        }
+    }
+}
+
+/// Note is a free-form markdown note on a device or topology
+#[derive(Debug, Clone, Serialize)]
+pub struct Note {
+    pub id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topology_id: Option<i64>,
+    pub title: String,
+    /// Markdown
+    pub body: String,
+    pub created_by: String,
+    pub updated_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateNoteRequest {
+    #[serde(default)]
+    pub title: String,
+    pub body: String,
+}
+
+impl Validate for CreateNoteRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("body", &self.body);
+    }
+}
+
+/// Changes to a note; fields left out are kept
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateNoteRequest {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+impl Validate for UpdateNoteRequest {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(body) = &self.body {
+            v.required("body", body);
+        }
+    }
+}
+
+/// Attachment is a file attached to a device or topology, such as a rack photo or an LOA.
+/// The file itself is stored on disk under `stored_name`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Attachment {
+    pub id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topology_id: Option<i64>,
+    pub filename: String,
+    #[serde(skip)]
+    pub stored_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub description: String,
+    pub uploaded_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UploadAttachmentRequest {
+    pub filename: String,
+    /// File content, base64-encoded
+    pub content: String,
+    /// Defaults to one guessed from the filename's extension
+    #[serde(default)]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// DeviceDetail is a device with its group memberships, latest backup and documentation
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceDetail {
+    pub device: Device,
+    pub groups: Vec<Group>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_backup: Option<Backup>,
+    pub notes: Vec<Note>,
+    pub attachments: Vec<Attachment>,
+}
@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// Resource types that can be addressed by a client-supplied external ID
+pub mod external_resource_type {
+    pub const DEVICE: &str = "device";
+    pub const GROUP: &str = "group";
+    pub const TEMPLATE: &str = "template";
+    pub const IPAM_PREFIX: &str = "ipam_prefix";
+    pub const IPAM_IP_ADDRESS: &str = "ipam_ip_address";
+
+    pub const ALL: &[&str] = &[DEVICE, GROUP, TEMPLATE, IPAM_PREFIX, IPAM_IP_ADDRESS];
+}
+
+/// ExternalId maps a client's identifier to a local resource
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalId {
+    pub resource_type: String,
+    pub external_id: String,
+    pub resource_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Bind an existing resource to an external ID, e.g. to import it into Terraform state
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateExternalIdRequest {
+    pub resource_type: String,
+    pub external_id: String,
+    pub resource_id: i64,
+}
+
+impl Validate for CreateExternalIdRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("external_id", &self.external_id);
+        v.one_of("resource_type", &self.resource_type, external_resource_type::ALL);
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExternalIdQuery {
+    #[serde(default)]
+    pub resource_type: Option<String>,
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GpuCluster {
@@ -16,6 +17,8 @@ pub struct GpuCluster {
     pub topology_id: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vrf_id: Option<i64>,
+    /// Devices assigned to this cluster
+    pub device_count: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -41,8 +44,27 @@ pub struct CreateGpuClusterRequest {
     pub vrf_id: Option<i64>,
 }
 
+impl Validate for CreateGpuClusterRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.min("node_count", self.node_count.into(), 1);
+        v.min("gpus_per_node", self.gpus_per_node.into(), 1);
+    }
+}
+
 fn default_gpu_model() -> String { "MI350X 8-GPU Node".to_string() }
 fn default_node_count() -> i32 { 1 }
 fn default_gpus_per_node() -> i32 { 8 }
 fn default_interconnect() -> String { "InfiniBand".to_string() }
 fn default_status() -> String { "provisioning".to_string() }
+
+/// A variable applied to every device assigned to the GPU cluster
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuClusterVariable {
+    pub id: i64,
+    pub gpu_cluster_id: i64,
+    pub key: String,
+    pub value: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
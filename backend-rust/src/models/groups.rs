@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use super::{Validate, Validator};
 
 /// DeviceVariable represents a key-value pair associated with a device
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +24,9 @@ pub struct Group {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_id: Option<i64>,
     pub precedence: i32,
+    /// Credential for member devices without their own binding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_id: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub device_count: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -41,10 +45,21 @@ pub struct CreateGroupRequest {
     pub parent_id: Option<i64>,
     #[serde(default = "default_precedence")]
     pub precedence: i32,
+    #[serde(default)]
+    pub credential_id: Option<i64>,
 }
 
+impl Validate for CreateGroupRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+    }
+}
+
+/// Precedence of groups created without one
+pub const DEFAULT_GROUP_PRECEDENCE: i32 = 1000;
+
 fn default_precedence() -> i32 {
-    1000
+    DEFAULT_GROUP_PRECEDENCE
 }
 
 /// GroupVariable is a key-value pair on a group
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Hardware component types
+pub mod hardware_component {
+    pub const CHASSIS: &str = "chassis";
+    pub const MODULE: &str = "module";
+    pub const POWER_SUPPLY: &str = "power-supply";
+    pub const FAN: &str = "fan";
+    pub const TRANSCEIVER: &str = "transceiver";
+}
+
+/// DeviceHardware is a hardware component collected from a device's inventory
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceHardware {
+    pub id: i64,
+    pub device_id: i64,
+    pub component_type: String,
+    pub name: String,
+    pub part_number: String,
+    pub serial_number: String,
+    pub description: String,
+    pub collected_at: DateTime<Utc>,
+}
+
+/// HardwareInventoryEntry is one component parsed from device inventory output
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HardwareInventoryEntry {
+    pub component_type: String,
+    pub name: String,
+    pub part_number: String,
+    pub serial_number: String,
+    pub description: String,
+}
+
+/// HardwareInventorySummary reports what a hardware collection stored
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HardwareInventorySummary {
+    pub components: usize,
+    /// Components no longer reported by the device, removed from the inventory
+    pub removed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chassis_serial: Option<String>,
+    /// The device had no serial on record and took the chassis serial
+    pub serial_recorded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mismatch: Option<SerialMismatch>,
+}
+
+/// SerialMismatch warns that a device's chassis reported a different serial than the one
+/// on record, typically after the switch was replaced
+#[derive(Debug, Clone, Serialize)]
+pub struct SerialMismatch {
+    pub id: i64,
+    pub device_id: i64,
+    pub hostname: String,
+    pub recorded_serial: String,
+    pub observed_serial: String,
+    pub detected_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub resolved_by: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SerialMismatchQuery {
+    /// Include mismatches that were already accepted
+    #[serde(default)]
+    pub include_resolved: bool,
+}
+
+/// HardwareCollectRequest selects devices to collect hardware inventory from.
+/// With neither field set, all devices with a topology role are used.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HardwareCollectRequest {
+    #[serde(default)]
+    pub device_ids: Vec<i64>,
+    #[serde(default)]
+    pub group_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetReportQuery {
+    /// Only report devices that are direct members of this group
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    /// Only report components of this type
+    #[serde(default)]
+    pub component_type: Option<String>,
+    /// `json` (default) or `csv`
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// AssetReportRow is one component of one device. Devices without a collected inventory are
+/// listed with a single chassis row built from the recorded model and serial.
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetReportRow {
+    pub device_id: i64,
+    pub hostname: String,
+    pub vendor: String,
+    pub model: String,
+    pub component_type: String,
+    pub name: String,
+    pub part_number: String,
+    pub serial_number: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collected_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetReport {
+    pub generated_at: DateTime<Utc>,
+    pub devices: usize,
+    /// Devices with no collected inventory
+    pub uncollected: usize,
+    pub rows: Vec<AssetReportRow>,
+    /// Unresolved serial mismatches of the reported devices
+    pub mismatches: Vec<SerialMismatch>,
+}
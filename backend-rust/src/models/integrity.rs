@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How the integrity cleanup repairs an orphaned reference
+pub mod integrity_action {
+    /// Delete the orphaned rows
+    pub const DELETE: &str = "delete";
+    /// Clear the dangling reference, keeping the row
+    pub const NULLIFY: &str = "nullify";
+}
+
+/// IntegrityIssue is one check that found rows referencing a missing parent
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityIssue {
+    pub check: String,
+    pub description: String,
+    pub table: String,
+    pub column: String,
+    pub references: String,
+    /// What the cleanup endpoint does with these rows
+    pub action: String,
+    pub count: i64,
+    /// Some of the missing parent IDs
+    pub missing_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub checked_at: DateTime<Utc>,
+    pub checks_run: usize,
+    pub total_orphans: i64,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IntegrityCleanupRequest {
+    /// Checks to clean up; all checks when empty
+    #[serde(default)]
+    pub checks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityCleanupResult {
+    pub check: String,
+    pub action: String,
+    pub rows_affected: u64,
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Canonical interface status values
+pub mod interface_status {
+    pub const UP: &str = "up";
+    pub const DOWN: &str = "down";
+    pub const ADMIN_DOWN: &str = "admin-down";
+}
+
+/// DeviceInterface is an interface collected from a device by an interface_collect job
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceInterface {
+    pub id: i64,
+    pub device_id: i64,
+    pub name: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_mbps: Option<i64>,
+    pub description: String,
+    pub mac: String,
+    /// Primary IPv4 address in CIDR notation
+    pub ip: String,
+    /// VRF the interface belongs to (empty for the default VRF), unset when not collected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vrf: Option<String>,
+    pub collected_at: DateTime<Utc>,
+}
+
+/// InterfaceInventoryEntry is one interface parsed from device command output
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InterfaceInventoryEntry {
+    pub name: String,
+    pub status: String,
+    pub speed_mbps: Option<i64>,
+    pub description: String,
+    pub mac: String,
+    pub ip: String,
+    pub vrf: Option<String>,
+}
+
+/// InterfaceInventorySummary counts what an interface collection stored
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InterfaceInventorySummary {
+    pub interfaces: usize,
+    /// Interfaces no longer reported by the device, removed from the inventory
+    pub removed: usize,
+    /// IPAM addresses bound to this device's interface by address
+    pub ipam_bound: usize,
+}
+
+/// InterfaceCollectRequest selects devices to collect interfaces from.
+/// With neither field set, all devices with a topology role are used.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InterfaceCollectRequest {
+    #[serde(default)]
+    pub device_ids: Vec<i64>,
+    #[serde(default)]
+    pub group_id: Option<i64>,
+}
+
+/// PortAssignmentCheck reports whether an assigned port exists in the device's collected inventory
+#[derive(Debug, Clone, Serialize)]
+pub struct PortAssignmentCheck {
+    pub port_name: String,
+    pub exists: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_mbps: Option<i64>,
+}
+
+/// PortAssignmentValidation checks a device's port assignments against its interface inventory.
+/// Without a collected inventory there is nothing to check against and `checks` is empty.
+#[derive(Debug, Clone, Serialize)]
+pub struct PortAssignmentValidation {
+    pub device_id: i64,
+    pub inventory_collected: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collected_at: Option<DateTime<Utc>>,
+    pub checks: Vec<PortAssignmentCheck>,
+    /// Assigned ports the device doesn't have
+    pub unknown_ports: Vec<String>,
+}
+
+/// InterfaceMismatch is one difference between a port assignment and the collected interface
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InterfaceMismatch {
+    pub port_name: String,
+    /// missing, status, description, vrf or speed
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// InterfaceIntentReport compares a device's modeled port assignments (descriptions, VRFs,
+/// speeds from the device model layout) with its collected interface state
+#[derive(Debug, Clone, Serialize)]
+pub struct InterfaceIntentReport {
+    pub device_id: i64,
+    pub hostname: String,
+    pub inventory_collected: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collected_at: Option<DateTime<Utc>>,
+    /// Whether VRF membership was collected; without it VRFs aren't compared
+    pub vrf_collected: bool,
+    pub ports_checked: usize,
+    pub mismatches: Vec<InterfaceMismatch>,
+}
+
+/// InterfaceDriftQuery filters the fleet-wide interface drift report
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InterfaceDriftQuery {
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    /// Include devices without mismatches
+    #[serde(default)]
+    pub include_clean: bool,
+}
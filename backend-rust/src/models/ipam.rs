@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
 
 /// IPAM status values
 #[allow(dead_code)]
@@ -8,6 +9,8 @@ pub mod ipam_status {
     pub const RESERVED: &str = "reserved";
     pub const DEPRECATED: &str = "deprecated";
     pub const DHCP: &str = "dhcp";
+
+    pub const ALL: &[&str] = &[ACTIVE, RESERVED, DEPRECATED, DHCP];
 }
 
 /// IPAM resource types (for polymorphic tags)
@@ -42,6 +45,12 @@ pub struct CreateIpamRegionRequest {
     pub description: Option<String>,
 }
 
+impl Validate for CreateIpamRegionRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpamCampus {
     pub id: i64,
@@ -65,6 +74,13 @@ pub struct CreateIpamCampusRequest {
     pub region_id: i64,
 }
 
+impl Validate for CreateIpamCampusRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.required_id("region_id", self.region_id);
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpamDatacenter {
     pub id: i64,
@@ -92,6 +108,13 @@ pub struct CreateIpamDatacenterRequest {
     pub campus_id: i64,
 }
 
+impl Validate for CreateIpamDatacenterRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.required_id("campus_id", self.campus_id);
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpamHall {
     pub id: i64,
@@ -115,6 +138,13 @@ pub struct CreateIpamHallRequest {
     pub datacenter_id: i64,
 }
 
+impl Validate for CreateIpamHallRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.required_id("datacenter_id", self.datacenter_id);
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpamRow {
     pub id: i64,
@@ -138,6 +168,13 @@ pub struct CreateIpamRowRequest {
     pub hall_id: i64,
 }
 
+impl Validate for CreateIpamRowRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.required_id("hall_id", self.hall_id);
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpamRack {
     pub id: i64,
@@ -170,10 +207,58 @@ pub struct CreateIpamRackRequest {
     pub depth_cm: i32,
 }
 
+impl Validate for CreateIpamRackRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.required_id("row_id", self.row_id);
+        v.min("width_cm", self.width_cm.into(), 1);
+        v.min("height_ru", self.height_ru.into(), 1);
+        v.min("depth_cm", self.depth_cm.into(), 1);
+    }
+}
+
 fn default_rack_width() -> i32 { 60 }
 fn default_rack_height() -> i32 { 42 }
 fn default_rack_depth() -> i32 { 100 }
 
+/// A device in a rack elevation. `position` is the lowest rack unit it occupies; devices
+/// assigned to the rack without a position have none.
+#[derive(Debug, Clone, Serialize)]
+pub struct RackElevationDevice {
+    pub device_id: i64,
+    pub hostname: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topology_role: Option<String>,
+    pub position: Option<i32>,
+    pub rack_units: i32,
+    /// Shares a rack unit with another device, or extends past the top of the rack
+    pub conflict: bool,
+}
+
+/// One rack unit of an elevation and the device occupying it
+#[derive(Debug, Clone, Serialize)]
+pub struct RackElevationUnit {
+    pub position: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+}
+
+/// RackElevation lays out a rack's devices by rack unit, for drawing the rack front
+#[derive(Debug, Clone, Serialize)]
+pub struct RackElevation {
+    pub rack: IpamRack,
+    pub used_ru: i32,
+    pub free_ru: i32,
+    /// Placed devices, top of the rack first
+    pub devices: Vec<RackElevationDevice>,
+    /// Every rack unit, top of the rack first
+    pub units: Vec<RackElevationUnit>,
+    /// Devices assigned to the rack without a position
+    pub unplaced: Vec<RackElevationDevice>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpamRole {
     pub id: i64,
@@ -190,6 +275,12 @@ pub struct CreateIpamRoleRequest {
     pub description: Option<String>,
 }
 
+impl Validate for CreateIpamRoleRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpamPrefix {
     pub id: i64,
@@ -229,6 +320,104 @@ pub struct IpamPrefix {
     pub updated_at: DateTime<Utc>,
 }
 
+/// IpamPrefixTreeNode is a prefix with its nested children, as returned by the prefix tree
+#[derive(Debug, Clone, Serialize)]
+pub struct IpamPrefixTreeNode {
+    #[serde(flatten)]
+    pub prefix: IpamPrefix,
+    pub depth: i32,
+    pub children: Vec<IpamPrefixTreeNode>,
+    /// The depth limit cut off this node's children; request it as `root` to load them
+    pub children_truncated: bool,
+}
+
+/// IpamAvailableBlock is a free CIDR block inside a prefix
+#[derive(Debug, Clone, Serialize)]
+pub struct IpamAvailableBlock {
+    pub prefix: String,
+    pub prefix_length: i32,
+    pub size: u64,
+}
+
+/// IpamPrefixAvailability lists the space of a prefix not covered by child prefixes or IP addresses
+#[derive(Debug, Clone, Serialize)]
+pub struct IpamPrefixAvailability {
+    pub prefix_id: i64,
+    pub prefix: String,
+    pub free_addresses: u64,
+    pub blocks: Vec<IpamAvailableBlock>,
+}
+
+/// Reverse DNS export formats
+pub mod reverse_dns_format {
+    /// BIND-style PTR zone files, one per /24
+    pub const ZONE: &str = "zone";
+    /// dnsmasq `ptr-record` lines
+    pub const DNSMASQ: &str = "dnsmasq";
+}
+
+/// ReverseDnsFile is one generated reverse DNS file
+#[derive(Debug, Clone, Serialize)]
+pub struct ReverseDnsFile {
+    pub name: String,
+    pub record_count: usize,
+    pub content: String,
+}
+
+/// ReverseDnsExport holds the reverse DNS files generated from IPAM addresses with a dns_name
+#[derive(Debug, Clone, Serialize)]
+pub struct ReverseDnsExport {
+    pub format: String,
+    pub files: Vec<ReverseDnsFile>,
+}
+
+/// NeighborEntry is one ARP (IPv4) or ND (IPv6) table entry collected from a device
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NeighborEntry {
+    pub address: String,
+    pub mac: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+}
+
+/// IpamDiscoveredAddress is an address seen in the network that IPAM doesn't know about
+#[derive(Debug, Clone, Serialize)]
+pub struct IpamDiscoveredAddress {
+    pub id: i64,
+    pub address: String,
+    pub mac: String,
+    /// Device whose ARP/ND table last reported the address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interface_name: Option<String>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// NeighborReconcileSummary counts how collected neighbor entries matched IPAM
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NeighborReconcileSummary {
+    pub entries: usize,
+    /// Entries matching managed IPAM addresses (last_seen updated)
+    pub managed: usize,
+    /// Entries not in IPAM, recorded as discovered
+    pub discovered: usize,
+}
+
+/// NeighborCollectRequest selects devices to collect ARP/ND tables from.
+/// With neither field set, devices in a routed role (super-spine, spine, leaf, core or
+/// distribution) are used.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NeighborCollectRequest {
+    #[serde(default)]
+    pub device_ids: Vec<i64>,
+    #[serde(default)]
+    pub group_id: Option<i64>,
+}
+
 fn default_ipam_status() -> String {
     "active".to_string()
 }
@@ -254,6 +443,17 @@ pub struct CreateIpamPrefixRequest {
     pub vrf_id: Option<i64>,
 }
 
+impl Validate for CreateIpamPrefixRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("prefix", &self.prefix);
+        v.cidr("prefix", &self.prefix);
+        v.one_of("status", &self.status, ipam_status::ALL);
+        if let Some(vlan_id) = self.vlan_id {
+            v.range("vlan_id", vlan_id.into(), 1, 4094);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpamIpAddress {
     pub id: i64,
@@ -281,6 +481,11 @@ pub struct IpamIpAddress {
     pub vrf_id: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vrf_name: Option<String>,
+    /// Last time the address was seen in a device's ARP/ND table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen_mac: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -305,6 +510,15 @@ pub struct CreateIpamIpAddressRequest {
     pub vrf_id: Option<i64>,
 }
 
+impl Validate for CreateIpamIpAddressRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("address", &self.address);
+        v.ipv4("address", self.address.trim_end_matches("/32"));
+        v.required_id("prefix_id", self.prefix_id);
+        v.one_of("status", &self.status, ipam_status::ALL);
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpamTag {
     pub id: i64,
@@ -376,3 +590,9 @@ pub struct CreateIpamVrfRequest {
     #[serde(default)]
     pub tenant_id: Option<i64>,
 }
+
+impl Validate for CreateIpamVrfRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+    }
+}
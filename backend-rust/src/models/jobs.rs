@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
 
 /// Canonical job status values
 #[allow(dead_code)]
@@ -8,17 +9,45 @@ pub mod job_status {
     pub const RUNNING: &str = "running";
     pub const COMPLETED: &str = "completed";
     pub const FAILED: &str = "failed";
+    /// Stopped through the cancel API, while queued or running
+    pub const CANCELLED: &str = "cancelled";
 }
 
 /// Canonical job type values
 pub mod job_type {
     pub const COMMAND: &str = "command";
     pub const DEPLOY: &str = "deploy";
+    /// Deploy with the vendor's confirmed commit: rolled back unless the device stays reachable
+    pub const CONFIRMED_DEPLOY: &str = "confirmed_deploy";
     pub const DIFF: &str = "diff";
     pub const WEBHOOK: &str = "webhook";
     pub const APPLY_TEMPLATE: &str = "apply_template";
+    pub const NEIGHBOR_COLLECT: &str = "neighbor_collect";
+    pub const INTERFACE_COLLECT: &str = "interface_collect";
+    pub const HARDWARE_COLLECT: &str = "hardware_collect";
+    /// Pre-flight checks; fails on a no-go
+    pub const PREFLIGHT: &str = "preflight";
+    /// Push a staged config; the job's command is the staged config ID
+    pub const STAGED_DEPLOY: &str = "staged_deploy";
+    /// Ping and SSH login test; the output is the JSON connect result
+    pub const CONNECT_TEST: &str = "connect_test";
+    /// Set a rotated credential's password on the device; the password is read from the job's
+    /// credential when the job runs and the command holds only the username
+    pub const PASSWORD_PUSH: &str = "password_push";
 }
 
+/// triggered_by value for jobs that push a rotated credential to devices
+pub const TRIGGERED_BY_CREDENTIAL_ROTATION: &str = "credential_rotation";
+
+/// triggered_by value for jobs that run against a topology's virtual lab nodes instead of hardware
+pub const TRIGGERED_BY_LAB: &str = "lab";
+
+/// triggered_by value for jobs that push a staged config at its activation time
+pub const TRIGGERED_BY_STAGED_CONFIG: &str = "staged_config";
+
+/// triggered_by value for jobs queued by a topology deploy
+pub const TRIGGERED_BY_TOPOLOGY_DEPLOY: &str = "topology_deploy";
+
 fn default_manual() -> String {
     "manual".to_string()
 }
@@ -44,6 +73,12 @@ pub struct Job {
     pub credential_id: String,
     #[serde(default = "default_manual")]
     pub triggered_by: String,
+    /// Run time limit in seconds; unset uses the job_timeout_secs setting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<i64>,
+    /// Bulk request batch the job belongs to; empty for a single job
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub batch_id: String,
 }
 
 /// CreateJobRequest for creating a new job
@@ -57,6 +92,121 @@ pub struct CreateJobRequest {
     pub credential_id: String,
     #[serde(default = "default_manual")]
     pub triggered_by: String,
+    #[serde(default)]
+    pub timeout_secs: Option<i64>,
+}
+
+/// Job types a bulk request can queue across devices
+pub const BULK_JOB_TYPES: &[&str] = &[
+    job_type::COMMAND,
+    job_type::DEPLOY,
+    job_type::DIFF,
+    job_type::CONNECT_TEST,
+];
+
+/// BulkJobRequest queues the same job on every member of a group or on a list of devices
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkJobRequest {
+    #[serde(default)]
+    pub group_id: i64,
+    #[serde(default)]
+    pub device_ids: Vec<i64>,
+    #[serde(default)]
+    pub job_type: String,
+    /// Command to run; only used by command jobs
+    #[serde(default)]
+    pub command: String,
+    #[serde(default)]
+    pub timeout_secs: Option<i64>,
+}
+
+impl Validate for BulkJobRequest {
+    fn validate(&self, v: &mut Validator) {
+        if self.group_id == 0 && self.device_ids.is_empty() {
+            v.error("device_ids", "group_id or device_ids is required");
+        }
+        v.check(
+            self.group_id == 0 || self.device_ids.is_empty(),
+            "device_ids",
+            "give either group_id or device_ids, not both",
+        );
+        v.one_of("job_type", &self.job_type, BULK_JOB_TYPES);
+        if self.job_type == job_type::COMMAND {
+            v.required("command", &self.command);
+        }
+        if let Some(secs) = self.timeout_secs {
+            v.min("timeout_secs", secs, 1);
+        }
+    }
+}
+
+/// JobBatch tracks the jobs a bulk request queued, with their aggregate status
+#[derive(Debug, Clone, Serialize)]
+pub struct JobBatch {
+    pub id: String,
+    pub job_type: String,
+    pub command: String,
+    /// Group whose members were targeted; 0 for an explicit device list
+    pub group_id: i64,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    /// queued until a job starts, running while any job is unfinished, then completed, or
+    /// failed when any job failed, or cancelled when the rest were cancelled
+    pub status: String,
+    pub total: i64,
+    pub queued: i64,
+    pub running: i64,
+    pub completed: i64,
+    pub failed: i64,
+    pub cancelled: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<Vec<Job>>,
+}
+
+impl JobBatch {
+    /// Set the per-status job counts and derive the batch status from them
+    pub fn set_counts(&mut self, counts: &[(String, i64)]) {
+        for (status, count) in counts {
+            match status.as_str() {
+                job_status::QUEUED => self.queued = *count,
+                job_status::RUNNING => self.running = *count,
+                job_status::COMPLETED => self.completed = *count,
+                job_status::FAILED => self.failed = *count,
+                job_status::CANCELLED => self.cancelled = *count,
+                _ => {}
+            }
+        }
+        self.total = counts.iter().map(|(_, count)| count).sum();
+        self.status = if self.queued == self.total {
+            job_status::QUEUED
+        } else if self.queued + self.running > 0 {
+            job_status::RUNNING
+        } else if self.failed > 0 {
+            job_status::FAILED
+        } else if self.cancelled > 0 {
+            job_status::CANCELLED
+        } else {
+            job_status::COMPLETED
+        }
+        .to_string();
+    }
+}
+
+/// What an Idempotency-Key holds when a request using it arrives
+#[derive(Debug, Clone)]
+pub enum IdempotencyReservation {
+    /// First use (or the last use expired): the request runs and its response is stored
+    Reserved,
+    /// The first request with the key is still running
+    InProgress,
+    /// The key was used for a different request
+    Mismatch,
+    /// The first request's response, to replay
+    Completed {
+        status_code: u16,
+        content_type: String,
+        body: Vec<u8>,
+    },
 }
 
 // ========== Job Template Models ==========
@@ -78,6 +228,10 @@ pub struct JobTemplate {
     pub target_device_ids: Vec<i64>,
     #[serde(default)]
     pub target_group_id: i64,
+    #[serde(default)]
+    pub target_saved_search_id: i64,
+    #[serde(default)]
+    pub target_tag: String,
     pub schedule: String,
     pub enabled: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -107,6 +261,10 @@ pub struct CreateJobTemplateRequest {
     #[serde(default)]
     pub target_group_id: i64,
     #[serde(default)]
+    pub target_saved_search_id: i64,
+    #[serde(default)]
+    pub target_tag: String,
+    #[serde(default)]
     pub schedule: String,
     #[serde(default = "default_true")]
     pub enabled: bool,
@@ -114,6 +272,13 @@ pub struct CreateJobTemplateRequest {
     pub credential_id: i64,
 }
 
+impl Validate for CreateJobTemplateRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.cron("schedule", &self.schedule);
+    }
+}
+
 // ========== Credential Models ==========
 
 fn default_ssh() -> String {
@@ -129,6 +294,13 @@ pub struct Credential {
     pub cred_type: String,
     pub username: String,
     pub password: String,
+    /// Password replaced by the last rotation, used to log in while pushing the new one
+    #[serde(skip)]
+    pub previous_password: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -145,3 +317,63 @@ pub struct CreateCredentialRequest {
     #[serde(default)]
     pub password: String,
 }
+
+impl Validate for CreateCredentialRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+    }
+}
+
+/// CredentialUsage records one job that authenticated with a credential
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialUsage {
+    pub id: i64,
+    pub credential_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_hostname: Option<String>,
+    pub job_id: String,
+    pub used_at: DateTime<Utc>,
+}
+
+/// CredentialAudit summarizes a credential's usage and any staleness warnings
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialAudit {
+    pub credential_id: i64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<DateTime<Utc>>,
+    /// Distinct devices the credential has been used against
+    pub device_count: i64,
+    pub job_count: i64,
+    pub warnings: Vec<String>,
+}
+
+/// CredentialUsageReport is the audit summary plus the most recent usage records
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialUsageReport {
+    #[serde(flatten)]
+    pub audit: CredentialAudit,
+    pub recent: Vec<CredentialUsage>,
+}
+
+/// RotateCredentialRequest sets a new password and optionally pushes it to devices
+#[derive(Debug, Clone, Deserialize)]
+pub struct RotateCredentialRequest {
+    pub password: String,
+    /// Queue jobs that change the password on devices
+    #[serde(default)]
+    pub push: bool,
+    /// Devices to push to; defaults to every device the credential has been used against
+    #[serde(default)]
+    pub device_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RotateCredentialResponse {
+    pub credential: Credential,
+    pub jobs: Vec<Job>,
+}
@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// MaintenanceWindow is scheduled work on one device, the direct members of one group,
+/// or every device when neither is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub id: i64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<i64>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MaintenanceWindow {
+    /// Whether the window covers a device that is a direct member of `group_ids`
+    pub fn applies_to(&self, device_id: i64, group_ids: &[i64]) -> bool {
+        match (self.device_id, self.group_id) {
+            (None, None) => true,
+            (Some(id), _) if id == device_id => true,
+            (_, Some(gid)) => group_ids.contains(&gid),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateMaintenanceWindowRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub device_id: Option<i64>,
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+impl Validate for CreateMaintenanceWindowRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.check(self.ends_at > self.starts_at, "ends_at", "must be after starts_at");
+        if self.device_id.is_some() && self.group_id.is_some() {
+            v.error("group_id", "set at most one of device_id or group_id");
+        }
+    }
+}
+
+/// MaintenanceModeState is the global read-only maintenance switch
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MaintenanceModeState {
+    pub enabled: bool,
+    pub reason: String,
+    pub changed_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetMaintenanceModeRequest {
+    pub enabled: bool,
+    #[serde(default)]
+    pub reason: String,
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetricsExportQuery {
+    /// Jobs finished in this many seconds are counted (default METRICS_EXPORT_INTERVAL_SECS)
+    #[serde(default)]
+    pub since_secs: Option<i64>,
+}
+
+/// LookupCacheStats reports one entry of the Store's lookup cache
+#[derive(Debug, Clone, Serialize)]
+pub struct LookupCacheStats {
+    pub name: String,
+    /// Whether a value is currently cached
+    pub cached: bool,
+    pub hits: u64,
+    pub misses: u64,
+}
@@ -1,33 +1,117 @@
+mod alerts;
+mod api_usage;
+mod apply;
 mod auth;
+mod automation;
+mod bgp;
+mod capacity;
+mod changelog;
+mod config_outline;
+mod custom_fields;
+mod db_maintenance;
 mod device_models;
 mod device_roles;
 mod devices;
+mod diff;
 mod discovery;
+mod documentation;
+mod external_ids;
+mod docker;
 mod groups;
+mod hardware;
+mod integrity;
+mod interfaces;
 mod ipam;
 mod jobs;
+mod maintenance;
+mod metrics;
+mod notifications;
+mod permissions;
 mod port_assignments;
+mod preflight;
+mod provisioning;
+mod references;
+mod render_inventory;
+mod reports;
+mod runbooks;
+mod runtime_config;
+mod saved_searches;
+mod search;
+mod seeds;
 mod settings;
+mod staged_configs;
+mod support_contracts;
+mod syslog;
+mod tags;
+mod teams;
+mod telemetry;
+mod tls;
 mod templates;
+mod template_catalog;
 mod topology;
+mod topology_deploys;
+mod underlay;
 mod output_parsers;
 mod vendors;
 mod gpu_cluster;
 mod tenant;
+mod validation;
 
+pub use alerts::*;
+pub use api_usage::*;
+pub use apply::*;
 pub use auth::*;
+pub use automation::*;
+pub use bgp::*;
+pub use capacity::*;
+pub use changelog::*;
+pub use config_outline::*;
+pub use custom_fields::*;
+pub use db_maintenance::*;
 pub use device_models::*;
 pub use device_roles::*;
 pub use devices::*;
+pub use diff::*;
 pub use discovery::*;
+pub use documentation::*;
+pub use external_ids::*;
+pub use docker::*;
 pub use groups::*;
+pub use hardware::*;
+pub use integrity::*;
+pub use interfaces::*;
 pub use ipam::*;
 pub use jobs::*;
+pub use maintenance::*;
+pub use metrics::*;
+pub use notifications::*;
+pub use permissions::*;
 pub use output_parsers::*;
 pub use port_assignments::*;
+pub use preflight::*;
+pub use provisioning::*;
+pub use references::*;
+pub use render_inventory::*;
+pub use reports::*;
+pub use runbooks::*;
+pub use runtime_config::*;
+pub use saved_searches::*;
+pub use search::*;
+pub use seeds::*;
 pub use settings::*;
+pub use staged_configs::*;
+pub use support_contracts::*;
+pub use syslog::*;
+pub use tags::*;
+pub use teams::*;
+pub use telemetry::*;
+pub use tls::*;
 pub use templates::*;
+pub use template_catalog::*;
 pub use topology::*;
+pub use topology_deploys::*;
+pub use underlay::*;
 pub use vendors::*;
 pub use gpu_cluster::*;
 pub use tenant::*;
+pub use validation::*;
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use super::{Validate, Validator};
+
+/// Where a notification channel delivers to
+pub mod notification_channel_type {
+    /// Mail to a list of recipients through the SMTP_* server
+    pub const EMAIL: &str = "email";
+    /// POST a JSON body to a URL
+    pub const WEBHOOK: &str = "webhook";
+
+    pub const ALL: &[&str] = &[EMAIL, WEBHOOK];
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// NotificationChannel is a delivery target for reports, configured by `channel_type`
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationChannel {
+    pub id: i64,
+    pub name: String,
+    pub channel_type: String,
+    pub config: serde_json::Value,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateNotificationChannelRequest {
+    pub name: String,
+    pub channel_type: String,
+    #[serde(default)]
+    pub config: serde_json::Value,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl Validate for CreateNotificationChannelRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+    }
+}
+
+/// Settings of an email channel
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmailChannelConfig {
+    pub recipients: Vec<String>,
+}
+
+/// Settings of a webhook channel. The body is `{"subject", "text", "payload"}`, so chat
+/// webhooks that read `text` show the plain-text message.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookChannelConfig {
+    pub url: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+/// Notification is one message sent to a channel
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub subject: String,
+    pub text: String,
+    pub payload: serde_json::Value,
+}
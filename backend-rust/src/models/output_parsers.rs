@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
 
 /// OutputParser represents a regex-based parser for extracting structured data from command output
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +30,14 @@ pub struct CreateOutputParserRequest {
     pub enabled: bool,
 }
 
+impl Validate for CreateOutputParserRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.required("pattern", &self.pattern);
+        v.regex("pattern", &self.pattern);
+    }
+}
+
 fn default_enabled() -> bool {
     true
 }
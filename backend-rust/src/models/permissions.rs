@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// Who a permission grant is assigned to
+pub mod permission_subject {
+    pub const ROLE: &str = "role";
+    pub const TEAM: &str = "team";
+}
+
+/// Actions in the permissions matrix. GET and HEAD requests are reads, every other method is a
+/// write; a write grant also allows reads of the same resource.
+pub mod permission_action {
+    pub const READ: &str = "read";
+    pub const WRITE: &str = "write";
+    pub const ANY: &str = "*";
+}
+
+/// Resources in the permissions matrix. A request's resource is the first path segment after
+/// `/api/`, so `/api/templates/3` is `templates`.
+pub mod permission_resource {
+    pub const ANY: &str = "*";
+    /// Not a path: a write grant lets a user generate impersonation tokens. It is never implied
+    /// for unrestricted users.
+    pub const IMPERSONATE: &str = "impersonate";
+
+    /// Resources offered by the matrix editor
+    pub const KNOWN: &[&str] = &[
+        "alert-rules", "alerts", "apply", "attachments", "automation-rules", "backups", "branding", "changelog", "config",
+        "connect", "credentials", "custom-fields", "device-models", "device-roles", "devices", "dhcp-options",
+        "discovery", "docker", "external-ids", "gpu-clusters", "groups", "hardware", "impersonate", "interfaces",
+        "ipam", "job-templates", "jobs", "maintenance-mode", "maintenance-windows", "metrics",
+        "netbox", "network", "notes", "notification-channels", "output-parsers", "permissions",
+        "probe-commands", "reload", "render", "reports", "roles", "runbook-runs", "runbooks", "saved-searches",
+        "search", "seeds", "settings", "staged-configs", "support-contracts", "syslog", "system",
+        "tags", "teams", "telemetry", "template-catalog", "templates", "tenants", "topologies",
+        "topology-builder", "users", "variables", "vendor-actions", "vendors", "ws",
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Permission {
+    pub resource: String,
+    pub action: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RoleMember {
+    pub user_id: i64,
+    pub username: String,
+}
+
+/// Role is a named set of permissions assigned to users
+#[derive(Debug, Clone, Serialize)]
+pub struct Role {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub members: Vec<RoleMember>,
+    pub permissions: Vec<Permission>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateRoleRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl Validate for CreateRoleRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetRoleMembersRequest {
+    pub user_ids: Vec<i64>,
+}
+
+/// Replaces every grant of a role or team
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetPermissionsRequest {
+    pub permissions: Vec<Permission>,
+}
+
+/// Grants of one role or team
+#[derive(Debug, Clone, Serialize)]
+pub struct SubjectPermissions {
+    pub subject_type: String,
+    pub subject_id: i64,
+    pub name: String,
+    pub permissions: Vec<Permission>,
+}
+
+/// The full matrix: the resources and actions that can be granted and every role's and team's grants
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionMatrix {
+    pub resources: Vec<String>,
+    pub actions: Vec<String>,
+    pub subjects: Vec<SubjectPermissions>,
+}
+
+/// What the current user may do. Users with no role and no team grants are unrestricted.
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectivePermissions {
+    pub unrestricted: bool,
+    pub permissions: Vec<Permission>,
+}
@@ -69,3 +69,64 @@ pub struct SetPortAssignmentRequest {
 pub struct BulkPortAssignmentRequest {
     pub assignments: Vec<SetPortAssignmentRequest>,
 }
+
+/// Kinds of power and console connection. Device model layouts mark the matching ports with the
+/// same `role`.
+pub mod aux_connection_kind {
+    pub const POWER: &str = "power";
+    pub const CONSOLE: &str = "console";
+}
+
+/// AuxConnection is a power feed from a PDU outlet to a device's power supply, or a console line
+/// from a console server port to a device's console port
+#[derive(Debug, Clone, Serialize)]
+pub struct AuxConnection {
+    pub id: i64,
+    pub device_id: i64,
+    pub kind: String,
+    /// The device's power supply or console port
+    pub port_name: String,
+    /// The PDU or console server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_device_id: Option<i64>,
+    /// The PDU outlet or console server port
+    pub remote_port_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cable_length_meters: Option<f64>,
+    // Enriched via JOIN (not stored)
+    pub device_hostname: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_device_hostname: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// SetAuxConnectionRequest for creating/updating a power or console connection. The port name
+/// comes from the path.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetAuxConnectionRequest {
+    #[serde(default)]
+    pub remote_device_id: Option<i64>,
+    #[serde(default)]
+    pub remote_port_name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub cable_length_meters: Option<f64>,
+}
+
+/// CablingPlan lists everything cabled to a device — data ports, power feeds and console lines,
+/// including those landing on it as a patch panel, PDU or console server — for racking it or
+/// swapping it out under RMA
+#[derive(Debug, Clone, Serialize)]
+pub struct CablingPlan {
+    pub device_id: i64,
+    pub hostname: String,
+    pub data: Vec<PortAssignment>,
+    pub power: Vec<AuxConnection>,
+    pub console: Vec<AuxConnection>,
+    /// Power and console ports in the device model's layout with no connection
+    pub unconnected_ports: Vec<String>,
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Pre-flight checks, in the order they run
+pub mod preflight_check {
+    /// TCP connect to the device's SSH port
+    pub const REACHABILITY: &str = "reachability";
+    /// SSH login with the device's resolved credentials
+    pub const CREDENTIALS: &str = "credentials";
+    /// No configuration lock, and a free config session where the vendor limits them
+    pub const CONFIG_SESSION: &str = "config_session";
+    /// Device clock within the allowed skew, and NTP synchronized
+    pub const CLOCK: &str = "clock";
+}
+
+pub mod preflight_status {
+    pub const PASS: &str = "pass";
+    /// Worth a look, but doesn't block a deploy
+    pub const WARN: &str = "warn";
+    pub const FAIL: &str = "fail";
+    /// Not run: an earlier check failed, or the vendor has no command for it
+    pub const SKIPPED: &str = "skipped";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightCheck {
+    pub name: String,
+    pub status: String,
+    pub message: String,
+}
+
+/// PreflightResult is the outcome of the pre-flight checks on a device. `go` is false when any
+/// check failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightResult {
+    pub device_id: i64,
+    pub hostname: String,
+    pub go: bool,
+    pub checks: Vec<PreflightCheck>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl PreflightResult {
+    /// One line per failed check, for job errors
+    pub fn failures(&self) -> String {
+        self.checks
+            .iter()
+            .filter(|c| c.status == preflight_status::FAIL)
+            .map(|c| format!("{}: {}", c.name, c.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Provisioning session status constants
+pub mod provisioning_status {
+    /// Waiting for the device to re-bootstrap and come back online
+    pub const ACTIVE: &str = "active";
+    /// The device took a new lease and came back online
+    pub const COMPLETED: &str = "completed";
+    /// Replaced by a newer reprovision of the same device
+    pub const SUPERSEDED: &str = "superseded";
+}
+
+/// ProvisioningSession tracks a device re-bootstrapping after a reprovision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisioningSession {
+    pub id: i64,
+    pub device_id: i64,
+    pub status: String,
+    pub config_file: String,
+    pub lease_released: bool,
+    pub started_at: DateTime<Utc>,
+    /// Last DHCP lease seen for the device during the session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lease_at: Option<DateTime<Utc>>,
+    /// Last time the device fetched its config over HTTP during the session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_pulled_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// ReprovisionQuery holds options for a device reprovision
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReprovisionQuery {
+    /// Release the device's DHCP lease, forcing a fresh DISCOVER
+    #[serde(default)]
+    pub release_lease: bool,
+}
+
+/// ReprovisionResponse reports what a reprovision did
+#[derive(Debug, Clone, Serialize)]
+pub struct ReprovisionResponse {
+    pub session: ProvisioningSession,
+    /// Set when the lease release was requested but failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lease_error: Option<String>,
+}
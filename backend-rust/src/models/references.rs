@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// ReferenceCount is one kind of row that refers to an entity
+#[derive(Debug, Clone, Serialize)]
+pub struct ReferenceCount {
+    /// e.g. "devices", "child_groups"
+    pub kind: String,
+    pub count: i64,
+    /// A few names of the referring rows
+    pub examples: Vec<String>,
+    /// Whether the rows are deleted along with the entity, rather than just losing the reference
+    pub deleted_with_entity: bool,
+}
+
+/// EntityReferences lists what refers to a vendor, template or group, checked before deleting it
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityReferences {
+    pub resource_type: String,
+    pub id: i64,
+    pub name: String,
+    /// The on-delete policy that applies (see delete_policy)
+    pub on_delete: String,
+    pub in_use: bool,
+    pub references: Vec<ReferenceCount>,
+}
+
+/// Query string of vendor, template and group deletes
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeleteQuery {
+    /// Delete even if still referenced, clearing the references
+    #[serde(default)]
+    pub force: bool,
+}
@@ -0,0 +1,76 @@
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// RenderInventory describes devices, templates and variables to render without the database,
+/// e.g. from a Git repository in CI
+#[derive(Debug, Clone, Deserialize)]
+pub struct RenderInventory {
+    /// Settings exposed to templates as Subnet and Gateway
+    #[serde(default)]
+    pub dhcp_subnet: String,
+    #[serde(default)]
+    pub dhcp_gateway: String,
+    pub templates: Vec<InventoryTemplate>,
+    #[serde(default)]
+    pub groups: Vec<InventoryGroup>,
+    /// Variables every device starts from, like the "all" group
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+    pub devices: Vec<InventoryDevice>,
+    /// "json" (default) or "zip"
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// InventoryTemplate is a template by name, given inline or (from the CLI) as a file path
+/// relative to the inventory file
+#[derive(Debug, Clone, Deserialize)]
+pub struct InventoryTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+/// InventoryGroup carries variables for its member devices; higher precedence wins
+#[derive(Debug, Clone, Deserialize)]
+pub struct InventoryGroup {
+    pub name: String,
+    #[serde(default)]
+    pub precedence: i32,
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+}
+
+/// InventoryDevice is a device to render with the named template and role layers
+#[derive(Debug, Clone, Deserialize)]
+pub struct InventoryDevice {
+    pub hostname: String,
+    #[serde(default)]
+    pub ip: String,
+    #[serde(default)]
+    pub mac: Option<String>,
+    #[serde(default)]
+    pub vendor: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub serial_number: Option<String>,
+    #[serde(default)]
+    pub topology_id: Option<i64>,
+    #[serde(default)]
+    pub topology_role: Option<String>,
+    pub template: String,
+    /// Role layers in include order
+    #[serde(default)]
+    pub role_templates: Vec<String>,
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Host variables; these override group variables
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+    /// Custom field values, exposed to templates as {{custom_fields.<name>}}
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, serde_json::Value>,
+}
@@ -0,0 +1,221 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// StatusTransition is one recorded change of a device's status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusTransition {
+    pub device_id: i64,
+    pub previous_status: String,
+    pub status: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Time accounting for one device (or a sum over devices) within a report period
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct AvailabilitySpan {
+    /// Seconds with a known status
+    pub monitored_secs: i64,
+    /// Seconds offline outside maintenance windows
+    pub downtime_secs: i64,
+    /// Seconds offline inside maintenance windows (not counted as downtime)
+    pub maintenance_secs: i64,
+    pub outages: u32,
+}
+
+impl AvailabilitySpan {
+    pub fn add(&mut self, other: &AvailabilitySpan) {
+        self.monitored_secs += other.monitored_secs;
+        self.downtime_secs += other.downtime_secs;
+        self.maintenance_secs += other.maintenance_secs;
+        self.outages += other.outages;
+    }
+
+    /// Percentage of monitored time not lost to downtime; None when nothing was monitored
+    pub fn availability_pct(&self) -> Option<f64> {
+        if self.monitored_secs <= 0 {
+            return None;
+        }
+        let up = (self.monitored_secs - self.downtime_secs) as f64;
+        Some((up / self.monitored_secs as f64 * 100_000.0).round() / 1000.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceAvailability {
+    pub device_id: i64,
+    pub hostname: String,
+    #[serde(flatten)]
+    pub span: AvailabilitySpan,
+    pub availability_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupAvailability {
+    pub group_id: i64,
+    pub name: String,
+    pub devices: usize,
+    #[serde(flatten)]
+    pub span: AvailabilitySpan,
+    pub availability_pct: Option<f64>,
+}
+
+/// AvailabilityReport is the monthly uptime/SLA report
+#[derive(Debug, Clone, Serialize)]
+pub struct AvailabilityReport {
+    pub month: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub devices: Vec<DeviceAvailability>,
+    pub groups: Vec<GroupAvailability>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AvailabilityQuery {
+    /// `YYYY-MM`, defaults to the current month
+    #[serde(default)]
+    pub month: Option<String>,
+    /// Only report devices that are direct members of this group
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    /// `json` (default) or `csv`
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+pub mod report_run_status {
+    pub const SUCCESS: &str = "success";
+    pub const FAILED: &str = "failed";
+}
+
+/// Default backup age after which a device counts as stale in fleet reports
+pub const DEFAULT_STALE_BACKUP_DAYS: i64 = 7;
+
+fn default_stale_backup_days() -> i64 {
+    DEFAULT_STALE_BACKUP_DAYS
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A device with interface drift
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetDriftEntry {
+    pub device_id: i64,
+    pub hostname: String,
+    pub mismatches: usize,
+    /// Not drifted at the previous run of the schedule
+    pub new: bool,
+}
+
+/// A syslog alert rule with unacknowledged alerts
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetFailingRule {
+    pub rule_id: i64,
+    pub rule_name: String,
+    pub unacknowledged: usize,
+    pub last_alert_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetFailedJob {
+    pub job_id: String,
+    pub device_id: i64,
+    pub hostname: String,
+    pub job_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A device whose latest backup is older than the threshold, or that was never backed up
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetStaleBackup {
+    pub device_id: i64,
+    pub hostname: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_backup: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetOfflineDevice {
+    pub device_id: i64,
+    pub hostname: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// FleetReport summarizes fleet health since `since`: interface drift, syslog alert rules with
+/// open alerts, failed jobs, stale backups and offline devices
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetReport {
+    pub generated_at: DateTime<Utc>,
+    pub since: DateTime<Utc>,
+    pub device_count: usize,
+    pub stale_backup_days: i64,
+    pub new_drift: usize,
+    pub drift: Vec<FleetDriftEntry>,
+    pub failing_rules: Vec<FleetFailingRule>,
+    pub failed_jobs: Vec<FleetFailedJob>,
+    pub stale_backups: Vec<FleetStaleBackup>,
+    pub offline_devices: Vec<FleetOfflineDevice>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FleetReportQuery {
+    #[serde(default = "default_stale_backup_days")]
+    pub stale_backup_days: i64,
+    /// Failed jobs are counted over this many hours (default 24)
+    #[serde(default)]
+    pub since_hours: Option<i64>,
+    /// `json` (default) or `text`, the body sent to email channels
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// ReportSchedule sends a fleet report to notification channels on a cron schedule. Failed jobs
+/// are counted since the previous run, and drift is marked new against the previous run.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportSchedule {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub schedule: String,
+    pub channel_ids: Vec<i64>,
+    pub stale_backup_days: i64,
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip)]
+    pub last_drift_device_ids: Vec<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateReportScheduleRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub schedule: String,
+    #[serde(default)]
+    pub channel_ids: Vec<i64>,
+    #[serde(default = "default_stale_backup_days")]
+    pub stale_backup_days: i64,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl Validate for CreateReportScheduleRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.required("schedule", &self.schedule);
+        v.cron("schedule", &self.schedule);
+        v.min("stale_backup_days", self.stale_backup_days, 1);
+    }
+}
@@ -0,0 +1,188 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+pub mod runbook_step_type {
+    /// Instructions carried out by a person
+    pub const MANUAL: &str = "manual";
+    /// Run a vendor action on the run's device
+    pub const VENDOR_ACTION: &str = "vendor_action";
+    /// Queue a job template against the run's device
+    pub const JOB_TEMPLATE: &str = "job_template";
+
+    pub const ALL: &[&str] = &[MANUAL, VENDOR_ACTION, JOB_TEMPLATE];
+}
+
+pub mod runbook_run_status {
+    pub const IN_PROGRESS: &str = "in_progress";
+    pub const COMPLETED: &str = "completed";
+    pub const FAILED: &str = "failed";
+    pub const ABORTED: &str = "aborted";
+}
+
+pub mod runbook_step_status {
+    pub const PENDING: &str = "pending";
+    /// An automated step whose jobs were queued, awaiting sign-off
+    pub const EXECUTED: &str = "executed";
+    pub const DONE: &str = "done";
+    pub const SKIPPED: &str = "skipped";
+    pub const FAILED: &str = "failed";
+
+    /// Statuses a step can be signed off with
+    pub const SIGN_OFF: &[&str] = &[DONE, SKIPPED, FAILED];
+}
+
+/// RunbookStep is one step of a runbook: `instructions` for the operator, plus the vendor action
+/// or job template an automated step runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunbookStep {
+    pub title: String,
+    #[serde(default)]
+    pub instructions: String,
+    pub step_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor_action_id: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_template_id: Option<i64>,
+}
+
+impl Validate for RunbookStep {
+    fn validate(&self, v: &mut Validator) {
+        v.required("title", &self.title);
+        v.one_of("step_type", &self.step_type, runbook_step_type::ALL);
+        match self.step_type.as_str() {
+            runbook_step_type::VENDOR_ACTION => {
+                v.check(self.vendor_action_id.is_some(), "vendor_action_id", "is required");
+            }
+            runbook_step_type::JOB_TEMPLATE => {
+                v.check(self.job_template_id.is_some(), "job_template_id", "is required");
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Runbook is an ordered list of steps for a procedure such as a cutover. Runbooks attached to a
+/// device role only run on devices with that role.
+#[derive(Debug, Clone, Serialize)]
+pub struct Runbook {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_role_id: Option<i64>,
+    pub steps: Vec<RunbookStep>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateRunbookRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub device_role_id: Option<i64>,
+    pub steps: Vec<RunbookStep>,
+}
+
+impl Validate for CreateRunbookRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.check(!self.steps.is_empty(), "steps", "at least one step is required");
+        for (i, step) in self.steps.iter().enumerate() {
+            v.nested(&format!("steps[{}]", i), step);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunbookQuery {
+    #[serde(default)]
+    pub device_role_id: Option<i64>,
+}
+
+/// RunbookRunStep is a step of a run, copied from the runbook when the run started
+#[derive(Debug, Clone, Serialize)]
+pub struct RunbookRunStep {
+    pub position: i64,
+    pub title: String,
+    pub instructions: String,
+    pub step_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor_action_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_template_id: Option<i64>,
+    pub status: String,
+    /// Jobs queued by the step's last execution
+    pub job_ids: Vec<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub signed_off_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_off_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+
+impl RunbookRunStep {
+    pub fn is_signed_off(&self) -> bool {
+        runbook_step_status::SIGN_OFF.contains(&self.status.as_str())
+    }
+}
+
+/// RunbookRun is one execution of a runbook against a device, worked through step by step
+#[derive(Debug, Clone, Serialize)]
+pub struct RunbookRun {
+    pub id: i64,
+    pub runbook_id: i64,
+    pub runbook_name: String,
+    pub device_id: i64,
+    pub hostname: String,
+    pub status: String,
+    pub started_by: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+    pub steps: Vec<RunbookRunStep>,
+}
+
+impl RunbookRun {
+    /// The first step not yet signed off
+    pub fn current_step(&self) -> Option<&RunbookRunStep> {
+        self.steps.iter().find(|s| !s.is_signed_off())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StartRunbookRunRequest {
+    pub device_id: i64,
+}
+
+fn default_sign_off_status() -> String {
+    runbook_step_status::DONE.to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunbookSignOffRequest {
+    /// `done` (default), `skipped`, or `failed`, which fails the run
+    #[serde(default = "default_sign_off_status")]
+    pub status: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+fn default_run_limit() -> i64 {
+    100
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunbookRunQuery {
+    #[serde(default)]
+    pub runbook_id: Option<i64>,
+    #[serde(default)]
+    pub device_id: Option<i64>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default = "default_run_limit")]
+    pub limit: i64,
+}
@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// A configuration value that differs between the running process and the config source
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChange {
+    pub key: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+/// ConfigReloadResult reports what a reload applied and what still needs a restart
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigReloadResult {
+    pub reloaded_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_file: Option<String>,
+    pub applied: Vec<ConfigChange>,
+    pub restart_required: Vec<ConfigChange>,
+}
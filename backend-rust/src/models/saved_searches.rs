@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// Comparison operators for variable predicates
+pub mod variable_op {
+    pub const EQ: &str = "eq";
+    pub const NE: &str = "ne";
+    pub const CONTAINS: &str = "contains";
+    pub const EXISTS: &str = "exists";
+    pub const MISSING: &str = "missing";
+
+    pub fn is_valid(op: &str) -> bool {
+        matches!(op, EQ | NE | CONTAINS | EXISTS | MISSING)
+    }
+}
+
+fn default_variable_op() -> String {
+    variable_op::EQ.to_string()
+}
+
+/// A predicate on a device's own variables (not inherited group variables)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariablePredicate {
+    pub key: String,
+    #[serde(default = "default_variable_op")]
+    pub op: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// DeviceFilter is a declarative device selection — all set fields must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceFilter {
+    /// Vendor ID or vendor name (case-insensitive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+    /// Direct membership in this group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topology_id: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topology_role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu_cluster_id: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_type: Option<String>,
+    /// Hostname glob, `*` matches any run of characters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// true = has at least one backup, false = never backed up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_backup: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<VariablePredicate>,
+    /// Predicates on custom field values, keyed by field name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_fields: Vec<VariablePredicate>,
+    /// Tag selectors (`key` or `key=value`), all must match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Validate for DeviceFilter {
+    fn validate(&self, v: &mut Validator) {
+        for (i, pred) in self.variables.iter().enumerate() {
+            v.required(&format!("variables[{}].key", i), &pred.key);
+            v.check(
+                variable_op::is_valid(&pred.op),
+                &format!("variables[{}].op", i),
+                "must be one of eq, ne, contains, exists, missing",
+            );
+        }
+    }
+}
+
+/// SavedSearch is a named, reusable device filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub id: i64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub filter: DeviceFilter,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateSavedSearchRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub filter: DeviceFilter,
+}
+
+impl Validate for CreateSavedSearchRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.nested("filter", &self.filter);
+    }
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// SearchHit is a single global search match with a highlighted snippet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub id: String,
+    pub title: String,
+    pub snippet: String,
+}
+
+/// SearchResults groups global search hits by resource type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub devices: Vec<SearchHit>,
+    pub templates: Vec<SearchHit>,
+    pub jobs: Vec<SearchHit>,
+    pub discovery_logs: Vec<SearchHit>,
+}
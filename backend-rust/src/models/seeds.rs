@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// Kinds of tracked seed items
+pub mod seed_item_kind {
+    pub const TEMPLATE: &str = "template";
+}
+
+/// SeedSetVersion is a seed set's current version and the version applied to this database
+#[derive(Debug, Clone, Serialize)]
+pub struct SeedSetVersion {
+    pub name: String,
+    pub version: i64,
+    pub applied_version: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+/// SeedItem tracks one seeded resource and the seed content last applied to it
+#[derive(Debug, Clone, Serialize)]
+pub struct SeedItem {
+    pub id: i64,
+    pub kind: String,
+    pub seed_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_id: Option<i64>,
+    #[serde(skip_serializing)]
+    pub applied_content: String,
+    /// Protects the item from being overwritten by seed upgrades
+    pub user_modified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_content: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// PendingSeedUpdate is a seed upgrade held back because the local copy was customized
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingSeedUpdate {
+    #[serde(flatten)]
+    pub item: SeedItem,
+    pub name: String,
+    /// Unified diff from the local copy to the pending seed content
+    pub diff: String,
+}
+
+/// SeedStatus lists seed set versions and held-back seed upgrades
+#[derive(Debug, Clone, Serialize)]
+pub struct SeedStatus {
+    pub sets: Vec<SeedSetVersion>,
+    pub pending: Vec<PendingSeedUpdate>,
+}
+
+/// UpdateSeedItemRequest sets or clears an item's protection from seed upgrades
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateSeedItemRequest {
+    pub user_modified: bool,
+}
+
+impl Validate for UpdateSeedItemRequest {
+    fn validate(&self, _v: &mut Validator) {}
+}
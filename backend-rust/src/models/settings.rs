@@ -39,12 +39,129 @@ pub struct Settings {
     pub default_mgmt_switch_model: Option<String>,
     #[serde(default)]
     pub default_gpu_model: Option<String>,
+    // Publish PTR records for IPAM addresses with a dns_name in the dnsmasq config (on the next config generation)
+    #[serde(default)]
+    pub dns_ptr_records: bool,
+    // Credentials not rotated (or not used) within this many days are flagged as stale
+    #[serde(default = "default_credential_max_age_days")]
+    pub credential_max_age_days: i32,
+    // Community template catalog: a Git repository of templates that can be browsed and installed
+    #[serde(default)]
+    pub template_catalog_url: Option<String>,
+    #[serde(default)]
+    pub template_catalog_branch: Option<String>,
+    // Database backups are also PUT to {db_backup_upload_url}/{filename} when an upload is requested
+    #[serde(default)]
+    pub db_backup_upload_url: Option<String>,
+    // Hours between scheduled VACUUM/ANALYZE runs (0 disables)
+    #[serde(default = "default_db_maintenance_interval_hours")]
+    pub db_maintenance_interval_hours: i32,
+    // Months of job, syslog, lease and status history kept in partitions; older months are dropped (0 keeps them all)
+    #[serde(default)]
+    pub partition_retention_months: i32,
+    // Run pre-flight checks before each deploy job and fail the job on a no-go
+    #[serde(default)]
+    pub preflight_before_deploy: bool,
+    // Largest device clock offset the pre-flight clock check accepts
+    #[serde(default = "default_preflight_max_clock_skew_secs")]
+    pub preflight_max_clock_skew_secs: i32,
+    // Rollback timer for confirmed deploys; the commit is confirmed once the device is verified reachable
+    #[serde(default = "default_confirmed_deploy_timer_secs")]
+    pub confirmed_deploy_timer_secs: i32,
+    // Device backups run at once; backups of the same device never overlap
+    #[serde(default = "default_backup_concurrency")]
+    pub backup_concurrency: i32,
+    // Back a device up after each successful deploy or template apply on it
+    #[serde(default = "default_true")]
+    pub backup_after_change: bool,
+    // Seconds to wait after the change before that backup
+    #[serde(default = "default_backup_after_change_delay_secs")]
+    pub backup_after_change_delay_secs: i32,
+    // Jobs still running after this many seconds fail; a job can set its own limit (0 disables)
+    #[serde(default = "default_job_timeout_secs")]
+    pub job_timeout_secs: i32,
+    // Notify this many days before a support contract expires
+    #[serde(default = "default_contract_alert_days")]
+    pub contract_alert_days: i32,
+    // Notification channels that receive support contract expiry alerts (none disables them)
+    #[serde(default)]
+    pub contract_alert_channel_ids: Vec<i64>,
+    // Hours of gNMI telemetry samples kept (0 keeps them all)
+    #[serde(default = "default_telemetry_retention_hours")]
+    pub telemetry_retention_hours: i32,
+    // What happens when a referenced vendor, template or group is deleted
+    #[serde(default)]
+    pub delete_policy: DeletePolicy,
+    // Subsystems that can be switched off at runtime
+    #[serde(default)]
+    pub features: FeatureFlags,
+}
+
+/// FeatureFlags turn risky subsystems off without a restart
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FeatureFlags {
+    /// Stop recording DHCP leases as discovered devices
+    #[serde(default)]
+    pub disable_discovery: bool,
+    /// Stop the automatic backup after a device takes a DHCP lease (manual backups still run)
+    #[serde(default)]
+    pub disable_auto_backups: bool,
+    /// Stop running scheduled job templates
+    #[serde(default)]
+    pub disable_scheduler: bool,
+    /// Reject mutating API calls, except login and settings updates
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// On-delete behaviors for entities that other rows refer to
+pub mod delete_policy {
+    /// Refuse to delete while anything still refers to the entity
+    pub const RESTRICT: &str = "restrict";
+    /// Delete anyway: references are cleared and rows owned by the entity are deleted with it
+    pub const CASCADE: &str = "cascade";
+}
+
+fn default_delete_policy() -> String {
+    delete_policy::RESTRICT.to_string()
+}
+
+/// DeletePolicy sets the on-delete behavior per entity type. A delete with `?force=true`
+/// always cascades.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeletePolicy {
+    #[serde(default = "default_delete_policy")]
+    pub vendors: String,
+    #[serde(default = "default_delete_policy")]
+    pub templates: String,
+    #[serde(default = "default_delete_policy")]
+    pub groups: String,
+}
+
+impl Default for DeletePolicy {
+    fn default() -> Self {
+        Self {
+            vendors: default_delete_policy(),
+            templates: default_delete_policy(),
+            groups: default_delete_policy(),
+        }
+    }
 }
 
 fn default_hostname_pattern() -> String {
     "$datacenter-$role-#".to_string()
 }
 fn default_cable_slack_percent() -> i32 { 20 }
+fn default_credential_max_age_days() -> i32 { 90 }
+fn default_db_maintenance_interval_hours() -> i32 { 168 }
+fn default_preflight_max_clock_skew_secs() -> i32 { 30 }
+fn default_confirmed_deploy_timer_secs() -> i32 { 300 }
+fn default_backup_concurrency() -> i32 { 4 }
+fn default_backup_after_change_delay_secs() -> i32 { 30 }
+fn default_job_timeout_secs() -> i32 { 1800 }
+fn default_contract_alert_days() -> i32 { 60 }
+fn default_telemetry_retention_hours() -> i32 { 24 }
+fn default_true() -> bool { true }
 
 impl Default for Settings {
     fn default() -> Self {
@@ -69,6 +186,25 @@ impl Default for Settings {
             default_leaf_model: None,
             default_mgmt_switch_model: None,
             default_gpu_model: None,
+            dns_ptr_records: false,
+            credential_max_age_days: default_credential_max_age_days(),
+            template_catalog_url: None,
+            template_catalog_branch: None,
+            db_backup_upload_url: None,
+            db_maintenance_interval_hours: default_db_maintenance_interval_hours(),
+            partition_retention_months: 0,
+            preflight_before_deploy: false,
+            preflight_max_clock_skew_secs: default_preflight_max_clock_skew_secs(),
+            confirmed_deploy_timer_secs: default_confirmed_deploy_timer_secs(),
+            backup_concurrency: default_backup_concurrency(),
+            backup_after_change: true,
+            backup_after_change_delay_secs: default_backup_after_change_delay_secs(),
+            job_timeout_secs: default_job_timeout_secs(),
+            contract_alert_days: default_contract_alert_days(),
+            contract_alert_channel_ids: Vec::new(),
+            telemetry_retention_hours: default_telemetry_retention_hours(),
+            delete_policy: DeletePolicy::default(),
+            features: FeatureFlags::default(),
         }
     }
 }
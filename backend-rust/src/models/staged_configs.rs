@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+pub mod staged_config_status {
+    /// Waiting for its activation time, or for a maintenance window
+    pub const SCHEDULED: &str = "scheduled";
+    /// Deploy job queued
+    pub const ACTIVATING: &str = "activating";
+    pub const ACTIVATED: &str = "activated";
+    pub const FAILED: &str = "failed";
+    pub const CANCELLED: &str = "cancelled";
+}
+
+/// StagedConfig is a config held for a device until its activation time, then pushed by a
+/// staged_deploy job
+#[derive(Debug, Clone, Serialize)]
+pub struct StagedConfig {
+    pub id: i64,
+    pub device_id: i64,
+    pub hostname: String,
+    pub description: String,
+    pub config: String,
+    pub activate_at: DateTime<Utc>,
+    pub require_maintenance_window: bool,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub error: String,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateStagedConfigRequest {
+    pub activate_at: DateTime<Utc>,
+    /// Config to push; defaults to the device's config rendered now
+    #[serde(default)]
+    pub config: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub require_maintenance_window: bool,
+}
+
+impl Validate for CreateStagedConfigRequest {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(config) = &self.config {
+            v.required("config", config);
+        }
+    }
+}
+
+/// Changes to a scheduled staged config; fields left out are kept
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateStagedConfigRequest {
+    #[serde(default)]
+    pub activate_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub config: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub require_maintenance_window: Option<bool>,
+}
+
+impl Validate for UpdateStagedConfigRequest {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(config) = &self.config {
+            v.required("config", config);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StagedConfigQuery {
+    #[serde(default)]
+    pub device_id: Option<i64>,
+    #[serde(default)]
+    pub status: Option<String>,
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// What a support contract covers
+pub mod contract_scope {
+    pub const DEVICE: &str = "device";
+    pub const MODEL: &str = "model";
+}
+
+/// SupportContract is a warranty or support contract for one device, or for every device of a
+/// model that has no contract of its own
+#[derive(Debug, Clone, Serialize)]
+pub struct SupportContract {
+    pub id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_model_id: Option<i64>,
+    pub provider: String,
+    pub support_id: String,
+    pub vendor_sla: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starts_on: Option<NaiveDate>,
+    pub expires_on: NaiveDate,
+    pub notes: String,
+    /// Set once the expiry notification for the current expires_on was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alerted_expires_on: Option<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SupportContract {
+    pub fn scope(&self) -> &'static str {
+        if self.device_id.is_some() {
+            contract_scope::DEVICE
+        } else {
+            contract_scope::MODEL
+        }
+    }
+}
+
+/// Exactly one of `device_id` and `device_model_id` must be set
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateSupportContractRequest {
+    #[serde(default)]
+    pub device_id: Option<i64>,
+    #[serde(default)]
+    pub device_model_id: Option<i64>,
+    #[serde(default)]
+    pub provider: String,
+    #[serde(default)]
+    pub support_id: String,
+    #[serde(default)]
+    pub vendor_sla: String,
+    #[serde(default)]
+    pub starts_on: Option<NaiveDate>,
+    pub expires_on: NaiveDate,
+    #[serde(default)]
+    pub notes: String,
+}
+
+impl Validate for CreateSupportContractRequest {
+    fn validate(&self, v: &mut Validator) {
+        if self.device_id.is_some() == self.device_model_id.is_some() {
+            v.error("device_id", "exactly one of device_id and device_model_id is required");
+        }
+        if let Some(starts_on) = self.starts_on {
+            v.check(starts_on <= self.expires_on, "starts_on", "must not be after expires_on");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContractExpiryQuery {
+    /// Report contracts expiring within this many days; defaults to the contract_alert_days setting
+    #[serde(default)]
+    pub days: Option<i64>,
+    /// Also list devices without any contract
+    #[serde(default)]
+    pub include_uncovered: bool,
+}
+
+/// ContractExpiryRow is one device whose effective contract expires within the window, or
+/// already expired (negative days_remaining)
+#[derive(Debug, Clone, Serialize)]
+pub struct ContractExpiryRow {
+    pub device_id: i64,
+    pub hostname: String,
+    pub vendor: String,
+    pub model: String,
+    pub serial_number: String,
+    pub contract_id: i64,
+    /// `device` or `model`: where the contract is attached
+    pub scope: String,
+    pub provider: String,
+    pub support_id: String,
+    pub vendor_sla: String,
+    pub expires_on: NaiveDate,
+    pub days_remaining: i64,
+}
+
+/// UncoveredDevice has no contract of its own nor through its model
+#[derive(Debug, Clone, Serialize)]
+pub struct UncoveredDevice {
+    pub device_id: i64,
+    pub hostname: String,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContractExpiryReport {
+    pub generated_at: DateTime<Utc>,
+    pub within_days: i64,
+    /// Devices whose contract already lapsed
+    pub expired: usize,
+    /// Soonest expiry first
+    pub rows: Vec<ContractExpiryRow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uncovered: Option<Vec<UncoveredDevice>>,
+}
+
+/// ContractAlertResult reports an on-demand expiry check
+#[derive(Debug, Clone, Serialize)]
+pub struct ContractAlertResult {
+    /// Contracts notified about; already-notified contracts are skipped
+    pub alerted: usize,
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// SyslogEvent is a message received from a device, matched to it by source IP
+#[derive(Debug, Clone, Serialize)]
+pub struct SyslogEvent {
+    pub id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_hostname: Option<String>,
+    pub source_ip: String,
+    pub facility: i64,
+    pub severity: i64,
+    pub hostname: String,
+    pub app_name: String,
+    pub message: String,
+    pub received_at: DateTime<Utc>,
+}
+
+/// SyslogMessage is a parsed RFC 3164 or RFC 5424 message
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyslogMessage {
+    pub facility: i64,
+    pub severity: i64,
+    pub hostname: String,
+    pub app_name: String,
+    pub message: String,
+}
+
+fn default_max_severity() -> i64 {
+    7
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// SyslogAlertRule raises an alert for received messages matching `pattern` (a regex)
+/// with a severity of `max_severity` or more severe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyslogAlertRule {
+    pub id: i64,
+    pub name: String,
+    pub pattern: String,
+    pub max_severity: i64,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateSyslogAlertRuleRequest {
+    pub name: String,
+    pub pattern: String,
+    #[serde(default = "default_max_severity")]
+    pub max_severity: i64,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl Validate for CreateSyslogAlertRuleRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.required("pattern", &self.pattern);
+        v.regex("pattern", &self.pattern);
+        v.range("max_severity", self.max_severity, 0, 7);
+    }
+}
+
+/// SyslogAlert is a received message that matched an alert rule
+#[derive(Debug, Clone, Serialize)]
+pub struct SyslogAlert {
+    pub id: i64,
+    pub rule_id: i64,
+    pub rule_name: String,
+    pub event_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_hostname: Option<String>,
+    pub source_ip: String,
+    pub severity: i64,
+    pub message: String,
+    pub acknowledged: bool,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub acknowledged_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+fn default_syslog_limit() -> i64 {
+    200
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyslogEventQuery {
+    #[serde(default)]
+    pub device_id: Option<i64>,
+    /// Only events at this severity or more severe
+    #[serde(default)]
+    pub max_severity: Option<i64>,
+    /// Case-insensitive substring of the message
+    #[serde(default)]
+    pub q: Option<String>,
+    #[serde(default = "default_syslog_limit")]
+    pub limit: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyslogAlertQuery {
+    #[serde(default)]
+    pub device_id: Option<i64>,
+    #[serde(default)]
+    pub acknowledged: Option<bool>,
+    #[serde(default = "default_syslog_limit")]
+    pub limit: i64,
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Resource types that accept tags
+pub mod tag_resource {
+    pub const DEVICE: &str = "device";
+    pub const TEMPLATE: &str = "template";
+    pub const CREDENTIAL: &str = "credential";
+    pub const JOB: &str = "job";
+
+    pub fn is_valid(resource_type: &str) -> bool {
+        matches!(resource_type, DEVICE | TEMPLATE | CREDENTIAL | JOB)
+    }
+}
+
+/// ResourceTag is a key/value label attached to a device, template, credential or job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceTag {
+    pub id: i64,
+    pub resource_type: String,
+    pub resource_id: String,
+    pub key: String,
+    pub value: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetResourceTagRequest {
+    pub key: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// TagSelector matches resources by tag: `key` matches any value, `key=value` an exact one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagSelector {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl TagSelector {
+    pub fn parse(s: &str) -> Option<Self> {
+        let (key, value) = match s.split_once('=') {
+            Some((k, v)) => (k.trim(), Some(v.trim().to_string())),
+            None => (s.trim(), None),
+        };
+        if key.is_empty() {
+            return None;
+        }
+        Some(Self { key: key.to_string(), value })
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// Resource types a team can own
+pub mod team_resource_type {
+    pub const DEVICE: &str = "device";
+    pub const GROUP: &str = "group";
+    pub const TEMPLATE: &str = "template";
+}
+
+/// Device actions a team rule can grant
+pub mod team_action {
+    /// Deploy, apply or diff rendered config
+    pub const DEPLOY: &str = "deploy";
+    /// Run commands and vendor actions
+    pub const EXEC: &str = "exec";
+    pub const BACKUP: &str = "backup";
+    /// Every action
+    pub const ANY: &str = "*";
+}
+
+/// Devices a team rule applies to
+pub mod team_rule_scope {
+    pub const ALL: &str = "all";
+    /// Devices owned by the rule's team
+    pub const OWNED: &str = "owned";
+    /// Members of the rule's group or its subgroups
+    pub const GROUP: &str = "group";
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamMember {
+    pub user_id: i64,
+    pub username: String,
+}
+
+/// Team is a set of users that can own resources and be granted device actions
+#[derive(Debug, Clone, Serialize)]
+pub struct Team {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub members: Vec<TeamMember>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateTeamRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl Validate for CreateTeamRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetTeamMembersRequest {
+    pub user_ids: Vec<i64>,
+}
+
+/// TeamResource is a device, group or template owned by a team
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamResource {
+    pub resource_type: String,
+    pub resource_id: i64,
+    pub team_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TeamResourceRequest {
+    pub resource_type: String,
+    pub resource_id: i64,
+}
+
+/// TeamRule grants team members an action on a set of devices, e.g. "exec `show *` on all
+/// devices" or "deploy to members of the fabric group"
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamRule {
+    pub id: i64,
+    pub team_id: i64,
+    pub action: String,
+    pub scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<i64>,
+    /// Commands an exec rule allows; `*` matches any run of characters
+    pub command_pattern: String,
+    pub created_at: DateTime<Utc>,
+}
+
+fn default_command_pattern() -> String {
+    "*".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateTeamRuleRequest {
+    pub action: String,
+    pub scope: String,
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    #[serde(default = "default_command_pattern")]
+    pub command_pattern: String,
+}
+
+impl Validate for CreateTeamRuleRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.one_of("action", &self.action, &[team_action::DEPLOY, team_action::EXEC, team_action::BACKUP, team_action::ANY]);
+        v.one_of("scope", &self.scope, &[team_rule_scope::ALL, team_rule_scope::OWNED, team_rule_scope::GROUP]);
+        if self.scope == team_rule_scope::GROUP {
+            v.check(self.group_id.is_some(), "group_id", "is required for group scope");
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// gNMI subscription modes
+pub mod telemetry_mode {
+    /// Updates every sample_interval_secs
+    pub const SAMPLE: &str = "sample";
+    /// Updates when a value changes
+    pub const ON_CHANGE: &str = "on_change";
+    /// The device picks sample or on_change per leaf
+    pub const TARGET_DEFINED: &str = "target_defined";
+    pub const ALL: &[&str] = &[SAMPLE, ON_CHANGE, TARGET_DEFINED];
+}
+
+/// gNMI value encodings a subscription can ask for
+pub mod telemetry_encoding {
+    pub const JSON: &str = "json";
+    pub const JSON_IETF: &str = "json_ietf";
+    pub const PROTO: &str = "proto";
+    pub const ASCII: &str = "ascii";
+    pub const ALL: &[&str] = &[JSON, JSON_IETF, PROTO, ASCII];
+}
+
+fn default_telemetry_mode() -> String {
+    telemetry_mode::SAMPLE.to_string()
+}
+
+fn default_telemetry_encoding() -> String {
+    telemetry_encoding::JSON_IETF.to_string()
+}
+
+fn default_sample_interval_secs() -> i64 {
+    30
+}
+
+fn default_gnmi_port() -> i64 {
+    57400
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// TelemetrySubscription streams gNMI paths from one device, or from every device of a vendor
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetrySubscription {
+    pub id: i64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor_id: Option<i64>,
+    pub paths: Vec<String>,
+    pub mode: String,
+    pub sample_interval_secs: i64,
+    pub encoding: String,
+    pub port: i64,
+    pub tls: bool,
+    pub tls_skip_verify: bool,
+    /// PEM CA certificate the device's certificate must chain to; public roots when empty
+    pub tls_ca_cert: String,
+    /// Name the device's certificate is checked against; the device's IP when empty
+    pub tls_server_name: String,
+    /// Credential to log in with; the device's SSH credentials when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_id: Option<i64>,
+    /// Keep samples in the telemetry table; updates are streamed over WebSocket either way
+    pub store_samples: bool,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateTelemetrySubscriptionRequest {
+    pub name: String,
+    #[serde(default)]
+    pub device_id: Option<i64>,
+    #[serde(default)]
+    pub vendor_id: Option<i64>,
+    pub paths: Vec<String>,
+    #[serde(default = "default_telemetry_mode")]
+    pub mode: String,
+    #[serde(default = "default_sample_interval_secs")]
+    pub sample_interval_secs: i64,
+    #[serde(default = "default_telemetry_encoding")]
+    pub encoding: String,
+    #[serde(default = "default_gnmi_port")]
+    pub port: i64,
+    #[serde(default = "default_true")]
+    pub tls: bool,
+    #[serde(default)]
+    pub tls_skip_verify: bool,
+    #[serde(default)]
+    pub tls_ca_cert: String,
+    #[serde(default)]
+    pub tls_server_name: String,
+    #[serde(default)]
+    pub credential_id: Option<i64>,
+    #[serde(default = "default_true")]
+    pub store_samples: bool,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl Validate for CreateTelemetrySubscriptionRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.check(
+            self.device_id.is_some() != self.vendor_id.is_some(),
+            "device_id",
+            "set either device_id or vendor_id",
+        );
+        v.check(!self.paths.is_empty(), "paths", "at least one path is required");
+        for (i, path) in self.paths.iter().enumerate() {
+            v.check(
+                crate::gnmi::parse_path(path).is_some(),
+                &format!("paths[{}]", i),
+                "must be a gNMI path like /interfaces/interface[name=Ethernet1]/state/counters",
+            );
+        }
+        v.one_of("mode", &self.mode, telemetry_mode::ALL);
+        v.range("sample_interval_secs", self.sample_interval_secs, 1, 86400);
+        v.one_of("encoding", &self.encoding, telemetry_encoding::ALL);
+        v.range("port", self.port, 1, 65535);
+        v.check(
+            self.tls_ca_cert.is_empty() || self.tls_ca_cert.contains("-----BEGIN CERTIFICATE-----"),
+            "tls_ca_cert",
+            "must be a PEM certificate",
+        );
+    }
+}
+
+/// TelemetrySample is one leaf value a device streamed
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetrySample {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub device_id: i64,
+    pub path: String,
+    pub value: serde_json::Value,
+    /// When the device took the sample
+    pub timestamp: DateTime<Utc>,
+}
+
+/// TelemetryUpdate is a leaf value received from a device, before it is stored
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryUpdate {
+    pub path: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TelemetrySampleQuery {
+    #[serde(default)]
+    pub device_id: Option<i64>,
+    #[serde(default)]
+    pub subscription_id: Option<i64>,
+    /// Paths starting with this
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Only the newest sample of each device and path
+    #[serde(default)]
+    pub latest: bool,
+    #[serde(default = "default_sample_limit")]
+    pub limit: i64,
+}
+
+fn default_sample_limit() -> i64 {
+    500
+}
+
+/// TelemetryStream is the state of one device's stream for a subscription
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TelemetryStream {
+    pub subscription_id: i64,
+    pub subscription_name: String,
+    pub device_id: i64,
+    pub hostname: String,
+    pub connected: bool,
+    /// Notifications received since the stream started
+    pub updates: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_update: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How a catalog template relates to the local templates
+pub mod catalog_template_status {
+    /// Not installed
+    pub const AVAILABLE: &str = "available";
+    /// Installed and identical to the catalog version
+    pub const INSTALLED: &str = "installed";
+    /// Installed, unchanged locally, and the catalog has a newer version
+    pub const UPDATE_AVAILABLE: &str = "update_available";
+    /// Installed, but edited locally since
+    pub const LOCALLY_MODIFIED: &str = "locally_modified";
+    /// A local template with the same name exists that wasn't installed from the catalog
+    pub const CONFLICT: &str = "conflict";
+}
+
+/// Outcomes of installing a catalog template
+pub mod catalog_install_action {
+    pub const CREATED: &str = "created";
+    pub const UPDATED: &str = "updated";
+    pub const UNCHANGED: &str = "unchanged";
+    pub const SKIPPED: &str = "skipped";
+}
+
+/// TemplateProvenance records where an installed template came from
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateProvenance {
+    pub template_id: i64,
+    pub source_url: String,
+    pub source_path: String,
+    pub commit_sha: String,
+    /// The catalog content that was installed
+    #[serde(skip_serializing)]
+    pub content: String,
+    pub installed_by: String,
+    pub installed_at: DateTime<Utc>,
+}
+
+/// CatalogTemplate is a template file in the synced catalog checkout
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogTemplate {
+    /// Path relative to the repository root
+    pub path: String,
+    pub name: String,
+    pub description: String,
+    /// Vendor named by the template's top-level directory, when it matches a known vendor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor_id: Option<i64>,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed_commit: Option<String>,
+    pub content: String,
+}
+
+/// TemplateCatalog is the synced catalog with each template's local status
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateCatalog {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub commit: String,
+    pub templates: Vec<CatalogTemplate>,
+}
+
+/// TemplateCatalogInstallRequest installs or updates catalog templates by path.
+/// Locally modified templates and same-named local templates are only overwritten with `force`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemplateCatalogInstallRequest {
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// CatalogInstallResult reports what installing one catalog template did
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogInstallResult {
+    pub path: String,
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
 
 /// Template represents a configuration template
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +18,45 @@ pub struct Template {
     pub updated_at: DateTime<Utc>,
 }
 
+/// A commit of a template in the Git history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateRevision {
+    pub commit: String,
+    pub author: String,
+    pub message: String,
+    pub committed_at: DateTime<Utc>,
+}
+
+/// Template content to check without saving it; with `device_id`, `vars.*` lookups are checked
+/// against that device's resolved variables instead of every key set anywhere
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemplateValidationRequest {
+    pub content: String,
+    #[serde(default)]
+    pub device_id: Option<i64>,
+}
+
+/// A problem found in a template, at a 1-based line (and column, for syntax errors) when known
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateIssue {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+/// Result of checking a template: syntax errors make it invalid; variables no render would
+/// provide are warnings, since they may be set later
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateValidation {
+    pub valid: bool,
+    pub errors: Vec<TemplateIssue>,
+    /// Context variables the template reads, sorted; `vars.*` and `custom_fields.*` keep the key
+    pub variables: Vec<String>,
+    pub warnings: Vec<TemplateIssue>,
+}
+
 /// CreateTemplateRequest for creating new templates
 #[derive(Debug, Clone, Deserialize)]
 pub struct CreateTemplateRequest {
@@ -28,6 +68,13 @@ pub struct CreateTemplateRequest {
     pub content: String,
 }
 
+impl Validate for CreateTemplateRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.required("content", &self.content);
+    }
+}
+
 /// TemplatePreviewDevice contains the device fields for preview
 #[derive(Debug, Clone, Deserialize)]
 pub struct TemplatePreviewDevice {
@@ -76,3 +123,136 @@ pub struct TemplateVariable {
     pub description: String,
     pub example: String,
 }
+
+/// How a device ends up using a template
+pub mod template_usage_via {
+    /// Assigned as the device's config_template
+    pub const DIRECT: &str = "direct";
+    /// Default template of the device's vendor
+    pub const VENDOR_DEFAULT: &str = "vendor_default";
+    /// Mapped to the device's role and included as its role template
+    pub const ROLE: &str = "role";
+}
+
+/// TemplateUsageDevice is one device affected by a template change
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateUsageDevice {
+    pub device_id: i64,
+    pub hostname: String,
+    pub via: String,
+    /// ID of the device's base template (differs from the inspected template for role usage)
+    pub base_template_id: i64,
+    /// Unified diff of the latest backup against the currently rendered config (preview only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview_error: Option<String>,
+}
+
+/// TemplateUsage is the blast radius of editing or deleting a template
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateUsage {
+    pub template_id: i64,
+    pub template_name: String,
+    /// Vendors whose default_template is this template
+    pub vendors: Vec<String>,
+    pub devices: Vec<TemplateUsageDevice>,
+}
+
+/// ContextSchemaField describes one value available in the render context
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextSchemaField {
+    pub name: String,
+    /// Dotted path as written in a template, e.g. "VRFs[].interfaces[].port_name"
+    pub path: String,
+    /// JSON type of the value: string, integer, number, boolean, array, object
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub description: String,
+    pub example: serde_json::Value,
+    /// Fields of an object, or of each element of an array
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<ContextSchemaField>,
+}
+
+/// ContextSchemaInclude describes a template name usable with {% include %}
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextSchemaInclude {
+    pub name: String,
+    pub description: String,
+    pub example: String,
+}
+
+/// ContextSchemaFilter describes a filter usable in templates
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextSchemaFilter {
+    pub name: String,
+    pub description: String,
+    pub example: String,
+    /// Output of rendering the example against the sample context
+    pub output: String,
+}
+
+/// TemplateContextSchema is the machine-readable description of the render context
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateContextSchema {
+    pub variables: Vec<ContextSchemaField>,
+    pub includes: Vec<ContextSchemaInclude>,
+    pub filters: Vec<ContextSchemaFilter>,
+}
+
+/// RenderCacheStats reports render cache occupancy and effectiveness
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// BatchRenderRequest selects the devices to render: exactly one of group_id or topology_id
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchRenderRequest {
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    #[serde(default)]
+    pub topology_id: Option<i64>,
+    /// "json" (default) or "zip"
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// BatchRenderResponse maps hostnames to rendered configs, and to errors for devices that failed
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchRenderResponse {
+    pub configs: std::collections::BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub errors: std::collections::BTreeMap<String, String>,
+}
+
+/// TftpExportRequest selects the devices whose TFTP config files are rewritten: exactly one of device_id or group_id
+#[derive(Debug, Clone, Deserialize)]
+pub struct TftpExportRequest {
+    #[serde(default)]
+    pub device_id: Option<i64>,
+    #[serde(default)]
+    pub group_id: Option<i64>,
+}
+
+/// TftpExportResult is the outcome of writing one device's TFTP config file
+#[derive(Debug, Clone, Serialize)]
+pub struct TftpExportResult {
+    pub device_id: i64,
+    pub hostname: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// TftpExportResponse summarizes a TFTP export
+#[derive(Debug, Clone, Serialize)]
+pub struct TftpExportResponse {
+    pub written: usize,
+    pub failed: usize,
+    pub results: Vec<TftpExportResult>,
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tenant {
@@ -8,6 +9,8 @@ pub struct Tenant {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     pub status: String,
+    /// Devices assigned to this tenant
+    pub device_count: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -21,4 +24,21 @@ pub struct CreateTenantRequest {
     pub status: String,
 }
 
+impl Validate for CreateTenantRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+    }
+}
+
 fn default_tenant_status() -> String { "active".to_string() }
+
+/// A variable applied to every device assigned to the tenant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantVariable {
+    pub id: i64,
+    pub tenant_id: i64,
+    pub key: String,
+    pub value: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
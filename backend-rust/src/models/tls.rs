@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// ACME challenge types
+pub mod acme_challenge {
+    pub const HTTP_01: &str = "http-01";
+    pub const DNS_01: &str = "dns-01";
+}
+
+/// Where the served certificate came from
+pub mod tls_certificate_source {
+    /// TLS_CERT_PATH and TLS_KEY_PATH
+    pub const FILES: &str = "files";
+    pub const ACME: &str = "acme";
+    /// Placeholder served until the first ACME certificate is issued
+    pub const SELF_SIGNED: &str = "self_signed";
+}
+
+/// TlsStatus describes the HTTPS listener and its certificate
+#[derive(Debug, Clone, Serialize)]
+pub struct TlsStatus {
+    pub listen_addr: String,
+    pub source: String,
+    pub domains: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_renewed_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
 
 /// Canonical topology role values
 pub mod topology_role {
@@ -61,3 +62,43 @@ pub struct CreateTopologyRequest {
     #[serde(default)]
     pub datacenter_id: Option<i64>,
 }
+
+impl Validate for CreateTopologyRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+    }
+}
+
+/// LabNode maps a topology device to the container standing in for it in the virtual lab
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabNode {
+    pub id: i64,
+    pub topology_id: i64,
+    pub device_id: i64,
+    /// Joined from devices (not stored)
+    pub hostname: String,
+    pub container_name: String,
+    pub container_id: String,
+    pub image: String,
+    /// Management IP of the container; lab jobs connect here instead of the device IP
+    pub mgmt_ip: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// LabDeployRequest launches a topology as a virtual lab
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LabDeployRequest {
+    /// cEOS or FRR image; defaults to $CEOS_IMAGE
+    #[serde(default)]
+    pub image: String,
+}
+
+/// LabJobRequest queues deploy or diff jobs against every lab node of a topology
+#[derive(Debug, Clone, Deserialize)]
+pub struct LabJobRequest {
+    pub job_type: String,
+    /// Credential to log in to the lab nodes with; defaults to the lab image's admin account
+    #[serde(default)]
+    pub credential_id: String,
+}
@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::topology_role;
+
+/// Topology deploy status values
+pub mod topology_deploy_status {
+    pub const RUNNING: &str = "running";
+    pub const COMPLETED: &str = "completed";
+    pub const FAILED: &str = "failed";
+}
+
+/// Topology deploy stage status values, derived from the deploy's progress
+pub mod topology_deploy_stage_status {
+    pub const PENDING: &str = "pending";
+    pub const RUNNING: &str = "running";
+    pub const COMPLETED: &str = "completed";
+    pub const FAILED: &str = "failed";
+    pub const SKIPPED: &str = "skipped";
+}
+
+/// Default deploy order: the fabric's upper tiers before the tiers that depend on them
+pub fn default_deploy_stages() -> Vec<Vec<String>> {
+    [
+        topology_role::SUPER_SPINE,
+        topology_role::CORE,
+        topology_role::SPINE,
+        topology_role::DISTRIBUTION,
+        topology_role::LEAF,
+        topology_role::ACCESS,
+        topology_role::GPU_NODE,
+    ]
+    .iter()
+    .map(|role| vec![role.to_string()])
+    .collect()
+}
+
+/// TopologyDeploy is one orchestrated deploy of a whole topology
+#[derive(Debug, Clone, Serialize)]
+pub struct TopologyDeploy {
+    pub id: i64,
+    pub topology_id: i64,
+    pub status: String,
+    /// Topology roles deployed together, in order
+    pub stages: Vec<Vec<String>>,
+    /// Command run on every device of a stage after its deploy; empty gates on deploy success only
+    pub verify_command: String,
+    /// Deploy to the topology's virtual lab nodes instead of the devices
+    pub lab: bool,
+    /// Index of the stage in progress (or where the deploy stopped)
+    pub current_stage: i64,
+    pub error: String,
+    pub triggered_by: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// TopologyDeployRequest starts a topology deploy
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TopologyDeployRequest {
+    /// Stages of topology roles; defaults to super-spine, core, spine, distribution, leaf,
+    /// access, gpu-node. Devices whose role isn't in any stage are not deployed.
+    #[serde(default)]
+    pub stages: Option<Vec<Vec<String>>>,
+    #[serde(default)]
+    pub verify_command: String,
+    #[serde(default)]
+    pub lab: bool,
+}
+
+/// TopologyDeployDevice is a device planned into a deploy stage, with its jobs' status
+#[derive(Debug, Clone, Serialize)]
+pub struct TopologyDeployDevice {
+    pub device_id: i64,
+    pub hostname: String,
+    pub topology_role: String,
+    pub stage: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deploy_job_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deploy_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verify_job_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verify_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopologyDeployStage {
+    pub index: i64,
+    pub roles: Vec<String>,
+    pub status: String,
+    pub devices: Vec<TopologyDeployDevice>,
+}
+
+/// TopologyDeployProgress is the consolidated progress view of a topology deploy
+#[derive(Debug, Clone, Serialize)]
+pub struct TopologyDeployProgress {
+    #[serde(flatten)]
+    pub deploy: TopologyDeploy,
+    pub total_devices: usize,
+    /// Devices whose deploy (and verification, if any) succeeded
+    pub succeeded_devices: usize,
+    pub failed_devices: usize,
+    pub stage_progress: Vec<TopologyDeployStage>,
+}
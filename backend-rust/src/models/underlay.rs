@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+fn default_links_per_leaf() -> i64 {
+    1
+}
+
+fn default_underlay_asn() -> i64 {
+    65000
+}
+
+/// GenerateUnderlayRequest allocates a topology's spine-leaf underlay from an IPAM supernet
+#[derive(Debug, Clone, Deserialize)]
+pub struct GenerateUnderlayRequest {
+    /// IPAM prefix the loopback and link pools are carved from
+    pub supernet_id: i64,
+    /// Links between each spine and leaf, used only when the topology has no spine-leaf port
+    /// assignments to follow
+    #[serde(default = "default_links_per_leaf")]
+    pub links_per_leaf: i64,
+    /// ASN shared by the spines
+    #[serde(default = "default_underlay_asn")]
+    pub spine_asn: i64,
+    /// Leaf N (1-based, in hostname order) gets leaf_asn_base + N
+    #[serde(default = "default_underlay_asn")]
+    pub leaf_asn_base: i64,
+    /// Replace an underlay generated before: its variables are rewritten and its IPAM pools released
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+impl Validate for GenerateUnderlayRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.range("links_per_leaf", self.links_per_leaf, 1, 8);
+        v.range("spine_asn", self.spine_asn, 1, u32::MAX as i64);
+        v.range("leaf_asn_base", self.leaf_asn_base, 0, u32::MAX as i64 - 1);
+    }
+}
+
+/// Addressing and ASN generated for one device
+#[derive(Debug, Clone, Serialize)]
+pub struct UnderlayDevice {
+    pub device_id: i64,
+    pub hostname: String,
+    pub role: String,
+    pub asn: i64,
+    pub loopback: String,
+    pub peers: usize,
+}
+
+/// One spine-leaf /31; the spine takes the even address
+#[derive(Debug, Clone, Serialize)]
+pub struct UnderlayLink {
+    pub subnet: String,
+    pub spine: String,
+    pub spine_port: String,
+    pub spine_addr: String,
+    pub leaf: String,
+    pub leaf_port: String,
+    pub leaf_addr: String,
+}
+
+/// Result of generating a topology's underlay
+#[derive(Debug, Clone, Serialize)]
+pub struct UnderlayResult {
+    pub topology_id: i64,
+    pub loopback_pool: String,
+    pub link_pool: String,
+    /// Links came from the topology's port assignments rather than a generated full mesh
+    pub from_port_assignments: bool,
+    pub devices: Vec<UnderlayDevice>,
+    pub links: Vec<UnderlayLink>,
+    /// Device variables written
+    pub variables: usize,
+}
@@ -0,0 +1,117 @@
+use serde::Serialize;
+
+/// One invalid field of a request body
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Checks a request body field by field, collecting every failure rather than stopping at the
+/// first, so a form can mark all of its bad inputs at once
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn error(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(FieldError { field: field.into(), message: message.into() });
+    }
+
+    pub fn check(&mut self, ok: bool, field: &str, message: impl Into<String>) {
+        if !ok {
+            self.error(field, message);
+        }
+    }
+
+    pub fn required(&mut self, field: &str, value: &str) {
+        self.check(!value.trim().is_empty(), field, "is required");
+    }
+
+    pub fn required_id(&mut self, field: &str, id: i64) {
+        self.check(id > 0, field, "is required");
+    }
+
+    pub fn range(&mut self, field: &str, value: i64, min: i64, max: i64) {
+        self.check((min..=max).contains(&value), field, format!("must be between {} and {}", min, max));
+    }
+
+    pub fn min(&mut self, field: &str, value: i64, min: i64) {
+        self.check(value >= min, field, format!("must be at least {}", min));
+    }
+
+    pub fn one_of(&mut self, field: &str, value: &str, allowed: &[&str]) {
+        self.check(allowed.contains(&value), field, format!("must be one of {}", allowed.join(", ")));
+    }
+
+    /// A MAC address or EUI-64 in any common notation; empty passes (use `required` too when it isn't optional)
+    pub fn mac(&mut self, field: &str, value: &str) {
+        let ok = value.is_empty() || crate::utils::parse_mac(value).is_some();
+        self.check(ok, field, "must be a MAC address or EUI-64, e.g. 00:1c:73:aa:bb:cc");
+    }
+
+    /// Dotted-decimal IPv4; empty passes
+    pub fn ipv4(&mut self, field: &str, value: &str) {
+        let ok = value.is_empty() || crate::utils::is_valid_ipv4(value);
+        self.check(ok, field, "must be an IPv4 address");
+    }
+
+    /// IPv4 CIDR such as 10.0.0.0/24; empty passes
+    pub fn cidr(&mut self, field: &str, value: &str) {
+        if value.is_empty() {
+            return;
+        }
+        if let Err(e) = crate::utils::parse_cidr(value) {
+            self.error(field, format!("must be an IPv4 CIDR prefix ({})", e));
+        }
+    }
+
+    pub fn hostname(&mut self, field: &str, value: &str) {
+        let ok = value.is_empty() || crate::utils::is_valid_hostname(value);
+        self.check(ok, field, "may only contain letters, digits, hyphens, dots and underscores");
+    }
+
+    /// Cron expression as the job scheduler reads it; empty passes
+    pub fn cron(&mut self, field: &str, value: &str) {
+        if value.is_empty() {
+            return;
+        }
+        if let Err(e) = croner::Cron::new(value).parse() {
+            self.error(field, format!("must be a cron expression ({})", e));
+        }
+    }
+
+    pub fn regex(&mut self, field: &str, value: &str) {
+        if let Err(e) = regex_lite::Regex::new(value) {
+            self.error(field, format!("must be a regular expression ({})", e));
+        }
+    }
+
+    /// Validate a nested value, prefixing its field names with `prefix`
+    pub fn nested<T: Validate + ?Sized>(&mut self, prefix: &str, value: &T) {
+        let mut inner = Validator::new();
+        value.validate(&mut inner);
+        for e in inner.errors {
+            self.error(format!("{}.{}", prefix, e.field), e.message);
+        }
+    }
+
+    pub fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// Field-level checks on a request body, run by the `ValidJson` extractor before the handler.
+/// Checks that need the database (references, uniqueness) stay in the handlers.
+pub trait Validate {
+    fn validate(&self, v: &mut Validator);
+}
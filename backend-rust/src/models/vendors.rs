@@ -1,5 +1,20 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use super::{Validate, Validator};
+
+/// How deploys reach a vendor's devices
+pub mod vendor_transport {
+    /// Interactive SSH shell, with the config wrapped in the vendor's deploy_command
+    pub const SSH: &str = "ssh";
+    /// NETCONF over SSH (port 830): lock, edit-config and commit the candidate config
+    pub const NETCONF: &str = "netconf";
+
+    pub const ALL: &[&str] = &[SSH, NETCONF];
+}
+
+fn default_transport() -> String {
+    vendor_transport::SSH.to_string()
+}
 
 /// Vendor represents a network device vendor configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +24,14 @@ pub struct Vendor {
     pub backup_command: String,
     pub deploy_command: String,
     pub diff_command: String,
+    /// Commits `{CONFIG}` with a rollback timer (`{SESSION}`, `{TIMER_HMS}`, `{TIMER_MINUTES}`)
+    /// for confirmed deploys
+    pub commit_confirm_command: String,
+    /// Confirms the commit of `{SESSION}` so the timer doesn't roll it back
+    pub confirm_command: String,
+    /// Deploy transport, one of vendor_transport
+    #[serde(default = "default_transport")]
+    pub transport: String,
     pub ssh_port: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ssh_user: Option<String>,
@@ -19,6 +42,10 @@ pub struct Vendor {
     pub default_template: String,
     #[serde(default)]
     pub group_names: Vec<String>,
+    /// Tera template wrapping the config served from `/ztp/config/{mac}` (`{{ config }}` is the
+    /// rendered config), e.g. a bootstrap script; empty serves the config as-is
+    #[serde(default)]
+    pub ztp_wrapper: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub device_count: Option<i32>,
     pub created_at: DateTime<Utc>,
@@ -35,6 +62,12 @@ pub struct CreateVendorRequest {
     pub deploy_command: String,
     #[serde(default)]
     pub diff_command: String,
+    #[serde(default)]
+    pub commit_confirm_command: String,
+    #[serde(default)]
+    pub confirm_command: String,
+    #[serde(default = "default_transport")]
+    pub transport: String,
     #[serde(default = "default_ssh_port")]
     pub ssh_port: i32,
     #[serde(default)]
@@ -49,6 +82,16 @@ pub struct CreateVendorRequest {
     pub default_template: String,
     #[serde(default)]
     pub group_names: Vec<String>,
+    #[serde(default)]
+    pub ztp_wrapper: String,
+}
+
+impl Validate for CreateVendorRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.range("ssh_port", self.ssh_port.into(), 1, 65535);
+        v.one_of("transport", &self.transport, vendor_transport::ALL);
+    }
 }
 
 fn default_backup_command() -> String {
@@ -94,6 +137,13 @@ pub struct CreateDhcpOptionRequest {
     pub enabled: bool,
 }
 
+impl Validate for CreateDhcpOptionRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.range("option_number", self.option_number.into(), 1, 254);
+    }
+}
+
 fn default_option_type() -> String {
     "string".to_string()
 }
@@ -143,6 +193,20 @@ pub struct CreateVendorActionRequest {
     pub output_parser_id: Option<i64>,
 }
 
+impl Validate for CreateVendorActionRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required_id("vendor_id", self.vendor_id);
+        v.required("label", &self.label);
+        if self.action_type == "webhook" {
+            // Not checked as a URL: it may hold template variables
+            v.required("webhook_url", &self.webhook_url);
+            v.one_of("webhook_method", &self.webhook_method.to_uppercase(), &["GET", "POST", "PUT", "PATCH", "DELETE"]);
+        } else {
+            v.required("command", &self.command);
+        }
+    }
+}
+
 fn default_action_type() -> String {
     "ssh".to_string()
 }
@@ -155,6 +219,67 @@ fn default_webhook_headers() -> String {
     "{}".to_string()
 }
 
+/// Device facts the SSH connect test reads, one probe command set each
+pub mod probe_field {
+    pub const UPTIME: &str = "uptime";
+    pub const HOSTNAME: &str = "hostname";
+    pub const VERSION: &str = "version";
+    pub const INTERFACES: &str = "interfaces";
+
+    pub const ALL: &[&str] = &[UPTIME, HOSTNAME, VERSION, INTERFACES];
+}
+
+/// ProbeCommand is the command set the connect test runs to read one field from a vendor's
+/// devices. A row without a vendor_id applies to vendors that have no row for the field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeCommand {
+    pub id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor_id: Option<i64>,
+    pub field: String,
+    /// Tried in order; the first that prints non-error output is used
+    pub commands: Vec<String>,
+    /// Output parser applied to the command output before it is stored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_parser_id: Option<i64>,
+    /// Keep only this many lines of output; 0 keeps all of it
+    pub max_lines: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// CreateProbeCommandRequest for creating/updating probe commands
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateProbeCommandRequest {
+    #[serde(default)]
+    pub vendor_id: Option<i64>,
+    pub field: String,
+    pub commands: Vec<String>,
+    #[serde(default)]
+    pub output_parser_id: Option<i64>,
+    #[serde(default)]
+    pub max_lines: i32,
+}
+
+impl Validate for CreateProbeCommandRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.one_of("field", &self.field, probe_field::ALL);
+        v.check(!self.commands.is_empty(), "commands", "is required");
+        v.check(self.commands.iter().all(|c| !c.trim().is_empty()), "commands", "must not contain empty commands");
+        v.min("max_lines", self.max_lines.into(), 0);
+    }
+}
+
+/// One field of a resolved probe command set, with its parser loaded, as the connect test runs it
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeStep {
+    pub field: String,
+    pub commands: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parser: Option<super::OutputParser>,
+    pub max_lines: i32,
+}
+
 /// ExecRequest for executing a command on a device via SSH or webhook
 #[derive(Debug, Clone, Deserialize)]
 pub struct ExecRequest {
@@ -162,6 +287,9 @@ pub struct ExecRequest {
     pub command: String,
     #[serde(default)]
     pub action_id: Option<i64>,
+    /// Run time limit for the job in seconds; unset uses the job_timeout_secs setting
+    #[serde(default)]
+    pub timeout_secs: Option<i64>,
 }
 
 /// ExecResponse returned after executing a command
@@ -3,5 +3,5 @@ pub mod sync;
 pub mod types;
 
 pub use client::NetBoxClient;
-pub use sync::{sync_pull, sync_push, sync_vendors_pull, sync_vendors_push};
+pub use sync::{resolve_conflict, sync_pull, sync_push, sync_vendors_pull, sync_vendors_push};
 pub use types::SyncResult;
@@ -1,8 +1,12 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::db::Store;
-use crate::models::{CreateDeviceRequest, CreateVendorRequest, NetBoxConfig, device_status};
+use crate::models::{
+    CreateDeviceRequest, CreateVendorRequest, Device, NetBoxConfig, ResolveSyncConflictRequest,
+    SyncConflict, SyncConflictField, UpdateDeviceRequest, device_status, netbox_conflict_policy,
+    sync_resolution, vendor_transport,
+};
 
 use super::client::NetBoxClient;
 use super::types::{DeviceCreate, SyncCounts, SyncResult};
@@ -36,6 +40,102 @@ fn map_status_from_netbox(status: &str) -> &str {
     }
 }
 
+/// Device fields kept in sync with NetBox, keyed by name
+fn synced_fields(hostname: &str, ip: &str, serial_number: Option<&str>) -> BTreeMap<String, String> {
+    BTreeMap::from([
+        ("hostname".to_string(), hostname.to_string()),
+        ("ip".to_string(), ip.to_string()),
+        ("serial_number".to_string(), serial_number.unwrap_or("").to_string()),
+    ])
+}
+
+fn device_synced_fields(device: &Device) -> BTreeMap<String, String> {
+    synced_fields(&device.hostname, &device.ip, device.serial_number.as_deref())
+}
+
+/// Apply synced field values to a device, leaving everything else as is
+async fn apply_device_fields(store: &Store, device: &Device, values: &BTreeMap<String, String>) -> Result<()> {
+    let field = |name: &str, current: &str| values.get(name).cloned().unwrap_or_else(|| current.to_string());
+    let serial = field("serial_number", device.serial_number.as_deref().unwrap_or(""));
+    let req = UpdateDeviceRequest {
+        ip: field("ip", &device.ip),
+        hostname: field("hostname", &device.hostname),
+        vendor: device.vendor_id.clone(),
+        model: device.model.clone(),
+        serial_number: if serial.is_empty() { None } else { Some(serial) },
+        config_template: device.config_template.clone(),
+        credential_id: device.credential_id,
+        ssh_user: device.ssh_user.clone(),
+        ssh_pass: device.ssh_pass.clone(),
+        topology_id: device.topology_id,
+        topology_role: device.topology_role.clone(),
+        hall_id: device.hall_id,
+        row_id: device.row_id,
+        rack_id: device.rack_id,
+        rack_position: device.rack_position,
+        device_type: Some(device.device_type.clone()),
+    };
+    store.update_device(device.id, &req).await?;
+    Ok(())
+}
+
+/// Outcome of reconciling one pulled device against its local copy
+#[derive(Default)]
+struct PullMerge {
+    /// Remote values to write to the device
+    apply: BTreeMap<String, String>,
+    /// Fields changed on both sides that the policy left for review
+    conflicts: Vec<SyncConflictField>,
+    /// New baseline: remote values, except queued conflicts keep their previous baseline
+    baseline: BTreeMap<String, String>,
+}
+
+/// Three-way merge of synced fields. A side changed if it differs from the baseline; with no
+/// baseline (never synced) any disagreement counts as a conflict.
+fn merge_pulled_fields(
+    local: &BTreeMap<String, String>,
+    remote: &BTreeMap<String, String>,
+    baseline: Option<&BTreeMap<String, String>>,
+    policy: &str,
+) -> PullMerge {
+    let mut merge = PullMerge::default();
+    for (field, remote_value) in remote {
+        let local_value = local.get(field).cloned().unwrap_or_default();
+        let base = baseline.and_then(|b| b.get(field));
+        merge.baseline.insert(field.clone(), remote_value.clone());
+
+        if &local_value == remote_value || base == Some(remote_value) {
+            // Agreed, or only the local side changed
+            continue;
+        }
+        if base == Some(&local_value) {
+            // Only NetBox changed
+            merge.apply.insert(field.clone(), remote_value.clone());
+            continue;
+        }
+
+        match policy {
+            netbox_conflict_policy::PREFER_LOCAL => {}
+            netbox_conflict_policy::PREFER_REMOTE => {
+                merge.apply.insert(field.clone(), remote_value.clone());
+            }
+            _ => {
+                match base {
+                    Some(b) => merge.baseline.insert(field.clone(), b.clone()),
+                    None => merge.baseline.remove(field),
+                };
+                merge.conflicts.push(SyncConflictField {
+                    field: field.clone(),
+                    local: local_value,
+                    remote: remote_value.clone(),
+                    baseline: base.cloned(),
+                });
+            }
+        }
+    }
+    merge
+}
+
 /// Push all devices from local DB to NetBox
 pub async fn sync_push(store: &Store, nb: &NetBoxClient, config: &NetBoxConfig) -> Result<SyncResult> {
     let devices = store.list_devices().await?;
@@ -112,6 +212,7 @@ pub async fn sync_push(store: &Store, nb: &NetBoxClient, config: &NetBoxConfig)
                     errors.push(format!("{}: update: {}", device.hostname, e));
                 } else {
                     updated += 1;
+                    record_pushed(store, device, existing.id).await;
                 }
             }
             Ok(None) => {
@@ -122,6 +223,7 @@ pub async fn sync_push(store: &Store, nb: &NetBoxClient, config: &NetBoxConfig)
                             tracing::warn!("Failed to create interface for {}: {}", device.hostname, e);
                         }
                         created += 1;
+                        record_pushed(store, device, nb_dev.id).await;
                     }
                     Err(e) => {
                         errors.push(format!("{}: create: {}", device.hostname, e));
@@ -136,15 +238,28 @@ pub async fn sync_push(store: &Store, nb: &NetBoxClient, config: &NetBoxConfig)
 
     Ok(SyncResult {
         message: format!("Pushed {} devices ({} created, {} updated)", devices.len(), created, updated),
-        result: SyncCounts { created, updated, errors },
+        result: SyncCounts { created, updated, conflicts: 0, errors },
     })
 }
 
-/// Pull devices from NetBox into local DB
-pub async fn sync_pull(store: &Store, nb: &NetBoxClient) -> Result<SyncResult> {
+/// After a push NetBox matches the device, so its values become the new baseline
+async fn record_pushed(store: &Store, device: &Device, netbox_id: i32) {
+    if let Err(e) = store.set_netbox_sync_baseline(device.id, netbox_id as i64, &device_synced_fields(device)).await {
+        tracing::warn!("Failed to record NetBox sync baseline for {}: {}", device.hostname, e);
+    }
+    // NetBox now holds the local values, so any queued conflict is moot
+    if let Err(e) = store.clear_sync_conflict(device.id).await {
+        tracing::warn!("Failed to clear NetBox sync conflict for {}: {}", device.hostname, e);
+    }
+}
+
+/// Pull devices from NetBox into local DB. Existing devices are three-way merged against the
+/// last synced values; fields changed on both sides follow the configured conflict policy.
+pub async fn sync_pull(store: &Store, nb: &NetBoxClient, config: &NetBoxConfig) -> Result<SyncResult> {
     let nb_devices = nb.list_devices().await?;
     let mut created = 0;
     let mut updated = 0;
+    let mut conflicts = 0;
     let mut errors: Vec<String> = Vec::new();
 
     for nb_device in &nb_devices {
@@ -200,10 +315,41 @@ pub async fn sync_pull(store: &Store, nb: &NetBoxClient) -> Result<SyncResult> {
             .map(|s| map_status_from_netbox(&s.value))
             .unwrap_or("offline");
 
+        let serial = if nb_device.serial.is_empty() { None } else { Some(nb_device.serial.clone()) };
+        let remote = synced_fields(&name, &ip, serial.as_deref());
+        let netbox_id = nb_device.id as i64;
+
         // Check if device already exists in local DB
         match store.get_device_by_mac(&mac).await {
-            Ok(Some(_)) => {
-                updated += 1; // Already exists, skip
+            Ok(Some(device)) => {
+                let baseline = match store.get_netbox_sync_baseline(device.id).await {
+                    Ok(b) => b,
+                    Err(e) => {
+                        errors.push(format!("{}: baseline: {}", mac, e));
+                        continue;
+                    }
+                };
+                let merge = merge_pulled_fields(&device_synced_fields(&device), &remote, baseline.as_ref(), &config.conflict_policy);
+
+                if !merge.apply.is_empty() {
+                    if let Err(e) = apply_device_fields(store, &device, &merge.apply).await {
+                        errors.push(format!("{}: update: {}", mac, e));
+                        continue;
+                    }
+                    updated += 1;
+                }
+                let recorded = if merge.conflicts.is_empty() {
+                    store.clear_sync_conflict(device.id).await
+                } else {
+                    conflicts += 1;
+                    store.upsert_sync_conflict(device.id, netbox_id, &merge.conflicts).await
+                };
+                if let Err(e) = recorded {
+                    errors.push(format!("{}: conflict: {}", mac, e));
+                }
+                if let Err(e) = store.set_netbox_sync_baseline(device.id, netbox_id, &merge.baseline).await {
+                    errors.push(format!("{}: baseline: {}", mac, e));
+                }
             }
             Ok(None) => {
                 let req = CreateDeviceRequest {
@@ -212,8 +358,9 @@ pub async fn sync_pull(store: &Store, nb: &NetBoxClient) -> Result<SyncResult> {
                     hostname: name,
                     vendor,
                     model: None,
-                    serial_number: if nb_device.serial.is_empty() { None } else { Some(nb_device.serial.clone()) },
+                    serial_number: serial,
                     config_template: String::new(),
+                    credential_id: None,
                     ssh_user: None,
                     ssh_pass: None,
                     topology_id: None,
@@ -226,7 +373,12 @@ pub async fn sync_pull(store: &Store, nb: &NetBoxClient) -> Result<SyncResult> {
                 };
 
                 match store.create_device(&req).await {
-                    Ok(_) => created += 1,
+                    Ok(device) => {
+                        created += 1;
+                        if let Err(e) = store.set_netbox_sync_baseline(device.id, netbox_id, &remote).await {
+                            errors.push(format!("{}: baseline: {}", mac, e));
+                        }
+                    }
                     Err(e) => errors.push(format!("{}: {}", mac, e)),
                 }
             }
@@ -235,11 +387,44 @@ pub async fn sync_pull(store: &Store, nb: &NetBoxClient) -> Result<SyncResult> {
     }
 
     Ok(SyncResult {
-        message: format!("Pulled {} devices ({} created, {} existing)", nb_devices.len(), created, updated),
-        result: SyncCounts { created, updated, errors },
+        message: format!(
+            "Pulled {} devices ({} created, {} updated, {} conflicts queued)",
+            nb_devices.len(), created, updated, conflicts
+        ),
+        result: SyncCounts { created, updated, conflicts, errors },
     })
 }
 
+/// Apply a conflict resolution: write the chosen values to the device, take NetBox's values
+/// as the new baseline, and close the conflict. The request must already be validated.
+pub async fn resolve_conflict(store: &Store, conflict: &SyncConflict, req: &ResolveSyncConflictRequest) -> Result<SyncConflict> {
+    let device = store.get_device(conflict.device_id).await?
+        .ok_or_else(|| crate::db::NotFoundError::new("Device", &conflict.device_id.to_string()))?;
+
+    let take_remote = |field: &str| match req.resolution.as_str() {
+        sync_resolution::REMOTE => true,
+        sync_resolution::MERGE => req.fields.get(field).map(String::as_str) == Some(sync_resolution::REMOTE),
+        _ => false,
+    };
+    let apply: BTreeMap<String, String> = conflict
+        .fields
+        .iter()
+        .filter(|f| take_remote(&f.field))
+        .map(|f| (f.field.clone(), f.remote.clone()))
+        .collect();
+    if !apply.is_empty() {
+        apply_device_fields(store, &device, &apply).await?;
+    }
+
+    let mut baseline = store.get_netbox_sync_baseline(device.id).await?.unwrap_or_default();
+    for f in &conflict.fields {
+        baseline.insert(f.field.clone(), f.remote.clone());
+    }
+    store.set_netbox_sync_baseline(device.id, conflict.netbox_id, &baseline).await?;
+
+    store.resolve_sync_conflict(conflict.id, &req.resolution).await
+}
+
 /// Push vendors to NetBox as manufacturers
 pub async fn sync_vendors_push(store: &Store, nb: &NetBoxClient) -> Result<SyncResult> {
     let vendors = store.list_vendors().await?;
@@ -266,7 +451,7 @@ pub async fn sync_vendors_push(store: &Store, nb: &NetBoxClient) -> Result<SyncR
 
     Ok(SyncResult {
         message: format!("Pushed {} vendors ({} created, {} existing)", vendors.len(), created, updated),
-        result: SyncCounts { created, updated, errors },
+        result: SyncCounts { created, updated, conflicts: 0, errors },
     })
 }
 
@@ -288,6 +473,9 @@ pub async fn sync_vendors_pull(store: &Store, nb: &NetBoxClient) -> Result<SyncR
                     backup_command: "show running-config".to_string(),
                     deploy_command: String::new(),
                     diff_command: String::new(),
+                    commit_confirm_command: String::new(),
+                    confirm_command: String::new(),
+                    transport: vendor_transport::SSH.to_string(),
                     ssh_port: 22,
                     ssh_user: String::new(),
                     ssh_pass: String::new(),
@@ -295,6 +483,7 @@ pub async fn sync_vendors_pull(store: &Store, nb: &NetBoxClient) -> Result<SyncR
                     vendor_class: String::new(),
                     default_template: String::new(),
                     group_names: Vec::new(),
+                    ztp_wrapper: String::new(),
                 };
 
                 match store.create_vendor(&req).await {
@@ -308,6 +497,6 @@ pub async fn sync_vendors_pull(store: &Store, nb: &NetBoxClient) -> Result<SyncR
 
     Ok(SyncResult {
         message: format!("Pulled {} manufacturers ({} created, {} existing)", manufacturers.len(), created, updated),
-        result: SyncCounts { created, updated, errors },
+        result: SyncCounts { created, updated, conflicts: 0, errors },
     })
 }
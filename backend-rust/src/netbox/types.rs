@@ -166,6 +166,8 @@ pub struct SyncResult {
 pub struct SyncCounts {
     pub created: i32,
     pub updated: i32,
+    /// Devices with fields queued for conflict review (pull only)
+    pub conflicts: i32,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub errors: Vec<String>,
 }
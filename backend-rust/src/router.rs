@@ -1,4 +1,5 @@
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{delete, get, post, put},
     Router,
 };
@@ -23,30 +24,116 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         .route("/api/template-large", get(handlers::benchmarks::template_large_handler))
         .route("/api/template-acl", get(handlers::benchmarks::template_acl_handler))
         .route("/api/template-acl10k", get(handlers::benchmarks::template_acl10k_handler))
+        .route("/api/benchmarks/store", post(handlers::benchmarks::store_benchmark_handler))
         // Device routes
         .route("/api/devices", get(handlers::devices::list_devices))
         .route("/api/devices", post(handlers::devices::create_device))
         .route("/api/devices/next-hostname", get(handlers::devices::next_hostname))
+        .route("/api/devices/duplicates", get(handlers::devices::list_duplicate_devices))
+        .route("/api/devices/export", get(handlers::device_csv::export_devices_csv))
+        .route("/api/devices/import", post(handlers::device_csv::import_devices_csv))
         .route("/api/devices/:id", get(handlers::devices::get_device))
         .route("/api/devices/:id", put(handlers::devices::update_device))
         .route("/api/devices/:id", delete(handlers::devices::delete_device))
+        .route("/api/devices/:id/detail", get(handlers::documentation::get_device_detail))
+        .route("/api/devices/:id/notes", get(handlers::documentation::list_device_notes))
+        .route("/api/devices/:id/notes", post(handlers::documentation::create_device_note))
+        .route("/api/devices/:id/attachments", get(handlers::documentation::list_device_attachments))
+        .route(
+            "/api/devices/:id/attachments",
+            post(handlers::documentation::upload_device_attachment)
+                .layer(DefaultBodyLimit::max(handlers::documentation::MAX_ATTACHMENT_UPLOAD_BYTES)),
+        )
+        .route("/api/devices/external/:external_id", get(handlers::external_ids::get_device))
+        .route("/api/devices/external/:external_id", put(handlers::external_ids::put_device))
+        .route("/api/devices/external/:external_id", delete(handlers::external_ids::delete_device))
+        .route("/api/devices/:id/merge", post(handlers::devices::merge_devices))
+        .route("/api/devices/:id/change-mac", post(handlers::devices::change_device_mac))
         .route("/api/devices/:id/connect", post(handlers::devices::connect_device))
         .route("/api/devices/:id/config", get(handlers::devices::get_device_config))
+        .route("/api/devices/:id/ztp-url", get(handlers::devices::get_device_ztp_url))
         .route("/api/devices/:id/preview-config", post(handlers::devices::preview_device_config))
-        .route("/api/devices/:id/deploy-config", post(handlers::devices::deploy_device_config))
-        .route("/api/devices/:id/diff-config", post(handlers::devices::diff_device_config))
-        .route("/api/devices/:id/exec", post(handlers::devices::exec_command))
+        .route("/api/devices/:id/config/outline", get(handlers::config_outline::rendered_config_outline))
+        .route("/api/devices/:id/config-snippets", get(handlers::devices::get_device_config_snippets))
+        .route("/api/devices/:id/config-snippets", put(handlers::devices::set_device_config_snippets))
+        .route("/api/devices/:id/config-snippets", delete(handlers::devices::delete_device_config_snippets))
+        .route("/api/devices/:id/reprovision", post(handlers::devices::reprovision_device))
+        .route("/api/devices/:id/provisioning-sessions", get(handlers::devices::list_provisioning_sessions))
+        .route(
+            "/api/devices/:id/deploy-config",
+            post(handlers::devices::deploy_device_config)
+                .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::idempotency::idempotency_guard)),
+        )
+        .route("/api/devices/:id/preflight", post(handlers::devices::preflight_device))
+        .route("/api/devices/:id/staged-configs", post(handlers::staged_configs::create_staged_config))
+        .route(
+            "/api/devices/:id/diff-config",
+            post(handlers::devices::diff_device_config)
+                .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::idempotency::idempotency_guard)),
+        )
+        .route(
+            "/api/devices/:id/exec",
+            post(handlers::devices::exec_command)
+                .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::idempotency::idempotency_guard)),
+        )
         // Job routes
         .route("/api/jobs", get(handlers::jobs::list_jobs))
+        .route(
+            "/api/jobs/bulk",
+            post(handlers::jobs::create_bulk_job)
+                .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::idempotency::idempotency_guard)),
+        )
+        .route("/api/jobs/queue", get(handlers::jobs::get_job_queue))
+        .route("/api/jobs/batches/:id", get(handlers::jobs::get_job_batch))
         .route("/api/jobs/:id", get(handlers::jobs::get_job))
+        .route("/api/jobs/:id/cancel", post(handlers::jobs::cancel_job))
         // Job template routes
         .route("/api/job-templates", get(handlers::job_templates::list_job_templates))
         .route("/api/job-templates", post(handlers::job_templates::create_job_template))
         .route("/api/job-templates/:id", get(handlers::job_templates::get_job_template))
         .route("/api/job-templates/:id", put(handlers::job_templates::update_job_template))
         .route("/api/job-templates/:id", delete(handlers::job_templates::delete_job_template))
-        .route("/api/job-templates/:id/run", post(handlers::job_templates::run_job_template))
+        .route(
+            "/api/job-templates/:id/run",
+            post(handlers::job_templates::run_job_template)
+                .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::idempotency::idempotency_guard)),
+        )
+        // Runbooks
+        .route("/api/runbooks", get(handlers::runbooks::list_runbooks))
+        .route("/api/runbooks", post(handlers::runbooks::create_runbook))
+        .route("/api/runbooks/:id", get(handlers::runbooks::get_runbook))
+        .route("/api/runbooks/:id", put(handlers::runbooks::update_runbook))
+        .route("/api/runbooks/:id", delete(handlers::runbooks::delete_runbook))
+        .route("/api/runbooks/:id/runs", post(handlers::runbooks::start_runbook_run))
+        .route("/api/runbook-runs", get(handlers::runbooks::list_runbook_runs))
+        .route("/api/runbook-runs/:id", get(handlers::runbooks::get_runbook_run))
+        .route("/api/runbook-runs/:id/abort", post(handlers::runbooks::abort_runbook_run))
+        .route("/api/runbook-runs/:id/steps/:position/execute", post(handlers::runbooks::execute_runbook_step))
+        .route("/api/runbook-runs/:id/steps/:position/sign-off", post(handlers::runbooks::sign_off_runbook_step))
+        // Global search
+        .route("/api/search", get(handlers::search::search))
+        // Saved search routes
+        .route("/api/saved-searches", get(handlers::saved_searches::list_saved_searches))
+        .route("/api/saved-searches", post(handlers::saved_searches::create_saved_search))
+        .route("/api/saved-searches/preview", post(handlers::saved_searches::preview_device_filter))
+        .route("/api/saved-searches/:id", get(handlers::saved_searches::get_saved_search))
+        .route("/api/saved-searches/:id", put(handlers::saved_searches::update_saved_search))
+        .route("/api/saved-searches/:id", delete(handlers::saved_searches::delete_saved_search))
+        .route("/api/saved-searches/:id/devices", get(handlers::saved_searches::list_saved_search_devices))
+        // Tag routes
+        .route("/api/tags", get(handlers::tags::list_all_tags))
+        .route("/api/tags/keys", get(handlers::tags::list_tag_keys))
+        .route("/api/tags/:resource_type/:resource_id", get(handlers::tags::list_resource_tags))
+        .route("/api/tags/:resource_type/:resource_id", post(handlers::tags::set_resource_tag))
+        .route("/api/tags/:resource_type/:resource_id/:key", delete(handlers::tags::delete_resource_tag))
         // Device variable routes
+        .route("/api/devices/:id/custom-fields", get(handlers::custom_fields::get_device_custom_fields))
+        .route("/api/devices/:id/custom-fields", put(handlers::custom_fields::set_device_custom_fields))
+        .route("/api/custom-fields", get(handlers::custom_fields::list_custom_fields))
+        .route("/api/custom-fields", post(handlers::custom_fields::create_custom_field))
+        .route("/api/custom-fields/:id", get(handlers::custom_fields::get_custom_field))
+        .route("/api/custom-fields/:id", put(handlers::custom_fields::update_custom_field))
+        .route("/api/custom-fields/:id", delete(handlers::custom_fields::delete_custom_field))
         .route("/api/devices/:id/variables", get(handlers::device_variables::list_device_variables))
         .route("/api/devices/:id/variables", put(handlers::device_variables::set_device_variables))
         .route("/api/devices/:id/variables/:key", put(handlers::device_variables::set_device_variable))
@@ -60,14 +147,64 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         .route("/api/devices/:id/port-assignments", put(handlers::port_assignments::bulk_set_port_assignments))
         .route("/api/devices/:id/port-assignments/:port_name", put(handlers::port_assignments::set_port_assignment))
         .route("/api/devices/:id/port-assignments/:port_name", delete(handlers::port_assignments::delete_port_assignment))
+        .route("/api/devices/:id/port-assignments/validate", get(handlers::interfaces::validate_port_assignments))
+        .route("/api/devices/:id/power-connections", get(handlers::port_assignments::list_power_connections))
+        .route("/api/devices/:id/power-connections/:port_name", put(handlers::port_assignments::set_power_connection))
+        .route("/api/devices/:id/power-connections/:port_name", delete(handlers::port_assignments::delete_power_connection))
+        .route("/api/devices/:id/console-connections", get(handlers::port_assignments::list_console_connections))
+        .route("/api/devices/:id/console-connections/:port_name", put(handlers::port_assignments::set_console_connection))
+        .route("/api/devices/:id/console-connections/:port_name", delete(handlers::port_assignments::delete_console_connection))
+        .route("/api/devices/:id/cabling", get(handlers::port_assignments::get_device_cabling))
+        // Interface inventory routes
+        .route("/api/devices/:id/interfaces", get(handlers::interfaces::list_device_interfaces))
+        .route("/api/devices/:id/runbooks", get(handlers::runbooks::list_device_runbooks))
+        .route("/api/devices/:id/interfaces/drift", get(handlers::interfaces::device_interface_drift))
+        .route("/api/interfaces/collect", post(handlers::interfaces::collect_interfaces))
+        .route("/api/interfaces/drift", get(handlers::interfaces::interface_drift_report))
+        // Hardware inventory routes
+        .route("/api/devices/:id/hardware", get(handlers::hardware::list_device_hardware))
+        .route("/api/hardware/collect", post(handlers::hardware::collect_hardware))
+        .route("/api/hardware/serial-mismatches", get(handlers::hardware::list_serial_mismatches))
+        .route("/api/hardware/serial-mismatches/:id/accept", post(handlers::hardware::accept_serial_mismatch))
+        .route("/api/devices/:id/support-contract", get(handlers::support_contracts::get_device_support_contract))
+        .route("/api/support-contracts", get(handlers::support_contracts::list_support_contracts))
+        .route("/api/support-contracts", post(handlers::support_contracts::create_support_contract))
+        .route("/api/support-contracts/expiring", get(handlers::support_contracts::expiring_support_contracts))
+        .route("/api/support-contracts/check-expiry", post(handlers::support_contracts::check_support_contract_expiry))
+        .route("/api/support-contracts/:id", get(handlers::support_contracts::get_support_contract))
+        .route("/api/support-contracts/:id", put(handlers::support_contracts::update_support_contract))
+        .route("/api/support-contracts/:id", delete(handlers::support_contracts::delete_support_contract))
         // Backup routes
         .route("/api/devices/:id/backup", post(handlers::backups::trigger_backup))
         .route("/api/devices/:id/backups", get(handlers::backups::list_backups))
+        .route("/api/devices/:id/backups/:backup_id/diff/:other", get(handlers::backups::diff_backups))
+        .route("/api/devices/:id/backups/upload", post(handlers::backups::upload_backup))
+        .route("/api/backups/search", get(handlers::backups::search_backups))
         .route("/api/backups/:id", get(handlers::backups::get_backup))
+        .route("/api/backups/:id/restore-preview", get(handlers::backups::preview_restore))
+        .route("/api/backups/:id/outline", get(handlers::config_outline::backup_config_outline))
+        .route("/api/config/outline", post(handlers::config_outline::text_config_outline))
         // Settings routes
         .route("/api/settings", get(handlers::settings::get_settings))
         .route("/api/settings", put(handlers::settings::update_settings))
         .route("/api/reload", post(handlers::settings::reload_config))
+        .route("/api/system/db-backup", post(handlers::system::create_db_backup))
+        .route("/api/system/db-backups", get(handlers::system::list_db_backups))
+        .route("/api/system/db-vacuum", post(handlers::system::run_db_vacuum))
+        .route("/api/system/db-vacuum", get(handlers::system::list_db_vacuums))
+        .route("/api/system/partitions", get(handlers::system::list_partitions))
+        .route("/api/system/partitions/rollover", post(handlers::system::run_partition_rollover))
+        .route("/api/system/integrity", get(handlers::system::check_integrity))
+        .route("/api/system/integrity/cleanup", post(handlers::system::cleanup_integrity))
+        .route("/api/system/reload-config", post(handlers::system::reload_config))
+        .route("/api/system/api-usage", get(handlers::api_usage::api_usage_report))
+        .route("/api/system/ssh-pool", get(handlers::system::get_ssh_pool))
+        .route("/api/system/ssh-breakers", get(handlers::system::list_ssh_breakers))
+        .route("/api/system/ssh-breakers/:host", delete(handlers::system::reset_ssh_breaker))
+        .route("/api/system/cache", get(handlers::system::get_lookup_cache))
+        .route("/api/system/cache", delete(handlers::system::clear_lookup_cache))
+        .route("/api/system/tls", get(handlers::tls::get_tls_status))
+        .route("/api/system/tls/renew", post(handlers::tls::renew_tls_certificate))
         .route("/api/network/addresses", get(handlers::settings::get_local_addresses))
         // Branding routes (get_branding and get_logo are public, upload/delete require auth)
         .route("/api/branding", get(handlers::settings::get_branding))
@@ -82,7 +219,9 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         .route("/api/vendors/:id", get(handlers::vendors::get_vendor))
         .route("/api/vendors/:id", put(handlers::vendors::update_vendor))
         .route("/api/vendors/:id", delete(handlers::vendors::delete_vendor))
+        .route("/api/vendors/:id/references", get(handlers::references::get_vendor_references))
         .route("/api/vendors/:id/actions", get(handlers::vendors::list_vendor_actions_by_vendor))
+        .route("/api/vendors/:id/probe-commands", get(handlers::vendors::get_vendor_probe_steps))
         // Device model routes
         .route("/api/device-models", get(handlers::device_models::list_device_models))
         .route("/api/device-models", post(handlers::device_models::create_device_model))
@@ -94,27 +233,94 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         .route("/api/vendor-actions", post(handlers::vendors::create_vendor_action))
         .route("/api/vendor-actions/:id", put(handlers::vendors::update_vendor_action))
         .route("/api/vendor-actions/:id", delete(handlers::vendors::delete_vendor_action))
-        .route("/api/vendor-actions/:id/run", post(handlers::vendors::run_vendor_action))
+        .route(
+            "/api/vendor-actions/:id/run",
+            post(handlers::vendors::run_vendor_action)
+                .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::idempotency::idempotency_guard)),
+        )
+        // Probe command routes
+        .route("/api/probe-commands", get(handlers::vendors::list_probe_commands))
+        .route("/api/probe-commands", post(handlers::vendors::create_probe_command))
+        .route("/api/probe-commands/:id", put(handlers::vendors::update_probe_command))
+        .route("/api/probe-commands/:id", delete(handlers::vendors::delete_probe_command))
         // Topology routes
         .route("/api/topologies", get(handlers::topologies::list_topologies))
         .route("/api/topologies", post(handlers::topologies::create_topology))
         .route("/api/topologies/:id", get(handlers::topologies::get_topology))
         .route("/api/topologies/:id", put(handlers::topologies::update_topology))
         .route("/api/topologies/:id", delete(handlers::topologies::delete_topology))
+        .route("/api/topologies/:id/notes", get(handlers::documentation::list_topology_notes))
+        .route("/api/topologies/:id/notes", post(handlers::documentation::create_topology_note))
+        .route("/api/topologies/:id/attachments", get(handlers::documentation::list_topology_attachments))
+        .route(
+            "/api/topologies/:id/attachments",
+            post(handlers::documentation::upload_topology_attachment)
+                .layer(DefaultBodyLimit::max(handlers::documentation::MAX_ATTACHMENT_UPLOAD_BYTES)),
+        )
+        // Notes and attachments
+        .route("/api/notes/:id", put(handlers::documentation::update_note))
+        .route("/api/notes/:id", delete(handlers::documentation::delete_note))
+        .route("/api/attachments/:id", get(handlers::documentation::get_attachment))
+        .route("/api/attachments/:id", delete(handlers::documentation::delete_attachment))
+        .route("/api/attachments/:id/download", get(handlers::documentation::download_attachment))
+        .route(
+            "/api/topologies/:id/deploy",
+            post(handlers::topologies::deploy_topology)
+                .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::idempotency::idempotency_guard)),
+        )
+        .route("/api/topologies/:id/deploys", get(handlers::topologies::list_topology_deploys))
+        .route("/api/topologies/:id/deploys/:deploy_id", get(handlers::topologies::get_topology_deploy))
+        .route("/api/topologies/:id/bgp-sessions", get(handlers::bgp::bgp_session_matrix))
+        .route("/api/topologies/:id/capacity", get(handlers::capacity::topology_capacity))
+        .route("/api/topologies/:id/generate-underlay", post(handlers::topologies::generate_underlay))
+        .route("/api/topologies/:id/lab", get(handlers::docker::list_lab_nodes))
+        .route("/api/topologies/:id/lab", post(handlers::docker::deploy_lab))
+        .route("/api/topologies/:id/lab", delete(handlers::docker::destroy_lab))
+        .route("/api/topologies/:id/lab/containerlab", get(handlers::docker::get_containerlab_definition))
+        .route("/api/topologies/:id/lab/jobs", post(handlers::docker::run_lab_jobs))
         // Template routes
         .route("/api/templates", get(handlers::templates::list_templates))
         .route("/api/templates", post(handlers::templates::create_template))
         .route("/api/templates/_/variables", get(handlers::templates::get_template_variables))
+        .route("/api/templates/context-schema", get(handlers::templates::get_template_context_schema))
+        .route("/api/templates/validate", post(handlers::templates::validate_template))
         .route("/api/templates/:id", get(handlers::templates::get_template))
         .route("/api/templates/:id", put(handlers::templates::update_template))
         .route("/api/templates/:id", delete(handlers::templates::delete_template))
+        .route("/api/templates/external/:external_id", get(handlers::external_ids::get_template))
+        .route("/api/templates/external/:external_id", put(handlers::external_ids::put_template))
+        .route("/api/templates/external/:external_id", delete(handlers::external_ids::delete_template))
+        .route("/api/templates/:id/references", get(handlers::references::get_template_references))
         .route("/api/templates/:id/preview", post(handlers::templates::preview_template))
+        .route("/api/templates/:id/provenance", get(handlers::template_catalog::get_template_provenance))
+        .route("/api/templates/:id/history", get(handlers::templates::get_template_history))
+        .route("/api/templates/:id/revert/:commit", post(handlers::templates::revert_template))
+        .route("/api/seeds", get(handlers::seeds::get_seed_status))
+        .route("/api/seeds/:id", put(handlers::seeds::update_seed_item))
+        .route("/api/seeds/:id/apply", post(handlers::seeds::apply_seed_update))
+        .route("/api/seeds/:id/dismiss", post(handlers::seeds::dismiss_seed_update))
+        .route("/api/template-catalog", get(handlers::template_catalog::get_template_catalog))
+        .route("/api/template-catalog/sync", post(handlers::template_catalog::sync_template_catalog))
+        .route("/api/template-catalog/install", post(handlers::template_catalog::install_catalog_templates))
+        .route("/api/templates/:id/usage", get(handlers::templates::get_template_usage))
+        // Render routes
+        .route("/api/render/cache", get(handlers::render::get_render_cache_stats))
+        .route("/api/render/cache", delete(handlers::render::clear_render_cache))
+        .route("/api/render/batch", post(handlers::render::batch_render))
+        .route("/api/render/inventory", post(handlers::render::render_inventory))
+        .route("/api/render/tftp-export", post(handlers::render::export_tftp_configs))
+        // Declarative apply
+        .route("/api/apply", post(handlers::apply::apply))
         // Group routes
         .route("/api/groups", get(handlers::groups::list_groups))
         .route("/api/groups", post(handlers::groups::create_group))
         .route("/api/groups/:id", get(handlers::groups::get_group))
         .route("/api/groups/:id", put(handlers::groups::update_group))
         .route("/api/groups/:id", delete(handlers::groups::delete_group))
+        .route("/api/groups/external/:external_id", get(handlers::external_ids::get_group))
+        .route("/api/groups/external/:external_id", put(handlers::external_ids::put_group))
+        .route("/api/groups/external/:external_id", delete(handlers::external_ids::delete_group))
+        .route("/api/groups/:id/references", get(handlers::references::get_group_references))
         .route("/api/groups/:id/variables", get(handlers::groups::list_group_variables))
         .route("/api/groups/:id/variables/:key", put(handlers::groups::set_group_variable))
         .route("/api/groups/:id/variables/:key", delete(handlers::groups::delete_group_variable))
@@ -148,6 +354,9 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         .route("/api/netbox/sync/pull", post(handlers::netbox::sync_pull))
         .route("/api/netbox/sync/vendors/push", post(handlers::netbox::sync_vendors_push))
         .route("/api/netbox/sync/vendors/pull", post(handlers::netbox::sync_vendors_pull))
+        .route("/api/netbox/conflicts", get(handlers::netbox::list_conflicts))
+        .route("/api/netbox/conflicts/:id", get(handlers::netbox::get_conflict))
+        .route("/api/netbox/conflicts/:id/resolve", post(handlers::netbox::resolve_conflict))
         .route("/api/netbox/manufacturers", get(handlers::netbox::get_manufacturers))
         .route("/api/netbox/sites", get(handlers::netbox::get_sites))
         .route("/api/netbox/device-roles", get(handlers::netbox::get_device_roles))
@@ -159,11 +368,30 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         .route("/api/docker/containers/:id", delete(handlers::docker::remove_container))
         .route("/api/docker/containers/:id/start", post(handlers::docker::start_container))
         .route("/api/docker/containers/:id/restart", post(handlers::docker::restart_container))
+        .route("/api/docker/containers/:id/stop", post(handlers::docker::stop_container))
+        .route("/api/docker/containers/:id/logs/ws", get(handlers::docker::container_logs_ws))
+        // Docker stacks
+        .route("/api/docker/stacks", get(handlers::docker::list_stacks))
+        .route("/api/docker/stacks", post(handlers::docker::create_stack))
+        .route("/api/docker/stacks/:id", get(handlers::docker::get_stack))
+        .route("/api/docker/stacks/:id", put(handlers::docker::update_stack))
+        .route("/api/docker/stacks/:id", delete(handlers::docker::delete_stack))
+        .route("/api/docker/stacks/:id/status", get(handlers::docker::stack_status))
+        .route("/api/docker/stacks/:id/refresh", post(handlers::docker::refresh_stack))
+        .route("/api/docker/stacks/:id/up", post(handlers::docker::stack_up))
+        .route("/api/docker/stacks/:id/down", post(handlers::docker::stack_down))
+        .route("/api/docker/stacks/:id/start", post(handlers::docker::start_stack))
+        .route("/api/docker/stacks/:id/stop", post(handlers::docker::stop_stack))
+        .route("/api/docker/stacks/:id/restart", post(handlers::docker::restart_stack))
         // Unified topology builder (CLOS or Hierarchical)
         .route("/api/topology-builder", post(handlers::docker::build_topology))
         .route("/api/topology-builder/preview", post(handlers::docker::preview_topology))
         .route("/api/topology-builder/clos", delete(handlers::docker::teardown_virtual_clos))
+        .route("/api/topology-builder/hierarchical", post(handlers::docker::build_hierarchical_topology))
         .route("/api/topology-builder/hierarchical", delete(handlers::docker::teardown_three_tier))
+        .route("/api/topology-builder/hierarchical/preview", post(handlers::docker::preview_hierarchical_topology))
+        .route("/api/topology-builder/multi-pod", post(handlers::docker::build_multi_pod_topology))
+        .route("/api/topology-builder/multi-pod/preview", post(handlers::docker::preview_multi_pod_topology))
         // IPAM Region routes
         .route("/api/ipam/regions", get(handlers::ipam::list_regions))
         .route("/api/ipam/regions", post(handlers::ipam::create_region))
@@ -182,6 +410,7 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         .route("/api/ipam/datacenters/:id", get(handlers::ipam::get_datacenter))
         .route("/api/ipam/datacenters/:id", put(handlers::ipam::update_datacenter))
         .route("/api/ipam/datacenters/:id", delete(handlers::ipam::delete_datacenter))
+        .route("/api/ipam/datacenters/:id/elevation", get(handlers::ipam::get_datacenter_elevation))
         // IPAM Hall routes
         .route("/api/ipam/halls", get(handlers::ipam::list_halls))
         .route("/api/ipam/halls", post(handlers::ipam::create_hall))
@@ -200,6 +429,7 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         .route("/api/ipam/racks/:id", get(handlers::ipam::get_rack))
         .route("/api/ipam/racks/:id", put(handlers::ipam::update_rack))
         .route("/api/ipam/racks/:id", delete(handlers::ipam::delete_rack))
+        .route("/api/ipam/racks/:id/elevation", get(handlers::ipam::get_rack_elevation))
         // IPAM Role routes
         .route("/api/ipam/roles", get(handlers::ipam::list_roles))
         .route("/api/ipam/roles", post(handlers::ipam::create_role))
@@ -207,10 +437,15 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         // IPAM Prefix routes
         .route("/api/ipam/prefixes", get(handlers::ipam::list_prefixes))
         .route("/api/ipam/prefixes/supernets", get(handlers::ipam::list_supernets))
+        .route("/api/ipam/prefixes/tree", get(handlers::ipam::get_prefix_tree))
         .route("/api/ipam/prefixes", post(handlers::ipam::create_prefix))
         .route("/api/ipam/prefixes/:id", get(handlers::ipam::get_prefix))
         .route("/api/ipam/prefixes/:id", put(handlers::ipam::update_prefix))
         .route("/api/ipam/prefixes/:id", delete(handlers::ipam::delete_prefix))
+        .route("/api/ipam/prefixes/external/:external_id", get(handlers::external_ids::get_ipam_prefix))
+        .route("/api/ipam/prefixes/external/:external_id", put(handlers::external_ids::put_ipam_prefix))
+        .route("/api/ipam/prefixes/external/:external_id", delete(handlers::external_ids::delete_ipam_prefix))
+        .route("/api/ipam/prefixes/:id/available", get(handlers::ipam::get_prefix_availability))
         .route("/api/ipam/prefixes/:id/available-prefixes", post(handlers::ipam::next_available_prefix))
         .route("/api/ipam/prefixes/:id/available-ips", post(handlers::ipam::next_available_ip))
         // IPAM IP Address routes
@@ -219,6 +454,13 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         .route("/api/ipam/ip-addresses/:id", get(handlers::ipam::get_ip_address))
         .route("/api/ipam/ip-addresses/:id", put(handlers::ipam::update_ip_address))
         .route("/api/ipam/ip-addresses/:id", delete(handlers::ipam::delete_ip_address))
+        .route("/api/ipam/ip-addresses/external/:external_id", get(handlers::external_ids::get_ipam_ip_address))
+        .route("/api/ipam/ip-addresses/external/:external_id", put(handlers::external_ids::put_ipam_ip_address))
+        .route("/api/ipam/ip-addresses/external/:external_id", delete(handlers::external_ids::delete_ipam_ip_address))
+        // IPAM Neighbor Discovery routes
+        .route("/api/ipam/neighbors/collect", post(handlers::ipam::collect_neighbors))
+        .route("/api/ipam/discovered-addresses", get(handlers::ipam::list_discovered_addresses))
+        .route("/api/ipam/discovered-addresses/:id", delete(handlers::ipam::delete_discovered_address))
         // IPAM VRF routes
         .route("/api/ipam/vrfs", get(handlers::ipam::list_vrfs))
         .route("/api/ipam/vrfs", post(handlers::ipam::create_vrf))
@@ -229,12 +471,17 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         .route("/api/ipam/tags/:resource_type/:resource_id", get(handlers::ipam::list_tags))
         .route("/api/ipam/tags/:resource_type/:resource_id", post(handlers::ipam::set_tag))
         .route("/api/ipam/tags/:resource_type/:resource_id/:key", delete(handlers::ipam::delete_tag))
+        // IPAM export routes
+        .route("/api/ipam/dns/reverse", get(handlers::ipam::export_reverse_dns))
         // Credential routes
         .route("/api/credentials", get(handlers::credentials::list_credentials))
         .route("/api/credentials", post(handlers::credentials::create_credential))
+        .route("/api/credentials/audit", get(handlers::credentials::audit_credentials))
         .route("/api/credentials/:id", get(handlers::credentials::get_credential))
         .route("/api/credentials/:id", put(handlers::credentials::update_credential))
         .route("/api/credentials/:id", delete(handlers::credentials::delete_credential))
+        .route("/api/credentials/:id/usage", get(handlers::credentials::get_credential_usage))
+        .route("/api/credentials/:id/rotate", post(handlers::credentials::rotate_credential))
         // Device Role routes
         .route("/api/device-roles", get(handlers::device_roles::list_device_roles))
         .route("/api/device-roles", post(handlers::device_roles::create_device_role))
@@ -253,29 +500,148 @@ pub fn build(state: Arc<AppState>, frontend_dir: &str) -> Router {
         .route("/api/gpu-clusters/:id", get(handlers::gpu_clusters::get_gpu_cluster))
         .route("/api/gpu-clusters/:id", put(handlers::gpu_clusters::update_gpu_cluster))
         .route("/api/gpu-clusters/:id", delete(handlers::gpu_clusters::delete_gpu_cluster))
+        .route("/api/gpu-clusters/:id/devices", get(handlers::gpu_clusters::list_gpu_cluster_devices))
+        .route("/api/gpu-clusters/:id/devices", put(handlers::gpu_clusters::set_gpu_cluster_devices))
+        .route("/api/gpu-clusters/:id/devices/:device_id", put(handlers::gpu_clusters::assign_gpu_cluster_device))
+        .route("/api/gpu-clusters/:id/devices/:device_id", delete(handlers::gpu_clusters::unassign_gpu_cluster_device))
+        .route("/api/gpu-clusters/:id/variables", get(handlers::gpu_clusters::list_gpu_cluster_variables))
+        .route("/api/gpu-clusters/:id/variables/:key", put(handlers::gpu_clusters::set_gpu_cluster_variable))
+        .route("/api/gpu-clusters/:id/variables/:key", delete(handlers::gpu_clusters::delete_gpu_cluster_variable))
         // Tenant routes
         .route("/api/tenants", get(handlers::tenants::list_tenants))
         .route("/api/tenants", post(handlers::tenants::create_tenant))
         .route("/api/tenants/:id", get(handlers::tenants::get_tenant))
         .route("/api/tenants/:id", put(handlers::tenants::update_tenant))
         .route("/api/tenants/:id", delete(handlers::tenants::delete_tenant))
+        .route("/api/tenants/:id/devices", get(handlers::tenants::list_tenant_devices))
+        .route("/api/tenants/:id/devices", put(handlers::tenants::set_tenant_devices))
+        .route("/api/tenants/:id/devices/:device_id", put(handlers::tenants::assign_tenant_device))
+        .route("/api/tenants/:id/devices/:device_id", delete(handlers::tenants::unassign_tenant_device))
+        .route("/api/tenants/:id/variables", get(handlers::tenants::list_tenant_variables))
+        .route("/api/tenants/:id/variables/:key", put(handlers::tenants::set_tenant_variable))
+        .route("/api/tenants/:id/variables/:key", delete(handlers::tenants::delete_tenant_variable))
+        // Maintenance window routes
+        .route("/api/maintenance-mode", get(handlers::maintenance::get_maintenance_mode))
+        .route("/api/maintenance-mode", put(handlers::maintenance::set_maintenance_mode))
+        .route("/api/maintenance-windows", get(handlers::maintenance::list_maintenance_windows))
+        .route("/api/maintenance-windows", post(handlers::maintenance::create_maintenance_window))
+        .route("/api/maintenance-windows/:id", get(handlers::maintenance::get_maintenance_window))
+        .route("/api/maintenance-windows/:id", put(handlers::maintenance::update_maintenance_window))
+        .route("/api/maintenance-windows/:id", delete(handlers::maintenance::delete_maintenance_window))
+        .route("/api/staged-configs", get(handlers::staged_configs::list_staged_configs))
+        .route("/api/staged-configs/:id", get(handlers::staged_configs::get_staged_config))
+        .route("/api/staged-configs/:id", put(handlers::staged_configs::update_staged_config))
+        .route("/api/staged-configs/:id/cancel", post(handlers::staged_configs::cancel_staged_config))
+        // Syslog routes
+        .route("/api/syslog/events", get(handlers::syslog::list_syslog_events))
+        .route("/api/syslog/alerts", get(handlers::syslog::list_syslog_alerts))
+        .route("/api/syslog/alerts/:id/acknowledge", post(handlers::syslog::acknowledge_syslog_alert))
+        .route("/api/syslog/rules", get(handlers::syslog::list_syslog_alert_rules))
+        .route("/api/syslog/rules", post(handlers::syslog::create_syslog_alert_rule))
+        .route("/api/syslog/rules/:id", get(handlers::syslog::get_syslog_alert_rule))
+        .route("/api/syslog/rules/:id", put(handlers::syslog::update_syslog_alert_rule))
+        .route("/api/syslog/rules/:id", delete(handlers::syslog::delete_syslog_alert_rule))
+        // Telemetry routes
+        .route("/api/telemetry/subscriptions", get(handlers::telemetry::list_telemetry_subscriptions))
+        .route("/api/telemetry/subscriptions", post(handlers::telemetry::create_telemetry_subscription))
+        .route("/api/telemetry/subscriptions/:id", get(handlers::telemetry::get_telemetry_subscription))
+        .route("/api/telemetry/subscriptions/:id", put(handlers::telemetry::update_telemetry_subscription))
+        .route("/api/telemetry/subscriptions/:id", delete(handlers::telemetry::delete_telemetry_subscription))
+        .route("/api/telemetry/streams", get(handlers::telemetry::list_telemetry_streams))
+        .route("/api/telemetry/samples", get(handlers::telemetry::list_telemetry_samples))
+        // Automation rules
+        .route("/api/automation-rules", get(handlers::automation::list_automation_rules))
+        .route("/api/automation-rules", post(handlers::automation::create_automation_rule))
+        .route("/api/automation-rules/:id", get(handlers::automation::get_automation_rule))
+        .route("/api/automation-rules/:id", put(handlers::automation::update_automation_rule))
+        .route("/api/automation-rules/:id", delete(handlers::automation::delete_automation_rule))
+        .route("/api/automation-rules/:id/executions", get(handlers::automation::list_automation_executions))
+        // Change log routes
+        .route("/api/changelog", get(handlers::changelog::list_changelog))
+        // Report routes
+        .route("/api/reports/availability", get(handlers::reports::availability_report))
+        .route("/api/reports/assets", get(handlers::reports::asset_report))
+        .route("/api/reports/fleet", get(handlers::reports::fleet_report))
+        .route("/api/reports/schedules", get(handlers::reports::list_report_schedules))
+        .route("/api/reports/schedules", post(handlers::reports::create_report_schedule))
+        .route("/api/reports/schedules/:id", get(handlers::reports::get_report_schedule))
+        .route("/api/reports/schedules/:id", put(handlers::reports::update_report_schedule))
+        .route("/api/reports/schedules/:id", delete(handlers::reports::delete_report_schedule))
+        .route("/api/reports/schedules/:id/run", post(handlers::reports::run_report_schedule))
+        // Metric alert rules and alerts
+        .route("/api/alerts", get(handlers::alerts::list_alerts))
+        .route("/api/alerts/evaluate", post(handlers::alerts::evaluate_alert_rules))
+        .route("/api/alerts/:id", get(handlers::alerts::get_alert))
+        .route("/api/alerts/:id/acknowledge", post(handlers::alerts::acknowledge_alert))
+        .route("/api/alerts/:id/assign", put(handlers::alerts::assign_alert))
+        .route("/api/alerts/:id/resolve", post(handlers::alerts::resolve_alert))
+        .route("/api/alert-rules", get(handlers::alerts::list_alert_rules))
+        .route("/api/alert-rules", post(handlers::alerts::create_alert_rule))
+        .route("/api/alert-rules/:id", get(handlers::alerts::get_alert_rule))
+        .route("/api/alert-rules/:id", put(handlers::alerts::update_alert_rule))
+        .route("/api/alert-rules/:id", delete(handlers::alerts::delete_alert_rule))
+        // Metrics export
+        .route("/api/metrics/influx", get(handlers::metrics::influx_metrics))
+        // Notification channels
+        .route("/api/notification-channels", get(handlers::notifications::list_notification_channels))
+        .route("/api/notification-channels", post(handlers::notifications::create_notification_channel))
+        .route("/api/notification-channels/:id", get(handlers::notifications::get_notification_channel))
+        .route("/api/notification-channels/:id", put(handlers::notifications::update_notification_channel))
+        .route("/api/notification-channels/:id", delete(handlers::notifications::delete_notification_channel))
+        .route("/api/notification-channels/:id/test", post(handlers::notifications::test_notification_channel))
         // User management routes
         .route("/api/users", get(handlers::users::list_users))
         .route("/api/users", post(handlers::users::create_user))
         .route("/api/users/:id", get(handlers::users::get_user))
         .route("/api/users/:id", put(handlers::users::update_user))
         .route("/api/users/:id", delete(handlers::users::delete_user))
+        .route("/api/users/:id/impersonate", post(handlers::users::impersonate_user))
+        // Teams
+        .route("/api/external-ids", get(handlers::external_ids::list_external_ids))
+        .route("/api/external-ids", post(handlers::external_ids::create_external_id))
+        .route("/api/external-ids/:resource_type/:external_id", delete(handlers::external_ids::delete_external_id))
+        .route("/api/teams", get(handlers::teams::list_teams))
+        .route("/api/teams", post(handlers::teams::create_team))
+        .route("/api/teams/:id", get(handlers::teams::get_team))
+        .route("/api/teams/:id", put(handlers::teams::update_team))
+        .route("/api/teams/:id", delete(handlers::teams::delete_team))
+        .route("/api/teams/:id/members", put(handlers::teams::set_team_members))
+        .route("/api/teams/:id/resources", get(handlers::teams::list_team_resources))
+        .route("/api/teams/:id/resources", post(handlers::teams::add_team_resource))
+        .route("/api/teams/:id/resources/:resource_type/:resource_id", delete(handlers::teams::remove_team_resource))
+        .route("/api/teams/:id/rules", get(handlers::teams::list_team_rules))
+        .route("/api/teams/:id/rules", post(handlers::teams::create_team_rule))
+        .route("/api/teams/:id/rules/:rule_id", delete(handlers::teams::delete_team_rule))
+        .route("/api/teams/:id/permissions", get(handlers::permissions::get_team_permissions))
+        .route("/api/teams/:id/permissions", put(handlers::permissions::set_team_permissions))
+        // Permissions matrix
+        .route("/api/permissions", get(handlers::permissions::get_permission_matrix))
+        .route("/api/permissions/me", get(handlers::permissions::get_my_permissions))
+        .route("/api/roles", get(handlers::permissions::list_roles))
+        .route("/api/roles", post(handlers::permissions::create_role))
+        .route("/api/roles/:id", get(handlers::permissions::get_role))
+        .route("/api/roles/:id", put(handlers::permissions::update_role))
+        .route("/api/roles/:id", delete(handlers::permissions::delete_role))
+        .route("/api/roles/:id/members", put(handlers::permissions::set_role_members))
+        .route("/api/roles/:id/permissions", put(handlers::permissions::set_role_permissions))
         // WebSocket route
         .route("/api/ws", get(crate::ws_upgrade_handler))
         .route("/api/ws/broadcast", post(handlers::ws_broadcast::broadcast))
+        // ACME HTTP-01 challenge responses
+        .route("/.well-known/acme-challenge/:token", get(handlers::tls::acme_challenge))
         // Config server route
         .route("/configs/:filename", get(handlers::configs::serve_config))
+        // HTTP ZTP, authorized by the per-device token in the URL
+        .route("/ztp/config/:mac", get(handlers::configs::serve_ztp_config))
         // Static files (frontend)
         .nest_service("/assets", ServeDir::new(format!("{}/assets", frontend_dir)))
         .fallback_service(ServeDir::new(frontend_dir).fallback(
             tower_http::services::ServeFile::new(format!("{}/index.html", frontend_dir)),
         ))
         // Add state and middleware
+        .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::permissions::permission_guard))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::settings::read_only_guard))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::api_usage::usage_meter))
         .with_state(state)
         .layer(
             CorsLayer::new()
@@ -0,0 +1,299 @@
+//! Minimal ACME (RFC 8555) client: account registration, orders, HTTP-01/DNS-01 authorization
+//! and certificate download. Requests are signed with an ES256 account key kept on disk.
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+const POLL_ATTEMPTS: usize = 40;
+const BAD_NONCE: &str = "urn:ietf:params:acme:error:badNonce";
+
+/// Publishes and withdraws challenge responses for the CA to check
+#[async_trait::async_trait]
+pub trait ChallengeSolver: Send + Sync {
+    /// ACME challenge type this solver answers
+    fn challenge_type(&self) -> &'static str;
+    async fn present(&self, domain: &str, token: &str, key_authorization: &str) -> Result<()>;
+    async fn cleanup(&self, domain: &str, token: &str, key_authorization: &str);
+}
+
+/// Write `contents` to a new owner-only (0600) file beside `path` and return its path, for
+/// renaming over `path` once everything it goes with is written too. Keys never sit on disk
+/// readable by other users, and readers of `path` never see a half-written file.
+pub async fn stage_file(path: &Path, contents: &[u8]) -> Result<PathBuf> {
+    let mut staged = path.as_os_str().to_owned();
+    staged.push(".tmp");
+    let staged = PathBuf::from(staged);
+    // A leftover from a crash keeps its old mode; start over so the mode below applies
+    let _ = tokio::fs::remove_file(&staged).await;
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&staged)
+        .await
+        .with_context(|| format!("failed to create {}", staged.display()))?;
+    file.write_all(contents).await?;
+    file.sync_all().await?;
+    Ok(staged)
+}
+
+/// Replace `path` with an owner-only file holding `contents`, in one step
+pub async fn write_private_file(path: &Path, contents: &[u8]) -> Result<()> {
+    let staged = stage_file(path, contents).await?;
+    tokio::fs::rename(&staged, path).await?;
+    Ok(())
+}
+
+/// TXT record value for a DNS-01 challenge
+pub fn dns_txt_value(key_authorization: &str) -> String {
+    URL_SAFE_NO_PAD.encode(ring::digest::digest(&ring::digest::SHA256, key_authorization.as_bytes()))
+}
+
+fn b64(data: impl AsRef<[u8]>) -> String {
+    URL_SAFE_NO_PAD.encode(data)
+}
+
+fn str_field<'a>(value: &'a Value, field: &str) -> Result<&'a str> {
+    value[field].as_str().ok_or_else(|| anyhow!("ACME response is missing '{}'", field))
+}
+
+/// Problem details of a failed authorization, if the CA reported any
+fn challenge_error(authz: &Value) -> String {
+    authz["challenges"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find_map(|c| c["error"]["detail"].as_str())
+        .unwrap_or("authorization failed")
+        .to_string()
+}
+
+struct Response {
+    location: Option<String>,
+    body: String,
+}
+
+impl Response {
+    fn json(&self) -> Result<Value> {
+        serde_json::from_str(&self.body).context("invalid ACME response")
+    }
+}
+
+pub struct AcmeClient {
+    http: reqwest::Client,
+    rng: SystemRandom,
+    key: EcdsaKeyPair,
+    new_nonce_url: String,
+    new_account_url: String,
+    new_order_url: String,
+    nonce: Option<String>,
+    account_url: Option<String>,
+}
+
+impl AcmeClient {
+    /// Load (or create) the account key, fetch the CA's directory and register the account
+    pub async fn connect(directory_url: &str, account_key_path: &Path, email: &str) -> Result<Self> {
+        let rng = SystemRandom::new();
+        let pkcs8 = match tokio::fs::read(account_key_path).await {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                let generated = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)
+                    .map_err(|_| anyhow!("failed to generate ACME account key"))?;
+                write_private_file(account_key_path, generated.as_ref())
+                    .await
+                    .context("failed to save ACME account key")?;
+                generated.as_ref().to_vec()
+            }
+        };
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &pkcs8, &rng)
+            .map_err(|_| anyhow!("invalid ACME account key at {}", account_key_path.display()))?;
+
+        let http = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        let directory: Value = http
+            .get(directory_url)
+            .send()
+            .await
+            .context("failed to fetch ACME directory")?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let mut client = Self {
+            http,
+            rng,
+            key,
+            new_nonce_url: str_field(&directory, "newNonce")?.to_string(),
+            new_account_url: str_field(&directory, "newAccount")?.to_string(),
+            new_order_url: str_field(&directory, "newOrder")?.to_string(),
+            nonce: None,
+            account_url: None,
+        };
+
+        let mut account = json!({ "termsOfServiceAgreed": true });
+        if !email.is_empty() {
+            account["contact"] = json!([format!("mailto:{}", email)]);
+        }
+        let url = client.new_account_url.clone();
+        let response = client.post(&url, Some(&account)).await.context("ACME account registration failed")?;
+        client.account_url = Some(response.location.ok_or_else(|| anyhow!("ACME account response has no Location"))?);
+        Ok(client)
+    }
+
+    fn jwk(&self) -> Value {
+        // Uncompressed point: 0x04 || x || y
+        let point = self.key.public_key().as_ref();
+        json!({
+            "crv": "P-256",
+            "kty": "EC",
+            "x": b64(&point[1..33]),
+            "y": b64(&point[33..65]),
+        })
+    }
+
+    /// RFC 7638 thumbprint of the account key, the second half of every key authorization
+    fn thumbprint(&self) -> String {
+        let jwk = self.jwk();
+        // Members in lexicographic order with no whitespace
+        let canonical = format!(
+            r#"{{"crv":"P-256","kty":"EC","x":"{}","y":"{}"}}"#,
+            jwk["x"].as_str().unwrap_or_default(),
+            jwk["y"].as_str().unwrap_or_default()
+        );
+        b64(ring::digest::digest(&ring::digest::SHA256, canonical.as_bytes()))
+    }
+
+    async fn fresh_nonce(&mut self) -> Result<String> {
+        if let Some(nonce) = self.nonce.take() {
+            return Ok(nonce);
+        }
+        let response = self.http.head(&self.new_nonce_url).send().await.context("failed to get ACME nonce")?;
+        response
+            .headers()
+            .get("replay-nonce")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("ACME server returned no nonce"))
+    }
+
+    /// Signed POST; `None` sends a POST-as-GET
+    async fn post(&mut self, url: &str, payload: Option<&Value>) -> Result<Response> {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let mut protected = json!({ "alg": "ES256", "nonce": self.fresh_nonce().await?, "url": url });
+            match &self.account_url {
+                Some(kid) => protected["kid"] = json!(kid),
+                None => protected["jwk"] = self.jwk(),
+            }
+            let protected = b64(protected.to_string());
+            let payload = payload.map(|p| b64(p.to_string())).unwrap_or_default();
+            let signature = self
+                .key
+                .sign(&self.rng, format!("{}.{}", protected, payload).as_bytes())
+                .map_err(|_| anyhow!("failed to sign ACME request"))?;
+            let body = json!({ "protected": protected, "payload": payload, "signature": b64(signature) });
+
+            let response = self
+                .http
+                .post(url)
+                .header("content-type", "application/jose+json")
+                .body(body.to_string())
+                .send()
+                .await
+                .with_context(|| format!("ACME request to {} failed", url))?;
+            let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+            self.nonce = header("replay-nonce");
+            let location = header("location");
+            let status = response.status();
+            let body = response.text().await?;
+
+            if status.is_success() {
+                return Ok(Response { location, body });
+            }
+            let problem: Value = serde_json::from_str(&body).unwrap_or_default();
+            if problem["type"] == BAD_NONCE && attempts < 3 {
+                continue;
+            }
+            bail!(
+                "ACME server returned HTTP {}: {}",
+                status,
+                problem["detail"].as_str().unwrap_or(&body)
+            );
+        }
+    }
+
+    /// POST-as-GET `url` until its status is `valid`
+    async fn poll_valid(&mut self, url: &str) -> Result<Value> {
+        for _ in 0..POLL_ATTEMPTS {
+            let value = self.post(url, None).await?.json()?;
+            match value["status"].as_str() {
+                Some("valid") => return Ok(value),
+                Some("invalid") => bail!("{}", challenge_error(&value)),
+                _ => tokio::time::sleep(POLL_INTERVAL).await,
+            }
+        }
+        bail!("timed out waiting for {}", url)
+    }
+
+    async fn authorize(&mut self, authz_url: &str, solver: &dyn ChallengeSolver) -> Result<()> {
+        let authz = self.post(authz_url, None).await?.json()?;
+        if authz["status"] == "valid" {
+            return Ok(());
+        }
+        let domain = authz["identifier"]["value"].as_str().unwrap_or_default().to_string();
+        let challenge = authz["challenges"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|c| c["type"] == solver.challenge_type())
+            .ok_or_else(|| anyhow!("CA offered no {} challenge for {}", solver.challenge_type(), domain))?;
+        let token = str_field(challenge, "token")?.to_string();
+        let challenge_url = str_field(challenge, "url")?.to_string();
+        let key_authorization = format!("{}.{}", token, self.thumbprint());
+
+        solver.present(&domain, &token, &key_authorization).await?;
+        let result = async {
+            self.post(&challenge_url, Some(&json!({}))).await?;
+            self.poll_valid(authz_url).await
+        }
+        .await;
+        solver.cleanup(&domain, &token, &key_authorization).await;
+        result.with_context(|| format!("validation of {} failed", domain))?;
+        Ok(())
+    }
+
+    /// Order a certificate for `domains` and return the PEM chain. `csr_der` must cover the
+    /// same names.
+    pub async fn issue(&mut self, domains: &[String], csr_der: &[u8], solver: &dyn ChallengeSolver) -> Result<String> {
+        let identifiers: Vec<Value> = domains.iter().map(|d| json!({ "type": "dns", "value": d })).collect();
+        let url = self.new_order_url.clone();
+        let response = self.post(&url, Some(&json!({ "identifiers": identifiers }))).await?;
+        let order_url = response.location.clone().ok_or_else(|| anyhow!("ACME order response has no Location"))?;
+        let order = response.json()?;
+
+        let authorizations: Vec<String> = order["authorizations"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|a| a.as_str().map(str::to_string))
+            .collect();
+        for authz_url in &authorizations {
+            self.authorize(authz_url, solver).await?;
+        }
+
+        let finalize_url = str_field(&order, "finalize")?.to_string();
+        self.post(&finalize_url, Some(&json!({ "csr": b64(csr_der) }))).await?;
+        let order = self.poll_valid(&order_url).await?;
+        let certificate_url = str_field(&order, "certificate")?.to_string();
+        Ok(self.post(&certificate_url, None).await?.body)
+    }
+}
@@ -0,0 +1,245 @@
+//! Metric alerting: enabled alert rules are evaluated against collected data every few minutes.
+//! A subject over a rule's threshold opens an alert, which fires once it has stayed over for the
+//! rule's for_minutes and resolves when the value drops back. The rule's channels are notified
+//! when alerts fire and when fired alerts resolve; acknowledging an alert doesn't stop it from
+//! resolving on its own.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::db::Store;
+use crate::models::*;
+use crate::services::interface_drift;
+use crate::services::maintenance_mode::MaintenanceMode;
+use crate::services::notifications;
+
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// One subject's value of a metric: (subject id, subject name, value)
+type Measurement = (i64, String, f64);
+
+/// Measure `metric` for every device or prefix
+async fn measure(store: &Store, metric: &str, now: DateTime<Utc>) -> Result<Vec<Measurement>> {
+    match metric {
+        alert_metric::BACKUP_AGE_HOURS => Ok(store
+            .list_devices()
+            .await?
+            .into_iter()
+            .map(|d| {
+                let since = d.last_backup.unwrap_or(d.created_at);
+                let hours = (now - since).num_minutes() as f64 / 60.0;
+                (d.id, d.hostname, hours)
+            })
+            .collect()),
+        alert_metric::INTERFACE_DRIFT => {
+            let models = store.list_device_models().await?;
+            let mut out = Vec::new();
+            for device in store.list_devices().await? {
+                let report = interface_drift::device_report(store, &device, &models).await?;
+                out.push((device.id, device.hostname, report.mismatches.len() as f64));
+            }
+            Ok(out)
+        }
+        alert_metric::STATUS_CHANGES_PER_DAY => {
+            let mut changes: HashMap<i64, usize> = HashMap::new();
+            for transition in store.list_status_transitions_since(now - Duration::days(1)).await? {
+                *changes.entry(transition.device_id).or_default() += 1;
+            }
+            Ok(store
+                .list_devices()
+                .await?
+                .into_iter()
+                .map(|d| (d.id, d.hostname, changes.get(&d.id).copied().unwrap_or(0) as f64))
+                .collect())
+        }
+        alert_metric::PREFIX_UTILIZATION => {
+            let prefixes = store.list_ipam_prefixes().await?;
+            let utilization = crate::utils::prefix_utilization(&prefixes);
+            Ok(prefixes
+                .into_iter()
+                .map(|p| {
+                    let value = utilization.get(&p.id).copied().unwrap_or(0.0);
+                    (p.id, p.prefix, (value * 100.0).round() / 100.0)
+                })
+                .collect())
+        }
+        other => anyhow::bail!("unknown metric '{}'", other),
+    }
+}
+
+/// Update one rule's alerts from the latest measurements. Returns (opened, newly firing, resolved
+/// after firing or being acknowledged) so the caller can count and notify.
+async fn evaluate_rule(
+    store: &Store,
+    rule: &AlertRule,
+    measurements: &[Measurement],
+    now: DateTime<Utc>,
+) -> Result<(usize, Vec<Alert>, Vec<Alert>)> {
+    let mut open: HashMap<i64, Alert> = store
+        .list_open_alerts(rule.id)
+        .await?
+        .into_iter()
+        .map(|a| (a.subject_id, a))
+        .collect();
+    let mut opened = 0;
+    let mut fired = Vec::new();
+
+    for (subject_id, subject_name, value) in measurements {
+        if *value <= rule.threshold {
+            continue;
+        }
+        match open.remove(subject_id) {
+            Some(mut alert) => {
+                // An acknowledged alert stays acknowledged while the value is still over
+                let status = if alert.status == alert_status::ACKNOWLEDGED {
+                    alert_status::ACKNOWLEDGED
+                } else {
+                    crate::utils::exceeded_alert_status(alert.started_at, rule.for_minutes, now)
+                };
+                store.update_alert(alert.id, *value, status, now).await?;
+                if alert.status == alert_status::PENDING && status == alert_status::FIRING {
+                    alert.status = status.to_string();
+                    alert.value = *value;
+                    alert.fired_at = Some(now);
+                    fired.push(alert);
+                }
+            }
+            None => {
+                let status = crate::utils::exceeded_alert_status(now, rule.for_minutes, now);
+                let alert = store.open_alert(rule, *subject_id, subject_name, *value, status, now).await?;
+                opened += 1;
+                if status == alert_status::FIRING {
+                    fired.push(alert);
+                }
+            }
+        }
+    }
+
+    // What's left is back under the threshold, or the subject is gone
+    let mut resolved = Vec::new();
+    for (subject_id, mut alert) in open {
+        let value = measurements
+            .iter()
+            .find(|(id, _, _)| *id == subject_id)
+            .map_or(alert.value, |(_, _, v)| *v);
+        store.update_alert(alert.id, value, alert_status::RESOLVED, now).await?;
+        if alert.status != alert_status::PENDING {
+            alert.value = value;
+            alert.status = alert_status::RESOLVED.to_string();
+            alert.resolved_at = Some(now);
+            resolved.push(alert);
+        }
+    }
+    Ok((opened, fired, resolved))
+}
+
+fn render_text(rule: &AlertRule, fired: &[Alert], resolved: &[Alert]) -> String {
+    let mut out = format!("Alert rule '{}': {} > {}\n", rule.name, rule.metric, rule.threshold);
+    if !fired.is_empty() {
+        out.push_str(&format!("\nFiring ({}):\n", fired.len()));
+        for alert in fired {
+            out.push_str(&format!("  - {} {}: {}\n", alert.subject_type, alert.subject_name, alert.value));
+        }
+    }
+    if !resolved.is_empty() {
+        out.push_str(&format!("\nResolved ({}):\n", resolved.len()));
+        for alert in resolved {
+            out.push_str(&format!("  - {} {}: {}\n", alert.subject_type, alert.subject_name, alert.value));
+        }
+    }
+    out
+}
+
+async fn notify(store: &Store, config: &Config, rule: &AlertRule, fired: &[Alert], resolved: &[Alert]) -> Result<Vec<String>> {
+    let mut channels = Vec::new();
+    for id in &rule.channel_ids {
+        match store.get_notification_channel(*id).await? {
+            Some(channel) => channels.push(channel),
+            None => tracing::warn!("Alert rule '{}': notification channel {} not found", rule.name, id),
+        }
+    }
+    if channels.is_empty() {
+        return Ok(Vec::new());
+    }
+    let notification = Notification {
+        subject: format!(
+            "[ForgeConfig] {}: {} firing, {} resolved",
+            rule.name,
+            fired.len(),
+            resolved.len()
+        ),
+        text: render_text(rule, fired, resolved),
+        payload: serde_json::json!({ "rule": rule, "fired": fired, "resolved": resolved }),
+    };
+    Ok(notifications::send_all(config, &channels, &notification).await)
+}
+
+/// Evaluate every enabled rule once. Each metric is measured once per pass however many rules
+/// watch it. A rule that fails to evaluate or notify is reported in `errors` without stopping
+/// the others.
+pub async fn evaluate(store: &Store, config: &Config) -> Result<AlertEvaluation> {
+    let now = Utc::now();
+    let rules = store.list_enabled_alert_rules().await?;
+    let mut result = AlertEvaluation { rules: rules.len(), ..Default::default() };
+    let mut measured: HashMap<String, Vec<Measurement>> = HashMap::new();
+
+    for rule in &rules {
+        if !measured.contains_key(&rule.metric) {
+            match measure(store, &rule.metric, now).await {
+                Ok(values) => {
+                    measured.insert(rule.metric.clone(), values);
+                }
+                Err(e) => {
+                    result.errors.push(format!("{}: {:#}", rule.name, e));
+                    continue;
+                }
+            }
+        }
+        let (opened, fired, resolved) = match evaluate_rule(store, rule, &measured[&rule.metric], now).await {
+            Ok(changes) => changes,
+            Err(e) => {
+                result.errors.push(format!("{}: {:#}", rule.name, e));
+                continue;
+            }
+        };
+        result.opened += opened;
+        result.fired += fired.len();
+        result.resolved += resolved.len();
+        if fired.is_empty() && resolved.is_empty() {
+            continue;
+        }
+        match notify(store, config, rule, &fired, &resolved).await {
+            Ok(errors) if errors.is_empty() => {}
+            Ok(errors) => result.errors.push(format!("{}: delivery failed: {}", rule.name, errors.join("; "))),
+            Err(e) => result.errors.push(format!("{}: {:#}", rule.name, e)),
+        }
+    }
+    Ok(result)
+}
+
+/// Evaluate alert rules every five minutes
+pub fn start_scheduler(store: Store, config: Config, maintenance_mode: Arc<MaintenanceMode>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if maintenance_mode.is_enabled() {
+                continue;
+            }
+            match evaluate(&store, &config).await {
+                Ok(result) => {
+                    if result.fired > 0 || result.resolved > 0 {
+                        tracing::info!("Alert evaluation: {} fired, {} resolved", result.fired, result.resolved);
+                    }
+                    for error in &result.errors {
+                        tracing::warn!("Alert rule evaluation failed: {}", error);
+                    }
+                }
+                Err(e) => tracing::warn!("Alert evaluation failed: {:#}", e),
+            }
+        }
+    });
+}
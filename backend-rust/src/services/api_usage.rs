@@ -0,0 +1,142 @@
+//! Per-consumer API usage metering. Every authenticated API request is counted against its user,
+//! login token and endpoint class (the resource and whether it reads or writes) in memory, and the
+//! counts are added to hourly rows once a minute, so metering costs no database write per request.
+//! Counts not yet flushed are lost on restart.
+
+use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::db::Store;
+use crate::models::*;
+
+use super::maintenance_mode::MaintenanceMode;
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+/// Flushes between prunes of hours past retention
+const PRUNE_EVERY_FLUSHES: u32 = 60;
+const RETENTION_DAYS: i64 = 90;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct UsageKey {
+    hour: DateTime<Utc>,
+    username: String,
+    token_id: String,
+    resource: String,
+    access: String,
+}
+
+struct PendingUsage {
+    token_issued_at: DateTime<Utc>,
+    totals: ApiUsageTotals,
+}
+
+/// Counts since the last flush
+static PENDING: OnceLock<Mutex<HashMap<UsageKey, PendingUsage>>> = OnceLock::new();
+
+fn pending() -> &'static Mutex<HashMap<UsageKey, PendingUsage>> {
+    PENDING.get_or_init(Default::default)
+}
+
+fn add_pending(key: UsageKey, token_issued_at: DateTime<Utc>, totals: &ApiUsageTotals) {
+    let mut pending = pending().lock().unwrap_or_else(|e| e.into_inner());
+    pending
+        .entry(key)
+        .or_insert_with(|| PendingUsage { token_issued_at, totals: ApiUsageTotals::default() })
+        .totals
+        .add(totals);
+}
+
+/// Short id of a login token, from its subject and issue and expiry times; the token itself isn't
+/// kept
+pub fn token_id(claims: &Claims) -> String {
+    let digest = ring::digest::digest(
+        &ring::digest::SHA256,
+        format!("{}:{}:{}", claims.sub, claims.iat, claims.exp).as_bytes(),
+    );
+    URL_SAFE_NO_PAD.encode(digest)[..12].to_string()
+}
+
+/// Count one request by the holder of `claims`
+pub fn record(claims: &Claims, resource: &str, access: &str, is_error: bool, request_bytes: u64, response_bytes: u64) {
+    let now = Utc::now().timestamp();
+    let key = UsageKey {
+        hour: DateTime::from_timestamp(now - now.rem_euclid(3600), 0).unwrap_or_default(),
+        username: claims.username.clone(),
+        token_id: token_id(claims),
+        resource: resource.to_string(),
+        access: access.to_string(),
+    };
+    let token_issued_at = DateTime::from_timestamp(claims.iat as i64, 0).unwrap_or_default();
+    let totals = ApiUsageTotals {
+        requests: 1,
+        errors: is_error as i64,
+        request_bytes: request_bytes as i64,
+        response_bytes: response_bytes as i64,
+    };
+    add_pending(key, token_issued_at, &totals);
+}
+
+/// Add the counts gathered since the last flush to the database. If that fails they are kept
+/// for the next flush.
+pub async fn flush(store: &Store) -> Result<()> {
+    let drained: Vec<(UsageKey, PendingUsage)> = pending().lock().unwrap_or_else(|e| e.into_inner()).drain().collect();
+    if drained.is_empty() {
+        return Ok(());
+    }
+    let entries: Vec<ApiUsageEntry> = drained
+        .into_iter()
+        .map(|(key, usage)| ApiUsageEntry {
+            hour: key.hour,
+            username: key.username,
+            token_id: key.token_id,
+            token_issued_at: usage.token_issued_at,
+            resource: key.resource,
+            access: key.access,
+            totals: usage.totals,
+        })
+        .collect();
+    if let Err(e) = store.add_api_usage(&entries).await {
+        for entry in entries {
+            let key = UsageKey {
+                hour: entry.hour,
+                username: entry.username,
+                token_id: entry.token_id,
+                resource: entry.resource,
+                access: entry.access,
+            };
+            add_pending(key, entry.token_issued_at, &entry.totals);
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Flush the counts every minute and drop hours older than the retention hourly. Paused while
+/// maintenance mode is on; requests are still counted in memory meanwhile.
+pub fn start_flusher(store: Store, maintenance_mode: Arc<MaintenanceMode>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        let mut flushes: u32 = 0;
+        loop {
+            interval.tick().await;
+            if maintenance_mode.is_enabled() {
+                continue;
+            }
+            if let Err(e) = flush(&store).await {
+                tracing::warn!("Failed to record API usage: {:#}", e);
+            }
+            flushes = flushes.wrapping_add(1);
+            if flushes.is_multiple_of(PRUNE_EVERY_FLUSHES) {
+                let cutoff = Utc::now() - chrono::Duration::days(RETENTION_DAYS);
+                if let Err(e) = store.prune_api_usage(cutoff).await {
+                    tracing::warn!("Failed to prune API usage: {:#}", e);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,219 @@
+//! Automation rules engine: subscribes to the hub and runs the action of every enabled rule whose
+//! event type and filter match, recording each run in the rule's execution history.
+
+use anyhow::{bail, Context, Result};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::db::Store;
+use crate::jobs::JobService;
+use crate::models::*;
+use crate::services::maintenance_mode::MaintenanceMode;
+use crate::ws::Hub;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(30);
+/// Username recorded in the change log for variables set by rules
+const CHANGE_LOG_USER: &str = "automation";
+
+/// Payload field as a string; `path` is dotted for nested objects
+fn payload_field(payload: &serde_json::Value, path: &str) -> Option<String> {
+    let value = path.split('.').try_fold(payload, |v, key| v.get(key))?;
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Every filter field equals its expected value
+fn matches_filter(rule: &AutomationRule, payload: &serde_json::Value) -> bool {
+    rule.filter.iter().all(|(path, expected)| payload_field(payload, path).as_deref() == Some(expected.as_str()))
+}
+
+/// Replace `{{field}}` placeholders with payload fields; unknown fields become empty
+fn interpolate(value: &str, payload: &serde_json::Value) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else { break };
+        out.push_str(&rest[..start]);
+        out.push_str(&payload_field(payload, rest[start + 2..start + 2 + len].trim()).unwrap_or_default());
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Validate a rule's action settings
+pub fn validate_action(action_type: &str, action: &serde_json::Value) -> Result<()> {
+    match action_type {
+        automation_action::JOB_TEMPLATE => {
+            serde_json::from_value::<JobTemplateAutomationAction>(action.clone())?;
+        }
+        automation_action::WEBHOOK => {
+            let webhook: WebhookAutomationAction = serde_json::from_value(action.clone())?;
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                bail!("url must be an http(s) URL");
+            }
+            reqwest::Method::from_bytes(webhook.method.to_uppercase().as_bytes()).context("invalid method")?;
+        }
+        automation_action::SET_VARIABLE => {
+            let set: SetVariableAutomationAction = serde_json::from_value(action.clone())?;
+            if set.key.is_empty() {
+                bail!("key is required");
+            }
+        }
+        other => bail!("unknown action_type '{}': expected one of {}", other, automation_action::ALL.join(", ")),
+    }
+    Ok(())
+}
+
+pub struct AutomationEngine {
+    store: Store,
+    job_service: Arc<JobService>,
+    maintenance_mode: Arc<MaintenanceMode>,
+    http: reqwest::Client,
+}
+
+impl AutomationEngine {
+    /// Subscribe to the hub and evaluate rules for every event
+    pub fn start(store: Store, hub: &Hub, job_service: Arc<JobService>, maintenance_mode: Arc<MaintenanceMode>) {
+        let mut rx = hub.subscribe();
+        let engine = Arc::new(Self {
+            store,
+            job_service,
+            maintenance_mode,
+            http: reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build().unwrap_or_default(),
+        });
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(message) => engine.handle(&message).await,
+                    Err(RecvError::Lagged(missed)) => tracing::warn!("Automation engine missed {} events", missed),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    async fn handle(self: &Arc<Self>, message: &str) {
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(message) else { return };
+        let Some(event_type) = event.get("type").and_then(|t| t.as_str()) else { return };
+        if !automation_event::ALL.contains(&event_type) {
+            return;
+        }
+        let payload = event.get("payload").cloned().unwrap_or_default();
+        // Jobs queued by rules don't trigger rules, so a rule can't feed itself
+        if payload_field(&payload, "triggered_by").as_deref() == Some(TRIGGERED_BY_AUTOMATION) {
+            return;
+        }
+        if self.maintenance_mode.is_enabled() {
+            return;
+        }
+
+        let rules = match self.store.list_automation_rules_for_event(event_type).await {
+            Ok(rules) => rules,
+            Err(e) => {
+                tracing::warn!("Automation: failed to load rules: {}", e);
+                return;
+            }
+        };
+        for rule in rules.into_iter().filter(|r| matches_filter(r, &payload)) {
+            let engine = self.clone();
+            let event_type = event_type.to_string();
+            let payload = payload.clone();
+            tokio::spawn(async move {
+                let result = engine.run(&rule, &event_type, &payload).await.map_err(|e| format!("{:#}", e));
+                match &result {
+                    Ok(output) => tracing::info!("Automation rule '{}' ran on {}: {}", rule.name, event_type, output),
+                    Err(e) => tracing::warn!("Automation rule '{}' failed on {}: {}", rule.name, event_type, e),
+                }
+                if let Err(e) = engine.store.record_automation_execution(rule.id, &event_type, &payload, &result).await {
+                    tracing::warn!("Automation: failed to record execution of rule {}: {}", rule.id, e);
+                }
+            });
+        }
+    }
+
+    /// The device an event is about, from its device_id or mac
+    async fn event_device(&self, payload: &serde_json::Value) -> Result<Device> {
+        if let Some(id) = payload.get("device_id").and_then(|v| v.as_i64()).filter(|id| *id > 0) {
+            return self.store.get_device(id).await?.with_context(|| format!("device {} not found", id));
+        }
+        if let Some(mac) = payload_field(payload, "mac") {
+            return self
+                .store
+                .get_device_by_mac(&crate::utils::normalize_mac(&mac))
+                .await?
+                .with_context(|| format!("no device with MAC {}", mac));
+        }
+        bail!("event does not reference a device")
+    }
+
+    async fn run(&self, rule: &AutomationRule, event_type: &str, payload: &serde_json::Value) -> Result<String> {
+        match rule.action_type.as_str() {
+            automation_action::JOB_TEMPLATE => {
+                let action: JobTemplateAutomationAction = serde_json::from_value(rule.action.clone())?;
+                let template = self
+                    .store
+                    .get_job_template(action.job_template_id)
+                    .await?
+                    .with_context(|| format!("job template {} not found", action.job_template_id))?;
+                let device_ids = if action.event_device {
+                    vec![self.event_device(payload).await?.id]
+                } else {
+                    self.store.resolve_job_template_targets(&template).await?
+                };
+                let jobs = self.job_service.queue_template_jobs(&template, &device_ids, TRIGGERED_BY_AUTOMATION).await?;
+                if jobs.is_empty() {
+                    bail!("job template '{}' queued no jobs", template.name);
+                }
+                let ids: Vec<&str> = jobs.iter().map(|j| j.id.as_str()).collect();
+                Ok(format!("queued {} job(s) from '{}': {}", jobs.len(), template.name, ids.join(", ")))
+            }
+            automation_action::WEBHOOK => {
+                let action: WebhookAutomationAction = serde_json::from_value(rule.action.clone())?;
+                let method = reqwest::Method::from_bytes(action.method.to_uppercase().as_bytes())?;
+                let mut request = self.http.request(method, &action.url).json(&serde_json::json!({
+                    "rule": rule.name,
+                    "event_type": event_type,
+                    "payload": payload,
+                }));
+                for (key, value) in &action.headers {
+                    request = request.header(key.as_str(), value.as_str());
+                }
+                let response = request.send().await.context("HTTP request failed")?;
+                let status = response.status();
+                if !status.is_success() {
+                    bail!("HTTP {} from {}", status, action.url);
+                }
+                Ok(format!("HTTP {}", status))
+            }
+            automation_action::SET_VARIABLE => {
+                let action: SetVariableAutomationAction = serde_json::from_value(rule.action.clone())?;
+                let value = interpolate(&action.value, payload);
+                let summary = format!("set {} (automation rule '{}')", action.key, rule.name);
+                let entry = NewChangeLogEntry::variable(change_action::UPDATE, &action.key, summary);
+                let (entry, target) = match action.group_id {
+                    Some(group_id) => {
+                        self.store.set_group_variable(group_id, &action.key, &value).await?;
+                        (NewChangeLogEntry { group_id: Some(group_id), ..entry }, format!("group {}", group_id))
+                    }
+                    None => {
+                        let device = self.event_device(payload).await?;
+                        self.store.set_device_variable(device.id, &action.key, &value).await?;
+                        (NewChangeLogEntry { device_id: Some(device.id), ..entry }, device.hostname)
+                    }
+                };
+                if let Err(e) = self.store.record_change(&entry, CHANGE_LOG_USER).await {
+                    tracing::warn!("Failed to record variable change: {}", e);
+                }
+                // Values are left out since variables may hold secrets
+                Ok(format!("set {} on {}", action.key, target))
+            }
+            other => bail!("unknown action_type '{}'", other),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Optional Git history of templates and backups. With GIT_HISTORY_DIR set, every template
+//! change and every backup is committed to a local repository there, authored by the user who
+//! made it (`forge-config` for automatic backups), and pushed to GIT_HISTORY_REMOTE when set.
+//! Templates are kept as `templates/{id}.tmpl` and each device's latest config as
+//! `backups/{hostname}.cfg`, so a file's log is its history.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::template_catalog::git_output;
+use crate::models::{Template, TemplateRevision};
+
+/// Author of commits nobody in particular made, and the repository's committer
+const SYSTEM_AUTHOR: &str = "forge-config";
+
+pub struct ConfigHistory {
+    dir: PathBuf,
+    remote: String,
+    /// Commits stage and commit one path at a time, so they must not interleave
+    lock: Mutex<()>,
+}
+
+impl ConfigHistory {
+    /// Open the repository in `dir`, initializing it on first use
+    pub async fn open(dir: &str, remote: &str) -> Result<Arc<Self>> {
+        let dir = PathBuf::from(dir);
+        if !dir.join(".git").exists() {
+            tokio::fs::create_dir_all(&dir).await?;
+            git_output(&["init", "--quiet"], Some(&dir)).await?;
+            git_output(&["config", "user.name", SYSTEM_AUTHOR], Some(&dir)).await?;
+            git_output(&["config", "user.email", &email(SYSTEM_AUTHOR)], Some(&dir)).await?;
+        }
+        Ok(Arc::new(Self { dir, remote: remote.to_string(), lock: Mutex::new(()) }))
+    }
+
+    /// Open the repository configured by GIT_HISTORY_DIR, if any. A repository that can't be
+    /// opened disables history rather than startup.
+    pub async fn from_config(cfg: &crate::config::Config) -> Option<Arc<Self>> {
+        if cfg.git_history_dir.is_empty() {
+            return None;
+        }
+        match Self::open(&cfg.git_history_dir, &cfg.git_history_remote).await {
+            Ok(history) => Some(history),
+            Err(e) => {
+                tracing::error!("Git history disabled: failed to open {}: {}", cfg.git_history_dir, e);
+                None
+            }
+        }
+    }
+
+    /// Commit a template's current content
+    pub async fn record_template(&self, template: &Template, author: &str, message: &str) -> Result<Option<String>> {
+        self.commit(&template_path(template.id), Some(&template.content), author, message).await
+    }
+
+    /// Commit the removal of a deleted template
+    pub async fn remove_template(&self, template: &Template, author: &str) -> Result<Option<String>> {
+        let message = format!("Delete template {}", template.name);
+        self.commit(&template_path(template.id), None, author, &message).await
+    }
+
+    /// Commit a device's backed-up config
+    pub async fn record_backup(&self, hostname: &str, config: &str, author: Option<&str>) -> Result<Option<String>> {
+        let path = format!("backups/{}.cfg", hostname.replace('/', "_"));
+        let message = format!("Back up {}", hostname);
+        self.commit(&path, Some(config), author.unwrap_or(SYSTEM_AUTHOR), &message).await
+    }
+
+    /// Commits that changed a template, newest first
+    pub async fn template_history(&self, id: i64) -> Result<Vec<TemplateRevision>> {
+        if !self.has_commits().await {
+            return Ok(Vec::new());
+        }
+        let log = git_output(
+            &["log", "--format=%H%x1f%an%x1f%aI%x1f%s", "--", &template_path(id)],
+            Some(&self.dir),
+        )
+        .await?;
+        Ok(log
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(4, '\x1f');
+                let (commit, author, date, message) = (fields.next()?, fields.next()?, fields.next()?, fields.next()?);
+                Some(TemplateRevision {
+                    commit: commit.to_string(),
+                    author: author.to_string(),
+                    message: message.to_string(),
+                    committed_at: DateTime::parse_from_rfc3339(date).ok()?.with_timezone(&Utc),
+                })
+            })
+            .collect())
+    }
+
+    /// A template's content as of `commit`, or None when the template didn't exist there
+    pub async fn template_at(&self, id: i64, commit: &str) -> Result<Option<String>> {
+        if commit.len() < 4 || commit.len() > 40 || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow::anyhow!("invalid commit '{}'", commit));
+        }
+        let object = format!("{}:{}", commit, template_path(id));
+        Ok(git_output(&["show", &object], Some(&self.dir)).await.ok())
+    }
+
+    async fn has_commits(&self) -> bool {
+        git_output(&["rev-parse", "--verify", "--quiet", "HEAD"], Some(&self.dir)).await.is_ok()
+    }
+
+    /// Write (or, with no content, remove) one file and commit it. Returns the commit, or None
+    /// when the file was already up to date.
+    async fn commit(&self, path: &str, content: Option<&str>, author: &str, message: &str) -> Result<Option<String>> {
+        let _guard = self.lock.lock().await;
+        let file = self.dir.join(path);
+        match content {
+            Some(content) => {
+                if let Some(parent) = file.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(&file, content).await?;
+            }
+            None if file.exists() => tokio::fs::remove_file(&file).await?,
+            None => return Ok(None),
+        }
+
+        git_output(&["add", "--all", "--", path], Some(&self.dir)).await?;
+        if git_output(&["status", "--porcelain", "--", path], Some(&self.dir)).await?.trim().is_empty() {
+            return Ok(None);
+        }
+        let author = format!("{} <{}>", author, email(author));
+        git_output(&["commit", "--quiet", "--author", &author, "-m", message, "--", path], Some(&self.dir)).await?;
+        let commit = git_output(&["rev-parse", "HEAD"], Some(&self.dir)).await?.trim().to_string();
+
+        if !self.remote.is_empty() {
+            let (dir, remote) = (self.dir.clone(), self.remote.clone());
+            tokio::spawn(async move {
+                if let Err(e) = git_output(&["push", "--quiet", &remote, "HEAD"], Some(Path::new(&dir))).await {
+                    tracing::warn!("Failed to push config history: {}", e);
+                }
+            });
+        }
+        Ok(Some(commit))
+    }
+}
+
+fn template_path(id: i64) -> String {
+    format!("templates/{}.tmpl", id)
+}
+
+/// Commit email for a username; users have no email address of their own
+fn email(username: &str) -> String {
+    format!("{}@forge-config", username.replace(['<', '>', ' '], "_"))
+}
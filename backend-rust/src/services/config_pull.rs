@@ -0,0 +1,71 @@
+//! Bookkeeping for a device fetching its config file, over the HTTP config server or TFTP
+
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::db::Store;
+use crate::models::Device;
+use crate::utils::normalize_mac;
+use crate::ws::Hub;
+
+/// Find the device that fetched `filename` (by the MAC in a `<mac>.cfg` name, else by
+/// `source_ip`) and record the pull. Returns the device, if one matched.
+pub async fn on_config_pulled(
+    store: &Store,
+    ws_hub: Option<&Arc<Hub>>,
+    filename: &str,
+    source_ip: Option<&str>,
+    protocol: &str,
+) -> Option<Device> {
+    let device = match find_device(store, filename, source_ip).await {
+        Ok(Some(device)) => device,
+        Ok(None) => return None,
+        Err(e) => {
+            tracing::warn!("Failed to look up device for config pull of {}: {}", filename, e);
+            return None;
+        }
+    };
+    record_config_pull(store, ws_hub, &device, filename, source_ip, protocol).await;
+    Some(device)
+}
+
+/// Mark a device's config pull on its provisioning session and broadcast a config_pulled event
+pub async fn record_config_pull(
+    store: &Store,
+    ws_hub: Option<&Arc<Hub>>,
+    device: &Device,
+    filename: &str,
+    source_ip: Option<&str>,
+    protocol: &str,
+) {
+    if let Err(e) = store.record_provisioning_config_pulled(device.id).await {
+        tracing::warn!("Failed to record provisioning config pull for {}: {}", device.id, e);
+    }
+    if let Some(hub) = ws_hub {
+        hub.broadcast_config_pulled(
+            device.mac.as_deref().unwrap_or_default(),
+            source_ip.unwrap_or(&device.ip),
+            &device.hostname,
+            filename,
+            protocol,
+        )
+        .await;
+    }
+}
+
+async fn find_device(store: &Store, filename: &str, source_ip: Option<&str>) -> Result<Option<Device>> {
+    let name = filename.rsplit('/').next().unwrap_or(filename);
+    if let Some(mac_part) = name.strip_suffix(".cfg") {
+        let mac = normalize_mac(&mac_part.replace('_', ":"));
+        if let Some(device) = store.get_device_by_mac(&mac).await? {
+            return Ok(Some(device));
+        }
+    }
+    match source_ip {
+        Some(ip) => match store.find_device_id_by_ip(ip).await? {
+            Some(id) => store.get_device(id).await,
+            None => Ok(None),
+        },
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,174 @@
+//! Support contract expiry: the expiring-soon report and the scheduled check that notifies the
+//! contract_alert_channel_ids channels once per contract, contract_alert_days before it lapses.
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::db::Store;
+use crate::models::*;
+use crate::services::maintenance_mode::MaintenanceMode;
+use crate::services::notifications;
+use crate::utils::find_device_model;
+
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+/// Devices listed in the text notification; the payload always has all of them
+const TEXT_ROW_LIMIT: usize = 50;
+
+/// The contract covering a device: its own, else its model's
+pub fn effective_contract<'a>(
+    device: &Device,
+    by_device: &HashMap<i64, &'a SupportContract>,
+    by_model: &HashMap<i64, &'a SupportContract>,
+    models: &[DeviceModel],
+) -> Option<&'a SupportContract> {
+    by_device.get(&device.id).copied().or_else(|| {
+        let model = find_device_model(device.vendor_id.as_deref(), device.model.as_deref(), models)?;
+        by_model.get(&model.id).copied()
+    })
+}
+
+/// Devices whose effective contract expires within `within_days` (or already expired), soonest first
+pub async fn report(store: &Store, within_days: i64, include_uncovered: bool) -> Result<ContractExpiryReport> {
+    let today = Utc::now().date_naive();
+    let devices = store.list_devices().await?;
+    let models = store.list_device_models().await?;
+    let contracts = store.list_support_contracts().await?;
+    let by_device: HashMap<i64, &SupportContract> =
+        contracts.iter().filter_map(|c| Some((c.device_id?, c))).collect();
+    let by_model: HashMap<i64, &SupportContract> =
+        contracts.iter().filter_map(|c| Some((c.device_model_id?, c))).collect();
+
+    let mut rows = Vec::new();
+    let mut uncovered = Vec::new();
+    for device in &devices {
+        let Some(contract) = effective_contract(device, &by_device, &by_model, &models) else {
+            uncovered.push(UncoveredDevice {
+                device_id: device.id,
+                hostname: device.hostname.clone(),
+                model: device.model.clone().unwrap_or_default(),
+            });
+            continue;
+        };
+        let days_remaining = (contract.expires_on - today).num_days();
+        if days_remaining > within_days {
+            continue;
+        }
+        rows.push(ContractExpiryRow {
+            device_id: device.id,
+            hostname: device.hostname.clone(),
+            vendor: device.vendor.clone().unwrap_or_default(),
+            model: device.model.clone().unwrap_or_default(),
+            serial_number: device.serial_number.clone().unwrap_or_default(),
+            contract_id: contract.id,
+            scope: contract.scope().to_string(),
+            provider: contract.provider.clone(),
+            support_id: contract.support_id.clone(),
+            vendor_sla: contract.vendor_sla.clone(),
+            expires_on: contract.expires_on,
+            days_remaining,
+        });
+    }
+    rows.sort_by(|a, b| a.expires_on.cmp(&b.expires_on).then_with(|| a.hostname.cmp(&b.hostname)));
+
+    Ok(ContractExpiryReport {
+        generated_at: Utc::now(),
+        within_days,
+        expired: rows.iter().filter(|r| r.days_remaining < 0).count(),
+        rows,
+        uncovered: include_uncovered.then_some(uncovered),
+    })
+}
+
+/// Plain-text rendering of expiring contracts, as sent to email channels
+pub fn render_text(report: &ContractExpiryReport) -> String {
+    let mut out = format!(
+        "Support contracts expiring within {} days: {} device(s), {} already expired\n\n",
+        report.within_days,
+        report.rows.len(),
+        report.expired
+    );
+    for row in report.rows.iter().take(TEXT_ROW_LIMIT) {
+        let when = if row.days_remaining < 0 {
+            format!("expired {} ({} days ago)", row.expires_on, -row.days_remaining)
+        } else {
+            format!("expires {} (in {} days)", row.expires_on, row.days_remaining)
+        };
+        out.push_str(&format!(
+            "  - {} [{} {}]: {}, {} {} {}\n",
+            row.hostname, row.vendor, row.model, when, row.provider, row.support_id, row.vendor_sla
+        ));
+    }
+    if report.rows.len() > TEXT_ROW_LIMIT {
+        out.push_str(&format!("  ... and {} more\n", report.rows.len() - TEXT_ROW_LIMIT));
+    }
+    out
+}
+
+/// Notify about contracts that entered the alert window since the last check. Returns the
+/// number of contracts alerted; each is only alerted again after its expires_on changes.
+pub async fn send_alerts(store: &Store, config: &Config) -> Result<usize> {
+    let settings = store.get_settings().await?;
+    if settings.contract_alert_channel_ids.is_empty() {
+        return Ok(0);
+    }
+    let mut report = report(store, settings.contract_alert_days as i64, false).await?;
+    let contracts: HashMap<i64, SupportContract> =
+        store.list_support_contracts().await?.into_iter().map(|c| (c.id, c)).collect();
+    report.rows.retain(|r| {
+        contracts
+            .get(&r.contract_id)
+            .is_some_and(|c| c.alerted_expires_on != Some(c.expires_on))
+    });
+    if report.rows.is_empty() {
+        return Ok(0);
+    }
+    report.expired = report.rows.iter().filter(|r| r.days_remaining < 0).count();
+    let pending: BTreeMap<i64, chrono::NaiveDate> =
+        report.rows.iter().map(|r| (r.contract_id, r.expires_on)).collect();
+
+    let mut channels = Vec::new();
+    for id in &settings.contract_alert_channel_ids {
+        match store.get_notification_channel(*id).await? {
+            Some(channel) => channels.push(channel),
+            None => tracing::warn!("Contract expiry alert: notification channel {} not found", id),
+        }
+    }
+    let notification = Notification {
+        subject: format!(
+            "[ForgeConfig] Support contracts expiring: {} device(s) within {} days",
+            report.rows.len(),
+            report.within_days
+        ),
+        text: render_text(&report),
+        payload: serde_json::to_value(&report)?,
+    };
+    let errors = notifications::send_all(config, &channels, &notification).await;
+    if !errors.is_empty() {
+        bail!("delivery failed: {}", errors.join("; "));
+    }
+    for (id, expires_on) in &pending {
+        store.mark_support_contract_alerted(*id, *expires_on).await?;
+    }
+    Ok(pending.len())
+}
+
+/// Check for expiring contracts every hour
+pub fn start_scheduler(store: Store, config: Config, maintenance_mode: Arc<MaintenanceMode>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if maintenance_mode.is_enabled() {
+                continue;
+            }
+            match send_alerts(&store, &config).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Sent expiry alerts for {} support contract(s)", n),
+                Err(e) => tracing::warn!("Contract expiry alert failed: {:#}", e),
+            }
+        }
+    });
+}
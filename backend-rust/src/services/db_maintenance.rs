@@ -0,0 +1,152 @@
+//! Database backups and VACUUM/ANALYZE maintenance. Backups are written with `VACUUM INTO`,
+//! which snapshots the live database without blocking readers, to `{backup_dir}/db`.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::db::Store;
+use crate::models::*;
+
+use super::maintenance_mode::MaintenanceMode;
+
+const BACKUP_SUBDIR: &str = "db";
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+const SCHEDULER_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Backups and vacuums never overlap
+static RUN_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+pub fn backup_dir(backup_dir: &str) -> PathBuf {
+    Path::new(backup_dir).join(BACKUP_SUBDIR)
+}
+
+async fn upload(url: &str, filename: &str, path: &Path) -> Result<String> {
+    let target = format!("{}/{}", url.trim_end_matches('/'), filename);
+    let body = tokio::fs::read(path).await.context("failed to read backup file")?;
+    let response = reqwest::Client::new()
+        .put(&target)
+        .timeout(UPLOAD_TIMEOUT)
+        .body(body)
+        .send()
+        .await
+        .context("upload request failed")?;
+    if !response.status().is_success() {
+        anyhow::bail!("upload rejected with HTTP {}", response.status());
+    }
+    Ok(target)
+}
+
+/// Take a backup of the live database, optionally uploading it, and record it in the history.
+/// A failed upload is recorded on the run but still leaves the local backup in place.
+pub async fn backup_database(
+    store: &Store,
+    backup_root: &str,
+    upload_requested: bool,
+    triggered_by: &str,
+) -> Result<DbMaintenanceRun> {
+    let _guard = RUN_LOCK.lock().await;
+    let start = Instant::now();
+    let settings = store.get_settings().await?;
+    let upload_url = settings.db_backup_upload_url.filter(|u| !u.trim().is_empty());
+    if upload_requested && upload_url.is_none() {
+        anyhow::bail!("db_backup_upload_url is not configured");
+    }
+
+    let dir = backup_dir(backup_root);
+    let filename = format!("forge-config-{}.db", chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+    let path = dir.join(&filename);
+    let result = async {
+        tokio::fs::create_dir_all(&dir).await.context("failed to create DB backup directory")?;
+        store.backup_database_to(&path.to_string_lossy()).await?;
+        Ok::<_, anyhow::Error>(tokio::fs::metadata(&path).await?.len() as i64)
+    }
+    .await;
+
+    let mut run = NewDbMaintenanceRun {
+        kind: db_maintenance_kind::BACKUP,
+        triggered_by: triggered_by.to_string(),
+        ..Default::default()
+    };
+    match result {
+        Ok(size) => {
+            run.filename = Some(filename.clone());
+            run.size_bytes = size;
+            if let Some(url) = upload_url.as_deref().filter(|_| upload_requested) {
+                match upload(url, &filename, &path).await {
+                    Ok(target) => run.uploaded_to = Some(target),
+                    Err(e) => run.error = Some(format!("upload failed: {:#}", e)),
+                }
+            }
+            tracing::info!("Database backed up to {} ({} bytes)", path.display(), size);
+        }
+        Err(e) => {
+            tracing::error!("Database backup failed: {:#}", e);
+            run.error = Some(format!("{:#}", e));
+        }
+    }
+    run.duration_ms = start.elapsed().as_millis() as i64;
+    store.record_db_maintenance_run(&run).await
+}
+
+/// Run VACUUM and ANALYZE and record the run in the history
+pub async fn vacuum_database(store: &Store, triggered_by: &str) -> Result<DbMaintenanceRun> {
+    let _guard = RUN_LOCK.lock().await;
+    let start = Instant::now();
+    let mut run = NewDbMaintenanceRun {
+        kind: db_maintenance_kind::VACUUM,
+        triggered_by: triggered_by.to_string(),
+        ..Default::default()
+    };
+    match store.vacuum_analyze().await {
+        Ok(size) => {
+            run.size_bytes = size;
+            tracing::info!("Database vacuumed ({} bytes)", size);
+        }
+        Err(e) => {
+            tracing::error!("Database vacuum failed: {:#}", e);
+            run.error = Some(format!("{:#}", e));
+        }
+    }
+    run.duration_ms = start.elapsed().as_millis() as i64;
+    store.record_db_maintenance_run(&run).await
+}
+
+/// Start the background task that vacuums the database every db_maintenance_interval_hours.
+/// Paused while maintenance mode is on.
+pub fn start_scheduler(store: Store, maintenance_mode: Arc<MaintenanceMode>) {
+    tokio::spawn(async move {
+        // First check an interval after startup, so a restart doesn't vacuum straight away
+        let first = tokio::time::Instant::now() + SCHEDULER_CHECK_INTERVAL;
+        let mut interval = tokio::time::interval_at(first, SCHEDULER_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if maintenance_mode.is_enabled() {
+                continue;
+            }
+            let hours = match store.get_settings().await {
+                Ok(settings) => settings.db_maintenance_interval_hours,
+                Err(e) => {
+                    tracing::warn!("DB maintenance: failed to load settings: {}", e);
+                    continue;
+                }
+            };
+            if hours <= 0 {
+                continue;
+            }
+            let due = match store.last_successful_db_maintenance_run(db_maintenance_kind::VACUUM).await {
+                Ok(last) => last.is_none_or(|run| chrono::Utc::now() - run.created_at >= chrono::Duration::hours(hours as i64)),
+                Err(e) => {
+                    tracing::warn!("DB maintenance: failed to load history: {}", e);
+                    continue;
+                }
+            };
+            if due {
+                if let Err(e) = vacuum_database(&store, "scheduler").await {
+                    tracing::warn!("DB maintenance: failed to record vacuum: {}", e);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,236 @@
+//! Fleet health reports: interface drift, syslog alert rules with open alerts, failed jobs,
+//! stale backups and offline devices, sent to notification channels on report schedules.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::db::Store;
+use crate::models::*;
+use crate::services::interface_drift;
+use crate::services::maintenance_mode::MaintenanceMode;
+use crate::services::notifications;
+
+const SCHEDULER_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+/// Failed jobs are counted over this window when there is no previous run
+pub const DEFAULT_REPORT_WINDOW_HOURS: i64 = 24;
+/// Entries listed per section in the text report; the counts always cover everything
+const TEXT_SECTION_LIMIT: usize = 25;
+
+/// Build a fleet report. Failed jobs are those created since `since`; drifted devices not in
+/// `previous_drift` are marked new.
+pub async fn generate(
+    store: &Store,
+    stale_backup_days: i64,
+    since: DateTime<Utc>,
+    previous_drift: &[i64],
+) -> Result<FleetReport> {
+    let now = Utc::now();
+    let devices = store.list_devices().await?;
+    let hostnames: HashMap<i64, &str> = devices.iter().map(|d| (d.id, d.hostname.as_str())).collect();
+    let previous_drift: HashSet<i64> = previous_drift.iter().copied().collect();
+
+    let models = store.list_device_models().await?;
+    let mut drift = Vec::new();
+    for device in &devices {
+        let report = interface_drift::device_report(store, device, &models).await?;
+        if report.inventory_collected && !report.mismatches.is_empty() {
+            drift.push(FleetDriftEntry {
+                device_id: device.id,
+                hostname: device.hostname.clone(),
+                mismatches: report.mismatches.len(),
+                new: !previous_drift.contains(&device.id),
+            });
+        }
+    }
+
+    let open_alerts = store
+        .list_syslog_alerts(&SyslogAlertQuery { device_id: None, acknowledged: Some(false), limit: -1 })
+        .await?;
+    let mut rules: BTreeMap<i64, FleetFailingRule> = BTreeMap::new();
+    for alert in &open_alerts {
+        let rule = rules.entry(alert.rule_id).or_insert_with(|| FleetFailingRule {
+            rule_id: alert.rule_id,
+            rule_name: alert.rule_name.clone(),
+            unacknowledged: 0,
+            last_alert_at: alert.created_at,
+        });
+        rule.unacknowledged += 1;
+        rule.last_alert_at = rule.last_alert_at.max(alert.created_at);
+    }
+
+    let failed_jobs = store
+        .list_jobs_failed_since(since)
+        .await?
+        .into_iter()
+        .map(|job| FleetFailedJob {
+            hostname: hostnames.get(&job.device_id).copied().unwrap_or_default().to_string(),
+            job_id: job.id,
+            device_id: job.device_id,
+            job_type: job.job_type,
+            error: job.error,
+            created_at: job.created_at,
+        })
+        .collect();
+
+    let stale_before = now - Duration::days(stale_backup_days);
+    let stale_backups = devices
+        .iter()
+        .filter(|d| d.last_backup.is_none_or(|at| at < stale_before))
+        .map(|d| FleetStaleBackup { device_id: d.id, hostname: d.hostname.clone(), last_backup: d.last_backup })
+        .collect();
+
+    let offline_devices = devices
+        .iter()
+        .filter(|d| d.status == device_status::OFFLINE)
+        .map(|d| FleetOfflineDevice { device_id: d.id, hostname: d.hostname.clone(), last_seen: d.last_seen })
+        .collect();
+
+    Ok(FleetReport {
+        generated_at: now,
+        since,
+        device_count: devices.len(),
+        stale_backup_days,
+        new_drift: drift.iter().filter(|d| d.new).count(),
+        drift,
+        failing_rules: rules.into_values().collect(),
+        failed_jobs,
+        stale_backups,
+        offline_devices,
+    })
+}
+
+fn section<T>(out: &mut String, title: &str, items: &[T], line: impl Fn(&T) -> String) {
+    if items.is_empty() {
+        return;
+    }
+    out.push_str(&format!("\n{} ({})\n", title, items.len()));
+    for item in items.iter().take(TEXT_SECTION_LIMIT) {
+        out.push_str(&format!("  - {}\n", line(item)));
+    }
+    if items.len() > TEXT_SECTION_LIMIT {
+        out.push_str(&format!("  ... and {} more\n", items.len() - TEXT_SECTION_LIMIT));
+    }
+}
+
+/// Plain-text rendering of a report, as sent to email channels
+pub fn render_text(report: &FleetReport) -> String {
+    let time = |t: DateTime<Utc>| t.format("%Y-%m-%d %H:%M UTC").to_string();
+    let mut out = format!(
+        "Fleet report generated {}\n{} devices; failed jobs since {}\n\n\
+         Interface drift: {} ({} new)\nFailing syslog rules: {}\nFailed jobs: {}\n\
+         Stale backups (older than {} days): {}\nOffline devices: {}\n",
+        time(report.generated_at),
+        report.device_count,
+        time(report.since),
+        report.drift.len(),
+        report.new_drift,
+        report.failing_rules.len(),
+        report.failed_jobs.len(),
+        report.stale_backup_days,
+        report.stale_backups.len(),
+        report.offline_devices.len(),
+    );
+    section(&mut out, "Interface drift", &report.drift, |d| {
+        format!("{}: {} mismatch(es){}", d.hostname, d.mismatches, if d.new { " [new]" } else { "" })
+    });
+    section(&mut out, "Failing syslog rules", &report.failing_rules, |r| {
+        format!("{}: {} unacknowledged, last {}", r.rule_name, r.unacknowledged, time(r.last_alert_at))
+    });
+    section(&mut out, "Failed jobs", &report.failed_jobs, |j| {
+        format!("{} {} on {}: {}", time(j.created_at), j.job_type, j.hostname, j.error.as_deref().unwrap_or("-"))
+    });
+    section(&mut out, "Stale backups", &report.stale_backups, |b| {
+        format!("{}: {}", b.hostname, b.last_backup.map(time).unwrap_or_else(|| "never".to_string()))
+    });
+    section(&mut out, "Offline devices", &report.offline_devices, |d| {
+        format!("{}: last seen {}", d.hostname, d.last_seen.map(time).unwrap_or_else(|| "never".to_string()))
+    });
+    out
+}
+
+/// Generate a schedule's report, send it to the schedule's channels and record the run
+pub async fn run_schedule(store: &Store, config: &Config, schedule: &ReportSchedule) -> Result<FleetReport> {
+    let since = schedule
+        .last_run_at
+        .unwrap_or_else(|| Utc::now() - Duration::hours(DEFAULT_REPORT_WINDOW_HOURS));
+    let result = async {
+        let report = generate(store, schedule.stale_backup_days, since, &schedule.last_drift_device_ids).await?;
+        let mut channels = Vec::new();
+        for id in &schedule.channel_ids {
+            match store.get_notification_channel(*id).await? {
+                Some(channel) => channels.push(channel),
+                None => tracing::warn!("Report '{}': notification channel {} not found", schedule.name, id),
+            }
+        }
+        let notification = Notification {
+            subject: format!(
+                "[ForgeConfig] {}: {} drift ({} new), {} failed jobs, {} stale backups",
+                schedule.name,
+                report.drift.len(),
+                report.new_drift,
+                report.failed_jobs.len(),
+                report.stale_backups.len()
+            ),
+            text: render_text(&report),
+            payload: serde_json::to_value(&report)?,
+        };
+        let errors = notifications::send_all(config, &channels, &notification).await;
+        if !errors.is_empty() {
+            bail!("delivery failed: {}", errors.join("; "));
+        }
+        Ok(report)
+    }
+    .await;
+
+    // A failed run keeps the previous drift baseline, so the drift it found is still new next time
+    let (error, drift_ids) = match &result {
+        Ok(report) => (None, report.drift.iter().map(|d| d.device_id).collect()),
+        Err(e) => (Some(format!("{:#}", e)), schedule.last_drift_device_ids.clone()),
+    };
+    store.record_report_schedule_run(schedule.id, error.as_deref(), &drift_ids).await?;
+    result
+}
+
+/// Run enabled report schedules when their cron schedule is due
+pub fn start_scheduler(store: Store, config: Config, maintenance_mode: Arc<MaintenanceMode>) {
+    tokio::spawn(async move {
+        use croner::Cron;
+
+        let mut interval = tokio::time::interval(SCHEDULER_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if maintenance_mode.is_enabled() {
+                continue;
+            }
+            let schedules = match store.list_enabled_report_schedules().await {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::warn!("Report scheduler: failed to list schedules: {}", e);
+                    continue;
+                }
+            };
+            let now = Utc::now();
+            for schedule in &schedules {
+                let cron = match Cron::new(&schedule.schedule).parse() {
+                    Ok(c) => c,
+                    Err(e) => {
+                        tracing::warn!("Report scheduler: invalid cron '{}' for '{}': {}", schedule.schedule, schedule.name, e);
+                        continue;
+                    }
+                };
+                let reference = schedule.last_run_at.unwrap_or(schedule.created_at);
+                match cron.find_next_occurrence(&reference, false) {
+                    Ok(next) if next <= now => {}
+                    _ => continue,
+                }
+                tracing::info!("Report scheduler: running '{}' ({})", schedule.name, schedule.id);
+                if let Err(e) = run_schedule(&store, &config, schedule).await {
+                    tracing::warn!("Report '{}' failed: {:#}", schedule.name, e);
+                }
+            }
+        }
+    });
+}
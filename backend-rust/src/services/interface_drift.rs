@@ -0,0 +1,55 @@
+//! Interface intent vs. operational state: a device's port assignments (descriptions, VRFs, model
+//! port speeds) compared with its collected interface inventory.
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::db::Store;
+use crate::models::*;
+
+/// Port speeds from the layout of the device's model, matched on model name and vendor
+async fn model_port_speeds(
+    store: &Store,
+    device: &Device,
+    models: &[DeviceModel],
+) -> Result<HashMap<String, i64>> {
+    let Some(model) = device.model.as_deref().filter(|m| !m.is_empty()) else {
+        return Ok(HashMap::new());
+    };
+    let vendor_id = match device.vendor_id.as_deref().or(device.vendor.as_deref()) {
+        Some(v) if !v.is_empty() => store.resolve_vendor(v).await?.map(|vendor| vendor.id),
+        _ => None,
+    };
+    Ok(models
+        .iter()
+        .find(|m| m.model == model && vendor_id.is_none_or(|id| m.vendor_id == id))
+        .map(|m| crate::utils::layout_port_speeds(&m.layout))
+        .unwrap_or_default())
+}
+
+/// Compare a device's port assignments with its collected interface state
+pub async fn device_report(
+    store: &Store,
+    device: &Device,
+    models: &[DeviceModel],
+) -> Result<InterfaceIntentReport> {
+    let interfaces = store.list_device_interfaces(device.id).await?;
+    let mut report = InterfaceIntentReport {
+        device_id: device.id,
+        hostname: device.hostname.clone(),
+        inventory_collected: !interfaces.is_empty(),
+        collected_at: interfaces.iter().map(|i| i.collected_at).max(),
+        vrf_collected: interfaces.iter().any(|i| i.vrf.is_some()),
+        ports_checked: 0,
+        mismatches: Vec::new(),
+    };
+    if interfaces.is_empty() {
+        return Ok(report);
+    }
+
+    let assignments = store.list_port_assignments(device.id).await?;
+    let port_speeds = model_port_speeds(store, device, models).await?;
+    report.ports_checked = assignments.len();
+    report.mismatches = crate::utils::compare_interface_intent(&assignments, &interfaces, &port_speeds);
+    Ok(report)
+}
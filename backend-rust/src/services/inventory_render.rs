@@ -0,0 +1,142 @@
+//! Headless rendering: the same template rendering and variable layering as a live device, driven
+//! by an inventory document instead of the database. Used by `forge-config render-batch` and
+//! POST /api/render/inventory so CI can validate configs kept in Git.
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::models::*;
+
+/// Read an inventory file, loading template `path`s relative to it
+pub async fn load(path: &Path) -> Result<RenderInventory> {
+    let data = tokio::fs::read_to_string(path).await.with_context(|| format!("failed to read {}", path.display()))?;
+    let mut inventory: RenderInventory =
+        serde_json::from_str(&data).with_context(|| format!("invalid inventory {}", path.display()))?;
+    let base = path.parent().unwrap_or_else(|| Path::new("."));
+    for template in &mut inventory.templates {
+        if let (None, Some(file)) = (&template.content, &template.path) {
+            let file = base.join(file);
+            template.content = Some(
+                tokio::fs::read_to_string(&file)
+                    .await
+                    .with_context(|| format!("failed to read template '{}' from {}", template.name, file.display()))?,
+            );
+        }
+    }
+    Ok(inventory)
+}
+
+fn template(id: i64, source: &InventoryTemplate) -> Result<Template> {
+    let Some(content) = &source.content else {
+        bail!("template '{}' has no content", source.name);
+    };
+    let now = Utc::now();
+    Ok(Template {
+        id,
+        name: source.name.clone(),
+        description: None,
+        vendor_id: None,
+        content: content.clone(),
+        device_count: None,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+fn device(id: i64, source: &InventoryDevice) -> Device {
+    let now = Utc::now();
+    Device {
+        id,
+        mac: source.mac.as_deref().map(crate::utils::normalize_mac),
+        ip: source.ip.clone(),
+        hostname: source.hostname.clone(),
+        vendor: source.vendor.clone(),
+        vendor_id: None,
+        model: source.model.clone(),
+        serial_number: source.serial_number.clone(),
+        config_template: String::new(),
+        credential_id: None,
+        ssh_user: None,
+        ssh_pass: None,
+        topology_id: source.topology_id,
+        topology_role: source.topology_role.clone(),
+        hall_id: None,
+        row_id: None,
+        rack_id: None,
+        rack_position: None,
+        tenant_id: None,
+        gpu_cluster_id: None,
+        status: device_status::OFFLINE.to_string(),
+        device_type: "internal".to_string(),
+        last_seen: None,
+        last_backup: None,
+        last_error: None,
+        custom_fields: source.custom_fields.clone(),
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// Inventory vars, then the device's groups by precedence (ties in listed order), then host vars
+fn resolve_vars(inventory: &RenderInventory, source: &InventoryDevice) -> Result<HashMap<String, String>> {
+    let mut groups = Vec::with_capacity(source.groups.len());
+    for name in &source.groups {
+        let group = inventory
+            .groups
+            .iter()
+            .find(|g| &g.name == name)
+            .with_context(|| format!("unknown group '{}'", name))?;
+        groups.push(group);
+    }
+    groups.sort_by_key(|g| g.precedence);
+
+    let mut vars = inventory.vars.clone();
+    for group in groups {
+        vars.extend(group.vars.clone());
+    }
+    vars.extend(source.vars.clone());
+    Ok(vars)
+}
+
+fn render_one(
+    inventory: &RenderInventory,
+    templates: &HashMap<&str, Template>,
+    settings: &Settings,
+    id: i64,
+    source: &InventoryDevice,
+) -> Result<String> {
+    let lookup = |name: &str| templates.get(name).cloned().with_context(|| format!("unknown template '{}'", name));
+    let template = lookup(&source.template)?;
+    let role_templates = source.role_templates.iter().map(|name| lookup(name)).collect::<Result<Vec<_>>>()?;
+    let vars = resolve_vars(inventory, source)?;
+    crate::jobs::render_config(&device(id, source), &template, settings, &role_templates, &vars, None)
+}
+
+/// Render every device in the inventory; failures are reported per hostname
+pub fn render(inventory: &RenderInventory) -> Result<BatchRenderResponse> {
+    let mut templates = HashMap::new();
+    for (i, source) in inventory.templates.iter().enumerate() {
+        if templates.insert(source.name.as_str(), template(i as i64 + 1, source)?).is_some() {
+            bail!("duplicate template '{}'", source.name);
+        }
+    }
+    let settings = Settings {
+        dhcp_subnet: inventory.dhcp_subnet.clone(),
+        dhcp_gateway: inventory.dhcp_gateway.clone(),
+        ..Default::default()
+    };
+
+    let mut response = BatchRenderResponse { configs: BTreeMap::new(), errors: BTreeMap::new() };
+    for (i, source) in inventory.devices.iter().enumerate() {
+        if response.configs.contains_key(&source.hostname) || response.errors.contains_key(&source.hostname) {
+            bail!("duplicate device '{}'", source.hostname);
+        }
+        match render_one(inventory, &templates, &settings, i as i64 + 1, source) {
+            Ok(config) => { response.configs.insert(source.hostname.clone(), config); }
+            Err(e) => { response.errors.insert(source.hostname.clone(), format!("{:#}", e)); }
+        }
+    }
+    Ok(response)
+}
@@ -6,19 +6,37 @@ use crate::models::{CreateDiscoveryLogRequest, Lease, discovery_event};
 use crate::utils;
 use crate::ws::Hub;
 
+use super::maintenance_mode::MaintenanceMode;
+
 /// Handle a new or renewed DHCP lease event.
 /// Performs vendor detection, persists the discovered device, sends WebSocket
-/// notifications, and creates a discovery log entry.
+/// notifications, and creates a discovery log entry. Ignored while maintenance mode is on.
 pub fn on_lease_event(
     store: Store,
+    maintenance_mode: Arc<MaintenanceMode>,
     backup_svc: Arc<BackupService>,
     ws_hub: Arc<Hub>,
     lease: Lease,
 ) {
+    if maintenance_mode.is_enabled() {
+        tracing::debug!("Maintenance mode: ignoring lease event for {}", lease.mac);
+        return;
+    }
+
     tokio::spawn(async move {
         // Backup callback
         backup_svc.on_new_lease(lease.clone()).await;
 
+        // Mark the lease on a pending reprovision of this device
+        if let Err(e) = store.record_provisioning_lease(&lease.mac).await {
+            tracing::warn!("Failed to record provisioning lease for {}: {}", lease.mac, e);
+        }
+
+        let discovery_disabled = store.get_settings().await.is_ok_and(|s| s.features.disable_discovery);
+        if discovery_disabled {
+            return;
+        }
+
         // Auto-detect vendor from MAC prefix and DHCP vendor class
         let detected_vendor = match store.list_vendors().await {
             Ok(vendors) => utils::detect_vendor(&lease.mac, &vendors),
@@ -42,19 +60,32 @@ pub fn on_lease_event(
             .broadcast_device_discovered(&lease.mac, &lease.ip, Some(&lease.hostname), vendor_id)
             .await;
 
-        // Discovery log callback
-        let event_type = if store.get_device_by_mac(&lease.mac).await.ok().flatten().is_some() {
+        // Discovery log callback. An unknown MAC whose serial is a configured device's is that
+        // device with a new MAC, e.g. after a supervisor swap.
+        let known = store.get_device_by_mac(&lease.mac).await.ok().flatten().is_some();
+        let serial_match = match enriched_lease.serial_number.as_deref() {
+            Some(serial) if !known => store.get_device_by_serial(serial).await.ok().flatten(),
+            _ => None,
+        };
+        let event_type = if known {
             discovery_event::LEASE_RENEWED
+        } else if serial_match.is_some() {
+            discovery_event::SERIAL_MATCHED
         } else {
             discovery_event::DISCOVERED
         };
 
-        let message = match (event_type, detection_method) {
-            (discovery_event::DISCOVERED, Some(method)) => {
+        let message = match (&serial_match, event_type, detection_method) {
+            (Some(device), _, _) => format!(
+                "Serial number matches device {} (MAC {}); move the device to this MAC to adopt it",
+                device.hostname,
+                device.mac.as_deref().unwrap_or("none")
+            ),
+            (None, discovery_event::DISCOVERED, Some(method)) => {
                 format!("New device detected via DHCP (vendor detected via {})", method)
             }
-            (discovery_event::DISCOVERED, None) => "New device detected via DHCP".to_string(),
-            (_, Some(method)) => {
+            (None, discovery_event::DISCOVERED, None) => "New device detected via DHCP".to_string(),
+            (None, _, Some(method)) => {
                 format!("DHCP lease renewed (vendor detected via {})", method)
             }
             _ => "DHCP lease renewed for configured device".to_string(),
@@ -0,0 +1,153 @@
+//! HTTP listeners. LISTEN_ADDR is either `host:port` or `unix:/path/to.sock`; when the process is
+//! started through systemd socket activation (LISTEN_FDS) the inherited sockets are served instead,
+//! so restarts don't refuse connections.
+
+use anyhow::{bail, Context, Result};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::server::graceful::GracefulShutdown;
+use hyper_util::service::TowerToHyperService;
+use std::os::fd::{FromRawFd, RawFd};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// First file descriptor passed by systemd
+const SD_LISTEN_FDS_START: RawFd = 3;
+const UNIX_PREFIX: &str = "unix:";
+const UNIX_SOCKET_MODE: u32 = 0o660;
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+pub enum Listener {
+    Tcp(tokio::net::TcpListener),
+    /// The path is removed on shutdown when we created the socket ourselves
+    Unix(tokio::net::UnixListener, Option<PathBuf>),
+}
+
+impl Listener {
+    pub fn describe(&self) -> String {
+        match self {
+            Listener::Tcp(l) => l.local_addr().map(|a| a.to_string()).unwrap_or_else(|_| "tcp".to_string()),
+            Listener::Unix(l, _) => match l.local_addr().ok().and_then(|a| a.as_pathname().map(|p| p.display().to_string())) {
+                Some(path) => format!("{}{}", UNIX_PREFIX, path),
+                None => "unix socket".to_string(),
+            },
+        }
+    }
+}
+
+/// Sockets passed by systemd, or None when the process wasn't socket activated
+fn systemd_listeners() -> Result<Option<Vec<Listener>>> {
+    use nix::fcntl::{fcntl, FcntlArg, FdFlag};
+    use nix::sys::socket::{getsockname, AddressFamily, SockaddrLike, SockaddrStorage};
+
+    let Ok(count) = std::env::var("LISTEN_FDS") else {
+        return Ok(None);
+    };
+    // LISTEN_PID guards against inheriting another process's activation environment
+    if std::env::var("LISTEN_PID").ok().and_then(|p| p.parse::<u32>().ok()) != Some(std::process::id()) {
+        return Ok(None);
+    }
+    let count: RawFd = count.parse().context("invalid LISTEN_FDS")?;
+
+    let mut listeners = Vec::new();
+    for fd in SD_LISTEN_FDS_START..SD_LISTEN_FDS_START + count {
+        // Keep the sockets out of child processes such as dnsmasq
+        fcntl(fd, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC)).with_context(|| format!("invalid inherited fd {}", fd))?;
+        let family = getsockname::<SockaddrStorage>(fd)
+            .with_context(|| format!("inherited fd {} is not a socket", fd))?
+            .family();
+        // Safety: systemd hands these fds to this process, and each is wrapped exactly once
+        let listener = match family {
+            Some(AddressFamily::Inet) | Some(AddressFamily::Inet6) => {
+                let std_listener = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+                std_listener.set_nonblocking(true)?;
+                Listener::Tcp(tokio::net::TcpListener::from_std(std_listener)?)
+            }
+            Some(AddressFamily::Unix) => {
+                let std_listener = unsafe { std::os::unix::net::UnixListener::from_raw_fd(fd) };
+                std_listener.set_nonblocking(true)?;
+                Listener::Unix(tokio::net::UnixListener::from_std(std_listener)?, None)
+            }
+            other => bail!("inherited fd {} has unsupported address family {:?}", fd, other),
+        };
+        listeners.push(listener);
+    }
+    Ok(Some(listeners))
+}
+
+async fn bind(addr: &str) -> Result<Listener> {
+    let Some(path) = addr.strip_prefix(UNIX_PREFIX) else {
+        let listener = tokio::net::TcpListener::bind(addr).await.with_context(|| format!("failed to bind {}", addr))?;
+        return Ok(Listener::Tcp(listener));
+    };
+    let path = PathBuf::from(path);
+    // A socket file left behind by an unclean exit would make bind fail. Anything else at the path
+    // is left alone, so a mistyped LISTEN_ADDR can't delete a regular file.
+    if let Ok(metadata) = tokio::fs::symlink_metadata(&path).await {
+        if !metadata.file_type().is_socket() {
+            bail!("{} exists and is not a socket", path.display());
+        }
+        tokio::fs::remove_file(&path).await.with_context(|| format!("failed to remove stale socket {}", path.display()))?;
+    }
+    let listener = tokio::net::UnixListener::bind(&path).with_context(|| format!("failed to bind {}", path.display()))?;
+    tokio::fs::set_permissions(&path, std::fs::Permissions::from_mode(UNIX_SOCKET_MODE)).await?;
+    Ok(Listener::Unix(listener, Some(path)))
+}
+
+/// Listeners inherited from systemd, or LISTEN_ADDR bound directly
+pub async fn open(listen_addr: &str) -> Result<Vec<Listener>> {
+    match systemd_listeners()? {
+        Some(listeners) if !listeners.is_empty() => {
+            tracing::info!("Using {} socket(s) passed by systemd", listeners.len());
+            Ok(listeners)
+        }
+        _ => Ok(vec![bind(listen_addr).await?]),
+    }
+}
+
+/// Serve `app` on `listener` until `shutdown` is cancelled
+pub async fn serve(listener: Listener, app: axum::Router, shutdown: CancellationToken) -> Result<()> {
+    tracing::info!("ForgeConfig listening on {}", listener.describe());
+    match listener {
+        Listener::Tcp(listener) => {
+            axum::serve(listener, app).with_graceful_shutdown(shutdown.cancelled_owned()).await?;
+        }
+        Listener::Unix(listener, path) => {
+            let builder = Builder::new(TokioExecutor::new());
+            let graceful = GracefulShutdown::new();
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => {
+                        let stream = match accepted {
+                            Ok((stream, _)) => stream,
+                            Err(e) => {
+                                tracing::warn!("Unix socket accept failed: {}", e);
+                                continue;
+                            }
+                        };
+                        let connection = builder
+                            .serve_connection_with_upgrades(TokioIo::new(stream), TowerToHyperService::new(app.clone()))
+                            .into_owned();
+                        let connection = graceful.watch(connection);
+                        tokio::spawn(async move {
+                            if let Err(e) = connection.await {
+                                tracing::debug!("Unix socket connection error: {}", e);
+                            }
+                        });
+                    }
+                    _ = shutdown.cancelled() => break,
+                }
+            }
+            drop(listener);
+            if tokio::time::timeout(SHUTDOWN_GRACE, graceful.shutdown()).await.is_err() {
+                tracing::warn!("Timed out waiting for Unix socket connections to close");
+            }
+            if let Some(path) = path {
+                let _ = tokio::fs::remove_file(path).await;
+            }
+        }
+    }
+    Ok(())
+}
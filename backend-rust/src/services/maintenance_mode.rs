@@ -0,0 +1,40 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::models::MaintenanceModeState;
+
+/// Global maintenance mode. While enabled the API rejects mutating calls and background
+/// automation (job scheduler, lease handling, status checks) is paused; reads and WebSocket
+/// monitoring keep working. The switch lives in memory so checking it never touches the
+/// database, which may be mid-migration.
+#[derive(Default)]
+pub struct MaintenanceMode {
+    enabled: AtomicBool,
+    state: Mutex<MaintenanceModeState>,
+}
+
+impl MaintenanceMode {
+    pub fn new(state: MaintenanceModeState) -> Arc<Self> {
+        Arc::new(Self {
+            enabled: AtomicBool::new(state.enabled),
+            state: Mutex::new(state),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn state(&self) -> MaintenanceModeState {
+        self.lock().clone()
+    }
+
+    pub fn set(&self, state: MaintenanceModeState) {
+        self.enabled.store(state.enabled, Ordering::Relaxed);
+        *self.lock() = state;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MaintenanceModeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,237 @@
+//! Time-series export of device status, interface state, job statistics and queue depth, SSH pool
+//! usage, lookup cache hit rates and gNMI telemetry samples as InfluxDB line protocol, pushed to
+//! METRICS_EXPORT_URL on an interval and served at /api/metrics/influx.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::db::Store;
+use crate::jobs::{JobQueueStats, JobService};
+use crate::models::*;
+use crate::services::maintenance_mode::MaintenanceMode;
+use crate::utils::{influx_line, influx_string};
+
+const PUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn int(value: impl Into<i64>) -> String {
+    format!("{}i", value.into())
+}
+
+/// A telemetry sample's value as a line protocol field. JSON-IETF sends 64-bit counters as
+/// strings, so numeric strings are sent as numbers; containers and nulls are skipped.
+fn telemetry_field(value: &serde_json::Value) -> Option<String> {
+    use serde_json::Value;
+
+    match value {
+        Value::Number(n) => Some(match n.as_i64() {
+            Some(i) => int(i),
+            None => n.as_f64().unwrap_or_default().to_string(),
+        }),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::String(s) => Some(match (s.parse::<i64>(), s.parse::<f64>()) {
+            (Ok(i), _) => int(i),
+            // f64 also parses "inf" and "NaN"
+            (_, Ok(f)) if f.is_finite() && s.bytes().all(|c| c.is_ascii_digit() || b".-+eE".contains(&c)) => {
+                f.to_string()
+            }
+            _ => influx_string(s),
+        }),
+        _ => None,
+    }
+}
+
+/// Line protocol for the current device and interface state, and for jobs that finished and
+/// telemetry samples taken since `since`.
+/// The job queue's depth and refusals come from `job_queue` when the job service is running.
+pub async fn collect(
+    store: &Store,
+    job_queue: Option<JobQueueStats>,
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<String> {
+    let ts = now.timestamp_nanos_opt().unwrap_or_default();
+    let devices = store.list_devices().await?;
+    let interfaces = store.list_all_device_interfaces().await?;
+    let mut lines = Vec::new();
+
+    let mut by_device: HashMap<i64, Vec<&DeviceInterface>> = HashMap::new();
+    for interface in &interfaces {
+        by_device.entry(interface.device_id).or_default().push(interface);
+    }
+    let mut status_counts: BTreeMap<&str, i64> = BTreeMap::new();
+    for device in &devices {
+        *status_counts.entry(device.status.as_str()).or_default() += 1;
+        let device_interfaces = by_device.remove(&device.id).unwrap_or_default();
+        let interfaces_up = device_interfaces.iter().filter(|i| i.status == interface_status::UP).count() as i64;
+        let id = device.id.to_string();
+        let tags = [
+            ("device_id", id.as_str()),
+            ("hostname", device.hostname.as_str()),
+            ("vendor", device.vendor.as_deref().unwrap_or_default()),
+            ("model", device.model.as_deref().unwrap_or_default()),
+            ("role", device.topology_role.as_deref().unwrap_or_default()),
+        ];
+        lines.push(influx_line(
+            "forge_device",
+            &tags,
+            &[
+                ("up", int(device.status == device_status::ONLINE)),
+                ("status", influx_string(&device.status)),
+                ("interfaces_up", int(interfaces_up)),
+                ("interfaces_down", int(device_interfaces.len() as i64 - interfaces_up)),
+            ],
+            ts,
+        ));
+        for interface in device_interfaces {
+            let mut fields = vec![
+                ("up", int(interface.status == interface_status::UP)),
+                ("status", influx_string(&interface.status)),
+            ];
+            if let Some(speed) = interface.speed_mbps {
+                fields.push(("speed_mbps", int(speed)));
+            }
+            lines.push(influx_line(
+                "forge_interface",
+                &[
+                    ("device_id", id.as_str()),
+                    ("hostname", device.hostname.as_str()),
+                    ("interface", interface.name.as_str()),
+                    ("vrf", interface.vrf.as_deref().unwrap_or_default()),
+                ],
+                &fields,
+                ts,
+            ));
+        }
+    }
+
+    let mut fleet = vec![("devices", int(devices.len() as i64))];
+    for status in [device_status::ONLINE, device_status::OFFLINE, device_status::PROVISIONING] {
+        fleet.push((status, int(status_counts.get(status).copied().unwrap_or_default())));
+    }
+    lines.push(influx_line("forge_fleet", &[], &fleet, ts));
+
+    // (count, total duration, max duration) in ms per job type and outcome
+    let mut jobs: BTreeMap<(String, String), (i64, i64, i64)> = BTreeMap::new();
+    for job in store.list_jobs_finished_since(since).await? {
+        let duration = match (job.started_at, job.completed_at) {
+            (Some(start), Some(end)) => (end - start).num_milliseconds().max(0),
+            _ => 0,
+        };
+        let entry = jobs.entry((job.job_type, job.status)).or_default();
+        entry.0 += 1;
+        entry.1 += duration;
+        entry.2 = entry.2.max(duration);
+    }
+    for ((job_type, status), (count, total, max)) in &jobs {
+        lines.push(influx_line(
+            "forge_jobs",
+            &[("job_type", job_type.as_str()), ("status", status.as_str())],
+            &[
+                ("count", int(*count)),
+                ("duration_avg_ms", int(total / count)),
+                ("duration_max_ms", int(*max)),
+            ],
+            ts,
+        ));
+    }
+    let active = store.list_jobs_stuck().await?;
+    let queued = active.iter().filter(|j| j.status == job_status::QUEUED).count() as i64;
+    let mut queue_fields = vec![("queued", int(queued)), ("running", int(active.len() as i64 - queued))];
+    if let Some(queue) = job_queue {
+        queue_fields.push(("depth", int(queue.queued as i64)));
+        queue_fields.push(("capacity", int(queue.capacity as i64)));
+        queue_fields.push(("rejected", int(queue.rejected as i64)));
+    }
+    lines.push(influx_line("forge_job_queue", &[], &queue_fields, ts));
+
+    for cache in store.lookup_cache_stats() {
+        lines.push(influx_line(
+            "forge_lookup_cache",
+            &[("cache", cache.name.as_str())],
+            &[("hits", int(cache.hits as i64)), ("misses", int(cache.misses as i64))],
+            ts,
+        ));
+    }
+
+    let hostnames: HashMap<i64, &str> = devices.iter().map(|d| (d.id, d.hostname.as_str())).collect();
+    for sample in store.telemetry_samples_since(since).await? {
+        let Some(value) = telemetry_field(&sample.value) else { continue };
+        let id = sample.device_id.to_string();
+        lines.push(influx_line(
+            "forge_telemetry",
+            &[
+                ("device_id", id.as_str()),
+                ("hostname", hostnames.get(&sample.device_id).copied().unwrap_or_default()),
+                ("path", sample.path.as_str()),
+            ],
+            &[("value", value)],
+            sample.timestamp.timestamp_nanos_opt().unwrap_or(ts),
+        ));
+    }
+
+    let ssh = crate::utils::ssh_pool_stats();
+    lines.push(influx_line(
+        "forge_ssh",
+        &[],
+        &[
+            ("in_use", int(ssh.in_use as i64)),
+            ("idle_sessions", int(ssh.idle_sessions as i64)),
+            ("throttled", int(ssh.throttled as i64)),
+        ],
+        ts,
+    ));
+
+    let mut body = lines.join("\n");
+    body.push('\n');
+    Ok(body)
+}
+
+async fn push(http: &reqwest::Client, config: &Config, body: String) -> Result<()> {
+    let mut request = http
+        .post(&config.metrics_export_url)
+        .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(body);
+    if !config.metrics_export_token.is_empty() {
+        request = request.header(reqwest::header::AUTHORIZATION, format!("Token {}", config.metrics_export_token));
+    }
+    let response = request.send().await.context("HTTP request failed")?;
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        bail!("HTTP {}: {}", status, text.trim());
+    }
+    Ok(())
+}
+
+/// Push metrics every METRICS_EXPORT_INTERVAL_SECS when METRICS_EXPORT_URL is set
+pub fn start(store: Store, config: Config, maintenance_mode: Arc<MaintenanceMode>, job_service: Arc<JobService>) {
+    if config.metrics_export_url.is_empty() {
+        return;
+    }
+    tracing::info!("Exporting metrics to {} every {}s", config.metrics_export_url, config.metrics_export_interval_secs);
+    tokio::spawn(async move {
+        let http = reqwest::Client::builder().timeout(PUSH_TIMEOUT).build().unwrap_or_default();
+        let mut interval = tokio::time::interval(Duration::from_secs(config.metrics_export_interval_secs));
+        let mut since = Utc::now();
+        loop {
+            interval.tick().await;
+            if maintenance_mode.is_enabled() {
+                continue;
+            }
+            let now = Utc::now();
+            let result = match collect(&store, Some(job_service.queue_stats()), since, now).await {
+                Ok(body) => push(&http, &config, body).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                // Jobs and telemetry samples are sent once; after a failed push they are sent with the next one
+                Ok(()) => since = now,
+                Err(e) => tracing::warn!("Metrics export failed: {:#}", e),
+            }
+        }
+    });
+}
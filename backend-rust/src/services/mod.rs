@@ -1 +1,25 @@
+pub mod acme;
+pub mod alerting;
+pub mod api_usage;
+pub mod automation;
+pub mod config_history;
+pub mod config_pull;
+pub mod contract_expiry;
+pub mod db_maintenance;
+pub mod fleet_report;
+pub mod interface_drift;
+pub mod inventory_render;
 pub mod lease_handler;
+pub mod listeners;
+pub mod maintenance_mode;
+pub mod metrics_export;
+pub mod notifications;
+pub mod partitions;
+pub mod permissions;
+pub mod runtime_config;
+pub mod smtp;
+pub mod team_access;
+pub mod template_catalog;
+pub mod tls;
+pub mod underlay;
+pub mod ztp;
@@ -0,0 +1,73 @@
+//! Delivery of notifications to email and webhook channels.
+
+use anyhow::{bail, Context, Result};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::models::*;
+use crate::services::smtp;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Validate a channel's settings
+pub fn validate_channel(channel_type: &str, config: &serde_json::Value) -> Result<()> {
+    match channel_type {
+        notification_channel_type::EMAIL => {
+            let email: EmailChannelConfig = serde_json::from_value(config.clone())?;
+            if email.recipients.is_empty() {
+                bail!("at least one recipient is required");
+            }
+            if let Some(bad) = email.recipients.iter().find(|r| !smtp::is_valid_address(r)) {
+                bail!("invalid recipient '{}'", bad);
+            }
+        }
+        notification_channel_type::WEBHOOK => {
+            let webhook: WebhookChannelConfig = serde_json::from_value(config.clone())?;
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                bail!("url must be an http(s) URL");
+            }
+        }
+        other => bail!(
+            "unknown channel_type '{}': expected one of {}",
+            other,
+            notification_channel_type::ALL.join(", ")
+        ),
+    }
+    Ok(())
+}
+
+/// Send a notification to one channel
+pub async fn send(config: &Config, channel: &NotificationChannel, notification: &Notification) -> Result<()> {
+    match channel.channel_type.as_str() {
+        notification_channel_type::EMAIL => {
+            let email: EmailChannelConfig = serde_json::from_value(channel.config.clone())?;
+            smtp::send_mail(config, &email.recipients, &notification.subject, &notification.text).await
+        }
+        notification_channel_type::WEBHOOK => {
+            let webhook: WebhookChannelConfig = serde_json::from_value(channel.config.clone())?;
+            let client = reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build()?;
+            let mut request = client.post(&webhook.url).json(notification);
+            for (key, value) in &webhook.headers {
+                request = request.header(key.as_str(), value.as_str());
+            }
+            let response = request.send().await.context("HTTP request failed")?;
+            if !response.status().is_success() {
+                bail!("HTTP {} from {}", response.status(), webhook.url);
+            }
+            Ok(())
+        }
+        other => bail!("unknown channel_type '{}'", other),
+    }
+}
+
+/// Send a notification to each channel, returning the failures as `name: error`
+pub async fn send_all(config: &Config, channels: &[NotificationChannel], notification: &Notification) -> Vec<String> {
+    let mut errors = Vec::new();
+    for channel in channels.iter().filter(|c| c.enabled) {
+        if let Err(e) = send(config, channel, notification).await {
+            tracing::warn!("Notification to channel '{}' failed: {:#}", channel.name, e);
+            errors.push(format!("{}: {:#}", channel.name, e));
+        }
+    }
+    errors
+}
@@ -0,0 +1,60 @@
+//! Monthly partitioning of the history tables: jobs, syslog events, DHCP lease history
+//! (discovery logs) and device status history. Once a month is over, its rows move from the live
+//! table into a partition table for that month, so the live tables only hold the current month.
+//! Readers go through views over the live table and its partitions. Expiring history drops a
+//! whole partition instead of deleting rows from a busy table.
+
+use anyhow::Result;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::db::Store;
+use crate::models::*;
+
+use super::maintenance_mode::MaintenanceMode;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Rollovers never overlap
+static RUN_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Move past months' rows into their partitions, then drop partitions older than
+/// partition_retention_months
+pub async fn rollover(store: &Store) -> Result<PartitionRollover> {
+    let _guard = RUN_LOCK.lock().await;
+    let current_month = Utc::now().format("%Y-%m").to_string();
+    let (moved_rows, created) = store.rollover_partitions(&current_month).await?;
+    let retention = store.get_settings().await?.partition_retention_months;
+    let dropped = match crate::utils::shift_month(&current_month, -retention).filter(|_| retention > 0) {
+        Some(cutoff) => store.drop_partitions_before(&cutoff).await?,
+        None => Vec::new(),
+    };
+    Ok(PartitionRollover { moved_rows, created, dropped })
+}
+
+/// Check hourly for rows from past months and expired partitions. The first check runs at
+/// startup, which moves everything older than the current month after an upgrade. Paused while
+/// maintenance mode is on.
+pub fn start_scheduler(store: Store, maintenance_mode: Arc<MaintenanceMode>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if maintenance_mode.is_enabled() {
+                continue;
+            }
+            match rollover(&store).await {
+                Ok(result) => {
+                    if result.moved_rows > 0 || !result.dropped.is_empty() {
+                        tracing::info!(
+                            "Partition rollover: moved {} rows, created {:?}, dropped {:?}",
+                            result.moved_rows, result.created, result.dropped
+                        );
+                    }
+                }
+                Err(e) => tracing::warn!("Partition rollover failed: {:#}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,66 @@
+//! Permissions matrix enforcement. A user is restricted once they hold a role or belong to a team
+//! with grants; restricted users may only make requests covered by the union of those grants.
+//! Everyone else is unrestricted, so existing installs keep working until the matrix is configured.
+
+use anyhow::Result;
+use std::collections::BTreeSet;
+
+use crate::db::Store;
+use crate::models::*;
+
+pub struct UserPermissions {
+    /// None when the user is unrestricted
+    grants: Option<BTreeSet<(String, String)>>,
+}
+
+impl UserPermissions {
+    pub async fn load(store: &Store, user_id: i64, username: &str) -> Result<Self> {
+        let role_ids = store.role_ids_for_user(user_id).await?;
+        let mut grants = BTreeSet::new();
+        for role_id in &role_ids {
+            for p in store.list_permissions(permission_subject::ROLE, *role_id).await? {
+                grants.insert((p.resource, p.action));
+            }
+        }
+        let mut team_grants = false;
+        for team_id in store.team_ids_for_user(username).await? {
+            for p in store.list_permissions(permission_subject::TEAM, team_id).await? {
+                team_grants = true;
+                grants.insert((p.resource, p.action));
+            }
+        }
+        let restricted = !role_ids.is_empty() || team_grants;
+        Ok(Self { grants: restricted.then_some(grants) })
+    }
+
+    /// Whether `action` on `resource` is granted. Write grants also allow reads.
+    pub fn allows(&self, resource: &str, action: &str) -> bool {
+        let Some(grants) = &self.grants else {
+            return true;
+        };
+        grants.iter().any(|(r, a)| {
+            (r == resource || r == permission_resource::ANY)
+                && (a == action
+                    || a == permission_action::ANY
+                    || (action == permission_action::READ && a == permission_action::WRITE))
+        })
+    }
+
+    /// Whether `action` on `resource` is granted by the matrix itself; unrestricted users hold no
+    /// grants, so this is false for them
+    pub fn grants(&self, resource: &str, action: &str) -> bool {
+        self.grants.is_some() && self.allows(resource, action)
+    }
+
+    pub fn effective(&self) -> EffectivePermissions {
+        EffectivePermissions {
+            unrestricted: self.grants.is_none(),
+            permissions: self
+                .grants
+                .iter()
+                .flatten()
+                .map(|(resource, action)| Permission { resource: resource.clone(), action: action.clone() })
+                .collect(),
+        }
+    }
+}
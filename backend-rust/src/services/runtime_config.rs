@@ -0,0 +1,121 @@
+//! Configuration that can change while the server runs. SIGHUP or POST /api/system/reload-config
+//! re-reads the environment and CONFIG_FILE; the log filter and background task intervals take
+//! effect immediately, anything else is reported as needing a restart.
+
+use anyhow::{Context, Result};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+use crate::config::{Config, SECRET_KEYS};
+use crate::models::{ConfigChange, ConfigReloadResult};
+
+pub type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+const MASKED: &str = "********";
+
+pub struct RuntimeConfig {
+    config: Mutex<Config>,
+    log_filter: LogFilterHandle,
+    reload_lock: tokio::sync::Mutex<()>,
+}
+
+impl RuntimeConfig {
+    pub fn new(config: Config, log_filter: LogFilterHandle) -> Arc<Self> {
+        Arc::new(Self {
+            config: Mutex::new(config),
+            log_filter,
+            reload_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Config> {
+        self.config.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn status_check_interval(&self) -> Duration {
+        Duration::from_secs(self.lock().status_check_interval_secs)
+    }
+
+    pub fn discovery_cleanup_interval(&self) -> Duration {
+        Duration::from_secs(self.lock().discovery_cleanup_interval_secs)
+    }
+
+    /// Re-read the configuration and apply the values that are safe to change at runtime
+    pub async fn reload(&self) -> Result<ConfigReloadResult> {
+        let _guard = self.reload_lock.lock().await;
+        let mut next = tokio::task::spawn_blocking(Config::load).await??;
+
+        let mut current = self.lock().clone();
+        // A generated secret stays in place; replacing it would sign everyone out
+        if next.jwt_secret.is_empty() {
+            next.jwt_secret = current.jwt_secret.clone();
+        }
+        let filter = EnvFilter::try_new(&next.log_filter).with_context(|| format!("invalid RUST_LOG '{}'", next.log_filter))?;
+
+        let mut applied = Vec::new();
+        let mut restart_required = Vec::new();
+        for ((key, old_value, reloadable), (_, new_value, _)) in current.entries().into_iter().zip(next.entries()) {
+            if old_value == new_value {
+                continue;
+            }
+            let change = if SECRET_KEYS.contains(&key) {
+                ConfigChange { key: key.to_string(), old_value: MASKED.to_string(), new_value: MASKED.to_string() }
+            } else {
+                ConfigChange { key: key.to_string(), old_value, new_value }
+            };
+            if reloadable {
+                applied.push(change);
+            } else {
+                restart_required.push(change);
+            }
+        }
+
+        if current.log_filter != next.log_filter {
+            self.log_filter.reload(filter).context("failed to apply RUST_LOG")?;
+        }
+        current.log_filter = next.log_filter;
+        current.status_check_interval_secs = next.status_check_interval_secs;
+        current.discovery_cleanup_interval_secs = next.discovery_cleanup_interval_secs;
+        *self.lock() = current;
+
+        Ok(ConfigReloadResult {
+            reloaded_at: chrono::Utc::now(),
+            config_file: (!next.config_file.is_empty()).then_some(next.config_file),
+            applied,
+            restart_required,
+        })
+    }
+
+    /// Reload on every SIGHUP
+    pub fn start_signal_handler(self: &Arc<Self>) {
+        let runtime = self.clone();
+        tokio::spawn(async move {
+            let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+                Ok(sig) => sig,
+                Err(e) => {
+                    tracing::error!("Failed to install SIGHUP handler: {}", e);
+                    return;
+                }
+            };
+            while hangup.recv().await.is_some() {
+                match runtime.reload().await {
+                    Ok(result) => log_result(&result),
+                    Err(e) => tracing::warn!("Configuration reload failed: {:#}", e),
+                }
+            }
+        });
+    }
+}
+
+pub fn log_result(result: &ConfigReloadResult) {
+    for change in &result.applied {
+        tracing::info!("Configuration reload: {} changed from '{}' to '{}'", change.key, change.old_value, change.new_value);
+    }
+    for change in &result.restart_required {
+        tracing::warn!("Configuration reload: {} changed but requires a restart", change.key);
+    }
+    if result.applied.is_empty() && result.restart_required.is_empty() {
+        tracing::info!("Configuration reload: no changes");
+    }
+}
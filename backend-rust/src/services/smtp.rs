@@ -0,0 +1,148 @@
+//! Minimal SMTP submission client for notification emails: EHLO, optional STARTTLS or implicit
+//! TLS, AUTH PLAIN, and a single plain-text message to a list of recipients.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufStream};
+use tokio::net::TcpStream;
+use tokio_native_tls::{native_tls, TlsConnector};
+
+use crate::config::Config;
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(60);
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+/// Whether email can be sent at all
+pub fn is_configured(config: &Config) -> bool {
+    !config.smtp_host.is_empty()
+}
+
+/// A usable recipient or sender address; rejects anything that could inject SMTP commands or headers
+pub fn is_valid_address(address: &str) -> bool {
+    address.contains('@') && !address.contains(|c: char| c.is_whitespace() || c.is_control() || "<>,;".contains(c))
+}
+
+/// RFC 2047 encoded-word for non-ASCII header values
+fn encode_header(value: &str) -> String {
+    let value: String = value.chars().filter(|c| !c.is_control()).collect();
+    if value.is_ascii() {
+        value
+    } else {
+        format!("=?UTF-8?B?{}?=", base64::engine::general_purpose::STANDARD.encode(value))
+    }
+}
+
+struct Connection {
+    stream: BufStream<Box<dyn Stream>>,
+}
+
+impl Connection {
+    /// Read a (possibly multi-line) reply and check its code class
+    async fn reply(&mut self, expected: u16) -> Result<String> {
+        let mut text = String::new();
+        loop {
+            let mut line = String::new();
+            if self.stream.read_line(&mut line).await? == 0 {
+                bail!("connection closed by server");
+            }
+            let line = line.trim_end();
+            let code: u16 = line.get(..3).and_then(|c| c.parse().ok()).with_context(|| format!("invalid reply: {}", line))?;
+            text.push_str(line.get(4..).unwrap_or_default());
+            text.push('\n');
+            if line.as_bytes().get(3) != Some(&b'-') {
+                if code / 100 != expected / 100 {
+                    bail!("server replied {}: {}", code, text.trim_end());
+                }
+                return Ok(text);
+            }
+        }
+    }
+
+    async fn command(&mut self, line: &str, expected: u16) -> Result<String> {
+        self.stream.write_all(line.as_bytes()).await?;
+        self.stream.write_all(b"\r\n").await?;
+        self.stream.flush().await?;
+        self.reply(expected).await
+    }
+}
+
+async fn tls_wrap(host: &str, stream: Box<dyn Stream>) -> Result<Box<dyn Stream>> {
+    let connector = TlsConnector::from(native_tls::TlsConnector::new()?);
+    let tls = connector.connect(host, stream).await.context("TLS handshake failed")?;
+    Ok(Box::new(tls))
+}
+
+async fn deliver(config: &Config, recipients: &[String], message: &str) -> Result<()> {
+    let host = config.smtp_host.as_str();
+    let tcp = TcpStream::connect((host, config.smtp_port))
+        .await
+        .with_context(|| format!("failed to connect to {}:{}", host, config.smtp_port))?;
+    let stream: Box<dyn Stream> = match config.smtp_tls.as_str() {
+        "tls" => tls_wrap(host, Box::new(tcp)).await?,
+        _ => Box::new(tcp),
+    };
+    let mut conn = Connection { stream: BufStream::new(stream) };
+    conn.reply(220).await?;
+
+    let helo_domain = config.smtp_from.rsplit('@').next().unwrap_or("localhost").to_string();
+    let mut capabilities = conn.command(&format!("EHLO {}", helo_domain), 250).await?;
+    if config.smtp_tls == "starttls" {
+        if !capabilities.lines().any(|l| l.eq_ignore_ascii_case("STARTTLS")) {
+            bail!("server does not offer STARTTLS; set SMTP_TLS=none to send unencrypted");
+        }
+        conn.command("STARTTLS", 220).await?;
+        let stream = tls_wrap(host, conn.stream.into_inner()).await?;
+        conn = Connection { stream: BufStream::new(stream) };
+        capabilities = conn.command(&format!("EHLO {}", helo_domain), 250).await?;
+    }
+    if !config.smtp_username.is_empty() {
+        if !capabilities.lines().any(|l| l.to_uppercase().starts_with("AUTH")) {
+            bail!("server does not offer AUTH");
+        }
+        let credentials = format!("\0{}\0{}", config.smtp_username, config.smtp_password);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
+        conn.command(&format!("AUTH PLAIN {}", encoded), 235)
+            .await
+            .context("authentication failed")?;
+    }
+
+    conn.command(&format!("MAIL FROM:<{}>", config.smtp_from), 250).await?;
+    for recipient in recipients {
+        conn.command(&format!("RCPT TO:<{}>", recipient), 250)
+            .await
+            .with_context(|| format!("recipient {} rejected", recipient))?;
+    }
+    conn.command("DATA", 354).await?;
+    conn.stream.write_all(crate::utils::smtp_data(message).as_bytes()).await?;
+    conn.command(".", 250).await?;
+    let _ = conn.command("QUIT", 221).await;
+    Ok(())
+}
+
+/// Send a plain-text email through the SMTP_* server
+pub async fn send_mail(config: &Config, recipients: &[String], subject: &str, body: &str) -> Result<()> {
+    if !is_configured(config) {
+        bail!("email is not configured: set SMTP_HOST");
+    }
+    if recipients.is_empty() {
+        bail!("no recipients");
+    }
+    let domain = config.smtp_from.rsplit('@').next().unwrap_or("localhost");
+    let message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMessage-ID: <{}@{}>\r\nMIME-Version: 1.0\r\n\
+         Content-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n{}",
+        config.smtp_from,
+        recipients.join(", "),
+        encode_header(subject),
+        chrono::Utc::now().to_rfc2822(),
+        uuid::Uuid::new_v4(),
+        domain,
+        body,
+    );
+    tokio::time::timeout(SMTP_TIMEOUT, deliver(config, recipients, &message))
+        .await
+        .context("SMTP session timed out")?
+}
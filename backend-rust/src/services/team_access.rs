@@ -0,0 +1,89 @@
+//! Team-based access control. Users who belong to no team are unrestricted. Team members
+//! can only run the device actions granted by their teams' rules, and can only modify
+//! resources that are unowned or owned by one of their teams.
+
+use anyhow::Result;
+use std::collections::HashSet;
+
+use crate::db::Store;
+use crate::models::*;
+
+pub struct TeamAccess {
+    team_ids: Vec<i64>,
+    rules: Vec<TeamRule>,
+}
+
+impl TeamAccess {
+    pub async fn load(store: &Store, username: &str) -> Result<Self> {
+        let team_ids = store.team_ids_for_user(username).await?;
+        let rules = store.list_team_rules(&team_ids).await?;
+        Ok(Self { team_ids, rules })
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.team_ids.is_empty()
+    }
+
+    /// Whether the user may modify or delete a resource
+    pub async fn can_modify(&self, store: &Store, resource_type: &str, resource_id: i64) -> Result<bool> {
+        if self.is_unrestricted() {
+            return Ok(true);
+        }
+        Ok(store
+            .resource_owner(resource_type, resource_id)
+            .await?
+            .is_none_or(|owner| self.team_ids.contains(&owner)))
+    }
+
+    /// Whether the user may run `action` on a device. `command` is checked against the
+    /// command pattern of exec rules.
+    pub async fn can_run(&self, store: &Store, device_id: i64, action: &str, command: Option<&str>) -> Result<bool> {
+        if self.is_unrestricted() {
+            return Ok(true);
+        }
+        let candidates: Vec<&TeamRule> = self
+            .rules
+            .iter()
+            .filter(|r| r.action == team_action::ANY || r.action == action)
+            .filter(|r| {
+                action != team_action::EXEC
+                    || command.is_none_or(|c| crate::utils::wildcard_match(&r.command_pattern, c))
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Ok(false);
+        }
+        if candidates.iter().any(|r| r.scope == team_rule_scope::ALL) {
+            return Ok(true);
+        }
+
+        let owner = store.resource_owner(team_resource_type::DEVICE, device_id).await?;
+        if candidates
+            .iter()
+            .any(|r| r.scope == team_rule_scope::OWNED && owner == Some(r.team_id))
+        {
+            return Ok(true);
+        }
+
+        let device_groups = self.device_group_ids(store, device_id).await?;
+        Ok(candidates.iter().any(|r| {
+            r.scope == team_rule_scope::GROUP && r.group_id.is_some_and(|g| device_groups.contains(&g))
+        }))
+    }
+
+    /// The device's groups and all their ancestors
+    async fn device_group_ids(&self, store: &Store, device_id: i64) -> Result<HashSet<i64>> {
+        let groups = store.list_groups().await?;
+        let mut ids = HashSet::new();
+        for group in store.list_device_groups(device_id).await? {
+            let mut current = Some(group.id);
+            while let Some(id) = current {
+                if !ids.insert(id) {
+                    break;
+                }
+                current = groups.iter().find(|g| g.id == id).and_then(|g| g.parent_id);
+            }
+        }
+        Ok(ids)
+    }
+}
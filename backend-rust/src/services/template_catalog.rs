@@ -0,0 +1,145 @@
+//! Community template catalog: a Git repository of templates, checked out under the
+//! templates directory. Template files are `.tmpl`, `.j2`, `.jinja` or `.jinja2`; the file stem
+//! is the template name and the top-level directory names its vendor. A leading `{# ... #}` or
+//! `{{/* ... */}}` comment is used as the description.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+const CHECKOUT_DIR: &str = ".catalog";
+const TEMPLATE_EXTENSIONS: &[&str] = &["tmpl", "j2", "jinja", "jinja2"];
+const GIT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A template file found in the catalog checkout
+pub struct CatalogEntry {
+    /// Path relative to the repository root, `/`-separated
+    pub path: String,
+    pub name: String,
+    /// Top-level directory, if the file isn't at the repository root
+    pub vendor_dir: Option<String>,
+    pub description: String,
+    pub content: String,
+}
+
+pub fn checkout_dir(templates_dir: &str) -> PathBuf {
+    Path::new(templates_dir).join(CHECKOUT_DIR)
+}
+
+async fn git(args: &[&str], cwd: Option<&Path>) -> Result<String> {
+    Ok(git_output(args, cwd).await?.trim().to_string())
+}
+
+/// Run git non-interactively and return its stdout as-is, failing with its stderr
+pub(crate) async fn git_output(args: &[&str], cwd: Option<&Path>) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args).env("GIT_TERMINAL_PROMPT", "0").kill_on_drop(true);
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    let output = tokio::time::timeout(GIT_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| anyhow::anyhow!("git {} timed out", args[0]))?
+        .context("failed to run git")?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Commit of the current checkout, if the catalog has been synced
+pub async fn head_commit(templates_dir: &str) -> Result<Option<String>> {
+    let dir = checkout_dir(templates_dir);
+    if !dir.join(".git").exists() {
+        return Ok(None);
+    }
+    Ok(Some(git(&["rev-parse", "HEAD"], Some(&dir)).await?))
+}
+
+/// Fetch the latest catalog (shallow) and return its commit. An existing checkout of the same
+/// repository is updated in place; a checkout of a different URL is replaced.
+pub async fn sync(templates_dir: &str, url: &str, branch: Option<&str>) -> Result<String> {
+    let dir = checkout_dir(templates_dir);
+    let same_origin = dir.join(".git").exists()
+        && git(&["remote", "get-url", "origin"], Some(&dir)).await.ok().as_deref() == Some(url);
+
+    if same_origin {
+        git(&["fetch", "--depth", "1", "origin", branch.unwrap_or("HEAD")], Some(&dir)).await?;
+        git(&["reset", "--hard", "FETCH_HEAD"], Some(&dir)).await?;
+    } else {
+        if dir.exists() {
+            tokio::fs::remove_dir_all(&dir).await
+                .with_context(|| format!("failed to remove old checkout {}", dir.display()))?;
+        }
+        tokio::fs::create_dir_all(templates_dir).await?;
+        let dir_str = dir.to_string_lossy().to_string();
+        let mut args = vec!["clone", "--depth", "1"];
+        if let Some(branch) = branch {
+            args.extend(["--branch", branch]);
+        }
+        args.extend(["--", url, &dir_str]);
+        git(&args, None).await?;
+    }
+    git(&["rev-parse", "HEAD"], Some(&dir)).await
+}
+
+/// Description from a leading template comment
+fn description_of(content: &str) -> String {
+    let start = content.trim_start();
+    let comment = if let Some(rest) = start.strip_prefix("{#") {
+        rest.split("#}").next()
+    } else if let Some(rest) = start.strip_prefix("{{/*") {
+        rest.split("*/}}").next()
+    } else {
+        None
+    };
+    comment.map(|c| c.split_whitespace().collect::<Vec<_>>().join(" ")).unwrap_or_default()
+}
+
+/// List the template files in the catalog checkout, sorted by path
+pub fn scan(templates_dir: &str) -> Result<Vec<CatalogEntry>> {
+    let root = checkout_dir(templates_dir);
+    let mut entries = Vec::new();
+    let mut pending = vec![root.clone()];
+    while let Some(dir) = pending.pop() {
+        for item in std::fs::read_dir(&dir)? {
+            let path = item?.path();
+            let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            if file_name.starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let is_template = path
+                .extension()
+                .is_some_and(|ext| TEMPLATE_EXTENSIONS.iter().any(|t| ext.eq_ignore_ascii_case(t)));
+            if !is_template {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let relative: Vec<String> = path
+                .strip_prefix(&root)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            entries.push(CatalogEntry {
+                path: relative.join("/"),
+                name: path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+                vendor_dir: (relative.len() > 1).then(|| relative[0].clone()),
+                description: description_of(&content),
+                content,
+            });
+        }
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
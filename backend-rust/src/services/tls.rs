@@ -0,0 +1,287 @@
+//! HTTPS listener. The certificate comes from TLS_CERT_PATH/TLS_KEY_PATH or is issued and renewed
+//! over ACME; either way it is reloaded in place without dropping connections. With ACME and no
+//! certificate yet, a self-signed placeholder is served until the first issuance succeeds.
+
+use anyhow::{bail, Context, Result};
+use axum_server::tls_rustls::RustlsConfig;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::models::*;
+
+use super::acme::{self, AcmeClient, ChallengeSolver};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(12 * 3600);
+const RETRY_INTERVAL: Duration = Duration::from_secs(3600);
+/// Renew ACME certificates this close to expiry
+const RENEW_BEFORE_DAYS: i64 = 30;
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+fn not_after(cert_pem: &str) -> Option<DateTime<Utc>> {
+    let params = rcgen::CertificateParams::from_ca_cert_pem(cert_pem).ok()?;
+    DateTime::from_timestamp(params.not_after.unix_timestamp(), 0)
+}
+
+/// Answers HTTP-01 challenges from the map served at /.well-known/acme-challenge/
+struct HttpSolver<'a>(&'a Mutex<HashMap<String, String>>);
+
+#[async_trait::async_trait]
+impl ChallengeSolver for HttpSolver<'_> {
+    fn challenge_type(&self) -> &'static str {
+        acme_challenge::HTTP_01
+    }
+
+    async fn present(&self, _domain: &str, token: &str, key_authorization: &str) -> Result<()> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).insert(token.to_string(), key_authorization.to_string());
+        Ok(())
+    }
+
+    async fn cleanup(&self, _domain: &str, token: &str, _key_authorization: &str) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).remove(token);
+    }
+}
+
+/// Publishes DNS-01 TXT records through the ACME_DNS_HOOK command
+struct DnsHookSolver<'a>(&'a str);
+
+impl DnsHookSolver<'_> {
+    async fn run(&self, verb: &str, domain: &str, key_authorization: &str) -> Result<()> {
+        let record = format!("_acme-challenge.{}", domain);
+        let output = tokio::process::Command::new(self.0)
+            .args([verb, &record, &acme::dns_txt_value(key_authorization)])
+            .output()
+            .await
+            .with_context(|| format!("failed to run DNS hook {}", self.0))?;
+        if !output.status.success() {
+            bail!("DNS hook '{} {}' failed: {}", verb, record, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ChallengeSolver for DnsHookSolver<'_> {
+    fn challenge_type(&self) -> &'static str {
+        acme_challenge::DNS_01
+    }
+
+    async fn present(&self, domain: &str, _token: &str, key_authorization: &str) -> Result<()> {
+        self.run("present", domain, key_authorization).await
+    }
+
+    async fn cleanup(&self, domain: &str, _token: &str, key_authorization: &str) {
+        if let Err(e) = self.run("cleanup", domain, key_authorization).await {
+            tracing::warn!("{}", e);
+        }
+    }
+}
+
+pub struct TlsManager {
+    config: Config,
+    rustls: RustlsConfig,
+    status: Mutex<TlsStatus>,
+    http_challenges: Mutex<HashMap<String, String>>,
+    renew_lock: tokio::sync::Mutex<()>,
+}
+
+impl TlsManager {
+    /// Build the TLS listener state from the config; `None` when TLS_LISTEN_ADDR is unset
+    pub async fn from_config(config: &Config) -> Result<Option<Arc<Self>>> {
+        if config.tls_listen_addr.is_empty() {
+            return Ok(None);
+        }
+        // Both the listener and rcgen use ring; install it as the process-wide rustls provider
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let acme = !config.acme_domains.is_empty();
+        if acme {
+            match config.acme_challenge.as_str() {
+                acme_challenge::HTTP_01 => {}
+                acme_challenge::DNS_01 if !config.acme_dns_hook.is_empty() => {}
+                acme_challenge::DNS_01 => bail!("ACME_CHALLENGE=dns-01 requires ACME_DNS_HOOK"),
+                other => bail!("unknown ACME_CHALLENGE '{}': expected http-01 or dns-01", other),
+            }
+            tokio::fs::create_dir_all(&config.acme_dir)
+                .await
+                .with_context(|| format!("failed to create ACME_DIR {}", config.acme_dir))?;
+        } else if config.tls_cert_path.is_empty() || config.tls_key_path.is_empty() {
+            bail!("TLS_LISTEN_ADDR requires TLS_CERT_PATH and TLS_KEY_PATH, or ACME_DOMAINS");
+        }
+
+        let self_signed = || -> Result<(&'static str, String, String)> {
+            let key = rcgen::KeyPair::generate()?;
+            let cert = rcgen::CertificateParams::new(config.acme_domains.clone())?.self_signed(&key)?;
+            Ok((tls_certificate_source::SELF_SIGNED, cert.pem(), key.serialize_pem()))
+        };
+        let (cert_path, key_path) = Self::cert_paths(config);
+        let (mut source, mut cert, key) = match (tokio::fs::read_to_string(&cert_path).await, tokio::fs::read_to_string(&key_path).await) {
+            (Ok(cert), Ok(key)) => {
+                let source = if acme { tls_certificate_source::ACME } else { tls_certificate_source::FILES };
+                (source, cert, key)
+            }
+            _ if acme => self_signed()?,
+            _ => bail!("failed to read {} and {}", cert_path.display(), key_path.display()),
+        };
+        let rustls = match RustlsConfig::from_pem(cert.clone().into_bytes(), key.into_bytes()).await {
+            Ok(rustls) => rustls,
+            // A crash between replacing the key and the certificate leaves a mismatched pair;
+            // serve the placeholder until renewal issues a new one
+            Err(e) if acme && source == tls_certificate_source::ACME => {
+                tracing::warn!("Stored ACME certificate is unusable, serving a self-signed one until renewal: {}", e);
+                let key;
+                (source, cert, key) = self_signed()?;
+                RustlsConfig::from_pem(cert.clone().into_bytes(), key.into_bytes())
+                    .await
+                    .context("invalid TLS certificate or key")?
+            }
+            Err(e) => return Err(e).context("invalid TLS certificate or key"),
+        };
+
+        Ok(Some(Arc::new(Self {
+            status: Mutex::new(TlsStatus {
+                listen_addr: config.tls_listen_addr.clone(),
+                source: source.to_string(),
+                domains: config.acme_domains.clone(),
+                challenge: acme.then(|| config.acme_challenge.clone()),
+                not_after: not_after(&cert),
+                last_renewed_at: None,
+                last_error: None,
+            }),
+            config: config.clone(),
+            rustls,
+            http_challenges: Mutex::new(HashMap::new()),
+            renew_lock: tokio::sync::Mutex::new(()),
+        })))
+    }
+
+    fn cert_paths(config: &Config) -> (PathBuf, PathBuf) {
+        if config.acme_domains.is_empty() {
+            (PathBuf::from(&config.tls_cert_path), PathBuf::from(&config.tls_key_path))
+        } else {
+            let dir = PathBuf::from(&config.acme_dir);
+            (dir.join("cert.pem"), dir.join("key.pem"))
+        }
+    }
+
+    fn uses_acme(&self) -> bool {
+        !self.config.acme_domains.is_empty()
+    }
+
+    pub fn status(&self) -> TlsStatus {
+        self.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn update_status(&self, f: impl FnOnce(&mut TlsStatus)) {
+        f(&mut self.status.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    /// Key authorization for a pending HTTP-01 challenge token
+    pub fn http_challenge(&self, token: &str) -> Option<String> {
+        self.http_challenges.lock().unwrap_or_else(|e| e.into_inner()).get(token).cloned()
+    }
+
+    /// Start the HTTPS server. The returned handle shuts it down.
+    pub fn serve(&self, app: axum::Router) -> Result<axum_server::Handle> {
+        let addr: std::net::SocketAddr = self
+            .config
+            .tls_listen_addr
+            .parse()
+            .with_context(|| format!("invalid TLS_LISTEN_ADDR {}", self.config.tls_listen_addr))?;
+        let handle = axum_server::Handle::new();
+        let server = axum_server::bind_rustls(addr, self.rustls.clone()).handle(handle.clone());
+        tokio::spawn(async move {
+            if let Err(e) = server.serve(app.into_make_service()).await {
+                tracing::error!("HTTPS server failed: {}", e);
+            }
+        });
+        tracing::info!("ForgeConfig listening on {} (HTTPS)", addr);
+        Ok(handle)
+    }
+
+    pub fn shutdown(handle: &axum_server::Handle) {
+        handle.graceful_shutdown(Some(SHUTDOWN_GRACE));
+    }
+
+    /// Reload certificate files, or renew the ACME certificate when it is close to expiry
+    pub async fn refresh(&self, force: bool) -> Result<TlsStatus> {
+        let _guard = self.renew_lock.lock().await;
+        let result = if self.uses_acme() { self.renew_if_due(force).await } else { self.reload_files().await };
+        if let Err(e) = &result {
+            self.update_status(|s| s.last_error = Some(format!("{:#}", e)));
+        }
+        result.map(|_| self.status())
+    }
+
+    async fn reload_files(&self) -> Result<()> {
+        let (cert_path, key_path) = Self::cert_paths(&self.config);
+        let cert = tokio::fs::read_to_string(&cert_path).await?;
+        self.rustls.reload_from_pem_file(&cert_path, &key_path).await?;
+        self.update_status(|s| {
+            s.not_after = not_after(&cert);
+            s.last_error = None;
+        });
+        Ok(())
+    }
+
+    async fn renew_if_due(&self, force: bool) -> Result<()> {
+        let status = self.status();
+        let due = force
+            || status.source == tls_certificate_source::SELF_SIGNED
+            || status.not_after.is_none_or(|t| t - Utc::now() < chrono::TimeDelta::days(RENEW_BEFORE_DAYS));
+        if !due {
+            return Ok(());
+        }
+
+        let domains = &self.config.acme_domains;
+        tracing::info!("Requesting ACME certificate for {}", domains.join(", "));
+        let key = rcgen::KeyPair::generate()?;
+        let csr = rcgen::CertificateParams::new(domains.clone())?.serialize_request(&key)?;
+        let dir = PathBuf::from(&self.config.acme_dir);
+        let mut client =
+            AcmeClient::connect(&self.config.acme_directory_url, &dir.join("account.key"), &self.config.acme_email).await?;
+        let chain = if self.config.acme_challenge == acme_challenge::DNS_01 {
+            client.issue(domains, csr.der(), &DnsHookSolver(&self.config.acme_dns_hook)).await?
+        } else {
+            client.issue(domains, csr.der(), &HttpSolver(&self.http_challenges)).await?
+        };
+
+        // Both files are written out before either is replaced, so a failed write leaves the old
+        // pair in place
+        let (cert_path, key_path) = Self::cert_paths(&self.config);
+        let staged_key = acme::stage_file(&key_path, key.serialize_pem().as_bytes()).await?;
+        let staged_cert = acme::stage_file(&cert_path, chain.as_bytes()).await?;
+        tokio::fs::rename(&staged_key, &key_path).await?;
+        tokio::fs::rename(&staged_cert, &cert_path).await?;
+        self.rustls.reload_from_pem(chain.clone().into_bytes(), key.serialize_pem().into_bytes()).await?;
+        let expires = not_after(&chain);
+        self.update_status(|s| {
+            s.source = tls_certificate_source::ACME.to_string();
+            s.not_after = expires;
+            s.last_renewed_at = Some(Utc::now());
+            s.last_error = None;
+        });
+        tracing::info!("Installed ACME certificate for {}", domains.join(", "));
+        Ok(())
+    }
+
+    /// Refresh on startup and then periodically; failed renewals retry hourly
+    pub fn start_renewal(self: &Arc<Self>) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let delay = match manager.refresh(false).await {
+                    Ok(_) => CHECK_INTERVAL,
+                    Err(e) => {
+                        tracing::warn!("TLS certificate refresh failed: {:#}", e);
+                        RETRY_INTERVAL
+                    }
+                };
+                tokio::time::sleep(delay).await;
+            }
+        });
+    }
+}
@@ -0,0 +1,316 @@
+//! Underlay calculator: allocates a spine-leaf topology's loopbacks, /31 fabric links and ASNs
+//! from an IPAM supernet, and writes them as the device variables the seeded CLOS templates
+//! render from (Loopback, ASN, PeerN, PeerNAddr, PeerNASN, PeerNName).
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::db::{ConflictError, Store};
+use crate::models::*;
+use crate::utils::{block_prefix_length, format_cidr, peer_indexes, u32_to_ipv4};
+
+/// IPAM tag on the prefixes allocated for a topology, so regenerating can release them
+const POOL_TAG: &str = "underlay_topology";
+
+/// Variables the calculator writes, and replaces when regenerating
+fn is_underlay_variable(key: &str) -> bool {
+    key == "Loopback"
+        || key == "ASN"
+        || key.strip_prefix("Peer").is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// A spine-leaf link, by index into the spine and leaf lists
+struct Link {
+    spine: usize,
+    spine_port: String,
+    leaf: usize,
+    leaf_port: String,
+}
+
+/// Links cabled between the topology's spines and leaves in their port assignments
+async fn assigned_links(store: &Store, spines: &[Device], leaves: &[Device]) -> Result<Vec<Link>> {
+    let leaf_index: HashMap<i64, usize> = leaves.iter().enumerate().map(|(i, d)| (d.id, i)).collect();
+    let mut links = Vec::new();
+    for (si, spine) in spines.iter().enumerate() {
+        for assignment in store.list_port_assignments(spine.id).await? {
+            if let Some(&li) = assignment.remote_device_id.and_then(|id| leaf_index.get(&id)) {
+                links.push(Link {
+                    spine: si,
+                    spine_port: assignment.port_name,
+                    leaf: li,
+                    leaf_port: assignment.remote_port_name,
+                });
+            }
+        }
+    }
+    Ok(links)
+}
+
+/// Full mesh cabled the way the CLOS builder does: spine port leaf*links+link+1 to leaf port
+/// spine*links+link+1
+fn full_mesh_links(spines: usize, leaves: usize, links_per_leaf: usize) -> Vec<Link> {
+    let mut links = Vec::new();
+    for spine in 0..spines {
+        for leaf in 0..leaves {
+            for link in 0..links_per_leaf {
+                links.push(Link {
+                    spine,
+                    spine_port: format!("Ethernet{}", leaf * links_per_leaf + link + 1),
+                    leaf,
+                    leaf_port: format!("Ethernet{}", spine * links_per_leaf + link + 1),
+                });
+            }
+        }
+    }
+    links
+}
+
+/// Release the pools an earlier run allocated for the topology. Deleting a prefix removes its
+/// IP addresses; the /31s are deleted before their pool since child prefixes don't cascade.
+async fn release_pools(store: &Store, topology_id: i64) -> Result<()> {
+    let topology = topology_id.to_string();
+    let pool_ids: Vec<i64> = store
+        .list_all_ipam_tags()
+        .await?
+        .into_iter()
+        .filter(|t| t.resource_type == "prefix" && t.key == POOL_TAG && t.value == topology)
+        .filter_map(|t| t.resource_id.parse().ok())
+        .collect();
+    if pool_ids.is_empty() {
+        return Ok(());
+    }
+    for prefix in store.list_ipam_prefixes().await? {
+        if prefix.parent_id.is_some_and(|id| pool_ids.contains(&id)) {
+            store.delete_ipam_prefix(prefix.id).await?;
+        }
+    }
+    for id in pool_ids {
+        store.delete_ipam_prefix(id).await?;
+        store.delete_ipam_tag("prefix", &id.to_string(), POOL_TAG).await?;
+    }
+    Ok(())
+}
+
+/// Carve a pool of `prefix_length` out of the supernet and tag it with the topology
+async fn allocate_pool(
+    store: &Store,
+    topology: &Topology,
+    supernet: &IpamPrefix,
+    prefix_length: u8,
+    purpose: &str,
+) -> Result<IpamPrefix> {
+    let req = NextAvailablePrefixRequest {
+        prefix_length: prefix_length as i32,
+        description: Some(format!("{} {}", topology.name, purpose)),
+        status: "active".to_string(),
+        datacenter_id: topology.datacenter_id,
+    };
+    let pool = store
+        .next_available_ipam_prefix(supernet.id, &req)
+        .await
+        .map_err(|e| ConflictError::new(format!("No room in {} for the /{} {} pool: {}", supernet.prefix, prefix_length, purpose, e)))?;
+    store.set_ipam_tag("prefix", &pool.id.to_string(), POOL_TAG, &topology.id.to_string()).await?;
+    Ok(pool)
+}
+
+/// Reserve an address in IPAM for a device interface
+async fn reserve_ip(
+    store: &Store,
+    prefix_id: i64,
+    address: &str,
+    device: &Device,
+    interface: &str,
+    description: String,
+    dns_name: Option<String>,
+) -> Result<()> {
+    let req = CreateIpamIpAddressRequest {
+        address: address.to_string(),
+        prefix_id,
+        description: Some(description),
+        status: "active".to_string(),
+        role_ids: vec![],
+        dns_name,
+        device_id: Some(device.id),
+        interface_name: Some(interface.to_string()),
+        vrf_id: None,
+    };
+    store.create_ipam_ip_address(&req).await?;
+    Ok(())
+}
+
+/// Generate the underlay of a topology's spines and leaves. Links follow the spine-leaf port
+/// assignments when there are any, else a full mesh of `links_per_leaf` links is assumed.
+/// Devices with a Loopback or ASN variable already are a conflict unless `overwrite` is set, in
+/// which case the underlay variables are replaced and the pools of the earlier run released.
+pub async fn generate(
+    store: &Store,
+    topology: &Topology,
+    supernet: &IpamPrefix,
+    devices: Vec<Device>,
+    req: &GenerateUnderlayRequest,
+) -> Result<UnderlayResult> {
+    let mut devices = devices;
+    devices.sort_by(|a, b| a.hostname.cmp(&b.hostname));
+    let (spines, leaves): (Vec<Device>, Vec<Device>) = devices
+        .into_iter()
+        .filter(|d| matches!(d.topology_role.as_deref(), Some(topology_role::SPINE) | Some(topology_role::LEAF)))
+        .partition(|d| d.topology_role.as_deref() == Some(topology_role::SPINE));
+
+    let mut existing = Vec::new();
+    for device in spines.iter().chain(&leaves) {
+        let keys: Vec<String> = store
+            .list_device_variables(device.id)
+            .await?
+            .into_iter()
+            .map(|v| v.key)
+            .filter(|k| is_underlay_variable(k))
+            .collect();
+        if !req.overwrite && keys.iter().any(|k| k == "Loopback" || k == "ASN") {
+            return Err(ConflictError::new(format!(
+                "Device {} already has underlay variables; set overwrite to regenerate them",
+                device.hostname
+            ))
+            .into());
+        }
+        existing.push((device.id, keys));
+    }
+    if req.overwrite {
+        release_pools(store, topology.id).await?;
+        for (device_id, keys) in &existing {
+            for key in keys {
+                store.delete_device_variable(*device_id, key).await?;
+            }
+        }
+    }
+
+    let mut links = assigned_links(store, &spines, &leaves).await?;
+    let from_port_assignments = !links.is_empty();
+    if !from_port_assignments {
+        links = full_mesh_links(spines.len(), leaves.len(), req.links_per_leaf as usize);
+    }
+
+    let device_count = spines.len() + leaves.len();
+    let loopback_pool = allocate_pool(store, topology, supernet, block_prefix_length(device_count + 1), "loopbacks").await?;
+    let link_pool = allocate_pool(store, topology, supernet, block_prefix_length(links.len() * 2), "fabric links").await?;
+
+    // Loopbacks from the pool's first host address, spines first
+    let mut loopbacks = Vec::with_capacity(device_count);
+    for (i, device) in spines.iter().chain(&leaves).enumerate() {
+        let address = u32_to_ipv4(loopback_pool.network_int as u32 + i as u32 + 1);
+        reserve_ip(
+            store,
+            loopback_pool.id,
+            &address,
+            device,
+            "Loopback0",
+            format!("{} Loopback0", device.hostname),
+            Some(format!("{}.lo", device.hostname)),
+        )
+        .await?;
+        loopbacks.push(address);
+    }
+    let (spine_loopbacks, leaf_loopbacks) = loopbacks.split_at(spines.len());
+    let leaf_asns: Vec<i64> = (1..=leaves.len() as i64).map(|n| req.leaf_asn_base + n).collect();
+
+    // Consecutive /31s of the link pool; the spine takes the even address
+    let mut result_links = Vec::with_capacity(links.len());
+    for (i, link) in links.iter().enumerate() {
+        let (spine, leaf) = (&spines[link.spine], &leaves[link.leaf]);
+        let network = link_pool.network_int as u32 + 2 * i as u32;
+        let subnet = store
+            .create_ipam_prefix(&CreateIpamPrefixRequest {
+                prefix: format_cidr(network, 31),
+                description: Some(format!("{} {} <-> {} {}", spine.hostname, link.spine_port, leaf.hostname, link.leaf_port)),
+                status: "active".to_string(),
+                is_supernet: false,
+                role_ids: vec![],
+                parent_id: Some(link_pool.id),
+                datacenter_id: topology.datacenter_id,
+                vlan_id: None,
+                vrf_id: None,
+            })
+            .await?;
+        let (spine_addr, leaf_addr) = (u32_to_ipv4(network), u32_to_ipv4(network + 1));
+        reserve_ip(
+            store,
+            subnet.id,
+            &spine_addr,
+            spine,
+            &link.spine_port,
+            format!("{} {} -> {}", spine.hostname, link.spine_port, leaf.hostname),
+            None,
+        )
+        .await?;
+        reserve_ip(
+            store,
+            subnet.id,
+            &leaf_addr,
+            leaf,
+            &link.leaf_port,
+            format!("{} {} -> {}", leaf.hostname, link.leaf_port, spine.hostname),
+            None,
+        )
+        .await?;
+        result_links.push(UnderlayLink {
+            subnet: subnet.prefix,
+            spine: spine.hostname.clone(),
+            spine_port: link.spine_port.clone(),
+            spine_addr,
+            leaf: leaf.hostname.clone(),
+            leaf_port: link.leaf_port.clone(),
+            leaf_addr,
+        });
+    }
+
+    let mut entries = Vec::new();
+    let mut result_devices = Vec::with_capacity(device_count);
+    let roles = [(topology_role::SPINE, &spines, spine_loopbacks), (topology_role::LEAF, &leaves, leaf_loopbacks)];
+    for (role, role_devices, role_loopbacks) in roles {
+        let is_spine = role == topology_role::SPINE;
+        for (i, device) in role_devices.iter().enumerate() {
+            let asn = if is_spine { req.spine_asn } else { leaf_asns[i] };
+            // (local port, peer address, peer ASN, peer hostname, local address) of each link
+            let peers: Vec<(&str, &str, i64, &str, &str)> = links
+                .iter()
+                .zip(&result_links)
+                .filter(|(link, _)| if is_spine { link.spine == i } else { link.leaf == i })
+                .map(|(link, r)| {
+                    if is_spine {
+                        (link.spine_port.as_str(), r.leaf_addr.as_str(), leaf_asns[link.leaf], r.leaf.as_str(), r.spine_addr.as_str())
+                    } else {
+                        (link.leaf_port.as_str(), r.spine_addr.as_str(), req.spine_asn, r.spine.as_str(), r.leaf_addr.as_str())
+                    }
+                })
+                .collect();
+            let ports: Vec<&str> = peers.iter().map(|p| p.0).collect();
+
+            entries.push((device.id, "Loopback".to_string(), role_loopbacks[i].clone()));
+            entries.push((device.id, "ASN".to_string(), asn.to_string()));
+            for (idx, (_, peer_ip, peer_asn, peer_name, local_addr)) in peer_indexes(&ports).into_iter().zip(&peers) {
+                entries.push((device.id, format!("Peer{}", idx), peer_ip.to_string()));
+                entries.push((device.id, format!("Peer{}ASN", idx), peer_asn.to_string()));
+                entries.push((device.id, format!("Peer{}Name", idx), peer_name.to_string()));
+                entries.push((device.id, format!("Peer{}Addr", idx), local_addr.to_string()));
+            }
+            result_devices.push(UnderlayDevice {
+                device_id: device.id,
+                hostname: device.hostname.clone(),
+                role: role.to_string(),
+                asn,
+                loopback: role_loopbacks[i].clone(),
+                peers: peers.len(),
+            });
+        }
+    }
+    store.bulk_set_device_variables(&entries).await?;
+
+    Ok(UnderlayResult {
+        topology_id: topology.id,
+        loopback_pool: loopback_pool.prefix,
+        link_pool: link_pool.prefix,
+        from_port_assignments,
+        devices: result_devices,
+        links: result_links,
+        variables: entries.len(),
+    })
+}
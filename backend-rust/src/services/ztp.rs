@@ -0,0 +1,52 @@
+//! HTTP ZTP: per-device config URLs under `/ztp/config/{mac}`. The URL carries a token derived
+//! from the device's MAC and JWT_SECRET, so it needs no login but only fetches that device's
+//! config. Tokens change with JWT_SECRET (and so with every restart when it isn't set).
+
+use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::hmac;
+use std::collections::HashMap;
+
+use crate::models::{Device, Settings};
+use crate::utils::normalize_mac;
+
+fn key(secret: &str) -> hmac::Key {
+    hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes())
+}
+
+fn message(mac: &str) -> String {
+    format!("ztp:{}", normalize_mac(mac))
+}
+
+/// The token authorizing ZTP fetches of the config for `mac`
+pub fn device_token(secret: &str, mac: &str) -> String {
+    URL_SAFE_NO_PAD.encode(hmac::sign(&key(secret), message(mac).as_bytes()))
+}
+
+pub fn verify_token(secret: &str, mac: &str, token: &str) -> bool {
+    URL_SAFE_NO_PAD
+        .decode(token)
+        .is_ok_and(|tag| hmac::verify(&key(secret), message(mac).as_bytes(), &tag).is_ok())
+}
+
+/// Path and query of a device's ZTP config URL
+pub fn config_path(secret: &str, mac: &str) -> String {
+    let mac = normalize_mac(mac);
+    format!("/ztp/config/{}?token={}", mac, device_token(secret, &mac))
+}
+
+/// Render a vendor's ZTP wrapper around a device's rendered config
+pub fn wrap_config(
+    wrapper: &str,
+    device: &Device,
+    settings: &Settings,
+    vars: &HashMap<String, String>,
+    config: &str,
+) -> Result<String> {
+    let mut context = crate::jobs::build_render_context(device, settings, vars, None);
+    context.insert("config", config.trim_end());
+    crate::utils::template_engine()
+        .render_str(wrapper, &context)
+        .map_err(|e| anyhow::anyhow!("ZTP wrapper rendering failed: {}", e))
+}
@@ -1,20 +1,33 @@
+use std::sync::Arc;
 use tokio::process::Command;
-use tokio::time::{interval, Duration};
+use tokio::time::{interval, interval_at, Instant};
 
 use crate::db::Store;
+use crate::services::maintenance_mode::MaintenanceMode;
+use crate::services::runtime_config::RuntimeConfig;
+use crate::ws::Hub;
 
 /// Status checker periodically pings devices to check connectivity
 pub struct StatusChecker {
     store: Store,
-    interval_secs: u64,
+    runtime_config: Arc<RuntimeConfig>,
+    maintenance_mode: Arc<MaintenanceMode>,
+    ws_hub: Arc<Hub>,
     stop_tx: Option<tokio::sync::oneshot::Sender<()>>,
 }
 
 impl StatusChecker {
-    pub fn new(store: Store, interval_secs: u64) -> Self {
+    pub fn new(
+        store: Store,
+        runtime_config: Arc<RuntimeConfig>,
+        maintenance_mode: Arc<MaintenanceMode>,
+        ws_hub: Arc<Hub>,
+    ) -> Self {
         Self {
             store,
-            interval_secs,
+            runtime_config,
+            maintenance_mode,
+            ws_hub,
             stop_tx: None,
         }
     }
@@ -25,15 +38,28 @@ impl StatusChecker {
         self.stop_tx = Some(stop_tx);
 
         let store = self.store.clone();
-        let interval_secs = self.interval_secs;
+        let runtime_config = self.runtime_config.clone();
+        let maintenance_mode = self.maintenance_mode.clone();
+        let ws_hub = self.ws_hub.clone();
 
         tokio::spawn(async move {
-            let mut ticker = interval(Duration::from_secs(interval_secs));
+            let mut period = runtime_config.status_check_interval();
+            let mut ticker = interval(period);
 
             loop {
                 tokio::select! {
                     _ = ticker.tick() => {
-                        if let Err(e) = check_all_devices(&store).await {
+                        // Pick up STATUS_CHECK_INTERVAL_SECS changes from a config reload
+                        let next = runtime_config.status_check_interval();
+                        if next != period {
+                            period = next;
+                            ticker = interval_at(Instant::now() + period, period);
+                        }
+                        // Status writes are frozen during maintenance
+                        if maintenance_mode.is_enabled() {
+                            continue;
+                        }
+                        if let Err(e) = check_all_devices(&store, &ws_hub).await {
                             tracing::warn!("Error checking device status: {}", e);
                         }
                     }
@@ -55,7 +81,7 @@ impl StatusChecker {
     }
 }
 
-async fn check_all_devices(store: &Store) -> anyhow::Result<()> {
+async fn check_all_devices(store: &Store, ws_hub: &Hub) -> anyhow::Result<()> {
     let devices = store.list_devices().await?;
 
     for device in devices {
@@ -66,6 +92,17 @@ async fn check_all_devices(store: &Store) -> anyhow::Result<()> {
         if device.status != new_status || is_reachable {
             if let Err(e) = store.update_device_status(device.id, new_status).await {
                 tracing::warn!("Failed to update status for {}: {}", device.id, e);
+            } else if device.status != new_status {
+                ws_hub.broadcast_device_status(&device, new_status).await;
+            }
+        }
+
+        // A reachable device that has taken a new lease has finished re-bootstrapping
+        if is_reachable {
+            match store.complete_provisioning_session(device.id).await {
+                Ok(true) => tracing::info!("Device {} finished reprovisioning", device.hostname),
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Failed to complete provisioning session for {}: {}", device.id, e),
             }
         }
     }
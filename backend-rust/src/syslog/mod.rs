@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::net::{TcpListener, UdpSocket};
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration, Instant};
+
+use crate::db::Store;
+use crate::models::SyslogAlertRule;
+use crate::ws::Hub;
+
+/// Largest message accepted, per UDP datagram or TCP frame
+const MAX_MESSAGE_LEN: usize = 8192;
+/// Number of most recent events kept
+const EVENT_RETENTION: i64 = 50_000;
+/// How often rules are reloaded and old events pruned
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+/// Repeat matches of the same rule from the same source within this window don't raise new alerts
+const ALERT_COOLDOWN: Duration = Duration::from_secs(60);
+
+struct CompiledRule {
+    rule: SyslogAlertRule,
+    regex: regex_lite::Regex,
+}
+
+/// Syslog receiver accepts messages from managed devices over UDP and TCP, stores them
+/// against the device with the sending IP, and raises alerts for messages matching a rule
+pub struct SyslogReceiver {
+    store: Store,
+    ws_hub: Option<Arc<Hub>>,
+    rules: RwLock<Vec<CompiledRule>>,
+    last_alerts: RwLock<HashMap<(i64, IpAddr), Instant>>,
+}
+
+impl SyslogReceiver {
+    pub fn new(store: Store, ws_hub: Option<Arc<Hub>>) -> Arc<Self> {
+        Arc::new(Self {
+            store,
+            ws_hub,
+            rules: RwLock::new(Vec::new()),
+            last_alerts: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Bind the UDP and TCP listeners on `addr` and start the refresh task.
+    /// A listener that fails to bind is logged and skipped.
+    pub async fn start(self: &Arc<Self>, addr: &str) {
+        self.reload().await;
+
+        match UdpSocket::bind(addr).await {
+            Ok(socket) => {
+                tracing::info!("Syslog receiver listening on udp/{}", addr);
+                let receiver = self.clone();
+                tokio::spawn(async move { receiver.serve_udp(socket).await });
+            }
+            Err(e) => tracing::warn!("Failed to bind syslog udp/{}: {}", addr, e),
+        }
+        match TcpListener::bind(addr).await {
+            Ok(listener) => {
+                tracing::info!("Syslog receiver listening on tcp/{}", addr);
+                let receiver = self.clone();
+                tokio::spawn(async move { receiver.serve_tcp(listener).await });
+            }
+            Err(e) => tracing::warn!("Failed to bind syslog tcp/{}: {}", addr, e),
+        }
+
+        let receiver = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = interval(REFRESH_INTERVAL);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                receiver.reload().await;
+                match receiver.store.prune_syslog_events(EVENT_RETENTION).await {
+                    Ok(count) if count > 0 => tracing::debug!("Pruned {} old syslog events", count),
+                    Err(e) => tracing::warn!("Syslog event pruning failed: {}", e),
+                    _ => {}
+                }
+                let now = Instant::now();
+                receiver.last_alerts.write().await.retain(|_, at| now.duration_since(*at) < ALERT_COOLDOWN);
+            }
+        });
+    }
+
+    /// Reload the alert rules
+    pub async fn reload(&self) {
+        match self.store.list_syslog_alert_rules().await {
+            Ok(rules) => {
+                let compiled = rules
+                    .into_iter()
+                    .filter(|rule| rule.enabled)
+                    .filter_map(|rule| match regex_lite::Regex::new(&rule.pattern) {
+                        Ok(regex) => Some(CompiledRule { rule, regex }),
+                        Err(e) => {
+                            tracing::warn!("Skipping syslog alert rule '{}': invalid pattern: {}", rule.name, e);
+                            None
+                        }
+                    })
+                    .collect();
+                *self.rules.write().await = compiled;
+            }
+            Err(e) => tracing::warn!("Failed to load syslog alert rules: {}", e),
+        }
+    }
+
+    async fn serve_udp(self: Arc<Self>, socket: UdpSocket) {
+        let mut buf = vec![0u8; MAX_MESSAGE_LEN];
+        loop {
+            match socket.recv_from(&mut buf).await {
+                Ok((len, peer)) => self.handle(peer.ip(), &String::from_utf8_lossy(&buf[..len])).await,
+                Err(e) => {
+                    tracing::warn!("Syslog udp receive failed: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+    }
+
+    async fn serve_tcp(self: Arc<Self>, listener: TcpListener) {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    tracing::warn!("Syslog tcp accept failed: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            let receiver = self.clone();
+            tokio::spawn(async move {
+                let mut reader = BufReader::new(stream);
+                let mut frame = Vec::new();
+                loop {
+                    match read_frame(&mut reader, &mut frame).await {
+                        Ok(true) => receiver.handle(peer.ip(), &String::from_utf8_lossy(&frame)).await,
+                        Ok(false) => break,
+                        Err(e) => {
+                            tracing::debug!("Closing syslog connection from {}: {}", peer, e);
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+    }
+
+    async fn handle(&self, source: IpAddr, raw: &str) {
+        if raw.trim().is_empty() {
+            return;
+        }
+        let msg = crate::utils::parse_syslog(raw);
+        let source_ip = source.to_string();
+        let device_id = match self.store.find_device_id_by_ip(&source_ip).await {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::warn!("Failed to look up device for syslog source {}: {}", source_ip, e);
+                None
+            }
+        };
+
+        let event_id = match self.store.create_syslog_event(&source_ip, device_id, &msg).await {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::warn!("Failed to store syslog event from {}: {}", source_ip, e);
+                return;
+            }
+        };
+
+        let matched: Vec<i64> = self
+            .rules
+            .read()
+            .await
+            .iter()
+            .filter(|r| msg.severity <= r.rule.max_severity && r.regex.is_match(&msg.message))
+            .map(|r| r.rule.id)
+            .collect();
+        for rule_id in matched {
+            let now = Instant::now();
+            {
+                let mut last_alerts = self.last_alerts.write().await;
+                if last_alerts.get(&(rule_id, source)).is_some_and(|at| now.duration_since(*at) < ALERT_COOLDOWN) {
+                    continue;
+                }
+                last_alerts.insert((rule_id, source), now);
+            }
+            match self.store.create_syslog_alert(rule_id, event_id, device_id).await {
+                Ok(alert) => {
+                    tracing::info!("Syslog alert '{}' from {}: {}", alert.rule_name, source_ip, alert.message);
+                    if let Some(ref hub) = self.ws_hub {
+                        hub.broadcast_syslog_alert(&alert).await;
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to record syslog alert: {}", e),
+            }
+        }
+    }
+}
+
+/// Read one TCP syslog frame into `buf`: octet-counted ("LEN SP MSG", RFC 6587) when it starts
+/// with a digit, else newline-terminated. Returns false at end of stream.
+async fn read_frame<R: AsyncBufRead + Unpin>(reader: &mut R, buf: &mut Vec<u8>) -> io::Result<bool> {
+    buf.clear();
+    let first = match reader.fill_buf().await?.first() {
+        Some(b) => *b,
+        None => return Ok(false),
+    };
+    if first.is_ascii_digit() {
+        (&mut *reader).take(8).read_until(b' ', buf).await?;
+        let len = std::str::from_utf8(buf)
+            .ok()
+            .and_then(|s| s.trim_end().parse::<usize>().ok())
+            .filter(|len| *len <= MAX_MESSAGE_LEN)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid octet count"))?;
+        buf.resize(len, 0);
+        reader.read_exact(buf).await?;
+    } else {
+        (&mut *reader).take(MAX_MESSAGE_LEN as u64).read_until(b'\n', buf).await?;
+    }
+    Ok(true)
+}
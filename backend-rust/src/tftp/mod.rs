@@ -0,0 +1,371 @@
+//! Built-in TFTP server (RFC 1350) serving TFTP_DIR to booting devices. Read requests only,
+//! with the blksize, timeout and tsize options (RFCs 2347-2349). Each completed transfer is
+//! logged with the requester's MAC and IP and recorded as a config pull.
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::net::UdpSocket;
+use tokio::time::{timeout, Duration};
+
+use crate::db::Store;
+use crate::ws::Hub;
+
+const OP_RRQ: u16 = 1;
+const OP_WRQ: u16 = 2;
+const OP_DATA: u16 = 3;
+const OP_ACK: u16 = 4;
+const OP_ERROR: u16 = 5;
+const OP_OACK: u16 = 6;
+
+const ERR_UNDEFINED: u16 = 0;
+const ERR_FILE_NOT_FOUND: u16 = 1;
+const ERR_ACCESS_VIOLATION: u16 = 2;
+const ERR_ILLEGAL_OPERATION: u16 = 4;
+
+/// Largest request packet accepted
+const MAX_REQUEST_LEN: usize = 2048;
+const DEFAULT_BLOCK_SIZE: usize = 512;
+/// Largest blksize a client may negotiate (RFC 2348)
+const MAX_BLOCK_SIZE: usize = 65464;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+/// Sends of one packet before the transfer is abandoned
+const MAX_ATTEMPTS: u32 = 5;
+
+/// A parsed read request
+struct ReadRequest {
+    filename: String,
+    netascii: bool,
+    /// Options as sent, names lowercased
+    options: Vec<(String, String)>,
+}
+
+/// Transfer parameters after option negotiation
+struct Transfer {
+    block_size: usize,
+    timeout: Duration,
+    /// Options acknowledged in an OACK; empty sends data straight away
+    accepted: Vec<(&'static str, String)>,
+}
+
+/// TFTP server for the files ConfigManager writes, so no external tftpd is needed
+pub struct TftpServer {
+    store: Store,
+    root: PathBuf,
+    lease_path: String,
+    ws_hub: Option<Arc<Hub>>,
+}
+
+impl TftpServer {
+    pub fn new(store: Store, root: &str, lease_path: &str, ws_hub: Option<Arc<Hub>>) -> Arc<Self> {
+        Arc::new(Self {
+            store,
+            root: PathBuf::from(root),
+            lease_path: lease_path.to_string(),
+            ws_hub,
+        })
+    }
+
+    /// Bind `addr` and start serving. A failure to bind is logged and leaves TFTP disabled.
+    pub async fn start(self: &Arc<Self>, addr: &str) {
+        match UdpSocket::bind(addr).await {
+            Ok(socket) => {
+                tracing::info!("TFTP server listening on udp/{} serving {}", addr, self.root.display());
+                let server = self.clone();
+                tokio::spawn(async move { server.serve(socket).await });
+            }
+            Err(e) => tracing::warn!("Failed to bind TFTP udp/{}: {}", addr, e),
+        }
+    }
+
+    async fn serve(self: Arc<Self>, socket: UdpSocket) {
+        // Transfers run from their own port on the listening address
+        let local_ip = match socket.local_addr() {
+            Ok(addr) => addr.ip(),
+            Err(e) => {
+                tracing::warn!("TFTP server has no local address: {}", e);
+                return;
+            }
+        };
+        let mut buf = vec![0u8; MAX_REQUEST_LEN];
+        loop {
+            let (len, peer) = match socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(e) => {
+                    tracing::warn!("TFTP receive failed: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            match parse_request(&buf[..len]) {
+                Ok(request) => {
+                    let server = self.clone();
+                    tokio::spawn(async move { server.transfer(local_ip, peer, request).await });
+                }
+                Err((code, message)) => {
+                    tracing::debug!("Rejected TFTP request from {}: {}", peer, message);
+                    let _ = socket.send_to(&error_packet(code, message), peer).await;
+                }
+            }
+        }
+    }
+
+    /// Send the requested file to `peer` from a new socket
+    async fn transfer(&self, local_ip: IpAddr, peer: SocketAddr, request: ReadRequest) {
+        let socket = match UdpSocket::bind(SocketAddr::new(local_ip, 0)).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                tracing::warn!("Failed to open TFTP transfer socket for {}: {}", peer, e);
+                return;
+            }
+        };
+        if let Err(e) = socket.connect(peer).await {
+            tracing::warn!("Failed to connect TFTP transfer socket to {}: {}", peer, e);
+            return;
+        }
+
+        let Some(path) = resolve_path(&self.root, &request.filename) else {
+            tracing::warn!("TFTP request from {} for {} is outside the TFTP root", peer.ip(), request.filename);
+            let _ = socket.send(&error_packet(ERR_ACCESS_VIOLATION, "Access violation")).await;
+            return;
+        };
+        let (source, size) = match open_file(&path, request.netascii).await {
+            Ok(opened) => opened,
+            Err(e) => {
+                tracing::info!("TFTP request from {} for {} failed: {}", peer.ip(), request.filename, e);
+                let (code, message) = match e.kind() {
+                    io::ErrorKind::NotFound => (ERR_FILE_NOT_FOUND, "File not found"),
+                    io::ErrorKind::PermissionDenied => (ERR_ACCESS_VIOLATION, "Access violation"),
+                    _ => (ERR_UNDEFINED, "Cannot read file"),
+                };
+                let _ = socket.send(&error_packet(code, message)).await;
+                return;
+            }
+        };
+
+        let transfer = negotiate(&request.options, size);
+        match send_file(&socket, &transfer, source).await {
+            Ok(sent) => self.completed(peer.ip(), &request.filename, sent).await,
+            Err(e) => tracing::warn!("TFTP transfer of {} to {} failed: {}", request.filename, peer.ip(), e),
+        }
+    }
+
+    async fn completed(&self, peer_ip: IpAddr, filename: &str, bytes: u64) {
+        let source_ip = peer_ip.to_string();
+        let device = crate::services::config_pull::on_config_pulled(
+            &self.store,
+            self.ws_hub.as_ref(),
+            filename,
+            Some(&source_ip),
+            "tftp",
+        )
+        .await;
+
+        let mac = match device.as_ref().and_then(|d| d.mac.clone()) {
+            Some(mac) => Some(mac),
+            None => crate::dhcp::parse_lease_file(&self.lease_path)
+                .await
+                .ok()
+                .and_then(|leases| leases.into_iter().find(|l| l.ip == source_ip))
+                .map(|l| l.mac),
+        };
+        tracing::info!(
+            "TFTP: {} ({}{}) fetched {} ({} bytes)",
+            source_ip,
+            mac.as_deref().unwrap_or("unknown MAC"),
+            device.map(|d| format!(", {}", d.hostname)).unwrap_or_default(),
+            filename,
+            bytes
+        );
+    }
+}
+
+/// Parse an RRQ. Anything else is answered with the error code and message returned.
+fn parse_request(packet: &[u8]) -> Result<ReadRequest, (u16, &'static str)> {
+    if packet.len() < 2 {
+        return Err((ERR_ILLEGAL_OPERATION, "Malformed request"));
+    }
+    match u16::from_be_bytes([packet[0], packet[1]]) {
+        OP_RRQ => {}
+        OP_WRQ => return Err((ERR_ACCESS_VIOLATION, "Write requests are not supported")),
+        _ => return Err((ERR_ILLEGAL_OPERATION, "Illegal TFTP operation")),
+    }
+
+    // filename NUL mode NUL [option NUL value NUL]...
+    let mut fields: Vec<String> = packet[2..]
+        .split(|b| *b == 0)
+        .map(|f| String::from_utf8_lossy(f).into_owned())
+        .collect();
+    if fields.pop().is_none_or(|last| !last.is_empty()) || fields.len() < 2 || !fields.len().is_multiple_of(2) {
+        return Err((ERR_ILLEGAL_OPERATION, "Malformed request"));
+    }
+    let netascii = match fields[1].to_ascii_lowercase().as_str() {
+        "octet" => false,
+        "netascii" => true,
+        _ => return Err((ERR_ILLEGAL_OPERATION, "Unsupported transfer mode")),
+    };
+    let options = fields[2..]
+        .chunks(2)
+        .map(|pair| (pair[0].to_ascii_lowercase(), pair[1].clone()))
+        .collect();
+    Ok(ReadRequest { filename: fields[0].clone(), netascii, options })
+}
+
+/// The file under `root` a request names, or None if it would escape the root
+fn resolve_path(root: &Path, filename: &str) -> Option<PathBuf> {
+    let relative = filename.replace('\\', "/");
+    let relative = Path::new(relative.trim_start_matches('/'));
+    if relative.as_os_str().is_empty() || relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return None;
+    }
+    Some(root.join(relative))
+}
+
+/// Open a file for sending, with its transfer size. Netascii files are converted in memory.
+async fn open_file(path: &Path, netascii: bool) -> io::Result<(Box<dyn AsyncRead + Send + Unpin>, u64)> {
+    if netascii {
+        let data = to_netascii(&tokio::fs::read(path).await?);
+        let size = data.len() as u64;
+        return Ok((Box::new(io::Cursor::new(data)), size));
+    }
+    let file = File::open(path).await?;
+    let metadata = file.metadata().await?;
+    if !metadata.is_file() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "not a file"));
+    }
+    Ok((Box::new(file), metadata.len()))
+}
+
+/// Line endings become CR LF and bare CRs CR NUL
+fn to_netascii(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 32);
+    for b in data {
+        match b {
+            b'\n' => out.extend_from_slice(b"\r\n"),
+            b'\r' => out.extend_from_slice(b"\r\0"),
+            _ => out.push(*b),
+        }
+    }
+    out
+}
+
+/// Accept the options this server supports; unknown or out-of-range options are left out of
+/// the OACK, so the client falls back to the defaults
+fn negotiate(options: &[(String, String)], size: u64) -> Transfer {
+    let mut transfer = Transfer {
+        block_size: DEFAULT_BLOCK_SIZE,
+        timeout: DEFAULT_TIMEOUT,
+        accepted: Vec::new(),
+    };
+    for (name, value) in options {
+        match name.as_str() {
+            "blksize" => {
+                if let Some(size) = value.parse::<usize>().ok().filter(|s| *s >= 8) {
+                    transfer.block_size = size.min(MAX_BLOCK_SIZE);
+                    transfer.accepted.push(("blksize", transfer.block_size.to_string()));
+                }
+            }
+            "timeout" => {
+                if let Some(secs) = value.parse::<u64>().ok().filter(|s| (1..=255).contains(s)) {
+                    transfer.timeout = Duration::from_secs(secs);
+                    transfer.accepted.push(("timeout", secs.to_string()));
+                }
+            }
+            "tsize" => transfer.accepted.push(("tsize", size.to_string())),
+            _ => {}
+        }
+    }
+    transfer
+}
+
+/// Send `source` block by block, returning the bytes sent
+async fn send_file(
+    socket: &UdpSocket,
+    transfer: &Transfer,
+    mut source: Box<dyn AsyncRead + Send + Unpin>,
+) -> io::Result<u64> {
+    if !transfer.accepted.is_empty() {
+        let mut oack = OP_OACK.to_be_bytes().to_vec();
+        for (name, value) in &transfer.accepted {
+            oack.extend_from_slice(name.as_bytes());
+            oack.push(0);
+            oack.extend_from_slice(value.as_bytes());
+            oack.push(0);
+        }
+        send_until_acked(socket, &oack, 0, transfer.timeout).await?;
+    }
+
+    let mut packet = vec![0u8; 4 + transfer.block_size];
+    let mut block: u16 = 1;
+    let mut sent = 0u64;
+    loop {
+        let len = read_block(&mut source, &mut packet[4..]).await?;
+        packet[..2].copy_from_slice(&OP_DATA.to_be_bytes());
+        packet[2..4].copy_from_slice(&block.to_be_bytes());
+        send_until_acked(socket, &packet[..4 + len], block, transfer.timeout).await?;
+        sent += len as u64;
+        // A short block ends the transfer; block numbers wrap for large files
+        if len < transfer.block_size {
+            return Ok(sent);
+        }
+        block = block.wrapping_add(1);
+    }
+}
+
+/// Fill `buf` from `source`, short only at end of file
+async fn read_block(source: &mut (dyn AsyncRead + Send + Unpin), buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match source.read(&mut buf[filled..]).await? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Send `packet` and wait for the ACK of `block`, resending on timeout. Duplicate ACKs of
+/// earlier blocks are ignored rather than answered, which avoids the Sorcerer's Apprentice bug.
+async fn send_until_acked(socket: &UdpSocket, packet: &[u8], block: u16, wait: Duration) -> io::Result<()> {
+    let mut buf = [0u8; 512];
+    for _ in 0..MAX_ATTEMPTS {
+        socket.send(packet).await?;
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            let len = match timeout(remaining, socket.recv(&mut buf)).await {
+                Ok(received) => received?,
+                Err(_) => break,
+            };
+            if len < 4 {
+                continue;
+            }
+            let opcode = u16::from_be_bytes([buf[0], buf[1]]);
+            let number = u16::from_be_bytes([buf[2], buf[3]]);
+            if opcode == OP_ACK && number == block {
+                return Ok(());
+            }
+            if opcode == OP_ERROR {
+                let message = String::from_utf8_lossy(&buf[4..len]);
+                return Err(io::Error::other(format!(
+                    "client aborted with error {}: {}",
+                    number,
+                    message.trim_end_matches('\0')
+                )));
+            }
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::TimedOut, format!("no ACK for block {}", block)))
+}
+
+fn error_packet(code: u16, message: &str) -> Vec<u8> {
+    let mut packet = OP_ERROR.to_be_bytes().to_vec();
+    packet.extend_from_slice(&code.to_be_bytes());
+    packet.extend_from_slice(message.as_bytes());
+    packet.push(0);
+    packet
+}
@@ -0,0 +1,113 @@
+//! Line diffs of configs. `diff_lines` gives the changes as structured hunks for API clients, and
+//! `unified_diff` renders the same hunks as unified diff text.
+
+use std::time::{Duration, Instant};
+
+use similar::{capture_diff_slices_deadline, Algorithm, DiffTag};
+
+use crate::models::{diff_line_kind, ConfigDiff, DiffHunk, DiffLine};
+
+/// How long a diff may search for the smallest edit before settling for a larger one
+const DIFF_DEADLINE: Duration = Duration::from_secs(2);
+
+/// Line-based diff of `old` against `new`, grouped into hunks with `context` lines around each
+/// change. Hunks closer than twice the context are merged.
+pub fn diff_lines(old: &str, new: &str, old_label: &str, new_label: &str, context: usize) -> ConfigDiff {
+    let mut diff = ConfigDiff {
+        old_label: old_label.to_string(),
+        new_label: new_label.to_string(),
+        added: 0,
+        removed: 0,
+        hunks: Vec::new(),
+    };
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // Myers diff in linear space, settling for a coarser diff if a pathological pair of configs
+    // runs past the deadline
+    let deadline = Instant::now() + DIFF_DEADLINE;
+    let mut ops: Vec<(&str, usize, usize, &str)> = Vec::with_capacity(a.len().max(b.len()));
+    for op in capture_diff_slices_deadline(Algorithm::Myers, &a, &b, Some(deadline)) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        match tag {
+            DiffTag::Equal => {
+                for (i, j) in old_range.zip(new_range) {
+                    ops.push((diff_line_kind::CONTEXT, i, j, a[i]));
+                }
+            }
+            DiffTag::Delete | DiffTag::Insert | DiffTag::Replace => {
+                for i in old_range.clone() {
+                    ops.push((diff_line_kind::REMOVED, i, new_range.start, a[i]));
+                }
+                for j in new_range {
+                    ops.push((diff_line_kind::ADDED, old_range.end, j, b[j]));
+                }
+            }
+        }
+    }
+
+    diff.added = ops.iter().filter(|op| op.0 == diff_line_kind::ADDED).count();
+    diff.removed = ops.iter().filter(|op| op.0 == diff_line_kind::REMOVED).count();
+
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| op.0 != diff_line_kind::CONTEXT)
+        .map(|(k, _)| k)
+        .collect();
+    let mut k = 0;
+    while k < changed.len() {
+        let start = changed[k].saturating_sub(context);
+        let mut end = changed[k];
+        while k < changed.len() && changed[k] <= end + 2 * context + 1 {
+            end = changed[k];
+            k += 1;
+        }
+        let end = (end + context + 1).min(ops.len());
+        let hunk = &ops[start..end];
+        let old_count = hunk.iter().filter(|op| op.0 != diff_line_kind::ADDED).count();
+        let new_count = hunk.iter().filter(|op| op.0 != diff_line_kind::REMOVED).count();
+        diff.hunks.push(DiffHunk {
+            old_start: hunk[0].1 + usize::from(old_count > 0),
+            old_count,
+            new_start: hunk[0].2 + usize::from(new_count > 0),
+            new_count,
+            lines: hunk
+                .iter()
+                .map(|&(kind, old_index, new_index, text)| DiffLine {
+                    kind: kind.to_string(),
+                    text: text.to_string(),
+                    old_line: (kind != diff_line_kind::ADDED).then_some(old_index + 1),
+                    new_line: (kind != diff_line_kind::REMOVED).then_some(new_index + 1),
+                })
+                .collect(),
+        });
+    }
+    diff
+}
+
+/// Line-based unified diff of `old` against `new` with `context` lines around each change.
+/// Returns an empty string when the inputs are identical.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str, context: usize) -> String {
+    let diff = diff_lines(old, new, old_label, new_label, context);
+    if diff.hunks.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("--- {}\n+++ {}\n", diff.old_label, diff.new_label);
+    for hunk in &diff.hunks {
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            hunk.old_start, hunk.old_count, hunk.new_start, hunk.new_count
+        ));
+        for line in &hunk.lines {
+            out.push(match line.kind.as_str() {
+                diff_line_kind::ADDED => '+',
+                diff_line_kind::REMOVED => '-',
+                _ => ' ',
+            });
+            out.push_str(&line.text);
+            out.push('\n');
+        }
+    }
+    out
+}
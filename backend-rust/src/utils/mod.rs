@@ -1,26 +1,56 @@
-use std::io::Read;
-use std::net::TcpStream;
-use std::time::Duration;
-
-/// Keyboard-interactive prompt handler that always responds with the password
-struct PasswordPrompt {
-    password: String,
+use std::collections::{HashMap, HashSet};
+
+mod diff;
+mod netconf;
+mod ssh;
+mod template_filters;
+mod template_lint;
+pub use diff::*;
+pub use netconf::*;
+pub use ssh::*;
+pub use template_filters::*;
+pub use template_lint::*;
+
+/// SSH credentials resolved for a device
+pub struct ResolvedSshCredentials {
+    pub user: String,
+    pub pass: String,
+    /// Stored credential (device or group binding) that supplied the user or password
+    pub credential_id: Option<i64>,
 }
 
-impl ssh2::KeyboardInteractivePrompt for PasswordPrompt {
-    fn prompt<'a>(
-        &mut self,
-        _username: &str,
-        _instructions: &str,
-        prompts: &[ssh2::Prompt<'a>],
-    ) -> Vec<String> {
-        prompts.iter().map(|_| self.password.clone()).collect()
-    }
+/// Resolve SSH credentials for a device. Each of user and password falls back independently:
+/// device credential -> inline ssh_user/ssh_pass (deprecated) -> group credential -> vendor defaults -> global settings
+pub async fn resolve_ssh_credentials(store: &crate::db::Store, device: &crate::models::Device) -> ResolvedSshCredentials {
+    let device_cred = match device.credential_id {
+        Some(id) => store.get_credential(id).await.ok().flatten(),
+        None => None,
+    };
+    let group_cred = if device_cred.is_none() {
+        match store.resolve_group_credential(device.id).await.ok().flatten() {
+            Some(id) => store.get_credential(id).await.ok().flatten(),
+            None => None,
+        }
+    } else {
+        None
+    };
+
+    let non_empty = |s: &String| !s.is_empty();
+    let user = device_cred.as_ref().map(|c| c.username.clone()).filter(non_empty)
+        .or_else(|| device.ssh_user.clone().filter(non_empty))
+        .or_else(|| group_cred.as_ref().map(|c| c.username.clone()).filter(non_empty));
+    let pass = device_cred.as_ref().map(|c| c.password.clone()).filter(non_empty)
+        .or_else(|| device.ssh_pass.clone().filter(non_empty))
+        .or_else(|| group_cred.as_ref().map(|c| c.password.clone()).filter(non_empty));
+
+    let credential_id = device_cred.or(group_cred).map(|c| c.id);
+    let (user, pass) = resolve_inline_ssh_credentials(store, user, pass, device.vendor.as_deref()).await;
+    ResolvedSshCredentials { user, pass, credential_id }
 }
 
 /// Resolve SSH credentials using the fallback chain:
 /// explicit user/pass -> vendor defaults -> global settings
-pub async fn resolve_ssh_credentials(
+pub async fn resolve_inline_ssh_credentials(
     store: &crate::db::Store,
     ssh_user: Option<String>,
     ssh_pass: Option<String>,
@@ -47,27 +77,87 @@ pub async fn resolve_ssh_credentials(
     (user, pass)
 }
 
-/// Normalize MAC address to lowercase with colons
+/// Parse a MAC address strictly: a 48-bit MAC or a 64-bit EUI-64 / InfiniBand GUID, written as
+/// bare hex, octets separated by `:` or `-` (leading zeros optional), or groups of four hex
+/// digits separated by `.` or `:` (`001c.73aa.bbcc`, `0002:c903:0001:2345`). The separator must be
+/// used throughout. Returns the canonical form, lowercase octets separated by colons.
+pub fn parse_mac(mac: &str) -> Option<String> {
+    let mac = mac.trim();
+    let hex_group = |g: &str, max: usize| !g.is_empty() && g.len() <= max && g.chars().all(|c| c.is_ascii_hexdigit());
+    let octets: Vec<String> = if mac.chars().all(|c| c.is_ascii_hexdigit()) {
+        mac.as_bytes().chunks(2).map(|c| String::from_utf8_lossy(c).into_owned()).collect()
+    } else {
+        let separator = mac.chars().find(|c| !c.is_ascii_hexdigit())?;
+        let groups: Vec<&str> = mac.split(separator).collect();
+        let quads = matches!(groups.len(), 3 | 4) && groups.iter().all(|g| g.len() == 4 && hex_group(g, 4));
+        if quads && (separator == '.' || (separator == ':' && groups.len() == 4)) {
+            groups.iter().flat_map(|g| [g[..2].to_string(), g[2..].to_string()]).collect()
+        } else if matches!(separator, ':' | '-') && groups.iter().all(|g| hex_group(g, 2)) {
+            groups.iter().map(|g| format!("{:0>2}", g)).collect()
+        } else {
+            return None;
+        }
+    };
+    if !matches!(octets.len(), 6 | 8) || octets.iter().any(|o| o.len() != 2) {
+        return None;
+    }
+    Some(octets.join(":").to_lowercase())
+}
+
+/// Normalize a MAC address or EUI-64 to its canonical form (see `parse_mac`). Input that isn't
+/// a MAC is returned trimmed and lowercased, so lookups with it find nothing rather than a
+/// device whose MAC merely has the same hex digits.
 pub fn normalize_mac(mac: &str) -> String {
-    // Remove any existing separators
-    let clean: String = mac
-        .chars()
-        .filter(|c| c.is_ascii_hexdigit())
-        .collect();
+    parse_mac(mac).unwrap_or_else(|| mac.trim().to_lowercase())
+}
 
-    // Convert to lowercase and insert colons
-    if clean.len() != 12 {
-        return mac.to_lowercase();
+/// The 48-bit MAC a canonical EUI-64 was built from by inserting ff:fe between its OUI and
+/// device bytes; other addresses are returned as they are. Devices whose MACs have the same
+/// identity are the same hardware reported in different widths.
+pub fn mac_identity(mac: &str) -> String {
+    let octets: Vec<&str> = mac.split(':').collect();
+    if octets.len() == 8 && octets[3] == "ff" && octets[4] == "fe" {
+        [&octets[..3], &octets[5..]].concat().join(":")
+    } else {
+        mac.to_string()
     }
+}
 
-    clean
-        .chars()
-        .collect::<Vec<_>>()
-        .chunks(2)
-        .map(|c| c.iter().collect::<String>())
-        .collect::<Vec<_>>()
-        .join(":")
-        .to_lowercase()
+/// The canonical MACs that share a MAC's identity: itself and, for a 48-bit MAC or an EUI-64
+/// built from one, the other width
+pub fn mac_forms(mac: &str) -> Vec<String> {
+    let identity = mac_identity(mac);
+    let octets: Vec<&str> = identity.split(':').collect();
+    if octets.len() != 6 {
+        return vec![identity];
+    }
+    let eui64 = [&octets[..3], &["ff", "fe"], &octets[3..]].concat().join(":");
+    vec![identity, eui64]
+}
+
+/// Serial numbers are compared trimmed and ignoring case
+pub fn normalize_serial(serial: &str) -> String {
+    serial.trim().to_uppercase()
+}
+
+/// The serial number carried in a DHCP client identifier (option 61), as dnsmasq reports it:
+/// colon-separated hex bytes. Switches that identify themselves by serial send type 0 followed by
+/// the serial in ASCII; a hardware-address (type 1) or DUID (type 255) identifier carries none.
+/// A client-id that isn't hex is taken as the serial itself.
+pub fn serial_from_client_id(client_id: &str) -> Option<String> {
+    let client_id = client_id.trim();
+    let bytes: Option<Vec<u8>> = client_id.split(':').map(|b| u8::from_str_radix(b, 16).ok().filter(|_| b.len() == 2)).collect();
+    let text = match bytes {
+        Some(bytes) if bytes.len() > 1 => match bytes[0] {
+            0 => String::from_utf8(bytes[1..].to_vec()).ok()?,
+            1 | 255 => return None,
+            _ => String::from_utf8(bytes).ok()?,
+        },
+        _ => client_id.to_string(),
+    };
+    let text = text.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    let printable = !text.is_empty() && text.chars().all(|c| c.is_ascii_graphic());
+    printable.then(|| text.to_string())
 }
 
 /// Convert a MAC address to a config filename
@@ -95,374 +185,6 @@ pub fn is_valid_hostname(hostname: &str) -> bool {
     hostname.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_')
 }
 
-/// Create an SSH session and authenticate with password + keyboard-interactive.
-/// Returns the authenticated Session. Uses the ssh2 crate (libssh2).
-/// This is blocking, so call from a spawn_blocking context.
-pub fn ssh_connect(host: &str, user: &str, pass: &str, timeout_secs: u64) -> Result<ssh2::Session, String> {
-    let addr = format!("{}:22", host);
-    let tcp = TcpStream::connect_timeout(
-        &addr.parse().map_err(|e| format!("Invalid address {}: {}", addr, e))?,
-        Duration::from_secs(timeout_secs),
-    )
-    .map_err(|e| format!("TCP connection failed: {}", e))?;
-
-    tcp.set_read_timeout(Some(Duration::from_secs(timeout_secs)))
-        .ok();
-    tcp.set_write_timeout(Some(Duration::from_secs(timeout_secs)))
-        .ok();
-
-    let mut session = ssh2::Session::new()
-        .map_err(|e| format!("Failed to create SSH session: {}", e))?;
-    session.set_tcp_stream(tcp);
-    session.set_timeout(timeout_secs as u32 * 1000);
-    session.handshake()
-        .map_err(|e| format!("SSH handshake failed: {}", e))?;
-
-    // Try password auth first
-    match session.userauth_password(user, pass) {
-        Ok(_) if session.authenticated() => return Ok(session),
-        _ => {}
-    }
-
-    // Try keyboard-interactive auth (needed for Arista EOS and similar)
-    let mut prompter = PasswordPrompt { password: pass.to_string() };
-    let _ = session.userauth_keyboard_interactive(user, &mut prompter);
-
-    if session.authenticated() {
-        Ok(session)
-    } else {
-        Err("SSH authentication failed: all methods exhausted".to_string())
-    }
-}
-
-/// Connect via SSH and run a single command, returning the output.
-/// This is blocking, so call from a spawn_blocking context.
-pub fn ssh_run_command(host: &str, user: &str, pass: &str, command: &str) -> Result<String, String> {
-    let session = ssh_connect(host, user, pass, 30)?;
-
-    let mut channel = session.channel_session()
-        .map_err(|e| format!("Failed to open channel: {}", e))?;
-
-    channel.exec(command)
-        .map_err(|e| format!("Failed to execute command: {}", e))?;
-
-    let mut stdout = String::new();
-    channel.read_to_string(&mut stdout)
-        .map_err(|e| format!("Failed to read output: {}", e))?;
-
-    let mut stderr = String::new();
-    channel.stderr().read_to_string(&mut stderr)
-        .map_err(|e| format!("Failed to read stderr: {}", e))?;
-
-    channel.wait_close()
-        .map_err(|e| format!("Failed to close channel: {}", e))?;
-
-    // Combine stdout and stderr
-    let output = if !stdout.is_empty() && !stderr.is_empty() {
-        format!("{}\n{}", stdout, stderr)
-    } else if !stderr.is_empty() {
-        stderr
-    } else {
-        stdout
-    };
-
-    Ok(output)
-}
-
-/// Send multi-line commands via an interactive SSH shell (PTY).
-/// This is needed for network devices (EOS, IOS, JunOS) that require
-/// entering config mode interactively rather than via exec.
-pub fn ssh_run_interactive(host: &str, user: &str, pass: &str, commands: &str) -> Result<String, String> {
-    use std::io::Write;
-
-    let session = ssh_connect(host, user, pass, 60)?;
-
-    let mut channel = session.channel_session()
-        .map_err(|e| format!("Failed to open channel: {}", e))?;
-
-    // Request a PTY so EOS gives us an interactive CLI
-    channel.request_pty("xterm", None, None)
-        .map_err(|e| format!("Failed to request PTY: {}", e))?;
-
-    channel.shell()
-        .map_err(|e| format!("Failed to start shell: {}", e))?;
-
-    // Helper: drain any available output from the channel (non-blocking)
-    let drain = |ch: &mut ssh2::Channel, buf: &mut String| {
-        let mut tmp = [0u8; 8192];
-        loop {
-            match ch.read(&mut tmp) {
-                Ok(0) => break,
-                Ok(n) => { buf.push_str(&String::from_utf8_lossy(&tmp[..n])); }
-                Err(_) => break,
-            }
-        }
-    };
-
-    let mut output = String::new();
-
-    // Wait for the initial prompt then drain it
-    std::thread::sleep(Duration::from_secs(2));
-    session.set_blocking(false);
-    drain(&mut channel, &mut output);
-
-    // Disable pager so show commands don't paginate with --More--
-    session.set_blocking(true);
-    channel.write_all(b"terminal length 0\n").ok();
-    channel.flush().ok();
-    std::thread::sleep(Duration::from_millis(500));
-    session.set_blocking(false);
-    drain(&mut channel, &mut output);
-
-    // Send each line, draining output between commands
-    for line in commands.lines() {
-        let line = line.trim();
-        if line.is_empty() || line.starts_with('!') {
-            continue; // Skip empty lines and EOS comments
-        }
-        session.set_blocking(true);
-        channel.write_all(format!("{}\n", line).as_bytes())
-            .map_err(|e| format!("Failed to write command: {}", e))?;
-        channel.flush()
-            .map_err(|e| format!("Failed to flush: {}", e))?;
-        // Give the device time to process and produce output
-        std::thread::sleep(Duration::from_millis(500));
-        session.set_blocking(false);
-        drain(&mut channel, &mut output);
-    }
-
-    // Wait longer for any final output (show commands may take time)
-    for _ in 0..10 {
-        std::thread::sleep(Duration::from_millis(500));
-        let before = output.len();
-        drain(&mut channel, &mut output);
-        // If no new output appeared, we're likely done
-        if output.len() == before {
-            break;
-        }
-    }
-
-    // Send exit to close the session cleanly
-    session.set_blocking(true);
-    channel.write_all(b"exit\n").ok();
-    channel.flush().ok();
-    std::thread::sleep(Duration::from_millis(500));
-    session.set_blocking(false);
-    drain(&mut channel, &mut output);
-
-    session.set_blocking(true);
-    channel.wait_close().ok();
-
-    Ok(output)
-}
-
-/// Async wrapper for ssh_run_interactive - runs in a blocking thread pool
-pub async fn ssh_run_interactive_async(host: &str, user: &str, pass: &str, commands: &str) -> Result<String, String> {
-    let host = host.to_string();
-    let user = user.to_string();
-    let pass = pass.to_string();
-    let commands = commands.to_string();
-
-    tokio::task::spawn_blocking(move || {
-        ssh_run_interactive(&host, &user, &pass, &commands)
-    })
-    .await
-    .map_err(|e| format!("Task join error: {}", e))?
-}
-
-/// Async wrapper for ssh_run_command - runs in a blocking thread pool
-pub async fn ssh_run_command_async(host: &str, user: &str, pass: &str, command: &str) -> Result<String, String> {
-    let host = host.to_string();
-    let user = user.to_string();
-    let pass = pass.to_string();
-    let command = command.to_string();
-
-    tokio::task::spawn_blocking(move || {
-        ssh_run_command(&host, &user, &pass, &command)
-    })
-    .await
-    .map_err(|e| format!("Task join error: {}", e))?
-}
-
-/// Async wrapper for ssh_connect - runs in a blocking thread pool.
-/// Tests SSH connectivity and tries to run uptime commands.
-#[allow(dead_code)]
-pub async fn ssh_test_connection(host: &str, user: &str, pass: &str) -> (bool, Option<String>, Option<String>) {
-    let host = host.to_string();
-    let user = user.to_string();
-    let pass = pass.to_string();
-
-    let result = tokio::task::spawn_blocking(move || -> Result<String, String> {
-        let session = ssh_connect(&host, &user, &pass, 10)?;
-
-        // Try uptime commands
-        for cmd in &["uptime", "show version | include uptime"] {
-            let mut channel = match session.channel_session() {
-                Ok(ch) => ch,
-                Err(e) => return Ok(format!("Connected (session error: {})", e)),
-            };
-
-            if channel.exec(cmd).is_ok() {
-                let mut output = String::new();
-                if channel.read_to_string(&mut output).is_ok() && !output.trim().is_empty() {
-                    let _ = channel.wait_close();
-                    return Ok(output.trim().to_string());
-                }
-            }
-            let _ = channel.wait_close();
-        }
-
-        Ok("Connected (uptime command not available)".to_string())
-    })
-    .await;
-
-    match result {
-        Ok(Ok(uptime)) => (true, Some(uptime), None),
-        Ok(Err(e)) => (false, None, Some(e)),
-        Err(e) => (false, None, Some(format!("Task error: {}", e))),
-    }
-}
-
-/// Result from probing a device via SSH
-pub struct DeviceProbeResult {
-    pub uptime: Option<String>,
-    pub hostname: Option<String>,
-    pub version: Option<String>,
-    pub interfaces: Option<String>,
-}
-
-/// Execute a command on an existing SSH session, returning trimmed output or None
-fn ssh_exec_on_session(session: &ssh2::Session, cmd: &str) -> Option<String> {
-    let mut channel = session.channel_session().ok()?;
-    if channel.exec(cmd).is_err() {
-        let _ = channel.wait_close();
-        return None;
-    }
-    let mut output = String::new();
-    if channel.read_to_string(&mut output).is_ok() && !output.trim().is_empty() {
-        let _ = channel.wait_close();
-        let trimmed = output.trim().to_string();
-        // Skip outputs that look like error messages
-        if trimmed.starts_with('%') || trimmed.contains("Invalid input") || trimmed.contains("not found") {
-            return None;
-        }
-        Some(trimmed)
-    } else {
-        let _ = channel.wait_close();
-        None
-    }
-}
-
-/// Try multiple commands on a session, returning the first successful output
-fn try_first(session: &ssh2::Session, commands: &[&str]) -> Option<String> {
-    for cmd in commands {
-        if let Some(output) = ssh_exec_on_session(session, cmd) {
-            return Some(output);
-        }
-    }
-    None
-}
-
-/// Truncate output to a maximum number of lines
-fn truncate_lines(s: &str, max_lines: usize) -> String {
-    let lines: Vec<&str> = s.lines().collect();
-    if lines.len() <= max_lines {
-        s.to_string()
-    } else {
-        let truncated: Vec<&str> = lines[..max_lines].to_vec();
-        format!("{}\n... ({} more lines)", truncated.join("\n"), lines.len() - max_lines)
-    }
-}
-
-/// Probe a device via SSH with vendor-aware commands.
-/// Connects once and runs multiple commands on the same session.
-/// Returns (connected, probe_result, error)
-pub async fn ssh_probe_device(
-    host: &str,
-    user: &str,
-    pass: &str,
-    vendor_hint: Option<&str>,
-) -> (bool, DeviceProbeResult, Option<String>) {
-    let host = host.to_string();
-    let user = user.to_string();
-    let pass = pass.to_string();
-    let vendor_hint = vendor_hint.map(|s| s.to_string());
-
-    let result = tokio::task::spawn_blocking(move || -> Result<DeviceProbeResult, String> {
-        let session = ssh_connect(&host, &user, &pass, 15)?;
-
-        let vendor_lower = vendor_hint.as_deref().unwrap_or("").to_lowercase();
-        let is_linux = matches!(
-            vendor_lower.as_str(),
-            "opengear" | "raspberry pi" | "linux" | "frr" | "gobgp"
-        );
-
-        let (uptime, hostname, version, interfaces) = if is_linux {
-            // Linux-style commands
-            let uptime = try_first(&session, &["uptime"]);
-            let hostname = try_first(&session, &["hostname"]);
-            let version = try_first(&session, &["uname -a", "cat /etc/os-release"]);
-            let interfaces = try_first(&session, &["ip -brief addr show", "ip addr show"]);
-            (uptime, hostname, version, interfaces)
-        } else {
-            // Network device commands (Cisco, Arista, Juniper, etc.)
-            let uptime = try_first(&session, &[
-                "show version | include uptime",
-                "show version | match uptime",
-                "show system uptime",
-                "uptime",
-            ]);
-            let hostname = try_first(&session, &[
-                "show hostname",
-                "show running-config | include hostname",
-                "hostname",
-            ]);
-            let version = try_first(&session, &[
-                "show version",
-            ]);
-            let interfaces = try_first(&session, &[
-                "show ip interface brief",
-                "show interfaces terse",
-                "show interface brief",
-                "ip -brief addr show",
-            ]);
-            (uptime, hostname, version, interfaces)
-        };
-
-        Ok(DeviceProbeResult {
-            uptime,
-            hostname,
-            version: version.map(|v| truncate_lines(&v, 20)),
-            interfaces: interfaces.map(|i| truncate_lines(&i, 30)),
-        })
-    })
-    .await;
-
-    match result {
-        Ok(Ok(probe)) => (true, probe, None),
-        Ok(Err(e)) => (
-            false,
-            DeviceProbeResult {
-                uptime: None,
-                hostname: None,
-                version: None,
-                interfaces: None,
-            },
-            Some(e),
-        ),
-        Err(e) => (
-            false,
-            DeviceProbeResult {
-                uptime: None,
-                hostname: None,
-                version: None,
-                interfaces: None,
-            },
-            Some(format!("Task error: {}", e)),
-        ),
-    }
-}
-
 /// Look up vendor by MAC address OUI (first 3 bytes) against known vendor prefixes.
 /// Returns the vendor ID if a match is found.
 pub fn lookup_vendor_by_mac(mac: &str, vendors: &[crate::models::Vendor]) -> Option<String> {
@@ -631,8 +353,8 @@ pub fn enrich_lease_with_dhcp_info(lease: &mut crate::models::Lease) {
         // Derive model: prefer cpewan_class, fall back to user_class
         lease.model = info.cpewan_class.or(info.user_class);
 
-        // Derive serial number: prefer cpewan_serial, fall back to client_id
-        lease.serial_number = info.cpewan_serial.or(info.client_id);
+        // Derive serial number: prefer cpewan_serial, fall back to the one in the client-id
+        lease.serial_number = info.cpewan_serial.or_else(|| info.client_id.as_deref().and_then(serial_from_client_id));
     }
 }
 
@@ -836,132 +558,1722 @@ pub fn next_available_prefix(
     }
 }
 
-/// Find next available IP address in a prefix.
-/// `allocated` is a sorted list of address_int for existing IPs.
-/// Skips network address (first) and broadcast address (last) for prefixes < /31.
-pub fn next_available_ip(
+/// Split the unallocated space of a prefix into the largest aligned CIDR blocks.
+/// `allocated` holds (network, broadcast) ranges in any order; they may overlap or extend past the prefix.
+/// Returns (network, prefix_len) pairs in address order.
+pub fn free_cidr_blocks(
     prefix_network: u32,
     prefix_broadcast: u32,
-    prefix_len: u8,
-    allocated: &[u32],
-) -> Option<u32> {
-    let (start, end) = if prefix_len >= 31 {
-        // /31 and /32: use all addresses (RFC 3021)
-        (prefix_network, prefix_broadcast)
-    } else {
-        // Skip network and broadcast addresses
-        (prefix_network + 1, prefix_broadcast - 1)
-    };
+    allocated: &[(u32, u32)],
+) -> Vec<(u32, u8)> {
+    let mut ranges: Vec<(u32, u32)> = allocated.to_vec();
+    ranges.sort_unstable();
+
+    // Collect the gaps between allocations, as inclusive u64 ranges to avoid overflow at 255.255.255.255
+    let mut gaps = Vec::new();
+    let mut next_free = prefix_network as u64;
+    for (net, bcast) in ranges {
+        if (net as u64) > next_free {
+            gaps.push((next_free, (net as u64 - 1).min(prefix_broadcast as u64)));
+        }
+        next_free = next_free.max(bcast as u64 + 1);
+        if next_free > prefix_broadcast as u64 {
+            break;
+        }
+    }
+    if next_free <= prefix_broadcast as u64 {
+        gaps.push((next_free, prefix_broadcast as u64));
+    }
 
-    let alloc_set: std::collections::HashSet<u32> = allocated.iter().copied().collect();
-    for candidate in start..=end {
-        if !alloc_set.contains(&candidate) {
-            return Some(candidate);
+    let mut blocks = Vec::new();
+    for (mut start, end) in gaps {
+        if start > end {
+            continue;
+        }
+        while start <= end {
+            // Largest block aligned at `start` that still fits in the gap
+            let align = if start == 0 { 1u64 << 32 } else { 1u64 << start.trailing_zeros() };
+            let mut size = align;
+            while start + size - 1 > end {
+                size >>= 1;
+            }
+            blocks.push((start as u32, (32 - size.trailing_zeros()) as u8));
+            start += size;
         }
     }
-    None
+    blocks
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Reverse DNS name of an IPv4 address, split into its /24 in-addr.arpa zone and the
+/// record label within it, e.g. 10.1.2.3 -> ("2.1.10.in-addr.arpa", "3")
+pub fn ptr_zone_and_label(addr: u32) -> (String, String) {
+    let [a, b, c, d] = addr.to_be_bytes();
+    (format!("{}.{}.{}.in-addr.arpa", c, b, a), d.to_string())
+}
 
-    #[test]
-    fn test_normalize_mac() {
-        assert_eq!(normalize_mac("AA:BB:CC:DD:EE:FF"), "aa:bb:cc:dd:ee:ff");
-        assert_eq!(normalize_mac("AA-BB-CC-DD-EE-FF"), "aa:bb:cc:dd:ee:ff");
-        assert_eq!(normalize_mac("AABBCCDDEEFF"), "aa:bb:cc:dd:ee:ff");
-        assert_eq!(normalize_mac("aa:bb:cc:dd:ee:ff"), "aa:bb:cc:dd:ee:ff");
+/// Ensure a DNS name is fully qualified (ends with a dot)
+pub fn fqdn(name: &str) -> String {
+    if name.ends_with('.') {
+        name.to_string()
+    } else {
+        format!("{}.", name)
     }
+}
 
-    #[test]
-    fn test_mac_to_config_filename() {
-        assert_eq!(mac_to_config_filename("00:1c:73:aa:bb:cc"), "00_1c_73_aa_bb_cc.cfg");
+/// Parse ARP/ND table output into neighbor entries. Vendor formats differ in column order, so each
+/// line is scanned for an IP address and a MAC (colon, dash, or dotted notation); the interface is
+/// the token after `dev` (Linux `ip neigh`) or else the last interface-looking token. Lines without
+/// both an address and a MAC (headers, incomplete entries) are skipped.
+pub fn parse_neighbor_table(output: &str) -> Vec<crate::models::NeighborEntry> {
+    fn is_mac(token: &str) -> bool {
+        let groups: Vec<&str> = token.split([':', '-', '.']).collect();
+        let sizes_ok = match groups.len() {
+            6 => groups.iter().all(|g| (1..=2).contains(&g.len())),
+            3 => groups.iter().all(|g| g.len() == 4),
+            _ => false,
+        };
+        sizes_ok && groups.iter().all(|g| g.chars().all(|c| c.is_ascii_hexdigit()))
     }
 
-    #[test]
-    fn test_go_template_simple() {
-        let go = "hostname {{.Hostname}}\nip {{.IP}}";
-        let tera = convert_go_template_to_tera(go);
-        assert_eq!(tera, "hostname {{Hostname}}\nip {{IP}}");
-    }
+    let mut entries = Vec::new();
+    for line in output.lines() {
+        let tokens: Vec<&str> = line
+            .split_whitespace()
+            .map(|t| t.trim_end_matches(','))
+            .collect();
+        let address = tokens.iter().find_map(|t| {
+            // Strip a zone/scope suffix such as fe80::1%eth0
+            t.split('%').next().and_then(|a| a.parse::<std::net::IpAddr>().ok())
+        });
+        let mac = tokens.iter().find(|t| is_mac(t));
+        let (address, mac) = match (address, mac) {
+            (Some(a), Some(m)) => (a, m),
+            _ => continue,
+        };
 
-    #[test]
-    fn test_tera_passthrough() {
-        let tera = "hostname {{Hostname}}\nip {{IP}}";
-        assert_eq!(convert_go_template_to_tera(tera), tera);
+        let interface = tokens
+            .iter()
+            .position(|t| *t == "dev")
+            .and_then(|i| tokens.get(i + 1))
+            .or_else(|| {
+                tokens.iter().rev().find(|t| {
+                    t.starts_with(|c: char| c.is_ascii_alphabetic())
+                        && t.chars().any(|c| c.is_ascii_digit())
+                        && !is_mac(t)
+                        && t.parse::<std::net::IpAddr>().is_err()
+                })
+            })
+            .map(|t| t.to_string());
+
+        entries.push(crate::models::NeighborEntry {
+            address: address.to_string(),
+            mac: normalize_mac(mac),
+            interface,
+        });
     }
+    entries
+}
 
-    #[test]
-    fn test_go_template_if() {
-        let go = "{{if .Vendor}}has vendor{{else}}no vendor{{end}}";
-        let tera = convert_go_template_to_tera(go);
-        assert_eq!(tera, "{% if Vendor %}has vendor{% else %}no vendor{% endif %}");
-    }
+/// Parse a speed such as `1Gb/s`, `100G`, `10Gbps`, `1000mbps` or `25000Mb/s` into Mbps
+fn parse_speed_mbps(token: &str) -> Option<i64> {
+    let lower = token.trim_end_matches(',').to_lowercase();
+    let digits_end = lower.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let value: f64 = lower[..digits_end].parse().ok()?;
+    let multiplier = match lower[digits_end..].trim_end_matches("/s").trim_end_matches("bps") {
+        "g" | "gb" => 1000.0,
+        "m" | "mb" => 1.0,
+        _ => return None,
+    };
+    Some((value * multiplier) as i64)
+}
 
-    #[test]
-    fn test_go_template_range() {
-        let go = "{{range .Items}}item{{end}}";
-        let tera = convert_go_template_to_tera(go);
-        assert_eq!(tera, "{% for item in Items %}item{% endfor %}");
+/// Parse interface details from vendor output: `show interfaces` on Cisco/Arista
+/// ("Ethernet1 is up, line protocol is up") and Juniper ("Physical interface: ge-0/0/0, ..."),
+/// or the JSON from Linux `ip -j addr show` (FRR, SONiC, Cumulus). Only the first IPv4
+/// address of each interface is kept.
+pub fn parse_interface_inventory(output: &str) -> Vec<crate::models::InterfaceInventoryEntry> {
+    use crate::models::{interface_status, InterfaceInventoryEntry};
+
+    let trimmed = output.trim_start();
+    if trimmed.starts_with('[') {
+        let Ok(serde_json::Value::Array(links)) = serde_json::from_str::<serde_json::Value>(trimmed) else {
+            return Vec::new();
+        };
+        return links
+            .iter()
+            .filter_map(|link| {
+                let name = link.get("ifname")?.as_str()?;
+                if name == "lo" {
+                    return None;
+                }
+                let admin_up = link
+                    .get("flags")
+                    .and_then(|f| f.as_array())
+                    .is_some_and(|flags| flags.iter().any(|f| f == "UP"));
+                let oper_up = link.get("operstate").and_then(|s| s.as_str()) == Some("UP");
+                let status = match (admin_up, oper_up) {
+                    (false, _) => interface_status::ADMIN_DOWN,
+                    (true, true) => interface_status::UP,
+                    (true, false) => interface_status::DOWN,
+                };
+                let ip = link
+                    .get("addr_info")
+                    .and_then(|a| a.as_array())
+                    .and_then(|addrs| {
+                        addrs.iter().find(|a| a.get("family").and_then(|f| f.as_str()) == Some("inet"))
+                    })
+                    .and_then(|a| Some(format!("{}/{}", a.get("local")?.as_str()?, a.get("prefixlen")?.as_i64()?)))
+                    .unwrap_or_default();
+                Some(InterfaceInventoryEntry {
+                    name: name.to_string(),
+                    status: status.to_string(),
+                    speed_mbps: None,
+                    description: link.get("ifalias").and_then(|d| d.as_str()).unwrap_or_default().to_string(),
+                    mac: link.get("address").and_then(|m| m.as_str()).map(normalize_mac).unwrap_or_default(),
+                    ip,
+                    vrf: None,
+                })
+            })
+            .collect();
     }
 
-    #[test]
-    fn test_is_valid_ipv4() {
-        assert!(is_valid_ipv4("192.168.1.1"));
-        assert!(is_valid_ipv4("0.0.0.0"));
-        assert!(is_valid_ipv4("255.255.255.255"));
-        assert!(!is_valid_ipv4(""));
-        assert!(!is_valid_ipv4("not-an-ip"));
-        assert!(!is_valid_ipv4("256.1.1.1"));
-        assert!(!is_valid_ipv4("1.2.3"));
-        assert!(!is_valid_ipv4("1.2.3.4.5"));
-        assert!(!is_valid_ipv4("1.2.3.-1"));
-        assert!(!is_valid_ipv4("; rm -rf /"));
+    fn link_status(text: &str) -> &'static str {
+        let text = text.to_lowercase();
+        if text.contains("administratively down") || text.contains("disabled") {
+            interface_status::ADMIN_DOWN
+        } else if let Some(proto) = text.split("line protocol is ").nth(1) {
+            if proto.starts_with("up") { interface_status::UP } else { interface_status::DOWN }
+        } else if text.contains("link is up") || text.contains(" is up") {
+            interface_status::UP
+        } else {
+            interface_status::DOWN
+        }
     }
 
-    #[test]
-    fn test_is_valid_hostname() {
-        assert!(is_valid_hostname("switch-01"));
-        assert!(is_valid_hostname("router.lab.local"));
-        assert!(is_valid_hostname("my_host"));
-        assert!(!is_valid_hostname(""));
-        assert!(!is_valid_hostname("host name")); // spaces
-        assert!(!is_valid_hostname("host;rm")); // semicolon
-        assert!(!is_valid_hostname("../etc/passwd")); // path traversal
-        assert!(!is_valid_hostname("host\nname")); // newline
-    }
+    let mut entries: Vec<InterfaceInventoryEntry> = Vec::new();
+    for line in output.lines() {
+        let body = line.trim();
+        if let Some(rest) = body.strip_prefix("Physical interface: ") {
+            let name = rest.split(',').next().unwrap_or_default().trim();
+            entries.push(InterfaceInventoryEntry {
+                name: name.to_string(),
+                status: link_status(rest).to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) && body.contains(" is ") {
+            let name = body.split_whitespace().next().unwrap_or_default();
+            entries.push(InterfaceInventoryEntry {
+                name: name.to_string(),
+                status: link_status(body).to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(entry) = entries.last_mut() else {
+            continue;
+        };
 
-    #[test]
-    fn test_parse_cidr() {
-        let (net, bcast, len) = parse_cidr("10.0.0.0/8").unwrap();
-        assert_eq!(net, 0x0A000000);
-        assert_eq!(bcast, 0x0AFFFFFF);
-        assert_eq!(len, 8);
+        if let Some(desc) = body.strip_prefix("Description: ") {
+            if entry.description.is_empty() {
+                entry.description = desc.trim().to_string();
+            }
+        } else if let Some(addr) = body.strip_prefix("Internet address is ") {
+            if entry.ip.is_empty() {
+                entry.ip = addr.split_whitespace().next().unwrap_or_default().to_string();
+            }
+        } else if body.contains("Local: ") && entry.ip.is_empty() {
+            // Juniper: "Destination: 10.0.0.0/31, Local: 10.0.0.1"
+            let local = body.split("Local: ").nth(1).and_then(|l| l.split([',', ' ']).next()).unwrap_or_default();
+            if local.parse::<std::net::Ipv4Addr>().is_ok() {
+                let prefix_len = body
+                    .split("Destination: ")
+                    .nth(1)
+                    .and_then(|d| d.split([',', ' ']).next())
+                    .and_then(|d| d.split_once('/'))
+                    .map(|(_, len)| len.to_string());
+                entry.ip = match prefix_len {
+                    Some(len) => format!("{}/{}", local, len),
+                    None => local.to_string(),
+                };
+            }
+        }
 
-        let (net, bcast, len) = parse_cidr("192.168.1.0/24").unwrap();
-        assert_eq!(u32_to_ipv4(net), "192.168.1.0");
-        assert_eq!(u32_to_ipv4(bcast), "192.168.1.255");
-        assert_eq!(len, 24);
+        if entry.mac.is_empty() {
+            let mac = body
+                .split("address is ")
+                .nth(1)
+                .or_else(|| body.split("Current address: ").nth(1))
+                .and_then(|m| m.split([',', ' ']).next());
+            if let Some(mac) = mac {
+                entry.mac = normalize_mac(mac);
+            }
+        }
+
+        if entry.speed_mbps.is_none() {
+            let tokens: Vec<&str> = body.split_whitespace().collect();
+            entry.speed_mbps = tokens
+                .windows(3)
+                .find(|w| w[0] == "BW" && w[2].to_lowercase().starts_with("kbit"))
+                .and_then(|w| w[1].parse::<i64>().ok())
+                .map(|kbit| kbit / 1000)
+                .or_else(|| {
+                    tokens
+                        .windows(2)
+                        .find(|w| w[0] == "Speed:")
+                        .and_then(|w| parse_speed_mbps(w[1]))
+                })
+                .or_else(|| tokens.iter().find(|t| t.contains("b/s")).and_then(|t| parse_speed_mbps(t)));
+        }
     }
+    entries
+}
 
-    #[test]
-    fn test_parse_ipv4_to_u32() {
-        assert_eq!(parse_ipv4_to_u32("10.0.0.1").unwrap(), 0x0A000001);
-        assert_eq!(parse_ipv4_to_u32("255.255.255.255").unwrap(), 0xFFFFFFFF);
-        assert_eq!(parse_ipv4_to_u32("0.0.0.0").unwrap(), 0);
-        assert!(parse_ipv4_to_u32("invalid").is_err());
+/// Map interfaces to their VRF from vendor output: Arista `show vrf | json`, Cisco
+/// `show vrf interface` / `show ip vrf interfaces` tables, or Linux `ip -j -d link show`
+/// (interfaces enslaved to a VRF device). The default VRF maps to an empty name; interfaces
+/// not listed are in the default VRF. Returns None when the output isn't recognized, so an
+/// error message isn't mistaken for "everything in the default VRF".
+pub fn parse_interface_vrfs(output: &str) -> Option<HashMap<String, String>> {
+    fn vrf_name(name: &str) -> String {
+        if name.eq_ignore_ascii_case("default") || name == "--" { String::new() } else { name.to_string() }
     }
 
-    #[test]
-    fn test_u32_to_ipv4_roundtrip() {
-        assert_eq!(u32_to_ipv4(0x0A000001), "10.0.0.1");
-        assert_eq!(u32_to_ipv4(0xC0A80164), "192.168.1.100");
+    let mut vrfs = HashMap::new();
+    let trimmed = output.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        match serde_json::from_str::<serde_json::Value>(trimmed).ok()? {
+            serde_json::Value::Object(obj) => {
+                for (name, vrf) in obj.get("vrfs")?.as_object()? {
+                    let interfaces = vrf.get("interfaces").and_then(|i| i.as_array()).into_iter().flatten();
+                    for interface in interfaces.filter_map(|i| i.as_str()) {
+                        vrfs.insert(interface.to_string(), vrf_name(name));
+                    }
+                }
+            }
+            serde_json::Value::Array(links) => {
+                let vrf_devices: HashSet<&str> = links
+                    .iter()
+                    .filter(|l| l.pointer("/linkinfo/info_kind").and_then(|k| k.as_str()) == Some("vrf"))
+                    .filter_map(|l| l.get("ifname")?.as_str())
+                    .collect();
+                for link in &links {
+                    let Some(name) = link.get("ifname").and_then(|n| n.as_str()) else {
+                        continue;
+                    };
+                    let master = link.get("master").and_then(|m| m.as_str()).filter(|m| vrf_devices.contains(m));
+                    vrfs.insert(name.to_string(), master.map(vrf_name).unwrap_or_default());
+                }
+            }
+            _ => return None,
+        }
+        return Some(vrfs);
     }
 
-    #[test]
-    fn test_next_available_prefix() {
-        // 10.0.0.0/8, allocate /24s
+    let mut columns: Option<(usize, usize)> = None;
+    for line in output.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let interface_col = tokens.iter().position(|t| t.eq_ignore_ascii_case("interface"));
+        let vrf_col = tokens.iter().position(|t| t.to_uppercase().starts_with("VRF"));
+        if let (Some(interface_col), Some(vrf_col)) = (interface_col, vrf_col) {
+            columns = Some((interface_col, vrf_col));
+            continue;
+        }
+        let Some((interface_col, vrf_col)) = columns else {
+            continue;
+        };
+        if let (Some(interface), Some(vrf)) = (tokens.get(interface_col), tokens.get(vrf_col)) {
+            vrfs.insert(interface.to_string(), vrf_name(vrf));
+        }
+    }
+    columns.map(|_| vrfs)
+}
+
+/// Port speeds (Mbps) by vendor port name from a device model's chassis layout
+pub fn layout_port_speeds(layout_json: &str) -> HashMap<String, i64> {
+    let Ok(rows) = serde_json::from_str::<Vec<serde_json::Value>>(layout_json) else {
+        return HashMap::new();
+    };
+    rows.iter()
+        .filter_map(|row| row.get("sections")?.as_array())
+        .flatten()
+        .filter_map(|section| section.get("ports")?.as_array())
+        .flatten()
+        .filter_map(|port| {
+            let name = port.get("vendor_port_name")?.as_str().filter(|n| !n.is_empty())?;
+            let speed = port.get("speed")?.as_i64().filter(|s| *s > 0)?;
+            Some((name.to_string(), speed))
+        })
+        .collect()
+}
+
+/// Names of the ports in a device model layout with the given role, such as the `power` supplies
+/// or `console` ports, in layout order
+pub fn layout_ports_with_role(layout_json: &str, role: &str) -> Vec<String> {
+    let Ok(rows) = serde_json::from_str::<Vec<serde_json::Value>>(layout_json) else {
+        return Vec::new();
+    };
+    rows.iter()
+        .filter_map(|row| row.get("sections")?.as_array())
+        .flatten()
+        .filter_map(|section| section.get("ports")?.as_array())
+        .flatten()
+        .filter(|port| port.get("role").and_then(|r| r.as_str()) == Some(role))
+        .filter_map(|port| port.get("vendor_port_name")?.as_str().filter(|n| !n.is_empty()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Compare port assignments with collected interfaces. Descriptions are only compared when the
+/// assignment sets one, VRFs only when the interface's VRF was collected, speeds only when the
+/// model layout and the interface both report one, and connected ports are expected to be up.
+pub fn compare_interface_intent(
+    assignments: &[crate::models::PortAssignment],
+    interfaces: &[crate::models::DeviceInterface],
+    port_speeds: &HashMap<String, i64>,
+) -> Vec<crate::models::InterfaceMismatch> {
+    use crate::models::{interface_status, InterfaceMismatch};
+
+    let mut mismatches = Vec::new();
+    let mut push = |port: &str, field: &str, expected: String, actual: String| {
+        mismatches.push(InterfaceMismatch {
+            port_name: port.to_string(),
+            field: field.to_string(),
+            expected,
+            actual,
+        });
+    };
+    let vrf_display = |vrf: &str| if vrf.is_empty() { "default".to_string() } else { vrf.to_string() };
+
+    for assignment in assignments {
+        let port = assignment.port_name.as_str();
+        let Some(interface) = interfaces.iter().find(|i| i.name.eq_ignore_ascii_case(port)) else {
+            push(port, "missing", "present".to_string(), "absent".to_string());
+            continue;
+        };
+
+        if assignment.remote_device_id.is_some() && interface.status != interface_status::UP {
+            push(port, "status", interface_status::UP.to_string(), interface.status.clone());
+        }
+
+        if let Some(expected) = assignment.description.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+            if expected != interface.description.trim() {
+                push(port, "description", expected.to_string(), interface.description.clone());
+            }
+        }
+
+        if let Some(actual) = interface.vrf.as_deref() {
+            let expected = assignment.vrf_name.as_deref().unwrap_or_default();
+            let expected = if expected.eq_ignore_ascii_case("default") { "" } else { expected };
+            if !expected.eq_ignore_ascii_case(actual) {
+                push(port, "vrf", vrf_display(expected), vrf_display(actual));
+            }
+        }
+
+        let expected_speed = port_speeds
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(port))
+            .map(|(_, speed)| *speed);
+        if let (Some(expected), Some(actual)) = (expected_speed, interface.speed_mbps) {
+            if expected != actual {
+                push(port, "speed", expected.to_string(), actual.to_string());
+            }
+        }
+    }
+    mismatches
+}
+
+/// Classify a config statement (a stanza's opening line, or a `set` path) into a section kind
+fn config_section_kind(statement: &str) -> &'static str {
+    use crate::models::config_section_kind as kind;
+
+    let lower = statement.trim().to_lowercase();
+    let words: Vec<&str> = lower.split_whitespace().collect();
+    let first = words.first().copied().unwrap_or_default();
+    let second = words.get(1).copied().unwrap_or_default();
+
+    if matches!(first, "interface" | "interfaces") {
+        kind::INTERFACES
+    } else if words.contains(&"bgp") {
+        kind::BGP
+    } else if first == "access-list" || first == "firewall" || second == "access-list" {
+        kind::ACL
+    } else if matches!(
+        first,
+        "router" | "routing-options" | "routing-instances" | "protocols" | "route-map" | "policy-options" | "vrf" | "mpls"
+    ) || (matches!(first, "ip" | "ipv6")
+        && matches!(second, "route" | "routing" | "prefix-list" | "vrf" | "community-list" | "as-path"))
+    {
+        kind::ROUTING
+    } else if matches!(first, "vlan" | "vlans" | "spanning-tree" | "mlag" | "lacp" | "switch-options" | "bridge-domains") {
+        kind::SWITCHING
+    } else if matches!(
+        first,
+        "hostname" | "username" | "aaa" | "ntp" | "logging" | "snmp-server" | "snmp" | "system" | "management"
+            | "banner" | "clock" | "service" | "daemon" | "chassis" | "tacacs-server" | "radius-server" | "boot"
+            | "version" | "lldp" | "sflow" | "dns" | "alias"
+    ) || (first == "ip" && matches!(second, "name-server" | "domain-name" | "domain" | "host"))
+    {
+        kind::SYSTEM
+    } else {
+        kind::OTHER
+    }
+}
+
+/// Lexer hints for a vendor's config dialect. For vendors without a known dialect (and for
+/// Junos, which can be shown either way) the block style is detected from the content: a line
+/// opening a brace block means a hierarchy, mostly `set` lines a flat set config.
+pub fn config_syntax(vendor_name: &str, content: &str) -> crate::models::ConfigSyntax {
+    use crate::models::{config_block_style, ConfigSyntax};
+
+    let statements: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with(['!', '#']))
+        .collect();
+    let detected_style = if statements.iter().any(|l| l.ends_with('{')) {
+        config_block_style::BRACES
+    } else if !statements.is_empty()
+        && statements.iter().filter(|l| l.starts_with("set ") || l.starts_with("delete ")).count() * 2 > statements.len()
+    {
+        config_block_style::SET
+    } else {
+        config_block_style::INDENT
+    };
+    let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let indent_keywords = strings(&[
+        "interface", "router bgp", "router ospf", "ip route", "vrf instance", "route-map", "ip prefix-list",
+        "ip access-list", "vlan", "spanning-tree", "hostname", "username", "aaa", "ntp", "logging", "snmp-server",
+        "management",
+    ]);
+
+    let name = vendor_name.to_lowercase();
+    if name.contains("arista") {
+        ConfigSyntax {
+            dialect: "eos".to_string(),
+            block_style: config_block_style::INDENT.to_string(),
+            comment_prefixes: strings(&["!"]),
+            negation_prefix: Some("no ".to_string()),
+            keywords: indent_keywords,
+            interface_pattern: r"\b(Ethernet|Management|Port-Channel|Vlan|Loopback|Vxlan)\d+(/\d+)*(\.\d+)?\b".to_string(),
+        }
+    } else if name.contains("cisco") {
+        ConfigSyntax {
+            dialect: "ios".to_string(),
+            block_style: config_block_style::INDENT.to_string(),
+            comment_prefixes: strings(&["!"]),
+            negation_prefix: Some("no ".to_string()),
+            keywords: indent_keywords,
+            interface_pattern: r"\b(GigabitEthernet|TenGigabitEthernet|FortyGigabitEthernet|HundredGigE|Ethernet|mgmt|Port-channel|Vlan|Loopback|nve)\d+(/\d+)*(\.\d+)?\b".to_string(),
+        }
+    } else if name.contains("juniper") {
+        let set = detected_style == config_block_style::SET;
+        ConfigSyntax {
+            dialect: "junos".to_string(),
+            block_style: if set { config_block_style::SET } else { config_block_style::BRACES }.to_string(),
+            comment_prefixes: strings(&["#", "/*"]),
+            negation_prefix: set.then(|| "delete ".to_string()),
+            keywords: strings(&[
+                "system", "chassis", "interfaces", "snmp", "routing-options", "protocols", "policy-options",
+                "firewall", "routing-instances", "vlans",
+            ]),
+            interface_pattern: r"\b((ge|xe|et|mge)-\d+/\d+/\d+|(ae|lo|irb|em|fxp|vme)\d*)(\.\d+)?\b".to_string(),
+        }
+    } else {
+        ConfigSyntax {
+            dialect: "generic".to_string(),
+            block_style: detected_style.to_string(),
+            comment_prefixes: strings(&["!", "#"]),
+            negation_prefix: (detected_style == config_block_style::INDENT).then(|| "no ".to_string()),
+            keywords: indent_keywords,
+            interface_pattern: r"\b(eth|swp|Ethernet|bond|lo|vlan|br)\d+(\.\d+)?\b".to_string(),
+        }
+    }
+}
+
+/// Split config text into collapsible sections for the given block style. Top-level stanzas
+/// become sections (braces configs also get their second-level blocks as children, `set`
+/// configs are grouped by their first two path elements). Consecutive one-line statements of
+/// the same kind, like `ntp server` lines, are merged into one section.
+pub fn parse_config_sections(content: &str, block_style: &str) -> Vec<crate::models::ConfigSection> {
+    use crate::models::{config_block_style, ConfigSection};
+
+    fn section(title: &str, kind: &str, line: usize) -> ConfigSection {
+        ConfigSection {
+            kind: kind.to_string(),
+            title: title.to_string(),
+            start_line: line,
+            end_line: line,
+            children: Vec::new(),
+        }
+    }
+    let is_comment = |line: &str| line.starts_with(['!', '#']) || line.starts_with("/*");
+
+    // (section, whether it spans more than its opening line)
+    let mut sections: Vec<(ConfigSection, bool)> = Vec::new();
+    match block_style {
+        config_block_style::BRACES => {
+            let mut depth = 0usize;
+            let mut open: Vec<ConfigSection> = Vec::new();
+            for (idx, raw) in content.lines().enumerate() {
+                let line_no = idx + 1;
+                let line = raw.trim();
+                if line.is_empty() || is_comment(line) {
+                    continue;
+                }
+                if let Some(title) = line.strip_suffix('{').map(str::trim) {
+                    match depth {
+                        0 => open.push(section(title, config_section_kind(title), line_no)),
+                        1 => {
+                            let path = format!("{} {}", open.last().map(|s| s.title.as_str()).unwrap_or_default(), title);
+                            open.push(section(title, config_section_kind(&path), line_no));
+                        }
+                        _ => {}
+                    }
+                    depth += 1;
+                } else if line.starts_with('}') {
+                    depth = depth.saturating_sub(1);
+                    if depth <= 1 {
+                        if let Some(mut closed) = open.pop() {
+                            closed.end_line = line_no;
+                            match open.last_mut() {
+                                Some(parent) => parent.children.push(closed),
+                                None => sections.push((closed, true)),
+                            }
+                        }
+                    }
+                } else if depth == 0 {
+                    let title = line.trim_end_matches(';');
+                    sections.push((section(title, config_section_kind(title), line_no), false));
+                }
+            }
+        }
+        config_block_style::SET => {
+            for (idx, raw) in content.lines().enumerate() {
+                let line_no = idx + 1;
+                let words: Vec<&str> = raw.split_whitespace().collect();
+                if words.len() < 2 || !matches!(words[0], "set" | "delete") {
+                    continue;
+                }
+                let (top, child) = (words[1], words.get(2).copied());
+                if sections.last().is_none_or(|(s, _)| s.title != top) {
+                    sections.push((section(top, config_section_kind(top), line_no), true));
+                }
+                let (current, _) = sections.last_mut().expect("section was just pushed");
+                current.end_line = line_no;
+                if let Some(child) = child {
+                    match current.children.last_mut() {
+                        Some(last) if last.title == child => last.end_line = line_no,
+                        _ => {
+                            let kind = config_section_kind(&format!("{} {}", top, child));
+                            current.children.push(section(child, kind, line_no));
+                        }
+                    }
+                }
+            }
+        }
+        _ => {
+            for (idx, raw) in content.lines().enumerate() {
+                let line_no = idx + 1;
+                let line = raw.trim_end();
+                if line.trim().is_empty() || is_comment(line.trim_start()) {
+                    continue;
+                }
+                if !line.starts_with(char::is_whitespace) {
+                    sections.push((section(line, config_section_kind(line), line_no), false));
+                } else if let Some((current, spans)) = sections.last_mut() {
+                    current.end_line = line_no;
+                    *spans = true;
+                }
+            }
+        }
+    }
+
+    let mut merged: Vec<(ConfigSection, bool)> = Vec::new();
+    for (current, spans) in sections {
+        if let Some((prev, prev_spans)) = merged.last_mut() {
+            if !spans && !*prev_spans && prev.kind == current.kind {
+                prev.end_line = current.end_line;
+                continue;
+            }
+        }
+        merged.push((current, spans));
+    }
+    merged.into_iter().map(|(s, _)| s).collect()
+}
+
+/// Classify an inventory item by its name and description
+fn hardware_component_type(name: &str, description: &str) -> &'static str {
+    use crate::models::hardware_component;
+
+    let text = format!("{} {}", name, description).to_lowercase();
+    if text.contains("chassis") {
+        hardware_component::CHASSIS
+    } else if text.contains("power supply") || text.contains("psu") || text.starts_with("pem ") {
+        hardware_component::POWER_SUPPLY
+    } else if text.contains("fan") {
+        hardware_component::FAN
+    } else if text.contains("sfp") || text.contains("xcvr") || text.contains("transceiver") || text.contains("optic") {
+        hardware_component::TRANSCEIVER
+    } else {
+        hardware_component::MODULE
+    }
+}
+
+/// Parse hardware inventory from vendor output: Arista `show inventory | json`, Cisco
+/// `show inventory` (NAME/DESCR + PID/VID/SN pairs) or Juniper `show chassis hardware`.
+/// Components without a part number or serial (empty slots, built-ins) are skipped. When no
+/// component is named as the chassis, the first one is taken to be it.
+pub fn parse_hardware_inventory(output: &str) -> Vec<crate::models::HardwareInventoryEntry> {
+    use crate::models::{hardware_component, HardwareInventoryEntry};
+
+    fn clean(value: &str) -> String {
+        let value = value.trim();
+        match value.to_uppercase().as_str() {
+            "N/A" | "NA" | "BUILTIN" | "NOT INSERTED" => String::new(),
+            _ => value.to_string(),
+        }
+    }
+
+    let mut entries: Vec<HardwareInventoryEntry> = Vec::new();
+    let trimmed = output.trim_start();
+
+    if trimmed.starts_with('{') {
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(trimmed) else {
+            return Vec::new();
+        };
+        let text = |v: &serde_json::Value, key: &str| clean(v.get(key).and_then(|s| s.as_str()).unwrap_or_default());
+        if let Some(system) = json.get("systemInformation") {
+            entries.push(HardwareInventoryEntry {
+                component_type: hardware_component::CHASSIS.to_string(),
+                name: "Chassis".to_string(),
+                part_number: text(system, "name"),
+                serial_number: text(system, "serialNum"),
+                description: text(system, "description"),
+            });
+        }
+        let slots = [
+            ("cardSlots", hardware_component::MODULE, "Card"),
+            ("powerSupplySlots", hardware_component::POWER_SUPPLY, "Power Supply"),
+            ("fanTraySlots", hardware_component::FAN, "Fan Tray"),
+            ("xcvrSlots", hardware_component::TRANSCEIVER, "Port"),
+        ];
+        for (key, component_type, label) in slots {
+            let Some(serde_json::Value::Object(map)) = json.get(key) else {
+                continue;
+            };
+            let mut slot_entries: Vec<(String, &serde_json::Value)> = map.iter().map(|(k, v)| (k.clone(), v)).collect();
+            slot_entries.sort_by_key(|(slot, _)| (slot.parse::<u32>().unwrap_or(u32::MAX), slot.clone()));
+            for (slot, item) in slot_entries {
+                let model = match text(item, "modelName") {
+                    m if m.is_empty() => text(item, "name"),
+                    m => m,
+                };
+                entries.push(HardwareInventoryEntry {
+                    component_type: component_type.to_string(),
+                    name: format!("{} {}", label, slot),
+                    part_number: model,
+                    serial_number: text(item, "serialNum"),
+                    description: text(item, "mfgName"),
+                });
+            }
+        }
+    } else if output.contains("NAME:") && output.contains("PID:") {
+        let quoted = |line: &str, key: &str| -> String {
+            line.split(key)
+                .nth(1)
+                .and_then(|rest| rest.trim_start().strip_prefix('"'))
+                .and_then(|rest| rest.split('"').next())
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        };
+        let mut current: Option<(String, String)> = None;
+        for line in output.lines().map(str::trim) {
+            if line.starts_with("NAME:") {
+                current = Some((quoted(line, "NAME:"), quoted(line, "DESCR:")));
+            } else if line.starts_with("PID:") {
+                let Some((name, description)) = current.take() else {
+                    continue;
+                };
+                let field = |key: &str| {
+                    line.split(',')
+                        .find_map(|part| part.trim().strip_prefix(key))
+                        .map(clean)
+                        .unwrap_or_default()
+                };
+                entries.push(HardwareInventoryEntry {
+                    component_type: hardware_component_type(&name, &description).to_string(),
+                    name,
+                    part_number: field("PID:"),
+                    serial_number: field("SN:"),
+                    description,
+                });
+            }
+        }
+    } else if let Some(header_idx) = output.lines().position(|l| l.contains("Part number") && l.contains("Serial number")) {
+        let lines: Vec<&str> = output.lines().collect();
+        let header = lines[header_idx];
+        let col = |label: &str| header.find(label).unwrap_or(header.len());
+        let (version_col, part_col, serial_col, desc_col) =
+            (col("Version"), col("Part number"), col("Serial number"), col("Description"));
+        let slice = |line: &str, start: usize, end: usize| clean(line.get(start.min(line.len())..end.min(line.len())).unwrap_or_default());
+
+        // Nested items (PICs, transceivers) are indented under their parent; prefix them with
+        // its path so names stay unique per device
+        let mut parents: Vec<(usize, String)> = Vec::new();
+        for line in &lines[header_idx + 1..] {
+            let item = line.get(..version_col.min(line.len())).unwrap_or_default();
+            let item_name = item.trim();
+            if item_name.is_empty() {
+                continue;
+            }
+            let indent = item.len() - item.trim_start().len();
+            while parents.last().is_some_and(|(parent_indent, _)| *parent_indent >= indent) {
+                parents.pop();
+            }
+            let name = match parents.last() {
+                Some((_, parent)) => format!("{} / {}", parent, item_name),
+                None => item_name.to_string(),
+            };
+            parents.push((indent, name.clone()));
+
+            let description = line.get(desc_col.min(line.len())..).unwrap_or_default().trim().to_string();
+            entries.push(HardwareInventoryEntry {
+                component_type: hardware_component_type(item_name, &description).to_string(),
+                name,
+                part_number: slice(line, part_col, serial_col),
+                serial_number: slice(line, serial_col, desc_col),
+                description,
+            });
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    entries.retain(|e| {
+        let chassis = e.component_type == hardware_component::CHASSIS;
+        (chassis || !e.part_number.is_empty() || !e.serial_number.is_empty()) && seen.insert(e.name.clone())
+    });
+    if !entries.iter().any(|e| e.component_type == hardware_component::CHASSIS) {
+        if let Some(first) = entries.first_mut() {
+            first.component_type = hardware_component::CHASSIS.to_string();
+        }
+    }
+    entries
+}
+
+/// Parse neighbors from `show ip bgp summary` style tables (Arista, Cisco, FRR) and
+/// `gobgp neighbor`. Columns are located from each header line, so both the combined
+/// `State/PfxRcd` column and separate `State` / `PfxRcd` columns work; a numeric state means
+/// the session is established. Only rows whose first column is an IP address are kept.
+pub fn parse_bgp_summary(output: &str) -> Vec<crate::models::BgpSummaryEntry> {
+    struct Columns {
+        asn: Option<usize>,
+        up_down: Option<usize>,
+        state: usize,
+        prefixes: Option<usize>,
+    }
+
+    let tokens_of = |line: &str| -> Vec<String> {
+        line.split_whitespace()
+            .map(|t| t.trim_start_matches('|').to_string())
+            .filter(|t| !t.is_empty())
+            .collect()
+    };
+
+    let mut columns: Option<Columns> = None;
+    let mut entries = Vec::new();
+    for line in output.lines() {
+        let tokens = tokens_of(line);
+        let Some(first) = tokens.first() else {
+            continue;
+        };
+        if first == "Neighbor" || first == "Peer" {
+            let find = |names: &[&str]| tokens.iter().position(|t| names.contains(&t.as_str()));
+            columns = find(&["State/PfxRcd", "State"]).map(|state| Columns {
+                asn: find(&["AS"]),
+                up_down: find(&["Up/Down"]),
+                state,
+                prefixes: find(&["PfxRcd", "#Received"]),
+            });
+            continue;
+        }
+        let Some(cols) = &columns else {
+            continue;
+        };
+        if first.parse::<std::net::IpAddr>().is_err() {
+            continue;
+        }
+        let Some(state) = tokens.get(cols.state) else {
+            continue;
+        };
+        let column = |idx: Option<usize>| idx.and_then(|i| tokens.get(i));
+
+        let (state, established, prefixes) = match state.parse::<i64>() {
+            Ok(count) => ("Established".to_string(), true, Some(count)),
+            Err(_) => {
+                let established = state.to_lowercase().starts_with("estab");
+                let prefixes = column(cols.prefixes).and_then(|p| p.parse::<i64>().ok());
+                (state.clone(), established, prefixes)
+            }
+        };
+        entries.push(crate::models::BgpSummaryEntry {
+            neighbor: first.clone(),
+            remote_asn: column(cols.asn).and_then(|a| a.parse::<i64>().ok()),
+            state,
+            established,
+            prefixes_received: prefixes,
+            up_down: column(cols.up_down).cloned().unwrap_or_default(),
+        });
+    }
+    entries
+}
+
+/// Find next available IP address in a prefix.
+/// `allocated` is a sorted list of address_int for existing IPs.
+/// Skips network address (first) and broadcast address (last) for prefixes < /31.
+pub fn next_available_ip(
+    prefix_network: u32,
+    prefix_broadcast: u32,
+    prefix_len: u8,
+    allocated: &[u32],
+) -> Option<u32> {
+    let (start, end) = if prefix_len >= 31 {
+        // /31 and /32: use all addresses (RFC 3021)
+        (prefix_network, prefix_broadcast)
+    } else {
+        // Skip network and broadcast addresses
+        (prefix_network + 1, prefix_broadcast - 1)
+    };
+
+    let alloc_set: std::collections::HashSet<u32> = allocated.iter().copied().collect();
+    for candidate in start..=end {
+        if !alloc_set.contains(&candidate) {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+/// Find the lines of `content` accepted by `is_match`, each with up to `context` lines before and after
+pub fn find_line_matches(
+    content: &str,
+    is_match: impl Fn(&str) -> bool,
+    context: usize,
+) -> Vec<crate::models::LineMatch> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| is_match(line))
+        .map(|(i, line)| crate::models::LineMatch {
+            line_number: i + 1,
+            line: line.to_string(),
+            before: lines[i.saturating_sub(context)..i].iter().map(|l| l.to_string()).collect(),
+            after: lines[(i + 1).min(lines.len())..(i + 1 + context).min(lines.len())]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+        })
+        .collect()
+}
+
+/// Turn free-form user input into a safe FTS5 MATCH expression: each word becomes a quoted
+/// prefix term and all terms must match. A word that is a MAC in any notation also matches the
+/// indexed canonical MAC, or the other width of it, as a phrase of its octets. Returns None if
+/// the input has no searchable words.
+pub fn fts_match_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|w| w.replace('"', ""))
+        .filter(|w| !w.is_empty())
+        .map(|w| match parse_mac(&w) {
+            Some(mac) => {
+                let phrases: Vec<String> = mac_forms(&mac).iter().map(|m| format!("\"{}\"", m.replace(':', " "))).collect();
+                format!("(\"{}\"* OR {})", w, phrases.join(" OR "))
+            }
+            None => format!("\"{}\"*", w),
+        })
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Map a device port name to a containerlab endpoint interface: `Ethernet49` becomes `eth49`
+/// and `Ethernet1/1` becomes `eth1_1`. Returns None for names without a port number.
+pub fn containerlab_interface(port_name: &str) -> Option<String> {
+    let start = port_name.find(|c: char| c.is_ascii_digit())?;
+    let number = &port_name[start..];
+    if !number.chars().all(|c| c.is_ascii_digit() || c == '/') || number.ends_with('/') {
+        return None;
+    }
+    Some(format!("eth{}", number.replace('/', "_")))
+}
+
+/// Render a containerlab topology file. `nodes` are (name, kind, image) and `links` are
+/// (node_a, port_a, node_b, port_b) with device port names; links whose ports have no
+/// containerlab equivalent are skipped.
+pub fn containerlab_topology(
+    name: &str,
+    nodes: &[(&str, &str, &str)],
+    links: &[(&str, &str, &str, &str)],
+) -> String {
+    let mut out = format!("name: \"{}\"\n\ntopology:\n  nodes:\n", name);
+    for (node, kind, image) in nodes {
+        out.push_str(&format!(
+            "    \"{}\":\n      kind: {}\n      image: \"{}\"\n",
+            node, kind, image
+        ));
+    }
+
+    let endpoints: Vec<String> = links
+        .iter()
+        .filter_map(|(a, a_port, b, b_port)| {
+            let a_if = containerlab_interface(a_port)?;
+            let b_if = containerlab_interface(b_port)?;
+            Some(format!("    - endpoints: [\"{}:{}\", \"{}:{}\"]\n", a, a_if, b, b_if))
+        })
+        .collect();
+    if !endpoints.is_empty() {
+        out.push_str("\n  links:\n");
+        for line in endpoints {
+            out.push_str(&line);
+        }
+    }
+    out
+}
+
+/// Nearest-rank percentile (0-100) of an ascending-sorted sample; 0.0 for an empty sample
+pub fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (pct / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// UTC bounds `[start, end)` of a `YYYY-MM` month
+pub fn month_bounds(month: &str) -> Option<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> {
+    let (year, mon) = month.split_once('-')?;
+    let (year, mon): (i32, u32) = (year.parse().ok()?, mon.parse().ok()?);
+    let start = chrono::NaiveDate::from_ymd_opt(year, mon, 1)?;
+    let end = if mon == 12 {
+        chrono::NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        chrono::NaiveDate::from_ymd_opt(year, mon + 1, 1)?
+    };
+    Some((start.and_hms_opt(0, 0, 0)?.and_utc(), end.and_hms_opt(0, 0, 0)?.and_utc()))
+}
+
+/// The `YYYY-MM` month `months` after (or, negative, before) `month`
+pub fn shift_month(month: &str, months: i32) -> Option<String> {
+    use chrono::Datelike;
+    let (start, _) = month_bounds(month)?;
+    let index = start.year() * 12 + start.month0() as i32 + months;
+    if index < 0 {
+        return None;
+    }
+    Some(format!("{:04}-{:02}", index / 12, index % 12 + 1))
+}
+
+/// Account a device's time within `[start, end)` (unix seconds). `initial` is the status at
+/// `start` (None if unknown, which leaves time unmonitored until the first transition);
+/// `transitions` are (timestamp, new status) in order. Offline time overlapping a
+/// maintenance window counts as maintenance rather than downtime.
+pub fn availability_span(
+    initial: Option<&str>,
+    transitions: &[(i64, &str)],
+    start: i64,
+    end: i64,
+    maintenance: &[(i64, i64)],
+) -> crate::models::AvailabilitySpan {
+    let mut windows: Vec<(i64, i64)> = maintenance.iter().copied().filter(|(a, b)| a < b).collect();
+    windows.sort_unstable();
+    let mut merged: Vec<(i64, i64)> = Vec::with_capacity(windows.len());
+    for (a, b) in windows {
+        match merged.last_mut() {
+            Some(last) if a <= last.1 => last.1 = last.1.max(b),
+            _ => merged.push((a, b)),
+        }
+    }
+
+    let mut span = crate::models::AvailabilitySpan::default();
+    let mut account = |state: Option<&str>, a: i64, b: i64| {
+        let (a, b) = (a.max(start), b.min(end));
+        let Some(state) = state else { return };
+        if b <= a {
+            return;
+        }
+        span.monitored_secs += b - a;
+        if state == crate::models::device_status::OFFLINE {
+            let excused: i64 = merged.iter().map(|(ma, mb)| (b.min(*mb) - a.max(*ma)).max(0)).sum();
+            span.maintenance_secs += excused;
+            span.downtime_secs += b - a - excused;
+            if b - a > excused {
+                span.outages += 1;
+            }
+        }
+    };
+
+    let mut state = initial;
+    let mut t = start;
+    for (ts, status) in transitions {
+        account(state, t, *ts);
+        state = Some(status);
+        t = t.max(*ts);
+    }
+    account(state, t, end);
+    span
+}
+
+/// Parse an RFC 5424 or RFC 3164 (BSD) syslog message. Anything unrecognised is kept whole
+/// as the message, with the default user.notice priority when the `<PRI>` header is missing.
+pub fn parse_syslog(raw: &str) -> crate::models::SyslogMessage {
+    let raw = raw.trim_end_matches(['\r', '\n', '\0']);
+
+    let (pri, rest) = raw
+        .strip_prefix('<')
+        .and_then(|r| r.split_once('>'))
+        .and_then(|(digits, rest)| {
+            let pri = digits.parse::<i64>().ok().filter(|p| digits.len() <= 3 && *p <= 191)?;
+            Some((pri, rest))
+        })
+        .unwrap_or((13, raw));
+
+    let mut parsed = crate::models::SyslogMessage {
+        facility: pri / 8,
+        severity: pri % 8,
+        hostname: String::new(),
+        app_name: String::new(),
+        message: rest.to_string(),
+    };
+    let nil = |field: &str| if field == "-" { String::new() } else { field.to_string() };
+
+    if let Some(rest) = rest.strip_prefix("1 ") {
+        // RFC 5424: TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA MSG
+        let mut fields = rest.splitn(6, ' ');
+        let (_ts, host, app, _procid, _msgid) = (fields.next(), fields.next(), fields.next(), fields.next(), fields.next());
+        let (Some(host), Some(app)) = (host, app) else {
+            return parsed;
+        };
+        let remainder = fields.next().unwrap_or("");
+        let msg = if let Some(msg) = remainder.strip_prefix('-') {
+            msg
+        } else {
+            // Skip bracketed SD elements, honouring escaped \] inside values
+            let bytes = remainder.as_bytes();
+            let mut i = 0;
+            while i < bytes.len() && bytes[i] == b'[' {
+                while i < bytes.len() && bytes[i] != b']' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            remainder.get(i.min(remainder.len())..).unwrap_or("")
+        };
+        parsed.hostname = nil(host);
+        parsed.app_name = nil(app);
+        parsed.message = msg.trim_start().trim_start_matches('\u{feff}').to_string();
+        return parsed;
+    }
+
+    // RFC 3164: "Mmm dd hh:mm:ss HOSTNAME TAG[pid]: MSG"
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let has_timestamp = rest.len() > 16
+        && rest.get(..3).is_some_and(|month| MONTHS.contains(&month))
+        && rest.as_bytes()[15] == b' '
+        && rest.as_bytes()[9] == b':'
+        && rest.as_bytes()[12] == b':';
+    if !has_timestamp {
+        return parsed;
+    }
+    let Some((host, body)) = rest[16..].split_once(' ') else {
+        parsed.message = rest[16..].to_string();
+        return parsed;
+    };
+    parsed.hostname = host.to_string();
+    parsed.message = body.to_string();
+    if let Some((tag, msg)) = body.split_once(": ") {
+        if !tag.is_empty() && tag.len() <= 48 && !tag.contains(' ') {
+            parsed.app_name = tag.split('[').next().unwrap_or(tag).to_string();
+            parsed.message = msg.to_string();
+        }
+    }
+    parsed
+}
+
+/// Find devices that share a MAC, serial number or IP. MACs are compared by identity, so a MAC
+/// matches the EUI-64 built from it, and serial numbers case-insensitively; empty values never
+/// match.
+pub fn find_duplicate_devices(devices: &[crate::models::Device]) -> Vec<crate::models::DuplicateDeviceSet> {
+    use crate::models::duplicate_match;
+
+    let key = |field: &str, d: &crate::models::Device| match field {
+        duplicate_match::MAC => d.mac.as_deref().map(|m| mac_identity(&normalize_mac(m))),
+        duplicate_match::SERIAL_NUMBER => d.serial_number.as_deref().map(normalize_serial),
+        _ => Some(d.ip.trim().to_string()),
+    };
+    let mut sets = Vec::new();
+    for field in [duplicate_match::MAC, duplicate_match::SERIAL_NUMBER, duplicate_match::IP] {
+        let mut by_value: std::collections::BTreeMap<String, Vec<&crate::models::Device>> = Default::default();
+        for device in devices {
+            if let Some(value) = key(field, device).filter(|v| !v.is_empty()) {
+                by_value.entry(value).or_default().push(device);
+            }
+        }
+        sets.extend(by_value.into_iter().filter(|(_, ds)| ds.len() > 1).map(|(value, ds)| {
+            crate::models::DuplicateDeviceSet {
+                match_field: field.to_string(),
+                value,
+                devices: ds.into_iter().cloned().collect(),
+            }
+        }));
+    }
+    sets
+}
+
+/// Case-insensitive match of `text` against a pattern where `*` matches any run of characters
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let text = text.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || text.len() < first.len() + last.len() || !text.ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Permissions matrix resource of an API path: the first segment after `/api/`
+pub fn api_resource(path: &str) -> Option<&str> {
+    let segment = path.strip_prefix("/api/")?.split('/').next()?;
+    (!segment.is_empty()).then_some(segment)
+}
+
+/// SMTP DATA body: CRLF line endings, lines starting with `.` doubled, ending in CRLF
+pub fn smtp_data(body: &str) -> String {
+    let mut out = String::with_capacity(body.len() + 16);
+    for line in body.lines() {
+        if line.starts_with('.') {
+            out.push('.');
+        }
+        out.push_str(line);
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// InfluxDB line protocol escaping for measurements, tag keys and tag values
+pub fn influx_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            ',' | '=' | ' ' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' | '\t' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// InfluxDB line protocol string field value
+pub fn influx_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+/// One line of InfluxDB line protocol. Tags with empty values are left out; `fields` are
+/// already formatted (`5i`, `1.5`, or a quoted string from `influx_string`).
+pub fn influx_line(measurement: &str, tags: &[(&str, &str)], fields: &[(&str, String)], timestamp_ns: i64) -> String {
+    let mut line = influx_escape(measurement);
+    for (key, value) in tags.iter().filter(|(_, v)| !v.is_empty()) {
+        line.push_str(&format!(",{}={}", influx_escape(key), influx_escape(value)));
+    }
+    let fields: Vec<String> = fields.iter().map(|(k, v)| format!("{}={}", influx_escape(k), v)).collect();
+    format!("{} {} {}", line, fields.join(","), timestamp_ns)
+}
+
+/// Device clock from `show clock` (Arista, Cisco), `show system uptime` (Juniper) or
+/// `date -u '+%Y-%m-%d %H:%M:%S UTC'`. None unless the time is readable and in UTC.
+pub fn parse_device_clock(output: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::NaiveDateTime;
+
+    let is_utc = |tz: &str| matches!(tz.to_uppercase().as_str(), "UTC" | "GMT" | "ETC/UTC" | "Z");
+    let lines: Vec<&str> = output.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+
+    for line in &lines {
+        let line = line.strip_prefix("Current time:").map(str::trim).unwrap_or(line);
+        // 2024-01-11 10:23:45 UTC
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() == 3 && is_utc(parts[2]) {
+            if let Ok(t) = NaiveDateTime::parse_from_str(&format!("{} {}", parts[0], parts[1]), "%Y-%m-%d %H:%M:%S") {
+                return Some(t.and_utc());
+            }
+        }
+        // *10:23:45.123 UTC Thu Jan 11 2024 (Cisco; * or . marks an unsynchronized clock)
+        if parts.len() == 6 && is_utc(parts[1]) {
+            let time = parts[0].trim_start_matches(['*', '.']);
+            let time = time.split('.').next().unwrap_or(time);
+            let text = format!("{} {} {} {}", parts[3], parts[4], parts[5], time);
+            if let Ok(t) = NaiveDateTime::parse_from_str(&text, "%b %d %Y %H:%M:%S") {
+                return Some(t.and_utc());
+            }
+        }
+        // Thu Jan 11 10:23:45 2024, with the zone on a "Timezone:" line (Arista)
+        if parts.len() == 5 {
+            let zone = lines
+                .iter()
+                .find_map(|l| l.strip_prefix("Timezone:"))
+                .map(str::trim)
+                .unwrap_or_default();
+            if is_utc(zone) {
+                if let Ok(t) = NaiveDateTime::parse_from_str(&parts[1..].join(" "), "%b %d %H:%M:%S %Y") {
+                    return Some(t.and_utc());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether `show ntp status` reports the clock synchronized, or None when the output says neither
+pub fn parse_ntp_synchronized(output: &str) -> Option<bool> {
+    let text = output.to_lowercase();
+    if text.contains("unsynchroni") || text.contains("sync_unspec") || text.contains("leap_alarm") {
+        Some(false)
+    } else if text.contains("synchroni") || text.contains("sync_ntp") {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Whether a config session can be opened, from `show configuration sessions` (Arista: pending
+/// sessions below the maximum) or `show configuration lock` (Cisco: no lock owner), with a
+/// description. None when the output is in neither format.
+pub fn parse_config_session_status(output: &str) -> Option<(bool, String)> {
+    let value = |prefix: &str| {
+        output
+            .lines()
+            .find_map(|l| l.trim().strip_prefix(prefix))
+            .map(|v| v.trim_start_matches([' ', ':']).trim().to_string())
+    };
+
+    if let Some(owner) = value("Owner PID") {
+        if owner == "-1" {
+            return Some((true, "configuration is not locked".to_string()));
+        }
+        let user = value("User").unwrap_or_default();
+        return Some((false, format!("configuration locked by {} (pid {})", if user.is_empty() { "unknown" } else { &user }, owner)));
+    }
+
+    let max_pending = value("Maximum number of pending sessions")?.parse::<usize>().ok()?;
+    let pending: Vec<&str> = output
+        .lines()
+        .filter_map(|l| match l.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [name, "pending", ..] => Some(*name),
+            _ => None,
+        })
+        .collect();
+    if pending.len() >= max_pending {
+        Some((false, format!("{} of {} pending sessions in use ({})", pending.len(), max_pending, pending.join(", "))))
+    } else {
+        Some((true, format!("{} of {} pending sessions in use", pending.len(), max_pending)))
+    }
+}
+
+/// A device's model, matched on model name and preferring one of the device's vendor
+pub fn find_device_model<'a>(
+    vendor_id: Option<&str>,
+    model: Option<&str>,
+    models: &'a [crate::models::DeviceModel],
+) -> Option<&'a crate::models::DeviceModel> {
+    let model = model.filter(|m| !m.is_empty())?;
+    let vendor_id = vendor_id.and_then(|v| v.parse::<i64>().ok());
+    let matching = || models.iter().filter(|m| m.model.eq_ignore_ascii_case(model));
+    matching()
+        .find(|m| Some(m.vendor_id) == vendor_id)
+        .or_else(|| matching().next())
+}
+
+/// A device's height in rack units from its device model; 1U when no model matches
+pub fn device_rack_units(vendor_id: Option<&str>, model: Option<&str>, models: &[crate::models::DeviceModel]) -> i32 {
+    find_device_model(vendor_id, model, models)
+        .map(|m| m.rack_units.max(1))
+        .unwrap_or(1)
+}
+
+/// Lay out a rack's devices by rack unit, top of the rack first. A device sharing a unit with a
+/// lower one, or extending past the top of the rack, is flagged as a conflict; the lower device
+/// keeps the shared units.
+pub fn build_rack_elevation(
+    rack: crate::models::IpamRack,
+    devices: &[crate::models::Device],
+    models: &[crate::models::DeviceModel],
+) -> crate::models::RackElevation {
+    use crate::models::{RackElevation, RackElevationDevice, RackElevationUnit};
+
+    let height = rack.height_ru.max(0);
+    let entry = |d: &crate::models::Device| RackElevationDevice {
+        device_id: d.id,
+        hostname: d.hostname.clone(),
+        model: d.model.clone(),
+        topology_role: d.topology_role.clone(),
+        position: d.rack_position.filter(|p| *p > 0),
+        rack_units: device_rack_units(d.vendor_id.as_deref(), d.model.as_deref(), models),
+        conflict: false,
+    };
+    let (mut placed, unplaced): (Vec<_>, Vec<_>) = devices.iter().map(entry).partition(|d| d.position.is_some());
+    placed.sort_by_key(|d| (d.position, d.device_id));
+
+    // Index 0 is U1
+    let mut occupant: Vec<Option<i64>> = vec![None; height as usize];
+    for device in &mut placed {
+        let bottom = device.position.unwrap_or(1);
+        let top = bottom + device.rack_units - 1;
+        device.conflict = top > height;
+        for u in bottom..=top.min(height) {
+            let slot = &mut occupant[(u - 1) as usize];
+            match slot {
+                Some(_) => device.conflict = true,
+                None => *slot = Some(device.device_id),
+            }
+        }
+    }
+    placed.reverse();
+
+    let used_ru = occupant.iter().filter(|o| o.is_some()).count() as i32;
+    let units = (1..=height)
+        .rev()
+        .map(|u| RackElevationUnit { position: u, device_id: occupant[(u - 1) as usize] })
+        .collect();
+    RackElevation {
+        rack,
+        used_ru,
+        free_ru: height - used_ru,
+        devices: placed,
+        units,
+        unplaced,
+    }
+}
+
+/// The placed device, other than `device_id`, occupying any of the `units` rack units from
+/// `position` up
+pub fn find_rack_collision(
+    elevation: &crate::models::RackElevation,
+    device_id: Option<i64>,
+    position: i32,
+    units: i32,
+) -> Option<&crate::models::RackElevationDevice> {
+    let top = position + units - 1;
+    elevation.devices.iter().find(|d| {
+        let bottom = d.position.unwrap_or(0);
+        Some(d.device_id) != device_id && bottom <= top && position < bottom + d.rack_units
+    })
+}
+
+/// Parse CSV text into rows of fields. Quoted fields may contain delimiters, doubled quotes and
+/// newlines; blank lines are skipped.
+pub fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    let mut line = 1;
+    let mut quote_line = 1;
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => {
+                in_quotes = true;
+                quote_line = line;
+            }
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|f| !f.is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                } else {
+                    row.clear();
+                }
+                line += 1;
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!("unterminated quoted field starting on line {}", quote_line));
+    }
+    row.push(field);
+    if row.iter().any(|f| !f.is_empty()) {
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Utilization percentage of each prefix: the addresses covered by its direct child prefixes
+/// plus its own IP addresses, over its size
+pub fn prefix_utilization(prefixes: &[crate::models::IpamPrefix]) -> HashMap<i64, f64> {
+    let mut child_addresses: HashMap<i64, i64> = HashMap::new();
+    for prefix in prefixes {
+        if let Some(parent_id) = prefix.parent_id {
+            *child_addresses.entry(parent_id).or_default() += prefix.broadcast_int - prefix.network_int + 1;
+        }
+    }
+    prefixes
+        .iter()
+        .map(|p| {
+            let size = (p.broadcast_int - p.network_int + 1) as f64;
+            let used = child_addresses.get(&p.id).copied().unwrap_or(0) + p.ip_address_count.unwrap_or(0) as i64;
+            (p.id, ((used as f64 / size) * 100.0).min(100.0))
+        })
+        .collect()
+}
+
+/// Prefix length of the smallest IPv4 block holding `addresses` addresses
+pub fn block_prefix_length(addresses: usize) -> u8 {
+    let mut len = 32u8;
+    while len > 0 && (1u64 << (32 - len)) < addresses as u64 {
+        len -= 1;
+    }
+    len
+}
+
+/// Peer variable index of a fabric port: the number its name ends with. The seeded templates
+/// configure EthernetN from PeerN, so "Ethernet12" is 12. None for names like "Ethernet1/1".
+pub fn port_peer_index(port: &str) -> Option<usize> {
+    port.trim_start_matches(|c: char| !c.is_ascii_digit())
+        .parse()
+        .ok()
+        .filter(|n| *n > 0)
+}
+
+/// PeerN index of each of a device's fabric ports: the port's number where its name ends with one
+/// (see [`port_peer_index`]), else the lowest index not taken
+pub fn peer_indexes(ports: &[&str]) -> Vec<usize> {
+    let mut used = std::collections::BTreeSet::new();
+    let mut indexes: Vec<Option<usize>> = ports
+        .iter()
+        .map(|port| port_peer_index(port).filter(|n| used.insert(*n)))
+        .collect();
+    let mut next = 1;
+    for index in indexes.iter_mut().filter(|i| i.is_none()) {
+        while used.contains(&next) {
+            next += 1;
+        }
+        used.insert(next);
+        *index = Some(next);
+    }
+    indexes.into_iter().flatten().collect()
+}
+
+/// Status of an alert whose threshold is exceeded: `started_at` is when it was first exceeded,
+/// and it fires once `for_minutes` have passed since
+pub fn exceeded_alert_status(
+    started_at: chrono::DateTime<chrono::Utc>,
+    for_minutes: i64,
+    now: chrono::DateTime<chrono::Utc>,
+) -> &'static str {
+    if now - started_at >= chrono::Duration::minutes(for_minutes) {
+        crate::models::alert_status::FIRING
+    } else {
+        crate::models::alert_status::PENDING
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_mac() {
+        assert_eq!(normalize_mac("AA:BB:CC:DD:EE:FF"), "aa:bb:cc:dd:ee:ff");
+        assert_eq!(normalize_mac("AA-BB-CC-DD-EE-FF"), "aa:bb:cc:dd:ee:ff");
+        assert_eq!(normalize_mac("AABBCCDDEEFF"), "aa:bb:cc:dd:ee:ff");
+        assert_eq!(normalize_mac("aa:bb:cc:dd:ee:ff"), "aa:bb:cc:dd:ee:ff");
+        assert_eq!(normalize_mac(" 0002:C903:0001:2345 "), "00:02:c9:03:00:01:23:45");
+        // Hex digits alone don't make a MAC
+        assert_eq!(normalize_mac("AA:BB:CC:DD:EE:F:F"), "aa:bb:cc:dd:ee:f:f");
+    }
+
+    #[test]
+    fn test_parse_mac() {
+        let mac = Some("00:1c:73:aa:bb:cc".to_string());
+        assert_eq!(parse_mac("00:1C:73:AA:BB:CC"), mac);
+        assert_eq!(parse_mac("00-1c-73-aa-bb-cc"), mac);
+        assert_eq!(parse_mac("001c.73aa.bbcc"), mac);
+        assert_eq!(parse_mac("001c73aabbcc"), mac);
+        assert_eq!(parse_mac("0:1c:73:aa:bb:cc"), mac);
+        let eui64 = Some("00:02:c9:03:00:01:23:45".to_string());
+        assert_eq!(parse_mac("00:02:c9:03:00:01:23:45"), eui64);
+        assert_eq!(parse_mac("0002:c903:0001:2345"), eui64);
+        assert_eq!(parse_mac("0002.c903.0001.2345"), eui64);
+        assert_eq!(parse_mac("0002c90300012345"), eui64);
+
+        for bad in [
+            "",
+            "00:1c:73:aa:bb",
+            "00:1c:73:aa:bb:cc:dd",
+            "00:1c:73-aa-bb-cc",
+            "001:c73:aab:bcc",
+            "001c:73aa:bbcc",
+            "00:1c:73:aa:bb:cg",
+            "00::1c:73:aa:bb:cc",
+            "001c73aabbc",
+            "mac 00:1c:73:aa:bb:cc",
+        ] {
+            assert_eq!(parse_mac(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_serial_from_client_id() {
+        // Type 0, "FOC1234X0AB"
+        assert_eq!(serial_from_client_id("00:46:4f:43:31:32:33:34:58:30:41:42"), Some("FOC1234X0AB".to_string()));
+        // No type byte
+        assert_eq!(serial_from_client_id("4a:50:45:31:32"), Some("JPE12".to_string()));
+        assert_eq!(serial_from_client_id(" SSJ17372617 "), Some("SSJ17372617".to_string()));
+        // Hardware address and DUID identifiers
+        assert_eq!(serial_from_client_id("01:00:1c:73:aa:bb:cc"), None);
+        assert_eq!(serial_from_client_id("ff:00:00:00:01:00:01"), None);
+        assert_eq!(serial_from_client_id("00:01:02"), None);
+        assert_eq!(serial_from_client_id(""), None);
+        assert_eq!(normalize_serial(" ssj1737 "), "SSJ1737");
+    }
+
+    #[test]
+    fn test_mac_identity_and_forms() {
+        assert_eq!(mac_identity("00:1c:73:ff:fe:aa:bb:cc"), "00:1c:73:aa:bb:cc");
+        assert_eq!(mac_identity("00:02:c9:03:00:01:23:45"), "00:02:c9:03:00:01:23:45");
+        assert_eq!(mac_identity("00:1c:73:aa:bb:cc"), "00:1c:73:aa:bb:cc");
+        let forms = vec!["00:1c:73:aa:bb:cc".to_string(), "00:1c:73:ff:fe:aa:bb:cc".to_string()];
+        assert_eq!(mac_forms("00:1c:73:aa:bb:cc"), forms);
+        assert_eq!(mac_forms("00:1c:73:ff:fe:aa:bb:cc"), forms);
+        assert_eq!(mac_forms("00:02:c9:03:00:01:23:45"), vec!["00:02:c9:03:00:01:23:45".to_string()]);
+    }
+
+    #[test]
+    fn test_mac_to_config_filename() {
+        assert_eq!(mac_to_config_filename("00:1c:73:aa:bb:cc"), "00_1c_73_aa_bb_cc.cfg");
+    }
+
+    #[test]
+    fn test_go_template_simple() {
+        let go = "hostname {{.Hostname}}\nip {{.IP}}";
+        let tera = convert_go_template_to_tera(go);
+        assert_eq!(tera, "hostname {{Hostname}}\nip {{IP}}");
+    }
+
+    #[test]
+    fn test_tera_passthrough() {
+        let tera = "hostname {{Hostname}}\nip {{IP}}";
+        assert_eq!(convert_go_template_to_tera(tera), tera);
+    }
+
+    #[test]
+    fn test_go_template_if() {
+        let go = "{{if .Vendor}}has vendor{{else}}no vendor{{end}}";
+        let tera = convert_go_template_to_tera(go);
+        assert_eq!(tera, "{% if Vendor %}has vendor{% else %}no vendor{% endif %}");
+    }
+
+    #[test]
+    fn test_go_template_range() {
+        let go = "{{range .Items}}item{{end}}";
+        let tera = convert_go_template_to_tera(go);
+        assert_eq!(tera, "{% for item in Items %}item{% endfor %}");
+    }
+
+    #[test]
+    fn test_is_valid_ipv4() {
+        assert!(is_valid_ipv4("192.168.1.1"));
+        assert!(is_valid_ipv4("0.0.0.0"));
+        assert!(is_valid_ipv4("255.255.255.255"));
+        assert!(!is_valid_ipv4(""));
+        assert!(!is_valid_ipv4("not-an-ip"));
+        assert!(!is_valid_ipv4("256.1.1.1"));
+        assert!(!is_valid_ipv4("1.2.3"));
+        assert!(!is_valid_ipv4("1.2.3.4.5"));
+        assert!(!is_valid_ipv4("1.2.3.-1"));
+        assert!(!is_valid_ipv4("; rm -rf /"));
+    }
+
+    #[test]
+    fn test_is_valid_hostname() {
+        assert!(is_valid_hostname("switch-01"));
+        assert!(is_valid_hostname("router.lab.local"));
+        assert!(is_valid_hostname("my_host"));
+        assert!(!is_valid_hostname(""));
+        assert!(!is_valid_hostname("host name")); // spaces
+        assert!(!is_valid_hostname("host;rm")); // semicolon
+        assert!(!is_valid_hostname("../etc/passwd")); // path traversal
+        assert!(!is_valid_hostname("host\nname")); // newline
+    }
+
+    #[test]
+    fn test_parse_cidr() {
+        let (net, bcast, len) = parse_cidr("10.0.0.0/8").unwrap();
+        assert_eq!(net, 0x0A000000);
+        assert_eq!(bcast, 0x0AFFFFFF);
+        assert_eq!(len, 8);
+
+        let (net, bcast, len) = parse_cidr("192.168.1.0/24").unwrap();
+        assert_eq!(u32_to_ipv4(net), "192.168.1.0");
+        assert_eq!(u32_to_ipv4(bcast), "192.168.1.255");
+        assert_eq!(len, 24);
+    }
+
+    #[test]
+    fn test_parse_ipv4_to_u32() {
+        assert_eq!(parse_ipv4_to_u32("10.0.0.1").unwrap(), 0x0A000001);
+        assert_eq!(parse_ipv4_to_u32("255.255.255.255").unwrap(), 0xFFFFFFFF);
+        assert_eq!(parse_ipv4_to_u32("0.0.0.0").unwrap(), 0);
+        assert!(parse_ipv4_to_u32("invalid").is_err());
+    }
+
+    #[test]
+    fn test_u32_to_ipv4_roundtrip() {
+        assert_eq!(u32_to_ipv4(0x0A000001), "10.0.0.1");
+        assert_eq!(u32_to_ipv4(0xC0A80164), "192.168.1.100");
+    }
+
+    #[test]
+    fn test_next_available_prefix() {
+        // 10.0.0.0/8, allocate /24s
         let (pnet, pbcast, _) = parse_cidr("10.0.0.0/8").unwrap();
 
         // No allocations yet — first /24 should be 10.0.0.0/24
@@ -979,6 +2291,125 @@ mod tests {
         assert_eq!(format_cidr(net2, 24), "10.0.1.0/24");
     }
 
+    #[test]
+    fn test_free_cidr_blocks() {
+        let (pnet, pbcast, _) = parse_cidr("10.0.0.0/24").unwrap();
+        let (cnet, cbcast, _) = parse_cidr("10.0.0.64/26").unwrap();
+        let ip = parse_ipv4_to_u32("10.0.0.1").unwrap();
+
+        let blocks: Vec<String> = free_cidr_blocks(pnet, pbcast, &[(cnet, cbcast), (ip, ip)])
+            .into_iter()
+            .map(|(net, len)| format_cidr(net, len))
+            .collect();
+        assert_eq!(blocks, vec!["10.0.0.0/32", "10.0.0.2/31", "10.0.0.4/30", "10.0.0.8/29",
+            "10.0.0.16/28", "10.0.0.32/27", "10.0.0.128/25"]);
+
+        // Fully free and fully allocated prefixes
+        assert_eq!(free_cidr_blocks(pnet, pbcast, &[]), vec![(pnet, 24)]);
+        assert!(free_cidr_blocks(pnet, pbcast, &[(pnet, pbcast)]).is_empty());
+        assert_eq!(free_cidr_blocks(0, u32::MAX, &[]), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_ptr_zone_and_label() {
+        let addr = parse_ipv4_to_u32("10.1.2.3").unwrap();
+        assert_eq!(ptr_zone_and_label(addr), ("2.1.10.in-addr.arpa".to_string(), "3".to_string()));
+        assert_eq!(fqdn("leaf-01.example.com"), "leaf-01.example.com.");
+        assert_eq!(fqdn("leaf-01.example.com."), "leaf-01.example.com.");
+    }
+
+    #[test]
+    fn test_parse_neighbor_table() {
+        let cisco = "Protocol  Address          Age (min)  Hardware Addr   Type   Interface\n\
+                     Internet  10.0.0.5               3   0050.56a1.0001  ARPA   Vlan10\n\
+                     Internet  10.0.0.6               0   Incomplete      ARPA\n";
+        let entries = parse_neighbor_table(cisco);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].address, "10.0.0.5");
+        assert_eq!(entries[0].mac, "00:50:56:a1:00:01");
+        assert_eq!(entries[0].interface.as_deref(), Some("Vlan10"));
+
+        let linux = "fe80::1 dev eth0 lladdr 00:50:56:a1:00:02 router REACHABLE\n";
+        let entries = parse_neighbor_table(linux);
+        assert_eq!(entries[0].address, "fe80::1");
+        assert_eq!(entries[0].interface.as_deref(), Some("eth0"));
+
+        let juniper = "00:50:56:a1:00:03 10.0.1.7 ge-0/0/0.0 none\n";
+        let entries = parse_neighbor_table(juniper);
+        assert_eq!(entries[0].address, "10.0.1.7");
+        assert_eq!(entries[0].interface.as_deref(), Some("ge-0/0/0.0"));
+    }
+
+    #[test]
+    fn test_containerlab_topology() {
+        assert_eq!(containerlab_interface("Ethernet49").as_deref(), Some("eth49"));
+        assert_eq!(containerlab_interface("Ethernet1/1").as_deref(), Some("eth1_1"));
+        assert_eq!(containerlab_interface("swp3").as_deref(), Some("eth3"));
+        assert_eq!(containerlab_interface("Management"), None);
+        assert_eq!(containerlab_interface("Ethernet1.100"), None);
+
+        let yaml = containerlab_topology(
+            "dc1",
+            &[("spine1", "ceos", "ceosimage:latest"), ("leaf1", "ceos", "ceosimage:latest")],
+            &[("spine1", "Ethernet1", "leaf1", "Ethernet49"), ("spine1", "Ethernet2", "leaf1", "Mgmt")],
+        );
+        assert!(yaml.starts_with("name: \"dc1\"\n"));
+        assert!(yaml.contains("    \"leaf1\":\n      kind: ceos\n"));
+        assert!(yaml.contains("    - endpoints: [\"spine1:eth1\", \"leaf1:eth49\"]\n"));
+        assert_eq!(yaml.matches("endpoints").count(), 1);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let sample: Vec<f64> = (1..=10).map(|v| v as f64).collect();
+        assert_eq!(percentile(&sample, 50.0), 5.0);
+        assert_eq!(percentile(&sample, 90.0), 9.0);
+        assert_eq!(percentile(&sample, 99.0), 10.0);
+        assert_eq!(percentile(&sample, 0.0), 1.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        let (start, end) = month_bounds("2026-12").unwrap();
+        assert_eq!(start.to_rfc3339(), "2026-12-01T00:00:00+00:00");
+        assert_eq!(end.to_rfc3339(), "2027-01-01T00:00:00+00:00");
+        assert!(month_bounds("2026-13").is_none());
+        assert!(month_bounds("202612").is_none());
+    }
+
+    #[test]
+    fn test_shift_month() {
+        assert_eq!(shift_month("2026-10", 1).as_deref(), Some("2026-11"));
+        assert_eq!(shift_month("2026-12", 1).as_deref(), Some("2027-01"));
+        assert_eq!(shift_month("2026-03", -6).as_deref(), Some("2025-09"));
+        assert_eq!(shift_month("2026-10", -24).as_deref(), Some("2024-10"));
+        assert!(shift_month("2026-13", 1).is_none());
+    }
+
+    #[test]
+    fn test_availability_span() {
+        // Down 100..300, with maintenance 250..400 excusing the last 50s
+        let span = availability_span(
+            Some("online"),
+            &[(100, "offline"), (300, "online")],
+            0,
+            1000,
+            &[(250, 400)],
+        );
+        assert_eq!(span.monitored_secs, 1000);
+        assert_eq!(span.downtime_secs, 150);
+        assert_eq!(span.maintenance_secs, 50);
+        assert_eq!(span.outages, 1);
+        assert_eq!(span.availability_pct(), Some(85.0));
+
+        // Unknown until the first transition; outage fully inside maintenance isn't counted
+        let span = availability_span(None, &[(500, "offline"), (600, "online")], 0, 1000, &[(450, 700)]);
+        assert_eq!(span.monitored_secs, 500);
+        assert_eq!(span.downtime_secs, 0);
+        assert_eq!(span.outages, 0);
+    }
+
     #[test]
     fn test_next_available_ip() {
         let (pnet, pbcast, plen) = parse_cidr("10.0.0.0/24").unwrap();
@@ -992,4 +2423,545 @@ mod tests {
         let result = next_available_ip(pnet, pbcast, plen, &allocated);
         assert_eq!(result, Some(parse_ipv4_to_u32("10.0.0.2").unwrap()));
     }
+
+    #[test]
+    fn test_find_line_matches() {
+        let content = "hostname sw1\nradius-server host 10.0.0.5\nntp server 1.1.1.1\nradius-server key x";
+        let matches = find_line_matches(content, |l| l.contains("radius"), 1);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matches[0].before, vec!["hostname sw1"]);
+        assert_eq!(matches[0].after, vec!["ntp server 1.1.1.1"]);
+        assert_eq!(matches[1].line_number, 4);
+        assert!(matches[1].after.is_empty());
+
+        assert!(find_line_matches(content, |l| l.contains("snmp"), 2).is_empty());
+    }
+
+    #[test]
+    fn test_fts_match_query() {
+        assert_eq!(fts_match_query("spine"), Some("\"spine\"*".to_string()));
+        assert_eq!(fts_match_query("  radius  10.0.0.5 "), Some("\"radius\"* \"10.0.0.5\"*".to_string()));
+        assert_eq!(fts_match_query("say \"hi\" OR"), Some("\"say\"* \"hi\"* \"OR\"*".to_string()));
+        assert_eq!(fts_match_query("   "), None);
+        assert_eq!(fts_match_query("\"\""), None);
+        assert_eq!(
+            fts_match_query("leaf 001c.73aa.bbcc"),
+            Some("\"leaf\"* (\"001c.73aa.bbcc\"* OR \"00 1c 73 aa bb cc\" OR \"00 1c 73 ff fe aa bb cc\")".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new", 3), "");
+
+        let old = "hostname sw1\nntp server 1.1.1.1\nradius-server host 10.0.0.5\nend";
+        let new = "hostname sw1\nntp server 1.1.1.1\nradius-server host 10.0.0.9\nend";
+        let diff = unified_diff(old, new, "backup", "rendered", 1);
+        assert_eq!(
+            diff,
+            "--- backup\n+++ rendered\n@@ -2,3 +2,3 @@\n ntp server 1.1.1.1\n-radius-server host 10.0.0.5\n+radius-server host 10.0.0.9\n end\n"
+        );
+
+        let diff = unified_diff("", "line", "a", "b", 3);
+        assert_eq!(diff, "--- a\n+++ b\n@@ -0,0 +1,1 @@\n+line\n");
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines("a\nb\n", "a\nb\n", "old", "new", 3);
+        assert!(diff.hunks.is_empty());
+
+        let old = "hostname sw1\nntp server 1.1.1.1\nradius-server host 10.0.0.5\nend";
+        let new = "hostname sw1\nntp server 1.1.1.1\nradius-server host 10.0.0.9\nlogging host 10.0.0.7\nend";
+        let diff = diff_lines(old, new, "backup", "rendered", 1);
+        assert_eq!((diff.added, diff.removed), (2, 1));
+        assert_eq!(diff.hunks.len(), 1);
+        let hunk = &diff.hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_count, hunk.new_start, hunk.new_count), (2, 3, 2, 4));
+        let kinds: Vec<&str> = hunk.lines.iter().map(|l| l.kind.as_str()).collect();
+        assert_eq!(kinds, ["context", "removed", "added", "added", "context"]);
+        assert_eq!((hunk.lines[1].old_line, hunk.lines[1].new_line), (Some(3), None));
+        assert_eq!((hunk.lines[3].old_line, hunk.lines[3].new_line), (None, Some(4)));
+        assert_eq!((hunk.lines[4].old_line, hunk.lines[4].new_line), (Some(4), Some(5)));
+    }
+
+    #[test]
+    fn test_parse_syslog() {
+        let m = parse_syslog("<179>Oct 16 12:00:01 leaf1 Bgp[2044]: %BGP-3-NOTIFICATION: peer 10.0.0.1 down\n");
+        assert_eq!((m.facility, m.severity), (22, 3));
+        assert_eq!(m.hostname, "leaf1");
+        assert_eq!(m.app_name, "Bgp");
+        assert_eq!(m.message, "%BGP-3-NOTIFICATION: peer 10.0.0.1 down");
+
+        let m = parse_syslog("<165>1 2026-10-16T12:00:01Z spine1 bgpd 77 ID1 [ex@1 a=\"x\\]\"] neighbor Down");
+        assert_eq!((m.facility, m.severity), (20, 5));
+        assert_eq!(m.hostname, "spine1");
+        assert_eq!(m.app_name, "bgpd");
+        assert_eq!(m.message, "neighbor Down");
+
+        let m = parse_syslog("<14>1 - - - - - - hello");
+        assert_eq!((m.hostname.as_str(), m.app_name.as_str(), m.message.as_str()), ("", "", "hello"));
+
+        let m = parse_syslog("no header at all");
+        assert_eq!((m.facility, m.severity), (1, 5));
+        assert_eq!(m.message, "no header at all");
+    }
+
+    #[test]
+    fn test_parse_interface_inventory() {
+        let eos = "Ethernet1 is up, line protocol is up (connected)\n  Hardware is Ethernet, address is 5254.00ab.cd01 (bia 5254.00ab.cd01)\n  Description: to-spine1\n  Internet address is 10.0.0.1/31\n  Broadcast address is 255.255.255.255\n  IP MTU 1500 bytes, BW 100000000 kbit\n  Full-duplex, 100Gb/s, auto negotiation: off\nEthernet2 is administratively down, line protocol is down (disabled)\n  Hardware is Ethernet, address is 5254.00ab.cd02\n  Full-duplex, 25Gb/s\n";
+        let parsed = parse_interface_inventory(eos);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].name, "Ethernet1");
+        assert_eq!(parsed[0].status, "up");
+        assert_eq!(parsed[0].mac, "52:54:00:ab:cd:01");
+        assert_eq!(parsed[0].description, "to-spine1");
+        assert_eq!(parsed[0].ip, "10.0.0.1/31");
+        assert_eq!(parsed[0].speed_mbps, Some(100_000));
+        assert_eq!(parsed[1].status, "admin-down");
+        assert_eq!(parsed[1].speed_mbps, Some(25_000));
+
+        let junos = "Physical interface: ge-0/0/0, Enabled, Physical link is Down\n  Description: uplink\n  Link-level type: Ethernet, MTU: 1514, Speed: 1000mbps\n  Current address: 00:05:86:71:1a:02, Hardware address: 00:05:86:71:1a:02\n  Logical interface ge-0/0/0.0 (Index 70)\n      Destination: 10.1.0.0/30, Local: 10.1.0.1, Broadcast: 10.1.0.3\n";
+        let parsed = parse_interface_inventory(junos);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].name, "ge-0/0/0");
+        assert_eq!(parsed[0].status, "down");
+        assert_eq!(parsed[0].speed_mbps, Some(1000));
+        assert_eq!(parsed[0].mac, "00:05:86:71:1a:02");
+        assert_eq!(parsed[0].ip, "10.1.0.1/30");
+
+        let linux = r#"[{"ifname":"lo","flags":["UP"],"operstate":"UNKNOWN"},{"ifname":"eth1","flags":["BROADCAST","UP"],"operstate":"UP","address":"aa:bb:cc:00:00:01","ifalias":"leaf1","addr_info":[{"family":"inet6","local":"fe80::1","prefixlen":64},{"family":"inet","local":"10.2.0.1","prefixlen":31}]},{"ifname":"eth2","flags":["BROADCAST"],"operstate":"DOWN","address":"aa:bb:cc:00:00:02","addr_info":[]}]"#;
+        let parsed = parse_interface_inventory(linux);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!((parsed[0].name.as_str(), parsed[0].status.as_str(), parsed[0].ip.as_str()), ("eth1", "up", "10.2.0.1/31"));
+        assert_eq!(parsed[0].description, "leaf1");
+        assert_eq!(parsed[1].status, "admin-down");
+    }
+
+    #[test]
+    fn test_parse_hardware_inventory() {
+        let ios = "NAME: \"1\", DESCR: \"WS-C3850-48P-E\"\nPID: WS-C3850-48P-E    , VID: V07  , SN: FOC1234X0AB\n\nNAME: \"Switch 1 - Power Supply A\", DESCR: \"Switch 1 - Power Supply A\"\nPID: PWR-C1-715WAC     , VID: V02  , SN: LIT1234ABCD\n\nNAME: \"TenGigabitEthernet1/1/1\", DESCR: \"SFP-10GBase-SR\"\nPID: SFP-10G-SR          , VID: V03  , SN: AVD1234ABCD\n";
+        let parsed = parse_hardware_inventory(ios);
+        assert_eq!(parsed.len(), 3);
+        assert_eq!((parsed[0].component_type.as_str(), parsed[0].serial_number.as_str()), ("chassis", "FOC1234X0AB"));
+        assert_eq!(parsed[0].part_number, "WS-C3850-48P-E");
+        assert_eq!(parsed[1].component_type, "power-supply");
+        assert_eq!(parsed[2].component_type, "transceiver");
+
+        let eos = r#"{"systemInformation":{"name":"DCS-7050SX3-48YC8","description":"48x25G switch","serialNum":"JPE12345678"},"powerSupplySlots":{"2":{"name":"PWR-500AC-F","serialNum":"EEWT0002"},"1":{"name":"PWR-500AC-F","serialNum":"EEWT0001"}},"xcvrSlots":{"1":{"mfgName":"Arista Networks","modelName":"SFP-10G-SR","serialNum":"XCS1"},"2":{"mfgName":"","modelName":"","serialNum":""}}}"#;
+        let parsed = parse_hardware_inventory(eos);
+        let names: Vec<&str> = parsed.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Chassis", "Power Supply 1", "Power Supply 2", "Port 1"]);
+        assert_eq!(parsed[0].serial_number, "JPE12345678");
+        assert_eq!(parsed[3].description, "Arista Networks");
+
+        let junos = "Hardware inventory:\nItem             Version  Part number  Serial number     Description\nChassis                                WS3718000123      QFX5100-48S-6Q\nFPC 0            REV 05   650-056265   WS3718000999      QFX5100-48S-6Q\n  PIC 0                   BUILTIN      BUILTIN           48x10G-6x40G\n    Xcvr 0       REV 01   740-021308   AS30LCM           SFP+-10G-SR\nPower Supply 0   REV 04   740-041741   1GA24302137       JPSU-650W-AC-AFO\nFan Tray 0                                               QFX5100 Fan Tray 0\n";
+        let parsed = parse_hardware_inventory(junos);
+        let names: Vec<&str> = parsed.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Chassis", "FPC 0", "FPC 0 / PIC 0 / Xcvr 0", "Power Supply 0"]);
+        assert_eq!(parsed[0].serial_number, "WS3718000123");
+        assert_eq!((parsed[2].component_type.as_str(), parsed[2].part_number.as_str()), ("transceiver", "740-021308"));
+        assert_eq!(parsed[3].component_type, "power-supply");
+    }
+
+    #[test]
+    fn test_parse_bgp_summary() {
+        let frr = "IPv4 Unicast Summary:\nBGP router identifier 10.255.0.1, local AS number 65000 vrf-id 0\nNeighbor        V         AS   MsgRcvd   MsgSent   TblVer  InQ OutQ  Up/Down State/PfxRcd   PfxSnt Desc\n10.0.0.1        4      65001       120       118        0    0    0 01:02:03            5        7 leaf-1\n10.0.0.3        4      65002         0         0        0    0    0    never       Active        0 leaf-2\n\nTotal number of neighbors 2\n";
+        let parsed = parse_bgp_summary(frr);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].neighbor, "10.0.0.1");
+        assert_eq!(parsed[0].remote_asn, Some(65001));
+        assert!(parsed[0].established);
+        assert_eq!(parsed[0].prefixes_received, Some(5));
+        assert_eq!(parsed[0].up_down, "01:02:03");
+        assert_eq!((parsed[1].state.as_str(), parsed[1].established), ("Active", false));
+
+        let eos = "BGP summary information for VRF default\nNeighbor Status Codes: m - Under maintenance\n  Neighbor V AS           MsgRcvd   MsgSent  InQ OutQ  Up/Down State   PfxRcd PfxAcc\n  10.0.0.0 4 65000            100       101    0    0 02:00:00 Estab   12     12\n  10.0.0.4 4 65000              3         5    0    0 00:00:10 Idle\n";
+        let parsed = parse_bgp_summary(eos);
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed[0].established);
+        assert_eq!(parsed[0].prefixes_received, Some(12));
+        assert_eq!((parsed[1].state.as_str(), parsed[1].established), ("Idle", false));
+
+        let gobgp = "Peer        AS  Up/Down State       |#Received  Accepted\n10.0.0.5 65003 00:10:00 Establ      |        4         4\n";
+        let parsed = parse_bgp_summary(gobgp);
+        assert_eq!(parsed.len(), 1);
+        assert!(parsed[0].established);
+        assert_eq!(parsed[0].remote_asn, Some(65003));
+        assert_eq!(parsed[0].prefixes_received, Some(4));
+    }
+
+    #[test]
+    fn test_parse_interface_vrfs() {
+        let eos = r#"{"vrfs": {"default": {"interfaces": ["Ethernet1"]}, "blue": {"interfaces": ["Ethernet2", "Vlan10"]}}}"#;
+        let vrfs = parse_interface_vrfs(eos).unwrap();
+        assert_eq!(vrfs.get("Ethernet1").map(String::as_str), Some(""));
+        assert_eq!(vrfs.get("Vlan10").map(String::as_str), Some("blue"));
+
+        let nxos = "Interface                 VRF-Name                        VRF-ID  Site-of-Origin
+Ethernet1/1               red                                  3  --
+mgmt0                     management                           2  --
+";
+        let vrfs = parse_interface_vrfs(nxos).unwrap();
+        assert_eq!(vrfs.get("Ethernet1/1").map(String::as_str), Some("red"));
+        assert_eq!(vrfs.len(), 2);
+
+        let linux = r#"[{"ifname": "red", "linkinfo": {"info_kind": "vrf"}}, {"ifname": "eth1", "master": "red"}, {"ifname": "eth2", "master": "br0"}]"#;
+        let vrfs = parse_interface_vrfs(linux).unwrap();
+        assert_eq!(vrfs.get("eth1").map(String::as_str), Some("red"));
+        assert_eq!(vrfs.get("eth2").map(String::as_str), Some(""));
+
+        assert!(parse_interface_vrfs("% Invalid input detected at '^' marker.").is_none());
+    }
+
+    #[test]
+    fn test_parse_config_sections() {
+        let eos = "hostname leaf1\nntp server 10.0.0.1\n!\ninterface Ethernet1\n   description to spine1\n   no switchport\n!\nrouter bgp 65001\n   neighbor 10.0.0.0 remote-as 65000\n";
+        let syntax = config_syntax("Arista", eos);
+        let sections = parse_config_sections(eos, &syntax.block_style);
+        let summary: Vec<(&str, usize, usize)> =
+            sections.iter().map(|s| (s.kind.as_str(), s.start_line, s.end_line)).collect();
+        assert_eq!(summary, vec![("system", 1, 2), ("interfaces", 4, 6), ("bgp", 8, 9)]);
+
+        let junos = "system {\n    host-name leaf1;\n}\ninterfaces {\n    ge-0/0/0 {\n        unit 0;\n    }\n}\nprotocols {\n    bgp {\n        group spines;\n    }\n}\n";
+        let syntax = config_syntax("Juniper", junos);
+        assert_eq!(syntax.block_style, "braces");
+        let sections = parse_config_sections(junos, &syntax.block_style);
+        assert_eq!(sections.len(), 3);
+        assert_eq!((sections[1].start_line, sections[1].end_line), (4, 8));
+        assert_eq!(sections[1].children[0].title, "ge-0/0/0");
+        assert_eq!((sections[2].kind.as_str(), sections[2].children[0].kind.as_str()), ("routing", "bgp"));
+
+        let set = "set system host-name leaf1\nset interfaces ge-0/0/0 unit 0\nset interfaces ge-0/0/1 unit 0\n";
+        let syntax = config_syntax("Juniper", set);
+        assert_eq!(syntax.block_style, "set");
+        let sections = parse_config_sections(set, &syntax.block_style);
+        assert_eq!((sections[1].title.as_str(), sections[1].children.len()), ("interfaces", 2));
+    }
+
+    #[test]
+    fn test_find_duplicate_devices() {
+        let now = chrono::Utc::now();
+        let device = |id: i64, mac: Option<&str>, serial: Option<&str>, ip: &str| crate::models::Device {
+            id,
+            mac: mac.map(String::from),
+            ip: ip.to_string(),
+            hostname: format!("dev{}", id),
+            vendor: None,
+            vendor_id: None,
+            model: None,
+            serial_number: serial.map(String::from),
+            config_template: String::new(),
+            credential_id: None,
+            ssh_user: None,
+            ssh_pass: None,
+            topology_id: None,
+            topology_role: None,
+            hall_id: None,
+            row_id: None,
+            rack_id: None,
+            rack_position: None,
+            tenant_id: None,
+            gpu_cluster_id: None,
+            status: "offline".to_string(),
+            device_type: "internal".to_string(),
+            last_seen: None,
+            last_backup: None,
+            last_error: None,
+            custom_fields: Default::default(),
+            created_at: now,
+            updated_at: now,
+        };
+        let devices = vec![
+            device(1, Some("00:1C:73:AA:BB:CC"), Some("sn1"), "10.0.0.1"),
+            device(2, Some("001c.73aa.bbcc"), Some(""), "10.0.0.2"),
+            device(3, None, Some("SN1 "), ""),
+            device(4, Some(""), None, ""),
+            device(5, Some("00:1c:73:ff:fe:aa:bb:cc"), None, ""),
+        ];
+        let sets: Vec<(String, String, Vec<i64>)> = find_duplicate_devices(&devices)
+            .into_iter()
+            .map(|s| (s.match_field, s.value, s.devices.iter().map(|d| d.id).collect()))
+            .collect();
+        assert_eq!(
+            sets,
+            vec![
+                ("mac".to_string(), "00:1c:73:aa:bb:cc".to_string(), vec![1, 2, 5]),
+                ("serial_number".to_string(), "SN1".to_string(), vec![1, 3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*", "reload"));
+        assert!(wildcard_match("show *", "Show version"));
+        assert!(wildcard_match("show * detail", "show interfaces Ethernet1 detail"));
+        assert!(!wildcard_match("show *", "configure terminal"));
+        assert!(!wildcard_match("show*ip*", "show"));
+        assert!(wildcard_match("ping", "PING"));
+        assert!(!wildcard_match("ping", "ping 10.0.0.1"));
+    }
+
+    #[test]
+    fn test_api_resource() {
+        assert_eq!(api_resource("/api/templates"), Some("templates"));
+        assert_eq!(api_resource("/api/job-templates/4/run"), Some("job-templates"));
+        assert_eq!(api_resource("/api/"), None);
+        assert_eq!(api_resource("/configs/leaf1.cfg"), None);
+    }
+
+    #[test]
+    fn test_smtp_data() {
+        assert_eq!(smtp_data("a\n.b\r\n..c"), "a\r\n..b\r\n...c\r\n");
+        assert_eq!(smtp_data(""), "");
+    }
+
+    #[test]
+    fn test_influx_line() {
+        let line = influx_line(
+            "forge_device",
+            &[("hostname", "leaf 1,a"), ("vendor", "")],
+            &[("up", "1i".to_string()), ("status", influx_string("on\"line"))],
+            42,
+        );
+        assert_eq!(line, "forge_device,hostname=leaf\\ 1\\,a up=1i,status=\"on\\\"line\" 42");
+    }
+
+    #[test]
+    fn test_parse_device_clock() {
+        let expected = chrono::NaiveDate::from_ymd_opt(2024, 1, 11)
+            .unwrap()
+            .and_hms_opt(10, 23, 45)
+            .unwrap()
+            .and_utc();
+        let arista = "Thu Jan 11 10:23:45 2024\nTimezone: UTC\nClock source: NTP server (10.0.0.1)\n";
+        assert_eq!(parse_device_clock(arista), Some(expected));
+        assert_eq!(parse_device_clock("*10:23:45.123 UTC Thu Jan 11 2024"), Some(expected));
+        assert_eq!(parse_device_clock("Current time: 2024-01-11 10:23:45 UTC\nSystem booted: ..."), Some(expected));
+        assert_eq!(parse_device_clock("Thu Jan 11 10:23:45 2024\nTimezone: US/Pacific\n"), None);
+        assert_eq!(parse_device_clock("10:23:45.123 PST Thu Jan 11 2024"), None);
+
+        assert_eq!(parse_ntp_synchronized("synchronised to NTP server (10.0.0.1) at stratum 3"), Some(true));
+        assert_eq!(parse_ntp_synchronized("Clock is unsynchronized, stratum 16"), Some(false));
+        assert_eq!(parse_ntp_synchronized("status=0644 leap_none, sync_ntp, 4 events"), Some(true));
+        assert_eq!(parse_ntp_synchronized("NTP is disabled."), None);
+    }
+
+    #[test]
+    fn test_parse_config_session_status() {
+        let arista = "Maximum number of completed sessions: 1\nMaximum number of pending sessions: 2\n\n  \
+                      Name   State     User   Terminal\n  ------ --------- ------ --------\n  \
+                      s1     pending   admin  vty3\n  s2     completed admin\n";
+        assert_eq!(parse_config_session_status(arista), Some((true, "1 of 2 pending sessions in use".to_string())));
+        let full = arista.replace("s2     completed", "s2     pending");
+        assert_eq!(parse_config_session_status(&full).map(|s| s.0), Some(false));
+
+        let cisco = "Parser Configure Lock\n---------------------\nOwner PID : -1\nUser : unknown\n";
+        assert_eq!(parse_config_session_status(cisco).map(|s| s.0), Some(true));
+        let locked = "Owner PID : 231\nUser : netops\n";
+        assert_eq!(
+            parse_config_session_status(locked),
+            Some((false, "configuration locked by netops (pid 231)".to_string()))
+        );
+        assert_eq!(parse_config_session_status("% Invalid input"), None);
+    }
+
+    #[test]
+    fn test_layout_ports_with_role() {
+        let layout = r#"[{"row":1,"sections":[
+            {"label":"Data","ports":[{"col":1,"vendor_port_name":"Ethernet1","speed":10000}]},
+            {"label":"Power","ports":[{"col":2,"vendor_port_name":"PSU1","role":"power"},{"col":3,"vendor_port_name":"PSU2","role":"power"}]},
+            {"label":"Console","ports":[{"col":4,"vendor_port_name":"Console","role":"console"}]}]}]"#;
+        assert_eq!(layout_ports_with_role(layout, "power"), vec!["PSU1", "PSU2"]);
+        assert_eq!(layout_ports_with_role(layout, "console"), vec!["Console"]);
+        assert!(layout_ports_with_role("[]", "power").is_empty());
+        assert!(layout_ports_with_role("not json", "power").is_empty());
+    }
+
+    #[test]
+    fn test_build_rack_elevation() {
+        use crate::models::{Device, DeviceModel, IpamRack};
+
+        let now = chrono::Utc::now();
+        let rack = IpamRack {
+            id: 1, name: "A1".into(), description: None, row_id: 1, row_name: None,
+            width_cm: 60, height_ru: 4, depth_cm: 100, device_count: None, created_at: now, updated_at: now,
+        };
+        let model = |vendor_id, name: &str, rack_units| DeviceModel {
+            id: 0, vendor_id, model: name.into(), display_name: name.into(), rack_units,
+            layout: String::new(), device_count: None, created_at: now, updated_at: now,
+        };
+        let models = vec![model(1, "7280R", 2), model(2, "7280R", 3)];
+        let device = |id, model: &str, rack_position| Device {
+            id, mac: None, ip: String::new(), hostname: format!("d{}", id), vendor: None,
+            vendor_id: Some("1".into()), model: Some(model.into()), serial_number: None,
+            config_template: String::new(), credential_id: None, ssh_user: None, ssh_pass: None,
+            topology_id: None, topology_role: None, hall_id: None, row_id: None, rack_id: Some(1),
+            rack_position, tenant_id: None, gpu_cluster_id: None, status: String::new(), device_type: String::new(), last_seen: None,
+            last_backup: None, last_error: None, custom_fields: Default::default(), created_at: now,
+            updated_at: now,
+        };
+
+        assert_eq!(device_rack_units(Some("2"), Some("7280r"), &models), 3);
+        assert_eq!(device_rack_units(Some("9"), Some("7280R"), &models), 2);
+        assert_eq!(device_rack_units(None, Some("unknown"), &models), 1);
+
+        // d1 takes U1-U2, d2 overlaps it at U2, d3 is unplaced
+        let devices = vec![device(1, "7280R", Some(1)), device(2, "x", Some(2)), device(3, "x", None)];
+        let elevation = build_rack_elevation(rack, &devices, &models);
+        assert_eq!(elevation.used_ru, 2);
+        assert_eq!(elevation.free_ru, 2);
+        let units: Vec<_> = elevation.units.iter().map(|u| (u.position, u.device_id)).collect();
+        assert_eq!(units, vec![(4, None), (3, None), (2, Some(1)), (1, Some(1))]);
+        let conflicts: Vec<_> = elevation.devices.iter().map(|d| (d.device_id, d.conflict)).collect();
+        assert_eq!(conflicts, vec![(2, true), (1, false)]);
+        assert_eq!(elevation.unplaced.len(), 1);
+
+        assert_eq!(find_rack_collision(&elevation, None, 3, 2).map(|d| d.device_id), None);
+        assert_eq!(find_rack_collision(&elevation, None, 2, 1).map(|d| d.device_id), Some(2));
+        assert_eq!(find_rack_collision(&elevation, Some(2), 2, 2).map(|d| d.device_id), Some(1));
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let text = "hostname,cf_owner\r\nsw1,\"Ops, \"\"East\"\"\"\n\nsw2,\"multi\nline\"\nsw3,";
+        let rows = parse_csv(text).unwrap();
+        assert_eq!(rows, vec![
+            vec!["hostname".to_string(), "cf_owner".to_string()],
+            vec!["sw1".to_string(), "Ops, \"East\"".to_string()],
+            vec!["sw2".to_string(), "multi\nline".to_string()],
+            vec!["sw3".to_string(), String::new()],
+        ]);
+        assert!(parse_csv("a,\"b").is_err());
+    }
+
+    #[test]
+    fn test_prefix_utilization() {
+        let prefix = |id: i64, parent_id: Option<i64>, network_int: i64, len: i64, ips: i32| crate::models::IpamPrefix {
+            id, prefix: String::new(), network_int, broadcast_int: network_int + len - 1, prefix_length: 0,
+            description: None, status: String::new(), is_supernet: false, role_ids: vec![], role_names: vec![],
+            parent_id, parent_prefix: None, datacenter_id: None, datacenter_name: None, vlan_id: None,
+            vrf_id: None, vrf_name: None, child_prefix_count: None, ip_address_count: Some(ips),
+            utilization: None, created_at: chrono::Utc::now(), updated_at: chrono::Utc::now(),
+        };
+        // A /24 with two /26 children; one /26 with 16 addresses assigned
+        let prefixes = vec![prefix(1, None, 0, 256, 0), prefix(2, Some(1), 0, 64, 16), prefix(3, Some(1), 64, 64, 0)];
+        let util = prefix_utilization(&prefixes);
+        assert_eq!(util[&1], 50.0);
+        assert_eq!(util[&2], 25.0);
+        assert_eq!(util[&3], 0.0);
+    }
+
+    #[test]
+    fn test_block_prefix_length() {
+        assert_eq!(block_prefix_length(1), 32);
+        assert_eq!(block_prefix_length(2), 31);
+        assert_eq!(block_prefix_length(3), 30);
+        assert_eq!(block_prefix_length(256), 24);
+        assert_eq!(block_prefix_length(257), 23);
+        assert_eq!(block_prefix_length(0), 32);
+    }
+
+    #[test]
+    fn test_port_peer_index() {
+        assert_eq!(port_peer_index("Ethernet12"), Some(12));
+        assert_eq!(port_peer_index("swp3"), Some(3));
+        assert_eq!(port_peer_index("Ethernet1/1"), None);
+        assert_eq!(port_peer_index("mgmt"), None);
+        assert_eq!(port_peer_index("Ethernet0"), None);
+    }
+
+    #[test]
+    fn test_peer_indexes() {
+        assert_eq!(peer_indexes(&["Ethernet3", "Ethernet1"]), vec![3, 1]);
+        assert_eq!(peer_indexes(&["Ethernet1/1", "Ethernet1", "Ethernet1/2"]), vec![2, 1, 3]);
+        assert_eq!(peer_indexes(&["Ethernet2", "Ethernet2"]), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_exceeded_alert_status() {
+        let now = chrono::Utc::now();
+        assert_eq!(exceeded_alert_status(now, 0, now), crate::models::alert_status::FIRING);
+        assert_eq!(exceeded_alert_status(now - chrono::Duration::minutes(30), 60, now), crate::models::alert_status::PENDING);
+        assert_eq!(exceeded_alert_status(now - chrono::Duration::minutes(60), 60, now), crate::models::alert_status::FIRING);
+    }
+
+    #[test]
+    fn test_template_references() {
+        let source = "hostname {{ Hostname | upper }}\n\
+            {% for vrf in VRFs %}vrf {{ vrf.name }} {{ vars.Loopback }}{% endfor %}\n\
+            {% set asn = vars.ASN | default(value=\"65000\") %}{{ asn }}\n\
+            {% if custom_fields.site is defined and Mgmt %}{{ \"{{ Literal }}\" }}{% endif %}\n\
+            {# {{ Commented }} #}{% raw %}{{ Raw }}{% endraw %}{{ range(end=2) | length }}";
+        let refs: Vec<(String, usize)> = template_references(source).into_iter().map(|r| (r.path, r.line)).collect();
+        assert_eq!(refs, vec![
+            ("Hostname".to_string(), 1),
+            ("VRFs".to_string(), 2),
+            ("vars.Loopback".to_string(), 2),
+            ("vars.ASN".to_string(), 3),
+            ("custom_fields.site".to_string(), 4),
+            ("Mgmt".to_string(), 4),
+        ]);
+    }
+
+    #[test]
+    fn test_tera_error_location() {
+        let message = "Failed to parse 'template'\n --> 3:12\n  |\n3 | {{ Hostname | }}\n  |            ^---\n";
+        assert_eq!(tera_error_location(message), Some((3, 12)));
+        assert_eq!(tera_error_location("Variable `x` not found"), None);
+    }
+
+    #[test]
+    fn test_ip_filters() {
+        assert_eq!(ipaddr_query("10.0.0.1/24", "network").unwrap(), "10.0.0.0");
+        assert_eq!(ipaddr_query("10.0.0.1/24", "netmask").unwrap(), "255.255.255.0");
+        assert_eq!(ipaddr_query("10.0.0.1/22", "wildcard").unwrap(), "0.0.3.255");
+        assert_eq!(ipaddr_query("10.0.0.1/24", "broadcast").unwrap(), "10.0.0.255");
+        assert_eq!(ipaddr_query("10.0.0.1/24", "cidr").unwrap(), "10.0.0.0/24");
+        assert_eq!(ipaddr_query("10.0.0.1", "prefix").unwrap(), "32");
+        assert_eq!(ipaddr_query("10.0.0.4/31", "peer").unwrap(), "10.0.0.5");
+        assert_eq!(ipaddr_query("10.0.0.6/30", "peer").unwrap(), "10.0.0.5");
+        assert_eq!(ipaddr_query("2001:db8::/127", "peer").unwrap(), "2001:db8::1");
+        assert!(ipaddr_query("10.0.0.4/30", "peer").is_err());
+        assert!(ipaddr_query("10.0.0.1/33", "network").is_err());
+
+        assert_eq!(ipaddr_index("10.1.0.0/24", 1).unwrap(), "10.1.0.1/24");
+        assert_eq!(ipaddr_index("10.1.0.0/24", -1).unwrap(), "10.1.0.255/24");
+        assert!(ipaddr_index("10.1.0.0/24", 256).is_err());
+        assert_eq!(ipmath("10.255.0.1/32", 255).unwrap(), "10.255.1.0");
+        assert_eq!(ipmath("2001:db8::1", -1).unwrap(), "2001:db8::");
+        assert!(ipmath("255.255.255.255", 1).is_err());
+        assert_eq!(prefix_to_mask("/20").unwrap(), "255.255.240.0");
+        assert_eq!(prefix_to_mask("0").unwrap(), "0.0.0.0");
+    }
+
+    #[test]
+    fn test_mac_and_vlan_filters() {
+        assert_eq!(hwaddr("00:1C:73:0A:bb:cc", "cisco").unwrap(), "001c.730a.bbcc");
+        assert_eq!(hwaddr("001c.730a.bbcc", "linux").unwrap(), "00:1c:73:0a:bb:cc");
+        assert_eq!(hwaddr("001c.730a.bbcc", "unix").unwrap(), "0:1c:73:a:bb:cc");
+        assert_eq!(hwaddr("001c730abbcc", "eui48").unwrap(), "00-1C-73-0A-BB-CC");
+        assert!(hwaddr("00:1c:73:0a:bb", "linux").is_err());
+
+        assert_eq!(vlan_expand("20-22, 10,21").unwrap(), vec![10, 20, 21, 22]);
+        assert!(vlan_expand("5-1").is_err());
+        assert!(vlan_expand("4095").is_err());
+        assert_eq!(vlan_ranges(&[22, 10, 20, 21, 30]), "10,20-22,30");
+    }
+
+    #[test]
+    fn test_template_engine_filters() {
+        let mut tera = template_engine();
+        let mut context = tera::Context::new();
+        context.insert("Loopback", "10.255.0.1/32");
+        let out = tera
+            .render_str(
+                r#"{{ Loopback | ipaddr(query="address") }} {{ "10.0.0.0/31" | ipaddr(index=1) }} {{ 24 | prefix_to_mask }} {{ "1-3" | vlan_expand | vlan_ranges }} {{ "leaf" | b64encode | b64decode }}"#,
+                &context,
+            )
+            .unwrap();
+        assert_eq!(out, "10.255.0.1 10.0.0.1/31 255.255.255.0 1-3 leaf");
+    }
 }
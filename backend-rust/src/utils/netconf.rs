@@ -0,0 +1,261 @@
+//! NETCONF (RFC 6241) client over the SSH "netconf" subsystem, for deploying to devices whose CLI
+//! is awkward to drive through an interactive shell, such as JunOS. Sessions come from the SSH
+//! pool on port 830 and use base:1.0 framing, each message ending in `]]>]]>`.
+
+use std::time::Duration;
+
+use russh::{client, Channel, ChannelMsg};
+
+use super::ssh::{ssh_pool, PooledSession};
+
+pub const NETCONF_PORT: u16 = 830;
+const NETCONF_NS: &str = "urn:ietf:params:xml:ns:netconf:base:1.0";
+const BASE_CAPABILITY: &str = "urn:ietf:params:netconf:base:1.0";
+const END_OF_MESSAGE: &str = "]]>]]>";
+const TIMEOUT_SECS: u64 = 60;
+
+/// First words of JunOS set-style config lines
+const SET_COMMANDS: &[&str] = &["set", "delete", "activate", "deactivate", "insert", "rename", "annotate"];
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Config made only of set-style lines (`set ...`, `delete ...`), ignoring blanks and comments
+fn is_set_style(config: &str) -> bool {
+    config
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .all(|l| l.split_whitespace().next().is_some_and(|word| SET_COMMANDS.contains(&word)))
+}
+
+/// edit-config merging a config into the candidate datastore. XML is sent as the `<config>`;
+/// text goes in JunOS's `<config-text>`, as set commands when every line is one and as
+/// curly-brace configuration otherwise.
+pub(crate) fn edit_config_rpc(config: &str) -> String {
+    let config = config.trim();
+    let body = if config.starts_with('<') {
+        format!("<config>{}</config>", config)
+    } else if is_set_style(config) {
+        format!("<config-text><configuration-set>{}</configuration-set></config-text>", xml_escape(config))
+    } else {
+        format!("<config-text><configuration-text>{}</configuration-text></config-text>", xml_escape(config))
+    };
+    format!(
+        "<edit-config><target><candidate/></target><default-operation>merge</default-operation>{}</edit-config>",
+        body
+    )
+}
+
+/// Commit the candidate; with a timeout, a confirmed commit that the device rolls back unless a
+/// plain commit follows within that many seconds
+pub(crate) fn commit_rpc(confirm_timeout_secs: Option<u64>) -> String {
+    match confirm_timeout_secs {
+        Some(secs) => format!("<commit><confirmed/><confirm-timeout>{}</confirm-timeout></commit>", secs),
+        None => "<commit/>".to_string(),
+    }
+}
+
+/// Contents of each element with the given name, with or without a namespace prefix
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(tag_end) = rest.find('>') else { break };
+        let tag = &rest[..tag_end];
+        let tag_name = tag.split_whitespace().next().unwrap_or_default();
+        if tag.starts_with('/') || tag.ends_with('/') || tag_name.rsplit(':').next() != Some(name) {
+            continue;
+        }
+        let body = &rest[tag_end + 1..];
+        let close = format!("</{}>", tag_name);
+        if let Some(end) = body.find(&close) {
+            found.push(&body[..end]);
+            rest = &body[end + close.len()..];
+        }
+    }
+    found
+}
+
+/// Messages of an rpc-reply's errors and warnings, as (is_error, message)
+pub(crate) fn rpc_errors(reply: &str) -> Vec<(bool, String)> {
+    elements(reply, "rpc-error")
+        .into_iter()
+        .map(|error| {
+            let severity = elements(error, "error-severity").first().map(|s| s.trim()).unwrap_or("error");
+            let message = elements(error, "error-message")
+                .first()
+                .map(|m| m.trim().to_string())
+                .or_else(|| elements(error, "error-tag").first().map(|t| t.trim().to_string()))
+                .unwrap_or_else(|| "unspecified error".to_string());
+            (severity != "warning", message)
+        })
+        .collect()
+}
+
+/// A NETCONF session on a pooled SSH connection. The exchange is logged for the job output.
+pub struct NetconfSession {
+    session: PooledSession,
+    channel: Channel<client::Msg>,
+    buffer: String,
+    message_id: u64,
+    /// The candidate datastore is locked by this session
+    locked: bool,
+    log: Vec<String>,
+}
+
+impl NetconfSession {
+    /// Connect to the device's NETCONF port, start the subsystem and exchange hellos
+    pub async fn open(host: &str, user: &str, pass: &str) -> Result<Self, String> {
+        let mut session = ssh_pool().checkout_port(host, NETCONF_PORT, user, pass, TIMEOUT_SECS).await?;
+        let channel = session.channel().await?;
+        channel
+            .request_subsystem(true, "netconf")
+            .await
+            .map_err(|e| format!("Failed to start NETCONF subsystem: {}", e))?;
+        let mut netconf = Self { session, channel, buffer: String::new(), message_id: 0, locked: false, log: Vec::new() };
+
+        netconf
+            .send(&format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?><hello xmlns="{}"><capabilities><capability>{}</capability></capabilities></hello>"#,
+                NETCONF_NS, BASE_CAPABILITY
+            ))
+            .await?;
+        let hello = netconf.read_message().await?;
+        if !hello.contains(BASE_CAPABILITY) {
+            return Err("Device does not offer NETCONF base:1.0".to_string());
+        }
+        netconf.log.push(format!("connected to {}:{}", host, NETCONF_PORT));
+        Ok(netconf)
+    }
+
+    async fn send(&mut self, message: &str) -> Result<(), String> {
+        let framed = format!("{}\n{}\n", message, END_OF_MESSAGE);
+        self.channel
+            .data(framed.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write NETCONF message: {}", e))
+    }
+
+    async fn read_message(&mut self) -> Result<String, String> {
+        let timeout = Duration::from_secs(TIMEOUT_SECS);
+        loop {
+            if let Some(end) = self.buffer.find(END_OF_MESSAGE) {
+                let message = self.buffer[..end].trim().to_string();
+                self.buffer.drain(..end + END_OF_MESSAGE.len());
+                return Ok(message);
+            }
+            match tokio::time::timeout(timeout, self.channel.wait()).await {
+                Ok(Some(ChannelMsg::Data { data })) => self.buffer.push_str(&String::from_utf8_lossy(&data)),
+                Ok(Some(ChannelMsg::Close)) | Ok(Some(ChannelMsg::Eof)) | Ok(None) => {
+                    return Err("NETCONF session closed by the device".to_string());
+                }
+                Ok(Some(_)) => {}
+                Err(_) => return Err(format!("NETCONF reply timed out after {}s", TIMEOUT_SECS)),
+            }
+        }
+    }
+
+    /// Send an RPC and wait for its reply. rpc-errors fail it; warnings are logged.
+    async fn rpc(&mut self, label: &str, operation: &str) -> Result<String, String> {
+        self.message_id += 1;
+        self.send(&format!(r#"<rpc message-id="{}" xmlns="{}">{}</rpc>"#, self.message_id, NETCONF_NS, operation))
+            .await?;
+        let reply = self.read_message().await?;
+        let (errors, warnings): (Vec<_>, Vec<_>) = rpc_errors(&reply).into_iter().partition(|(is_error, _)| *is_error);
+        for (_, warning) in warnings {
+            self.log.push(format!("{}: warning: {}", label, warning));
+        }
+        if !errors.is_empty() {
+            let message = errors.into_iter().map(|(_, m)| m).collect::<Vec<_>>().join("; ");
+            self.log.push(format!("{}: error: {}", label, message));
+            return Err(format!("{} failed: {}", label, message));
+        }
+        self.log.push(format!("{}: ok", label));
+        Ok(reply)
+    }
+
+    /// Lock the candidate datastore and merge the config into it
+    pub async fn load(&mut self, config: &str) -> Result<(), String> {
+        self.rpc("lock candidate", "<lock><target><candidate/></target></lock>").await?;
+        self.locked = true;
+        self.rpc("edit-config", &edit_config_rpc(config)).await?;
+        Ok(())
+    }
+
+    /// Commit the candidate. A confirmed commit must be followed by a plain commit on this
+    /// session before the timeout, or the device rolls back; so does closing the session first.
+    pub async fn commit(&mut self, confirm_timeout_secs: Option<u64>) -> Result<(), String> {
+        let label = match confirm_timeout_secs {
+            Some(secs) => format!("commit confirmed ({}s)", secs),
+            None => "commit".to_string(),
+        };
+        self.rpc(&label, &commit_rpc(confirm_timeout_secs)).await?;
+        Ok(())
+    }
+
+    /// Discard anything left uncommitted, unlock and end the session, returning the log
+    pub async fn close(mut self) -> String {
+        if self.locked {
+            let _ = self.rpc("discard-changes", "<discard-changes/>").await;
+            let _ = self.rpc("unlock candidate", "<unlock><target><candidate/></target></unlock>").await;
+        }
+        if self.rpc("close-session", "<close-session/>").await.is_ok() {
+            self.channel.close().await.ok();
+            self.session.in_flight = false;
+        }
+        self.log.join("\n")
+    }
+}
+
+/// Load a config and commit it over NETCONF. On failure the candidate changes are discarded
+/// and the error carries the session log.
+pub async fn netconf_deploy(host: &str, user: &str, pass: &str, config: &str) -> Result<String, String> {
+    let mut netconf = NetconfSession::open(host, user, pass).await?;
+    let result = match netconf.load(config).await {
+        Ok(()) => netconf.commit(None).await,
+        Err(e) => Err(e),
+    };
+    let log = netconf.close().await;
+    match result {
+        Ok(()) => Ok(log),
+        Err(e) => Err(format!("{}\n{}", e, log)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_config_rpc() {
+        let set = edit_config_rpc("set system host-name leaf1\n# uplinks\ndelete interfaces et-0/0/1 disable\n");
+        assert!(set.contains("<configuration-set>set system host-name leaf1\n# uplinks\ndelete interfaces et-0/0/1 disable</configuration-set>"));
+        let text = edit_config_rpc("system {\n    host-name \"a&b\";\n}");
+        assert!(text.contains("<configuration-text>system {\n    host-name \"a&amp;b\";\n}</configuration-text>"));
+        let xml = edit_config_rpc("<configuration><system/></configuration>");
+        assert!(xml.contains("<config><configuration><system/></configuration></config>"));
+        assert!(xml.starts_with("<edit-config><target><candidate/></target>"));
+    }
+
+    #[test]
+    fn test_commit_rpc() {
+        assert_eq!(commit_rpc(None), "<commit/>");
+        assert_eq!(commit_rpc(Some(300)), "<commit><confirmed/><confirm-timeout>300</confirm-timeout></commit>");
+    }
+
+    #[test]
+    fn test_rpc_errors() {
+        assert!(rpc_errors(r#"<rpc-reply message-id="1"><ok/></rpc-reply>"#).is_empty());
+        let reply = r#"<rpc-reply xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0">
+            <nc:rpc-error><nc:error-severity>warning</nc:error-severity><nc:error-message>statement not found</nc:error-message></nc:rpc-error>
+            <rpc-error><error-type>protocol</error-type><error-tag>lock-denied</error-tag><error-severity>error</error-severity></rpc-error>
+        </rpc-reply>"#;
+        assert_eq!(
+            rpc_errors(reply),
+            vec![(false, "statement not found".to_string()), (true, "lock-denied".to_string())]
+        );
+    }
+}
@@ -0,0 +1,806 @@
+//! Async SSH client on russh. Authenticated sessions are pooled per device and login so backups,
+//! jobs and probes reuse one connection, each command running on its own channel. A global limit
+//! caps how many SSH operations run at once, and a per-device limit keeps them from piling onto
+//! one device. A per-device circuit breaker stops connecting to a device that keeps failing for a
+//! cooldown, so operations on it fail fast.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use russh::client::{self, Handle, KeyboardInteractiveAuthResponse};
+use russh::{Channel, ChannelMsg};
+use tokio::sync::{broadcast, OwnedSemaphorePermit, Semaphore};
+
+const SSH_PORT: u16 = 22;
+const DEFAULT_MAX_CONCURRENCY: usize = 64;
+const DEFAULT_MAX_PER_DEVICE: usize = 2;
+const DEFAULT_IDLE_SECS: u64 = 60;
+const DEFAULT_BREAKER_THRESHOLD: u32 = 5;
+const DEFAULT_BREAKER_COOLDOWN_SECS: u64 = 300;
+/// Idle sessions kept per device and login
+const MAX_IDLE_PER_KEY: usize = 4;
+
+/// russh event handler. Host keys are accepted without verification: devices are reprovisioned
+/// and replaced too often for a known-hosts list to stay accurate.
+struct Client;
+
+impl client::Handler for Client {
+    type Error = russh::Error;
+
+    async fn check_server_key(&mut self, _key: &russh::keys::PublicKey) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+/// Sessions are shared between callers using the same host, port and login
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+    host: String,
+    port: u16,
+    user: String,
+    pass: String,
+}
+
+struct IdleSession {
+    handle: Handle<Client>,
+    idle_since: Instant,
+}
+
+/// Point-in-time view of the SSH pool
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SshPoolStats {
+    pub max_concurrency: usize,
+    pub max_per_device: usize,
+    pub in_use: usize,
+    pub idle_sessions: usize,
+    pub idle_timeout_secs: u64,
+    /// Operations since startup that waited because their device was at its limit
+    pub throttled: u64,
+}
+
+/// Consecutive failures to reach a device host. Once they reach the threshold the breaker opens:
+/// SSH to the host fails without connecting until `open_until`, after which a single probe
+/// connection is let through while other callers keep failing fast, and another failure opens it
+/// again. A rejected login doesn't count, since the device answered.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SshBreaker {
+    pub host: String,
+    pub failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_until: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_error: String,
+    /// The half-open probe connection is in flight
+    #[serde(skip)]
+    probing: bool,
+}
+
+impl SshBreaker {
+    fn is_open(&self) -> bool {
+        self.open_until.is_some_and(|until| until > chrono::Utc::now())
+    }
+
+    /// The cooldown has passed and the next connection is a probe
+    fn is_half_open(&self) -> bool {
+        self.open_until.is_some() && !self.is_open()
+    }
+
+    /// Why a connection to the host should fail fast, if it should
+    fn rejection(&self) -> Option<String> {
+        if self.is_open() {
+            Some(format!(
+                "SSH circuit breaker open after {} consecutive failures (last: {}); retrying after {}",
+                self.failures,
+                self.last_error,
+                self.open_until.map(|t| t.to_rfc3339()).unwrap_or_default()
+            ))
+        } else if self.probing {
+            Some(format!(
+                "SSH circuit breaker half-open after {} consecutive failures (last: {}); waiting on a probe connection",
+                self.failures, self.last_error
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Why a connection attempt failed
+enum ConnectError {
+    /// TCP, handshake or timeout failure; counts toward the host's breaker
+    Unreachable(String),
+    /// The device refused the login
+    Rejected(String),
+}
+
+impl From<ConnectError> for String {
+    fn from(e: ConnectError) -> Self {
+        match e {
+            ConnectError::Unreachable(msg) | ConnectError::Rejected(msg) => msg,
+        }
+    }
+}
+
+/// Clears a host's probe flag when the probe connection ends, including when its caller is
+/// cancelled mid-connect
+struct ProbeGuard<'a> {
+    pool: &'a SshPool,
+    host: &'a str,
+}
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        let mut breakers = self.pool.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(breaker) = breakers.get_mut(self.host) {
+            breaker.probing = false;
+        }
+    }
+}
+
+pub struct SshPool {
+    limit: Arc<Semaphore>,
+    max_concurrency: usize,
+    max_per_device: usize,
+    /// Slots per device host, dropped once nothing holds or waits for them
+    device_limits: Mutex<HashMap<String, Arc<Semaphore>>>,
+    throttled: AtomicU64,
+    /// Consecutive failures that open a breaker; 0 disables breakers
+    breaker_threshold: u32,
+    breaker_cooldown: Duration,
+    /// Hosts whose last connection attempts failed
+    breakers: Mutex<HashMap<String, SshBreaker>>,
+    breaker_opened: broadcast::Sender<SshBreaker>,
+    idle_timeout: Duration,
+    idle: Mutex<HashMap<PoolKey, Vec<IdleSession>>>,
+}
+
+static POOL: OnceLock<SshPool> = OnceLock::new();
+
+/// Configure the shared SSH pool and start closing idle sessions. Call once at startup; SSH use
+/// before then gets the defaults.
+pub fn init_ssh_pool(
+    max_concurrency: usize,
+    max_per_device: usize,
+    idle_timeout_secs: u64,
+    breaker_threshold: u32,
+    breaker_cooldown_secs: u64,
+) {
+    let pool = SshPool::new(max_concurrency, max_per_device, idle_timeout_secs, breaker_threshold, breaker_cooldown_secs);
+    if POOL.set(pool).is_err() {
+        tracing::warn!("SSH pool already initialized, keeping its settings");
+        return;
+    }
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(Duration::from_secs(15));
+        loop {
+            interval.tick().await;
+            ssh_pool().prune();
+        }
+    });
+}
+
+pub(super) fn ssh_pool() -> &'static SshPool {
+    POOL.get_or_init(|| {
+        SshPool::new(
+            DEFAULT_MAX_CONCURRENCY,
+            DEFAULT_MAX_PER_DEVICE,
+            DEFAULT_IDLE_SECS,
+            DEFAULT_BREAKER_THRESHOLD,
+            DEFAULT_BREAKER_COOLDOWN_SECS,
+        )
+    })
+}
+
+pub fn ssh_pool_stats() -> SshPoolStats {
+    let pool = ssh_pool();
+    SshPoolStats {
+        max_concurrency: pool.max_concurrency,
+        max_per_device: pool.max_per_device,
+        in_use: pool.max_concurrency - pool.limit.available_permits(),
+        idle_sessions: pool.idle.lock().map(|idle| idle.values().map(Vec::len).sum()).unwrap_or(0),
+        idle_timeout_secs: pool.idle_timeout.as_secs(),
+        throttled: pool.throttled.load(Ordering::Relaxed),
+    }
+}
+
+/// Hosts with failed connection attempts, open breakers first
+pub fn ssh_breakers() -> Vec<SshBreaker> {
+    let mut breakers: Vec<SshBreaker> = ssh_pool()
+        .breakers
+        .lock()
+        .map(|breakers| breakers.values().cloned().collect())
+        .unwrap_or_default();
+    breakers.sort_by(|a, b| b.is_open().cmp(&a.is_open()).then_with(|| a.host.cmp(&b.host)));
+    breakers
+}
+
+/// Close a host's breaker and clear its failures; false if it had none
+pub fn reset_ssh_breaker(host: &str) -> bool {
+    ssh_pool().breakers.lock().unwrap_or_else(|e| e.into_inner()).remove(host).is_some()
+}
+
+/// Subscribe to breakers as they open
+pub fn subscribe_ssh_breakers() -> broadcast::Receiver<SshBreaker> {
+    ssh_pool().breaker_opened.subscribe()
+}
+
+impl SshPool {
+    fn new(
+        max_concurrency: usize,
+        max_per_device: usize,
+        idle_timeout_secs: u64,
+        breaker_threshold: u32,
+        breaker_cooldown_secs: u64,
+    ) -> Self {
+        let max_concurrency = max_concurrency.max(1);
+        Self {
+            limit: Arc::new(Semaphore::new(max_concurrency)),
+            max_concurrency,
+            max_per_device: max_per_device.max(1),
+            device_limits: Mutex::new(HashMap::new()),
+            throttled: AtomicU64::new(0),
+            breaker_threshold,
+            breaker_cooldown: Duration::from_secs(breaker_cooldown_secs),
+            breakers: Mutex::new(HashMap::new()),
+            breaker_opened: broadcast::channel(64).0,
+            idle_timeout: Duration::from_secs(idle_timeout_secs),
+            idle: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Drop idle sessions that timed out or were closed by the device
+    fn prune(&self) {
+        let Ok(mut idle) = self.idle.lock() else { return };
+        for sessions in idle.values_mut() {
+            sessions.retain(|s| !s.handle.is_closed() && s.idle_since.elapsed() < self.idle_timeout);
+        }
+        idle.retain(|_, sessions| !sessions.is_empty());
+        drop(idle);
+        if let Ok(mut device_limits) = self.device_limits.lock() {
+            device_limits.retain(|_, limit| Arc::strong_count(limit) > 1);
+        }
+    }
+
+    fn take_idle(&self, key: &PoolKey) -> Option<Handle<Client>> {
+        self.prune();
+        let mut idle = self.idle.lock().ok()?;
+        let handle = idle.get_mut(key)?.pop().map(|s| s.handle);
+        if idle.get(key).is_some_and(Vec::is_empty) {
+            idle.remove(key);
+        }
+        handle
+    }
+
+    fn put_idle(&self, key: PoolKey, handle: Handle<Client>) {
+        if handle.is_closed() || self.idle_timeout.is_zero() {
+            return;
+        }
+        let Ok(mut idle) = self.idle.lock() else { return };
+        let sessions = idle.entry(key).or_default();
+        if sessions.len() < MAX_IDLE_PER_KEY {
+            sessions.push(IdleSession { handle, idle_since: Instant::now() });
+        }
+    }
+
+    /// Fail fast while the host's breaker is open or its half-open probe is in flight
+    fn check_breaker(&self, host: &str) -> Result<(), String> {
+        let breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        breakers.get(host).and_then(SshBreaker::rejection).map_or(Ok(()), Err)
+    }
+
+    /// Check the host's breaker before connecting, claiming the probe if it's half-open
+    fn begin_attempt<'a>(&'a self, host: &'a str) -> Result<Option<ProbeGuard<'a>>, String> {
+        let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(breaker) = breakers.get_mut(host) else { return Ok(None) };
+        if let Some(e) = breaker.rejection() {
+            return Err(e);
+        }
+        if !breaker.is_half_open() {
+            return Ok(None);
+        }
+        breaker.probing = true;
+        Ok(Some(ProbeGuard { pool: self, host }))
+    }
+
+    /// Connect through the host's breaker, clearing its failures once the device answers and
+    /// opening it when failures to reach the device hit the threshold
+    async fn connect(&self, key: &PoolKey, timeout_secs: u64) -> Result<Handle<Client>, String> {
+        if self.breaker_threshold == 0 {
+            return connect(key, timeout_secs).await.map_err(String::from);
+        }
+        let _probe = self.begin_attempt(&key.host)?;
+        let result = connect(key, timeout_secs).await;
+        let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        match &result {
+            Ok(_) | Err(ConnectError::Rejected(_)) => {
+                breakers.remove(&key.host);
+            }
+            Err(ConnectError::Unreachable(e)) => {
+                let breaker = breakers.entry(key.host.clone()).or_insert_with(|| SshBreaker {
+                    host: key.host.clone(),
+                    failures: 0,
+                    open_until: None,
+                    last_error: String::new(),
+                    probing: false,
+                });
+                breaker.failures += 1;
+                breaker.last_error = e.clone();
+                if breaker.failures >= self.breaker_threshold {
+                    let cooldown = chrono::Duration::from_std(self.breaker_cooldown).unwrap_or_default();
+                    breaker.open_until = Some(chrono::Utc::now() + cooldown);
+                    tracing::warn!(
+                        "SSH circuit breaker opened for {} after {} consecutive failures: {}",
+                        key.host, breaker.failures, e
+                    );
+                    let _ = self.breaker_opened.send(breaker.clone());
+                }
+            }
+        }
+        drop(breakers);
+        result.map_err(String::from)
+    }
+
+    /// Wait for a slot on the device, counting the operations that had to wait
+    async fn device_permit(&self, host: &str) -> Result<OwnedSemaphorePermit, String> {
+        let limit = self
+            .device_limits
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(host.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_per_device)))
+            .clone();
+        if let Ok(permit) = limit.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!("SSH operation on {} waiting for a device slot", host);
+        limit.acquire_owned().await.map_err(|e| format!("SSH pool closed: {}", e))
+    }
+
+    /// Wait for a slot on the device and then a global slot, then take an idle session for the
+    /// login or open a new one. The device slot comes first so an operation queued behind a busy
+    /// device doesn't hold a global slot.
+    async fn checkout(&'static self, host: &str, user: &str, pass: &str, timeout_secs: u64) -> Result<PooledSession, String> {
+        self.checkout_port(host, SSH_PORT, user, pass, timeout_secs).await
+    }
+
+    /// [`checkout`](Self::checkout) for a service on another port, e.g. NETCONF on 830. The
+    /// device slots and breaker are per host whatever the port.
+    pub(super) async fn checkout_port(
+        &'static self,
+        host: &str,
+        port: u16,
+        user: &str,
+        pass: &str,
+        timeout_secs: u64,
+    ) -> Result<PooledSession, String> {
+        self.check_breaker(host)?;
+        let device_permit = self.device_permit(host).await?;
+        let permit = self
+            .limit
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| format!("SSH pool closed: {}", e))?;
+        let key = PoolKey { host: host.to_string(), port, user: user.to_string(), pass: pass.to_string() };
+        let (handle, reused) = match self.take_idle(&key) {
+            Some(handle) => (handle, true),
+            None => (self.connect(&key, timeout_secs).await?, false),
+        };
+        Ok(PooledSession {
+            pool: self,
+            key,
+            handle: Some(handle),
+            reused,
+            in_flight: false,
+            timeout_secs,
+            _permit: permit,
+            _device_permit: device_permit,
+        })
+    }
+}
+
+/// Connect and authenticate with password, falling back to keyboard-interactive (needed for
+/// Arista EOS and similar), answering every prompt with the password
+async fn connect(key: &PoolKey, timeout_secs: u64) -> Result<Handle<Client>, ConnectError> {
+    let config = Arc::new(client::Config {
+        inactivity_timeout: None,
+        keepalive_interval: Some(Duration::from_secs(30)),
+        ..Default::default()
+    });
+    let timeout = Duration::from_secs(timeout_secs);
+    let mut handle = tokio::time::timeout(timeout, client::connect(config, (key.host.as_str(), key.port), Client))
+        .await
+        .map_err(|_| ConnectError::Unreachable(format!("TCP connection failed: timed out after {}s", timeout_secs)))?
+        .map_err(|e| ConnectError::Unreachable(format!("SSH connection failed: {}", e)))?;
+
+    let authenticated = tokio::time::timeout(timeout, async {
+        if handle.authenticate_password(&key.user, &key.pass).await?.success() {
+            return Ok::<_, russh::Error>(true);
+        }
+        let mut response = handle.authenticate_keyboard_interactive_start(&key.user, None).await?;
+        loop {
+            match response {
+                KeyboardInteractiveAuthResponse::Success => return Ok(true),
+                KeyboardInteractiveAuthResponse::Failure { .. } => return Ok(false),
+                KeyboardInteractiveAuthResponse::InfoRequest { prompts, .. } => {
+                    let answers = prompts.iter().map(|_| key.pass.clone()).collect();
+                    response = handle.authenticate_keyboard_interactive_respond(answers).await?;
+                }
+            }
+        }
+    })
+    .await
+    .map_err(|_| ConnectError::Unreachable(format!("SSH authentication timed out after {}s", timeout_secs)))?
+    .map_err(|e| ConnectError::Unreachable(format!("SSH authentication failed: {}", e)))?;
+
+    if authenticated {
+        Ok(handle)
+    } else {
+        Err(ConnectError::Rejected("SSH authentication failed: all methods exhausted".to_string()))
+    }
+}
+
+/// A session checked out of the pool, holding a global and a device slot. It goes back to the pool when
+/// dropped unless the device closed it or a command was still running on it.
+pub(super) struct PooledSession {
+    pool: &'static SshPool,
+    key: PoolKey,
+    handle: Option<Handle<Client>>,
+    reused: bool,
+    /// A channel is open. A session dropped mid-command (its job was cancelled or timed out) is
+    /// disconnected instead of pooled, which stops the command on the device.
+    pub(super) in_flight: bool,
+    timeout_secs: u64,
+    _permit: OwnedSemaphorePermit,
+    _device_permit: OwnedSemaphorePermit,
+}
+
+impl Drop for PooledSession {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take().filter(|_| !self.in_flight) {
+            self.pool.put_idle(self.key.clone(), handle);
+        }
+    }
+}
+
+impl PooledSession {
+    /// Open a channel, reconnecting once when a pooled session turns out to be dead
+    pub(super) async fn channel(&mut self) -> Result<Channel<client::Msg>, String> {
+        self.in_flight = true;
+        let handle = self.handle.as_ref().ok_or("SSH session closed")?;
+        match handle.channel_open_session().await {
+            Ok(channel) => Ok(channel),
+            Err(_) if self.reused => {
+                self.reused = false;
+                self.handle = Some(self.pool.connect(&self.key, self.timeout_secs).await?);
+                self.channel_fresh().await
+            }
+            Err(e) => Err(format!("Failed to open channel: {}", e)),
+        }
+    }
+
+    async fn channel_fresh(&self) -> Result<Channel<client::Msg>, String> {
+        let handle = self.handle.as_ref().ok_or("SSH session closed")?;
+        handle
+            .channel_open_session()
+            .await
+            .map_err(|e| format!("Failed to open channel: {}", e))
+    }
+
+    /// Run a command on its own channel, returning (stdout, stderr)
+    async fn exec(&mut self, command: &str) -> Result<(String, String), String> {
+        let mut channel = self.channel().await?;
+        channel
+            .exec(true, command)
+            .await
+            .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let timeout = Duration::from_secs(self.timeout_secs);
+        loop {
+            match tokio::time::timeout(timeout, channel.wait()).await {
+                Ok(Some(ChannelMsg::Data { data })) => stdout.extend_from_slice(&data),
+                Ok(Some(ChannelMsg::ExtendedData { data, ext: 1 })) => stderr.extend_from_slice(&data),
+                Ok(Some(ChannelMsg::Close)) | Ok(None) => break,
+                Ok(Some(_)) => {}
+                Err(_) => {
+                    let _ = channel.close().await;
+                    return Err(format!("Failed to read output: timed out after {}s", self.timeout_secs));
+                }
+            }
+        }
+        self.in_flight = false;
+        Ok((String::from_utf8_lossy(&stdout).into_owned(), String::from_utf8_lossy(&stderr).into_owned()))
+    }
+
+    /// Run a command, returning its trimmed output, or None when it fails or prints an error
+    async fn exec_output(&mut self, command: &str) -> Option<String> {
+        let (stdout, _) = self.exec(command).await.ok()?;
+        let trimmed = stdout.trim();
+        // Skip outputs that look like error messages
+        if trimmed.is_empty() || trimmed.starts_with('%') || trimmed.contains("Invalid input") || trimmed.contains("not found") {
+            return None;
+        }
+        Some(trimmed.to_string())
+    }
+
+    /// Try commands in order, returning the first successful output
+    async fn try_first(&mut self, commands: &[&str]) -> Option<String> {
+        for cmd in commands {
+            if let Some(output) = self.exec_output(cmd).await {
+                return Some(output);
+            }
+        }
+        None
+    }
+}
+
+/// Collect channel output for `wait`, or until the channel closes
+async fn drain(channel: &mut Channel<client::Msg>, output: &mut String, wait: Duration) {
+    let deadline = tokio::time::Instant::now() + wait;
+    while let Ok(msg) = tokio::time::timeout_at(deadline, channel.wait()).await {
+        match msg {
+            Some(ChannelMsg::Data { data }) | Some(ChannelMsg::ExtendedData { data, .. }) => {
+                output.push_str(&String::from_utf8_lossy(&data));
+            }
+            Some(ChannelMsg::Close) | None => break,
+            Some(_) => {}
+        }
+    }
+}
+
+/// Combine stdout and stderr
+fn combine_output(stdout: String, stderr: String) -> String {
+    if !stdout.is_empty() && !stderr.is_empty() {
+        format!("{}\n{}", stdout, stderr)
+    } else if !stderr.is_empty() {
+        stderr
+    } else {
+        stdout
+    }
+}
+
+/// Run a single command, returning its combined stdout and stderr
+pub async fn ssh_run_command_async(host: &str, user: &str, pass: &str, command: &str) -> Result<String, String> {
+    let mut session = ssh_pool().checkout(host, user, pass, 30).await?;
+    let (stdout, stderr) = session.exec(command).await?;
+    Ok(combine_output(stdout, stderr))
+}
+
+/// Send multi-line commands via an interactive shell (PTY). This is needed for network devices
+/// (EOS, IOS, JunOS) that require entering config mode interactively rather than via exec.
+pub async fn ssh_run_interactive_async(host: &str, user: &str, pass: &str, commands: &str) -> Result<String, String> {
+    let mut session = ssh_pool().checkout(host, user, pass, 60).await?;
+    let mut channel = session.channel().await?;
+
+    // Request a PTY so EOS gives us an interactive CLI
+    channel
+        .request_pty(true, "xterm", 80, 24, 0, 0, &[])
+        .await
+        .map_err(|e| format!("Failed to request PTY: {}", e))?;
+    channel
+        .request_shell(true)
+        .await
+        .map_err(|e| format!("Failed to start shell: {}", e))?;
+
+    let mut output = String::new();
+    let send = |line: &str| format!("{}\n", line).into_bytes();
+
+    // Wait for the initial prompt then drain it
+    drain(&mut channel, &mut output, Duration::from_secs(2)).await;
+
+    // Disable pager so show commands don't paginate with --More--
+    channel.data(&send("terminal length 0")[..]).await.ok();
+    drain(&mut channel, &mut output, Duration::from_millis(500)).await;
+
+    // Send each line, draining output between commands
+    for line in commands.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('!') {
+            continue; // Skip empty lines and EOS comments
+        }
+        channel
+            .data(&send(line)[..])
+            .await
+            .map_err(|e| format!("Failed to write command: {}", e))?;
+        // Give the device time to process and produce output
+        drain(&mut channel, &mut output, Duration::from_millis(500)).await;
+    }
+
+    // Wait longer for any final output (show commands may take time)
+    for _ in 0..10 {
+        let before = output.len();
+        drain(&mut channel, &mut output, Duration::from_millis(500)).await;
+        // If no new output appeared, we're likely done
+        if output.len() == before {
+            break;
+        }
+    }
+
+    // Send exit to close the shell cleanly
+    channel.data(&send("exit")[..]).await.ok();
+    drain(&mut channel, &mut output, Duration::from_millis(500)).await;
+    channel.close().await.ok();
+    session.in_flight = false;
+
+    Ok(output)
+}
+
+/// Log in once and run each command on its own channel. A command that fails or prints an error
+/// gives None; Err means the login itself failed.
+pub async fn ssh_run_commands_async(
+    host: &str,
+    user: &str,
+    pass: &str,
+    commands: &[&str],
+) -> Result<Vec<Option<String>>, String> {
+    let mut session = ssh_pool().checkout(host, user, pass, 15).await?;
+    let mut outputs = Vec::with_capacity(commands.len());
+    for cmd in commands {
+        outputs.push(session.exec_output(cmd).await);
+    }
+    Ok(outputs)
+}
+
+/// Test SSH connectivity and try to run uptime commands
+#[allow(dead_code)]
+pub async fn ssh_test_connection(host: &str, user: &str, pass: &str) -> (bool, Option<String>, Option<String>) {
+    let mut session = match ssh_pool().checkout(host, user, pass, 10).await {
+        Ok(session) => session,
+        Err(e) => return (false, None, Some(e)),
+    };
+    for cmd in ["uptime", "show version | include uptime"] {
+        match session.exec(cmd).await {
+            Ok((output, _)) if !output.trim().is_empty() => return (true, Some(output.trim().to_string()), None),
+            Ok(_) => {}
+            Err(e) => return (true, Some(format!("Connected (session error: {})", e)), None),
+        }
+    }
+    (true, Some("Connected (uptime command not available)".to_string()), None)
+}
+
+/// Result from probing a device via SSH
+pub struct DeviceProbeResult {
+    pub uptime: Option<String>,
+    pub hostname: Option<String>,
+    pub version: Option<String>,
+    pub interfaces: Option<String>,
+}
+
+/// Truncate output to a maximum number of lines
+fn truncate_lines(s: &str, max_lines: usize) -> String {
+    let lines: Vec<&str> = s.lines().collect();
+    if lines.len() <= max_lines {
+        s.to_string()
+    } else {
+        let truncated: Vec<&str> = lines[..max_lines].to_vec();
+        format!("{}\n... ({} more lines)", truncated.join("\n"), lines.len() - max_lines)
+    }
+}
+
+/// Apply an output parser to probe output: each line matching the pattern becomes its capture
+/// groups joined by spaces (or the whole match when there are none). Output the pattern doesn't
+/// match, or an invalid pattern, leaves the output as it was.
+fn parse_probe_output(output: &str, parser: &crate::models::OutputParser) -> String {
+    let Ok(re) = regex_lite::Regex::new(&parser.pattern) else {
+        return output.to_string();
+    };
+    let matches: Vec<String> = output
+        .lines()
+        .filter_map(|line| re.captures(line))
+        .map(|caps| {
+            if caps.len() == 1 {
+                return caps[0].trim().to_string();
+            }
+            caps.iter().skip(1).flatten().map(|m| m.as_str().trim()).collect::<Vec<_>>().join(" ")
+        })
+        .filter(|m| !m.is_empty())
+        .collect();
+    if matches.is_empty() {
+        output.to_string()
+    } else {
+        matches.join("\n")
+    }
+}
+
+/// Probe a device via SSH, running each step's commands on one session.
+/// Returns (connected, probe_result, error)
+pub async fn ssh_probe_device(
+    host: &str,
+    user: &str,
+    pass: &str,
+    steps: &[crate::models::ProbeStep],
+) -> (bool, DeviceProbeResult, Option<String>) {
+    use crate::models::probe_field;
+
+    let mut probe = DeviceProbeResult { uptime: None, hostname: None, version: None, interfaces: None };
+    let mut session = match ssh_pool().checkout(host, user, pass, 15).await {
+        Ok(session) => session,
+        Err(e) => return (false, probe, Some(e)),
+    };
+
+    for step in steps {
+        let commands: Vec<&str> = step.commands.iter().map(String::as_str).collect();
+        let Some(mut output) = session.try_first(&commands).await else {
+            continue;
+        };
+        if let Some(parser) = &step.parser {
+            output = parse_probe_output(&output, parser);
+        }
+        if step.max_lines > 0 {
+            output = truncate_lines(&output, step.max_lines as usize);
+        }
+        let slot = match step.field.as_str() {
+            probe_field::UPTIME => &mut probe.uptime,
+            probe_field::HOSTNAME => &mut probe.hostname,
+            probe_field::VERSION => &mut probe.version,
+            probe_field::INTERFACES => &mut probe.interfaces,
+            _ => continue,
+        };
+        *slot = Some(output);
+    }
+    (true, probe, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaker_half_open_allows_one_probe() {
+        let pool = SshPool::new(4, 2, 60, 3, 300);
+        pool.breakers.lock().unwrap().insert("10.0.0.1".to_string(), SshBreaker {
+            host: "10.0.0.1".to_string(),
+            failures: 3,
+            open_until: Some(chrono::Utc::now() - chrono::Duration::seconds(1)),
+            last_error: "SSH connection failed: refused".to_string(),
+            probing: false,
+        });
+        let probe = pool.begin_attempt("10.0.0.1").unwrap();
+        assert!(probe.is_some());
+        assert!(pool.begin_attempt("10.0.0.1").is_err());
+        assert!(pool.check_breaker("10.0.0.1").is_err());
+        drop(probe);
+        assert!(pool.check_breaker("10.0.0.1").is_ok());
+        assert!(pool.begin_attempt("10.0.0.2").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_combine_output() {
+        assert_eq!(combine_output("out".into(), "err".into()), "out\nerr");
+        assert_eq!(combine_output(String::new(), "err".into()), "err");
+        assert_eq!(combine_output("out".into(), String::new()), "out");
+    }
+
+    #[test]
+    fn test_truncate_lines() {
+        assert_eq!(truncate_lines("a\nb", 5), "a\nb");
+        assert_eq!(truncate_lines("a\nb\nc", 2), "a\nb\n... (1 more lines)");
+    }
+
+    #[test]
+    fn test_parse_probe_output() {
+        let parser = |pattern: &str| crate::models::OutputParser {
+            id: 1,
+            name: "test".into(),
+            description: None,
+            pattern: pattern.into(),
+            extract_names: String::new(),
+            enabled: true,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        let hostname = parser(r"^\s*(?:Hostname:\s*|hostname\s+)?([A-Za-z0-9][\w.-]*)\s*$");
+        assert_eq!(parse_probe_output("Hostname: spine1\nFQDN:     spine1.lab", &hostname), "spine1");
+        assert_eq!(parse_probe_output("hostname leaf-2", &hostname), "leaf-2");
+        assert_eq!(parse_probe_output("% Invalid input", &hostname), "% Invalid input");
+
+        let pairs = parser(r"^(\S+)\s+(\S+)$");
+        assert_eq!(parse_probe_output("Et1 up\nEt2 down\nheader line here", &pairs), "Et1 up\nEt2 down");
+        assert_eq!(parse_probe_output("up 3 days", &parser(r"\d+ days")), "3 days");
+        assert_eq!(parse_probe_output("raw", &parser("(")), "raw");
+    }
+}
@@ -0,0 +1,327 @@
+//! Networking filters for config templates, named after their Jinja2 (Ansible) counterparts so
+//! templates ported from Ansible keep working: IP math, masks, MAC formats, VLAN ranges and base64.
+//! Arguments are named, as Tera requires: `ipaddr(query="netmask")`, `ipmath(n=1)`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use tera::{Tera, Value};
+
+/// Tera instance with the networking filters registered; every device config render uses one
+pub fn template_engine() -> Tera {
+    let mut tera = Tera::default();
+    tera.register_filter("ipaddr", ipaddr_filter);
+    tera.register_filter("ipmath", ipmath_filter);
+    tera.register_filter("prefix_to_mask", prefix_to_mask_filter);
+    tera.register_filter("hwaddr", hwaddr_filter);
+    tera.register_filter("vlan_expand", vlan_expand_filter);
+    tera.register_filter("vlan_ranges", vlan_ranges_filter);
+    tera.register_filter("b64encode", b64encode_filter);
+    tera.register_filter("b64decode", b64decode_filter);
+    tera
+}
+
+/// An address with the prefix length it was written with, if any
+struct IpInput {
+    addr: IpAddr,
+    prefix: Option<u8>,
+}
+
+impl IpInput {
+    fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let (addr, prefix) = match value.split_once('/') {
+            Some((addr, len)) => (addr, Some(len.parse::<u8>().map_err(|_| format!("invalid prefix length in '{}'", value))?)),
+            None => (value, None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| format!("'{}' is not an IP address", value))?;
+        if prefix.is_some_and(|len| u32::from(len) > bits(&addr)) {
+            return Err(format!("prefix length out of range in '{}'", value));
+        }
+        Ok(Self { addr, prefix })
+    }
+
+    fn prefix_len(&self) -> u8 {
+        self.prefix.unwrap_or(bits(&self.addr) as u8)
+    }
+
+    fn mask(&self) -> u128 {
+        let bits = bits(&self.addr);
+        let len = u32::from(self.prefix_len());
+        if len == 0 {
+            0
+        } else {
+            (u128::MAX << (128 - len)) >> (128 - bits)
+        }
+    }
+
+    fn hostmask(&self) -> u128 {
+        !self.mask() & full_mask(&self.addr)
+    }
+
+    fn network(&self) -> u128 {
+        to_u128(&self.addr) & self.mask()
+    }
+
+    fn broadcast(&self) -> u128 {
+        self.network() | self.hostmask()
+    }
+}
+
+fn bits(addr: &IpAddr) -> u32 {
+    if addr.is_ipv4() { 32 } else { 128 }
+}
+
+fn full_mask(addr: &IpAddr) -> u128 {
+    if addr.is_ipv4() { u128::from(u32::MAX) } else { u128::MAX }
+}
+
+fn to_u128(addr: &IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(v4) => u128::from(u32::from(*v4)),
+        IpAddr::V6(v6) => u128::from(*v6),
+    }
+}
+
+/// An address of the same family as `like`
+fn from_u128(like: &IpAddr, value: u128) -> IpAddr {
+    match like {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from(value as u32)),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(value)),
+    }
+}
+
+/// Add a signed offset to an address, failing outside the address family's range
+fn offset(addr: &IpAddr, n: i64) -> Result<u128, String> {
+    let value = to_u128(addr);
+    let result = if n >= 0 {
+        value.checked_add(n as u128)
+    } else {
+        value.checked_sub(n.unsigned_abs() as u128)
+    };
+    result
+        .filter(|v| *v <= full_mask(addr))
+        .ok_or_else(|| format!("{} {:+} is out of range", addr, n))
+}
+
+/// One part of an address or prefix, like Ansible's `ipaddr(query)`: address, network, netmask,
+/// wildcard (or hostmask), prefix, broadcast, cidr (network/length), or peer (the other end of
+/// a /31 or /30, or an IPv6 /127)
+pub fn ipaddr_query(value: &str, query: &str) -> Result<String, String> {
+    let ip = IpInput::parse(value)?;
+    let family = ip.addr;
+    Ok(match query {
+        "address" => ip.addr.to_string(),
+        "network" => from_u128(&family, ip.network()).to_string(),
+        "netmask" => from_u128(&family, ip.mask()).to_string(),
+        "wildcard" | "hostmask" => from_u128(&family, ip.hostmask()).to_string(),
+        "prefix" => ip.prefix_len().to_string(),
+        "broadcast" => from_u128(&family, ip.broadcast()).to_string(),
+        "cidr" => format!("{}/{}", from_u128(&family, ip.network()), ip.prefix_len()),
+        "peer" => {
+            let host = to_u128(&ip.addr) & ip.hostmask();
+            let peer = match (bits(&family) - u32::from(ip.prefix_len()), host) {
+                (1, _) => to_u128(&ip.addr) ^ 1,
+                (2, 1) => ip.network() + 2,
+                (2, 2) => ip.network() + 1,
+                _ => return Err(format!("'{}' is not a point-to-point address (/31, /30 host or /127)", value)),
+            };
+            from_u128(&family, peer).to_string()
+        }
+        other => return Err(format!("unknown ipaddr query '{}'", other)),
+    })
+}
+
+/// The `index`th address of the prefix `value` is in, keeping the prefix length, like Ansible's
+/// `ipaddr(N)`; negative indexes count back from the broadcast address (-1 is the last)
+pub fn ipaddr_index(value: &str, index: i64) -> Result<String, String> {
+    let ip = IpInput::parse(value)?;
+    let (network, broadcast) = (ip.network(), ip.broadcast());
+    let addr = if index >= 0 {
+        network.checked_add(index as u128)
+    } else {
+        broadcast.checked_sub(u128::from(index.unsigned_abs() - 1))
+    }
+    .filter(|a| *a >= network && *a <= broadcast)
+    .ok_or_else(|| format!("index {} is outside {}/{}", index, from_u128(&ip.addr, network), ip.prefix_len()))?;
+    Ok(format!("{}/{}", from_u128(&ip.addr, addr), ip.prefix_len()))
+}
+
+/// An address moved by `n`, dropping any prefix length, like Ansible's `ipmath(n)`
+pub fn ipmath(value: &str, n: i64) -> Result<String, String> {
+    let ip = IpInput::parse(value)?;
+    Ok(from_u128(&ip.addr, offset(&ip.addr, n)?).to_string())
+}
+
+/// Dotted IPv4 netmask of a prefix length given as `24` or `/24`
+pub fn prefix_to_mask(prefix: &str) -> Result<String, String> {
+    let len: u8 = prefix
+        .trim()
+        .trim_start_matches('/')
+        .parse()
+        .ok()
+        .filter(|len| *len <= 32)
+        .ok_or_else(|| format!("'{}' is not an IPv4 prefix length", prefix))?;
+    ipaddr_query(&format!("0.0.0.0/{}", len), "netmask")
+}
+
+/// A MAC address in one of Ansible's `hwaddr` formats: linux (aa:bb:cc:dd:ee:ff, the default),
+/// unix (leading zeros dropped), cisco (aabb.ccdd.eeff), eui48 (AA-BB-CC-DD-EE-FF),
+/// bare (AABBCCDDEEFF) or pgsql (aabbcc:ddeeff)
+pub fn hwaddr(value: &str, format: &str) -> Result<String, String> {
+    let hex: String = value.chars().filter(|c| c.is_ascii_hexdigit()).collect::<String>().to_lowercase();
+    let separators = value.chars().filter(|c| !c.is_ascii_hexdigit()).all(|c| matches!(c, ':' | '-' | '.' | ' '));
+    if hex.len() != 12 || !separators {
+        return Err(format!("'{}' is not a MAC address", value));
+    }
+    let octets: Vec<&str> = (0..6).map(|i| &hex[i * 2..i * 2 + 2]).collect();
+    Ok(match format {
+        "linux" => octets.join(":"),
+        "unix" => octets
+            .iter()
+            .map(|o| o.strip_prefix('0').unwrap_or(o))
+            .collect::<Vec<_>>()
+            .join(":"),
+        "cisco" => format!("{}.{}.{}", &hex[0..4], &hex[4..8], &hex[8..12]),
+        "eui48" => octets.join("-").to_uppercase(),
+        "bare" => hex.to_uppercase(),
+        "pgsql" => format!("{}:{}", &hex[0..6], &hex[6..12]),
+        other => return Err(format!("unknown hwaddr format '{}'", other)),
+    })
+}
+
+/// VLAN IDs of a list like `10,20-22` in order, without duplicates
+pub fn vlan_expand(value: &str) -> Result<Vec<u16>, String> {
+    let mut vlans = BTreeSet::new();
+    for part in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (start, end) = part.split_once('-').unwrap_or((part, part));
+        let parse = |s: &str| {
+            s.trim()
+                .parse::<u16>()
+                .ok()
+                .filter(|v| (1..=4094).contains(v))
+                .ok_or_else(|| format!("'{}' is not a VLAN ID (1-4094)", s.trim()))
+        };
+        let (start, end) = (parse(start)?, parse(end)?);
+        if start > end {
+            return Err(format!("VLAN range '{}' is reversed", part));
+        }
+        vlans.extend(start..=end);
+    }
+    Ok(vlans.into_iter().collect())
+}
+
+/// VLAN IDs compacted into ranges: `[10, 20, 21, 22]` becomes `10,20-22`
+pub fn vlan_ranges(vlans: &[u16]) -> String {
+    let mut sorted = vlans.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let mut ranges: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < sorted.len() {
+        let start = sorted[i];
+        while i + 1 < sorted.len() && sorted[i + 1] == sorted[i] + 1 {
+            i += 1;
+        }
+        ranges.push(if sorted[i] == start { start.to_string() } else { format!("{}-{}", start, sorted[i]) });
+        i += 1;
+    }
+    ranges.join(",")
+}
+
+/// The filter input as a string; numbers are accepted so `{{ 24 | prefix_to_mask }}` works
+fn input_string(value: &Value, filter: &str) -> tera::Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        _ => Err(tera::Error::msg(format!("filter `{}` expects a string", filter))),
+    }
+}
+
+fn int_arg(args: &HashMap<String, Value>, name: &str, filter: &str) -> tera::Result<Option<i64>> {
+    match args.get(name) {
+        None => Ok(None),
+        Some(Value::Number(n)) => n
+            .as_i64()
+            .map(Some)
+            .ok_or_else(|| tera::Error::msg(format!("filter `{}`: `{}` must be an integer", filter, name))),
+        Some(Value::String(s)) => s
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| tera::Error::msg(format!("filter `{}`: `{}` must be an integer", filter, name))),
+        Some(_) => Err(tera::Error::msg(format!("filter `{}`: `{}` must be an integer", filter, name))),
+    }
+}
+
+fn str_arg<'a>(args: &'a HashMap<String, Value>, name: &str) -> Option<&'a str> {
+    args.get(name).and_then(Value::as_str)
+}
+
+fn filter_result(filter: &str, result: Result<String, String>) -> tera::Result<Value> {
+    result
+        .map(Value::String)
+        .map_err(|e| tera::Error::msg(format!("filter `{}`: {}", filter, e)))
+}
+
+fn ipaddr_filter(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let input = input_string(value, "ipaddr")?;
+    if let Some(index) = int_arg(args, "index", "ipaddr")? {
+        return filter_result("ipaddr", ipaddr_index(&input, index));
+    }
+    filter_result("ipaddr", ipaddr_query(&input, str_arg(args, "query").unwrap_or("address")))
+}
+
+fn ipmath_filter(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let input = input_string(value, "ipmath")?;
+    let n = int_arg(args, "n", "ipmath")?.unwrap_or(1);
+    filter_result("ipmath", ipmath(&input, n))
+}
+
+fn prefix_to_mask_filter(value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
+    filter_result("prefix_to_mask", prefix_to_mask(&input_string(value, "prefix_to_mask")?))
+}
+
+fn hwaddr_filter(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let input = input_string(value, "hwaddr")?;
+    filter_result("hwaddr", hwaddr(&input, str_arg(args, "format").unwrap_or("linux")))
+}
+
+fn vlan_expand_filter(value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let vlans = vlan_expand(&input_string(value, "vlan_expand")?)
+        .map_err(|e| tera::Error::msg(format!("filter `vlan_expand`: {}", e)))?;
+    Ok(Value::Array(vlans.into_iter().map(Value::from).collect()))
+}
+
+fn vlan_ranges_filter(value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let vlans = match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|v| match v {
+                Value::Number(n) => n.as_u64().and_then(|n| u16::try_from(n).ok()),
+                Value::String(s) => s.trim().parse().ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<u16>>>()
+            .ok_or_else(|| tera::Error::msg("filter `vlan_ranges`: expects VLAN IDs"))?,
+        Value::String(s) => vlan_expand(s).map_err(|e| tera::Error::msg(format!("filter `vlan_ranges`: {}", e)))?,
+        _ => return Err(tera::Error::msg("filter `vlan_ranges` expects an array or a string")),
+    };
+    Ok(Value::String(vlan_ranges(&vlans)))
+}
+
+fn b64encode_filter(value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
+    Ok(Value::String(STANDARD.encode(input_string(value, "b64encode")?)))
+}
+
+fn b64decode_filter(value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let input = input_string(value, "b64decode")?;
+    let bytes = STANDARD
+        .decode(input.trim())
+        .map_err(|e| tera::Error::msg(format!("filter `b64decode`: {}", e)))?;
+    String::from_utf8(bytes)
+        .map(Value::String)
+        .map_err(|_| tera::Error::msg("filter `b64decode`: decoded value is not UTF-8"))
+}
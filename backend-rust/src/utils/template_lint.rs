@@ -0,0 +1,167 @@
+//! Static checks of Tera templates without rendering them: which context variables a template
+//! reads, and where Tera's parser reported a syntax error.
+
+use std::collections::HashSet;
+
+/// Words in expressions that aren't context lookups
+const EXPRESSION_KEYWORDS: &[&str] = &[
+    "and", "or", "not", "in", "is", "as", "true", "false", "True", "False", "none", "None", "loop", "self", "super",
+    "__tera_context",
+];
+
+/// A context variable read by a template, at the line of its first use. Lookups in `vars` and
+/// `custom_fields` keep the key (`vars.Loopback`); anything else is its top-level name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateReference {
+    pub path: String,
+    pub line: usize,
+}
+
+/// Context variables read by a Tera template, in order of first use. Names bound by `for`,
+/// `set` and macro arguments, filter, test and function names, and keyword arguments are left
+/// out. Meant for linting a template that parses; it is a scan, not Tera's own parser.
+pub fn template_references(source: &str) -> Vec<TemplateReference> {
+    let mut refs: Vec<TemplateReference> = Vec::new();
+    let mut locals: HashSet<String> = HashSet::new();
+    let mut in_raw = false;
+    let mut i = 0;
+
+    while let Some(pos) = source[i..].find('{') {
+        let start = i + pos;
+        let close = match source.get(start..start + 2) {
+            Some("{{") => "}}",
+            Some("{%") => "%}",
+            Some("{#") => "#}",
+            _ => {
+                i = start + 1;
+                continue;
+            }
+        };
+        let Some(len) = source[start + 2..].find(close) else {
+            break;
+        };
+        let inner = source[start + 2..start + 2 + len].trim_matches('-').trim();
+        let line = source[..start].matches('\n').count() + 1;
+        i = start + 2 + len + 2;
+
+        match close {
+            "}}" if !in_raw => scan_expression(inner, line, &locals, &mut refs),
+            "%}" => {
+                let (keyword, args) = inner
+                    .split_once(char::is_whitespace)
+                    .map_or((inner, ""), |(k, a)| (k, a.trim()));
+                if in_raw {
+                    in_raw = keyword != "endraw";
+                    continue;
+                }
+                match keyword {
+                    "raw" => in_raw = true,
+                    "for" => {
+                        if let Some((names, iterable)) = args.split_once(" in ") {
+                            locals.extend(names.split(',').map(|n| n.trim().to_string()));
+                            scan_expression(iterable, line, &locals, &mut refs);
+                        }
+                    }
+                    "set" | "set_global" => {
+                        if let Some((name, value)) = args.split_once('=') {
+                            scan_expression(value, line, &locals, &mut refs);
+                            locals.insert(name.trim().to_string());
+                        }
+                    }
+                    "if" | "elif" => scan_expression(args, line, &locals, &mut refs),
+                    "macro" => {
+                        let params = args.split_once('(').map_or("", |(_, p)| p.trim_end_matches(')'));
+                        locals.extend(
+                            params
+                                .split(',')
+                                .map(|p| p.split('=').next().unwrap_or_default().trim().to_string())
+                                .filter(|p| !p.is_empty()),
+                        );
+                    }
+                    // include, extends, import, block, filter, else, end*, break, continue
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    refs
+}
+
+/// Record the context lookups in one expression
+fn scan_expression(expr: &str, line: usize, locals: &HashSet<String>, refs: &mut Vec<TemplateReference>) {
+    let chars: Vec<char> = expr.chars().collect();
+    // The two previous tokens, to tell filter and test names from lookups
+    let mut prev = String::new();
+    let mut prev2 = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let token = if c == '"' || c == '\'' || c == '`' {
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += 1;
+            }
+            i += 1;
+            "\"".to_string()
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            "0".to_string()
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                i += 1;
+            }
+            let path: String = chars[start..i].iter().collect::<String>().trim_end_matches('.').to_string();
+            let mut j = i;
+            while j < chars.len() && chars[j].is_whitespace() {
+                j += 1;
+            }
+            let next = chars.get(j).copied();
+            let after_next = chars.get(j + 1).copied();
+            let is_call = next == Some('(');
+            let is_kwarg = next == Some('=') && after_next != Some('=');
+            let is_namespace = next == Some(':') && after_next == Some(':');
+            let is_filter = prev == "|";
+            let is_test = prev == "is" || (prev == "not" && prev2 == "is");
+
+            let mut segments = path.split('.');
+            let root = segments.next().unwrap_or_default();
+            let lookup = !is_call
+                && !is_kwarg
+                && !is_namespace
+                && !is_filter
+                && !is_test
+                && !EXPRESSION_KEYWORDS.contains(&root)
+                && !locals.contains(root);
+            if lookup {
+                let path = match (root, segments.next()) {
+                    ("vars" | "custom_fields", Some(key)) => format!("{}.{}", root, key),
+                    _ => root.to_string(),
+                };
+                if !refs.iter().any(|r| r.path == path) {
+                    refs.push(TemplateReference { path, line });
+                }
+            }
+            root.to_string()
+        } else if c.is_whitespace() {
+            i += 1;
+            continue;
+        } else {
+            i += 1;
+            c.to_string()
+        };
+        prev2 = std::mem::replace(&mut prev, token);
+    }
+}
+
+/// Line and column of a Tera parse error, from the ` --> line:column` marker in its message chain
+pub fn tera_error_location(message: &str) -> Option<(usize, usize)> {
+    let marker = message.split("--> ").nth(1)?;
+    let location: String = marker.chars().take_while(|c| c.is_ascii_digit() || *c == ':').collect();
+    let (line, column) = location.split_once(':')?;
+    Some((line.parse().ok()?, column.parse().ok()?))
+}
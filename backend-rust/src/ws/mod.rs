@@ -25,6 +25,11 @@ pub enum EventType {
     JobStarted,
     JobCompleted,
     JobFailed,
+    JobCancelled,
+    JobBatchProgress,
+    SshBreakerOpened,
+    SyslogAlert,
+    TelemetryUpdate,
     SystemBroadcast,
     Message,
 }
@@ -48,6 +53,17 @@ pub struct DeviceDiscoveredPayload {
     pub vendor: Option<String>,
 }
 
+/// Payload for device online/offline events
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceStatusPayload {
+    pub device_id: i64,
+    pub hostname: String,
+    pub ip: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
+    pub status: String,
+}
+
 /// Payload for config pull events
 #[derive(Debug, Clone, Serialize)]
 pub struct ConfigPulledPayload {
@@ -59,6 +75,18 @@ pub struct ConfigPulledPayload {
     pub protocol: String,
 }
 
+/// Payload for backup started/completed/failed events, with the backup queue's progress
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupProgressPayload {
+    pub device_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Backups waiting for a free worker
+    pub queued: usize,
+    /// Backups in progress, including this one until it finishes
+    pub running: usize,
+}
+
 /// WebSocket hub manages connections and broadcasts events
 pub struct Hub {
     tx: broadcast::Sender<String>,
@@ -84,8 +112,9 @@ impl Hub {
             }
         };
 
+        // Server-side subscribers such as the automation engine listen without a client
         let count = *self.client_count.read().await;
-        if count > 0 {
+        if self.tx.receiver_count() > 0 {
             if let Err(e) = self.tx.send(data) {
                 tracing::warn!("Error broadcasting WebSocket event: {}", e);
             } else {
@@ -138,6 +167,27 @@ impl Hub {
         .await;
     }
 
+    /// Broadcast a device status change
+    pub async fn broadcast_device_status(&self, device: &crate::models::Device, status: &str) {
+        let event_type = if status == crate::models::device_status::ONLINE {
+            EventType::DeviceOnline
+        } else {
+            EventType::DeviceOffline
+        };
+        self.broadcast_event(Event {
+            event_type,
+            payload: serde_json::to_value(DeviceStatusPayload {
+                device_id: device.id,
+                hostname: device.hostname.clone(),
+                ip: device.ip.clone(),
+                mac: device.mac.clone(),
+                status: status.to_string(),
+            })
+            .unwrap_or_default(),
+        })
+        .await;
+    }
+
     /// Broadcast a job update event
     pub async fn broadcast_job_update(&self, event_type: EventType, job: &crate::models::Job) {
         self.broadcast_event(Event {
@@ -147,10 +197,58 @@ impl Hub {
         .await;
     }
 
+    /// Broadcast a bulk job batch's counts and aggregate status
+    pub async fn broadcast_job_batch_progress(&self, batch: &crate::models::JobBatch) {
+        self.broadcast_event(Event {
+            event_type: EventType::JobBatchProgress,
+            payload: serde_json::to_value(batch).unwrap_or_default(),
+        })
+        .await;
+    }
+
+    /// Broadcast a backup progress event
+    pub async fn broadcast_backup_progress(&self, event_type: EventType, payload: BackupProgressPayload) {
+        self.broadcast_event(Event {
+            event_type,
+            payload: serde_json::to_value(payload).unwrap_or_default(),
+        })
+        .await;
+    }
+
+    /// Tell clients when SSH to a device starts failing fast, for every breaker the SSH pool opens
+    pub fn forward_ssh_breakers(self: &Arc<Self>) {
+        let hub = self.clone();
+        let mut rx = crate::utils::subscribe_ssh_breakers();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(breaker) => {
+                        hub.broadcast_event(Event {
+                            event_type: EventType::SshBreakerOpened,
+                            payload: serde_json::to_value(breaker).unwrap_or_default(),
+                        })
+                        .await;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Broadcast a syslog alert event
+    pub async fn broadcast_syslog_alert(&self, alert: &crate::models::SyslogAlert) {
+        self.broadcast_event(Event {
+            event_type: EventType::SyslogAlert,
+            payload: serde_json::to_value(alert).unwrap_or_default(),
+        })
+        .await;
+    }
+
     /// Broadcast arbitrary JSON to all connected clients, returns client count
     pub async fn broadcast_json(&self, data: serde_json::Value) -> usize {
         let count = *self.client_count.read().await;
-        if count > 0 {
+        if self.tx.receiver_count() > 0 {
             match serde_json::to_string(&data) {
                 Ok(json) => {
                     if let Err(e) = self.tx.send(json) {
@@ -172,8 +270,8 @@ impl Hub {
         *self.client_count.read().await
     }
 
-    /// Subscribe to events
-    fn subscribe(&self) -> broadcast::Receiver<String> {
+    /// Subscribe to events, serialized as JSON
+    pub fn subscribe(&self) -> broadcast::Receiver<String> {
         self.tx.subscribe()
     }
 
@@ -15,6 +15,8 @@ import {
   navigateAction,
   usePersistedTab,
   formatRelativeTime,
+  formatJobOutput,
+  formatJobSummary,
   getJobTypeBadgeVariant,
   getHttpMethodBadgeVariant,
   getVendorFilterOptions,
@@ -754,15 +756,15 @@ export function Jobs() {
     {
       header: 'Result',
       accessor: (job) => {
-        const text = job.error || job.output;
+        const text = job.error ? job.error.replace(/\n/g, ' ') : formatJobSummary(job);
         if (!text) return <span className="text-muted">—</span>;
         return (
           <span style={job.error ? { color: 'var(--color-error)' } : undefined}>
-            {Cell.truncate(text.replace(/\n/g, ' '), 60)}
+            {Cell.truncate(text, 60)}
           </span>
         );
       },
-      searchValue: (job) => job.error || job.output || '',
+      searchValue: (job) => job.error || formatJobSummary(job),
     },
     {
       header: 'Source',
@@ -1280,9 +1282,9 @@ export function Jobs() {
                 <div style={{ marginTop: '0.75rem' }}>
                   <div style={{ display: 'flex', alignItems: 'center', gap: '4px', marginBottom: '4px' }}>
                     <span className="label">Output</span>
-                    <CopyButton text={selectedJob.output} />
+                    <CopyButton text={formatJobOutput(selectedJob)} />
                   </div>
-                  <pre className="command-entry-output">{selectedJob.output}</pre>
+                  <pre className="command-entry-output">{formatJobOutput(selectedJob)}</pre>
                 </div>
               )}
 
@@ -794,7 +794,7 @@ const WS_EVENT_TYPES = [
   'system_broadcast', 'message',
   'device_discovered', 'device_online', 'device_offline',
   'backup_started', 'backup_completed', 'backup_failed',
-  'config_pulled', 'job_queued', 'job_started', 'job_completed', 'job_failed',
+  'config_pulled', 'job_queued', 'job_started', 'job_completed', 'job_failed', 'job_cancelled',
 ];
 
 function BroadcastPanel() {
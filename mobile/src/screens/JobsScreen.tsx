@@ -9,7 +9,7 @@ import {
 } from 'react-native';
 import { MaterialIcons } from '@expo/vector-icons';
 import type { Job, JobStatus, JobType } from '../core';
-import { useJobs, useDevices, formatRelativeTime, getJobTypeBadgeVariant } from '../core';
+import { useJobs, useDevices, formatRelativeTime, formatJobOutput, getJobTypeBadgeVariant } from '../core';
 import {
   Card,
   Button,
@@ -219,7 +219,7 @@ export function JobsScreen() {
                 <Text style={[styles.detailSectionTitle, { color: colors.textPrimary }]}>Output</Text>
                 <View style={[styles.codeBlock, { backgroundColor: colors.bgSecondary }]}>
                   <Text style={[styles.codeText, { color: colors.success, fontFamily: Platform.OS === 'ios' ? 'Menlo' : 'monospace' }]}>
-                    {selectedJob.output}
+                    {formatJobOutput(selectedJob)}
                   </Text>
                 </View>
               </View>
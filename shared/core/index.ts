@@ -220,6 +220,10 @@ export {
   formatMacAddress,
   formatFileSize,
   formatExpiry,
+  parseDiffJobOutput,
+  formatConfigDiff,
+  formatJobOutput,
+  formatJobSummary,
   formatEventType,
   getEventTypeIcon,
   validateMacAddress,
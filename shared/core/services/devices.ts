@@ -1,7 +1,7 @@
 // Device service - handles all device-related API operations
 
 import { BaseService } from './base';
-import type { Device, Backup, ExecCommandResult, Job } from '../types';
+import type { Device, Backup, BulkJobRequest, ConfigDiff, ExecCommandResult, Job, JobBatch } from '../types';
 
 export interface PingResult {
   reachable: boolean;
@@ -91,12 +91,31 @@ export class DeviceService extends BaseService {
     return this.get<Backup[]>(`/devices/${encodeURIComponent(id)}/backups`);
   }
 
+  /** Diff a backup against another backup, or against the rendered config with 'rendered' */
+  async diffBackups(id: number, backupId: number, other: number | 'rendered'): Promise<ConfigDiff> {
+    return this.get<ConfigDiff>(`/devices/${encodeURIComponent(id)}/backups/${backupId}/diff/${other}`);
+  }
+
   async connect(id: number): Promise<ConnectResult> {
-    return this.post<ConnectResult>(`/devices/${encodeURIComponent(id)}/connect`);
+    const job = await this.post<Job>(`/devices/${encodeURIComponent(id)}/connect`);
+    return this.waitForConnectResult(job);
   }
 
   async connectByIp(ip: string, options?: { vendor?: string; ssh_user?: string; ssh_pass?: string }): Promise<ConnectResult> {
-    return this.post<ConnectResult>('/connect', { ip, ...options });
+    const job = await this.post<Job>('/connect', { ip, ...options });
+    return this.waitForConnectResult(job);
+  }
+
+  /** Poll a connect test job until it finishes and return its result */
+  private async waitForConnectResult(job: Job): Promise<ConnectResult> {
+    while (job.status === 'queued' || job.status === 'running') {
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      job = await this.getJob(job.id);
+    }
+    if (job.status !== 'completed' || !job.output) {
+      throw new Error(job.error || `Connect test ${job.status}`);
+    }
+    return JSON.parse(job.output) as ConnectResult;
   }
 
   async getConfig(id: number): Promise<ConfigResult> {
@@ -134,4 +153,12 @@ export class DeviceService extends BaseService {
     const params = deviceId ? `?device_id=${encodeURIComponent(deviceId)}` : '';
     return this.get<Job[]>(`/jobs${params}`);
   }
+
+  async bulkJob(req: BulkJobRequest): Promise<JobBatch> {
+    return this.post<JobBatch>('/jobs/bulk', req);
+  }
+
+  async getJobBatch(id: string): Promise<JobBatch> {
+    return this.get<JobBatch>(`/jobs/batches/${encodeURIComponent(id)}`);
+  }
 }
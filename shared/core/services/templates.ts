@@ -1,7 +1,7 @@
 // Template service - handles all template-related API operations
 
 import { BaseService } from './base';
-import type { Template, TemplateVariable } from '../types';
+import type { Template, TemplateValidation, TemplateVariable } from '../types';
 
 export class TemplateService extends BaseService {
   async list(): Promise<Template[]> {
@@ -38,6 +38,10 @@ export class TemplateService extends BaseService {
     return this.post<{ output: string }>(`/templates/${encodeURIComponent(id)}/preview`, data);
   }
 
+  async validate(content: string, deviceId?: number): Promise<TemplateValidation> {
+    return this.post<TemplateValidation>('/templates/validate', { content, device_id: deviceId });
+  }
+
   async getVariables(): Promise<TemplateVariable[]> {
     return this.get<TemplateVariable[]>('/templates/_/variables');
   }
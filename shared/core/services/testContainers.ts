@@ -32,6 +32,8 @@ export interface UnifiedTopologyRequest {
   super_spine_model?: string;
   spine_to_super_spine_ratio?: number;
   pods?: number;
+  // Spine ASNs: one per pod (default) or one per spine
+  asn_scheme?: 'per-pod' | 'per-device';
   // Physical spacing for cable length estimation
   row_spacing_cm?: number;
   // Rack dimensions
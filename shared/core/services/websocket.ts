@@ -15,6 +15,8 @@ export type WebSocketEventType =
   | 'job_started'
   | 'job_completed'
   | 'job_failed'
+  | 'job_batch_progress'
+  | 'ssh_breaker_opened'
   | 'system_broadcast'
   | 'message';
 
@@ -174,9 +174,40 @@ export interface Backup {
   device_id: number;
   filename: string;
   size: number;
+  content_hash?: string;
+  changed?: boolean;
   created_at: string;
 }
 
+export interface DiffLine {
+  kind: 'added' | 'removed' | 'context';
+  text: string;
+  old_line?: number;
+  new_line?: number;
+}
+
+export interface DiffHunk {
+  old_start: number;
+  old_count: number;
+  new_start: number;
+  new_count: number;
+  lines: DiffLine[];
+}
+
+export interface ConfigDiff {
+  old_label: string;
+  new_label: string;
+  added: number;
+  removed: number;
+  hunks: DiffHunk[];
+}
+
+/** JSON output of a completed diff job */
+export interface DiffJobOutput {
+  diff: ConfigDiff;
+  device_output?: string;
+}
+
 // UI State types
 export type Theme = 'dark' | 'light' | 'plain' | 'solarized' | 'evergreen-dark' | 'evergreen-light' | 'ocean-dark' | 'ocean-light' | 'nautical-dark' | 'nautical-light' | 'contrast-dark' | 'contrast-light';
 
@@ -265,6 +296,26 @@ export interface Template {
   updated_at?: string;
 }
 
+export interface TemplateRevision {
+  commit: string;
+  author: string;
+  message: string;
+  committed_at: string;
+}
+
+export interface TemplateIssue {
+  line?: number;
+  column?: number;
+  message: string;
+}
+
+export interface TemplateValidation {
+  valid: boolean;
+  errors: TemplateIssue[];
+  variables: string[];
+  warnings: TemplateIssue[];
+}
+
 export interface TemplateFormData {
   id?: number | string;
   name: string;
@@ -420,8 +471,8 @@ export interface ExecCommandResult {
 }
 
 // Job types
-export type JobStatus = 'queued' | 'running' | 'completed' | 'failed';
-export type JobType = 'command' | 'deploy' | 'webhook' | 'apply_template';
+export type JobStatus = 'queued' | 'running' | 'completed' | 'failed' | 'cancelled';
+export type JobType = 'command' | 'deploy' | 'diff' | 'webhook' | 'apply_template' | 'connect_test';
 
 export interface Job {
   id: string;
@@ -436,6 +487,32 @@ export interface Job {
   created_at: string;
   started_at: string | null;
   completed_at: string | null;
+  batch_id?: string;
+}
+
+export interface BulkJobRequest {
+  group_id?: number;
+  device_ids?: number[];
+  job_type: 'command' | 'deploy' | 'diff' | 'connect_test';
+  command?: string;
+  timeout_secs?: number;
+}
+
+export interface JobBatch {
+  id: string;
+  job_type: JobType;
+  command: string;
+  group_id: number;
+  created_by: string;
+  created_at: string;
+  status: JobStatus;
+  total: number;
+  queued: number;
+  running: number;
+  completed: number;
+  failed: number;
+  cancelled: number;
+  jobs?: Job[];
 }
 
 // Job template types
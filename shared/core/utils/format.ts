@@ -1,5 +1,7 @@
 // Formatting utilities - platform agnostic
 
+import type { ConfigDiff, DiffJobOutput, Job } from '../types';
+
 export function formatDate(date?: string | Date | null): string {
   if (!date) return '-';
   const d = typeof date === 'string' ? new Date(date) : date;
@@ -53,6 +55,58 @@ export function formatExpiry(expiresAt: string): string {
   return `${diffMins}m`;
 }
 
+/**
+ * Parse the JSON output of a completed diff job
+ * @returns The diff, or null for other jobs and for output that isn't a diff
+ */
+export function parseDiffJobOutput(job: Job): DiffJobOutput | null {
+  if (job.job_type !== 'diff' || !job.output) return null;
+  try {
+    const parsed = JSON.parse(job.output) as DiffJobOutput;
+    return parsed && parsed.diff && Array.isArray(parsed.diff.hunks) ? parsed : null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Render a config diff as unified diff text
+ * @returns Empty string when the configs are identical
+ */
+export function formatConfigDiff(diff: ConfigDiff): string {
+  if (diff.hunks.length === 0) return '';
+  const lines = [`--- ${diff.old_label}`, `+++ ${diff.new_label}`];
+  for (const hunk of diff.hunks) {
+    lines.push(`@@ -${hunk.old_start},${hunk.old_count} +${hunk.new_start},${hunk.new_count} @@`);
+    for (const line of hunk.lines) {
+      const prefix = line.kind === 'added' ? '+' : line.kind === 'removed' ? '-' : ' ';
+      lines.push(prefix + line.text);
+    }
+  }
+  return lines.join('\n');
+}
+
+/**
+ * Format a job's output for display. Diff jobs render their diff as unified diff text followed
+ * by what the device's diff command printed; other jobs show their output as is.
+ */
+export function formatJobOutput(job: Job): string {
+  const parsed = parseDiffJobOutput(job);
+  if (!parsed) return job.output || '';
+  const diff = formatConfigDiff(parsed.diff) || 'No differences';
+  return parsed.device_output ? `${diff}\n\n${parsed.device_output}` : diff;
+}
+
+/**
+ * Summarize a job's output in one line, e.g. "+3 -1 lines" for a diff job
+ */
+export function formatJobSummary(job: Job): string {
+  const parsed = parseDiffJobOutput(job);
+  if (!parsed) return (job.output || '').replace(/\n/g, ' ');
+  const { added, removed } = parsed.diff;
+  return added || removed ? `+${added} -${removed} lines` : 'No differences';
+}
+
 /**
  * Discovery event types
  */
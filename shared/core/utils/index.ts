@@ -6,6 +6,10 @@ export {
   formatMacAddress,
   formatFileSize,
   formatExpiry,
+  parseDiffJobOutput,
+  formatConfigDiff,
+  formatJobOutput,
+  formatJobSummary,
   formatEventType,
   getEventTypeIcon,
   type DiscoveryEventType,